@@ -2,6 +2,19 @@
 
 use dotenv::dotenv;
 use std::env;
+use std::time::Duration;
+
+mod snapshot;
+pub use snapshot::Sources;
+
+/// Wallet RPC operations with their own timeout budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletRpcOp {
+    /// Cheap node calls (tip, fee estimate, tx lookup)
+    Call,
+    /// `scantxoutset` walks the whole UTXO set and takes tens of seconds
+    UtxoScan,
+}
 
 /// Configuration settings for the Charms Explorer API server
 #[derive(Debug, Clone)]
@@ -14,9 +27,7 @@ pub struct ApiConfig {
     pub database_url: String,
 
     // Network configuration
-    #[allow(dead_code)] // Reserved for network switching
     pub enable_bitcoin_testnet4: bool,
-    #[allow(dead_code)] // Reserved for network switching
     pub enable_bitcoin_mainnet: bool,
     #[allow(dead_code)] // Reserved for network switching
     pub enable_cardano: bool,
@@ -37,11 +48,41 @@ pub struct ApiConfig {
     #[allow(dead_code)] // Reserved for mainnet integration
     pub bitcoin_mainnet_rpc_password: String,
 
-    // QuickNode (mainnet UTXOs/balance — secondary/fallback)
+    // QuickNode (UTXOs/balance — secondary/fallback); empty = not configured
     pub bitcoin_mainnet_quicknode_endpoint: String,
+    pub bitcoin_testnet4_quicknode_endpoint: String,
+
+    // Wallet RPC timeouts: default per call, and the override for UTXO scans
+    pub wallet_rpc_timeout_secs: u64,
+    pub wallet_scan_timeout_secs: u64,
+    // How long a cached scantxoutset result is served when the tip is unknown
+    pub wallet_scan_cache_ttl_secs: u64,
+    // Charms with fewer confirmations than this are flagged `reorg_risk`
+    pub wallet_reorg_risk_confirmations: u64,
 
     // Maestro (mainnet — primary provider)
     pub maestro_api_key: String,
+
+    // Bearer token for /admin endpoints (unset = admin endpoints disabled)
+    pub admin_api_token: Option<String>,
+
+    // Days an API-monitored address stays tracked after its last query
+    pub monitor_ttl_days: i64,
+
+    // Deepest height below the indexed tip /address/{addr}/balance-at answers
+    // for, in blocks (0 = no limit)
+    pub balance_at_max_lookback_blocks: u64,
+
+    // Scheme and host pagination links start with, e.g. https://api.example
+    // (unset = taken from the request's Host header)
+    pub public_base_url: Option<String>,
+
+    // Longest wait for in-flight requests after SIGTERM/SIGINT, in seconds
+    pub shutdown_drain_secs: u64,
+
+    // Redis shared by every replica for the tip and scan caches
+    // (unset = per-process memory)
+    pub redis_url: Option<String>,
 }
 
 impl ApiConfig {
@@ -95,10 +136,57 @@ impl ApiConfig {
 
         let bitcoin_mainnet_quicknode_endpoint =
             env::var("BITCOIN_MAINNET_QUICKNODE_ENDPOINT").unwrap_or_else(|_| String::new());
+        let bitcoin_testnet4_quicknode_endpoint =
+            env::var("BITCOIN_TESTNET4_QUICKNODE_ENDPOINT").unwrap_or_else(|_| String::new());
+
+        let wallet_rpc_timeout_secs = env::var("WALLET_RPC_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(3);
+        let wallet_scan_timeout_secs = env::var("WALLET_SCAN_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(60);
+        let wallet_scan_cache_ttl_secs = env::var("WALLET_SCAN_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+        let wallet_reorg_risk_confirmations = env::var("WALLET_REORG_RISK_CONFIRMATIONS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(3);
 
         let maestro_api_key =
             env::var("MAESTRO_API_KEY").unwrap_or_else(|_| String::new());
 
+        let admin_api_token = env::var("ADMIN_API_TOKEN").ok().filter(|t| !t.is_empty());
+
+        let monitor_ttl_days = env::var("MONITOR_TTL_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|d| *d > 0)
+            .unwrap_or(90);
+
+        let balance_at_max_lookback_blocks = env::var("BALANCE_AT_MAX_LOOKBACK_BLOCKS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(52_560);
+
+        let public_base_url = env::var("PUBLIC_BASE_URL")
+            .ok()
+            .map(|u| u.trim_end_matches('/').to_string())
+            .filter(|u| !u.is_empty());
+
+        let shutdown_drain_secs = env::var("SHUTDOWN_DRAIN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(25);
+
+        let redis_url = env::var("REDIS_URL").ok().filter(|u| !u.is_empty());
+
         Self {
             host,
             port,
@@ -115,7 +203,18 @@ impl ApiConfig {
             bitcoin_mainnet_rpc_username,
             bitcoin_mainnet_rpc_password,
             bitcoin_mainnet_quicknode_endpoint,
+            bitcoin_testnet4_quicknode_endpoint,
+            wallet_rpc_timeout_secs,
+            wallet_scan_timeout_secs,
+            wallet_scan_cache_ttl_secs,
+            wallet_reorg_risk_confirmations,
             maestro_api_key,
+            admin_api_token,
+            monitor_ttl_days,
+            balance_at_max_lookback_blocks,
+            public_base_url,
+            shutdown_drain_secs,
+            redis_url,
         }
     }
 
@@ -123,4 +222,64 @@ impl ApiConfig {
     pub fn server_addr(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
+
+    /// Bitcoin networks this deployment serves, mainnet first
+    pub fn enabled_networks(&self) -> Vec<&'static str> {
+        [
+            ("mainnet", self.enable_bitcoin_mainnet),
+            ("testnet4", self.enable_bitcoin_testnet4),
+        ]
+        .into_iter()
+        .filter_map(|(network, enabled)| enabled.then_some(network))
+        .collect()
+    }
+
+    /// QuickNode endpoint for a network (empty string = not configured)
+    pub fn quicknode_endpoint(&self, network: &str) -> &str {
+        match network {
+            "testnet4" => &self.bitcoin_testnet4_quicknode_endpoint,
+            _ => &self.bitcoin_mainnet_quicknode_endpoint,
+        }
+    }
+
+    /// Timeout for a wallet RPC call before falling back to QuickNode.
+    /// UTXO scans never get less than the default budget.
+    pub fn wallet_rpc_timeout(&self, op: WalletRpcOp) -> Duration {
+        let secs = match op {
+            WalletRpcOp::Call => self.wallet_rpc_timeout_secs,
+            WalletRpcOp::UtxoScan => self
+                .wallet_scan_timeout_secs
+                .max(self.wallet_rpc_timeout_secs),
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::config;
+
+    #[test]
+    fn quicknode_endpoint_follows_network() {
+        let mut cfg = config();
+        assert_eq!(cfg.quicknode_endpoint("mainnet"), "https://main.qn.example");
+        assert_eq!(cfg.quicknode_endpoint("testnet4"), "https://t4.qn.example");
+
+        // testnet4 without its own endpoint has no fallback, never mainnet's.
+        cfg.bitcoin_testnet4_quicknode_endpoint.clear();
+        assert_eq!(cfg.quicknode_endpoint("testnet4"), "");
+    }
+
+    #[test]
+    fn utxo_scan_uses_its_own_timeout() {
+        let mut cfg = config();
+        let secs = |cfg: &ApiConfig, op| cfg.wallet_rpc_timeout(op).as_secs();
+        assert_eq!(secs(&cfg, WalletRpcOp::Call), 3);
+        assert_eq!(secs(&cfg, WalletRpcOp::UtxoScan), 60);
+
+        // A scan never gets a smaller budget than a plain call.
+        cfg.wallet_rpc_timeout_secs = 90;
+        assert_eq!(secs(&cfg, WalletRpcOp::UtxoScan), 90);
+    }
 }
@@ -3,6 +3,7 @@
 pub mod error;
 pub mod pool;
 pub mod repositories;
+pub mod stores;
 
 pub use error::DbError;
 pub use pool::DbPool;
@@ -10,6 +10,7 @@ use crate::db::repositories::Repositories;
 /// Database connection pool for managing Sea-ORM connections
 pub struct DbPool {
     pool: DatabaseConnection,
+    monitor_ttl_days: i64,
 }
 
 impl DbPool {
@@ -50,7 +51,10 @@ impl DbPool {
 
         Database::connect(conn_opts)
             .await
-            .map(|pool| DbPool { pool })
+            .map(|pool| DbPool {
+                pool,
+                monitor_ttl_days: config.monitor_ttl_days,
+            })
             .map_err(|e| DbError::ConnectionError(e.to_string()))
     }
 
@@ -60,8 +64,16 @@ impl DbPool {
         &self.pool
     }
 
+    /// Closes every connection of the pool, repositories' clones included
+    pub async fn close(self) -> Result<(), DbError> {
+        self.pool
+            .close()
+            .await
+            .map_err(|e| DbError::ConnectionError(e.to_string()))
+    }
+
     /// Creates repository instances for database operations
     pub fn repositories(&self) -> Repositories {
-        Repositories::new(self.pool.clone())
+        Repositories::new(self.pool.clone(), self.monitor_ttl_days)
     }
 }
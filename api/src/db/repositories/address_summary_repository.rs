@@ -0,0 +1,271 @@
+// Address summary repository — counts behind GET /address/{address}/summary:
+// the assets and charm UTXOs an address holds, its BTC buckets when it is
+// monitored, and when it last saw activity.
+
+use std::collections::BTreeMap;
+
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+/// BTC held in the address's tracked UTXOs, in sats, bucketed the way the
+/// wallet balance does: unconfirmed outputs are `unconfirmed`, confirmed
+/// ones carrying (or possibly carrying) charms are `locked`, the rest
+/// `available`. Unlike the wallet balance, outputs a mempool tx already
+/// spends are not set aside.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromQueryResult)]
+pub struct BtcBuckets {
+    pub available: i64,
+    pub locked: i64,
+    pub unconfirmed: i64,
+    pub total: i64,
+    pub utxo_count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddressSummary {
+    /// Whether the indexer tracks the address's BTC UTXOs. Without that,
+    /// only charm-derived figures are known and `btc` is absent.
+    pub monitored: bool,
+    /// Distinct app_ids held (unspent), by asset type
+    pub assets: BTreeMap<String, i64>,
+    /// Unspent outputs carrying at least one charm
+    pub charm_utxos: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub btc: Option<BtcBuckets>,
+    /// Latest of: a charm received or spent, and (monitored only) a tracked
+    /// transaction. Receives without a block time count from when the
+    /// indexer wrote them.
+    pub last_activity_at: Option<chrono::NaiveDateTime>,
+}
+
+/// One row per asset type, plus the grouping-set total (null asset type)
+/// so a UTXO carrying several types counts once.
+const HOLDINGS_SQL: &str = "
+SELECT asset_type,
+       COUNT(DISTINCT app_id) AS assets,
+       COUNT(DISTINCT (txid, vout)) AS utxos
+  FROM charms
+ WHERE address = $1 AND network = $2 AND spent = false
+ GROUP BY GROUPING SETS ((asset_type), ())";
+
+/// Charm receives and spends, live and archived. Spends carry only a
+/// height, so they take the spending block's time.
+const CHARM_ACTIVITY_SQL: &str = "
+WITH received AS (
+    SELECT block_time, date_created, spent_height, network
+      FROM charms WHERE address = $1 AND network = $2
+    UNION ALL
+    SELECT block_time, date_created, spent_height, network
+      FROM charms_archive WHERE address = $1 AND network = $2
+)
+SELECT GREATEST(
+    (SELECT MAX(COALESCE(block_time, date_created)) FROM received),
+    (SELECT MAX(b.block_time)
+       FROM received r
+       JOIN block_status b ON b.block_height = r.spent_height AND b.network = r.network)
+) AS last_activity_at";
+
+/// No row when the address is not monitored. A UTXO the indexer has not
+/// annotated yet carries charms when a charm is stored for its outpoint.
+const BTC_SQL: &str = "
+WITH utxos AS (
+    SELECT u.value,
+           COALESCE(u.block_height, 0) > 0 AS confirmed,
+           COALESCE(u.has_charms, EXISTS (
+               SELECT 1 FROM charms c
+                WHERE c.txid = u.txid AND c.vout = u.vout AND c.network = u.network
+           )) OR u.possible_charm AS locked
+      FROM address_utxos u
+     WHERE u.address = $1 AND u.network = $2
+)
+SELECT COALESCE(SUM(value) FILTER (WHERE confirmed AND NOT locked), 0)::BIGINT AS available,
+       COALESCE(SUM(value) FILTER (WHERE confirmed AND locked), 0)::BIGINT AS locked,
+       COALESCE(SUM(value) FILTER (WHERE NOT confirmed), 0)::BIGINT AS unconfirmed,
+       COALESCE(SUM(value), 0)::BIGINT AS total,
+       COUNT(value) AS utxo_count,
+       (SELECT to_timestamp(MAX(t.block_time)) AT TIME ZONE 'UTC'
+          FROM address_transactions t
+         WHERE t.address = $1 AND t.network = $2) AS last_tx_at
+  FROM monitored_addresses m
+  LEFT JOIN utxos ON true
+ WHERE m.address = $1 AND m.network = $2
+ GROUP BY m.address";
+
+#[derive(FromQueryResult)]
+struct HoldingRow {
+    asset_type: Option<String>,
+    assets: i64,
+    utxos: i64,
+}
+
+#[derive(FromQueryResult)]
+struct ActivityRow {
+    last_activity_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(FromQueryResult)]
+struct BtcRow {
+    available: i64,
+    locked: i64,
+    unconfirmed: i64,
+    total: i64,
+    utxo_count: i64,
+    last_tx_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Clone)]
+pub struct AddressSummaryRepository {
+    conn: DatabaseConnection,
+}
+
+impl AddressSummaryRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Summary of `address` on `network`. An address the explorer has never
+    /// seen comes back empty rather than as an error.
+    pub async fn summary(&self, address: &str, network: &str) -> Result<AddressSummary, DbError> {
+        let statement = |sql: &str| {
+            Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [address.into(), network.into()],
+            )
+        };
+
+        let mut assets = BTreeMap::new();
+        let mut charm_utxos = 0;
+        for row in HoldingRow::find_by_statement(statement(HOLDINGS_SQL))
+            .all(&self.conn)
+            .await?
+        {
+            match row.asset_type {
+                Some(asset_type) => {
+                    assets.insert(asset_type, row.assets);
+                }
+                None => charm_utxos = row.utxos,
+            }
+        }
+
+        let charm_activity = ActivityRow::find_by_statement(statement(CHARM_ACTIVITY_SQL))
+            .one(&self.conn)
+            .await?
+            .and_then(|row| row.last_activity_at);
+        let btc = BtcRow::find_by_statement(statement(BTC_SQL))
+            .one(&self.conn)
+            .await?;
+
+        let last_tx_at = btc.as_ref().and_then(|row| row.last_tx_at);
+        Ok(AddressSummary {
+            monitored: btc.is_some(),
+            assets,
+            charm_utxos,
+            btc: btc.map(|row| BtcBuckets {
+                available: row.available,
+                locked: row.locked,
+                unconfirmed: row.unconfirmed,
+                total: row.total,
+                utxo_count: row.utxo_count,
+            }),
+            last_activity_at: charm_activity.max(last_tx_at),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use chrono::NaiveDate;
+    use sea_orm::ConnectionTrait;
+
+    const SEED: &str = "
+        -- 'holder': two NFTs (one UTXO also carries a token), two tokens
+        -- split over three UTXOs, one spent token, and the same app on
+        -- testnet4. 'other' holds an NFT and is not monitored.
+        INSERT INTO charms (txid, vout, app_id, asset_type, address, spent, spent_height, block_time, network,
+                            amount, blockchain, date_created) VALUES
+            ('n1', 0, 'n/aaa/1', 'nft',   'holder', false, NULL, '2026-03-01', 'mainnet', 1, 'bitcoin', '2026-01-01'),
+            ('n2', 0, 'n/bbb/1', 'nft',   'holder', false, NULL, '2026-03-02', 'mainnet', 1, 'bitcoin', '2026-01-01'),
+            ('n2', 0, 't/bbb/1', 'token', 'holder', false, NULL, '2026-03-02', 'mainnet', 1, 'bitcoin', '2026-01-01'),
+            ('t1', 1, 't/bbb/1', 'token', 'holder', false, NULL, '2026-03-03', 'mainnet', 1, 'bitcoin', '2026-01-01'),
+            ('t2', 0, 't/ccc/1', 'token', 'holder', false, NULL, NULL,         'mainnet', 1, 'bitcoin', '2026-01-01'),
+            ('s1', 0, 't/ddd/1', 'token', 'holder', true,  120,  '2026-02-01', 'mainnet', 1, 'bitcoin', '2026-01-01'),
+            ('x1', 0, 'n/aaa/1', 'nft',   'holder', false, NULL, '2026-09-01', 'testnet4', 1, 'bitcoin', '2026-01-01'),
+            ('o1', 0, 'n/eee/1', 'nft',   'other',  false, NULL, '2026-04-01', 'mainnet', 1, 'bitcoin', '2026-01-01');
+        INSERT INTO block_status (block_height, network, blockchain, block_time) VALUES
+            (120, 'mainnet', 'bitcoin', '2026-03-05');
+        INSERT INTO monitored_addresses (address, network, source) VALUES
+            ('holder', 'mainnet', 'api');
+        -- n1 annotated, t1 not yet (matched against charms), p1 flagged,
+        -- plain confirmed and mempool outputs, and another address's UTXO.
+        INSERT INTO address_utxos (txid, vout, network, address, value, block_height, possible_charm, has_charms) VALUES
+            ('n1', 0, 'mainnet', 'holder', 546,    100, false, true),
+            ('t1', 1, 'mainnet', 'holder', 1000,   101, false, NULL),
+            ('p1', 0, 'mainnet', 'holder', 330,    102, true,  false),
+            ('b1', 0, 'mainnet', 'holder', 50000,  103, false, false),
+            ('m1', 0, 'mainnet', 'holder', 7000,   0,   false, NULL),
+            ('o1', 0, 'mainnet', 'other',  99999,  104, false, true);
+        INSERT INTO address_transactions (txid, address, network, block_time, direction, amount)
+        VALUES ('b1', 'holder', 'mainnet', 1775001600, 'in', 50000);
+    ";
+
+    fn at(month: u32, day: u32) -> Option<chrono::NaiveDateTime> {
+        NaiveDate::from_ymd_opt(2026, month, day).and_then(|d| d.and_hms_opt(0, 0, 0))
+    }
+
+    /// Mixed NFT/token holdings, BTC buckets, activity and the unmonitored
+    /// case. Needs a scratch database; tables live in a throwaway schema.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn summarizes_holdings_btc_and_activity() {
+        let schema = TestSchema::new("address_summary", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(SEED).await.expect("fixture");
+        let repo = AddressSummaryRepository::new(conn.clone());
+
+        let holder = repo.summary("holder", "mainnet").await.unwrap();
+        assert!(holder.monitored);
+        assert_eq!(
+            holder.assets,
+            BTreeMap::from([("nft".to_string(), 2), ("token".to_string(), 2)])
+        );
+        assert_eq!(holder.charm_utxos, 4, "n2 carries two types, counted once");
+        assert_eq!(
+            holder.btc,
+            Some(BtcBuckets {
+                available: 50000,
+                locked: 546 + 1000 + 330,
+                unconfirmed: 7000,
+                total: 58876,
+                utxo_count: 5,
+            })
+        );
+        // The 2026-04-01 tracked tx beats the 2026-03-05 spend.
+        assert_eq!(holder.last_activity_at, at(4, 1));
+
+        let other = repo.summary("other", "mainnet").await.unwrap();
+        assert!(!other.monitored);
+        assert_eq!(other.btc, None);
+        assert_eq!(other.assets, BTreeMap::from([("nft".to_string(), 1)]));
+        assert_eq!(other.charm_utxos, 1);
+        assert_eq!(other.last_activity_at, at(4, 1));
+
+        let unknown = repo.summary("nobody", "mainnet").await.unwrap();
+        assert_eq!(
+            unknown,
+            AddressSummary {
+                monitored: false,
+                assets: BTreeMap::new(),
+                charm_utxos: 0,
+                btc: None,
+                last_activity_at: None,
+            }
+        );
+
+        schema.drop_schema().await;
+    }
+}
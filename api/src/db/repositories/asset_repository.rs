@@ -1,10 +1,44 @@
+use sea_orm::sea_query::{Expr, Order};
 use sea_orm::{
-    ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
-    QuerySelect,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Statement,
 };
 use std::sync::Arc;
 
+use charms_core::AssetType;
+
+use crate::db::repositories::moderation_repository::ModerationStatus;
 use crate::entity::assets::{Column, Entity as Asset, Model};
+use crate::models::AssetSort;
+
+/// One NFT collection: its id, member count and the image of its earliest
+/// member that has one.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct CollectionSummary {
+    pub collection: String,
+    pub items: i64,
+    pub sample_image: Option<String>,
+}
+
+/// Collections on network `$1`, largest first, without hidden members.
+/// Served by idx_assets_network_collection.
+const COLLECTIONS_SQL: &str = "
+SELECT collection,
+       COUNT(*) AS items,
+       (ARRAY_AGG(image_url ORDER BY block_height, id)
+          FILTER (WHERE image_url IS NOT NULL AND image_url <> ''))[1] AS sample_image
+  FROM assets
+ WHERE asset_type = 'nft' AND network = $1 AND collection IS NOT NULL
+   AND moderation_status <> 'hidden'
+ GROUP BY collection
+ ORDER BY items DESC, collection
+ LIMIT $2 OFFSET $3";
+
+const COUNT_COLLECTIONS_SQL: &str = "
+SELECT COUNT(DISTINCT collection) AS count
+  FROM assets
+ WHERE asset_type = 'nft' AND network = $1 AND collection IS NOT NULL
+   AND moderation_status <> 'hidden'";
 
 /// Repository for asset database operations
 #[derive(Clone)]
@@ -18,14 +52,26 @@ impl AssetRepository {
         Self { db }
     }
 
-    /// Find assets with pagination and optional filtering
+    /// Deepest row `AssetSort::LikesDesc` will page to, mirroring
+    /// `CharmRepository::LIKES_SORT_MAX_OFFSET`; past this the listing
+    /// returns an empty page.
+    pub const LIKES_SORT_MAX_OFFSET: u64 = 1000;
+
+    /// Find assets with pagination and optional filtering, in `sort` order.
+    /// Hidden assets are left out unless `include_hidden`.
     pub async fn find_paginated(
         &self,
         asset_type: Option<&str>,
         network: Option<&str>,
+        sort: AssetSort,
         limit: u64,
         offset: u64,
+        include_hidden: bool,
     ) -> Result<Vec<Model>, Box<dyn std::error::Error + Send + Sync>> {
+        if sort == AssetSort::LikesDesc && offset >= Self::LIKES_SORT_MAX_OFFSET {
+            return Ok(vec![]);
+        }
+
         let mut query = Asset::find();
 
         if let Some(asset_type) = asset_type {
@@ -36,13 +82,36 @@ impl AssetRepository {
             query = query.filter(Column::Network.eq(network));
         }
 
+        if !include_hidden {
+            query = query.filter(Column::ModerationStatus.ne(ModerationStatus::Hidden.as_str()));
+        }
+
         // Order by on-chain mint height (newest mints first), with id as a
         // stable tiebreaker. A full DB reseed (Plan 16) collapses every row
         // to the same created_at, so sorting by created_at hid newer mints
         // behind genesis-era ones — block_height is the real timeline.
+        let query = match sort {
+            AssetSort::Newest => query
+                .order_by_desc(Column::BlockHeight)
+                .order_by_desc(Column::Id),
+            AssetSort::Oldest => query
+                .order_by_asc(Column::BlockHeight)
+                .order_by_asc(Column::Id),
+            AssetSort::LikesDesc => {
+                // likes.charm_id holds the app_id; newest mint breaks ties.
+                query
+                    .order_by(
+                        Expr::cust(
+                            "(SELECT COUNT(*) FROM likes WHERE likes.charm_id = assets.app_id)",
+                        ),
+                        Order::Desc,
+                    )
+                    .order_by_desc(Column::BlockHeight)
+                    .order_by_desc(Column::Id)
+            }
+        };
+
         let assets = query
-            .order_by_desc(Column::BlockHeight)
-            .order_by_desc(Column::Id)
             .limit(limit)
             .offset(offset)
             .all(self.db.as_ref())
@@ -51,11 +120,13 @@ impl AssetRepository {
         Ok(assets)
     }
 
-    /// Count assets with optional filtering
+    /// Count assets with optional filtering, hidden ones only when
+    /// `include_hidden`
     pub async fn count_assets(
         &self,
         asset_type: Option<&str>,
         network: Option<&str>,
+        include_hidden: bool,
     ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
         let mut query = Asset::find();
 
@@ -67,6 +138,10 @@ impl AssetRepository {
             query = query.filter(Column::Network.eq(network));
         }
 
+        if !include_hidden {
+            query = query.filter(Column::ModerationStatus.ne(ModerationStatus::Hidden.as_str()));
+        }
+
         let count = query.count(self.db.as_ref()).await?;
         Ok(count)
     }
@@ -173,7 +248,7 @@ impl AssetRepository {
 
         // Get all NFTs matching the hash pattern, scoped to the network.
         let assets = Asset::find()
-            .filter(Column::AssetType.eq("nft"))
+            .filter(Column::AssetType.eq(AssetType::Nft.as_str()))
             .filter(Column::AppId.like(&pattern))
             .filter(Column::Network.eq(network))
             .order_by_asc(Column::BlockHeight)
@@ -208,7 +283,7 @@ impl AssetRepository {
     ) -> Result<Option<Model>, Box<dyn std::error::Error + Send + Sync>> {
         let pattern = format!("n/%/{}", vk);
         let assets = Asset::find()
-            .filter(Column::AssetType.eq("nft"))
+            .filter(Column::AssetType.eq(AssetType::Nft.as_str()))
             .filter(Column::AppId.like(&pattern))
             .all(self.db.as_ref())
             .await?;
@@ -249,4 +324,128 @@ impl AssetRepository {
 
         Ok(max_supply)
     }
+
+    /// NFT collections on a network with item counts, paginated
+    pub async fn find_collections(
+        &self,
+        network: &str,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<CollectionSummary>, u64), Box<dyn std::error::Error + Send + Sync>> {
+        let collections = CollectionSummary::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            COLLECTIONS_SQL,
+            [network.into(), (limit as i64).into(), (offset as i64).into()],
+        ))
+        .all(self.db.as_ref())
+        .await?;
+
+        let total = self
+            .db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                COUNT_COLLECTIONS_SQL,
+                [network.into()],
+            ))
+            .await?
+            .map(|row| row.try_get::<i64>("", "count"))
+            .transpose()?
+            .unwrap_or(0);
+
+        Ok((collections, total as u64))
+    }
+
+    /// NFTs belonging to a collection, oldest mint first, paginated
+    pub async fn find_by_collection(
+        &self,
+        collection: &str,
+        network: &str,
+        limit: u64,
+        offset: u64,
+        include_hidden: bool,
+    ) -> Result<(Vec<Model>, u64), Box<dyn std::error::Error + Send + Sync>> {
+        let mut query = Asset::find()
+            .filter(Column::AssetType.eq(AssetType::Nft.as_str()))
+            .filter(Column::Network.eq(network))
+            .filter(Column::Collection.eq(collection));
+        if !include_hidden {
+            query = query.filter(Column::ModerationStatus.ne(ModerationStatus::Hidden.as_str()));
+        }
+
+        let total = query.clone().count(self.db.as_ref()).await?;
+        let assets = query
+            .order_by_asc(Column::BlockHeight)
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .offset(offset)
+            .all(self.db.as_ref())
+            .await?;
+
+        Ok((assets, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+
+    const SEED: &str = "
+        INSERT INTO assets
+            (app_id, block_height, txid, vout_index, charm_id, asset_type, blockchain, network)
+        VALUES
+            ('n/a/a', 10, 'tx', 0, '', 'nft', 'bitcoin', 'mainnet'),
+            ('n/b/b', 11, 'tx', 0, '', 'nft', 'bitcoin', 'mainnet'),
+            ('n/c/c', 12, 'tx', 0, '', 'nft', 'bitcoin', 'mainnet');
+        INSERT INTO likes (charm_id, user_id) VALUES ('n/a/a', 1);
+    ";
+
+    /// `likes_desc` ranks by like count, newest mint breaking ties, and
+    /// re-ranks as likes arrive. Needs a scratch database.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn likes_desc_follows_new_likes() {
+        let schema = TestSchema::new("asset_sort", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(SEED).await.expect("fixture");
+
+        let repo = AssetRepository::new(Arc::new(conn.clone()));
+        let order = |sort: AssetSort| {
+            let repo = repo.clone();
+            async move {
+                repo.find_paginated(Some("nft"), Some("mainnet"), sort, 10, 0, false)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|a| a.app_id)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(order(AssetSort::Newest).await, ["n/c/c", "n/b/b", "n/a/a"]);
+        assert_eq!(order(AssetSort::Oldest).await, ["n/a/a", "n/b/b", "n/c/c"]);
+        assert_eq!(order(AssetSort::LikesDesc).await, ["n/a/a", "n/c/c", "n/b/b"]);
+
+        conn.execute_unprepared(
+            "INSERT INTO likes (charm_id, user_id) VALUES ('n/b/b', 1), ('n/b/b', 2)",
+        )
+        .await
+        .unwrap();
+        assert_eq!(order(AssetSort::LikesDesc).await, ["n/b/b", "n/a/a", "n/c/c"]);
+
+        let too_deep = repo
+            .find_paginated(
+                None,
+                None,
+                AssetSort::LikesDesc,
+                10,
+                AssetRepository::LIKES_SORT_MAX_OFFSET,
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(too_deep.is_empty());
+
+        schema.drop_schema().await;
+    }
 }
@@ -0,0 +1,96 @@
+// Backfills repository — the indexer registers its backfills in
+// `backfill_progress` and reports each run's cursor there; operators read
+// it and queue runs through `/admin/backfills`.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+/// A registered backfill and its progress. `heartbeat_at` moves with every
+/// committed batch while it runs.
+#[derive(Debug, Clone, Serialize, FromQueryResult)]
+pub struct BackfillProgress {
+    pub name: String,
+    pub status: String,
+    pub last_key: Option<i64>,
+    pub rows_updated: i64,
+    pub batches: i32,
+    pub idempotent: bool,
+    pub requested_by: Option<String>,
+    pub requested_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub heartbeat_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+/// What queueing a backfill did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackfillRequest {
+    Queued,
+    /// No backfill of that name is registered.
+    Unknown,
+    /// Already pending or running, or done and not restartable as asked.
+    Refused,
+}
+
+#[derive(Clone)]
+pub struct BackfillsRepository {
+    conn: DatabaseConnection,
+}
+
+impl BackfillsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn list(&self) -> Result<Vec<BackfillProgress>, DbError> {
+        Ok(BackfillProgress::find_by_statement(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT name, status, last_key, rows_updated, batches, idempotent,
+                    requested_by, requested_at, started_at, heartbeat_at,
+                    finished_at, error
+               FROM backfill_progress ORDER BY name"
+                .to_string(),
+        ))
+        .all(&self.conn)
+        .await?)
+    }
+
+    /// Mark `name` pending for the indexer. A finished backfill is only
+    /// queued again with `restart`, and only when it is idempotent.
+    pub async fn request(
+        &self,
+        name: &str,
+        requested_by: &str,
+        restart: bool,
+    ) -> Result<BackfillRequest, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "WITH queued AS (
+                     UPDATE backfill_progress
+                        SET status = 'pending', restart = $3, requested_by = $2,
+                            requested_at = NOW(), error = NULL
+                      WHERE name = $1
+                        AND (status IN ('idle', 'paused', 'failed')
+                             OR (status = 'done' AND $3 AND idempotent))
+                  RETURNING 1)
+                 SELECT EXISTS (SELECT 1 FROM queued) AS queued,
+                        EXISTS (SELECT 1 FROM backfill_progress WHERE name = $1) AS known",
+                [name.into(), requested_by.into(), restart.into()],
+            ))
+            .await?
+            .ok_or_else(|| DbError::QueryError("backfill request returned no row".to_string()))?;
+        let queued: bool = row.try_get("", "queued")?;
+        let known: bool = row.try_get("", "known")?;
+        Ok(match (queued, known) {
+            (true, _) => BackfillRequest::Queued,
+            (false, true) => BackfillRequest::Refused,
+            (false, false) => BackfillRequest::Unknown,
+        })
+    }
+}
@@ -0,0 +1,453 @@
+// Blocks repository — processed `block_status` rows with the number of
+// charms stored at each height, and the contents of a single block: its
+// spells, its charms and the charm UTXOs it spent.
+
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct BlockRow {
+    pub height: i32,
+    pub hash: Option<String>,
+    /// "confirmed" once the indexer marked the block past reorg depth,
+    /// "processed" before that.
+    pub status: String,
+    /// Charms stored at this height (placeholders excluded).
+    pub charm_count: i64,
+    pub tx_count: Option<i32>,
+    pub processed_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    /// Header time; null for blocks processed before it was recorded.
+    pub block_time: Option<chrono::NaiveDateTime>,
+}
+
+/// A spell transaction confirmed in the block.
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct BlockSpell {
+    pub txid: String,
+    /// Apps the spell declares (`native_data.app_public_inputs`); 0 when the
+    /// stored JSON holds no parsed spell.
+    pub app_count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct BlockCharm {
+    pub txid: String,
+    pub vout: i32,
+    pub app_id: String,
+    pub asset_type: String,
+    pub amount: i64,
+    pub address: Option<String>,
+    pub spent: bool,
+    pub tags: Option<String>,
+    pub verified: bool,
+}
+
+/// Blocks in the rolling window of `BlockTiming::rolling_avg_ms` and in
+/// the averages `/status` reports.
+pub const TIMING_WINDOW: u64 = 100;
+
+/// How long the indexer took to process a block, by phase (see the
+/// indexer's `BlockTimings`).
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct BlockTiming {
+    pub height: i32,
+    pub tx_count: Option<i32>,
+    pub charm_count: Option<i32>,
+    pub processing_ms: i32,
+    pub rpc_ms: Option<i32>,
+    pub parse_ms: Option<i32>,
+    pub db_ms: Option<i32>,
+    /// Mean `processing_ms` of this block and the timed blocks before it,
+    /// `TIMING_WINDOW` in all.
+    pub rolling_avg_ms: f64,
+    pub processed_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+/// Mean phase times over the newest timed blocks; null when none are.
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct TimingAverages {
+    pub blocks: i64,
+    pub processing_ms: Option<f64>,
+    pub rpc_ms: Option<f64>,
+    pub parse_ms: Option<f64>,
+    pub db_ms: Option<f64>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Count {
+    count: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Tip {
+    height: Option<i32>,
+}
+
+/// Processed blocks of `$1` narrowed by `page_tail`, newest first, joined
+/// to a charm count grouped over just those heights.
+fn blocks_sql(page_tail: &str) -> String {
+    format!(
+        "WITH page AS (
+             SELECT block_height, block_hash, confirmed, tx_count, processed_at, block_time
+               FROM block_status
+              WHERE network = $1 AND processed {page_tail})
+         SELECT p.block_height AS height, p.block_hash AS hash,
+                CASE WHEN p.confirmed THEN 'confirmed' ELSE 'processed' END AS status,
+                COALESCE(c.charm_count, 0) AS charm_count, p.tx_count, p.processed_at,
+                p.block_time
+           FROM page p
+           LEFT JOIN (SELECT block_height, COUNT(*) AS charm_count
+                        FROM charms
+                       WHERE network = $1 AND NOT is_placeholder
+                         AND block_height IN (SELECT block_height FROM page)
+                       GROUP BY block_height) c ON c.block_height = p.block_height
+          ORDER BY p.block_height DESC"
+    )
+}
+
+#[derive(Clone)]
+pub struct BlocksRepository {
+    conn: DatabaseConnection,
+}
+
+impl BlocksRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// One page of processed blocks, newest first, with the total count.
+    pub async fn list(
+        &self,
+        network: &str,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<BlockRow>, u64), DbError> {
+        let blocks = BlockRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            blocks_sql("ORDER BY block_height DESC LIMIT $2 OFFSET $3"),
+            [network.into(), (limit as i64).into(), (offset as i64).into()],
+        ))
+        .all(&self.conn)
+        .await?;
+
+        let total = Count::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS count FROM block_status WHERE network = $1 AND processed",
+            [network.into()],
+        ))
+        .one(&self.conn)
+        .await?
+        .map_or(0, |c| c.count as u64);
+
+        Ok((blocks, total))
+    }
+
+    /// A processed block; `None` when the indexer has not processed it.
+    pub async fn get(&self, height: i32, network: &str) -> Result<Option<BlockRow>, DbError> {
+        Ok(BlockRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            blocks_sql("AND block_height = $2"),
+            [network.into(), height.into()],
+        ))
+        .one(&self.conn)
+        .await?)
+    }
+
+    /// Highest processed block of the network.
+    pub async fn tip(&self, network: &str) -> Result<Option<i32>, DbError> {
+        Ok(Tip::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT MAX(block_height) AS height FROM block_status WHERE network = $1 AND processed",
+            [network.into()],
+        ))
+        .one(&self.conn)
+        .await?
+        .and_then(|t| t.height))
+    }
+
+    /// The newest `limit` timed blocks, newest first. The rolling average
+    /// reads up to `TIMING_WINDOW - 1` older blocks past the page.
+    pub async fn timings(&self, network: &str, limit: u64) -> Result<Vec<BlockTiming>, DbError> {
+        Ok(BlockTiming::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                "SELECT * FROM (
+                     SELECT block_height AS height, tx_count, charm_count, processing_ms,
+                            rpc_ms, parse_ms, db_ms, processed_at,
+                            (AVG(processing_ms) OVER (
+                                ORDER BY block_height
+                                ROWS BETWEEN {preceding} PRECEDING AND CURRENT ROW))::float8
+                                AS rolling_avg_ms
+                       FROM (SELECT * FROM block_status
+                              WHERE network = $1 AND processing_ms IS NOT NULL
+                              ORDER BY block_height DESC
+                              LIMIT $2 + {preceding}) timed) w
+                  ORDER BY height DESC
+                  LIMIT $2",
+                preceding = TIMING_WINDOW - 1
+            ),
+            [network.into(), (limit as i64).into()],
+        ))
+        .all(&self.conn)
+        .await?)
+    }
+
+    /// Mean phase times over the newest `window` timed blocks.
+    pub async fn timing_averages(
+        &self,
+        network: &str,
+        window: u64,
+    ) -> Result<TimingAverages, DbError> {
+        let averages = TimingAverages::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS blocks,
+                    AVG(processing_ms)::float8 AS processing_ms, AVG(rpc_ms)::float8 AS rpc_ms,
+                    AVG(parse_ms)::float8 AS parse_ms, AVG(db_ms)::float8 AS db_ms
+               FROM (SELECT processing_ms, rpc_ms, parse_ms, db_ms FROM block_status
+                      WHERE network = $1 AND processing_ms IS NOT NULL
+                      ORDER BY block_height DESC
+                      LIMIT $2) timed",
+            [network.into(), (window as i64).into()],
+        ))
+        .one(&self.conn)
+        .await?;
+        Ok(averages.unwrap_or(TimingAverages {
+            blocks: 0,
+            processing_ms: None,
+            rpc_ms: None,
+            parse_ms: None,
+            db_ms: None,
+        }))
+    }
+
+    /// Charms stored at `height`, in block order.
+    pub async fn charms(&self, height: i32, network: &str) -> Result<Vec<BlockCharm>, DbError> {
+        Ok(BlockCharm::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT txid, vout, app_id, asset_type, amount, address, spent, tags, verified
+               FROM charms
+              WHERE network = $1 AND block_height = $2 AND NOT is_placeholder
+              ORDER BY tx_ordinal NULLS LAST, txid, vout, app_id",
+            [network.into(), height.into()],
+        ))
+        .all(&self.conn)
+        .await?)
+    }
+
+    /// Spell transactions confirmed at `height`, in block order.
+    pub async fn spells(&self, height: i32, network: &str) -> Result<Vec<BlockSpell>, DbError> {
+        Ok(
+            BlockSpell::find_by_statement(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT txid,
+                        CASE WHEN jsonb_typeof(apps) = 'object'
+                             THEN (SELECT COUNT(*) FROM jsonb_object_keys(apps))
+                             ELSE 0 END AS app_count
+                   FROM (SELECT txid, ordinal,
+                                charm #> '{native_data,app_public_inputs}' AS apps
+                           FROM transactions
+                          WHERE network = $1 AND block_height = $2) t
+                  ORDER BY ordinal, txid",
+                [network.into(), height.into()],
+            ))
+            .all(&self.conn)
+            .await?,
+        )
+    }
+
+    /// Charm UTXOs spent by the block at `height`, archived ones included.
+    /// A UTXO carrying several apps counts once.
+    pub async fn spent_count(&self, height: i32, network: &str) -> Result<u64, DbError> {
+        Ok(Count::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS count FROM (
+                 SELECT txid, vout FROM charms
+                  WHERE network = $1 AND spent AND spent_height = $2
+                 UNION
+                 SELECT txid, vout FROM charms_archive
+                  WHERE network = $1 AND spent_height = $2) spent",
+            [network.into(), height.into()],
+        ))
+        .one(&self.conn)
+        .await?
+        .map_or(0, |c| c.count as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use sea_orm::ConnectionTrait;
+
+    const SEED: &str = "
+        -- 100..=104 processed (100 confirmed), 105 downloaded only, 104 on testnet4 too.
+        INSERT INTO block_status
+            (block_height, network, blockchain, processed, confirmed, block_hash, tx_count,
+             processed_at)
+        SELECT h, 'mainnet', 'bitcoin', h < 105, h = 100, 'hash' || h, 10, NOW()
+          FROM generate_series(100, 105) h;
+        INSERT INTO block_status (block_height, network, blockchain, processed, block_hash)
+        VALUES (104, 'testnet4', 'bitcoin', true, 't4');
+        -- Three charms at 103 (one a placeholder), one at 101, one on testnet4 at 104.
+        INSERT INTO charms
+            (txid, vout, block_height, network, tx_ordinal, is_placeholder, app_id, amount,
+             asset_type, blockchain)
+        SELECT *, 't/x/x', 1, 'token', 'bitcoin' FROM (VALUES
+            ('b', 0, 103, 'mainnet', 2, false), ('a', 1, 103, 'mainnet', 1, false),
+            ('p', 0, 103, 'mainnet', 3, true), ('c', 0, 101, 'mainnet', 1, false),
+            ('t', 0, 104, 'testnet4', 1, false)) AS v;
+    ";
+
+    /// Pages, charm counts (including a block with none) and the unprocessed
+    /// case. Needs a scratch database; tables live in a throwaway schema.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn lists_processed_blocks_with_charm_counts() {
+        let schema = TestSchema::new("blocks", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(SEED).await.expect("fixture");
+
+        let repo = BlocksRepository::new(conn.clone());
+        let (first, total) = repo.list("mainnet", 2, 0).await.unwrap();
+        assert_eq!(total, 5);
+        let summary = |rows: &[BlockRow]| -> Vec<(i32, i64)> {
+            rows.iter().map(|b| (b.height, b.charm_count)).collect()
+        };
+        assert_eq!(summary(&first), [(104, 0), (103, 2)]);
+        let (second, _) = repo.list("mainnet", 2, 2).await.unwrap();
+        assert_eq!(summary(&second), [(102, 0), (101, 1)]);
+        let (last, _) = repo.list("mainnet", 2, 4).await.unwrap();
+        assert_eq!(summary(&last), [(100, 0)]);
+        assert_eq!(last[0].status, "confirmed");
+        assert_eq!(first[0].status, "processed");
+
+        let empty = repo.get(102, "mainnet").await.unwrap().unwrap();
+        assert_eq!((empty.hash.as_deref(), empty.charm_count), (Some("hash102"), 0));
+        assert!(repo.charms(102, "mainnet").await.unwrap().is_empty());
+        let txids: Vec<String> = repo
+            .charms(103, "mainnet")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.txid)
+            .collect();
+        assert_eq!(txids, ["a", "b"]);
+
+        assert_eq!(repo.get(105, "mainnet").await.unwrap(), None);
+        assert_eq!(repo.tip("mainnet").await.unwrap(), Some(104));
+        assert_eq!(repo.tip("signet").await.unwrap(), None);
+
+        schema.drop_schema().await;
+    }
+
+    /// Rolling averages reach past the page; untimed blocks are left out.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn timings_average_the_newest_timed_blocks() {
+        let schema = TestSchema::new("timings", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(&format!(
+            "{SEED}
+             -- 101..=104 timed at 10 ms per height step; 100 and 105 untimed.
+             UPDATE block_status SET processing_ms = (block_height - 100) * 10,
+                    rpc_ms = 1, parse_ms = 2, db_ms = (block_height - 100) * 10 - 3
+              WHERE network = 'mainnet' AND block_height BETWEEN 101 AND 104;"
+        ))
+        .await
+        .expect("fixture");
+
+        let repo = BlocksRepository::new(conn.clone());
+        let page = repo.timings("mainnet", 2).await.unwrap();
+        let rows: Vec<(i32, i32, f64)> = page
+            .iter()
+            .map(|t| (t.height, t.processing_ms, t.rolling_avg_ms))
+            .collect();
+        assert_eq!(rows, [(104, 40, 25.0), (103, 30, 20.0)]);
+
+        let averages = repo.timing_averages("mainnet", 3).await.unwrap();
+        assert_eq!(averages.blocks, 3);
+        assert_eq!(averages.processing_ms, Some(30.0));
+        assert_eq!(averages.rpc_ms, Some(1.0));
+        let none = repo.timing_averages("testnet4", 100).await.unwrap();
+        assert_eq!((none.blocks, none.processing_ms), (0, None));
+
+        schema.drop_schema().await;
+    }
+
+    /// Block 103 holds a mint ('a', one app) and a transfer ('b', two apps)
+    /// spending 'c' (two apps on one UTXO) and an archived charm. The DEX
+    /// order section comes from `DexOrdersRepository::find_by_block`.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn block_contents_cover_spells_charms_and_spends() {
+        let schema = TestSchema::new("block_contents", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(&format!(
+            "{SEED}
+             UPDATE block_status SET block_time = '2026-08-01 12:00:00'
+              WHERE network = 'mainnet' AND block_height = 103;
+             INSERT INTO transactions (txid, block_height, ordinal, charm, network, blockchain)
+             VALUES
+                 ('a', 103, 1, '{{\"native_data\": {{\"app_public_inputs\": {{\"t/x/x\": null}}}}}}', 'mainnet', 'bitcoin'),
+                 ('b', 103, 2, '{{\"native_data\": {{\"app_public_inputs\": {{\"t/x/x\": null, \"t/y/y\": null}}}}}}', 'mainnet', 'bitcoin'),
+                 ('u', 103, 3, '{{}}', 'mainnet', 'bitcoin'),
+                 ('t', 103, 1, '{{}}', 'testnet4', 'bitcoin');
+             INSERT INTO charms (txid, vout, app_id, block_height, network, asset_type, blockchain)
+             VALUES ('c', 0, 't/y/y', 101, 'mainnet', 'token', 'bitcoin');
+             UPDATE charms SET spent = true, spent_height = 103 WHERE txid = 'c';
+             INSERT INTO charms_archive (txid, vout, block_height, network, spent, spent_height,
+                                         app_id, amount, asset_type, blockchain)
+             VALUES ('old', 0, 50, 'mainnet', true, 103, 't/x/x', 1, 'token', 'bitcoin');
+             INSERT INTO dex_orders (order_id, txid, vout, block_height, platform, maker, side,
+                                     exec_type, price_num, price_den, amount, quantity,
+                                     asset_app_id, status, blockchain, network)
+             VALUES ('b:0', 'b', 0, 103, 'charms-cast', 'bc1q', 'ask', 'all_or_none', 1, 1,
+                     10, 10, 't/x/x', 'open', 'bitcoin', 'mainnet');"
+        ))
+        .await
+        .expect("fixture");
+
+        let repo = BlocksRepository::new(conn.clone());
+        let block = repo.get(103, "mainnet").await.unwrap().unwrap();
+        assert_eq!(block.hash.as_deref(), Some("hash103"));
+        assert_eq!(
+            block.block_time.map(|t| t.to_string()).as_deref(),
+            Some("2026-08-01 12:00:00")
+        );
+
+        let spells: Vec<(String, i64)> = repo
+            .spells(103, "mainnet")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.txid, s.app_count))
+            .collect();
+        assert_eq!(spells, [("a".into(), 1), ("b".into(), 2), ("u".into(), 0)]);
+
+        let charms: Vec<(String, i32)> = repo
+            .charms(103, "mainnet")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.txid, c.vout))
+            .collect();
+        assert_eq!(charms, [("a".into(), 1), ("b".into(), 0)]);
+
+        let orders = crate::db::repositories::DexOrdersRepository::new(conn.clone())
+            .find_by_block(103, "mainnet")
+            .await
+            .unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_id, "b:0");
+
+        // 'c' carries two apps on one UTXO, plus the archived 'old'.
+        assert_eq!(repo.spent_count(103, "mainnet").await.unwrap(), 2);
+        assert_eq!(repo.spent_count(103, "testnet4").await.unwrap(), 0);
+        assert!(repo.spells(102, "mainnet").await.unwrap().is_empty());
+
+        schema.drop_schema().await;
+    }
+}
@@ -0,0 +1,99 @@
+// Changefeed repository — the append-only log of charm, asset, DEX order
+// and holder writes the indexer records for downstream ETL, read by id.
+
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct ChangefeedEntry {
+    pub id: i64,
+    /// "charm", "asset", "dex_order" or "stats_holder"
+    pub entity_type: String,
+    /// `txid:vout:app_id`, `app_id`, `order_id` or `app_id:address`
+    pub entity_key: String,
+    /// "insert" or "update"
+    pub operation: String,
+    pub block_height: Option<i32>,
+    pub network: String,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+#[derive(Clone)]
+pub struct ChangefeedRepository {
+    conn: DatabaseConnection,
+}
+
+impl ChangefeedRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Up to `limit` entries with an id above `since_id`, in id order.
+    pub async fn since(&self, since_id: i64, limit: u64) -> Result<Vec<ChangefeedEntry>, DbError> {
+        ChangefeedEntry::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT id, entity_type, entity_key, operation, block_height, network, created_at
+               FROM changefeed
+              WHERE id > $1
+              ORDER BY id
+              LIMIT $2",
+            [since_id.into(), (limit as i64).into()],
+        ))
+        .all(&self.conn)
+        .await
+        .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use sea_orm::ConnectionTrait;
+
+    const SEED: &str = "
+        INSERT INTO changefeed (entity_type, entity_key, operation, block_height, network) VALUES
+            ('charm', 'tx1:0:t/a/a', 'insert', 100, 'mainnet'),
+            ('stats_holder', 'n/a/a:bc1qa', 'insert', 100, 'mainnet'),
+            ('asset', 'n/a/a', 'update', 100, 'mainnet'),
+            ('charm', 'tx1:0:t/a/a', 'update', 101, 'mainnet');
+    ";
+
+    /// Batches come back in id order, and resuming from the last id of a
+    /// batch yields exactly the entries written after it. Needs a scratch
+    /// database; tables live in a throwaway schema.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn batches_resume_from_a_checkpoint() {
+        let schema = TestSchema::new("changefeed", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(SEED).await.expect("fixture");
+
+        let repo = ChangefeedRepository::new(conn.clone());
+        let first = repo.since(0, 3).await.unwrap();
+        let ids: Vec<i64> = first.iter().map(|e| e.id).collect();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        let checkpoint = *ids.last().unwrap();
+
+        conn.execute_unprepared(
+            "INSERT INTO changefeed (entity_type, entity_key, operation, block_height, network) \
+             VALUES ('dex_order', 'tx2:0', 'insert', 102, 'mainnet')",
+        )
+        .await
+        .unwrap();
+
+        let rest = repo.since(checkpoint, 100).await.unwrap();
+        let keys: Vec<(&str, &str)> = rest
+            .iter()
+            .map(|e| (e.entity_key.as_str(), e.operation.as_str()))
+            .collect();
+        assert_eq!(keys, [("tx1:0:t/a/a", "update"), ("tx2:0", "insert")]);
+        let last = rest.last().unwrap().id;
+        assert!(repo.since(last, 100).await.unwrap().is_empty());
+
+        schema.drop_schema().await;
+    }
+}
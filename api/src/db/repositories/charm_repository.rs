@@ -1,19 +1,24 @@
 // Charm database operations implementation
-// All queries use SeaORM ORM — no raw SQL.
+// All queries use SeaORM ORM — no raw SQL. The `charms_archive` fallback
+// is built with sea-query, since it has no entity of its own.
 
 use std::collections::HashMap;
 
-use sea_orm::sea_query::{Expr, NullOrdering, Order};
+use sea_orm::sea_query::{
+    Alias, Expr, Func, IntoTableRef, JoinType, NullOrdering, Order, Query, SimpleExpr, UnionType,
+};
 use sea_orm::{
-    ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
-    QuerySelect,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, FromQueryResult, Iterable,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Select,
 };
 
+use charms_core::CharmOperation;
 use serde::Serialize;
 
 use crate::db::error::DbError;
-use crate::entity::charms;
-use crate::models::PaginationParams;
+use crate::db::repositories::moderation_repository::ModerationStatus;
+use crate::entity::{assets, charms, likes};
+use crate::models::{CharmSort, PaginationParams};
 
 /// Aggregated charm balance for a single app_id
 #[derive(Debug, Serialize)]
@@ -26,6 +31,74 @@ pub struct CharmBalance {
     pub unconfirmed_count: i64,
 }
 
+/// Unspent holdings of one app_id at an address, with the asset's
+/// metadata when it has an `assets` row
+#[derive(Debug, Clone, PartialEq, Eq, FromQueryResult)]
+pub struct AddressHolding {
+    pub app_id: String,
+    pub amount: i64,
+    pub utxo_count: i64,
+    /// Lowest and highest block among the held UTXOs; null while all of
+    /// them are in mempool
+    pub first_block: Option<i32>,
+    pub last_block: Option<i32>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub image_url: Option<String>,
+}
+
+/// Unconfirmed spend of a UTXO, as recorded by the indexer's mempool processor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingSpend {
+    pub spending_txid: String,
+    /// When the indexer first saw the spending tx in mempool
+    pub first_seen_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Charms written by one (parser revision, indexer version) pair; both are
+/// null for rows written before the indexer stamped them
+#[derive(Debug, Serialize, sea_orm::FromQueryResult)]
+pub struct ParserRevisionCount {
+    pub parser_revision: Option<i32>,
+    pub indexer_version: Option<String>,
+    pub charms: i64,
+}
+
+/// Optional narrowing of a charms listing. Amounts are raw units.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CharmFilter {
+    pub operation: Option<CharmOperation>,
+    pub app_id: Option<String>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+}
+
+impl CharmFilter {
+    /// Whether `charm` passes, for listings filtered in memory. Rows written
+    /// before the operation column existed have none and only pass without
+    /// an operation filter.
+    pub fn matches(&self, charm: &charms::Model) -> bool {
+        self.operation
+            .is_none_or(|op| charm.operation.as_deref() == Some(op.as_str()))
+            && self.app_id.as_ref().is_none_or(|id| &charm.app_id == id)
+            && self.min_amount.is_none_or(|min| charm.amount >= min)
+            && self.max_amount.is_none_or(|max| charm.amount <= max)
+    }
+}
+
+/// An address's balance of one asset at the end of a block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BalanceAt {
+    /// Received at or before the block and still unspent at its end
+    pub held: i64,
+    /// Received at or before the block and spent at a height the indexer
+    /// never recorded, so possibly still held then
+    pub unknown: i64,
+}
+
+/// Spent charms the indexer pruned from `charms`; read only as a fallback
+const ARCHIVE_TABLE: &str = "charms_archive";
+
 /// Repository for charm database operations
 pub struct CharmRepository {
     conn: DatabaseConnection,
@@ -37,24 +110,220 @@ impl CharmRepository {
         CharmRepository { conn }
     }
 
-    /// Returns a reference to the underlying database connection
-    #[allow(dead_code)] // Used in charm_service for direct queries
-    pub fn get_connection(&self) -> &DatabaseConnection {
-        &self.conn
-    }
-
     /// Retrieves a charm by (txid, network). txid is unique per chain
     /// by SHA256 collision resistance, but scoping by network is the
-    /// invariant the rest of the system follows.
+    /// invariant the rest of the system follows. Unlike the listings
+    /// below, empty-spell placeholders are returned (with `is_placeholder`).
+    /// Falls back to `charms_archive` when the indexer pruned the tx.
     pub async fn get_by_txid(
         &self,
         txid: &str,
         network: &str,
     ) -> Result<Option<charms::Model>, DbError> {
-        charms::Entity::find()
+        let live = charms::Entity::find()
             .filter(charms::Column::Txid.eq(txid))
             .filter(charms::Column::Network.eq(network))
             .one(&self.conn)
+            .await?;
+        match live {
+            Some(charm) => Ok(Some(charm)),
+            None => Ok(self
+                .get_archived(&[txid.to_string()], network)
+                .await?
+                .into_iter()
+                .next()),
+        }
+    }
+
+    /// Archived charms of `txids` (see the indexer's spent-charm archiver).
+    /// Same columns as `charms`, so rows load into the same model.
+    async fn get_archived(
+        &self,
+        txids: &[String],
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        self.archived_where(
+            Expr::col(charms::Column::Txid).is_in(txids.to_vec()),
+            network,
+        )
+        .await
+    }
+
+    /// Archived charms of `network` matching `condition`, by outpoint.
+    async fn archived_where(
+        &self,
+        condition: SimpleExpr,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        let query = Query::select()
+            .columns(charms::Column::iter())
+            .from(Alias::new(ARCHIVE_TABLE))
+            .and_where(condition)
+            .and_where(Expr::col(charms::Column::Network).eq(network))
+            .order_by(charms::Column::Txid, Order::Asc)
+            .order_by(charms::Column::Vout, Order::Asc)
+            .to_owned();
+        let stmt = self.conn.get_database_backend().build(&query);
+        charms::Model::find_by_statement(stmt)
+            .all(&self.conn)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Charms consumed by `spending_txid`: the rows whose output it spent,
+    /// archive included, by outpoint. Empty for rows spent before the
+    /// indexer recorded `spending_txid`.
+    pub async fn find_by_spending_txid(
+        &self,
+        spending_txid: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        let mut rows = charms::Entity::find()
+            .filter(charms::Column::SpendingTxid.eq(spending_txid))
+            .filter(charms::Column::Network.eq(network))
+            .order_by_asc(charms::Column::Txid)
+            .order_by_asc(charms::Column::Vout)
+            .all(&self.conn)
+            .await?;
+        rows.extend(
+            self.archived_where(
+                Expr::col(charms::Column::SpendingTxid).eq(spending_txid),
+                network,
+            )
+            .await?,
+        );
+        Ok(rows)
+    }
+
+    /// `address`'s balance of `app_id` at the end of block `height`, in one
+    /// query over its rows of `charms` and `charms_archive` received up to
+    /// that height (`idx_charms_address_net_spent`,
+    /// `idx_charms_archive_address_net`). The cost grows with the address's
+    /// history in the asset, not with how far back `height` is.
+    pub async fn balance_at(
+        &self,
+        address: &str,
+        app_id: &str,
+        network: &str,
+        height: i32,
+    ) -> Result<BalanceAt, DbError> {
+        #[derive(FromQueryResult)]
+        struct Row {
+            held: i64,
+            unknown: i64,
+        }
+
+        let received = |table: sea_orm::sea_query::TableRef| {
+            Query::select()
+                .columns([
+                    charms::Column::Amount,
+                    charms::Column::Spent,
+                    charms::Column::SpentHeight,
+                ])
+                .from(table)
+                .and_where(Expr::col(charms::Column::Address).eq(address))
+                .and_where(Expr::col(charms::Column::Network).eq(network))
+                .and_where(Expr::col(charms::Column::AppId).eq(app_id))
+                .and_where(Expr::col(charms::Column::BlockHeight).lte(height))
+                .and_where(Expr::col(charms::Column::IsPlaceholder).eq(false))
+                .to_owned()
+        };
+        let archived = received(Alias::new(ARCHIVE_TABLE).into_table_ref());
+        let rows = received(charms::Entity.into_table_ref())
+            .union(UnionType::All, archived)
+            .to_owned();
+        let total = |condition: SimpleExpr| {
+            let amounts = Expr::case(condition, Expr::col(charms::Column::Amount)).finally(0);
+            Expr::expr(Func::coalesce([
+                Func::sum(amounts).into(),
+                Expr::val(0).into(),
+            ]))
+            .cast_as(Alias::new("BIGINT"))
+        };
+        let query = Query::select()
+            .expr_as(
+                total(
+                    Expr::col(charms::Column::Spent)
+                        .eq(false)
+                        .or(Expr::col(charms::Column::SpentHeight).gt(height)),
+                ),
+                Alias::new("held"),
+            )
+            .expr_as(
+                total(
+                    Expr::col(charms::Column::Spent)
+                        .eq(true)
+                        .and(Expr::col(charms::Column::SpentHeight).is_null()),
+                ),
+                Alias::new("unknown"),
+            )
+            .from_subquery(rows, Alias::new("received"))
+            .to_owned();
+        let stmt = self.conn.get_database_backend().build(&query);
+        let row = Row::find_by_statement(stmt)
+            .one(&self.conn)
+            .await?
+            .ok_or_else(|| DbError::QueryError("balance query returned no row".to_string()))?;
+        Ok(BalanceAt {
+            held: row.held,
+            unknown: row.unknown,
+        })
+    }
+
+    /// Networks holding a charm of `txid`, archive included, ignoring the
+    /// network scope of `get_by_txid`. Used to point a miss at the network
+    /// the tx is actually on.
+    pub async fn networks_for_txid(
+        &self,
+        txid: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<String>, DbError> {
+        let query = charms::Entity::find().filter(charms::Column::Txid.eq(txid));
+        let mut networks = self
+            .networks_of(with_visibility(query, include_hidden))
+            .await?;
+
+        let mut archived = Query::select()
+            .distinct()
+            .column(charms::Column::Network)
+            .from(Alias::new(ARCHIVE_TABLE))
+            .and_where(Expr::col(charms::Column::Txid).eq(txid))
+            .to_owned();
+        if !include_hidden {
+            archived.and_where(
+                Expr::col(charms::Column::ModerationStatus).ne(ModerationStatus::Hidden.as_str()),
+            );
+        }
+        let stmt = self.conn.get_database_backend().build(&archived);
+        for row in self.conn.query_all(stmt).await? {
+            networks.push(row.try_get("", "network")?);
+        }
+        networks.sort();
+        networks.dedup();
+        Ok(networks)
+    }
+
+    /// Networks holding charms of `charmid`, ignoring the network scope of
+    /// `find_by_charmid`.
+    pub async fn networks_for_charmid(
+        &self,
+        charmid: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<String>, DbError> {
+        let query = charms::Entity::find().filter(charms::Column::AppId.eq(charmid));
+        self.networks_of(with_visibility(query, include_hidden))
+            .await
+    }
+
+    /// Distinct, sorted `network` values of the rows `query` matches
+    async fn networks_of(&self, query: Select<charms::Entity>) -> Result<Vec<String>, DbError> {
+        query
+            .select_only()
+            .column(charms::Column::Network)
+            .distinct()
+            .order_by_asc(charms::Column::Network)
+            .into_tuple::<String>()
+            .all(&self.conn)
             .await
             .map_err(Into::into)
     }
@@ -67,6 +336,7 @@ impl CharmRepository {
     ) -> Result<Vec<charms::Model>, DbError> {
         charms::Entity::find()
             .filter(charms::Column::AssetType.eq(asset_type))
+            .filter(charms::Column::IsPlaceholder.eq(false))
             .all(&self.conn)
             .await
             .map_err(Into::into)
@@ -76,89 +346,79 @@ impl CharmRepository {
     #[allow(dead_code)]
     pub async fn get_all(&self) -> Result<Vec<charms::Model>, DbError> {
         charms::Entity::find()
+            .filter(charms::Column::IsPlaceholder.eq(false))
             .order_by_desc(charms::Column::BlockHeight)
             .all(&self.conn)
             .await
             .map_err(Into::into)
     }
 
-    /// Retrieves all charms paginated by network
-    /// NULLs FIRST so mempool charms (block_height=NULL) appear at the top
+    /// Retrieves all charms paginated by network, in `pagination.sort` order,
+    /// narrowed by `filter`
     pub async fn get_all_paginated_by_network(
         &self,
         pagination: &PaginationParams,
         network: &str,
+        filter: &CharmFilter,
+        include_hidden: bool,
     ) -> Result<(Vec<charms::Model>, u64), DbError> {
-        let total = charms::Entity::find()
+        let query = charms::Entity::find()
             .filter(charms::Column::Network.eq(network))
-            .count(&self.conn)
-            .await? as u64;
-
-        let offset = (pagination.page - 1) * pagination.limit;
-        let mut query = charms::Entity::find().filter(charms::Column::Network.eq(network));
-        QuerySelect::query(&mut query)
-            .order_by_with_nulls(
-                charms::Column::BlockHeight,
-                Order::Desc,
-                NullOrdering::First,
-            )
-            .order_by(charms::Column::DateCreated, Order::Desc);
-        let charms = query
-            .limit(pagination.limit)
-            .offset(offset)
-            .all(&self.conn)
-            .await?;
-
-        Ok((charms, total))
+            .filter(charms::Column::IsPlaceholder.eq(false));
+        let query = with_visibility(with_filter(query, filter), include_hidden);
+        self.list_sorted(query, pagination).await
     }
 
-    /// Retrieves all charms paginated (all networks)
-    /// NULLs FIRST so mempool charms (block_height=NULL) appear at the top
+    /// Retrieves all charms paginated (all networks), in `pagination.sort`
+    /// order, narrowed by `filter`
     pub async fn get_all_paginated(
         &self,
         pagination: &PaginationParams,
+        filter: &CharmFilter,
+        include_hidden: bool,
     ) -> Result<(Vec<charms::Model>, u64), DbError> {
-        let total = charms::Entity::find().count(&self.conn).await? as u64;
-
-        let offset = (pagination.page - 1) * pagination.limit;
-        let mut query = charms::Entity::find();
-        QuerySelect::query(&mut query)
-            .order_by_with_nulls(
-                charms::Column::BlockHeight,
-                Order::Desc,
-                NullOrdering::First,
-            )
-            .order_by(charms::Column::DateCreated, Order::Desc);
-        let charms = query
-            .limit(pagination.limit)
-            .offset(offset)
-            .all(&self.conn)
-            .await?;
-
-        Ok((charms, total))
+        let query = charms::Entity::find().filter(charms::Column::IsPlaceholder.eq(false));
+        let query = with_visibility(with_filter(query, filter), include_hidden);
+        self.list_sorted(query, pagination).await
     }
 
-    /// Finds charms by asset type with pagination
-    /// NULLs FIRST so mempool charms (block_height=NULL) appear at the top
+    /// Finds charms of one asset type on one network with pagination, in
+    /// `pagination.sort` order. Count and default page order are served by
+    /// `idx_charms_listed_type_net_height_ordinal`.
     pub async fn find_by_asset_type_paginated(
         &self,
         asset_type: &str,
+        network: &str,
         pagination: &PaginationParams,
+        include_hidden: bool,
     ) -> Result<(Vec<charms::Model>, u64), DbError> {
-        let total = charms::Entity::find()
+        let query = charms::Entity::find()
             .filter(charms::Column::AssetType.eq(asset_type))
-            .count(&self.conn)
-            .await? as u64;
+            .filter(charms::Column::Network.eq(network))
+            .filter(charms::Column::IsPlaceholder.eq(false));
+        self.list_sorted(with_visibility(query, include_hidden), pagination)
+            .await
+    }
+
+    /// Deepest row `likes_desc` will page to. Each page re-counts likes for
+    /// every matching charm, so deep offsets get expensive; past this the
+    /// listing returns an empty page.
+    pub const LIKES_SORT_MAX_OFFSET: u64 = 1000;
+
+    /// Count `query`, then fetch one page of it in `pagination.sort` order.
+    async fn list_sorted(
+        &self,
+        mut query: Select<charms::Entity>,
+        pagination: &PaginationParams,
+    ) -> Result<(Vec<charms::Model>, u64), DbError> {
+        let total = query.clone().count(&self.conn).await? as u64;
 
         let offset = (pagination.page - 1) * pagination.limit;
-        let mut query = charms::Entity::find().filter(charms::Column::AssetType.eq(asset_type));
-        QuerySelect::query(&mut query)
-            .order_by_with_nulls(
-                charms::Column::BlockHeight,
-                Order::Desc,
-                NullOrdering::First,
-            )
-            .order_by(charms::Column::DateCreated, Order::Desc);
+        if pagination.sort == CharmSort::LikesDesc && offset >= Self::LIKES_SORT_MAX_OFFSET {
+            return Ok((vec![], total));
+        }
+
+        apply_sort(&mut query, pagination.sort);
         let charms = query
             .limit(pagination.limit)
             .offset(offset)
@@ -173,10 +433,12 @@ impl CharmRepository {
         &self,
         charmid: &str,
         network: &str,
+        include_hidden: bool,
     ) -> Result<Vec<charms::Model>, DbError> {
-        charms::Entity::find()
+        let query = charms::Entity::find()
             .filter(charms::Column::AppId.eq(charmid))
-            .filter(charms::Column::Network.eq(network))
+            .filter(charms::Column::Network.eq(network));
+        with_visibility(query, include_hidden)
             .all(&self.conn)
             .await
             .map_err(Into::into)
@@ -198,12 +460,81 @@ impl CharmRepository {
             .map_err(Into::into)
     }
 
+    /// `address`'s unspent charms summed per app_id: the rows
+    /// `find_by_address` returns, narrowed by `filter` and folded by a
+    /// GROUP BY, joined with the app's `assets` row. Largest holding first.
+    pub async fn holdings_by_address(
+        &self,
+        address: &str,
+        network: &str,
+        filter: &CharmFilter,
+    ) -> Result<Vec<AddressHolding>, DbError> {
+        let charm = |column: charms::Column| Expr::col((charms::Entity, column));
+        let asset = |column: assets::Column| Expr::col((assets::Entity, column));
+        let amount = Expr::expr(Func::coalesce([
+            Func::sum(charm(charms::Column::Amount)).into(),
+            Expr::val(0).into(),
+        ]))
+        .cast_as(Alias::new("BIGINT"));
+
+        let mut query = with_filter(
+            charms::Entity::find()
+                .select_only()
+                .filter(charms::Column::Address.eq(address))
+                .filter(charms::Column::Network.eq(network))
+                .filter(charms::Column::Spent.eq(false)),
+            filter,
+        );
+        // (app_id, network) is unique in `assets`, so grouping on its
+        // columns does not split an app_id.
+        QuerySelect::query(&mut query)
+            .expr_as(charm(charms::Column::AppId), Alias::new("app_id"))
+            .expr_as(amount, Alias::new("amount"))
+            .expr_as(
+                charm(charms::Column::Txid).count(),
+                Alias::new("utxo_count"),
+            )
+            .expr_as(
+                Func::min(charm(charms::Column::BlockHeight)),
+                Alias::new("first_block"),
+            )
+            .expr_as(
+                Func::max(charm(charms::Column::BlockHeight)),
+                Alias::new("last_block"),
+            )
+            .expr_as(asset(assets::Column::Name), Alias::new("name"))
+            .expr_as(asset(assets::Column::Symbol), Alias::new("symbol"))
+            .expr_as(asset(assets::Column::ImageUrl), Alias::new("image_url"))
+            .join(
+                JoinType::LeftJoin,
+                assets::Entity,
+                asset(assets::Column::AppId)
+                    .equals((charms::Entity, charms::Column::AppId))
+                    .and(
+                        asset(assets::Column::Network)
+                            .equals((charms::Entity, charms::Column::Network)),
+                    ),
+            )
+            .group_by_col((charms::Entity, charms::Column::AppId))
+            .group_by_col((assets::Entity, assets::Column::Name))
+            .group_by_col((assets::Entity, assets::Column::Symbol))
+            .group_by_col((assets::Entity, assets::Column::ImageUrl))
+            .order_by_expr(Expr::cust("amount"), Order::Desc)
+            .order_by((charms::Entity, charms::Column::AppId), Order::Asc);
+
+        query
+            .into_model::<AddressHolding>()
+            .all(&self.conn)
+            .await
+            .map_err(Into::into)
+    }
+
     /// Retrieves all charm IDs, filtered by asset type if provided
     pub async fn get_charm_numbers_by_type(
         &self,
         asset_type: Option<&str>,
     ) -> Result<Vec<String>, DbError> {
-        let mut query = charms::Entity::find();
+        let mut query = charms::Entity::find().filter(charms::Column::IsPlaceholder.eq(false));
 
         if let Some(asset_type) = asset_type {
             query = query.filter(charms::Column::AssetType.eq(asset_type));
@@ -219,14 +550,52 @@ impl CharmRepository {
         Ok(charm_numbers)
     }
 
+    /// Charm counts per parser revision and indexer version, oldest first
+    pub async fn count_by_parser_revision(
+        &self,
+        network: Option<&str>,
+    ) -> Result<Vec<ParserRevisionCount>, DbError> {
+        let mut query = charms::Entity::find();
+        if let Some(network) = network {
+            query = query.filter(charms::Column::Network.eq(network));
+        }
+        let mut query = query
+            .select_only()
+            .column(charms::Column::ParserRevision)
+            .column(charms::Column::IndexerVersion)
+            .column_as(charms::Column::Txid.count(), "charms")
+            .group_by(charms::Column::ParserRevision)
+            .group_by(charms::Column::IndexerVersion);
+        QueryTrait::query(&mut query)
+            .order_by_with_nulls(
+                charms::Column::ParserRevision,
+                Order::Asc,
+                NullOrdering::First,
+            )
+            .order_by_with_nulls(
+                charms::Column::IndexerVersion,
+                Order::Asc,
+                NullOrdering::First,
+            );
+        query
+            .into_model::<ParserRevisionCount>()
+            .all(&self.conn)
+            .await
+            .map_err(Into::into)
+    }
+
     /// Counts all charms in the database
     #[allow(dead_code)] // Reserved for future use
     pub async fn count_all(&self) -> Result<i64, DbError> {
-        let count = charms::Entity::find().count(&self.conn).await?;
+        let count = charms::Entity::find()
+            .filter(charms::Column::IsPlaceholder.eq(false))
+            .count(&self.conn)
+            .await?;
         Ok(count as i64)
     }
 
-    /// Batch fetch charms by multiple txids, network-scoped.
+    /// Batch fetch charms by multiple txids, network-scoped. Txids with no
+    /// live rows are looked up in `charms_archive`.
     pub async fn get_by_txids(
         &self,
         txids: &[String],
@@ -235,12 +604,20 @@ impl CharmRepository {
         if txids.is_empty() {
             return Ok(vec![]);
         }
-        charms::Entity::find()
+        let mut rows = charms::Entity::find()
             .filter(charms::Column::Txid.is_in(txids.to_vec()))
             .filter(charms::Column::Network.eq(network))
             .all(&self.conn)
-            .await
-            .map_err(Into::into)
+            .await?;
+        let missing: Vec<String> = txids
+            .iter()
+            .filter(|txid| !rows.iter().any(|c| &c.txid == *txid))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            rows.extend(self.get_archived(&missing, network).await?);
+        }
+        Ok(rows)
     }
 
     /// Get charm balances by address, grouped by app_id
@@ -327,32 +704,55 @@ impl CharmRepository {
             .map_err(Into::into)
     }
 
-    /// Get set of (txid, vout) being spent by mempool transactions for a given network
-    pub async fn get_mempool_spent_utxos(
+    /// Pending mempool spends of the given (txid, vout) outpoints, keyed by outpoint.
+    /// Outpoints nobody is spending are absent from the map.
+    pub async fn get_pending_spends_for_outpoints(
         &self,
+        outpoints: &[(String, i32)],
         network: &str,
-    ) -> Result<std::collections::HashSet<(String, i32)>, DbError> {
+    ) -> Result<HashMap<(String, i32), PendingSpend>, DbError> {
         use sea_orm::FromQueryResult;
 
         #[derive(FromQueryResult)]
-        struct SpentUtxo {
+        struct SpendRow {
             spent_txid: String,
             spent_vout: i32,
+            spending_txid: String,
+            detected_at: chrono::DateTime<chrono::Utc>,
         }
 
-        let rows = sea_orm::Statement::from_sql_and_values(
+        if outpoints.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut values: Vec<sea_orm::Value> = vec![network.into()];
+        let mut pairs = Vec::with_capacity(outpoints.len());
+        for (txid, vout) in outpoints {
+            pairs.push(format!("(${}, ${})", values.len() + 1, values.len() + 2));
+            values.push(txid.as_str().into());
+            values.push((*vout).into());
+        }
+        let stmt = sea_orm::Statement::from_sql_and_values(
             sea_orm::DatabaseBackend::Postgres,
-            "SELECT spent_txid, spent_vout FROM mempool_spends WHERE network = $1",
-            [network.into()],
+            format!(
+                "SELECT spent_txid, spent_vout, spending_txid, detected_at FROM mempool_spends \
+                  WHERE network = $1 AND (spent_txid, spent_vout) IN ({})",
+                pairs.join(", ")
+            ),
+            values,
         );
-        let results = SpentUtxo::find_by_statement(rows)
-            .all(&self.conn)
-            .await
-            .map_err(|e| DbError::from(e))?;
+        let rows = SpendRow::find_by_statement(stmt).all(&self.conn).await?;
 
-        Ok(results
+        Ok(rows
             .into_iter()
-            .map(|r| (r.spent_txid, r.spent_vout))
+            .map(|r| {
+                (
+                    (r.spent_txid, r.spent_vout),
+                    PendingSpend {
+                        spending_txid: r.spending_txid,
+                        first_seen_at: r.detected_at,
+                    },
+                )
+            })
             .collect())
     }
 
@@ -383,6 +783,8 @@ impl CharmRepository {
         // Step 2: Get all charms sharing those txids (then filter by vout in memory)
         let siblings = charms::Entity::find()
             .filter(charms::Column::Txid.is_in(txids))
+            .filter(charms::Column::Network.eq(network))
+            .order_by_asc(charms::Column::AppId)
             .all(&self.conn)
             .await?;
 
@@ -425,3 +827,392 @@ impl CharmRepository {
         Ok(result.and_then(|r| r.total.map(|d| d.to_string().parse::<i64>().unwrap_or(0))))
     }
 }
+
+/// `CharmFilter::matches` as WHERE clauses. An app_id filter is served by
+/// `idx_charms_appid_net_spent`.
+fn with_filter(mut query: Select<charms::Entity>, filter: &CharmFilter) -> Select<charms::Entity> {
+    if let Some(op) = filter.operation {
+        query = query.filter(charms::Column::Operation.eq(op.as_str()));
+    }
+    if let Some(app_id) = &filter.app_id {
+        query = query.filter(charms::Column::AppId.eq(app_id.as_str()));
+    }
+    if let Some(min) = filter.min_amount {
+        query = query.filter(charms::Column::Amount.gte(min));
+    }
+    if let Some(max) = filter.max_amount {
+        query = query.filter(charms::Column::Amount.lte(max));
+    }
+    query
+}
+
+/// Leave out charms an operator hid, unless an admin asked for them.
+fn with_visibility(
+    query: Select<charms::Entity>,
+    include_hidden: bool,
+) -> Select<charms::Entity> {
+    if include_hidden {
+        query
+    } else {
+        query.filter(charms::Column::ModerationStatus.ne(ModerationStatus::Hidden.as_str()))
+    }
+}
+
+/// When a charm with no position in its block happened: its block's time,
+/// else when it was indexed. NULL for rows with a `tx_ordinal`, which the
+/// position alone orders.
+const UNPLACED_TIME: &str =
+    "CASE WHEN charms.tx_ordinal IS NULL THEN COALESCE(charms.block_time, charms.date_created) END";
+
+/// ORDER BY for a charms listing. Mempool rows (block_height NULL) lead in
+/// `newest` and trail in the ascending / block orderings; every ordering
+/// finishes on (txid, vout) so ties page stably. The time orderings follow
+/// (block_height, tx_ordinal, vout); rows without an ordinal (mempool and
+/// unbackfilled) fall back to their time before the vout.
+fn apply_sort(query: &mut Select<charms::Entity>, sort: CharmSort) {
+    let select = QuerySelect::query(query);
+    match sort {
+        CharmSort::Newest => {
+            select
+                .order_by_with_nulls(
+                    charms::Column::BlockHeight,
+                    Order::Desc,
+                    NullOrdering::First,
+                )
+                .order_by_with_nulls(charms::Column::TxOrdinal, Order::Desc, NullOrdering::First)
+                .order_by_expr(Expr::cust(UNPLACED_TIME), Order::Desc)
+                .order_by((charms::Entity, charms::Column::Vout), Order::Asc);
+        }
+        CharmSort::Oldest => {
+            select
+                .order_by_with_nulls(charms::Column::BlockHeight, Order::Asc, NullOrdering::Last)
+                .order_by_with_nulls(charms::Column::TxOrdinal, Order::Asc, NullOrdering::Last)
+                .order_by_expr(Expr::cust(UNPLACED_TIME), Order::Asc)
+                .order_by((charms::Entity, charms::Column::Vout), Order::Asc);
+        }
+        CharmSort::AmountDesc => {
+            select.order_by(charms::Column::Amount, Order::Desc);
+        }
+        CharmSort::AmountAsc => {
+            select.order_by(charms::Column::Amount, Order::Asc);
+        }
+        CharmSort::BlockDesc => {
+            select.order_by_with_nulls(
+                charms::Column::BlockHeight,
+                Order::Desc,
+                NullOrdering::Last,
+            );
+        }
+        CharmSort::LikesDesc => {
+            // likes.charm_id holds the app_id; count once per app_id, then
+            // join (idx_likes_charm_id keeps the GROUP BY index-only).
+            let likes_counts = sea_orm::sea_query::Query::select()
+                .column(likes::Column::CharmId)
+                .expr_as(Expr::col(likes::Column::Id).count(), Alias::new("likes"))
+                .from(likes::Entity)
+                .group_by_col(likes::Column::CharmId)
+                .to_owned();
+            select
+                .join_subquery(
+                    JoinType::LeftJoin,
+                    likes_counts,
+                    Alias::new("lc"),
+                    Expr::col((Alias::new("lc"), likes::Column::CharmId))
+                        .equals((charms::Entity, charms::Column::AppId)),
+                )
+                .order_by_expr(Expr::cust("COALESCE(lc.likes, 0)"), Order::Desc);
+        }
+    }
+    select
+        .order_by((charms::Entity, charms::Column::Txid), Order::Asc)
+        .order_by((charms::Entity, charms::Column::Vout), Order::Asc);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use sea_orm::ConnectionTrait;
+
+    /// Charms with ties on amount, height and likes; the tables come from
+    /// `TestSchema`.
+    const SEED: &str = "
+        INSERT INTO charms
+            (txid, vout, block_height, tx_ordinal, app_id, amount, asset_type, blockchain, network)
+        VALUES
+            ('a1', 0, 10, 0, 'n/x/x', 5, 'token', 'bitcoin', 'mainnet'),
+            ('b2', 0, 10, 1, 't/y/y', 5, 'token', 'bitcoin', 'mainnet'),
+            ('c3', 0, 12, 0, 't/z/z', 1, 'token', 'bitcoin', 'mainnet'),
+            ('d4', 0, NULL, NULL, 't/y/y', 9, 'token', 'bitcoin', 'mainnet');
+        INSERT INTO likes (charm_id, user_id) VALUES ('t/y/y', 1), ('t/y/y', 2), ('t/z/z', 1);
+    ";
+
+    /// Each ordering over a fixture with ties on amount, height and likes.
+    /// Needs a scratch database; tables live in a throwaway schema.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn listing_orderings_are_stable() {
+        let schema = TestSchema::new("charm_sort", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(SEED).await.expect("fixture");
+
+        let repo = CharmRepository::new(conn.clone());
+        let cases = [
+            (CharmSort::Newest, ["d4", "c3", "b2", "a1"]),
+            (CharmSort::Oldest, ["a1", "b2", "c3", "d4"]),
+            (CharmSort::AmountDesc, ["d4", "a1", "b2", "c3"]),
+            (CharmSort::AmountAsc, ["c3", "a1", "b2", "d4"]),
+            (CharmSort::BlockDesc, ["c3", "a1", "b2", "d4"]),
+            (CharmSort::LikesDesc, ["b2", "d4", "c3", "a1"]),
+        ];
+        for (sort, expected) in cases {
+            let pagination = PaginationParams {
+                page: 1,
+                limit: 10,
+                sort,
+            };
+            let (rows, total) = repo
+                .get_all_paginated(&pagination, &CharmFilter::default(), false)
+                .await
+                .unwrap();
+            let txids: Vec<_> = rows.iter().map(|c| c.txid.as_str()).collect();
+            assert_eq!(txids, expected, "{:?}", sort);
+            assert_eq!(total, 4);
+        }
+
+        let too_deep = PaginationParams {
+            page: CharmRepository::LIKES_SORT_MAX_OFFSET / 10 + 1,
+            limit: 10,
+            sort: CharmSort::LikesDesc,
+        };
+        let (rows, total) = repo
+            .get_all_paginated(&too_deep, &CharmFilter::default(), false)
+            .await
+            .unwrap();
+        assert!(rows.is_empty());
+        assert_eq!(total, 4);
+
+        // Outputs of one transaction follow their vout, not when each row
+        // was indexed.
+        conn.execute_unprepared(
+            "INSERT INTO charms (txid, vout, block_height, tx_ordinal, app_id, date_created, \
+                                 asset_type, blockchain, network) \
+             VALUES ('c3', 1, 12, 0, 't/w/w', '2027-01-01', 'token', 'bitcoin', 'mainnet')",
+        )
+        .await
+        .unwrap();
+        for sort in [CharmSort::Newest, CharmSort::Oldest] {
+            let pagination = PaginationParams {
+                page: 1,
+                limit: 10,
+                sort,
+            };
+            let (rows, _) = repo
+                .get_all_paginated(&pagination, &CharmFilter::default(), false)
+                .await
+                .unwrap();
+            let c3: Vec<_> = rows
+                .iter()
+                .filter(|c| c.txid == "c3")
+                .map(|c| c.vout)
+                .collect();
+            assert_eq!(c3, [0, 1], "{:?}", sort);
+        }
+
+        schema.drop_schema().await;
+    }
+
+    /// Only listed outpoints of the requested network come back.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn pending_spends_match_outpoint_and_network() {
+        let schema = TestSchema::new("pending_spends", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(
+            "INSERT INTO mempool_spends \
+                 (spent_txid, spent_vout, network, spending_txid, detected_at) VALUES \
+                 ('a1', 0, 'mainnet', 's1', to_timestamp(1760000000)), \
+                 ('b2', 1, 'mainnet', 's2', to_timestamp(1760000060)), \
+                 ('c3', 0, 'mainnet', 's3', to_timestamp(1760000120)), \
+                 ('b2', 0, 'testnet4', 's4', to_timestamp(1760000180));",
+        )
+        .await
+        .expect("fixture");
+
+        let repo = CharmRepository::new(conn.clone());
+        let outpoints = [
+            ("a1".to_string(), 0),
+            ("b2".to_string(), 0),
+            ("b2".to_string(), 1),
+        ];
+        let spends = repo
+            .get_pending_spends_for_outpoints(&outpoints, "mainnet")
+            .await
+            .unwrap();
+        assert_eq!(spends.len(), 2);
+        assert_eq!(spends[&("a1".to_string(), 0)].spending_txid, "s1");
+        let b2 = &spends[&("b2".to_string(), 1)];
+        assert_eq!(b2.spending_txid, "s2");
+        assert_eq!(b2.first_seen_at.timestamp(), 1_760_000_060);
+        assert!(repo
+            .get_pending_spends_for_outpoints(&[], "mainnet")
+            .await
+            .unwrap()
+            .is_empty());
+
+        schema.drop_schema().await;
+    }
+
+    /// Detail lookups fall back to `charms_archive` for pruned txs; live
+    /// rows win and other networks stay out.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn detail_reads_fall_back_to_the_archive() {
+        let schema = TestSchema::new("charms_archive", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(&format!(
+            "{SEED} \
+             INSERT INTO charms_archive \
+                 (txid, vout, block_height, app_id, amount, spent, asset_type, blockchain, network) \
+             VALUES \
+                 ('e5', 1, 8, 't/y/y', 3, TRUE, 'token', 'bitcoin', 'mainnet'), \
+                 ('e5', 0, 8, 't/y/y', 2, TRUE, 'token', 'bitcoin', 'mainnet'), \
+                 ('f6', 0, 8, 't/y/y', 0, FALSE, 'token', 'bitcoin', 'testnet4');"
+        ))
+        .await
+        .expect("fixture");
+
+        let repo = CharmRepository::new(conn.clone());
+        let archived = repo.get_by_txid("e5", "mainnet").await.unwrap().unwrap();
+        assert_eq!((archived.vout, archived.amount), (0, 2));
+        assert!(archived.spent);
+        assert!(repo.get_by_txid("f6", "mainnet").await.unwrap().is_none());
+        assert_eq!(
+            repo.networks_for_txid("f6", false).await.unwrap(),
+            ["testnet4"]
+        );
+
+        let rows = repo
+            .get_by_txids(&["a1".to_string(), "e5".to_string()], "mainnet")
+            .await
+            .unwrap();
+        let keys: Vec<_> = rows.iter().map(|c| (c.txid.as_str(), c.vout)).collect();
+        assert_eq!(keys, [("a1", 0), ("e5", 0), ("e5", 1)]);
+
+        schema.drop_schema().await;
+    }
+
+    /// Balances at several heights over receives and spends split between
+    /// `charms` and `charms_archive`.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn balance_at_sums_live_and_archived_rows() {
+        let schema = TestSchema::new("balance_at", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(&format!(
+            "{SEED} \
+             INSERT INTO charms_archive \
+                 (txid, vout, block_height, address, app_id, amount, spent, spent_height, \
+                  asset_type, blockchain, network) VALUES \
+                 ('r1', 0, 100, 'holder', 't/b/b', 10, TRUE, 105, 'token', 'bitcoin', 'mainnet'); \
+             INSERT INTO charms \
+                 (txid, vout, block_height, address, app_id, amount, spent, spent_height, \
+                  asset_type, blockchain, network) VALUES \
+                 ('r2', 0, 103, 'holder', 't/b/b', 5, FALSE, NULL, 'token', 'bitcoin', 'mainnet'), \
+                 ('r3', 0, 105, 'holder', 't/b/b', 7, TRUE, 110, 'token', 'bitcoin', 'mainnet'), \
+                 ('r4', 0, 108, 'holder', 't/b/b', 2, TRUE, NULL, 'token', 'bitcoin', 'mainnet'), \
+                 ('x1', 0, 100, 'holder', 't/c/c', 50, FALSE, NULL, 'token', 'bitcoin', 'mainnet'), \
+                 ('x2', 0, 100, 'other', 't/b/b', 50, FALSE, NULL, 'token', 'bitcoin', 'mainnet');"
+        ))
+        .await
+        .expect("fixture");
+
+        let repo = CharmRepository::new(conn.clone());
+        for (height, held, unknown) in [
+            (99, 0, 0),
+            (100, 10, 0),
+            (104, 15, 0),
+            (105, 12, 0),
+            (108, 12, 2),
+            (110, 5, 2),
+        ] {
+            let balance = repo
+                .balance_at("holder", "t/b/b", "mainnet", height)
+                .await
+                .unwrap();
+            assert_eq!(balance, BalanceAt { held, unknown }, "{height}");
+        }
+
+        schema.drop_schema().await;
+    }
+
+    /// The GROUP BY per app_id agrees with summing `find_by_address`, takes
+    /// the charm filter, and picks up the asset row of the same network.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn holdings_by_address_sum_the_unspent_rows() {
+        let schema = TestSchema::new("holdings", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(&format!(
+            "{SEED} \
+             INSERT INTO assets \
+                 (app_id, network, name, symbol, image_url, \
+                  txid, vout_index, charm_id, block_height, asset_type, blockchain) VALUES \
+                 ('t/a/a', 'mainnet', 'Alpha', 'ALP', 'https://img/a', \
+                  'a1', 0, 't/a/a', 100, 'token', 'bitcoin'), \
+                 ('t/b/b', 'testnet4', 'Beta', 'BET', NULL, \
+                  'b2', 0, 't/b/b', 110, 'token', 'bitcoin'); \
+             INSERT INTO charms \
+                 (txid, vout, block_height, address, app_id, amount, spent, network, \
+                  asset_type, blockchain) VALUES \
+                 ('a1', 0, 100, 'holder', 't/a/a', 150, FALSE, 'mainnet', 'token', 'bitcoin'), \
+                 ('a2', 0, 120, 'holder', 't/a/a', 25, FALSE, 'mainnet', 'token', 'bitcoin'), \
+                 ('a3', 0, NULL, 'holder', 't/a/a', 5, FALSE, 'mainnet', 'token', 'bitcoin'), \
+                 ('a4', 0, 90, 'holder', 't/a/a', 1000, TRUE, 'mainnet', 'token', 'bitcoin'), \
+                 ('b1', 0, 110, 'holder', 't/b/b', 7, FALSE, 'mainnet', 'token', 'bitcoin'), \
+                 ('b2', 0, 110, 'holder', 't/b/b', 9, FALSE, 'testnet4', 'token', 'bitcoin'), \
+                 ('c1', 0, 110, 'other', 't/a/a', 50, FALSE, 'mainnet', 'token', 'bitcoin');"
+        ))
+        .await
+        .expect("fixture");
+
+        let repo = CharmRepository::new(conn.clone());
+        let holdings = repo
+            .holdings_by_address("holder", "mainnet", &CharmFilter::default())
+            .await
+            .unwrap();
+        let flat = repo.find_by_address("holder", "mainnet").await.unwrap();
+        let app_ids: Vec<_> = holdings.iter().map(|h| h.app_id.as_str()).collect();
+        assert_eq!(app_ids, ["t/a/a", "t/b/b"]);
+        for holding in &holdings {
+            let rows: Vec<_> = flat.iter().filter(|c| c.app_id == holding.app_id).collect();
+            assert_eq!(holding.amount, rows.iter().map(|c| c.amount).sum::<i64>());
+            assert_eq!(holding.utxo_count, rows.len() as i64);
+            assert_eq!(
+                holding.first_block,
+                rows.iter().filter_map(|c| c.block_height).min()
+            );
+            assert_eq!(
+                holding.last_block,
+                rows.iter().filter_map(|c| c.block_height).max()
+            );
+        }
+        assert_eq!(holdings[0].name.as_deref(), Some("Alpha"));
+        assert_eq!(holdings[0].image_url.as_deref(), Some("https://img/a"));
+        // The only `t/b/b` asset row is on testnet4.
+        assert_eq!(holdings[1].symbol, None);
+
+        let filter = CharmFilter {
+            min_amount: Some(10),
+            ..CharmFilter::default()
+        };
+        let holdings = repo
+            .holdings_by_address("holder", "mainnet", &filter)
+            .await
+            .unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!((holdings[0].amount, holdings[0].utxo_count), (175, 2));
+
+        schema.drop_schema().await;
+    }
+}
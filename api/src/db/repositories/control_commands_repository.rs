@@ -0,0 +1,72 @@
+// Control commands database operations implementation
+// Writes operator pause/resume commands that the indexer polls each loop,
+// and reads the configuration snapshots indexer replicas record at startup.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde_json::{json, Value};
+
+use crate::db::DbError;
+
+/// Repository for the `control_commands` table (API side).
+#[derive(Clone)]
+pub struct ControlCommandsRepository {
+    conn: DatabaseConnection,
+}
+
+impl ControlCommandsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Record `command` ("pause" or "resume") for `network`, unless it is
+    /// already the network's current command. Returns whether a row was
+    /// written, so repeated calls are no-ops.
+    pub async fn issue(
+        &self,
+        network: &str,
+        command: &str,
+        requested_by: &str,
+    ) -> Result<bool, DbError> {
+        let res = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO control_commands (network, command, requested_by) \
+                 SELECT $1, $2, $3 \
+                  WHERE $2 <> COALESCE( \
+                        (SELECT command FROM control_commands \
+                          WHERE network = $1 ORDER BY id DESC LIMIT 1), \
+                        'resume')",
+                [network.into(), command.into(), requested_by.into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    /// The redacted `config_snapshots` of every indexer replica, newest
+    /// first.
+    pub async fn config_snapshots(&self) -> Result<Vec<Value>, DbError> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_string(
+                DbBackend::Postgres,
+                "SELECT instance_id, snapshot, recorded_at FROM config_snapshots \
+                  ORDER BY recorded_at DESC",
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        rows.iter()
+            .map(|r| {
+                let recorded_at: chrono::DateTime<chrono::Utc> = r.try_get("", "recorded_at")?;
+                Ok(json!({
+                    "instance_id": r.try_get::<String>("", "instance_id")?,
+                    "recorded_at": recorded_at.to_rfc3339(),
+                    "snapshot": r.try_get::<Value>("", "snapshot")?,
+                }))
+            })
+            .collect::<Result<_, sea_orm::DbErr>>()
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+}
@@ -1,10 +1,41 @@
 // [RJJ-DEX] Repository for DEX orders queries
 
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, sea_query::{Expr, Order}};
+use chrono::NaiveDateTime;
+use sea_orm::{
+    sea_query::{Expr, Order},
+    ColumnTrait, Condition, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Statement,
+};
 
 use crate::db::DbError;
 use crate::entity::dex_orders;
 
+/// Live orders of one side of a book at one price.
+#[derive(Debug, Clone, PartialEq, Eq, FromQueryResult)]
+pub struct BookLevel {
+    pub side: String,
+    pub price_num: i64,
+    pub price_den: i64,
+    pub orders: i64,
+    /// Unfilled token base units.
+    pub quantity: i64,
+    /// Unfilled satoshis.
+    pub amount: i64,
+}
+
+/// Live orders are open, or the remainder of a partial fill, and not past
+/// their expiry time. Orders without a usable price are left out.
+const BOOK_LEVELS_SQL: &str = "
+SELECT side, price_num, price_den, COUNT(*) AS orders,
+       SUM(GREATEST(quantity - filled_quantity, 0))::BIGINT AS quantity,
+       SUM(GREATEST(amount - filled_amount, 0))::BIGINT AS amount
+  FROM dex_orders
+ WHERE asset_app_id = $1 AND network = $2
+   AND status IN ('open', 'partial')
+   AND (expires_at_time IS NULL OR expires_at_time >= NOW() AT TIME ZONE 'UTC')
+   AND price_num > 0 AND price_den > 0
+ GROUP BY side, price_num, price_den";
+
 #[derive(Clone, Debug)]
 pub struct DexOrdersRepository {
     conn: DatabaseConnection,
@@ -15,15 +46,24 @@ impl DexOrdersRepository {
         Self { conn }
     }
 
-    /// Get all open orders (status = 'open'), optionally filtered by asset, side, network
+    /// Get all open orders (status = 'open'), optionally filtered by asset, side, network.
+    /// Orders past their expiry time are left out even before the indexer's
+    /// next block marks them `expired`. Returns one page and the total count.
     pub async fn find_open_orders(
         &self,
         asset_app_id: Option<&str>,
         side: Option<&str>,
         network: Option<&str>,
-    ) -> Result<Vec<dex_orders::Model>, DbError> {
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<dex_orders::Model>, u64), DbError> {
         let mut query = dex_orders::Entity::find()
-            .filter(dex_orders::Column::Status.eq("open"));
+            .filter(dex_orders::Column::Status.eq("open"))
+            .filter(
+                Condition::any()
+                    .add(dex_orders::Column::ExpiresAtTime.is_null())
+                    .add(dex_orders::Column::ExpiresAtTime.gte(chrono::Utc::now().naive_utc())),
+            );
 
         if let Some(asset) = asset_app_id {
             query = query.filter(dex_orders::Column::AssetAppId.eq(asset));
@@ -35,11 +75,14 @@ impl DexOrdersRepository {
             query = query.filter(dex_orders::Column::Network.eq(n));
         }
 
+        let total = query.clone().count(&self.conn).await?;
         let results = query
             .order_by_desc(dex_orders::Column::CreatedAt)
+            .limit(limit)
+            .offset(offset)
             .all(&self.conn)
             .await?;
-        Ok(results)
+        Ok((results, total))
     }
 
     /// Get order by ID
@@ -50,27 +93,68 @@ impl DexOrdersRepository {
         Ok(result)
     }
 
-    /// Find all orders by asset (any status), network-scoped.
-    pub async fn find_by_asset(
+    /// Order or activity row written by a tx, lowest vout first. Network-scoped.
+    pub async fn find_by_txid(
         &self,
-        asset_app_id: &str,
+        txid: &str,
+        network: &str,
+    ) -> Result<Option<dex_orders::Model>, DbError> {
+        let result = dex_orders::Entity::find()
+            .filter(dex_orders::Column::Txid.eq(txid))
+            .filter(dex_orders::Column::Network.eq(network))
+            .order_by_asc(dex_orders::Column::Vout)
+            .one(&self.conn)
+            .await?;
+        Ok(result)
+    }
+
+    /// Orders and activity rows written by a block's txs, network-scoped.
+    pub async fn find_by_block(
+        &self,
+        height: i32,
         network: &str,
     ) -> Result<Vec<dex_orders::Model>, DbError> {
         let results = dex_orders::Entity::find()
-            .filter(dex_orders::Column::AssetAppId.eq(asset_app_id))
+            .filter(dex_orders::Column::BlockHeight.eq(height))
             .filter(dex_orders::Column::Network.eq(network))
-            .order_by_desc(dex_orders::Column::CreatedAt)
+            .order_by_asc(dex_orders::Column::Txid)
+            .order_by_asc(dex_orders::Column::Vout)
             .all(&self.conn)
             .await?;
         Ok(results)
     }
 
-    /// Get all orders (any status), optionally filtered by network and/or status
+    /// Find all orders by asset (any status), network-scoped. Returns one
+    /// page and the total count.
+    pub async fn find_by_asset(
+        &self,
+        asset_app_id: &str,
+        network: &str,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<dex_orders::Model>, u64), DbError> {
+        let query = dex_orders::Entity::find()
+            .filter(dex_orders::Column::AssetAppId.eq(asset_app_id))
+            .filter(dex_orders::Column::Network.eq(network));
+        let total = query.clone().count(&self.conn).await?;
+        let results = query
+            .order_by_desc(dex_orders::Column::CreatedAt)
+            .limit(limit)
+            .offset(offset)
+            .all(&self.conn)
+            .await?;
+        Ok((results, total))
+    }
+
+    /// Get all orders (any status), optionally filtered by network and/or
+    /// status. Returns one page and the total count.
     pub async fn find_all_orders(
         &self,
         network: Option<&str>,
         status: Option<&str>,
-    ) -> Result<Vec<dex_orders::Model>, DbError> {
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<dex_orders::Model>, u64), DbError> {
         let mut query = dex_orders::Entity::find();
 
         if let Some(n) = network {
@@ -80,20 +164,57 @@ impl DexOrdersRepository {
             query = query.filter(dex_orders::Column::Status.eq(s));
         }
 
+        let total = query.clone().count(&self.conn).await?;
         let results = query
             .order_by(Expr::col(dex_orders::Column::BlockHeight).is_null(), Order::Desc)
             .order_by_desc(dex_orders::Column::BlockHeight)
+            .limit(limit)
+            .offset(offset)
             .all(&self.conn)
             .await?;
-        Ok(results)
+        Ok((results, total))
     }
 
-    /// Find orders by maker address
+    /// The live book of one asset, aggregated per side and price.
+    pub async fn find_book_levels(
+        &self,
+        asset_app_id: &str,
+        network: &str,
+    ) -> Result<Vec<BookLevel>, DbError> {
+        Ok(BookLevel::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            BOOK_LEVELS_SQL,
+            [asset_app_id.into(), network.into()],
+        ))
+        .all(&self.conn)
+        .await?)
+    }
+
+    /// When any order of the asset, live or not, last changed.
+    pub async fn last_activity(
+        &self,
+        asset_app_id: &str,
+        network: &str,
+    ) -> Result<Option<NaiveDateTime>, DbError> {
+        let latest: Option<Option<NaiveDateTime>> = dex_orders::Entity::find()
+            .select_only()
+            .column_as(dex_orders::Column::UpdatedAt.max(), "latest")
+            .filter(dex_orders::Column::AssetAppId.eq(asset_app_id))
+            .filter(dex_orders::Column::Network.eq(network))
+            .into_tuple()
+            .one(&self.conn)
+            .await?;
+        Ok(latest.flatten())
+    }
+
+    /// Find orders by maker address. Returns one page and the total count.
     pub async fn find_by_maker(
         &self,
         maker: &str,
         status: Option<&str>,
-    ) -> Result<Vec<dex_orders::Model>, DbError> {
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<dex_orders::Model>, u64), DbError> {
         let mut query = dex_orders::Entity::find()
             .filter(dex_orders::Column::Maker.eq(maker));
 
@@ -101,10 +222,226 @@ impl DexOrdersRepository {
             query = query.filter(dex_orders::Column::Status.eq(s));
         }
 
+        let total = query.clone().count(&self.conn).await?;
         let results = query
+            .order_by_desc(dex_orders::Column::CreatedAt)
+            .limit(limit)
+            .offset(offset)
+            .all(&self.conn)
+            .await?;
+        Ok((results, total))
+    }
+
+    /// Orders of `maker` still on the book (`open` or `partial`, or just
+    /// `status`), mempool ones included, newest first. Orders past their
+    /// expiry time stay in: their tokens or sats are locked until the
+    /// maker cancels.
+    pub async fn find_live_by_maker(
+        &self,
+        maker: &str,
+        network: &str,
+        status: Option<&str>,
+    ) -> Result<Vec<dex_orders::Model>, DbError> {
+        let statuses = match status {
+            Some(s) => vec![s],
+            None => vec!["open", "partial"],
+        };
+        let results = dex_orders::Entity::find()
+            .filter(dex_orders::Column::Network.eq(network))
+            .filter(dex_orders::Column::Maker.eq(maker))
+            .filter(dex_orders::Column::Status.is_in(statuses))
+            .order_by(
+                Expr::col(dex_orders::Column::BlockHeight).is_null(),
+                Order::Desc,
+            )
+            .order_by_desc(dex_orders::Column::BlockHeight)
             .order_by_desc(dex_orders::Column::CreatedAt)
             .all(&self.conn)
             .await?;
         Ok(results)
     }
+
+    /// Rows written against `parent_order_ids`: the remainder of a partial
+    /// fill and the activity rows of fulfills and cancels. Oldest first,
+    /// mempool ones last.
+    pub async fn find_children(
+        &self,
+        parent_order_ids: &[String],
+    ) -> Result<Vec<dex_orders::Model>, DbError> {
+        if parent_order_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let results = dex_orders::Entity::find()
+            .filter(dex_orders::Column::ParentOrderId.is_in(parent_order_ids.iter().cloned()))
+            .order_by(
+                Expr::col(dex_orders::Column::BlockHeight).is_null(),
+                Order::Asc,
+            )
+            .order_by_asc(dex_orders::Column::BlockHeight)
+            .order_by_asc(dex_orders::Column::CreatedAt)
+            .all(&self.conn)
+            .await?;
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use sea_orm::ConnectionTrait;
+
+    /// Orders past their expiry time are off the book before the indexer
+    /// sweeps them; swept ones are listed under `status=expired`.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn open_orders_leave_out_expired_ones() {
+        let schema = TestSchema::new("dex_expiry", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(
+            "INSERT INTO dex_orders (order_id, txid, vout, status, expires_at_height, expires_at_time, \
+                                     platform, maker, side, exec_type, price_num, price_den, \
+                                     amount, quantity, asset_app_id, blockchain, network) VALUES \
+                 ('live:0', 'live', 0, 'open', NULL, NULL, \
+                  'charms-cast', 'bc1q', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin', 'mainnet'), \
+                 ('later:0', 'later', 0, 'open', 900000, NOW() + INTERVAL '1 day', \
+                  'charms-cast', 'bc1q', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin', 'mainnet'), \
+                 ('lapsed:0', 'lapsed', 0, 'open', NULL, NOW() - INTERVAL '1 minute', \
+                  'charms-cast', 'bc1q', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin', 'mainnet'), \
+                 ('swept:0', 'swept', 0, 'expired', 100, NULL, \
+                  'charms-cast', 'bc1q', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin', 'mainnet');",
+        )
+        .await
+        .expect("fixture");
+
+        let repo = DexOrdersRepository::new(conn.clone());
+        let (open, total) = repo
+            .find_open_orders(None, None, Some("mainnet"), 100, 0)
+            .await
+            .unwrap();
+        let mut open: Vec<String> = open.into_iter().map(|o| o.order_id).collect();
+        open.sort();
+        assert_eq!(open, ["later:0", "live:0"]);
+        assert_eq!(total, 2);
+        let (page, total) = repo
+            .find_open_orders(None, None, Some("mainnet"), 1, 1)
+            .await
+            .unwrap();
+        assert_eq!((page.len(), total), (1, 2));
+        let (expired, _) = repo
+            .find_all_orders(Some("mainnet"), Some("expired"), 100, 0)
+            .await
+            .unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].expires_at_height, Some(100));
+
+        schema.drop_schema().await;
+    }
+
+    /// Levels sum what is left of each live order at one price; filled,
+    /// lapsed and other assets' orders stay off the book.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn book_levels_aggregate_live_orders() {
+        let schema = TestSchema::new("dex_book", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(
+            "INSERT INTO dex_orders (order_id, txid, vout, side, price_num, price_den, amount, \
+                                     quantity, filled_amount, filled_quantity, status, \
+                                     asset_app_id, expires_at_time, updated_at, platform, \
+                                     maker, exec_type, blockchain, network) VALUES \
+                 ('a1:0', 'a1', 0, 'ask', 1, 2, 500, 1000, 0, 0, 'open', 't/a/a', NULL, '2026-03-01', \
+                  'charms-cast', 'bc1q', 'partial', 'bitcoin', 'mainnet'), \
+                 ('a2:0', 'a2', 0, 'ask', 1, 2, 500, 1000, 250, 500, 'partial', 't/a/a', NULL, '2026-03-02', \
+                  'charms-cast', 'bc1q', 'partial', 'bitcoin', 'mainnet'), \
+                 ('b1:0', 'b1', 0, 'bid', 2, 5, 400, 1000, 0, 0, 'open', 't/a/a', NULL, '2026-03-01', \
+                  'charms-cast', 'bc1q', 'partial', 'bitcoin', 'mainnet'), \
+                 ('f1:0', 'f1', 0, 'bid', 2, 5, 400, 1000, 400, 1000, 'filled', 't/a/a', NULL, '2026-03-05', \
+                  'charms-cast', 'bc1q', 'partial', 'bitcoin', 'mainnet'), \
+                 ('x1:0', 'x1', 0, 'bid', 1, 1, 10, 10, 0, 0, 'open', 't/a/a', NOW() - INTERVAL '1 hour', '2026-03-01', \
+                  'charms-cast', 'bc1q', 'partial', 'bitcoin', 'mainnet'), \
+                 ('o1:0', 'o1', 0, 'ask', 1, 1, 10, 10, 0, 0, 'open', 't/b/b', NULL, '2026-04-01', \
+                  'charms-cast', 'bc1q', 'partial', 'bitcoin', 'mainnet');",
+        )
+        .await
+        .expect("fixture");
+
+        let repo = DexOrdersRepository::new(conn.clone());
+        let mut levels = repo.find_book_levels("t/a/a", "mainnet").await.unwrap();
+        levels.sort_by(|a, b| a.side.cmp(&b.side));
+        let level = |side: &str, num, den, orders, quantity, amount| BookLevel {
+            side: side.to_string(),
+            price_num: num,
+            price_den: den,
+            orders,
+            quantity,
+            amount,
+        };
+        assert_eq!(
+            levels,
+            [level("ask", 1, 2, 2, 1500, 750), level("bid", 2, 5, 1, 1000, 400)]
+        );
+        assert!(repo.find_book_levels("t/a/a", "testnet4").await.unwrap().is_empty());
+
+        let last = repo.last_activity("t/a/a", "mainnet").await.unwrap();
+        assert_eq!(last.map(|t| t.to_string()).as_deref(), Some("2026-03-05 00:00:00"));
+        assert_eq!(repo.last_activity("t/z/z", "mainnet").await.unwrap(), None);
+
+        schema.drop_schema().await;
+    }
+
+    /// A maker's live orders leave out filled ones, other makers and other
+    /// networks; children come back confirmed first, mempool ones last.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn live_orders_of_a_maker_and_their_children() {
+        let schema = TestSchema::new("dex_maker", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(
+            "INSERT INTO dex_orders (order_id, txid, vout, block_height, maker, status, \
+                                     parent_order_id, network, created_at, platform, side, \
+                                     exec_type, price_num, price_den, amount, quantity, \
+                                     asset_app_id, blockchain) VALUES \
+                 ('open1:0', 'open1', 0, 100, 'bc1qm', 'open', NULL, 'mainnet', '2026-03-01', \
+                  'charms-cast', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin'), \
+                 ('mem1:0', 'mem1', 0, NULL, 'bc1qm', 'open', NULL, 'mainnet', '2026-03-04', \
+                  'charms-cast', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin'), \
+                 ('part1:0', 'part1', 0, 101, 'bc1qm', 'partial', 'open1:0', 'mainnet', '2026-03-02', \
+                  'charms-cast', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin'), \
+                 ('pf:0', 'pf', 0, NULL, 'bc1qm', 'filled', 'part1:0', 'mainnet', '2026-03-05', \
+                  'charms-cast', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin'), \
+                 ('done1:0', 'done1', 0, 99, 'bc1qm', 'filled', NULL, 'mainnet', '2026-02-01', \
+                  'charms-cast', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin'), \
+                 ('other:0', 'other', 0, 100, 'bc1qo', 'open', NULL, 'mainnet', '2026-03-01', \
+                  'charms-cast', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin'), \
+                 ('test:0', 'test', 0, 100, 'bc1qm', 'open', NULL, 'testnet4', '2026-03-01', \
+                  'charms-cast', 'ask', 'partial', 1, 1, 0, 0, 't/a/a', 'bitcoin');",
+        )
+        .await
+        .expect("fixture");
+
+        let repo = DexOrdersRepository::new(conn.clone());
+        let ids = |orders: Vec<dex_orders::Model>| -> Vec<String> {
+            orders.into_iter().map(|o| o.order_id).collect()
+        };
+        let live = repo
+            .find_live_by_maker("bc1qm", "mainnet", None)
+            .await
+            .unwrap();
+        assert_eq!(ids(live), ["mem1:0", "part1:0", "open1:0"]);
+        let partial = repo
+            .find_live_by_maker("bc1qm", "mainnet", Some("partial"))
+            .await
+            .unwrap();
+        assert_eq!(ids(partial), ["part1:0"]);
+
+        let children = repo
+            .find_children(&["open1:0".to_string(), "part1:0".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(ids(children), ["part1:0", "pf:0"]);
+        assert!(repo.find_children(&[]).await.unwrap().is_empty());
+
+        schema.drop_schema().await;
+    }
 }
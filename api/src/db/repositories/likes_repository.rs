@@ -1,8 +1,28 @@
-use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, Statement, TransactionTrait,
+};
 use std::collections::{HashMap, HashSet};
 
 use crate::entity::{likes, prelude::Likes};
 
+/// Outcome of adding or removing a like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LikeChange {
+    /// False when the user had already liked (or not liked) the charm.
+    pub changed: bool,
+    /// Likes on the charm once the change is applied.
+    pub likes_count: i64,
+}
+
+/// Insert the like unless (charm_id, user_id) already has one.
+const ADD_LIKE_SQL: &str = r#"
+    INSERT INTO likes (charm_id, user_id) VALUES ($1, $2)
+    ON CONFLICT (charm_id, user_id) DO NOTHING"#;
+
+const REMOVE_LIKE_SQL: &str = "DELETE FROM likes WHERE charm_id = $1 AND user_id = $2";
+
 /// Repository for managing likes in the database
 pub struct LikesRepository {
     db: DatabaseConnection,
@@ -14,55 +34,40 @@ impl LikesRepository {
         Self { db }
     }
 
-    /// Adds a like for a charm by a user
-    pub async fn add_like(&self, charm_id: &str, user_id: i32) -> Result<i64, DbErr> {
-        // Check if the like already exists
-        let existing_like = Likes::find()
-            .filter(likes::Column::CharmId.eq(charm_id))
-            .filter(likes::Column::UserId.eq(user_id))
-            .one(&self.db)
-            .await?;
-
-        // If the like already exists, return the count
-        if existing_like.is_some() {
-            return self.get_likes_count(charm_id).await;
-        }
-
-        // Create a new like
-        let like = likes::ActiveModel {
-            charm_id: sea_orm::ActiveValue::Set(charm_id.to_string()),
-            user_id: sea_orm::ActiveValue::Set(user_id),
-            ..Default::default()
-        };
-
-        // Insert the like
-        Likes::insert(like).exec(&self.db).await?;
+    /// Adds a like for a charm by a user. Repeats change nothing.
+    pub async fn add_like(&self, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr> {
+        self.change(ADD_LIKE_SQL, charm_id, user_id).await
+    }
 
-        // Return the updated count
-        self.get_likes_count(charm_id).await
+    /// Removes a like for a charm by a user. Removing a missing like
+    /// changes nothing.
+    pub async fn remove_like(&self, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr> {
+        self.change(REMOVE_LIKE_SQL, charm_id, user_id).await
     }
 
-    /// Removes a like for a charm by a user
-    pub async fn remove_like(&self, charm_id: &str, user_id: i32) -> Result<i64, DbErr> {
-        // Delete the like
-        Likes::delete_many()
-            .filter(likes::Column::CharmId.eq(charm_id))
-            .filter(likes::Column::UserId.eq(user_id))
-            .exec(&self.db)
+    /// Runs `sql` and the count in one transaction. The count is a statement
+    /// of its own, so it sees our change and any concurrent like this one
+    /// waited on, which is committed by then.
+    async fn change(&self, sql: &str, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr> {
+        let txn = self.db.begin().await?;
+        let result = txn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [charm_id.into(), user_id.into()],
+            ))
             .await?;
-
-        // Return the updated count
-        self.get_likes_count(charm_id).await
+        let likes_count = count_likes(&txn, charm_id).await?;
+        txn.commit().await?;
+        Ok(LikeChange {
+            changed: result.rows_affected() > 0,
+            likes_count,
+        })
     }
 
     /// Counts the number of likes for a charm
     pub async fn get_likes_count(&self, charm_id: &str) -> Result<i64, DbErr> {
-        let count = Likes::find()
-            .filter(likes::Column::CharmId.eq(charm_id))
-            .count(&self.db)
-            .await?;
-
-        Ok(count as i64)
+        count_likes(&self.db, charm_id).await
     }
 
     /// Checks if a user has liked a charm
@@ -100,6 +105,17 @@ impl LikesRepository {
         Ok(counts)
     }
 
+    /// Likes on an app_id given at or after `since`
+    pub async fn count_since(&self, app_id: &str, since: DateTime<Utc>) -> Result<i64, DbErr> {
+        let count = Likes::find()
+            .filter(likes::Column::CharmId.eq(app_id))
+            .filter(likes::Column::CreatedAt.gte(since))
+            .count(&self.db)
+            .await?;
+
+        Ok(count as i64)
+    }
+
     /// Batch check if user liked multiple charms (single query)
     pub async fn get_user_likes_batch(
         &self,
@@ -123,3 +139,56 @@ impl LikesRepository {
         Ok(liked_set)
     }
 }
+
+/// Likes on `charm_id`, as seen by `conn`.
+async fn count_likes<C: ConnectionTrait>(conn: &C, charm_id: &str) -> Result<i64, DbErr> {
+    let count = Likes::find()
+        .filter(likes::Column::CharmId.eq(charm_id))
+        .count(conn)
+        .await?;
+
+    Ok(count as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use std::sync::Arc;
+
+    /// Double-clicks racing each other leave one row, and every answer
+    /// agrees on the count. Needs a scratch database; tables live in a
+    /// throwaway schema.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn concurrent_likes_from_one_user_count_once() {
+        let schema = TestSchema::new("likes", 10).await;
+        let repo = Arc::new(LikesRepository::new(schema.conn.clone()));
+        let tasks: Vec<_> = (0..10)
+            .map(|_| {
+                let repo = repo.clone();
+                tokio::spawn(async move { repo.add_like("t/a/a", 7).await.unwrap() })
+            })
+            .collect();
+        let mut changes = Vec::new();
+        for task in tasks {
+            changes.push(task.await.unwrap());
+        }
+        assert_eq!(changes.iter().filter(|c| c.changed).count(), 1);
+        assert!(changes.iter().all(|c| c.likes_count == 1), "{:?}", changes);
+        assert_eq!(repo.get_likes_count("t/a/a").await.unwrap(), 1);
+
+        let outcome = |c: LikeChange| (c.changed, c.likes_count);
+        assert_eq!(outcome(repo.add_like("t/a/a", 8).await.unwrap()), (true, 2));
+        assert_eq!(
+            outcome(repo.remove_like("t/a/a", 7).await.unwrap()),
+            (true, 1)
+        );
+        assert_eq!(
+            outcome(repo.remove_like("t/a/a", 7).await.unwrap()),
+            (false, 1)
+        );
+
+        schema.drop_schema().await;
+    }
+}
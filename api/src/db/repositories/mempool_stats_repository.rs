@@ -0,0 +1,217 @@
+// Mempool statistics repository — unconfirmed charm counts and the time
+// charms spent in mempool (`charms.confirmation_delay_secs`, stamped by the
+// indexer when a block confirms a charm it first saw unconfirmed).
+
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+/// Lower edges of the delay histogram buckets, in seconds. The last bucket
+/// is open-ended.
+pub const DELAY_BUCKETS_SECS: [i32; 9] = [0, 60, 300, 600, 1800, 3600, 7200, 21600, 86400];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DelayBucket {
+    pub min_secs: i32,
+    /// Exclusive; `None` for the last bucket.
+    pub max_secs: Option<i32>,
+    pub count: i64,
+}
+
+/// Confirmation delays of charms confirmed within a window. Charms never
+/// seen in mempool have no delay and are not counted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DelayStats {
+    pub samples: i64,
+    pub median_secs: Option<f64>,
+    pub p90_secs: Option<f64>,
+    pub max_secs: Option<i32>,
+    pub histogram: Vec<DelayBucket>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Count {
+    count: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Percentiles {
+    samples: i64,
+    median_secs: Option<f64>,
+    p90_secs: Option<f64>,
+    max_secs: Option<i32>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct BucketCount {
+    bucket: i32,
+    count: i64,
+}
+
+/// Charms of `$1` with a delay, confirmed (first sighting + delay) within
+/// the last `$2` hours.
+const WINDOWED_DELAYS: &str = "SELECT confirmation_delay_secs AS s FROM charms
+  WHERE network = $1 AND confirmation_delay_secs IS NOT NULL
+    AND mempool_detected_at IS NOT NULL
+    AND mempool_detected_at + make_interval(secs => confirmation_delay_secs)
+        >= NOW() - make_interval(hours => $2)";
+
+#[derive(Clone)]
+pub struct MempoolStatsRepository {
+    conn: DatabaseConnection,
+}
+
+impl MempoolStatsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Charms currently waiting in mempool.
+    pub async fn unconfirmed_count(&self, network: &str) -> Result<i64, DbError> {
+        Ok(Count::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS count FROM charms WHERE network = $1 AND block_height IS NULL",
+            [network.into()],
+        ))
+        .one(&self.conn)
+        .await?
+        .map_or(0, |c| c.count))
+    }
+
+    /// Median, p90 (interpolated, to 0.1s) and histogram of confirmation
+    /// delays over the last `window_hours`.
+    pub async fn confirmation_delays(
+        &self,
+        network: &str,
+        window_hours: i32,
+    ) -> Result<DelayStats, DbError> {
+        let percentiles = Percentiles::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                "SELECT COUNT(*) AS samples,
+                        round(percentile_cont(0.5) WITHIN GROUP (ORDER BY s)::numeric, 1)::float8
+                            AS median_secs,
+                        round(percentile_cont(0.9) WITHIN GROUP (ORDER BY s)::numeric, 1)::float8
+                            AS p90_secs,
+                        MAX(s) AS max_secs
+                   FROM ({}) d",
+                WINDOWED_DELAYS
+            ),
+            [network.into(), window_hours.into()],
+        ))
+        .one(&self.conn)
+        .await?;
+
+        let edges = DELAY_BUCKETS_SECS
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let counts = BucketCount::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                "SELECT width_bucket(s, ARRAY[{}]) AS bucket, COUNT(*) AS count
+                   FROM ({}) d GROUP BY 1",
+                edges, WINDOWED_DELAYS
+            ),
+            [network.into(), window_hours.into()],
+        ))
+        .all(&self.conn)
+        .await?;
+
+        // width_bucket numbers the buckets from 1 (delays are never negative).
+        let histogram = DELAY_BUCKETS_SECS
+            .iter()
+            .enumerate()
+            .map(|(i, &min_secs)| DelayBucket {
+                min_secs,
+                max_secs: DELAY_BUCKETS_SECS.get(i + 1).copied(),
+                count: counts
+                    .iter()
+                    .find(|c| c.bucket == i as i32 + 1)
+                    .map_or(0, |c| c.count),
+            })
+            .collect();
+
+        let p = percentiles.unwrap_or(Percentiles {
+            samples: 0,
+            median_secs: None,
+            p90_secs: None,
+            max_secs: None,
+        });
+        Ok(DelayStats {
+            samples: p.samples,
+            median_secs: p.median_secs,
+            p90_secs: p.p90_secs,
+            max_secs: p.max_secs,
+            histogram,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use sea_orm::ConnectionTrait;
+
+    const SEED: &str = "
+        -- Ten delays within the window: 30, 45, 120, 200, 400, 700, 900, 2000, 5000, 90000.
+        INSERT INTO charms
+            (txid, vout, block_height, mempool_detected_at, confirmation_delay_secs,
+             app_id, asset_type, blockchain, network)
+        SELECT 'w' || n, 0, 100, NOW() - make_interval(secs => d) - INTERVAL '1 hour', d,
+               't/x/x', 'token', 'bitcoin', 'mainnet'
+          FROM unnest(ARRAY[30, 45, 120, 200, 400, 700, 900, 2000, 5000, 90000])
+               WITH ORDINALITY AS t(d, n);
+        -- Left out: confirmed two days ago, never seen in mempool, other network.
+        INSERT INTO charms
+            (txid, vout, block_height, network, mempool_detected_at, confirmation_delay_secs,
+             app_id, asset_type, blockchain)
+        VALUES
+            ('old', 0, 90, 'mainnet', NOW() - INTERVAL '2 days', 10, 't/x/x', 'token', 'bitcoin'),
+            ('direct', 0, 100, 'mainnet', NULL, NULL, 't/x/x', 'token', 'bitcoin'),
+            ('t4', 0, 100, 'testnet4', NOW() - INTERVAL '1 hour', 99999, 't/x/x', 'token', 'bitcoin');
+        -- Still in mempool.
+        INSERT INTO charms
+            (txid, vout, block_height, network, mempool_detected_at, app_id, asset_type, blockchain)
+        VALUES
+            ('m1', 0, NULL, 'mainnet', NOW(), 't/x/x', 'token', 'bitcoin'),
+            ('m2', 0, NULL, 'mainnet', NOW(), 't/x/x', 'token', 'bitcoin'),
+            ('m3', 0, NULL, 'testnet4', NOW(), 't/x/x', 'token', 'bitcoin');
+    ";
+
+    /// Percentiles and buckets over a known set of delays. Needs a scratch
+    /// database; tables live in a throwaway schema.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn delay_percentiles_and_histogram() {
+        let schema = TestSchema::new("mempool_stats", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(SEED).await.expect("fixture");
+
+        let repo = MempoolStatsRepository::new(conn.clone());
+        assert_eq!(repo.unconfirmed_count("mainnet").await.unwrap(), 2);
+        assert_eq!(repo.unconfirmed_count("testnet4").await.unwrap(), 1);
+
+        let stats = repo.confirmation_delays("mainnet", 24).await.unwrap();
+        assert_eq!(stats.samples, 10);
+        // Linear interpolation: median between 400 and 700, p90 at
+        // 5000 + 0.1 * (90000 - 5000).
+        assert_eq!(stats.median_secs, Some(550.0));
+        assert_eq!(stats.p90_secs, Some(13500.0));
+        assert_eq!(stats.max_secs, Some(90000));
+        let counts: Vec<i64> = stats.histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [2, 2, 1, 2, 1, 1, 0, 0, 1]);
+        assert_eq!(stats.histogram[0].max_secs, Some(60));
+        assert_eq!(stats.histogram[8].max_secs, None);
+
+        let empty = repo.confirmation_delays("signet", 24).await.unwrap();
+        assert_eq!(empty.samples, 0);
+        assert_eq!(empty.median_secs, None);
+        assert!(empty.histogram.iter().all(|b| b.count == 0));
+
+        schema.drop_schema().await;
+    }
+}
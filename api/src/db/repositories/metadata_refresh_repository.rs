@@ -0,0 +1,102 @@
+// Metadata refresh jobs repository — operators queue a refresh here; the
+// indexer runs it and writes the per-asset results.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+/// A refresh job and its progress. `app_ids` is null for "all assets".
+#[derive(Debug, Clone, Serialize, FromQueryResult)]
+pub struct MetadataRefreshJob {
+    pub id: i64,
+    pub network: Option<String>,
+    pub app_ids: Option<serde_json::Value>,
+    pub status: String,
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub assets_scanned: i32,
+    pub assets_changed: i32,
+    pub error: Option<String>,
+}
+
+/// The fields one asset had changed, as `{"field": {"old", "new"}}`.
+#[derive(Debug, Clone, Serialize, FromQueryResult)]
+pub struct MetadataRefreshResult {
+    pub asset_id: i32,
+    pub app_id: String,
+    pub network: String,
+    pub changes: serde_json::Value,
+}
+
+#[derive(Clone)]
+pub struct MetadataRefreshRepository {
+    conn: DatabaseConnection,
+}
+
+impl MetadataRefreshRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Queue a job for `app_ids` (every asset when `None`). Returns its id,
+    /// or `None` when another job is still pending or running.
+    pub async fn enqueue(
+        &self,
+        app_ids: Option<&[String]>,
+        network: Option<&str>,
+        requested_by: &str,
+    ) -> Result<Option<i64>, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO metadata_refresh_jobs (app_ids, network, requested_by)
+                 VALUES (CASE WHEN $1::jsonb IS NULL THEN NULL
+                              ELSE ARRAY(SELECT jsonb_array_elements_text($1::jsonb)) END,
+                         $2, $3)
+                 ON CONFLICT DO NOTHING
+                 RETURNING id",
+                [
+                    app_ids.map(|ids| serde_json::json!(ids)).into(),
+                    network.map(str::to_string).into(),
+                    requested_by.into(),
+                ],
+            ))
+            .await?;
+        Ok(row.map(|r| r.try_get("", "id")).transpose()?)
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<MetadataRefreshJob>, DbError> {
+        Ok(
+            MetadataRefreshJob::find_by_statement(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT id, network, to_jsonb(app_ids) AS app_ids, status, requested_by,
+                        requested_at, started_at, finished_at, assets_scanned,
+                        assets_changed, error
+                   FROM metadata_refresh_jobs WHERE id = $1",
+                [id.into()],
+            ))
+            .one(&self.conn)
+            .await?,
+        )
+    }
+
+    /// The assets job `id` changed, in asset order.
+    pub async fn results(&self, id: i64) -> Result<Vec<MetadataRefreshResult>, DbError> {
+        Ok(
+            MetadataRefreshResult::find_by_statement(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT asset_id, app_id, network, changes
+                   FROM metadata_refresh_results WHERE job_id = $1
+                  ORDER BY asset_id",
+                [id.into()],
+            ))
+            .all(&self.conn)
+            .await?,
+        )
+    }
+}
@@ -0,0 +1,90 @@
+// Mint events repository — net supply increases recorded by the indexer's
+// block pipeline, one row per (network, txid, app_id).
+
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+#[derive(Debug, Clone, Serialize, FromQueryResult)]
+pub struct MintEvent {
+    pub app_id: String,
+    pub txid: String,
+    pub block_height: i32,
+    pub minted_amount: i64,
+    pub minter_address: Option<String>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Count {
+    count: i64,
+}
+
+#[derive(Clone)]
+pub struct MintEventsRepository {
+    conn: DatabaseConnection,
+}
+
+impl MintEventsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Mints of one app_id, newest block first, with the total count.
+    pub async fn by_app_id(
+        &self,
+        app_id: &str,
+        network: &str,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<MintEvent>, u64), DbError> {
+        let events = MintEvent::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT app_id, txid, block_height, minted_amount, minter_address
+               FROM mint_events
+              WHERE app_id = $1 AND network = $2
+              ORDER BY block_height DESC, id DESC
+              LIMIT $3 OFFSET $4",
+            [
+                app_id.into(),
+                network.into(),
+                (limit as i64).into(),
+                (offset as i64).into(),
+            ],
+        ))
+        .all(&self.conn)
+        .await?;
+
+        let total = Count::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS count FROM mint_events WHERE app_id = $1 AND network = $2",
+            [app_id.into(), network.into()],
+        ))
+        .one(&self.conn)
+        .await?
+        .map_or(0, |c| c.count as u64);
+
+        Ok((events, total))
+    }
+
+    /// Mints at or above `since`, oldest block first, for polling feeds.
+    pub async fn since(
+        &self,
+        network: &str,
+        since: i64,
+        limit: u64,
+    ) -> Result<Vec<MintEvent>, DbError> {
+        MintEvent::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT app_id, txid, block_height, minted_amount, minter_address
+               FROM mint_events
+              WHERE network = $1 AND block_height >= $2
+              ORDER BY block_height ASC, id ASC
+              LIMIT $3",
+            [network.into(), since.into(), (limit as i64).into()],
+        ))
+        .all(&self.conn)
+        .await
+        .map_err(Into::into)
+    }
+}
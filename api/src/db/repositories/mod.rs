@@ -1,44 +1,92 @@
 // Database repository management
 
+pub mod address_summary_repository;
 pub mod address_transactions_repository;
+pub mod admin_audit_repository;
 pub mod asset_repository;
+pub mod backfills_repository;
+pub mod blocks_repository;
+pub mod changefeed_repository;
 pub mod charm_repository;
+pub mod control_commands_repository;
 pub mod dex_orders_repository; // [RJJ-DEX]
 pub mod likes_repository;
+pub mod mempool_stats_repository;
+pub mod metadata_refresh_repository;
+pub mod mint_events_repository;
+pub mod moderation_repository;
 pub mod monitored_addresses_repository;
+pub mod parser_stats_repository;
 pub mod stats_holders_repository; // [RJJ-STATS-HOLDERS]
+pub mod supply_changes_repository;
 pub mod transaction_repository; // [RJJ-SPELL]
 pub mod utxo_repository;
+pub mod wallet_changes_repository;
+pub mod wallet_history_repository;
+pub mod webhook_subscriptions_repository;
 
+pub use address_summary_repository::AddressSummaryRepository;
 pub use address_transactions_repository::AddressTransactionsRepository;
+pub use admin_audit_repository::AdminAuditRepository;
 pub use asset_repository::AssetRepository;
+pub use backfills_repository::BackfillsRepository;
+pub use blocks_repository::{BlocksRepository, TIMING_WINDOW};
+pub use changefeed_repository::ChangefeedRepository;
 pub use charm_repository::CharmRepository;
+pub use control_commands_repository::ControlCommandsRepository;
 pub use dex_orders_repository::DexOrdersRepository; // [RJJ-DEX]
 pub use likes_repository::LikesRepository;
+pub use mempool_stats_repository::MempoolStatsRepository;
+pub use metadata_refresh_repository::MetadataRefreshRepository;
+pub use mint_events_repository::MintEventsRepository;
+pub use moderation_repository::ModerationRepository;
 pub use monitored_addresses_repository::MonitoredAddressesRepository;
+pub use parser_stats_repository::ParserStatsRepository;
 pub use stats_holders_repository::StatsHoldersRepository;
+pub use supply_changes_repository::SupplyChangesRepository;
 pub use transaction_repository::TransactionRepository; // [RJJ-SPELL]
 pub use utxo_repository::UtxoRepository;
+pub use wallet_changes_repository::WalletChangesRepository;
+pub use wallet_history_repository::WalletHistoryRepository;
+pub use webhook_subscriptions_repository::WebhookSubscriptionsRepository;
 
 use sea_orm::DatabaseConnection;
 use std::sync::Arc;
 
+use crate::db::stores::{AssetStore, AuditStore, CharmStore, LikesStore, StatsHoldersStore};
+
 /// Container for all database repositories
 pub struct Repositories {
+    conn: DatabaseConnection,
+    pub address_summary: AddressSummaryRepository,
     pub address_transactions: AddressTransactionsRepository,
-    pub asset_repository: Arc<AssetRepository>,
-    pub charm: CharmRepository,
+    pub admin_audit: Arc<dyn AuditStore>,
+    pub asset_repository: Arc<dyn AssetStore>,
+    pub backfills: BackfillsRepository,
+    pub blocks: BlocksRepository,
+    pub changefeed: ChangefeedRepository,
+    pub charm: Arc<dyn CharmStore>,
+    pub control_commands: ControlCommandsRepository,
     pub dex_orders: DexOrdersRepository, // [RJJ-DEX]
-    pub likes: LikesRepository,
-    pub stats_holders: StatsHoldersRepository, // [RJJ-STATS-HOLDERS]
+    pub likes: Arc<dyn LikesStore>,
+    pub mempool_stats: MempoolStatsRepository,
+    pub metadata_refresh: MetadataRefreshRepository,
+    pub mint_events: MintEventsRepository,
+    pub moderation: ModerationRepository,
+    pub parser_stats: ParserStatsRepository,
+    pub stats_holders: Arc<dyn StatsHoldersStore>, // [RJJ-STATS-HOLDERS]
+    pub supply_changes: SupplyChangesRepository,
     pub transactions: TransactionRepository,   // [RJJ-SPELL]
     pub utxo: UtxoRepository,
     pub monitored_addresses: MonitoredAddressesRepository,
+    pub wallet_changes: WalletChangesRepository,
+    pub wallet_history: WalletHistoryRepository,
+    pub webhook_subscriptions: WebhookSubscriptionsRepository,
 }
 
 impl Repositories {
     /// Creates a new repositories container with database connection
-    pub fn new(conn: DatabaseConnection) -> Self {
+    pub fn new(conn: DatabaseConnection, monitor_ttl_days: i64) -> Self {
         let db_conn = conn.clone();
         let db_conn2 = conn.clone();
         let db_conn3 = conn.clone();
@@ -47,16 +95,53 @@ impl Repositories {
         let db_conn6 = conn.clone();
         let db_conn7 = conn.clone();
         let db_conn8 = conn.clone();
+        let db_conn9 = conn.clone();
+        let db_conn10 = conn.clone();
+        let db_conn11 = conn.clone();
+        let db_conn12 = conn.clone();
+        let db_conn13 = conn.clone();
+        let db_conn14 = conn.clone();
+        let db_conn15 = conn.clone();
+        let db_conn16 = conn.clone();
+        let db_conn17 = conn.clone();
+        let db_conn18 = conn.clone();
+        let db_conn19 = conn.clone();
+        let db_conn20 = conn.clone();
+        let db_conn21 = conn.clone();
+        let db_conn22 = conn.clone();
+        let db_conn23 = conn.clone();
+        let db_conn24 = conn.clone();
         Repositories {
+            conn: db_conn14,
+            address_summary: AddressSummaryRepository::new(db_conn21),
             address_transactions: AddressTransactionsRepository::new(db_conn8),
+            admin_audit: Arc::new(AdminAuditRepository::new(db_conn22)),
             asset_repository: Arc::new(AssetRepository::new(std::sync::Arc::new(conn))),
-            charm: CharmRepository::new(db_conn),
+            backfills: BackfillsRepository::new(db_conn23),
+            blocks: BlocksRepository::new(db_conn15),
+            changefeed: ChangefeedRepository::new(db_conn19),
+            charm: Arc::new(CharmRepository::new(db_conn)),
+            control_commands: ControlCommandsRepository::new(db_conn9),
             dex_orders: DexOrdersRepository::new(db_conn5), // [RJJ-DEX]
-            likes: LikesRepository::new(db_conn2),
-            stats_holders: StatsHoldersRepository::new(db_conn3), // [RJJ-STATS-HOLDERS]
+            likes: Arc::new(LikesRepository::new(db_conn2)),
+            mempool_stats: MempoolStatsRepository::new(db_conn13),
+            metadata_refresh: MetadataRefreshRepository::new(db_conn17),
+            mint_events: MintEventsRepository::new(db_conn11),
+            moderation: ModerationRepository::new(db_conn18),
+            parser_stats: ParserStatsRepository::new(db_conn20),
+            stats_holders: Arc::new(StatsHoldersRepository::new(db_conn3)), // [RJJ-STATS-HOLDERS]
+            supply_changes: SupplyChangesRepository::new(db_conn16),
             transactions: TransactionRepository::new(db_conn4),   // [RJJ-SPELL]
             utxo: UtxoRepository::new(db_conn6),
-            monitored_addresses: MonitoredAddressesRepository::new(db_conn7),
+            monitored_addresses: MonitoredAddressesRepository::new(db_conn7, monitor_ttl_days),
+            wallet_changes: WalletChangesRepository::new(db_conn24),
+            wallet_history: WalletHistoryRepository::new(db_conn10),
+            webhook_subscriptions: WebhookSubscriptionsRepository::new(db_conn12),
         }
     }
+
+    /// Connection for queries no store covers (counts, diagnostics, resets)
+    pub fn connection(&self) -> &DatabaseConnection {
+        &self.conn
+    }
 }
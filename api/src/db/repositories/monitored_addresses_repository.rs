@@ -3,22 +3,68 @@
 // since pg_try_advisory_lock has no ORM equivalent.
 
 use sea_orm::{
-    ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait,
-    QueryFilter, Statement,
+    sea_query::Expr, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    DbBackend, EntityTrait, QueryFilter, Statement,
 };
 
-use crate::entity::monitored_addresses;
+use crate::entity::{address_utxos, monitored_addresses};
 
 /// Repository for monitored_addresses table (API side).
 /// Handles checking, registering, and seeding addresses for on-demand monitoring.
 #[derive(Clone)]
 pub struct MonitoredAddressesRepository {
     conn: DatabaseConnection,
+    /// MONITOR_TTL_DAYS: each query pushes `expires_at` this far out
+    ttl_days: i64,
 }
 
 impl MonitoredAddressesRepository {
-    pub fn new(conn: DatabaseConnection) -> Self {
-        Self { conn }
+    pub fn new(conn: DatabaseConnection, ttl_days: i64) -> Self {
+        Self { conn, ttl_days }
+    }
+
+    fn expires_at(&self, from: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+        from + chrono::Duration::days(self.ttl_days)
+    }
+
+    /// Record a query for an address: refresh `last_queried_at` and push
+    /// `expires_at` out by the TTL. No-op if the address is not monitored.
+    pub async fn touch(&self, address: &str, network: &str) -> Result<(), String> {
+        let now = chrono::Utc::now();
+        monitored_addresses::Entity::update_many()
+            .col_expr(monitored_addresses::Column::LastQueriedAt, Expr::value(now))
+            .col_expr(
+                monitored_addresses::Column::ExpiresAt,
+                Expr::value(self.expires_at(now)),
+            )
+            .filter(monitored_addresses::Column::Address.eq(address))
+            .filter(monitored_addresses::Column::Network.eq(network))
+            .exec(&self.conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("DB update failed: {}", e))
+    }
+
+    /// Stop monitoring an address: delete its row and its tracked UTXOs.
+    /// Returns false if it was not monitored. A later balance call (or a
+    /// charm it receives) registers it again.
+    pub async fn unsubscribe(&self, address: &str, network: &str) -> Result<bool, String> {
+        let removed = monitored_addresses::Entity::delete_many()
+            .filter(monitored_addresses::Column::Address.eq(address))
+            .filter(monitored_addresses::Column::Network.eq(network))
+            .exec(&self.conn)
+            .await
+            .map_err(|e| format!("DB delete failed: {}", e))?;
+        if removed.rows_affected == 0 {
+            return Ok(false);
+        }
+        address_utxos::Entity::delete_many()
+            .filter(address_utxos::Column::Address.eq(address))
+            .filter(address_utxos::Column::Network.eq(network))
+            .exec(&self.conn)
+            .await
+            .map_err(|e| format!("DB delete failed: {}", e))?;
+        Ok(true)
     }
 
     /// Check if an address is already monitored.
@@ -35,7 +81,7 @@ impl MonitoredAddressesRepository {
 
     /// Register an address for monitoring with seed data.
     /// Sets seeded_at and seed_height to indicate the address has been initialized.
-    /// Uses on_conflict to upsert.
+    /// Uses on_conflict to upsert; a re-seed keeps `synced_from`.
     pub async fn register_seeded(
         &self,
         address: &str,
@@ -52,6 +98,9 @@ impl MonitoredAddressesRepository {
             seed_height: Set(Some(seed_height)),
             seed_block_hash: Set(seed_block_hash.map(|s| s.to_string())),
             created_at: Set(now),
+            last_queried_at: Set(Some(now)),
+            expires_at: Set(Some(self.expires_at(now))),
+            synced_from: Set((seed_height > 0).then_some(seed_height)),
         };
 
         let result = monitored_addresses::Entity::insert(model)
@@ -64,7 +113,13 @@ impl MonitoredAddressesRepository {
                     monitored_addresses::Column::SeededAt,
                     monitored_addresses::Column::SeedHeight,
                     monitored_addresses::Column::SeedBlockHash,
+                    monitored_addresses::Column::LastQueriedAt,
+                    monitored_addresses::Column::ExpiresAt,
                 ])
+                .value(
+                    monitored_addresses::Column::SyncedFrom,
+                    Expr::cust("COALESCE(monitored_addresses.synced_from, EXCLUDED.synced_from)"),
+                )
                 .to_owned(),
             )
             .exec(&self.conn)
@@ -1,8 +1,16 @@
 // [RJJ-STATS-HOLDERS] Repository for stats_holders table operations
 use crate::db::DbError;
 use crate::entity::stats_holders;
+use sea_orm::sea_query::Expr;
 use sea_orm::*;
 
+/// Number of addresses holding an app_id
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, FromQueryResult)]
+pub struct HolderCount {
+    pub app_id: String,
+    pub holders: i64,
+}
+
 pub struct StatsHoldersRepository {
     conn: DatabaseConnection,
 }
@@ -106,6 +114,29 @@ impl StatsHoldersRepository {
         Ok(())
     }
 
+    /// The `limit` app_ids of `network` with the most holders (addresses
+    /// with a positive balance), most held first.
+    pub async fn top_by_holders(
+        &self,
+        network: &str,
+        limit: u64,
+    ) -> Result<Vec<HolderCount>, DbError> {
+        stats_holders::Entity::find()
+            .select_only()
+            .column(stats_holders::Column::AppId)
+            .column_as(Expr::col(stats_holders::Column::Address).count(), "holders")
+            .filter(stats_holders::Column::Network.eq(network))
+            .filter(stats_holders::Column::TotalAmount.gt(0))
+            .group_by(stats_holders::Column::AppId)
+            .order_by_desc(Expr::cust("holders"))
+            .order_by_asc(stats_holders::Column::AppId)
+            .limit(limit)
+            .into_model::<HolderCount>()
+            .all(&self.conn)
+            .await
+            .map_err(Into::into)
+    }
+
     /// Get total holder count for an app_id, network-scoped.
     #[allow(dead_code)] // Reserved for future use
     pub async fn get_holder_count(&self, app_id: &str, network: &str) -> Result<u64, DbError> {
@@ -0,0 +1,116 @@
+// Supply changes repository — the audit trail the indexer writes alongside
+// every change to `assets.total_supply`, one row per change.
+
+use rust_decimal::Decimal;
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct SupplyChange {
+    pub delta: Decimal,
+    /// "mint", "spend", "reindex" or "manual"
+    pub reason: String,
+    pub txid: Option<String>,
+    pub block_height: Option<i32>,
+    /// `None` when the change created the asset row.
+    pub old_supply: Option<Decimal>,
+    pub new_supply: Decimal,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Count {
+    count: i64,
+}
+
+#[derive(Clone)]
+pub struct SupplyChangesRepository {
+    conn: DatabaseConnection,
+}
+
+impl SupplyChangesRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Supply history of one app_id, newest change first, with the total
+    /// count.
+    pub async fn by_app_id(
+        &self,
+        app_id: &str,
+        network: &str,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<SupplyChange>, u64), DbError> {
+        let changes = SupplyChange::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT delta, reason, txid, block_height, old_supply, new_supply, created_at
+               FROM supply_changes
+              WHERE app_id = $1 AND network = $2
+              ORDER BY id DESC
+              LIMIT $3 OFFSET $4",
+            [
+                app_id.into(),
+                network.into(),
+                (limit as i64).into(),
+                (offset as i64).into(),
+            ],
+        ))
+        .all(&self.conn)
+        .await?;
+
+        let total = Count::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS count FROM supply_changes WHERE app_id = $1 AND network = $2",
+            [app_id.into(), network.into()],
+        ))
+        .one(&self.conn)
+        .await?
+        .map_or(0, |c| c.count as u64);
+
+        Ok((changes, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use sea_orm::ConnectionTrait;
+
+    const SEED: &str = "
+        INSERT INTO supply_changes (app_id, network, delta, reason, txid, block_height, old_supply, new_supply) VALUES
+            ('t/a/a', 'mainnet', 500, 'mint', 'tx1', 100, NULL, 500),
+            ('t/a/a', 'mainnet', 300, 'mint', 'tx2', 120, 500, 800),
+            ('t/a/a', 'mainnet', 100, 'manual', NULL, NULL, 800, 900),
+            ('t/a/a', 'testnet4', 7, 'mint', 'tx9', 5, NULL, 7),
+            ('t/b/b', 'mainnet', 1, 'mint', 'tx3', 130, NULL, 1);
+    ";
+
+    /// Newest first, paged, network-scoped. Needs a scratch database;
+    /// tables live in a throwaway schema.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn history_is_paged_newest_first() {
+        let schema = TestSchema::new("supply_changes", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(SEED).await.expect("fixture");
+
+        let repo = SupplyChangesRepository::new(conn.clone());
+        let (first, total) = repo.by_app_id("t/a/a", "mainnet", 2, 0).await.unwrap();
+        assert_eq!(total, 3);
+        let reasons: Vec<&str> = first.iter().map(|c| c.reason.as_str()).collect();
+        assert_eq!(reasons, ["manual", "mint"]);
+        assert_eq!(first[1].old_supply, Some(Decimal::from(500)));
+        let (rest, _) = repo.by_app_id("t/a/a", "mainnet", 2, 2).await.unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(
+            (rest[0].old_supply, rest[0].new_supply),
+            (None, Decimal::from(500))
+        );
+
+        schema.drop_schema().await;
+    }
+}
@@ -0,0 +1,435 @@
+// Wallet changes repository — what happened to an address between two
+// heights, behind GET /wallet/changes/{address}: charms received and spent
+// (live and archived), tracked BTC UTXOs created and spent (spends are kept
+// in `address_utxo_spends`), what is pending in the mempool, and whether a
+// cursor can still be answered at all.
+
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+/// What decides whether a cursor can be answered. Read before the address
+/// is touched or re-seeded, since both move `synced_from` and its TTL.
+#[derive(Debug, Clone, Default, PartialEq, Eq, FromQueryResult)]
+pub struct SyncState {
+    /// Last block the indexer processed on the network; what the changes
+    /// run up to.
+    pub tip: Option<i32>,
+    /// Lowest cursor whose history is still kept (`wallet_sync_horizon`).
+    pub horizon: Option<i32>,
+    /// Height from which the address's UTXOs are tracked without a gap;
+    /// `None` when it is not seeded or its monitoring lapsed.
+    pub synced_from: Option<i32>,
+    /// The cursor points into blocks a reorg rolled back.
+    pub reorged: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromQueryResult)]
+pub struct ReceivedCharm {
+    pub txid: String,
+    pub vout: i32,
+    pub app_id: String,
+    pub asset_type: String,
+    pub amount: i64,
+    pub block_height: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromQueryResult)]
+pub struct SpentCharm {
+    pub txid: String,
+    pub vout: i32,
+    pub app_id: String,
+    pub amount: i64,
+    /// NULL for spends indexed before the spender was recorded
+    pub spending_txid: Option<String>,
+    pub spent_height: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromQueryResult)]
+pub struct AddedUtxo {
+    pub txid: String,
+    pub vout: i32,
+    pub value: i64,
+    pub block_height: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromQueryResult)]
+pub struct RemovedUtxo {
+    pub txid: String,
+    pub vout: i32,
+    pub value: i64,
+    pub spending_txid: String,
+    pub spent_height: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromQueryResult)]
+pub struct PendingCharm {
+    pub txid: String,
+    pub vout: i32,
+    pub app_id: String,
+    pub asset_type: String,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromQueryResult)]
+pub struct PendingUtxo {
+    pub txid: String,
+    pub vout: i32,
+    pub value: i64,
+}
+
+/// A mempool transaction spending one of the address's outputs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromQueryResult)]
+pub struct PendingSpend {
+    pub txid: String,
+    pub vout: i32,
+    pub spending_txid: String,
+}
+
+/// Raw rows of a height window. An output created and spent inside the
+/// window shows up on both sides; `wallet_changes_service` nets them out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowChanges {
+    pub received: Vec<ReceivedCharm>,
+    pub spent: Vec<SpentCharm>,
+    pub added: Vec<AddedUtxo>,
+    pub removed: Vec<RemovedUtxo>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingItems {
+    pub charms: Vec<PendingCharm>,
+    pub utxos: Vec<PendingUtxo>,
+    pub spends: Vec<PendingSpend>,
+}
+
+/// ($1 address, $2 network, $3 cursor). A reorg found at `from_height`
+/// rolled back the blocks after `from_height - depth`.
+const SYNC_STATE_SQL: &str = "
+SELECT (SELECT last_processed_block FROM summary WHERE network = $2) AS tip,
+       (SELECT from_height FROM wallet_sync_horizon WHERE network = $2) AS horizon,
+       (SELECT synced_from FROM monitored_addresses
+         WHERE address = $1 AND network = $2 AND seeded_at IS NOT NULL
+           AND (expires_at IS NULL OR expires_at > NOW())) AS synced_from,
+       EXISTS (SELECT 1 FROM reorg_events
+                WHERE network = $2 AND $3 > from_height - depth AND $3 < from_height) AS reorged";
+
+// The window queries take ($1 address, $2 network, $3 since, $4 tip) and
+// cover heights in ($3, $4].
+
+const RECEIVED_SQL: &str = "
+SELECT txid, vout, app_id, asset_type, amount, block_height
+  FROM charms
+ WHERE address = $1 AND network = $2 AND block_height > $3 AND block_height <= $4
+   AND NOT is_placeholder
+UNION ALL
+SELECT txid, vout, app_id, asset_type, amount, block_height
+  FROM charms_archive
+ WHERE address = $1 AND network = $2 AND block_height > $3 AND block_height <= $4
+   AND NOT is_placeholder
+ ORDER BY block_height, txid, vout, app_id";
+
+const SPENT_SQL: &str = "
+SELECT txid, vout, app_id, amount, spending_txid, spent_height
+  FROM charms
+ WHERE address = $1 AND network = $2 AND spent AND spent_height > $3 AND spent_height <= $4
+   AND NOT is_placeholder
+UNION ALL
+SELECT txid, vout, app_id, amount, spending_txid, spent_height
+  FROM charms_archive
+ WHERE address = $1 AND network = $2 AND spent AND spent_height > $3 AND spent_height <= $4
+   AND NOT is_placeholder
+ ORDER BY spent_height, txid, vout, app_id";
+
+/// Outputs still unspent, and those already spent again. A spend after the
+/// tip is not part of this window: the output counts as added.
+const ADDED_SQL: &str = "
+SELECT txid, vout, value, block_height
+  FROM address_utxos
+ WHERE address = $1 AND network = $2 AND block_height > $3 AND block_height <= $4
+UNION ALL
+SELECT txid, vout, value, block_height
+  FROM address_utxo_spends
+ WHERE address = $1 AND network = $2 AND spent_height > $3
+   AND block_height > $3 AND block_height <= $4
+ ORDER BY block_height, txid, vout";
+
+const REMOVED_SQL: &str = "
+SELECT txid, vout, value, spending_txid, spent_height
+  FROM address_utxo_spends
+ WHERE address = $1 AND network = $2 AND spent_height > $3 AND spent_height <= $4
+ ORDER BY spent_height, txid, vout";
+
+// The pending queries take ($1 address, $2 network).
+
+const PENDING_CHARMS_SQL: &str = "
+SELECT txid, vout, app_id, asset_type, amount
+  FROM charms
+ WHERE address = $1 AND network = $2 AND block_height IS NULL AND NOT is_placeholder
+ ORDER BY txid, vout, app_id";
+
+const PENDING_UTXOS_SQL: &str = "
+SELECT txid, vout, value
+  FROM address_utxos
+ WHERE address = $1 AND network = $2 AND COALESCE(block_height, 0) = 0
+ ORDER BY txid, vout";
+
+const PENDING_SPENDS_SQL: &str = "
+SELECT s.spent_txid AS txid, s.spent_vout AS vout, s.spending_txid
+  FROM mempool_spends s
+ WHERE s.network = $2
+   AND (EXISTS (SELECT 1 FROM address_utxos u
+                 WHERE u.txid = s.spent_txid AND u.vout = s.spent_vout
+                   AND u.network = s.network AND u.address = $1)
+        OR EXISTS (SELECT 1 FROM charms c
+                    WHERE c.txid = s.spent_txid AND c.vout = s.spent_vout
+                      AND c.network = s.network AND c.address = $1 AND NOT c.spent))
+ ORDER BY 1, 2";
+
+#[derive(Clone)]
+pub struct WalletChangesRepository {
+    conn: DatabaseConnection,
+}
+
+impl WalletChangesRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn sync_state(
+        &self,
+        address: &str,
+        network: &str,
+        since: i32,
+    ) -> Result<SyncState, DbError> {
+        Ok(SyncState::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            SYNC_STATE_SQL,
+            [address.into(), network.into(), since.into()],
+        ))
+        .one(&self.conn)
+        .await?
+        .unwrap_or_default())
+    }
+
+    /// Rows that changed at heights in `(since, tip]`.
+    pub async fn window(
+        &self,
+        address: &str,
+        network: &str,
+        since: i32,
+        tip: i32,
+    ) -> Result<WindowChanges, DbError> {
+        let statement = |sql: &str| {
+            Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [address.into(), network.into(), since.into(), tip.into()],
+            )
+        };
+        Ok(WindowChanges {
+            received: ReceivedCharm::find_by_statement(statement(RECEIVED_SQL))
+                .all(&self.conn)
+                .await?,
+            spent: SpentCharm::find_by_statement(statement(SPENT_SQL))
+                .all(&self.conn)
+                .await?,
+            added: AddedUtxo::find_by_statement(statement(ADDED_SQL))
+                .all(&self.conn)
+                .await?,
+            removed: RemovedUtxo::find_by_statement(statement(REMOVED_SQL))
+                .all(&self.conn)
+                .await?,
+        })
+    }
+
+    /// What the mempool holds for the address right now.
+    pub async fn pending(&self, address: &str, network: &str) -> Result<PendingItems, DbError> {
+        let statement = |sql: &str| {
+            Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [address.into(), network.into()],
+            )
+        };
+        Ok(PendingItems {
+            charms: PendingCharm::find_by_statement(statement(PENDING_CHARMS_SQL))
+                .all(&self.conn)
+                .await?,
+            utxos: PendingUtxo::find_by_statement(statement(PENDING_UTXOS_SQL))
+                .all(&self.conn)
+                .await?,
+            spends: PendingSpend::find_by_statement(statement(PENDING_SPENDS_SQL))
+                .all(&self.conn)
+                .await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestSchema;
+    use sea_orm::ConnectionTrait;
+
+    const SEED: &str = "
+        INSERT INTO summary (network, last_processed_block) VALUES ('mainnet', 99);
+        INSERT INTO wallet_sync_horizon (network, from_height) VALUES ('mainnet', 50);
+        INSERT INTO monitored_addresses
+            (address, network, source, seeded_at, expires_at, synced_from)
+        VALUES
+            ('holder', 'mainnet', 'api', NOW(), NOW() + INTERVAL '1 day', 90),
+            ('lapsed', 'mainnet', 'api', NOW(), NOW() - INTERVAL '1 day', 90);
+        INSERT INTO reorg_events (network, from_height, depth) VALUES ('mainnet', 80, 3);
+    ";
+
+    /// Block 100: `holder` receives a token on r1:0 and 1000 sats on r1:1.
+    const BLOCK_100: &str = "
+        INSERT INTO charms
+            (txid, vout, block_height, app_id, amount, address, asset_type, blockchain, network)
+        VALUES ('r1', 0, 100, 't/aa/1', 25, 'holder', 'token', 'bitcoin', 'mainnet');
+        INSERT INTO address_utxos (txid, vout, address, value, block_height, network) VALUES
+            ('r1', 1, 'holder', 1000, 100, 'mainnet');
+        UPDATE summary SET last_processed_block = 100;
+    ";
+
+    /// Block 101: s1 spends both outputs, sends the token to `other` and
+    /// 400 sats of change back. Then a mempool tx m1 spends the change and
+    /// pays 300 back, with a pending charm.
+    const BLOCK_101: &str = "
+        UPDATE charms SET spent = true, spent_height = 101, spending_txid = 's1'
+         WHERE txid = 'r1' AND vout = 0;
+        INSERT INTO charms
+            (txid, vout, block_height, app_id, amount, address, asset_type, blockchain, network)
+        VALUES ('s1', 0, 101, 't/aa/1', 25, 'other', 'token', 'bitcoin', 'mainnet');
+        WITH gone AS (DELETE FROM address_utxos WHERE txid = 'r1' AND vout = 1 RETURNING *)
+        INSERT INTO address_utxo_spends
+        SELECT txid, vout, network, address, value, block_height, 's1', 101 FROM gone;
+        INSERT INTO address_utxos (txid, vout, address, value, block_height, network) VALUES
+            ('s1', 1, 'holder', 400, 101, 'mainnet'),
+            ('m1', 1, 'holder', 300, 0, 'mainnet');
+        INSERT INTO charms
+            (txid, vout, block_height, app_id, amount, address, asset_type, blockchain, network)
+        VALUES ('m1', 0, NULL, 't/bb/1', 7, 'holder', 'token', 'bitcoin', 'mainnet');
+        INSERT INTO mempool_spends (spent_txid, spent_vout, spending_txid, network) VALUES
+            ('s1', 1, 'm1', 'mainnet'), ('zz', 0, 'm2', 'mainnet');
+        UPDATE summary SET last_processed_block = 101;
+    ";
+
+    fn ids<T>(rows: &[T], key: impl Fn(&T) -> String) -> Vec<String> {
+        rows.iter().map(key).collect()
+    }
+
+    /// A receive at 100 and a spend at 101, read through cursors 99 and
+    /// 100. Needs a scratch database; tables live in a throwaway schema.
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL pointing at a scratch Postgres; run with --ignored"]
+    async fn receive_and_spend_across_two_heights() {
+        let schema = TestSchema::new("wallet_changes", 1).await;
+        let conn = &schema.conn;
+        conn.execute_unprepared(SEED).await.expect("fixture");
+        let repo = WalletChangesRepository::new(conn.clone());
+
+        let state = repo.sync_state("holder", "mainnet", 99).await.unwrap();
+        assert_eq!(
+            state,
+            SyncState {
+                tip: Some(99),
+                horizon: Some(50),
+                synced_from: Some(90),
+                reorged: false,
+            }
+        );
+        let lapsed = repo.sync_state("lapsed", "mainnet", 99).await.unwrap();
+        assert_eq!(lapsed.synced_from, None);
+        // The reorg found at 80 rolled back 78 and 79.
+        let reorged = |since| repo.sync_state("holder", "mainnet", since);
+        assert!(reorged(78).await.unwrap().reorged);
+        assert!(!reorged(77).await.unwrap().reorged);
+        assert_eq!(
+            repo.sync_state("holder", "testnet4", 0).await.unwrap(),
+            SyncState::default()
+        );
+
+        conn.execute_unprepared(BLOCK_100).await.unwrap();
+        let at_100 = repo.window("holder", "mainnet", 99, 100).await.unwrap();
+        assert_eq!(
+            at_100.received,
+            [ReceivedCharm {
+                txid: "r1".into(),
+                vout: 0,
+                app_id: "t/aa/1".into(),
+                asset_type: "token".into(),
+                amount: 25,
+                block_height: 100,
+            }]
+        );
+        assert_eq!(
+            at_100.added,
+            [AddedUtxo {
+                txid: "r1".into(),
+                vout: 1,
+                value: 1000,
+                block_height: 100,
+            }]
+        );
+        assert!(at_100.spent.is_empty() && at_100.removed.is_empty());
+
+        conn.execute_unprepared(BLOCK_101).await.unwrap();
+        // A client at 100 only learns about block 101.
+        let at_101 = repo.window("holder", "mainnet", 100, 101).await.unwrap();
+        assert!(at_101.received.is_empty(), "s1:0 went to another address");
+        assert_eq!(
+            at_101.spent,
+            [SpentCharm {
+                txid: "r1".into(),
+                vout: 0,
+                app_id: "t/aa/1".into(),
+                amount: 25,
+                spending_txid: Some("s1".into()),
+                spent_height: 101,
+            }]
+        );
+        assert_eq!(ids(&at_101.added, |u| u.txid.clone()), ["s1"]);
+        assert_eq!(
+            at_101.removed,
+            [RemovedUtxo {
+                txid: "r1".into(),
+                vout: 1,
+                value: 1000,
+                spending_txid: "s1".into(),
+                spent_height: 101,
+            }]
+        );
+
+        // A client still at 99 gets both blocks: r1 shows up created and
+        // spent, for the service to net out.
+        let both = repo.window("holder", "mainnet", 99, 101).await.unwrap();
+        assert_eq!(ids(&both.received, |c| c.txid.clone()), ["r1"]);
+        assert_eq!(ids(&both.spent, |c| c.txid.clone()), ["r1"]);
+        assert_eq!(
+            ids(&both.added, |u| format!("{}:{}", u.txid, u.vout)),
+            ["r1:1", "s1:1"]
+        );
+        assert_eq!(ids(&both.removed, |u| u.txid.clone()), ["r1"]);
+
+        // Read back at the old tip, the spend at 101 is not part of it.
+        let old_tip = repo.window("holder", "mainnet", 99, 100).await.unwrap();
+        assert_eq!(old_tip, at_100);
+
+        let pending = repo.pending("holder", "mainnet").await.unwrap();
+        assert_eq!(ids(&pending.charms, |c| c.app_id.clone()), ["t/bb/1"]);
+        assert_eq!(ids(&pending.utxos, |u| u.txid.clone()), ["m1"]);
+        assert_eq!(
+            pending.spends,
+            [PendingSpend {
+                txid: "s1".into(),
+                vout: 1,
+                spending_txid: "m1".into(),
+            }]
+        );
+
+        schema.drop_schema().await;
+    }
+}
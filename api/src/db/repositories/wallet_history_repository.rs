@@ -0,0 +1,88 @@
+// Wallet history repository — raw balance-changing events for an address,
+// gathered from our own tables (no provider calls).
+
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+
+use crate::db::error::DbError;
+
+/// One balance change seen by the address. `app_id` is NULL for BTC events.
+/// `source` is "tx" (address_transactions), "utxo" (address_utxos and their
+/// pending spends) or "charm" (charms rows and their spends).
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct HistoryEvent {
+    pub txid: String,
+    pub block_height: Option<i32>,
+    pub block_time: Option<i64>,
+    pub confirmed: bool,
+    pub app_id: Option<String>,
+    pub delta: i64,
+    pub source: String,
+}
+
+/// Every arm is scoped to ($1 address, $2 network). Pending spends come from
+/// `mempool_spends`; confirmed charm spends from `charms.spending_txid`,
+/// with the height taken from the spell transaction when we indexed it.
+/// An NFT row counts as one unit whatever its stored `amount`.
+const EVENTS_SQL: &str = "
+SELECT txid, block_height, block_time, block_height IS NOT NULL AS confirmed,
+       NULL::text AS app_id,
+       CASE WHEN direction = 'out' THEN -amount ELSE amount END AS delta,
+       'tx' AS source
+  FROM address_transactions
+ WHERE address = $1 AND network = $2
+UNION ALL
+SELECT txid, NULLIF(block_height, 0), NULL::bigint, block_height > 0,
+       NULL::text, value, 'utxo'
+  FROM address_utxos
+ WHERE address = $1 AND network = $2
+UNION ALL
+SELECT s.spending_txid, NULL::int, NULL::bigint, FALSE,
+       NULL::text, -u.value, 'utxo'
+  FROM address_utxos u
+  JOIN mempool_spends s
+    ON s.spent_txid = u.txid AND s.spent_vout = u.vout AND s.network = u.network
+ WHERE u.address = $1 AND u.network = $2
+UNION ALL
+SELECT txid, block_height, NULL::bigint, block_height IS NOT NULL,
+       app_id, CASE WHEN app_id LIKE 'n/%' THEN 1 ELSE amount END, 'charm'
+  FROM charms
+ WHERE address = $1 AND network = $2 AND NOT is_placeholder
+UNION ALL
+SELECT c.spending_txid, t.block_height, NULL::bigint, TRUE,
+       c.app_id, CASE WHEN c.app_id LIKE 'n/%' THEN -1 ELSE -c.amount END, 'charm'
+  FROM charms c
+  LEFT JOIN transactions t ON t.txid = c.spending_txid AND t.network = c.network
+ WHERE c.address = $1 AND c.network = $2 AND c.spending_txid IS NOT NULL
+   AND NOT c.is_placeholder
+UNION ALL
+SELECT s.spending_txid, NULL::int, NULL::bigint, FALSE,
+       c.app_id, CASE WHEN c.app_id LIKE 'n/%' THEN -1 ELSE -c.amount END, 'charm'
+  FROM charms c
+  JOIN mempool_spends s
+    ON s.spent_txid = c.txid AND s.spent_vout = c.vout AND s.network = c.network
+ WHERE c.address = $1 AND c.network = $2 AND NOT c.spent AND NOT c.is_placeholder
+";
+
+#[derive(Clone)]
+pub struct WalletHistoryRepository {
+    conn: DatabaseConnection,
+}
+
+impl WalletHistoryRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// All events for an address, unordered. Merged per transaction by
+    /// `WalletHistoryService`.
+    pub async fn events(&self, address: &str, network: &str) -> Result<Vec<HistoryEvent>, DbError> {
+        HistoryEvent::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            EVENTS_SQL,
+            [address.into(), network.into()],
+        ))
+        .all(&self.conn)
+        .await
+        .map_err(Into::into)
+    }
+}
@@ -0,0 +1,116 @@
+// Webhook subscriptions repository — endpoints the indexer pushes block
+// events to. Deliveries and their attempts are written by the indexer.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+
+use crate::db::error::DbError;
+
+/// A subscription as listed to admins. The secret is never read back.
+#[derive(Debug, Clone, Serialize, FromQueryResult)]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub url: String,
+    pub app_id: Option<String>,
+    pub event_types: serde_json::Value,
+    pub active: bool,
+    pub consecutive_failures: i32,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub pending_deliveries: i64,
+    pub failed_deliveries: i64,
+    pub last_error: Option<String>,
+}
+
+const SELECT_SUBSCRIPTIONS: &str = "SELECT s.id, s.url, s.app_id, to_jsonb(s.event_types) AS event_types,
+        s.active, s.consecutive_failures, s.disabled_at, s.created_by, s.created_at,
+        (SELECT COUNT(*) FROM webhook_deliveries d
+          WHERE d.subscription_id = s.id AND d.status = 'pending') AS pending_deliveries,
+        (SELECT COUNT(*) FROM webhook_deliveries d
+          WHERE d.subscription_id = s.id AND d.status = 'failed') AS failed_deliveries,
+        (SELECT d.last_error FROM webhook_deliveries d
+          WHERE d.subscription_id = s.id AND d.last_error IS NOT NULL
+          ORDER BY d.next_attempt_at DESC LIMIT 1) AS last_error
+   FROM webhook_subscriptions s";
+
+#[derive(Clone)]
+pub struct WebhookSubscriptionsRepository {
+    conn: DatabaseConnection,
+}
+
+impl WebhookSubscriptionsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Register an endpoint and return it.
+    pub async fn create(
+        &self,
+        url: &str,
+        secret: &str,
+        app_id: Option<&str>,
+        event_types: &[String],
+        created_by: &str,
+    ) -> Result<WebhookSubscription, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO webhook_subscriptions (url, secret, app_id, event_types, created_by)
+                 VALUES ($1, $2, $3, ARRAY(SELECT jsonb_array_elements_text($4::jsonb)), $5)
+                 RETURNING id",
+                [
+                    url.into(),
+                    secret.into(),
+                    app_id.map(str::to_string).into(),
+                    serde_json::json!(event_types).into(),
+                    created_by.into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| DbError::QueryError("insert returned no row".to_string()))?;
+        let id: i64 = row.try_get("", "id")?;
+        self.get(id)
+            .await?
+            .ok_or_else(|| DbError::QueryError(format!("subscription {} vanished", id)))
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<WebhookSubscription>, DbError> {
+        Ok(
+            WebhookSubscription::find_by_statement(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!("{} WHERE s.id = $1", SELECT_SUBSCRIPTIONS),
+                [id.into()],
+            ))
+            .one(&self.conn)
+            .await?,
+        )
+    }
+
+    /// All subscriptions, newest first.
+    pub async fn list(&self) -> Result<Vec<WebhookSubscription>, DbError> {
+        Ok(
+            WebhookSubscription::find_by_statement(Statement::from_string(
+                DbBackend::Postgres,
+                format!("{} ORDER BY s.id DESC", SELECT_SUBSCRIPTIONS),
+            ))
+            .all(&self.conn)
+            .await?,
+        )
+    }
+
+    /// Delete a subscription with its queued deliveries. Returns whether it existed.
+    pub async fn delete(&self, id: i64) -> Result<bool, DbError> {
+        let res = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM webhook_subscriptions WHERE id = $1",
+                [id.into()],
+            ))
+            .await?;
+        Ok(res.rows_affected() == 1)
+    }
+}
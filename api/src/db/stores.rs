@@ -0,0 +1,572 @@
+// Store traits over the repositories services and handlers read through.
+// Production wires the SeaORM repositories; unit tests swap in the
+// in-memory fakes from `test_support`.
+
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use sea_orm::DbErr;
+
+use crate::db::repositories::admin_audit_repository::{
+    AuditEntry, AuditFilter, AuditOutcome, NewAuditEntry,
+};
+use crate::db::repositories::asset_repository::CollectionSummary;
+use crate::db::repositories::charm_repository::{
+    AddressHolding, BalanceAt, CharmFilter, ParserRevisionCount, PendingSpend,
+};
+use crate::db::repositories::likes_repository::LikeChange;
+use crate::db::repositories::stats_holders_repository::HolderCount;
+use crate::db::repositories::{
+    AdminAuditRepository, AssetRepository, CharmRepository, LikesRepository, StatsHoldersRepository,
+};
+use crate::db::DbError;
+use crate::entity::{assets, charms, stats_holders};
+use crate::models::{AssetSort, PaginationParams};
+
+type AssetResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Charm lookups (`charms` table)
+#[async_trait]
+pub trait CharmStore: Send + Sync {
+    async fn get_by_txid(
+        &self,
+        txid: &str,
+        network: &str,
+    ) -> Result<Option<charms::Model>, DbError>;
+    async fn get_by_txids(
+        &self,
+        txids: &[String],
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError>;
+    async fn get_all(&self) -> Result<Vec<charms::Model>, DbError>;
+    async fn get_all_paginated(
+        &self,
+        pagination: &PaginationParams,
+        filter: &CharmFilter,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError>;
+    async fn get_all_paginated_by_network(
+        &self,
+        pagination: &PaginationParams,
+        network: &str,
+        filter: &CharmFilter,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError>;
+    async fn find_by_asset_type(&self, asset_type: &str) -> Result<Vec<charms::Model>, DbError>;
+    async fn find_by_asset_type_paginated(
+        &self,
+        asset_type: &str,
+        network: &str,
+        pagination: &PaginationParams,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError>;
+    async fn find_by_charmid(
+        &self,
+        charmid: &str,
+        network: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<charms::Model>, DbError>;
+    async fn find_by_spending_txid(
+        &self,
+        spending_txid: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError>;
+    async fn balance_at(
+        &self,
+        address: &str,
+        app_id: &str,
+        network: &str,
+        height: i32,
+    ) -> Result<BalanceAt, DbError>;
+    async fn networks_for_txid(
+        &self,
+        txid: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<String>, DbError>;
+    async fn networks_for_charmid(
+        &self,
+        charmid: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<String>, DbError>;
+    async fn find_by_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError>;
+    async fn holdings_by_address(
+        &self,
+        address: &str,
+        network: &str,
+        filter: &CharmFilter,
+    ) -> Result<Vec<AddressHolding>, DbError>;
+    async fn get_charm_numbers_by_type(
+        &self,
+        asset_type: Option<&str>,
+    ) -> Result<Vec<String>, DbError>;
+    async fn get_unspent_charms_by_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError>;
+    async fn get_pending_spends_for_outpoints(
+        &self,
+        outpoints: &[(String, i32)],
+        network: &str,
+    ) -> Result<HashMap<(String, i32), PendingSpend>, DbError>;
+    async fn get_sibling_app_ids_for_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<HashMap<(String, i32), Vec<String>>, DbError>;
+    async fn count_by_parser_revision(
+        &self,
+        network: Option<&str>,
+    ) -> Result<Vec<ParserRevisionCount>, DbError>;
+}
+
+/// Per-user charm likes (`likes` table)
+#[async_trait]
+pub trait LikesStore: Send + Sync {
+    async fn add_like(&self, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr>;
+    async fn remove_like(&self, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr>;
+    async fn get_likes_count(&self, charm_id: &str) -> Result<i64, DbErr>;
+    async fn has_user_liked(&self, charm_id: &str, user_id: i32) -> Result<bool, DbErr>;
+    async fn get_likes_counts_batch(
+        &self,
+        charm_ids: &[String],
+    ) -> Result<HashMap<String, i64>, DbErr>;
+    async fn get_user_likes_batch(
+        &self,
+        charm_ids: &[String],
+        user_id: i32,
+    ) -> Result<HashSet<String>, DbErr>;
+    async fn count_since(
+        &self,
+        app_id: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DbErr>;
+}
+
+/// Asset metadata and supply (`assets` table)
+#[async_trait]
+pub trait AssetStore: Send + Sync {
+    async fn find_paginated(
+        &self,
+        asset_type: Option<&str>,
+        network: Option<&str>,
+        sort: AssetSort,
+        limit: u64,
+        offset: u64,
+        include_hidden: bool,
+    ) -> AssetResult<Vec<assets::Model>>;
+    async fn count_assets(
+        &self,
+        asset_type: Option<&str>,
+        network: Option<&str>,
+        include_hidden: bool,
+    ) -> AssetResult<u64>;
+    async fn find_by_id(&self, id: i32) -> AssetResult<Option<assets::Model>>;
+    async fn find_by_app_id(
+        &self,
+        app_id: &str,
+        network: &str,
+    ) -> AssetResult<Option<assets::Model>>;
+    async fn find_by_app_ids(
+        &self,
+        app_ids: Vec<String>,
+        network: &str,
+    ) -> AssetResult<Vec<assets::Model>>;
+    async fn find_by_asset_type(&self, asset_type: &str) -> AssetResult<Vec<assets::Model>>;
+    async fn find_by_network(&self, network: &str) -> AssetResult<Vec<assets::Model>>;
+    async fn find_reference_nft_by_hash(
+        &self,
+        hash: &str,
+        network: &str,
+    ) -> AssetResult<Option<assets::Model>>;
+    async fn find_reference_nft_by_vk(&self, vk: &str) -> AssetResult<Option<assets::Model>>;
+    async fn get_max_total_supply_by_prefix(
+        &self,
+        base_app_id: &str,
+        network: &str,
+    ) -> AssetResult<Option<rust_decimal::Decimal>>;
+    async fn find_collections(
+        &self,
+        network: &str,
+        limit: u64,
+        offset: u64,
+    ) -> AssetResult<(Vec<CollectionSummary>, u64)>;
+    async fn find_by_collection(
+        &self,
+        collection: &str,
+        network: &str,
+        limit: u64,
+        offset: u64,
+        include_hidden: bool,
+    ) -> AssetResult<(Vec<assets::Model>, u64)>;
+}
+
+/// Holder statistics (`stats_holders` table)
+#[async_trait]
+pub trait StatsHoldersStore: Send + Sync {
+    async fn get_holders_by_app_id(
+        &self,
+        app_id: &str,
+    ) -> Result<Vec<stats_holders::Model>, DbError>;
+    async fn top_by_holders(&self, network: &str, limit: u64) -> Result<Vec<HolderCount>, DbError>;
+}
+
+/// Admin audit trail (`admin_audit_log` table)
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    async fn begin(&self, entry: &NewAuditEntry) -> Result<i64, DbError>;
+    async fn finish(&self, id: i64, outcome: &AuditOutcome) -> Result<(), DbError>;
+    async fn list(&self, filter: &AuditFilter, limit: u64) -> Result<Vec<AuditEntry>, DbError>;
+}
+
+#[async_trait]
+impl CharmStore for CharmRepository {
+    async fn get_by_txid(
+        &self,
+        txid: &str,
+        network: &str,
+    ) -> Result<Option<charms::Model>, DbError> {
+        CharmRepository::get_by_txid(self, txid, network).await
+    }
+
+    async fn get_by_txids(
+        &self,
+        txids: &[String],
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        CharmRepository::get_by_txids(self, txids, network).await
+    }
+
+    async fn get_all(&self) -> Result<Vec<charms::Model>, DbError> {
+        CharmRepository::get_all(self).await
+    }
+
+    async fn get_all_paginated(
+        &self,
+        pagination: &PaginationParams,
+        filter: &CharmFilter,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError> {
+        CharmRepository::get_all_paginated(self, pagination, filter, include_hidden).await
+    }
+
+    async fn get_all_paginated_by_network(
+        &self,
+        pagination: &PaginationParams,
+        network: &str,
+        filter: &CharmFilter,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError> {
+        CharmRepository::get_all_paginated_by_network(
+            self,
+            pagination,
+            network,
+            filter,
+            include_hidden,
+        )
+        .await
+    }
+
+    async fn find_by_asset_type(&self, asset_type: &str) -> Result<Vec<charms::Model>, DbError> {
+        CharmRepository::find_by_asset_type(self, asset_type).await
+    }
+
+    async fn find_by_asset_type_paginated(
+        &self,
+        asset_type: &str,
+        network: &str,
+        pagination: &PaginationParams,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError> {
+        CharmRepository::find_by_asset_type_paginated(
+            self,
+            asset_type,
+            network,
+            pagination,
+            include_hidden,
+        )
+        .await
+    }
+
+    async fn find_by_charmid(
+        &self,
+        charmid: &str,
+        network: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        CharmRepository::find_by_charmid(self, charmid, network, include_hidden).await
+    }
+
+    async fn find_by_spending_txid(
+        &self,
+        spending_txid: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        CharmRepository::find_by_spending_txid(self, spending_txid, network).await
+    }
+
+    async fn balance_at(
+        &self,
+        address: &str,
+        app_id: &str,
+        network: &str,
+        height: i32,
+    ) -> Result<BalanceAt, DbError> {
+        CharmRepository::balance_at(self, address, app_id, network, height).await
+    }
+
+    async fn networks_for_txid(
+        &self,
+        txid: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<String>, DbError> {
+        CharmRepository::networks_for_txid(self, txid, include_hidden).await
+    }
+
+    async fn networks_for_charmid(
+        &self,
+        charmid: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<String>, DbError> {
+        CharmRepository::networks_for_charmid(self, charmid, include_hidden).await
+    }
+
+    async fn find_by_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        CharmRepository::find_by_address(self, address, network).await
+    }
+
+    async fn holdings_by_address(
+        &self,
+        address: &str,
+        network: &str,
+        filter: &CharmFilter,
+    ) -> Result<Vec<AddressHolding>, DbError> {
+        CharmRepository::holdings_by_address(self, address, network, filter).await
+    }
+
+    async fn get_charm_numbers_by_type(
+        &self,
+        asset_type: Option<&str>,
+    ) -> Result<Vec<String>, DbError> {
+        CharmRepository::get_charm_numbers_by_type(self, asset_type).await
+    }
+
+    async fn get_unspent_charms_by_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        CharmRepository::get_unspent_charms_by_address(self, address, network).await
+    }
+
+    async fn get_pending_spends_for_outpoints(
+        &self,
+        outpoints: &[(String, i32)],
+        network: &str,
+    ) -> Result<HashMap<(String, i32), PendingSpend>, DbError> {
+        CharmRepository::get_pending_spends_for_outpoints(self, outpoints, network).await
+    }
+
+    async fn get_sibling_app_ids_for_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<HashMap<(String, i32), Vec<String>>, DbError> {
+        CharmRepository::get_sibling_app_ids_for_address(self, address, network).await
+    }
+
+    async fn count_by_parser_revision(
+        &self,
+        network: Option<&str>,
+    ) -> Result<Vec<ParserRevisionCount>, DbError> {
+        CharmRepository::count_by_parser_revision(self, network).await
+    }
+}
+
+#[async_trait]
+impl LikesStore for LikesRepository {
+    async fn add_like(&self, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr> {
+        LikesRepository::add_like(self, charm_id, user_id).await
+    }
+
+    async fn remove_like(&self, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr> {
+        LikesRepository::remove_like(self, charm_id, user_id).await
+    }
+
+    async fn get_likes_count(&self, charm_id: &str) -> Result<i64, DbErr> {
+        LikesRepository::get_likes_count(self, charm_id).await
+    }
+
+    async fn has_user_liked(&self, charm_id: &str, user_id: i32) -> Result<bool, DbErr> {
+        LikesRepository::has_user_liked(self, charm_id, user_id).await
+    }
+
+    async fn get_likes_counts_batch(
+        &self,
+        charm_ids: &[String],
+    ) -> Result<HashMap<String, i64>, DbErr> {
+        LikesRepository::get_likes_counts_batch(self, charm_ids).await
+    }
+
+    async fn get_user_likes_batch(
+        &self,
+        charm_ids: &[String],
+        user_id: i32,
+    ) -> Result<HashSet<String>, DbErr> {
+        LikesRepository::get_user_likes_batch(self, charm_ids, user_id).await
+    }
+
+    async fn count_since(
+        &self,
+        app_id: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DbErr> {
+        LikesRepository::count_since(self, app_id, since).await
+    }
+}
+
+#[async_trait]
+impl AssetStore for AssetRepository {
+    async fn find_paginated(
+        &self,
+        asset_type: Option<&str>,
+        network: Option<&str>,
+        sort: AssetSort,
+        limit: u64,
+        offset: u64,
+        include_hidden: bool,
+    ) -> AssetResult<Vec<assets::Model>> {
+        AssetRepository::find_paginated(
+            self,
+            asset_type,
+            network,
+            sort,
+            limit,
+            offset,
+            include_hidden,
+        )
+        .await
+    }
+
+    async fn count_assets(
+        &self,
+        asset_type: Option<&str>,
+        network: Option<&str>,
+        include_hidden: bool,
+    ) -> AssetResult<u64> {
+        AssetRepository::count_assets(self, asset_type, network, include_hidden).await
+    }
+
+    async fn find_by_id(&self, id: i32) -> AssetResult<Option<assets::Model>> {
+        AssetRepository::find_by_id(self, id).await
+    }
+
+    async fn find_by_app_id(
+        &self,
+        app_id: &str,
+        network: &str,
+    ) -> AssetResult<Option<assets::Model>> {
+        AssetRepository::find_by_app_id(self, app_id, network).await
+    }
+
+    async fn find_by_app_ids(
+        &self,
+        app_ids: Vec<String>,
+        network: &str,
+    ) -> AssetResult<Vec<assets::Model>> {
+        AssetRepository::find_by_app_ids(self, app_ids, network).await
+    }
+
+    async fn find_by_asset_type(&self, asset_type: &str) -> AssetResult<Vec<assets::Model>> {
+        AssetRepository::find_by_asset_type(self, asset_type).await
+    }
+
+    async fn find_by_network(&self, network: &str) -> AssetResult<Vec<assets::Model>> {
+        AssetRepository::find_by_network(self, network).await
+    }
+
+    async fn find_reference_nft_by_hash(
+        &self,
+        hash: &str,
+        network: &str,
+    ) -> AssetResult<Option<assets::Model>> {
+        AssetRepository::find_reference_nft_by_hash(self, hash, network).await
+    }
+
+    async fn find_reference_nft_by_vk(&self, vk: &str) -> AssetResult<Option<assets::Model>> {
+        AssetRepository::find_reference_nft_by_vk(self, vk).await
+    }
+
+    async fn get_max_total_supply_by_prefix(
+        &self,
+        base_app_id: &str,
+        network: &str,
+    ) -> AssetResult<Option<rust_decimal::Decimal>> {
+        AssetRepository::get_max_total_supply_by_prefix(self, base_app_id, network).await
+    }
+
+    async fn find_collections(
+        &self,
+        network: &str,
+        limit: u64,
+        offset: u64,
+    ) -> AssetResult<(Vec<CollectionSummary>, u64)> {
+        AssetRepository::find_collections(self, network, limit, offset).await
+    }
+
+    async fn find_by_collection(
+        &self,
+        collection: &str,
+        network: &str,
+        limit: u64,
+        offset: u64,
+        include_hidden: bool,
+    ) -> AssetResult<(Vec<assets::Model>, u64)> {
+        AssetRepository::find_by_collection(
+            self,
+            collection,
+            network,
+            limit,
+            offset,
+            include_hidden,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl StatsHoldersStore for StatsHoldersRepository {
+    async fn get_holders_by_app_id(
+        &self,
+        app_id: &str,
+    ) -> Result<Vec<stats_holders::Model>, DbError> {
+        StatsHoldersRepository::get_holders_by_app_id(self, app_id).await
+    }
+
+    async fn top_by_holders(&self, network: &str, limit: u64) -> Result<Vec<HolderCount>, DbError> {
+        StatsHoldersRepository::top_by_holders(self, network, limit).await
+    }
+}
+
+#[async_trait]
+impl AuditStore for AdminAuditRepository {
+    async fn begin(&self, entry: &NewAuditEntry) -> Result<i64, DbError> {
+        AdminAuditRepository::begin(self, entry).await
+    }
+
+    async fn finish(&self, id: i64, outcome: &AuditOutcome) -> Result<(), DbError> {
+        AdminAuditRepository::finish(self, id, outcome).await
+    }
+
+    async fn list(&self, filter: &AuditFilter, limit: u64) -> Result<Vec<AuditEntry>, DbError> {
+        AdminAuditRepository::list(self, filter, limit).await
+    }
+}
@@ -18,6 +18,13 @@ pub struct Model {
     pub block_height: i32,
     #[sea_orm(column_type = "Text", nullable)]
     pub source: Option<String>,
+    /// The funding tx looks like a spell but no charm was stored for it.
+    pub possible_charm: bool,
+    /// A charm is stored for this outpoint; `None` until the indexer
+    /// annotates the row.
+    pub has_charms: Option<bool>,
+    /// Sorted app_ids of the charms on this outpoint.
+    pub charm_app_ids: Json,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -29,6 +29,19 @@ pub struct Model {
     pub cardano_policy_id: Option<String>,
     pub cardano_asset_name: Option<String>,
     pub cardano_fingerprint: Option<String>,
+    pub collection: Option<String>, // Declared collection id, or `deployer:{address}`
+    /// Off-chain JSON document the NFT links to, once the indexer fetched it
+    pub offchain_metadata: Option<serde_json::Value>,
+    /// First-seen transaction of the app_id and the block it was mined in
+    pub deploy_txid: Option<String>,
+    pub deploy_block_height: Option<i32>,
+    /// Address the deploy transaction sent the app to
+    pub deployer_address: Option<String>,
+    /// visible, hidden or flagged; set by operators through /admin/moderate
+    pub moderation_status: String,
+    /// Unspent amount held at open DEX order and configured lock addresses;
+    /// refreshed by the indexer
+    pub locked_supply: Decimal,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
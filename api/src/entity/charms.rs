@@ -20,12 +20,13 @@ pub struct Model {
     pub asset_type: String,
     #[sea_orm(column_type = "Text")]
     pub blockchain: String,
-    #[sea_orm(column_type = "Text")]
+    #[sea_orm(primary_key, column_type = "Text")]
     pub network: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub address: Option<String>,
     pub spent: bool,
-    #[sea_orm(column_type = "Text")]
+    /// Part of the key: one output can carry several tokens
+    #[sea_orm(primary_key, column_type = "Text")]
     pub app_id: String,
     pub amount: i64,
     #[sea_orm(nullable)]
@@ -33,6 +34,41 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub tags: Option<String>,
     pub verified: bool,
+    /// Hash of the confirming block; NULL while in mempool
+    #[sea_orm(column_type = "Text", nullable)]
+    pub block_hash: Option<String>,
+    /// Position of the tx inside its block; NULL while in mempool
+    #[sea_orm(nullable)]
+    pub tx_ordinal: Option<i32>,
+    /// Empty-spell placeholder; excluded from listings and counts
+    pub is_placeholder: bool,
+    /// Transaction that spent this output; NULL while unspent
+    #[sea_orm(column_type = "Text", nullable)]
+    pub spending_txid: Option<String>,
+    /// Indexer build that last wrote the row; NULL before stamping existed
+    #[sea_orm(column_type = "Text", nullable)]
+    pub indexer_version: Option<String>,
+    /// Parser revision that last wrote the row; NULL before stamping existed
+    #[sea_orm(nullable)]
+    pub parser_revision: Option<i32>,
+    /// Reindex run that last rewrote the row
+    #[sea_orm(column_type = "Text", nullable)]
+    pub reindex_run_id: Option<String>,
+    /// mint, transfer or burn; NULL until the row is confirmed, and for
+    /// rows indexed before the classification existed
+    #[sea_orm(column_type = "Text", nullable)]
+    pub operation: Option<String>,
+    /// visible, hidden or flagged; set by operators through /admin/moderate
+    #[sea_orm(column_type = "Text")]
+    pub moderation_status: String,
+    /// Header time of the confirming block; NULL while in mempool and for
+    /// rows not yet backfilled. `date_created` is when the indexer wrote it.
+    #[sea_orm(nullable)]
+    pub block_time: Option<NaiveDateTime>,
+    /// Height of the block that spent this output; NULL while unspent, and
+    /// for rows spent by an unindexed tx before the column existed
+    #[sea_orm(nullable)]
+    pub spent_height: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
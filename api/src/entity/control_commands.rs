@@ -0,0 +1,27 @@
+//! SeaORM Entity for `control_commands`. Operator commands for the indexer
+//! (pause/resume per network), kept as an audit log.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "control_commands")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    #[sea_orm(column_type = "Text")]
+    pub network: String,
+    #[sea_orm(column_type = "Text")]
+    pub command: String,
+    #[sea_orm(column_type = "Text")]
+    pub requested_by: String,
+    #[sea_orm(column_type = "TimestampWithTimeZone")]
+    pub requested_at: DateTimeWithTimeZone,
+    #[sea_orm(column_type = "TimestampWithTimeZone", nullable)]
+    pub acknowledged_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -46,6 +46,11 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub parent_order_id: Option<String>,
 
+    #[sea_orm(nullable)]
+    pub expires_at_height: Option<i32>,
+    #[sea_orm(nullable)]
+    pub expires_at_time: Option<NaiveDateTime>,
+
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 
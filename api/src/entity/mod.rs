@@ -5,6 +5,7 @@ pub mod address_utxos;
 pub mod assets;
 pub mod bookmark;
 pub mod charms;
+pub mod control_commands;
 pub mod dex_orders; // [RJJ-DEX]
 pub mod likes;
 pub mod monitored_addresses;
@@ -31,6 +31,15 @@ pub struct Model {
     pub charms_cast_count: i64,
     pub bro_count: i64,
     pub dex_orders_count: i64,
+    /// Set by the indexer while block processing is paused by an operator
+    pub indexer_paused: bool,
+    /// Set by the indexer while its primary node trails the fallback
+    /// provider's chain tip (see the tip watch)
+    pub provider_stale: bool,
+    /// When the indexer's garbage collector last finished a pass
+    pub last_gc_at: Option<DateTime<Utc>>,
+    /// Rows removed per GC sweep on that pass
+    pub last_gc_stats: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
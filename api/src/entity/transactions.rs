@@ -28,6 +28,9 @@ pub struct Model {
     pub mempool_detected_at: Option<DateTimeWithTimeZone>,
     #[sea_orm(column_type = "Text", nullable)]
     pub tags: Option<String>,
+    /// Per-output app map of the spell (`charms_core::spell_outputs`).
+    #[sea_orm(nullable)]
+    pub spell_outputs: Option<Value>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
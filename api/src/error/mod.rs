@@ -8,18 +8,34 @@ use thiserror::Error;
 
 use crate::db::DbError;
 
+mod upstream;
+
+pub use upstream::{internal_error, upstream_error};
+
 #[derive(Error, Debug)]
 pub enum ExplorerError {
     #[error("Database error: {0}")]
     DatabaseError(String),
     #[error("Not found: {0}")]
     NotFound(String),
+    /// Not found on the requested network, but present on the others listed
+    #[error("Not found: {message}")]
+    NotFoundOnNetwork {
+        message: String,
+        found_on_networks: Vec<String>,
+    },
     #[error("Invalid request: {0}")]
     #[allow(dead_code)] // Reserved for validation errors
     InvalidRequest(String),
     #[error("Internal error: {0}")]
     #[allow(dead_code)] // Reserved for general errors
     InternalError(String),
+    /// An upstream node could not be reached
+    #[error("Bad gateway: {0}")]
+    BadGateway(String),
+    /// An upstream node did not answer in time
+    #[error("Gateway timeout: {0}")]
+    GatewayTimeout(String),
 }
 
 pub type ExplorerResult<T> = Result<T, ExplorerError>;
@@ -29,8 +45,20 @@ impl IntoResponse for ExplorerError {
         let (status, err_msg) = match self {
             ExplorerError::DatabaseError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             ExplorerError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ExplorerError::NotFoundOnNetwork {
+                message,
+                found_on_networks,
+            } => {
+                let body = Json(json!({
+                    "error": message,
+                    "found_on_networks": found_on_networks,
+                }));
+                return (StatusCode::NOT_FOUND, body).into_response();
+            }
             ExplorerError::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             ExplorerError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            ExplorerError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, msg),
+            ExplorerError::GatewayTimeout(msg) => (StatusCode::GATEWAY_TIMEOUT, msg),
         };
 
         let body = Json(json!({
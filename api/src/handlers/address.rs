@@ -0,0 +1,126 @@
+// Bitcoin addresses at the API boundary. Address-taking handlers run the
+// path or body value through `normalize_address` before any lookup: it must
+// parse for the requested network, and bech32 comes back lowercase, the form
+// the indexer stores. Uppercase bech32 is valid (BIP-173) but would never
+// match a stored row; a mistyped or wrong-network address is a 400 instead
+// of an empty result.
+
+use std::str::FromStr;
+
+use bitcoincore_rpc::bitcoin::{address::NetworkUnchecked, Address, Network};
+
+use crate::error::{ExplorerError, ExplorerResult};
+
+/// Address network for an API network name.
+fn address_network(network: &str) -> ExplorerResult<Network> {
+    match network {
+        "mainnet" => Ok(Network::Bitcoin),
+        "testnet4" | "testnet" => Ok(Network::Testnet),
+        "signet" => Ok(Network::Signet),
+        "regtest" => Ok(Network::Regtest),
+        other => Err(ExplorerError::InvalidRequest(format!(
+            "unknown network '{}'",
+            other
+        ))),
+    }
+}
+
+/// Prefixes of valid addresses on `network`, for error messages.
+fn expected_prefixes(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => "bc1…, 1… or 3…",
+        Network::Regtest => "bcrt1…, m…, n… or 2…",
+        _ => "tb1…, m…, n… or 2…",
+    }
+}
+
+/// Canonical form of `address` on `network`: bech32 lowercased, base58
+/// unchanged. Rejects anything that does not parse for that network.
+pub fn normalize_address(address: &str, network: &str) -> ExplorerResult<String> {
+    let btc_network = address_network(network)?;
+    let invalid = || {
+        ExplorerError::InvalidRequest(format!(
+            "invalid {} address '{}': expected {}",
+            network,
+            address,
+            expected_prefixes(btc_network)
+        ))
+    };
+    let parsed = Address::<NetworkUnchecked>::from_str(address.trim()).map_err(|_| invalid())?;
+    if !parsed.is_valid_for_network(btc_network) {
+        return Err(invalid());
+    }
+    Ok(parsed.assume_checked().to_string())
+}
+
+/// `normalize_address` over a batch body's `addresses`; the first invalid
+/// one rejects the request.
+pub fn normalize_addresses(addresses: Vec<String>, network: &str) -> ExplorerResult<Vec<String>> {
+    addresses
+        .iter()
+        .map(|address| normalize_address(address, network))
+        .collect()
+}
+
+/// Lowercase a bech32 address without checking its network, for lookups
+/// that take no network (DEX makers). Base58 is case-sensitive and kept.
+pub fn canonical_address(address: &str) -> String {
+    let lower = address.trim().to_ascii_lowercase();
+    if ["bc1", "tb1", "bcrt1"]
+        .iter()
+        .any(|hrp| lower.starts_with(hrp))
+    {
+        lower
+    } else {
+        address.trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAINNET_P2WPKH: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    const TESTNET_P2WPKH: &str = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+
+    #[test]
+    fn uppercase_bech32_is_lowercased() {
+        assert_eq!(
+            normalize_address(&MAINNET_P2WPKH.to_uppercase(), "mainnet").unwrap(),
+            MAINNET_P2WPKH
+        );
+        assert_eq!(
+            normalize_address(TESTNET_P2WPKH, "testnet4").unwrap(),
+            TESTNET_P2WPKH
+        );
+        assert_eq!(
+            normalize_address("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH", "mainnet").unwrap(),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+    }
+
+    #[test]
+    fn wrong_network_and_broken_checksums_are_rejected() {
+        let err = normalize_address(TESTNET_P2WPKH, "mainnet").unwrap_err();
+        assert!(matches!(&err, ExplorerError::InvalidRequest(msg) if msg.contains("bc1")));
+        let err = normalize_address(MAINNET_P2WPKH, "testnet4").unwrap_err();
+        assert!(matches!(&err, ExplorerError::InvalidRequest(msg) if msg.contains("tb1")));
+
+        // Last character changed: the checksum no longer matches.
+        let broken = format!("{}5", &MAINNET_P2WPKH[..MAINNET_P2WPKH.len() - 1]);
+        assert!(normalize_address(&broken, "mainnet").is_err());
+        assert!(normalize_address(MAINNET_P2WPKH, "litecoin").is_err());
+    }
+
+    #[test]
+    fn canonical_address_only_touches_bech32() {
+        assert_eq!(
+            canonical_address(&MAINNET_P2WPKH.to_uppercase()),
+            MAINNET_P2WPKH
+        );
+        assert_eq!(
+            canonical_address("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+    }
+}
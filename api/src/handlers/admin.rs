@@ -0,0 +1,869 @@
+//! Operator endpoints for the indexer.
+//!
+//! `POST /admin/indexer/:network/pause` and `/resume` append a row to
+//! `control_commands`; the indexer's block loop picks it up on its next
+//! iteration and reports the state back through `/status`
+//! (`indexer_status.paused`).
+//!
+//! `POST/GET /admin/webhooks` and `DELETE /admin/webhooks/:id` manage the
+//! endpoints the indexer pushes block events to (see the indexer's
+//! `webhooks.rs`). The secret keys the `X-Charms-Signature` HMAC; it is
+//! returned once, on creation, and generated when not given.
+//!
+//! `GET /admin/charms/versions?network=` counts charms by the parser revision
+//! and indexer version that last wrote them, to scope reindexing after a
+//! parser fix (the indexer's `reindex --parser-revision-lt`).
+//!
+//! `POST /admin/assets/refresh-metadata` queues a job for the indexer to
+//! re-derive asset metadata from stored data, for the listed `app_ids` or
+//! `all`; only one job runs at a time (409 otherwise).
+//! `GET /admin/assets/refresh-metadata/:job_id` reports its progress and the
+//! fields it changed per asset.
+//!
+//! `GET /admin/backfills` lists the indexer's registered backfills with
+//! their status, cursor and last heartbeat. `POST /admin/backfills/:name`
+//! queues one for the live indexer (`?restart=true` starts a finished,
+//! idempotent one over); 404 for an unknown name, 409 while it is pending
+//! or running, or when it is done and not restarted.
+//!
+//! `GET /admin/block-timings?network=&limit=` lists how long the indexer
+//! took per block, by phase, with a rolling average and the mean over the
+//! last `TIMING_WINDOW` blocks, to chart performance regressions.
+//!
+//! `POST /admin/moderate` sets a charm's (by txid, every output) or asset's
+//! (by app_id) `moderation_status` to `visible`, `hidden` or `flagged`, with
+//! a required reason; each change is written to `moderation_log`. Public
+//! listings and detail endpoints leave hidden rows out unless the request
+//! carries `?include_hidden=true` and the admin token.
+//!
+//! `GET /diagnose/config` returns the effective configuration of this API
+//! process and the snapshot each indexer replica recorded in
+//! `config_snapshots` at startup, every setting with its source (`env`,
+//! `file` or `default`). Credentials are reduced to whether they are set and
+//! URLs to scheme, host and port.
+//!
+//! `GET /admin/audit?from=&to=&action=&limit=` reads `admin_audit_log`,
+//! the record `admin_audit::audited` keeps of every admin mutation, newest
+//! first.
+//!
+//! All of them require `Authorization: Bearer $ADMIN_API_TOKEN`; with no
+//! token configured they always answer 403.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::Sources;
+use crate::db::repositories::admin_audit_repository::AuditFilter;
+use crate::db::repositories::backfills_repository::BackfillRequest;
+use crate::db::repositories::moderation_repository::{ModerationStatus, ModerationTarget};
+use crate::db::repositories::TIMING_WINDOW;
+use crate::error::{ExplorerError, ExplorerResult};
+use crate::handlers::AppState;
+
+/// Handler for POST /admin/indexer/{network}/pause
+pub async fn pause_indexer(
+    State(state): State<AppState>,
+    Path(network): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    issue_command(&state, &network, &headers, "pause").await
+}
+
+/// Handler for POST /admin/indexer/{network}/resume
+pub async fn resume_indexer(
+    State(state): State<AppState>,
+    Path(network): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    issue_command(&state, &network, &headers, "resume").await
+}
+
+async fn issue_command(
+    state: &AppState,
+    network: &str,
+    headers: &HeaderMap,
+    command: &str,
+) -> axum::response::Response {
+    if !is_authorized(state.config.admin_api_token.as_deref(), headers) {
+        return forbidden();
+    }
+    let requested_by = requested_by(headers);
+
+    let result: ExplorerResult<bool> = async {
+        if network != "mainnet" && network != "testnet4" {
+            return Err(ExplorerError::InvalidRequest(format!(
+                "Unknown network: {}",
+                network
+            )));
+        }
+        Ok(state
+            .repositories
+            .control_commands
+            .issue(network, command, requested_by)
+            .await?)
+    }
+    .await;
+
+    match result {
+        Ok(changed) => {
+            tracing::info!(
+                "Indexer {} requested for {} by {} (changed: {})",
+                command,
+                network,
+                requested_by,
+                changed
+            );
+            Json(json!({
+                "network": network,
+                "command": command,
+                "requested_by": requested_by,
+                "changed": changed,
+            }))
+            .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CharmVersionsQuery {
+    /// Limit to one network; all networks when absent.
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+}
+
+/// Handler for GET /admin/charms/versions
+pub async fn list_charm_versions(
+    State(state): State<AppState>,
+    Query(params): Query<CharmVersionsQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    match state
+        .repositories
+        .charm
+        .count_by_parser_revision(params.network.as_deref())
+        .await
+    {
+        Ok(versions) => Json(json!({
+            "network": params.network,
+            "versions": versions,
+        }))
+        .into_response(),
+        Err(e) => ExplorerError::from(e).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshMetadataRequest {
+    pub app_ids: Option<Vec<String>>,
+    #[serde(default)]
+    pub all: bool,
+    /// Limit to one network; all networks when absent.
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+}
+
+/// Handler for POST /admin/assets/refresh-metadata
+pub async fn refresh_asset_metadata(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<RefreshMetadataRequest>,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    let requested_by = requested_by(&headers);
+
+    let result: ExplorerResult<Option<i64>> = async {
+        let app_ids = validate_refresh(&req)?;
+        Ok(state
+            .repositories
+            .metadata_refresh
+            .enqueue(app_ids, req.network.as_deref(), requested_by)
+            .await?)
+    }
+    .await;
+
+    match result {
+        Ok(Some(job_id)) => {
+            tracing::info!("Metadata refresh job {} queued by {}", job_id, requested_by);
+            (
+                StatusCode::ACCEPTED,
+                Json(json!({ "job_id": job_id, "status": "pending" })),
+            )
+                .into_response()
+        }
+        Ok(None) => (
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "a metadata refresh job is already pending or running" })),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Handler for GET /admin/assets/refresh-metadata/{job_id}
+pub async fn get_metadata_refresh(
+    State(state): State<AppState>,
+    Path(job_id): Path<i64>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    let repo = &state.repositories.metadata_refresh;
+    let result: ExplorerResult<_> = async {
+        let job = repo.get(job_id).await?.ok_or_else(|| {
+            ExplorerError::NotFound(format!("Metadata refresh job {} not found", job_id))
+        })?;
+        let results = repo.results(job_id).await?;
+        Ok((job, results))
+    }
+    .await;
+
+    match result {
+        Ok((job, results)) => {
+            let mut body = json!(job);
+            body["results"] = json!(results);
+            Json(body).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BackfillRunQuery {
+    #[serde(default)]
+    pub restart: bool,
+}
+
+/// Handler for GET /admin/backfills
+pub async fn list_backfills(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    match state.repositories.backfills.list().await {
+        Ok(backfills) => Json(json!({ "backfills": backfills })).into_response(),
+        Err(e) => ExplorerError::from(e).into_response(),
+    }
+}
+
+/// Handler for POST /admin/backfills/{name}
+pub async fn request_backfill(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<BackfillRunQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    let requested_by = requested_by(&headers);
+
+    match state
+        .repositories
+        .backfills
+        .request(&name, requested_by, query.restart)
+        .await
+    {
+        Ok(BackfillRequest::Queued) => {
+            tracing::info!("Backfill {} queued by {}", name, requested_by);
+            (
+                StatusCode::ACCEPTED,
+                Json(json!({ "name": name, "status": "pending" })),
+            )
+                .into_response()
+        }
+        Ok(BackfillRequest::Unknown) => {
+            ExplorerError::NotFound(format!("Backfill {} not found", name)).into_response()
+        }
+        Ok(BackfillRequest::Refused) => (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": "backfill is pending or running, or done and not restartable as requested"
+            })),
+        )
+            .into_response(),
+        Err(e) => ExplorerError::from(e).into_response(),
+    }
+}
+
+/// Most blocks `GET /admin/block-timings` returns at once.
+const MAX_TIMING_BLOCKS: u64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct BlockTimingsQuery {
+    /// Defaults to mainnet.
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+    /// Defaults to `TIMING_WINDOW`, at most `MAX_TIMING_BLOCKS`.
+    pub limit: Option<u64>,
+}
+
+/// Handler for GET /admin/block-timings
+pub async fn list_block_timings(
+    State(state): State<AppState>,
+    Query(params): Query<BlockTimingsQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    let network = params.network.as_deref().unwrap_or("mainnet");
+    let limit = params
+        .limit
+        .unwrap_or(TIMING_WINDOW)
+        .clamp(1, MAX_TIMING_BLOCKS);
+    let blocks = &state.repositories.blocks;
+    let result: ExplorerResult<_> = async {
+        if network != "mainnet" && network != "testnet4" {
+            return Err(ExplorerError::InvalidRequest(format!(
+                "Unknown network: {}",
+                network
+            )));
+        }
+        let timings = blocks.timings(network, limit).await?;
+        let averages = blocks.timing_averages(network, TIMING_WINDOW).await?;
+        Ok((timings, averages))
+    }
+    .await;
+
+    match result {
+        Ok((timings, averages)) => Json(json!({
+            "network": network,
+            "limit": limit,
+            "window": TIMING_WINDOW,
+            "averages": averages,
+            "blocks": timings,
+        }))
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Most rows `GET /admin/audit` returns at once.
+const MAX_AUDIT_ROWS: u64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    /// RFC 3339; calls made at or after it.
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    /// RFC 3339; calls made before it.
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    /// Method and route, e.g. `POST /admin/moderate`.
+    pub action: Option<String>,
+    /// Defaults to 100, at most `MAX_AUDIT_ROWS`.
+    pub limit: Option<u64>,
+}
+
+/// Handler for GET /admin/audit
+pub async fn list_admin_audit(
+    State(state): State<AppState>,
+    Query(params): Query<AuditQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    let limit = params.limit.unwrap_or(100).clamp(1, MAX_AUDIT_ROWS);
+    let filter = AuditFilter {
+        from: params.from,
+        to: params.to,
+        action: params.action.filter(|a| !a.is_empty()),
+    };
+    match state.repositories.admin_audit.list(&filter, limit).await {
+        Ok(entries) => Json(json!({ "limit": limit, "entries": entries })).into_response(),
+        Err(e) => ExplorerError::from(e).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerateRequest {
+    pub target_type: ModerationTarget,
+    /// txid for a charm, app_id for an asset
+    pub id: String,
+    /// Defaults to mainnet.
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+    pub status: ModerationStatus,
+    pub reason: String,
+}
+
+/// Handler for POST /admin/moderate
+pub async fn moderate(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ModerateRequest>,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    let moderated_by = requested_by(&headers);
+    let network = req.network.as_deref().unwrap_or("mainnet");
+
+    let result: ExplorerResult<_> = async {
+        validate_moderation(&req, network)?;
+        state
+            .repositories
+            .moderation
+            .moderate(
+                req.target_type,
+                req.id.trim(),
+                network,
+                req.status,
+                req.reason.trim(),
+                moderated_by,
+            )
+            .await?
+            .ok_or_else(|| {
+                ExplorerError::NotFound(format!(
+                    "No {} {} on {}",
+                    req.target_type.as_str(),
+                    req.id,
+                    network
+                ))
+            })
+    }
+    .await;
+
+    match result {
+        Ok(entry) => {
+            tracing::info!(
+                "{} {} on {} set {} by {}: {}",
+                entry.target_type,
+                entry.target,
+                entry.network,
+                entry.status,
+                entry.moderated_by,
+                entry.reason
+            );
+            Json(json!(entry)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+fn validate_moderation(req: &ModerateRequest, network: &str) -> ExplorerResult<()> {
+    if network != "mainnet" && network != "testnet4" {
+        return Err(ExplorerError::InvalidRequest(format!(
+            "Unknown network: {}",
+            network
+        )));
+    }
+    if req.id.trim().is_empty() {
+        return Err(ExplorerError::InvalidRequest("id is required".to_string()));
+    }
+    if req.reason.trim().is_empty() {
+        return Err(ExplorerError::InvalidRequest(
+            "reason is required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The app ids to refresh, `None` for all. Exactly one of `app_ids` and
+/// `all` must be given.
+fn validate_refresh(req: &RefreshMetadataRequest) -> ExplorerResult<Option<&[String]>> {
+    match (&req.app_ids, req.all) {
+        (Some(_), true) => Err(ExplorerError::InvalidRequest(
+            "give either app_ids or all, not both".to_string(),
+        )),
+        (None, false) => Err(ExplorerError::InvalidRequest(
+            "app_ids or all: true is required".to_string(),
+        )),
+        (Some(ids), false) if ids.is_empty() => Err(ExplorerError::InvalidRequest(
+            "app_ids must not be empty".to_string(),
+        )),
+        (Some(ids), false) => Ok(Some(ids.as_slice())),
+        (None, true) => Ok(None),
+    }
+}
+
+/// Event types the indexer emits; mirrors `EVENT_TYPES` in its `webhooks.rs`.
+const WEBHOOK_EVENT_TYPES: [&str; 5] = [
+    "charm_created",
+    "charm_spent",
+    "asset_supply_changed",
+    "dex_order_created",
+    "dex_order_filled",
+];
+
+/// Shortest secret accepted for signing deliveries.
+const MIN_SECRET_LEN: usize = 16;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub secret: Option<String>,
+    pub app_id: Option<String>,
+    pub event_types: Vec<String>,
+}
+
+/// Handler for POST /admin/webhooks
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateWebhookRequest>,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    let created_by = requested_by(&headers);
+
+    let result: ExplorerResult<_> = async {
+        validate_webhook(&req)?;
+        let secret = req.secret.clone().unwrap_or_else(|| {
+            format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            )
+        });
+        let subscription = state
+            .repositories
+            .webhook_subscriptions
+            .create(
+                &req.url,
+                &secret,
+                req.app_id.as_deref().filter(|a| !a.is_empty()),
+                &req.event_types,
+                created_by,
+            )
+            .await?;
+        Ok((subscription, secret))
+    }
+    .await;
+
+    match result {
+        Ok((subscription, secret)) => {
+            tracing::info!(
+                "Webhook {} created for {} by {}",
+                subscription.id,
+                subscription.url,
+                created_by
+            );
+            let mut body = json!(subscription);
+            body["secret"] = json!(secret);
+            (StatusCode::CREATED, Json(body)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Handler for GET /admin/webhooks
+pub async fn list_webhooks(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    match state.repositories.webhook_subscriptions.list().await {
+        Ok(subscriptions) => Json(json!({ "webhooks": subscriptions })).into_response(),
+        Err(e) => ExplorerError::from(e).into_response(),
+    }
+}
+
+/// Handler for DELETE /admin/webhooks/{id}
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    match state.repositories.webhook_subscriptions.delete(id).await {
+        Ok(true) => {
+            tracing::info!("Webhook {} deleted by {}", id, requested_by(&headers));
+            Json(json!({ "id": id, "deleted": true })).into_response()
+        }
+        Ok(false) => {
+            ExplorerError::NotFound(format!("Webhook {} not found", id)).into_response()
+        }
+        Err(e) => ExplorerError::from(e).into_response(),
+    }
+}
+
+/// Handler for GET /diagnose/config
+pub async fn diagnose_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !is_authorized(state.config.admin_api_token.as_deref(), &headers) {
+        return forbidden();
+    }
+    let api = state.config.snapshot(&Sources::from_env());
+    // The API snapshot is still useful when the indexer rows can't be read.
+    match state.repositories.control_commands.config_snapshots().await {
+        Ok(indexer) => Json(json!({ "api": api, "indexer": indexer })).into_response(),
+        Err(e) => {
+            tracing::warn!("Failed to read config snapshots: {}", e);
+            Json(json!({
+                "api": api,
+                "indexer": null,
+                "indexer_error": "config snapshots unavailable",
+            }))
+            .into_response()
+        }
+    }
+}
+
+fn validate_webhook(req: &CreateWebhookRequest) -> ExplorerResult<()> {
+    if !(req.url.starts_with("https://") || req.url.starts_with("http://")) {
+        return Err(ExplorerError::InvalidRequest(
+            "url must be an http(s) URL".to_string(),
+        ));
+    }
+    if req.event_types.is_empty() {
+        return Err(ExplorerError::InvalidRequest(
+            "event_types must not be empty".to_string(),
+        ));
+    }
+    if let Some(unknown) = req
+        .event_types
+        .iter()
+        .find(|t| !WEBHOOK_EVENT_TYPES.contains(&t.as_str()))
+    {
+        return Err(ExplorerError::InvalidRequest(format!(
+            "Unknown event type: {} (expected one of {})",
+            unknown,
+            WEBHOOK_EVENT_TYPES.join(", ")
+        )));
+    }
+    if req.secret.as_ref().is_some_and(|s| s.len() < MIN_SECRET_LEN) {
+        return Err(ExplorerError::InvalidRequest(format!(
+            "secret must be at least {} characters",
+            MIN_SECRET_LEN
+        )));
+    }
+    Ok(())
+}
+
+pub(super) fn forbidden() -> axum::response::Response {
+    (StatusCode::FORBIDDEN, Json(json!({ "error": "forbidden" }))).into_response()
+}
+
+/// Audit trail: who asked. Defaults to "admin" for plain curl calls.
+pub(super) fn requested_by(headers: &HeaderMap) -> &str {
+    headers
+        .get("x-admin-user")
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .unwrap_or("admin")
+}
+
+/// Whether a public read should include hidden charms and assets: only
+/// when `?include_hidden=true` comes with the admin token. Without the
+/// token the flag is ignored rather than refused.
+pub(crate) fn reveal_hidden(state: &AppState, headers: &HeaderMap, requested: bool) -> bool {
+    requested && is_authorized(state.config.admin_api_token.as_deref(), headers)
+}
+
+pub(super) fn is_authorized(token: Option<&str>, headers: &HeaderMap) -> bool {
+    let Some(token) = token else {
+        return false;
+    };
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|given| given == token)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::test_support::{app_state, charm, repositories, FakeCharms};
+
+    fn request(url: &str, secret: Option<&str>, events: &[&str]) -> CreateWebhookRequest {
+        CreateWebhookRequest {
+            url: url.to_string(),
+            secret: secret.map(str::to_string),
+            app_id: None,
+            event_types: events.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn validates_webhook_requests() {
+        assert!(validate_webhook(&request("https://x.io/h", None, &["charm_spent"])).is_ok());
+        assert!(validate_webhook(&request(
+            "http://x.io/h",
+            Some("0123456789abcdef"),
+            &["dex_order_created", "dex_order_filled"]
+        ))
+        .is_ok());
+        assert!(validate_webhook(&request("ftp://x.io", None, &["charm_spent"])).is_err());
+        assert!(validate_webhook(&request("https://x.io", None, &[])).is_err());
+        assert!(validate_webhook(&request("https://x.io", None, &["charm_burned"])).is_err());
+        assert!(validate_webhook(&request("https://x.io", Some("short"), &["charm_spent"])).is_err());
+    }
+
+    #[test]
+    fn refresh_needs_either_app_ids_or_all() {
+        let req = |app_ids: Option<&[&str]>, all| RefreshMetadataRequest {
+            app_ids: app_ids.map(|ids| ids.iter().map(|i| i.to_string()).collect()),
+            all,
+            network: None,
+        };
+        assert_eq!(
+            validate_refresh(&req(Some(&["n/aa/01"]), false)).unwrap(),
+            Some(&["n/aa/01".to_string()][..])
+        );
+        assert_eq!(validate_refresh(&req(None, true)).unwrap(), None);
+        assert!(validate_refresh(&req(None, false)).is_err());
+        assert!(validate_refresh(&req(Some(&["n/aa/01"]), true)).is_err());
+        assert!(validate_refresh(&req(Some(&[]), false)).is_err());
+    }
+
+    #[tokio::test]
+    async fn block_timings_need_the_token_and_a_known_network() {
+        let mut state = app_state(repositories());
+        let query = |network: &str| {
+            Query(BlockTimingsQuery {
+                network: Some(network.to_string()),
+                limit: None,
+            })
+        };
+        let denied = list_block_timings(State(state.clone()), query("mainnet"), HeaderMap::new())
+            .await
+            .into_response();
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+
+        state.config.admin_api_token = Some("secret".to_string());
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        let response = list_block_timings(State(state), query("signet"), headers)
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn moderation_needs_the_token_and_a_reason() {
+        let mut state = app_state(repositories());
+        let request = |network: &str, reason: &str| {
+            Json(ModerateRequest {
+                target_type: ModerationTarget::Asset,
+                id: "n/aa/01".to_string(),
+                network: Some(network.to_string()),
+                status: ModerationStatus::Hidden,
+                reason: reason.to_string(),
+            })
+        };
+        let denied = moderate(State(state.clone()), HeaderMap::new(), request("mainnet", "spam"))
+            .await
+            .into_response();
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+
+        state.config.admin_api_token = Some("secret".to_string());
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        for (network, reason) in [("mainnet", "  "), ("signet", "spam")] {
+            let response = moderate(State(state.clone()), headers.clone(), request(network, reason))
+                .await
+                .into_response();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[test]
+    fn moderation_requests_parse_lowercase_names() {
+        let req: ModerateRequest = serde_json::from_value(json!({
+            "target_type": "charm",
+            "id": "abc",
+            "status": "flagged",
+            "reason": "review",
+        }))
+        .unwrap();
+        assert_eq!(req.target_type, ModerationTarget::Charm);
+        assert_eq!(req.status, ModerationStatus::Flagged);
+        assert!(serde_json::from_value::<ModerateRequest>(json!({
+            "target_type": "asset",
+            "id": "n/aa/01",
+            "status": "deleted",
+            "reason": "nope",
+        }))
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn charm_versions_need_the_token_and_group_by_revision() {
+        let mut old = charm("a", "t/a/a");
+        old.vout = 1;
+        let mut current = charm("a", "t/a/a");
+        current.parser_revision = Some(1);
+        current.indexer_version = Some("0.2.0".to_string());
+        let mut testnet = current.clone();
+        testnet.network = "testnet4".to_string();
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![old, current, testnet]));
+        let mut state = app_state(repos);
+
+        let query = || Query(CharmVersionsQuery { network: Some("mainnet".to_string()) });
+        let denied = list_charm_versions(State(state.clone()), query(), HeaderMap::new())
+            .await
+            .into_response();
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+
+        state.config.admin_api_token = Some("secret".to_string());
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        let response = list_charm_versions(State(state), query(), headers)
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["versions"],
+            json!([
+                {"parser_revision": null, "indexer_version": null, "charms": 1},
+                {"parser_revision": 1, "indexer_version": "0.2.0", "charms": 1},
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn config_diagnosis_needs_the_token_and_hides_it() {
+        let mut state = app_state(repositories());
+        let denied = diagnose_config(State(state.clone()), HeaderMap::new())
+            .await
+            .into_response();
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+
+        state.config.admin_api_token = Some("admin-secret".to_string());
+        state.config.bitcoin_testnet4_rpc_password = "rpc-secret".to_string();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            "Bearer admin-secret".parse().unwrap(),
+        );
+        let response = diagnose_config(State(state), headers).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(!text.contains("admin-secret"), "{text}");
+        assert!(!text.contains("rpc-secret"), "{text}");
+        let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(body["api"]["admin_api_token_set"]["value"], true);
+        // The test database is unreachable.
+        assert_eq!(body["indexer"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,378 @@
+// The admin router and its audit trail. Every `/admin/*` route is
+// registered in `admin_routes`, whose `audited` layer answers 403 without
+// the admin token and records each authorized mutation (any method but
+// GET) in `admin_audit_log`: the row is opened before the handler runs,
+// with the path, query and body parameters, a fingerprint of the token and
+// the caller's address, then completed with the status and JSON response.
+//
+// A mutation whose row can't be opened is refused, so the trail has no
+// gaps. One whose row can't be completed has already run; that is only
+// logged. Values under keys naming a credential are redacted from both the
+// parameters and the result.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, MatchedPath, Query, RawPathParams, Request, State},
+    http::Method,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Router,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::admin::{
+    create_webhook, delete_webhook, forbidden, get_metadata_refresh, is_authorized,
+    list_admin_audit, list_backfills, list_block_timings, list_charm_versions, list_webhooks,
+    moderate, pause_indexer, refresh_asset_metadata, request_backfill, requested_by,
+    resume_indexer,
+};
+use super::json_body::MAX_BODY_BYTES;
+use super::reset::reset_indexer;
+use crate::db::repositories::admin_audit_repository::{AuditOutcome, NewAuditEntry};
+use crate::error::ExplorerError;
+use crate::handlers::AppState;
+
+/// Object keys whose values never reach the audit log.
+const REDACTED_KEYS: [&str; 4] = ["secret", "token", "password", "authorization"];
+
+/// Longest non-JSON body kept, in characters.
+const MAX_TEXT_CHARS: usize = 1024;
+
+/// Every admin route, behind `audited`.
+pub fn admin_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/admin/reset", post(reset_indexer))
+        .route("/admin/indexer/{network}/pause", post(pause_indexer))
+        .route("/admin/indexer/{network}/resume", post(resume_indexer))
+        .route("/admin/webhooks", post(create_webhook).get(list_webhooks))
+        .route("/admin/webhooks/{id}", delete(delete_webhook))
+        .route("/admin/charms/versions", get(list_charm_versions))
+        .route(
+            "/admin/assets/refresh-metadata",
+            post(refresh_asset_metadata),
+        )
+        .route(
+            "/admin/assets/refresh-metadata/{job_id}",
+            get(get_metadata_refresh),
+        )
+        .route("/admin/backfills", get(list_backfills))
+        .route("/admin/backfills/{name}", post(request_backfill))
+        .route("/admin/block-timings", get(list_block_timings))
+        .route("/admin/moderate", post(moderate))
+        .route("/admin/audit", get(list_admin_audit))
+        .route_layer(middleware::from_fn_with_state(state, audited))
+}
+
+/// Token check for every admin route, and the audit row around mutations.
+async fn audited(
+    State(state): State<AppState>,
+    matched: MatchedPath,
+    path: RawPathParams,
+    req: Request,
+    next: Next,
+) -> Response {
+    let token = state.config.admin_api_token.as_deref();
+    if !is_authorized(token, req.headers()) {
+        return forbidden();
+    }
+    if req.method() == Method::GET {
+        return next.run(req).await;
+    }
+
+    // Mounted under /v1 and /; both are the same action.
+    let route = matched.as_str();
+    let action = format!(
+        "{} {}",
+        req.method(),
+        route.strip_prefix("/v1").unwrap_or(route)
+    );
+    let (parts, body) = req.into_parts();
+    let Ok(body) = to_bytes(body, MAX_BODY_BYTES).await else {
+        return ExplorerError::InvalidRequest(format!(
+            "request body exceeds {} bytes",
+            MAX_BODY_BYTES
+        ))
+        .into_response();
+    };
+    let query = Query::<BTreeMap<String, String>>::try_from_uri(&parts.uri)
+        .map(|Query(query)| query)
+        .unwrap_or_default();
+    let path: BTreeMap<&str, &str> = path.iter().collect();
+    let entry = NewAuditEntry {
+        action,
+        parameters: redacted(json!({
+            "path": path,
+            "query": query,
+            "body": parse_body(&body),
+        })),
+        key_fingerprint: token.map(fingerprint),
+        source_ip: source_ip(&parts),
+        requested_by: requested_by(&parts.headers).to_string(),
+    };
+
+    let audit = &state.repositories.admin_audit;
+    let id = match audit.begin(&entry).await {
+        Ok(id) => id,
+        Err(e) => {
+            tracing::error!("Refusing {}: audit log unavailable: {}", entry.action, e);
+            return ExplorerError::InternalError(
+                "audit log unavailable; admin mutations are refused until it is back".to_string(),
+            )
+            .into_response();
+        }
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, usize::MAX).await.unwrap_or_else(|e| {
+        tracing::warn!("Audit {}: could not read the response: {}", id, e);
+        Default::default()
+    });
+    let outcome = AuditOutcome {
+        success: parts.status.is_success(),
+        status_code: parts.status.as_u16(),
+        result: redacted(parse_body(&body)),
+    };
+    if let Err(e) = audit.finish(id, &outcome).await {
+        tracing::warn!(
+            "{} by {} ran, but audit row {} was not completed: {}",
+            entry.action,
+            entry.requested_by,
+            id,
+            e
+        );
+    }
+    tracing::info!(
+        "{} by {} ({}): {}",
+        entry.action,
+        entry.requested_by,
+        entry.key_fingerprint.as_deref().unwrap_or("-"),
+        parts.status
+    );
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// `sha256:` and the first 12 hex digits of the token's digest: enough to
+/// tell keys apart, useless for recovering one.
+fn fingerprint(token: &str) -> String {
+    let digest = Sha256::digest(token.as_bytes());
+    let hex: String = digest[..6].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256:{}", hex)
+}
+
+/// The first `X-Forwarded-For` hop (the API runs behind a proxy), else the
+/// peer address.
+fn source_ip(parts: &axum::http::request::Parts) -> Option<String> {
+    parts
+        .headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .or_else(|| {
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        })
+}
+
+/// JSON bodies as parsed, others as (truncated) text, empty ones as null.
+fn parse_body(bytes: &[u8]) -> Value {
+    if bytes.is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_slice(bytes).unwrap_or_else(|_| {
+        Value::String(
+            String::from_utf8_lossy(bytes)
+                .chars()
+                .take(MAX_TEXT_CHARS)
+                .collect(),
+        )
+    })
+}
+
+fn redacted(mut value: Value) -> Value {
+    redact(&mut value);
+    value
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if REDACTED_KEYS.iter().any(|k| key.contains(k)) {
+                    *v = Value::String("[redacted]".to_string());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::http::{header, StatusCode};
+    use tower::Service;
+
+    use super::*;
+    use crate::db::repositories::admin_audit_repository::AuditEntry;
+    use crate::test_support::{app_state, repositories, FakeAudit};
+
+    const TOKEN: &str = "s3cret-admin-token";
+
+    fn app(audit: Arc<FakeAudit>) -> Router {
+        let mut repos = repositories();
+        repos.admin_audit = audit;
+        let mut state = app_state(repos);
+        state.config.admin_api_token = Some(TOKEN.to_string());
+        admin_routes(state.clone()).with_state(state)
+    }
+
+    async fn send(app: &mut Router, method: Method, uri: &str, body: Value) -> (StatusCode, Value) {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-admin-user", "ops")
+            .header("x-forwarded-for", "203.0.113.7, 10.0.0.2")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.call(req).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, parse_body(&bytes))
+    }
+
+    fn entries(audit: &FakeAudit) -> Vec<AuditEntry> {
+        audit.entries.lock().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn a_reset_call_leaves_one_row_with_its_parameters_and_outcome() {
+        let audit = Arc::new(FakeAudit::default());
+        let mut app = app(audit.clone());
+
+        // The test database is unreachable, so the reset fails.
+        let (status, body) = send(
+            &mut app,
+            Method::POST,
+            "/admin/reset?confirm=yes",
+            json!({ "reason": "fresh start" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["success"], json!(false));
+
+        let entries = entries(&audit);
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.action, "POST /admin/reset");
+        assert_eq!(
+            entry.parameters,
+            json!({
+                "path": {},
+                "query": { "confirm": "yes" },
+                "body": { "reason": "fresh start" },
+            })
+        );
+        assert_eq!(entry.requested_by, "ops");
+        assert_eq!(entry.source_ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(entry.key_fingerprint, Some(fingerprint(TOKEN)));
+        assert!(entry.finished_at.is_some());
+        assert_eq!(entry.success, Some(false));
+        assert_eq!(entry.status_code, Some(500));
+        let result = entry.result.as_ref().unwrap();
+        assert_eq!(result["tables_cleared"]["charms"], json!(false));
+        assert_eq!(result["rows_deleted"]["charms"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn path_parameters_are_recorded_and_secrets_redacted() {
+        let audit = Arc::new(FakeAudit::default());
+        let mut app = app(audit.clone());
+
+        send(
+            &mut app,
+            Method::POST,
+            "/admin/indexer/testnet4/pause",
+            Value::Null,
+        )
+        .await;
+        send(
+            &mut app,
+            Method::POST,
+            "/admin/webhooks",
+            json!({
+                "url": "https://hooks.example/charms",
+                "secret": "0123456789abcdef0123",
+                "event_types": ["charm_spent"],
+            }),
+        )
+        .await;
+
+        let entries = entries(&audit);
+        assert_eq!(entries[0].action, "POST /admin/indexer/{network}/pause");
+        assert_eq!(
+            entries[0].parameters["path"],
+            json!({ "network": "testnet4" })
+        );
+        assert_eq!(entries[0].parameters["body"], Value::Null);
+        assert_eq!(entries[1].parameters["body"]["secret"], json!("[redacted]"));
+        assert_eq!(
+            entries[1].parameters["body"]["url"],
+            json!("https://hooks.example/charms")
+        );
+        let fingerprint = entries[1].key_fingerprint.as_deref().unwrap();
+        assert!(fingerprint.starts_with("sha256:") && !fingerprint.contains(TOKEN));
+    }
+
+    #[tokio::test]
+    async fn reads_and_refused_calls_leave_no_row() {
+        let audit = Arc::new(FakeAudit::default());
+        let mut app = app(audit.clone());
+
+        let (status, body) = send(&mut app, Method::GET, "/admin/audit", Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["entries"], json!([]));
+
+        let unauthorized = Request::builder()
+            .method(Method::POST)
+            .uri("/admin/reset")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.call(unauthorized).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(entries(&audit).is_empty());
+    }
+
+    #[tokio::test]
+    async fn mutations_are_refused_while_the_audit_log_is_down() {
+        let mut app = app(Arc::new(FakeAudit::failing()));
+        let (status, body) = send(&mut app, Method::POST, "/admin/reset", Value::Null).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(
+            body["error"]
+                .as_str()
+                .unwrap()
+                .contains("audit log unavailable"),
+            "{}",
+            body
+        );
+        assert_eq!(body.get("tables_cleared"), None, "the reset never ran");
+    }
+}
@@ -1,13 +1,21 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
+use charms_core::AppKind;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::db::repositories::moderation_repository::is_hidden;
+use crate::handlers::admin::reveal_hidden;
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::path_params::{AppIdPath, AssetIdPath, HashPath};
 use crate::handlers::AppState;
+use crate::models::{AssetSort, PageLimits, PaginationMeta};
 use crate::services::asset_service::AssetService;
+use crate::services::decimals_service::DecimalsResolver;
 
 /// Normalize image value - handles both URLs and base64 data
 /// People sometimes put URLs in the 'image' field instead of 'image_url'
@@ -96,18 +104,23 @@ fn extract_asset_metadata_from_charm(
 #[derive(Debug, Deserialize)]
 pub struct AssetQueryParams {
     pub asset_type: Option<String>,
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
     pub network: Option<String>,
     pub page: Option<u64>,
     pub limit: Option<u64>,
-    #[allow(dead_code)]
-    pub sort: Option<String>,
+    /// `newest` (default), `oldest` or `likes_desc`
+    #[serde(default)]
+    pub sort: AssetSort,
     pub app_id: Option<String>,
+    /// Also return hidden assets; honoured only with the admin token
+    #[serde(default)]
+    pub include_hidden: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct AssetResponse {
     pub data: AssetData,
-    pub pagination: PaginationInfo,
+    pub pagination: PaginationMeta,
 }
 
 #[derive(Debug, Serialize)]
@@ -125,6 +138,12 @@ pub struct AssetItem {
     pub description: Option<String>,
     pub image_url: Option<String>,
     pub total_supply: Option<i64>,
+    pub total_supply_formatted: Option<String>,
+    /// Unspent supply resting in open DEX orders or at lock addresses
+    pub locked_supply: i64,
+    /// `total_supply - locked_supply`; null when the total is unknown
+    pub circulating_supply: Option<i64>,
+    pub circulating_supply_formatted: Option<String>,
     pub decimals: i16, // [RJJ-DECIMALS] Dynamic decimal precision
     pub network: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -132,25 +151,43 @@ pub struct AssetItem {
     // Additional fields for compatibility with charm structure
     pub block_height: Option<i32>,
     pub transaction_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    /// First-seen (deploy) transaction; null until the indexer recorded it
+    pub deploy_txid: Option<String>,
+    pub deploy_block_height: Option<i32>,
+    pub deployer_address: Option<String>,
+    /// Off-chain metadata document; only filled on asset detail
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offchain: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct PaginationInfo {
-    pub page: u64,
-    pub limit: u64,
-    pub total: u64,
-    pub total_pages: u64,
+/// `(total, locked, circulating)` supply in raw units. Circulating supply
+/// never goes below zero, even while a stale lock outweighs the total.
+pub(crate) fn supply_split(
+    total_supply: Option<Decimal>,
+    locked_supply: Decimal,
+) -> (Option<i64>, i64, Option<i64>) {
+    let raw = |d: Decimal| d.to_string().parse::<i64>().unwrap_or(0);
+    let total = total_supply.map(raw);
+    let locked = raw(locked_supply);
+    (total, locked, total.map(|t| (t - locked).max(0)))
 }
 
-/// Get assets with optional filtering by type, network, and app_id
+/// Get assets with optional filtering by type, network, and app_id.
+/// Hidden assets are left out unless revealed to an admin.
 pub async fn get_assets(
     Query(params): Query<AssetQueryParams>,
     State(state): State<AppState>,
+    headers: HeaderMap,
+    links: PageLinks,
 ) -> Result<Json<AssetResponse>, StatusCode> {
     let asset_service = AssetService::new(state.repositories.asset_repository.clone());
+    let include_hidden = reveal_hidden(&state, &headers, params.include_hidden);
 
-    let page = params.page.unwrap_or(1);
-    let limit = params.limit.unwrap_or(20);
+    let (page, limit) = PageLimits::global()
+        .apply(params.page.unwrap_or(1), params.limit.unwrap_or(20))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
     let offset = (page - 1) * limit;
 
     // If app_id is provided, search by app_id directly. Network scope is
@@ -158,8 +195,10 @@ pub async fn get_assets(
     let network = params.network.as_deref().unwrap_or("mainnet");
     let (assets, total) = if let Some(ref app_id) = params.app_id {
         match asset_service.get_asset_by_app_id(app_id, network).await {
-            Ok(Some(asset)) => (vec![asset], 1u64),
-            Ok(None) => (vec![], 0u64),
+            Ok(Some(asset)) if include_hidden || !is_hidden(&asset.moderation_status) => {
+                (vec![asset], 1u64)
+            }
+            Ok(_) => (vec![], 0u64),
             Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
         }
     } else {
@@ -167,8 +206,10 @@ pub async fn get_assets(
             .get_assets_paginated(
                 params.asset_type.as_deref(),
                 params.network.as_deref(),
+                params.sort,
                 limit,
                 offset,
+                include_hidden,
             )
             .await
         {
@@ -196,6 +237,9 @@ pub async fn get_assets(
                 |s: Option<String>| s.filter(|v: &String| !v.is_empty());
             let mut vk_cache: HashMap<String, Option<crate::entity::assets::Model>> =
                 HashMap::new();
+            let mut decimals =
+                DecimalsResolver::new(state.repositories.asset_repository.as_ref());
+            decimals.resolve_assets(&assets).await;
 
             let mut asset_items = Vec::new();
             for asset in assets {
@@ -219,7 +263,7 @@ pub async fn get_assets(
                 // Cross-network fallback by app_vk for tokens whose own NFT
                 // anchor lives in another network (FIRE-style fixed-string
                 // tickers).
-                if asset.app_id.starts_with("t/")
+                if AppKind::of(&asset.app_id) == AppKind::Token
                     && (name.is_none() || image_url.is_none() || description.is_none())
                 {
                     if let Some(vk) = asset
@@ -258,38 +302,42 @@ pub async fn get_assets(
                     }
                 }
 
+                let (total_supply, locked_supply, circulating_supply) =
+                    supply_split(asset.total_supply, asset.locked_supply);
                 asset_items.push(AssetItem {
                     id: asset.id.to_string(),
+                    total_supply_formatted: total_supply
+                        .map(|raw| decimals.format(&asset.network, &asset.app_id, raw)),
+                    circulating_supply_formatted: circulating_supply
+                        .map(|raw| decimals.format(&asset.network, &asset.app_id, raw)),
+                    decimals: decimals.get(&asset.network, &asset.app_id) as i16, // [RJJ-DECIMALS]
                     app_id: asset.app_id,
                     asset_type: asset.asset_type,
                     name,
                     symbol,
                     description,
                     image_url,
-                    total_supply: asset
-                        .total_supply
-                        .map(|d| d.to_string().parse::<i64>().unwrap_or(0)),
-                    decimals: asset.decimals, // [RJJ-DECIMALS]
+                    total_supply,
+                    locked_supply,
+                    circulating_supply,
                     network: asset.network,
                     created_at: asset.created_at,
                     updated_at: asset.updated_at,
                     block_height: Some(asset.block_height),
                     transaction_hash: Some(asset.txid),
+                    collection: asset.collection,
+                    deploy_txid: asset.deploy_txid,
+                    deploy_block_height: asset.deploy_block_height,
+                    deployer_address: asset.deployer_address,
+                    offchain: None,
                 });
             }
 
-            let total_pages = total.div_ceil(limit); // Ceiling division
-
             let response = AssetResponse {
                 data: AssetData {
                     assets: asset_items,
                 },
-                pagination: PaginationInfo {
-                    page,
-                    limit,
-                    total,
-                    total_pages,
-                },
+                pagination: links.meta(page, limit, total),
             };
 
             Ok(Json(response))
@@ -304,6 +352,7 @@ pub async fn get_assets(
 /// Query params for asset counts
 #[derive(Debug, Deserialize)]
 pub struct AssetCountParams {
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
     pub network: Option<String>,
 }
 
@@ -347,15 +396,17 @@ pub struct ReferenceNftResponse {
 /// This endpoint is used by the frontend to fetch the image from the reference NFT
 /// when displaying a token, avoiding storing duplicate images in the database
 pub async fn get_reference_nft_by_hash(
-    axum::extract::Path(hash): axum::extract::Path<String>,
+    HashPath(hash): HashPath,
     Query(params): Query<AssetQueryParams>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<Json<ReferenceNftResponse>, StatusCode> {
     let asset_service = AssetService::new(state.repositories.asset_repository.clone());
     let network = params.network.as_deref().unwrap_or("mainnet");
+    let include_hidden = reveal_hidden(&state, &headers, params.include_hidden);
 
     match asset_service.get_reference_nft_by_hash(&hash, network).await {
-        Ok(Some(nft)) => {
+        Ok(Some(nft)) if include_hidden || !is_hidden(&nft.moderation_status) => {
             let mut image_url = nft.image_url;
 
             // If no image_url in asset, try to extract from charm data
@@ -380,7 +431,7 @@ pub async fn get_reference_nft_by_hash(
                 cardano_fingerprint: nft.cardano_fingerprint,
             }))
         }
-        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Error fetching reference NFT by hash: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -388,35 +439,39 @@ pub async fn get_reference_nft_by_hash(
     }
 }
 
-/// Get a specific asset by ID
+/// Get a specific asset by ID; a hidden one is 404 unless revealed to an
+/// admin
 pub async fn get_asset_by_id(
-    axum::extract::Path(asset_id): axum::extract::Path<String>,
+    asset_id: AssetIdPath,
     Query(params): Query<AssetQueryParams>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<Json<AssetItem>, StatusCode> {
     let asset_service = AssetService::new(state.repositories.asset_repository.clone());
     let network = params.network.as_deref().unwrap_or("mainnet");
+    let include_hidden = reveal_hidden(&state, &headers, params.include_hidden);
 
-    // Try to parse asset_id as UUID, if it fails try as app_id
-    let asset_result = if let Ok(id) = asset_id.parse::<i32>() {
-        asset_service.get_asset_by_id(id).await
-    } else {
-        asset_service.get_asset_by_app_id(&asset_id, network).await
+    let asset_result = match &asset_id {
+        AssetIdPath::Id(id) => asset_service.get_asset_by_id(*id).await,
+        AssetIdPath::AppId(app_id) => asset_service.get_asset_by_app_id(app_id, network).await,
     };
 
     match asset_result {
-        Ok(Some(asset)) => {
+        Ok(Some(asset)) if include_hidden || !is_hidden(&asset.moderation_status) => {
             let mut name = asset.name;
             let mut symbol = asset.symbol;
             let mut description = asset.description;
             let mut image_url = asset.image_url;
-            let _decimals = asset.decimals; // Keep for potential future use
+            let decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref())
+                .resolve_decimals(&asset.network, &asset.app_id)
+                .await;
 
             // [RJJ-TOKEN-METADATA] If this is a token, try to inherit metadata from reference NFT
-            let total_supply = asset.total_supply;
-            if asset.app_id.starts_with("t/") {
+            let (total_supply, locked_supply, circulating_supply) =
+                supply_split(asset.total_supply, asset.locked_supply);
+            if AppKind::of(&asset.app_id) == AppKind::Token {
                 // Convert t/HASH/... to n/HASH/... to find reference NFT (same network)
-                let nft_app_id = asset.app_id.replacen("t/", "n/", 1);
+                let nft_app_id = charms_core::token_to_nft(&asset.app_id);
 
                 // Try to find the reference NFT
                 if let Ok(Some(nft_asset)) = asset_service.get_asset_by_app_id(&nft_app_id, network).await {
@@ -471,21 +526,422 @@ pub async fn get_asset_by_id(
                 symbol,
                 description,
                 image_url,
-                total_supply: total_supply.map(|d| d.to_string().parse::<i64>().unwrap_or(0)),
-                decimals: asset.decimals, // [RJJ-DECIMALS]
+                total_supply,
+                total_supply_formatted: total_supply
+                    .map(|raw| charms_core::format_amount(raw, decimals)),
+                locked_supply,
+                circulating_supply,
+                circulating_supply_formatted: circulating_supply
+                    .map(|raw| charms_core::format_amount(raw, decimals)),
+                decimals: decimals as i16, // [RJJ-DECIMALS]
                 network: asset.network,
                 created_at: asset.created_at,
                 updated_at: asset.updated_at,
                 block_height: Some(asset.block_height),
                 transaction_hash: Some(asset.txid),
+                collection: asset.collection,
+                deploy_txid: asset.deploy_txid,
+                deploy_block_height: asset.deploy_block_height,
+                deployer_address: asset.deployer_address,
+                offchain: asset.offchain_metadata,
             };
 
             Ok(Json(asset_item))
         }
-        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             tracing::error!("Error fetching asset by ID: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
+
+/// Window for `recent_likes` in `GET /assets/{app_id}/likes`.
+const RECENT_LIKES_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize)]
+pub struct AssetLikesResponse {
+    pub app_id: String,
+    pub total_likes: i64,
+    pub recent_likes: i64,
+    pub window_days: i64,
+}
+
+/// Like totals for one app_id: all time and the last seven days
+pub async fn get_asset_likes(
+    AppIdPath(app_id): AppIdPath,
+    State(state): State<AppState>,
+) -> Result<Json<AssetLikesResponse>, StatusCode> {
+    let likes = &state.repositories.likes;
+    let since = chrono::Utc::now() - chrono::Duration::days(RECENT_LIKES_DAYS);
+    let counts = match likes.get_likes_count(&app_id).await {
+        Ok(total) => likes
+            .count_since(&app_id, since)
+            .await
+            .map(|recent| (total, recent)),
+        Err(e) => Err(e),
+    };
+
+    match counts {
+        Ok((total_likes, recent_likes)) => Ok(Json(AssetLikesResponse {
+            app_id,
+            total_likes,
+            recent_likes,
+            window_days: RECENT_LIKES_DAYS,
+        })),
+        Err(e) => {
+            tracing::error!("Error counting likes for {}: {:?}", app_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::test_support::{
+        app_state, asset, charm, repositories, FakeAssets, FakeCharms, FakeLikes,
+    };
+
+    fn links() -> PageLinks {
+        PageLinks::new("", &"/v1/assets".parse().unwrap())
+    }
+
+    fn params(page: Option<u64>, limit: Option<u64>) -> AssetQueryParams {
+        AssetQueryParams {
+            asset_type: None,
+            network: None,
+            page,
+            limit,
+            sort: AssetSort::Newest,
+            app_id: None,
+            include_hidden: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn get_assets_paginates_and_fills_metadata_from_charms() {
+        let mut minted = charm("tx1", "n/a/a");
+        minted.data = serde_json::json!({
+            "native_data": {"tx": {"outs": [{"0": {"name": "From spell", "image": "ipfs://x"}}]}}
+        });
+        let mut named = asset(2, "n/b/b", "nft");
+        named.name = Some("Own name".to_string());
+        let mut repos = repositories();
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![
+            asset(1, "n/a/a", "nft"),
+            named,
+            asset(3, "n/c/c", "nft"),
+        ]));
+        repos.charm = Arc::new(FakeCharms::new(vec![minted]));
+        let state = app_state(repos);
+
+        let Json(first) = get_assets(
+            Query(params(None, Some(2))),
+            State(state.clone()),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.pagination.total, 3);
+        assert_eq!(first.pagination.total_pages, 2);
+        let names: Vec<_> = first.data.assets.iter().map(|a| a.name.as_deref()).collect();
+        assert_eq!(names, [Some("From spell"), Some("Own name")]);
+        assert_eq!(first.data.assets[0].image_url.as_deref(), Some("ipfs://x"));
+
+        let Json(second) = get_assets(
+            Query(params(Some(2), Some(2))),
+            State(state),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(second.data.assets.len(), 1);
+        assert_eq!(second.data.assets[0].app_id, "n/c/c");
+    }
+
+    #[tokio::test]
+    async fn get_assets_clamps_limit_and_rejects_deep_pages() {
+        let limits = PageLimits::global();
+        let mut repos = repositories();
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![asset(1, "n/a/a", "nft")]));
+        let state = app_state(repos);
+
+        let Json(resp) = get_assets(
+            Query(params(Some(0), Some(u64::MAX))),
+            State(state.clone()),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.pagination.page, 1);
+        assert_eq!(resp.pagination.limit, limits.max_limit);
+        assert_eq!(resp.data.assets.len(), 1);
+
+        let deep = limits.max_offset / limits.max_limit + 2;
+        let err = get_assets(
+            Query(params(Some(deep), Some(limits.max_limit))),
+            State(state),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn asset_store_errors_become_500() {
+        let mut repos = repositories();
+        repos.asset_repository = Arc::new(FakeAssets::failing());
+        let state = app_state(repos);
+
+        let err = get_assets(
+
+            Query(params(None, None)),
+
+            State(state.clone()),
+
+            HeaderMap::new(),
+
+            links(),
+
+        )
+
+        .await
+
+        .unwrap_err();
+        assert_eq!(err, StatusCode::INTERNAL_SERVER_ERROR);
+        let err = get_asset_counts(Query(AssetCountParams { network: None }), State(state))
+            .await
+            .unwrap_err();
+        assert_eq!(err, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn asset_likes_split_total_and_last_seven_days() {
+        let old = chrono::Utc::now() - chrono::Duration::days(30);
+        let mut repos = repositories();
+        repos.likes = Arc::new(
+            FakeLikes::new(&[("n/a/a", 1), ("n/a/a", 2), ("n/b/b", 1)])
+                .with_like_at("n/a/a", 3, old),
+        );
+        let state = app_state(repos);
+
+        let Json(likes) = get_asset_likes(AppIdPath("n/a/a".to_string()), State(state.clone()))
+            .await
+            .unwrap();
+        assert_eq!((likes.total_likes, likes.recent_likes), (3, 2));
+        assert_eq!(likes.window_days, 7);
+
+        let Json(none) = get_asset_likes(AppIdPath("n/z/z".to_string()), State(state))
+            .await
+            .unwrap();
+        assert_eq!((none.total_likes, none.recent_likes), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn offchain_metadata_shows_on_detail_only() {
+        let mut linked = asset(1, "n/a/a", "nft");
+        linked.name = Some("Linked".to_string());
+        linked.image_url = Some("ipfs://meta.json".to_string());
+        linked.offchain_metadata = Some(serde_json::json!({"attributes": [{"value": "gold"}]}));
+        let mut repos = repositories();
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![linked]));
+        let state = app_state(repos);
+
+        let Json(detail) = get_asset_by_id(
+            AssetIdPath::Id(1),
+            Query(params(None, None)),
+            State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap();
+        let detail = serde_json::to_value(detail).unwrap();
+        assert_eq!(detail["offchain"]["attributes"][0]["value"], "gold");
+
+        let Json(list) = get_assets(
+
+            Query(params(None, None)),
+
+            State(state),
+
+            HeaderMap::new(),
+
+            links(),
+
+        )
+
+        .await
+
+        .unwrap();
+        let listed = serde_json::to_value(&list.data.assets[0]).unwrap();
+        assert!(listed.get("offchain").is_none());
+    }
+
+    #[tokio::test]
+    async fn deploy_origin_shows_on_detail_and_listing() {
+        let mut deployed = asset(1, "t/a/a", "token");
+        deployed.txid = "latest-write".to_string();
+        deployed.deploy_txid = Some("deploy".to_string());
+        deployed.deploy_block_height = Some(90);
+        deployed.deployer_address = Some("bc1qdeployer".to_string());
+        let mut repos = repositories();
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![deployed]));
+        let state = app_state(repos);
+
+        let Json(detail) = get_asset_by_id(
+            AssetIdPath::Id(1),
+            Query(params(None, None)),
+            State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap();
+        let Json(list) = get_assets(
+            Query(params(None, None)),
+            State(state),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        let listed = serde_json::to_value(&list.data.assets[0]).unwrap();
+        for item in [serde_json::to_value(detail).unwrap(), listed] {
+            assert_eq!(item["deploy_txid"], "deploy");
+            assert_eq!(item["deploy_block_height"], 90);
+            assert_eq!(item["deployer_address"], "bc1qdeployer");
+            assert_eq!(item["transaction_hash"], "latest-write");
+        }
+    }
+
+    #[tokio::test]
+    async fn supply_in_open_orders_is_not_circulating() {
+        let mut token = asset(1, "t/a/a", "token");
+        token.total_supply = Some(Decimal::from(150_000_000_i64));
+        token.locked_supply = Decimal::from(40_000_000_i64);
+        let mut repos = repositories();
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![token]));
+        let state = app_state(repos);
+
+        let Json(detail) = get_asset_by_id(
+            AssetIdPath::Id(1),
+            Query(params(None, None)),
+            State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap();
+        let Json(list) = get_assets(
+            Query(params(None, None)),
+            State(state),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        let listed = serde_json::to_value(&list.data.assets[0]).unwrap();
+        for item in [serde_json::to_value(detail).unwrap(), listed] {
+            assert_eq!(item["total_supply"], 150_000_000);
+            assert_eq!(item["locked_supply"], 40_000_000);
+            assert_eq!(item["circulating_supply"], 110_000_000);
+            assert_eq!(item["circulating_supply_formatted"], "1.1");
+        }
+
+        assert_eq!(
+            supply_split(Some(Decimal::from(5)), Decimal::from(9)),
+            (Some(5), 9, Some(0))
+        );
+        assert_eq!(supply_split(None, Decimal::ZERO), (None, 0, None));
+    }
+
+    #[tokio::test]
+    async fn hidden_assets_leave_listings_and_detail_unless_an_admin_asks() {
+        let mut hidden = asset(2, "n/bad/bad", "nft");
+        hidden.moderation_status = "hidden".to_string();
+        let mut repos = repositories();
+        repos.asset_repository =
+            Arc::new(FakeAssets::new(vec![asset(1, "n/a/a", "nft"), hidden]));
+        let mut state = app_state(repos);
+        state.config.admin_api_token = Some("secret".to_string());
+        let mut admin = HeaderMap::new();
+        admin.insert(http::header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        let revealing = || AssetQueryParams {
+            include_hidden: true,
+            ..params(None, None)
+        };
+        let listed = |resp: AssetResponse| -> Vec<String> {
+            resp.data.assets.into_iter().map(|a| a.app_id).collect()
+        };
+
+        let Json(public) = get_assets(
+            Query(params(None, None)),
+            State(state.clone()),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(public.pagination.total, 1);
+        assert_eq!(listed(public), ["n/a/a"]);
+        let Json(no_token) = get_assets(
+            Query(revealing()),
+            State(state.clone()),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed(no_token), ["n/a/a"]);
+        let by_app_id = AssetQueryParams {
+            app_id: Some("n/bad/bad".to_string()),
+            ..params(None, None)
+        };
+        let Json(looked_up) = get_assets(
+            Query(by_app_id),
+            State(state.clone()),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(looked_up.pagination.total, 0);
+        let err = get_asset_by_id(
+            AssetIdPath::Id(2),
+            Query(params(None, None)),
+            State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, StatusCode::NOT_FOUND);
+
+        let Json(revealed) = get_assets(
+
+            Query(revealing()),
+
+            State(state.clone()),
+
+            admin.clone(),
+
+            links(),
+
+        )
+
+        .await
+
+        .unwrap();
+        assert_eq!(listed(revealed), ["n/a/a", "n/bad/bad"]);
+        let Json(detail) =
+            get_asset_by_id(AssetIdPath::Id(2), Query(revealing()), State(state), admin)
+                .await
+                .unwrap();
+        assert_eq!(detail.app_id, "n/bad/bad");
+    }
+}
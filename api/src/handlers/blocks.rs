@@ -0,0 +1,190 @@
+// Block handlers: a browsable list of processed blocks and a per-block view
+// with the spells, charms and DEX orders the indexer stored at that height
+// and the charm UTXOs the block spent.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::db::repositories::blocks_repository::BlockCharm;
+use crate::error::{ExplorerError, ExplorerResult};
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::AppState;
+use crate::services::dex_orders_service;
+
+fn default_network() -> String {
+    "mainnet".to_string()
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_limit() -> u64 {
+    50
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlocksQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+    #[serde(default = "default_page")]
+    pub page: u64,
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlockQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+}
+
+/// GET /blocks?network=mainnet&page=1&limit=50
+/// Processed blocks, newest first, with the number of charms at each.
+pub async fn get_blocks(
+    State(state): State<AppState>,
+    Query(params): Query<BlocksQuery>,
+    links: PageLinks,
+) -> ExplorerResult<Json<Value>> {
+    let limit = params.limit.clamp(1, 100);
+    let page = params.page.max(1);
+    let (blocks, total) = state
+        .repositories
+        .blocks
+        .list(&params.network, limit, (page - 1) * limit)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+
+    let total_pages = total.div_ceil(limit);
+    let mut body = json!({
+        "network": params.network,
+        "blocks": blocks,
+        "page": page,
+        "limit": limit,
+        "total": total,
+        "total_pages": total_pages,
+    });
+    links.nav(page, total_pages).extend(&mut body);
+    Ok(Json(body))
+}
+
+/// GET /blocks/{height}?network=mainnet
+/// One processed block (hash and header time included) with its spells,
+/// its charms grouped by transaction, the DEX orders created, filled or
+/// cancelled in it, and the number of charm UTXOs it spent. 404 (with the
+/// current tip) for blocks the indexer has not processed yet.
+pub async fn get_block(
+    State(state): State<AppState>,
+    Path(height): Path<i32>,
+    Query(params): Query<BlockQuery>,
+) -> ExplorerResult<Json<Value>> {
+    let blocks = &state.repositories.blocks;
+    let block = blocks
+        .get(height, &params.network)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+    let Some(block) = block else {
+        let tip = blocks
+            .tip(&params.network)
+            .await
+            .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+        return Err(ExplorerError::NotFound(match tip {
+            Some(tip) => format!(
+                "Block {} not processed on {}; indexer tip is {}",
+                height, params.network, tip
+            ),
+            None => format!("No blocks processed on {} yet", params.network),
+        }));
+    };
+
+    let spells = blocks
+        .spells(height, &params.network)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+    let charms = blocks
+        .charms(height, &params.network)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+    // Fills and cancels are stored as activity rows at the block's height.
+    let dex = dex_orders_service::get_orders_by_block(&state, height, &params.network).await?;
+    let spent = blocks
+        .spent_count(height, &params.network)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+
+    Ok(Json(json!({
+        "network": params.network,
+        "block": block,
+        "spells": spells,
+        "charms": by_transaction(charms),
+        "dex_orders": dex.orders,
+        "spent_charm_utxos": spent,
+    })))
+}
+
+/// `charms` (in block order) as `[{txid, charms}]`, one entry per
+/// transaction.
+fn by_transaction(charms: Vec<BlockCharm>) -> Vec<Value> {
+    let mut groups: Vec<(String, Vec<BlockCharm>)> = Vec::new();
+    for charm in charms {
+        match groups.last_mut() {
+            Some((txid, group)) if *txid == charm.txid => group.push(charm),
+            _ => groups.push((charm.txid.clone(), vec![charm])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(txid, charms)| json!({ "txid": txid, "charms": charms }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn charm(txid: &str, vout: i32, app_id: &str) -> BlockCharm {
+        BlockCharm {
+            txid: txid.to_string(),
+            vout,
+            app_id: app_id.to_string(),
+            asset_type: "token".to_string(),
+            amount: 1,
+            address: None,
+            spent: false,
+            tags: None,
+            verified: true,
+        }
+    }
+
+    #[test]
+    fn charms_are_grouped_by_transaction_in_block_order() {
+        let grouped = by_transaction(vec![
+            charm("mint", 0, "t/a/a"),
+            charm("transfer", 0, "t/a/a"),
+            charm("transfer", 0, "t/b/b"),
+            charm("transfer", 1, "t/a/a"),
+        ]);
+        let summary: Vec<(&str, usize)> = grouped
+            .iter()
+            .map(|g| {
+                (
+                    g["txid"].as_str().unwrap(),
+                    g["charms"].as_array().unwrap().len(),
+                )
+            })
+            .collect();
+        assert_eq!(summary, [("mint", 1), ("transfer", 3)]);
+        assert_eq!(grouped[1]["charms"][1]["app_id"], "t/b/b");
+        assert!(by_transaction(vec![]).is_empty());
+    }
+}
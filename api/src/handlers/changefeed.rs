@@ -0,0 +1,53 @@
+// Changefeed handler: the indexer's append-only log of row changes, paged
+// by id for incremental replication.
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::{ExplorerError, ExplorerResult};
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::AppState;
+
+fn default_limit() -> u64 {
+    500
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangefeedQuery {
+    /// Last id the consumer applied (exclusive).
+    #[serde(default)]
+    pub since_id: i64,
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+/// GET /changefeed?since_id=<id>&limit=500
+/// Entries after `since_id`, oldest first. Store `next_since_id` once the
+/// batch is applied and poll with it; an empty batch means caught up.
+/// Entries carry keys only: fetch the current row to apply one.
+pub async fn get_changefeed(
+    State(state): State<AppState>,
+    Query(params): Query<ChangefeedQuery>,
+    links: PageLinks,
+) -> ExplorerResult<Json<Value>> {
+    let limit = params.limit.clamp(1, 5000);
+    let since_id = params.since_id.max(0);
+    let entries = state
+        .repositories
+        .changefeed
+        .since(since_id, limit)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+    let next_since_id = entries.last().map_or(since_id, |e| e.id);
+
+    Ok(Json(json!({
+        "since_id": since_id,
+        "entries": entries,
+        "next_since_id": next_since_id,
+        "next_url": links.with_param("since_id", next_since_id),
+    })))
+}
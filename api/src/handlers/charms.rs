@@ -2,14 +2,21 @@
 
 use axum::{
     extract::{Path, Query, State},
+    http::HeaderMap,
     Json,
 };
 
 use crate::error::{ExplorerError, ExplorerResult};
+use crate::handlers::address::normalize_address;
+use crate::handlers::admin::reveal_hidden;
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::path_params::{AppIdPath, TxidPath};
 use crate::handlers::AppState;
 use crate::models::{
-    CharmCountResponse, CharmData, CharmsCountByTypeResponse, CharmsResponse, GetCharmNumbersQuery,
-    GetCharmsByTypeQuery, GetCharmsQuery, LikeCharmRequest, LikeResponse, PaginatedResponse,
+    AddressCharmsResponse, AddressSummaryQuery, AddressSummaryResponse, BalanceAtQuery,
+    BalanceAtResponse, CharmCountResponse, CharmData, CharmGrouping, CharmsCountByTypeResponse,
+    CharmsResponse, GetCharmNumbersQuery, GetCharmsByTypeQuery, GetCharmsQuery, LikeCharmRequest,
+    LikeResponse, PaginatedResponse,
 };
 use crate::services::charm_service;
 
@@ -33,50 +40,79 @@ pub async fn get_charms_count_by_type(
     Ok(Json(response))
 }
 
-/// Handler for GET /charms - Returns all charms with pagination, optionally filtered by network
+/// Handler for GET /charms - Returns all charms with pagination, optionally filtered by network,
+/// operation, app_id and amount range
 pub async fn get_charms(
     State(state): State<AppState>,
     Query(params): Query<GetCharmsQuery>,
+    headers: HeaderMap,
+    links: PageLinks,
 ) -> ExplorerResult<Json<PaginatedResponse<CharmsResponse>>> {
-    let response = if let Some(network) = &params.network {
+    let filter = charm_service::charm_filter(
+        &state,
+        &params,
+        params.network.as_deref().unwrap_or("mainnet"),
+    )
+    .await?;
+    let include_hidden = reveal_hidden(&state, &headers, params.include_hidden);
+    let mut response = if let Some(network) = &params.network {
         charm_service::get_all_charms_paginated_by_network(
             &state,
             &params.pagination,
             params.user_id,
             Some(network),
+            &filter,
+            include_hidden,
         )
         .await?
     } else {
-        charm_service::get_all_charms_paginated(&state, &params.pagination, params.user_id).await?
+        charm_service::get_all_charms_paginated(
+            &state,
+            &params.pagination,
+            params.user_id,
+            &filter,
+            include_hidden,
+        )
+        .await?
     };
+    links.link(&mut response.pagination);
     Ok(Json(response))
 }
 
-/// Handler for GET /charms/by-type - Returns charms filtered by asset type with pagination
+/// Handler for GET /charms/by-type - Returns charms of an asset type on one
+/// network (default mainnet) with pagination
 pub async fn get_charms_by_type(
     State(state): State<AppState>,
     Query(params): Query<GetCharmsByTypeQuery>,
+    headers: HeaderMap,
+    links: PageLinks,
 ) -> ExplorerResult<Json<PaginatedResponse<CharmsResponse>>> {
     // Use default user_id of 1 as specified in requirements
-    let response = charm_service::get_charms_by_type_paginated(
+    let mut response = charm_service::get_charms_by_type_paginated(
         &state,
         &params.asset_type,
+        &params.network,
         &params.pagination,
         1,
+        reveal_hidden(&state, &headers, params.include_hidden),
     )
     .await?;
+    links.link(&mut response.pagination);
     Ok(Json(response))
 }
 
 /// Handler for GET /charms/{txid} — DEPRECATED, use GET /transactions/{txid}
 pub async fn get_charm_by_txid(
     State(state): State<AppState>,
-    Path(txid): Path<String>,
+    TxidPath(txid): TxidPath,
     Query(params): Query<GetCharmsQuery>,
+    request_headers: HeaderMap,
 ) -> Result<(http::HeaderMap, Json<CharmData>), ExplorerError> {
     tracing::warn!("DEPRECATED: GET /charms/{{txid}} — use GET /transactions/{{txid}}");
     let network = params.network.as_deref().unwrap_or("mainnet");
-    let charm_data = charm_service::get_charm_by_txid(&state, &txid, network, 1).await?;
+    let include_hidden = reveal_hidden(&state, &request_headers, params.include_hidden);
+    let charm_data =
+        charm_service::get_charm_by_txid(&state, &txid, network, 1, include_hidden).await?;
     let mut headers = http::HeaderMap::new();
     headers.insert(
         "deprecation",
@@ -94,12 +130,15 @@ pub async fn get_charm_by_txid(
 /// Handler for GET /charms/by-charmid/{charmid} - Returns a specific charm by its charm ID
 pub async fn get_charm_by_charmid(
     State(state): State<AppState>,
-    Path(charmid): Path<String>,
+    AppIdPath(charmid): AppIdPath,
     Query(params): Query<GetCharmsQuery>,
+    headers: HeaderMap,
 ) -> ExplorerResult<Json<CharmData>> {
     // Use default user_id of 1 as specified in requirements
     let network = params.network.as_deref().unwrap_or("mainnet");
-    let charm_data = charm_service::get_charm_by_charmid(&state, &charmid, network, 1).await?;
+    let include_hidden = reveal_hidden(&state, &headers, params.include_hidden);
+    let charm_data =
+        charm_service::get_charm_by_charmid(&state, &charmid, network, 1, include_hidden).await?;
     Ok(Json(charm_data))
 }
 
@@ -122,14 +161,610 @@ pub async fn unlike_charm(
 }
 
 /// [RJJ-ADDRESS-SEARCH] Handler for GET /charms/by-address/{address}
-/// Returns UNSPENT charms for a Bitcoin address
+/// Returns UNSPENT charms for a Bitcoin address, or with `group=asset` one
+/// summed entry per app_id
 pub async fn get_charms_by_address(
     State(state): State<AppState>,
     Path(address): Path<String>,
     Query(params): Query<GetCharmsQuery>,
-) -> ExplorerResult<Json<CharmsResponse>> {
+) -> ExplorerResult<Json<AddressCharmsResponse>> {
+    let network = params.network.as_deref().unwrap_or("mainnet");
+    let address = normalize_address(&address, network)?;
+    let filter = charm_service::charm_filter(&state, &params, network).await?;
+    let response = match params.group {
+        CharmGrouping::None => AddressCharmsResponse::Charms(
+            charm_service::get_charms_by_address(
+                &state,
+                &address,
+                network,
+                params.user_id,
+                &filter,
+            )
+            .await?,
+        ),
+        CharmGrouping::Asset => AddressCharmsResponse::Assets(
+            charm_service::get_assets_by_address(&state, &address, network, &filter).await?,
+        ),
+    };
+    Ok(Json(response))
+}
+
+/// Handler for GET /address/{address}/balance-at - Balance of one asset at
+/// the end of a past block (network default mainnet)
+pub async fn get_balance_at(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<BalanceAtQuery>,
+) -> ExplorerResult<Json<BalanceAtResponse>> {
     let network = params.network.as_deref().unwrap_or("mainnet");
-    let response =
-        charm_service::get_charms_by_address(&state, &address, network, params.user_id).await?;
+    let address = normalize_address(&address, network)?;
+    let tip = state.repositories.blocks.tip(network).await?;
+    let response = charm_service::get_balance_at(
+        &state,
+        &address,
+        &params.app_id,
+        network,
+        params.height,
+        tip,
+    )
+    .await?;
     Ok(Json(response))
 }
+
+/// Handler for GET /address/{address}/summary - Asset and charm UTXO counts,
+/// BTC buckets when the address is monitored, and its latest activity
+/// (network default mainnet)
+pub async fn get_address_summary(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<AddressSummaryQuery>,
+) -> ExplorerResult<Json<AddressSummaryResponse>> {
+    let network = params.network.as_deref().unwrap_or("mainnet");
+    let address = normalize_address(&address, network)?;
+    let summary = state
+        .repositories
+        .address_summary
+        .summary(&address, network)
+        .await?;
+    Ok(Json(AddressSummaryResponse {
+        address,
+        network: network.to_string(),
+        summary,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::response::IntoResponse;
+    use http::StatusCode;
+
+    use super::*;
+    use crate::entity::charms;
+    use crate::models::PaginationParams;
+    use crate::test_support::{app_state, asset, charm, repositories, FakeAssets, FakeCharms};
+
+    fn txids(response: &PaginatedResponse<CharmsResponse>) -> Vec<&str> {
+        response
+            .data
+            .charms
+            .iter()
+            .map(|c| c.txid.as_str())
+            .collect()
+    }
+
+    fn links() -> PageLinks {
+        PageLinks::new("", &"/v1/charms".parse().unwrap())
+    }
+
+    fn query(network: Option<&str>) -> Query<GetCharmsQuery> {
+        Query(GetCharmsQuery {
+            pagination: PaginationParams {
+                page: 1,
+                limit: 20,
+                ..Default::default()
+            },
+            user_id: 1,
+            network: network.map(str::to_string),
+            operation: None,
+            app_id: None,
+            min_amount: None,
+            max_amount: None,
+            decimals: false,
+            include_hidden: false,
+            group: CharmGrouping::None,
+        })
+    }
+
+    #[tokio::test]
+    async fn charm_by_txid_is_network_scoped_and_deprecated() {
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![charm("abc", "t/a/a")]));
+        let state = app_state(repos);
+
+        let (headers, Json(found)) = get_charm_by_txid(
+            State(state.clone()),
+            TxidPath("abc".to_string()),
+            query(Some("mainnet")),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(found.charmid, "t/a/a");
+        assert_eq!(headers["deprecation"], "true");
+
+        let err = get_charm_by_txid(
+            State(state),
+            TxidPath("abc".to_string()),
+            query(Some("testnet4")),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::NOT_FOUND);
+    }
+
+    async fn error_body(err: ExplorerError) -> (StatusCode, serde_json::Value) {
+        let response = err.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn a_miss_names_the_networks_that_have_the_charm() {
+        let mut testnet = charm("t4only", "t/b/b");
+        testnet.network = "testnet4".to_string();
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![testnet]));
+        let mut state = app_state(repos);
+
+        let err = get_charm_by_txid(
+            State(state.clone()),
+            TxidPath("t4only".to_string()),
+            query(Some("mainnet")),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        let (status, body) = error_body(err).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["found_on_networks"], serde_json::json!(["testnet4"]));
+
+        // The default network is mainnet too.
+        let err = get_charm_by_charmid(
+            State(state.clone()),
+            AppIdPath("t/b/b".to_string()),
+            query(None),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        let (status, body) = error_body(err).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["found_on_networks"], serde_json::json!(["testnet4"]));
+
+        // Networks this deployment does not serve are not suggested.
+        state.config.enable_bitcoin_testnet4 = false;
+        let err = get_charm_by_txid(
+            State(state),
+            TxidPath("t4only".to_string()),
+            query(Some("mainnet")),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        let (status, body) = error_body(err).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body.get("found_on_networks").is_none());
+    }
+
+    #[tokio::test]
+    async fn get_charms_filters_by_network_when_given() {
+        let mut testnet = charm("t4", "t/b/b");
+        testnet.network = "testnet4".to_string();
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![charm("main", "t/a/a"), testnet]));
+        let state = app_state(repos);
+
+        let Json(scoped) = get_charms(
+            State(state.clone()),
+            query(Some("testnet4")),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(scoped.pagination.total, 1);
+        assert_eq!(scoped.data.charms[0].txid, "t4");
+
+        let Json(all) = get_charms(State(state), query(None), HeaderMap::new(), links())
+            .await
+            .unwrap();
+        assert_eq!(all.pagination.total, 2);
+    }
+
+    #[tokio::test]
+    async fn get_charms_filters_by_operation() {
+        let mut mint = charm("mint", "t/a/a");
+        mint.operation = Some("mint".to_string());
+        let mut transfer = charm("transfer", "t/a/a");
+        transfer.operation = Some("transfer".to_string());
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            mint,
+            transfer,
+            charm("legacy", "t/a/a"),
+        ]));
+        let state = app_state(repos);
+
+        let mut params = query(Some("mainnet"));
+        params.operation = Some("transfer".to_string());
+        let Json(transfers) = get_charms(State(state.clone()), params, HeaderMap::new(), links())
+            .await
+            .unwrap();
+        assert_eq!(transfers.pagination.total, 1);
+        assert_eq!(transfers.data.charms[0].txid, "transfer");
+
+        let mut params = query(None);
+        params.operation = Some("mint".to_string());
+        let Json(mints) = get_charms(State(state.clone()), params, HeaderMap::new(), links())
+            .await
+            .unwrap();
+        assert_eq!(mints.data.charms[0].txid, "mint");
+
+        let mut params = query(None);
+        params.operation = Some("swap".to_string());
+        let err = get_charms(State(state), params, HeaderMap::new(), links())
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    const HOLDER: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+    fn transfer(txid: &str, app_id: &str, amount: i64) -> charms::Model {
+        let mut c = charm(txid, app_id);
+        c.amount = amount;
+        c.address = Some(HOLDER.to_string());
+        c
+    }
+
+    fn amount_query(
+        app_id: Option<&str>,
+        min: Option<&str>,
+        max: Option<&str>,
+    ) -> Query<GetCharmsQuery> {
+        let Query(mut params) = query(Some("mainnet"));
+        params.app_id = app_id.map(str::to_string);
+        params.min_amount = min.map(str::to_string);
+        params.max_amount = max.map(str::to_string);
+        Query(params)
+    }
+
+    #[tokio::test]
+    async fn get_charms_filters_by_app_id_and_raw_amount() {
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            transfer("small", "t/a/a", 999_999),
+            transfer("edge", "t/a/a", 1_000_000),
+            transfer("big", "t/a/a", 5_000_000),
+            transfer("other", "t/b/b", 5_000_000),
+        ]));
+        let state = app_state(repos);
+
+        let Json(page) = get_charms(
+            State(state.clone()),
+            amount_query(Some("t/a/a"), Some("1000000"), None),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        let mut got = txids(&page);
+        got.sort();
+        assert_eq!(got, ["big", "edge"]);
+
+        let Json(page) = get_charms(
+            State(state.clone()),
+            amount_query(None, None, Some("1000000")),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        let mut got = txids(&page);
+        got.sort();
+        assert_eq!(got, ["edge", "small"]);
+
+        // The by-address listing takes the same filter.
+        let Json(held) = get_charms_by_address(
+            State(state.clone()),
+            Path(HOLDER.to_string()),
+            amount_query(Some("t/b/b"), Some("1"), None),
+        )
+        .await
+        .unwrap();
+        let AddressCharmsResponse::Charms(held) = held else {
+            panic!("flat listing expected");
+        };
+        assert_eq!(held.charms.len(), 1);
+        assert_eq!(held.charms[0].txid, "other");
+
+        for (min, max) in [
+            (Some("2"), Some("1")),
+            (Some("1.5"), None),
+            (Some("-"), None),
+        ] {
+            let err = get_charms(
+                State(state.clone()),
+                amount_query(None, min, max),
+                HeaderMap::new(),
+                links(),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn decimal_amount_filters_use_the_asset_decimals() {
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            transfer("below", "t/a/a", 149),
+            transfer("at", "t/a/a", 150),
+            transfer("above", "t/a/a", 12_000),
+        ]));
+        let mut nft = asset(1, "n/a/a", "nft");
+        nft.decimals = 2;
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![nft]));
+        let state = app_state(repos);
+
+        let Query(mut params) = amount_query(Some("t/a/a"), Some("1.5"), Some("100"));
+        params.decimals = true;
+        let Json(page) = get_charms(
+            State(state.clone()),
+            Query(params),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(txids(&page), ["at"]);
+
+        // More places than the asset has, or no asset to take them from.
+        let Query(mut params) = amount_query(Some("t/a/a"), Some("1.505"), None);
+        params.decimals = true;
+        let err = get_charms(
+            State(state.clone()),
+            Query(params),
+            HeaderMap::new(),
+            links(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let Query(mut params) = amount_query(None, Some("1.5"), None);
+        params.decimals = true;
+        let err = get_charms(State(state), Query(params), HeaderMap::new(), links())
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn grouped_holdings_sum_the_flat_listing() {
+        let at = |txid: &str, app_id: &str, amount: i64, height: Option<i32>| {
+            let mut c = transfer(txid, app_id, amount);
+            c.block_height = height;
+            c
+        };
+        let mut spent = at("spent", "t/a/a", 1_000, Some(90));
+        spent.spent = true;
+        let mut elsewhere = at("elsewhere", "t/a/a", 1_000, Some(90));
+        elsewhere.address = Some("bc1qother".to_string());
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            at("a1", "t/a/a", 150, Some(100)),
+            at("a2", "t/a/a", 25, Some(120)),
+            at("a3", "t/a/a", 5, None),
+            at("b1", "t/b/b", 7, Some(110)),
+            spent,
+            elsewhere,
+        ]));
+        let mut nft = asset(1, "n/a/a", "nft");
+        nft.decimals = 2;
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![nft]));
+        let state = app_state(repos);
+
+        let list = |group: CharmGrouping| {
+            let Query(mut params) = amount_query(None, None, None);
+            params.group = group;
+            get_charms_by_address(
+                State(state.clone()),
+                Path(HOLDER.to_string()),
+                Query(params),
+            )
+        };
+        let Json(AddressCharmsResponse::Charms(flat)) = list(CharmGrouping::None).await.unwrap()
+        else {
+            panic!("flat listing expected");
+        };
+        let Json(AddressCharmsResponse::Assets(grouped)) =
+            list(CharmGrouping::Asset).await.unwrap()
+        else {
+            panic!("grouped listing expected");
+        };
+
+        let app_ids: Vec<&str> = grouped.assets.iter().map(|a| a.app_id.as_str()).collect();
+        assert_eq!(app_ids, ["t/a/a", "t/b/b"]);
+        for holding in &grouped.assets {
+            let held: Vec<_> = flat
+                .charms
+                .iter()
+                .filter(|c| c.charmid == holding.app_id)
+                .collect();
+            assert_eq!(holding.amount, held.iter().map(|c| c.amount).sum::<i64>());
+            assert_eq!(holding.utxo_count, held.len() as i64);
+            assert_eq!(
+                holding.first_block,
+                held.iter().filter_map(|c| c.block_height).min()
+            );
+            assert_eq!(
+                holding.last_block,
+                held.iter().filter_map(|c| c.block_height).max()
+            );
+        }
+        let a = &grouped.assets[0];
+        assert_eq!((a.amount, a.utxo_count), (180, 3));
+        assert_eq!((a.first_block, a.last_block), (Some(100), Some(120)));
+        assert_eq!((a.decimals, a.amount_formatted.as_str()), (2, "1.8"));
+
+        // Filters narrow the groups like they narrow the flat listing.
+        let Query(mut params) = amount_query(None, Some("10"), None);
+        params.group = CharmGrouping::Asset;
+        let Json(AddressCharmsResponse::Assets(grouped)) = get_charms_by_address(
+            State(state.clone()),
+            Path(HOLDER.to_string()),
+            Query(params),
+        )
+        .await
+        .unwrap() else {
+            panic!("grouped listing expected");
+        };
+        assert_eq!(grouped.assets.len(), 1);
+        assert_eq!(
+            (grouped.assets[0].amount, grouped.assets[0].utxo_count),
+            (175, 2)
+        );
+    }
+
+    #[tokio::test]
+    async fn by_type_lists_dapp_charms() {
+        let mut dapp = charm("d1", "d/a/a");
+        dapp.asset_type = "dapp".to_string();
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![charm("t1", "t/a/a"), dapp]));
+        let state = app_state(repos);
+
+        let uri: http::Uri = "/charms/by-type?type=dapp".parse().unwrap();
+        let params = Query::<GetCharmsByTypeQuery>::try_from_uri(&uri).unwrap();
+        let Json(listed) = get_charms_by_type(
+            State(state),
+            params,
+            HeaderMap::new(),
+            PageLinks::new("", &uri),
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed.pagination.total, 1);
+        assert_eq!(listed.data.charms[0].txid, "d1");
+        assert_eq!(listed.data.charms[0].asset_type, "dapp");
+    }
+
+    #[tokio::test]
+    async fn by_type_is_network_scoped_and_skips_placeholders() {
+        let mut testnet = charm("t4", "n/b/b");
+        testnet.asset_type = "nft".to_string();
+        testnet.network = "testnet4".to_string();
+        let mut mainnet = charm("m1", "n/a/a");
+        mainnet.asset_type = "nft".to_string();
+        let mut placeholder = mainnet.clone();
+        placeholder.txid = "m2".to_string();
+        placeholder.is_placeholder = true;
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![mainnet, placeholder, testnet]));
+        let state = app_state(repos);
+
+        let list = |uri: &'static str| {
+            let state = state.clone();
+            async move {
+                let uri: http::Uri = uri.parse().unwrap();
+                let params = Query::<GetCharmsByTypeQuery>::try_from_uri(&uri).unwrap();
+                let links = PageLinks::new("", &uri);
+                get_charms_by_type(State(state), params, HeaderMap::new(), links)
+                    .await
+                    .unwrap()
+                    .0
+            }
+        };
+
+        let main = list("/charms/by-type?type=nft").await;
+        assert_eq!(main.pagination.total, 1);
+        assert_eq!(main.data.charms[0].txid, "m1");
+
+        let test = list("/charms/by-type?type=nft&network=testnet4&page=1&limit=10").await;
+        assert_eq!(test.pagination.total, 1);
+        assert_eq!(test.data.charms[0].txid, "t4");
+    }
+
+    /// Following `next_url` and then `prev_url` walks the listing under the
+    /// filters of the first request.
+    #[tokio::test]
+    async fn page_links_round_trip_with_the_same_filters() {
+        let charm_with = |txid: &str, network: &str, operation: &str| {
+            let mut c = charm(txid, "t/a/a");
+            c.network = network.to_string();
+            c.operation = Some(operation.to_string());
+            c
+        };
+        let mut rows: Vec<_> = (0..5)
+            .map(|i| charm_with(&format!("m{i}"), "mainnet", "transfer"))
+            .collect();
+        rows.push(charm_with("t4", "testnet4", "transfer"));
+        rows.push(charm_with("mint", "mainnet", "mint"));
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(rows));
+        let state = app_state(repos);
+
+        let base = "https://api.example";
+        let fetch = |url: String| {
+            let state = state.clone();
+            async move {
+                let uri: http::Uri = url.strip_prefix(base).unwrap().parse().unwrap();
+                let params = Query::<GetCharmsQuery>::try_from_uri(&uri).unwrap();
+                let links = PageLinks::new(base, &uri);
+                get_charms(State(state), params, HeaderMap::new(), links)
+                    .await
+                    .unwrap()
+                    .0
+            }
+        };
+
+        let first = fetch(format!(
+            "{base}/v1/charms?network=mainnet&operation=transfer&limit=2"
+        ))
+        .await;
+        assert_eq!(txids(&first), ["m0", "m1"]);
+        assert_eq!(
+            (first.pagination.total, first.pagination.total_pages),
+            (5, 3)
+        );
+        assert!(first.pagination.nav.has_next && !first.pagination.nav.has_prev);
+        assert_eq!(
+            first.pagination.nav.next_url.as_deref(),
+            Some(
+                "https://api.example/v1/charms?network=mainnet&operation=transfer&limit=2\
+                 &page=2"
+            )
+        );
+
+        let second = fetch(first.pagination.nav.next_url.clone().unwrap()).await;
+        assert_eq!(txids(&second), ["m2", "m3"]);
+        assert_eq!(second.pagination.page, 2);
+        assert_eq!(second.pagination.total, 5);
+
+        let last = fetch(second.pagination.nav.next_url.clone().unwrap()).await;
+        assert_eq!(txids(&last), ["m4"]);
+        assert!(!last.pagination.nav.has_next);
+        assert_eq!(last.pagination.nav.next_url, None);
+
+        let back = fetch(second.pagination.nav.prev_url.clone().unwrap()).await;
+        assert_eq!(txids(&back), txids(&first));
+    }
+}
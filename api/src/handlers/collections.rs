@@ -0,0 +1,173 @@
+// NFT collection handlers. An NFT's collection is the id it declares in its
+// metadata, or `deployer:{address}` for NFTs minted without one.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::handlers::admin::reveal_hidden;
+use crate::handlers::assets::{supply_split, AssetItem};
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::AppState;
+use crate::models::PaginationMeta;
+use crate::services::decimals_service::DecimalsResolver;
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionQueryParams {
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    /// Also list hidden NFTs of a collection; honoured only with the admin
+    /// token. Collection summaries never count hidden NFTs.
+    #[serde(default)]
+    pub include_hidden: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionItem {
+    pub id: String,
+    pub items: u64,
+    pub sample_image: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionsData {
+    pub collections: Vec<CollectionItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionsResponse {
+    pub data: CollectionsData,
+    pub pagination: PaginationMeta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionAssetsData {
+    pub collection: String,
+    pub assets: Vec<AssetItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionAssetsResponse {
+    pub data: CollectionAssetsData,
+    pub pagination: PaginationMeta,
+}
+
+fn page_and_limit(params: &CollectionQueryParams) -> (u64, u64) {
+    let page = params.page.unwrap_or(1).max(1);
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    (page, limit)
+}
+
+/// List NFT collections with item counts and a sample image
+pub async fn get_collections(
+    Query(params): Query<CollectionQueryParams>,
+    State(state): State<AppState>,
+    links: PageLinks,
+) -> Result<Json<CollectionsResponse>, StatusCode> {
+    let network = params.network.as_deref().unwrap_or("mainnet");
+    let (page, limit) = page_and_limit(&params);
+
+    let (collections, total) = state
+        .repositories
+        .asset_repository
+        .find_collections(network, limit, (page - 1) * limit)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error fetching collections: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(CollectionsResponse {
+        data: CollectionsData {
+            collections: collections
+                .into_iter()
+                .map(|c| CollectionItem {
+                    id: c.collection,
+                    items: c.items as u64,
+                    sample_image: c.sample_image,
+                })
+                .collect(),
+        },
+        pagination: links.meta(page, limit, total),
+    }))
+}
+
+/// List the NFTs of one collection, oldest mint first
+pub async fn get_collection_assets(
+    Path(collection): Path<String>,
+    Query(params): Query<CollectionQueryParams>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    links: PageLinks,
+) -> Result<Json<CollectionAssetsResponse>, StatusCode> {
+    let network = params.network.as_deref().unwrap_or("mainnet");
+    let (page, limit) = page_and_limit(&params);
+    let include_hidden = reveal_hidden(&state, &headers, params.include_hidden);
+
+    let (assets, total) = state
+        .repositories
+        .asset_repository
+        .find_by_collection(
+            &collection,
+            network,
+            limit,
+            (page - 1) * limit,
+            include_hidden,
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Error fetching collection assets: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if total == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let mut decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref());
+    decimals.resolve_assets(&assets).await;
+    let assets = assets
+        .into_iter()
+        .map(|asset| {
+            let (total_supply, locked_supply, circulating_supply) =
+                supply_split(asset.total_supply, asset.locked_supply);
+            AssetItem {
+                id: asset.id.to_string(),
+                total_supply_formatted: total_supply
+                    .map(|raw| decimals.format(&asset.network, &asset.app_id, raw)),
+                circulating_supply_formatted: circulating_supply
+                    .map(|raw| decimals.format(&asset.network, &asset.app_id, raw)),
+                decimals: decimals.get(&asset.network, &asset.app_id) as i16,
+                app_id: asset.app_id,
+                asset_type: asset.asset_type,
+                name: asset.name,
+                symbol: asset.symbol,
+                description: asset.description,
+                image_url: asset.image_url,
+                total_supply,
+                locked_supply,
+                circulating_supply,
+                network: asset.network,
+                created_at: asset.created_at,
+                updated_at: asset.updated_at,
+                block_height: Some(asset.block_height),
+                transaction_hash: Some(asset.txid),
+                collection: asset.collection,
+                deploy_txid: asset.deploy_txid,
+                deploy_block_height: asset.deploy_block_height,
+                deployer_address: asset.deployer_address,
+                offchain: None,
+            }
+        })
+        .collect();
+
+    Ok(Json(CollectionAssetsResponse {
+        data: CollectionAssetsData { collection, assets },
+        pagination: links.meta(page, limit, total),
+    }))
+}
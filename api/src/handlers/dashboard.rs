@@ -0,0 +1,34 @@
+// Dashboard handler: everything the homepage shows, in one response.
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::handlers::AppState;
+use crate::services::dashboard_service::{self, DashboardResponse, SECTION_TIMEOUT};
+
+fn default_network() -> String {
+    "mainnet".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+}
+
+/// GET /dashboard?network=mainnet
+/// Charm counts per type, asset total, the newest charms and blocks, the
+/// assets with most holders, the mempool backlog and the indexer's sync
+/// state. Always 200: a section that failed is null and listed in `errors`.
+pub async fn get_dashboard(
+    State(state): State<AppState>,
+    Query(params): Query<DashboardQuery>,
+) -> Json<DashboardResponse> {
+    Json(dashboard_service::get_dashboard(&state, &params.network, SECTION_TIMEOUT).await)
+}
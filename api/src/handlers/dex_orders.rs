@@ -7,88 +7,159 @@ use axum::{
 use serde::Deserialize;
 
 use crate::error::ExplorerResult;
+use crate::handlers::address::canonical_address;
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::path_params::{AppIdPath, OrderIdPath};
 use crate::handlers::AppState;
-use crate::services::dex_orders_service::{self, DexOrderResponse, DexOrdersListResponse};
+use crate::models::PageLimits;
+use crate::services::dex_orders_service::{
+    self, DexMarketResponse, DexOrderResponse, DexOrdersListResponse,
+};
+
+/// `(page, limit)` for an order listing. Without a limit a page holds the
+/// most orders `PageLimits` allows.
+fn order_page(page: Option<u64>, limit: Option<u64>) -> ExplorerResult<(u64, u64)> {
+    let limits = PageLimits::global();
+    Ok(limits.apply(page.unwrap_or(1), limit.unwrap_or(limits.max_limit))?)
+}
+
+/// `response` with links to the neighbouring pages of `page`.
+fn linked(
+    mut response: DexOrdersListResponse,
+    links: &PageLinks,
+    page: u64,
+    limit: u64,
+) -> Json<DexOrdersListResponse> {
+    response.nav = Some(links.nav(page, response.total.div_ceil(limit)));
+    Json(response)
+}
 
 #[derive(Debug, Deserialize)]
 pub struct OpenOrdersQuery {
     pub asset: Option<String>,
     pub side: Option<String>,
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
     pub network: Option<String>,
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
 }
 
-/// GET /dex/orders/open?asset=...&side=...&network=...
-/// Returns all active/open DEX positions
+/// GET /dex/orders/open?asset=...&side=...&network=...&page=...&limit=...
+/// Returns active/open DEX positions, expired ones excluded
 pub async fn get_open_orders(
     State(state): State<AppState>,
     Query(params): Query<OpenOrdersQuery>,
+    links: PageLinks,
 ) -> ExplorerResult<Json<DexOrdersListResponse>> {
+    let (page, limit) = order_page(params.page, params.limit)?;
     let response = dex_orders_service::get_open_orders(
         &state,
         params.asset.as_deref(),
         params.side.as_deref(),
         params.network.as_deref(),
+        page,
+        limit,
     )
     .await?;
-    Ok(Json(response))
+    Ok(linked(response, &links, page, limit))
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AllOrdersQuery {
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
     pub network: Option<String>,
     pub status: Option<String>,
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
 }
 
-/// GET /dex/orders?network=...&status=...
-/// Returns all DEX orders (any status) — full activity history
+/// GET /dex/orders?network=...&status=...&page=...&limit=...
+/// Returns DEX orders (any status) — full activity history, paginated.
+/// `status=expired` lists orders the indexer expired.
 pub async fn get_all_orders(
     State(state): State<AppState>,
     Query(params): Query<AllOrdersQuery>,
+    links: PageLinks,
 ) -> ExplorerResult<Json<DexOrdersListResponse>> {
+    let (page, limit) = order_page(params.page, params.limit)?;
     let response = dex_orders_service::get_all_orders(
         &state,
         params.network.as_deref(),
         params.status.as_deref(),
+        page,
+        limit,
     )
     .await?;
-    Ok(Json(response))
+    Ok(linked(response, &links, page, limit))
 }
 
 /// GET /dex/orders/{order_id}
 /// Returns a single order by ID
 pub async fn get_order_by_id(
     State(state): State<AppState>,
-    Path(order_id): Path<String>,
+    OrderIdPath(order_id): OrderIdPath,
 ) -> ExplorerResult<Json<Option<DexOrderResponse>>> {
     let response = dex_orders_service::get_order_by_id(&state, &order_id).await?;
     Ok(Json(response))
 }
 
-/// GET /dex/orders/by-asset/{asset_app_id}?network=...
-/// Returns all orders (any status) for a specific asset, network-scoped.
+/// GET /dex/orders/by-asset/{asset_app_id}?network=...&page=...&limit=...
+/// Returns orders (any status) for a specific asset, network-scoped.
 pub async fn get_orders_by_asset(
     State(state): State<AppState>,
-    Path(asset_app_id): Path<String>,
+    AppIdPath(asset_app_id): AppIdPath,
     Query(params): Query<AllOrdersQuery>,
+    links: PageLinks,
 ) -> ExplorerResult<Json<DexOrdersListResponse>> {
+    let (page, limit) = order_page(params.page, params.limit)?;
+    let network = params.network.as_deref().unwrap_or("mainnet");
+    let response =
+        dex_orders_service::get_orders_by_asset(&state, &asset_app_id, network, page, limit)
+            .await?;
+    Ok(linked(response, &links, page, limit))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarketQuery {
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+}
+
+/// GET /dex/markets/{asset_app_id}?network=...
+/// Returns best bid/ask, spread and open interest of the asset's live book.
+pub async fn get_market_summary(
+    State(state): State<AppState>,
+    AppIdPath(asset_app_id): AppIdPath,
+    Query(params): Query<MarketQuery>,
+) -> ExplorerResult<Json<DexMarketResponse>> {
     let network = params.network.as_deref().unwrap_or("mainnet");
-    let response = dex_orders_service::get_orders_by_asset(&state, &asset_app_id, network).await?;
+    let response = dex_orders_service::get_market_summary(&state, &asset_app_id, network).await?;
     Ok(Json(response))
 }
 
 #[derive(Debug, Deserialize)]
 pub struct MakerOrdersQuery {
     pub status: Option<String>,
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
 }
 
-/// GET /dex/orders/by-maker/{maker}?status=open
+/// GET /dex/orders/by-maker/{maker}?status=open&page=...&limit=...
 /// Returns orders by maker address, optionally filtered by status
 pub async fn get_orders_by_maker(
     State(state): State<AppState>,
     Path(maker): Path<String>,
     Query(params): Query<MakerOrdersQuery>,
+    links: PageLinks,
 ) -> ExplorerResult<Json<DexOrdersListResponse>> {
-    let response =
-        dex_orders_service::get_orders_by_maker(&state, &maker, params.status.as_deref()).await?;
-    Ok(Json(response))
+    let (page, limit) = order_page(params.page, params.limit)?;
+    let response = dex_orders_service::get_orders_by_maker(
+        &state,
+        &canonical_address(&maker),
+        params.status.as_deref(),
+        page,
+        limit,
+    )
+    .await?;
+    Ok(linked(response, &links, page, limit))
 }
@@ -9,10 +9,10 @@ use crate::services::diagnostic::DiagnosticService;
 
 /// Handler for GET /diagnose - Returns detailed database diagnostic information
 pub async fn diagnose_database(State(app_state): State<AppState>) -> impl IntoResponse {
-    // Create diagnostic service with a reference to the database connection and config
+    // Create diagnostic service with a reference to the database connection and RPC clients
     let diagnostic_service = DiagnosticService::new(
-        app_state.repositories.charm.get_connection(),
-        &app_state.config,
+        app_state.repositories.connection(),
+        &app_state.rpc_clients,
     );
 
     // Run diagnostic checks
@@ -22,7 +22,7 @@ pub async fn diagnose_database(State(app_state): State<AppState>) -> impl IntoRe
     let mut response = HashMap::new();
 
     // Add version number to identify the diagnostic format
-    response.insert("version", json!("1.2.0"));
+    response.insert("version", json!("1.3.0"));
 
     // Add Bitcoin RPC test information
     if let Some(bitcoin_rpc) = diagnostic_result.get("bitcoin_rpc") {
@@ -79,6 +79,12 @@ pub async fn diagnose_database(State(app_state): State<AppState>) -> impl IntoRe
     };
     response.insert("all_tables", all_tables);
 
+    // Outbound QuickNode traffic since process start
+    response.insert(
+        "quicknode",
+        crate::services::wallet_service::QUICKNODE_COUNTERS.snapshot(),
+    );
+
     // Return JSON response
     Json(json!(response))
 }
@@ -7,19 +7,21 @@
 
 use axum::{
     extract::{Path, State},
-    response::IntoResponse,
     Json,
 };
 use sea_orm::{ConnectionTrait, DbBackend, FromQueryResult, Statement};
 use serde_json::{json, Value};
 
+use crate::error::ExplorerResult;
+use crate::handlers::address::normalize_address;
 use crate::handlers::AppState;
 
 pub async fn diagnostics_address(
     State(app_state): State<AppState>,
     Path((network, address)): Path<(String, String)>,
-) -> impl IntoResponse {
-    let conn = app_state.repositories.charm.get_connection();
+) -> ExplorerResult<Json<Value>> {
+    let address = normalize_address(&address, &network)?;
+    let conn = app_state.repositories.connection();
 
     let monitored = fetch_monitored(&conn, &address, &network).await;
     let indexer_view = match &monitored {
@@ -82,7 +84,7 @@ pub async fn diagnostics_address(
         })).collect::<Vec<_>>(),
     });
 
-    Json(body)
+    Ok(Json(body))
 }
 
 #[derive(FromQueryResult)]
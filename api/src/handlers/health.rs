@@ -1,6 +1,9 @@
 // Health check endpoint handler implementation
 
+use crate::handlers::AppState;
 use crate::services::health::HealthChecker;
+use axum::extract::State;
+use axum::http::StatusCode;
 use axum::response::IntoResponse;
 
 /// Handler for GET /health - Returns a simple health check response to verify the API is running
@@ -12,3 +15,30 @@ pub async fn health_check() -> impl IntoResponse {
         "Service Unavailable"
     }
 }
+
+/// Handler for GET /health/ready - 503 from the moment shutdown begins, so
+/// load balancers stop routing here while in-flight requests drain
+pub async fn health_ready(State(state): State<AppState>) -> impl IntoResponse {
+    if state.lifecycle.is_ready() {
+        (StatusCode::OK, "OK")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "Shutting down")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app_state, repositories};
+
+    #[tokio::test]
+    async fn readiness_flips_when_shutdown_begins() {
+        let state = app_state(repositories());
+        let ready = health_ready(State(state.clone())).await.into_response();
+        assert_eq!(ready.status(), StatusCode::OK);
+
+        state.lifecycle.begin_shutdown();
+        let draining = health_ready(State(state)).await.into_response();
+        assert_eq!(draining.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
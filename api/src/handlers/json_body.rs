@@ -0,0 +1,166 @@
+// Bounded JSON request bodies. The wallet batch endpoints take free-form
+// `serde_json::Value` bodies, so nothing in their shape limits what a
+// client can send. `BoundedJson` refuses a body over `MAX_BODY_BYTES` or
+// nested deeper than `MAX_DEPTH` before deserializing it: the depth scan is
+// a single pass over the bytes, where a deep document would otherwise be
+// built (and later dropped) recursively.
+//
+// Rejections are 400s without the body echoed back.
+
+use axum::{
+    body::to_bytes,
+    extract::{FromRequest, Request},
+};
+use serde::de::DeserializeOwned;
+
+use crate::error::ExplorerError;
+
+/// Largest accepted body. A 50-address batch is a few KiB.
+pub const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Deepest accepted nesting of arrays and objects. The batch bodies are
+/// two levels deep.
+pub const MAX_DEPTH: usize = 16;
+
+/// `Json<T>` with the size and depth bounds above.
+#[derive(Debug)]
+pub struct BoundedJson<T>(pub T);
+
+/// Nesting depth of `bytes` read as JSON, or `None` once it exceeds `max`.
+/// Brackets inside strings do not count; malformed input is left to the
+/// parser.
+fn depth_within(bytes: &[u8], max: usize) -> Option<usize> {
+    let (mut depth, mut deepest) = (0usize, 0usize);
+    let (mut in_string, mut escaped) = (false, false);
+    for &b in bytes {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max {
+                    return None;
+                }
+                deepest = deepest.max(depth);
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Some(deepest)
+}
+
+/// Parse `bytes` as `T` within the bounds.
+pub fn parse_bounded<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ExplorerError> {
+    if bytes.len() > MAX_BODY_BYTES {
+        return Err(ExplorerError::InvalidRequest(format!(
+            "request body exceeds {} bytes",
+            MAX_BODY_BYTES
+        )));
+    }
+    if depth_within(bytes, MAX_DEPTH).is_none() {
+        return Err(ExplorerError::InvalidRequest(format!(
+            "request body nested deeper than {} levels",
+            MAX_DEPTH
+        )));
+    }
+    serde_json::from_slice(bytes)
+        .map_err(|e| ExplorerError::InvalidRequest(format!("invalid JSON body: {}", e)))
+}
+
+impl<S, T> FromRequest<S> for BoundedJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = ExplorerError;
+
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        // One byte over the cap is enough to tell the body is too large.
+        let bytes = to_bytes(req.into_body(), MAX_BODY_BYTES + 1)
+            .await
+            .map_err(|_| {
+                ExplorerError::InvalidRequest(format!(
+                    "request body exceeds {} bytes",
+                    MAX_BODY_BYTES
+                ))
+            })?;
+        parse_bounded(&bytes).map(BoundedJson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use serde_json::{Value, json};
+    use std::time::{Duration, Instant};
+
+    fn nested(levels: usize) -> String {
+        format!("{}{}", "[".repeat(levels), "]".repeat(levels))
+    }
+
+    async fn extract(body: impl Into<Body>) -> Result<Value, ExplorerError> {
+        let req = Request::builder()
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(body.into())
+            .unwrap();
+        BoundedJson::<Value>::from_request(req, &())
+            .await
+            .map(|BoundedJson(v)| v)
+    }
+
+    #[tokio::test]
+    async fn a_batch_body_is_parsed() {
+        let body = r#"{"addresses": ["bc1p...", "bc1q..."], "network": "testnet4"}"#;
+        assert_eq!(
+            extract(body).await.unwrap(),
+            json!({"addresses": ["bc1p...", "bc1q..."], "network": "testnet4"})
+        );
+    }
+
+    #[tokio::test]
+    async fn a_deeply_nested_body_is_rejected_quickly() {
+        // Well under the size cap, but far deeper than any request needs.
+        let body = nested(30_000);
+        let started = Instant::now();
+        let err = extract(body).await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_millis(200));
+        assert!(err.to_string().contains("nested deeper than 16"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn an_oversized_body_is_rejected() {
+        let body = format!(r#"{{"addresses": ["{}"]}}"#, "a".repeat(MAX_BODY_BYTES));
+        let err = extract(body).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn malformed_json_is_a_bad_request() {
+        let err = extract("{\"addresses\": [").await.unwrap_err();
+        assert!(matches!(err, ExplorerError::InvalidRequest(_)));
+    }
+
+    #[test]
+    fn brackets_in_strings_do_not_count() {
+        assert_eq!(depth_within(br#"{"a": "[[[[\"]]"}"#, 2), Some(1));
+        assert_eq!(
+            depth_within(nested(MAX_DEPTH).as_bytes(), MAX_DEPTH),
+            Some(MAX_DEPTH)
+        );
+        assert_eq!(
+            depth_within(nested(MAX_DEPTH + 1).as_bytes(), MAX_DEPTH),
+            None
+        );
+    }
+}
@@ -0,0 +1,58 @@
+// Mempool statistics handler: how many charms wait unconfirmed and how long
+// recently confirmed ones waited, from `charms.confirmation_delay_secs`.
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::{ExplorerError, ExplorerResult};
+use crate::handlers::AppState;
+
+/// Delay statistics cover charms confirmed within this many hours.
+const DELAY_WINDOW_HOURS: i32 = 24;
+
+fn default_network() -> String {
+    "mainnet".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MempoolStatsQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+}
+
+/// GET /stats/mempool?network=mainnet
+/// Current unconfirmed charm count, plus median / p90 and a histogram of
+/// the time charms confirmed in the last 24h spent in mempool. Charms that
+/// were never seen unconfirmed are left out of the delay figures.
+pub async fn get_mempool_stats(
+    State(state): State<AppState>,
+    Query(params): Query<MempoolStatsQuery>,
+) -> ExplorerResult<Json<Value>> {
+    let repo = &state.repositories.mempool_stats;
+    let (unconfirmed, delays) = tokio::join!(
+        repo.unconfirmed_count(&params.network),
+        repo.confirmation_delays(&params.network, DELAY_WINDOW_HOURS)
+    );
+    let unconfirmed = unconfirmed.map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+    let delays = delays.map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+
+    Ok(Json(json!({
+        "network": params.network,
+        "unconfirmed_charms": unconfirmed,
+        "confirmation_delay": {
+            "window_hours": DELAY_WINDOW_HOURS,
+            "samples": delays.samples,
+            "median_secs": delays.median_secs,
+            "p90_secs": delays.p90_secs,
+            "max_secs": delays.max_secs,
+            "histogram": delays.histogram,
+        },
+    })))
+}
@@ -0,0 +1,16 @@
+// Process counters endpoint handler
+
+use axum::{extract::State, Json};
+use serde_json::json;
+
+use crate::handlers::AppState;
+use crate::services::wallet_service::QUICKNODE_COUNTERS;
+
+/// Handler for GET /metrics - Outbound provider and cache counters since
+/// process start
+pub async fn get_metrics(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(json!({
+        "quicknode": QUICKNODE_COUNTERS.snapshot(),
+        "scan_cache": state.scan_cache.snapshot(),
+    }))
+}
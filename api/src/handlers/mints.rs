@@ -0,0 +1,147 @@
+// Mint event handlers: per-asset issuance history and a global feed, both
+// read from the `mint_events` rows the indexer writes on net supply increases,
+// and the per-asset supply audit trail from `supply_changes`.
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::{ExplorerError, ExplorerResult};
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::path_params::AppIdPath;
+use crate::handlers::AppState;
+
+fn default_network() -> String {
+    "mainnet".to_string()
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_limit() -> u64 {
+    50
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssetMintsQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+    #[serde(default = "default_page")]
+    pub page: u64,
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MintFeedQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+    /// Lowest block height to return (inclusive).
+    #[serde(default)]
+    pub since: i64,
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+/// GET /assets/{app_id}/mints?network=mainnet&page=1&limit=50
+/// Issuance history of one asset, newest block first.
+pub async fn get_asset_mints(
+    State(state): State<AppState>,
+    AppIdPath(app_id): AppIdPath,
+    Query(params): Query<AssetMintsQuery>,
+    links: PageLinks,
+) -> ExplorerResult<Json<Value>> {
+    let limit = params.limit.clamp(1, 100);
+    let page = params.page.max(1);
+    let (mints, total) = state
+        .repositories
+        .mint_events
+        .by_app_id(&app_id, &params.network, limit, (page - 1) * limit)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+
+    let total_pages = total.div_ceil(limit);
+    let mut body = json!({
+        "app_id": app_id,
+        "network": params.network,
+        "mints": mints,
+        "page": page,
+        "limit": limit,
+        "total": total,
+        "total_pages": total_pages,
+    });
+    links.nav(page, total_pages).extend(&mut body);
+    Ok(Json(body))
+}
+
+/// GET /assets/{app_id}/supply-history?network=mainnet&page=1&limit=50
+/// Every change to the asset's recorded supply, newest first, with the
+/// reason and the transaction behind it.
+pub async fn get_asset_supply_history(
+    State(state): State<AppState>,
+    AppIdPath(app_id): AppIdPath,
+    Query(params): Query<AssetMintsQuery>,
+    links: PageLinks,
+) -> ExplorerResult<Json<Value>> {
+    let limit = params.limit.clamp(1, 100);
+    let page = params.page.max(1);
+    let (changes, total) = state
+        .repositories
+        .supply_changes
+        .by_app_id(&app_id, &params.network, limit, (page - 1) * limit)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+
+    let total_pages = total.div_ceil(limit);
+    let mut body = json!({
+        "app_id": app_id,
+        "network": params.network,
+        "changes": changes,
+        "page": page,
+        "limit": limit,
+        "total": total,
+        "total_pages": total_pages,
+    });
+    links.nav(page, total_pages).extend(&mut body);
+    Ok(Json(body))
+}
+
+/// GET /stats/mints?network=mainnet&since=<height>&limit=50
+/// Mints across all assets from `since` upward, oldest block first. Poll
+/// `next_url` (`since=next_cursor`); `since` is inclusive, so dedupe the
+/// boundary block on (txid, app_id).
+pub async fn get_mint_feed(
+    State(state): State<AppState>,
+    Query(params): Query<MintFeedQuery>,
+    links: PageLinks,
+) -> ExplorerResult<Json<Value>> {
+    let limit = params.limit.clamp(1, 1000);
+    let mints = state
+        .repositories
+        .mint_events
+        .since(&params.network, params.since.max(0), limit)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+    let next_since = mints
+        .last()
+        .map_or(params.since.max(0), |m| m.block_height as i64);
+
+    Ok(Json(json!({
+        "network": params.network,
+        "since": params.since.max(0),
+        "mints": mints,
+        "next_since": next_since,
+        "next_cursor": next_since,
+        "next_url": links.with_param("since", next_since),
+    })))
+}
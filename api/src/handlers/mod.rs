@@ -1,46 +1,80 @@
 // API endpoint handlers implementation
 
+mod address;
+mod admin;
+mod admin_audit;
 mod assets;
+mod blocks;
+mod changefeed;
 mod charms;
+mod collections;
+mod dashboard;
 mod dex_orders; // [RJJ-DEX]
 mod diagnostic;
 mod diagnostics_address;
 mod health;
+mod json_body;
+mod metrics;
+mod mempool_stats;
+mod mints;
+mod negotiate;
+mod pagination;
+mod path_params;
+mod parser_stats;
 mod reset;
 mod stats_holders; // [RJJ-STATS-HOLDERS]
 pub mod status;
 mod transactions;
 pub mod wallet; // [RJJ-WALLET]
 
-use bitcoincore_rpc::Client;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, AtomicU32, Ordering};
 use tokio::sync::Semaphore;
 
 use crate::config::ApiConfig;
 use crate::db::Repositories;
+use crate::services::rpc_clients::RpcClients;
+use crate::services::scan_cache::ScanCache;
+use crate::services::shutdown::Lifecycle;
+use crate::services::tip_cache::TipCache;
+use crate::services::tx_hex_cache::TxHexCache;
 
 // Handler function re-exports
-pub use assets::{get_asset_by_id, get_asset_counts, get_assets, get_reference_nft_by_hash};
+pub use admin::diagnose_config;
+pub use admin_audit::admin_routes;
+pub use assets::{
+    get_asset_by_id, get_asset_counts, get_asset_likes, get_assets, get_reference_nft_by_hash,
+};
+pub use blocks::{get_block, get_blocks};
+pub use changefeed::get_changefeed;
 pub use charms::{
-    get_charm_by_charmid, get_charm_by_txid, get_charm_numbers, get_charms, get_charms_by_address,
-    get_charms_by_type, get_charms_count_by_type, like_charm, unlike_charm,
+    get_address_summary, get_balance_at, get_charm_by_charmid, get_charm_by_txid,
+    get_charm_numbers, get_charms, get_charms_by_address, get_charms_by_type,
+    get_charms_count_by_type, like_charm, unlike_charm,
 };
-pub use dex_orders::{get_all_orders, get_open_orders, get_order_by_id, get_orders_by_asset, get_orders_by_maker}; // [RJJ-DEX]
+pub use collections::{get_collection_assets, get_collections};
+pub use dashboard::get_dashboard;
+pub use mempool_stats::get_mempool_stats;
+pub use mints::{get_asset_mints, get_asset_supply_history, get_mint_feed};
+pub use parser_stats::get_parser_stats;
+pub use dex_orders::{get_all_orders, get_market_summary, get_open_orders, get_order_by_id, get_orders_by_asset, get_orders_by_maker}; // [RJJ-DEX]
 pub use diagnostic::diagnose_database;
 pub use diagnostics_address::diagnostics_address;
-pub use health::health_check;
+pub use health::{health_check, health_ready};
+pub use metrics::get_metrics;
 pub use stats_holders::get_asset_holders; // [RJJ-STATS-HOLDERS]
 pub use status::get_indexer_status;
-pub use transactions::{get_transaction_by_txid, get_transactions};
+pub use transactions::{get_spell_outputs, get_transaction_by_txid, get_transactions, get_tx_hex};
 pub use wallet::{
     broadcast_wallet_transaction, get_wallet_balance, get_wallet_balance_batch,
     get_wallet_chain_tip,
     get_wallet_charm_balances, get_wallet_charm_balances_batch,
     get_wallet_charm_balances_batch_indexed, get_wallet_fee_estimate,
-    get_wallet_prev_txs, get_wallet_transaction, get_wallet_transactions,
+    get_wallet_changes, get_wallet_history, get_wallet_orders, get_wallet_prev_txs,
+    get_wallet_transaction,
+    get_wallet_transactions,
     get_wallet_transactions_batch,
-    get_wallet_tx_hex, get_wallet_utxos, get_wallet_utxos_batch,
+    get_wallet_tx_hex, get_wallet_utxos, get_wallet_utxos_batch, unmonitor_wallet_address,
 }; // [RJJ-WALLET]
 
 /// Circuit breaker for Maestro API.
@@ -117,7 +151,14 @@ pub struct AppState {
     pub scan_semaphore: Arc<Semaphore>,
     pub quicknode_semaphore: Arc<Semaphore>,
     pub http_client: reqwest::Client,
-    pub rpc_mainnet: Arc<Client>,
-    pub rpc_testnet4: Arc<Client>,
+    /// Bitcoin RPC client per enabled network
+    pub rpc_clients: Arc<RpcClients>,
     pub maestro_cb: Arc<MaestroCircuitBreaker>,
+    pub scan_cache: Arc<ScanCache>,
+    /// Raw tx hex fetched from the node, for GET /tx/{txid}/hex
+    pub tx_hex_cache: Arc<TxHexCache>,
+    /// Node tip per network, shared by confirmation counts and scan caching
+    pub tip_cache: Arc<TipCache>,
+    /// Readiness and in-flight requests, for graceful shutdown
+    pub lifecycle: Arc<Lifecycle>,
 }
@@ -0,0 +1,168 @@
+// Content negotiation for the high-volume wallet endpoints. A request with
+// `Accept: application/cbor` gets the same response model encoded with
+// ciborium; any other Accept (or none) keeps JSON. Errors are always JSON.
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::error::ExplorerError;
+
+pub const APPLICATION_CBOR: &str = "application/cbor";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    Cbor,
+}
+
+impl ResponseFormat {
+    /// CBOR only when the Accept header lists `application/cbor`.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let wants_cbor = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .filter_map(|media| media.split(';').next())
+            .any(|media| media.trim().eq_ignore_ascii_case(APPLICATION_CBOR));
+        if wants_cbor {
+            Self::Cbor
+        } else {
+            Self::Json
+        }
+    }
+
+    pub fn respond<T: Serialize>(self, body: T) -> Negotiated<T> {
+        Negotiated { format: self, body }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ResponseFormat {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+/// A response body serialized in the format the client negotiated.
+#[derive(Debug)]
+pub struct Negotiated<T> {
+    format: ResponseFormat,
+    body: T,
+}
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        let mut response = match self.format {
+            ResponseFormat::Json => Json(self.body).into_response(),
+            ResponseFormat::Cbor => {
+                let mut bytes = Vec::new();
+                if let Err(e) = ciborium::into_writer(&self.body, &mut bytes) {
+                    return ExplorerError::InternalError(format!("CBOR encoding failed: {}", e))
+                        .into_response();
+                }
+                (
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(APPLICATION_CBOR),
+                    )],
+                    bytes,
+                )
+                    .into_response()
+            }
+        };
+        // Same URL, two representations: caches must key on Accept.
+        response
+            .headers_mut()
+            .insert(header::VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[test]
+    fn picks_cbor_only_when_accepted() {
+        assert_eq!(
+            ResponseFormat::from_headers(&HeaderMap::new()),
+            ResponseFormat::Json
+        );
+        assert_eq!(
+            ResponseFormat::from_headers(&accept("*/*")),
+            ResponseFormat::Json
+        );
+        assert_eq!(
+            ResponseFormat::from_headers(&accept("application/json")),
+            ResponseFormat::Json
+        );
+        assert_eq!(
+            ResponseFormat::from_headers(&accept("application/json, Application/CBOR;q=0.9")),
+            ResponseFormat::Cbor
+        );
+    }
+
+    /// The CBOR body decodes to exactly what the JSON body carries, for the
+    /// shapes served by `/wallet/charms/{address}` and `/wallet/balance/{address}`.
+    #[tokio::test]
+    async fn cbor_round_trips_to_json_model() {
+        let models = [
+            json!({
+                "address": "bc1qx",
+                "network": "mainnet",
+                "balances": [{
+                    "appId": "t/aa/bb",
+                    "assetType": "token",
+                    "symbol": "BRO",
+                    "confirmed": 1_000_000_000_000i64,
+                    "unconfirmed": 0,
+                    "mempoolSpent": -5,
+                    "utxos": [{"txid": "aa", "vout": 1, "blockHeight": null, "confirmed": false}],
+                }],
+                "count": 1,
+            }),
+            json!({"address": "bc1qx", "confirmed": 546, "monitored": true, "source": "rpc"}),
+        ];
+
+        for model in models {
+            let json_response = ResponseFormat::Json.respond(model.clone()).into_response();
+            let cbor_response = ResponseFormat::Cbor.respond(model.clone()).into_response();
+            assert_eq!(
+                cbor_response.headers()[header::CONTENT_TYPE],
+                APPLICATION_CBOR
+            );
+            assert_eq!(cbor_response.headers()[header::VARY], "accept");
+
+            let from_json: Value =
+                serde_json::from_slice(&body_bytes(json_response).await).unwrap();
+            let from_cbor: Value =
+                ciborium::from_reader(body_bytes(cbor_response).await.as_slice()).unwrap();
+            assert_eq!(from_cbor, from_json);
+            assert_eq!(from_cbor, model);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// Outbound pagination links. Paginated handlers extract `PageLinks` and turn
+// it into `next_url`/`prev_url` (or a cursor link), so clients follow links
+// instead of rebuilding query strings. A link is the request's own URI with
+// only the page or cursor parameter replaced; every other parameter keeps its
+// original encoding. Links start with `PUBLIC_BASE_URL` when set, otherwise
+// with the request's Host header (scheme from `X-Forwarded-Proto`, else http).
+
+use std::convert::Infallible;
+use std::fmt::Display;
+
+use axum::{
+    extract::{FromRequestParts, OriginalUri},
+    http::{header, request::Parts, HeaderMap, Uri},
+};
+
+use crate::handlers::AppState;
+use crate::models::{PageNav, PaginationMeta};
+
+#[derive(Debug, Clone)]
+pub struct PageLinks {
+    /// Scheme and host, no trailing slash; empty when neither is known
+    base: String,
+    path: String,
+    /// The request's raw `key=value` pairs, in order
+    query: Vec<String>,
+}
+
+impl PageLinks {
+    pub fn new(base: &str, uri: &Uri) -> Self {
+        Self {
+            base: base.trim_end_matches('/').to_string(),
+            path: uri.path().to_string(),
+            query: uri
+                .query()
+                .unwrap_or_default()
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// Links for a request to `uri`, based on `public_base_url` when set.
+    pub fn from_request(uri: &Uri, headers: &HeaderMap, public_base_url: Option<&str>) -> Self {
+        let value = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let base = match (public_base_url, value(header::HOST.as_str())) {
+            (Some(base), _) => base.to_string(),
+            (None, Some(host)) => {
+                let scheme = value("x-forwarded-proto")
+                    .and_then(|v| v.split(',').next())
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or("http");
+                format!("{scheme}://{host}")
+            }
+            (None, None) => String::new(),
+        };
+        Self::new(&base, uri)
+    }
+
+    /// This request's URL with `name` set to `value`.
+    pub fn with_param(&self, name: &str, value: impl Display) -> String {
+        let param = format!("{name}={value}");
+        let mut query: Vec<&str> = self
+            .query
+            .iter()
+            .map(String::as_str)
+            .filter(|pair| pair.split('=').next() != Some(name))
+            .collect();
+        query.push(&param);
+        format!("{}{}?{}", self.base, self.path, query.join("&"))
+    }
+
+    /// Neighbours of `page` out of `total_pages`. Past the end, `prev_url`
+    /// points at the last page.
+    pub fn nav(&self, page: u64, total_pages: u64) -> PageNav {
+        let has_next = page < total_pages;
+        let has_prev = page > 1;
+        PageNav {
+            has_next,
+            has_prev,
+            next_url: has_next.then(|| self.with_param("page", page + 1)),
+            prev_url: has_prev.then(|| self.with_param("page", (page - 1).min(total_pages.max(1)))),
+        }
+    }
+
+    /// Fill in `meta.nav` for the page it describes.
+    pub fn link(&self, meta: &mut PaginationMeta) {
+        meta.nav = self.nav(meta.page, meta.total_pages);
+    }
+
+    /// Linked metadata for `page` of `total` rows, `limit` per page.
+    pub fn meta(&self, page: u64, limit: u64, total: u64) -> PaginationMeta {
+        let mut meta = PaginationMeta::new(total, page, limit, total.div_ceil(limit.max(1)));
+        self.link(&mut meta);
+        meta
+    }
+}
+
+impl FromRequestParts<AppState> for PageLinks {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // Routes nested under /v1 see their path without it; links need it.
+        let uri = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map_or(&parts.uri, |original| &original.0);
+        Ok(Self::from_request(
+            uri,
+            &parts.headers,
+            state.config.public_base_url.as_deref(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn links(uri: &str) -> PageLinks {
+        PageLinks::new("https://api.example/", &uri.parse().unwrap())
+    }
+
+    #[test]
+    fn links_replace_only_the_page() {
+        let links = links("/v1/charms?network=testnet4&page=2&operation=mint&q=a%26b");
+        assert_eq!(
+            links.with_param("page", 3),
+            "https://api.example/v1/charms?network=testnet4&operation=mint&q=a%26b&page=3"
+        );
+        assert_eq!(
+            links.with_param("since", 900_000),
+            "https://api.example/v1/charms?network=testnet4&page=2&operation=mint&q=a%26b\
+             &since=900000"
+        );
+    }
+
+    #[test]
+    fn nav_stops_at_both_ends() {
+        let links = links("/v1/assets?limit=10");
+        let first = links.nav(1, 3);
+        assert!(first.has_next && !first.has_prev);
+        assert_eq!(
+            first.next_url.as_deref(),
+            Some("https://api.example/v1/assets?limit=10&page=2")
+        );
+        assert_eq!(first.prev_url, None);
+
+        let last = links.nav(3, 3);
+        assert!(!last.has_next && last.has_prev);
+        assert_eq!(
+            last.prev_url.as_deref(),
+            Some("https://api.example/v1/assets?limit=10&page=2")
+        );
+
+        assert_eq!(links.nav(1, 0), PageNav::default());
+        let past_end = links.nav(9, 3);
+        assert!(!past_end.has_next);
+        assert_eq!(
+            past_end.prev_url.as_deref(),
+            Some("https://api.example/v1/assets?limit=10&page=3")
+        );
+    }
+
+    #[test]
+    fn base_url_prefers_config_over_host() {
+        let uri: Uri = "/v1/blocks?page=1".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("internal:8000"));
+
+        let from_host = PageLinks::from_request(&uri, &headers, None);
+        assert_eq!(
+            from_host.with_param("page", 2),
+            "http://internal:8000/v1/blocks?page=2"
+        );
+
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("https, http"));
+        let proxied = PageLinks::from_request(&uri, &headers, None);
+        assert_eq!(
+            proxied.with_param("page", 2),
+            "https://internal:8000/v1/blocks?page=2"
+        );
+
+        let configured =
+            PageLinks::from_request(&uri, &headers, Some("https://api.charms.example"));
+        assert_eq!(
+            configured.with_param("page", 2),
+            "https://api.charms.example/v1/blocks?page=2"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+// Parser statistics handler: what the indexer's spell parser saw over a
+// time range, from the hourly `parser_stats` buckets.
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::{ExplorerError, ExplorerResult};
+use crate::handlers::AppState;
+
+fn default_network() -> String {
+    "mainnet".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParserStatsQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+    /// RFC 3339; first hour included. Open when absent.
+    pub from: Option<DateTime<Utc>>,
+    /// RFC 3339; buckets starting at or after it are left out.
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// GET /stats/parser?network=mainnet&from=2026-08-01T00:00:00Z&to=2026-08-02T00:00:00Z
+/// Txs scanned, spell envelopes found, spells parsed and parse failures by
+/// reason, summed per source (confirmed blocks, mempool). Buckets are
+/// hourly, so `from` and `to` select whole hours.
+pub async fn get_parser_stats(
+    State(state): State<AppState>,
+    Query(params): Query<ParserStatsQuery>,
+) -> ExplorerResult<Json<Value>> {
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from >= to {
+            return Err(ExplorerError::InvalidRequest(
+                "`from` must be before `to`".to_string(),
+            ));
+        }
+    }
+    let totals = state
+        .repositories
+        .parser_stats
+        .totals(&params.network, params.from, params.to)
+        .await
+        .map_err(|e| ExplorerError::DatabaseError(e.to_string()))?;
+
+    Ok(Json(json!({
+        "network": params.network,
+        "from": params.from,
+        "to": params.to,
+        "block": totals.block,
+        "mempool": totals.mempool,
+    })))
+}
@@ -0,0 +1,322 @@
+// Validated path parameters. Handlers taking a txid, app_id, DEX order id
+// or identity hash extract it through these instead of `Path<String>`, so a
+// malformed value is a 400 naming what was expected before any query runs,
+// rather than a wasted lookup (or a LIKE pattern with stray wildcards).
+//
+// Grammar, as the indexer writes it:
+// - txid, identity hash: 64 hex characters, stored lowercase
+// - app_id: `<tag>/<identity>/<vk>`, tag one of n t b B d c, both hashes 64 hex
+// - DEX order id: `<txid>:<vout>`, vout at most u16::MAX
+//
+// Hex is accepted in either case and passed on lowercase. Offending values
+// are not echoed back: they can be arbitrarily long.
+
+use axum::{
+    extract::{FromRequestParts, Path},
+    http::request::Parts,
+};
+
+use crate::error::{ExplorerError, ExplorerResult};
+
+/// Hex characters in a txid or hash.
+const HASH_LEN: usize = 64;
+
+/// App tags the indexer stores (see `charms_core::AppKind`).
+const APP_TAGS: &[char] = &['n', 't', 'b', 'B', 'd', 'c'];
+
+fn invalid(what: &str, expected: &str) -> ExplorerError {
+    ExplorerError::InvalidRequest(format!("invalid {}: expected {}", what, expected))
+}
+
+fn is_hash(s: &str) -> bool {
+    s.len() == HASH_LEN && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// A 64-hex txid, lowercased.
+pub fn parse_txid(raw: &str) -> ExplorerResult<String> {
+    if !is_hash(raw) {
+        return Err(invalid("txid", "64 hex characters"));
+    }
+    Ok(raw.to_ascii_lowercase())
+}
+
+/// A 64-hex identity hash, lowercased.
+pub fn parse_hash(raw: &str) -> ExplorerResult<String> {
+    if !is_hash(raw) {
+        return Err(invalid("hash", "64 hex characters"));
+    }
+    Ok(raw.to_ascii_lowercase())
+}
+
+/// An app_id with its hashes lowercased; the tag is case-sensitive.
+pub fn parse_app_id(raw: &str) -> ExplorerResult<String> {
+    let err = || {
+        invalid(
+            "app_id",
+            "<tag>/<64 hex>/<64 hex> with tag one of n, t, b, B, d, c",
+        )
+    };
+    let mut parts = raw.splitn(3, '/');
+    let (Some(tag), Some(identity), Some(vk)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(err());
+    };
+    let mut tag_chars = tag.chars();
+    let (Some(tag), None) = (tag_chars.next(), tag_chars.next()) else {
+        return Err(err());
+    };
+    if !APP_TAGS.contains(&tag) || !is_hash(identity) || !is_hash(vk) {
+        return Err(err());
+    }
+    Ok(format!(
+        "{}/{}/{}",
+        tag,
+        identity.to_ascii_lowercase(),
+        vk.to_ascii_lowercase()
+    ))
+}
+
+/// An output index: decimal, at most u16::MAX.
+pub fn parse_vout(raw: &str) -> ExplorerResult<u16> {
+    // `u16::from_str` takes a leading `+`; an index does not.
+    if raw.is_empty() || !raw.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid("vout", "a decimal output index"));
+    }
+    raw.parse::<u16>()
+        .map_err(|_| invalid("vout", "an output index of at most 65535"))
+}
+
+/// A DEX order id, `<txid>:<vout>`, re-assembled canonical.
+pub fn parse_order_id(raw: &str) -> ExplorerResult<String> {
+    let (txid, vout) = raw
+        .split_once(':')
+        .ok_or_else(|| invalid("order id", "<txid>:<vout>"))?;
+    Ok(format!("{}:{}", parse_txid(txid)?, parse_vout(vout)?))
+}
+
+/// A numeric asset id, or else an app_id.
+pub fn parse_asset_id(raw: &str) -> ExplorerResult<AssetIdPath> {
+    if !raw.is_empty() && raw.bytes().all(|b| b.is_ascii_digit()) {
+        return raw
+            .parse()
+            .map(AssetIdPath::Id)
+            .map_err(|_| invalid("asset id", "a numeric id or an app_id"));
+    }
+    parse_app_id(raw)
+        .map(AssetIdPath::AppId)
+        .map_err(|_| invalid("asset id", "a numeric id or an app_id"))
+}
+
+/// The raw path segment, or a 400 when the route captured none.
+async fn raw_segment<S: Send + Sync>(parts: &mut Parts, state: &S) -> ExplorerResult<String> {
+    Path::<String>::from_request_parts(parts, state)
+        .await
+        .map(|Path(raw)| raw)
+        .map_err(|e| ExplorerError::InvalidRequest(e.body_text()))
+}
+
+/// `{txid}` path segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxidPath(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for TxidPath {
+    type Rejection = ExplorerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        parse_txid(&raw_segment(parts, state).await?).map(Self)
+    }
+}
+
+/// `{app_id}` path segment (also `{charmid}`, which is an app_id).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppIdPath(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for AppIdPath {
+    type Rejection = ExplorerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        parse_app_id(&raw_segment(parts, state).await?).map(Self)
+    }
+}
+
+/// `{hash}` path segment: an app identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashPath(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for HashPath {
+    type Rejection = ExplorerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        parse_hash(&raw_segment(parts, state).await?).map(Self)
+    }
+}
+
+/// `{order_id}` path segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderIdPath(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for OrderIdPath {
+    type Rejection = ExplorerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        parse_order_id(&raw_segment(parts, state).await?).map(Self)
+    }
+}
+
+/// `{asset_id}` path segment: a numeric asset id or an app_id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetIdPath {
+    Id(i32),
+    AppId(String),
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AssetIdPath {
+    type Rejection = ExplorerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        parse_asset_id(&raw_segment(parts, state).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "7269cf1b2bc9e513440224ebebabcbd3a4a544d0adb6c5d8ca302953958bc4af";
+    const APP_ID: &str = "t/3d7fe7e4cea6121947af73d70e5119bebd8aa5b7edfe74bfaf6e779a1847bd9b/c975d4e0c292fb95efbda5c13312d6ac1d8b5aeff7f0f1e5578645a2da70ff5f";
+
+    fn message(err: ExplorerError) -> String {
+        match err {
+            ExplorerError::InvalidRequest(msg) => msg,
+            other => panic!("expected a 400, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_stored_forms_and_lowercases_hex() {
+        assert_eq!(parse_txid(TXID).unwrap(), TXID);
+        assert_eq!(parse_txid(&TXID.to_uppercase()).unwrap(), TXID);
+        assert_eq!(parse_app_id(APP_ID).unwrap(), APP_ID);
+        let shouted = format!("t/{}", APP_ID[2..].to_uppercase());
+        assert_eq!(parse_app_id(&shouted).unwrap(), APP_ID);
+        assert_eq!(
+            parse_order_id(&format!("{TXID}:65535")).unwrap(),
+            format!("{TXID}:65535")
+        );
+        assert_eq!(parse_asset_id("42").unwrap(), AssetIdPath::Id(42));
+        assert_eq!(
+            parse_asset_id(APP_ID).unwrap(),
+            AssetIdPath::AppId(APP_ID.to_string())
+        );
+    }
+
+    #[test]
+    fn rejections_say_what_was_expected() {
+        assert!(message(parse_txid("abc").unwrap_err()).contains("64 hex"));
+        assert!(message(parse_txid(&"z".repeat(64)).unwrap_err()).contains("txid"));
+        assert!(message(parse_app_id("n/a/a").unwrap_err()).contains("<tag>/"));
+        assert!(message(parse_app_id(&APP_ID.replacen('t', "x", 1)).unwrap_err()).contains("tag"));
+        assert!(message(parse_vout("65536").unwrap_err()).contains("65535"));
+        assert!(message(parse_vout("+1").unwrap_err()).contains("decimal"));
+        assert!(message(parse_order_id(TXID).unwrap_err()).contains("<txid>:<vout>"));
+        assert!(message(parse_asset_id("99999999999").unwrap_err()).contains("asset id"));
+        let huge = "%".repeat(5000);
+        for err in [
+            parse_txid(&huge).unwrap_err(),
+            parse_app_id(&huge).unwrap_err(),
+            parse_asset_id(&huge).unwrap_err(),
+        ] {
+            assert!(!message(err).contains('%'));
+        }
+    }
+
+    /// Deterministic xorshift, so a failure reproduces.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    /// Characters near the grammar (hex, separators, tags, wildcards) and
+    /// some far from it (multi-byte, controls).
+    const ALPHABET: &[char] = &[
+        '0', '7', '9', 'a', 'f', 'A', 'F', 'g', 'z', 'n', 't', 'b', 'B', 'd', 'c', '/', ':', '%',
+        '_', '+', '-', ' ', '\0', 'é', 'ß', '😀',
+    ];
+
+    fn random_string(rng: &mut Rng) -> String {
+        let len = match rng.below(4) {
+            0 => rng.below(8),
+            1 => HASH_LEN + rng.below(3),
+            2 => 2 * HASH_LEN + 3,
+            _ => rng.below(300),
+        };
+        (0..len)
+            .map(|_| ALPHABET[rng.below(ALPHABET.len())])
+            .collect()
+    }
+
+    /// Flip, drop or insert one character of a valid value.
+    fn mutate(rng: &mut Rng, valid: &str) -> String {
+        let mut chars: Vec<char> = valid.chars().collect();
+        let at = rng.below(chars.len());
+        match rng.below(3) {
+            0 => chars[at] = ALPHABET[rng.below(ALPHABET.len())],
+            1 => {
+                chars.remove(at);
+            }
+            _ => chars.insert(at, ALPHABET[rng.below(ALPHABET.len())]),
+        }
+        chars.into_iter().collect()
+    }
+
+    fn reference_app_id(s: &str) -> bool {
+        let parts: Vec<&str> = s.split('/').collect();
+        parts.len() == 3
+            && ["n", "t", "b", "B", "d", "c"].contains(&parts[0])
+            && is_hash(parts[1])
+            && is_hash(parts[2])
+    }
+
+    #[test]
+    fn fuzzed_inputs_never_panic_and_match_the_grammar() {
+        let mut rng = Rng(0x5eed_cafe_f00d_d00d);
+        for round in 0..20_000 {
+            let input = match round % 3 {
+                0 => random_string(&mut rng),
+                1 => mutate(&mut rng, TXID),
+                _ => mutate(&mut rng, APP_ID),
+            };
+
+            let txid = parse_txid(&input);
+            assert_eq!(txid.is_ok(), is_hash(&input), "txid {input:?}");
+            if let Ok(t) = txid {
+                assert_eq!(t, input.to_ascii_lowercase());
+            }
+
+            let app_id = parse_app_id(&input);
+            assert_eq!(app_id.is_ok(), reference_app_id(&input), "app_id {input:?}");
+            if let Ok(a) = app_id {
+                assert!(parse_app_id(&a).is_ok_and(|again| again == a));
+            }
+
+            let order = parse_order_id(&input);
+            let expected = input.split_once(':').is_some_and(|(t, v)| {
+                is_hash(t) && v.bytes().all(|b| b.is_ascii_digit()) && v.parse::<u16>().is_ok()
+            });
+            assert_eq!(order.is_ok(), expected, "order id {input:?}");
+
+            if let Err(e) = parse_asset_id(&input) {
+                assert!(message(e).starts_with("invalid asset id"));
+            }
+        }
+    }
+}
@@ -1,20 +1,26 @@
 // Indexer reset endpoint handler implementation
 
-use axum::{Json, extract::State, response::IntoResponse};
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
 use serde_json::{Value, json};
 
 use crate::handlers::AppState;
 
-/// Handler for POST /reset - Resets the indexer state
+/// Handler for POST /admin/reset - Resets the indexer state. The admin
+/// router checks the token and audits the call.
 pub async fn reset_indexer(State(app_state): State<AppState>) -> impl IntoResponse {
-    let conn = app_state.repositories.charm.get_connection();
-    let result = perform_reset(conn).await;
-    Json(result)
+    let conn = app_state.repositories.connection();
+    let (success, result) = perform_reset(conn).await;
+    let status = if success {
+        StatusCode::OK
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    (status, Json(result))
 }
 
 /// Performs the reset operation by clearing all indexer tables
-async fn perform_reset(conn: &DatabaseConnection) -> Value {
+async fn perform_reset(conn: &DatabaseConnection) -> (bool, Value) {
     // Clear all tables - use block_status instead of legacy bookmark
     let block_status_result = clear_table(conn, "block_status").await;
     let transactions_result = clear_table(conn, "transactions").await;
@@ -34,7 +40,7 @@ async fn perform_reset(conn: &DatabaseConnection) -> Value {
         errors.push(format!("Failed to clear charms table: {}", e));
     }
 
-    json!({
+    let result = json!({
         "success": success,
         "message": if success {
             "Indexer has been reset. All tables have been cleared. Restart the indexer service to begin indexing from the beginning.".to_string()
@@ -45,19 +51,25 @@ async fn perform_reset(conn: &DatabaseConnection) -> Value {
             "block_status": block_status_result.is_ok(),
             "transactions": transactions_result.is_ok(),
             "charms": charms_result.is_ok()
+        },
+        "rows_deleted": {
+            "block_status": block_status_result.ok(),
+            "transactions": transactions_result.ok(),
+            "charms": charms_result.ok()
         }
-    })
+    });
+    (success, result)
 }
 
-/// Clears all rows from a table
-async fn clear_table(conn: &DatabaseConnection, table: &str) -> Result<(), String> {
+/// Clears all rows from a table, returning how many were deleted
+async fn clear_table(conn: &DatabaseConnection, table: &str) -> Result<u64, String> {
     let query = format!("DELETE FROM {}", table);
 
     match conn
         .execute(Statement::from_string(conn.get_database_backend(), query))
         .await
     {
-        Ok(_) => Ok(()),
+        Ok(result) => Ok(result.rows_affected()),
         Err(e) => Err(format!("Failed to clear table {}: {}", table, e)),
     }
 }
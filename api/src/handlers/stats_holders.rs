@@ -1,20 +1,45 @@
 // [RJJ-STATS-HOLDERS] Handlers for holder statistics endpoints
 
 use axum::{
-    extract::{Path, State},
+    extract::{Query, State},
     Json,
 };
+use serde::Deserialize;
 
 use crate::error::ExplorerResult;
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::path_params::AppIdPath;
 use crate::handlers::AppState;
+use crate::models::PageLimits;
 use crate::services::stats_holders_service::{self, HoldersResponse};
 
+#[derive(Debug, Deserialize, Default)]
+pub struct HoldersQuery {
+    pub page: Option<u64>,
+    /// Defaults to the largest page allowed.
+    pub limit: Option<u64>,
+    /// Network the asset's decimals are read from; defaults to mainnet.
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+}
+
 /// [RJJ-STATS-HOLDERS] Handler for GET /assets/{app_id}/holders
-/// Returns holder statistics for a specific asset
+/// Returns holder statistics for a specific asset, one page of holders at a time
 pub async fn get_asset_holders(
     State(state): State<AppState>,
-    Path(app_id): Path<String>,
+    AppIdPath(app_id): AppIdPath,
+    Query(query): Query<HoldersQuery>,
+    links: PageLinks,
 ) -> ExplorerResult<Json<HoldersResponse>> {
-    let response = stats_holders_service::get_holders_by_app_id(&state, &app_id).await?;
+    let limits = PageLimits::global();
+    let (page, limit) = limits.apply(
+        query.page.unwrap_or(1),
+        query.limit.unwrap_or(limits.max_limit),
+    )?;
+    let network = query.network.as_deref().unwrap_or("mainnet");
+    let mut response =
+        stats_holders_service::get_holders_by_app_id(&state, &app_id, network, page, limit)
+            .await?;
+    response.nav = links.nav(page, (response.total_holders as u64).div_ceil(limit));
     Ok(Json(response))
 }
@@ -8,7 +8,7 @@ use crate::handlers::AppState;
 
 /// Handler for GET /status - Returns the indexer status
 pub async fn get_indexer_status(State(app_state): State<AppState>) -> impl IntoResponse {
-    let conn = app_state.repositories.charm.get_connection();
+    let conn = app_state.repositories.connection();
 
     // Run both network status queries in parallel
     let (testnet4_status, mainnet_status) = tokio::join!(
@@ -26,3 +26,46 @@ pub async fn get_indexer_status(State(app_state): State<AppState>) -> impl IntoR
 
     Json(combined_status)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app_state, repositories};
+
+    /// The test repositories point at an unreachable database: both networks
+    /// report the failure, with `charm_stats` shaped as on success.
+    #[tokio::test]
+    async fn an_unreachable_database_keeps_the_charm_stats_shape() {
+        let response = get_indexer_status(State(app_state(repositories())))
+            .await
+            .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let status: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        for network in ["testnet4", "mainnet"] {
+            let network_status = &status["networks"][network];
+            assert_eq!(network_status["indexer_status"]["status"], "database_error");
+            let charm_stats = network_status["charm_stats"].as_object().unwrap();
+            let keys: Vec<_> = charm_stats.keys().map(String::as_str).collect();
+            assert_eq!(
+                keys,
+                [
+                    "charms_by_asset_type",
+                    "confirmation_rate",
+                    "confirmed_transactions",
+                    "parser_stats",
+                    "pending_spells",
+                    "recent_charms",
+                    "stats_source",
+                    "total_charms",
+                    "total_transactions",
+                    "unconfirmed_charms",
+                ]
+            );
+            assert_eq!(charm_stats["total_charms"], 0);
+            assert_eq!(charm_stats["charms_by_asset_type"][0]["count"], 0);
+        }
+    }
+}
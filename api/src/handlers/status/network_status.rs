@@ -1,8 +1,17 @@
 // Simplified network status module that uses the Summary table
 
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use std::future::Future;
+
+use charms_core::AssetType;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Statement,
+};
 use serde_json::{Value, json};
 
+use crate::db::repositories::{
+    BlocksRepository, MempoolStatsRepository, ParserStatsRepository, TIMING_WINDOW,
+};
 use crate::entity::prelude::*;
 use crate::entity::{charms, summary};
 
@@ -19,16 +28,47 @@ pub async fn get_network_status(conn: &DatabaseConnection, network_type: &str) -
         .filter(summary::Column::Network.eq(db_network))
         .one(conn)
         .await;
+    // Only a missing row falls back to the live scans: a database that just
+    // failed is reported as such rather than scanned.
+    let summary = match summary_result {
+        Ok(summary) => summary,
+        Err(err) => return database_error_status(network_type, &err),
+    };
 
     // Get recent charms (last 10)
     let recent_charms_result = Charms::find()
         .filter(charms::Column::Network.eq(db_network))
+        .filter(charms::Column::IsPlaceholder.eq(false))
         .order_by_desc(charms::Column::BlockHeight)
         .order_by_desc(charms::Column::DateCreated)
         .limit(10)
         .all(conn)
         .await;
 
+    let replicas = get_replicas(conn, db_network).await;
+    let reindex = get_reindex(conn, db_network).await;
+    let pending_spells = count_pending_spells(conn, db_network).await;
+    let skipped_blocks = count_skipped_blocks(conn, db_network).await;
+    let block_timings = BlocksRepository::new(conn.clone())
+        .timing_averages(db_network, TIMING_WINDOW)
+        .await
+        .map(|averages| json!(averages))
+        .unwrap_or(Value::Null);
+    let unconfirmed_charms = MempoolStatsRepository::new(conn.clone())
+        .unconfirmed_count(db_network)
+        .await
+        .unwrap_or(0);
+    let parser_stats = ParserStatsRepository::new(conn.clone())
+        .totals(db_network, None, None)
+        .await
+        .map(|totals| json!(totals))
+        .unwrap_or(Value::Null);
+    let leader = replicas
+        .iter()
+        .find(|r| r["role"] == "leader")
+        .map(|r| r["instance_id"].clone())
+        .unwrap_or(Value::Null);
+
     let recent_charms_json: Vec<Value> = match recent_charms_result {
         Ok(charms_list) => charms_list
             .into_iter()
@@ -46,36 +86,41 @@ pub async fn get_network_status(conn: &DatabaseConnection, network_type: &str) -
         Err(_) => vec![],
     };
 
-    match summary_result {
-        Ok(Some(summary)) => {
-            // Build asset type breakdown
-            let asset_types = json!([
-                {
-                    "asset_type": "nft",
-                    "count": summary.nft_count
-                },
-                {
-                    "asset_type": "token",
-                    "count": summary.token_count
-                },
-                {
-                    "asset_type": "dapp",
-                    "count": summary.dapp_count
-                },
-                {
-                    "asset_type": "other",
-                    "count": summary.other_count
-                }
-            ]);
+    // The webapp polls this endpoint: charm totals come from the summary the
+    // indexer maintains, never from scans, unless there is no summary yet.
+    let stats = charm_stats(summary.as_ref(), || live_charm_stats(conn, db_network)).await;
+    let charm_stats_json = stats.to_json(
+        unconfirmed_charms,
+        pending_spells,
+        parser_stats,
+        recent_charms_json,
+    );
 
+    match summary {
+        Some(summary) => {
             // Construct the final response
             json!({
                 "indexer_status": {
-                    "status": determine_status(&summary.last_updated),
+                    "status": if summary.indexer_paused {
+                        "paused"
+                    } else {
+                        determine_status(&summary.last_updated)
+                    },
+                    "paused": summary.indexer_paused,
+                    "provider_stale": summary.provider_stale,
                     "last_processed_block": summary.last_processed_block,
                     "latest_confirmed_block": summary.latest_confirmed_block,
                     "last_updated_at": summary.last_updated.to_string(),
-                    "last_indexer_loop_time": summary.last_updated.to_string()
+                    "last_indexer_loop_time": summary.last_updated.to_string(),
+                    "skipped_blocks": skipped_blocks,
+                    "block_timings": block_timings,
+                    "leader": leader,
+                    "replicas": replicas,
+                    "reindex_in_progress": reindex,
+                    "gc": {
+                        "last_run_at": summary.last_gc_at.map(|t| t.to_string()),
+                        "removed": summary.last_gc_stats
+                    }
                 },
                 "bitcoin_node": {
                     "status": summary.bitcoin_node_status,
@@ -83,14 +128,7 @@ pub async fn get_network_status(conn: &DatabaseConnection, network_type: &str) -
                     "block_count": summary.bitcoin_node_block_count,
                     "best_block_hash": summary.bitcoin_node_best_block_hash
                 },
-                "charm_stats": {
-                    "total_charms": summary.total_charms,
-                    "total_transactions": summary.total_transactions,
-                    "confirmed_transactions": summary.confirmed_transactions,
-                    "confirmation_rate": summary.confirmation_rate,
-                    "charms_by_asset_type": asset_types,
-                    "recent_charms": recent_charms_json
-                },
+                "charm_stats": charm_stats_json,
                 "tag_stats": {
                     "charms_cast_count": summary.charms_cast_count,
                     "bro_count": summary.bro_count,
@@ -98,15 +136,26 @@ pub async fn get_network_status(conn: &DatabaseConnection, network_type: &str) -
                 }
             })
         }
-        _ => {
-            // Fallback to default values if query fails
+        None => {
+            // No summary yet: default values, with live charm counts
             json!({
                 "indexer_status": {
                     "status": "unknown",
+                    "paused": false,
+                    "provider_stale": false,
                     "last_processed_block": 0,
                     "latest_confirmed_block": 0,
                     "last_updated_at": "Never",
-                    "last_indexer_loop_time": "Never"
+                    "last_indexer_loop_time": "Never",
+                    "skipped_blocks": skipped_blocks,
+                    "block_timings": block_timings,
+                    "leader": leader,
+                    "replicas": replicas,
+                    "reindex_in_progress": reindex,
+                    "gc": {
+                        "last_run_at": null,
+                        "removed": null
+                    }
                 },
                 "bitcoin_node": {
                     "status": "unknown",
@@ -114,13 +163,7 @@ pub async fn get_network_status(conn: &DatabaseConnection, network_type: &str) -
                     "block_count": 0,
                     "best_block_hash": "unknown"
                 },
-                "charm_stats": {
-                    "total_charms": 0,
-                    "total_transactions": 0,
-                    "confirmed_transactions": 0,
-                    "confirmation_rate": 0,
-                    "charms_by_asset_type": []
-                },
+                "charm_stats": charm_stats_json,
                 "tag_stats": {
                     "charms_cast_count": 0,
                     "bro_count": 0,
@@ -131,8 +174,258 @@ pub async fn get_network_status(conn: &DatabaseConnection, network_type: &str) -
     }
 }
 
+/// Degraded status for a network whose summary could not be read.
+/// `charm_stats` keeps its usual keys, zeroed.
+fn database_error_status(network_type: &str, err: &DbErr) -> Value {
+    json!({
+        "indexer_status": {
+            "status": "database_error",
+            "error": err.to_string()
+        },
+        "bitcoin_node": {
+            "status": "unknown",
+            "network": network_type
+        },
+        "charm_stats": CharmStats::unavailable().to_json(0, 0, Value::Null, vec![])
+    })
+}
+
+/// Totals shown under `charm_stats`.
+#[derive(Debug, Clone, PartialEq)]
+struct CharmStats {
+    total_charms: i64,
+    total_transactions: i64,
+    confirmed_transactions: i64,
+    confirmation_rate: i32,
+    nft_count: i64,
+    token_count: i64,
+    dapp_count: i64,
+    other_count: i64,
+    /// `summary` when read from the indexer's aggregates, `live` when counted
+    /// on the spot, `unavailable` when the database could not be read.
+    source: &'static str,
+}
+
+impl CharmStats {
+    fn from_summary(summary: &summary::Model) -> Self {
+        Self {
+            total_charms: summary.total_charms,
+            total_transactions: summary.total_transactions,
+            confirmed_transactions: summary.confirmed_transactions,
+            confirmation_rate: summary.confirmation_rate,
+            nft_count: summary.nft_count,
+            token_count: summary.token_count,
+            dapp_count: summary.dapp_count,
+            other_count: summary.other_count,
+            source: "summary",
+        }
+    }
+
+    /// All zero, for a database that could not be read.
+    fn unavailable() -> Self {
+        Self {
+            total_charms: 0,
+            total_transactions: 0,
+            confirmed_transactions: 0,
+            confirmation_rate: 0,
+            nft_count: 0,
+            token_count: 0,
+            dapp_count: 0,
+            other_count: 0,
+            source: "unavailable",
+        }
+    }
+
+    /// The `charm_stats` object, with the counts read next to these totals.
+    fn to_json(
+        &self,
+        unconfirmed_charms: i64,
+        pending_spells: i64,
+        parser_stats: Value,
+        recent_charms: Vec<Value>,
+    ) -> Value {
+        json!({
+            "total_charms": self.total_charms,
+            "unconfirmed_charms": unconfirmed_charms,
+            "pending_spells": pending_spells,
+            "parser_stats": parser_stats,
+            "total_transactions": self.total_transactions,
+            "confirmed_transactions": self.confirmed_transactions,
+            "confirmation_rate": self.confirmation_rate,
+            "charms_by_asset_type": self.by_asset_type(),
+            "recent_charms": recent_charms,
+            "stats_source": self.source
+        })
+    }
+
+    fn by_asset_type(&self) -> Value {
+        json!([
+            { "asset_type": AssetType::Nft.as_str(), "count": self.nft_count },
+            { "asset_type": AssetType::Token.as_str(), "count": self.token_count },
+            { "asset_type": AssetType::Dapp.as_str(), "count": self.dapp_count },
+            { "asset_type": AssetType::Other.as_str(), "count": self.other_count }
+        ])
+    }
+}
+
+/// Charm totals from the summary row the indexer keeps up to date per block.
+/// `live` scans charms and transactions, so it only runs when that row is
+/// missing (fresh database, or before the first processed block).
+async fn charm_stats<F, Fut>(summary: Option<&summary::Model>, live: F) -> CharmStats
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = CharmStats>,
+{
+    match summary {
+        Some(summary) => CharmStats::from_summary(summary),
+        None => live().await,
+    }
+}
+
+/// The summary's aggregates counted from the tables: non-placeholder charms
+/// per asset type, and transactions by status. Zero where a query fails.
+async fn live_charm_stats(conn: &DatabaseConnection, network: &str) -> CharmStats {
+    let count = |row: &Option<sea_orm::QueryResult>, col: &str| {
+        row.as_ref()
+            .and_then(|r| r.try_get::<i64>("", col).ok())
+            .unwrap_or(0)
+    };
+    let charms = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS total, \
+                    COUNT(*) FILTER (WHERE asset_type = 'nft') AS nft, \
+                    COUNT(*) FILTER (WHERE asset_type = 'token') AS token, \
+                    COUNT(*) FILTER (WHERE asset_type = 'dapp') AS dapp \
+               FROM charms WHERE network = $1 AND NOT is_placeholder",
+            [network.into()],
+        ))
+        .await
+        .ok()
+        .flatten();
+    let txs = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS total, COUNT(*) FILTER (WHERE status = 'confirmed') AS confirmed \
+               FROM transactions WHERE network = $1",
+            [network.into()],
+        ))
+        .await
+        .ok()
+        .flatten();
+
+    let total_charms = count(&charms, "total");
+    let (nft_count, token_count, dapp_count) =
+        (count(&charms, "nft"), count(&charms, "token"), count(&charms, "dapp"));
+    let total_transactions = count(&txs, "total");
+    let confirmed_transactions = count(&txs, "confirmed");
+    CharmStats {
+        total_charms,
+        total_transactions,
+        confirmed_transactions,
+        confirmation_rate: if total_transactions > 0 {
+            (confirmed_transactions * 100 / total_transactions) as i32
+        } else {
+            0
+        },
+        nft_count,
+        token_count,
+        dapp_count,
+        other_count: total_charms - nft_count - token_count - dapp_count,
+        source: "live",
+    }
+}
+
+/// Indexer replicas heard from in the last 30 seconds, leader first. Each
+/// replica heartbeats its role (`leader` or `standby`) every second or so.
+async fn get_replicas(conn: &DatabaseConnection, network: &str) -> Vec<Value> {
+    let rows = conn
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT instance_id, role, role_since, last_seen FROM indexer_replicas \
+              WHERE network = $1 AND last_seen > NOW() - INTERVAL '30 seconds' \
+              ORDER BY role = 'leader' DESC, instance_id",
+            [network.into()],
+        ))
+        .await
+        .unwrap_or_default();
+
+    rows.iter()
+        .filter_map(|r| {
+            let role_since: chrono::DateTime<chrono::Utc> = r.try_get("", "role_since").ok()?;
+            let last_seen: chrono::DateTime<chrono::Utc> = r.try_get("", "last_seen").ok()?;
+            Some(json!({
+                "instance_id": r.try_get::<String>("", "instance_id").ok()?,
+                "role": r.try_get::<String>("", "role").ok()?,
+                "role_since": role_since.to_string(),
+                "last_seen": last_seen.to_string()
+            }))
+        })
+        .collect()
+}
+
+/// The `reindex` run holding the network, or null. The live indexer pauses
+/// block processing while a run holds it; a run that stopped refreshing its
+/// lock for 5 minutes (the indexer's `LOCK_STALE_SECS`) no longer does.
+async fn get_reindex(conn: &DatabaseConnection, network: &str) -> Value {
+    let row = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT run_id, from_height, to_height, started_at, heartbeat_at \
+               FROM reindex_locks \
+              WHERE network = $1 AND heartbeat_at > NOW() - INTERVAL '300 seconds'",
+            [network.into()],
+        ))
+        .await
+        .ok()
+        .flatten();
+
+    row.and_then(|r| {
+        let started_at: chrono::DateTime<chrono::Utc> = r.try_get("", "started_at").ok()?;
+        let heartbeat_at: chrono::DateTime<chrono::Utc> = r.try_get("", "heartbeat_at").ok()?;
+        Some(json!({
+            "run_id": r.try_get::<String>("", "run_id").ok()?,
+            "from_height": r.try_get::<i64>("", "from_height").ok()?,
+            "to_height": r.try_get::<i64>("", "to_height").ok()?,
+            "started_at": started_at.to_string(),
+            "heartbeat_at": heartbeat_at.to_string()
+        }))
+    })
+    .unwrap_or(Value::Null)
+}
+
+/// Transactions the indexer kept aside because their spell version is newer
+/// than its parser supports; they are reprocessed after an upgrade.
+async fn count_pending_spells(conn: &DatabaseConnection, network: &str) -> i64 {
+    conn.query_one(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT COUNT(*) AS n FROM pending_spells WHERE network = $1",
+        [network.into()],
+    ))
+    .await
+    .ok()
+    .flatten()
+    .and_then(|r| r.try_get::<i64>("", "n").ok())
+    .unwrap_or(0)
+}
+
+/// Blocks the node did not have (pruned or missing) when the indexer reached
+/// them; the indexer retries them periodically and fills them in.
+async fn count_skipped_blocks(conn: &DatabaseConnection, network: &str) -> i64 {
+    conn.query_one(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT COUNT(*) AS n FROM block_status WHERE network = $1 AND skipped_pruned",
+        [network.into()],
+    ))
+    .await
+    .ok()
+    .flatten()
+    .and_then(|r| r.try_get::<i64>("", "n").ok())
+    .unwrap_or(0)
+}
+
 /// Helper function to determine status based on last_updated timestamp
-fn determine_status(last_updated: &chrono::DateTime<chrono::Utc>) -> &'static str {
+pub(crate) fn determine_status(last_updated: &chrono::DateTime<chrono::Utc>) -> &'static str {
     let now = chrono::Utc::now();
     let duration = now.signed_duration_since(*last_updated);
     let seconds = duration.num_seconds();
@@ -145,3 +438,69 @@ fn determine_status(last_updated: &chrono::DateTime<chrono::Utc>) -> &'static st
         "inactive"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary_row() -> summary::Model {
+        let at = chrono::DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+        summary::Model {
+            id: 1,
+            network: "mainnet".to_string(),
+            last_processed_block: 900_000,
+            latest_confirmed_block: 899_995,
+            total_charms: 120,
+            total_transactions: 50,
+            confirmed_transactions: 45,
+            confirmation_rate: 90,
+            nft_count: 20,
+            token_count: 90,
+            dapp_count: 4,
+            other_count: 6,
+            bitcoin_node_status: "connected".to_string(),
+            bitcoin_node_block_count: 900_005,
+            bitcoin_node_best_block_hash: "00".to_string(),
+            last_updated: at,
+            created_at: at,
+            updated_at: at,
+            charms_cast_count: 0,
+            bro_count: 0,
+            dex_orders_count: 0,
+            indexer_paused: false,
+            provider_stale: false,
+            last_gc_at: None,
+            last_gc_stats: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn summary_row_means_no_live_scan() {
+        let stats = charm_stats(Some(&summary_row()), || async {
+            panic!("live charm statistics queried although the summary exists")
+        })
+        .await;
+
+        assert_eq!(stats.source, "summary");
+        assert_eq!((stats.total_charms, stats.confirmed_transactions), (120, 45));
+        assert_eq!(stats.by_asset_type()[1], json!({"asset_type": "token", "count": 90}));
+    }
+
+    #[tokio::test]
+    async fn missing_summary_falls_back_to_live_counts() {
+        let live = CharmStats {
+            source: "live",
+            ..CharmStats::from_summary(&summary_row())
+        };
+        let stats = charm_stats(None, || async { live.clone() }).await;
+        assert_eq!(stats, live);
+    }
+
+    #[test]
+    fn a_failed_summary_read_reports_the_error() {
+        let status = database_error_status("mainnet", &DbErr::Custom("gone".to_string()));
+        assert_eq!(status["indexer_status"]["status"], "database_error");
+        assert_eq!(status["charm_stats"]["total_charms"], 0);
+        assert_eq!(status["charm_stats"]["stats_source"], "unavailable");
+    }
+}
@@ -1,24 +1,39 @@
 // Handlers for transaction-related API endpoints
 
+use std::future::Future;
+
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Query, State},
     Json,
 };
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use charms_core::AppKind;
 
-use crate::error::ExplorerResult;
+use crate::config::WalletRpcOp;
+use crate::db::DbError;
+use crate::entity::transactions;
+use crate::error::{ExplorerError, ExplorerResult};
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::path_params::TxidPath;
+use crate::handlers::wallet::{quicknode_url, rpc_client, rpc_with_fallback};
 use crate::handlers::AppState;
 use crate::models::{
     GetTransactionsQuery, PaginatedResponse, TransactionAsset, TransactionData,
     TransactionsResponse,
 };
 use crate::services::transaction_service;
+use crate::services::tx_hex_cache::TxHexCache;
+use crate::services::wallet_service::WalletService;
 
 /// Handler for GET /transactions - Returns all transactions with pagination
 pub async fn get_transactions(
     State(state): State<AppState>,
     Query(params): Query<GetTransactionsQuery>,
+    links: PageLinks,
 ) -> ExplorerResult<Json<PaginatedResponse<TransactionsResponse>>> {
-    let response = if let Some(network) = &params.network {
+    let mut response = if let Some(network) = &params.network {
         transaction_service::get_all_transactions_paginated_by_network(
             &state,
             &params.pagination,
@@ -28,6 +43,7 @@ pub async fn get_transactions(
     } else {
         transaction_service::get_all_transactions_paginated(&state, &params.pagination).await?
     };
+    links.link(&mut response.pagination);
     Ok(Json(response))
 }
 
@@ -35,7 +51,7 @@ pub async fn get_transactions(
 /// enriched with asset metadata from charms + assets tables when available.
 pub async fn get_transaction_by_txid(
     State(state): State<AppState>,
-    Path(txid): Path<String>,
+    TxidPath(txid): TxidPath,
 ) -> ExplorerResult<Json<TransactionData>> {
     let tx = state
         .repositories
@@ -115,7 +131,7 @@ pub async fn get_transaction_by_txid(
         let contract_token_map: std::collections::HashMap<String, &crate::entity::assets::Model> =
             meta_map
                 .iter()
-                .filter(|(k, _)| k.starts_with("t/"))
+                .filter(|(k, _)| AppKind::of(k) == AppKind::Token)
                 .map(|(k, v)| {
                     let contract_id = format!("c/{}", &k[2..]);
                     (contract_id, *v)
@@ -127,12 +143,13 @@ pub async fn get_transaction_by_txid(
             .map(|charm| {
                 let meta = meta_map.get(&charm.app_id);
                 // For contracts, inherit name from matching token
-                let contract_meta = if charm.app_id.starts_with("c/") {
+                let is_contract = AppKind::of(&charm.app_id) == AppKind::Contract;
+                let contract_meta = if is_contract {
                     contract_token_map.get(&charm.app_id)
                 } else {
                     None
                 };
-                let role = if charm.app_id.starts_with("c/") {
+                let role = if is_contract {
                     "contract".to_string()
                 } else if beamed_out_indices.contains(&charm.vout.to_string()) {
                     "beamed".to_string()
@@ -151,7 +168,7 @@ pub async fn get_transaction_by_txid(
                     image_url: meta.and_then(|m| m.image_url.clone())
                         .or_else(|| contract_meta.and_then(|m| m.image_url.clone())),
                     amount: charm.amount,
-                    asset_type: if contract_meta.is_some() && charm.asset_type == "unknown" { "contract".to_string() } else { charm.asset_type.clone() },
+                    asset_type: if contract_meta.is_some() { "contract".to_string() } else { charm.asset_type.clone() },
                     role,
                     vout: charm.vout,
                     address: charm.address.clone(),
@@ -166,7 +183,7 @@ pub async fn get_transaction_by_txid(
         // Add consumed input tokens from spell that aren't in charms table
         // These are tokens in app_public_inputs that have no output in spell outs
         for (app_idx, app_id) in spell_app_ids.iter().enumerate() {
-            if charm_app_ids.contains(app_id) || app_id.starts_with("c/") {
+            if charm_app_ids.contains(app_id) || AppKind::of(app_id) == AppKind::Contract {
                 continue; // Already in charms or is a contract
             }
             // Check if this app index appears in any output
@@ -178,13 +195,7 @@ pub async fn get_transaction_by_txid(
             if !has_output {
                 // This is a consumed input token
                 let meta = meta_map.get(app_id);
-                let asset_type = if app_id.starts_with("t/") {
-                    "token"
-                } else if app_id.starts_with("n/") {
-                    "nft"
-                } else {
-                    "unknown"
-                };
+                let asset_type = AppKind::of(app_id).asset_type();
                 assets.push(TransactionAsset {
                     app_id: app_id.clone(),
                     name: meta.and_then(|m| m.name.clone()),
@@ -209,3 +220,303 @@ pub async fn get_transaction_by_txid(
 
     Ok(Json(data))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct TxHexQuery {
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+}
+
+/// Values of the `source` header of GET /tx/{txid}/hex, besides the node
+/// backends reported by `rpc_with_fallback`
+const SOURCE_TRANSACTIONS: &str = "transactions";
+const SOURCE_MEMPOOL: &str = "mempool";
+const SOURCE_CACHE: &str = "cache";
+
+/// Resolve raw hex: indexed tx row (confirmed, then mempool), then the
+/// node-result cache, then the node itself. Only node answers are cached;
+/// indexed rows are already served from the database.
+async fn resolve_tx_hex<DbFut, NodeFut>(
+    cache: &TxHexCache,
+    txid: &str,
+    network: &str,
+    db_lookup: DbFut,
+    node_lookup: impl FnOnce() -> NodeFut,
+) -> ExplorerResult<(String, &'static str)>
+where
+    DbFut: Future<Output = Result<Option<transactions::Model>, DbError>>,
+    NodeFut: Future<Output = Result<(String, &'static str), String>>,
+{
+    match db_lookup.await {
+        Ok(Some(row)) if row.network == network => {
+            if let Some(hex) = row.raw["hex"].as_str().filter(|h| !h.is_empty()) {
+                let source = if row.block_height.is_some() {
+                    SOURCE_TRANSACTIONS
+                } else {
+                    SOURCE_MEMPOOL
+                };
+                return Ok((hex.to_string(), source));
+            }
+        }
+        Ok(_) => {}
+        // A database hiccup should not block wallets while the node can answer
+        Err(e) => tracing::warn!("TX hex: database lookup failed for {}: {}", txid, e),
+    }
+
+    if let Some(hex) = cache.get(txid, network) {
+        return Ok((hex, SOURCE_CACHE));
+    }
+
+    match node_lookup().await {
+        Ok((hex, source)) => {
+            cache.insert(txid, network, hex.clone());
+            Ok((hex, source))
+        }
+        Err(e) => {
+            tracing::warn!("TX hex: node lookup failed for {}: {}", txid, e);
+            Err(ExplorerError::NotFound(format!(
+                "Transaction {} not found",
+                txid
+            )))
+        }
+    }
+}
+
+/// Handler for GET /tx/{txid}/hex?network= - Raw transaction hex as
+/// text/plain, with a `source` header naming the tier that served it
+pub async fn get_tx_hex(
+    State(state): State<AppState>,
+    TxidPath(txid): TxidPath,
+    Query(params): Query<TxHexQuery>,
+) -> ExplorerResult<(http::HeaderMap, String)> {
+    let network = params.network.as_deref().unwrap_or("mainnet");
+    let client = rpc_client(&state, network)?;
+    let (hex, source) = resolve_tx_hex(
+        &state.tx_hex_cache,
+        &txid,
+        network,
+        state.repositories.transactions.get_by_txid(&txid),
+        || {
+            let qn_url = quicknode_url(&state, network);
+            rpc_with_fallback(
+                WalletService::get_raw_transaction_hex(client, &txid),
+                WalletService::get_raw_transaction_hex_quicknode(&state.http_client, qn_url, &txid),
+                qn_url,
+                state.config.wallet_rpc_timeout(WalletRpcOp::Call),
+                "TX hex",
+            )
+        },
+    )
+    .await?;
+
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    headers.insert("source", http::HeaderValue::from_static(source));
+    Ok((headers, hex))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpellOutputsQuery {
+    #[serde(default, deserialize_with = "crate::models::optional_network_param")]
+    pub network: Option<String>,
+}
+
+/// What a spell committed to each of its outputs, for wallets building the
+/// spell that spends them.
+#[derive(Debug, Serialize)]
+pub struct SpellOutputsResponse {
+    pub txid: String,
+    pub network: String,
+    /// `[{"vout", "charms": {app_id: amount or state}}]`, by vout.
+    pub outputs: Value,
+    /// Where the charm-bearing outputs landed in the tx, as the indexer
+    /// resolved them (`tx_outputs`, `outputs`, `missing`).
+    pub charm_output_map: Value,
+}
+
+/// The output map of `row` on `network`: the stored one, else derived from
+/// the spell JSON (rows indexed before it was stored). `None` for another
+/// network's row or one without a parsed spell.
+fn spell_outputs_of(row: transactions::Model, network: &str) -> Option<SpellOutputsResponse> {
+    if row.network != network {
+        return None;
+    }
+    let outputs = row
+        .spell_outputs
+        .or_else(|| charms_core::spell_outputs(&row.charm))?;
+    Some(SpellOutputsResponse {
+        txid: row.txid,
+        network: row.network,
+        outputs,
+        charm_output_map: row.charm["charm_output_map"].clone(),
+    })
+}
+
+/// Handler for GET /spells/{txid}/outputs?network= - The spell's per-output
+/// app map alongside its resolved vout mapping
+pub async fn get_spell_outputs(
+    State(state): State<AppState>,
+    TxidPath(txid): TxidPath,
+    Query(params): Query<SpellOutputsQuery>,
+) -> ExplorerResult<Json<SpellOutputsResponse>> {
+    let network = params.network.as_deref().unwrap_or("mainnet");
+    let row = state.repositories.transactions.get_by_txid(&txid).await?;
+    row.and_then(|row| spell_outputs_of(row, network))
+        .map(Json)
+        .ok_or_else(|| ExplorerError::NotFound(format!("Spell {} not found", txid)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    use axum::response::IntoResponse;
+    use http::StatusCode;
+
+    use super::*;
+
+    fn tx_row(network: &str, block_height: Option<i32>) -> transactions::Model {
+        let at = chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        transactions::Model {
+            txid: "aa".to_string(),
+            block_height,
+            ordinal: 0,
+            raw: serde_json::json!({ "hex": "0200db" }),
+            charm: serde_json::json!({}),
+            updated_at: at,
+            status: "pending".to_string(),
+            confirmations: 0,
+            blockchain: "bitcoin".to_string(),
+            network: network.to_string(),
+            mempool_detected_at: None,
+            tags: None,
+            spell_outputs: None,
+        }
+    }
+
+    fn cache() -> TxHexCache {
+        TxHexCache::new(Duration::from_secs(120))
+    }
+
+    async fn none() -> Result<Option<transactions::Model>, DbError> {
+        Ok(None)
+    }
+
+    #[tokio::test]
+    async fn indexed_rows_are_served_without_the_node() {
+        let cache = cache();
+        for (height, source) in [(Some(100), SOURCE_TRANSACTIONS), (None, SOURCE_MEMPOOL)] {
+            let row = tx_row("mainnet", height);
+            let served = resolve_tx_hex(&cache, "aa", "mainnet", async { Ok(Some(row)) }, || async {
+                panic!("node must not be queried")
+            })
+            .await
+            .unwrap();
+            assert_eq!(served, ("0200db".to_string(), source));
+        }
+        assert_eq!(cache.get("aa", "mainnet"), None);
+    }
+
+    #[tokio::test]
+    async fn node_answers_are_cached_per_network() {
+        let cache = cache();
+        let calls = Cell::new(0);
+        let node = || async {
+            calls.set(calls.get() + 1);
+            Ok(("0200ff".to_string(), "quicknode"))
+        };
+        // A row of another network does not count as indexed.
+        let other = tx_row("testnet4", Some(100));
+        let first = resolve_tx_hex(&cache, "aa", "mainnet", async { Ok(Some(other)) }, node)
+            .await
+            .unwrap();
+        assert_eq!(first, ("0200ff".to_string(), "quicknode"));
+
+        let second = resolve_tx_hex(&cache, "aa", "mainnet", none(), node).await.unwrap();
+        assert_eq!(second, ("0200ff".to_string(), SOURCE_CACHE));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn database_errors_fall_through_to_the_node() {
+        let served = resolve_tx_hex(
+            &cache(),
+            "aa",
+            "mainnet",
+            async { Err(DbError::QueryError("connection refused".to_string())) },
+            || async { Ok(("0200aa".to_string(), "rpc")) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(served, ("0200aa".to_string(), "rpc"));
+    }
+
+    /// A spell minting an NFT and a token to output 0 and sending the rest
+    /// of the token to output 2, with a plain output between them.
+    fn spell_row() -> transactions::Model {
+        let mut row = tx_row("mainnet", Some(100));
+        row.charm = serde_json::json!({
+            "type": "spell",
+            "native_data": {
+                "app_public_inputs": {"n/aa/bb": null, "t/aa/bb": null},
+                "tx": {"outs": [{"0": {"ticker": "AA"}, "1": 100}, {}, {"1": 900}]},
+            },
+            "charm_output_map": {
+                "tx_outputs": 4,
+                "outputs": [
+                    {"vout": 0, "apps": ["n/aa/bb", "t/aa/bb"]},
+                    {"vout": 2, "apps": ["t/aa/bb"]},
+                ],
+                "missing": [],
+            },
+        });
+        row
+    }
+
+    #[test]
+    fn spell_outputs_are_keyed_by_app_id_per_vout() {
+        let served = spell_outputs_of(spell_row(), "mainnet").unwrap();
+        assert_eq!(
+            served.outputs,
+            serde_json::json!([
+                {"vout": 0, "charms": {"n/aa/bb": {"ticker": "AA"}, "t/aa/bb": 100}},
+                {"vout": 2, "charms": {"t/aa/bb": 900}},
+            ])
+        );
+        assert_eq!(served.charm_output_map["outputs"][1]["vout"], 2);
+        assert_eq!(served.charm_output_map["tx_outputs"], 4);
+    }
+
+    #[test]
+    fn a_stored_spell_output_map_is_served_as_is() {
+        let mut row = spell_row();
+        row.spell_outputs = Some(serde_json::json!([{"vout": 0, "charms": {"t/aa/bb": 1}}]));
+        let served = spell_outputs_of(row, "mainnet").unwrap();
+        assert_eq!(served.outputs[0]["charms"]["t/aa/bb"], 1);
+    }
+
+    #[test]
+    fn rows_of_another_network_or_without_a_spell_have_no_outputs() {
+        assert!(spell_outputs_of(spell_row(), "testnet4").is_none());
+        assert!(spell_outputs_of(tx_row("mainnet", Some(100)), "mainnet").is_none());
+    }
+
+    #[tokio::test]
+    async fn unknown_tx_is_not_found_and_not_cached() {
+        let cache = cache();
+        let err = resolve_tx_hex(&cache, "aa", "mainnet", none(), || async {
+            Err("No such mempool or blockchain transaction".to_string())
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::NOT_FOUND);
+        assert_eq!(cache.get("aa", "mainnet"), None);
+    }
+}
@@ -11,29 +11,36 @@ use std::time::Duration;
 use tokio::time::timeout;
 
 use bitcoincore_rpc::Client;
+use charms_core::AppKind;
 use std::sync::Arc;
 
-use crate::error::{ExplorerError, ExplorerResult};
+use crate::config::WalletRpcOp;
+use crate::db::repositories::charm_repository::PendingSpend;
+use crate::error::{internal_error, upstream_error, ExplorerError, ExplorerResult};
 use http::{HeaderMap, HeaderValue};
+use crate::handlers::json_body::BoundedJson;
+use crate::handlers::negotiate::{Negotiated, ResponseFormat};
+use crate::handlers::pagination::PageLinks;
+use crate::handlers::path_params::TxidPath;
+use crate::handlers::address::{normalize_address, normalize_addresses};
 use crate::handlers::AppState;
 use crate::services::address_monitor_service::AddressMonitorService;
+use crate::services::decimals_service::DecimalsResolver;
+use crate::services::dex_orders_service::{self, WalletOrdersResponse};
 use crate::services::maestro_service;
 use crate::services::mempool_space_service;
+use crate::services::wallet_changes_service::{self, WalletChangesResponse};
+use crate::services::wallet_history_service::WalletHistoryService;
 use crate::services::wallet_service::WalletService;
 
-const RPC_TIMEOUT: Duration = Duration::from_secs(3);
-
-/// Select the shared RPC client for the given network
-fn rpc_client(state: &AppState, network: &str) -> Arc<Client> {
-    match network {
-        "testnet4" => state.rpc_testnet4.clone(),
-        _ => state.rpc_mainnet.clone(),
-    }
+/// Shared RPC client for the given network; 404 unless the network is enabled
+pub(crate) fn rpc_client(state: &AppState, network: &str) -> ExplorerResult<Arc<Client>> {
+    state.rpc_clients.get(network)
 }
 
-/// QuickNode endpoint (empty string = not configured)
-fn quicknode_url(state: &AppState) -> &str {
-    &state.config.bitcoin_mainnet_quicknode_endpoint
+/// QuickNode endpoint for the network (empty string = not configured)
+pub(crate) fn quicknode_url<'a>(state: &'a AppState, network: &str) -> &'a str {
+    state.config.quicknode_endpoint(network)
 }
 
 /// Maestro API key (empty string = not configured)
@@ -46,23 +53,59 @@ fn maestro_available(state: &AppState) -> bool {
     !state.config.maestro_api_key.is_empty() && !state.maestro_cb.is_open()
 }
 
-/// Try an RPC future with timeout; on failure, try QuickNode fallback
-async fn rpc_with_fallback<T, RpcFut, QnFut>(
+/// Node tip for `network` from the shared cache, asking the node when the
+/// cached value is stale. `None` when the node does not answer in time.
+pub(crate) async fn chain_tip(state: &AppState, network: &str) -> Option<u64> {
+    let client = rpc_client(state, network).ok()?;
+    let budget = state.config.wallet_rpc_timeout(WalletRpcOp::Call);
+    state
+        .tip_cache
+        .get_or_fetch(network, || async {
+            timeout(budget, WalletService::get_chain_tip(client))
+                .await
+                .ok()
+                .and_then(Result::ok)
+                .map(|tip| tip.height)
+        })
+        .await
+}
+
+/// Confirmations of a charm mined at `block_height`: 0 while unconfirmed,
+/// 1 at the tip. With the tip unknown a mined charm counts as 1, the least
+/// it can have, so it is never reported safer than it is.
+fn confirmations(block_height: Option<i32>, tip: Option<u64>) -> u64 {
+    match block_height {
+        Some(h) if h > 0 => tip
+            .filter(|tip| *tip >= h as u64)
+            .map_or(1, |tip| tip - h as u64 + 1),
+        _ => 0,
+    }
+}
+
+/// Which backend answered a `rpc_with_fallback` call, echoed as `"source"`
+const SOURCE_RPC: &str = "rpc";
+const SOURCE_QUICKNODE: &str = "quicknode";
+
+/// Try an RPC future with timeout; on failure, try the QuickNode endpoint
+/// already resolved for the request's network. Returns the value and the
+/// source that served it.
+pub(crate) async fn rpc_with_fallback<T, RpcFut, QnFut>(
     rpc_future: RpcFut,
     qn_future: QnFut,
     qn_url: &str,
+    rpc_timeout: Duration,
     label: &str,
-) -> Result<T, String>
+) -> Result<(T, &'static str), String>
 where
     RpcFut: std::future::Future<Output = Result<T, String>>,
     QnFut: std::future::Future<Output = Result<T, String>>,
 {
-    match timeout(RPC_TIMEOUT, rpc_future).await {
-        Ok(Ok(val)) => Ok(val),
+    match timeout(rpc_timeout, rpc_future).await {
+        Ok(Ok(val)) => Ok((val, SOURCE_RPC)),
         Ok(Err(e)) => {
             if !qn_url.is_empty() {
                 tracing::warn!("{}: RPC failed, falling back to QuickNode: {}", label, e);
-                qn_future.await
+                qn_future.await.map(|val| (val, SOURCE_QUICKNODE))
             } else {
                 Err(e)
             }
@@ -72,14 +115,14 @@ where
                 tracing::warn!(
                     "{}: RPC timed out ({}s), falling back to QuickNode",
                     label,
-                    RPC_TIMEOUT.as_secs()
+                    rpc_timeout.as_secs()
                 );
-                qn_future.await
+                qn_future.await.map(|val| (val, SOURCE_QUICKNODE))
             } else {
                 Err(format!(
                     "{}: RPC timed out after {}s",
                     label,
-                    RPC_TIMEOUT.as_secs()
+                    rpc_timeout.as_secs()
                 ))
             }
         }
@@ -88,15 +131,33 @@ where
 
 #[derive(Debug, Deserialize)]
 pub struct NetworkQuery {
-    #[serde(default = "default_network")]
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
     pub network: String,
     pub min_value: Option<u64>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CharmBalancesQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+    /// Confirmations a charm needs to count as `confirmed`; below that it
+    /// is `unconfirmed`. Defaults to 1.
+    pub min_confirmations: Option<u64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct FeeEstimateQuery {
     pub blocks: Option<u16>,
-    #[serde(default = "default_network")]
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
     pub network: String,
 }
 
@@ -116,8 +177,9 @@ pub async fn get_wallet_utxos(
     Path(address): Path<String>,
     Query(params): Query<NetworkQuery>,
 ) -> ExplorerResult<Json<serde_json::Value>> {
-    let qn = quicknode_url(&state).to_string();
     let network = params.network.clone();
+    let address = normalize_address(&address, &network)?;
+    let qn = quicknode_url(&state, &network).to_string();
 
     let min_value = params.min_value;
 
@@ -149,10 +211,10 @@ pub async fn get_wallet_utxos(
             "utxos": utxos,
             "count": utxos.len(),
         }))),
-        Err(e) => {
-            tracing::error!("Wallet: failed to get UTXOs for {}: {}", address, e);
-            Err(ExplorerError::InternalError(e))
-        }
+        Err(e) => Err(upstream_error(
+            &format!("Wallet: failed to get UTXOs for {}", address),
+            &e,
+        )),
     }
 }
 
@@ -165,15 +227,27 @@ async fn fallback_utxos(
 ) -> Result<Vec<crate::services::wallet_service::Utxo>, String> {
     if !qn.is_empty() {
         match WalletService::get_utxos_quicknode(&state.http_client, qn, address).await {
-            Ok(utxos) => Ok(utxos),
-            Err(e) => {
-                tracing::warn!("UTXOs: QuickNode failed, falling back to RPC: {}", e);
-                WalletService::get_utxos(rpc_client(state, network), address).await
-            }
+            Ok(utxos) => return Ok(utxos),
+            Err(e) => tracing::warn!("UTXOs: QuickNode failed, falling back to RPC: {}", e),
         }
-    } else {
-        WalletService::get_utxos(rpc_client(state, network), address).await
     }
+    // Scans are cached per (address, network) until the node tip moves.
+    let client = rpc_client(state, network).map_err(|e| e.to_string())?;
+    let tip = chain_tip(state, network).await;
+    let scan_timeout = state.config.wallet_rpc_timeout(WalletRpcOp::UtxoScan);
+    state
+        .scan_cache
+        .get_or_scan(address, network, tip, || async {
+            timeout(scan_timeout, WalletService::get_utxos(client.clone(), address))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(format!(
+                        "UTXOs: scantxoutset timed out after {}s",
+                        scan_timeout.as_secs()
+                    ))
+                })
+        })
+        .await
 }
 
 /// GET /wallet/balance/{address}
@@ -205,9 +279,11 @@ pub async fn get_wallet_balance(
     State(state): State<AppState>,
     Path(address): Path<String>,
     Query(params): Query<NetworkQuery>,
-) -> ExplorerResult<Json<serde_json::Value>> {
+    format: ResponseFormat,
+) -> ExplorerResult<Negotiated<serde_json::Value>> {
     let network = params.network.as_str();
-    let qn = quicknode_url(&state).to_string();
+    let address = normalize_address(&address, network)?;
+    let qn = quicknode_url(&state, network).to_string();
     let mk = maestro_key(&state).to_string();
 
     // Step 1: Ensure address is monitored (seeds from Maestro/QuickNode if needed)
@@ -229,7 +305,31 @@ pub async fn get_wallet_balance(
     if let Some(obj) = balance.as_object_mut() {
         obj.insert("monitored".to_string(), serde_json::json!(monitored));
     }
-    Ok(Json(balance))
+    Ok(format.respond(balance))
+}
+
+/// DELETE /wallet/monitor/{address}?network=mainnet
+/// Stops monitoring an address: removes it from `monitored_addresses` and
+/// drops its tracked UTXOs. Idempotent; `"removed": false` when it was not
+/// monitored. The next balance call for it seeds it again from scratch.
+pub async fn unmonitor_wallet_address(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<NetworkQuery>,
+) -> ExplorerResult<Json<serde_json::Value>> {
+    let network = params.network.as_str();
+    let address = normalize_address(&address, network)?;
+    let removed = state
+        .repositories
+        .monitored_addresses
+        .unsubscribe(&address, network)
+        .await
+        .map_err(|e| internal_error(&format!("Wallet: failed to unsubscribe {}", address), &e))?;
+    Ok(Json(serde_json::json!({
+        "address": address,
+        "network": network,
+        "removed": removed,
+    })))
 }
 
 /// GET /wallet/tx/{txid}
@@ -237,10 +337,10 @@ pub async fn get_wallet_balance(
 /// TODO: Add Maestro esplora TX lookup when format normalization is implemented
 pub async fn get_wallet_transaction(
     State(state): State<AppState>,
-    Path(txid): Path<String>,
+    TxidPath(txid): TxidPath,
     Query(params): Query<NetworkQuery>,
 ) -> ExplorerResult<Json<serde_json::Value>> {
-    let client = rpc_client(&state, &params.network);
+    let client = rpc_client(&state, &params.network)?;
 
     match WalletService::get_transaction(client, &txid).await {
         Ok(tx) => Ok(Json(serde_json::json!(tx))),
@@ -259,7 +359,7 @@ pub async fn get_wallet_transaction(
 /// Used by DEX order builders to construct prev_txs for spell proofs.
 pub async fn get_wallet_tx_hex(
     State(state): State<AppState>,
-    Path(txid): Path<String>,
+    TxidPath(txid): TxidPath,
     Query(params): Query<NetworkQuery>,
 ) -> ExplorerResult<Json<serde_json::Value>> {
     let network = params.network.clone();
@@ -293,7 +393,7 @@ pub async fn get_wallet_tx_hex(
 /// Response: { "transactions": { "abc...": "0200000001...", "def...": "0200000001..." } }
 pub async fn get_wallet_prev_txs(
     State(state): State<AppState>,
-    Json(body): Json<serde_json::Value>,
+    BoundedJson(body): BoundedJson<serde_json::Value>,
 ) -> ExplorerResult<Json<serde_json::Value>> {
     let network = body
         .get("network")
@@ -367,6 +467,8 @@ pub async fn broadcast_wallet_transaction(
 ) -> ExplorerResult<Json<serde_json::Value>> {
     let raw_tx = &body.raw_tx;
     let network = params.network.as_str();
+    // Resolved up front: a disabled network is refused before any provider
+    let client = rpc_client(&state, network)?;
 
     // Primary: mempool.space
     match mempool_space_service::broadcast(&state.http_client, raw_tx, network).await {
@@ -394,13 +496,9 @@ pub async fn broadcast_wallet_transaction(
     }
 
     // Last resort: local RPC node
-    let client = rpc_client(&state, &params.network);
     match WalletService::broadcast_transaction(client, raw_tx).await {
         Ok(result) => Ok(Json(serde_json::json!(result))),
-        Err(e) => {
-            tracing::error!("Broadcast: all paths failed: {}", e);
-            Err(ExplorerError::InternalError(e))
-        }
+        Err(e) => Err(upstream_error("Broadcast: all paths failed", &e)),
     }
 }
 
@@ -412,6 +510,7 @@ pub async fn get_wallet_fee_estimate(
 ) -> ExplorerResult<Json<serde_json::Value>> {
     let blocks = params.blocks.unwrap_or(6);
     let network = params.network.clone();
+    let client = rpc_client(&state, &network)?;
 
     // Try Maestro first
     if maestro_available(&state) {
@@ -429,25 +528,30 @@ pub async fn get_wallet_fee_estimate(
     }
 
     // Fallback: RPC
-    let client = rpc_client(&state, &params.network);
     match WalletService::get_fee_estimate(client, params.blocks).await {
         Ok(estimate) => Ok(Json(serde_json::json!(estimate))),
-        Err(e) => {
-            tracing::error!("Wallet: failed to get fee estimate: {}", e);
-            Err(ExplorerError::InternalError(e))
-        }
+        Err(e) => Err(upstream_error("Wallet: failed to get fee estimate", &e)),
     }
 }
 
 /// GET /wallet/charms/{address}
 /// Returns confirmed + unconfirmed charm balances from the indexed DB (instant)
 /// Response shape matches Cast's explorerApiProvider.getAggregateCharmBalances()
+/// UTXOs an unconfirmed tx already spends are flagged `pendingSpend` and
+/// counted in `pending_out` instead of `confirmed`/`unconfirmed`.
+/// Each UTXO carries its `confirmations` and `reorg_risk` (fewer than
+/// `WALLET_REORG_RISK_CONFIRMATIONS`); `?min_confirmations=N` counts charms
+/// with fewer than N confirmations as `unconfirmed`.
 pub async fn get_wallet_charm_balances(
     State(state): State<AppState>,
     Path(address): Path<String>,
-    Query(params): Query<NetworkQuery>,
-) -> ExplorerResult<Json<serde_json::Value>> {
+    Query(params): Query<CharmBalancesQuery>,
+    format: ResponseFormat,
+) -> ExplorerResult<Negotiated<serde_json::Value>> {
     let network = params.network.as_str();
+    let address = normalize_address(&address, network)?;
+    let min_confirmations = params.min_confirmations.unwrap_or(1).max(1);
+    let reorg_threshold = state.config.wallet_reorg_risk_confirmations;
 
     // 1. Get all unspent charms for this address
     let charms = match state
@@ -458,25 +562,35 @@ pub async fn get_wallet_charm_balances(
     {
         Ok(c) => c,
         Err(e) => {
-            tracing::error!("Wallet: failed to get charms for {}: {:?}", address, e);
-            return Err(ExplorerError::InternalError(format!("{:?}", e)));
+            return Err(internal_error(
+                &format!("Wallet: failed to get charms for {}", address),
+                &format!("{:?}", e),
+            ));
         }
     };
 
     if charms.is_empty() {
-        return Ok(Json(serde_json::json!({
+        return Ok(format.respond(serde_json::json!({
             "address": address,
             "network": network,
             "balances": [],
             "count": 0,
+            "min_confirmations": min_confirmations,
         })));
     }
+    let tip = chain_tip(&state, network).await;
 
-    // 2. Get mempool-spent UTXOs to flag them
-    let mempool_spent = state
+    // 2. Get pending mempool spends of these UTXOs to flag them
+    let outpoints: Vec<(String, i32)> = charms
+        .iter()
+        .map(|c| (c.txid.clone(), c.vout))
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    let pending_spends = state
         .repositories
         .charm
-        .get_mempool_spent_utxos(network)
+        .get_pending_spends_for_outpoints(&outpoints, network)
         .await
         .unwrap_or_default();
 
@@ -502,6 +616,8 @@ pub async fn get_wallet_charm_balances(
 
     // 5. Look up symbols from assets table (network-scoped)
     let app_ids: Vec<String> = charms.iter().map(|c| c.app_id.clone()).collect();
+    let mut decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref());
+    decimals.resolve_all(network, &app_ids).await;
     let assets = state
         .repositories
         .asset_repository
@@ -516,19 +632,22 @@ pub async fn get_wallet_charm_balances(
     // 6. Group charms by app_id and build Cast-compatible response
     let mut balance_map: std::collections::HashMap<
         String,
-        (String, String, i64, i64, i64, Vec<serde_json::Value>),
+        (String, String, i64, i64, i64, bool, Vec<serde_json::Value>),
     > = std::collections::HashMap::new();
-    // key -> (asset_type, symbol, confirmed, unconfirmed, mempool_spent_total, utxos)
+    // key -> (asset_type, symbol, confirmed, unconfirmed, mempool_spent_total, reorg_risk, utxos)
 
     for charm in &charms {
-        let confirmed = charm.block_height.map_or(false, |h| h > 0);
+        let confirmations = confirmations(charm.block_height, tip);
+        let confirmed = confirmations > 0;
+        let reorg_risk = confirmations < reorg_threshold;
         let key = (charm.txid.clone(), charm.vout);
-        let is_mempool_spent = mempool_spent.contains(&key);
+        let pending_spend = pending_spends.get(&key);
+        let is_mempool_spent = pending_spend.is_some();
         let all_app_ids = utxo_app_ids
             .get(&key)
             .cloned()
             .unwrap_or_else(|| vec![charm.app_id.clone()]);
-        let has_order_charm = all_app_ids.iter().any(|id| id.starts_with("b/"));
+        let has_order_charm = all_app_ids.iter().any(|id| AppKind::of(id) == AppKind::Dex);
         let btc_value = utxo_values.get(&key).copied().unwrap_or(546);
         let symbol = symbol_map.get(&charm.app_id).cloned().unwrap_or_default();
 
@@ -540,52 +659,70 @@ pub async fn get_wallet_charm_balances(
             "appId": charm.app_id,
             "amount": charm.amount,
             "confirmed": confirmed,
+            "confirmations": confirmations,
+            "reorg_risk": reorg_risk,
             "blockHeight": charm.block_height,
             "hasOrderCharm": has_order_charm,
             "allCharmAppIds": all_app_ids,
             "mempoolSpent": is_mempool_spent,
+            "pendingSpend": is_mempool_spent,
+            "spendingTxid": pending_spend.map(|p| &p.spending_txid),
         });
 
-        let entry = balance_map
-            .entry(charm.app_id.clone())
-            .or_insert_with(|| (charm.asset_type.clone(), symbol.clone(), 0, 0, 0, Vec::new()));
+        let entry = balance_map.entry(charm.app_id.clone()).or_insert_with(|| {
+            (charm.asset_type.clone(), symbol.clone(), 0, 0, 0, false, Vec::new())
+        });
 
         if is_mempool_spent {
             entry.4 += charm.amount;
-        } else if confirmed {
+        } else if confirmations >= min_confirmations {
             entry.2 += charm.amount;
         } else {
             entry.3 += charm.amount;
         }
-        entry.5.push(utxo_json);
+        entry.5 |= reorg_risk && !is_mempool_spent;
+        entry.6.push(utxo_json);
     }
 
     // 7. Build final balances array
     let balances: Vec<serde_json::Value> = balance_map
         .into_iter()
         .map(
-            |(app_id, (asset_type, symbol, confirmed, unconfirmed, mempool_spent_total, utxos))| {
+            |(
+                app_id,
+                (asset_type, symbol, confirmed, unconfirmed, mempool_spent_total, reorg_risk, utxos),
+            )| {
                 let available = confirmed + unconfirmed;
+                let total = available + mempool_spent_total;
+                let format = |raw: i64| decimals.format(network, &app_id, raw);
                 serde_json::json!({
                     "appId": app_id,
                     "assetType": asset_type,
                     "symbol": symbol,
+                    "decimals": decimals.get(network, &app_id),
                     "confirmed": confirmed,
+                    "confirmed_formatted": format(confirmed),
                     "unconfirmed": unconfirmed,
+                    "unconfirmed_formatted": format(unconfirmed),
                     "mempoolSpent": mempool_spent_total,
+                    "pending_out": mempool_spent_total,
                     "available": available,
-                    "total": available + mempool_spent_total,
+                    "available_formatted": format(available),
+                    "total": total,
+                    "total_formatted": format(total),
+                    "reorg_risk": reorg_risk,
                     "utxos": utxos,
                 })
             },
         )
         .collect();
 
-    Ok(Json(serde_json::json!({
+    Ok(format.respond(serde_json::json!({
         "address": address,
         "network": network,
         "balances": balances,
         "count": balances.len(),
+        "min_confirmations": min_confirmations,
     })))
 }
 
@@ -596,8 +733,9 @@ pub async fn get_wallet_charm_balances(
 /// Response: same shape as indexed batch — { "results": { "addr": { balances, count } } }
 pub async fn get_wallet_charm_balances_batch(
     State(state): State<AppState>,
-    Json(body): Json<serde_json::Value>,
-) -> Result<(HeaderMap, Json<serde_json::Value>), ExplorerError> {
+    format: ResponseFormat,
+    BoundedJson(body): BoundedJson<serde_json::Value>,
+) -> Result<(HeaderMap, Negotiated<serde_json::Value>), ExplorerError> {
     tracing::warn!("DEPRECATED: POST /wallet/charms/batch — migrate to POST /wallet/balance/batch");
 
     let dep_headers = || {
@@ -628,9 +766,10 @@ pub async fn get_wallet_charm_balances_batch(
                 .collect()
         })
         .unwrap_or_default();
+    let addresses = normalize_addresses(addresses, &network)?;
 
     if addresses.is_empty() {
-        return Ok((dep_headers(), Json(serde_json::json!({ "results": {} }))));
+        return Ok((dep_headers(), format.respond(serde_json::json!({ "results": {} }))));
     }
 
     let tasks: Vec<_> = addresses
@@ -666,7 +805,7 @@ pub async fn get_wallet_charm_balances_batch(
         }
     }
 
-    Ok((dep_headers(), Json(serde_json::json!({ "results": results }))))
+    Ok((dep_headers(), format.respond(serde_json::json!({ "results": results }))))
 }
 
 /// POST /wallet/charms/batch/indexed
@@ -674,8 +813,9 @@ pub async fn get_wallet_charm_balances_batch(
 /// May be slightly stale if indexer hasn't caught up with mempool.
 pub async fn get_wallet_charm_balances_batch_indexed(
     State(state): State<AppState>,
-    Json(body): Json<serde_json::Value>,
-) -> ExplorerResult<Json<serde_json::Value>> {
+    format: ResponseFormat,
+    BoundedJson(body): BoundedJson<serde_json::Value>,
+) -> ExplorerResult<Negotiated<serde_json::Value>> {
     let network = body
         .get("network")
         .and_then(|v| v.as_str())
@@ -692,9 +832,10 @@ pub async fn get_wallet_charm_balances_batch_indexed(
                 .collect()
         })
         .unwrap_or_default();
+    let addresses = normalize_addresses(addresses, &network)?;
 
     if addresses.is_empty() {
-        return Ok(Json(serde_json::json!({ "results": {} })));
+        return Ok(format.respond(serde_json::json!({ "results": {} })));
     }
 
     let tasks: Vec<_> = addresses
@@ -729,14 +870,14 @@ pub async fn get_wallet_charm_balances_batch_indexed(
         }
     }
 
-    Ok(Json(serde_json::json!({ "results": results })))
+    Ok(format.respond(serde_json::json!({ "results": results })))
 }
 
 /// POST /wallet/utxos/batch — DEPRECATED, use POST /wallet/balance/batch
 /// Batch fetch UTXOs for multiple addresses in a single request (max 50)
 pub async fn get_wallet_utxos_batch(
     State(state): State<AppState>,
-    Json(body): Json<serde_json::Value>,
+    BoundedJson(body): BoundedJson<serde_json::Value>,
 ) -> Result<(HeaderMap, Json<serde_json::Value>), ExplorerError> {
     tracing::warn!("DEPRECATED: POST /wallet/utxos/batch — migrate to POST /wallet/balance/batch");
 
@@ -768,6 +909,7 @@ pub async fn get_wallet_utxos_batch(
                 .collect()
         })
         .unwrap_or_default();
+    let addresses = normalize_addresses(addresses, &network)?;
 
     let min_value: Option<u64> = body
         .get("min_value")
@@ -777,7 +919,7 @@ pub async fn get_wallet_utxos_batch(
         return Ok((dep_headers(), Json(serde_json::json!({ "results": {} }))));
     }
 
-    let qn = quicknode_url(&state).to_string();
+    let qn = quicknode_url(&state, &network).to_string();
 
     let tasks: Vec<_> = addresses
         .iter()
@@ -851,7 +993,12 @@ async fn resolve_charm_balances_for_address(
         .charm
         .get_unspent_charms_by_address(address, network)
         .await
-        .map_err(|e| crate::error::ExplorerError::InternalError(format!("{:?}", e)))?;
+        .map_err(|e| {
+            internal_error(
+                &format!("Wallet: failed to get charms for {}", address),
+                &format!("{:?}", e),
+            )
+        })?;
 
     if charms.is_empty() {
         return Ok(serde_json::json!({
@@ -862,10 +1009,16 @@ async fn resolve_charm_balances_for_address(
         }));
     }
 
-    let mempool_spent = state
+    let outpoints: Vec<(String, i32)> = charms
+        .iter()
+        .map(|c| (c.txid.clone(), c.vout))
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    let pending_spends = state
         .repositories
         .charm
-        .get_mempool_spent_utxos(network)
+        .get_pending_spends_for_outpoints(&outpoints, network)
         .await
         .unwrap_or_default();
 
@@ -888,6 +1041,8 @@ async fn resolve_charm_balances_for_address(
         .collect();
 
     let app_ids: Vec<String> = charms.iter().map(|c| c.app_id.clone()).collect();
+    let mut decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref());
+    decimals.resolve_all(network, &app_ids).await;
     let assets = state
         .repositories
         .asset_repository
@@ -907,12 +1062,13 @@ async fn resolve_charm_balances_for_address(
     for charm in &charms {
         let confirmed = charm.block_height.map_or(false, |h| h > 0);
         let key = (charm.txid.clone(), charm.vout);
-        let is_mempool_spent = mempool_spent.contains(&key);
+        let pending_spend = pending_spends.get(&key);
+        let is_mempool_spent = pending_spend.is_some();
         let all_app_ids = utxo_app_ids
             .get(&key)
             .cloned()
             .unwrap_or_else(|| vec![charm.app_id.clone()]);
-        let has_order_charm = all_app_ids.iter().any(|id| id.starts_with("b/"));
+        let has_order_charm = all_app_ids.iter().any(|id| AppKind::of(id) == AppKind::Dex);
         let btc_value = utxo_values.get(&key).copied().unwrap_or(546);
         let symbol = asset_map.get(&charm.app_id).and_then(|a| a.symbol.clone()).unwrap_or_default();
 
@@ -928,6 +1084,8 @@ async fn resolve_charm_balances_for_address(
             "hasOrderCharm": has_order_charm,
             "allCharmAppIds": all_app_ids,
             "mempoolSpent": is_mempool_spent,
+            "pendingSpend": is_mempool_spent,
+            "spendingTxid": pending_spend.map(|p| &p.spending_txid),
         });
 
         let entry = balance_map
@@ -963,17 +1121,24 @@ async fn resolve_charm_balances_for_address(
                     }))
                 });
                 let available = confirmed + unconfirmed;
+                let total = available + mempool_spent_total;
+                let format = |raw: i64| decimals.format(network, &app_id, raw);
                 serde_json::json!({
                     "appId": app_id,
                     "assetType": asset_type,
                     "symbol": symbol,
                     "name": asset.and_then(|a| a.name.clone()),
-                    "decimals": asset.map(|a| a.decimals),
+                    "decimals": decimals.get(network, &app_id),
                     "confirmed": confirmed,
+                    "confirmed_formatted": format(confirmed),
                     "unconfirmed": unconfirmed,
+                    "unconfirmed_formatted": format(unconfirmed),
                     "mempoolSpent": mempool_spent_total,
+                    "pending_out": mempool_spent_total,
                     "available": available,
-                    "total": available + mempool_spent_total,
+                    "available_formatted": format(available),
+                    "total": total,
+                    "total_formatted": format(total),
                     "cardano": cardano,
                     "utxos": utxos,
                 })
@@ -1002,7 +1167,12 @@ async fn resolve_charm_balances_live(
         .charm
         .get_unspent_charms_by_address(address, network)
         .await
-        .map_err(|e| crate::error::ExplorerError::InternalError(format!("{:?}", e)))?;
+        .map_err(|e| {
+            internal_error(
+                &format!("Wallet: failed to get charms for {}", address),
+                &format!("{:?}", e),
+            )
+        })?;
 
     if charms.is_empty() {
         return Ok(serde_json::json!({
@@ -1019,7 +1189,7 @@ async fn resolve_charm_balances_live(
         let mk = maestro_key(state).to_string();
         match maestro_service::get_utxos(
             &state.http_client, &mk, network, address, None,
-            Some(&state.config.bitcoin_mainnet_quicknode_endpoint),
+            Some(quicknode_url(state, network)),
         ).await {
             Ok(utxos) => utxos.iter().map(|u| (u.txid.clone(), u.vout)).collect(),
             Err(e) => {
@@ -1034,6 +1204,8 @@ async fn resolve_charm_balances_live(
 
     // 3. Look up symbols
     let app_ids: Vec<String> = charms.iter().map(|c| c.app_id.clone()).collect();
+    let mut decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref());
+    decimals.resolve_all(network, &app_ids).await;
     let assets = state
         .repositories
         .asset_repository
@@ -1071,7 +1243,7 @@ async fn resolve_charm_balances_live(
             .get(&key)
             .cloned()
             .unwrap_or_else(|| vec![charm.app_id.clone()]);
-        let has_order_charm = all_app_ids.iter().any(|id| id.starts_with("b/"));
+        let has_order_charm = all_app_ids.iter().any(|id| AppKind::of(id) == AppKind::Dex);
         let symbol = asset_map.get(&charm.app_id).and_then(|a| a.symbol.clone()).unwrap_or_default();
 
         let utxo_json = serde_json::json!({
@@ -1118,17 +1290,22 @@ async fn resolve_charm_balances_live(
                 }))
             });
             let available = confirmed + unconfirmed;
+            let format = |raw: i64| decimals.format(network, &app_id, raw);
             serde_json::json!({
                 "appId": app_id,
                 "assetType": asset_type,
                 "symbol": symbol,
                 "name": asset.and_then(|a| a.name.clone()),
-                "decimals": asset.map(|a| a.decimals),
+                "decimals": decimals.get(network, &app_id),
                 "confirmed": confirmed,
+                "confirmed_formatted": format(confirmed),
                 "unconfirmed": unconfirmed,
+                "unconfirmed_formatted": format(unconfirmed),
                 "mempoolSpent": 0,
                 "available": available,
+                "available_formatted": format(available),
                 "total": available,
+                "total_formatted": format(available),
                 "cardano": cardano,
                 "utxos": utxos,
             })
@@ -1144,6 +1321,14 @@ async fn resolve_charm_balances_live(
     }))
 }
 
+/// Tag a response object with the backend that produced it
+fn with_source(mut value: serde_json::Value, source: &str) -> serde_json::Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("source".to_string(), serde_json::json!(source));
+    }
+    value
+}
+
 /// GET /wallet/tip
 /// Maestro (primary, circuit-breakered) → RPC fallback → QuickNode fallback
 pub async fn get_wallet_chain_tip(
@@ -1152,6 +1337,7 @@ pub async fn get_wallet_chain_tip(
 ) -> ExplorerResult<Json<serde_json::Value>> {
     let http = state.http_client.clone();
     let network = params.network.clone();
+    let client = rpc_client(&state, &network)?;
 
     // Try Maestro first
     if maestro_available(&state) {
@@ -1159,7 +1345,7 @@ pub async fn get_wallet_chain_tip(
         match maestro_service::get_chain_tip(&http, &mk, &network).await {
             Ok(tip) => {
                 state.maestro_cb.record_success();
-                return Ok(Json(serde_json::json!(tip)));
+                return Ok(Json(with_source(serde_json::json!(tip), "maestro")));
             }
             Err(e) => {
                 state.maestro_cb.record_failure();
@@ -1169,29 +1355,29 @@ pub async fn get_wallet_chain_tip(
     }
 
     // Fallback: RPC → QuickNode
-    let client = rpc_client(&state, &params.network);
-    let qn = quicknode_url(&state).to_string();
+    let qn = quicknode_url(&state, &network).to_string();
 
     let result = rpc_with_fallback(
         WalletService::get_chain_tip(client),
         WalletService::get_chain_tip_quicknode(&http, &qn),
         &qn,
+        state.config.wallet_rpc_timeout(WalletRpcOp::Call),
         "Tip",
     )
     .await;
 
     match result {
-        Ok(tip) => Ok(Json(serde_json::json!(tip))),
-        Err(e) => {
-            tracing::error!("Wallet: failed to get chain tip: {}", e);
-            Err(ExplorerError::InternalError(e))
-        }
+        Ok((tip, source)) => Ok(Json(with_source(serde_json::json!(tip), source))),
+        Err(e) => Err(upstream_error("Wallet: failed to get chain tip", &e)),
     }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TransactionsQuery {
-    #[serde(default = "default_network")]
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
     pub network: String,
     #[serde(default = "default_page")]
     pub page: u64,
@@ -1213,9 +1399,11 @@ pub async fn get_wallet_transactions(
     State(state): State<AppState>,
     Path(address): Path<String>,
     Query(params): Query<TransactionsQuery>,
+    links: PageLinks,
 ) -> ExplorerResult<Json<serde_json::Value>> {
     let network = params.network.as_str();
-    let qn = quicknode_url(&state).to_string();
+    let address = normalize_address(&address, network)?;
+    let qn = quicknode_url(&state, network).to_string();
     let mk = maestro_key(&state).to_string();
 
     // Ensure address is monitored and seeded
@@ -1243,7 +1431,7 @@ pub async fn get_wallet_transactions(
     {
         Ok((txs, total)) => {
             let total_pages = (total + page_size - 1) / page_size;
-            Ok(Json(serde_json::json!({
+            let mut body = serde_json::json!({
                 "address": address,
                 "network": network,
                 "transactions": txs,
@@ -1251,15 +1439,97 @@ pub async fn get_wallet_transactions(
                 "page_size": page_size,
                 "total": total,
                 "total_pages": total_pages,
-            })))
-        }
-        Err(e) => {
-            tracing::error!("Wallet: failed to get transactions for {}: {}", address, e);
-            Err(ExplorerError::InternalError(e))
+            });
+            links.nav(page, total_pages).extend(&mut body);
+            Ok(Json(body))
         }
+        Err(e) => Err(upstream_error(
+            &format!("Wallet: failed to get transactions for {}", address),
+            &e,
+        )),
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+    #[serde(default = "default_page")]
+    pub page: u64,
+    #[serde(default = "default_page_size")]
+    pub limit: u64,
+}
+
+/// GET /wallet/history/{address}?network=mainnet&page=1&limit=50
+/// Transaction history built only from indexed data: UTXO creations and
+/// spends, charm receipts and spends, and pending mempool spends, merged to
+/// one entry per txid (net `value` in sats, per-app_id charm deltas).
+/// Seeds the address if not yet monitored; `"monitored": false` then means
+/// the list only holds what the seed and the indexer have seen so far.
+pub async fn get_wallet_history(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<HistoryQuery>,
+    links: PageLinks,
+) -> ExplorerResult<Json<serde_json::Value>> {
+    let network = params.network.as_str();
+    let address = normalize_address(&address, network)?;
+    let qn = quicknode_url(&state, network).to_string();
+    let mk = maestro_key(&state).to_string();
+
+    let monitored = AddressMonitorService::ensure_monitored(
+        &state.repositories.monitored_addresses,
+        &state.repositories.utxo,
+        &state.repositories.address_transactions,
+        &state.http_client,
+        &qn,
+        &mk,
+        &address,
+        network,
+    )
+    .await
+    .unwrap_or(false);
+
+    let events = state
+        .repositories
+        .wallet_history
+        .events(&address, network)
+        .await
+        .map_err(|e| {
+            internal_error(
+                &format!("Wallet: failed to get history for {}", address),
+                &e.to_string(),
+            )
+        })?;
+    let history = WalletHistoryService::merge(events);
+
+    let limit = params.limit.clamp(1, 100);
+    let page = params.page.max(1);
+    let total = history.len() as u64;
+    let items: Vec<_> = history
+        .into_iter()
+        .skip(((page - 1) * limit) as usize)
+        .take(limit as usize)
+        .collect();
+
+    let total_pages = total.div_ceil(limit);
+    let mut body = serde_json::json!({
+        "address": address,
+        "network": network,
+        "monitored": monitored,
+        "history": items,
+        "page": page,
+        "limit": limit,
+        "total": total,
+        "total_pages": total_pages,
+    });
+    links.nav(page, total_pages).extend(&mut body);
+    Ok(Json(body))
+}
+
 /// Compute balance for a single address (used by balance batch endpoint).
 /// Does NOT call ensure_monitored — the batch handler seeds each address concurrently.
 async fn resolve_balance_for_batch(
@@ -1284,37 +1554,25 @@ async fn resolve_balance_for_batch(
     let charm_utxo_keys: std::collections::HashSet<(String, i32)> =
         charms.iter().map(|c| (c.txid.clone(), c.vout)).collect();
 
-    let mut available: u64 = 0;
-    let mut locked: u64 = 0;
-    let mut unconfirmed: u64 = 0;
-    let mut btc_utxos: Vec<serde_json::Value> = Vec::new();
-
-    for row in &utxo_rows {
-        let has_charms = charm_utxo_keys.contains(&(row.txid.clone(), row.vout));
-        let value = row.value as u64;
-        let is_confirmed = row.block_height > 0;
-
-        if !is_confirmed {
-            unconfirmed += value;
-        } else if has_charms {
-            locked += value;
-        } else {
-            available += value;
-        }
-
-        btc_utxos.push(serde_json::json!({
-            "txid": row.txid,
-            "vout": row.vout,
-            "value": row.value,
-            "blockHeight": row.block_height,
-            "hasCharms": has_charms,
-            "confirmed": is_confirmed,
-        }));
-    }
+    let outpoints: Vec<(String, i32)> = utxo_rows
+        .iter()
+        .map(|r| (r.txid.clone(), r.vout))
+        .chain(charm_utxo_keys.iter().cloned())
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    let pending_spends = state
+        .repositories
+        .charm
+        .get_pending_spends_for_outpoints(&outpoints, network)
+        .await
+        .unwrap_or_default();
 
-    let confirmed = available + locked;
+    let btc = classify_btc_utxos(&utxo_rows, &charm_utxo_keys, &pending_spends);
 
     let app_ids: Vec<String> = charms.iter().map(|c| c.app_id.clone()).collect();
+    let mut decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref());
+    decimals.resolve_all(network, &app_ids).await;
     let assets = state
         .repositories
         .asset_repository
@@ -1345,7 +1603,7 @@ async fn resolve_balance_for_batch(
     let mut vk_cache: std::collections::HashMap<String, Option<crate::entity::assets::Model>> =
         std::collections::HashMap::new();
     for app_id in &app_ids {
-        if !app_id.starts_with("t/") {
+        if AppKind::of(app_id) != AppKind::Token {
             continue;
         }
         let entry = meta_map.entry(app_id.clone()).or_insert((None, None, None, None));
@@ -1376,38 +1634,54 @@ async fn resolve_balance_for_batch(
         }
     }
 
+    let tip = chain_tip(state, network).await;
+    let reorg_threshold = state.config.wallet_reorg_risk_confirmations;
     let mut charm_balance_map: std::collections::HashMap<
         String,
-        (String, i64, Vec<serde_json::Value>),
+        (String, i64, bool, Vec<serde_json::Value>),
     > = std::collections::HashMap::new();
 
+    let mut pending_out_map: std::collections::HashMap<String, i64> =
+        std::collections::HashMap::new();
     for charm in &charms {
+        let confirmations = confirmations(charm.block_height, tip);
+        let reorg_risk = confirmations < reorg_threshold;
         let btc_value = utxo_rows
             .iter()
             .find(|r| r.txid == charm.txid && r.vout == charm.vout)
             .map(|r| r.value)
             .unwrap_or(546);
+        let pending_spend = pending_spends.get(&(charm.txid.clone(), charm.vout));
 
         let utxo_json = serde_json::json!({
             "txid": charm.txid,
             "vout": charm.vout,
             "value": btc_value,
             "amount": charm.amount,
-            "confirmed": charm.block_height.map_or(false, |h| h > 0),
+            "confirmed": confirmations > 0,
+            "confirmations": confirmations,
+            "reorg_risk": reorg_risk,
             "blockHeight": charm.block_height,
+            "pendingSpend": pending_spend.is_some(),
+            "spendingTxid": pending_spend.map(|p| &p.spending_txid),
         });
 
+        if pending_spend.is_some() {
+            *pending_out_map.entry(charm.app_id.clone()).or_default() += charm.amount;
+        }
         let entry = charm_balance_map
             .entry(charm.app_id.clone())
-            .or_insert_with(|| (charm.asset_type.clone(), 0, Vec::new()));
+            .or_insert_with(|| (charm.asset_type.clone(), 0, false, Vec::new()));
         entry.1 += charm.amount;
-        entry.2.push(utxo_json);
+        entry.2 |= reorg_risk && pending_spend.is_none();
+        entry.3.push(utxo_json);
     }
 
     let charm_balances: Vec<serde_json::Value> = charm_balance_map
         .into_iter()
-        .map(|(app_id, (asset_type, total, utxos))| {
+        .map(|(app_id, (asset_type, total, reorg_risk, utxos))| {
             let m = meta_map.get(&app_id).cloned().unwrap_or((None, None, None, None));
+            let pending_out = pending_out_map.get(&app_id).copied().unwrap_or(0);
             serde_json::json!({
                 "appId": app_id,
                 "assetType": asset_type,
@@ -1415,7 +1689,11 @@ async fn resolve_balance_for_batch(
                 "name": m.1,
                 "imageUrl": m.2,
                 "description": m.3,
+                "decimals": decimals.get(network, &app_id),
                 "total": total,
+                "total_formatted": decimals.format(network, &app_id, total),
+                "pending_out": pending_out,
+                "reorg_risk": reorg_risk,
                 "utxos": utxos,
             })
         })
@@ -1424,14 +1702,7 @@ async fn resolve_balance_for_batch(
     serde_json::json!({
         "address": address,
         "network": network,
-        "btc": {
-            "confirmed": confirmed,
-            "unconfirmed": unconfirmed,
-            "total": confirmed + unconfirmed,
-            "available": available,
-            "locked": locked,
-            "utxos": btc_utxos,
-        },
+        "btc": btc,
         "charms": {
             "balances": charm_balances,
             "count": charm_balances.len(),
@@ -1439,6 +1710,91 @@ async fn resolve_balance_for_batch(
     })
 }
 
+/// BTC side of a wallet balance. UTXOs an unconfirmed tx already spends are
+/// moved out of every bucket into `pending_spend`, so a wallet cannot select
+/// them twice; the spending tx's change to this address shows up as
+/// `unconfirmed` instead. Of the rest, unconfirmed UTXOs are `unconfirmed`,
+/// confirmed ones carrying charms are `locked` and the others `available`.
+/// Whether a UTXO carries charms is the indexer's `has_charms` annotation;
+/// only rows it has not annotated yet are matched against `charm_utxo_keys`.
+/// A UTXO the indexer flagged `possible_charm` without a stored charm may
+/// hold one the parser could not read: it counts as `locked`, never
+/// `available`, and is also listed under `needs_review`.
+fn classify_btc_utxos(
+    utxo_rows: &[crate::entity::address_utxos::Model],
+    charm_utxo_keys: &std::collections::HashSet<(String, i32)>,
+    pending_spends: &std::collections::HashMap<(String, i32), PendingSpend>,
+) -> serde_json::Value {
+    let mut available: u64 = 0;
+    let mut locked: u64 = 0;
+    let mut unconfirmed: u64 = 0;
+    let mut btc_utxos: Vec<serde_json::Value> = Vec::new();
+    let mut pending_spend: Vec<serde_json::Value> = Vec::new();
+    let mut needs_review: Vec<serde_json::Value> = Vec::new();
+
+    for row in utxo_rows {
+        let key = (row.txid.clone(), row.vout);
+        let has_charms = row
+            .has_charms
+            .unwrap_or_else(|| charm_utxo_keys.contains(&key));
+        let value = row.value as u64;
+        let is_confirmed = row.block_height > 0;
+
+        if let Some(spend) = pending_spends.get(&key) {
+            pending_spend.push(serde_json::json!({
+                "txid": row.txid,
+                "vout": row.vout,
+                "value": row.value,
+                "hasCharms": has_charms,
+                "spendingTxid": spend.spending_txid,
+                "firstSeenAt": spend.first_seen_at.to_rfc3339(),
+            }));
+            continue;
+        }
+
+        let unreviewed = row.possible_charm && !has_charms;
+        if unreviewed {
+            needs_review.push(serde_json::json!({
+                "txid": row.txid,
+                "vout": row.vout,
+                "value": row.value,
+                "blockHeight": row.block_height,
+            }));
+        }
+
+        if !is_confirmed {
+            unconfirmed += value;
+        } else if has_charms || unreviewed {
+            locked += value;
+        } else {
+            available += value;
+        }
+
+        btc_utxos.push(serde_json::json!({
+            "txid": row.txid,
+            "vout": row.vout,
+            "value": row.value,
+            "blockHeight": row.block_height,
+            "hasCharms": has_charms,
+            "charmAppIds": row.has_charms.map(|_| &row.charm_app_ids),
+            "possibleCharm": unreviewed,
+            "confirmed": is_confirmed,
+        }));
+    }
+
+    let confirmed = available + locked;
+    serde_json::json!({
+        "confirmed": confirmed,
+        "unconfirmed": unconfirmed,
+        "total": confirmed + unconfirmed,
+        "available": available,
+        "locked": locked,
+        "utxos": btc_utxos,
+        "pending_spend": pending_spend,
+        "needs_review": needs_review,
+    })
+}
+
 /// POST /wallet/balance/batch
 /// Batch fetch unified BTC + charm balances for up to 50 addresses in one request.
 /// Auto-monitors each address on first call (lazy seeding via Maestro/QuickNode).
@@ -1446,7 +1802,7 @@ async fn resolve_balance_for_batch(
 /// Response: { "results": { "bc1p...": { address, network, monitored, btc, charms }, ... } }
 pub async fn get_wallet_balance_batch(
     State(state): State<AppState>,
-    Json(body): Json<serde_json::Value>,
+    BoundedJson(body): BoundedJson<serde_json::Value>,
 ) -> ExplorerResult<Json<serde_json::Value>> {
     let network = body
         .get("network")
@@ -1464,6 +1820,7 @@ pub async fn get_wallet_balance_batch(
                 .collect()
         })
         .unwrap_or_default();
+    let addresses = normalize_addresses(addresses, &network)?;
 
     if addresses.is_empty() {
         return Ok(Json(serde_json::json!({ "results": {} })));
@@ -1476,7 +1833,7 @@ pub async fn get_wallet_balance_batch(
             let address = addr.clone();
             let network = network.clone();
             tokio::spawn(async move {
-                let qn = quicknode_url(&state).to_string();
+                let qn = quicknode_url(&state, &network).to_string();
                 let mk = maestro_key(&state).to_string();
                 let monitored = AddressMonitorService::ensure_monitored(
                     &state.repositories.monitored_addresses,
@@ -1523,7 +1880,7 @@ pub async fn get_wallet_balance_batch(
 /// Response: { "results": { "addr": { transactions, total, last_block } } }
 pub async fn get_wallet_transactions_batch(
     State(state): State<AppState>,
-    Json(body): Json<serde_json::Value>,
+    BoundedJson(body): BoundedJson<serde_json::Value>,
 ) -> ExplorerResult<Json<serde_json::Value>> {
     let network = body
         .get("network")
@@ -1541,6 +1898,7 @@ pub async fn get_wallet_transactions_batch(
                 .collect()
         })
         .unwrap_or_default();
+    let addresses = normalize_addresses(addresses, &network)?;
 
     let since_block: Option<i64> = body.get("since_block").and_then(|v| v.as_i64());
     let page_size: u64 = body
@@ -1560,7 +1918,7 @@ pub async fn get_wallet_transactions_batch(
             let address = addr.clone();
             let network = network.clone();
             tokio::spawn(async move {
-                let qn = quicknode_url(&state).to_string();
+                let qn = quicknode_url(&state, &network).to_string();
                 let mk = maestro_key(&state).to_string();
 
                 let _ = AddressMonitorService::ensure_monitored(
@@ -1699,3 +2057,541 @@ pub async fn get_wallet_transactions_batch(
 
     Ok(Json(serde_json::json!({ "results": results })))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct WalletOrdersQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+    pub status: Option<String>,
+}
+
+/// GET /wallet/orders/{address}?network=mainnet&status=open|partial
+/// DEX orders the address made that are still on the book, mempool ones
+/// flagged `"confirmed": false`, each with the txids that filled part of
+/// it. `locked_in_orders` sums per asset what those orders hold back from
+/// the wallet's balances. Orders are matched on their maker; the scrolls
+/// address holding an ask's tokens is not linked back to a wallet.
+pub async fn get_wallet_orders(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<WalletOrdersQuery>,
+) -> ExplorerResult<Json<WalletOrdersResponse>> {
+    let network = params.network.as_str();
+    let address = normalize_address(&address, network)?;
+    let response =
+        dex_orders_service::get_wallet_orders(&state, &address, network, params.status.as_deref())
+            .await?;
+    Ok(Json(response))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletChangesQuery {
+    #[serde(
+        default = "default_network",
+        deserialize_with = "crate::models::network_param"
+    )]
+    pub network: String,
+    pub since_height: i32,
+    pub since_mempool_seq: Option<u64>,
+}
+
+/// GET /wallet/changes/{address}?since_height=N&since_mempool_seq=S&network=mainnet
+/// What changed for the address after block N, for wallets that poll:
+/// charms received and spent (with the spending txid), tracked BTC UTXOs
+/// added and removed, and the pending mempool set when it differs from the
+/// one fingerprinted by S. `tip_height` is the next call's `since_height`,
+/// `mempool.seq` its `since_mempool_seq`. An output created and spent
+/// after N is left out. `resync_required` means the changes since N are no
+/// longer kept (or the address was not tracked since then): reload the
+/// full state, then poll from `tip_height`.
+pub async fn get_wallet_changes(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<WalletChangesQuery>,
+) -> ExplorerResult<Json<WalletChangesResponse>> {
+    let network = params.network.as_str();
+    let address = normalize_address(&address, network)?;
+    if params.since_height < 0 {
+        return Err(ExplorerError::InvalidRequest(
+            "since_height must not be negative".to_string(),
+        ));
+    }
+    let repo = &state.repositories.wallet_changes;
+    let db_error = |e: crate::db::DbError| {
+        internal_error(
+            &format!("Wallet: failed to get changes for {}", address),
+            &e.to_string(),
+        )
+    };
+
+    // Read before the call below renews the address or re-seeds it.
+    let sync = repo
+        .sync_state(&address, network, params.since_height)
+        .await
+        .map_err(db_error)?;
+    let qn = quicknode_url(&state, network).to_string();
+    let mk = maestro_key(&state).to_string();
+    let _ = AddressMonitorService::ensure_monitored(
+        &state.repositories.monitored_addresses,
+        &state.repositories.utxo,
+        &state.repositories.address_transactions,
+        &state.http_client,
+        &qn,
+        &mk,
+        &address,
+        network,
+    )
+    .await;
+
+    let response = wallet_changes_service::get_wallet_changes(
+        repo,
+        &address,
+        network,
+        params.since_height,
+        params.since_mempool_seq,
+        sync,
+    )
+    .await
+    .map_err(db_error)?;
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Arc;
+
+    use axum::response::IntoResponse;
+
+    use super::*;
+    use crate::entity::address_utxos;
+    use crate::services::rpc_clients::RpcClients;
+    use crate::test_support::{app_state, asset, charm, repositories, FakeAssets, FakeCharms};
+
+    const OWNER: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+    fn utxo(txid: &str, value: i64, block_height: i32) -> address_utxos::Model {
+        address_utxos::Model {
+            txid: txid.to_string(),
+            vout: 0,
+            network: "mainnet".to_string(),
+            address: OWNER.to_string(),
+            value,
+            script_pubkey: String::new(),
+            block_height,
+            source: None,
+            possible_charm: false,
+            has_charms: None,
+            charm_app_ids: serde_json::json!([]),
+        }
+    }
+
+    #[test]
+    fn pending_spends_leave_every_btc_bucket() {
+        let rows = [
+            utxo("plain", 1_000, 100),
+            utxo("charmed", 546, 100),
+            utxo("spent", 5_000, 100),
+            utxo("change", 700, 0),
+        ];
+        let charm_keys = HashSet::from([("charmed".to_string(), 0)]);
+        let pending = HashMap::from([(
+            ("spent".to_string(), 0),
+            PendingSpend {
+                spending_txid: "spender".to_string(),
+                first_seen_at: chrono::DateTime::from_timestamp(1_760_000_000, 0).unwrap(),
+            },
+        )]);
+
+        let btc = classify_btc_utxos(&rows, &charm_keys, &pending);
+
+        assert_eq!(btc["available"], 1_000);
+        assert_eq!(btc["locked"], 546);
+        assert_eq!(btc["unconfirmed"], 700);
+        assert_eq!(btc["total"], 2_246);
+        let listed: Vec<&str> = btc["utxos"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| u["txid"].as_str().unwrap())
+            .collect();
+        assert_eq!(listed, ["plain", "charmed", "change"]);
+        assert_eq!(
+            btc["pending_spend"],
+            serde_json::json!([{
+                "txid": "spent",
+                "vout": 0,
+                "value": 5_000,
+                "hasCharms": false,
+                "spendingTxid": "spender",
+                "firstSeenAt": "2025-10-09T08:53:20+00:00",
+            }])
+        );
+    }
+
+    #[test]
+    fn possible_charms_are_held_back_for_review() {
+        let flagged = |txid: &str, value: i64| address_utxos::Model {
+            possible_charm: true,
+            ..utxo(txid, value, 100)
+        };
+        let rows = [
+            utxo("plain", 1_000, 100),
+            flagged("unparsed", 600),
+            flagged("parsed", 546),
+        ];
+        // A charm was stored for `parsed` after a parser upgrade.
+        let charm_keys = HashSet::from([("parsed".to_string(), 0)]);
+
+        let btc = classify_btc_utxos(&rows, &charm_keys, &HashMap::new());
+
+        assert_eq!(btc["available"], 1_000);
+        assert_eq!(btc["locked"], 1_146);
+        assert_eq!(btc["total"], 2_146);
+        assert_eq!(
+            btc["needs_review"],
+            serde_json::json!([{
+                "txid": "unparsed",
+                "vout": 0,
+                "value": 600,
+                "blockHeight": 100,
+            }])
+        );
+        assert_eq!(btc["utxos"][1]["possibleCharm"], true);
+        assert_eq!(btc["utxos"][2]["possibleCharm"], false);
+    }
+
+    #[test]
+    fn the_indexer_annotation_wins_over_matching_charms() {
+        let annotated = |txid: &str, app_ids: &[&str]| address_utxos::Model {
+            has_charms: Some(!app_ids.is_empty()),
+            charm_app_ids: serde_json::json!(app_ids),
+            ..utxo(txid, 546, 100)
+        };
+        let rows = [
+            // Its charm is stored under another address form: no key below.
+            annotated("old_charm", &["t/aa/bb"]),
+            annotated("plain", &[]),
+            utxo("legacy", 546, 100),
+        ];
+        let charm_keys = HashSet::from([("legacy".to_string(), 0)]);
+
+        let btc = classify_btc_utxos(&rows, &charm_keys, &HashMap::new());
+
+        assert_eq!(btc["locked"], 1_092);
+        assert_eq!(btc["available"], 546);
+        let utxos = btc["utxos"].as_array().unwrap();
+        assert_eq!(utxos[0]["hasCharms"], true);
+        assert_eq!(utxos[0]["charmAppIds"], serde_json::json!(["t/aa/bb"]));
+        assert_eq!(utxos[1]["hasCharms"], false);
+        assert_eq!(utxos[2]["hasCharms"], true);
+        assert!(utxos[2]["charmAppIds"].is_null());
+    }
+
+    #[tokio::test]
+    async fn charm_balances_move_pending_spends_to_pending_out() {
+        let owned = |txid: &str, amount: i64| {
+            let mut c = charm(txid, "t/aa/bb");
+            c.address = Some(OWNER.to_string());
+            c.amount = amount;
+            c
+        };
+        let mut repos = repositories();
+        repos.charm = Arc::new(
+            FakeCharms::new(vec![owned("kept", 30), owned("leaving", 70)])
+                .with_pending_spend("leaving", 0, "spender"),
+        );
+
+        let response = get_wallet_charm_balances(
+            State(app_state(repos)),
+            Path(OWNER.to_string()),
+            Query(CharmBalancesQuery {
+                network: "mainnet".to_string(),
+                min_confirmations: None,
+            }),
+            ResponseFormat::Json,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let balance = &json["balances"][0];
+        assert_eq!(balance["confirmed"], 30);
+        assert_eq!(balance["pending_out"], 70);
+        assert_eq!(balance["available"], 30);
+        assert_eq!(balance["total"], 100);
+        let flags: HashMap<&str, (bool, Option<&str>)> = balance["utxos"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| {
+                (
+                    u["txid"].as_str().unwrap(),
+                    (u["pendingSpend"].as_bool().unwrap(), u["spendingTxid"].as_str()),
+                )
+            })
+            .collect();
+        assert_eq!(flags["kept"], (false, None));
+        assert_eq!(flags["leaving"], (true, Some("spender")));
+    }
+
+    /// One output carrying two tokens: each token gets its own balance and
+    /// both list the other as a sibling on the shared UTXO.
+    #[tokio::test]
+    async fn charm_balances_count_every_token_on_a_multi_asset_output() {
+        let owned = |app_id: &str, amount: i64| {
+            let mut c = charm("shared", app_id);
+            c.address = Some(OWNER.to_string());
+            c.amount = amount;
+            c
+        };
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            owned("t/aa/bb", 100),
+            owned("t/cc/dd", 7),
+        ]));
+
+        let response = get_wallet_charm_balances(
+            State(app_state(repos)),
+            Path(OWNER.to_string()),
+            Query(CharmBalancesQuery {
+                network: "mainnet".to_string(),
+                min_confirmations: None,
+            }),
+            ResponseFormat::Json,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["count"], 2);
+        let balances: HashMap<&str, &serde_json::Value> = json["balances"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| (b["appId"].as_str().unwrap(), b))
+            .collect();
+        assert_eq!(balances["t/aa/bb"]["confirmed"], 100);
+        assert_eq!(balances["t/cc/dd"]["confirmed"], 7);
+        for balance in balances.values() {
+            assert_eq!(
+                balance["utxos"][0]["allCharmAppIds"],
+                serde_json::json!(["t/aa/bb", "t/cc/dd"])
+            );
+        }
+    }
+
+    /// Decimals set on the token row itself win over its NFT's.
+    #[tokio::test]
+    async fn charm_balances_format_with_decimals_declared_on_the_token() {
+        let mut owned = charm("held", "t/aa/bb");
+        owned.address = Some(OWNER.to_string());
+        owned.amount = 1_250_000;
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![owned]));
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![
+            crate::entity::assets::Model {
+                decimals: 6,
+                ..asset(1, "t/aa/bb", "token")
+            },
+            crate::entity::assets::Model {
+                decimals: 2,
+                ..asset(2, "n/aa/bb", "nft")
+            },
+        ]));
+
+        let response = get_wallet_charm_balances(
+            State(app_state(repos)),
+            Path(OWNER.to_string()),
+            Query(CharmBalancesQuery {
+                network: "mainnet".to_string(),
+                min_confirmations: None,
+            }),
+            ResponseFormat::Json,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let balance = &json["balances"][0];
+        assert_eq!(balance["decimals"], 6);
+        assert_eq!(balance["confirmed_formatted"], "1.25");
+        assert_eq!(balance["available_formatted"], "1.25");
+        assert_eq!(balance["total_formatted"], "1.25");
+    }
+
+    #[tokio::test]
+    async fn min_confirmations_moves_recent_charms_to_unconfirmed() {
+        let mined_at = |txid: &str, block_height: i32, amount: i64| {
+            let mut c = charm(txid, "t/aa/bb");
+            c.address = Some(OWNER.to_string());
+            c.block_height = Some(block_height);
+            c.amount = amount;
+            c
+        };
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            mined_at("at-tip", 200, 1),
+            mined_at("tip-1", 199, 10),
+            mined_at("tip-10", 190, 100),
+        ]));
+        let state = app_state(repos);
+        state.tip_cache.insert("mainnet", 200).await;
+
+        let response = get_wallet_charm_balances(
+            State(state),
+            Path(OWNER.to_string()),
+            Query(CharmBalancesQuery {
+                network: "mainnet".to_string(),
+                min_confirmations: Some(3),
+            }),
+            ResponseFormat::Json,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["min_confirmations"], 3);
+        let balance = &json["balances"][0];
+        assert_eq!(balance["confirmed"], 100);
+        assert_eq!(balance["unconfirmed"], 11);
+        assert_eq!(balance["available"], 111);
+        assert_eq!(balance["reorg_risk"], true);
+        let utxos: HashMap<&str, (u64, bool)> = balance["utxos"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| {
+                (
+                    u["txid"].as_str().unwrap(),
+                    (
+                        u["confirmations"].as_u64().unwrap(),
+                        u["reorg_risk"].as_bool().unwrap(),
+                    ),
+                )
+            })
+            .collect();
+        assert_eq!(utxos["at-tip"], (1, true));
+        assert_eq!(utxos["tip-1"], (2, true));
+        assert_eq!(utxos["tip-10"], (11, false));
+    }
+
+    #[test]
+    fn confirmations_count_the_tip_block() {
+        assert_eq!(confirmations(None, Some(200)), 0);
+        assert_eq!(confirmations(Some(0), Some(200)), 0);
+        assert_eq!(confirmations(Some(200), Some(200)), 1);
+        assert_eq!(confirmations(Some(190), Some(200)), 11);
+        assert_eq!(confirmations(Some(190), None), 1);
+        assert_eq!(confirmations(Some(201), Some(200)), 1);
+    }
+
+    #[tokio::test]
+    async fn uppercase_bech32_finds_the_stored_address() {
+        let mut owned = charm("tx1", "t/aa/bb");
+        owned.address = Some(OWNER.to_string());
+        owned.amount = 5;
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![owned]));
+
+        let response = get_wallet_charm_balances(
+            State(app_state(repos)),
+            Path(OWNER.to_uppercase()),
+            Query(CharmBalancesQuery {
+                network: "mainnet".to_string(),
+                min_confirmations: None,
+            }),
+            ResponseFormat::Json,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["address"], OWNER);
+        assert_eq!(json["balances"][0]["appId"], "t/aa/bb");
+    }
+
+    #[tokio::test]
+    async fn an_address_from_another_network_is_rejected() {
+        let response = get_wallet_charm_balances(
+            State(app_state(repositories())),
+            Path("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx".to_string()),
+            Query(CharmBalancesQuery {
+                network: "mainnet".to_string(),
+                min_confirmations: None,
+            }),
+            ResponseFormat::Json,
+        )
+        .await
+        .unwrap_err()
+        .into_response();
+
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("bc1"));
+    }
+
+    #[tokio::test]
+    async fn a_network_that_is_not_enabled_is_not_found() {
+        let mut state = app_state(repositories());
+        state.config.enable_bitcoin_testnet4 = false;
+        state.rpc_clients = Arc::new(RpcClients::from_config(&state.config));
+
+        for network in ["testnet4", "regtest"] {
+            let response = get_wallet_transaction(
+                State(state.clone()),
+                TxidPath("ab".repeat(32)),
+                Query(NetworkQuery {
+                    network: network.to_string(),
+                    min_value: None,
+                }),
+            )
+            .await
+            .unwrap_err()
+            .into_response();
+            assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[tokio::test]
+    async fn each_network_routes_to_its_own_client() {
+        let state = app_state(repositories());
+        let mainnet = rpc_client(&state, "mainnet").unwrap();
+        let testnet4 = rpc_client(&state, "testnet4").unwrap();
+        assert!(!Arc::ptr_eq(&mainnet, &testnet4));
+        assert!(Arc::ptr_eq(
+            &testnet4,
+            &state.rpc_clients.get("testnet4").unwrap()
+        ));
+        assert_eq!(
+            state.rpc_clients.url("testnet4"),
+            Some("http://127.0.0.1:2")
+        );
+    }
+}
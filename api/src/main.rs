@@ -7,11 +7,14 @@ mod error;
 mod handlers;
 mod models;
 mod services;
+#[cfg(test)]
+mod test_support;
 
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use axum::middleware;
 use axum::routing::{Router, delete, get, post};
 use http::{Method, header};
 use tower_http::cors::{Any, CorsLayer};
@@ -21,22 +24,32 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use config::ApiConfig;
 use db::DbPool;
+use services::rpc_clients::RpcClients;
+use services::scan_cache::ScanCache;
+use services::shutdown::{self, Drain, Lifecycle};
+use services::tip_cache::TipCache;
+use services::tx_hex_cache::TxHexCache;
 use handlers::{
     AppState, MaestroCircuitBreaker,
-    broadcast_wallet_transaction, diagnose_database, diagnostics_address,
-    get_asset_by_id, get_asset_counts,
-    get_asset_holders, get_assets, get_charm_by_charmid, get_charm_by_txid, get_charm_numbers,
+    broadcast_wallet_transaction, diagnose_config, diagnose_database, diagnostics_address,
+    get_asset_by_id, get_asset_counts, get_asset_likes, get_asset_mints, get_asset_supply_history,
+    get_address_summary, get_asset_holders, get_assets, get_balance_at, get_block, get_blocks, get_changefeed, get_charm_by_charmid, get_charm_by_txid, get_charm_numbers,
     get_charms, get_charms_by_address, get_charms_by_type, get_charms_count_by_type,
+    get_collection_assets, get_collections, get_dashboard,
     get_all_orders, get_indexer_status, get_open_orders, get_order_by_id, get_orders_by_asset,
-    get_orders_by_maker,
-    get_reference_nft_by_hash, get_transaction_by_txid, get_transactions, get_wallet_balance,
+    get_market_summary, get_orders_by_maker,
+    get_reference_nft_by_hash, health_ready, get_spell_outputs, get_transaction_by_txid, get_transactions, get_tx_hex,
+    get_wallet_balance,
     get_wallet_balance_batch,
     get_wallet_chain_tip, get_wallet_charm_balances, get_wallet_charm_balances_batch,
     get_wallet_charm_balances_batch_indexed,
-    get_wallet_fee_estimate, get_wallet_prev_txs, get_wallet_transaction, get_wallet_transactions,
-    get_wallet_transactions_batch,
+    get_wallet_changes, get_wallet_fee_estimate, get_wallet_history, get_wallet_orders,
+    get_wallet_prev_txs,
+    get_wallet_transaction,
+    get_wallet_transactions, get_wallet_transactions_batch,
     get_wallet_tx_hex, get_wallet_utxos, get_wallet_utxos_batch,
-    health_check, like_charm, unlike_charm,
+    get_mempool_stats, get_metrics, get_mint_feed, get_parser_stats, health_check, like_charm, unlike_charm,
+    unmonitor_wallet_address, admin_routes,
 };
 
 fn load_env() {
@@ -64,34 +77,9 @@ async fn main() {
         .expect("Failed to connect to database");
     tracing::info!("Connected to database");
 
-    // Initialize shared Bitcoin RPC clients (one per network, reused across all requests)
-    let rpc_mainnet = {
-        let url = format!(
-            "http://{}:{}",
-            config.bitcoin_mainnet_rpc_host, config.bitcoin_mainnet_rpc_port
-        );
-        let auth = bitcoincore_rpc::Auth::UserPass(
-            config.bitcoin_mainnet_rpc_username.clone(),
-            config.bitcoin_mainnet_rpc_password.clone(),
-        );
-        Arc::new(
-            bitcoincore_rpc::Client::new(&url, auth).expect("Failed to create mainnet RPC client"),
-        )
-    };
-    let rpc_testnet4 = {
-        let url = format!(
-            "http://{}:{}",
-            config.bitcoin_testnet4_rpc_host, config.bitcoin_testnet4_rpc_port
-        );
-        let auth = bitcoincore_rpc::Auth::UserPass(
-            config.bitcoin_testnet4_rpc_username.clone(),
-            config.bitcoin_testnet4_rpc_password.clone(),
-        );
-        Arc::new(
-            bitcoincore_rpc::Client::new(&url, auth).expect("Failed to create testnet4 RPC client"),
-        )
-    };
-    tracing::info!("Bitcoin RPC clients initialized (mainnet + testnet4)");
+    // Bitcoin RPC clients for the enabled networks, each built on first use
+    let rpc_clients = Arc::new(RpcClients::from_config(&config));
+    tracing::info!("Bitcoin RPC networks: {}", rpc_clients.networks().join(", "));
 
     // Initialize application state with repositories and config
     let repositories = db_pool.repositories();
@@ -100,24 +88,42 @@ async fn main() {
     //   - 200 idle connections per host (Maestro + QuickNode)
     //   - 10s connect timeout, 15s request timeout
     //   - TCP keepalive avoids connection churn under load
+    //   - HTTP/2 keepalive pings hold multiplexed QuickNode sessions open
+    // Every outbound helper takes `&state.http_client`; never build a
+    // per-call client, or each request pays a fresh TLS handshake.
     let http_client = reqwest::Client::builder()
         .pool_max_idle_per_host(200)
         .pool_idle_timeout(Duration::from_secs(60))
         .connect_timeout(Duration::from_secs(10))
         .timeout(Duration::from_secs(15))
         .tcp_keepalive(Duration::from_secs(60))
+        .http2_adaptive_window(true)
+        .http2_keep_alive_interval(Duration::from_secs(30))
+        .http2_keep_alive_while_idle(true)
         .build()
         .expect("Failed to build HTTP client");
 
+    // Tip and scan results, shared across replicas when REDIS_URL is set
+    let cache = services::cache::connect(config.redis_url.as_deref()).await;
+
+    let lifecycle = Arc::new(Lifecycle::default());
     let app_state = AppState {
         repositories: Arc::new(repositories),
         config: config.clone(),
         scan_semaphore: Arc::new(tokio::sync::Semaphore::new(1)),
         quicknode_semaphore: Arc::new(tokio::sync::Semaphore::new(64)),
         http_client,
-        rpc_mainnet,
-        rpc_testnet4,
+        rpc_clients,
         maestro_cb: Arc::new(MaestroCircuitBreaker::new()),
+        scan_cache: Arc::new(ScanCache::new(
+            cache.clone(),
+            Duration::from_secs(config.wallet_scan_cache_ttl_secs),
+        )),
+        // Mempool hex is immutable; the TTL only bounds memory
+        tx_hex_cache: Arc::new(TxHexCache::new(Duration::from_secs(120))),
+        // Short enough that confirmation counts lag a new block by seconds
+        tip_cache: Arc::new(TipCache::new(cache, Duration::from_secs(10))),
+        lifecycle: lifecycle.clone(),
     };
 
     // Configure CORS policy
@@ -140,12 +146,19 @@ async fn main() {
     let api_routes = Router::new()
         // Infrastructure
         .route("/health", get(health_check))
+        .route("/health/ready", get(health_ready))
         .route("/status", get(get_indexer_status))
+        .route("/metrics", get(get_metrics))
         .route("/diagnose", get(diagnose_database))
+        .route("/diagnose/config", get(diagnose_config))
         .route(
             "/internal/diagnostics/address/{network}/{address}",
             get(diagnostics_address),
         )
+        // Operator endpoints: token-checked, mutations audited
+        .merge(admin_routes(app_state.clone()))
+        // Homepage figures in one call
+        .route("/dashboard", get(get_dashboard))
         // Charms
         .route("/charms", get(get_charms))
         .route("/charms/count", get(get_charm_numbers))
@@ -156,6 +169,9 @@ async fn main() {
         .route("/charms/like", post(like_charm))
         .route("/charms/like", delete(unlike_charm))
         .route("/charms/{txid}", get(get_charm_by_txid))
+        // Addresses
+        .route("/address/{address}/balance-at", get(get_balance_at))
+        .route("/address/{address}/summary", get(get_address_summary))
         // Assets
         .route("/assets", get(get_assets))
         .route("/assets/count", get(get_asset_counts))
@@ -164,10 +180,30 @@ async fn main() {
             get(get_reference_nft_by_hash),
         )
         .route("/assets/{app_id}/holders", get(get_asset_holders))
+        .route("/assets/{app_id}/likes", get(get_asset_likes))
+        .route("/assets/{app_id}/mints", get(get_asset_mints))
+        .route(
+            "/assets/{app_id}/supply-history",
+            get(get_asset_supply_history),
+        )
         .route("/assets/{asset_id}", get(get_asset_by_id))
+        // Processed blocks
+        .route("/blocks", get(get_blocks))
+        .route("/blocks/{height}", get(get_block))
+        // NFT collections
+        .route("/collections", get(get_collections))
+        .route("/collections/{id}/assets", get(get_collection_assets))
+        // Issuance feed
+        .route("/stats/mints", get(get_mint_feed))
+        .route("/stats/mempool", get(get_mempool_stats))
+        .route("/stats/parser", get(get_parser_stats))
+        // Row-change log for downstream ETL
+        .route("/changefeed", get(get_changefeed))
         // Transactions
         .route("/transactions", get(get_transactions))
         .route("/transactions/{txid}", get(get_transaction_by_txid))
+        .route("/tx/{txid}/hex", get(get_tx_hex))
+        .route("/spells/{txid}/outputs", get(get_spell_outputs))
         // DEX Orders
         .route("/dex/orders", get(get_all_orders))
         .route("/dex/orders/open", get(get_open_orders))
@@ -177,11 +213,13 @@ async fn main() {
         )
         .route("/dex/orders/by-maker/{maker}", get(get_orders_by_maker))
         .route("/dex/orders/{order_id}", get(get_order_by_id))
+        .route("/dex/markets/{asset_app_id}", get(get_market_summary))
         // Wallet
         .route("/wallet/utxos/{address}", get(get_wallet_utxos))
         .route("/wallet/utxos/batch", post(get_wallet_utxos_batch))
         .route("/wallet/balance/{address}", get(get_wallet_balance))
         .route("/wallet/balance/batch", post(get_wallet_balance_batch))
+        .route("/wallet/monitor/{address}", delete(unmonitor_wallet_address))
         .route("/wallet/tx/{txid}", get(get_wallet_transaction))
         .route("/wallet/tx/{txid}/hex", get(get_wallet_tx_hex))
         .route("/wallet/prev-txs", post(get_wallet_prev_txs))
@@ -204,7 +242,10 @@ async fn main() {
         .route(
             "/wallet/transactions/batch",
             post(get_wallet_transactions_batch),
-        );
+        )
+        .route("/wallet/history/{address}", get(get_wallet_history))
+        .route("/wallet/orders/{address}", get(get_wallet_orders))
+        .route("/wallet/changes/{address}", get(get_wallet_changes));
 
     // Mount under /v1/ (canonical) and / (backward compat for Explorer webapp)
     let app = Router::new()
@@ -213,6 +254,10 @@ async fn main() {
         .layer(TimeoutLayer::new(Duration::from_secs(60)))
         .layer(TraceLayer::new_for_http())
         .layer(cors)
+        .layer(middleware::from_fn_with_state(
+            lifecycle.clone(),
+            shutdown::track_in_flight,
+        ))
         .with_state(app_state);
 
     // Parse server address from config
@@ -223,7 +268,26 @@ async fn main() {
     let listener = tokio::net::TcpListener::bind(&addr)
         .await
         .expect("Failed to bind to address");
-    axum::serve(listener, app)
+    let drain_timeout = Duration::from_secs(config.shutdown_drain_secs);
+    match shutdown::serve(listener, app, lifecycle, shutdown::signal(), drain_timeout)
         .await
-        .expect("Failed to start server");
+        .expect("Failed to start server")
+    {
+        Drain::Complete { drained } => {
+            tracing::info!("Server stopped: {} in-flight request(s) drained", drained)
+        }
+        Drain::TimedOut { drained, abandoned } => tracing::warn!(
+            "Server stopped after the {}s drain timeout: {} request(s) drained, {} abandoned",
+            drain_timeout.as_secs(),
+            drained,
+            abandoned
+        ),
+    }
+
+    // Closing the pool waits for checked-out connections, so no query is
+    // left running server-side on a dropped socket.
+    match db_pool.close().await {
+        Ok(()) => tracing::info!("Database pool closed"),
+        Err(e) => tracing::warn!("Failed to close the database pool: {}", e),
+    }
 }
@@ -1,6 +1,10 @@
 // API request/response models
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::db::repositories::address_summary_repository::AddressSummary;
+
 /// Custom deserializer to convert string to u64
 fn deserialize_string_to_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
@@ -10,22 +14,210 @@ where
     s.parse::<u64>().map_err(serde::de::Error::custom)
 }
 
-/// Common pagination parameters for API endpoints
+/// Custom deserializer for query flags. Structs that flatten
+/// `PaginationParams` see every value as a string.
+fn deserialize_string_to_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = String::deserialize(deserializer)?;
+    s.parse::<bool>().map_err(serde::de::Error::custom)
+}
+
+/// `network` query parameters are read in canonical form
+/// (`charms_core::network`): `?network=Testnet` filters on `testnet4`.
+pub fn network_param<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = String::deserialize(deserializer)?;
+    Ok(charms_core::normalize_network(&s))
+}
+
+/// `network_param` for optional parameters; needs `#[serde(default)]`.
+pub fn optional_network_param<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    Ok(s.map(|s| charms_core::normalize_network(&s)))
+}
+
+/// Common pagination parameters for API endpoints. `limit` is clamped and
+/// over-deep pages are rejected at deserialization (see `PageLimits`), so the
+/// values here are the ones actually applied.
 #[derive(Debug, Deserialize, Default)]
+#[serde(try_from = "RawPaginationParams")]
 pub struct PaginationParams {
+    pub page: u64,
+    pub limit: u64,
+    pub sort: CharmSort,
+}
+
+/// `PaginationParams` as sent, before `PageLimits` apply.
+#[derive(Deserialize)]
+struct RawPaginationParams {
     #[serde(
         default = "default_page",
         deserialize_with = "deserialize_string_to_u64"
     )]
-    pub page: u64,
+    page: u64,
     #[serde(
         default = "default_limit",
         deserialize_with = "deserialize_string_to_u64"
     )]
-    pub limit: u64,
-    #[serde(default = "default_sort_order")]
-    #[allow(dead_code)] // Deserialized from query but not yet implemented
-    pub sort: String,
+    limit: u64,
+    #[serde(default)]
+    sort: CharmSort,
+}
+
+impl TryFrom<RawPaginationParams> for PaginationParams {
+    type Error = PageTooDeep;
+
+    fn try_from(raw: RawPaginationParams) -> Result<Self, Self::Error> {
+        Self::bounded(raw, &PageLimits::global())
+    }
+}
+
+impl PaginationParams {
+    fn bounded(raw: RawPaginationParams, limits: &PageLimits) -> Result<Self, PageTooDeep> {
+        let (page, limit) = limits.apply(raw.page, raw.limit)?;
+        Ok(Self {
+            page,
+            limit,
+            sort: raw.sort,
+        })
+    }
+}
+
+/// Server-side bounds on offset pagination, shared by every listing
+/// endpoint: `limit` is clamped to `max_limit` and pages starting past
+/// `max_offset` rows are refused, so no single request can make the
+/// database scan or ship an unbounded number of rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageLimits {
+    pub max_limit: u64,
+    pub max_offset: u64,
+}
+
+impl Default for PageLimits {
+    fn default() -> Self {
+        Self {
+            max_limit: 100,
+            max_offset: 10_000,
+        }
+    }
+}
+
+impl PageLimits {
+    /// `API_MAX_PAGE_LIMIT` / `API_MAX_PAGE_OFFSET` when set to positive
+    /// numbers, otherwise 100 rows per page and 10 000 rows deep.
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|v| *v > 0)
+        };
+        let defaults = Self::default();
+        Self {
+            max_limit: var("API_MAX_PAGE_LIMIT").unwrap_or(defaults.max_limit),
+            max_offset: var("API_MAX_PAGE_OFFSET").unwrap_or(defaults.max_offset),
+        }
+    }
+
+    /// Limits from the environment, read once per process. Query extractors
+    /// have no access to `AppState`, hence the global.
+    pub fn global() -> Self {
+        static LIMITS: std::sync::OnceLock<PageLimits> = std::sync::OnceLock::new();
+        *LIMITS.get_or_init(Self::from_env)
+    }
+
+    /// `(page, limit)` to apply: page at least 1, limit within
+    /// `1..=max_limit`. Fails when the page would start past `max_offset`.
+    pub fn apply(&self, page: u64, limit: u64) -> Result<(u64, u64), PageTooDeep> {
+        let page = page.max(1);
+        let limit = limit.clamp(1, self.max_limit);
+        let offset = (page - 1).saturating_mul(limit);
+        if offset > self.max_offset {
+            return Err(PageTooDeep {
+                offset,
+                max_offset: self.max_offset,
+            });
+        }
+        Ok((page, limit))
+    }
+}
+
+/// A page starting deeper than `PageLimits::max_offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageTooDeep {
+    pub offset: u64,
+    pub max_offset: u64,
+}
+
+impl std::fmt::Display for PageTooDeep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "page starts at row {}, past the {}-row limit for page/limit pagination; \
+             narrow the filter or page by cursor (e.g. block height) instead",
+            self.offset, self.max_offset
+        )
+    }
+}
+
+impl From<PageTooDeep> for crate::error::ExplorerError {
+    fn from(e: PageTooDeep) -> Self {
+        crate::error::ExplorerError::InvalidRequest(e.to_string())
+    }
+}
+
+/// Ordering for charm listings (`?sort=`). Unknown values fail
+/// deserialization, which the query extractor turns into a 400.
+///
+/// Every ordering ends with `txid, vout` so equal keys page stably.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharmSort {
+    /// Mempool first, then newest block / position in block
+    #[default]
+    Newest,
+    Oldest,
+    AmountDesc,
+    AmountAsc,
+    /// Confirmed charms by block height, mempool last
+    BlockDesc,
+    /// Most liked first. Counts likes per row with a join, so it is slower
+    /// than the other orderings and its page depth is capped
+    /// (`CharmRepository::LIKES_SORT_MAX_OFFSET`).
+    LikesDesc,
+}
+
+/// Ordering for asset listings (`GET /assets?sort=`). Unknown values fail
+/// deserialization like `CharmSort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetSort {
+    /// Newest mint block first
+    #[default]
+    Newest,
+    Oldest,
+    /// Most liked first (trending). Joins a likes count per app_id, so its
+    /// page depth is capped (`AssetRepository::LIKES_SORT_MAX_OFFSET`).
+    LikesDesc,
+}
+
+/// How GET /charms/by-address lists an address's charms (`?group=`).
+/// Unknown values fail deserialization like `CharmSort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharmGrouping {
+    /// One entry per unspent charm
+    #[default]
+    None,
+    /// One entry per app_id, its charms summed
+    Asset,
 }
 
 fn default_page() -> u64 {
@@ -36,10 +228,6 @@ fn default_limit() -> u64 {
     20
 }
 
-fn default_sort_order() -> String {
-    "newest".to_string()
-}
-
 /// Query parameters for GET /charms/count endpoint
 #[derive(Debug, Deserialize)]
 pub struct GetCharmNumbersQuery {
@@ -60,6 +248,8 @@ pub struct CharmsCountByTypeResponse {
     pub nft: u64,
     pub token: u64,
     pub dapp: u64,
+    /// Charms per `mint` / `transfer` / `burn`
+    pub by_operation: BTreeMap<&'static str, u64>,
 }
 
 /// Response structure for GET /charms endpoint
@@ -68,6 +258,42 @@ pub struct CharmsResponse {
     pub charms: Vec<CharmData>,
 }
 
+/// One app_id held by an address, for GET /charms/by-address?group=asset
+#[derive(Debug, Serialize)]
+pub struct AssetHoldingData {
+    pub app_id: String,
+    /// Sum of the unspent charms, raw units
+    pub amount: i64,
+    pub amount_formatted: String,
+    pub decimals: u8,
+    pub utxo_count: i64,
+    /// Blocks of the earliest and latest held UTXO; null while all are in
+    /// mempool
+    pub first_block: Option<i32>,
+    pub last_block: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+/// Response for GET /charms/by-address?group=asset
+#[derive(Debug, Serialize)]
+pub struct AddressAssetsResponse {
+    pub assets: Vec<AssetHoldingData>,
+}
+
+/// Response for GET /charms/by-address: the flat charm list, or the
+/// per-asset summary with `group=asset`
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum AddressCharmsResponse {
+    Charms(CharmsResponse),
+    Assets(AddressAssetsResponse),
+}
+
 /// Charm data structure for API responses
 #[derive(Debug, Serialize)]
 pub struct CharmData {
@@ -76,7 +302,11 @@ pub struct CharmData {
     pub charmid: String,
     pub block_height: Option<i32>,
     pub data: serde_json::Value,
+    /// When the indexer wrote the charm
     pub date_created: String,
+    /// Header time of the confirming block; null while in mempool and for
+    /// charms indexed before block times were recorded
+    pub block_time: Option<String>,
     pub asset_type: String,
     pub network: String,
     pub amount: i64, // [RJJ-ADDRESS] Token amount in this UTXO
@@ -95,12 +325,84 @@ pub struct CharmData {
     pub description: Option<String>,
     /// Whether the spell proof has been verified
     pub verified: bool,
+    /// Empty-spell placeholder; only ever returned by direct lookups
+    #[serde(default)]
+    pub is_placeholder: bool,
     // [RJJ-BEAMING] Tags for transaction classification (e.g., "beaming", "bro", "charms-cast")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<String>,
     // [RJJ-SPELL] Original spell data from transactions table
     #[serde(skip_serializing_if = "Option::is_none")]
     pub spell: Option<serde_json::Value>,
+    // [RJJ-DEX] Charms Cast order details; only set by GET /charms/{txid}
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dex: Option<CharmDex>,
+    /// The rest of the charm's spell; only set by GET /charms/{txid}
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spell_context: Option<SpellContext>,
+}
+
+/// The spell a charm belongs to: every charm output of its transaction and
+/// the charms it consumed, each summarized without its data JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpellContext {
+    /// Shape of the spell JSON; absent when the tx has no stored spell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spell: Option<SpellSummary>,
+    /// Charm outputs of the tx, the requested one included
+    pub outputs: Vec<SpellCharm>,
+    /// Charm outputs the tx spent. Rows spent before the indexer recorded
+    /// `spending_txid` are missing.
+    pub inputs: Vec<SpellCharm>,
+    /// Whether `outputs` or `inputs` were cut at `SPELL_CONTEXT_LIMIT`
+    pub truncated: bool,
+}
+
+/// Counts read from a spell JSON
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpellSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    /// Apps the spell declares
+    pub apps: usize,
+    /// Inputs and outputs the spell declares
+    pub ins: usize,
+    pub outs: usize,
+    /// Outputs of the Bitcoin tx, when the indexer recorded them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_outputs: Option<u64>,
+}
+
+/// One charm row of a spell
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpellCharm {
+    pub txid: String,
+    pub vout: i32,
+    pub app_id: String,
+    pub asset_type: String,
+    pub amount: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub spent: bool,
+}
+
+/// Charms Cast DEX details of a charm's transaction. Order fields are absent
+/// when the indexer has no `dex_orders` row for the tx.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CharmDex {
+    /// Operation tag, e.g. "create-bid" or "fulfill-ask"
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+    /// Sats per whole token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
 }
 
 #[allow(dead_code)] // Used by serde default attribute
@@ -118,8 +420,17 @@ fn default_user_liked() -> bool {
 pub struct GetCharmsByTypeQuery {
     #[serde(rename = "type")]
     pub asset_type: String,
+    #[serde(default = "default_network", deserialize_with = "network_param")]
+    pub network: String,
     #[serde(flatten)]
     pub pagination: PaginationParams,
+    /// Also list hidden charms; honoured only with the admin token
+    #[serde(default, deserialize_with = "deserialize_string_to_bool")]
+    pub include_hidden: bool,
+}
+
+fn default_network() -> String {
+    "mainnet".to_string()
 }
 
 /// Query parameters for GET /charms endpoint
@@ -129,13 +440,73 @@ pub struct GetCharmsQuery {
     pub pagination: PaginationParams,
     #[serde(default = "default_user_id")]
     pub user_id: i32,
+    #[serde(default, deserialize_with = "optional_network_param")]
     pub network: Option<String>,
+    /// `mint`, `transfer` or `burn`
+    pub operation: Option<String>,
+    /// Only charms of this app_id
+    pub app_id: Option<String>,
+    /// Inclusive amount bounds, raw units unless `decimals` is set
+    pub min_amount: Option<String>,
+    pub max_amount: Option<String>,
+    /// Read the amount bounds in decimal units of `app_id`
+    #[serde(default, deserialize_with = "deserialize_string_to_bool")]
+    pub decimals: bool,
+    /// Also return hidden charms; honoured only with the admin token
+    #[serde(default, deserialize_with = "deserialize_string_to_bool")]
+    pub include_hidden: bool,
+    /// `asset` sums the by-address listing per app_id; ignored elsewhere
+    #[serde(default)]
+    pub group: CharmGrouping,
 }
 
 fn default_user_id() -> i32 {
     1 // Default user ID as specified in requirements
 }
 
+/// Query parameters for GET /address/{address}/balance-at
+#[derive(Debug, Deserialize)]
+pub struct BalanceAtQuery {
+    pub app_id: String,
+    /// Block at whose end the balance is taken
+    pub height: i32,
+    #[serde(default, deserialize_with = "optional_network_param")]
+    pub network: Option<String>,
+}
+
+/// Response for GET /address/{address}/balance-at: `balance` when `exact`,
+/// otherwise the bounds it lies between
+#[derive(Debug, Serialize)]
+pub struct BalanceAtResponse {
+    pub address: String,
+    pub app_id: String,
+    pub network: String,
+    pub height: i32,
+    pub exact: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lower_bound: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upper_bound: Option<i64>,
+}
+
+/// Query parameters for GET /address/{address}/summary
+#[derive(Debug, Deserialize)]
+pub struct AddressSummaryQuery {
+    #[serde(default, deserialize_with = "optional_network_param")]
+    pub network: Option<String>,
+}
+
+/// Response for GET /address/{address}/summary
+#[derive(Debug, Serialize)]
+pub struct AddressSummaryResponse {
+    pub address: String,
+    pub network: String,
+    #[serde(flatten)]
+    pub summary: AddressSummary,
+}
+
 /// Request body for POST /charms/like endpoint
 #[derive(Debug, Deserialize)]
 pub struct LikeCharmRequest {
@@ -149,6 +520,8 @@ pub struct LikeCharmRequest {
 pub struct LikeResponse {
     pub success: bool,
     pub message: String,
+    /// False on a repeat: the charm was already liked (or not liked).
+    pub changed: bool,
     pub likes_count: i64,
 }
 
@@ -159,6 +532,44 @@ pub struct PaginationMeta {
     pub page: u64,
     pub limit: u64,
     pub total_pages: u64,
+    #[serde(flatten)]
+    pub nav: PageNav,
+}
+
+impl PaginationMeta {
+    /// `nav` starts empty; the handler fills it from the request's
+    /// `PageLinks`.
+    pub fn new(total: u64, page: u64, limit: u64, total_pages: u64) -> Self {
+        Self {
+            total,
+            page,
+            limit,
+            total_pages,
+            nav: PageNav::default(),
+        }
+    }
+}
+
+/// Links to the neighbouring pages of a listing. URLs are absolute and
+/// repeat the request's own query, filters included, with only the page
+/// changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PageNav {
+    pub has_next: bool,
+    pub has_prev: bool,
+    pub next_url: Option<String>,
+    pub prev_url: Option<String>,
+}
+
+impl PageNav {
+    /// Add these fields to a `json!` response body.
+    pub fn extend(self, body: &mut serde_json::Value) {
+        if let (Some(body), Ok(serde_json::Value::Object(fields))) =
+            (body.as_object_mut(), serde_json::to_value(self))
+        {
+            body.extend(fields);
+        }
+    }
 }
 
 /// Response structure with pagination
@@ -173,6 +584,7 @@ pub struct PaginatedResponse<T> {
 pub struct GetTransactionsQuery {
     #[serde(flatten)]
     pub pagination: PaginationParams,
+    #[serde(default, deserialize_with = "optional_network_param")]
     pub network: Option<String>,
 }
 
@@ -240,3 +652,110 @@ impl From<crate::entity::transactions::Model> for TransactionData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charm_sort_parses_known_values_and_rejects_others() {
+        let parse = |v: &str| serde_json::from_value::<CharmSort>(serde_json::json!(v));
+        assert_eq!(parse("newest").unwrap(), CharmSort::Newest);
+        assert_eq!(parse("amount_asc").unwrap(), CharmSort::AmountAsc);
+        assert_eq!(parse("likes_desc").unwrap(), CharmSort::LikesDesc);
+        assert!(parse("popular").is_err());
+        assert!(parse("Newest").is_err());
+
+        let params: PaginationParams = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(params.sort, CharmSort::Newest);
+    }
+
+    #[test]
+    fn charm_grouping_defaults_to_flat() {
+        let parse = |v: serde_json::Value| serde_json::from_value::<GetCharmsQuery>(v);
+        let params = parse(serde_json::json!({ "group": "asset" })).unwrap();
+        assert_eq!(params.group, CharmGrouping::Asset);
+        let params = parse(serde_json::json!({})).unwrap();
+        assert_eq!(params.group, CharmGrouping::None);
+        assert!(parse(serde_json::json!({ "group": "app_id" })).is_err());
+    }
+
+    #[test]
+    fn network_filters_are_read_in_canonical_form() {
+        let parse = |v: serde_json::Value| serde_json::from_value::<GetCharmsQuery>(v).unwrap();
+        let params = parse(serde_json::json!({ "network": "Bitcoin-testnet4" }));
+        assert_eq!(params.network.as_deref(), Some("testnet4"));
+        assert_eq!(parse(serde_json::json!({})).network, None);
+
+        let by_type: GetCharmsByTypeQuery =
+            serde_json::from_value(serde_json::json!({ "type": "nft", "network": "Mainnet" }))
+                .unwrap();
+        assert_eq!(by_type.network, "mainnet");
+    }
+
+    fn raw(page: u64, limit: u64) -> RawPaginationParams {
+        RawPaginationParams {
+            page,
+            limit,
+            sort: CharmSort::Newest,
+        }
+    }
+
+    #[test]
+    fn pagination_clamps_limit_and_page() {
+        let limits = PageLimits {
+            max_limit: 100,
+            max_offset: 1_000,
+        };
+        let p = PaginationParams::bounded(raw(1, 100_000), &limits).unwrap();
+        assert_eq!((p.page, p.limit), (1, 100));
+        let p = PaginationParams::bounded(raw(0, 0), &limits).unwrap();
+        assert_eq!((p.page, p.limit), (1, 1));
+        // Row 1 000 is the deepest page start allowed.
+        let p = PaginationParams::bounded(raw(11, 100), &limits).unwrap();
+        assert_eq!((p.page, p.limit), (11, 100));
+    }
+
+    #[test]
+    fn pagination_rejects_pages_past_the_depth_cap() {
+        let limits = PageLimits {
+            max_limit: 100,
+            max_offset: 1_000,
+        };
+        let err = PaginationParams::bounded(raw(12, 100), &limits).unwrap_err();
+        assert_eq!(
+            err,
+            PageTooDeep {
+                offset: 1_100,
+                max_offset: 1_000
+            }
+        );
+        assert!(err.to_string().contains("cursor"));
+        // The clamp applies first: a huge limit cannot dodge the cap.
+        assert!(PaginationParams::bounded(raw(u64::MAX, u64::MAX), &limits).is_err());
+    }
+
+    #[test]
+    fn pagination_deserializer_applies_the_global_limits() {
+        let limits = PageLimits::global();
+        let params: PaginationParams =
+            serde_json::from_value(serde_json::json!({"page": "1", "limit": "100000"})).unwrap();
+        assert_eq!(params.limit, limits.max_limit);
+
+        let too_deep = (limits.max_offset / limits.max_limit + 2).to_string();
+        let err = serde_json::from_value::<PaginationParams>(
+            serde_json::json!({"page": too_deep, "limit": "100000"}),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("row limit"), "{err}");
+    }
+
+    #[test]
+    fn asset_sort_accepts_the_webapp_values() {
+        let parse = |v: &str| serde_json::from_value::<AssetSort>(serde_json::json!(v));
+        assert_eq!(parse("newest").unwrap(), AssetSort::Newest);
+        assert_eq!(parse("oldest").unwrap(), AssetSort::Oldest);
+        assert_eq!(parse("likes_desc").unwrap(), AssetSort::LikesDesc);
+        assert!(parse("likes").is_err());
+    }
+}
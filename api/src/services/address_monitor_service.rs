@@ -18,6 +18,11 @@ use crate::services::wallet_service::WalletService;
 ///    provider (QuickNode / Mempool) and registers it. From that moment on,
 ///    the indexer keeps the UTXO set up to date as new blocks arrive.
 ///
+/// Every call refreshes `last_queried_at` and pushes `expires_at` out by
+/// `MONITOR_TTL_DAYS`. Once it lapses the indexer stops tracking the address
+/// (unless it holds charms) and its GC drops the seeded UTXOs, so the next
+/// call here re-seeds it like a new address.
+///
 /// An advisory lock prevents concurrent seeding of the same address.
 pub struct AddressMonitorService;
 
@@ -35,6 +40,11 @@ impl AddressMonitorService {
         address: &str,
         network: &str,
     ) -> Result<bool, String> {
+        // 0. Keep the address alive for another TTL period.
+        if let Err(e) = monitored_repo.touch(address, network).await {
+            tracing::warn!("Failed to refresh monitor TTL for {}: {}", address, e);
+        }
+
         // 1. Soft refresh: a row marked `seeded` is still re-fetched from the
         //    provider when its last seed is older than MEMPOOL_REFRESH. The
         //    indexer only updates `address_utxos` at block confirmation, so a
@@ -1,44 +1,51 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::db::repositories::asset_repository::AssetRepository;
+use charms_core::AssetType;
+
+use crate::db::stores::AssetStore;
 use crate::entity::assets::Model as Asset;
+use crate::models::AssetSort;
 
 /// Service for asset-related business logic
 pub struct AssetService {
-    asset_repository: Arc<AssetRepository>,
+    asset_repository: Arc<dyn AssetStore>,
 }
 
 impl AssetService {
     /// Create a new asset service instance
-    pub fn new(asset_repository: Arc<AssetRepository>) -> Self {
+    pub fn new(asset_repository: Arc<dyn AssetStore>) -> Self {
         Self { asset_repository }
     }
 
-    /// Get assets with pagination and optional filtering
+    /// Get assets with pagination and optional filtering, in `sort` order.
+    /// Hidden assets are left out unless `include_hidden`.
     pub async fn get_assets_paginated(
         &self,
         asset_type: Option<&str>,
         network: Option<&str>,
+        sort: AssetSort,
         limit: u64,
         offset: u64,
+        include_hidden: bool,
     ) -> Result<(Vec<Asset>, u64), Box<dyn std::error::Error + Send + Sync>> {
         // Get filtered assets with pagination
         let assets = self
             .asset_repository
-            .find_paginated(asset_type, network, limit, offset)
+            .find_paginated(asset_type, network, sort, limit, offset, include_hidden)
             .await?;
 
         // Get total count for pagination info
         let total = self
             .asset_repository
-            .count_assets(asset_type, network)
+            .count_assets(asset_type, network, include_hidden)
             .await?;
 
         Ok((assets, total))
     }
 
-    /// Get asset counts by type, optionally filtered by network
+    /// Get counts of listed (not hidden) assets by type, optionally filtered
+    /// by network
     pub async fn get_asset_counts(
         &self,
         network: Option<&str>,
@@ -46,26 +53,17 @@ impl AssetService {
         let mut counts = HashMap::new();
 
         // Get total count
-        let total = self.asset_repository.count_assets(None, network).await?;
+        let total = self.asset_repository.count_assets(None, network, false).await?;
         counts.insert("total".to_string(), total);
 
         // Get counts by type
-        let nft_count = self
-            .asset_repository
-            .count_assets(Some("nft"), network)
-            .await?;
-        let token_count = self
-            .asset_repository
-            .count_assets(Some("token"), network)
-            .await?;
-        let dapp_count = self
-            .asset_repository
-            .count_assets(Some("dapp"), network)
-            .await?;
-
-        counts.insert("nft".to_string(), nft_count);
-        counts.insert("token".to_string(), token_count);
-        counts.insert("dapp".to_string(), dapp_count);
+        for asset_type in [AssetType::Nft, AssetType::Token, AssetType::Dapp] {
+            let count = self
+                .asset_repository
+                .count_assets(Some(asset_type.as_str()), network, false)
+                .await?;
+            counts.insert(asset_type.to_string(), count);
+        }
 
         Ok(counts)
     }
@@ -117,6 +115,7 @@ impl AssetService {
 
     /// Get max total_supply from all assets matching a base app_id prefix
     /// Used to get the correct total supply for tokens with multiple outputs (:0, :1, etc.)
+    #[allow(dead_code)] // Reserved for future use
     pub async fn get_max_total_supply_by_app_id_prefix(
         &self,
         base_app_id: &str,
@@ -127,3 +126,70 @@ impl AssetService {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{asset, FakeAssets};
+
+    fn service() -> AssetService {
+        let mut testnet = asset(5, "t/t4/t4", "token");
+        testnet.network = "testnet4".to_string();
+        AssetService::new(Arc::new(FakeAssets::new(vec![
+            asset(1, "n/a/a", "nft"),
+            asset(2, "t/b/b", "token"),
+            asset(3, "n/c/c", "nft"),
+            asset(4, "n/d/d", "nft"),
+            testnet,
+        ])))
+    }
+
+    #[tokio::test]
+    async fn pages_carry_the_filtered_total() {
+        let (page, total) = service()
+            .get_assets_paginated(Some("nft"), Some("mainnet"), AssetSort::Newest, 2, 2, false)
+            .await
+            .unwrap();
+        assert_eq!(page.iter().map(|a| a.id).collect::<Vec<_>>(), [4]);
+        assert_eq!(total, 3);
+
+        let (_, all_networks) = service()
+            .get_assets_paginated(None, None, AssetSort::Newest, 10, 0, false)
+            .await
+            .unwrap();
+        assert_eq!(all_networks, 5);
+    }
+
+    #[tokio::test]
+    async fn counts_split_by_type() {
+        let counts = service().get_asset_counts(Some("mainnet")).await.unwrap();
+        assert_eq!(counts["total"], 4);
+        assert_eq!(counts["nft"], 3);
+        assert_eq!(counts["token"], 1);
+        assert_eq!(counts["dapp"], 0);
+    }
+
+    #[tokio::test]
+    async fn lookups_are_network_scoped_and_errors_propagate() {
+        let svc = service();
+        assert!(svc
+            .get_asset_by_app_id("t/t4/t4", "mainnet")
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            svc.get_asset_by_app_id("t/t4/t4", "testnet4")
+                .await
+                .unwrap()
+                .map(|a| a.id),
+            Some(5)
+        );
+
+        let failing = AssetService::new(Arc::new(FakeAssets::failing()));
+        assert!(failing.get_asset_counts(None).await.is_err());
+        assert!(failing
+            .get_assets_paginated(None, None, AssetSort::Newest, 10, 0, false)
+            .await
+            .is_err());
+    }
+}
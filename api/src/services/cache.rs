@@ -0,0 +1,302 @@
+// Key/value cache behind the tip and scan caches, shared by every replica
+// when `REDIS_URL` is set.
+//
+// Behind a load balancer each replica otherwise warms its own copy: three
+// replicas run three `scantxoutset`s for one address, and confirmation
+// counts differ by which replica answered. Values are serde JSON under
+// `charms-explorer:<network>:<kind>:<id>` keys, each with its own TTL.
+//
+// Redis is an optimisation, never a dependency: when it can't be reached at
+// startup, or a command fails later, values go to (and come from) process
+// memory instead, with one warning per outage.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Prefix of every key, so the explorer can share a Redis instance
+const KEY_PREFIX: &str = "charms-explorer";
+
+/// Entries the memory cache keeps before expired ones are pruned
+const MAX_ENTRIES: usize = 4096;
+
+/// Longest wait for a Redis command (and for the initial connection)
+const REDIS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Cache key of `id` for `kind` on `network`.
+pub fn key(network: &str, kind: &str, id: &str) -> String {
+    format!("{}:{}:{}:{}", KEY_PREFIX, network, kind, id)
+}
+
+#[async_trait]
+pub trait Cache: Send + Sync {
+    /// Stored value of `key`, `None` when absent or expired.
+    async fn get(&self, key: &str) -> Option<String>;
+
+    /// Store `value` under `key` for `ttl`.
+    async fn set(&self, key: &str, value: String, ttl: Duration);
+
+    /// `memory` or `redis`, for /metrics.
+    fn backend(&self) -> &'static str;
+}
+
+/// `get` deserialized. A value that no longer parses counts as a miss.
+pub async fn get_json<T: DeserializeOwned>(cache: &dyn Cache, key: &str) -> Option<T> {
+    serde_json::from_str(&cache.get(key).await?).ok()
+}
+
+/// `set` of `value` serialized.
+pub async fn set_json<T: Serialize>(cache: &dyn Cache, key: &str, value: &T, ttl: Duration) {
+    match serde_json::to_string(value) {
+        Ok(json) => cache.set(key, json, ttl).await,
+        Err(e) => tracing::warn!("cache: could not serialize {}: {}", key, e),
+    }
+}
+
+/// Per-process cache.
+#[derive(Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(_, expires)| Instant::now() < *expires)
+            .map(|(value, _)| value.clone())
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_ENTRIES {
+            let now = Instant::now();
+            entries.retain(|_, (_, expires)| now < *expires);
+        }
+        entries.insert(key.to_string(), (value, Instant::now() + ttl));
+    }
+
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+}
+
+/// Redis-backed cache, falling back to memory while Redis is unreachable.
+pub struct RedisCache {
+    conn: ConnectionManager,
+    fallback: MemoryCache,
+    degraded: AtomicBool,
+}
+
+impl RedisCache {
+    pub async fn connect(url: &str) -> Result<Self, String> {
+        let client = redis::Client::open(url).map_err(|e| e.to_string())?;
+        let conn = tokio::time::timeout(REDIS_TIMEOUT, ConnectionManager::new(client))
+            .await
+            .map_err(|_| format!("no answer within {}s", REDIS_TIMEOUT.as_secs()))?
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            conn,
+            fallback: MemoryCache::new(),
+            degraded: AtomicBool::new(false),
+        })
+    }
+
+    async fn query<T: redis::FromRedisValue>(&self, cmd: &redis::Cmd) -> Option<T> {
+        let mut conn = self.conn.clone();
+        let result = tokio::time::timeout(REDIS_TIMEOUT, cmd.query_async::<T>(&mut conn)).await;
+        match result {
+            Ok(Ok(value)) => {
+                if self.degraded.swap(false, Ordering::Relaxed) {
+                    tracing::info!("cache: Redis reachable again");
+                }
+                Some(value)
+            }
+            Ok(Err(e)) => {
+                self.degrade(&e.to_string());
+                None
+            }
+            Err(_) => {
+                self.degrade("timed out");
+                None
+            }
+        }
+    }
+
+    fn degrade(&self, reason: &str) {
+        if !self.degraded.swap(true, Ordering::Relaxed) {
+            tracing::warn!(
+                "cache: Redis unavailable ({}), using process memory",
+                reason
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        match self
+            .query::<Option<String>>(redis::cmd("GET").arg(key))
+            .await
+        {
+            Some(value) => value,
+            None => self.fallback.get(key).await,
+        }
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) {
+        let millis = ttl.as_millis().max(1) as u64;
+        let stored = self
+            .query::<()>(redis::cmd("SET").arg(key).arg(&value).arg("PX").arg(millis))
+            .await;
+        if stored.is_none() {
+            self.fallback.set(key, value, ttl).await;
+        }
+    }
+
+    fn backend(&self) -> &'static str {
+        "redis"
+    }
+}
+
+/// The cache for `redis_url`: Redis when set and reachable, else memory.
+pub async fn connect(redis_url: Option<&str>) -> Arc<dyn Cache> {
+    let Some(url) = redis_url else {
+        return Arc::new(MemoryCache::new());
+    };
+    match RedisCache::connect(url).await {
+        Ok(cache) => {
+            tracing::info!("cache: shared through Redis");
+            Arc::new(cache)
+        }
+        Err(e) => {
+            tracing::warn!(
+                "cache: Redis unreachable at startup ({}), using process memory",
+                e
+            );
+            Arc::new(MemoryCache::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::{JoinHandle, JoinSet};
+
+    #[tokio::test]
+    async fn memory_entries_expire_after_their_ttl() {
+        let cache = MemoryCache::new();
+        let k = key("mainnet", "tip", "");
+        set_json(&cache, &k, &900_000u64, Duration::from_millis(50)).await;
+        assert_eq!(get_json::<u64>(&cache, &k).await, Some(900_000));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(get_json::<u64>(&cache, &k).await, None);
+    }
+
+    #[tokio::test]
+    async fn keys_are_namespaced_by_network() {
+        let cache = MemoryCache::new();
+        set_json(
+            &cache,
+            &key("mainnet", "tip", ""),
+            &1u64,
+            Duration::from_secs(5),
+        )
+        .await;
+        assert_eq!(
+            get_json::<u64>(&cache, &key("testnet4", "tip", "")).await,
+            None
+        );
+        assert_eq!(
+            key("testnet4", "scan", "tb1q"),
+            "charms-explorer:testnet4:scan:tb1q"
+        );
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_redis_falls_back_to_memory() {
+        // Nothing listens on port 1.
+        let cache = connect(Some("redis://127.0.0.1:1/")).await;
+        assert_eq!(cache.backend(), "memory");
+        cache
+            .set("k", "v".to_string(), Duration::from_secs(5))
+            .await;
+        assert_eq!(cache.get("k").await.as_deref(), Some("v"));
+    }
+
+    /// `TEST_REDIS_URL`, a scratch Redis as `redis://host:port/`.
+    fn redis_url() -> String {
+        std::env::var("TEST_REDIS_URL").expect("TEST_REDIS_URL")
+    }
+
+    /// A local port forwarding to `url`'s Redis. Aborting the handle drops
+    /// every forwarded connection: an outage on demand.
+    async fn proxy(url: &str) -> (JoinHandle<()>, String) {
+        let target = url
+            .trim_start_matches("redis://")
+            .trim_end_matches('/')
+            .to_string();
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let handle = tokio::spawn(async move {
+            let mut links = JoinSet::new();
+            while let Ok((mut client, _)) = listener.accept().await {
+                let target = target.clone();
+                links.spawn(async move {
+                    if let Ok(mut server) = TcpStream::connect(target).await {
+                        let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                    }
+                });
+            }
+        });
+        (handle, format!("redis://{addr}/"))
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_REDIS_URL pointing at a scratch Redis; run with --ignored"]
+    async fn redis_entries_are_shared_and_expire() {
+        let url = redis_url();
+        let (a, b) = (connect(Some(&url)).await, connect(Some(&url)).await);
+        assert_eq!(a.backend(), "redis");
+
+        let k = key("test", "tip", &std::process::id().to_string());
+        set_json(a.as_ref(), &k, &900_000u64, Duration::from_millis(300)).await;
+        assert_eq!(get_json::<u64>(b.as_ref(), &k).await, Some(900_000));
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(get_json::<u64>(b.as_ref(), &k).await, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires TEST_REDIS_URL pointing at a scratch Redis; run with --ignored"]
+    async fn a_redis_outage_falls_back_to_memory() {
+        let (link, url) = proxy(&redis_url()).await;
+        let cache = connect(Some(&url)).await;
+        assert_eq!(cache.backend(), "redis");
+
+        link.abort();
+        let _ = link.await;
+        cache
+            .set("k", "v".to_string(), Duration::from_secs(5))
+            .await;
+        assert_eq!(cache.get("k").await.as_deref(), Some("v"));
+    }
+}
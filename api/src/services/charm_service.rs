@@ -1,14 +1,36 @@
 // Charm-related business logic implementation
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use charms_core::{parse_amount, AppKind, AssetType, CharmOperation};
+
+use crate::db::repositories::charm_repository::CharmFilter;
+use crate::db::repositories::moderation_repository::is_hidden;
 use crate::db::DbError;
-use crate::error::ExplorerResult;
+use crate::entity::{charms, dex_orders};
+use crate::error::{ExplorerError, ExplorerResult};
 use crate::handlers::AppState;
 use crate::models::{
-    CharmCountResponse, CharmData, CharmsCountByTypeResponse, CharmsResponse, LikeCharmRequest,
-    LikeResponse, PaginatedResponse, PaginationMeta, PaginationParams,
+    AddressAssetsResponse, AssetHoldingData, BalanceAtResponse, CharmCountResponse, CharmData,
+    CharmDex, CharmsCountByTypeResponse, CharmsResponse, GetCharmsQuery, LikeCharmRequest,
+    LikeResponse, PaginatedResponse, PaginationMeta, PaginationParams, SpellCharm, SpellContext,
+    SpellSummary,
 };
+use crate::services::decimals_service::DecimalsResolver;
+use crate::services::dex_orders_service::price_per_token;
+
+/// Operation tags the indexer puts on Charms Cast txs.
+const DEX_OPERATION_TAGS: [&str; 6] = [
+    "create-ask",
+    "create-bid",
+    "fulfill-ask",
+    "fulfill-bid",
+    "cancel",
+    "partial-fill",
+];
+
+/// Most charms a `SpellContext` lists per side.
+const SPELL_CONTEXT_LIMIT: usize = 100;
 
 pub async fn get_charms_count_by_type(
     state: &AppState,
@@ -19,11 +41,12 @@ pub async fn get_charms_count_by_type(
     use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
 
     let network_str = network.unwrap_or("mainnet");
-    let conn = state.repositories.charm.get_connection();
+    let conn = state.repositories.connection();
 
-    // Count total charms
+    // Count total charms (placeholders excluded, served by the partial index)
     let total = Charms::find()
         .filter(CharmColumn::Network.eq(network_str))
+        .filter(CharmColumn::IsPlaceholder.eq(false))
         .count(conn)
         .await
         .unwrap_or(0);
@@ -31,30 +54,45 @@ pub async fn get_charms_count_by_type(
     // Count assets by type (unique assets, not charm instances)
     let nft_count = Assets::find()
         .filter(AssetColumn::Network.eq(network_str))
-        .filter(AssetColumn::AssetType.eq("nft"))
+        .filter(AssetColumn::AssetType.eq(AssetType::Nft.as_str()))
         .count(conn)
         .await
         .unwrap_or(0);
 
     let token_count = Assets::find()
         .filter(AssetColumn::Network.eq(network_str))
-        .filter(AssetColumn::AssetType.eq("token"))
+        .filter(AssetColumn::AssetType.eq(AssetType::Token.as_str()))
         .count(conn)
         .await
         .unwrap_or(0);
 
     let dapp_count = Assets::find()
         .filter(AssetColumn::Network.eq(network_str))
-        .filter(AssetColumn::AssetType.eq("dapp"))
+        .filter(AssetColumn::AssetType.eq(AssetType::Dapp.as_str()))
         .count(conn)
         .await
         .unwrap_or(0);
 
+    // Charms per operation (idx_charms_network_operation); rows indexed
+    // before the column existed are in `total` only
+    let mut by_operation = BTreeMap::new();
+    for op in CharmOperation::ALL {
+        let count = Charms::find()
+            .filter(CharmColumn::Network.eq(network_str))
+            .filter(CharmColumn::IsPlaceholder.eq(false))
+            .filter(CharmColumn::Operation.eq(op.as_str()))
+            .count(conn)
+            .await
+            .unwrap_or(0);
+        by_operation.insert(op.as_str(), count);
+    }
+
     Ok(CharmsCountByTypeResponse {
         total,
         nft: nft_count,
         token: token_count,
         dapp: dapp_count,
+        by_operation,
     })
 }
 
@@ -84,18 +122,78 @@ pub async fn get_charm_numbers_by_type(
     })
 }
 
+/// The listing filter a charms query asks for. Amount bounds are raw units,
+/// or with `decimals=true` decimal units of `app_id` (which is then
+/// required) on `network`, converted without floats.
+pub async fn charm_filter(
+    state: &AppState,
+    params: &GetCharmsQuery,
+    network: &str,
+) -> ExplorerResult<CharmFilter> {
+    let operation = params
+        .operation
+        .as_deref()
+        .map(str::parse::<CharmOperation>)
+        .transpose()
+        .map_err(|e| ExplorerError::InvalidRequest(e.to_string()))?;
+
+    let decimals = match (&params.app_id, params.decimals) {
+        (_, false) => 0,
+        (Some(app_id), true) => {
+            DecimalsResolver::new(state.repositories.asset_repository.as_ref())
+                .resolve_decimals(network, app_id)
+                .await
+        }
+        (None, true) => {
+            return Err(ExplorerError::InvalidRequest(
+                "decimals=true needs an app_id".to_string(),
+            ))
+        }
+    };
+    let bound = |name: &str, text: &Option<String>| {
+        text.as_deref()
+            .map(|t| {
+                parse_amount(t, decimals).ok_or_else(|| {
+                    ExplorerError::InvalidRequest(format!(
+                        "{} must be an amount with at most {} decimal places, got {:?}",
+                        name, decimals, t
+                    ))
+                })
+            })
+            .transpose()
+    };
+    let min_amount = bound("min_amount", &params.min_amount)?;
+    let max_amount = bound("max_amount", &params.max_amount)?;
+    if let (Some(min), Some(max)) = (min_amount, max_amount) {
+        if min > max {
+            return Err(ExplorerError::InvalidRequest(
+                "min_amount is greater than max_amount".to_string(),
+            ));
+        }
+    }
+
+    Ok(CharmFilter {
+        operation,
+        app_id: params.app_id.clone(),
+        min_amount,
+        max_amount,
+    })
+}
+
 pub async fn get_all_charms_paginated_by_network(
     state: &AppState,
     pagination: &PaginationParams,
     _user_id: i32,
     network: Option<&str>,
+    filter: &CharmFilter,
+    include_hidden: bool,
 ) -> ExplorerResult<PaginatedResponse<CharmsResponse>> {
     // Handle database query with graceful error handling
     let network_str = network.unwrap_or("mainnet");
     let (charms, total) = match state
         .repositories
         .charm
-        .get_all_paginated_by_network(pagination, network_str)
+        .get_all_paginated_by_network(pagination, network_str, filter, include_hidden)
         .await
     {
         Ok(result) => result,
@@ -109,12 +207,7 @@ pub async fn get_all_charms_paginated_by_network(
             // Return empty response on database error
             return Ok(PaginatedResponse {
                 data: CharmsResponse { charms: vec![] },
-                pagination: PaginationMeta {
-                    total: 0,
-                    page: pagination.page,
-                    limit: pagination.limit,
-                    total_pages: 0,
-                },
+                pagination: PaginationMeta::new(0, pagination.page, pagination.limit, 0),
             });
         }
     };
@@ -126,10 +219,6 @@ pub async fn get_all_charms_paginated_by_network(
     let mut charm_data = Vec::new();
 
     for charm in charms {
-        if is_empty_spell_charm(&charm.data) {
-            continue;
-        }
-
         // Get metadata for this charm
         let (name, image, ticker, description) = metadata_map
             .get(&charm.app_id)
@@ -143,6 +232,7 @@ pub async fn get_all_charms_paginated_by_network(
             block_height: charm.block_height,
             data: charm.data,
             date_created: charm.date_created.to_string(),
+            block_time: charm.block_time.map(|t| t.to_string()),
             asset_type: charm.asset_type,
             network: charm.network,
             amount: charm.amount,
@@ -153,8 +243,11 @@ pub async fn get_all_charms_paginated_by_network(
             ticker,
             description,
             verified: charm.verified,
+            is_placeholder: charm.is_placeholder,
             tags: charm.tags,
             spell: None,
+            dex: None,
+            spell_context: None,
         };
 
         charm_data.push(charm_data_item);
@@ -168,12 +261,7 @@ pub async fn get_all_charms_paginated_by_network(
 
     Ok(PaginatedResponse {
         data: CharmsResponse { charms: charm_data },
-        pagination: PaginationMeta {
-            total,
-            page: pagination.page,
-            limit: pagination.limit,
-            total_pages,
-        },
+        pagination: PaginationMeta::new(total, pagination.page, pagination.limit, total_pages),
     })
 }
 
@@ -181,9 +269,16 @@ pub async fn get_all_charms_paginated(
     state: &AppState,
     pagination: &PaginationParams,
     user_id: i32,
+    filter: &CharmFilter,
+    include_hidden: bool,
 ) -> ExplorerResult<PaginatedResponse<CharmsResponse>> {
     // Handle database query with graceful error handling
-    let (charms, total) = match state.repositories.charm.get_all_paginated(pagination).await {
+    let (charms, total) = match state
+        .repositories
+        .charm
+        .get_all_paginated(pagination, filter, include_hidden)
+        .await
+    {
         Ok(result) => result,
         Err(err) => {
             // Log database error for monitoring
@@ -192,12 +287,7 @@ pub async fn get_all_charms_paginated(
             // Return empty response on database error
             return Ok(PaginatedResponse {
                 data: CharmsResponse { charms: vec![] },
-                pagination: PaginationMeta {
-                    total: 0,
-                    page: pagination.page,
-                    limit: pagination.limit,
-                    total_pages: 0,
-                },
+                pagination: PaginationMeta::new(0, pagination.page, pagination.limit, 0),
             });
         }
     };
@@ -223,10 +313,6 @@ pub async fn get_all_charms_paginated(
     let mut charm_data = Vec::new();
 
     for charm in charms {
-        if is_empty_spell_charm(&charm.data) {
-            continue;
-        }
-
         // Get likes from batch results
         let likes_count = *likes_counts.get(&charm.app_id).unwrap_or(&0);
         let user_liked = user_likes.contains(&charm.app_id);
@@ -244,6 +330,7 @@ pub async fn get_all_charms_paginated(
             block_height: charm.block_height,
             data: charm.data.clone(),
             date_created: charm.date_created.to_string(),
+            block_time: charm.block_time.map(|t| t.to_string()),
             asset_type: charm.asset_type,
             network: charm.network,
             amount: charm.amount,
@@ -254,8 +341,11 @@ pub async fn get_all_charms_paginated(
             ticker,
             description,
             verified: charm.verified,
+            is_placeholder: charm.is_placeholder,
             tags: charm.tags,
             spell: None,
+            dex: None,
+            spell_context: None,
         });
     }
 
@@ -267,12 +357,7 @@ pub async fn get_all_charms_paginated(
 
     Ok(PaginatedResponse {
         data: CharmsResponse { charms: charm_data },
-        pagination: PaginationMeta {
-            total,
-            page: pagination.page,
-            limit: pagination.limit,
-            total_pages,
-        },
+        pagination: PaginationMeta::new(total, pagination.page, pagination.limit, total_pages),
     })
 }
 
@@ -297,10 +382,6 @@ pub async fn get_all_charms(state: &AppState, user_id: i32) -> ExplorerResult<Ch
     let mut charm_data = Vec::new();
 
     for charm in charms {
-        if is_empty_spell_charm(&charm.data) {
-            continue;
-        }
-
         // Get likes count for this charm
         let likes_count = (state
             .repositories
@@ -330,6 +411,7 @@ pub async fn get_all_charms(state: &AppState, user_id: i32) -> ExplorerResult<Ch
             block_height: charm.block_height,
             data: charm.data.clone(),
             date_created: charm.date_created.to_string(),
+            block_time: charm.block_time.map(|t| t.to_string()),
             asset_type: charm.asset_type,
             network: charm.network,
             amount: charm.amount,
@@ -340,8 +422,11 @@ pub async fn get_all_charms(state: &AppState, user_id: i32) -> ExplorerResult<Ch
             ticker,
             description,
             verified: charm.verified,
+            is_placeholder: charm.is_placeholder,
             tags: charm.tags,
             spell: None,
+            dex: None,
+            spell_context: None,
         });
     }
 
@@ -351,14 +436,16 @@ pub async fn get_all_charms(state: &AppState, user_id: i32) -> ExplorerResult<Ch
 pub async fn get_charms_by_type_paginated(
     state: &AppState,
     asset_type: &str,
+    network: &str,
     pagination: &PaginationParams,
     user_id: i32,
+    include_hidden: bool,
 ) -> ExplorerResult<PaginatedResponse<CharmsResponse>> {
     // Wrap the database call in a try-catch to provide more detailed error information
     let (charms, total) = match state
         .repositories
         .charm
-        .find_by_asset_type_paginated(asset_type, pagination)
+        .find_by_asset_type_paginated(asset_type, network, pagination, include_hidden)
         .await
     {
         Ok(result) => result,
@@ -369,12 +456,7 @@ pub async fn get_charms_by_type_paginated(
             // Return a fallback empty response instead of propagating the error
             return Ok(PaginatedResponse {
                 data: CharmsResponse { charms: vec![] },
-                pagination: PaginationMeta {
-                    total: 0,
-                    page: pagination.page,
-                    limit: pagination.limit,
-                    total_pages: 0,
-                },
+                pagination: PaginationMeta::new(0, pagination.page, pagination.limit, 0),
             });
         }
     };
@@ -400,10 +482,6 @@ pub async fn get_charms_by_type_paginated(
     let mut charm_data = Vec::new();
 
     for charm in charms {
-        if is_empty_spell_charm(&charm.data) {
-            continue;
-        }
-
         // Get likes from batch results
         let likes_count = *likes_counts.get(&charm.app_id).unwrap_or(&0);
         let user_liked = user_likes.contains(&charm.app_id);
@@ -421,6 +499,7 @@ pub async fn get_charms_by_type_paginated(
             block_height: charm.block_height,
             data: charm.data.clone(),
             date_created: charm.date_created.to_string(),
+            block_time: charm.block_time.map(|t| t.to_string()),
             asset_type: charm.asset_type,
             network: charm.network,
             amount: charm.amount,
@@ -431,8 +510,11 @@ pub async fn get_charms_by_type_paginated(
             ticker,
             description,
             verified: charm.verified,
+            is_placeholder: charm.is_placeholder,
             tags: charm.tags,
             spell: None,
+            dex: None,
+            spell_context: None,
         });
     }
 
@@ -444,12 +526,7 @@ pub async fn get_charms_by_type_paginated(
 
     Ok(PaginatedResponse {
         data: CharmsResponse { charms: charm_data },
-        pagination: PaginationMeta {
-            total,
-            page: pagination.page,
-            limit: pagination.limit,
-            total_pages,
-        },
+        pagination: PaginationMeta::new(total, pagination.page, pagination.limit, total_pages),
     })
 }
 
@@ -483,10 +560,6 @@ pub async fn get_charms_by_type(
     let mut charm_data = Vec::new();
 
     for charm in charms {
-        if is_empty_spell_charm(&charm.data) {
-            continue;
-        }
-
         // Get likes count for this charm
         let likes_count = (state
             .repositories
@@ -516,6 +589,7 @@ pub async fn get_charms_by_type(
             block_height: charm.block_height,
             data: charm.data.clone(),
             date_created: charm.date_created.to_string(),
+            block_time: charm.block_time.map(|t| t.to_string()),
             asset_type: charm.asset_type,
             network: charm.network,
             amount: charm.amount,
@@ -526,35 +600,53 @@ pub async fn get_charms_by_type(
             ticker,
             description,
             verified: charm.verified,
+            is_placeholder: charm.is_placeholder,
             tags: charm.tags,
             spell: None,
+            dex: None,
+            spell_context: None,
         });
     }
 
     Ok(CharmsResponse { charms: charm_data })
 }
 
-/// Checks if a charm is an empty spell charm with the structure {"data": {}, "type": "spell", "detected": true}
-fn is_empty_spell_charm(data: &serde_json::Value) -> bool {
-    if let Some(data_obj) = data.get("data") {
-        if data_obj.is_object() && data_obj.as_object().unwrap().is_empty() {
-            if let Some(type_value) = data.get("type") {
-                if type_value.is_string() && type_value.as_str().unwrap() == "spell" {
-                    if let Some(detected) = data.get("detected") {
-                        return detected.is_boolean() && detected.as_bool().unwrap();
-                    }
-                }
-            }
+/// Not-found error for a lookup scoped to `network`. When other enabled
+/// networks hold the charm, the 404 lists them in `found_on_networks`, so a
+/// txid pasted without its network is not a dead end.
+fn not_found_on_network(
+    state: &AppState,
+    message: String,
+    network: &str,
+    elsewhere: Result<Vec<String>, DbError>,
+) -> ExplorerError {
+    let enabled = state.config.enabled_networks();
+    let found_on_networks: Vec<String> = elsewhere
+        .unwrap_or_else(|err| {
+            tracing::warn!("Error looking up other networks: {:?}", err);
+            Vec::new()
+        })
+        .into_iter()
+        .filter(|n| n != network && enabled.contains(&n.as_str()))
+        .collect();
+    if found_on_networks.is_empty() {
+        DbError::QueryError(message).into()
+    } else {
+        ExplorerError::NotFoundOnNetwork {
+            message,
+            found_on_networks,
         }
     }
-    false
 }
 
+/// A charm by txid; a hidden one is reported not found unless
+/// `include_hidden`.
 pub async fn get_charm_by_txid(
     state: &AppState,
     txid: &str,
     network: &str,
     user_id: i32,
+    include_hidden: bool,
 ) -> ExplorerResult<CharmData> {
     // Wrap the database call in a try-catch to provide more detailed error information
     let charm_result = match state.repositories.charm.get_by_txid(txid, network).await {
@@ -574,9 +666,19 @@ pub async fn get_charm_by_txid(
 
     // Check if the charm was found
     let charm = match charm_result {
-        Some(charm) => charm,
-        None => {
-            return Err(DbError::QueryError(format!("Charm with txid {} not found", txid)).into());
+        Some(charm) if include_hidden || !is_hidden(&charm.moderation_status) => charm,
+        _ => {
+            let elsewhere = state
+                .repositories
+                .charm
+                .networks_for_txid(txid, include_hidden)
+                .await;
+            return Err(not_found_on_network(
+                state,
+                format!("Charm with txid {} not found", txid),
+                network,
+                elsewhere,
+            ));
         }
     };
 
@@ -594,6 +696,8 @@ pub async fn get_charm_by_txid(
         }
     };
 
+    let spell_context = spell_context(state, txid, network, spell.as_ref(), include_hidden).await;
+
     // Get likes count for this charm
     let likes_count = (state
         .repositories
@@ -610,6 +714,23 @@ pub async fn get_charm_by_txid(
         .await)
         .unwrap_or(false);
 
+    // [RJJ-DEX] Order details, looked up only for Charms Cast txs
+    let dex = match dex_operation(charm.tags.as_deref()) {
+        Some(_) => {
+            let order = state
+                .repositories
+                .dex_orders
+                .find_by_txid(txid, network)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::warn!("Error getting DEX order for {}: {:?}", txid, err);
+                    None
+                });
+            charm_dex(charm.tags.as_deref(), order.as_ref())
+        }
+        None => None,
+    };
+
     // Get metadata for this charm
     let metadata_map = get_metadata_map(state, std::slice::from_ref(&charm)).await;
     let (name, image, ticker, description) = metadata_map
@@ -622,8 +743,9 @@ pub async fn get_charm_by_txid(
         vout: charm.vout,
         charmid: charm.app_id,
         block_height: charm.block_height,
-        data: charm.data,
+        data: hydrate_data(charm.data, spell.as_ref()),
         date_created: charm.date_created.to_string(),
+        block_time: charm.block_time.map(|t| t.to_string()),
         asset_type: charm.asset_type,
         network: charm.network,
         amount: charm.amount,
@@ -634,20 +756,29 @@ pub async fn get_charm_by_txid(
         ticker,
         description,
         verified: charm.verified,
+        is_placeholder: charm.is_placeholder,
         tags: charm.tags,
         spell, // [RJJ-SPELL] Include original spell from transactions
+        dex,
+        spell_context: Some(spell_context),
     })
 }
 
-/// Gets a charm by its charm ID
+/// Gets a charm by its charm ID, skipping hidden rows unless `include_hidden`
 pub async fn get_charm_by_charmid(
     state: &AppState,
     charmid: &str,
     network: &str,
     user_id: i32,
+    include_hidden: bool,
 ) -> ExplorerResult<CharmData> {
     // Wrap the database call in a try-catch to provide more detailed error information
-    let charms = match state.repositories.charm.find_by_charmid(charmid, network).await {
+    let charms = match state
+        .repositories
+        .charm
+        .find_by_charmid(charmid, network, include_hidden)
+        .await
+    {
         Ok(result) => result,
         Err(err) => {
             // Log the error for debugging
@@ -663,9 +794,17 @@ pub async fn get_charm_by_charmid(
     };
 
     if charms.is_empty() {
-        return Err(
-            DbError::QueryError(format!("Charm with charmid {} not found", charmid)).into(),
-        );
+        let elsewhere = state
+            .repositories
+            .charm
+            .networks_for_charmid(charmid, include_hidden)
+            .await;
+        return Err(not_found_on_network(
+            state,
+            format!("Charm with charmid {} not found", charmid),
+            network,
+            elsewhere,
+        ));
     }
 
     // Get likes count for this charm
@@ -687,16 +826,31 @@ pub async fn get_charm_by_charmid(
         .cloned()
         .unwrap_or((None, None, None, None));
 
-    // First try to find a non-empty spell charm
+    // First try to find a charm that is not an empty-spell placeholder
     for charm in &charms {
-        if !is_empty_spell_charm(&charm.data) {
+        if !charm.is_placeholder {
+            let data = if charms_core::is_data_truncated(&charm.data) {
+                let spell = state
+                    .repositories
+                    .transactions
+                    .get_spell_by_txid(&charm.txid)
+                    .await
+                    .unwrap_or_else(|err| {
+                        tracing::warn!("Error getting spell for {}: {:?}", charm.txid, err);
+                        None
+                    });
+                hydrate_data(charm.data.clone(), spell.as_ref())
+            } else {
+                charm.data.clone()
+            };
             return Ok(CharmData {
                 txid: charm.txid.clone(),
                 vout: charm.vout,
                 charmid: charm.app_id.clone(),
                 block_height: charm.block_height,
-                data: charm.data.clone(),
+                data,
                 date_created: charm.date_created.to_string(),
+                block_time: charm.block_time.map(|t| t.to_string()),
                 asset_type: charm.asset_type.clone(),
                 network: charm.network.clone(),
                 amount: charm.amount,
@@ -707,13 +861,16 @@ pub async fn get_charm_by_charmid(
                 ticker: ticker.clone(),
                 description: description.clone(),
                 verified: charm.verified,
+                is_placeholder: charm.is_placeholder,
                 tags: charm.tags.clone(),
                 spell: None,
+                dex: None,
+                spell_context: None,
             });
         }
     }
 
-    // If all are empty spell charms, return the first one
+    // If all are placeholders, return the first one
     let first_charm = &charms[0];
     Ok(CharmData {
         txid: first_charm.txid.clone(),
@@ -722,6 +879,7 @@ pub async fn get_charm_by_charmid(
         block_height: first_charm.block_height,
         data: first_charm.data.clone(),
         date_created: first_charm.date_created.to_string(),
+        block_time: first_charm.block_time.map(|t| t.to_string()),
         asset_type: first_charm.asset_type.clone(),
         network: first_charm.network.clone(),
         amount: first_charm.amount,
@@ -732,12 +890,15 @@ pub async fn get_charm_by_charmid(
         ticker,
         description,
         verified: first_charm.verified,
+        is_placeholder: first_charm.is_placeholder,
         tags: first_charm.tags.clone(),
         spell: None,
+        dex: None,
+        spell_context: None,
     })
 }
 
-/// Adds a like to a charm
+/// Adds a like to a charm. Liking twice succeeds with `changed: false`.
 pub async fn add_like(
     state: &AppState,
     request: &LikeCharmRequest,
@@ -749,10 +910,16 @@ pub async fn add_like(
         .add_like(&request.charm_id, request.user_id)
         .await
     {
-        Ok(likes_count) => Ok(LikeResponse {
+        Ok(change) => Ok(LikeResponse {
             success: true,
-            message: "Like added successfully".to_string(),
-            likes_count,
+            message: if change.changed {
+                "Like added successfully"
+            } else {
+                "Charm already liked"
+            }
+            .to_string(),
+            changed: change.changed,
+            likes_count: change.likes_count,
         }),
         Err(err) => {
             tracing::warn!("Database error in add_like: {:?}", err);
@@ -762,7 +929,8 @@ pub async fn add_like(
     }
 }
 
-/// Removes a like from a charm
+/// Removes a like from a charm. Removing a missing like succeeds with
+/// `changed: false`.
 pub async fn remove_like(
     state: &AppState,
     request: &LikeCharmRequest,
@@ -774,10 +942,16 @@ pub async fn remove_like(
         .remove_like(&request.charm_id, request.user_id)
         .await
     {
-        Ok(likes_count) => Ok(LikeResponse {
+        Ok(change) => Ok(LikeResponse {
             success: true,
-            message: "Like removed successfully".to_string(),
-            likes_count,
+            message: if change.changed {
+                "Like removed successfully"
+            } else {
+                "Charm not liked"
+            }
+            .to_string(),
+            changed: change.changed,
+            likes_count: change.likes_count,
         }),
         Err(err) => {
             tracing::warn!("Database error in remove_like: {:?}", err);
@@ -787,6 +961,59 @@ pub async fn remove_like(
     }
 }
 
+/// `address`'s balance of `app_id` at the end of block `height`, given the
+/// network's highest processed block `tip`.
+///
+/// Outputs spent before the indexer recorded spend heights may have been
+/// spent before or after `height`; while any such output is in range the
+/// answer is a `lower_bound`/`upper_bound` pair with `exact: false`.
+/// Heights more than `BALANCE_AT_MAX_LOOKBACK_BLOCKS` below the tip are
+/// refused: a deployment pruning spent charms without archiving them
+/// (indexer `ARCHIVE=false`) has lost the rows those answers need, so set
+/// the limit to its prune depth there.
+pub async fn get_balance_at(
+    state: &AppState,
+    address: &str,
+    app_id: &str,
+    network: &str,
+    height: i32,
+    tip: Option<i32>,
+) -> ExplorerResult<BalanceAtResponse> {
+    let tip = tip.ok_or_else(|| {
+        ExplorerError::InvalidRequest(format!("no processed blocks on {}", network))
+    })?;
+    if height < 0 || height > tip {
+        return Err(ExplorerError::InvalidRequest(format!(
+            "height {} is outside the indexed range 0..={}",
+            height, tip
+        )));
+    }
+    let max_lookback = state.config.balance_at_max_lookback_blocks;
+    if max_lookback > 0 && (tip - height) as u64 > max_lookback {
+        return Err(ExplorerError::InvalidRequest(format!(
+            "height {} is more than {} blocks below the indexed tip {}",
+            height, max_lookback, tip
+        )));
+    }
+
+    let balance = state
+        .repositories
+        .charm
+        .balance_at(address, app_id, network, height)
+        .await?;
+    let exact = balance.unknown == 0;
+    Ok(BalanceAtResponse {
+        address: address.to_string(),
+        app_id: app_id.to_string(),
+        network: network.to_string(),
+        height,
+        exact,
+        balance: exact.then_some(balance.held),
+        lower_bound: (!exact).then_some(balance.held),
+        upper_bound: (!exact).then(|| balance.held.saturating_add(balance.unknown)),
+    })
+}
+
 /// [RJJ-ADDRESS-SEARCH] Get charms by address (UNSPENT only)
 /// Returns charms with enriched metadata from related assets
 pub async fn get_charms_by_address(
@@ -794,10 +1021,20 @@ pub async fn get_charms_by_address(
     address: &str,
     network: &str,
     user_id: i32,
+    filter: &CharmFilter,
 ) -> ExplorerResult<CharmsResponse> {
-    // Get unspent charms for this address (network-scoped)
-    let charms = match state.repositories.charm.find_by_address(address, network).await {
-        Ok(result) => result,
+    // Get unspent charms for this address (network-scoped); one address
+    // holds few enough to filter here
+    let charms = match state
+        .repositories
+        .charm
+        .find_by_address(address, network)
+        .await
+    {
+        Ok(result) => result
+            .into_iter()
+            .filter(|c| filter.matches(c))
+            .collect::<Vec<_>>(),
         Err(err) => {
             tracing::warn!("Database error in get_charms_by_address: {:?}", err);
             return Ok(CharmsResponse { charms: vec![] });
@@ -843,6 +1080,7 @@ pub async fn get_charms_by_address(
             block_height: charm.block_height,
             data: charm.data,
             date_created: charm.date_created.to_string(),
+            block_time: charm.block_time.map(|t| t.to_string()),
             asset_type: charm.asset_type,
             network: charm.network,
             amount: charm.amount,
@@ -853,25 +1091,174 @@ pub async fn get_charms_by_address(
             ticker,
             description,
             verified: charm.verified,
+            is_placeholder: charm.is_placeholder,
             tags: charm.tags,
             spell: None,
+            dex: None,
+            spell_context: None,
         });
     }
 
     Ok(CharmsResponse { charms: charm_data })
 }
 
-/// Extract hash from app_id (removes t/ or n/ prefix) [RJJ-ADDRESS-SEARCH]
-#[allow(dead_code)] // Reserved for future address search enhancements
-fn extract_hash_from_app_id(app_id: &str) -> String {
-    let parts: Vec<&str> = app_id.split('/').collect();
-    if parts.len() >= 2 {
-        parts[1..].join("/")
-    } else {
-        app_id.to_string()
+/// `address`'s unspent charms summed per app_id (`?group=asset`). The
+/// database does the folding; amounts are formatted with each asset's
+/// decimals.
+pub async fn get_assets_by_address(
+    state: &AppState,
+    address: &str,
+    network: &str,
+    filter: &CharmFilter,
+) -> ExplorerResult<AddressAssetsResponse> {
+    let holdings = match state
+        .repositories
+        .charm
+        .holdings_by_address(address, network, filter)
+        .await
+    {
+        Ok(holdings) => holdings,
+        Err(err) => {
+            tracing::warn!("Database error in get_assets_by_address: {:?}", err);
+            return Ok(AddressAssetsResponse { assets: vec![] });
+        }
+    };
+
+    let mut decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref());
+    decimals
+        .resolve_all(network, holdings.iter().map(|h| h.app_id.as_str()))
+        .await;
+
+    let assets = holdings
+        .into_iter()
+        .map(|h| AssetHoldingData {
+            amount_formatted: decimals.format(network, &h.app_id, h.amount),
+            decimals: decimals.get(network, &h.app_id),
+            amount: h.amount,
+            utxo_count: h.utxo_count,
+            first_block: h.first_block,
+            last_block: h.last_block,
+            name: h.name,
+            symbol: h.symbol,
+            image: h.image_url,
+            app_id: h.app_id,
+        })
+        .collect();
+    Ok(AddressAssetsResponse { assets })
+}
+
+/// Charm data for a detail view: the full spell JSON kept on the
+/// transaction row when the charm's copy was trimmed by the indexer.
+fn hydrate_data(data: serde_json::Value, spell: Option<&serde_json::Value>) -> serde_json::Value {
+    match spell {
+        Some(full) if charms_core::is_data_truncated(&data) => full.clone(),
+        _ => data,
     }
 }
 
+/// The spell around tx `txid`: its charm outputs and the charms it spent.
+/// A failed lookup leaves its list empty rather than failing the detail.
+async fn spell_context(
+    state: &AppState,
+    txid: &str,
+    network: &str,
+    spell: Option<&serde_json::Value>,
+    include_hidden: bool,
+) -> SpellContext {
+    let store = &state.repositories.charm;
+    let mut outputs = store
+        .get_by_txids(&[txid.to_string()], network)
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!("Error getting outputs of spell {}: {:?}", txid, err);
+            Vec::new()
+        });
+    outputs.sort_by(|a, b| (a.vout, &a.app_id).cmp(&(b.vout, &b.app_id)));
+    let mut inputs = store
+        .find_by_spending_txid(txid, network)
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!("Error getting inputs of spell {}: {:?}", txid, err);
+            Vec::new()
+        });
+    inputs.sort_by(|a, b| (&a.txid, a.vout, &a.app_id).cmp(&(&b.txid, b.vout, &b.app_id)));
+
+    let (outputs, cut_outputs) = spell_charms(outputs, include_hidden);
+    let (inputs, cut_inputs) = spell_charms(inputs, include_hidden);
+    SpellContext {
+        spell: spell.map(summarize_spell),
+        outputs,
+        inputs,
+        truncated: cut_outputs || cut_inputs,
+    }
+}
+
+/// Listed `rows` as spell charms, at most `SPELL_CONTEXT_LIMIT`, and whether
+/// some were cut. Placeholders never show; hidden rows only when revealed.
+fn spell_charms(rows: Vec<charms::Model>, include_hidden: bool) -> (Vec<SpellCharm>, bool) {
+    let mut listed = rows
+        .into_iter()
+        .filter(|c| !c.is_placeholder && (include_hidden || !is_hidden(&c.moderation_status)))
+        .map(|c| SpellCharm {
+            txid: c.txid,
+            vout: c.vout,
+            app_id: c.app_id,
+            asset_type: c.asset_type,
+            amount: c.amount,
+            address: c.address,
+            spent: c.spent,
+        });
+    let kept: Vec<SpellCharm> = listed.by_ref().take(SPELL_CONTEXT_LIMIT).collect();
+    let cut = listed.next().is_some();
+    (kept, cut)
+}
+
+/// Counts of a stored spell JSON (`native_data` is the parsed spell).
+fn summarize_spell(spell: &serde_json::Value) -> SpellSummary {
+    let native = &spell["native_data"];
+    let count = |v: &serde_json::Value| match v {
+        serde_json::Value::Array(items) => items.len(),
+        serde_json::Value::Object(entries) => entries.len(),
+        _ => 0,
+    };
+    SpellSummary {
+        version: native["version"].as_u64(),
+        apps: count(&native["app_public_inputs"]),
+        ins: count(&native["tx"]["ins"]),
+        outs: count(&native["tx"]["outs"]),
+        tx_outputs: spell["charm_output_map"]["tx_outputs"].as_u64(),
+    }
+}
+
+/// Charms Cast operation named by a charm's tags, e.g. "create-bid".
+fn dex_operation(tags: Option<&str>) -> Option<&str> {
+    tags?.split(',').find(|tag| DEX_OPERATION_TAGS.contains(tag))
+}
+
+/// DEX object for a charm whose tags name a Charms Cast operation. The order
+/// fields come from the tx's `dex_orders` row when the indexer saved one.
+fn charm_dex(tags: Option<&str>, order: Option<&dex_orders::Model>) -> Option<CharmDex> {
+    let operation = dex_operation(tags)?.to_string();
+    Some(match order {
+        Some(order) => CharmDex {
+            operation,
+            order_id: Some(order.order_id.clone()),
+            side: Some(order.side.clone()),
+            price: Some(price_per_token(order.price_num, order.price_den)),
+            quantity: Some(order.quantity),
+            status: Some(order.status.clone()),
+        },
+        None => CharmDex {
+            operation,
+            order_id: None,
+            side: None,
+            price: None,
+            quantity: None,
+            status: None,
+        },
+    })
+}
+
 // Helper to enrich charms with metadata from assets table. Network is
 // derived from the charm rows themselves and lookups are grouped per
 // network so the same app_id on mainnet and testnet4 returns the right
@@ -931,7 +1318,7 @@ async fn get_metadata_map(
     let mut vk_cache: HashMap<String, Option<crate::entity::assets::Model>> = HashMap::new();
     for app_ids in per_network.values() {
         for app_id in app_ids {
-            if !app_id.starts_with("t/") {
+            if AppKind::of(app_id) != AppKind::Token {
                 continue;
             }
             let entry = map.entry(app_id.clone()).or_insert((None, None, None, None));
@@ -967,3 +1354,483 @@ async fn get_metadata_map(
     }
     map
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_support::{
+        app_state, asset, charm, repositories, FakeAssets, FakeCharms, FakeLikes,
+    };
+
+    fn pagination(page: u64, limit: u64) -> PaginationParams {
+        PaginationParams {
+            page,
+            limit,
+            ..Default::default()
+        }
+    }
+
+    fn txids(response: &PaginatedResponse<CharmsResponse>) -> Vec<&str> {
+        response.data.charms.iter().map(|c| c.txid.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn paginated_listing_counts_pages_and_merges_likes() {
+        let mut repos = repositories();
+        let mut placeholder = charm("p", "t/a/a");
+        placeholder.is_placeholder = true;
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            charm("a", "t/a/a"),
+            placeholder,
+            charm("b", "t/b/b"),
+            charm("c", "t/c/c"),
+            charm("d", "t/d/d"),
+            charm("e", "t/e/e"),
+        ]));
+        repos.likes = Arc::new(FakeLikes::new(&[("t/e/e", 1), ("t/e/e", 2), ("t/c/c", 2)]));
+        let state = app_state(repos);
+        let any = CharmFilter::default();
+
+        let last = get_all_charms_paginated(&state, &pagination(3, 2), 1, &any, false)
+            .await
+            .unwrap();
+        assert_eq!(txids(&last), ["e"]);
+        assert_eq!(last.pagination.total, 5);
+        assert_eq!(last.pagination.total_pages, 3);
+        assert_eq!(last.data.charms[0].likes_count, 2);
+        assert!(last.data.charms[0].user_liked);
+
+        let second = get_all_charms_paginated(&state, &pagination(2, 2), 1, &any, false)
+            .await
+            .unwrap();
+        assert_eq!(txids(&second), ["c", "d"]);
+        assert_eq!(second.data.charms[0].likes_count, 1);
+        assert!(!second.data.charms[0].user_liked);
+
+        let zero_limit = get_all_charms_paginated(&state, &pagination(1, 0), 1, &any, false)
+            .await
+            .unwrap();
+        assert_eq!(zero_limit.pagination.total_pages, 1);
+    }
+
+    const HOLDER: &str = "bc1qholder";
+
+    /// `HOLDER` receiving `amount` at `height`, spent at `spent` (`Some(None)`
+    /// for a spend at an unrecorded height).
+    fn received(txid: &str, height: i32, amount: i64, spent: Option<Option<i32>>) -> charms::Model {
+        let mut c = charm(txid, "t/bro/bro");
+        c.address = Some(HOLDER.to_string());
+        c.block_height = Some(height);
+        c.amount = amount;
+        c.spent = spent.is_some();
+        c.spent_height = spent.flatten();
+        c
+    }
+
+    #[tokio::test]
+    async fn balance_at_replays_receives_and_spends() {
+        let mut elsewhere = received("other-app", 100, 1_000, None);
+        elsewhere.app_id = "t/zzz/zzz".to_string();
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            received("r1", 100, 10, Some(Some(105))),
+            received("r2", 103, 5, None),
+            received("r3", 105, 7, Some(Some(110))),
+            received("r4", 108, 2, Some(None)),
+            elsewhere,
+        ]));
+        let state = app_state(repos);
+
+        let at = |height| get_balance_at(&state, HOLDER, "t/bro/bro", "mainnet", height, Some(120));
+        for (height, expected) in [(99, 0), (100, 10), (104, 15), (105, 12), (107, 12)] {
+            let balance = at(height).await.unwrap();
+            assert!(balance.exact, "{height}");
+            assert_eq!(balance.balance, Some(expected), "{height}");
+            assert_eq!(balance.lower_bound, None);
+        }
+        // r4 was spent at an unknown height: held or not, from 108 on.
+        for (height, lower, upper) in [(108, 12, 14), (110, 5, 7), (120, 5, 7)] {
+            let balance = at(height).await.unwrap();
+            assert!(!balance.exact);
+            assert_eq!(balance.balance, None);
+            assert_eq!(
+                (balance.lower_bound, balance.upper_bound),
+                (Some(lower), Some(upper))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn balance_at_refuses_heights_outside_the_indexed_window() {
+        let mut state = app_state(repositories());
+        state.config.balance_at_max_lookback_blocks = 1_000;
+        let at = |height, tip| get_balance_at(&state, HOLDER, "t/bro/bro", "mainnet", height, tip);
+
+        assert!(at(5_000, Some(5_000)).await.is_ok());
+        assert!(at(4_000, Some(5_000)).await.is_ok());
+        for (height, tip) in [
+            (5_001, Some(5_000)),
+            (3_999, Some(5_000)),
+            (-1, Some(5_000)),
+            (1, None),
+        ] {
+            assert!(
+                matches!(at(height, tip).await, Err(ExplorerError::InvalidRequest(_))),
+                "{height} at tip {tip:?}"
+            );
+        }
+
+        state.config.balance_at_max_lookback_blocks = 0;
+        let balance = get_balance_at(&state, HOLDER, "t/bro/bro", "mainnet", 0, Some(5_000))
+            .await
+            .unwrap();
+        assert_eq!(balance.balance, Some(0));
+    }
+
+    #[tokio::test]
+    async fn listings_degrade_to_empty_on_database_errors() {
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::failing());
+        let state = app_state(repos);
+        let any = CharmFilter::default();
+
+        let page =
+            get_all_charms_paginated_by_network(&state, &pagination(2, 10), 1, None, &any, false)
+                .await
+                .unwrap();
+        assert!(page.data.charms.is_empty());
+        assert_eq!((page.pagination.total, page.pagination.total_pages), (0, 0));
+        assert_eq!(page.pagination.page, 2);
+
+        let by_address = get_charms_by_address(&state, "bc1q", "mainnet", 1, &any)
+            .await
+            .unwrap();
+        assert!(by_address.charms.is_empty());
+        let numbers = get_charm_numbers_by_type(&state, None).await.unwrap();
+        assert_eq!(numbers.count, 0);
+    }
+
+    #[tokio::test]
+    async fn charmid_lookup_skips_empty_spell_placeholders() {
+        let mut placeholder = charm("p", "t/a/a");
+        placeholder.is_placeholder = true;
+        let mut other_placeholder = charm("q", "n/b/b");
+        other_placeholder.is_placeholder = true;
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            placeholder,
+            charm("real", "t/a/a"),
+            other_placeholder,
+        ]));
+        let state = app_state(repos);
+
+        let found = get_charm_by_charmid(&state, "t/a/a", "mainnet", 1, false)
+            .await
+            .unwrap();
+        assert_eq!(found.txid, "real");
+        assert!(!found.is_placeholder);
+
+        // Nothing but placeholders: the first one is still served.
+        let only = get_charm_by_charmid(&state, "n/b/b", "mainnet", 1, false)
+            .await
+            .unwrap();
+        assert_eq!(only.txid, "q");
+        assert!(only.is_placeholder);
+    }
+
+    #[tokio::test]
+    async fn missing_or_failed_lookups_map_to_not_found() {
+        let state = app_state(repositories());
+        assert!(matches!(
+            get_charm_by_txid(&state, "nope", "mainnet", 1, false).await,
+            Err(ExplorerError::NotFound(_))
+        ));
+        assert!(matches!(
+            get_charm_by_charmid(&state, "t/x/x", "mainnet", 1, false).await,
+            Err(ExplorerError::NotFound(_))
+        ));
+
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::failing());
+        let state = app_state(repos);
+        match get_charm_by_txid(&state, "abc", "mainnet", 1, false).await {
+            Err(ExplorerError::NotFound(msg)) => assert!(msg.contains("database error")),
+            other => panic!("expected NotFound, got {:?}", other.map(|c| c.txid)),
+        }
+    }
+
+    #[tokio::test]
+    async fn hidden_charms_are_not_found_unless_revealed() {
+        let mut hidden = charm("bad", "t/bad/bad");
+        hidden.moderation_status = "hidden".to_string();
+        let mut flagged = charm("odd", "t/odd/odd");
+        flagged.moderation_status = "flagged".to_string();
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![hidden, flagged]));
+        let state = app_state(repos);
+        let any = CharmFilter::default();
+
+        let page = get_all_charms_paginated(&state, &pagination(1, 10), 1, &any, false)
+            .await
+            .unwrap();
+        assert_eq!(txids(&page), ["odd"]);
+        assert!(matches!(
+            get_charm_by_txid(&state, "bad", "mainnet", 1, false).await,
+            Err(ExplorerError::NotFound(_))
+        ));
+        assert!(matches!(
+            get_charm_by_charmid(&state, "t/bad/bad", "mainnet", 1, false).await,
+            Err(ExplorerError::NotFound(_))
+        ));
+
+        let revealed = get_all_charms_paginated(&state, &pagination(1, 10), 1, &any, true)
+            .await
+            .unwrap();
+        assert_eq!(txids(&revealed), ["bad", "odd"]);
+        assert!(get_charm_by_txid(&state, "bad", "mainnet", 1, true).await.is_ok());
+    }
+
+    fn order(order_id: &str, side: &str, status: &str) -> dex_orders::Model {
+        let at = chrono::DateTime::from_timestamp(1_760_000_000, 0)
+            .unwrap()
+            .naive_utc();
+        dex_orders::Model {
+            order_id: order_id.to_string(),
+            txid: "d".to_string(),
+            vout: 0,
+            block_height: Some(100),
+            platform: "charms-cast".to_string(),
+            maker: "bc1qmaker".to_string(),
+            side: side.to_string(),
+            exec_type: "partial".to_string(),
+            price_num: 1,
+            price_den: 2,
+            amount: 30_000,
+            quantity: 10_000,
+            filled_amount: 0,
+            filled_quantity: 0,
+            asset_app_id: "t/a/a".to_string(),
+            scrolls_address: None,
+            status: status.to_string(),
+            parent_order_id: None,
+            expires_at_height: None,
+            expires_at_time: None,
+            created_at: at,
+            updated_at: at,
+            blockchain: "Bitcoin".to_string(),
+            network: "mainnet".to_string(),
+        }
+    }
+
+    #[test]
+    fn charm_dex_takes_operation_from_tags_and_details_from_the_order() {
+        let bid = order("d:0", "bid", "open");
+        assert_eq!(
+            charm_dex(Some("charms-cast,create-bid"), Some(&bid)),
+            Some(CharmDex {
+                operation: "create-bid".to_string(),
+                order_id: Some("d:0".to_string()),
+                side: Some("bid".to_string()),
+                price: Some(50_000_000.0),
+                quantity: Some(10_000),
+                status: Some("open".to_string()),
+            })
+        );
+        let bare = charm_dex(Some("charms-cast,fulfill-bid"), None).unwrap();
+        assert_eq!(bare.operation, "fulfill-bid");
+        assert!(bare.order_id.is_none() && bare.price.is_none());
+        assert_eq!(charm_dex(Some("beaming,beam-out"), Some(&bid)), None);
+        assert_eq!(charm_dex(None, None), None);
+    }
+
+    #[test]
+    fn trimmed_data_is_hydrated_from_the_spell() {
+        let full = json!({"type": "spell", "native_data": {"tx": {"outs": [{"0": {"image": "data:…"}}]}}});
+        let trimmed = json!({"type": "spell", "data_truncated": true, "native_data": {}});
+
+        assert_eq!(hydrate_data(trimmed.clone(), Some(&full)), full);
+        // Without the transaction row the summary is still served.
+        assert_eq!(hydrate_data(trimmed.clone(), None), trimmed);
+        let plain = json!({"type": "spell"});
+        assert_eq!(hydrate_data(plain.clone(), Some(&full)), plain);
+    }
+
+    #[tokio::test]
+    async fn txid_lookup_embeds_dex_only_for_charms_cast_txs() {
+        let mut cast = charm("d", "t/a/a");
+        cast.tags = Some("charms-cast,cancel".to_string());
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![charm("plain", "t/b/b"), cast]));
+        let state = app_state(repos);
+
+        let plain = get_charm_by_txid(&state, "plain", "mainnet", 1, false)
+            .await
+            .unwrap();
+        assert!(plain.dex.is_none());
+        // dex_orders is unreachable here: the operation is still reported.
+        let found = get_charm_by_txid(&state, "d", "mainnet", 1, false).await.unwrap();
+        let dex = found.dex.expect("dex object");
+        assert_eq!(dex.operation, "cancel");
+        assert!(dex.status.is_none());
+    }
+
+    #[tokio::test]
+    async fn txid_lookup_lists_the_whole_spell() {
+        // Spell "s" spends a token output of "p1" and an NFT of "p2"; it
+        // creates two token outputs and an NFT at vout 1.
+        let mut spent_token = charm("p1", "t/a/a");
+        spent_token.vout = 2;
+        spent_token.amount = 500;
+        spent_token.spent = true;
+        spent_token.spending_txid = Some("s".to_string());
+        let mut spent_nft = charm("p2", "n/a/a");
+        spent_nft.asset_type = "nft".to_string();
+        spent_nft.spent = true;
+        spent_nft.spending_txid = Some("s".to_string());
+        let mut unrelated = charm("p3", "t/a/a");
+        unrelated.spent = true;
+        unrelated.spending_txid = Some("other".to_string());
+        let mut change = charm("s", "t/a/a");
+        change.vout = 2;
+        change.amount = 200;
+        change.address = Some("bc1qchange".to_string());
+        let mut payment = charm("s", "t/a/a");
+        payment.amount = 300;
+        payment.data = json!({"large": "blob"});
+        let mut nft = charm("s", "n/a/a");
+        nft.vout = 1;
+        nft.asset_type = "nft".to_string();
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            change,
+            spent_token,
+            payment,
+            nft,
+            spent_nft,
+            unrelated,
+        ]));
+        let state = app_state(repos);
+
+        let found = get_charm_by_txid(&state, "s", "mainnet", 1, false)
+            .await
+            .unwrap();
+        let context = found.spell_context.expect("spell context");
+        let outputs: Vec<_> = context
+            .outputs
+            .iter()
+            .map(|c| (c.vout, c.app_id.as_str(), c.amount))
+            .collect();
+        assert_eq!(
+            outputs,
+            [(0, "t/a/a", 300), (1, "n/a/a", 1), (2, "t/a/a", 200)]
+        );
+        assert_eq!(context.outputs[2].address.as_deref(), Some("bc1qchange"));
+        let inputs: Vec<_> = context
+            .inputs
+            .iter()
+            .map(|c| (c.txid.as_str(), c.vout, c.app_id.as_str()))
+            .collect();
+        assert_eq!(inputs, [("p1", 2, "t/a/a"), ("p2", 0, "n/a/a")]);
+        assert!(!context.truncated);
+        // The transactions table is unreachable here: no summary.
+        assert!(context.spell.is_none());
+        // Siblings are summaries: no data JSON.
+        let body = serde_json::to_string(&context).unwrap();
+        assert!(!body.contains("blob"));
+    }
+
+    #[test]
+    fn spell_summary_counts_apps_and_outputs() {
+        let spell = json!({
+            "type": "spell",
+            "native_data": {
+                "version": 8,
+                "app_public_inputs": {"t/a/a": null, "n/a/a": null},
+                "tx": {"ins": ["p1:2", "p2:0"], "outs": [{"0": 300}, {"1": {}}, {"0": 200}]},
+            },
+            "charm_output_map": {"tx_outputs": 4, "outputs": [], "missing": []},
+        });
+        assert_eq!(
+            summarize_spell(&spell),
+            SpellSummary {
+                version: Some(8),
+                apps: 2,
+                ins: 2,
+                outs: 3,
+                tx_outputs: Some(4),
+            }
+        );
+        assert_eq!(summarize_spell(&json!({})).apps, 0);
+    }
+
+    #[test]
+    fn spell_charms_are_capped() {
+        let rows: Vec<_> = (0..=SPELL_CONTEXT_LIMIT as i32)
+            .map(|vout| {
+                let mut c = charm("s", "t/a/a");
+                c.vout = vout;
+                c
+            })
+            .collect();
+        let (listed, cut) = spell_charms(rows.clone(), false);
+        assert_eq!(listed.len(), SPELL_CONTEXT_LIMIT);
+        assert!(cut);
+        let (listed, cut) = spell_charms(rows[1..].to_vec(), false);
+        assert_eq!(listed.len(), SPELL_CONTEXT_LIMIT);
+        assert!(!cut);
+    }
+
+    #[tokio::test]
+    async fn metadata_comes_from_assets_on_the_charms_network() {
+        let mut named = asset(1, "t/a/a", "token");
+        named.name = Some("Alpha".to_string());
+        let mut wrong_network = asset(2, "t/a/a", "token");
+        wrong_network.network = "testnet4".to_string();
+        wrong_network.name = Some("Alpha (t4)".to_string());
+        // Token with no asset row of its own, sharing its vk with an NFT.
+        let mut nft = asset(3, "n/other/vk", "nft");
+        nft.network = "testnet4".to_string();
+        nft.name = Some("Fire".to_string());
+        nft.image_url = Some("https://img".to_string());
+
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(vec![
+            charm("a", "t/a/a"),
+            charm("f", "t/fire/vk"),
+        ]));
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![wrong_network, named, nft]));
+        let state = app_state(repos);
+        let any = CharmFilter::default();
+
+        let page = get_all_charms_paginated(&state, &pagination(1, 10), 1, &any, false)
+            .await
+            .unwrap();
+        let names: Vec<_> = page.data.charms.iter().map(|c| c.name.as_deref()).collect();
+        assert_eq!(names, [Some("Alpha"), Some("Fire")]);
+        assert_eq!(page.data.charms[1].image.as_deref(), Some("https://img"));
+    }
+
+    #[tokio::test]
+    async fn likes_round_trip() {
+        let state = app_state(repositories());
+        let like = |user_id| LikeCharmRequest {
+            charm_id: "t/a/a".to_string(),
+            user_id,
+        };
+        let r = add_like(&state, &like(1)).await.unwrap();
+        assert_eq!((r.changed, r.likes_count), (true, 1));
+        let r = add_like(&state, &like(2)).await.unwrap();
+        assert_eq!((r.changed, r.likes_count), (true, 2));
+        // Repeats succeed without changing anything.
+        let r = add_like(&state, &like(2)).await.unwrap();
+        assert_eq!((r.changed, r.likes_count), (false, 2));
+        let r = remove_like(&state, &like(1)).await.unwrap();
+        assert_eq!((r.changed, r.likes_count), (true, 1));
+        let r = remove_like(&state, &like(1)).await.unwrap();
+        assert_eq!((r.changed, r.likes_count), (false, 1));
+    }
+}
@@ -0,0 +1,299 @@
+// Homepage dashboard: the figures the landing page shows, gathered in one
+// call. Sections are fetched concurrently, each under its own time limit;
+// one that fails or runs late comes back null and is named in `errors`, so
+// a slow table never blanks the whole page.
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+use charms_core::AssetType;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::db::repositories::charm_repository::CharmFilter;
+use crate::entity::prelude::Summary;
+use crate::entity::summary;
+use crate::handlers::status::network_status::determine_status;
+use crate::handlers::AppState;
+use crate::models::PaginationParams;
+use crate::services::asset_service::AssetService;
+
+/// Time limit of each section.
+pub const SECTION_TIMEOUT: Duration = Duration::from_secs(3);
+const RECENT_CHARMS: u64 = 5;
+const RECENT_BLOCKS: u64 = 10;
+const TOP_ASSETS: u64 = 5;
+
+#[derive(Debug, Serialize)]
+pub struct SectionError {
+    pub section: &'static str,
+    pub error: String,
+}
+
+/// Null sections are listed in `errors`.
+#[derive(Debug, Serialize)]
+pub struct DashboardResponse {
+    pub network: String,
+    pub charm_counts: Option<Value>,
+    pub assets: Option<Value>,
+    pub recent_charms: Option<Value>,
+    pub recent_blocks: Option<Value>,
+    pub top_assets: Option<Value>,
+    pub mempool: Option<Value>,
+    pub sync: Option<Value>,
+    pub errors: Vec<SectionError>,
+}
+
+/// `fut` under `limit`, with its error as text.
+async fn section<T, E: Display>(
+    limit: Duration,
+    fut: impl Future<Output = Result<T, E>>,
+) -> Result<T, String> {
+    match tokio::time::timeout(limit, fut).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("timed out after {}ms", limit.as_millis())),
+    }
+}
+
+/// Every dashboard section of `network`, each allowed `limit`.
+pub async fn get_dashboard(state: &AppState, network: &str, limit: Duration) -> DashboardResponse {
+    let repos = &state.repositories;
+    let asset_service = AssetService::new(repos.asset_repository.clone());
+    let (summary, asset_counts, recent_charms, blocks, top_assets, unconfirmed) = tokio::join!(
+        section(
+            limit,
+            Summary::find()
+                .filter(summary::Column::Network.eq(network))
+                .one(repos.connection())
+        ),
+        section(limit, asset_service.get_asset_counts(Some(network))),
+        section(limit, recent_charms_section(state, network)),
+        section(limit, repos.blocks.list(network, RECENT_BLOCKS, 0)),
+        section(limit, top_assets_section(state, network)),
+        section(limit, repos.mempool_stats.unconfirmed_count(network)),
+    );
+
+    let mut errors = Vec::new();
+    let mut keep = |name: &'static str, result: Result<Value, String>| match result {
+        Ok(value) => Some(value),
+        Err(error) => {
+            tracing::warn!(
+                "Dashboard section {} failed for {}: {}",
+                name,
+                network,
+                error
+            );
+            errors.push(SectionError {
+                section: name,
+                error,
+            });
+            None
+        }
+    };
+
+    DashboardResponse {
+        network: network.to_string(),
+        charm_counts: keep(
+            "charm_counts",
+            summary.clone().map(|s| charm_counts(s.as_ref())),
+        ),
+        assets: keep(
+            "assets",
+            asset_counts
+                .map(|counts| json!({ "total": counts.get("total").copied().unwrap_or(0) })),
+        ),
+        recent_charms: keep("recent_charms", recent_charms),
+        recent_blocks: keep("recent_blocks", blocks.map(|(blocks, _)| json!(blocks))),
+        top_assets: keep("top_assets", top_assets),
+        mempool: keep(
+            "mempool",
+            unconfirmed.map(|n| json!({ "unconfirmed_charms": n })),
+        ),
+        sync: keep("sync", summary.map(|s| sync_status(s.as_ref()))),
+        errors,
+    }
+}
+
+/// Charms per asset type from the indexer's summary; zeros before it has
+/// written one.
+fn charm_counts(summary: Option<&summary::Model>) -> Value {
+    let count = |f: fn(&summary::Model) -> i64| summary.map_or(0, f);
+    json!({
+        "total": count(|s| s.total_charms),
+        (AssetType::Nft.as_str()): count(|s| s.nft_count),
+        (AssetType::Token.as_str()): count(|s| s.token_count),
+        (AssetType::Dapp.as_str()): count(|s| s.dapp_count),
+        (AssetType::Other.as_str()): count(|s| s.other_count),
+    })
+}
+
+fn sync_status(summary: Option<&summary::Model>) -> Value {
+    match summary {
+        Some(s) => json!({
+            "status": if s.indexer_paused { "paused" } else { determine_status(&s.last_updated) },
+            "last_processed_block": s.last_processed_block,
+            "latest_confirmed_block": s.latest_confirmed_block,
+            "last_updated_at": s.last_updated.to_string(),
+        }),
+        None => json!({
+            "status": "unknown",
+            "last_processed_block": 0,
+            "latest_confirmed_block": 0,
+            "last_updated_at": null,
+        }),
+    }
+}
+
+/// Newest visible charms, as the status endpoint lists them.
+async fn recent_charms_section(state: &AppState, network: &str) -> Result<Value, String> {
+    let pagination = PaginationParams {
+        page: 1,
+        limit: RECENT_CHARMS,
+        ..Default::default()
+    };
+    let (charms, _) = state
+        .repositories
+        .charm
+        .get_all_paginated_by_network(&pagination, network, &CharmFilter::default(), false)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(charms
+        .into_iter()
+        .map(|c| {
+            json!({
+                "txid": c.txid,
+                "charmid": format!("{}:{}", c.txid, c.vout),
+                "block_height": c.block_height,
+                "asset_type": c.asset_type,
+                "app_id": c.app_id,
+            })
+        })
+        .collect())
+}
+
+/// Assets with the most holders. Names come from the assets table; when
+/// that lookup fails they are left out rather than failing the section.
+async fn top_assets_section(state: &AppState, network: &str) -> Result<Value, String> {
+    let top = state
+        .repositories
+        .stats_holders
+        .top_by_holders(network, TOP_ASSETS)
+        .await
+        .map_err(|e| e.to_string())?;
+    let app_ids = top.iter().map(|t| t.app_id.clone()).collect();
+    let assets = state
+        .repositories
+        .asset_repository
+        .find_by_app_ids(app_ids, network)
+        .await
+        .unwrap_or_default();
+    Ok(top
+        .into_iter()
+        .map(|t| {
+            let asset = assets.iter().find(|a| a.app_id == t.app_id);
+            json!({
+                "app_id": t.app_id,
+                "holders": t.holders,
+                "name": asset.and_then(|a| a.name.clone()),
+                "symbol": asset.and_then(|a| a.symbol.clone()),
+                "image_url": asset.and_then(|a| a.image_url.clone()),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::entity::stats_holders;
+    use crate::test_support::{
+        app_state, asset, charm, repositories, FakeAssets, FakeCharms, FakeStatsHolders,
+    };
+
+    fn holder(app_id: &str, address: &str) -> stats_holders::Model {
+        let at = chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        stats_holders::Model {
+            id: 0,
+            app_id: app_id.to_string(),
+            address: address.to_string(),
+            network: "mainnet".to_string(),
+            total_amount: 10,
+            charm_count: 1,
+            first_seen_block: 100,
+            last_updated_block: 100,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    fn failed(response: &DashboardResponse) -> Vec<&str> {
+        response.errors.iter().map(|e| e.section).collect()
+    }
+
+    #[tokio::test]
+    async fn a_failing_section_leaves_the_others_in_place() {
+        let mut repos = repositories();
+        repos.charm = Arc::new(FakeCharms::new(
+            (0..7)
+                .map(|i| charm(&format!("tx{}", i), "t/a/a"))
+                .collect(),
+        ));
+        repos.asset_repository = Arc::new(FakeAssets::failing());
+        repos.stats_holders = Arc::new(FakeStatsHolders {
+            holders: vec![
+                holder("t/a/a", "bc1qa"),
+                holder("t/b/b", "bc1qa"),
+                holder("t/b/b", "bc1qb"),
+            ],
+        });
+        let state = app_state(repos);
+
+        let dashboard = get_dashboard(&state, "mainnet", SECTION_TIMEOUT).await;
+
+        assert_eq!(
+            dashboard
+                .recent_charms
+                .as_ref()
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .len(),
+            5
+        );
+        let top = dashboard.top_assets.as_ref().unwrap();
+        assert_eq!(top[0]["app_id"], "t/b/b");
+        assert_eq!(top[0]["holders"], 2);
+        assert_eq!(top[0]["name"], Value::Null);
+        assert!(dashboard.assets.is_none());
+        // The rest reads tables the test database does not have.
+        assert_eq!(
+            failed(&dashboard),
+            ["charm_counts", "assets", "recent_blocks", "mempool", "sync"]
+        );
+        assert!(dashboard.sync.is_none());
+    }
+
+    #[tokio::test]
+    async fn sections_past_the_limit_are_reported_as_timed_out() {
+        let mut repos = repositories();
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![asset(1, "t/a/a", "token")]));
+        let state = app_state(repos);
+
+        let dashboard = get_dashboard(&state, "mainnet", Duration::from_millis(20)).await;
+
+        assert_eq!(dashboard.assets.unwrap()["total"], 1);
+        let blocks = dashboard
+            .errors
+            .iter()
+            .find(|e| e.section == "recent_blocks")
+            .unwrap();
+        assert_eq!(blocks.error, "timed out after 20ms");
+    }
+}
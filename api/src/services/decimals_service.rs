@@ -0,0 +1,170 @@
+// Decimal precision lookup shared by every endpoint that formats amounts.
+//
+// Holders are consolidated under the NFT app_id (`n/`), wallet balances are
+// keyed by the token app_id (`t/`), and the token row may still carry the
+// default precision when it was indexed before its NFT. Resolving both
+// through here keeps `*_formatted` values identical across endpoints.
+
+use std::collections::HashMap;
+
+use charms_core::{format_amount, nft_to_token, token_to_nft, DEFAULT_DECIMALS, MAX_DECIMALS};
+
+use crate::db::stores::AssetStore;
+use crate::entity::assets;
+
+/// Resolves and caches decimals for the app_ids of one request.
+pub struct DecimalsResolver<'a> {
+    assets: &'a dyn AssetStore,
+    cache: HashMap<(String, String), u8>,
+}
+
+impl<'a> DecimalsResolver<'a> {
+    pub fn new(assets: &'a dyn AssetStore) -> Self {
+        Self {
+            assets,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Look up every uncached app_id of `app_ids` on `network` in one query.
+    /// A failed query resolves them to `DEFAULT_DECIMALS`.
+    pub async fn resolve_all<I, S>(&mut self, network: &str, app_ids: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let pending: Vec<String> = app_ids
+            .into_iter()
+            .map(|id| id.as_ref().to_string())
+            .filter(|id| !self.cache.contains_key(&(network.to_string(), id.clone())))
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+
+        let mut lookup: Vec<String> = pending
+            .iter()
+            .flat_map(|id| [nft_to_token(id), token_to_nft(id)])
+            .collect();
+        lookup.sort();
+        lookup.dedup();
+        let rows: HashMap<String, assets::Model> = match self
+            .assets
+            .find_by_app_ids(lookup, network)
+            .await
+        {
+            Ok(rows) => rows.into_iter().map(|a| (a.app_id.clone(), a)).collect(),
+            Err(e) => {
+                tracing::warn!("Decimals lookup failed, using defaults: {:?}", e);
+                HashMap::new()
+            }
+        };
+
+        for id in pending {
+            let decimals = pick(
+                rows.get(&nft_to_token(&id)),
+                rows.get(&token_to_nft(&id)),
+            );
+            self.cache.insert((network.to_string(), id), decimals);
+        }
+    }
+
+    /// `resolve_all` for asset rows, which may span networks.
+    pub async fn resolve_assets(&mut self, rows: &[assets::Model]) {
+        let mut by_network: HashMap<&str, Vec<&str>> = HashMap::new();
+        for row in rows {
+            by_network
+                .entry(row.network.as_str())
+                .or_default()
+                .push(row.app_id.as_str());
+        }
+        for (network, app_ids) in by_network {
+            self.resolve_all(network, app_ids).await;
+        }
+    }
+
+    /// Decimals for `app_id` on `network`.
+    pub async fn resolve_decimals(&mut self, network: &str, app_id: &str) -> u8 {
+        self.resolve_all(network, [app_id]).await;
+        self.get(network, app_id)
+    }
+
+    /// A value resolved earlier in this request, `DEFAULT_DECIMALS` otherwise.
+    pub fn get(&self, network: &str, app_id: &str) -> u8 {
+        self.cache
+            .get(&(network.to_string(), app_id.to_string()))
+            .copied()
+            .unwrap_or(DEFAULT_DECIMALS)
+    }
+
+    /// `raw` formatted with the decimals of `app_id`.
+    pub fn format(&self, network: &str, app_id: &str, raw: i64) -> String {
+        format_amount(raw, self.get(network, app_id))
+    }
+}
+
+/// The token row when it declares its own precision (anything but the
+/// default, which it also holds when indexed before its NFT), then the
+/// reference NFT, then `DEFAULT_DECIMALS`.
+fn pick(token: Option<&assets::Model>, nft: Option<&assets::Model>) -> u8 {
+    let clamp = |d: i16| d.clamp(0, MAX_DECIMALS as i16) as u8;
+    match (token.map(|t| clamp(t.decimals)), nft) {
+        (Some(own), _) if own != DEFAULT_DECIMALS => own,
+        (_, Some(nft)) => clamp(nft.decimals),
+        _ => DEFAULT_DECIMALS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{asset, FakeAssets};
+
+    fn with_decimals(id: i32, app_id: &str, asset_type: &str, decimals: i16) -> assets::Model {
+        assets::Model {
+            decimals,
+            ..asset(id, app_id, asset_type)
+        }
+    }
+
+    #[tokio::test]
+    async fn token_without_own_decimals_uses_its_nft() {
+        let store = FakeAssets::new(vec![
+            asset(1, "t/aa/bb", "token"),
+            with_decimals(2, "n/aa/bb", "nft", 2),
+        ]);
+        let mut resolver = DecimalsResolver::new(&store);
+
+        assert_eq!(resolver.resolve_decimals("mainnet", "t/aa/bb").await, 2);
+        assert_eq!(resolver.resolve_decimals("mainnet", "n/aa/bb").await, 2);
+        assert_eq!(resolver.format("mainnet", "t/aa/bb", 12_345), "123.45");
+    }
+
+    #[tokio::test]
+    async fn token_row_decimals_win_over_the_nft() {
+        let store = FakeAssets::new(vec![
+            with_decimals(1, "t/aa/bb", "token", 6),
+            with_decimals(2, "n/aa/bb", "nft", 2),
+        ]);
+        let mut resolver = DecimalsResolver::new(&store);
+        resolver.resolve_all("mainnet", ["t/aa/bb", "n/aa/bb"]).await;
+
+        assert_eq!(resolver.get("mainnet", "t/aa/bb"), 6);
+        assert_eq!(resolver.get("mainnet", "n/aa/bb"), 6);
+    }
+
+    #[tokio::test]
+    async fn unknown_or_unreachable_assets_default() {
+        let empty = FakeAssets::default();
+        let failing = FakeAssets::failing();
+
+        assert_eq!(
+            DecimalsResolver::new(&empty).resolve_decimals("mainnet", "t/aa/bb").await,
+            DEFAULT_DECIMALS
+        );
+        assert_eq!(
+            DecimalsResolver::new(&failing).resolve_decimals("mainnet", "t/aa/bb").await,
+            DEFAULT_DECIMALS
+        );
+    }
+}
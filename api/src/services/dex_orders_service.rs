@@ -1,7 +1,15 @@
 // [RJJ-DEX] DEX orders service - Business logic for Charms Cast DEX positions
 
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use charms_core::format_amount;
+
+use crate::db::repositories::dex_orders_repository::BookLevel;
 use crate::error::ExplorerResult;
 use crate::handlers::AppState;
+use crate::models::PageNav;
+use crate::services::decimals_service::DecimalsResolver;
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
@@ -26,22 +34,55 @@ pub struct DexOrderResponse {
     pub status: String,
     pub confirmed: bool,
     pub parent_order_id: Option<String>,
+    pub expires_at_height: Option<i32>,
+    pub expires_at_time: Option<String>,
     pub created_at: String,
     pub network: String,
 }
 
 #[derive(Debug, Serialize)]
 pub struct DexOrdersListResponse {
-    pub total: usize,
+    /// All matching orders, not just this page.
+    pub total: u64,
+    /// Page and limit applied; absent on unpaginated (per-block) listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    /// Set by the handler on paginated listings.
+    #[serde(flatten)]
+    pub nav: Option<PageNav>,
     pub orders: Vec<DexOrderResponse>,
 }
 
-fn model_to_response(m: &crate::entity::dex_orders::Model) -> DexOrderResponse {
-    let price_per_token = if m.price_den != 0 {
-        (m.price_num as f64 / m.price_den as f64) * 100_000_000.0
+impl DexOrdersListResponse {
+    fn page(
+        orders: &[crate::entity::dex_orders::Model],
+        total: u64,
+        page: u64,
+        limit: u64,
+    ) -> Self {
+        Self {
+            total,
+            page: Some(page),
+            limit: Some(limit),
+            nav: None,
+            orders: orders.iter().map(model_to_response).collect(),
+        }
+    }
+}
+
+/// Price in sats per whole token from an order's price fraction.
+pub fn price_per_token(price_num: i64, price_den: i64) -> f64 {
+    if price_den != 0 {
+        (price_num as f64 / price_den as f64) * 100_000_000.0
     } else {
         0.0
-    };
+    }
+}
+
+fn model_to_response(m: &crate::entity::dex_orders::Model) -> DexOrderResponse {
+    let price_per_token = price_per_token(m.price_num, m.price_den);
 
     DexOrderResponse {
         order_id: m.order_id.clone(),
@@ -64,6 +105,8 @@ fn model_to_response(m: &crate::entity::dex_orders::Model) -> DexOrderResponse {
         status: m.status.clone(),
         confirmed: m.block_height.map_or(false, |h| h > 0),
         parent_order_id: m.parent_order_id.clone(),
+        expires_at_height: m.expires_at_height,
+        expires_at_time: m.expires_at_time.map(|t| t.to_string()),
         created_at: m.created_at.to_string(),
         network: m.network.clone(),
     }
@@ -75,23 +118,20 @@ pub async fn get_open_orders(
     asset_app_id: Option<&str>,
     side: Option<&str>,
     network: Option<&str>,
+    page: u64,
+    limit: u64,
 ) -> ExplorerResult<DexOrdersListResponse> {
-    let orders = state
+    let (orders, total) = state
         .repositories
         .dex_orders
-        .find_open_orders(asset_app_id, side, network)
+        .find_open_orders(asset_app_id, side, network, limit, (page - 1) * limit)
         .await
         .map_err(|e| {
             tracing::warn!("Database error in get_open_orders: {:?}", e);
             crate::error::ExplorerError::InternalError(format!("Database error: {}", e))
         })?;
 
-    let responses: Vec<DexOrderResponse> = orders.iter().map(model_to_response).collect();
-
-    Ok(DexOrdersListResponse {
-        total: responses.len(),
-        orders: responses,
-    })
+    Ok(DexOrdersListResponse::page(&orders, total, page, limit))
 }
 
 /// Get all DEX orders (any status), optionally filtered by network and status
@@ -99,23 +139,20 @@ pub async fn get_all_orders(
     state: &AppState,
     network: Option<&str>,
     status: Option<&str>,
+    page: u64,
+    limit: u64,
 ) -> ExplorerResult<DexOrdersListResponse> {
-    let orders = state
+    let (orders, total) = state
         .repositories
         .dex_orders
-        .find_all_orders(network, status)
+        .find_all_orders(network, status, limit, (page - 1) * limit)
         .await
         .map_err(|e| {
             tracing::warn!("Database error in get_all_orders: {:?}", e);
             crate::error::ExplorerError::InternalError(format!("Database error: {}", e))
         })?;
 
-    let responses: Vec<DexOrderResponse> = orders.iter().map(model_to_response).collect();
-
-    Ok(DexOrdersListResponse {
-        total: responses.len(),
-        orders: responses,
-    })
+    Ok(DexOrdersListResponse::page(&orders, total, page, limit))
 }
 
 /// Get a single order by ID
@@ -141,21 +178,45 @@ pub async fn get_orders_by_asset(
     state: &AppState,
     asset_app_id: &str,
     network: &str,
+    page: u64,
+    limit: u64,
 ) -> ExplorerResult<DexOrdersListResponse> {
-    let orders = state
+    let (orders, total) = state
         .repositories
         .dex_orders
-        .find_by_asset(asset_app_id, network)
+        .find_by_asset(asset_app_id, network, limit, (page - 1) * limit)
         .await
         .map_err(|e| {
             tracing::warn!("Database error in get_orders_by_asset: {:?}", e);
             crate::error::ExplorerError::InternalError(format!("Database error: {}", e))
         })?;
 
+    Ok(DexOrdersListResponse::page(&orders, total, page, limit))
+}
+
+/// Get all orders written by the txs of one block, network-scoped.
+pub async fn get_orders_by_block(
+    state: &AppState,
+    height: i32,
+    network: &str,
+) -> ExplorerResult<DexOrdersListResponse> {
+    let orders = state
+        .repositories
+        .dex_orders
+        .find_by_block(height, network)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Database error in get_orders_by_block: {:?}", e);
+            crate::error::ExplorerError::InternalError(format!("Database error: {}", e))
+        })?;
+
     let responses: Vec<DexOrderResponse> = orders.iter().map(model_to_response).collect();
 
     Ok(DexOrdersListResponse {
-        total: responses.len(),
+        total: responses.len() as u64,
+        page: None,
+        limit: None,
+        nav: None,
         orders: responses,
     })
 }
@@ -165,21 +226,526 @@ pub async fn get_orders_by_maker(
     state: &AppState,
     maker: &str,
     status: Option<&str>,
+    page: u64,
+    limit: u64,
 ) -> ExplorerResult<DexOrdersListResponse> {
-    let orders = state
+    let (orders, total) = state
         .repositories
         .dex_orders
-        .find_by_maker(maker, status)
+        .find_by_maker(maker, status, limit, (page - 1) * limit)
         .await
         .map_err(|e| {
             tracing::warn!("Database error in get_orders_by_maker: {:?}", e);
             crate::error::ExplorerError::InternalError(format!("Database error: {}", e))
         })?;
 
-    let responses: Vec<DexOrderResponse> = orders.iter().map(model_to_response).collect();
+    Ok(DexOrdersListResponse::page(&orders, total, page, limit))
+}
 
-    Ok(DexOrdersListResponse {
-        total: responses.len(),
-        orders: responses,
+/// Live orders on one side of a market.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct DexBookSide {
+    pub orders: u64,
+    /// Unfilled token base units.
+    pub quantity: i64,
+    pub quantity_formatted: String,
+    /// Unfilled satoshis.
+    pub value_sats: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DexMarketResponse {
+    pub asset_app_id: String,
+    pub network: String,
+    pub decimals: u8,
+    /// Highest bid and lowest ask, in sats per whole token; null when that
+    /// side of the book is empty.
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub mid_price: Option<f64>,
+    /// `best_ask - best_bid`; negative when the book is crossed.
+    pub spread: Option<f64>,
+    /// Spread as a percentage of the mid price.
+    pub spread_pct: Option<f64>,
+    pub crossed: bool,
+    pub bids: DexBookSide,
+    pub asks: DexBookSide,
+    /// Always 0: there is no trades table to count from yet.
+    pub trades_24h: u64,
+    pub volume_24h_sats: i64,
+    /// Last change to any order of the asset, live or not.
+    pub last_activity_at: Option<String>,
+}
+
+/// Book figures of a market, before the lookups that frame them.
+#[derive(Debug, PartialEq)]
+struct BookSummary {
+    best_bid: Option<f64>,
+    best_ask: Option<f64>,
+    mid_price: Option<f64>,
+    spread: Option<f64>,
+    spread_pct: Option<f64>,
+    crossed: bool,
+    bids: DexBookSide,
+    asks: DexBookSide,
+}
+
+/// `num/den` sats per base unit compared exactly.
+fn cmp_price(a: &BookLevel, b: &BookLevel) -> Ordering {
+    (a.price_num as i128 * b.price_den as i128).cmp(&(b.price_num as i128 * a.price_den as i128))
+}
+
+/// Sats per whole token at `decimals`.
+fn whole_token_price(level: &BookLevel, decimals: u8) -> f64 {
+    level.price_num as f64 / level.price_den as f64 * 10f64.powi(decimals as i32)
+}
+
+fn book_side(levels: &[&BookLevel], decimals: u8) -> DexBookSide {
+    let quantity = levels.iter().map(|l| l.quantity).sum();
+    DexBookSide {
+        orders: levels.iter().map(|l| l.orders as u64).sum(),
+        quantity,
+        quantity_formatted: format_amount(quantity, decimals),
+        value_sats: levels.iter().map(|l| l.amount).sum(),
+    }
+}
+
+/// Best prices, spread and depth of a book. Crossing is decided on the
+/// exact price fractions, not the rounded whole-token prices.
+fn summarize_book(levels: &[BookLevel], decimals: u8) -> BookSummary {
+    let (bids, asks): (Vec<&BookLevel>, Vec<&BookLevel>) =
+        levels.iter().partition(|l| l.side == "bid");
+    let best_bid = bids.iter().copied().max_by(|a, b| cmp_price(a, b));
+    let best_ask = asks.iter().copied().min_by(|a, b| cmp_price(a, b));
+
+    let (mut mid_price, mut spread, mut spread_pct, mut crossed) = (None, None, None, false);
+    if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
+        let bid_price = whole_token_price(bid, decimals);
+        let ask_price = whole_token_price(ask, decimals);
+        let mid = (bid_price + ask_price) / 2.0;
+        mid_price = Some(mid);
+        spread = Some(ask_price - bid_price);
+        spread_pct = (mid > 0.0).then(|| (ask_price - bid_price) / mid * 100.0);
+        crossed = cmp_price(bid, ask) != Ordering::Less;
+    }
+
+    BookSummary {
+        best_bid: best_bid.map(|l| whole_token_price(l, decimals)),
+        best_ask: best_ask.map(|l| whole_token_price(l, decimals)),
+        mid_price,
+        spread,
+        spread_pct,
+        crossed,
+        bids: book_side(&bids, decimals),
+        asks: book_side(&asks, decimals),
+    }
+}
+
+/// Market summary of one asset: best prices, spread and open interest of
+/// its live book, decimal-adjusted. An empty book yields nulls.
+pub async fn get_market_summary(
+    state: &AppState,
+    asset_app_id: &str,
+    network: &str,
+) -> ExplorerResult<DexMarketResponse> {
+    let repo = &state.repositories.dex_orders;
+    let db_error = |e: crate::db::DbError| {
+        tracing::warn!("Database error in get_market_summary: {:?}", e);
+        crate::error::ExplorerError::InternalError(format!("Database error: {}", e))
+    };
+    let levels = repo
+        .find_book_levels(asset_app_id, network)
+        .await
+        .map_err(db_error)?;
+    let last_activity = repo
+        .last_activity(asset_app_id, network)
+        .await
+        .map_err(db_error)?;
+    let decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref())
+        .resolve_decimals(network, asset_app_id)
+        .await;
+
+    let book = summarize_book(&levels, decimals);
+    Ok(DexMarketResponse {
+        asset_app_id: asset_app_id.to_string(),
+        network: network.to_string(),
+        decimals,
+        best_bid: book.best_bid,
+        best_ask: book.best_ask,
+        mid_price: book.mid_price,
+        spread: book.spread,
+        spread_pct: book.spread_pct,
+        crossed: book.crossed,
+        bids: book.bids,
+        asks: book.asks,
+        trades_24h: 0,
+        volume_24h_sats: 0,
+        last_activity_at: last_activity.map(|t| t.to_string()),
     })
 }
+
+/// A transaction that filled or cancelled part of an order.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OrderFillResponse {
+    pub txid: String,
+    /// `partial` for a fill that left a remainder order, else `filled` or
+    /// `cancelled`.
+    pub status: String,
+    pub block_height: Option<i32>,
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WalletOrderResponse {
+    #[serde(flatten)]
+    pub order: DexOrderResponse,
+    /// Transactions spending this order, mempool ones last.
+    pub fills: Vec<OrderFillResponse>,
+}
+
+/// What a wallet's live orders of one asset keep out of its balances.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LockedInOrders {
+    pub asset_app_id: String,
+    pub orders: u64,
+    /// Orders still in the mempool.
+    pub unconfirmed_orders: u64,
+    /// Unfilled token base units of asks, held at the orders' scrolls
+    /// addresses rather than the wallet's.
+    pub quantity: i64,
+    pub quantity_formatted: String,
+    /// Unfilled satoshis of bids.
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WalletOrdersResponse {
+    pub address: String,
+    pub network: String,
+    pub orders: Vec<WalletOrderResponse>,
+    /// Fills of these orders still in the mempool.
+    pub pending_fills: u64,
+    /// Per asset, by app_id.
+    pub locked_in_orders: Vec<LockedInOrders>,
+}
+
+/// Attach each order's children as its fills and sum what the orders lock
+/// per asset. `children` come from `find_children` over `orders`.
+fn group_wallet_orders(
+    address: &str,
+    network: &str,
+    orders: &[crate::entity::dex_orders::Model],
+    children: &[crate::entity::dex_orders::Model],
+    decimals: &DecimalsResolver<'_>,
+) -> WalletOrdersResponse {
+    let mut fills: HashMap<&str, Vec<OrderFillResponse>> = HashMap::new();
+    for child in children {
+        if let Some(parent) = child.parent_order_id.as_deref() {
+            fills.entry(parent).or_default().push(OrderFillResponse {
+                txid: child.txid.clone(),
+                status: child.status.clone(),
+                block_height: child.block_height,
+                confirmed: child.block_height.map_or(false, |h| h > 0),
+            });
+        }
+    }
+
+    let mut locked: BTreeMap<&str, LockedInOrders> = BTreeMap::new();
+    for order in orders {
+        let entry = locked
+            .entry(order.asset_app_id.as_str())
+            .or_insert_with(|| LockedInOrders {
+                asset_app_id: order.asset_app_id.clone(),
+                orders: 0,
+                unconfirmed_orders: 0,
+                quantity: 0,
+                quantity_formatted: String::new(),
+                amount: 0,
+            });
+        entry.orders += 1;
+        if !order.block_height.map_or(false, |h| h > 0) {
+            entry.unconfirmed_orders += 1;
+        }
+        if order.side == "ask" {
+            entry.quantity += (order.quantity - order.filled_quantity).max(0);
+        } else {
+            entry.amount += (order.amount - order.filled_amount).max(0);
+        }
+    }
+    for entry in locked.values_mut() {
+        entry.quantity_formatted = decimals.format(network, &entry.asset_app_id, entry.quantity);
+    }
+
+    let orders: Vec<WalletOrderResponse> = orders
+        .iter()
+        .map(|o| WalletOrderResponse {
+            order: model_to_response(o),
+            fills: fills.remove(o.order_id.as_str()).unwrap_or_default(),
+        })
+        .collect();
+    let pending_fills = orders
+        .iter()
+        .flat_map(|o| &o.fills)
+        .filter(|f| !f.confirmed)
+        .count() as u64;
+
+    WalletOrdersResponse {
+        address: address.to_string(),
+        network: network.to_string(),
+        orders,
+        pending_fills,
+        locked_in_orders: locked.into_values().collect(),
+    }
+}
+
+/// Live orders of the wallet at `address` (its maker address) with their
+/// fills, and what they lock per asset. `status` narrows to `open` or
+/// `partial`; both by default.
+pub async fn get_wallet_orders(
+    state: &AppState,
+    address: &str,
+    network: &str,
+    status: Option<&str>,
+) -> ExplorerResult<WalletOrdersResponse> {
+    if let Some(s) = status.filter(|s| !matches!(*s, "open" | "partial")) {
+        return Err(crate::error::ExplorerError::InvalidRequest(format!(
+            "invalid status '{}': expected open or partial",
+            s
+        )));
+    }
+    let repo = &state.repositories.dex_orders;
+    let db_error = |e: crate::db::DbError| {
+        tracing::warn!("Database error in get_wallet_orders: {:?}", e);
+        crate::error::ExplorerError::InternalError(format!("Database error: {}", e))
+    };
+    let orders = repo
+        .find_live_by_maker(address, network, status)
+        .await
+        .map_err(db_error)?;
+    let ids: Vec<String> = orders.iter().map(|o| o.order_id.clone()).collect();
+    let children = repo.find_children(&ids).await.map_err(db_error)?;
+
+    let mut decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref());
+    decimals
+        .resolve_all(network, orders.iter().map(|o| o.asset_app_id.as_str()))
+        .await;
+    Ok(group_wallet_orders(
+        address, network, &orders, &children, &decimals,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(side: &str, price_num: i64, price_den: i64, quantity: i64, amount: i64) -> BookLevel {
+        BookLevel {
+            side: side.to_string(),
+            price_num,
+            price_den,
+            orders: 1,
+            quantity,
+            amount,
+        }
+    }
+
+    /// Prices are sats per base unit; at 2 decimals `95/100` is 95 sats per
+    /// whole token.
+    #[test]
+    fn spread_of_a_book_that_does_not_cross() {
+        let book = summarize_book(
+            &[
+                level("bid", 90, 100, 1_000, 900),
+                level("bid", 95, 100, 500, 475),
+                level("ask", 110, 100, 200, 220),
+                level("ask", 100, 100, 300, 300),
+            ],
+            2,
+        );
+
+        assert_eq!(book.best_bid, Some(95.0));
+        assert_eq!(book.best_ask, Some(100.0));
+        assert_eq!(book.mid_price, Some(97.5));
+        assert_eq!(book.spread, Some(5.0));
+        let pct = book.spread_pct.unwrap();
+        assert!((pct - 5.128_205).abs() < 1e-6, "{pct}");
+        assert!(!book.crossed);
+        assert_eq!(
+            book.bids,
+            DexBookSide {
+                orders: 2,
+                quantity: 1_500,
+                quantity_formatted: "15".to_string(),
+                value_sats: 1_375,
+            }
+        );
+        assert_eq!((book.asks.quantity, book.asks.value_sats), (500, 520));
+    }
+
+    #[test]
+    fn crossed_and_touching_books_are_flagged() {
+        let crossed = summarize_book(
+            &[level("bid", 21, 20, 10, 10), level("ask", 1, 1, 10, 10)],
+            0,
+        );
+        assert_eq!(crossed.spread, Some(1.0 - 1.05));
+        assert!(crossed.spread.unwrap() < 0.0);
+        assert!(crossed.spread_pct.unwrap() < 0.0);
+        assert!(crossed.crossed);
+
+        // Equal prices written as different fractions still touch.
+        let touching = summarize_book(
+            &[level("bid", 1, 3, 10, 3), level("ask", 2, 6, 10, 3)],
+            8,
+        );
+        assert!(touching.crossed);
+        assert!(touching.spread.unwrap().abs() < 1e-6);
+    }
+
+    #[test]
+    fn empty_or_one_sided_books_have_null_prices() {
+        let empty = summarize_book(&[], 8);
+        assert_eq!(
+            (empty.best_bid, empty.best_ask, empty.mid_price, empty.spread, empty.spread_pct),
+            (None, None, None, None, None)
+        );
+        assert!(!empty.crossed);
+        assert_eq!(empty.bids.quantity_formatted, "0");
+
+        let asks_only = summarize_book(&[level("ask", 1, 1, 100_000_000, 100_000_000)], 8);
+        assert_eq!(asks_only.best_ask, Some(100_000_000.0));
+        assert_eq!((asks_only.best_bid, asks_only.spread), (None, None));
+        assert_eq!(asks_only.asks.quantity_formatted, "1");
+    }
+
+    /// A confirmed order of 1000 base units for 500 sats by `bc1qm`.
+    fn order(id: &str, side: &str, status: &str, asset: &str) -> crate::entity::dex_orders::Model {
+        let at = chrono::DateTime::from_timestamp(1_760_000_000, 0)
+            .unwrap()
+            .naive_utc();
+        crate::entity::dex_orders::Model {
+            order_id: format!("{}:0", id),
+            txid: id.to_string(),
+            vout: 0,
+            block_height: Some(100),
+            platform: "charms-cast".to_string(),
+            maker: "bc1qm".to_string(),
+            side: side.to_string(),
+            exec_type: "partial".to_string(),
+            price_num: 1,
+            price_den: 2,
+            amount: 500,
+            quantity: 1_000,
+            filled_amount: 0,
+            filled_quantity: 0,
+            asset_app_id: asset.to_string(),
+            scrolls_address: Some("bc1pscrolls".to_string()),
+            status: status.to_string(),
+            parent_order_id: None,
+            expires_at_height: None,
+            expires_at_time: None,
+            created_at: at,
+            updated_at: at,
+            blockchain: "Bitcoin".to_string(),
+            network: "mainnet".to_string(),
+        }
+    }
+
+    /// `order` written against `parent`, mined at `block_height`.
+    fn child(
+        id: &str,
+        status: &str,
+        parent: &str,
+        block_height: Option<i32>,
+    ) -> crate::entity::dex_orders::Model {
+        crate::entity::dex_orders::Model {
+            block_height,
+            parent_order_id: Some(parent.to_string()),
+            ..order(id, "ask", status, "t/aa/01")
+        }
+    }
+
+    /// Fills hang off the order they spent; only live orders count toward
+    /// what is locked, asks in tokens and bids in sats.
+    #[tokio::test]
+    async fn wallet_orders_group_fills_and_locked_amounts() {
+        use crate::test_support::{asset, FakeAssets};
+
+        let mut token = asset(1, "t/aa/01", "token");
+        token.decimals = 2;
+        let assets = FakeAssets::new(vec![token]);
+        let mut decimals = DecimalsResolver::new(&assets);
+        decimals
+            .resolve_all("mainnet", ["t/aa/01", "t/bb/01"])
+            .await;
+
+        let open = order("open", "ask", "open", "t/aa/01");
+        let partial = crate::entity::dex_orders::Model {
+            filled_quantity: 400,
+            filled_amount: 200,
+            ..child("part", "partial", "old:0", Some(101))
+        };
+        let bid = crate::entity::dex_orders::Model {
+            block_height: None,
+            ..order("bid", "bid", "open", "t/bb/01")
+        };
+        let children = [
+            child("part", "partial", "old:0", Some(101)),
+            child("fill1", "partial", "part:0", Some(102)),
+            child("fill2", "filled", "part:0", None),
+        ];
+
+        let grouped = group_wallet_orders(
+            "bc1qm",
+            "mainnet",
+            &[bid, partial, open],
+            &children,
+            &decimals,
+        );
+
+        let fills: Vec<(&str, Vec<&str>)> = grouped
+            .orders
+            .iter()
+            .map(|o| {
+                (
+                    o.order.order_id.as_str(),
+                    o.fills.iter().map(|f| f.txid.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            fills,
+            [
+                ("bid:0", vec![]),
+                ("part:0", vec!["fill1", "fill2"]),
+                ("open:0", vec![])
+            ]
+        );
+        assert!(!grouped.orders[0].order.confirmed);
+        assert_eq!(grouped.pending_fills, 1);
+        assert_eq!(
+            grouped.locked_in_orders,
+            [
+                LockedInOrders {
+                    asset_app_id: "t/aa/01".to_string(),
+                    orders: 2,
+                    unconfirmed_orders: 0,
+                    quantity: 1_600,
+                    quantity_formatted: "16".to_string(),
+                    amount: 0,
+                },
+                LockedInOrders {
+                    asset_app_id: "t/bb/01".to_string(),
+                    orders: 1,
+                    unconfirmed_orders: 1,
+                    quantity: 0,
+                    quantity_formatted: "0".to_string(),
+                    amount: 500,
+                },
+            ]
+        );
+
+        let empty = group_wallet_orders("bc1qm", "mainnet", &[], &[], &decimals);
+        assert!(empty.orders.is_empty() && empty.locked_in_orders.is_empty());
+    }
+}
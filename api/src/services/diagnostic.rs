@@ -1,24 +1,28 @@
 // Database diagnostic service implementation
 
-use bitcoincore_rpc::{Auth, Client, RpcApi};
+use bitcoincore_rpc::RpcApi;
 use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
 use serde_json::{Value, json};
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use crate::config::ApiConfig;
+use crate::services::rpc_clients::RpcClients;
+
+/// Tables whose `network` column API filters match exactly.
+const NETWORK_TABLES: [&str; 4] = ["charms", "assets", "transactions", "block_status"];
 
 /// Service for database diagnostics
 pub struct DiagnosticService {
     conn: DatabaseConnection,
-    config: ApiConfig,
+    rpc_clients: Arc<RpcClients>,
 }
 
 impl DiagnosticService {
-    /// Creates a new diagnostic service with database connection and configuration
-    pub fn new(conn: &DatabaseConnection, config: &ApiConfig) -> Self {
+    /// Creates a new diagnostic service with database connection and RPC clients
+    pub fn new(conn: &DatabaseConnection, rpc_clients: &Arc<RpcClients>) -> Self {
         Self {
             conn: conn.clone(),
-            config: config.clone(),
+            rpc_clients: rpc_clients.clone(),
         }
     }
 
@@ -38,6 +42,10 @@ impl DiagnosticService {
         let summary_content = self.get_summary_table_content().await;
         result.insert("summary_table", summary_content);
 
+        // Distinct network values per table, to spot naming drift
+        let network_values = self.get_network_values().await;
+        result.insert("network_values", network_values);
+
         // Test Bitcoin RPC connection
         let bitcoin_rpc_test = self.test_bitcoin_rpc_connection().await;
         result.insert("bitcoin_rpc", bitcoin_rpc_test);
@@ -336,19 +344,53 @@ impl DiagnosticService {
         }
     }
 
-    /// Tests the Bitcoin RPC connection
-    async fn test_bitcoin_rpc_connection(&self) -> Value {
-        // Use Bitcoin Testnet4 RPC connection details from configuration
-        let host = &self.config.bitcoin_testnet4_rpc_host;
-        let port = &self.config.bitcoin_testnet4_rpc_port;
-        let username = &self.config.bitcoin_testnet4_rpc_username;
-        let password = &self.config.bitcoin_testnet4_rpc_password;
+    /// Row counts per distinct `network` value of the tables filtered by
+    /// network. Values outside `charms_core::network::NETWORKS` are listed
+    /// under `non_canonical`: exact-match filters miss those rows.
+    async fn get_network_values(&self) -> Value {
+        let mut tables = serde_json::Map::new();
+        for table in NETWORK_TABLES {
+            let query = format!(
+                "SELECT network, COUNT(*) AS count FROM {} GROUP BY network ORDER BY network",
+                table
+            );
+            let info = match self
+                .conn
+                .query_all(Statement::from_string(DbBackend::Postgres, query))
+                .await
+            {
+                Ok(rows) => {
+                    let mut values = serde_json::Map::new();
+                    let mut non_canonical = Vec::new();
+                    for row in &rows {
+                        let network: String = row.try_get("", "network").unwrap_or_default();
+                        let count: i64 = row.try_get("", "count").unwrap_or(0);
+                        if !charms_core::network::NETWORKS.contains(&network.as_str()) {
+                            non_canonical.push(network.clone());
+                        }
+                        values.insert(network, json!(count));
+                    }
+                    json!({ "values": values, "non_canonical": non_canonical })
+                }
+                Err(e) => json!({ "error": e.to_string() }),
+            };
+            tables.insert(table.to_string(), info);
+        }
+        Value::Object(tables)
+    }
 
-        let rpc_url = format!("http://{}:{}", host, port);
-        let auth = Auth::UserPass(username.clone(), password.clone());
+    /// Tests the Bitcoin RPC connection of every enabled network
+    async fn test_bitcoin_rpc_connection(&self) -> Value {
+        let mut networks = serde_json::Map::new();
+        for network in self.rpc_clients.networks() {
+            networks.insert(network.to_string(), self.test_network_rpc(network).await);
+        }
+        Value::Object(networks)
+    }
 
-        // Try to connect to the Bitcoin RPC server
-        match Client::new(&rpc_url, auth) {
+    /// Tests one network's Bitcoin RPC connection
+    async fn test_network_rpc(&self, network: &str) -> Value {
+        match self.rpc_clients.get(network) {
             Ok(client) => {
                 // Try to get the block count with a timeout to prevent hanging
                 let block_count_result =
@@ -365,9 +407,6 @@ impl DiagnosticService {
                             Err(_) => "Unknown".to_string(),
                         };
 
-                        // Try to get network info to determine if mainnet or testnet
-                        let network = "testnet"; // Default to testnet for now
-
                         json!({
                             "status": "connected",
                             "block_count": block_count,
@@ -2,12 +2,22 @@
 
 pub mod address_monitor_service;
 pub mod asset_service;
+pub mod cache;
 pub mod charm_service;
+pub mod dashboard_service;
+pub mod decimals_service;
 pub mod dex_orders_service; // [RJJ-DEX]
 pub mod diagnostic;
 pub mod health;
 pub mod stats_holders_service; // [RJJ-STATS-HOLDERS]
 pub mod transaction_service;
+pub mod tx_hex_cache;
 pub mod maestro_service; // Maestro Bitcoin API provider (backup broadcast, UTXOs, chain data)
 pub mod mempool_space_service; // mempool.space broadcast provider (primary)
+pub mod rpc_clients;
+pub mod scan_cache;
+pub mod shutdown;
+pub mod tip_cache;
+pub mod wallet_changes_service;
+pub mod wallet_history_service;
 pub mod wallet_service; // [RJJ-WALLET]
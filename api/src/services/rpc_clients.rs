@@ -0,0 +1,181 @@
+// Bitcoin Core RPC clients, one per enabled network.
+//
+// Only networks enabled in the config get an entry, so a wallet request for
+// any other network is refused instead of landing on mainnet's node. A
+// client is built on the first request that needs it and then shared: a
+// malformed RPC URL fails the requests for its network, not server startup.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use bitcoincore_rpc::{Auth, Client};
+
+use crate::config::ApiConfig;
+use crate::error::{ExplorerError, ExplorerResult};
+
+struct Endpoint {
+    url: String,
+    auth: Auth,
+    client: Mutex<Option<Arc<Client>>>,
+}
+
+pub struct RpcClients {
+    endpoints: HashMap<String, Endpoint>,
+}
+
+impl RpcClients {
+    /// Registry over `(network, url, auth)` entries.
+    pub fn new(entries: impl IntoIterator<Item = (String, String, Auth)>) -> Self {
+        let endpoints = entries
+            .into_iter()
+            .map(|(network, url, auth)| {
+                let endpoint = Endpoint {
+                    url,
+                    auth,
+                    client: Mutex::new(None),
+                };
+                (network, endpoint)
+            })
+            .collect();
+        Self { endpoints }
+    }
+
+    /// One entry per network `config` enables, from its RPC settings.
+    pub fn from_config(config: &ApiConfig) -> Self {
+        Self::new(config.enabled_networks().into_iter().map(|network| {
+            let (host, port, username, password) = match network {
+                "testnet4" => (
+                    &config.bitcoin_testnet4_rpc_host,
+                    &config.bitcoin_testnet4_rpc_port,
+                    &config.bitcoin_testnet4_rpc_username,
+                    &config.bitcoin_testnet4_rpc_password,
+                ),
+                _ => (
+                    &config.bitcoin_mainnet_rpc_host,
+                    &config.bitcoin_mainnet_rpc_port,
+                    &config.bitcoin_mainnet_rpc_username,
+                    &config.bitcoin_mainnet_rpc_password,
+                ),
+            };
+            (
+                network.to_string(),
+                format!("http://{}:{}", host, port),
+                Auth::UserPass(username.clone(), password.clone()),
+            )
+        }))
+    }
+
+    /// Networks with an entry, sorted.
+    pub fn networks(&self) -> Vec<&str> {
+        let mut networks: Vec<&str> = self.endpoints.keys().map(String::as_str).collect();
+        networks.sort_unstable();
+        networks
+    }
+
+    /// RPC URL of `network`, if it has an entry
+    #[allow(dead_code)] // Used by tests
+    pub fn url(&self, network: &str) -> Option<&str> {
+        self.endpoints.get(network).map(|e| e.url.as_str())
+    }
+
+    /// Shared client for `network`, built on first use. `NotFound` for a
+    /// network that is unknown or disabled; a client that cannot be built
+    /// is an internal error, retried on the next call.
+    pub fn get(&self, network: &str) -> ExplorerResult<Arc<Client>> {
+        let endpoint = self
+            .endpoints
+            .get(network)
+            .ok_or_else(|| ExplorerError::NotFound("Network not enabled".to_string()))?;
+
+        let mut slot = endpoint.client.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(client) = slot.as_ref() {
+            return Ok(client.clone());
+        }
+        let client = Client::new(&endpoint.url, endpoint.auth.clone()).map_err(|e| {
+            tracing::error!(
+                "Bitcoin RPC client for {} could not be built: {}",
+                network,
+                e
+            );
+            ExplorerError::InternalError(format!("Bitcoin RPC for {} is misconfigured", network))
+        })?;
+        let client = Arc::new(client);
+        *slot = Some(client.clone());
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::config;
+
+    fn entry(network: &str, url: &str) -> (String, String, Auth) {
+        (network.to_string(), url.to_string(), Auth::None)
+    }
+
+    fn two_networks() -> RpcClients {
+        RpcClients::new([
+            entry("mainnet", "http://127.0.0.1:1"),
+            entry("testnet4", "http://127.0.0.1:2"),
+        ])
+    }
+
+    #[test]
+    fn unknown_or_disabled_networks_are_not_found() {
+        let clients = two_networks();
+        for network in ["regtest", "", "Mainnet"] {
+            assert!(matches!(
+                clients.get(network),
+                Err(ExplorerError::NotFound(_))
+            ));
+        }
+
+        let mut cfg = config();
+        cfg.enable_bitcoin_testnet4 = false;
+        let clients = RpcClients::from_config(&cfg);
+        assert_eq!(clients.networks(), ["mainnet"]);
+        assert!(matches!(
+            clients.get("testnet4"),
+            Err(ExplorerError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn each_network_gets_its_own_shared_client() {
+        let clients = two_networks();
+        assert_eq!(clients.url("mainnet"), Some("http://127.0.0.1:1"));
+        assert_eq!(clients.url("testnet4"), Some("http://127.0.0.1:2"));
+
+        let mainnet = clients.get("mainnet").unwrap();
+        let testnet4 = clients.get("testnet4").unwrap();
+        assert!(!Arc::ptr_eq(&mainnet, &testnet4));
+        assert!(Arc::ptr_eq(&mainnet, &clients.get("mainnet").unwrap()));
+    }
+
+    #[test]
+    fn from_config_uses_each_networks_settings() {
+        let mut cfg = config();
+        cfg.bitcoin_mainnet_rpc_host = "main.node".into();
+        cfg.bitcoin_mainnet_rpc_port = "8332".into();
+        cfg.bitcoin_testnet4_rpc_host = "t4.node".into();
+        cfg.bitcoin_testnet4_rpc_port = "48332".into();
+        let clients = RpcClients::from_config(&cfg);
+        assert_eq!(clients.networks(), ["mainnet", "testnet4"]);
+        assert_eq!(clients.url("mainnet"), Some("http://main.node:8332"));
+        assert_eq!(clients.url("testnet4"), Some("http://t4.node:48332"));
+    }
+
+    #[test]
+    fn a_misconfigured_network_fails_alone() {
+        let clients = RpcClients::new([
+            entry("mainnet", "http://127.0.0.1:1"),
+            entry("testnet4", "http://127.0.0.1:port"),
+        ]);
+        assert!(matches!(
+            clients.get("testnet4"),
+            Err(ExplorerError::InternalError(_))
+        ));
+        assert!(clients.get("mainnet").is_ok());
+    }
+}
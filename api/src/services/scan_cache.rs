@@ -0,0 +1,225 @@
+// Cache for `scantxoutset` results.
+//
+// A scan walks the whole UTXO set (minutes on mainnet) and the node runs one
+// at a time, so repeated or concurrent requests for the same address must
+// not each start their own. Entries are keyed by (address, network) and
+// remember the node tip they were scanned at; a new block invalidates them.
+// Results live in the shared cache (`cache.rs`), so with Redis one replica's
+// scan serves them all; concurrent requests wait on each other per replica.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::services::cache::{self, Cache};
+use crate::services::wallet_service::Utxo;
+
+/// Per-address locks kept before idle ones are pruned
+const MAX_ENTRIES: usize = 1024;
+
+/// How long a scan is stored. It is only served while the tip is unchanged
+/// (or within the TTL when the tip is unknown); this just bounds storage.
+const RETENTION: Duration = Duration::from_secs(3600);
+
+#[derive(Serialize, Deserialize)]
+struct CachedScan {
+    tip: Option<u64>,
+    /// Unix time of the scan, in milliseconds
+    scanned_at: i64,
+    utxos: Vec<Utxo>,
+}
+
+impl CachedScan {
+    /// Same tip as the node now; when the tip is unknown fall back to age.
+    fn is_fresh(&self, tip: Option<u64>, ttl: Duration) -> bool {
+        match tip {
+            Some(tip) => self.tip == Some(tip),
+            None => {
+                let age = chrono::Utc::now().timestamp_millis() - self.scanned_at;
+                age >= 0 && (age as u128) < ttl.as_millis()
+            }
+        }
+    }
+}
+
+type Slot = Arc<AsyncMutex<()>>;
+
+pub struct ScanCache {
+    ttl: Duration,
+    cache: Arc<dyn Cache>,
+    slots: Mutex<HashMap<(String, String), Slot>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ScanCache {
+    pub fn new(cache: Arc<dyn Cache>, ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cache,
+            slots: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Return the cached UTXOs for `address` if they were scanned at `tip`
+    /// (or within the TTL when `tip` is None), otherwise run `scan` and cache
+    /// its result. Callers for the same address wait on one another, so
+    /// concurrent requests share a single scan. Errors are not cached.
+    pub async fn get_or_scan<F, Fut>(
+        &self,
+        address: &str,
+        network: &str,
+        tip: Option<u64>,
+        scan: F,
+    ) -> Result<Vec<Utxo>, String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<Utxo>, String>>,
+    {
+        let slot = self.slot(address, network);
+        let _guard = slot.lock().await;
+        let key = cache::key(network, "scan", address);
+
+        let cached: Option<CachedScan> = cache::get_json(self.cache.as_ref(), &key).await;
+        if let Some(entry) = cached.filter(|e| e.is_fresh(tip, self.ttl)) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(entry.utxos);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let utxos = scan().await?;
+        let entry = CachedScan {
+            tip,
+            scanned_at: chrono::Utc::now().timestamp_millis(),
+            utxos,
+        };
+        cache::set_json(self.cache.as_ref(), &key, &entry, RETENTION.max(self.ttl)).await;
+        Ok(entry.utxos)
+    }
+
+    fn slot(&self, address: &str, network: &str) -> Slot {
+        let mut slots = self.slots.lock().unwrap();
+        if slots.len() >= MAX_ENTRIES {
+            // Keep only the locks someone holds or waits on.
+            slots.retain(|_, slot| Arc::strong_count(slot) > 1 || slot.try_lock().is_err());
+        }
+        slots
+            .entry((address.to_string(), network.to_string()))
+            .or_default()
+            .clone()
+    }
+
+    pub fn snapshot(&self) -> Value {
+        serde_json::json!({
+            "backend": self.cache.backend(),
+            "hits": self.hits.load(Ordering::Relaxed),
+            "misses": self.misses.load(Ordering::Relaxed),
+            "entries": self.slots.lock().unwrap().len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::cache::MemoryCache;
+    use std::sync::atomic::AtomicUsize;
+
+    fn utxo(txid: &str) -> Utxo {
+        Utxo {
+            txid: txid.to_string(),
+            vout: 0,
+            value: 1_000,
+            script_pubkey: String::new(),
+            confirmations: 1,
+            block_height: Some(100),
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_share_one_scan() {
+        let cache = ScanCache::new(Arc::new(MemoryCache::new()), Duration::from_secs(30));
+        let scans = AtomicUsize::new(0);
+        let scan = || async {
+            scans.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(vec![utxo("aa")])
+        };
+
+        let (a, b) = tokio::join!(
+            cache.get_or_scan("bc1qx", "mainnet", Some(100), scan),
+            cache.get_or_scan("bc1qx", "mainnet", Some(100), scan),
+        );
+        assert_eq!(a.unwrap()[0].txid, "aa");
+        assert_eq!(b.unwrap()[0].txid, "aa");
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.snapshot()["hits"], 1);
+        assert_eq!(cache.snapshot()["misses"], 1);
+    }
+
+    #[tokio::test]
+    async fn new_tip_invalidates_entry() {
+        let cache = ScanCache::new(Arc::new(MemoryCache::new()), Duration::from_secs(30));
+        let scans = AtomicUsize::new(0);
+        let scan = || async {
+            let n = scans.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![utxo(&format!("scan{}", n))])
+        };
+
+        cache
+            .get_or_scan("bc1qx", "mainnet", Some(100), scan)
+            .await
+            .unwrap();
+        let same = cache
+            .get_or_scan("bc1qx", "mainnet", Some(100), scan)
+            .await
+            .unwrap();
+        assert_eq!(same[0].txid, "scan0");
+
+        let next = cache
+            .get_or_scan("bc1qx", "mainnet", Some(101), scan)
+            .await
+            .unwrap();
+        assert_eq!(next[0].txid, "scan1");
+
+        // Keyed per network: testnet4 scans on its own.
+        cache
+            .get_or_scan("bc1qx", "testnet4", Some(101), scan)
+            .await
+            .unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn replicas_sharing_a_cache_share_a_scan() {
+        let shared: Arc<dyn Cache> = Arc::new(MemoryCache::new());
+        let (a, b) = (
+            ScanCache::new(shared.clone(), Duration::from_secs(30)),
+            ScanCache::new(shared, Duration::from_secs(30)),
+        );
+        let scans = AtomicUsize::new(0);
+        let scan = || async {
+            scans.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![utxo("aa")])
+        };
+
+        a.get_or_scan("bc1qx", "mainnet", Some(100), scan)
+            .await
+            .unwrap();
+        let utxos = b
+            .get_or_scan("bc1qx", "mainnet", Some(100), scan)
+            .await
+            .unwrap();
+        assert_eq!(utxos[0].txid, "aa");
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+        assert_eq!(b.snapshot()["hits"], 1);
+    }
+}
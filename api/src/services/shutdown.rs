@@ -0,0 +1,253 @@
+// Graceful shutdown: on SIGTERM/SIGINT the server reports itself not ready
+// (GET /health/ready answers 503, so load balancers stop routing to it),
+// stops accepting connections and lets in-flight requests finish, for at
+// most the drain timeout. `main` closes the database pool afterwards, so no
+// query is cut off mid-way by a dropped connection.
+
+use std::future::{Future, IntoFuture};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::Router;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+
+/// Readiness flag and in-flight request count of the server.
+#[derive(Debug)]
+pub struct Lifecycle {
+    ready: AtomicBool,
+    in_flight: AtomicUsize,
+    /// Requests in flight when shutdown began.
+    at_shutdown: AtomicUsize,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self {
+            ready: AtomicBool::new(true),
+            in_flight: AtomicUsize::new(0),
+            at_shutdown: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Lifecycle {
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::SeqCst)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Flip readiness off and remember how many requests are left to drain.
+    pub fn begin_shutdown(&self) -> usize {
+        self.ready.store(false, Ordering::SeqCst);
+        let in_flight = self.in_flight();
+        self.at_shutdown.store(in_flight, Ordering::SeqCst);
+        in_flight
+    }
+}
+
+/// Middleware counting requests while their handler runs.
+pub async fn track_in_flight(
+    State(lifecycle): State<Arc<Lifecycle>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    struct Guard<'a>(&'a Lifecycle);
+    impl Drop for Guard<'_> {
+        fn drop(&mut self) {
+            self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    lifecycle.in_flight.fetch_add(1, Ordering::SeqCst);
+    let _guard = Guard(&lifecycle);
+    next.run(request).await
+}
+
+/// How the server stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Drain {
+    /// Every request in flight at shutdown finished.
+    Complete { drained: usize },
+    /// The drain timeout passed with `abandoned` requests still running.
+    TimedOut { drained: usize, abandoned: usize },
+}
+
+/// Resolves on SIGINT, or SIGTERM on Unix.
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Serve `app` on `listener` until `shutdown` resolves, then drain
+/// in-flight requests for at most `drain_timeout`. `app` must be wrapped in
+/// `track_in_flight` over the same `lifecycle` for the counts to mean
+/// anything.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    lifecycle: Arc<Lifecycle>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+    drain_timeout: Duration,
+) -> std::io::Result<Drain> {
+    let started = Arc::new(Notify::new());
+    let graceful = {
+        let (lifecycle, started) = (lifecycle.clone(), started.clone());
+        async move {
+            shutdown.await;
+            let in_flight = lifecycle.begin_shutdown();
+            tracing::info!(
+                "Shutdown requested: not ready, draining {} in-flight request(s) for up to {}s",
+                in_flight,
+                drain_timeout.as_secs()
+            );
+            started.notify_one();
+        }
+    };
+
+    // Peer addresses feed the admin audit log's `source_ip`.
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(graceful)
+    .into_future();
+    let deadline = async {
+        started.notified().await;
+        tokio::time::sleep(drain_timeout).await;
+    };
+
+    let at_shutdown = || lifecycle.at_shutdown.load(Ordering::SeqCst);
+    tokio::select! {
+        result = server => {
+            result?;
+            Ok(Drain::Complete { drained: at_shutdown() })
+        }
+        _ = deadline => {
+            let abandoned = lifecycle.in_flight();
+            Ok(Drain::TimedOut {
+                drained: at_shutdown().saturating_sub(abandoned),
+                abandoned,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::middleware;
+    use axum::routing::get;
+    use tokio::sync::oneshot;
+
+    /// A server whose `/slow` answers after `delay`, stopped by the sender.
+    async fn start(
+        delay: Duration,
+        drain_timeout: Duration,
+    ) -> (
+        String,
+        Arc<Lifecycle>,
+        oneshot::Sender<()>,
+        tokio::task::JoinHandle<std::io::Result<Drain>>,
+    ) {
+        let lifecycle = Arc::new(Lifecycle::default());
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(move || async move {
+                    tokio::time::sleep(delay).await;
+                    "done"
+                }),
+            )
+            .layer(middleware::from_fn_with_state(
+                lifecycle.clone(),
+                track_in_flight,
+            ));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/slow", listener.local_addr().unwrap());
+        let (stop, stopped) = oneshot::channel();
+        let server = tokio::spawn(serve(
+            listener,
+            app,
+            lifecycle.clone(),
+            async {
+                let _ = stopped.await;
+            },
+            drain_timeout,
+        ));
+        (url, lifecycle, stop, server)
+    }
+
+    async fn until_in_flight(lifecycle: &Lifecycle) {
+        while lifecycle.in_flight() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn a_slow_request_completes_before_the_server_exits() {
+        let (url, lifecycle, stop, server) =
+            start(Duration::from_millis(300), Duration::from_secs(5)).await;
+        let request = tokio::spawn(reqwest::get(url));
+        until_in_flight(&lifecycle).await;
+
+        stop.send(()).unwrap();
+        let response = request.await.unwrap().unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "done");
+        assert!(!lifecycle.is_ready());
+        assert_eq!(
+            server.await.unwrap().unwrap(),
+            Drain::Complete { drained: 1 }
+        );
+    }
+
+    #[tokio::test]
+    async fn the_drain_stops_at_its_timeout() {
+        let (url, lifecycle, stop, server) =
+            start(Duration::from_secs(30), Duration::from_millis(100)).await;
+        let _request = tokio::spawn(reqwest::get(url));
+        until_in_flight(&lifecycle).await;
+
+        stop.send(()).unwrap();
+        assert_eq!(
+            server.await.unwrap().unwrap(),
+            Drain::TimedOut {
+                drained: 0,
+                abandoned: 1
+            }
+        );
+    }
+}
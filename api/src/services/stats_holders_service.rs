@@ -2,12 +2,15 @@
 
 use crate::error::ExplorerResult;
 use crate::handlers::AppState;
+use crate::models::PageNav;
+use crate::services::decimals_service::DecimalsResolver;
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
 pub struct HolderInfo {
     pub address: String,
     pub total_amount: i64,
+    pub total_amount_formatted: String,
     pub charm_count: i32,
     pub percentage: f64,
     pub first_seen_block: i32,
@@ -19,21 +22,30 @@ pub struct HoldersResponse {
     pub app_id: String,
     pub total_holders: usize,
     pub total_supply: i64,
+    pub total_supply_formatted: String,
+    /// Resolved like wallet balances and asset listings, so formatted
+    /// amounts agree across endpoints.
+    pub decimals: u8,
+    pub page: u64,
+    pub limit: u64,
+    /// Set by the handler.
+    #[serde(flatten)]
+    pub nav: PageNav,
     pub holders: Vec<HolderInfo>,
 }
 
-/// Get holders for a specific asset (app_id)
+/// Get one page of holders for a specific asset (app_id). `total_holders`,
+/// `total_supply` and percentages cover every holder, not just the page.
 pub async fn get_holders_by_app_id(
     state: &AppState,
     app_id: &str,
+    network: &str,
+    page: u64,
+    limit: u64,
 ) -> ExplorerResult<HoldersResponse> {
     // [RJJ-TOKEN-METADATA] Convert token app_id (t/) to NFT app_id (n/) for lookup
     // Stats are consolidated under NFT app_ids in the database
-    let lookup_app_id = if app_id.starts_with("t/") {
-        app_id.replacen("t/", "n/", 1)
-    } else {
-        app_id.to_string()
-    };
+    let lookup_app_id = charms_core::token_to_nft(app_id);
 
     // Remove the :N suffix for broader matching (stats are per-asset, not per-output)
     let base_app_id = if let Some(pos) = lookup_app_id.rfind(':') {
@@ -42,6 +54,10 @@ pub async fn get_holders_by_app_id(
         &lookup_app_id
     };
 
+    let decimals = DecimalsResolver::new(state.repositories.asset_repository.as_ref())
+        .resolve_decimals(network, app_id)
+        .await;
+
     // Get holders from database
     let holders = match state
         .repositories
@@ -56,6 +72,11 @@ pub async fn get_holders_by_app_id(
                 app_id: app_id.to_string(),
                 total_holders: 0,
                 total_supply: 0,
+                total_supply_formatted: charms_core::format_amount(0, decimals),
+                decimals,
+                page,
+                limit,
+                nav: PageNav::default(),
                 holders: vec![],
             });
         }
@@ -64,9 +85,13 @@ pub async fn get_holders_by_app_id(
     // Calculate total supply
     let total_supply: i64 = holders.iter().map(|h| h.total_amount).sum();
 
-    // Transform to response format with percentages
+    let total_holders = holders.len();
+
+    // Transform the requested page to response format with percentages
     let holder_infos: Vec<HolderInfo> = holders
         .into_iter()
+        .skip(((page - 1) * limit) as usize)
+        .take(limit as usize)
         .map(|h| {
             let percentage = if total_supply > 0 {
                 (h.total_amount as f64 / total_supply as f64) * 100.0
@@ -77,6 +102,7 @@ pub async fn get_holders_by_app_id(
             HolderInfo {
                 address: h.address,
                 total_amount: h.total_amount,
+                total_amount_formatted: charms_core::format_amount(h.total_amount, decimals),
                 charm_count: h.charm_count,
                 percentage,
                 first_seen_block: h.first_seen_block,
@@ -87,8 +113,72 @@ pub async fn get_holders_by_app_id(
 
     Ok(HoldersResponse {
         app_id: app_id.to_string(),
-        total_holders: holder_infos.len(),
+        total_holders,
         total_supply,
+        total_supply_formatted: charms_core::format_amount(total_supply, decimals),
+        decimals,
+        page,
+        limit,
+        nav: PageNav::default(),
         holders: holder_infos,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::entity::{assets, stats_holders};
+    use crate::test_support::{app_state, asset, repositories, FakeAssets, FakeStatsHolders};
+
+    fn holder(address: &str, total_amount: i64) -> stats_holders::Model {
+        let at = chrono::DateTime::from_timestamp(1_760_000_000, 0)
+            .unwrap()
+            .naive_utc();
+        stats_holders::Model {
+            id: 0,
+            app_id: "n/aa/bb".to_string(),
+            address: address.to_string(),
+            network: "mainnet".to_string(),
+            total_amount,
+            charm_count: 1,
+            first_seen_block: 100,
+            last_updated_block: 100,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    /// The token row still has the default precision; only its NFT declares
+    /// 2 decimals, and holders are stored under the NFT app_id.
+    #[tokio::test]
+    async fn holders_format_with_decimals_declared_on_the_nft() {
+        let mut repos = repositories();
+        repos.asset_repository = Arc::new(FakeAssets::new(vec![
+            asset(1, "t/aa/bb", "token"),
+            assets::Model {
+                decimals: 2,
+                ..asset(2, "n/aa/bb", "nft")
+            },
+        ]));
+        repos.stats_holders = Arc::new(FakeStatsHolders {
+            holders: vec![holder("bc1qbig", 7_500), holder("bc1qsmall", 2_550)],
+        });
+        let state = app_state(repos);
+
+        for app_id in ["t/aa/bb", "n/aa/bb"] {
+            let response = get_holders_by_app_id(&state, app_id, "mainnet", 1, 10)
+                .await
+                .unwrap();
+            assert_eq!(response.decimals, 2);
+            assert_eq!(response.total_supply_formatted, "100.5");
+            let formatted: Vec<&str> = response
+                .holders
+                .iter()
+                .map(|h| h.total_amount_formatted.as_str())
+                .collect();
+            assert_eq!(formatted, ["75", "25.5"]);
+        }
+    }
+}
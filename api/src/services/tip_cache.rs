@@ -0,0 +1,67 @@
+// Cache of the node's chain tip, per network.
+//
+// Confirmation counts and scan-cache freshness both need the current tip,
+// and a wallet page fires several requests at once. Sharing one short-lived
+// value keeps them consistent and spares the node a `getblockchaininfo` per
+// request; with Redis configured the value is shared across replicas too
+// (see `cache.rs`). Failed lookups are not cached.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::services::cache::{self, Cache};
+
+pub struct TipCache {
+    ttl: Duration,
+    cache: Arc<dyn Cache>,
+}
+
+impl TipCache {
+    pub fn new(cache: Arc<dyn Cache>, ttl: Duration) -> Self {
+        Self { ttl, cache }
+    }
+
+    /// Tip for `network` if fetched within the TTL.
+    pub async fn get(&self, network: &str) -> Option<u64> {
+        cache::get_json(self.cache.as_ref(), &cache::key(network, "tip", "")).await
+    }
+
+    pub async fn insert(&self, network: &str, height: u64) {
+        let key = cache::key(network, "tip", "");
+        cache::set_json(self.cache.as_ref(), &key, &height, self.ttl).await;
+    }
+
+    /// Cached tip for `network`, otherwise the result of `fetch` (cached when
+    /// it succeeds). `None` when the tip is unknown.
+    pub async fn get_or_fetch<F, Fut>(&self, network: &str, fetch: F) -> Option<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Option<u64>>,
+    {
+        if let Some(height) = self.get(network).await {
+            return Some(height);
+        }
+        let height = fetch().await?;
+        self.insert(network, height).await;
+        Some(height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::cache::MemoryCache;
+
+    #[tokio::test]
+    async fn serves_the_cached_tip_until_it_expires() {
+        let cache = TipCache::new(Arc::new(MemoryCache::new()), Duration::from_millis(50));
+        assert_eq!(cache.get_or_fetch("mainnet", || async { None }).await, None);
+        assert_eq!(cache.get_or_fetch("mainnet", || async { Some(10) }).await, Some(10));
+        assert_eq!(cache.get_or_fetch("mainnet", || async { Some(11) }).await, Some(10));
+        assert_eq!(cache.get("testnet4").await, None);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(cache.get_or_fetch("mainnet", || async { Some(11) }).await, Some(11));
+    }
+}
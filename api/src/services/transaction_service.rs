@@ -15,12 +15,7 @@ pub async fn get_all_transactions_paginated(
             tracing::warn!("Database error in get_all_transactions_paginated: {:?}", err);
             return Ok(PaginatedResponse {
                 data: TransactionsResponse { transactions: vec![] },
-                pagination: PaginationMeta {
-                    total: 0,
-                    page: pagination.page,
-                    limit: pagination.limit,
-                    total_pages: 0,
-                },
+                pagination: PaginationMeta::new(0, pagination.page, pagination.limit, 0),
             });
         }
     };
@@ -35,12 +30,7 @@ pub async fn get_all_transactions_paginated(
 
     Ok(PaginatedResponse {
         data: TransactionsResponse { transactions },
-        pagination: PaginationMeta {
-            total,
-            page: pagination.page,
-            limit: pagination.limit,
-            total_pages,
-        },
+        pagination: PaginationMeta::new(total, pagination.page, pagination.limit, total_pages),
     })
 }
 
@@ -64,12 +54,7 @@ pub async fn get_all_transactions_paginated_by_network(
             );
             return Ok(PaginatedResponse {
                 data: TransactionsResponse { transactions: vec![] },
-                pagination: PaginationMeta {
-                    total: 0,
-                    page: pagination.page,
-                    limit: pagination.limit,
-                    total_pages: 0,
-                },
+                pagination: PaginationMeta::new(0, pagination.page, pagination.limit, 0),
             });
         }
     };
@@ -84,11 +69,6 @@ pub async fn get_all_transactions_paginated_by_network(
 
     Ok(PaginatedResponse {
         data: TransactionsResponse { transactions },
-        pagination: PaginationMeta {
-            total,
-            page: pagination.page,
-            limit: pagination.limit,
-            total_pages,
-        },
+        pagination: PaginationMeta::new(total, pagination.page, pagination.limit, total_pages),
     })
 }
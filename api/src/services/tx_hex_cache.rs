@@ -0,0 +1,116 @@
+// In-process cache for raw transaction hex fetched from the node.
+//
+// A tx's hex never changes once broadcast, so entries only expire to bound
+// memory and to let a dropped-then-replaced tx fall out eventually. Keyed by
+// (txid, network); the least recently used entry goes first when full.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Entries kept before the least recently used one is evicted
+const MAX_ENTRIES: usize = 1024;
+
+struct Entry {
+    hex: String,
+    stored_at: Instant,
+    last_used: u64,
+}
+
+pub struct TxHexCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, String), Entry>>,
+    clock: Mutex<u64>,
+}
+
+impl TxHexCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            clock: Mutex::new(0),
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        let mut clock = self.clock.lock().unwrap();
+        *clock += 1;
+        *clock
+    }
+
+    /// Cached hex for `txid` on `network`, unless older than the TTL.
+    pub fn get(&self, txid: &str, network: &str) -> Option<String> {
+        let tick = self.tick();
+        let mut entries = self.entries.lock().unwrap();
+        let key = (txid.to_string(), network.to_string());
+        match entries.get_mut(&key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => {
+                entry.last_used = tick;
+                Some(entry.hex.clone())
+            }
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, txid: &str, network: &str, hex: String) {
+        let tick = self.tick();
+        let mut entries = self.entries.lock().unwrap();
+        let key = (txid.to_string(), network.to_string());
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            let ttl = self.ttl;
+            entries.retain(|_, e| e.stored_at.elapsed() < ttl);
+            if entries.len() >= MAX_ENTRIES {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, e)| e.last_used)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                hex,
+                stored_at: Instant::now(),
+                last_used: tick,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_after_ttl_and_are_per_network() {
+        let cache = TxHexCache::new(Duration::from_millis(30));
+        cache.insert("aa", "mainnet", "0200".to_string());
+        assert_eq!(cache.get("aa", "mainnet").as_deref(), Some("0200"));
+        assert_eq!(cache.get("aa", "testnet4"), None);
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert_eq!(cache.get("aa", "mainnet"), None);
+    }
+
+    #[test]
+    fn full_cache_evicts_least_recently_used() {
+        let cache = TxHexCache::new(Duration::from_secs(120));
+        for i in 0..MAX_ENTRIES {
+            cache.insert(&format!("tx{}", i), "mainnet", "00".to_string());
+        }
+        // Touch the oldest entry so tx1 becomes the least recently used.
+        assert!(cache.get("tx0", "mainnet").is_some());
+        cache.insert("new", "mainnet", "01".to_string());
+
+        assert!(cache.get("tx0", "mainnet").is_some());
+        assert!(cache.get("tx1", "mainnet").is_none());
+        assert_eq!(cache.get("new", "mainnet").as_deref(), Some("01"));
+    }
+}
@@ -0,0 +1,372 @@
+// Wallet changes: turns the raw rows of a height window into the delta a
+// polling wallet applies to its last state, and tells it when the cursor
+// can't be answered and it has to load the full state again.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::db::error::DbError;
+use crate::db::repositories::wallet_changes_repository::{
+    AddedUtxo, PendingCharm, PendingItems, PendingSpend, PendingUtxo, ReceivedCharm, RemovedUtxo,
+    SpentCharm, SyncState, WindowChanges,
+};
+use crate::db::repositories::WalletChangesRepository;
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct CharmChanges {
+    pub received: Vec<ReceivedCharm>,
+    pub spent: Vec<SpentCharm>,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct UtxoChanges {
+    pub added: Vec<AddedUtxo>,
+    pub removed: Vec<RemovedUtxo>,
+}
+
+/// The pending set and its `seq`. When `seq` equals the caller's
+/// `since_mempool_seq` nothing changed and the lists are left empty;
+/// otherwise they hold the whole set, which replaces the previous one
+/// (an item missing from it left the mempool).
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct MempoolChanges {
+    pub seq: u64,
+    pub changed: bool,
+    pub charms: Vec<PendingCharm>,
+    pub utxos: Vec<PendingUtxo>,
+    pub spends: Vec<PendingSpend>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct WalletChangesResponse {
+    pub address: String,
+    pub network: String,
+    pub since_height: i32,
+    /// Height the changes run up to: the next call's `since_height`
+    pub tip_height: i32,
+    /// The changes after `since_height` can't be served; reload the full
+    /// state and continue from `tip_height`. `charms` and `btc` are empty.
+    pub resync_required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resync_reason: Option<&'static str>,
+    pub charms: CharmChanges,
+    pub btc: UtxoChanges,
+    pub mempool: MempoolChanges,
+}
+
+/// Why a cursor can't be answered from what is kept, if it can't.
+pub fn resync_reason(since: i32, state: &SyncState) -> Option<&'static str> {
+    let tip = state.tip.unwrap_or(0);
+    if since > tip {
+        return Some("since_height_ahead_of_tip");
+    }
+    if state.reorged {
+        return Some("since_height_reorged");
+    }
+    let Some(synced_from) = state.synced_from else {
+        return Some("address_not_tracked");
+    };
+    if since < synced_from.max(state.horizon.unwrap_or(0)) {
+        return Some("history_unavailable");
+    }
+    None
+}
+
+/// Drop what was both created and spent inside the window: the caller
+/// never saw it, and it is gone at the tip.
+fn net(window: WindowChanges) -> (CharmChanges, UtxoChanges) {
+    let spent_charms: HashSet<(&str, i32, &str)> = window
+        .spent
+        .iter()
+        .map(|c| (c.txid.as_str(), c.vout, c.app_id.as_str()))
+        .collect();
+    let received_charms: HashSet<(&str, i32, &str)> = window
+        .received
+        .iter()
+        .map(|c| (c.txid.as_str(), c.vout, c.app_id.as_str()))
+        .collect();
+    let removed_utxos: HashSet<(&str, i32)> = window
+        .removed
+        .iter()
+        .map(|u| (u.txid.as_str(), u.vout))
+        .collect();
+    let added_utxos: HashSet<(&str, i32)> = window
+        .added
+        .iter()
+        .map(|u| (u.txid.as_str(), u.vout))
+        .collect();
+
+    let charms = CharmChanges {
+        received: window
+            .received
+            .iter()
+            .filter(|c| !spent_charms.contains(&(c.txid.as_str(), c.vout, c.app_id.as_str())))
+            .cloned()
+            .collect(),
+        spent: window
+            .spent
+            .iter()
+            .filter(|c| !received_charms.contains(&(c.txid.as_str(), c.vout, c.app_id.as_str())))
+            .cloned()
+            .collect(),
+    };
+    let btc = UtxoChanges {
+        added: window
+            .added
+            .iter()
+            .filter(|u| !removed_utxos.contains(&(u.txid.as_str(), u.vout)))
+            .cloned()
+            .collect(),
+        removed: window
+            .removed
+            .iter()
+            .filter(|u| !added_utxos.contains(&(u.txid.as_str(), u.vout)))
+            .cloned()
+            .collect(),
+    };
+    (charms, btc)
+}
+
+/// Fingerprint of a pending set: 48 bits of a SHA-256 over its sorted
+/// keys, so it stays exact as a JavaScript number. 0 for an empty set.
+pub fn mempool_seq(pending: &PendingItems) -> u64 {
+    let mut keys: Vec<String> = pending
+        .charms
+        .iter()
+        .map(|c| format!("c:{}:{}:{}:{}", c.txid, c.vout, c.app_id, c.amount))
+        .chain(
+            pending
+                .utxos
+                .iter()
+                .map(|u| format!("u:{}:{}:{}", u.txid, u.vout, u.value)),
+        )
+        .chain(
+            pending
+                .spends
+                .iter()
+                .map(|s| format!("s:{}:{}:{}", s.txid, s.vout, s.spending_txid)),
+        )
+        .collect();
+    if keys.is_empty() {
+        return 0;
+    }
+    keys.sort();
+    let digest = Sha256::digest(keys.join("\n").as_bytes());
+    digest[..6]
+        .iter()
+        .fold(0u64, |seq, byte| (seq << 8) | u64::from(*byte))
+}
+
+fn mempool_changes(pending: PendingItems, since_seq: Option<u64>) -> MempoolChanges {
+    let seq = mempool_seq(&pending);
+    if since_seq == Some(seq) {
+        return MempoolChanges {
+            seq,
+            ..MempoolChanges::default()
+        };
+    }
+    MempoolChanges {
+        seq,
+        changed: true,
+        charms: pending.charms,
+        utxos: pending.utxos,
+        spends: pending.spends,
+    }
+}
+
+/// Changes of `address` after `since` up to the tip in `state`. `state`
+/// must be read before the address was touched or re-seeded for this call.
+pub async fn get_wallet_changes(
+    repo: &WalletChangesRepository,
+    address: &str,
+    network: &str,
+    since: i32,
+    since_mempool_seq: Option<u64>,
+    state: SyncState,
+) -> Result<WalletChangesResponse, DbError> {
+    let tip = state.tip.unwrap_or(0);
+    let resync_reason = resync_reason(since, &state);
+    let (charms, btc) = match resync_reason {
+        Some(_) => Default::default(),
+        None => net(repo.window(address, network, since, tip).await?),
+    };
+    let pending = repo.pending(address, network).await?;
+
+    Ok(WalletChangesResponse {
+        address: address.to_string(),
+        network: network.to_string(),
+        since_height: since,
+        tip_height: tip,
+        resync_required: resync_reason.is_some(),
+        resync_reason,
+        charms,
+        btc,
+        mempool: mempool_changes(pending, since_mempool_seq),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn received(txid: &str, height: i32) -> ReceivedCharm {
+        ReceivedCharm {
+            txid: txid.to_string(),
+            vout: 0,
+            app_id: "t/aa/1".to_string(),
+            asset_type: "token".to_string(),
+            amount: 25,
+            block_height: height,
+        }
+    }
+
+    fn spent(txid: &str, height: i32) -> SpentCharm {
+        SpentCharm {
+            txid: txid.to_string(),
+            vout: 0,
+            app_id: "t/aa/1".to_string(),
+            amount: 25,
+            spending_txid: Some("s1".to_string()),
+            spent_height: height,
+        }
+    }
+
+    fn added(txid: &str, value: i64, height: i32) -> AddedUtxo {
+        AddedUtxo {
+            txid: txid.to_string(),
+            vout: 1,
+            value,
+            block_height: height,
+        }
+    }
+
+    fn removed(txid: &str, value: i64, height: i32) -> RemovedUtxo {
+        RemovedUtxo {
+            txid: txid.to_string(),
+            vout: 1,
+            value,
+            spending_txid: "s1".to_string(),
+            spent_height: height,
+        }
+    }
+
+    fn tracked(tip: i32) -> SyncState {
+        SyncState {
+            tip: Some(tip),
+            horizon: Some(50),
+            synced_from: Some(90),
+            reorged: false,
+        }
+    }
+
+    /// r1 received at 100 and spent by s1 at 101, which returns change:
+    /// what the repository reads for each cursor, netted.
+    #[test]
+    fn a_receive_and_a_spend_net_to_minimal_deltas() {
+        // Cursor 99, tip 100: the receive.
+        let (charms, btc) = net(WindowChanges {
+            received: vec![received("r1", 100)],
+            added: vec![added("r1", 1000, 100)],
+            ..WindowChanges::default()
+        });
+        assert_eq!(charms.received, [received("r1", 100)]);
+        assert_eq!(btc.added, [added("r1", 1000, 100)]);
+        assert!(charms.spent.is_empty() && btc.removed.is_empty());
+
+        // Cursor 100, tip 101: only the spend and the change.
+        let (charms, btc) = net(WindowChanges {
+            spent: vec![spent("r1", 101)],
+            added: vec![added("s1", 400, 101)],
+            removed: vec![removed("r1", 1000, 101)],
+            ..WindowChanges::default()
+        });
+        assert!(charms.received.is_empty());
+        assert_eq!(charms.spent, [spent("r1", 101)]);
+        assert_eq!(btc.added, [added("s1", 400, 101)]);
+        assert_eq!(btc.removed, [removed("r1", 1000, 101)]);
+
+        // Cursor 99, tip 101: r1 came and went, only the change is left.
+        let (charms, btc) = net(WindowChanges {
+            received: vec![received("r1", 100)],
+            spent: vec![spent("r1", 101)],
+            added: vec![added("r1", 1000, 100), added("s1", 400, 101)],
+            removed: vec![removed("r1", 1000, 101)],
+        });
+        assert_eq!(charms, CharmChanges::default());
+        assert_eq!(btc.added, [added("s1", 400, 101)]);
+        assert!(btc.removed.is_empty());
+    }
+
+    #[test]
+    fn cursors_outside_the_kept_history_ask_for_a_resync() {
+        assert_eq!(resync_reason(100, &tracked(101)), None);
+        assert_eq!(resync_reason(101, &tracked(101)), None, "up to date");
+        assert_eq!(
+            resync_reason(102, &tracked(101)),
+            Some("since_height_ahead_of_tip")
+        );
+        assert_eq!(
+            resync_reason(89, &tracked(101)),
+            Some("history_unavailable"),
+            "before the address was tracked"
+        );
+        let pruned = SyncState {
+            horizon: Some(95),
+            ..tracked(101)
+        };
+        assert_eq!(resync_reason(94, &pruned), Some("history_unavailable"));
+        assert_eq!(resync_reason(95, &pruned), None);
+        let reorged = SyncState {
+            reorged: true,
+            ..tracked(101)
+        };
+        assert_eq!(resync_reason(100, &reorged), Some("since_height_reorged"));
+        let untracked = SyncState {
+            synced_from: None,
+            ..tracked(101)
+        };
+        assert_eq!(resync_reason(100, &untracked), Some("address_not_tracked"));
+    }
+
+    #[test]
+    fn an_unchanged_mempool_sends_nothing() {
+        let pending = || PendingItems {
+            utxos: vec![PendingUtxo {
+                txid: "m1".to_string(),
+                vout: 1,
+                value: 300,
+            }],
+            spends: vec![PendingSpend {
+                txid: "s1".to_string(),
+                vout: 1,
+                spending_txid: "m1".to_string(),
+            }],
+            ..PendingItems::default()
+        };
+        let seq = mempool_seq(&pending());
+        assert_ne!(seq, 0);
+        assert!(seq < 1 << 48);
+        assert_eq!(mempool_seq(&PendingItems::default()), 0);
+
+        let first = mempool_changes(pending(), None);
+        assert!(first.changed);
+        assert_eq!((first.utxos.len(), first.spends.len()), (1, 1));
+
+        let again = mempool_changes(pending(), Some(seq));
+        assert_eq!(
+            again,
+            MempoolChanges {
+                seq,
+                ..MempoolChanges::default()
+            }
+        );
+
+        // m1 confirmed: the set empties and says so.
+        let cleared = mempool_changes(PendingItems::default(), Some(seq));
+        assert!(cleared.changed);
+        assert_eq!(cleared.seq, 0);
+        assert!(cleared.utxos.is_empty() && cleared.spends.is_empty());
+    }
+}
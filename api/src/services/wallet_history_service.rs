@@ -0,0 +1,171 @@
+// Wallet history: merges the raw per-table events for an address into one
+// entry per transaction, with the field names Cast reads.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::db::repositories::wallet_history_repository::HistoryEvent;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CharmDelta {
+    pub app_id: String,
+    pub delta: i64,
+}
+
+/// One transaction touching the address. `value` is the net BTC change in
+/// sats (negative when the address paid out).
+#[derive(Debug, Serialize, PartialEq)]
+pub struct HistoryEntry {
+    pub txid: String,
+    pub value: i64,
+    pub confirmed: bool,
+    #[serde(rename = "blockHeight")]
+    pub block_height: Option<i32>,
+    #[serde(rename = "blockTime")]
+    pub block_time: Option<i64>,
+    pub charms: Vec<CharmDelta>,
+}
+
+#[derive(Default)]
+struct Acc {
+    tx_value: Option<i64>,
+    utxo_value: i64,
+    confirmed: bool,
+    block_height: Option<i32>,
+    block_time: Option<i64>,
+    charms: BTreeMap<String, i64>,
+}
+
+pub struct WalletHistoryService;
+
+impl WalletHistoryService {
+    /// Merge events into a chronological list: pending first, then newest
+    /// block first.
+    ///
+    /// BTC: `address_transactions` rows are authoritative for their txid
+    /// (provider seeding records amounts for spends we never saw as UTXOs);
+    /// otherwise the value is derived from `address_utxos` creations and
+    /// pending spends. Charm deltas are summed per app_id.
+    pub fn merge(events: Vec<HistoryEvent>) -> Vec<HistoryEntry> {
+        let mut by_txid: HashMap<String, Acc> = HashMap::new();
+        for e in events {
+            let acc = by_txid.entry(e.txid).or_default();
+            acc.confirmed |= e.confirmed;
+            acc.block_height = acc.block_height.max(e.block_height);
+            acc.block_time = acc.block_time.max(e.block_time);
+            match (e.source.as_str(), e.app_id) {
+                (_, Some(app_id)) => *acc.charms.entry(app_id).or_default() += e.delta,
+                ("tx", None) => *acc.tx_value.get_or_insert(0) += e.delta,
+                (_, None) => acc.utxo_value += e.delta,
+            }
+        }
+
+        let mut entries: Vec<HistoryEntry> = by_txid
+            .into_iter()
+            .map(|(txid, acc)| HistoryEntry {
+                txid,
+                value: acc.tx_value.unwrap_or(acc.utxo_value),
+                confirmed: acc.confirmed,
+                block_height: acc.block_height,
+                block_time: acc.block_time,
+                charms: acc
+                    .charms
+                    .into_iter()
+                    .filter(|(_, delta)| *delta != 0)
+                    .map(|(app_id, delta)| CharmDelta { app_id, delta })
+                    .collect(),
+            })
+            .collect();
+
+        entries.sort_by(|a, b| {
+            a.confirmed
+                .cmp(&b.confirmed)
+                .then(b.block_height.cmp(&a.block_height))
+                .then(b.block_time.cmp(&a.block_time))
+                .then(a.txid.cmp(&b.txid))
+        });
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(
+        txid: &str,
+        height: Option<i32>,
+        confirmed: bool,
+        app_id: Option<&str>,
+        delta: i64,
+        source: &str,
+    ) -> HistoryEvent {
+        HistoryEvent {
+            txid: txid.to_string(),
+            block_height: height,
+            block_time: None,
+            confirmed,
+            app_id: app_id.map(str::to_string),
+            delta,
+            source: source.to_string(),
+        }
+    }
+
+    /// receive (block 100) → spend (block 105) → pending send, as the
+    /// repository reports them after each step was indexed.
+    #[test]
+    fn merges_receive_spend_pending_lifecycle() {
+        let token = "t/aa/bb";
+        let nft = "n/aa/bb";
+        let events = vec![
+            // Receive: BTC seen by the indexer, plus two charm outputs.
+            event("recv", Some(100), true, None, 10_000, "tx"),
+            event("recv", Some(100), true, Some(token), 500, "charm"),
+            event("recv", Some(100), true, Some(nft), 1, "charm"),
+            // Spend: the token output moves on, 200 comes back as change.
+            event("spend", Some(105), true, None, -9_000, "tx"),
+            event("spend", Some(105), true, Some(token), -500, "charm"),
+            event("spend", Some(105), true, Some(token), 200, "charm"),
+            event("spend", Some(105), true, None, 1_000, "utxo"),
+            // Pending: a mempool tx spends the change output.
+            event("pending", None, false, None, -1_000, "utxo"),
+            event("pending", None, false, Some(token), -200, "charm"),
+        ];
+
+        let history = WalletHistoryService::merge(events);
+        let summary: Vec<_> = history
+            .iter()
+            .map(|h| (h.txid.as_str(), h.value, h.confirmed, h.block_height))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("pending", -1_000, false, None),
+                // address_transactions wins over the utxo-derived value.
+                ("spend", -9_000, true, Some(105)),
+                ("recv", 10_000, true, Some(100)),
+            ]
+        );
+        assert_eq!(
+            history[0].charms,
+            vec![CharmDelta { app_id: token.into(), delta: -200 }]
+        );
+        assert_eq!(
+            history[1].charms,
+            vec![CharmDelta { app_id: token.into(), delta: -300 }]
+        );
+        assert_eq!(
+            history[2].charms,
+            vec![
+                CharmDelta { app_id: nft.into(), delta: 1 },
+                CharmDelta { app_id: token.into(), delta: 500 },
+            ]
+        );
+
+        let json = serde_json::to_value(&history[1]).unwrap();
+        assert_eq!(json["blockHeight"], 105);
+        assert_eq!(json["value"], -9_000);
+        assert_eq!(json["confirmed"], true);
+    }
+}
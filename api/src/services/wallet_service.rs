@@ -1,7 +1,9 @@
 // Wallet service for Bitcoin RPC operations
 // Phase 1: Direct Bitcoin node access for wallet extension
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use bitcoincore_rpc::{Client, RpcApi};
 use serde::{Deserialize, Serialize};
@@ -9,7 +11,7 @@ use serde_json::Value;
 
 // --- Response types ---
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Utxo {
     pub txid: String,
     pub vout: u32,
@@ -104,6 +106,92 @@ pub struct AddressTxRecord {
     pub confirmations: i32,
 }
 
+// --- QuickNode transport ---
+
+/// How many times a QuickNode call is re-sent after a 429
+const QUICKNODE_MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// Never wait longer than this on a Retry-After header
+const QUICKNODE_MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+
+/// Process-wide QuickNode counters (requests, 429 responses, retries)
+#[derive(Debug)]
+pub struct QuickNodeCounters {
+    pub requests: AtomicU64,
+    pub rate_limited: AtomicU64,
+    pub retries: AtomicU64,
+}
+
+pub static QUICKNODE_COUNTERS: QuickNodeCounters = QuickNodeCounters {
+    requests: AtomicU64::new(0),
+    rate_limited: AtomicU64::new(0),
+    retries: AtomicU64::new(0),
+};
+
+impl QuickNodeCounters {
+    pub fn snapshot(&self) -> Value {
+        serde_json::json!({
+            "requests": self.requests.load(Ordering::Relaxed),
+            "rate_limited": self.rate_limited.load(Ordering::Relaxed),
+            "retries": self.retries.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// Parse a Retry-After header (delta-seconds or HTTP date)
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// POST a JSON-RPC body to QuickNode over the shared pooled client,
+/// re-sending on HTTP 429 after the server's Retry-After delay.
+async fn quicknode_post(
+    http_client: &reqwest::Client,
+    quicknode_url: &str,
+    body: &Value,
+    label: &str,
+) -> Result<Value, String> {
+    let mut attempt = 0;
+    loop {
+        QUICKNODE_COUNTERS.requests.fetch_add(1, Ordering::Relaxed);
+        let resp = http_client
+            .post(quicknode_url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| format!("{} failed: {}", label, e))?;
+
+        if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            QUICKNODE_COUNTERS.rate_limited.fetch_add(1, Ordering::Relaxed);
+            if attempt >= QUICKNODE_MAX_RATE_LIMIT_RETRIES {
+                return Err(format!("{} rate limited after {} retries", label, attempt));
+            }
+            let delay = retry_after(resp.headers())
+                .unwrap_or_else(|| Duration::from_millis(250 * 2_u64.pow(attempt)))
+                .min(QUICKNODE_MAX_RETRY_AFTER);
+            attempt += 1;
+            QUICKNODE_COUNTERS.retries.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("{}: 429, retry {} in {:?}", label, attempt, delay);
+            tokio::time::sleep(delay).await;
+            continue;
+        }
+
+        return resp
+            .json()
+            .await
+            .map_err(|e| format!("{} parse failed: {}", label, e));
+    }
+}
+
 // --- Service ---
 
 pub struct WalletService;
@@ -176,17 +264,7 @@ impl WalletService {
             "id": 1
         });
 
-        let resp = http_client
-            .post(quicknode_url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("QuickNode request failed: {}", e))?;
-
-        let data: Value = resp
-            .json()
-            .await
-            .map_err(|e| format!("QuickNode response parse failed: {}", e))?;
+        let data = quicknode_post(http_client, quicknode_url, &body, "QuickNode request").await?;
 
         if let Some(err) = data.get("error").filter(|e| !e.is_null()) {
             return Err(format!("QuickNode error: {}", err));
@@ -228,17 +306,7 @@ impl WalletService {
             "id": 1
         });
 
-        let resp = http_client
-            .post(quicknode_url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("QuickNode request failed: {}", e))?;
-
-        let data: Value = resp
-            .json()
-            .await
-            .map_err(|e| format!("QuickNode response parse failed: {}", e))?;
+        let data = quicknode_post(http_client, quicknode_url, &body, "QuickNode request").await?;
 
         if let Some(err) = data.get("error").filter(|e| !e.is_null()) {
             return Err(format!("QuickNode error: {}", err));
@@ -254,17 +322,7 @@ impl WalletService {
             "id": 2
         });
 
-        let resp2 = http_client
-            .post(quicknode_url)
-            .json(&body2)
-            .send()
-            .await
-            .map_err(|e| format!("QuickNode request failed: {}", e))?;
-
-        let data2: Value = resp2
-            .json()
-            .await
-            .map_err(|e| format!("QuickNode response parse failed: {}", e))?;
+        let data2 = quicknode_post(http_client, quicknode_url, &body2, "QuickNode request").await?;
 
         let hash = data2["result"].as_str().unwrap_or("").to_string();
 
@@ -275,6 +333,31 @@ impl WalletService {
         })
     }
 
+    /// Raw hex of a transaction via QuickNode getrawtransaction
+    pub async fn get_raw_transaction_hex_quicknode(
+        http_client: &reqwest::Client,
+        quicknode_url: &str,
+        txid: &str,
+    ) -> Result<String, String> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "getrawtransaction",
+            "params": [txid, false],
+            "id": 1
+        });
+
+        let data = quicknode_post(http_client, quicknode_url, &body, "QuickNode request").await?;
+
+        if let Some(err) = data.get("error").filter(|e| !e.is_null()) {
+            return Err(format!("QuickNode error: {}", err));
+        }
+
+        data["result"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| "QuickNode getrawtransaction returned no hex".to_string())
+    }
+
     /// Get address info via QuickNode bb_getAddress (UTXOs + transaction history)
     /// Returns (utxos, transactions) tuple for seeding.
     pub async fn get_address_quicknode(
@@ -302,17 +385,8 @@ impl WalletService {
                 "id": 1
             });
 
-            let resp = http_client
-                .post(quicknode_url)
-                .json(&body)
-                .send()
-                .await
-                .map_err(|e| format!("QuickNode bb_getAddress failed: {}", e))?;
-
-            let data: Value = resp
-                .json()
-                .await
-                .map_err(|e| format!("QuickNode bb_getAddress parse failed: {}", e))?;
+            let data =
+                quicknode_post(http_client, quicknode_url, &body, "QuickNode bb_getAddress").await?;
 
             if let Some(err) = data.get("error").filter(|e| !e.is_null()) {
                 return Err(format!("QuickNode bb_getAddress error: {}", err));
@@ -587,6 +661,22 @@ impl WalletService {
         .map_err(|e| format!("Task join error: {}", e))?
     }
 
+    /// Raw hex of a transaction (mempool, or confirmed with txindex)
+    pub async fn get_raw_transaction_hex(client: Arc<Client>, txid: &str) -> Result<String, String> {
+        let txid_str = txid.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            client
+                .call::<String>(
+                    "getrawtransaction",
+                    &[serde_json::json!(txid_str), serde_json::json!(false)],
+                )
+                .map_err(|e| format!("getrawtransaction failed: {}", e))
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
     /// POST /wallet/broadcast — Broadcast a signed transaction
     pub async fn broadcast_transaction(
         client: Arc<Client>,
@@ -0,0 +1,984 @@
+// In-memory stores and app state for service and handler unit tests.
+// Repositories without a fake sit on a pool pointed at a closed port, so
+// any query they run fails like a database outage would.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use charms_core::AssetType;
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbErr, SqlxPostgresConnector,
+};
+
+use crate::config::ApiConfig;
+use crate::db::repositories::admin_audit_repository::{
+    AuditEntry, AuditFilter, AuditOutcome, NewAuditEntry,
+};
+use crate::db::repositories::asset_repository::CollectionSummary;
+use crate::db::repositories::charm_repository::{
+    AddressHolding, BalanceAt, CharmFilter, ParserRevisionCount, PendingSpend,
+};
+use crate::db::repositories::likes_repository::LikeChange;
+use crate::db::repositories::moderation_repository::is_hidden;
+use crate::db::repositories::stats_holders_repository::HolderCount;
+use crate::db::stores::{AssetStore, AuditStore, CharmStore, LikesStore, StatsHoldersStore};
+use crate::db::{DbError, Repositories};
+use crate::entity::{assets, charms, stats_holders};
+use crate::handlers::{AppState, MaestroCircuitBreaker};
+use crate::models::{AssetSort, PaginationParams};
+use crate::services::cache::MemoryCache;
+use crate::services::rpc_clients::RpcClients;
+use crate::services::scan_cache::ScanCache;
+use crate::services::shutdown::Lifecycle;
+use crate::services::tip_cache::TipCache;
+use crate::services::tx_hex_cache::TxHexCache;
+
+type AssetResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+pub fn config() -> ApiConfig {
+    ApiConfig {
+        host: "127.0.0.1".into(),
+        port: 8000,
+        database_url: String::new(),
+        enable_bitcoin_testnet4: true,
+        enable_bitcoin_mainnet: true,
+        enable_cardano: false,
+        bitcoin_testnet4_rpc_host: String::new(),
+        bitcoin_testnet4_rpc_port: String::new(),
+        bitcoin_testnet4_rpc_username: String::new(),
+        bitcoin_testnet4_rpc_password: String::new(),
+        bitcoin_mainnet_rpc_host: String::new(),
+        bitcoin_mainnet_rpc_port: String::new(),
+        bitcoin_mainnet_rpc_username: String::new(),
+        bitcoin_mainnet_rpc_password: String::new(),
+        bitcoin_mainnet_quicknode_endpoint: "https://main.qn.example".into(),
+        bitcoin_testnet4_quicknode_endpoint: "https://t4.qn.example".into(),
+        wallet_rpc_timeout_secs: 3,
+        wallet_scan_timeout_secs: 60,
+        wallet_scan_cache_ttl_secs: 30,
+        wallet_reorg_risk_confirmations: 3,
+        maestro_api_key: String::new(),
+        admin_api_token: None,
+        monitor_ttl_days: 90,
+        balance_at_max_lookback_blocks: 52_560,
+        public_base_url: None,
+        shutdown_drain_secs: 25,
+        redis_url: None,
+    }
+}
+
+/// Repositories on an unreachable database, with empty fake stores.
+/// Replace a store field to seed it. Needs a Tokio runtime.
+pub fn repositories() -> Repositories {
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(200))
+        .connect_lazy("postgres://127.0.0.1:1/unreachable")
+        .expect("lazy pool");
+    let conn = SqlxPostgresConnector::from_sqlx_postgres_pool(pool);
+    let mut repos = Repositories::new(conn, 90);
+    repos.charm = Arc::new(FakeCharms::default());
+    repos.likes = Arc::new(FakeLikes::default());
+    repos.asset_repository = Arc::new(FakeAssets::default());
+    repos.stats_holders = Arc::new(FakeStatsHolders::default());
+    repos.admin_audit = Arc::new(FakeAudit::default());
+    repos
+}
+
+/// App state over `repositories`. Both networks are enabled, with RPC
+/// clients pointing at closed ports.
+pub fn app_state(repositories: Repositories) -> AppState {
+    let rpc_clients = RpcClients::new([("mainnet", 1), ("testnet4", 2)].map(|(network, port)| {
+        (
+            network.to_string(),
+            format!("http://127.0.0.1:{}", port),
+            bitcoincore_rpc::Auth::None,
+        )
+    }));
+    AppState {
+        repositories: Arc::new(repositories),
+        config: config(),
+        scan_semaphore: Arc::new(tokio::sync::Semaphore::new(1)),
+        quicknode_semaphore: Arc::new(tokio::sync::Semaphore::new(1)),
+        http_client: reqwest::Client::new(),
+        rpc_clients: Arc::new(rpc_clients),
+        maestro_cb: Arc::new(MaestroCircuitBreaker::new()),
+        scan_cache: Arc::new(ScanCache::new(
+            Arc::new(MemoryCache::new()),
+            Duration::from_secs(30),
+        )),
+        tx_hex_cache: Arc::new(TxHexCache::new(Duration::from_secs(120))),
+        tip_cache: Arc::new(TipCache::new(
+            Arc::new(MemoryCache::new()),
+            Duration::from_secs(60),
+        )),
+        lifecycle: Arc::new(Lifecycle::default()),
+    }
+}
+
+/// A confirmed mainnet token charm at height 100.
+pub fn charm(txid: &str, app_id: &str) -> charms::Model {
+    charms::Model {
+        txid: txid.to_string(),
+        vout: 0,
+        block_height: Some(100),
+        data: serde_json::json!({}),
+        date_created: chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap(),
+        asset_type: "token".to_string(),
+        blockchain: "bitcoin".to_string(),
+        network: "mainnet".to_string(),
+        address: None,
+        spent: false,
+        app_id: app_id.to_string(),
+        amount: 1,
+        mempool_detected_at: None,
+        tags: None,
+        verified: true,
+        block_hash: None,
+        tx_ordinal: None,
+        is_placeholder: false,
+        spending_txid: None,
+        indexer_version: None,
+        parser_revision: None,
+        reindex_run_id: None,
+        operation: None,
+        moderation_status: "visible".to_string(),
+        block_time: None,
+        spent_height: None,
+    }
+}
+
+/// A mainnet asset with no metadata.
+pub fn asset(id: i32, app_id: &str, asset_type: &str) -> assets::Model {
+    let created = chrono::DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+    assets::Model {
+        id,
+        app_id: app_id.to_string(),
+        txid: format!("tx{}", id),
+        vout_index: 0,
+        charm_id: app_id.to_string(),
+        block_height: 100,
+        date_created: created,
+        data: serde_json::json!({}),
+        asset_type: asset_type.to_string(),
+        blockchain: "bitcoin".to_string(),
+        network: "mainnet".to_string(),
+        created_at: created,
+        updated_at: created,
+        name: None,
+        symbol: None,
+        description: None,
+        image_url: None,
+        total_supply: None,
+        decimals: 8,
+        is_reference_nft: false,
+        cardano_policy_id: None,
+        cardano_asset_name: None,
+        cardano_fingerprint: None,
+        collection: None,
+        offchain_metadata: None,
+        deploy_txid: None,
+        deploy_block_height: None,
+        deployer_address: None,
+        moderation_status: "visible".to_string(),
+        locked_supply: rust_decimal::Decimal::ZERO,
+    }
+}
+
+/// One page of `rows`, 1-based like the repositories.
+fn page<T: Clone>(rows: &[T], pagination: &PaginationParams) -> Vec<T> {
+    let offset = pagination.page.saturating_sub(1) * pagination.limit;
+    rows.iter()
+        .skip(offset as usize)
+        .take(pagination.limit as usize)
+        .cloned()
+        .collect()
+}
+
+/// Whether a row passes the listings' moderation filter.
+fn listed(moderation_status: &str, include_hidden: bool) -> bool {
+    include_hidden || !is_hidden(moderation_status)
+}
+
+/// Charms in insertion order. Listings skip placeholders and, unless asked,
+/// hidden rows like the repository does; `failing()` errors on every call.
+#[derive(Default)]
+pub struct FakeCharms {
+    pub charms: Vec<charms::Model>,
+    /// Mempool spends keyed by spent (txid, vout), for every network.
+    pub pending_spends: HashMap<(String, i32), PendingSpend>,
+    pub fail: bool,
+}
+
+impl FakeCharms {
+    pub fn new(charms: Vec<charms::Model>) -> Self {
+        Self {
+            charms,
+            ..Default::default()
+        }
+    }
+
+    pub fn failing() -> Self {
+        Self {
+            fail: true,
+            ..Default::default()
+        }
+    }
+
+    /// Record `spending_txid` as spending `txid:vout` in mempool.
+    pub fn with_pending_spend(mut self, txid: &str, vout: i32, spending_txid: &str) -> Self {
+        self.pending_spends.insert(
+            (txid.to_string(), vout),
+            PendingSpend {
+                spending_txid: spending_txid.to_string(),
+                first_seen_at: chrono::DateTime::from_timestamp(1_760_000_000, 0).unwrap(),
+            },
+        );
+        self
+    }
+
+    fn select(&self, f: impl Fn(&charms::Model) -> bool) -> Result<Vec<charms::Model>, DbError> {
+        if self.fail {
+            return Err(DbError::QueryError("connection refused".to_string()));
+        }
+        Ok(self.charms.iter().filter(|c| f(c)).cloned().collect())
+    }
+
+    fn networks(&self, f: impl Fn(&charms::Model) -> bool) -> Result<Vec<String>, DbError> {
+        let mut networks: Vec<String> = self.select(f)?.into_iter().map(|c| c.network).collect();
+        networks.sort();
+        networks.dedup();
+        Ok(networks)
+    }
+
+    fn list(
+        &self,
+        pagination: &PaginationParams,
+        f: impl Fn(&charms::Model) -> bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError> {
+        let rows = self.select(|c| !c.is_placeholder && f(c))?;
+        Ok((page(&rows, pagination), rows.len() as u64))
+    }
+}
+
+#[async_trait]
+impl CharmStore for FakeCharms {
+    async fn get_by_txid(
+        &self,
+        txid: &str,
+        network: &str,
+    ) -> Result<Option<charms::Model>, DbError> {
+        Ok(self
+            .select(|c| c.txid == txid && c.network == network)?
+            .into_iter()
+            .next())
+    }
+
+    async fn get_by_txids(
+        &self,
+        txids: &[String],
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        self.select(|c| txids.contains(&c.txid) && c.network == network)
+    }
+
+    async fn get_all(&self) -> Result<Vec<charms::Model>, DbError> {
+        self.select(|c| !c.is_placeholder)
+    }
+
+    async fn get_all_paginated(
+        &self,
+        pagination: &PaginationParams,
+        filter: &CharmFilter,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError> {
+        self.list(pagination, |c| {
+            filter.matches(c) && listed(&c.moderation_status, include_hidden)
+        })
+    }
+
+    async fn get_all_paginated_by_network(
+        &self,
+        pagination: &PaginationParams,
+        network: &str,
+        filter: &CharmFilter,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError> {
+        self.list(pagination, |c| {
+            c.network == network
+                && filter.matches(c)
+                && listed(&c.moderation_status, include_hidden)
+        })
+    }
+
+    async fn find_by_asset_type(&self, asset_type: &str) -> Result<Vec<charms::Model>, DbError> {
+        self.select(|c| c.asset_type == asset_type)
+    }
+
+    async fn find_by_asset_type_paginated(
+        &self,
+        asset_type: &str,
+        network: &str,
+        pagination: &PaginationParams,
+        include_hidden: bool,
+    ) -> Result<(Vec<charms::Model>, u64), DbError> {
+        self.list(pagination, |c| {
+            c.asset_type == asset_type
+                && c.network == network
+                && listed(&c.moderation_status, include_hidden)
+        })
+    }
+
+    async fn find_by_charmid(
+        &self,
+        charmid: &str,
+        network: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        self.select(|c| {
+            c.app_id == charmid
+                && c.network == network
+                && listed(&c.moderation_status, include_hidden)
+        })
+    }
+
+    async fn find_by_spending_txid(
+        &self,
+        spending_txid: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        self.select(|c| c.spending_txid.as_deref() == Some(spending_txid) && c.network == network)
+    }
+
+    async fn balance_at(
+        &self,
+        address: &str,
+        app_id: &str,
+        network: &str,
+        height: i32,
+    ) -> Result<BalanceAt, DbError> {
+        let received = self.select(|c| {
+            c.address.as_deref() == Some(address)
+                && c.app_id == app_id
+                && c.network == network
+                && !c.is_placeholder
+                && c.block_height.is_some_and(|h| h <= height)
+        })?;
+        let mut balance = BalanceAt::default();
+        for c in received {
+            match (c.spent, c.spent_height) {
+                (true, Some(spent)) if spent <= height => {}
+                (true, None) => balance.unknown += c.amount,
+                _ => balance.held += c.amount,
+            }
+        }
+        Ok(balance)
+    }
+
+    async fn networks_for_txid(
+        &self,
+        txid: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<String>, DbError> {
+        self.networks(|c| c.txid == txid && listed(&c.moderation_status, include_hidden))
+    }
+
+    async fn networks_for_charmid(
+        &self,
+        charmid: &str,
+        include_hidden: bool,
+    ) -> Result<Vec<String>, DbError> {
+        self.networks(|c| c.app_id == charmid && listed(&c.moderation_status, include_hidden))
+    }
+
+    async fn find_by_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        self.select(|c| c.address.as_deref() == Some(address) && c.network == network && !c.spent)
+    }
+
+    async fn holdings_by_address(
+        &self,
+        address: &str,
+        network: &str,
+        filter: &CharmFilter,
+    ) -> Result<Vec<AddressHolding>, DbError> {
+        let mut holdings: BTreeMap<String, AddressHolding> = BTreeMap::new();
+        for c in self.find_by_address(address, network).await? {
+            if !filter.matches(&c) {
+                continue;
+            }
+            let holding = holdings
+                .entry(c.app_id.clone())
+                .or_insert_with(|| AddressHolding {
+                    app_id: c.app_id.clone(),
+                    amount: 0,
+                    utxo_count: 0,
+                    first_block: None,
+                    last_block: None,
+                    name: None,
+                    symbol: None,
+                    image_url: None,
+                });
+            holding.amount += c.amount;
+            holding.utxo_count += 1;
+            holding.first_block = holding.first_block.into_iter().chain(c.block_height).min();
+            holding.last_block = holding.last_block.into_iter().chain(c.block_height).max();
+        }
+        let mut holdings: Vec<_> = holdings.into_values().collect();
+        holdings.sort_by_key(|h| std::cmp::Reverse(h.amount));
+        Ok(holdings)
+    }
+
+    async fn get_charm_numbers_by_type(
+        &self,
+        asset_type: Option<&str>,
+    ) -> Result<Vec<String>, DbError> {
+        Ok(self
+            .select(|c| !c.is_placeholder && asset_type.is_none_or(|t| c.asset_type == t))?
+            .into_iter()
+            .map(|c| c.app_id)
+            .collect())
+    }
+
+    async fn get_unspent_charms_by_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<Vec<charms::Model>, DbError> {
+        self.find_by_address(address, network).await
+    }
+
+    async fn get_pending_spends_for_outpoints(
+        &self,
+        outpoints: &[(String, i32)],
+        _network: &str,
+    ) -> Result<HashMap<(String, i32), PendingSpend>, DbError> {
+        self.select(|_| false)?;
+        Ok(outpoints
+            .iter()
+            .filter_map(|o| self.pending_spends.get(o).map(|s| (o.clone(), s.clone())))
+            .collect())
+    }
+
+    async fn get_sibling_app_ids_for_address(
+        &self,
+        address: &str,
+        network: &str,
+    ) -> Result<HashMap<(String, i32), Vec<String>>, DbError> {
+        let owned: HashSet<(String, i32)> = self
+            .find_by_address(address, network)
+            .await?
+            .into_iter()
+            .map(|c| (c.txid, c.vout))
+            .collect();
+        let mut map: HashMap<(String, i32), Vec<String>> = HashMap::new();
+        for c in self.select(|c| c.network == network)? {
+            let key = (c.txid, c.vout);
+            if owned.contains(&key) {
+                map.entry(key).or_default().push(c.app_id);
+            }
+        }
+        for app_ids in map.values_mut() {
+            app_ids.sort();
+        }
+        Ok(map)
+    }
+
+    async fn count_by_parser_revision(
+        &self,
+        network: Option<&str>,
+    ) -> Result<Vec<ParserRevisionCount>, DbError> {
+        let mut counts: Vec<ParserRevisionCount> = Vec::new();
+        for c in self.select(|c| network.is_none_or(|n| c.network == n))? {
+            match counts.iter_mut().find(|r| {
+                r.parser_revision == c.parser_revision && r.indexer_version == c.indexer_version
+            }) {
+                Some(row) => row.charms += 1,
+                None => counts.push(ParserRevisionCount {
+                    parser_revision: c.parser_revision,
+                    indexer_version: c.indexer_version,
+                    charms: 1,
+                }),
+            }
+        }
+        counts.sort_by(|a, b| {
+            (a.parser_revision, &a.indexer_version).cmp(&(b.parser_revision, &b.indexer_version))
+        });
+        Ok(counts)
+    }
+}
+
+/// Likes keyed by (charm_id, user_id) with the time they were given.
+/// Seeded and added likes are dated now unless `with_like_at` says otherwise.
+#[derive(Default)]
+pub struct FakeLikes {
+    pub likes: Mutex<HashMap<(String, i32), chrono::DateTime<chrono::Utc>>>,
+}
+
+impl FakeLikes {
+    pub fn new(likes: &[(&str, i32)]) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            likes: Mutex::new(
+                likes
+                    .iter()
+                    .map(|(c, u)| ((c.to_string(), *u), now))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Record a like given at `at`.
+    pub fn with_like_at(
+        self,
+        charm_id: &str,
+        user_id: i32,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        self.likes
+            .lock()
+            .unwrap()
+            .insert((charm_id.to_string(), user_id), at);
+        self
+    }
+
+    fn count(&self, charm_id: &str) -> i64 {
+        self.likes
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|(c, _)| c == charm_id)
+            .count() as i64
+    }
+}
+
+#[async_trait]
+impl LikesStore for FakeLikes {
+    async fn add_like(&self, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr> {
+        let changed = {
+            let mut likes = self.likes.lock().unwrap();
+            let key = (charm_id.to_string(), user_id);
+            let absent = !likes.contains_key(&key);
+            if absent {
+                likes.insert(key, chrono::Utc::now());
+            }
+            absent
+        };
+        Ok(LikeChange {
+            changed,
+            likes_count: self.count(charm_id),
+        })
+    }
+
+    async fn remove_like(&self, charm_id: &str, user_id: i32) -> Result<LikeChange, DbErr> {
+        let changed = self
+            .likes
+            .lock()
+            .unwrap()
+            .remove(&(charm_id.to_string(), user_id))
+            .is_some();
+        Ok(LikeChange {
+            changed,
+            likes_count: self.count(charm_id),
+        })
+    }
+
+    async fn get_likes_count(&self, charm_id: &str) -> Result<i64, DbErr> {
+        Ok(self.count(charm_id))
+    }
+
+    async fn has_user_liked(&self, charm_id: &str, user_id: i32) -> Result<bool, DbErr> {
+        Ok(self
+            .likes
+            .lock()
+            .unwrap()
+            .contains_key(&(charm_id.to_string(), user_id)))
+    }
+
+    async fn get_likes_counts_batch(
+        &self,
+        charm_ids: &[String],
+    ) -> Result<HashMap<String, i64>, DbErr> {
+        Ok(charm_ids
+            .iter()
+            .map(|c| (c.clone(), self.count(c)))
+            .filter(|(_, n)| *n > 0)
+            .collect())
+    }
+
+    async fn get_user_likes_batch(
+        &self,
+        charm_ids: &[String],
+        user_id: i32,
+    ) -> Result<HashSet<String>, DbErr> {
+        let likes = self.likes.lock().unwrap();
+        Ok(charm_ids
+            .iter()
+            .filter(|c| likes.contains_key(&((*c).clone(), user_id)))
+            .cloned()
+            .collect())
+    }
+
+    async fn count_since(
+        &self,
+        app_id: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DbErr> {
+        Ok(self
+            .likes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((c, _), at)| c == app_id && **at >= since)
+            .count() as i64)
+    }
+}
+
+/// Assets in insertion order; listings skip hidden rows unless asked.
+/// `failing()` errors on every call.
+#[derive(Default)]
+pub struct FakeAssets {
+    pub assets: Vec<assets::Model>,
+    pub fail: bool,
+}
+
+impl FakeAssets {
+    pub fn new(assets: Vec<assets::Model>) -> Self {
+        Self {
+            assets,
+            fail: false,
+        }
+    }
+
+    pub fn failing() -> Self {
+        Self {
+            assets: vec![],
+            fail: true,
+        }
+    }
+
+    fn select(&self, f: impl Fn(&assets::Model) -> bool) -> AssetResult<Vec<assets::Model>> {
+        if self.fail {
+            return Err("connection refused".into());
+        }
+        Ok(self.assets.iter().filter(|a| f(a)).cloned().collect())
+    }
+
+    fn matches(
+        a: &assets::Model,
+        asset_type: Option<&str>,
+        network: Option<&str>,
+        include_hidden: bool,
+    ) -> bool {
+        asset_type.is_none_or(|t| a.asset_type == t)
+            && network.is_none_or(|n| a.network == n)
+            && listed(&a.moderation_status, include_hidden)
+    }
+}
+
+#[async_trait]
+impl AssetStore for FakeAssets {
+    /// Insertion order whatever `sort` asks for; ordering is covered by the
+    /// `AssetRepository` DB tests.
+    async fn find_paginated(
+        &self,
+        asset_type: Option<&str>,
+        network: Option<&str>,
+        _sort: AssetSort,
+        limit: u64,
+        offset: u64,
+        include_hidden: bool,
+    ) -> AssetResult<Vec<assets::Model>> {
+        Ok(self
+            .select(|a| Self::matches(a, asset_type, network, include_hidden))?
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn count_assets(
+        &self,
+        asset_type: Option<&str>,
+        network: Option<&str>,
+        include_hidden: bool,
+    ) -> AssetResult<u64> {
+        Ok(self
+            .select(|a| Self::matches(a, asset_type, network, include_hidden))?
+            .len() as u64)
+    }
+
+    async fn find_by_id(&self, id: i32) -> AssetResult<Option<assets::Model>> {
+        Ok(self.select(|a| a.id == id)?.into_iter().next())
+    }
+
+    async fn find_by_app_id(
+        &self,
+        app_id: &str,
+        network: &str,
+    ) -> AssetResult<Option<assets::Model>> {
+        Ok(self
+            .select(|a| a.app_id == app_id && a.network == network)?
+            .into_iter()
+            .next())
+    }
+
+    async fn find_by_app_ids(
+        &self,
+        app_ids: Vec<String>,
+        network: &str,
+    ) -> AssetResult<Vec<assets::Model>> {
+        self.select(|a| app_ids.contains(&a.app_id) && a.network == network)
+    }
+
+    async fn find_by_asset_type(&self, asset_type: &str) -> AssetResult<Vec<assets::Model>> {
+        self.select(|a| a.asset_type == asset_type)
+    }
+
+    async fn find_by_network(&self, network: &str) -> AssetResult<Vec<assets::Model>> {
+        self.select(|a| a.network == network)
+    }
+
+    async fn find_reference_nft_by_hash(
+        &self,
+        hash: &str,
+        network: &str,
+    ) -> AssetResult<Option<assets::Model>> {
+        let prefix = format!("n/{}/", hash);
+        Ok(self
+            .select(|a| a.app_id.starts_with(&prefix) && a.network == network)?
+            .into_iter()
+            .next())
+    }
+
+    async fn find_reference_nft_by_vk(&self, vk: &str) -> AssetResult<Option<assets::Model>> {
+        let suffix = format!("/{}", vk);
+        let mut nfts =
+            self.select(|a| a.app_id.starts_with("n/") && a.app_id.ends_with(&suffix))?;
+        nfts.sort_by_key(|a| a.network != "mainnet");
+        Ok(nfts.into_iter().next())
+    }
+
+    async fn get_max_total_supply_by_prefix(
+        &self,
+        base_app_id: &str,
+        network: &str,
+    ) -> AssetResult<Option<rust_decimal::Decimal>> {
+        Ok(self
+            .select(|a| a.app_id.starts_with(base_app_id) && a.network == network)?
+            .into_iter()
+            .filter_map(|a| a.total_supply)
+            .max())
+    }
+
+    async fn find_collections(
+        &self,
+        _network: &str,
+        _limit: u64,
+        _offset: u64,
+    ) -> AssetResult<(Vec<CollectionSummary>, u64)> {
+        self.select(|_| false).map(|_| (vec![], 0))
+    }
+
+    async fn find_by_collection(
+        &self,
+        collection: &str,
+        network: &str,
+        limit: u64,
+        offset: u64,
+        include_hidden: bool,
+    ) -> AssetResult<(Vec<assets::Model>, u64)> {
+        let rows = self.select(|a| {
+            a.asset_type == AssetType::Nft.as_str()
+                && a.network == network
+                && a.collection.as_deref() == Some(collection)
+                && listed(&a.moderation_status, include_hidden)
+        })?;
+        let total = rows.len() as u64;
+        Ok((
+            rows.into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect(),
+            total,
+        ))
+    }
+}
+
+/// Holder rows matched by app_id prefix, like the repository.
+#[derive(Default)]
+pub struct FakeStatsHolders {
+    pub holders: Vec<stats_holders::Model>,
+}
+
+#[async_trait]
+impl StatsHoldersStore for FakeStatsHolders {
+    async fn get_holders_by_app_id(
+        &self,
+        app_id: &str,
+    ) -> Result<Vec<stats_holders::Model>, DbError> {
+        let mut holders: Vec<_> = self
+            .holders
+            .iter()
+            .filter(|h| h.app_id.starts_with(app_id))
+            .cloned()
+            .collect();
+        holders.sort_by_key(|h| std::cmp::Reverse(h.total_amount));
+        Ok(holders)
+    }
+
+    async fn top_by_holders(&self, network: &str, limit: u64) -> Result<Vec<HolderCount>, DbError> {
+        let mut counts: BTreeMap<&str, i64> = BTreeMap::new();
+        for h in &self.holders {
+            if h.network == network && h.total_amount > 0 {
+                *counts.entry(h.app_id.as_str()).or_default() += 1;
+            }
+        }
+        let mut top: Vec<HolderCount> = counts
+            .into_iter()
+            .map(|(app_id, holders)| HolderCount {
+                app_id: app_id.to_string(),
+                holders,
+            })
+            .collect();
+        top.sort_by_key(|c| std::cmp::Reverse(c.holders));
+        top.truncate(limit as usize);
+        Ok(top)
+    }
+}
+
+/// Audit rows in insertion order, ids starting at 1; `failing()` refuses
+/// to open rows.
+#[derive(Default)]
+pub struct FakeAudit {
+    pub entries: Mutex<Vec<AuditEntry>>,
+    pub fail: bool,
+}
+
+impl FakeAudit {
+    pub fn failing() -> Self {
+        Self {
+            fail: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl AuditStore for FakeAudit {
+    async fn begin(&self, entry: &NewAuditEntry) -> Result<i64, DbError> {
+        if self.fail {
+            return Err(DbError::QueryError("connection refused".to_string()));
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let id = entries.len() as i64 + 1;
+        entries.push(AuditEntry {
+            id,
+            action: entry.action.clone(),
+            parameters: entry.parameters.clone(),
+            key_fingerprint: entry.key_fingerprint.clone(),
+            source_ip: entry.source_ip.clone(),
+            requested_by: entry.requested_by.clone(),
+            created_at: chrono::Utc::now(),
+            finished_at: None,
+            success: None,
+            status_code: None,
+            result: None,
+        });
+        Ok(id)
+    }
+
+    async fn finish(&self, id: i64, outcome: &AuditOutcome) -> Result<(), DbError> {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+            entry.finished_at = Some(chrono::Utc::now());
+            entry.success = Some(outcome.success);
+            entry.status_code = Some(i32::from(outcome.status_code));
+            entry.result = Some(outcome.result.clone());
+        }
+        Ok(())
+    }
+
+    async fn list(&self, filter: &AuditFilter, limit: u64) -> Result<Vec<AuditEntry>, DbError> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .iter()
+            .rev()
+            .filter(|e| filter.matches(e))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+/// The indexer's integration-test schema, the base the migrations replay on;
+/// `database/init/01-schema.sql` predates too many of them to seed the chain.
+const BASE_SCHEMA: &str = include_str!("../../indexer/tests/fixtures/schema.sql");
+
+/// A throwaway Postgres schema holding the tables production runs, for
+/// repository tests against `TEST_DATABASE_URL`: the indexer fixture with
+/// every file under `database/migrations` replayed on top, in order.
+pub struct TestSchema {
+    pub conn: DatabaseConnection,
+    name: String,
+}
+
+impl TestSchema {
+    /// Create `{prefix}_{pid}` and connect to it with a pool of
+    /// `max_connections`.
+    pub async fn new(prefix: &str, max_connections: u32) -> Self {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL");
+        let name = format!("{}_{}", prefix, std::process::id());
+        // One connection, so the `search_path` set here holds for every file.
+        let mut opts = ConnectOptions::new(url.clone());
+        opts.max_connections(1);
+        let setup = Database::connect(opts).await.expect("connect");
+        setup
+            .execute_unprepared(&format!(
+                "DROP SCHEMA IF EXISTS {name} CASCADE; CREATE SCHEMA {name}; \
+                 SET search_path TO {name}; {BASE_SCHEMA} \
+                 CREATE TABLE IF NOT EXISTS seaql_migrations ( \
+                     version VARCHAR NOT NULL PRIMARY KEY, \
+                     applied_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP);"
+            ))
+            .await
+            .expect("base schema");
+
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../database/migrations");
+        let mut migrations: Vec<_> = std::fs::read_dir(dir)
+            .expect("migrations")
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+            .collect();
+        migrations.sort();
+        for path in migrations {
+            let sql = std::fs::read_to_string(&path).unwrap();
+            setup
+                .execute_unprepared(&sql)
+                .await
+                .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        }
+        setup.close().await.unwrap();
+
+        let mut opts = ConnectOptions::new(url);
+        opts.max_connections(max_connections)
+            .set_schema_search_path(name.clone());
+        let conn = Database::connect(opts).await.expect("connect");
+        Self { conn, name }
+    }
+
+    /// Drop the schema and everything in it.
+    pub async fn drop_schema(self) {
+        self.conn
+            .execute_unprepared(&format!("DROP SCHEMA {} CASCADE", self.name))
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,315 @@
+//! App id classification.
+//!
+//! An app_id is `<tag>/<identity>/<vk>`; the one-character tag says what the
+//! app is. Only NFTs and tokens are assets with holders and supply; token
+//! balances are consolidated under the NFT app_id that shares their
+//! identity and vk. Dapps (`B/`) and data apps (`d/`) are both stored as
+//! `dapp`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::asset_type::AssetType;
+
+/// What an app_id's tag says about the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppKind {
+    /// `n/` — NFT, also the reference anchor for a token's metadata.
+    Nft,
+    /// `t/` — fungible token.
+    Token,
+    /// `B/` — dapp.
+    Dapp,
+    /// `b/` — DEX (Charms Cast) operator.
+    Dex,
+    /// `d/` — data app.
+    Data,
+    /// `c/` — contract, the proxy validator used when beaming.
+    Contract,
+    /// Any other tag, or a string that isn't `<tag>/…`.
+    Other,
+}
+
+impl AppKind {
+    pub const ALL: [AppKind; 7] = [
+        AppKind::Nft,
+        AppKind::Token,
+        AppKind::Dapp,
+        AppKind::Dex,
+        AppKind::Data,
+        AppKind::Contract,
+        AppKind::Other,
+    ];
+
+    /// Classify from the app tag character (`charms_data::App::tag`).
+    pub fn from_tag(tag: char) -> Self {
+        match tag {
+            'n' => AppKind::Nft,
+            't' => AppKind::Token,
+            'B' => AppKind::Dapp,
+            'b' => AppKind::Dex,
+            'd' => AppKind::Data,
+            'c' => AppKind::Contract,
+            _ => AppKind::Other,
+        }
+    }
+
+    /// Classify an app_id string by its `<tag>/` prefix, without parsing
+    /// it into an `AppId`.
+    pub fn of(app_id: &str) -> Self {
+        split_tag(app_id).map_or(AppKind::Other, |(tag, _)| AppKind::from_tag(tag))
+    }
+
+    /// The `asset_type` stored on `charms` and `assets` rows.
+    pub fn asset_type(self) -> AssetType {
+        match self {
+            AppKind::Nft => AssetType::Nft,
+            AppKind::Token => AssetType::Token,
+            AppKind::Dapp | AppKind::Data => AssetType::Dapp,
+            AppKind::Dex | AppKind::Contract | AppKind::Other => AssetType::Other,
+        }
+    }
+
+    /// NFTs and tokens carry supply and holder balances.
+    pub fn is_asset(self) -> bool {
+        matches!(self, AppKind::Nft | AppKind::Token)
+    }
+}
+
+/// `<tag>/<rest>` → `(tag, rest)`.
+fn split_tag(app_id: &str) -> Option<(char, &str)> {
+    let mut chars = app_id.chars();
+    match (chars.next(), chars.next()) {
+        (Some(tag), Some('/')) => Some((tag, chars.as_str())),
+        _ => None,
+    }
+}
+
+/// An app_id known to have the `<tag>/…` shape.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AppId(String);
+
+impl AppId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// The tag character before the first `/`.
+    pub fn tag(&self) -> char {
+        self.split().0
+    }
+
+    /// The identity hash: the segment between the tag and the vk.
+    pub fn identity(&self) -> &str {
+        let rest = self.split().1;
+        rest.split_once('/').map_or(rest, |(identity, _)| identity)
+    }
+
+    pub fn classify(&self) -> AppKind {
+        AppKind::from_tag(self.tag())
+    }
+
+    /// The NFT app_id a token's metadata and balances are anchored to
+    /// (`t/HASH/VK` → `n/HASH/VK`). Any other app_id is its own reference.
+    pub fn to_reference_nft(&self) -> AppId {
+        self.with_tag(AppKind::Token, 'n')
+    }
+
+    /// The token app_id issued against an NFT (`n/HASH/VK` → `t/HASH/VK`).
+    /// Any other app_id is returned unchanged.
+    pub fn to_token(&self) -> AppId {
+        self.with_tag(AppKind::Nft, 't')
+    }
+
+    fn split(&self) -> (char, &str) {
+        split_tag(&self.0).expect("AppId is validated on construction")
+    }
+
+    fn with_tag(&self, from: AppKind, to: char) -> AppId {
+        if self.classify() == from {
+            AppId(format!("{to}/{}", self.split().1))
+        } else {
+            self.clone()
+        }
+    }
+}
+
+/// A string without the `<tag>/` prefix every app_id has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidAppId(pub String);
+
+impl fmt::Display for InvalidAppId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid app_id {:?}: expected <tag>/…", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAppId {}
+
+impl FromStr for AppId {
+    type Err = InvalidAppId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match split_tag(s) {
+            Some(_) => Ok(AppId(s.to_string())),
+            None => Err(InvalidAppId(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for AppId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for AppId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<AppId> for String {
+    fn from(app_id: AppId) -> Self {
+        app_id.0
+    }
+}
+
+/// Convert a token app_id (`t/HASH/VK`) into the matching NFT app_id
+/// (`n/HASH/VK`). Non-token app_ids are returned unchanged.
+pub fn token_to_nft(app_id: &str) -> String {
+    match app_id.parse::<AppId>() {
+        Ok(id) => id.to_reference_nft().into_string(),
+        Err(_) => app_id.to_string(),
+    }
+}
+
+/// Convert an NFT app_id (`n/HASH/VK`) into the matching token app_id
+/// (`t/HASH/VK`). Non-NFT app_ids are returned unchanged.
+pub fn nft_to_token(app_id: &str) -> String {
+    match app_id.parse::<AppId>() {
+        Ok(id) => id.to_token().into_string(),
+        Err(_) => app_id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const H: &str = "3d7fe7e4cea6121947af73d70e5119bebd8aa5b7edfe74bfaf6e779a1847bd9b";
+
+    /// (tag, kind, asset_type, is_asset, reference NFT tag) — every tag
+    /// the explorer knows.
+    const MATRIX: [(char, AppKind, AssetType, bool, char); 6] = [
+        ('n', AppKind::Nft, AssetType::Nft, true, 'n'),
+        ('t', AppKind::Token, AssetType::Token, true, 'n'),
+        ('B', AppKind::Dapp, AssetType::Dapp, false, 'B'),
+        ('b', AppKind::Dex, AssetType::Other, false, 'b'),
+        ('d', AppKind::Data, AssetType::Dapp, false, 'd'),
+        ('c', AppKind::Contract, AssetType::Other, false, 'c'),
+    ];
+
+    #[test]
+    fn classification_matrix() {
+        for (tag, kind, asset_type, is_asset, reference_tag) in MATRIX {
+            let app_id = format!("{tag}/{H}/{H}");
+            let parsed: AppId = app_id.parse().unwrap();
+            assert_eq!(AppKind::from_tag(tag), kind, "from_tag({tag})");
+            assert_eq!(AppKind::of(&app_id), kind, "of({app_id})");
+            assert_eq!(parsed.classify(), kind, "classify({app_id})");
+            assert_eq!(parsed.tag(), tag);
+            assert_eq!(parsed.identity(), H);
+            assert_eq!(parsed.to_string(), app_id);
+            assert_eq!(
+                parsed.to_reference_nft().as_str(),
+                format!("{reference_tag}/{H}/{H}"),
+                "to_reference_nft({app_id})"
+            );
+            assert_eq!(kind.asset_type(), asset_type, "{kind:?}");
+            assert_eq!(kind.is_asset(), is_asset, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn unknown_tags_parse_as_other_app_ids() {
+        let parsed: AppId = "x/a/b".parse().unwrap();
+        assert_eq!(parsed.classify(), AppKind::Other);
+        assert_eq!(parsed.to_reference_nft(), parsed);
+        assert_eq!(parsed.identity(), "a");
+        assert_eq!("é/a".parse::<AppId>().unwrap().tag(), 'é');
+    }
+
+    #[test]
+    fn app_id_requires_a_tag_prefix() {
+        for app_id in ["", "n", "nft/a/b", "/n/a", "other"] {
+            assert_eq!(
+                app_id.parse::<AppId>(),
+                Err(InvalidAppId(app_id.to_string())),
+                "{app_id:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_kind_is_covered_by_the_matrix_or_other() {
+        for kind in AppKind::ALL {
+            assert!(
+                kind == AppKind::Other || MATRIX.iter().any(|(_, k, ..)| *k == kind),
+                "{kind:?} missing from MATRIX"
+            );
+        }
+    }
+
+    #[test]
+    fn tags_are_case_sensitive() {
+        assert_eq!(AppKind::of("N/x/y"), AppKind::Other);
+        assert_eq!(AppKind::of("T/x/y"), AppKind::Other);
+        assert_eq!(AppKind::of("b/x/y"), AppKind::Dex);
+        assert_eq!(AppKind::of("B/x/y"), AppKind::Dapp);
+    }
+
+    #[test]
+    fn unknown_and_malformed_ids_are_other() {
+        for app_id in ["x/a/b", "", "n", "nft/a/b", "/n/a", "other", "é/a/b"] {
+            assert_eq!(AppKind::of(app_id), AppKind::Other, "{app_id:?}");
+            assert_eq!(AppKind::of(app_id).asset_type(), AssetType::Other);
+        }
+        assert_eq!(AppKind::from_tag('x'), AppKind::Other);
+    }
+
+    #[test]
+    fn token_and_nft_ids_convert_both_ways() {
+        assert_eq!(token_to_nft("t/abc/def"), "n/abc/def");
+        assert_eq!(nft_to_token("n/abc/def"), "t/abc/def");
+        assert_eq!(nft_to_token(&token_to_nft("t/abc/def")), "t/abc/def");
+    }
+
+    #[test]
+    fn conversion_leaves_other_kinds_alone() {
+        for app_id in [
+            "n/abc/def",
+            "c/abc/def",
+            "b/abc/def",
+            "B/abc/def",
+            "d/abc/def",
+            "",
+        ] {
+            assert_eq!(token_to_nft(app_id), app_id);
+        }
+        for app_id in ["t/abc/def", "c/abc/def", "B/abc/def", ""] {
+            assert_eq!(nft_to_token(app_id), app_id);
+        }
+    }
+
+    #[test]
+    fn conversion_only_rewrites_the_tag() {
+        assert_eq!(token_to_nft("t/t/x"), "n/t/x");
+        assert_eq!(nft_to_token("n/n/x"), "t/n/x");
+        assert_eq!(token_to_nft("t/abc/def:0"), "n/abc/def:0");
+    }
+}
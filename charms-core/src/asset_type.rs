@@ -0,0 +1,104 @@
+//! The `asset_type` column.
+//!
+//! `charms`, `assets` and the API's JSON carry it as a lowercase string;
+//! code that branches on it parses into `AssetType` first.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// What a stored charm or asset row is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetType {
+    Nft,
+    Token,
+    Dapp,
+    /// An app that isn't an asset or a dapp (DEX operator, data, contract,
+    /// unknown tag).
+    Other,
+    /// A spell charm row with no app outputs.
+    Spell,
+}
+
+impl AssetType {
+    pub const ALL: [AssetType; 5] = [
+        AssetType::Nft,
+        AssetType::Token,
+        AssetType::Dapp,
+        AssetType::Other,
+        AssetType::Spell,
+    ];
+
+    /// The stored string.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AssetType::Nft => "nft",
+            AssetType::Token => "token",
+            AssetType::Dapp => "dapp",
+            AssetType::Other => "other",
+            AssetType::Spell => "spell",
+        }
+    }
+
+    /// Parse a stored string, reading anything unrecognised as `Other`
+    /// (the bucket such rows are counted in).
+    pub fn of(value: &str) -> Self {
+        value.parse().unwrap_or(AssetType::Other)
+    }
+}
+
+impl fmt::Display for AssetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A string that isn't one of the stored `asset_type` values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownAssetType(pub String);
+
+impl fmt::Display for UnknownAssetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown asset_type {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownAssetType {}
+
+impl FromStr for AssetType {
+    type Err = UnknownAssetType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AssetType::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| UnknownAssetType(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_stored_string() {
+        for (t, s) in [
+            (AssetType::Nft, "nft"),
+            (AssetType::Token, "token"),
+            (AssetType::Dapp, "dapp"),
+            (AssetType::Other, "other"),
+            (AssetType::Spell, "spell"),
+        ] {
+            assert_eq!(t.to_string(), s);
+            assert_eq!(s.parse::<AssetType>(), Ok(t));
+            assert_eq!(AssetType::of(s), t);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_strings() {
+        for s in ["", "NFT", "Token", "charm", "nft "] {
+            assert_eq!(s.parse::<AssetType>(), Err(UnknownAssetType(s.to_string())));
+            assert_eq!(AssetType::of(s), AssetType::Other);
+        }
+    }
+}
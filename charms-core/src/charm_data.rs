@@ -0,0 +1,164 @@
+//! Size guard for the spell JSON stored on every charm row.
+//!
+//! Each charm of a transaction gets a copy of its spell in `charms.data`.
+//! A spell embedding an inline image or a large app state would make every
+//! listing that reads the column slow, so past a threshold the indexer
+//! stores a trimmed summary instead, marked `data_truncated`. The full JSON
+//! stays on the transaction row (`transactions.charm`), where the API's
+//! detail views read it back.
+
+use serde_json::{Map, Value};
+
+/// Used when the indexer's threshold is not configured.
+pub const DEFAULT_MAX_CHARM_DATA_BYTES: usize = 64 * 1024;
+
+/// Marker set on trimmed charm data.
+pub const DATA_TRUNCATED: &str = "data_truncated";
+
+/// Longest string value a trimmed summary keeps (names, tickers, URLs).
+const MAX_SUMMARY_STRING: usize = 1024;
+
+/// Whether `data` is a trimmed summary rather than the full spell JSON.
+pub fn is_data_truncated(data: &Value) -> bool {
+    data.get(DATA_TRUNCATED).and_then(Value::as_bool) == Some(true)
+}
+
+/// `None` when `data` serializes within `max_bytes`, else its trimmed
+/// summary: the wrapper fields, the spell version, app ids, inputs and the
+/// outputs with long strings and large arrays dropped (app values reduced
+/// to `null` if that is still too big), plus `data_truncated` and the full
+/// size in `full_bytes`.
+pub fn trim_charm_data(data: &Value, max_bytes: usize) -> Option<Value> {
+    let full_bytes = serde_json::to_vec(data).map(|v| v.len()).unwrap_or(0);
+    if full_bytes <= max_bytes {
+        return None;
+    }
+
+    let mut trimmed: Map<String, Value> = data
+        .as_object()
+        .map(|o| {
+            o.iter()
+                .filter(|(k, _)| k.as_str() != "native_data")
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(native) = data.get("native_data") {
+        let tx = native.get("tx").unwrap_or(&Value::Null);
+        let outs = tx.get("outs").and_then(Value::as_array);
+        let mut summary_tx = Map::new();
+        for key in ["ins", "beamed_outs"] {
+            if let Some(v) = tx.get(key) {
+                summary_tx.insert(key.to_string(), v.clone());
+            }
+        }
+        summary_tx.insert(
+            "outs".to_string(),
+            Value::Array(outs.into_iter().flatten().map(summarize).collect()),
+        );
+        if serde_json::to_vec(&summary_tx).map_or(0, |v| v.len()) > max_bytes {
+            let bare = outs.into_iter().flatten().map(null_values).collect();
+            summary_tx.insert("outs".to_string(), Value::Array(bare));
+        }
+
+        let mut summary = Map::new();
+        if let Some(version) = native.get("version") {
+            summary.insert("version".to_string(), version.clone());
+        }
+        if let Some(apps) = native.get("app_public_inputs") {
+            summary.insert("app_public_inputs".to_string(), null_values(apps));
+        }
+        summary.insert("tx".to_string(), Value::Object(summary_tx));
+        trimmed.insert("native_data".to_string(), Value::Object(summary));
+    }
+    trimmed.insert(DATA_TRUNCATED.to_string(), Value::Bool(true));
+    trimmed.insert("full_bytes".to_string(), Value::from(full_bytes));
+    Some(Value::Object(trimmed))
+}
+
+/// `value` without strings longer than `MAX_SUMMARY_STRING` and arrays
+/// that serialize past it; both become `null`.
+fn summarize(value: &Value) -> Value {
+    match value {
+        Value::String(s) if s.len() > MAX_SUMMARY_STRING => Value::Null,
+        Value::Array(_)
+            if serde_json::to_vec(value).map_or(0, |v| v.len()) > MAX_SUMMARY_STRING =>
+        {
+            Value::Null
+        }
+        Value::Object(o) => {
+            Value::Object(o.iter().map(|(k, v)| (k.clone(), summarize(v))).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+/// The keys of an object with every value `null`.
+fn null_values(value: &Value) -> Value {
+    match value {
+        Value::Object(o) => Value::Object(o.keys().map(|k| (k.clone(), Value::Null)).collect()),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spell(image: &str) -> Value {
+        json!({
+            "type": "spell",
+            "detected": true,
+            "native_data": {
+                "version": 10,
+                "app_public_inputs": {"n/aa/bb": {"big": "input"}},
+                "tx": {
+                    "ins": ["aa:0"],
+                    "outs": [{"0": {"name": "Pic", "ticker": "PIC", "image": image}}],
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn small_data_is_kept() {
+        let data = spell("ipfs://pic");
+        assert_eq!(trim_charm_data(&data, DEFAULT_MAX_CHARM_DATA_BYTES), None);
+        assert!(!is_data_truncated(&data));
+    }
+
+    #[test]
+    fn oversized_data_keeps_its_summary_and_is_marked() {
+        let image = format!("data:image/png;base64,{}", "A".repeat(100_000));
+        let data = spell(&image);
+        let trimmed = trim_charm_data(&data, DEFAULT_MAX_CHARM_DATA_BYTES).unwrap();
+
+        assert!(is_data_truncated(&trimmed));
+        assert!(trimmed["full_bytes"].as_u64().unwrap() > 100_000);
+        assert_eq!(trimmed["type"], "spell");
+        assert_eq!(trimmed["native_data"]["version"], 10);
+        assert_eq!(
+            trimmed["native_data"]["app_public_inputs"],
+            json!({"n/aa/bb": null})
+        );
+        assert_eq!(trimmed["native_data"]["tx"]["ins"], json!(["aa:0"]));
+        assert_eq!(
+            trimmed["native_data"]["tx"]["outs"],
+            json!([{"0": {"name": "Pic", "ticker": "PIC", "image": null}}])
+        );
+        assert!(serde_json::to_vec(&trimmed).unwrap().len() < 1024);
+    }
+
+    #[test]
+    fn outs_fall_back_to_app_indices_when_still_too_big() {
+        let fields: Map<String, Value> = (0..200)
+            .map(|i| (format!("field{i}"), json!("x".repeat(500))))
+            .collect();
+        let mut data = spell("ipfs://pic");
+        data["native_data"]["tx"]["outs"] = json!([{"0": fields}]);
+
+        let trimmed = trim_charm_data(&data, 10_000).unwrap();
+        assert_eq!(trimmed["native_data"]["tx"]["outs"], json!([{"0": null}]));
+    }
+}
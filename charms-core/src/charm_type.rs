@@ -0,0 +1,52 @@
+//! Predicates over the charm JSON stored in `charms.data`.
+
+use serde_json::Value;
+
+/// A charm row written for a spell that produced no app data:
+/// `{"type": "spell", "detected": true, "data": {}}`. The API hides these
+/// from listings.
+pub fn is_empty_spell_charm(data: &Value) -> bool {
+    let empty_data = data
+        .get("data")
+        .and_then(Value::as_object)
+        .is_some_and(|o| o.is_empty());
+    empty_data
+        && data.get("type").and_then(Value::as_str) == Some("spell")
+        && data.get("detected").and_then(Value::as_bool) == Some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matches_the_empty_spell_shape() {
+        assert!(is_empty_spell_charm(
+            &json!({"data": {}, "type": "spell", "detected": true})
+        ));
+        assert!(is_empty_spell_charm(
+            &json!({"data": {}, "type": "spell", "detected": true, "version": "x"})
+        ));
+    }
+
+    #[test]
+    fn rejects_every_partial_match() {
+        for data in [
+            json!({"data": {"a": 1}, "type": "spell", "detected": true}),
+            json!({"data": [], "type": "spell", "detected": true}),
+            json!({"data": null, "type": "spell", "detected": true}),
+            json!({"type": "spell", "detected": true}),
+            json!({"data": {}, "type": "charm", "detected": true}),
+            json!({"data": {}, "detected": true}),
+            json!({"data": {}, "type": "spell", "detected": false}),
+            json!({"data": {}, "type": "spell", "detected": "true"}),
+            json!({"data": {}, "type": "spell"}),
+            json!({"type": "spell", "detected": true, "native_data": {}}),
+            json!(null),
+            json!("spell"),
+        ] {
+            assert!(!is_empty_spell_charm(&data), "{data}");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Decimal precision of token amounts.
+//!
+//! Amounts are stored as raw integers; an asset's `decimals` says where the
+//! point goes. The reference NFT normally declares it, and the indexer
+//! copies it onto the token row.
+
+/// Precision assumed when neither the token nor its NFT declares one
+/// (Bitcoin standard).
+pub const DEFAULT_DECIMALS: u8 = 8;
+
+/// Largest precision honoured; higher declared values are capped.
+pub const MAX_DECIMALS: u8 = 18;
+
+/// `raw` as a decimal string with the point `decimals` places from the
+/// right, trailing fractional zeros dropped: `(150_000_000, 8)` → `"1.5"`.
+pub fn format_amount(raw: i64, decimals: u8) -> String {
+    let decimals = decimals.min(MAX_DECIMALS) as u32;
+    let scale = 10u128.pow(decimals);
+    let abs = raw.unsigned_abs() as u128;
+    let sign = if raw < 0 { "-" } else { "" };
+    let (whole, frac) = (abs / scale, abs % scale);
+    if frac == 0 {
+        return format!("{sign}{whole}");
+    }
+    let frac = format!("{:0width$}", frac, width = decimals as usize);
+    format!("{sign}{whole}.{}", frac.trim_end_matches('0'))
+}
+
+/// A decimal string in units of `10^-decimals` as a raw integer, the
+/// inverse of `format_amount`: `("1.5", 8)` → `150_000_000`. `None` for
+/// malformed text, more fractional digits than `decimals`, or a value out
+/// of `i64` range.
+pub fn parse_amount(text: &str, decimals: u8) -> Option<i64> {
+    let decimals = decimals.min(MAX_DECIMALS) as u32;
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (whole, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() && frac.is_empty()
+        || !is_digits(whole)
+        || !is_digits(frac)
+        || frac.len() > decimals as usize
+    {
+        return None;
+    }
+    let whole: i128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let frac: i128 = if frac.is_empty() {
+        0
+    } else {
+        frac.parse::<i128>().ok()? * 10i128.pow(decimals - frac.len() as u32)
+    };
+    let abs = whole.checked_mul(10i128.pow(decimals))?.checked_add(frac)?;
+    i64::try_from(if negative { -abs } else { abs }).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn places_the_point_and_trims_zeros() {
+        assert_eq!(format_amount(150_000_000, 8), "1.5");
+        assert_eq!(format_amount(100, 0), "100");
+        assert_eq!(format_amount(1, 6), "0.000001");
+        assert_eq!(format_amount(2_000_000, 6), "2");
+        assert_eq!(format_amount(-25, 1), "-2.5");
+    }
+
+    #[test]
+    fn parses_what_it_formats() {
+        assert_eq!(parse_amount("1.5", 8), Some(150_000_000));
+        assert_eq!(parse_amount("100", 0), Some(100));
+        assert_eq!(parse_amount("0.000001", 6), Some(1));
+        assert_eq!(parse_amount(".5", 1), Some(5));
+        assert_eq!(parse_amount("-2.5", 1), Some(-25));
+        assert_eq!(parse_amount("9.223372036854775807", 18), Some(i64::MAX));
+    }
+
+    #[test]
+    fn rejects_malformed_or_out_of_range_amounts() {
+        for text in ["", ".", "1.2.3", "1e6", "+1", " 1", "abc"] {
+            assert_eq!(parse_amount(text, 8), None, "{text:?}");
+        }
+        assert_eq!(parse_amount("0.001", 2), None);
+        assert_eq!(parse_amount("9.223372036854775808", 18), None);
+        assert_eq!(
+            parse_amount("99999999999999999999999999999999999999999", 0),
+            None
+        );
+    }
+
+    #[test]
+    fn extreme_values_do_not_overflow() {
+        assert_eq!(format_amount(i64::MIN, 18), "-9.223372036854775808");
+        assert_eq!(format_amount(i64::MAX, 255), "9.223372036854775807");
+    }
+}
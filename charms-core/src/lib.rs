@@ -0,0 +1,33 @@
+//! Charm interpretation rules shared by the indexer and the API.
+//!
+//! The indexer assigns `asset_type` and holder keys when it writes a charm;
+//! the API reads them back and sometimes has to re-derive them (consumed
+//! inputs, legacy rows). Both sides use this crate so the two can't drift.
+//!
+//! - `app_id`: the `AppId` newtype, prefix classification (`n/`, `t/`,
+//!   `B/`, `b/`, `d/`, `c/`) and the token ↔ NFT app_id conversion
+//! - `asset_type`: the stored `asset_type` values
+//! - `charm_type`: stored charm JSON predicates
+//! - `charm_data`: the size guard on the spell JSON stored per charm
+//! - `decimals`: the default token precision, amount formatting and parsing
+//! - `network`: the canonical `network` column values
+//! - `operation`: the stored mint / transfer / burn classification
+//! - `spell_outputs`: the per-output app map of a stored spell
+
+pub mod app_id;
+pub mod asset_type;
+pub mod charm_data;
+pub mod charm_type;
+pub mod decimals;
+pub mod network;
+pub mod operation;
+pub mod spell_outputs;
+
+pub use app_id::{nft_to_token, token_to_nft, AppId, AppKind, InvalidAppId};
+pub use asset_type::{AssetType, UnknownAssetType};
+pub use charm_data::{is_data_truncated, trim_charm_data, DEFAULT_MAX_CHARM_DATA_BYTES};
+pub use charm_type::is_empty_spell_charm;
+pub use decimals::{format_amount, parse_amount, DEFAULT_DECIMALS, MAX_DECIMALS};
+pub use network::{canonical_network, normalize_network};
+pub use operation::{CharmOperation, UnknownCharmOperation};
+pub use spell_outputs::spell_outputs;
@@ -0,0 +1,61 @@
+//! Canonical network names, as stored in every `network` column.
+//!
+//! Rows are written with `network = "mainnet" | "testnet4"` (the chain is in
+//! `blockchain`), but older rows and callers used other spellings:
+//! `"Testnet"`, or `NetworkId`'s display form `"Bitcoin-testnet4"`. An exact
+//! match filter silently misses those, so writes and API filters both go
+//! through [`normalize_network`]. Migration
+//! `m20260813_000001_canonical_network_names` rewrites the legacy rows with
+//! the same mapping.
+
+pub const MAINNET: &str = "mainnet";
+pub const TESTNET4: &str = "testnet4";
+
+/// Every canonical name.
+pub const NETWORKS: [&str; 2] = [MAINNET, TESTNET4];
+
+/// The canonical name of a known spelling, ignoring case and surrounding
+/// whitespace. `None` for anything else.
+pub fn canonical_network(raw: &str) -> Option<&'static str> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "mainnet" | "main" | "bitcoin-mainnet" => Some(MAINNET),
+        "testnet4" | "testnet" | "bitcoin-testnet4" | "bitcoin-testnet" => Some(TESTNET4),
+        _ => None,
+    }
+}
+
+/// [`canonical_network`], or `raw` trimmed and lowercased when unknown, so a
+/// filter on an unknown network matches nothing instead of failing.
+pub fn normalize_network(raw: &str) -> String {
+    canonical_network(raw)
+        .map(str::to_string)
+        .unwrap_or_else(|| raw.trim().to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_spellings_map_to_the_canonical_names() {
+        for raw in ["mainnet", "Mainnet", "MAIN", " bitcoin-mainnet "] {
+            assert_eq!(canonical_network(raw), Some(MAINNET), "{raw}");
+        }
+        for raw in [
+            "testnet4",
+            "Testnet",
+            "testnet",
+            "Bitcoin-testnet4",
+            "bitcoin-testnet",
+        ] {
+            assert_eq!(canonical_network(raw), Some(TESTNET4), "{raw}");
+        }
+    }
+
+    #[test]
+    fn unknown_networks_are_only_lowercased() {
+        assert_eq!(canonical_network("regtest"), None);
+        assert_eq!(normalize_network(" Regtest"), "regtest");
+        assert_eq!(normalize_network("Bitcoin-testnet4"), TESTNET4);
+    }
+}
@@ -0,0 +1,95 @@
+//! What a charm output does to its app's supply, from the transaction's net
+//! change for that app: outputs minus the charms it spent.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Stored `charms.operation` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharmOperation {
+    /// The transaction created supply.
+    Mint,
+    /// Supply only moved between outputs.
+    Transfer,
+    /// The transaction destroyed supply; the output holds what was left.
+    Burn,
+}
+
+impl CharmOperation {
+    pub const ALL: [CharmOperation; 3] = [
+        CharmOperation::Mint,
+        CharmOperation::Transfer,
+        CharmOperation::Burn,
+    ];
+
+    /// The stored string.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CharmOperation::Mint => "mint",
+            CharmOperation::Transfer => "transfer",
+            CharmOperation::Burn => "burn",
+        }
+    }
+
+    /// Classify by the sign of the transaction's net supply change for the
+    /// charm's app.
+    pub fn from_net_change(net_change: i64) -> Self {
+        match net_change.signum() {
+            1 => CharmOperation::Mint,
+            -1 => CharmOperation::Burn,
+            _ => CharmOperation::Transfer,
+        }
+    }
+}
+
+impl fmt::Display for CharmOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A string that isn't one of the stored `operation` values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCharmOperation(pub String);
+
+impl fmt::Display for UnknownCharmOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown operation {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownCharmOperation {}
+
+impl FromStr for CharmOperation {
+    type Err = UnknownCharmOperation;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CharmOperation::ALL
+            .into_iter()
+            .find(|o| o.as_str() == s)
+            .ok_or_else(|| UnknownCharmOperation(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn net_change_sign_picks_the_operation() {
+        assert_eq!(CharmOperation::from_net_change(1000), CharmOperation::Mint);
+        assert_eq!(CharmOperation::from_net_change(0), CharmOperation::Transfer);
+        assert_eq!(CharmOperation::from_net_change(-1), CharmOperation::Burn);
+    }
+
+    #[test]
+    fn round_trips_every_stored_string() {
+        for op in CharmOperation::ALL {
+            assert_eq!(op.to_string().parse::<CharmOperation>(), Ok(op));
+        }
+        assert_eq!(
+            "Mint".parse::<CharmOperation>(),
+            Err(UnknownCharmOperation("Mint".to_string()))
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! The per-output app map of a stored spell (`transactions.spell_outputs`).
+//!
+//! `native_data.tx.outs[i]` holds what the spell committed to output `i`,
+//! keyed by app index: `{"0": 1000, "1": {...}}`. The index is the position
+//! of the app in `native_data.app_public_inputs`, whose keys are the app_ids
+//! in sorted order. Wallets building a spending spell need the app_ids, not
+//! the indices, so the map is stored resolved.
+
+use serde_json::{json, Map, Value};
+
+/// `[{"vout": i, "charms": {app_id: amount or state}}]` for every output of
+/// `spell` (the JSON stored in `transactions.charm`) that carries charms, by
+/// vout. Indices without an app are left out. `None` when `spell` holds no
+/// parsed spell.
+pub fn spell_outputs(spell: &Value) -> Option<Value> {
+    let native = spell.get("native_data")?;
+    let apps: Vec<&String> = native
+        .get("app_public_inputs")?
+        .as_object()?
+        .keys()
+        .collect();
+    let outs = native.pointer("/tx/outs")?.as_array()?;
+
+    let outputs = outs
+        .iter()
+        .enumerate()
+        .filter_map(|(vout, out)| {
+            let charms: Map<String, Value> = out
+                .as_object()?
+                .iter()
+                .filter_map(|(index, data)| {
+                    let app = apps.get(index.parse::<usize>().ok()?)?;
+                    Some((app.to_string(), data.clone()))
+                })
+                .collect();
+            (!charms.is_empty()).then(|| json!({ "vout": vout, "charms": charms }))
+        })
+        .collect();
+    Some(Value::Array(outputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "t/aa/bb";
+    const NFT: &str = "n/aa/bb";
+
+    fn spell(outs: Value) -> Value {
+        json!({
+            "type": "spell",
+            "native_data": {
+                "app_public_inputs": {NFT: null, TOKEN: null},
+                "tx": {"ins": [], "outs": outs},
+            },
+        })
+    }
+
+    #[test]
+    fn two_outputs_with_two_apps_resolve_to_app_ids() {
+        let outputs = spell_outputs(&spell(json!([
+            {"0": {"ticker": "AA", "remaining": 900}, "1": 100},
+            {"1": 900},
+        ])))
+        .unwrap();
+        assert_eq!(
+            outputs,
+            json!([
+                {"vout": 0, "charms": {NFT: {"ticker": "AA", "remaining": 900}, TOKEN: 100}},
+                {"vout": 1, "charms": {TOKEN: 900}},
+            ])
+        );
+    }
+
+    #[test]
+    fn plain_outputs_keep_the_vouts_of_the_rest() {
+        let outputs = spell_outputs(&spell(json!([{}, null, {"1": 5}, {"7": 1}]))).unwrap();
+        assert_eq!(outputs, json!([{"vout": 2, "charms": {TOKEN: 5}}]));
+    }
+
+    #[test]
+    fn rows_without_a_parsed_spell_have_none() {
+        assert_eq!(spell_outputs(&json!({})), None);
+        assert_eq!(
+            spell_outputs(&json!({"native_data": {"tx": {"outs": []}}})),
+            None
+        );
+    }
+}
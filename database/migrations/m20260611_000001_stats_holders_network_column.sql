@@ -34,6 +34,16 @@ BEGIN
     END IF;
 END$$;
 
-ALTER TABLE stats_holders
-    ADD CONSTRAINT stats_holders_app_id_address_network_key
-    UNIQUE (app_id, address, network);
+-- Only added when missing, so re-running the migration is a no-op.
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+         WHERE conrelid = 'stats_holders'::regclass
+           AND conname = 'stats_holders_app_id_address_network_key'
+    ) THEN
+        ALTER TABLE stats_holders
+            ADD CONSTRAINT stats_holders_app_id_address_network_key
+            UNIQUE (app_id, address, network);
+    END IF;
+END$$;
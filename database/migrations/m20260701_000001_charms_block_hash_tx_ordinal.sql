@@ -0,0 +1,46 @@
+-- Migration: m20260701_000001_charms_block_hash_tx_ordinal
+-- Purpose: charms only carried block_height, so reorg cleanup could not tell
+-- rows of an orphaned block apart from rows of its replacement at the same
+-- height, and charms inside one block had no stable order (pagination fell
+-- back to date_created, which ties for a whole batch).
+--
+-- Adds:
+--   block_hash  — hash of the block the charm confirmed in (NULL in mempool)
+--   tx_ordinal  — position of the tx inside that block (NULL in mempool)
+--
+-- Backfill: tx_ordinal comes from transactions.ordinal (same block position)
+-- and block_hash from block_status. Rows with no match stay NULL; readers
+-- and the reorg rollback treat NULL as "unknown" and fall back to height.
+
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS block_hash TEXT;
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS tx_ordinal INTEGER;
+
+UPDATE charms c
+   SET tx_ordinal = t.ordinal
+  FROM transactions t
+ WHERE t.txid = c.txid
+   AND t.network = c.network
+   AND t.block_height IS NOT NULL
+   AND c.block_height IS NOT NULL
+   AND c.tx_ordinal IS NULL;
+
+UPDATE charms c
+   SET block_hash = b.block_hash
+  FROM block_status b
+ WHERE b.block_height = c.block_height
+   AND b.network = c.network
+   AND b.block_hash NOT LIKE 'unknown%'
+   AND c.block_height IS NOT NULL
+   AND c.block_hash IS NULL;
+
+-- Listing order used by the API: newest block first, then block position.
+CREATE INDEX IF NOT EXISTS idx_charms_net_height_ordinal
+    ON charms (network, block_height DESC, tx_ordinal DESC, vout);
+
+CREATE INDEX IF NOT EXISTS idx_charms_block_hash
+    ON charms (block_hash)
+    WHERE block_hash IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260701_000001_charms_block_hash_tx_ordinal')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,68 @@
+-- Migration: m20260702_000001_stats_holders_dedupe_unique
+-- Purpose: guarantee one stats_holders row per (app_id, address, network).
+-- Deployments where m20260611 could not add the network-scoped UNIQUE (or
+-- where it was dropped by hand during a rebuild) accumulated duplicate rows
+-- whenever two writers inserted the same holder concurrently; balances then
+-- diverged between the copies depending on which row an UPDATE hit.
+--
+-- Step 1 folds duplicates into the lowest id: amounts and charm counts are
+-- summed, first_seen_block takes the minimum and last_updated_block the
+-- maximum. Step 2 drops the now-redundant rows and zero balances left over.
+-- Step 3 adds the constraint only if it is missing, so re-running is a no-op.
+--
+-- With duplicates gone, holder deltas are applied additively under that key.
+-- Step 4 adds the ledger the block path claims before applying a block's
+-- deltas, which keeps replaying a block after a crash from counting it twice.
+
+WITH merged AS (
+    SELECT MIN(id)                 AS keep_id,
+           SUM(total_amount)       AS total_amount,
+           SUM(charm_count)        AS charm_count,
+           MIN(first_seen_block)   AS first_seen_block,
+           MAX(last_updated_block) AS last_updated_block
+      FROM stats_holders
+  GROUP BY app_id, address, network
+    HAVING COUNT(*) > 1
+)
+UPDATE stats_holders s
+   SET total_amount       = m.total_amount,
+       charm_count        = m.charm_count,
+       first_seen_block   = m.first_seen_block,
+       last_updated_block = m.last_updated_block,
+       updated_at         = CURRENT_TIMESTAMP
+  FROM merged m
+ WHERE s.id = m.keep_id;
+
+DELETE FROM stats_holders s
+ USING stats_holders k
+ WHERE k.app_id = s.app_id
+   AND k.address = s.address
+   AND k.network = s.network
+   AND k.id < s.id;
+
+DELETE FROM stats_holders WHERE total_amount <= 0;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+         WHERE conrelid = 'stats_holders'::regclass
+           AND conname = 'stats_holders_app_id_address_network_key'
+    ) THEN
+        ALTER TABLE stats_holders
+            ADD CONSTRAINT stats_holders_app_id_address_network_key
+            UNIQUE (app_id, address, network);
+    END IF;
+END$$;
+
+CREATE TABLE IF NOT EXISTS stats_holders_applied_blocks (
+    network      TEXT      NOT NULL,
+    block_hash   TEXT      NOT NULL,
+    block_height INTEGER   NOT NULL,
+    applied_at   TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (network, block_hash)
+);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260702_000001_stats_holders_dedupe_unique')
+ON CONFLICT (version) DO NOTHING;
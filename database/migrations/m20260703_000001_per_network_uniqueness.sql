@@ -0,0 +1,29 @@
+-- Migration: m20260703_000001_per_network_uniqueness
+-- Purpose: scope charm and spell uniqueness by network. A txid is only
+-- unique within one chain, but the charms PK (txid, vout, app_id) and the
+-- spells PK (txid) let a testnet4 row shadow a mainnet row with the same
+-- txid: the second insert hit ON CONFLICT DO NOTHING and was dropped.
+--
+-- charms: PK (txid, vout, app_id) -> (txid, vout, app_id, network).
+--   app_id stays in the key — it is what lets one output carry several
+--   tokens (see m20260615_000001_charms_pk_with_app_id).
+-- spells: PK (txid) -> (txid, network). The table is only present on
+--   deployments created by the sea-orm migrator, so the change is guarded.
+--
+-- Safe on existing data: the new keys are supersets of the old ones, so no
+-- existing row can collide.
+
+ALTER TABLE charms DROP CONSTRAINT IF EXISTS charms_pkey;
+ALTER TABLE charms ADD PRIMARY KEY (txid, vout, app_id, network);
+
+DO $$
+BEGIN
+    IF to_regclass('spells') IS NOT NULL THEN
+        ALTER TABLE spells DROP CONSTRAINT IF EXISTS spells_pkey;
+        ALTER TABLE spells ADD PRIMARY KEY (txid, network);
+    END IF;
+END$$;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260703_000001_per_network_uniqueness')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,34 @@
+-- Migration: m20260704_000001_charms_is_placeholder
+-- Purpose: "empty spell" placeholder charms ({"type": "spell", "detected":
+-- true, "data": {}}) were filtered out by the API after fetching each page,
+-- so listing pages came back short and every count included them.
+--
+-- Adds:
+--   is_placeholder — set by the indexer at save time (charms_core::is_empty_spell_charm)
+--
+-- Backfill: the same predicate over the stored JSON. Listings and counts
+-- then filter `is_placeholder = false` through the partial indexes below.
+-- Placeholders stay in the table and are still returned by txid lookups.
+
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS is_placeholder BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE charms
+   SET is_placeholder = TRUE
+ WHERE data -> 'data' = '{}'::jsonb
+   AND data ->> 'type' = 'spell'
+   AND data -> 'detected' = 'true'::jsonb
+   AND NOT is_placeholder;
+
+-- Per-network listing and count (newest block first, then block position).
+CREATE INDEX IF NOT EXISTS idx_charms_listed_net_height_ordinal
+    ON charms (network, block_height DESC, tx_ordinal DESC, vout)
+    WHERE is_placeholder = FALSE;
+
+-- Listing by asset type across networks.
+CREATE INDEX IF NOT EXISTS idx_charms_listed_type_height_ordinal
+    ON charms (asset_type, block_height DESC, tx_ordinal DESC, vout)
+    WHERE is_placeholder = FALSE;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260704_000001_charms_is_placeholder')
+ON CONFLICT (version) DO NOTHING;
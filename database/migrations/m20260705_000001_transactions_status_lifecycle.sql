@@ -0,0 +1,41 @@
+-- Migration: m20260705_000001_transactions_status_lifecycle
+-- Purpose: transactions.status was 'confirmed' for every mined row and its
+-- confirmations stayed at the value seen at insert time, so rows saved near
+-- the tip kept 1-5 confirmations forever and summary.confirmation_rate was
+-- always ~100%.
+--
+-- Statuses (indexer TransactionStatus):
+--   pending     in the mempool, no block yet
+--   confirming  mined, fewer than 6 confirmations
+--   confirmed   6+ confirmations
+--
+-- The indexer promotes confirming rows once their block matures (see
+-- BitcoinProcessor::confirm_pending_blocks). This migration moves the
+-- shallow legacy rows back to confirming so that pass picks them up, and
+-- recounts summary.confirmed_transactions to match.
+
+UPDATE transactions
+   SET status = 'confirming'
+ WHERE block_height IS NOT NULL
+   AND confirmations < 6
+   AND status IN ('confirmed', 'pending');
+
+UPDATE summary s
+   SET confirmed_transactions = LEAST(c.confirmed, s.total_transactions),
+       confirmation_rate = CASE WHEN s.total_transactions > 0
+           THEN (LEAST(c.confirmed, s.total_transactions) * 100 / s.total_transactions)::int
+           ELSE 0 END
+  FROM (SELECT network, COUNT(*) AS confirmed
+          FROM transactions
+         WHERE status = 'confirmed'
+      GROUP BY network) c
+ WHERE c.network = s.network;
+
+-- Rows still waiting for promotion, looked up by network and height.
+CREATE INDEX IF NOT EXISTS idx_transactions_confirming
+    ON transactions (network, block_height)
+    WHERE status = 'confirming';
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260705_000001_transactions_status_lifecycle')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,28 @@
+-- Migration: m20260706_000001_control_commands
+-- Purpose: let operators pause and resume indexing of one network at
+-- runtime (e.g. mainnet during DB maintenance) without stopping the
+-- process, which would also stop the other networks.
+--
+-- control_commands — append-only audit log of operator commands. The
+--   newest row per network is the requested state. The indexer stamps
+--   acknowledged_at when its block loop picks a command up.
+-- summary.indexer_paused — the state the indexer is actually in. It keeps
+--   last_updated fresh while paused so /status does not report it stale.
+
+CREATE TABLE IF NOT EXISTS control_commands (
+    id               BIGSERIAL   PRIMARY KEY,
+    network          TEXT        NOT NULL,
+    command          TEXT        NOT NULL CHECK (command IN ('pause', 'resume')),
+    requested_by     TEXT        NOT NULL,
+    requested_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    acknowledged_at  TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_control_commands_network_id
+    ON control_commands (network, id DESC);
+
+ALTER TABLE summary ADD COLUMN IF NOT EXISTS indexer_paused BOOLEAN NOT NULL DEFAULT FALSE;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260706_000001_control_commands')
+ON CONFLICT (version) DO NOTHING;
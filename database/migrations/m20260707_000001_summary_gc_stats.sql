@@ -0,0 +1,14 @@
+-- Migration: m20260707_000001_summary_gc_stats
+-- Purpose: surface the indexer's periodic garbage collector on /status.
+--
+-- summary.last_gc_at — when the last GC pass finished (NULL = never ran).
+-- summary.last_gc_stats — rows removed per sweep on that pass, e.g.
+--   {"mempool_charms": 12, "dex_orders": 0, ...}. A sweep that failed or
+--   is disabled is absent from the object.
+
+ALTER TABLE summary ADD COLUMN IF NOT EXISTS last_gc_at TIMESTAMPTZ;
+ALTER TABLE summary ADD COLUMN IF NOT EXISTS last_gc_stats JSONB;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260707_000001_summary_gc_stats')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,29 @@
+-- Migration: m20260708_000001_monitored_addresses_lifecycle
+-- Purpose: stop monitored_addresses from growing forever. Every address
+-- that ever asked for a balance used to be watched by the block and
+-- mempool UTXO trackers indefinitely.
+--
+-- last_queried_at — refreshed by the API on every balance/history call.
+-- expires_at — last_queried_at + MONITOR_TTL_DAYS (API config). NULL means
+--   no TTL (indexer-registered charm holders never queried by the API).
+--   Past it, the indexer stops tracking the address unless it still holds
+--   unspent charms, and the GC demotes it (drops its UTXOs and seed so the
+--   next balance call re-seeds from the provider).
+--
+-- Backfill: API-registered rows start their TTL from their last seed.
+
+ALTER TABLE monitored_addresses ADD COLUMN IF NOT EXISTS last_queried_at TIMESTAMPTZ;
+ALTER TABLE monitored_addresses ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+UPDATE monitored_addresses
+   SET last_queried_at = COALESCE(seeded_at, created_at),
+       expires_at      = COALESCE(seeded_at, created_at) + INTERVAL '90 days'
+ WHERE source = 'api' AND last_queried_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_monitored_addresses_expires_at
+    ON monitored_addresses (expires_at)
+    WHERE expires_at IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260708_000001_monitored_addresses_lifecycle')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,18 @@
+-- Migration: m20260709_000001_charms_spending_txid
+-- Purpose: remember which transaction spent a charm so wallet history can
+-- attribute the outgoing side of a transfer. `spent` alone only says that
+-- the output is gone, and address_utxos drops spent rows entirely.
+--
+-- spending_txid — set by the block spent tracker together with spent =
+--   true. NULL for unspent charms and for rows spent before this migration
+--   (history falls back to mempool_spends for pending spends).
+
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS spending_txid TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_charms_spending_txid
+    ON charms (spending_txid)
+    WHERE spending_txid IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260709_000001_charms_spending_txid')
+ON CONFLICT (version) DO NOTHING;
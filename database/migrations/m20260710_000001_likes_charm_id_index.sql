@@ -0,0 +1,12 @@
+-- Migration: m20260710_000001_likes_charm_id_index
+-- Purpose: back the `sort=likes_desc` charms listing. It joins the likes
+-- count per app_id (`SELECT charm_id, COUNT(id) ... GROUP BY charm_id`);
+-- (charm_id, id) lets Postgres answer that from the index alone instead of
+-- scanning the heap.
+
+CREATE INDEX IF NOT EXISTS idx_likes_charm_id
+    ON likes (charm_id, id);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260710_000001_likes_charm_id_index')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,14 @@
+-- Migration: m20260711_000001_assets_collection
+-- Purpose: group NFTs into collections. `collection` holds the id declared in
+-- the NFT metadata, or `deployer:{address}` (the address the NFT was minted
+-- to) when none is declared. Historical rows are filled by backfill-metadata.
+
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS collection TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_assets_network_collection
+    ON assets (network, collection, block_height DESC)
+    WHERE asset_type = 'nft' AND collection IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260711_000001_assets_collection')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,27 @@
+-- Migration: m20260712_000001_mint_events
+-- Purpose: issuance feed. The block pipeline writes one row per (network,
+-- txid, app_id) whose net supply increased in that transaction, with the
+-- amount minted and the address of the first output carrying it. Reindexing
+-- a range re-emits the rows, and the unique key keeps that idempotent.
+
+CREATE TABLE IF NOT EXISTS mint_events (
+    id              BIGSERIAL   PRIMARY KEY,
+    app_id          TEXT        NOT NULL,
+    txid            TEXT        NOT NULL,
+    block_height    INTEGER     NOT NULL,
+    minted_amount   BIGINT      NOT NULL,
+    minter_address  TEXT,
+    network         TEXT        NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT mint_events_network_txid_app_id_key UNIQUE (network, txid, app_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mint_events_app_id
+    ON mint_events (network, app_id, block_height DESC);
+
+CREATE INDEX IF NOT EXISTS idx_mint_events_block_height
+    ON mint_events (network, block_height DESC);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260712_000001_mint_events')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,20 @@
+-- Migration: m20260713_000001_indexer_replicas
+-- Purpose: heartbeat of every indexer replica per network. Replicas share the
+-- database and elect one writer per network with a Postgres advisory lock;
+-- each one refreshes its row with its current role ('leader' or 'standby')
+-- every few seconds so /status can show who is indexing. Rows of replicas
+-- that stopped cleanly are deleted, crashed ones simply go stale.
+
+CREATE TABLE IF NOT EXISTS indexer_replicas (
+    instance_id  TEXT        NOT NULL,
+    network      TEXT        NOT NULL,
+    role         TEXT        NOT NULL CHECK (role IN ('leader', 'standby')),
+    started_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    role_since   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (instance_id, network)
+);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260713_000001_indexer_replicas')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,61 @@
+-- Migration: m20260714_000001_webhooks
+-- Purpose: push notifications for dApp activity. Admins register endpoints in
+-- webhook_subscriptions (optionally narrowed to one app_id); after each block
+-- the indexer queues one webhook_deliveries row per matching event and
+-- subscription, and a worker POSTs them with an HMAC signature, retrying
+-- with backoff. Every HTTP attempt lands in webhook_delivery_attempts for
+-- debugging. An endpoint failing `consecutive_failures` times in a row is
+-- deactivated (active = false, disabled_at set).
+
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id                   BIGSERIAL   PRIMARY KEY,
+    url                  TEXT        NOT NULL,
+    secret               TEXT        NOT NULL,
+    app_id               TEXT,
+    event_types          TEXT[]      NOT NULL,
+    active               BOOLEAN     NOT NULL DEFAULT TRUE,
+    consecutive_failures INTEGER     NOT NULL DEFAULT 0,
+    disabled_at          TIMESTAMPTZ,
+    created_by           TEXT,
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Outbox: one row per (subscription, event). `event_id` is derived from the
+-- chain data, so reprocessing a block queues nothing twice.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id               BIGSERIAL   PRIMARY KEY,
+    subscription_id  BIGINT      NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+    event_id         TEXT        NOT NULL,
+    event_type       TEXT        NOT NULL,
+    network          TEXT        NOT NULL,
+    block_height     INTEGER     NOT NULL,
+    payload          JSONB       NOT NULL,
+    status           TEXT        NOT NULL DEFAULT 'pending'
+                                 CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts         INTEGER     NOT NULL DEFAULT 0,
+    next_attempt_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error       TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at     TIMESTAMPTZ,
+    UNIQUE (subscription_id, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+
+CREATE TABLE IF NOT EXISTS webhook_delivery_attempts (
+    id            BIGSERIAL   PRIMARY KEY,
+    delivery_id   BIGINT      NOT NULL REFERENCES webhook_deliveries (id) ON DELETE CASCADE,
+    attempt       INTEGER     NOT NULL,
+    status_code   INTEGER,
+    error         TEXT,
+    duration_ms   INTEGER     NOT NULL,
+    attempted_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_delivery
+    ON webhook_delivery_attempts (delivery_id);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260714_000001_webhooks')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,17 @@
+-- Migration: m20260715_000001_charms_confirmation_delay
+-- Purpose: time each charm spent in mempool. When a block confirms a charm
+-- first seen unconfirmed, the indexer stores block time minus
+-- mempool_detected_at (seconds, floored at 0). Charms never seen in mempool
+-- keep NULL and are left out of the /stats/mempool delay figures. Existing
+-- rows are not backfilled: their block time is not stored.
+
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS confirmation_delay_secs INTEGER;
+
+-- /stats/mempool windows on confirmation time over the few charms with a delay.
+CREATE INDEX IF NOT EXISTS idx_charms_confirmation_delay
+    ON charms (network, mempool_detected_at)
+    WHERE confirmation_delay_secs IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260715_000001_charms_confirmation_delay')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,24 @@
+-- Migration: m20260716_000001_pending_spells
+-- Purpose: keep transactions whose spell envelope the indexer recognizes but
+-- whose protocol version its parser does not support yet, instead of
+-- dropping them as non-charm txs. block_height is NULL for txs only seen in
+-- mempool. On startup after a parser upgrade, rows whose detected_version is
+-- now supported are reprocessed through the block pipeline and deleted.
+
+CREATE TABLE IF NOT EXISTS pending_spells (
+    txid              TEXT        NOT NULL,
+    network           TEXT        NOT NULL,
+    raw_hex           TEXT        NOT NULL,
+    detected_version  INTEGER     NOT NULL,
+    block_height      INTEGER,
+    first_seen_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (txid, network)
+);
+
+-- The startup drain looks up rows by network and version.
+CREATE INDEX IF NOT EXISTS idx_pending_spells_network_version
+    ON pending_spells (network, detected_version);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260716_000001_pending_spells')
+ON CONFLICT (version) DO NOTHING;
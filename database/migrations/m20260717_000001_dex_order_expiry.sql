@@ -0,0 +1,19 @@
+-- Migration: m20260717_000001_dex_order_expiry
+-- Purpose: Charms Cast orders may carry an expiry (block height and/or unix
+-- time). The indexer stores it here and, on each block, moves open/partial
+-- orders whose expiry that block passed to status 'expired', which keeps
+-- them off the orderbook. Orders saved before this migration keep NULL and
+-- never expire.
+
+ALTER TABLE dex_orders ADD COLUMN IF NOT EXISTS expires_at_height INTEGER;
+ALTER TABLE dex_orders ADD COLUMN IF NOT EXISTS expires_at_time TIMESTAMP;
+
+-- The per-block sweep only looks at live orders that carry an expiry.
+CREATE INDEX IF NOT EXISTS idx_dex_orders_live_expiry
+    ON dex_orders (network)
+    WHERE status IN ('open', 'partial')
+      AND (expires_at_height IS NOT NULL OR expires_at_time IS NOT NULL);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260717_000001_dex_order_expiry')
+ON CONFLICT (version) DO NOTHING;
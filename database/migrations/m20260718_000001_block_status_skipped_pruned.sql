@@ -0,0 +1,23 @@
+-- Migration: m20260718_000001_block_status_skipped_pruned
+-- Purpose: a block the node does not have (pruned or missing) used to be
+-- marked downloaded+processed with tx_count 0, so it was never looked at
+-- again. It is now flagged skipped_pruned and left unprocessed, and a
+-- periodic retry pass fills it in once a provider serves the block.
+
+ALTER TABLE block_status
+    ADD COLUMN IF NOT EXISTS skipped_pruned BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Every real block has a coinbase, so a processed row with no transactions
+-- is a skip written by the old code path.
+UPDATE block_status
+    SET skipped_pruned = TRUE, downloaded = FALSE, processed = FALSE, updated_at = NOW()
+    WHERE processed AND tx_count = 0;
+
+-- The retry pass and /status only look at skipped rows.
+CREATE INDEX IF NOT EXISTS idx_block_status_skipped_pruned
+    ON block_status (network, block_height)
+    WHERE skipped_pruned;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260718_000001_block_status_skipped_pruned')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,27 @@
+-- Migration: m20260719_000001_supply_changes
+-- Purpose: audit trail for assets.total_supply. Every write to the column
+-- (block pipeline mint, spend, reindex, verify --fix) records one row in the
+-- same transaction, so a wrong number can be traced to the write that set
+-- it. Rows are only written when the value moves, so growth follows the
+-- number of supply changes, not holders or transfers.
+
+CREATE TABLE IF NOT EXISTS supply_changes (
+    id            BIGSERIAL      PRIMARY KEY,
+    app_id        TEXT           NOT NULL,
+    network       TEXT           NOT NULL,
+    delta         NUMERIC(30, 0) NOT NULL,
+    reason        TEXT           NOT NULL
+                  CHECK (reason IN ('mint', 'spend', 'reindex', 'manual')),
+    txid          TEXT,
+    block_height  INTEGER,
+    old_supply    NUMERIC(30, 0),
+    new_supply    NUMERIC(30, 0) NOT NULL,
+    created_at    TIMESTAMPTZ    NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_supply_changes_app_id
+    ON supply_changes (network, app_id, id DESC);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260719_000001_supply_changes')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,12 @@
+-- Migration: m20260720_000001_likes_created_at_index
+-- Purpose: back the recent-likes window of `GET /assets/{app_id}/likes`
+-- (`WHERE charm_id = $1 AND created_at >= now() - 7 days`). The existing
+-- (charm_id, id) index answers the totals; this one answers the window
+-- without reading every like the asset ever had.
+
+CREATE INDEX IF NOT EXISTS idx_likes_charm_id_created_at
+    ON likes (charm_id, created_at);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260720_000001_likes_created_at_index')
+ON CONFLICT (version) DO NOTHING;
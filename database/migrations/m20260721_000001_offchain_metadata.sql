@@ -0,0 +1,28 @@
+-- Migration: m20260721_000001_offchain_metadata
+-- Purpose: NFT metadata that only points at an off-chain JSON document
+-- (`metadata_url`, or an image URL ending in .json). When
+-- FETCH_OFFCHAIN_METADATA is on, the indexer queues one
+-- offchain_metadata_fetches row per such NFT after its block is persisted;
+-- a separate worker downloads the document under strict size/time limits
+-- and stores it in assets.offchain_metadata, retrying failures with backoff.
+
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS offchain_metadata JSONB;
+
+CREATE TABLE IF NOT EXISTS offchain_metadata_fetches (
+    asset_id         INTEGER     PRIMARY KEY REFERENCES assets (id) ON DELETE CASCADE,
+    url              TEXT        NOT NULL,
+    status           TEXT        NOT NULL DEFAULT 'pending'
+                                 CHECK (status IN ('pending', 'fetched', 'failed')),
+    attempts         INTEGER     NOT NULL DEFAULT 0,
+    next_attempt_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error       TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    fetched_at       TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_offchain_metadata_fetches_due
+    ON offchain_metadata_fetches (next_attempt_at) WHERE status = 'pending';
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260721_000001_offchain_metadata')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,36 @@
+-- Migration: m20260722_000001_dapp_asset_type
+-- Purpose: `d/` apps are now classified as `dapp` (like `B/`), and every dapp
+-- gets an assets row the first time it appears. Reclassify the `d/` charms
+-- indexed as `other`, move them between the summary buckets, and create the
+-- missing dapp asset rows from each app's first charm.
+
+WITH moved AS (
+    UPDATE charms SET asset_type = 'dapp'
+     WHERE app_id LIKE 'd/%' AND asset_type = 'other'
+    RETURNING network
+), per_network AS (
+    SELECT network, COUNT(*) AS n FROM moved GROUP BY network
+)
+UPDATE summary s
+   SET dapp_count = s.dapp_count + p.n,
+       other_count = GREATEST(s.other_count - p.n, 0)
+  FROM per_network p
+ WHERE s.network = p.network;
+
+UPDATE assets SET asset_type = 'dapp'
+ WHERE app_id LIKE 'd/%' AND asset_type = 'other';
+
+INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, data,
+                    asset_type, blockchain, network, total_supply)
+SELECT DISTINCT ON (c.app_id, c.network)
+       c.app_id, c.txid, c.vout, 'charm-' || c.app_id, c.block_height,
+       jsonb_build_object('supply', 0), 'dapp', c.blockchain, c.network, 0
+  FROM charms c
+ WHERE c.asset_type = 'dapp' AND c.block_height IS NOT NULL
+   AND NOT EXISTS (SELECT 1 FROM assets a
+                    WHERE a.app_id = c.app_id AND a.network = c.network)
+ ORDER BY c.app_id, c.network, c.block_height, c.txid, c.vout;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260722_000001_dapp_asset_type')
+ON CONFLICT (version) DO NOTHING;
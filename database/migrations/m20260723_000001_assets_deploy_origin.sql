@@ -0,0 +1,15 @@
+-- Migration: m20260723_000001_assets_deploy_origin
+-- Purpose: record where each asset was deployed. `txid`/`block_height` on
+-- assets hold whatever write created the row, which after reindexes and
+-- token/NFT ordering races is not always the first transaction of the
+-- app_id. The indexer sets these columns when it inserts an asset and only
+-- replaces them with a charm from a strictly earlier block. Existing rows are
+-- filled by `charms-indexer backfill-deploys`.
+
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS deploy_txid TEXT;
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS deploy_block_height INTEGER;
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS deployer_address TEXT;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260723_000001_assets_deploy_origin')
+ON CONFLICT (version) DO NOTHING;
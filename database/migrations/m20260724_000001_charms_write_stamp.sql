@@ -0,0 +1,22 @@
+-- Migration: m20260724_000001_charms_write_stamp
+-- Purpose: record which build wrote each charm, so rows produced by a parser
+-- bug can be found and reindexed once it is fixed. `parser_revision` is
+-- NativeCharmParser::PARSER_REVISION at write time, `indexer_version` the
+-- crate version, and `reindex_run_id` the reindex run that rewrote the row.
+-- Rows written before this migration stay NULL and count as the oldest
+-- revision (`reindex --parser-revision-lt` always selects them).
+
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS indexer_version TEXT;
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS parser_revision INTEGER;
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS reindex_run_id TEXT;
+
+-- Legacy table; absent on databases created after it was retired.
+ALTER TABLE IF EXISTS spells ADD COLUMN IF NOT EXISTS indexer_version TEXT;
+ALTER TABLE IF EXISTS spells ADD COLUMN IF NOT EXISTS parser_revision INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_charms_network_parser_revision
+    ON charms (network, parser_revision, block_height);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260724_000001_charms_write_stamp')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,12 @@
+-- Migration: m20260725_000001_charms_type_network_index
+-- Purpose: GET /charms/by-type is now scoped to one network. Its count and
+-- its default page order (newest block first, then block position) are
+-- served from this partial index instead of a scan over every network.
+
+CREATE INDEX IF NOT EXISTS idx_charms_listed_type_net_height_ordinal
+    ON charms (asset_type, network, block_height DESC, tx_ordinal DESC, vout)
+    WHERE is_placeholder = FALSE;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260725_000001_charms_type_network_index')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,43 @@
+-- Migration: m20260726_000001_metadata_refresh_jobs
+-- Purpose: re-derive asset metadata after the extraction improves. Asset
+-- rows keep the name/symbol/image they were created with; an operator
+-- queues a refresh through POST /admin/assets/refresh-metadata and the
+-- indexer re-derives the targeted rows from their stored data (no RPC).
+--
+-- metadata_refresh_jobs    — one row per requested refresh. `app_ids` NULL
+--   means every asset. At most one job is pending or running at a time.
+-- metadata_refresh_results — the fields each job changed, per asset, as
+--   {"field": {"old": ..., "new": ...}}.
+
+CREATE TABLE IF NOT EXISTS metadata_refresh_jobs (
+    id              BIGSERIAL   PRIMARY KEY,
+    network         TEXT,
+    app_ids         TEXT[],
+    status          TEXT        NOT NULL DEFAULT 'pending'
+                                CHECK (status IN ('pending', 'running', 'done', 'failed')),
+    requested_by    TEXT        NOT NULL,
+    requested_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at      TIMESTAMPTZ,
+    heartbeat_at    TIMESTAMPTZ,
+    finished_at     TIMESTAMPTZ,
+    assets_scanned  INTEGER     NOT NULL DEFAULT 0,
+    assets_changed  INTEGER     NOT NULL DEFAULT 0,
+    error           TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_metadata_refresh_jobs_active
+    ON metadata_refresh_jobs ((TRUE))
+    WHERE status IN ('pending', 'running');
+
+CREATE TABLE IF NOT EXISTS metadata_refresh_results (
+    job_id    BIGINT  NOT NULL REFERENCES metadata_refresh_jobs (id) ON DELETE CASCADE,
+    asset_id  INTEGER NOT NULL,
+    app_id    TEXT    NOT NULL,
+    network   TEXT    NOT NULL,
+    changes   JSONB   NOT NULL,
+    PRIMARY KEY (job_id, asset_id)
+);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260726_000001_metadata_refresh_jobs')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,20 @@
+-- Migration: m20260727_000001_charms_operation
+-- Purpose: tell mints from transfers. Every spell output used to count as a
+-- created token/nft charm, whether the transaction minted supply or only
+-- moved it. `operation` records the sign of the transaction's net supply
+-- change for the charm's app: 'mint' (grew), 'transfer' (unchanged) or
+-- 'burn' (shrank). Classification only; holders and supply are untouched.
+--
+-- Rows written before this migration stay NULL. They carry parser revision
+-- 1, so `charms-indexer reindex --parser-revision-lt 2` classifies them.
+
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS operation TEXT
+    CHECK (operation IN ('mint', 'transfer', 'burn'));
+
+CREATE INDEX IF NOT EXISTS idx_charms_network_operation
+    ON charms (network, operation)
+    WHERE is_placeholder = FALSE;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260727_000001_charms_operation')
+ON CONFLICT (version) DO NOTHING;
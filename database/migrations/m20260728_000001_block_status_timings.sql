@@ -0,0 +1,24 @@
+-- Migration: m20260728_000001_block_status_timings
+-- Purpose: keep how long each block took, to spot indexer slowdowns after a
+-- change. `processing_ms` is the whole of `BlockProcessor::process_block`;
+-- `rpc_ms` fetching the block (and the reorg check), `parse_ms` detecting
+-- its charms and `db_ms` everything persisted after that. The three phases
+-- add up to roughly the total. tx_count and charm_count already exist.
+--
+-- Rows written before this migration, and blocks processed by the retry
+-- paths that don't go through `process_block`, stay NULL.
+
+ALTER TABLE block_status
+    ADD COLUMN IF NOT EXISTS processing_ms INTEGER,
+    ADD COLUMN IF NOT EXISTS rpc_ms INTEGER,
+    ADD COLUMN IF NOT EXISTS parse_ms INTEGER,
+    ADD COLUMN IF NOT EXISTS db_ms INTEGER;
+
+-- GET /admin/block-timings and /status read the newest timed blocks.
+CREATE INDEX IF NOT EXISTS idx_block_status_timed
+    ON block_status (network, block_height DESC)
+    WHERE processing_ms IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260728_000001_block_status_timings')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,47 @@
+-- Migration: m20260729_000001_moderation
+-- Purpose: let operators hide abusive charms and assets from the public API
+-- without deleting indexed data. `hidden` rows drop out of every public
+-- listing and detail endpoint; `flagged` rows stay visible but are marked
+-- for review. Set through `POST /admin/moderate`, which records each change
+-- in `moderation_log`.
+--
+-- Only the API writes these columns. The indexer's entities don't map them,
+-- so its upserts, reindex runs and mempool promotion leave an operator's
+-- decision in place and new rows start out `visible`.
+
+ALTER TABLE charms
+    ADD COLUMN IF NOT EXISTS moderation_status TEXT NOT NULL DEFAULT 'visible'
+        CHECK (moderation_status IN ('visible', 'hidden', 'flagged'));
+
+ALTER TABLE assets
+    ADD COLUMN IF NOT EXISTS moderation_status TEXT NOT NULL DEFAULT 'visible'
+        CHECK (moderation_status IN ('visible', 'hidden', 'flagged'));
+
+-- Few rows are ever moderated; keep the lookups for them cheap.
+CREATE INDEX IF NOT EXISTS idx_charms_moderated
+    ON charms (network, moderation_status)
+    WHERE moderation_status <> 'visible';
+CREATE INDEX IF NOT EXISTS idx_assets_moderated
+    ON assets (network, moderation_status)
+    WHERE moderation_status <> 'visible';
+
+CREATE TABLE IF NOT EXISTS moderation_log (
+    id              BIGSERIAL   PRIMARY KEY,
+    target_type     TEXT        NOT NULL CHECK (target_type IN ('charm', 'asset')),
+    -- app_id for assets, txid for charms
+    target          TEXT        NOT NULL,
+    network         TEXT        NOT NULL,
+    status          TEXT        NOT NULL CHECK (status IN ('visible', 'hidden', 'flagged')),
+    -- Status before the change; comma-joined when a charm txid's rows differed
+    previous_status TEXT,
+    reason          TEXT        NOT NULL,
+    moderated_by    TEXT        NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_moderation_log_target
+    ON moderation_log (target_type, target, network, created_at DESC);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260729_000001_moderation')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,15 @@
+-- Migration: m20260730_000001_provider_stale
+-- Purpose: surface a stuck primary Bitcoin node on /status. A node that
+-- stops following the chain keeps answering with its old tip, so the block
+-- loop idles "waiting for new blocks" and nothing looks wrong.
+--
+-- summary.provider_stale — set by the indexer's tip watch while the primary
+--   provider has trailed the fallback provider by more than
+--   TIP_WATCH_MAX_LAG_BLOCKS for TIP_WATCH_STALE_AFTER_SECS; cleared once it
+--   catches up. Always false when no fallback provider is configured.
+
+ALTER TABLE summary ADD COLUMN IF NOT EXISTS provider_stale BOOLEAN NOT NULL DEFAULT FALSE;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260730_000001_provider_stale')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,33 @@
+-- Migration: m20260731_000001_charms_archive
+-- Purpose: let lean deployments drop old spent charms from the hot table.
+--
+-- charms.spent_height — height of the block that spent the row, set by the
+--   block spent tracker. Backfilled below where the spender has a
+--   `transactions` row; rows spent by a non-spell tx before this migration
+--   keep NULL and are never pruned.
+-- charms_archive — same columns, in the same order, as `charms`. The
+--   indexer's archiver (PRUNE_SPENT_CHARMS_AFTER_BLOCKS) moves rows here
+--   with `INSERT ... SELECT *`, so any column added to `charms` later must
+--   be added to `charms_archive` in the same migration.
+
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS spent_height INTEGER;
+
+UPDATE charms c
+   SET spent_height = t.block_height
+  FROM transactions t
+ WHERE c.spent
+   AND c.spent_height IS NULL
+   AND t.txid = c.spending_txid
+   AND t.network = c.network
+   AND t.block_height IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_charms_spent_height
+    ON charms (network, spent_height) WHERE spent;
+
+CREATE TABLE IF NOT EXISTS charms_archive
+    (LIKE charms INCLUDING DEFAULTS INCLUDING CONSTRAINTS,
+     PRIMARY KEY (txid, vout, app_id, network));
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260731_000001_charms_archive')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,82 @@
+-- Migration: m20260801_000001_canonical_addresses
+-- Purpose: store every bech32 address lowercased.
+-- Bech32 is case-insensitive, and the indexer and API now write and look up
+-- the lowercase form only. Rows written before that may hold an uppercase
+-- copy (addresses registered through the API, DEX makers copied from spell
+-- data), which exact-match lookups would no longer find. BIP-173 forbids
+-- mixed case, so each address has at most one non-canonical spelling. Base58
+-- addresses are case-sensitive and left alone.
+--
+-- Tables keyed by address first fold a non-canonical row into its lowercase
+-- twin when both exist: stats_holders sums the balances, the others keep
+-- the lowercase row and drop the duplicate.
+
+-- stats_holders: merge into an existing lowercase row, then lowercase the rest.
+UPDATE stats_holders k
+   SET total_amount       = k.total_amount + d.total_amount,
+       charm_count        = k.charm_count + d.charm_count,
+       first_seen_block   = LEAST(k.first_seen_block, d.first_seen_block),
+       last_updated_block = GREATEST(k.last_updated_block, d.last_updated_block),
+       updated_at         = CURRENT_TIMESTAMP
+  FROM stats_holders d
+ WHERE d.address ~* '^(bc|tb|bcrt)1'
+   AND d.address <> lower(d.address)
+   AND k.address = lower(d.address)
+   AND k.app_id = d.app_id
+   AND k.network = d.network;
+
+DELETE FROM stats_holders d
+ USING stats_holders k
+ WHERE d.address ~* '^(bc|tb|bcrt)1'
+   AND d.address <> lower(d.address)
+   AND k.address = lower(d.address)
+   AND k.app_id = d.app_id
+   AND k.network = d.network;
+
+UPDATE stats_holders SET address = lower(address)
+ WHERE address ~* '^(bc|tb|bcrt)1' AND address <> lower(address);
+
+-- monitored_addresses and address_transactions: keep the lowercase row.
+DELETE FROM monitored_addresses d
+ USING monitored_addresses k
+ WHERE d.address ~* '^(bc|tb|bcrt)1'
+   AND d.address <> lower(d.address)
+   AND k.address = lower(d.address)
+   AND k.network = d.network;
+
+UPDATE monitored_addresses SET address = lower(address)
+ WHERE address ~* '^(bc|tb|bcrt)1' AND address <> lower(address);
+
+DELETE FROM address_transactions d
+ USING address_transactions k
+ WHERE d.address ~* '^(bc|tb|bcrt)1'
+   AND d.address <> lower(d.address)
+   AND k.address = lower(d.address)
+   AND k.txid = d.txid
+   AND k.network = d.network;
+
+UPDATE address_transactions SET address = lower(address)
+ WHERE address ~* '^(bc|tb|bcrt)1' AND address <> lower(address);
+
+-- Columns without an address key.
+UPDATE charms SET address = lower(address)
+ WHERE address ~* '^(bc|tb|bcrt)1' AND address <> lower(address);
+
+UPDATE charms_archive SET address = lower(address)
+ WHERE address ~* '^(bc|tb|bcrt)1' AND address <> lower(address);
+
+UPDATE address_utxos SET address = lower(address)
+ WHERE address ~* '^(bc|tb|bcrt)1' AND address <> lower(address);
+
+UPDATE dex_orders SET maker = lower(maker)
+ WHERE maker ~* '^(bc|tb|bcrt)1' AND maker <> lower(maker);
+
+UPDATE assets SET deployer_address = lower(deployer_address)
+ WHERE deployer_address ~* '^(bc|tb|bcrt)1' AND deployer_address <> lower(deployer_address);
+
+UPDATE mint_events SET minter_address = lower(minter_address)
+ WHERE minter_address ~* '^(bc|tb|bcrt)1' AND minter_address <> lower(minter_address);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260801_000001_canonical_addresses')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,55 @@
+-- Migration: m20260802_000001_block_time
+-- Purpose: date charms by their block instead of by when the indexer wrote
+-- them. `date_created` is the ingestion time, so a reindex or a catch-up
+-- from genesis stamps years-old activity with today's date.
+--
+-- block_status.block_time — header time of the block, written when the
+--   block is downloaded. The dry-run reindex report reads it from here.
+-- charms.block_time — header time of the confirming block; NULL while in
+--   mempool. Set on insert by the block path, on promotion by the mempool
+--   consolidator, and on rewrite by a reindex run.
+-- charms_archive.block_time — same column, kept in the same position.
+--
+-- Backfill: block times are known for heights that have an
+-- `address_transactions` row (its `block_time` is the header time in unix
+-- seconds). Other existing rows stay NULL until their range is reindexed;
+-- readers fall back to `date_created` for them.
+
+ALTER TABLE block_status ADD COLUMN IF NOT EXISTS block_time TIMESTAMP;
+ALTER TABLE charms ADD COLUMN IF NOT EXISTS block_time TIMESTAMP;
+ALTER TABLE charms_archive ADD COLUMN IF NOT EXISTS block_time TIMESTAMP;
+
+UPDATE block_status b
+   SET block_time = t.block_time
+  FROM (SELECT DISTINCT ON (network, block_height)
+               network, block_height,
+               to_timestamp(block_time) AT TIME ZONE 'UTC' AS block_time
+          FROM address_transactions
+         WHERE block_height IS NOT NULL AND block_time IS NOT NULL
+      ORDER BY network, block_height) t
+ WHERE b.block_time IS NULL
+   AND b.network = t.network
+   AND b.block_height = t.block_height;
+
+UPDATE charms c
+   SET block_time = b.block_time
+  FROM block_status b
+ WHERE c.block_time IS NULL
+   AND b.block_time IS NOT NULL
+   AND b.network = c.network
+   AND b.block_height = c.block_height;
+
+UPDATE charms_archive c
+   SET block_time = b.block_time
+  FROM block_status b
+ WHERE c.block_time IS NULL
+   AND b.block_time IS NOT NULL
+   AND b.network = c.network
+   AND b.block_height = c.block_height;
+
+CREATE INDEX IF NOT EXISTS idx_charms_block_time
+    ON charms (network, block_time DESC);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260802_000001_block_time')
+ON CONFLICT (version) DO NOTHING;
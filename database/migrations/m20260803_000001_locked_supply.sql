@@ -0,0 +1,22 @@
+-- Migration: m20260803_000001_locked_supply
+-- Purpose: split token supply into circulating and locked.
+--
+-- assets.locked_supply — unspent confirmed amount of the app held at a lock
+--   address: the scrolls address of an open or partially filled DEX order
+--   on the asset's network, or an address matching LOCKED_SUPPLY_ADDRESSES.
+--   The indexer refreshes it for the app_ids each block touches and for
+--   every token on its periodic pass; the API reports
+--   circulating_supply = total_supply - locked_supply.
+--
+-- Rows start at 0 and are filled by the first periodic pass.
+
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS locked_supply NUMERIC(30, 0) NOT NULL DEFAULT 0;
+
+-- The locked-supply query looks up open orders by scrolls address.
+CREATE INDEX IF NOT EXISTS idx_dex_orders_scrolls_address
+    ON dex_orders (network, scrolls_address)
+    WHERE status IN ('open', 'partial');
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260803_000001_locked_supply')
+ON CONFLICT (version) DO NOTHING;
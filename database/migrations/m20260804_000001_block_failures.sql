@@ -0,0 +1,20 @@
+-- Migration: m20260804_000001_block_failures
+-- Purpose: remember blocks the reindex job keeps failing on.
+--
+-- block_status.failed_attempts — reindex runs that failed on the block since
+--   it was last processed; reset by a successful run.
+-- block_status.last_error — the error of the latest failed attempt.
+--
+-- A block at the run's attempt limit is sidelined: later runs skip it until
+-- `reindex --retry-failed` resets its counter.
+
+ALTER TABLE block_status ADD COLUMN IF NOT EXISTS failed_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE block_status ADD COLUMN IF NOT EXISTS last_error TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_block_status_failed
+    ON block_status (network, block_height)
+    WHERE failed_attempts > 0;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260804_000001_block_failures')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,18 @@
+-- Migration: m20260805_000001_possible_charm
+-- Purpose: flag monitored UTXOs that may carry a charm the parser missed.
+--
+-- address_utxos.possible_charm — the funding transaction carries something
+--   shaped like a spell (a witness envelope or an OP_RETURN with the spell
+--   marker) but the UTXO has no stored charm, e.g. an unknown protocol
+--   version or a parser bug. Wallet balances keep such UTXOs out of
+--   `available` and list them under `needs_review`, so a wallet does not
+--   spend the charm as plain BTC. Once a parser upgrade stores the charm,
+--   the UTXO counts as `locked` like any other charm UTXO.
+--
+-- Existing rows start unflagged; the indexer sets the flag on new UTXOs.
+
+ALTER TABLE address_utxos ADD COLUMN IF NOT EXISTS possible_charm BOOLEAN NOT NULL DEFAULT FALSE;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260805_000001_possible_charm')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,16 @@
+-- Migration: m20260806_000001_config_snapshots
+-- Purpose: redacted effective configuration of every indexer replica, written
+-- at startup so /diagnose/config can show how a deployed instance is set up
+-- without shell access. One row per replica, replaced on each start. The
+-- snapshot never holds credentials: secrets are reduced to "is set" and URLs
+-- to scheme, host and port.
+
+CREATE TABLE IF NOT EXISTS config_snapshots (
+    instance_id  TEXT        PRIMARY KEY,
+    snapshot     JSONB       NOT NULL,
+    recorded_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260806_000001_config_snapshots')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,13 @@
+-- Migration: m20260807_000001_charms_archive_spending_txid
+-- Purpose: charm details list the charms a spell consumed by looking up
+-- `spending_txid`, in `charms` and in `charms_archive`. The live table is
+-- indexed since m20260709; give the archive the same partial index so the
+-- fallback does not scan it.
+
+CREATE INDEX IF NOT EXISTS idx_charms_archive_spending_txid
+    ON charms_archive (spending_txid)
+    WHERE spending_txid IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260807_000001_charms_archive_spending_txid')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,13 @@
+-- Migration: m20260808_000001_charms_archive_address
+-- Purpose: GET /address/{address}/balance-at sums an address's charms of
+-- one asset across `charms` and `charms_archive`. The live table has
+-- idx_charms_address_net_spent; index the archive by address as well so
+-- historical balances do not scan it.
+
+CREATE INDEX IF NOT EXISTS idx_charms_archive_address_net
+    ON charms_archive (address, network)
+    WHERE address IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260808_000001_charms_archive_address')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,26 @@
+-- Migration: m20260809_000001_changefeed
+-- Purpose: append-only feed of row changes for downstream ETL. The indexer
+-- repositories for charms, assets, dex_orders and stats_holders record one
+-- row per inserted or updated entity (one multi-row INSERT per batched
+-- write); GET /changefeed pages it by id so a consumer can checkpoint the
+-- last id it applied and re-read only what changed since. Entries carry the
+-- key, not the row: consumers fetch current state themselves. The GC task
+-- drops entries older than GC_CHANGEFEED_RETENTION_DAYS.
+
+CREATE TABLE IF NOT EXISTS changefeed (
+    id            BIGSERIAL   PRIMARY KEY,
+    entity_type   TEXT        NOT NULL
+                  CHECK (entity_type IN ('charm', 'asset', 'dex_order', 'stats_holder')),
+    entity_key    TEXT        NOT NULL,
+    operation     TEXT        NOT NULL CHECK (operation IN ('insert', 'update')),
+    block_height  INTEGER,
+    network       TEXT        NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_changefeed_created_at
+    ON changefeed (created_at);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260809_000001_changefeed')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,25 @@
+-- Migration: m20260810_000001_parser_stats
+-- Purpose: hourly parser counters for the parser health dashboard. For each
+-- network, source (confirmed blocks or mempool) and hour: transactions
+-- scanned, those carrying a spell envelope, spells parsed, and the txs
+-- yielding no charm by reason. The indexer adds its in-memory counters after
+-- each block; GET /stats/parser sums the buckets of a time range and /status
+-- reports the all-time totals.
+
+CREATE TABLE IF NOT EXISTS parser_stats (
+    network              TEXT        NOT NULL,
+    source               TEXT        NOT NULL CHECK (source IN ('block', 'mempool')),
+    bucket               TIMESTAMPTZ NOT NULL,
+    txs_scanned          BIGINT      NOT NULL DEFAULT 0,
+    envelopes_found      BIGINT      NOT NULL DEFAULT 0,
+    parsed               BIGINT      NOT NULL DEFAULT 0,
+    no_envelope          BIGINT      NOT NULL DEFAULT 0,
+    cbor_error           BIGINT      NOT NULL DEFAULT 0,
+    version_unsupported  BIGINT      NOT NULL DEFAULT 0,
+    verification_failed  BIGINT      NOT NULL DEFAULT 0,
+    PRIMARY KEY (network, source, bucket)
+);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260810_000001_parser_stats')
+ON CONFLICT (version) DO NOTHING;
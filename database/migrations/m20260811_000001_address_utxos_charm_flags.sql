@@ -0,0 +1,32 @@
+-- Migration: m20260811_000001_address_utxos_charm_flags
+-- Purpose: store on each monitored UTXO whether it carries charms, instead of
+-- wallets matching the address's charms against its UTXOs on every request.
+--
+-- address_utxos.has_charms    — a charm is stored for this outpoint.
+--                               NULL = not annotated yet (rows seeded by the
+--                               API); wallets fall back to matching charms.
+-- address_utxos.charm_app_ids — JSON array of the app_ids on this outpoint,
+--                               sorted; empty without charms.
+--
+-- The indexer annotates a UTXO after saving the charms of the tx that
+-- created it (block, mempool and address seeding). Backfill: every existing
+-- row, from the charms stored for its outpoint, whatever their address.
+
+ALTER TABLE address_utxos ADD COLUMN IF NOT EXISTS has_charms BOOLEAN;
+ALTER TABLE address_utxos ADD COLUMN IF NOT EXISTS charm_app_ids JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+UPDATE address_utxos u
+   SET charm_app_ids = COALESCE(
+           (SELECT jsonb_agg(DISTINCT c.app_id ORDER BY c.app_id)
+              FROM charms c
+             WHERE c.txid = u.txid AND c.vout = u.vout AND c.network = u.network),
+           '[]'::jsonb)
+ WHERE u.has_charms IS NULL;
+
+UPDATE address_utxos
+   SET has_charms = charm_app_ids <> '[]'::jsonb
+ WHERE has_charms IS NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260811_000001_address_utxos_charm_flags')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,18 @@
+-- Migration: m20260812_000001_transactions_spell_outputs
+-- Purpose: store each spell's per-output app map next to its JSON, so
+-- wallets building a spending spell need not re-parse `native_data`.
+--
+-- transactions.spell_outputs — [{"vout": i, "charms": {app_id: data}}] for
+--                              the outputs the spell assigns charms to
+--                              (see charms_core::spell_outputs). NULL = not
+--                              derived yet; the API derives it on request.
+--
+-- The indexer fills it whenever it writes the transaction (blocks, mempool,
+-- reindex). Existing rows: `charms-indexer backfill-spell-outputs`, which
+-- derives it from the stored `charm` JSON.
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS spell_outputs JSONB;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260812_000001_transactions_spell_outputs')
+ON CONFLICT (version) DO NOTHING;
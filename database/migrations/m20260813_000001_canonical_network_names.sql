@@ -0,0 +1,83 @@
+-- Migration: m20260813_000001_canonical_network_names
+-- Purpose: rewrite legacy network spellings to the canonical names, so an
+-- exact `network = 'testnet4'` filter finds every row.
+--
+-- Canonical (charms_core::network):
+--   mainnet  <- Mainnet, main, Bitcoin-mainnet
+--   testnet4 <- Testnet, testnet, Bitcoin-testnet4 (NetworkId's display
+--               form, the old bookmark default), Bitcoin-testnet
+-- Matching ignores case and surrounding whitespace.
+--
+-- A legacy row whose canonical twin already exists (same key) is dropped
+-- rather than rewritten: the canonical row is the one the indexer keeps
+-- up to date. Tables: charms, assets, transactions, block_status and the
+-- legacy bookmark table when it is still present.
+
+-- Dropped again at the end; new writes are canonical on the Rust side.
+CREATE OR REPLACE FUNCTION canonical_network(raw TEXT) RETURNS TEXT
+LANGUAGE SQL IMMUTABLE AS $$
+    SELECT CASE lower(btrim(raw))
+        WHEN 'mainnet' THEN 'mainnet'
+        WHEN 'main' THEN 'mainnet'
+        WHEN 'bitcoin-mainnet' THEN 'mainnet'
+        WHEN 'testnet4' THEN 'testnet4'
+        WHEN 'testnet' THEN 'testnet4'
+        WHEN 'bitcoin-testnet4' THEN 'testnet4'
+        WHEN 'bitcoin-testnet' THEN 'testnet4'
+        ELSE raw
+    END
+$$;
+
+-- charms: PRIMARY KEY (txid, vout, app_id, network)
+DELETE FROM charms legacy
+ USING charms canon
+ WHERE canon.network = canonical_network(legacy.network)
+   AND legacy.network <> canon.network
+   AND (canon.txid, canon.vout, canon.app_id) = (legacy.txid, legacy.vout, legacy.app_id);
+UPDATE charms SET network = canonical_network(network)
+ WHERE network <> canonical_network(network);
+
+-- assets: UNIQUE (app_id, network)
+DELETE FROM assets legacy
+ USING assets canon
+ WHERE canon.network = canonical_network(legacy.network)
+   AND legacy.network <> canon.network
+   AND canon.app_id = legacy.app_id;
+UPDATE assets SET network = canonical_network(network)
+ WHERE network <> canonical_network(network);
+
+-- transactions: keyed by txid alone
+UPDATE transactions SET network = canonical_network(network)
+ WHERE network <> canonical_network(network);
+
+-- block_status: PRIMARY KEY (block_height, network, blockchain)
+DELETE FROM block_status legacy
+ USING block_status canon
+ WHERE canon.network = canonical_network(legacy.network)
+   AND legacy.network <> canon.network
+   AND (canon.block_height, canon.blockchain) = (legacy.block_height, legacy.blockchain);
+UPDATE block_status SET network = canonical_network(network)
+ WHERE network <> canonical_network(network);
+
+-- bookmark: PRIMARY KEY (hash, network, blockchain); superseded by
+-- block_status, only present on old deployments.
+DO $$
+BEGIN
+    IF to_regclass('bookmark') IS NOT NULL THEN
+        DELETE FROM bookmark legacy
+         USING bookmark canon
+         WHERE canon.network = canonical_network(legacy.network)
+           AND legacy.network <> canon.network
+           AND (canon.hash, canon.blockchain) = (legacy.hash, legacy.blockchain);
+        UPDATE bookmark SET network = canonical_network(network)
+         WHERE network <> canonical_network(network);
+        ALTER TABLE bookmark ALTER COLUMN network SET DEFAULT 'testnet4';
+    END IF;
+END
+$$;
+
+DROP FUNCTION canonical_network(TEXT);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260813_000001_canonical_network_names')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,19 @@
+-- Migration: m20260814_000001_reindex_locks
+-- Purpose: one row per network while a `reindex` run rewrites it. The live
+-- block processor checks the row every loop and stands still until it is
+-- gone, so the two never process the same blocks at once. The run refreshes
+-- `heartbeat_at` as it goes and deletes the row when it ends; a row whose
+-- heartbeat went stale (crashed run) no longer holds the network.
+
+CREATE TABLE IF NOT EXISTS reindex_locks (
+    network       TEXT        PRIMARY KEY,
+    run_id        TEXT        NOT NULL,
+    from_height   BIGINT      NOT NULL,
+    to_height     BIGINT      NOT NULL,
+    started_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    heartbeat_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260814_000001_reindex_locks')
+ON CONFLICT (version) DO NOTHING;
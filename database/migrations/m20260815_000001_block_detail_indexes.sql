@@ -0,0 +1,15 @@
+-- Migration: m20260815_000001_block_detail_indexes
+-- Purpose: keep the sections of GET /blocks/{height} index lookups on
+-- (network, height). Spells, charms and live spent charms already have one;
+-- DEX orders (created, filled or cancelled at the block) and archived charms
+-- spent by it did not.
+
+CREATE INDEX IF NOT EXISTS idx_dex_orders_net_block
+    ON dex_orders (network, block_height);
+
+CREATE INDEX IF NOT EXISTS idx_charms_archive_spent_height
+    ON charms_archive (network, spent_height);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260815_000001_block_detail_indexes')
+ON CONFLICT (version) DO NOTHING;
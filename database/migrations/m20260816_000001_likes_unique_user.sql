@@ -0,0 +1,19 @@
+-- Migration: m20260816_000001_likes_unique_user
+-- Purpose: one like per (charm_id, user_id). Databases created from the
+-- init schema already have the constraint; others could collect duplicate
+-- rows from double-clicks, since the like was inserted after a separate
+-- existence check. Keep the oldest row of each pair, then enforce it; the
+-- index takes the init constraint's name so it is skipped where that exists.
+
+DELETE FROM likes l
+ USING likes d
+ WHERE l.charm_id = d.charm_id
+   AND l.user_id = d.user_id
+   AND l.id > d.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS likes_charm_id_user_id_key
+    ON likes (charm_id, user_id);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260816_000001_likes_unique_user')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,35 @@
+-- Migration: m20260817_000001_admin_audit_log
+-- Purpose: a trace of every admin mutation (reset, moderation,
+-- pause/resume, metadata refresh, webhooks). The API's admin router writes
+-- the row before the handler runs and completes it after, so a call that
+-- never finished still shows up, with `finished_at` null.
+--
+-- `key_fingerprint` is a truncated SHA-256 of the bearer token, never the
+-- token itself; secrets in `parameters` and `result` are redacted.
+
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id              BIGSERIAL   PRIMARY KEY,
+    -- Method and route, e.g. 'POST /admin/moderate'
+    action          TEXT        NOT NULL,
+    -- Path, query and body parameters as sent
+    parameters      JSONB       NOT NULL DEFAULT '{}',
+    key_fingerprint TEXT,
+    source_ip       TEXT,
+    -- X-Admin-User, 'admin' when absent
+    requested_by    TEXT        NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at     TIMESTAMPTZ,
+    success         BOOLEAN,
+    status_code     INTEGER,
+    -- The handler's JSON response (affected rows, error message)
+    result          JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_created
+    ON admin_audit_log (created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_action
+    ON admin_audit_log (action, created_at DESC);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260817_000001_admin_audit_log')
+ON CONFLICT (version) DO NOTHING;
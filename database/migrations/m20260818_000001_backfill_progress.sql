@@ -0,0 +1,35 @@
+-- Migration: m20260818_000001_backfill_progress
+-- Purpose: one-off data repairs (block times, address forms, flags) run in
+-- small batches instead of one long UPDATE. Each backfill registered in the
+-- indexer has one row here holding its cursor, so an interrupted run picks
+-- up after the last committed batch instead of starting over.
+--
+-- `last_key` is the highest primary key (e.g. block height) whose batch has
+-- committed; NULL means nothing ran yet. Each batch commits together with
+-- its cursor move. The indexer inserts an `idle` row per backfill at
+-- startup; operators queue one with `POST /admin/backfills/{name}` (status
+-- `pending`, picked up by the live indexer) or run it with
+-- `charms-indexer backfill <name>`.
+
+CREATE TABLE IF NOT EXISTS backfill_progress (
+    name            TEXT        PRIMARY KEY,
+    status          TEXT        NOT NULL DEFAULT 'idle'
+                                CHECK (status IN ('idle', 'pending', 'running', 'paused', 'done', 'failed')),
+    last_key        BIGINT,
+    rows_updated    BIGINT      NOT NULL DEFAULT 0,
+    batches         INTEGER     NOT NULL DEFAULT 0,
+    -- Declared by the backfill: re-running a finished one is harmless
+    idempotent      BOOLEAN     NOT NULL DEFAULT TRUE,
+    -- Start from scratch on the next claim (idempotent backfills only)
+    restart         BOOLEAN     NOT NULL DEFAULT FALSE,
+    requested_by    TEXT,
+    requested_at    TIMESTAMPTZ,
+    started_at      TIMESTAMPTZ,
+    heartbeat_at    TIMESTAMPTZ,
+    finished_at     TIMESTAMPTZ,
+    error           TEXT
+);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260818_000001_backfill_progress')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,18 @@
+-- Migration: m20260819_000001_wallet_dex_orders
+-- Purpose: serve GET /wallet/orders/{address} from index lookups. A wallet
+-- reads its maker's live orders, then the fill rows pointing back at them
+-- through parent_order_id. idx_dex_orders_maker (maker, network) also holds
+-- every filled and cancelled order and activity row of busy makers; the
+-- partial index below only holds what is still on the book.
+
+CREATE INDEX IF NOT EXISTS idx_dex_orders_maker_live
+    ON dex_orders (network, maker)
+    WHERE status IN ('open', 'partial');
+
+CREATE INDEX IF NOT EXISTS idx_dex_orders_parent
+    ON dex_orders (parent_order_id)
+    WHERE parent_order_id IS NOT NULL;
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260819_000001_wallet_dex_orders')
+ON CONFLICT (version) DO NOTHING;
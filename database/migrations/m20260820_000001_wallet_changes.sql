@@ -0,0 +1,65 @@
+-- Migration: m20260820_000001_wallet_changes
+-- Purpose: serve GET /wallet/changes/{address}, which returns what changed
+-- for a wallet after a height instead of its full state.
+--
+-- address_utxo_spends — the indexer deletes a monitored UTXO when a block
+--   spends it, which left nothing to report as removed. The row now moves
+--   here with the spending txid and height. The GC drops rows more than
+--   GC_UTXO_SPENDS_RETENTION_BLOCKS below the tip; a reorg rollback drops
+--   the ones spent above the fork.
+-- wallet_sync_horizon — per network, the lowest since_height the endpoint
+--   can still answer from. Raised whenever history it needs is dropped
+--   (old spend rows, spent charms pruned without archiving); starts at the
+--   current tip, since no spend rows exist before this migration.
+-- monitored_addresses.synced_from — height from which the indexer has
+--   tracked the address's UTXOs without a gap: set by its first seed, kept
+--   by later re-seeds, cleared when the GC demotes the address.
+
+CREATE TABLE IF NOT EXISTS address_utxo_spends (
+    txid           TEXT    NOT NULL,
+    vout           INTEGER NOT NULL,
+    network        TEXT    NOT NULL,
+    address        TEXT    NOT NULL,
+    value          BIGINT  NOT NULL,
+    block_height   INTEGER,
+    spending_txid  TEXT    NOT NULL,
+    spent_height   INTEGER NOT NULL,
+    PRIMARY KEY (txid, vout, network)
+);
+
+CREATE INDEX IF NOT EXISTS idx_address_utxo_spends_address
+    ON address_utxo_spends (network, address, spent_height);
+
+CREATE INDEX IF NOT EXISTS idx_address_utxo_spends_height
+    ON address_utxo_spends (network, spent_height);
+
+CREATE TABLE IF NOT EXISTS wallet_sync_horizon (
+    network      TEXT        PRIMARY KEY,
+    from_height  INTEGER     NOT NULL,
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO wallet_sync_horizon (network, from_height)
+SELECT network, last_processed_block FROM summary
+ON CONFLICT (network) DO NOTHING;
+
+ALTER TABLE monitored_addresses ADD COLUMN IF NOT EXISTS synced_from INTEGER;
+
+UPDATE monitored_addresses
+   SET synced_from = seed_height
+ WHERE seeded_at IS NOT NULL AND synced_from IS NULL AND seed_height > 0;
+
+-- Per-address windows over the hot charms table and the archive.
+CREATE INDEX IF NOT EXISTS idx_charms_address_block_height
+    ON charms (network, address, block_height);
+
+CREATE INDEX IF NOT EXISTS idx_charms_address_spent_height
+    ON charms (network, address, spent_height)
+    WHERE spent;
+
+CREATE INDEX IF NOT EXISTS idx_charms_archive_address_spent_height
+    ON charms_archive (network, address, spent_height);
+
+INSERT INTO seaql_migrations (version)
+VALUES ('m20260820_000001_wallet_changes')
+ON CONFLICT (version) DO NOTHING;
@@ -0,0 +1,189 @@
+//! Spent-charm pruning for lean deployments.
+//!
+//! Explorers that only serve current balances do not need every spent charm
+//! in the hot `charms` table. With `PRUNE_SPENT_CHARMS_AFTER_BLOCKS=N`, this
+//! task moves charms whose `spent_height` is more than N blocks below the
+//! network's processed tip into `charms_archive` (same columns), or deletes
+//! them when `ARCHIVE=false`. It runs every `GC_INTERVAL_SECS`, in batches of
+//! `GC_BATCH_SIZE` rows.
+//!
+//! Always kept in `charms`:
+//! - rows of an asset's deploy transaction (`assets.deploy_txid`);
+//! - rows of a transaction named in `supply_changes`, so every recorded
+//!   supply write still points at the charms that caused it;
+//! - rows with no `spent_height` (spent before the column existed by a tx
+//!   the explorer never indexed).
+//!
+//! N is raised to `MIN_PRUNE_DEPTH` so a reorg rollback never has to touch
+//! an archived row. Reads that must see archived rows (the API detail
+//! endpoints, `verify`, `reindex`) consult `charms_archive` themselves; a
+//! reindex restores the archived rows of its range before reprocessing.
+//! Deleting instead raises the network's `wallet_sync_horizon` past the
+//! dropped spends, so the API's `/wallet/changes` stops answering cursors
+//! that would miss them.
+
+use std::time::Duration;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement};
+use tokio_util::sync::CancellationToken;
+
+use crate::utils::logging;
+
+/// Lowest accepted depth: the reorg walk's own limit, so rows that a
+/// rollback could still delete or rewrite stay in `charms`.
+pub const MIN_PRUNE_DEPTH: u64 = 100;
+
+/// Batch of prunable rows (`$1` rows, spent at least `$2` blocks below the
+/// tip). The tip is the highest processed block of the row's network.
+const PRUNABLE_CTE: &str = "
+    tips AS (
+        SELECT network, MAX(block_height) AS tip
+          FROM block_status
+         WHERE processed
+      GROUP BY network
+    ),
+    doomed AS (
+        SELECT c.ctid, c.network, c.spent_height
+          FROM charms c
+          JOIN tips t ON t.network = c.network
+         WHERE c.spent
+           AND c.spent_height <= t.tip - $2
+           AND NOT EXISTS (SELECT 1 FROM assets a
+                            WHERE a.deploy_txid = c.txid AND a.network = c.network)
+           AND NOT EXISTS (SELECT 1 FROM supply_changes s
+                            WHERE s.txid = c.txid AND s.network = c.network)
+         LIMIT $1
+    )";
+
+/// Columns copied to `charms_archive`, named so the move does not depend on
+/// both tables keeping the same column order.
+const ARCHIVED_COLUMNS: &str = "txid, vout, block_height, data, date_created, asset_type, \
+    blockchain, network, address, spent, app_id, amount, mempool_detected_at, tags, verified, \
+    block_hash, tx_ordinal, is_placeholder, spending_txid, confirmation_delay_secs, \
+    indexer_version, parser_revision, reindex_run_id, operation, moderation_status, \
+    spent_height, block_time";
+
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    /// Pause between passes.
+    pub interval: Duration,
+    /// Rows moved per statement.
+    pub batch_size: u64,
+    /// Blocks a charm must have been spent for before it is pruned.
+    pub after_blocks: u64,
+    /// Move pruned rows to `charms_archive`; when false they are deleted.
+    pub archive: bool,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(6 * 3600),
+            batch_size: 1000,
+            after_blocks: 1000,
+            archive: true,
+        }
+    }
+}
+
+pub struct CharmArchiver {
+    conn: DatabaseConnection,
+    cfg: ArchiveConfig,
+}
+
+impl CharmArchiver {
+    pub fn new(conn: DatabaseConnection, cfg: ArchiveConfig) -> Self {
+        Self { conn, cfg }
+    }
+
+    /// Run a pass every `interval` until cancelled. Like the GC, the first
+    /// pass waits one interval.
+    pub async fn run(self, cancel: CancellationToken) {
+        logging::log_info(&format!(
+            "[archive] 🗄️ CharmArchiver started (every {}s, spent charms older than {} blocks {})",
+            self.cfg.interval.as_secs(),
+            self.depth(),
+            self.verb()
+        ));
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.cfg.interval) => {}
+                _ = cancel.cancelled() => {
+                    logging::log_info("[archive] 🛑 CharmArchiver stopping (cancellation requested)");
+                    return;
+                }
+            }
+            match self.run_once().await {
+                Ok(0) => {}
+                Ok(n) => logging::log_info(&format!(
+                    "[archive] 🗄️ {} spent charm(s) {}",
+                    n,
+                    self.verb()
+                )),
+                Err(e) => logging::log_warning(&format!("[archive] ⚠️ Pass failed: {}", e)),
+            }
+        }
+    }
+
+    /// Prune every eligible row, `batch_size` at a time. Returns the rows
+    /// removed from `charms`.
+    pub async fn run_once(&self) -> Result<u64, DbErr> {
+        let batch_size = self.cfg.batch_size.max(1);
+        let sql = self.statement();
+
+        let mut moved = 0;
+        loop {
+            let res = self
+                .conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    &sql,
+                    [(batch_size as i64).into(), (self.depth() as i64).into()],
+                ))
+                .await?;
+            moved += res.rows_affected();
+            if res.rows_affected() < batch_size {
+                return Ok(moved);
+            }
+        }
+    }
+
+    fn depth(&self) -> u64 {
+        self.cfg.after_blocks.max(MIN_PRUNE_DEPTH)
+    }
+
+    fn verb(&self) -> &'static str {
+        if self.cfg.archive {
+            "archived"
+        } else {
+            "deleted"
+        }
+    }
+
+    /// One batch. Archiving moves the rows in a single statement, so a row
+    /// is never in both tables or in neither.
+    fn statement(&self) -> String {
+        if self.cfg.archive {
+            format!(
+                "WITH {PRUNABLE_CTE}, \
+                 moved AS (DELETE FROM charms WHERE ctid IN (SELECT ctid FROM doomed) \
+                           RETURNING {ARCHIVED_COLUMNS}) \
+                 INSERT INTO charms_archive ({ARCHIVED_COLUMNS}) \
+                 SELECT {ARCHIVED_COLUMNS} FROM moved \
+                 ON CONFLICT (txid, vout, app_id, network) DO NOTHING"
+            )
+        } else {
+            format!(
+                "WITH {PRUNABLE_CTE}, \
+                 horizon AS ( \
+                   INSERT INTO wallet_sync_horizon (network, from_height) \
+                   SELECT network, MAX(spent_height) FROM doomed GROUP BY network \
+                   ON CONFLICT (network) DO UPDATE \
+                      SET from_height = GREATEST(wallet_sync_horizon.from_height, \
+                                                 EXCLUDED.from_height), \
+                          updated_at = NOW()) \
+                 DELETE FROM charms WHERE ctid IN (SELECT ctid FROM doomed)"
+            )
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Runs backfills operators request through `POST /admin/backfills/{name}`
+//! inside the live indexer.
+//!
+//! The worker registers every known backfill at startup (so the API can
+//! list them), then polls `backfill_progress` for a `pending` row, or a
+//! `running` one whose heartbeat went stale, and drives it with the
+//! rate-limited `BackfillRunner`. One backfill runs at a time; on shutdown
+//! the current one stops between batches and is queued again.
+
+use std::time::Duration;
+
+use sea_orm::DatabaseConnection;
+use tokio_util::sync::CancellationToken;
+
+use crate::application::maintenance::backfill::{BackfillConfig, BackfillRunner};
+use crate::utils::logging;
+
+pub struct BackfillWorker {
+    runner: BackfillRunner,
+    poll_interval: Duration,
+}
+
+impl BackfillWorker {
+    pub fn new(conn: DatabaseConnection, cfg: BackfillConfig) -> Self {
+        Self {
+            runner: BackfillRunner::new(conn, cfg),
+            poll_interval: Duration::from_secs(10),
+        }
+    }
+
+    /// Poll for requested backfills until cancelled.
+    pub async fn run(self, cancel: CancellationToken) {
+        logging::log_info("[backfill] 🧩 BackfillWorker started");
+        if let Err(e) = self.runner.register_all().await {
+            logging::log_warning(&format!(
+                "[backfill] ⚠️ Failed to register backfills: {}",
+                e
+            ));
+        }
+        loop {
+            match self.runner.run_pending(&cancel).await {
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    logging::log_warning(&format!("[backfill] ⚠️ Backfill failed: {}", e))
+                }
+                None => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(self.poll_interval) => {}
+                        _ = cancel.cancelled() => {}
+                    }
+                }
+            }
+            if cancel.is_cancelled() {
+                logging::log_info("[backfill] 🛑 BackfillWorker stopping (cancellation requested)");
+                return;
+            }
+        }
+    }
+}
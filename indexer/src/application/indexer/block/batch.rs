@@ -1,11 +1,14 @@
 //! Batch processor for handling bulk operations on charms and transactions
 
+use charms_core::{AppKind, AssetType, CharmOperation};
+use chrono::NaiveDateTime;
 use serde_json::Value;
 
 use crate::config::NetworkId;
 use crate::domain::errors::BlockProcessorError;
+use crate::domain::models::TransactionStatus;
 use crate::domain::services::CharmService;
-use crate::infrastructure::persistence::repositories::TransactionRepository;
+use crate::infrastructure::persistence::repositories::{MintEventsRepository, TransactionRepository};
 use crate::utils::logging;
 
 /// Handles batch processing of charms and transactions
@@ -13,13 +16,19 @@ use crate::utils::logging;
 pub struct BatchProcessor {
     charm_service: CharmService,
     transaction_repository: TransactionRepository,
+    mint_events_repository: MintEventsRepository,
 }
 
 impl BatchProcessor {
-    pub fn new(charm_service: CharmService, transaction_repository: TransactionRepository) -> Self {
+    pub fn new(
+        charm_service: CharmService,
+        transaction_repository: TransactionRepository,
+        mint_events_repository: MintEventsRepository,
+    ) -> Self {
         Self {
             charm_service,
             transaction_repository,
+            mint_events_repository,
         }
     }
 
@@ -53,10 +62,10 @@ impl BatchProcessor {
     ///
     /// Returns the positive holder deltas that the block processor must merge
     /// with the negative deltas coming from `mark_spent_charms` before
-    /// calling `update_holders_batch` ONCE per block. Doing one merged update
-    /// per (app_id, address) preserves the `last_updated_block < block` gate
-    /// (crash-recovery safety) while still letting within-block adds + spends
-    /// net correctly — the bug captured as anomaly A1 in the test report.
+    /// calling `apply_block_deltas` ONCE per block. Doing one merged update
+    /// per (app_id, address) keeps the per-block claim (crash-recovery
+    /// safety) while still letting within-block adds + spends net correctly
+    /// — the bug captured as anomaly A1 in the test report.
     pub async fn save_charm_batch(
         &self,
         batch: Vec<CharmBatchItem>,
@@ -80,20 +89,17 @@ impl BatchProcessor {
                 if addr.is_empty() {
                     return None;
                 }
-                if c.app_id.starts_with("t/") {
-                    if c.amount <= 0 {
-                        return None;
-                    }
-                    Some((
-                        crate::domain::services::app_id::token_to_nft(&c.app_id),
+                match AppKind::of(&c.app_id) {
+                    AppKind::Token if c.amount > 0 => Some((
+                        charms_core::token_to_nft(&c.app_id),
                         addr.clone(),
                         c.amount,
                         c.block_height as i32,
-                    ))
-                } else if c.app_id.starts_with("n/") {
-                    Some((c.app_id.clone(), addr.clone(), 1_i64, c.block_height as i32))
-                } else {
-                    None
+                    )),
+                    AppKind::Nft => {
+                        Some((c.app_id.clone(), addr.clone(), 1_i64, c.block_height as i32))
+                    }
+                    _ => None,
                 }
             })
             .collect();
@@ -135,6 +141,36 @@ impl BatchProcessor {
         .await
     }
 
+    /// Save mint event batch with retry logic
+    pub async fn save_mint_event_batch(
+        &self,
+        batch: Vec<MintEventBatchItem>,
+        height: u64,
+        network_id: &NetworkId,
+    ) -> Result<(), BlockProcessorError> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let tuples: Vec<_> = batch
+            .into_iter()
+            .map(MintEventBatchItem::into_tuple)
+            .collect();
+        self.execute_batch_save(
+            "mint event",
+            tuples.len(),
+            height,
+            network_id,
+            || async {
+                self.mint_events_repository
+                    .record_batch(&tuples, &network_id.name)
+                    .await
+            },
+            BlockProcessorError::DbError,
+        )
+        .await
+    }
+
     /// Generic batch save execution with retry logic
     async fn execute_batch_save<F, Fut, E, ErrMapper>(
         &self,
@@ -188,7 +224,7 @@ pub struct TransactionBatchItem {
     pub raw_json: Value,
     pub charm_data: Value,
     pub confirmations: i32,
-    pub is_confirmed: bool,
+    pub status: TransactionStatus,
     pub blockchain: String,
     pub network: String,
     pub tags: Option<String>,
@@ -207,7 +243,7 @@ impl TransactionBatchItem {
         Value,
         Value,
         i32,
-        bool,
+        TransactionStatus,
         String,
         String,
         Option<String>,
@@ -220,7 +256,7 @@ impl TransactionBatchItem {
             self.raw_json,
             self.charm_data,
             self.confirmations,
-            self.is_confirmed,
+            self.status,
             self.blockchain,
             self.network,
             self.tags,
@@ -236,17 +272,23 @@ pub struct CharmBatchItem {
     pub vout: i32,
     pub block_height: u64,
     pub data: Value,
-    pub asset_type: String,
+    pub asset_type: AssetType,
     pub blockchain: String,
     pub network: String,
     pub address: Option<String>,
     pub app_id: String,
     pub amount: i64,
     pub tags: Option<String>,
+    pub block_hash: Option<String>,
+    pub tx_ordinal: Option<i32>,
+    pub operation: CharmOperation,
+    /// Header time of the block; `None` when the reindexer has no stored
+    /// time for it.
+    pub block_time: Option<NaiveDateTime>,
 }
 
 impl CharmBatchItem {
-    /// Repos still consume the historical 11-tuple shape; this preserves
+    /// Repos still consume the historical tuple shape; this preserves
     /// the wire format until they migrate too.
     #[allow(clippy::type_complexity)]
     pub fn into_tuple(
@@ -263,19 +305,49 @@ impl CharmBatchItem {
         String,
         i64,
         Option<String>,
+        Option<String>,
+        Option<i32>,
+        Option<String>,
+        Option<NaiveDateTime>,
     ) {
         (
             self.txid,
             self.vout,
             self.block_height,
             self.data,
-            self.asset_type,
+            self.asset_type.to_string(),
             self.blockchain,
             self.network,
             self.address,
             self.app_id,
             self.amount,
             self.tags,
+            self.block_hash,
+            self.tx_ordinal,
+            Some(self.operation.to_string()),
+            self.block_time,
+        )
+    }
+}
+
+/// A net supply increase for one app_id in one transaction.
+#[derive(Debug, Clone)]
+pub struct MintEventBatchItem {
+    pub app_id: String,
+    pub txid: String,
+    pub block_height: u64,
+    pub minted_amount: i64,
+    pub minter_address: Option<String>,
+}
+
+impl MintEventBatchItem {
+    pub fn into_tuple(self) -> (String, String, u64, i64, Option<String>) {
+        (
+            self.app_id,
+            self.txid,
+            self.block_height,
+            self.minted_amount,
+            self.minter_address,
         )
     }
 }
@@ -287,7 +359,7 @@ pub struct AssetBatchItem {
     pub txid: String,
     pub vout: i32,
     pub block_height: u64,
-    pub asset_type: String,
+    pub asset_type: AssetType,
     pub supply: u64,
     pub blockchain: String,
     pub network: String,
@@ -299,6 +371,7 @@ pub struct AssetBatchItem {
     pub cardano_policy_id: Option<String>,
     pub cardano_asset_name: Option<String>,
     pub cardano_fingerprint: Option<String>,
+    pub collection: Option<String>,
 }
 
 impl AssetBatchItem {
@@ -323,13 +396,14 @@ impl AssetBatchItem {
         Option<String>,
         Option<String>,
         Option<String>,
+        Option<String>,
     ) {
         (
             self.app_id,
             self.txid,
             self.vout,
             self.block_height,
-            self.asset_type,
+            self.asset_type.to_string(),
             self.supply,
             self.blockchain,
             self.network,
@@ -341,6 +415,7 @@ impl AssetBatchItem {
             self.cardano_policy_id,
             self.cardano_asset_name,
             self.cardano_fingerprint,
+            self.collection,
         )
     }
 }
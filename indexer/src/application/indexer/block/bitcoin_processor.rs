@@ -1,21 +1,32 @@
 //! Top-level Bitcoin block processor: owns the live loop and delegates
 //! per-block work to `BlockProcessor`.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use tokio::time;
 use tokio_util::sync::CancellationToken;
 
+use crate::application::indexer::control::PauseGate;
+use crate::application::indexer::leader::LeaderGate;
 use crate::application::indexer::processor_trait::BlockchainProcessor;
 use crate::config::{AppConfig, NetworkId};
 use crate::domain::errors::BlockProcessorError;
+use crate::domain::models::CONFIRMATION_DEPTH;
 use crate::domain::services::CharmService;
 use crate::infrastructure::bitcoin::BitcoinClient;
 use crate::infrastructure::persistence::Repositories;
 use crate::utils::logging;
 
+use super::pending_spells;
 use super::processor::BlockProcessor;
+use super::progress::{ProgressConfig, ProgressReporter};
+use super::{rescan, skipped_blocks};
+
+/// Minimum time between two passes over skipped (pruned/missing) blocks.
+const SKIPPED_RETRY_INTERVAL: Duration = Duration::from_secs(600);
+/// Skipped blocks re-attempted per pass.
+const SKIPPED_RETRY_BATCH: u64 = 10;
 
 /// Top-level processor: handles the live block processing loop.
 #[derive(Debug)]
@@ -26,6 +37,14 @@ pub struct BitcoinProcessor {
     config: AppConfig,
     current_height: u64,
     genesis_block_height: u64,
+    leader: LeaderGate,
+    /// Provider the skipped-block retry pass prefers, when configured.
+    fallback_client: Option<BitcoinClient>,
+    last_skipped_retry: Option<Instant>,
+    /// Set once the startup rescan ran (or was off); later promotions skip it.
+    rescanned: bool,
+    /// Shared by the per-block processors of the live loop.
+    progress: ProgressReporter,
 }
 
 impl BitcoinProcessor {
@@ -36,6 +55,11 @@ impl BitcoinProcessor {
         config: AppConfig,
         genesis_block_height: u64,
     ) -> Self {
+        let progress = ProgressReporter::new(ProgressConfig {
+            every_blocks: config.indexer.progress_every_blocks.max(1),
+            every: Duration::from_secs(config.indexer.progress_interval_secs),
+            ..ProgressConfig::default()
+        });
         Self {
             bitcoin_client,
             charm_service,
@@ -43,9 +67,27 @@ impl BitcoinProcessor {
             current_height: genesis_block_height,
             config,
             genesis_block_height,
+            leader: LeaderGate::always(),
+            fallback_client: None,
+            last_skipped_retry: None,
+            rescanned: false,
+            progress,
         }
     }
 
+    /// Only process blocks while this replica leads the network.
+    pub fn with_leader_gate(mut self, leader: LeaderGate) -> Self {
+        self.leader = leader;
+        self
+    }
+
+    /// Re-attempt skipped (pruned/missing) blocks through `client` rather
+    /// than the live provider, which already failed to serve them.
+    pub fn with_fallback_client(mut self, client: BitcoinClient) -> Self {
+        self.fallback_client = Some(client);
+        self
+    }
+
     pub fn network_id(&self) -> &NetworkId {
         self.bitcoin_client.network_id()
     }
@@ -56,6 +98,9 @@ impl BitcoinProcessor {
             self.charm_service.clone(),
             &self.repos,
         )
+        .with_offchain_metadata(self.config.indexer.fetch_offchain_metadata)
+        .with_locked_supply_addresses(&self.config.indexer.locked_supply_addresses)
+        .with_progress(self.progress.clone())
     }
 
     pub async fn initialize_block_height(&mut self) {
@@ -96,6 +141,125 @@ impl BitcoinProcessor {
         }
     }
 
+    /// Reprocess spells captured while the parser did not support their
+    /// version. A no-op unless an upgrade raised the supported version.
+    async fn drain_pending_spells(&self) {
+        let bp = self.create_block_processor();
+        let network_id = self.network_id().clone();
+        let result = pending_spells::drain(
+            &self.repos.pending_spells,
+            &self.repos.stats_holders,
+            &network_id.name,
+            |height| {
+                let (bp, network_id) = (&bp, &network_id);
+                async move { bp.process_block(height, network_id).await }
+            },
+        )
+        .await;
+        match result {
+            Ok(summary) if summary == pending_spells::DrainSummary::default() => {}
+            Ok(summary) => logging::log_info(&format!(
+                "[{}] ✅ pending spells drained: {} spell(s) in {} block(s) reprocessed, {} unconfirmed left to the mempool scan",
+                network_id.name, summary.spells, summary.blocks, summary.unconfirmed_dropped
+            )),
+            Err(e) => logging::log_error(&format!(
+                "[{}] ❌ pending spells drain failed: {}",
+                network_id.name, e
+            )),
+        }
+    }
+
+    /// Re-run the last `RESCAN_DEPTH` processed blocks before resuming.
+    async fn rescan_recent_blocks(&mut self) {
+        let Some((from, to)) = rescan::window(
+            self.current_height,
+            self.genesis_block_height,
+            self.config.indexer.rescan_depth,
+        ) else {
+            return;
+        };
+        let bp = self.create_block_processor();
+        let network_id = self.network_id().clone();
+        logging::log_info(&format!(
+            "[{}] 🔁 Rescanning blocks {}..={} before resuming",
+            network_id.name, from, to
+        ));
+        let result = rescan::rescan(
+            &self.repos.charm,
+            &self.repos.stats_holders,
+            &network_id.name,
+            from,
+            to,
+            |height| {
+                let (bp, network_id) = (&bp, &network_id);
+                async move { bp.process_block(height, network_id).await }
+            },
+        )
+        .await;
+        match result {
+            Ok(summary) => {
+                logging::log_info(&format!(
+                    "[{}] ✅ rescan: {} block(s) re-run, {} pruned skipped, {} new charm(s) found",
+                    network_id.name, summary.blocks, summary.pruned, summary.new_charms
+                ));
+                if let Some(h) = summary.rolled_back_to {
+                    self.current_height = self.current_height.min(h + 1);
+                }
+            }
+            Err(e) => logging::log_error(&format!("[{}] ❌ rescan failed: {}", network_id.name, e)),
+        }
+    }
+
+    /// Re-run a few blocks skipped as pruned/missing, at most once per
+    /// `SKIPPED_RETRY_INTERVAL`.
+    async fn retry_skipped_blocks(&mut self) {
+        if self
+            .last_skipped_retry
+            .is_some_and(|at| at.elapsed() < SKIPPED_RETRY_INTERVAL)
+        {
+            return;
+        }
+        self.last_skipped_retry = Some(Instant::now());
+
+        let bp = BlockProcessor::new(
+            self.fallback_client
+                .clone()
+                .unwrap_or_else(|| self.bitcoin_client.clone()),
+            self.charm_service.clone(),
+            &self.repos,
+        )
+        .with_offchain_metadata(self.config.indexer.fetch_offchain_metadata)
+        .with_locked_supply_addresses(&self.config.indexer.locked_supply_addresses);
+        let network_id = self.network_id().clone();
+        let result = skipped_blocks::retry(
+            &self.repos.block_status,
+            &self.repos.stats_holders,
+            &network_id,
+            SKIPPED_RETRY_BATCH,
+            |height| {
+                let (bp, network_id) = (&bp, &network_id);
+                async move { bp.process_block(height, network_id).await }
+            },
+        )
+        .await;
+        match result {
+            Ok(summary) => {
+                if summary.filled > 0 {
+                    logging::log_info(&format!(
+                        "[{}] ✅ skipped blocks: filled in {}, {} still missing",
+                        network_id.name, summary.filled, summary.still_missing
+                    ));
+                }
+                if let Some(h) = summary.rolled_back_to {
+                    self.current_height = self.current_height.min(h + 1);
+                }
+            }
+            Err(e) => logging::log_error(&format!(
+                "[{}] ❌ skipped blocks retry failed: {}",
+                network_id.name, e
+            )),
+        }
+    }
 
     pub async fn process_available_blocks(&mut self) -> Result<(), BlockProcessorError> {
         let latest_height = self.bitcoin_client.get_block_count().await.map_err(|e| {
@@ -129,6 +293,10 @@ impl BitcoinProcessor {
         }
 
         while self.current_height <= latest_height {
+            // Stepped down mid catch-up: the new leader continues from here.
+            if !self.leader.is_leader() {
+                return Ok(());
+            }
             let bp = self.create_block_processor();
 
             match bp
@@ -147,32 +315,27 @@ impl BitcoinProcessor {
                     self.current_height = h + 1;
                 }
                 Err(BlockProcessorError::BitcoinClientError(ref e)) => {
-                    let error_msg = e.to_string().to_lowercase();
-                    if error_msg.contains("pruned")
-                        || error_msg.contains("block not available")
-                        || error_msg.contains("block height out of range")
-                        || error_msg.contains("block not found")
-                    {
+                    // Timeouts are transient: bubble up so `start_processing`
+                    // backs off and retries this height instead of skipping it.
+                    if e.is_block_unavailable() {
                         logging::log_info(&format!(
-                            "[{}] Block {} pruned/missing, skipping",
+                            "[{}] Block {} pruned/missing, skipping (retried later)",
                             self.network_id().name,
                             self.current_height
                         ));
 
-                        let _ = self
+                        if let Err(e) = self
                             .repos.block_status
-                            .mark_downloaded(
-                                self.current_height as i32,
-                                None,
-                                None,
-                                0,
-                                self.network_id(),
-                            )
-                            .await;
-                        let _ = self
-                            .repos.block_status
-                            .mark_processed(self.current_height as i32, 0, self.network_id())
-                            .await;
+                            .mark_skipped_pruned(self.current_height as i32, self.network_id())
+                            .await
+                        {
+                            logging::log_warning(&format!(
+                                "[{}] ⚠️ Failed to record skipped block {}: {}",
+                                self.network_id().name,
+                                self.current_height,
+                                e
+                            ));
+                        }
                         self.current_height += 1;
                     } else {
                         return Err(BlockProcessorError::BitcoinClientError(e.clone()));
@@ -197,7 +360,10 @@ impl BitcoinProcessor {
     /// Retroactively confirm blocks with 6+ confirmations.
     /// Issues a single batch UPDATE so a long backlog of unconfirmed blocks
     /// doesn't fan out into thousands of individual queries (audit N13).
+    /// Transactions saved near the tip are promoted in the same pass.
     async fn confirm_pending_blocks(&self, latest_height: u64) {
+        self.confirm_matured_transactions(latest_height).await;
+
         let unconfirmed = match self
             .repos.block_status
             .get_unconfirmed_blocks(self.network_id())
@@ -216,7 +382,7 @@ impl BitcoinProcessor {
 
         let to_confirm: Vec<i32> = unconfirmed
             .into_iter()
-            .filter(|h| latest_height.saturating_sub(*h as u64) + 1 >= 6)
+            .filter(|h| latest_height.saturating_sub(*h as u64) + 1 >= CONFIRMATION_DEPTH as u64)
             .collect();
 
         if to_confirm.is_empty() {
@@ -240,6 +406,46 @@ impl BitcoinProcessor {
             )),
         }
     }
+
+    /// Promote `confirming` transactions whose block has matured and keep
+    /// the summary's confirmation rate in step.
+    async fn confirm_matured_transactions(&self, latest_height: u64) {
+        const BATCH_SIZE: u64 = 5_000;
+
+        let network_id = self.network_id();
+        let promoted = match self
+            .repos.transaction
+            .confirm_matured(&network_id.name, latest_height, BATCH_SIZE)
+            .await
+        {
+            Ok(n) => n,
+            Err(e) => {
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ Failed to confirm matured transactions: {}",
+                    network_id.name, e
+                ));
+                return;
+            }
+        };
+        if promoted == 0 {
+            return;
+        }
+
+        logging::log_info(&format!(
+            "[{}] ✅ Confirmed {} matured transactions",
+            network_id.name, promoted
+        ));
+        if let Err(e) = self
+            .repos.summary
+            .add_confirmed_transactions(network_id, promoted)
+            .await
+        {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Failed to update confirmed transaction count: {}",
+                network_id.name, e
+            ));
+        }
+    }
 }
 
 #[async_trait]
@@ -259,11 +465,18 @@ impl BlockchainProcessor for BitcoinProcessor {
         // sleep so graceful shutdown stays responsive.
         const MAX_BACKOFF_MS: u64 = 30_000;
 
-        self.initialize_block_height().await;
-
         let base_ms = self.config.indexer.process_interval_ms;
         let mut backoff_ms = base_ms;
         let mut consecutive_errors: u32 = 0;
+        let mut pause_gate = PauseGate::new(
+            self.network_id().clone(),
+            self.repos.control_commands.clone(),
+            self.repos.reindex_locks.clone(),
+            self.repos.summary.clone(),
+        );
+        // The resume height is read on every promotion, since another
+        // replica may have advanced it while this one stood by.
+        let mut leading = false;
 
         loop {
             if cancel.is_cancelled() {
@@ -274,8 +487,37 @@ impl BlockchainProcessor for BitcoinProcessor {
                 return Ok(());
             }
 
+            if !self.leader.is_leader() {
+                leading = false;
+                tokio::select! {
+                    _ = time::sleep(Duration::from_millis(base_ms)) => {}
+                    _ = cancel.cancelled() => return Ok(()),
+                }
+                continue;
+            }
+            if !leading {
+                self.initialize_block_height().await;
+                self.drain_pending_spells().await;
+                if !self.rescanned {
+                    self.rescan_recent_blocks().await;
+                    self.rescanned = true;
+                }
+                leading = true;
+            }
+
+            // Operator pause or running reindex: skip block processing, keep
+            // polling.
+            if pause_gate.is_paused().await {
+                tokio::select! {
+                    _ = time::sleep(Duration::from_millis(base_ms)) => {}
+                    _ = cancel.cancelled() => return Ok(()),
+                }
+                continue;
+            }
+
             match self.process_available_blocks().await {
                 Ok(()) => {
+                    self.retry_skipped_blocks().await;
                     if consecutive_errors > 0 {
                         logging::log_info(&format!(
                             "[{}] ✅ Recovered after {} consecutive RPC error(s); resuming normal cadence",
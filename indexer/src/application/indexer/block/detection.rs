@@ -3,20 +3,49 @@
 //! (supply calculation, metadata extraction, DEX order saving).
 
 use bitcoincore_rpc::bitcoin;
+use charms_core::{AppId, AppKind, AssetType, CharmOperation};
+use chrono::NaiveDateTime;
+use futures::stream::{BoxStream, StreamExt};
 use serde_json::json;
 use std::collections::HashMap;
 
+use crate::domain::models::asset_metadata::parse_collection;
+use crate::domain::models::TransactionStatus;
+use crate::domain::services::address_extractor::OutputAddress;
 use crate::domain::services::dex::{self, extract_ins0_order_id};
 use crate::domain::services::tx_analyzer::{self, AnalyzedTx};
-use crate::domain::services::CharmService;
-use crate::infrastructure::persistence::repositories::DexOrdersRepository;
-use crate::utils::logging;
+use crate::domain::services::{
+    AddressExtractor, AssetInfo, CharmService, NativeCharmParser, SpellFailure,
+};
+use crate::infrastructure::persistence::repositories::{DexOrdersRepository, PendingSpellsRepository};
+use crate::utils::parser_stats::{self, ParseSource};
+use crate::utils::{logging, metrics};
 
-use super::batch::{AssetBatchItem, CharmBatchItem, TransactionBatchItem};
+use super::batch::{AssetBatchItem, CharmBatchItem, MintEventBatchItem, TransactionBatchItem};
+use super::inflight::{InflightBudget, InflightPermit};
+use super::input_amounts::InputAmountCache;
+
+/// Batch items produced by detection: transactions, charms, assets and mint
+/// events.
+pub type DetectedBatches = (
+    Vec<TransactionBatchItem>,
+    Vec<CharmBatchItem>,
+    Vec<AssetBatchItem>,
+    Vec<MintEventBatchItem>,
+);
+
+/// Header time of `block` as a UTC timestamp, the `block_time` of its rows.
+pub fn header_time(block: &bitcoin::Block) -> Option<NaiveDateTime> {
+    chrono::DateTime::from_timestamp(block.header.time as i64, 0).map(|t| t.naive_utc())
+}
 
 /// Detect charms from all transactions in a block.
-/// Returns batch items for transactions, charms, and assets.
-/// No DB writes except DEX order saving.
+/// Returns batch items for transactions, charms, assets and mint events.
+/// No DB writes except DEX order saving and capturing spells of a protocol
+/// version the parser does not support yet into `pending_spells`.
+/// Transactions are serialized lazily under an `INDEXER_MAX_INFLIGHT_BYTES`
+/// budget instead of copying the whole block's hex up front.
+#[allow(clippy::too_many_arguments)]
 pub async fn detect_charms(
     block: &bitcoin::Block,
     height: u64,
@@ -25,35 +54,93 @@ pub async fn detect_charms(
     blockchain: &str,
     charm_service: &CharmService,
     dex_repo: Option<&DexOrdersRepository>,
-) -> (
-    Vec<TransactionBatchItem>,
-    Vec<CharmBatchItem>,
-    Vec<AssetBatchItem>,
-) {
-    let tx_data = extract_transaction_data(block);
+    pending_spells: Option<&PendingSpellsRepository>,
+) -> DetectedBatches {
+    detect_charms_in_txs(
+        block
+            .txdata
+            .iter()
+            .enumerate()
+            .map(|(tx_pos, tx)| TxSource::Block { tx, tx_pos }),
+        &InflightBudget::from_env(),
+        &block.block_hash().to_string(),
+        header_time(block),
+        height,
+        latest_height,
+        network,
+        blockchain,
+        charm_service,
+        dex_repo,
+        dex_repo,
+        pending_spells,
+    )
+    .await
+}
 
+/// Detection over a stream of transactions. Up to one analysis per CPU runs
+/// on the blocking pool while `budget` has room for its bytes; results are
+/// consumed in source order, so the batches come out in block order.
+/// `block_time` is stamped on every charm row; the reindexer passes the time
+/// stored in `block_status`, which is `None` for blocks never downloaded.
+/// `dex_lookups` serves the read-only FULFILL-BID correction; `dex_writes`
+/// and `pending_spells` are the only write targets, so passing `None` for
+/// both makes the pass read-only (reindex dry run). Only a pass with
+/// `pending_spells` counts its txs into the parser statistics.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn detect_charms_in_txs<'a>(
+    txs: impl Iterator<Item = TxSource<'a>> + Send,
+    budget: &InflightBudget,
+    block_hash: &str,
+    block_time: Option<NaiveDateTime>,
+    height: u64,
+    latest_height: u64,
+    network: &str,
+    blockchain: &str,
+    charm_service: &CharmService,
+    dex_lookups: Option<&DexOrdersRepository>,
+    dex_writes: Option<&DexOrdersRepository>,
+    pending_spells: Option<&PendingSpellsRepository>,
+) -> DetectedBatches {
     let mut transaction_batch = Vec::new();
     let mut charm_batch = Vec::new();
     let mut asset_batch: Vec<AssetBatchItem> = Vec::new();
+    let mut mint_batch: Vec<MintEventBatchItem> = Vec::new();
 
-    for ExtractedTx {
-        txid,
-        tx_hex,
-        tx_pos,
-        input_utxos,
-    } in tx_data
-    {
-        let input_txids: Vec<String> = input_utxos.iter().map(|(t, _)| t.clone()).collect();
+    let block = BlockContext {
+        block_hash,
+        block_time,
+        height,
+        latest_height,
+        blockchain,
+        network,
+    };
+    let classify_failures = pending_spells.is_some();
+    let mut analyses = analyze_stream(txs, budget, network, classify_failures);
+    let mut amount_cache = InputAmountCache::new();
 
-        let mut analyzed = match tx_analyzer::analyze_tx(
-            &txid,
-            &tx_hex,
-            network,
-            tx_analyzer::VerifyMode::Strict,
-        ) {
-            Some(a) => a,
-            None => continue,
+    while let Some((analysis, _permit)) = analyses.next().await {
+        let ExtractedTx {
+            txid,
+            tx_hex,
+            tx_pos,
+            input_utxos,
+        } = analysis.tx;
+        if classify_failures {
+            let result = analysis.outcome.parse_result();
+            parser_stats::global().record(network, ParseSource::Block, result);
+        }
+        let mut analyzed = match analysis.outcome {
+            Outcome::Charm(analyzed) => *analyzed,
+            Outcome::Unsupported(version) => {
+                if let Some(repo) = pending_spells {
+                    capture_unsupported_spell(repo, &txid, &tx_hex, version, network, height)
+                        .await;
+                }
+                continue;
+            }
+            Outcome::NotCharm(_) => continue,
         };
+        let input_txids: Vec<String> = input_utxos.iter().map(|(t, _)| t.clone()).collect();
 
         // Detect ADA→BTC claims: spells that create tokens but have no beam marker.
         // Heuristic: users commonly fund the Bitcoin claim tx with outputs from their prior
@@ -94,7 +181,12 @@ pub async fn detect_charms(
             }
         }
 
-        let confirmations = latest_height - height + 1;
+        // Same FULFILL-BID correction as the mempool path, so a charm gets
+        // identical tags whether it was first seen in mempool or in a block.
+        if let Some(repo) = dex_lookups {
+            correct_fulfill_classification(repo, &txid, &tx_hex, &mut analyzed, network, height)
+                .await;
+        }
 
         // Log + save DEX orders
         if let Some(ref dex_res) = analyzed.dex_result {
@@ -103,7 +195,7 @@ pub async fn detect_charms(
                 network, height, txid, dex_res.operation
             ));
 
-            if let Some(repo) = dex_repo {
+            if let Some(repo) = dex_writes {
                 if let Some(ref order) = dex_res.order {
                     // CREATE or PARTIAL: save the order directly
                     match repo
@@ -182,100 +274,154 @@ pub async fn detect_charms(
             ));
         }
 
-        transaction_batch.push(TransactionBatchItem {
-            txid: txid.clone(),
-            block_height: height,
-            position: tx_pos as i64,
-            raw_json: json!({ "hex": tx_hex, "txid": txid }),
-            charm_data: analyzed.charm_json.clone(),
-            confirmations: confirmations as i32,
-            is_confirmed: true,
-            blockchain: blockchain.to_string(),
-            network: network.to_string(),
-            tags: analyzed.tags.clone(),
-            tx_type: Some(analyzed.tx_type.clone()),
-        });
+        // Every verified spell gets its transactions row, whether or not any
+        // of its outputs carries a charm.
+        transaction_batch.push(transaction_item(&block, &txid, &tx_hex, tx_pos, &analyzed));
 
         // Extract per-vout addresses (preserving index alignment, OP_RETURN outputs map to None)
-        let vout_addresses: Vec<Option<String>> = {
-            use bitcoincore_rpc::bitcoin::{consensus::deserialize, Address, Network, Transaction};
-            let btc_network = match network {
-                "mainnet" => Network::Bitcoin,
-                "testnet4" | "testnet" => Network::Testnet,
-                "regtest" => Network::Regtest,
-                _ => Network::Testnet,
-            };
-            hex::decode(&tx_hex)
-                .ok()
-                .and_then(|bytes| deserialize::<Transaction>(&bytes).ok())
-                .map(|tx| {
-                    tx.output
-                        .iter()
-                        .map(|out| {
-                            Address::from_script(&out.script_pubkey, btc_network)
-                                .ok()
-                                .map(|a| a.to_string())
-                        })
-                        .collect()
+        let outputs = AddressExtractor::output_addresses(&tx_hex, network).unwrap_or_default();
+        let vout_addresses: Vec<Option<String>> =
+            outputs.iter().map(|o| o.address.clone()).collect();
+
+        let input_amounts = if !input_txids.is_empty() {
+            amount_cache
+                .resolve(&input_txids, |txids| async move {
+                    charm_service
+                        .get_charm_repository()
+                        .get_amounts_by_txids(&txids)
+                        .await
                 })
+                .await
                 .unwrap_or_default()
+        } else {
+            vec![]
         };
+        let net_changes = net_supply_changes(&analyzed, &input_amounts);
 
-        // Push one charm entry per charm-bearing output with its correct vout.
-        // Beamed-out outputs are committed to Cardano — amount is 0 on Bitcoin.
-        for asset in &analyzed.asset_infos {
-            let address = vout_addresses
-                .get(asset.vout_index as usize)
-                .and_then(|a| a.clone());
-            let is_beamed_out = analyzed.beamed_out_indices.contains(&(asset.vout_index as usize));
-            charm_batch.push(CharmBatchItem {
-                txid: txid.clone(),
-                vout: asset.vout_index,
-                block_height: height,
-                data: analyzed.charm_json.clone(),
-                asset_type: asset.asset_type.clone(),
-                blockchain: blockchain.to_string(),
-                network: network.to_string(),
-                address,
-                app_id: asset.app_id.clone(),
-                amount: if is_beamed_out { 0i64 } else { asset.amount as i64 },
-                tags: analyzed.tags.clone(),
-            });
+        let charms = charm_items(&block, &txid, tx_pos, &analyzed, &outputs, &net_changes);
+        amount_cache.record(&txid, &charms);
+        if charms.is_empty() {
+            metrics::spell_without_charms(network);
+            logging::log_debug(&format!(
+                "[{}] Block {}: spell tx {} has no charm outputs",
+                network, height, txid
+            ));
         }
+        charm_batch.extend(charms);
 
-        let asset_requests = build_asset_requests(
-            &analyzed, &input_txids, height, blockchain, network, charm_service,
-        )
-        .await;
+        mint_batch.extend(build_mint_events(&analyzed, &net_changes, &vout_addresses, height));
+
+        let asset_requests =
+            build_asset_requests(&analyzed, &net_changes, height, blockchain, network).await;
         asset_batch.extend(asset_requests);
     }
 
-    (transaction_batch, charm_batch, asset_batch)
+    let (hits, misses) = (amount_cache.hits(), amount_cache.misses());
+    if hits + misses > 0 {
+        metrics::input_amount_cache(network, hits, misses);
+        logging::log_debug(&format!(
+            "[{}] Block {}: input amounts {} cached, {} fetched ({:.0}% hit rate)",
+            network,
+            height,
+            hits,
+            misses,
+            hits as f64 * 100.0 / (hits + misses) as f64
+        ));
+    }
+
+    (transaction_batch, charm_batch, asset_batch, mint_batch)
 }
 
-/// Build asset save requests from an analyzed tx.
-/// Calculates net supply change (mint vs transfer) by comparing input/output amounts.
-async fn build_asset_requests(
-    analyzed: &AnalyzedTx,
-    input_txids: &[String],
+/// The block a detection pass runs over.
+struct BlockContext<'a> {
+    block_hash: &'a str,
+    block_time: Option<NaiveDateTime>,
     height: u64,
-    blockchain: &str,
-    network: &str,
-    charm_service: &CharmService,
-) -> Vec<AssetBatchItem> {
-    let input_amounts = if !input_txids.is_empty() {
-        charm_service
-            .get_charm_repository()
-            .get_amounts_by_txids(input_txids)
-            .await
-            .unwrap_or_default()
-    } else {
-        vec![]
-    };
+    latest_height: u64,
+    blockchain: &'a str,
+    network: &'a str,
+}
+
+/// The transactions row of a verified spell, with its spell JSON.
+fn transaction_item(
+    block: &BlockContext,
+    txid: &str,
+    tx_hex: &str,
+    tx_pos: usize,
+    analyzed: &AnalyzedTx,
+) -> TransactionBatchItem {
+    let confirmations = (block.latest_height - block.height + 1) as i32;
+    TransactionBatchItem {
+        txid: txid.to_string(),
+        block_height: block.height,
+        position: tx_pos as i64,
+        raw_json: json!({ "hex": tx_hex, "txid": txid }),
+        charm_data: analyzed.charm_json.clone(),
+        confirmations,
+        status: TransactionStatus::for_confirmations(confirmations),
+        blockchain: block.blockchain.to_string(),
+        network: block.network.to_string(),
+        tags: analyzed.tags.clone(),
+        tx_type: Some(analyzed.tx_type.clone()),
+    }
+}
+
+/// One charm row per charm-bearing output with its correct vout; none for
+/// a spell whose outputs carry no charm. Beamed-out outputs are committed
+/// to Cardano — amount is 0 on Bitcoin.
+fn charm_items(
+    block: &BlockContext,
+    txid: &str,
+    tx_pos: usize,
+    analyzed: &AnalyzedTx,
+    outputs: &[OutputAddress],
+    net_changes: &HashMap<String, i64>,
+) -> Vec<CharmBatchItem> {
+    let charm_data = analyzed.charm_row_data();
+    analyzed
+        .asset_infos
+        .iter()
+        .map(|asset| {
+            let address = AddressExtractor::charm_output_address(
+                outputs,
+                asset.vout_index as usize,
+                txid,
+                block.network,
+            );
+            let is_beamed_out = analyzed
+                .beamed_out_indices
+                .contains(&(asset.vout_index as usize));
+            CharmBatchItem {
+                txid: txid.to_string(),
+                vout: asset.vout_index,
+                block_height: block.height,
+                data: charm_data.clone(),
+                asset_type: asset.asset_type,
+                blockchain: block.blockchain.to_string(),
+                network: block.network.to_string(),
+                address,
+                app_id: asset.app_id.clone(),
+                amount: if is_beamed_out { 0i64 } else { asset.amount as i64 },
+                tags: analyzed.tags.clone(),
+                block_hash: Some(block.block_hash.to_string()),
+                tx_ordinal: Some(tx_pos as i32),
+                operation: charm_operation(asset, net_changes),
+                block_time: block.block_time,
+            }
+        })
+        .collect()
+}
 
+/// Net on-chain supply change per NFT-normalized app_id for one tx: output
+/// amounts (beamed-out outputs count as 0) minus the amounts stored for the
+/// input txids. Positive means the tx minted, zero is a pure transfer.
+fn net_supply_changes(
+    analyzed: &AnalyzedTx,
+    input_amounts: &[(String, String, u64)],
+) -> HashMap<String, i64> {
     let mut net_changes: HashMap<String, i64> = HashMap::new();
     for asset in &analyzed.asset_infos {
-        let nft_app_id = normalize_app_id(&asset.app_id, &asset.asset_type);
+        let nft_app_id = normalize_app_id(&asset.app_id, asset.asset_type);
         // Beamed-out outputs leave Bitcoin — don't count toward on-chain supply
         let on_chain_amount = if analyzed.beamed_out_indices.contains(&(asset.vout_index as usize)) {
             0i64
@@ -285,15 +431,73 @@ async fn build_asset_requests(
         *net_changes.entry(nft_app_id).or_insert(0) += on_chain_amount;
     }
 
-    for (_txid, app_id, amount) in &input_amounts {
-        let nft_app_id = if app_id.starts_with("t/") {
-            crate::domain::services::app_id::token_to_nft(app_id)
-        } else {
-            app_id.clone()
-        };
+    for (_txid, app_id, amount) in input_amounts {
+        let nft_app_id = charms_core::token_to_nft(app_id);
         *net_changes.entry(nft_app_id).or_insert(0) -= *amount as i64;
     }
+    net_changes
+}
+
+/// Mint, transfer or burn for one output, by the sign of its app's net
+/// change. Tokens and their NFT share a net-change key, as for mint events.
+fn charm_operation(asset: &AssetInfo, net_changes: &HashMap<String, i64>) -> CharmOperation {
+    let key = normalize_app_id(&asset.app_id, asset.asset_type);
+    CharmOperation::from_net_change(net_changes.get(&key).copied().unwrap_or(0))
+}
 
+/// One mint event per app_id whose net supply grew. Tokens and their NFT
+/// share a net-change key; the event is attributed to the token when the tx
+/// carries one. The minter is the address of the first output holding it.
+fn build_mint_events(
+    analyzed: &AnalyzedTx,
+    net_changes: &HashMap<String, i64>,
+    vout_addresses: &[Option<String>],
+    height: u64,
+) -> Vec<MintEventBatchItem> {
+    let mut events: Vec<MintEventBatchItem> = Vec::new();
+    let mut keys: Vec<String> = Vec::new();
+    for asset in &analyzed.asset_infos {
+        let key = normalize_app_id(&asset.app_id, asset.asset_type);
+        let minted = net_changes.get(&key).copied().unwrap_or(0);
+        if minted <= 0 {
+            continue;
+        }
+        let minter_address = vout_addresses
+            .get(asset.vout_index as usize)
+            .and_then(|a| a.clone());
+        match keys.iter().position(|k| *k == key) {
+            None => {
+                keys.push(key);
+                events.push(MintEventBatchItem {
+                    app_id: asset.app_id.clone(),
+                    txid: analyzed.txid.clone(),
+                    block_height: height,
+                    minted_amount: minted,
+                    minter_address,
+                });
+            }
+            Some(i)
+                if asset.asset_type == AssetType::Token
+                    && AppKind::of(&events[i].app_id) == AppKind::Nft =>
+            {
+                events[i].app_id = asset.app_id.clone();
+                events[i].minter_address = minter_address;
+            }
+            Some(_) => {}
+        }
+    }
+    events
+}
+
+/// Build asset save requests from an analyzed tx and its net supply changes
+/// (mint vs transfer).
+async fn build_asset_requests(
+    analyzed: &AnalyzedTx,
+    net_changes: &HashMap<String, i64>,
+    height: u64,
+    blockchain: &str,
+    network: &str,
+) -> Vec<AssetBatchItem> {
     let metadata = extract_nft_metadata(analyzed);
     let ParsedMetadata {
         mut name,
@@ -301,6 +505,7 @@ async fn build_asset_requests(
         mut description,
         mut image_url,
         mut decimals,
+        collection,
     } = parse_metadata_fields(&metadata);
 
     // Enrich beaming assets with Cardano token metadata
@@ -328,15 +533,17 @@ async fn build_asset_requests(
         .asset_infos
         .iter()
         .filter_map(|asset| {
-            let nft_app_id = normalize_app_id(&asset.app_id, &asset.asset_type);
+            let nft_app_id = normalize_app_id(&asset.app_id, asset.asset_type);
             let net_change = net_changes.get(&nft_app_id).copied().unwrap_or(0);
-            let is_nft = asset.asset_type == "nft";
+            let is_nft = asset.asset_type == AssetType::Nft;
+            let is_dapp = asset.asset_type == AssetType::Dapp;
 
             // NFTs always persist as identity rows (supply=1) so tokens can
-            // inherit name/symbol/image from them. Tokens still need a
-            // positive net_change (otherwise the spell is a pure transfer
+            // inherit name/symbol/image from them; dapps carry no amounts,
+            // so they too get a row whenever they appear. Tokens still need
+            // a positive net_change (otherwise the spell is a pure transfer
             // and the row already exists).
-            if !is_nft && net_change == 0 {
+            if !is_nft && !is_dapp && net_change == 0 {
                 return None;
             }
 
@@ -348,7 +555,7 @@ async fn build_asset_requests(
             let has_nft_companion = analyzed
                 .asset_infos
                 .iter()
-                .any(|a| a.app_id.starts_with("n/"));
+                .any(|a| AppKind::of(&a.app_id) == AppKind::Nft);
             let use_metadata = is_nft || analyzed.is_beaming || has_nft_companion;
 
             Some(AssetBatchItem {
@@ -356,7 +563,7 @@ async fn build_asset_requests(
                 txid: analyzed.txid.clone(),
                 vout: 0i32,
                 block_height: height,
-                asset_type: asset.asset_type.clone(),
+                asset_type: asset.asset_type,
                 supply,
                 blockchain: blockchain.to_string(),
                 network: network.to_string(),
@@ -365,6 +572,7 @@ async fn build_asset_requests(
                 description: if use_metadata { description.clone() } else { None },
                 image_url: if use_metadata { image_url.clone() } else { None },
                 decimals: if use_metadata { decimals } else { None },
+                collection: if is_nft { collection.clone() } else { None },
                 cardano_policy_id: cardano_policy_id.clone(),
                 cardano_asset_name: cardano_asset_name.clone(),
                 cardano_fingerprint: cardano_fingerprint.clone(),
@@ -384,14 +592,14 @@ async fn fetch_cardano_metadata_for_beaming(
     use crate::infrastructure::cardano::metadata;
 
     // Pick an app that's a token or NFT (not a contract)
-    let app_id = if analyzed.app_id.starts_with("t/") || analyzed.app_id.starts_with("n/") {
+    let app_id = if AppKind::of(&analyzed.app_id).is_asset() {
         analyzed.app_id.clone()
     } else {
         // Primary app is a contract — find first t/ or n/ from asset_infos
         analyzed
             .asset_infos
             .iter()
-            .find(|a| a.app_id.starts_with("t/") || a.app_id.starts_with("n/"))
+            .find(|a| AppKind::of(&a.app_id).is_asset())
             .map(|a| a.app_id.clone())?
     };
 
@@ -399,39 +607,47 @@ async fn fetch_cardano_metadata_for_beaming(
     metadata::fetch_metadata(&app).await
 }
 
-fn normalize_app_id(app_id: &str, asset_type: &str) -> String {
-    if asset_type == "token" {
-        crate::domain::services::app_id::token_to_nft(app_id)
-    } else {
-        app_id.to_string()
+/// Net-change key: a token counts under its reference NFT.
+fn normalize_app_id(app_id: &str, asset_type: AssetType) -> String {
+    match app_id.parse::<AppId>() {
+        Ok(id) if asset_type == AssetType::Token => id.to_reference_nft().into_string(),
+        _ => app_id.to_string(),
     }
 }
 
 fn extract_nft_metadata(analyzed: &AnalyzedTx) -> Option<serde_json::Value> {
-    if analyzed.asset_type == "nft" {
-        analyzed
-            .charm_json
-            .get("native_data")
-            .and_then(|nd| nd.get("tx"))
-            .and_then(|tx| tx.get("outs"))
-            .and_then(|outs| outs.get(0))
-            .and_then(|out0| out0.get("0"))
-            .cloned()
+    if analyzed.asset_type == AssetType::Nft {
+        nft_metadata_from_charm_json(&analyzed.charm_json)
     } else {
         None
     }
 }
 
+/// NFT metadata sits in the first output's `"0"` entry of the stored charm
+/// JSON (`charms.data`).
+pub(crate) fn nft_metadata_from_charm_json(
+    charm_json: &serde_json::Value,
+) -> Option<serde_json::Value> {
+    charm_json
+        .get("native_data")
+        .and_then(|nd| nd.get("tx"))
+        .and_then(|tx| tx.get("outs"))
+        .and_then(|outs| outs.get(0))
+        .and_then(|out0| out0.get("0"))
+        .cloned()
+}
+
 #[derive(Default)]
-struct ParsedMetadata {
-    name: Option<String>,
-    symbol: Option<String>,
-    description: Option<String>,
-    image_url: Option<String>,
-    decimals: Option<u8>,
+pub(crate) struct ParsedMetadata {
+    pub(crate) name: Option<String>,
+    pub(crate) symbol: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) image_url: Option<String>,
+    pub(crate) decimals: Option<u8>,
+    pub(crate) collection: Option<String>,
 }
 
-fn parse_metadata_fields(metadata: &Option<serde_json::Value>) -> ParsedMetadata {
+pub(crate) fn parse_metadata_fields(metadata: &Option<serde_json::Value>) -> ParsedMetadata {
     let Some(meta) = metadata else {
         return ParsedMetadata::default();
     };
@@ -456,38 +672,436 @@ fn parse_metadata_fields(metadata: &Option<serde_json::Value>) -> ParsedMetadata
             .get("decimals")
             .and_then(|v| v.as_u64())
             .map(|d| d as u8),
+        collection: meta.get("collection").and_then(parse_collection),
     }
 }
 
-struct ExtractedTx {
-    txid: String,
-    tx_hex: String,
-    tx_pos: usize,
-    input_utxos: Vec<(String, u32)>,
+/// Reclassify a 3-output FulfillAsk as FulfillBid when the order consumed
+/// at ins[0] is a bid. Block-path twin of the mempool correction.
+async fn correct_fulfill_classification(
+    repo: &DexOrdersRepository,
+    txid: &str,
+    tx_hex: &str,
+    analyzed: &mut AnalyzedTx,
+    network: &str,
+    height: u64,
+) {
+    let is_fulfill_ask = analyzed
+        .dex_result
+        .as_ref()
+        .is_some_and(|d| d.operation == dex::DexOperation::FulfillAsk);
+    if !is_fulfill_ask {
+        return;
+    }
+    let Some(order_id) = extract_ins0_order_id(tx_hex) else {
+        return;
+    };
+    if let Ok(Some(order)) = repo.get_by_id(&order_id).await {
+        if order.side == "bid" {
+            analyzed.reclassify_as_fulfill_bid();
+            logging::log_info(&format!(
+                "[{}] 🔄 Block {}: FULFILL-BID (3-out) corrected for tx {} (consumed order {})",
+                network, height, txid, order_id
+            ));
+        }
+    }
 }
 
-/// Owned snapshot of every tx in a block (txid, hex, position, parent outpoints).
-fn extract_transaction_data(block: &bitcoin::Block) -> Vec<ExtractedTx> {
-    block
-        .txdata
-        .iter()
-        .enumerate()
-        .map(|(tx_pos, tx)| ExtractedTx {
+/// Keep a tx whose spell is too new for the parser in `pending_spells`, so
+/// it can be reprocessed after an upgrade instead of being lost.
+async fn capture_unsupported_spell(
+    repo: &PendingSpellsRepository,
+    txid: &str,
+    tx_hex: &str,
+    version: u32,
+    network: &str,
+    height: u64,
+) {
+    match repo.record(txid, network, tx_hex, version, Some(height)).await {
+        Ok(()) => logging::log_warning(&format!(
+            "[{}] ⏳ Block {}: tx {} carries a V{} spell, not supported yet; kept in pending_spells",
+            network, height, txid, version
+        )),
+        Err(e) => logging::log_error(&format!(
+            "[{}] ❌ Block {}: failed to keep V{} spell tx {} in pending_spells: {}",
+            network, height, version, txid, e
+        )),
+    }
+}
+
+/// Where a transaction's bytes come from. Block transactions stay borrowed
+/// until their bytes fit in the in-flight budget.
+pub(crate) enum TxSource<'a> {
+    Block {
+        tx: &'a bitcoin::Transaction,
+        tx_pos: usize,
+    },
+    /// Rebuilt from a stored `transactions` row (reindex dry run).
+    Stored(ExtractedTx),
+}
+
+impl TxSource<'_> {
+    /// Bytes charged against the budget: the hex string (twice the raw
+    /// size) plus the decoded transaction.
+    fn footprint(&self) -> usize {
+        match self {
+            TxSource::Block { tx, .. } => tx.size() * 3,
+            TxSource::Stored(tx) => tx.tx_hex.len() / 2 * 3,
+        }
+    }
+}
+
+/// Owned input of one blocking analysis.
+enum OwnedSource {
+    Block(bitcoin::Transaction, usize),
+    Stored(ExtractedTx),
+}
+
+enum Outcome {
+    Charm(Box<AnalyzedTx>),
+    /// Spell of a protocol version the parser does not support yet.
+    Unsupported(u32),
+    /// Why, when the analysis was asked to classify failures.
+    NotCharm(Option<SpellFailure>),
+}
+
+impl Outcome {
+    /// As counted by `parser_stats`; only classifying passes record, so
+    /// `NotCharm(None)` never reaches the counters.
+    fn parse_result(&self) -> Result<(), SpellFailure> {
+        match self {
+            Outcome::Charm(_) => Ok(()),
+            Outcome::Unsupported(version) => Err(SpellFailure::VersionUnsupported(*version)),
+            Outcome::NotCharm(failure) => Err(failure.unwrap_or(SpellFailure::NoEnvelope)),
+        }
+    }
+}
+
+struct Analysis {
+    tx: ExtractedTx,
+    outcome: Outcome,
+}
+
+/// Ordered stream of analyses, each paired with the permit charging its
+/// bytes. The permit is held until the caller drops it, so transactions
+/// waiting to be consumed still count against the budget.
+fn analyze_stream<'s, 'a>(
+    txs: impl Iterator<Item = TxSource<'s>> + Send + 'a,
+    budget: &'a InflightBudget,
+    network: &'a str,
+    classify_failures: bool,
+) -> BoxStream<'a, (Analysis, InflightPermit)>
+where
+    's: 'a,
+{
+    let parallelism = std::thread::available_parallelism().map_or(4, |n| n.get());
+    futures::stream::iter(txs)
+        .map(move |source| async move {
+            let permit = budget.acquire(source.footprint()).await;
+            let owned = match source {
+                TxSource::Block { tx, tx_pos } => OwnedSource::Block(tx.clone(), tx_pos),
+                TxSource::Stored(tx) => OwnedSource::Stored(tx),
+            };
+            let network = network.to_string();
+            let analysis = tokio::task::spawn_blocking(move || {
+                analyze_owned(owned, &network, classify_failures)
+            })
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+            (analysis, permit)
+        })
+        .buffered(parallelism)
+        .boxed()
+}
+
+/// Serialize (block source) and run the strict analysis for one tx. With
+/// `classify_failures`, a tx yielding no charm is also told why, which
+/// singles out spells too new for the parser.
+fn analyze_owned(source: OwnedSource, network: &str, classify_failures: bool) -> Analysis {
+    let tx = match source {
+        OwnedSource::Block(tx, tx_pos) => ExtractedTx {
             txid: tx.txid().to_string(),
-            tx_hex: bitcoin::consensus::encode::serialize_hex(tx),
+            tx_hex: bitcoin::consensus::encode::serialize_hex(&tx),
             tx_pos,
-            input_utxos: tx
-                .input
-                .iter()
-                .filter(|input| !input.previous_output.is_null())
-                .map(|input| {
-                    (
-                        input.previous_output.txid.to_string(),
-                        input.previous_output.vout,
-                    )
-                })
-                .collect(),
+            input_utxos: input_outpoints(&tx),
+        },
+        OwnedSource::Stored(tx) => tx,
+    };
+    let outcome = match tx_analyzer::analyze_tx(
+        &tx.txid,
+        &tx.tx_hex,
+        network,
+        tx_analyzer::VerifyMode::Strict,
+    ) {
+        Some(analyzed) => Outcome::Charm(Box::new(analyzed)),
+        None if classify_failures => match NativeCharmParser::classify_failure(&tx.tx_hex) {
+            SpellFailure::VersionUnsupported(version) => Outcome::Unsupported(version),
+            failure => Outcome::NotCharm(Some(failure)),
+        },
+        None => Outcome::NotCharm(None),
+    };
+    Analysis { tx, outcome }
+}
+
+pub(crate) struct ExtractedTx {
+    pub(crate) txid: String,
+    pub(crate) tx_hex: String,
+    pub(crate) tx_pos: usize,
+    pub(crate) input_utxos: Vec<(String, u32)>,
+}
+
+impl ExtractedTx {
+    /// Rebuild from a stored `transactions` row; `None` when the hex does
+    /// not decode.
+    pub(crate) fn from_stored(txid: String, tx_hex: String, tx_pos: usize) -> Option<Self> {
+        let tx: bitcoin::Transaction =
+            bitcoin::consensus::deserialize(&hex::decode(&tx_hex).ok()?).ok()?;
+        Some(Self {
+            txid,
+            tx_hex,
+            tx_pos,
+            input_utxos: input_outpoints(&tx),
+        })
+    }
+}
+
+fn input_outpoints(tx: &bitcoin::Transaction) -> Vec<(String, u32)> {
+    tx.input
+        .iter()
+        .filter(|input| !input.previous_output.is_null())
+        .map(|input| {
+            (
+                input.previous_output.txid.to_string(),
+                input.previous_output.vout,
+            )
         })
         .collect()
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyzed(txid: &str, outputs: &[(&str, i32, u64)]) -> AnalyzedTx {
+        AnalyzedTx {
+            txid: txid.to_string(),
+            charm_json: serde_json::Value::Null,
+            app_id: outputs.first().map(|o| o.0.to_string()).unwrap_or_default(),
+            asset_type: AssetType::Token,
+            amount: 0,
+            address: None,
+            tags: None,
+            dex_result: None,
+            asset_infos: outputs
+                .iter()
+                .map(|(app_id, vout, amount)| AssetInfo {
+                    app_id: app_id.to_string(),
+                    vout_index: *vout,
+                    amount: *amount,
+                    asset_type: AppKind::of(app_id).asset_type(),
+                })
+                .collect(),
+            is_beaming: false,
+            version: 0,
+            tx_type: "spell".to_string(),
+            beamed_out_indices: Default::default(),
+        }
+    }
+
+    fn block() -> BlockContext<'static> {
+        BlockContext {
+            block_hash: "00",
+            block_time: None,
+            height: 100,
+            latest_height: 105,
+            blockchain: "bitcoin",
+            network: "mainnet",
+        }
+    }
+
+    #[test]
+    fn spell_without_charm_outputs_still_gets_a_transaction_row() {
+        let mut tx = analyzed("tx1", &[]);
+        tx.charm_json = json!({ "version": 8, "tx": { "outs": [{}] } });
+
+        let row = transaction_item(&block(), "tx1", "beef", 3, &tx);
+        assert_eq!(row.txid, "tx1");
+        assert_eq!(row.position, 3);
+        assert_eq!(row.confirmations, 6);
+        assert_eq!(row.charm_data, tx.charm_json);
+        assert_eq!(row.tx_type.as_deref(), Some("spell"));
+
+        assert!(charm_items(&block(), "tx1", 3, &tx, &[], &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn charm_items_follow_the_asset_outputs() {
+        let tx = analyzed("tx1", &[("t/cc/dd", 1, 5)]);
+        let outputs = vec![
+            OutputAddress {
+                address: None,
+                script_type: "op_return",
+            },
+            OutputAddress {
+                address: Some("bc1qxyz".to_string()),
+                script_type: "p2wpkh",
+            },
+        ];
+
+        let charms = charm_items(&block(), "tx1", 3, &tx, &outputs, &HashMap::new());
+        assert_eq!(charms.len(), 1);
+        assert_eq!(charms[0].vout, 1);
+        assert_eq!(charms[0].amount, 5);
+        assert_eq!(charms[0].address.as_deref(), Some("bc1qxyz"));
+        assert_eq!(charms[0].block_hash.as_deref(), Some("00"));
+    }
+
+    fn events_for(tx: &AnalyzedTx, inputs: &[(String, String, u64)]) -> Vec<MintEventBatchItem> {
+        let addresses = vec![Some("addrA".to_string()), Some("addrB".to_string())];
+        build_mint_events(tx, &net_supply_changes(tx, inputs), &addresses, 100)
+    }
+
+    fn large_tx(value: u64, script_len: usize) -> bitcoin::Transaction {
+        bitcoin::Transaction {
+            version: 2,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn::default()],
+            output: vec![bitcoin::TxOut {
+                value,
+                script_pubkey: bitcoin::ScriptBuf::from_bytes(vec![0x51; script_len]),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn large_block_stays_under_inflight_cap_in_block_order() {
+        let txs: Vec<_> = (0..64).map(|i| large_tx(i, 100_000)).collect();
+        let footprint = txs[0].size() * 3;
+        let budget = InflightBudget::new(footprint * 4);
+
+        let mut analyses = analyze_stream(
+            txs.iter()
+                .enumerate()
+                .map(|(tx_pos, tx)| TxSource::Block { tx, tx_pos }),
+            &budget,
+            "mainnet",
+            false,
+        );
+        let mut positions = Vec::new();
+        while let Some((analysis, _permit)) = analyses.next().await {
+            assert!(budget.in_flight() <= budget.cap());
+            assert!(matches!(analysis.outcome, Outcome::NotCharm(None)));
+            positions.push(analysis.tx.tx_pos);
+        }
+
+        assert_eq!(positions, (0..64).collect::<Vec<_>>());
+        assert!(budget.peak() >= footprint);
+        assert!(budget.peak() <= budget.cap());
+        assert!(budget.peak() < footprint * txs.len());
+        assert_eq!(budget.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn dapp_spells_get_an_asset_row() {
+        let tx = analyzed("tx1", &[("d/aa/bb", 0, 0), ("t/cc/dd", 1, 5)]);
+        let spent = ("tx0".to_string(), "t/cc/dd".to_string(), 5);
+        let net_changes = net_supply_changes(&tx, &[spent]);
+
+        let requests = build_asset_requests(&tx, &net_changes, 100, "bitcoin", "mainnet").await;
+        let rows: Vec<_> = requests
+            .iter()
+            .map(|r| (r.app_id.as_str(), r.asset_type, r.supply))
+            .collect();
+        assert_eq!(rows, [("d/aa/bb", AssetType::Dapp, 0)]);
+    }
+
+    #[test]
+    fn mint_then_transfer_emits_one_event() {
+        let token = "t/aa/bb";
+        let mint = analyzed("tx1", &[("n/aa/bb", 0, 1), (token, 1, 1000)]);
+        let nft_input = ("tx0".to_string(), "n/aa/bb".to_string(), 1);
+        let minted = events_for(&mint, &[nft_input]);
+        assert_eq!(minted.len(), 1);
+        assert_eq!(minted[0].app_id, token);
+        assert_eq!(minted[0].minted_amount, 1000);
+        assert_eq!(minted[0].minter_address.as_deref(), Some("addrB"));
+
+        // Pure transfer: 1000 in, 600 + 400 out.
+        let transfer = analyzed("tx2", &[(token, 0, 600), (token, 1, 400)]);
+        let spent = ("tx1".to_string(), token.to_string(), 1000);
+        assert!(events_for(&transfer, &[spent]).is_empty());
+    }
+
+    #[test]
+    fn mint_and_later_transfer_get_their_own_operation() {
+        let token = "t/aa/bb";
+        let operations = |tx: &AnalyzedTx, inputs: &[(String, String, u64)]| {
+            let net_changes = net_supply_changes(tx, inputs);
+            tx.asset_infos
+                .iter()
+                .map(|a| charm_operation(a, &net_changes))
+                .collect::<Vec<_>>()
+        };
+
+        let mint = analyzed("tx1", &[("n/aa/bb", 0, 1), (token, 1, 1000)]);
+        let nft_input = ("tx0".to_string(), "n/aa/bb".to_string(), 1);
+        assert_eq!(
+            operations(&mint, &[nft_input]),
+            [CharmOperation::Mint, CharmOperation::Mint]
+        );
+
+        let transfer = analyzed("tx2", &[(token, 0, 600), (token, 1, 400)]);
+        let spent = ("tx1".to_string(), token.to_string(), 1000);
+        assert_eq!(
+            operations(&transfer, std::slice::from_ref(&spent)),
+            [CharmOperation::Transfer, CharmOperation::Transfer]
+        );
+
+        let burn = analyzed("tx3", &[(token, 0, 600)]);
+        assert_eq!(operations(&burn, &[spent]), [CharmOperation::Burn]);
+    }
+
+    /// Three dependent transactions in one block: only the external parent
+    /// is fetched, and each hop nets to a transfer even though none of the
+    /// block's charms are saved yet.
+    #[tokio::test]
+    async fn a_chain_within_one_block_fetches_only_the_external_parent() {
+        let token = "t/aa/bb";
+        let fetches = std::cell::RefCell::new(Vec::new());
+        let fetch = |txids: Vec<String>| {
+            fetches.borrow_mut().push(txids.clone());
+            async move {
+                Ok::<_, ()>(
+                    txids
+                        .into_iter()
+                        .filter(|t| t == "external")
+                        .map(|t| (t, token.to_string(), 1000))
+                        .collect(),
+                )
+            }
+        };
+
+        let chain = [
+            ("a", "external", analyzed("a", &[(token, 0, 1000)])),
+            ("b", "a", analyzed("b", &[(token, 0, 600), (token, 1, 400)])),
+            ("c", "b", analyzed("c", &[(token, 0, 1000)])),
+        ];
+        let mut cache = InputAmountCache::new();
+        for (txid, parent, tx) in &chain {
+            // Two inputs from the same parent count once.
+            let inputs = [parent.to_string(), parent.to_string()];
+            let amounts = cache.resolve(&inputs, fetch).await.unwrap();
+            let net_changes = net_supply_changes(tx, &amounts);
+            assert!(
+                net_changes.values().all(|n| *n == 0),
+                "{txid} is a transfer: {net_changes:?}"
+            );
+
+            let charms = charm_items(&block(), txid, 0, tx, &[], &net_changes);
+            cache.record(txid, &charms);
+        }
+
+        assert_eq!(fetches.into_inner(), [vec!["external".to_string()]]);
+        assert_eq!((cache.hits(), cache.misses()), (2, 1));
+    }
+}
@@ -0,0 +1,130 @@
+//! Byte-weighted budget for transactions in flight during detection.
+//!
+//! A task count alone does not bound memory: one block can mix tiny
+//! transfers with multi-megabyte inscriptions. Each transaction is charged
+//! its estimated footprint before its hex is built and released once the
+//! detection pass is done with it, so the bytes held at once stay under
+//! `INDEXER_MAX_INFLIGHT_BYTES`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Used when `INDEXER_MAX_INFLIGHT_BYTES` is unset or invalid.
+pub const DEFAULT_MAX_INFLIGHT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Shared byte budget. Cloning shares the same counters.
+#[derive(Debug, Clone)]
+pub struct InflightBudget {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    cap: usize,
+    semaphore: Arc<Semaphore>,
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl InflightBudget {
+    /// Budget of `cap_bytes`; zero is raised to one byte and values beyond
+    /// what a semaphore can count are clamped.
+    pub fn new(cap_bytes: usize) -> Self {
+        let cap = cap_bytes.clamp(1, Semaphore::MAX_PERMITS.min(u32::MAX as usize));
+        Self {
+            inner: Arc::new(Inner {
+                cap,
+                semaphore: Arc::new(Semaphore::new(cap)),
+                in_flight: AtomicUsize::new(0),
+                peak: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// `INDEXER_MAX_INFLIGHT_BYTES` when set to a positive number, otherwise
+    /// 64 MiB.
+    pub fn from_env() -> Self {
+        let cap = std::env::var("INDEXER_MAX_INFLIGHT_BYTES")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|bytes| *bytes > 0)
+            .unwrap_or(DEFAULT_MAX_INFLIGHT_BYTES);
+        Self::new(cap)
+    }
+
+    pub fn cap(&self) -> usize {
+        self.inner.cap
+    }
+
+    /// Bytes currently charged.
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Highest `in_flight` seen since the budget was created.
+    pub fn peak(&self) -> usize {
+        self.inner.peak.load(Ordering::Relaxed)
+    }
+
+    /// Wait until `bytes` fit, then charge them until the permit drops. A
+    /// transaction larger than the whole budget is charged the full cap, so
+    /// it still runs, alone.
+    pub async fn acquire(&self, bytes: usize) -> InflightPermit {
+        let weight = bytes.clamp(1, self.inner.cap);
+        let permit = self
+            .inner
+            .semaphore
+            .clone()
+            .acquire_many_owned(weight as u32)
+            .await
+            .expect("inflight semaphore is never closed");
+        let now = self.inner.in_flight.fetch_add(weight, Ordering::Relaxed) + weight;
+        self.inner.peak.fetch_max(now, Ordering::Relaxed);
+        InflightPermit {
+            inner: self.inner.clone(),
+            weight,
+            _permit: permit,
+        }
+    }
+}
+
+/// Bytes charged for one transaction; released on drop.
+#[derive(Debug)]
+pub struct InflightPermit {
+    inner: Arc<Inner>,
+    weight: usize,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for InflightPermit {
+    fn drop(&mut self) {
+        self.inner.in_flight.fetch_sub(self.weight, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn oversized_requests_are_charged_the_cap() {
+        let budget = InflightBudget::new(100);
+        let big = budget.acquire(1_000).await;
+        assert_eq!(budget.in_flight(), 100);
+        drop(big);
+        assert_eq!(budget.in_flight(), 0);
+
+        let a = budget.acquire(60).await;
+        let pending = tokio::time::timeout(
+            std::time::Duration::from_millis(20),
+            budget.acquire(60),
+        )
+        .await;
+        assert!(pending.is_err(), "second request must wait for the first");
+        drop(a);
+        let _b = budget.acquire(60).await;
+        assert_eq!(budget.peak(), 100);
+    }
+}
@@ -0,0 +1,98 @@
+//! Per-block cache of the charm amounts held by input txids.
+//!
+//! Net supply changes need the charms of every input txid. A popular parent
+//! (a large mint split over many transactions of one block) would otherwise
+//! be fetched once per spending tx. The cache lives for one detection pass:
+//! misses are fetched in one query per tx and remembered, txids without
+//! charms included, and the charms detected in the block are recorded as they
+//! are built. Those rows are only written by the block's batch save, so a
+//! chain of transactions inside one block resolves from the cache alone.
+//! Nothing outlives the pass, so a reorg never sees stale amounts.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use super::batch::CharmBatchItem;
+
+/// `(txid, app_id, amount)`, as returned by `get_amounts_by_txids`.
+pub type InputAmount = (String, String, u64);
+
+#[derive(Debug, Default)]
+pub struct InputAmountCache {
+    /// (app_id, amount) of each known txid; empty for txids without charms
+    amounts: HashMap<String, Vec<(String, u64)>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl InputAmountCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Charm amounts of `txids`. Cached txids are served directly; the rest
+    /// go to `fetch` in a single call and are cached. A failed fetch caches
+    /// nothing and is returned as is.
+    pub async fn resolve<F, Fut, E>(
+        &mut self,
+        txids: &[String],
+        fetch: F,
+    ) -> Result<Vec<InputAmount>, E>
+    where
+        F: FnOnce(Vec<String>) -> Fut,
+        Fut: Future<Output = Result<Vec<InputAmount>, E>>,
+    {
+        let mut unique: Vec<&String> = txids.iter().collect();
+        unique.sort();
+        unique.dedup();
+
+        let missing: Vec<String> = unique
+            .iter()
+            .filter(|txid| !self.amounts.contains_key(txid.as_str()))
+            .map(|txid| txid.to_string())
+            .collect();
+        self.hits += (unique.len() - missing.len()) as u64;
+        self.misses += missing.len() as u64;
+
+        if !missing.is_empty() {
+            let rows = fetch(missing.clone()).await?;
+            for txid in missing {
+                self.amounts.entry(txid).or_default();
+            }
+            for (txid, app_id, amount) in rows {
+                self.amounts.entry(txid).or_default().push((app_id, amount));
+            }
+        }
+
+        Ok(unique
+            .into_iter()
+            .flat_map(|txid| {
+                self.amounts[txid.as_str()]
+                    .iter()
+                    .map(move |(app_id, amount)| (txid.clone(), app_id.clone(), *amount))
+            })
+            .collect())
+    }
+
+    /// Remember the charms detected for `txid` in this block, replacing
+    /// anything cached for it.
+    pub fn record(&mut self, txid: &str, charms: &[CharmBatchItem]) {
+        self.amounts.insert(
+            txid.to_string(),
+            charms
+                .iter()
+                .map(|c| (c.app_id.clone(), c.amount.max(0) as u64))
+                .collect(),
+        );
+    }
+
+    /// Input txids served from the cache so far.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Input txids that had to be fetched so far.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
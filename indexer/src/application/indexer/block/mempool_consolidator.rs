@@ -10,6 +10,10 @@ use bitcoincore_rpc::bitcoin;
 use sea_orm::{ConnectionTrait, DbBackend, Statement};
 
 use crate::config::NetworkId;
+use crate::domain::models::TransactionStatus;
+use crate::infrastructure::persistence::repositories::changefeed_repository::{
+    self as changefeed, ChangeEntity,
+};
 use crate::infrastructure::persistence::repositories::MempoolSpendsRepository;
 use crate::utils::logging;
 
@@ -123,11 +127,42 @@ pub async fn consolidate(
         .collect::<Vec<_>>()
         .join(", ");
 
-    // 1. Promote mempool charms to confirmed block_height
+    // 1. Promote mempool charms to confirmed block_height, stamping the
+    //    block hash and time, each tx's position within the block and how
+    //    long the charm waited in mempool (block time minus first sighting;
+    //    a block timestamp behind our clock counts as 0). Promoted rows go
+    //    to the changefeed in the same statement.
+    let block_hash = block.block_hash().to_string();
+    let ordinal_cases = block
+        .txdata
+        .iter()
+        .enumerate()
+        .map(|(pos, tx)| (tx.txid().to_string(), pos))
+        .filter(|(txid, _)| verified_txids.contains(txid))
+        .map(|(txid, pos)| format!("WHEN '{}' THEN {}", txid.replace('\'', "''"), pos))
+        .collect::<Vec<_>>()
+        .join(" ");
     let sql = format!(
-        "UPDATE charms SET block_height = {}, mempool_detected_at = mempool_detected_at \
-         WHERE txid IN ({}) AND network = '{}' AND block_height IS NULL",
-        height, ids_sql, network
+        "WITH promoted AS (UPDATE charms SET block_height = {}, block_hash = '{}', \
+         tx_ordinal = CASE txid {} END, mempool_detected_at = mempool_detected_at, \
+         block_time = to_timestamp({}) AT TIME ZONE 'UTC', \
+         confirmation_delay_secs = GREATEST(0, {} - EXTRACT(EPOCH FROM mempool_detected_at))::INTEGER \
+         WHERE txid IN ({}) AND network = '{}' AND block_height IS NULL \
+         RETURNING txid, vout, app_id) {}",
+        height,
+        block_hash,
+        ordinal_cases,
+        block.header.time,
+        block.header.time,
+        ids_sql,
+        network,
+        changefeed::updates_from(
+            ChangeEntity::Charm,
+            "promoted",
+            "txid || ':' || vout || ':' || app_id",
+            height as i32,
+            network
+        )
     );
     match conn.execute(Statement::from_string(DbBackend::Postgres, sql)).await {
         Ok(r) if r.rows_affected() > 0 => {
@@ -145,11 +180,16 @@ pub async fn consolidate(
         }
     }
 
-    // 2. Promote mempool transactions to confirmed
+    // 2. Promote mempool transactions to confirming; the block save and
+    // `confirm_pending_blocks` take them to confirmed once deep enough
     let sql = format!(
-        "UPDATE transactions SET block_height = {}, status = 'confirmed', updated_at = NOW() \
-         WHERE txid IN ({}) AND network = '{}' AND (block_height IS NULL OR status = 'pending')",
-        height, ids_sql, network
+        "UPDATE transactions SET block_height = {}, status = '{}', updated_at = NOW() \
+         WHERE txid IN ({}) AND network = '{}' AND (block_height IS NULL OR status = '{}')",
+        height,
+        TransactionStatus::Confirming,
+        ids_sql,
+        network,
+        TransactionStatus::Pending
     );
     match conn.execute(Statement::from_string(DbBackend::Postgres, sql)).await {
         Ok(r) if r.rows_affected() > 0 => {
@@ -167,11 +207,21 @@ pub async fn consolidate(
         }
     }
 
-    // 3. Promote mempool DEX orders to confirmed
+    // 3. Promote mempool DEX orders to confirmed, feeding them like charms
     let sql = format!(
-        "UPDATE dex_orders SET block_height = {}, updated_at = NOW() \
-         WHERE txid IN ({}) AND network = '{}' AND block_height IS NULL",
-        height, ids_sql, network
+        "WITH promoted AS (UPDATE dex_orders SET block_height = {}, updated_at = NOW() \
+         WHERE txid IN ({}) AND network = '{}' AND block_height IS NULL \
+         RETURNING order_id) {}",
+        height,
+        ids_sql,
+        network,
+        changefeed::updates_from(
+            ChangeEntity::DexOrder,
+            "promoted",
+            "order_id",
+            height as i32,
+            network
+        )
     );
     if let Err(e) = conn.execute(Statement::from_string(DbBackend::Postgres, sql)).await {
         logging::log_warning(&format!(
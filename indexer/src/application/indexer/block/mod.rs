@@ -4,9 +4,15 @@
 //! - `bitcoin_processor`: top-level driver (live loop)
 //! - `processor`: slim orchestrator for individual block processing
 //! - `detection`: charm detection from transactions using TxAnalyzer
+//! - `inflight`: byte budget bounding the transactions detection holds at once
+//! - `input_amounts`: per-block cache of input charm amounts for supply changes
+//! - `progress`: per-block logging at the tip, aggregated lines during catch-up
 //! - `spent_tracker`: marks charms as spent
 //! - `utxo_indexer`: registers addresses and tracks UTXOs
 //! - `mempool_consolidator`: promotes mempool entries to confirmed
+//! - `pending_spells`: reprocesses spells captured before the parser supported them
+//! - `skipped_blocks`: revisits blocks skipped because the node did not have them
+//! - `rescan`: re-runs the last blocks before the bookmark on startup
 //! - `batch`: batch persistence for charms, transactions, assets
 //! - `summary`: summary statistics updater
 //! - `retry`: retry handler with exponential backoff
@@ -14,10 +20,16 @@
 pub mod batch;
 pub mod bitcoin_processor;
 pub mod detection;
+pub mod inflight;
+pub mod input_amounts;
 pub mod mempool_consolidator;
+pub mod pending_spells;
 pub mod processor;
+pub mod progress;
 pub mod reorg;
+pub mod rescan;
 pub mod retry;
+pub mod skipped_blocks;
 pub mod spent_tracker;
 pub mod summary;
 pub mod utxo_indexer;
@@ -25,5 +37,6 @@ pub mod utxo_indexer;
 pub use batch::{AssetBatchItem, BatchProcessor, CharmBatchItem, TransactionBatchItem};
 pub use bitcoin_processor::BitcoinProcessor;
 pub use processor::BlockProcessor;
+pub use progress::{ProgressConfig, ProgressReporter};
 pub use retry::RetryHandler;
 pub use summary::SummaryUpdater;
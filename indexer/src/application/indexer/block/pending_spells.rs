@@ -0,0 +1,78 @@
+//! Drain `pending_spells` once the parser supports their protocol version.
+//!
+//! Confirmed txs are reprocessed by re-running their block through the block
+//! pipeline, which is idempotent (see `maintenance::reindex`). Holders of the
+//! network are rebuilt afterwards because the `last_updated_block` gate skips
+//! replayed heights. Mempool-only rows are dropped: the mempool scan starts
+//! from an empty seen set after a restart and re-detects them.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use charms_client::CURRENT_VERSION;
+
+use crate::domain::errors::BlockProcessorError;
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::{
+    PendingSpellsRepository, StatsHoldersRepository,
+};
+use crate::utils::logging;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrainSummary {
+    /// Blocks re-run through the pipeline.
+    pub blocks: u64,
+    /// Confirmed spells those blocks covered.
+    pub spells: u64,
+    /// Mempool-only rows dropped for the mempool scan to re-detect.
+    pub unconfirmed_dropped: u64,
+}
+
+/// Reprocess the captured spells of `network` the parser now supports.
+/// `reprocess_block` runs the block pipeline for one height; a block that
+/// fails keeps its rows for the next drain.
+pub async fn drain<F, Fut>(
+    pending_spells: &PendingSpellsRepository,
+    stats_holders: &StatsHoldersRepository,
+    network: &str,
+    mut reprocess_block: F,
+) -> Result<DrainSummary, DbError>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<(), BlockProcessorError>>,
+{
+    let mut summary = DrainSummary::default();
+    let mut by_height: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+    for spell in pending_spells.ready(network, CURRENT_VERSION).await? {
+        match spell.block_height {
+            Some(height) => by_height.entry(height).or_default().push(spell.txid),
+            None => {
+                pending_spells.remove(&spell.txid, network).await?;
+                summary.unconfirmed_dropped += 1;
+            }
+        }
+    }
+
+    for (height, txids) in by_height {
+        if let Err(e) = reprocess_block(height).await {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ pending spells: block {} not reprocessed, {} tx(s) kept for the next drain: {}",
+                network,
+                height,
+                txids.len(),
+                e
+            ));
+            continue;
+        }
+        for txid in &txids {
+            pending_spells.remove(txid, network).await?;
+        }
+        summary.blocks += 1;
+        summary.spells += txids.len() as u64;
+    }
+
+    if summary.blocks > 0 {
+        stats_holders.rebuild_from_charms(network, None).await?;
+    }
+    Ok(summary)
+}
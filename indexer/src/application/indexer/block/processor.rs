@@ -1,19 +1,22 @@
 //! Block processor: slim orchestrator for processing individual blocks.
 //! Each step delegates to a focused module.
 
+use crate::application::indexer::locked_supply::LockedSupply;
 use crate::config::NetworkId;
 use crate::domain::errors::BlockProcessorError;
 use crate::domain::services::CharmService;
-use crate::infrastructure::bitcoin::BitcoinClient;
+use crate::infrastructure::bitcoin::{BitcoinClient, BitcoinClientError};
 use crate::infrastructure::persistence::repositories::{
-    AddressTransactionsRepository, BlockStatusRepository, MempoolSpendsRepository,
-    MonitoredAddressesRepository, ReorgEventsRepository, SummaryRepository,
-    TransactionRepository, UtxoRepository,
+    AddressTransactionsRepository, BlockStatusRepository, BlockTimings, MempoolSpendsRepository,
+    MintEventsRepository, MonitoredAddressesRepository, OffchainMetadataRepository,
+    ParserStatsRepository, PendingSpellsRepository, ReorgEventsRepository, SummaryRepository,
+    TransactionRepository, UtxoRepository, WebhooksRepository,
 };
 use crate::infrastructure::persistence::Repositories;
 use crate::utils::logging;
 
 use super::batch::BatchProcessor;
+use super::progress::{BlockStats, ProgressReporter};
 use super::reorg::{self, ReorgDecision};
 use super::retry::RetryHandler;
 use super::summary::SummaryUpdater;
@@ -30,9 +33,20 @@ pub struct BlockProcessor {
     utxo_repository: UtxoRepository,
     monitored_addresses_repository: MonitoredAddressesRepository,
     mempool_spends_repository: MempoolSpendsRepository,
+    mint_events_repository: MintEventsRepository,
+    pending_spells_repository: PendingSpellsRepository,
     address_transactions_repository: AddressTransactionsRepository,
     reorg_events_repository: ReorgEventsRepository,
+    webhooks_repository: WebhooksRepository,
+    offchain_metadata_repository: OffchainMetadataRepository,
+    parser_stats_repository: ParserStatsRepository,
+    /// Queue off-chain metadata fetches for new NFTs (`FETCH_OFFCHAIN_METADATA`).
+    fetch_offchain_metadata: bool,
+    /// Refreshes `assets.locked_supply` for the tokens each block touches.
+    locked_supply: LockedSupply,
+    progress: ProgressReporter,
     retry_handler: RetryHandler,
+    rpc_retry_handler: RetryHandler,
 }
 
 impl BlockProcessor {
@@ -50,12 +64,41 @@ impl BlockProcessor {
             utxo_repository: repos.utxo.clone(),
             monitored_addresses_repository: repos.monitored_addresses.clone(),
             mempool_spends_repository: repos.mempool_spends.clone(),
+            mint_events_repository: repos.mint_events.clone(),
+            pending_spells_repository: repos.pending_spells.clone(),
             address_transactions_repository: repos.address_transactions.clone(),
             reorg_events_repository: repos.reorg_events.clone(),
+            webhooks_repository: repos.webhooks.clone(),
+            offchain_metadata_repository: repos.offchain_metadata.clone(),
+            parser_stats_repository: repos.parser_stats.clone(),
+            fetch_offchain_metadata: false,
+            locked_supply: LockedSupply::new(repos.mempool_spends.get_connection(), &[]),
+            progress: ProgressReporter::per_block(),
             retry_handler: RetryHandler::new(),
+            rpc_retry_handler: RetryHandler::for_rpc(),
         }
     }
 
+    /// Queue off-chain metadata fetches for the NFTs each block creates.
+    pub fn with_offchain_metadata(mut self, enabled: bool) -> Self {
+        self.fetch_offchain_metadata = enabled;
+        self
+    }
+
+    /// Count holdings at `LOCKED_SUPPLY_ADDRESSES` entries as locked supply,
+    /// besides open DEX order addresses.
+    pub fn with_locked_supply_addresses(mut self, addresses: &[String]) -> Self {
+        self.locked_supply =
+            LockedSupply::new(self.mempool_spends_repository.get_connection(), addresses);
+        self
+    }
+
+    /// Log completed blocks through `progress` instead of one line each.
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
     /// Process a single block: detect → save → mark spent → update stats
     #[tracing::instrument(
         name = "block",
@@ -74,14 +117,26 @@ impl BlockProcessor {
             .await
             .map_err(BlockProcessorError::BitcoinClientError)?;
 
+        // Timeouts and dropped connections are retried in place; pruned or
+        // missing blocks fail fast so the caller can skip the height.
         let block_hash = self
-            .bitcoin_client
-            .get_block_hash(height)
+            .rpc_retry_handler
+            .execute_with_retry_when(
+                || self.bitcoin_client.get_block_hash(height),
+                BitcoinClientError::is_retryable,
+                "get_block_hash",
+                &network_id.name,
+            )
             .await
             .map_err(BlockProcessorError::BitcoinClientError)?;
         let block = self
-            .bitcoin_client
-            .get_block(&block_hash)
+            .rpc_retry_handler
+            .execute_with_retry_when(
+                || self.bitcoin_client.get_block(&block_hash),
+                BitcoinClientError::is_retryable,
+                "get_block",
+                &network_id.name,
+            )
             .await
             .map_err(BlockProcessorError::BitcoinClientError)?;
 
@@ -103,13 +158,14 @@ impl BlockProcessor {
                 return Err(BlockProcessorError::ReorgRolledBackTo(h));
             }
         }
+        let fetched = started.elapsed();
 
         // STEP 1: Detect charms from all transactions (Strict ZK).
         // Runs BEFORE the mempool consolidator so we know exactly which
         // block txids passed verification — the consolidator then promotes
         // only those mempool rows and purges the rest. Plan 15.
         let dex_repo = self.charm_service.get_dex_orders_repository();
-        let (transaction_batch, charm_batch, asset_batch) = detection::detect_charms(
+        let (transaction_batch, charm_batch, asset_batch, mint_batch) = detection::detect_charms(
             &block,
             height,
             latest_height,
@@ -117,8 +173,10 @@ impl BlockProcessor {
             "Bitcoin",
             &self.charm_service,
             Some(dex_repo),
+            Some(&self.pending_spells_repository),
         )
         .await;
+        let parsed = started.elapsed();
 
         // STEP 0: Consolidate mempool, informed by the verified set.
         let verified_txids: std::collections::HashSet<String> = transaction_batch
@@ -137,6 +195,7 @@ impl BlockProcessor {
         let batch_processor = BatchProcessor::new(
             self.charm_service.clone(),
             self.transaction_repository.clone(),
+            self.mint_events_repository.clone(),
         );
 
         // STEP 2: Save transactions
@@ -154,6 +213,11 @@ impl BlockProcessor {
             .save_asset_batch(asset_batch, height, network_id)
             .await?;
 
+        // STEP 4b: Record mint events (net supply increases)
+        batch_processor
+            .save_mint_event_batch(mint_batch, height, network_id)
+            .await?;
+
         // STEP 5: Mark spent charms; gather NEGATIVE holder deltas (don't apply yet).
         let sub_deltas = spent_tracker::mark_spent_charms(
             &block,
@@ -165,14 +229,17 @@ impl BlockProcessor {
         .await?;
 
         // STEP 5.0: Merge add + sub deltas into a single net update per
-        // (app_id, address) and apply it once. Splitting the writes across
-        // two `update_holders_batch` calls in the same block re-trips the
-        // `last_updated_block < block` gate at the repo layer (anomaly A1):
-        // the second call always sees `last_updated_block == block` and
-        // silently drops the negative delta. Net-then-apply removes the
-        // race without weakening the gate's crash-recovery guarantee.
-        self.apply_merged_holder_updates(add_deltas, sub_deltas, network_id)
-            .await;
+        // (app_id, address) and apply it once under the block's claim in
+        // `stats_holders_applied_blocks`, so a block re-processed after a
+        // crash does not count its deltas twice.
+        self.apply_merged_holder_updates(
+            add_deltas,
+            sub_deltas,
+            &block_hash.to_string(),
+            height,
+            network_id,
+        )
+        .await;
 
         // STEP 5.5a: Auto-register charm addresses for monitoring
         utxo_indexer::register_charm_addresses(
@@ -202,6 +269,35 @@ impl BlockProcessor {
         )
         .await;
 
+        // STEP 5.6: Expire DEX orders whose expiry height/time this block passed
+        match dex_repo
+            .expire_orders(&network_id.name, height, block.header.time)
+            .await
+        {
+            Ok(0) => {}
+            Ok(n) => logging::log_info(&format!(
+                "[{}] ⌛ Block {}: Expired {} DEX order(s)",
+                network_id.name, height, n
+            )),
+            Err(e) => logging::log_warning(&format!(
+                "[{}] ⚠️ Block {}: Failed to expire DEX orders: {}",
+                network_id.name, height, e
+            )),
+        }
+
+        // STEP 5.7: Refresh the locked supply of tokens minted or spent in
+        // this block; the periodic pass covers orders closed without a spend.
+        if let Err(e) = self
+            .locked_supply
+            .refresh_block(&network_id.name, height)
+            .await
+        {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Block {}: Failed to refresh locked supply: {}",
+                network_id.name, height, e
+            ));
+        }
+
         // STEP 6: Update summary statistics
         let summary_updater =
             SummaryUpdater::new(self.bitcoin_client.clone(), self.summary_repository.clone());
@@ -224,6 +320,7 @@ impl BlockProcessor {
                 Some(&block_hash.to_string()),
                 Some(&block.header.prev_blockhash.to_string()),
                 block.txdata.len() as i32,
+                detection::header_time(&block),
                 network_id,
             )
             .await;
@@ -238,38 +335,103 @@ impl BlockProcessor {
                 .await;
         }
 
-        let remaining = latest_height.saturating_sub(height);
-        logging::log_info(&format!(
-            "[{}] ✅ Block {}: Tx {} | Charms {} ({} remaining)",
-            network_id.name,
-            height,
-            block.txdata.len(),
-            charm_batch.len(),
-            remaining
-        ));
+        // STEP 8: Queue webhook events now that the block is persisted. The
+        // dispatcher delivers them; a failure here only costs notifications.
+        let block_txids: Vec<String> = block
+            .txdata
+            .iter()
+            .filter(|tx| !tx.is_coin_base())
+            .map(|tx| tx.txid().to_string())
+            .collect();
+        if let Err(e) = self
+            .webhooks_repository
+            .enqueue_block_events(&network_id.name, height as i32, &block_txids)
+            .await
+        {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Block {}: Failed to queue webhook events: {}",
+                network_id.name, height, e
+            ));
+        }
 
-        // Metrics: block + per-asset_type charm counters + current height gauge.
-        crate::utils::metrics::block_processed(
+        // STEP 9: Queue off-chain metadata fetches for the block's new NFTs.
+        // Only rows are written here; the fetcher downloads them separately.
+        if self.fetch_offchain_metadata {
+            if let Err(e) = self
+                .offchain_metadata_repository
+                .enqueue_block_assets(&network_id.name, height as i32)
+                .await
+            {
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ Block {}: Failed to queue off-chain metadata fetches: {}",
+                    network_id.name, height, e
+                ));
+            }
+        }
+
+        // STEP 10: Record where the time went, for regression tracking.
+        let elapsed = started.elapsed();
+        let timings = BlockTimings::from_marks(fetched, parsed, elapsed);
+        if let Err(e) = self
+            .block_status_repository
+            .record_timings(height as i32, &timings, network_id)
+            .await
+        {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Block {}: Failed to record timings: {}",
+                network_id.name, height, e
+            ));
+        }
+
+        self.progress.report(
             &network_id.name,
-            started.elapsed().as_secs_f64(),
+            BlockStats {
+                height,
+                latest_height,
+                txs: block.txdata.len(),
+                charms: charm_batch.len(),
+            },
         );
+
+        // Metrics: block + per-asset_type charm counters + current height gauge.
+        crate::utils::metrics::block_processed(&network_id.name, elapsed.as_secs_f64());
         crate::utils::metrics::current_height(&network_id.name, height);
         for charm in &charm_batch {
-            crate::utils::metrics::charm_detected(&network_id.name, &charm.asset_type);
+            crate::utils::metrics::charm_detected(&network_id.name, charm.asset_type.as_str());
         }
 
+        // Parser counters of this block, and of mempool txs parsed since the last one.
+        let parser_stats = crate::utils::parser_stats::global();
+        for (source, counts) in parser_stats.take(&network_id.name) {
+            if let Err(e) = self
+                .parser_stats_repository
+                .add(&network_id.name, source, &counts)
+                .await
+            {
+                parser_stats.merge(&network_id.name, source, &counts);
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ Block {}: Failed to record parser stats: {}",
+                    network_id.name, height, e
+                ));
+            }
+        }
+
+        // Summarize per-row warnings held back during the last window.
+        crate::utils::throttled_log::global().flush();
+
         Ok(())
     }
 
     /// Merge the additive deltas from `save_charm_batch` with the
     /// subtractive deltas from `mark_spent_charms` by (app_id, address)
-    /// and apply a single `update_holders_batch` call. Zero-net entries are
-    /// skipped (their balance did not change). `last_updated_block` is the
-    /// max height seen on either side so the gate at the repo advances.
+    /// and apply them once for the block via `apply_block_deltas`.
+    /// Zero-net entries are skipped (their balance did not change).
     async fn apply_merged_holder_updates(
         &self,
         adds: Vec<(String, String, i64, i32)>,
         subs: Vec<(String, String, i64, i32)>,
+        block_hash: &str,
+        height: u64,
         network_id: &NetworkId,
     ) {
         if adds.is_empty() && subs.is_empty() {
@@ -295,7 +457,7 @@ impl BlockProcessor {
         if let Err(e) = self
             .charm_service
             .get_stats_holders_repository()
-            .update_holders_batch(updates, &network_id.name)
+            .apply_block_deltas(updates, &network_id.name, block_hash, height as i32)
             .await
         {
             logging::log_warning(&format!(
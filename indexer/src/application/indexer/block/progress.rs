@@ -0,0 +1,205 @@
+//! Block progress logging that stays quiet during catch-up.
+//!
+//! Near the tip each processed block gets its own line. Further behind, the
+//! reporter aggregates blocks and emits one progress line every
+//! `every_blocks` blocks or `every` elapsed, whichever comes first, so a
+//! catch-up does not flood log storage. Blocks that contained charms are
+//! always logged on their own.
+//!
+//! Clones share one window: `BitcoinProcessor` builds a fresh
+//! `BlockProcessor` per block and hands each a clone.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::utils::logging;
+
+/// Blocks from the tip within which every block is logged.
+pub const TIP_DISTANCE: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressConfig {
+    /// Blocks aggregated into one catch-up line.
+    pub every_blocks: u64,
+    /// Longest time between two catch-up lines.
+    pub every: Duration,
+    /// Blocks from the tip within which every block is logged.
+    pub tip_distance: u64,
+}
+
+impl Default for ProgressConfig {
+    fn default() -> Self {
+        Self {
+            every_blocks: 100,
+            every: Duration::from_secs(30),
+            tip_distance: TIP_DISTANCE,
+        }
+    }
+}
+
+/// What one processed block contributes to the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockStats {
+    pub height: u64,
+    pub latest_height: u64,
+    pub txs: usize,
+    pub charms: usize,
+}
+
+impl BlockStats {
+    pub fn remaining(&self) -> u64 {
+        self.latest_height.saturating_sub(self.height)
+    }
+}
+
+/// Catch-up blocks not yet covered by a progress line.
+#[derive(Debug)]
+struct Window {
+    first_height: u64,
+    blocks: u64,
+    txs: u64,
+    charms: u64,
+    started: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    cfg: ProgressConfig,
+    window: Arc<Mutex<Option<Window>>>,
+}
+
+impl ProgressReporter {
+    pub fn new(cfg: ProgressConfig) -> Self {
+        Self {
+            cfg,
+            window: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Logs every block, wherever it is; for one-off reprocessing.
+    pub fn per_block() -> Self {
+        Self::new(ProgressConfig {
+            tip_distance: u64::MAX,
+            ..ProgressConfig::default()
+        })
+    }
+
+    /// Record a processed block and log whatever the policy emits for it.
+    pub fn report(&self, network: &str, stats: BlockStats) {
+        if let Some(line) = self.record(network, stats, Instant::now()) {
+            logging::log_info(&line);
+        }
+    }
+
+    /// The line to emit for `stats` processed at `now`, if any.
+    pub fn record(&self, network: &str, stats: BlockStats, now: Instant) -> Option<String> {
+        let mut window = self.window.lock().unwrap();
+        if stats.remaining() <= self.cfg.tip_distance {
+            *window = None;
+            return Some(block_line(network, &stats));
+        }
+
+        let w = window.get_or_insert_with(|| Window {
+            first_height: stats.height,
+            blocks: 0,
+            txs: 0,
+            charms: 0,
+            started: now,
+        });
+        w.blocks += 1;
+        w.txs += stats.txs as u64;
+        w.charms += stats.charms as u64;
+        if stats.charms > 0 {
+            return Some(block_line(network, &stats));
+        }
+
+        let elapsed = now.saturating_duration_since(w.started);
+        if w.blocks < self.cfg.every_blocks && elapsed < self.cfg.every {
+            return None;
+        }
+        let line = format!(
+            "[{}] ⏩ catch-up blocks={}..={} count={} txs={} charms={} rate={:.1}/s remaining={}",
+            network,
+            w.first_height,
+            stats.height,
+            w.blocks,
+            w.txs,
+            w.charms,
+            w.blocks as f64 / elapsed.as_secs_f64().max(0.001),
+            stats.remaining()
+        );
+        *window = None;
+        Some(line)
+    }
+}
+
+fn block_line(network: &str, stats: &BlockStats) -> String {
+    format!(
+        "[{}] ✅ Block {}: Tx {} | Charms {} ({} remaining)",
+        network,
+        stats.height,
+        stats.txs,
+        stats.charms,
+        stats.remaining()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Heights 1..=1000 against a tip at 1000, one block per second.
+    fn run(reporter: &ProgressReporter, charm_heights: &[u64]) -> Vec<String> {
+        let start = Instant::now();
+        (1..=1000u64)
+            .filter_map(|height| {
+                let stats = BlockStats {
+                    height,
+                    latest_height: 1000,
+                    txs: 10,
+                    charms: usize::from(charm_heights.contains(&height)),
+                };
+                reporter.record("mainnet", stats, start + Duration::from_secs(height))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn catch_up_emits_one_line_per_window_plus_charm_and_tip_blocks() {
+        let reporter = ProgressReporter::new(ProgressConfig {
+            every_blocks: 100,
+            every: Duration::from_secs(3600),
+            tip_distance: 10,
+        });
+        let lines = run(&reporter, &[250, 550, 777]);
+
+        // 9 windows of 100 (blocks 1..=900), 3 charm blocks, 11 blocks
+        // within 10 of the tip (990..=1000).
+        assert_eq!(lines.len(), 9 + 3 + 11);
+        assert_eq!(lines.iter().filter(|l| l.contains("catch-up")).count(), 9);
+        assert!(lines[0].contains("blocks=1..=100 count=100 txs=1000 charms=0"));
+        assert!(lines.iter().any(|l| l.contains("blocks=201..=300 count=100 txs=1000 charms=1")));
+        assert!(lines.iter().any(|l| l.contains("Block 777: Tx 10 | Charms 1")));
+        assert!(lines.last().unwrap().contains("Block 1000: Tx 10 | Charms 0 (0 remaining)"));
+    }
+
+    #[test]
+    fn slow_catch_up_emits_a_line_per_interval() {
+        let reporter = ProgressReporter::new(ProgressConfig {
+            every_blocks: 10_000,
+            every: Duration::from_secs(30),
+            tip_distance: 10,
+        });
+        let lines = run(&reporter, &[]);
+
+        // A window opens at its first block and closes 30 blocks (seconds)
+        // later: 31 blocks per line over 1..=989, then the tip blocks.
+        assert_eq!(lines.len(), 989 / 31 + 11);
+        assert!(lines[0].contains("blocks=1..=31 count=31"));
+    }
+
+    #[test]
+    fn per_block_logs_everything() {
+        assert_eq!(run(&ProgressReporter::per_block(), &[]).len(), 1000);
+    }
+}
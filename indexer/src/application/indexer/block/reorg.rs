@@ -8,7 +8,10 @@
 //! ancestor; everything above is wiped and the indexer resumes from there.
 //!
 //! Tables wiped on rollback (idempotent — all use `DELETE WHERE block_height > h`):
-//! - `charms`, `transactions`, `assets`, `address_utxos`, `block_status`
+//! - `charms`, `transactions`, `assets`, `address_utxos`, `block_status`.
+//!   The four data tables share the one predicate so they never disagree on
+//!   which blocks are gone; everything above the common ancestor is orphaned,
+//!   whatever `block_hash` a charm row recorded.
 //! - `dex_orders` are marked `status='reorged'` instead of deleted (audit trail).
 //! - `mempool_spends` are fully cleared (mempool re-emerges naturally).
 //! - `stats_holders` is invalidated by deleting rows above the divergence;
@@ -145,6 +148,8 @@ async fn rollback_above(
         "UPDATE dex_orders SET status = 'reorged' WHERE block_height > $1 AND network = $2",
         "DELETE FROM mempool_spends WHERE network = $1",
         "DELETE FROM stats_holders WHERE last_updated_block > $1 AND network = $2",
+        "DELETE FROM mint_events WHERE block_height > $1 AND network = $2",
+        "DELETE FROM address_utxo_spends WHERE spent_height > $1 AND network = $2",
     ];
 
     for (i, sql) in statements.iter().enumerate() {
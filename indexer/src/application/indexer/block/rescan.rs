@@ -0,0 +1,99 @@
+//! Re-run the last blocks before the bookmark on startup (`RESCAN_DEPTH`).
+//!
+//! A block processed near the tip is marked done even when the provider
+//! served it stale or incomplete, and its charms would stay missing. The
+//! rescan replays the window through the block pipeline, which is
+//! idempotent (see `maintenance::reindex`), so only what the first pass
+//! missed is added. Blocks the node no longer has are skipped. Like the
+//! other replay passes, holders are rebuilt when charms were found because
+//! the `last_updated_block` gate skips replayed heights.
+
+use std::future::Future;
+
+use crate::domain::errors::BlockProcessorError;
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::{CharmRepository, StatsHoldersRepository};
+use crate::utils::logging;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RescanSummary {
+    /// Blocks re-run through the pipeline.
+    pub blocks: u64,
+    /// Blocks skipped because the node no longer serves them.
+    pub pruned: u64,
+    /// Charms in the window that were not stored before the rescan.
+    pub new_charms: u64,
+    /// Set when re-running a block detected a reorg; the live loop resumes
+    /// from the height after it.
+    pub rolled_back_to: Option<u64>,
+}
+
+/// Heights to rescan when the live loop resumes at `resume_height`: the
+/// last `depth` processed blocks, none below `genesis`.
+pub fn window(resume_height: u64, genesis: u64, depth: u64) -> Option<(u64, u64)> {
+    if depth == 0 || resume_height <= genesis {
+        return None;
+    }
+    let to = resume_height - 1;
+    Some((resume_height.saturating_sub(depth).max(genesis), to))
+}
+
+/// Re-run heights `from..=to` of `network`, oldest first. `reprocess_block`
+/// runs the block pipeline for one height. The pass stops at the first error
+/// other than a missing block; what ran before it is kept.
+pub async fn rescan<F, Fut>(
+    charms: &CharmRepository,
+    stats_holders: &StatsHoldersRepository,
+    network: &str,
+    from: u64,
+    to: u64,
+    mut reprocess_block: F,
+) -> Result<RescanSummary, DbError>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<(), BlockProcessorError>>,
+{
+    let mut summary = RescanSummary::default();
+    let before = charms.count_in_range(network, from, to).await?;
+    for height in from..=to {
+        match reprocess_block(height).await {
+            Ok(()) => summary.blocks += 1,
+            Err(BlockProcessorError::BitcoinClientError(e)) if e.is_block_unavailable() => {
+                summary.pruned += 1;
+            }
+            Err(BlockProcessorError::ReorgRolledBackTo(h)) => {
+                summary.rolled_back_to = Some(h);
+                break;
+            }
+            Err(e) => {
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ rescan: block {} failed, stopping the rescan: {}",
+                    network, height, e
+                ));
+                break;
+            }
+        }
+    }
+
+    let after = charms.count_in_range(network, from, to).await?;
+    summary.new_charms = after.saturating_sub(before);
+    if summary.new_charms > 0 {
+        stats_holders.rebuild_from_charms(network, None).await?;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_covers_the_last_processed_blocks() {
+        assert_eq!(window(1_000, 0, 6), Some((994, 999)));
+        assert_eq!(window(1_000, 997, 6), Some((997, 999)));
+        assert_eq!(window(3, 0, 10), Some((0, 2)));
+        // Off, or nothing processed yet.
+        assert_eq!(window(1_000, 0, 0), None);
+        assert_eq!(window(500, 500, 6), None);
+    }
+}
@@ -20,6 +20,25 @@ impl RetryHandler {
         }
     }
 
+    /// Policy for node RPC fetches (block hash / block body). Each attempt is
+    /// already bounded by `BITCOIN_RPC_TIMEOUT_SECS`, so fewer, shorter
+    /// retries keep a hung node from stalling a block for minutes; the outer
+    /// loop's backoff takes over after that.
+    pub fn for_rpc() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 500,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_policy(max_retries: u32, base_delay_ms: u64) -> Self {
+        Self {
+            max_retries,
+            base_delay_ms,
+        }
+    }
+
     /// Execute an operation with retry logic and custom error handling
     pub async fn execute_with_retry_and_logging<F, Fut, T, E>(
         &self,
@@ -31,6 +50,25 @@ impl RetryHandler {
         F: Fn() -> Fut,
         Fut: Future<Output = Result<T, E>>,
         E: std::fmt::Display,
+    {
+        self.execute_with_retry_when(operation, |_| true, operation_name, network_name)
+            .await
+    }
+
+    /// Like `execute_with_retry_and_logging`, but only retries errors for
+    /// which `is_retryable` returns true; anything else is returned at once.
+    pub async fn execute_with_retry_when<F, Fut, T, E, P>(
+        &self,
+        operation: F,
+        is_retryable: P,
+        operation_name: &str,
+        network_name: &str,
+    ) -> Result<T, E>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: std::fmt::Display,
+        P: Fn(&E) -> bool,
     {
         let mut retry_count = 0;
 
@@ -45,6 +83,7 @@ impl RetryHandler {
                     }
                     return Ok(result);
                 }
+                Err(e) if !is_retryable(&e) => return Err(e),
                 Err(e) => {
                     retry_count += 1;
 
@@ -79,3 +118,142 @@ impl Default for RetryHandler {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use bitcoincore_rpc::bitcoin::{Block, BlockHash};
+
+    use super::*;
+    use crate::config::{NetworkId, NetworkType};
+    use crate::infrastructure::bitcoin::{
+        BitcoinClient, BitcoinClientError, BitcoinProvider, RpcTimeouts, SimpleBitcoinClient,
+    };
+
+    const ZERO_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+    /// Hangs on the first `hang_for` calls, then answers normally.
+    #[derive(Debug)]
+    struct FlakyProvider {
+        hang_for: u32,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl BitcoinProvider for FlakyProvider {
+        fn provider_name(&self) -> String {
+            "flaky".to_string()
+        }
+
+        async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+            Ok(0)
+        }
+
+        async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.hang_for {
+                std::future::pending::<()>().await;
+            }
+            Ok(BlockHash::from_str(ZERO_HASH).unwrap())
+        }
+
+        async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+            Err(BitcoinClientError::Other("Block not available (pruned data)".to_string()))
+        }
+
+        async fn get_raw_transaction_hex(
+            &self,
+            _txid: &str,
+            _block_hash: Option<&BlockHash>,
+        ) -> Result<String, BitcoinClientError> {
+            Ok(String::new())
+        }
+
+        async fn apply_rate_limiting(&self) {}
+    }
+
+    fn client(provider: Arc<FlakyProvider>) -> BitcoinClient {
+        let simple = SimpleBitcoinClient::from_provider(
+            provider,
+            NetworkId::new(NetworkType::Bitcoin, "testnet4"),
+        );
+        BitcoinClient::from_simple_client(simple).with_timeouts(RpcTimeouts {
+            block: Duration::from_millis(20),
+            tx: Duration::from_millis(20),
+        })
+    }
+
+    #[tokio::test]
+    async fn retries_past_timeouts() {
+        let provider = Arc::new(FlakyProvider {
+            hang_for: 2,
+            calls: AtomicU32::new(0),
+        });
+        let client = client(provider.clone());
+        let handler = RetryHandler::with_policy(3, 1);
+
+        let hash = handler
+            .execute_with_retry_when(
+                || client.get_block_hash(1),
+                BitcoinClientError::is_retryable,
+                "get_block_hash",
+                "testnet4",
+            )
+            .await
+            .expect("third attempt should answer");
+        assert_eq!(hash.to_string(), ZERO_HASH);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_with_timeout_after_max_retries() {
+        let provider = Arc::new(FlakyProvider {
+            hang_for: u32::MAX,
+            calls: AtomicU32::new(0),
+        });
+        let client = client(provider.clone());
+        let handler = RetryHandler::with_policy(3, 1);
+
+        let err = handler
+            .execute_with_retry_when(
+                || client.get_block_hash(1),
+                BitcoinClientError::is_retryable,
+                "get_block_hash",
+                "testnet4",
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BitcoinClientError::Timeout(_)));
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_missing_blocks() {
+        let provider = Arc::new(FlakyProvider {
+            hang_for: 0,
+            calls: AtomicU32::new(0),
+        });
+        let client = client(provider);
+        let handler = RetryHandler::with_policy(3, 1);
+        let hash = BlockHash::from_str(ZERO_HASH).unwrap();
+        let attempts = AtomicU32::new(0);
+
+        let err = handler
+            .execute_with_retry_when(
+                || {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    client.get_block(&hash)
+                },
+                BitcoinClientError::is_retryable,
+                "get_block",
+                "testnet4",
+            )
+            .await
+            .unwrap_err();
+        assert!(err.is_block_unavailable());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
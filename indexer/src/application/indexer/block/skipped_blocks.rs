@@ -0,0 +1,72 @@
+//! Revisit blocks skipped because the node did not have them.
+//!
+//! The live loop flags a pruned/missing height `skipped_pruned` and moves
+//! on. This pass re-runs a few of those heights, oldest first, through the
+//! block pipeline; a block that is now served fills its row in and
+//! `mark_downloaded` clears the flag. Like the pending-spells drain, holders
+//! of the network are rebuilt afterwards because the `last_updated_block`
+//! gate skips the replayed heights.
+
+use std::future::Future;
+
+use crate::config::NetworkId;
+use crate::domain::errors::BlockProcessorError;
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::{
+    BlockStatusRepository, StatsHoldersRepository,
+};
+use crate::utils::logging;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetrySummary {
+    /// Skipped blocks now processed.
+    pub filled: u64,
+    /// Blocks the provider still does not have.
+    pub still_missing: u64,
+    /// Set when re-running a block detected a reorg; the live loop resumes
+    /// from the height after it.
+    pub rolled_back_to: Option<u64>,
+}
+
+/// Re-attempt up to `limit` skipped blocks of `network_id`. `reprocess_block`
+/// runs the block pipeline for one height. The pass stops at the first error
+/// other than a missing block, so a provider outage costs one call.
+pub async fn retry<F, Fut>(
+    block_status: &BlockStatusRepository,
+    stats_holders: &StatsHoldersRepository,
+    network_id: &NetworkId,
+    limit: u64,
+    mut reprocess_block: F,
+) -> Result<RetrySummary, DbError>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<(), BlockProcessorError>>,
+{
+    let mut summary = RetrySummary::default();
+    for height in block_status.get_skipped_pruned(network_id, limit).await? {
+        match reprocess_block(height as u64).await {
+            Ok(()) => summary.filled += 1,
+            Err(BlockProcessorError::BitcoinClientError(e)) if e.is_block_unavailable() => {
+                summary.still_missing += 1;
+            }
+            Err(BlockProcessorError::ReorgRolledBackTo(h)) => {
+                summary.rolled_back_to = Some(h);
+                break;
+            }
+            Err(e) => {
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ skipped blocks: retry of block {} failed, stopping this pass: {}",
+                    network_id.name, height, e
+                ));
+                break;
+            }
+        }
+    }
+
+    if summary.filled > 0 {
+        stats_holders
+            .rebuild_from_charms(&network_id.name, None)
+            .await?;
+    }
+    Ok(summary)
+}
@@ -9,9 +9,9 @@ use crate::domain::services::CharmService;
 use super::retry::RetryHandler;
 
 /// Collect all (txid, vout) pairs being spent in a block, mark them as
-/// spent in `charms`, and return the negative holder deltas so the block
+/// spent in `charms` (recording the spending txid), and return the negative holder deltas so the block
 /// processor can merge them with the additive deltas before calling
-/// `update_holders_batch` once per (app_id, address) per block.
+/// `apply_block_deltas` once per (app_id, address) per block.
 pub async fn mark_spent_charms(
     block: &bitcoin::Block,
     height: u64,
@@ -19,21 +19,23 @@ pub async fn mark_spent_charms(
     charm_service: &CharmService,
     retry_handler: &RetryHandler,
 ) -> Result<Vec<(String, String, i64, i32)>, BlockProcessorError> {
-    let mut spent_txid_vouts: Vec<(String, i32)> = Vec::new();
+    let mut spends: Vec<(String, i32, String)> = Vec::new();
 
     for tx in &block.txdata {
         if tx.is_coin_base() {
             continue;
         }
+        let spending_txid = tx.txid().to_string();
         for input in &tx.input {
-            spent_txid_vouts.push((
+            spends.push((
                 input.previous_output.txid.to_string(),
                 input.previous_output.vout as i32,
+                spending_txid.clone(),
             ));
         }
     }
 
-    if spent_txid_vouts.is_empty() {
+    if spends.is_empty() {
         return Ok(Vec::new());
     }
 
@@ -43,7 +45,7 @@ pub async fn mark_spent_charms(
             || async {
                 charm_service
                     .mark_charms_as_spent_batch(
-                        spent_txid_vouts.clone(),
+                        spends.clone(),
                         &network_id.name,
                         block_height,
                     )
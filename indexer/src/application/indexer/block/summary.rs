@@ -2,6 +2,7 @@
 
 use crate::config::NetworkId;
 use crate::domain::errors::BlockProcessorError;
+use crate::domain::models::{AssetType, TransactionStatus};
 use crate::infrastructure::bitcoin::BitcoinClient;
 use crate::infrastructure::persistence::repositories::SummaryRepository;
 
@@ -54,7 +55,7 @@ impl SummaryUpdater {
 
         let confirmed_transactions = transaction_batch
             .iter()
-            .filter(|tx| tx.is_confirmed)
+            .filter(|tx| tx.status == TransactionStatus::Confirmed)
             .count() as i64;
 
         let current_summary = self
@@ -115,10 +116,11 @@ impl SummaryUpdater {
 fn calculate_asset_counts(charm_batch: &[CharmBatchItem]) -> AssetCounts {
     let mut counts = AssetCounts::default();
     for charm_item in charm_batch {
-        match charm_item.asset_type.as_str() {
-            "nft" => counts.nft_count += 1,
-            "token" => counts.token_count += 1,
-            _ => counts.other_count += 1,
+        match charm_item.asset_type {
+            AssetType::Nft => counts.nft_count += 1,
+            AssetType::Token => counts.token_count += 1,
+            AssetType::Dapp => counts.dapp_count += 1,
+            AssetType::Other | AssetType::Spell => counts.other_count += 1,
         }
     }
     counts
@@ -171,3 +173,43 @@ struct SummaryTotals {
     total_dapp_count: i64,
     total_other_count: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn charm(asset_type: AssetType) -> CharmBatchItem {
+        CharmBatchItem {
+            txid: "aa".to_string(),
+            vout: 0,
+            block_height: 100,
+            data: serde_json::Value::Null,
+            asset_type,
+            blockchain: "Bitcoin".to_string(),
+            network: "mainnet".to_string(),
+            address: None,
+            app_id: "x/aa/bb".to_string(),
+            amount: 0,
+            tags: None,
+            block_hash: None,
+            tx_ordinal: None,
+            operation: charms_core::CharmOperation::Transfer,
+            block_time: None,
+        }
+    }
+
+    #[test]
+    fn asset_counts_bucket_every_type() {
+        let batch: Vec<_> = AssetType::ALL.into_iter().map(charm).collect();
+        let counts = calculate_asset_counts(&batch);
+        assert_eq!(
+            (
+                counts.nft_count,
+                counts.token_count,
+                counts.dapp_count,
+                counts.other_count
+            ),
+            (1, 1, 1, 2)
+        );
+    }
+}
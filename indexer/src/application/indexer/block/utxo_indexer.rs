@@ -5,6 +5,7 @@ use bitcoincore_rpc::bitcoin;
 
 use crate::config::NetworkId;
 use crate::domain::errors::BlockProcessorError;
+use crate::domain::services::AddressExtractor;
 use crate::infrastructure::persistence::repositories::address_transactions_repository::AddressTxInsert;
 use crate::infrastructure::persistence::repositories::utxo_repository::UtxoInsert;
 use crate::infrastructure::persistence::repositories::{
@@ -40,7 +41,7 @@ pub async fn register_charm_addresses(
     {
         Ok(new_count) => {
             if new_count > 0 {
-                logging::log_info(&format!(
+                logging::log_debug(&format!(
                     "[{}] 📡 Registered {} new monitored addresses from charms",
                     network_id.name, new_count
                 ));
@@ -55,10 +56,43 @@ pub async fn register_charm_addresses(
     }
 }
 
+/// Spell marker pushed by both spell envelopes.
+const SPELL_MARKER: &[u8] = b"spell";
+
+/// Cheap check for a spell-shaped tx, without parsing: a witness element
+/// holding the taproot envelope (`OP_FALSE OP_IF "spell"`) or an output
+/// script starting `OP_RETURN "spell"`. Monitored UTXOs of such a tx are
+/// flagged `possible_charm`, so a charm the parser missed (unknown version,
+/// parser bug) is not offered to wallets as spendable BTC. A tx whose charms
+/// were stored is flagged too; wallets count those UTXOs as charm UTXOs
+/// first.
+pub fn looks_like_spell(tx: &bitcoin::Transaction) -> bool {
+    const OP_FALSE_OP_IF: [u8; 2] = [0x00, 0x63];
+    const OP_RETURN: u8 = 0x6a;
+    let marker_push = |rest: &[u8]| {
+        rest.first() == Some(&(SPELL_MARKER.len() as u8)) && rest[1..].starts_with(SPELL_MARKER)
+    };
+
+    let in_witness = tx.input.iter().any(|input| {
+        input.witness.iter().any(|element| {
+            element
+                .windows(OP_FALSE_OP_IF.len())
+                .enumerate()
+                .any(|(i, w)| w == OP_FALSE_OP_IF && marker_push(&element[i + 2..]))
+        })
+    });
+    in_witness
+        || tx.output.iter().any(|output| {
+            let script = output.script_pubkey.as_bytes();
+            script.first() == Some(&OP_RETURN) && marker_push(&script[1..])
+        })
+}
+
 /// Update UTXO index for monitored addresses only.
 /// 1. Load monitored address set
-/// 2. Delete spent UTXOs
+/// 2. Delete spent UTXOs, recording their spender in `address_utxo_spends`
 /// 3. Insert new UTXOs only for monitored addresses
+/// 4. Annotate them with the charms the block's detection already saved
 pub async fn update_monitored_utxos(
     block: &bitcoin::Block,
     height: u64,
@@ -83,23 +117,21 @@ pub async fn update_monitored_utxos(
         return Ok(());
     }
 
-    let btc_network = match network_str.as_str() {
-        "mainnet" => bitcoin::Network::Bitcoin,
-        "testnet4" => bitcoin::Network::Testnet,
-        _ => bitcoin::Network::Testnet,
-    };
+    let btc_network = AddressExtractor::network_for(network_str);
 
-    // 1. Collect spent UTXOs from inputs
-    let mut spent: Vec<(String, i32)> = Vec::new();
+    // 1. Collect spent UTXOs from inputs, with their spender
+    let mut spent: Vec<(String, i32, String)> = Vec::new();
     for tx in &block.txdata {
         if tx.is_coin_base() {
             continue;
         }
+        let spending_txid = tx.txid().to_string();
         for input in &tx.input {
             if !input.previous_output.is_null() {
                 spent.push((
                     input.previous_output.txid.to_string(),
                     input.previous_output.vout as i32,
+                    spending_txid.clone(),
                 ));
             }
         }
@@ -109,6 +141,7 @@ pub async fn update_monitored_utxos(
     let mut new_utxos: Vec<UtxoInsert> = Vec::new();
     for tx in &block.txdata {
         let txid = tx.txid().to_string();
+        let mut possible_charm = None;
         for (vout, output) in tx.output.iter().enumerate() {
             if output.script_pubkey.is_provably_unspendable() {
                 continue;
@@ -125,6 +158,7 @@ pub async fn update_monitored_utxos(
                         block_height: height as i32,
                         network: network_str.clone(),
                         source: "node".to_string(),
+                        possible_charm: *possible_charm.get_or_insert_with(|| looks_like_spell(tx)),
                     });
                 }
             }
@@ -134,7 +168,7 @@ pub async fn update_monitored_utxos(
     // 3. Delete spent UTXOs
     if !spent.is_empty() {
         if let Err(e) = utxo_repository
-            .delete_spent_batch(&spent, network_str)
+            .delete_spent_batch(&spent, height as i32, network_str)
             .await
         {
             logging::log_warning(&format!(
@@ -152,6 +186,20 @@ pub async fn update_monitored_utxos(
                 network_str, height, e
             ));
         }
+
+        // 5. Flag the ones carrying charms (saved earlier in the block)
+        let txids: Vec<String> = new_utxos
+            .iter()
+            .map(|u| u.txid.clone())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        if let Err(e) = utxo_repository.annotate_charms(&txids, network_str).await {
+            logging::log_warning(&format!(
+                "[{}] Failed to annotate charm UTXOs at block {}: {}",
+                network_str, height, e
+            ));
+        }
     }
 
     Ok(())
@@ -181,11 +229,7 @@ pub async fn record_address_transactions(
         return;
     }
 
-    let btc_network = match network_str.as_str() {
-        "mainnet" => bitcoin::Network::Bitcoin,
-        "testnet4" => bitcoin::Network::Testnet,
-        _ => bitcoin::Network::Testnet,
-    };
+    let btc_network = AddressExtractor::network_for(network_str);
 
     // Get block time from header
     let block_time = block.header.time as i64;
@@ -239,7 +283,7 @@ pub async fn record_address_transactions(
             .await
         {
             Ok(n) if n > 0 => {
-                logging::log_info(&format!(
+                logging::log_debug(&format!(
                     "[{}] 📝 Recorded {} address transactions at block {}",
                     network_str, n, height
                 ));
@@ -254,3 +298,64 @@ pub async fn record_address_transactions(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoincore_rpc::bitcoin::absolute::LockTime;
+    use bitcoincore_rpc::bitcoin::{
+        OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    };
+
+    fn tx(witness: Vec<Vec<u8>>, op_return: Option<Vec<u8>>) -> Transaction {
+        let mut output = vec![TxOut {
+            value: 1_000,
+            script_pubkey: ScriptBuf::new(),
+        }];
+        if let Some(script) = op_return {
+            output.push(TxOut {
+                value: 0,
+                script_pubkey: ScriptBuf::from_bytes(script),
+            });
+        }
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&witness),
+            }],
+            output,
+        }
+    }
+
+    #[test]
+    fn spell_envelopes_are_spotted_without_parsing() {
+        // Witness envelope with a payload no parser understands.
+        let mut script = vec![0x20];
+        script.extend([7u8; 32]);
+        script.extend([0xac, 0x00, 0x63, 0x05]);
+        script.extend(b"spell");
+        script.extend([0x03, 0xde, 0xad, 0x00, 0x68]);
+        let witness = vec![vec![1; 64], script, vec![0xc0; 33]];
+        assert!(looks_like_spell(&tx(witness, None)));
+
+        let mut op_return = vec![0x6a, 0x05];
+        op_return.extend(b"spell");
+        op_return.extend([0x02, 0xff, 0xff]);
+        assert!(looks_like_spell(&tx(vec![], Some(op_return))));
+    }
+
+    #[test]
+    fn plain_transactions_are_not_flagged() {
+        assert!(!looks_like_spell(&tx(vec![vec![1; 64]], None)));
+        // The marker alone, outside an envelope or OP_RETURN.
+        assert!(!looks_like_spell(&tx(vec![b"spell".to_vec()], None)));
+        let mut other = vec![0x6a, 0x05];
+        other.extend(b"hello");
+        let truncated = vec![vec![0x00, 0x63, 0x04]];
+        assert!(!looks_like_spell(&tx(truncated, Some(other))));
+    }
+}
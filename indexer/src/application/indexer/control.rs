@@ -0,0 +1,118 @@
+//! Pause/resume for a network's block processor.
+//!
+//! Operator commands are rows in `control_commands` (written by the API's
+//! admin endpoints or `ControlCommandsRepository::issue`). A `reindex` run
+//! holding the network's `reindex_locks` row pauses it too, until the run
+//! ends or its lock lapses. `BitcoinProcessor` asks the gate at the top of
+//! every loop iteration; while paused it skips block processing but keeps
+//! the summary heartbeat fresh with `indexer_paused = true`, so `/status`
+//! shows "paused" rather than stale.
+
+use crate::application::maintenance::reindex::LOCK_STALE_SECS;
+use crate::config::NetworkId;
+use crate::domain::models::ControlCommand;
+use crate::infrastructure::persistence::repositories::{
+    ControlCommandsRepository, ReindexLocksRepository, SummaryRepository,
+};
+use crate::utils::logging;
+
+#[derive(Debug)]
+pub struct PauseGate {
+    network_id: NetworkId,
+    control_commands: ControlCommandsRepository,
+    reindex_locks: ReindexLocksRepository,
+    summary: SummaryRepository,
+    by_operator: bool,
+    /// Run id of the reindex holding the network.
+    by_reindex: Option<String>,
+    paused: bool,
+}
+
+impl PauseGate {
+    pub fn new(
+        network_id: NetworkId,
+        control_commands: ControlCommandsRepository,
+        reindex_locks: ReindexLocksRepository,
+        summary: SummaryRepository,
+    ) -> Self {
+        Self {
+            network_id,
+            control_commands,
+            reindex_locks,
+            summary,
+            by_operator: false,
+            by_reindex: None,
+            paused: false,
+        }
+    }
+
+    /// Poll the latest command and the reindex lock, and report whether
+    /// blocks must be skipped this iteration. If a poll fails its previous
+    /// state is kept, so a DB hiccup never resumes a paused network (or
+    /// pauses a running one).
+    pub async fn is_paused(&mut self) -> bool {
+        let network = &self.network_id.name;
+        match self.control_commands.poll(network).await {
+            Ok(command) => {
+                let paused = command == Some(ControlCommand::Pause);
+                if paused != self.by_operator {
+                    logging::log_info(&format!(
+                        "[{}] {} Block processing {} by operator command",
+                        network,
+                        if paused { "⏸️" } else { "▶️" },
+                        if paused { "paused" } else { "resumed" }
+                    ));
+                }
+                self.by_operator = paused;
+            }
+            Err(e) => logging::log_warning(&format!(
+                "[{}] ⚠️ Failed to poll control commands: {}",
+                network, e
+            )),
+        }
+        match self.reindex_locks.active(network, LOCK_STALE_SECS).await {
+            Ok(lock) => {
+                let run_id = lock.map(|l| l.run_id);
+                if run_id != self.by_reindex {
+                    match &run_id {
+                        Some(run_id) => logging::log_info(&format!(
+                            "[{}] ⏸️ Reindex {} in progress; block processing paused until it completes",
+                            network, run_id
+                        )),
+                        None => logging::log_info(&format!(
+                            "[{}] ▶️ Reindex {} completed; block processing resumed",
+                            network,
+                            self.by_reindex.as_deref().unwrap_or_default()
+                        )),
+                    }
+                }
+                self.by_reindex = run_id;
+            }
+            Err(e) => logging::log_warning(&format!(
+                "[{}] ⚠️ Failed to check for a running reindex: {}",
+                network, e
+            )),
+        }
+
+        let paused = self.by_operator || self.by_reindex.is_some();
+        let changed = paused != self.paused;
+        self.paused = paused;
+        if paused || changed {
+            self.write_heartbeat().await;
+        }
+        self.paused
+    }
+
+    async fn write_heartbeat(&self) {
+        if let Err(e) = self
+            .summary
+            .set_indexer_paused(&self.network_id, self.paused)
+            .await
+        {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Failed to record paused state: {}",
+                self.network_id.name, e
+            ));
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Periodic garbage collector for rows the live pipelines leave behind.
+//!
+//! `mempool/cleanup.rs` purges by age, per network, every few minutes. It
+//! cannot see rows that are junk for other reasons: a mempool charm whose tx
+//! was mined under a different row, a stale mempool DEX order whose `network`
+//! matches no running processor, a monitored address that went quiet months
+//! ago, or a `mempool_spends` entry whose spender is long confirmed. This
+//! task sweeps those across all networks every `GC_INTERVAL_SECS` (6h). It
+//! also demotes monitored addresses whose API TTL (`expires_at`) ran out
+//! and drops changefeed entries and UTXO spend records past their
+//! retention.
+//!
+//! Each sweep works in batches of `GC_BATCH_SIZE` rows so no statement
+//! holds locks for long, and can be disabled on its own. The outcome of the
+//! last pass lands in `summary.last_gc_at` / `last_gc_stats` for `/status`.
+
+use std::time::Duration;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement};
+use serde_json::{Map, Value};
+use tokio_util::sync::CancellationToken;
+
+use crate::application::indexer::mempool::cleanup::STALE_HOURS;
+use crate::domain::models::TransactionStatus;
+use crate::infrastructure::persistence::repositories::SummaryRepository;
+use crate::utils::logging;
+
+/// One category of orphaned rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcSweep {
+    /// Unconfirmed charms whose tx is already in a block.
+    MempoolCharms,
+    /// Unconfirmed DEX orders past the mempool stale window, any network.
+    DexOrders,
+    /// Seeded monitored addresses with no UTXOs, no unspent charms and no
+    /// activity within the idle window.
+    MonitoredAddresses,
+    /// Mempool spends whose spending tx is confirmed.
+    MempoolSpends,
+    /// Monitored addresses past `expires_at` that hold no unspent charms:
+    /// their UTXOs and seed are dropped (the row stays, with
+    /// `last_queried_at`) so the next API call re-seeds them.
+    ExpiredMonitors,
+    /// Changefeed entries older than the retention window.
+    Changefeed,
+    /// `address_utxo_spends` rows spent more than the retention depth below
+    /// their network's tip. Raises `wallet_sync_horizon` past them, so
+    /// `/wallet/changes` asks older cursors to resync.
+    UtxoSpends,
+}
+
+impl GcSweep {
+    pub const ALL: [GcSweep; 7] = [
+        GcSweep::MempoolCharms,
+        GcSweep::DexOrders,
+        GcSweep::MonitoredAddresses,
+        GcSweep::MempoolSpends,
+        GcSweep::ExpiredMonitors,
+        GcSweep::Changefeed,
+        GcSweep::UtxoSpends,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GcSweep::MempoolCharms => "mempool_charms",
+            GcSweep::DexOrders => "dex_orders",
+            GcSweep::MonitoredAddresses => "monitored_addresses",
+            GcSweep::MempoolSpends => "mempool_spends",
+            GcSweep::ExpiredMonitors => "expired_monitors",
+            GcSweep::Changefeed => "changefeed",
+            GcSweep::UtxoSpends => "utxo_spends",
+        }
+    }
+
+    /// One batch of this sweep: a statement taking the batch size as `$1`
+    /// and touching at most that many rows.
+    fn statement(self, cfg: &GcConfig) -> String {
+        let (table, predicate) = match self {
+            GcSweep::ExpiredMonitors => {
+                return "WITH doomed AS ( \
+                            SELECT x.address, x.network FROM monitored_addresses x \
+                             WHERE x.expires_at <= NOW() AND x.seeded_at IS NOT NULL \
+                               AND NOT EXISTS (SELECT 1 FROM charms c \
+                                                WHERE c.address = x.address AND c.network = x.network \
+                                                  AND NOT c.spent) \
+                             LIMIT $1), \
+                        dropped AS ( \
+                            DELETE FROM address_utxos u USING doomed d \
+                             WHERE u.address = d.address AND u.network = d.network) \
+                        UPDATE monitored_addresses m \
+                           SET seeded_at = NULL, seed_height = NULL, seed_block_hash = NULL, \
+                               synced_from = NULL \
+                          FROM doomed d \
+                         WHERE m.address = d.address AND m.network = d.network"
+                    .to_string();
+            }
+            GcSweep::UtxoSpends => {
+                return format!(
+                    "WITH doomed AS ( \
+                        SELECT x.ctid, x.network, x.spent_height FROM address_utxo_spends x \
+                          JOIN summary s ON s.network = x.network \
+                         WHERE x.spent_height < s.last_processed_block - {blocks} \
+                         LIMIT $1), \
+                     horizon AS ( \
+                        INSERT INTO wallet_sync_horizon (network, from_height) \
+                        SELECT network, MAX(spent_height) FROM doomed GROUP BY network \
+                        ON CONFLICT (network) DO UPDATE \
+                           SET from_height = GREATEST(wallet_sync_horizon.from_height, \
+                                                      EXCLUDED.from_height), \
+                               updated_at = NOW()) \
+                     DELETE FROM address_utxo_spends WHERE ctid IN (SELECT ctid FROM doomed)",
+                    blocks = cfg.utxo_spends_retention_blocks
+                );
+            }
+            GcSweep::MempoolCharms => (
+                "charms",
+                "x.block_height IS NULL \
+                 AND (EXISTS (SELECT 1 FROM transactions t \
+                               WHERE t.txid = x.txid AND t.network = x.network \
+                                 AND t.block_height IS NOT NULL) \
+                      OR EXISTS (SELECT 1 FROM charms c \
+                                  WHERE c.txid = x.txid AND c.network = x.network \
+                                    AND c.block_height IS NOT NULL))"
+                    .to_string(),
+            ),
+            GcSweep::DexOrders => (
+                "dex_orders",
+                format!(
+                    "x.block_height IS NULL AND x.created_at < NOW() - INTERVAL '{} hours'",
+                    STALE_HOURS
+                ),
+            ),
+            GcSweep::MonitoredAddresses => (
+                "monitored_addresses",
+                format!(
+                    "x.seeded_at IS NOT NULL \
+                     AND x.created_at < NOW() - INTERVAL '{days} days' \
+                     AND NOT EXISTS (SELECT 1 FROM address_utxos u \
+                                      WHERE u.address = x.address AND u.network = x.network) \
+                     AND NOT EXISTS (SELECT 1 FROM charms c \
+                                      WHERE c.address = x.address AND c.network = x.network \
+                                        AND NOT c.spent) \
+                     AND NOT EXISTS (SELECT 1 FROM address_transactions a \
+                                      WHERE a.address = x.address AND a.network = x.network \
+                                        AND (a.block_height IS NULL OR a.block_time IS NULL \
+                                             OR a.block_time >= EXTRACT(EPOCH FROM NOW() - INTERVAL '{days} days')))",
+                    days = cfg.monitored_idle_days
+                ),
+            ),
+            GcSweep::MempoolSpends => (
+                "mempool_spends",
+                format!(
+                    "EXISTS (SELECT 1 FROM transactions t \
+                              WHERE t.txid = x.spending_txid AND t.network = x.network \
+                                AND t.status = '{}')",
+                    TransactionStatus::Confirmed
+                ),
+            ),
+            GcSweep::Changefeed => (
+                "changefeed",
+                format!(
+                    "x.created_at < NOW() - INTERVAL '{} days'",
+                    cfg.changefeed_retention_days
+                ),
+            ),
+        };
+        format!(
+            "DELETE FROM {table} WHERE ctid IN \
+               (SELECT x.ctid FROM {table} x WHERE {predicate} LIMIT $1)"
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GcConfig {
+    /// Pause between passes.
+    pub interval: Duration,
+    /// Rows deleted per statement.
+    pub batch_size: u64,
+    /// Idle window for `GcSweep::MonitoredAddresses`.
+    pub monitored_idle_days: u64,
+    /// Retention window for `GcSweep::Changefeed`.
+    pub changefeed_retention_days: u64,
+    /// Retention depth, in blocks, for `GcSweep::UtxoSpends`.
+    pub utxo_spends_retention_blocks: u64,
+    /// Sweeps to run, in order.
+    pub sweeps: Vec<GcSweep>,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(6 * 3600),
+            batch_size: 1000,
+            monitored_idle_days: 180,
+            changefeed_retention_days: 30,
+            utxo_spends_retention_blocks: 4320,
+            sweeps: GcSweep::ALL.to_vec(),
+        }
+    }
+}
+
+pub struct GarbageCollector {
+    conn: DatabaseConnection,
+    summary: SummaryRepository,
+    cfg: GcConfig,
+}
+
+impl GarbageCollector {
+    pub fn new(conn: DatabaseConnection, summary: SummaryRepository, cfg: GcConfig) -> Self {
+        Self { conn, summary, cfg }
+    }
+
+    /// Run a pass every `interval` until cancelled. The first pass waits one
+    /// interval so restarts do not hammer the database.
+    pub async fn run(self, cancel: CancellationToken) {
+        logging::log_info(&format!(
+            "[gc] 🗑️ GarbageCollector started (every {}s, sweeps: {})",
+            self.cfg.interval.as_secs(),
+            self.cfg
+                .sweeps
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.cfg.interval) => {}
+                _ = cancel.cancelled() => {
+                    logging::log_info("[gc] 🛑 GarbageCollector stopping (cancellation requested)");
+                    return;
+                }
+            }
+            self.run_once().await;
+        }
+    }
+
+    /// Run every enabled sweep once and record the stats on `summary`.
+    /// A failing sweep is logged and left out of the stats; the others
+    /// still run. Returns rows removed (or demoted) per sweep.
+    pub async fn run_once(&self) -> Vec<(GcSweep, u64)> {
+        let mut removed = Vec::new();
+        for &sweep in &self.cfg.sweeps {
+            match self.sweep(sweep).await {
+                Ok(n) => {
+                    if n > 0 {
+                        logging::log_info(&format!(
+                            "[gc] 🗑️ {}: {} rows cleared",
+                            sweep.as_str(),
+                            n
+                        ));
+                    }
+                    removed.push((sweep, n));
+                }
+                Err(e) => logging::log_warning(&format!(
+                    "[gc] ⚠️ Sweep {} failed: {}",
+                    sweep.as_str(),
+                    e
+                )),
+            }
+        }
+
+        let stats: Map<String, Value> = removed
+            .iter()
+            .map(|(sweep, n)| (sweep.as_str().to_string(), Value::from(*n)))
+            .collect();
+        if let Err(e) = self.summary.record_gc(Value::Object(stats)).await {
+            logging::log_warning(&format!("[gc] ⚠️ Failed to record GC stats: {}", e));
+        }
+        removed
+    }
+
+    /// Run one sweep `batch_size` rows at a time until a batch comes back
+    /// short.
+    async fn sweep(&self, sweep: GcSweep) -> Result<u64, DbErr> {
+        let batch_size = self.cfg.batch_size.max(1);
+        let sql = sweep.statement(&self.cfg);
+
+        let mut removed = 0;
+        loop {
+            let res = self
+                .conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    &sql,
+                    [(batch_size as i64).into()],
+                ))
+                .await?;
+            removed += res.rows_affected();
+            if res.rows_affected() < batch_size {
+                return Ok(removed);
+            }
+        }
+    }
+}
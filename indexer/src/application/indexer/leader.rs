@@ -0,0 +1,303 @@
+//! Single-writer coordination between indexer replicas sharing a database.
+//!
+//! Each network has one Postgres advisory lock. `LeaderElection::run` keeps a
+//! dedicated session (outside the pool, so pool recycling never drops the
+//! lock) and retries `pg_try_advisory_lock` on it every poll. The replica
+//! holding the lock is the leader; Postgres releases it when that session
+//! ends, whether by `pg_advisory_unlock` on shutdown or by a crash or lost
+//! connection, and a standby picks it up on its next poll.
+//!
+//! A new holder waits `PROMOTION_POLLS` polls before it starts writing. A
+//! leader whose session died only notices on its next liveness check, and
+//! the delay lets it step down first, so two replicas never write at once.
+//!
+//! The block and mempool processors of a network follow its election through
+//! a `LeaderGate` and skip their work while standing by. Every poll also
+//! refreshes the replica's `indexer_replicas` row, which `/status` shows.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sea_orm::DatabaseConnection;
+use sqlx::{Connection, PgConnection, PgPool};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::NetworkId;
+use crate::infrastructure::persistence::repositories::IndexerReplicasRepository;
+use crate::utils::logging;
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Polls a new lock holder waits before acting as leader.
+pub const PROMOTION_POLLS: u32 = 3;
+/// Replicas whose heartbeat is older than this are not listed as live.
+pub const REPLICA_STALE_SECS: i64 = 30;
+/// First key of the two-key advisory lock, keeping ours apart from any other
+/// advisory lock user of the database. The second key is `hashtext(network)`.
+const LOCK_CLASS: i32 = 0x4348_524d;
+/// Standby replicas log who the leader is every this many polls.
+const STANDBY_LOG_EVERY: u32 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Standby,
+    Leader,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Standby => "standby",
+            Role::Leader => "leader",
+        }
+    }
+}
+
+/// Read side of an election, handed to the processors of its network.
+#[derive(Debug, Clone)]
+pub struct LeaderGate {
+    rx: watch::Receiver<Role>,
+}
+
+impl LeaderGate {
+    /// A gate that is always open, for processors run without an election.
+    pub fn always() -> Self {
+        let (_, rx) = watch::channel(Role::Leader);
+        Self { rx }
+    }
+
+    pub fn role(&self) -> Role {
+        *self.rx.borrow()
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.role() == Role::Leader
+    }
+}
+
+/// Publishes `Standby` when `run` exits, including by panic: the supervisor
+/// restarts it only after a backoff, and the gate must not stay open meanwhile.
+struct StepDownOnDrop(Arc<watch::Sender<Role>>);
+
+impl Drop for StepDownOnDrop {
+    fn drop(&mut self) {
+        self.0.send_replace(Role::Standby);
+    }
+}
+
+/// The lock session: connected but waiting, or holding the lock since `locked_since`.
+#[derive(Default)]
+struct Session {
+    conn: Option<PgConnection>,
+    locked_since: Option<Instant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LeaderElection {
+    pool: PgPool,
+    replicas: IndexerReplicasRepository,
+    network_id: NetworkId,
+    instance_id: String,
+    poll_interval: Duration,
+    role: Arc<watch::Sender<Role>>,
+}
+
+impl LeaderElection {
+    pub fn new(
+        conn: &DatabaseConnection,
+        replicas: IndexerReplicasRepository,
+        network_id: NetworkId,
+        instance_id: String,
+    ) -> Self {
+        let (role, _) = watch::channel(Role::Standby);
+        Self {
+            pool: conn.get_postgres_connection_pool().clone(),
+            replicas,
+            network_id,
+            instance_id,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            role: Arc::new(role),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn network_id(&self) -> &NetworkId {
+        &self.network_id
+    }
+
+    pub fn gate(&self) -> LeaderGate {
+        LeaderGate {
+            rx: self.role.subscribe(),
+        }
+    }
+
+    pub fn role(&self) -> Role {
+        *self.role.borrow()
+    }
+
+    /// Campaign until `cancel` fires, then step down and release the lock.
+    /// Cancel this only after the processors following the gate have stopped.
+    pub async fn run(&self, cancel: CancellationToken) {
+        let network = &self.network_id.name;
+        logging::log_info(&format!(
+            "[{}] 🗳️ Leader election started as {}",
+            network, self.instance_id
+        ));
+        self.set_role(Role::Standby);
+
+        let mut session = Session::default();
+        // Declared after `session`, so on a panic the gate closes before the
+        // session and its lock are dropped.
+        let _step_down = StepDownOnDrop(self.role.clone());
+        let mut polls: u32 = 0;
+        loop {
+            self.poll(&mut session).await;
+            let promote_after = self.poll_interval * PROMOTION_POLLS;
+            let role = match session.locked_since {
+                Some(since) if since.elapsed() >= promote_after => Role::Leader,
+                _ => Role::Standby,
+            };
+            self.set_role(role);
+
+            if let Err(e) = self
+                .replicas
+                .heartbeat(&self.instance_id, network, role.as_str())
+                .await
+            {
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ Failed to record replica heartbeat: {}",
+                    network, e
+                ));
+            }
+            polls = polls.wrapping_add(1);
+            if role == Role::Standby
+                && session.locked_since.is_none()
+                && polls.is_multiple_of(STANDBY_LOG_EVERY)
+            {
+                self.log_standby().await;
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.poll_interval) => {}
+                _ = cancel.cancelled() => break,
+            }
+        }
+
+        self.set_role(Role::Standby);
+        if let Some(mut conn) = session.conn.take() {
+            if session.locked_since.is_some() {
+                let _ = sqlx::query("SELECT pg_advisory_unlock($1, hashtext($2))")
+                    .bind(LOCK_CLASS)
+                    .bind(network)
+                    .execute(&mut conn)
+                    .await;
+            }
+            let _ = conn.close().await;
+        }
+        if let Err(e) = self.replicas.remove(&self.instance_id, network).await {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Failed to remove replica heartbeat: {}",
+                network, e
+            ));
+        }
+        logging::log_info(&format!("[{}] 🗳️ Leader election stopped", network));
+    }
+
+    /// One step: (re)connect, then check the lock we hold or try to take it.
+    /// Any session error drops the session, and with it the lock.
+    async fn poll(&self, session: &mut Session) {
+        let network = &self.network_id.name;
+        if session.conn.is_none() {
+            match PgConnection::connect_with(&self.pool.connect_options()).await {
+                Ok(conn) => session.conn = Some(conn),
+                Err(e) => {
+                    logging::log_warning(&format!(
+                        "[{}] ⚠️ Leader election cannot connect: {}",
+                        network, e
+                    ));
+                    return;
+                }
+            }
+        }
+        let Some(conn) = session.conn.as_mut() else {
+            return;
+        };
+
+        let result = if session.locked_since.is_some() {
+            // A hung query counts as a lost session: the standby only waits
+            // `PROMOTION_POLLS` polls before taking over.
+            match tokio::time::timeout(
+                self.poll_interval,
+                sqlx::query("SELECT 1").execute(&mut *conn),
+            )
+            .await
+            {
+                Ok(Ok(_)) => Ok(true),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("liveness check timed out".to_string()),
+            }
+        } else {
+            sqlx::query_scalar::<_, bool>("SELECT pg_try_advisory_lock($1, hashtext($2))")
+                .bind(LOCK_CLASS)
+                .bind(network)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())
+        };
+
+        match result {
+            Ok(true) => {
+                session.locked_since.get_or_insert_with(Instant::now);
+            }
+            Ok(false) => {}
+            Err(e) => {
+                if session.locked_since.is_some() {
+                    logging::log_error(&format!(
+                        "[{}] ❌ Lost the leader lock session: {}",
+                        network, e
+                    ));
+                } else {
+                    logging::log_warning(&format!(
+                        "[{}] ⚠️ Leader election query failed: {}",
+                        network, e
+                    ));
+                }
+                *session = Session::default();
+            }
+        }
+    }
+
+    fn set_role(&self, role: Role) {
+        let previous = self.role.send_replace(role);
+        if previous != role {
+            logging::log_info(&format!(
+                "[{}] {} {} is now {}",
+                self.network_id.name,
+                if role == Role::Leader { "👑" } else { "💤" },
+                self.instance_id,
+                role.as_str()
+            ));
+        }
+    }
+
+    async fn log_standby(&self) {
+        let leader = self
+            .replicas
+            .live(&self.network_id.name, REPLICA_STALE_SECS)
+            .await
+            .ok()
+            .and_then(|replicas| replicas.into_iter().find(|r| r.role == "leader"));
+        logging::log_info(&format!(
+            "[{}] 💤 {} standing by (leader: {})",
+            self.network_id.name,
+            self.instance_id,
+            leader
+                .map(|r| r.instance_id)
+                .unwrap_or_else(|| "none seen".to_string())
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+//! Locked token supply.
+//!
+//! Part of a token's supply cannot circulate: it rests in DEX order UTXOs
+//! (the order's `scrolls_address`) or at known burn and vault addresses.
+//! `assets.locked_supply` is the unspent, confirmed amount of the app held
+//! at such an address, and the API reports `total_supply - locked_supply` as
+//! circulating supply.
+//!
+//! Lock addresses are the scrolls addresses of open or partially filled
+//! orders on the asset's network, plus the patterns configured in
+//! `LOCKED_SUPPLY_ADDRESSES` (comma-separated; a trailing `*` matches any
+//! suffix). The block processor refreshes the app_ids a block mints or
+//! spends; a periodic pass every `GC_INTERVAL_SECS` refreshes every token,
+//! catching orders that expire or get cancelled without moving a charm.
+
+use std::time::Duration;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement};
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::services::address_extractor::AddressExtractor;
+use crate::utils::logging;
+
+/// Recompute `locked_supply` for the token rows selected by `{scope}`.
+/// `$1` is a JSON array of LIKE patterns. Only changed rows are written.
+const REFRESH_SQL: &str = "
+    WITH target AS (
+        SELECT id, network, app_id
+          FROM assets
+         WHERE app_id LIKE 't/%' {scope}
+    ),
+    computed AS (
+        SELECT t.id, COALESCE(SUM(c.amount), 0) AS locked
+          FROM target t
+          LEFT JOIN charms c
+            ON c.network = t.network
+           AND c.app_id = t.app_id
+           AND NOT c.spent
+           AND c.block_height IS NOT NULL
+           AND (c.address IN (SELECT d.scrolls_address
+                                FROM dex_orders d
+                               WHERE d.network = t.network
+                                 AND d.status IN ('open', 'partial'))
+                OR EXISTS (SELECT 1 FROM jsonb_array_elements_text($1::jsonb) p
+                            WHERE c.address LIKE p.value))
+      GROUP BY t.id
+    )
+    UPDATE assets a
+       SET locked_supply = computed.locked
+      FROM computed
+     WHERE a.id = computed.id
+       AND a.locked_supply <> computed.locked";
+
+/// App_ids with a charm created or spent at height `$3` on network `$2`.
+const BLOCK_SCOPE: &str = "AND network = $2 \
+    AND app_id IN (SELECT app_id FROM charms \
+                    WHERE network = $2 AND (block_height = $3 OR spent_height = $3))";
+
+/// LIKE patterns for the configured lock addresses. Bech32 entries are
+/// lowercased like stored addresses; LIKE metacharacters are escaped and a
+/// trailing `*` becomes `%`.
+fn lock_patterns(addresses: &[String]) -> Vec<String> {
+    addresses
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (address, wildcard) = match entry.strip_suffix('*') {
+                Some(prefix) => (prefix, "%"),
+                None => (entry, ""),
+            };
+            let escaped = AddressExtractor::canonical(address)
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("{escaped}{wildcard}")
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct LockedSupply {
+    conn: DatabaseConnection,
+    /// LIKE patterns from `lock_patterns`
+    patterns: Vec<String>,
+}
+
+impl LockedSupply {
+    /// Tracker for `LOCKED_SUPPLY_ADDRESSES` entries `addresses`.
+    pub fn new(conn: DatabaseConnection, addresses: &[String]) -> Self {
+        Self {
+            conn,
+            patterns: lock_patterns(addresses),
+        }
+    }
+
+    /// Refresh every token. Returns the asset rows whose locked supply
+    /// changed.
+    pub async fn refresh_all(&self) -> Result<u64, DbErr> {
+        self.refresh("", vec![json!(self.patterns).into()]).await
+    }
+
+    /// Refresh the tokens with a charm created or spent in block `height`.
+    pub async fn refresh_block(&self, network: &str, height: u64) -> Result<u64, DbErr> {
+        self.refresh(
+            BLOCK_SCOPE,
+            vec![
+                json!(self.patterns).into(),
+                network.into(),
+                (height as i32).into(),
+            ],
+        )
+        .await
+    }
+
+    async fn refresh(&self, scope: &str, values: Vec<sea_orm::Value>) -> Result<u64, DbErr> {
+        let res = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                REFRESH_SQL.replace("{scope}", scope),
+                values,
+            ))
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Run `refresh_all` every `interval` until cancelled, starting with a
+    /// pass right away so a fresh column is filled on first start.
+    pub async fn run(self, interval: Duration, cancel: CancellationToken) {
+        logging::log_info(&format!(
+            "[locked-supply] 🔒 LockedSupply started (every {}s, {} configured pattern(s))",
+            interval.as_secs(),
+            self.patterns.len()
+        ));
+        loop {
+            match self.refresh_all().await {
+                Ok(0) => {}
+                Ok(n) => logging::log_info(&format!(
+                    "[locked-supply] 🔒 Locked supply changed for {} asset(s)",
+                    n
+                )),
+                Err(e) => logging::log_warning(&format!("[locked-supply] ⚠️ Pass failed: {}", e)),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = cancel.cancelled() => {
+                    logging::log_info("[locked-supply] 🛑 LockedSupply stopping (cancellation requested)");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_escape_like_and_expand_trailing_stars() {
+        let patterns = lock_patterns(&[
+            " BC1QBURN* ".to_string(),
+            "1Burn_Address".to_string(),
+            String::new(),
+        ]);
+        assert_eq!(patterns, ["bc1qburn%", "1Burn\\_Address"]);
+    }
+}
@@ -8,7 +8,7 @@ use crate::infrastructure::persistence::repositories::MempoolSpendsRepository;
 use crate::utils::logging;
 
 /// How many hours before a mempool entry is considered stale
-const STALE_HOURS: i64 = 24;
+pub(crate) const STALE_HOURS: i64 = 24;
 
 /// Purge stale mempool entries and trim the seen_txids cache.
 pub async fn purge_stale(
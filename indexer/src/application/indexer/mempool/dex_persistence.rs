@@ -3,12 +3,12 @@
 //! up the consumed order's side.
 
 use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 
 use crate::domain::services::dex::{self, extract_ins0_order_id, ExecType, OrderSide};
 use crate::domain::services::tx_analyzer;
 use crate::infrastructure::persistence::entities::dex_orders;
-use crate::infrastructure::persistence::error::is_duplicate_key;
 use crate::utils::logging;
 
 /// Save a DEX order detected in a mempool transaction.
@@ -71,13 +71,16 @@ pub async fn save_dex_order(
         scrolls_address: Set(order.scrolls_address.clone()),
         status: Set(status.to_string()),
         parent_order_id: Set(parent_order_id),
+        expires_at_height: Set(order.expires_at_height.map(|h| h as i32)),
+        expires_at_time: Set(order.expires_at_datetime()),
         created_at: Set(now_dt),
         updated_at: Set(now_dt),
         blockchain: Set(blockchain.to_string()),
         network: Set(network.to_string()),
     };
 
-    match order_model.insert(db).await {
+    match insert_order_ignoring_existing(order_model, db).await {
+        Ok(0) => {}
         Ok(_) => {
             logging::log_info(&format!(
                 "[{}] 💾 Mempool DEX order saved: {} ({:?})",
@@ -88,7 +91,6 @@ pub async fn save_dex_order(
                 dex_operation_label(&dex_result.operation),
             );
         }
-        Err(e) if is_duplicate_key(&e) => {}
         Err(e) => {
             logging::log_warning(&format!(
                 "[{}] ⚠️ Failed to save mempool DEX order {}: {}",
@@ -145,12 +147,7 @@ pub async fn correct_fulfill_classification(
     };
 
     if order.side == "bid" {
-        if let Some(ref mut result) = analyzed.dex_result {
-            result.operation = dex::DexOperation::FulfillBid;
-        }
-        if let Some(ref mut tags) = analyzed.tags {
-            *tags = tags.replace("fulfill-ask", "fulfill-bid");
-        }
+        analyzed.reclassify_as_fulfill_bid();
         logging::log_info(&format!(
             "[{}] 🔄 FULFILL-BID (3-out) corrected for tx {} (consumed order {})",
             network, txid, order_id
@@ -237,20 +234,22 @@ pub async fn update_consumed_order_status(
         scrolls_address: Set(order.scrolls_address.clone()),
         status: Set(new_status.to_string()),
         parent_order_id: Set(Some(order.order_id.clone())),
+        expires_at_height: Set(None),
+        expires_at_time: Set(None),
         created_at: Set(now),
         updated_at: Set(now),
         blockchain: Set(blockchain.to_string()),
         network: Set(network.to_string()),
     };
 
-    match activity_model.insert(db).await {
+    match insert_order_ignoring_existing(activity_model, db).await {
+        Ok(0) => return, // raced, don't touch parent
         Ok(_) => {
             logging::log_info(&format!(
                 "[{}] 💾 Mempool activity row saved: {} ({}) parent={}",
                 network, txid, new_status, order_id
             ));
         }
-        Err(e) if is_duplicate_key(&e) => return, // raced, don't touch parent
         Err(e) => {
             logging::log_warning(&format!(
                 "[{}] ⚠️ Failed to save activity row for {}: {}",
@@ -300,3 +299,19 @@ pub async fn update_consumed_order_status(
         }
     }
 }
+
+/// Insert a dex_orders row unless its `order_id` already exists. Returns the
+/// number of rows written, so 0 means another writer got there first.
+async fn insert_order_ignoring_existing(
+    model: dex_orders::ActiveModel,
+    db: &DatabaseConnection,
+) -> Result<u64, sea_orm::DbErr> {
+    dex_orders::Entity::insert(model)
+        .on_conflict(
+            OnConflict::column(dex_orders::Column::OrderId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+}
@@ -4,9 +4,9 @@
 //! - `processor`: core detection + persistence for individual mempool txs
 //! - `cleanup`: stale entry purging
 
-mod cleanup;
+pub(crate) mod cleanup;
 mod dex_persistence;
-mod processor;
+pub mod processor;
 mod reconcile;
 mod spend_extraction;
 pub mod utxo_tracker;
@@ -17,6 +17,7 @@ use std::time::Duration;
 use sea_orm::DatabaseConnection;
 use tokio::sync::Mutex;
 
+use crate::application::indexer::leader::LeaderGate;
 use crate::config::NetworkId;
 use crate::infrastructure::bitcoin::client::BitcoinClient;
 use crate::infrastructure::persistence::repositories::{
@@ -52,6 +53,7 @@ pub struct MempoolProcessor {
     /// after disappearing from `getrawmempool` for several reconcile cycles
     /// in a row, so transient snapshot blips don't flicker the explorer UI.
     reconcile_miss_counts: std::sync::Arc<Mutex<std::collections::HashMap<String, u32>>>,
+    leader: LeaderGate,
 }
 
 impl MempoolProcessor {
@@ -75,9 +77,16 @@ impl MempoolProcessor {
             reconcile_miss_counts: std::sync::Arc::new(Mutex::new(
                 std::collections::HashMap::new(),
             )),
+            leader: LeaderGate::always(),
         }
     }
 
+    /// Follow the network's block processor: poll only while leading.
+    pub fn with_leader_gate(mut self, leader: LeaderGate) -> Self {
+        self.leader = leader;
+        self
+    }
+
     /// Main loop — polls mempool every POLL_INTERVAL_SECS until `cancel` fires.
     pub async fn run(&self, cancel: tokio_util::sync::CancellationToken) {
         use tracing::Instrument;
@@ -99,6 +108,13 @@ impl MempoolProcessor {
             if cancel.is_cancelled() {
                 break;
             }
+            if !self.leader.is_leader() {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)) => {}
+                    _ = cancel.cancelled() => break,
+                }
+                continue;
+            }
             cycle += 1;
 
             if cycle.is_multiple_of(MONITORED_SET_RELOAD_INTERVAL) {
@@ -208,8 +224,8 @@ impl MempoolProcessor {
             };
 
             // Track UTXO changes for monitored addresses
-            if !monitored_snapshot.is_empty() {
-                utxo_tracker::track_mempool_utxos(
+            let tracked_utxos = !monitored_snapshot.is_empty()
+                && utxo_tracker::track_mempool_utxos(
                     txid,
                     &raw_hex,
                     &self.network_id.name,
@@ -218,7 +234,6 @@ impl MempoolProcessor {
                     &self.mempool_spends_repository,
                 )
                 .await;
-            }
 
             // Detect charms (pass raw_hex to avoid re-fetching)
             match processor::process_tx_with_hex(
@@ -244,6 +259,16 @@ impl MempoolProcessor {
                     ));
                 }
             }
+
+            // Flag the tracked UTXOs now that the tx's charms are saved
+            if tracked_utxos {
+                utxo_tracker::annotate_mempool_utxos(
+                    txid,
+                    &self.network_id.name,
+                    &self.utxo_repository,
+                )
+                .await;
+            }
         }
 
         if charm_count > 0 {
@@ -4,19 +4,24 @@
 //! and the consumed-UTXO extraction lives in `spend_extraction`.
 
 use chrono::{DateTime, FixedOffset, Utc};
-use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{DatabaseConnection, EntityTrait, Set};
 
 use super::dex_persistence::{
     correct_fulfill_classification, save_dex_order, update_consumed_order_status,
 };
 use super::spend_extraction::extract_spends;
 use crate::config::NetworkId;
+use crate::domain::models::{TransactionStatus, WriteStamp};
 use crate::domain::services::tx_analyzer;
+use crate::domain::services::{AddressExtractor, AssetInfo, NativeCharmParser, SpellFailure};
 use crate::infrastructure::bitcoin::client::BitcoinClient;
 use crate::infrastructure::persistence::entities::{charms, transactions};
-use crate::infrastructure::persistence::error::is_duplicate_key;
-use crate::infrastructure::persistence::repositories::MempoolSpendsRepository;
+use crate::infrastructure::persistence::repositories::{
+    MempoolSpendsRepository, PendingSpellsRepository,
+};
 use crate::utils::logging;
+use crate::utils::parser_stats::{self, ParseSource};
 
 /// Result of processing a single mempool tx
 pub struct MempoolDetectionResult {
@@ -54,20 +59,44 @@ pub async fn process_tx_with_hex(
     let txid_owned = txid.to_string();
     let raw_hex_clone = raw_hex.to_string();
     let network = network_id.name.clone();
-    let analyzed = tokio::task::spawn_blocking(move || {
-        tx_analyzer::analyze_tx(
+    let (analyzed, failure) = tokio::task::spawn_blocking(move || {
+        let analyzed = tx_analyzer::analyze_tx(
             &txid_owned,
             &raw_hex_clone,
             &network,
             tx_analyzer::VerifyMode::Permissive,
-        )
+        );
+        let failure = match analyzed {
+            Some(_) => None,
+            None => Some(NativeCharmParser::classify_failure(&raw_hex_clone)),
+        };
+        (analyzed, failure)
     })
     .await
     .map_err(|e| format!("spawn_blocking join error: {}", e))?;
+    parser_stats::global().record(
+        &network_id.name,
+        ParseSource::Mempool,
+        failure.map_or(Ok(()), Err),
+    );
 
     let analyzed = match analyzed {
         Some(a) => a,
-        None => return Ok(None),
+        None => {
+            // A spell too new for the parser: keep it for reprocessing after
+            // an upgrade rather than treating the tx as non-charm.
+            if let Some(SpellFailure::VersionUnsupported(version)) = failure {
+                PendingSpellsRepository::new(db.clone())
+                    .record(txid, &network_id.name, raw_hex, version, None)
+                    .await
+                    .map_err(|e| format!("pending_spells insert failed: {}", e))?;
+                logging::log_warning(&format!(
+                    "[{}] ⏳ Mempool: tx {} carries a V{} spell, not supported yet; kept in pending_spells",
+                    network_id.name, txid, version
+                ));
+            }
+            return Ok(None);
+        }
     };
 
     let network = network_id.name.clone();
@@ -78,6 +107,7 @@ pub async fn process_tx_with_hex(
     let analyzed = correct_fulfill_classification(txid, raw_hex, analyzed, &network, db).await;
     let blockchain = "Bitcoin".to_string();
     let now = Utc::now().naive_utc();
+    let stamp = WriteStamp::live();
     let now_tz: DateTime<FixedOffset> = Utc::now().fixed_offset();
     let has_dex_order = analyzed
         .dex_result
@@ -92,45 +122,28 @@ pub async fn process_tx_with_hex(
     }
 
     // Extract per-vout addresses (preserving index alignment, OP_RETURN outputs map to None)
-    let vout_addresses: Vec<Option<String>> = {
-        use bitcoincore_rpc::bitcoin::{consensus::deserialize, Address, Network, Transaction};
-        let btc_network = match network.as_str() {
-            "mainnet" => Network::Bitcoin,
-            "testnet4" | "testnet" => Network::Testnet,
-            "regtest" => Network::Regtest,
-            _ => Network::Testnet,
-        };
-        hex::decode(raw_hex)
-            .ok()
-            .and_then(|bytes| deserialize::<Transaction>(&bytes).ok())
-            .map(|tx| {
-                tx.output
-                    .iter()
-                    .map(|out| {
-                        Address::from_script(&out.script_pubkey, btc_network)
-                            .ok()
-                            .map(|a| a.to_string())
-                    })
-                    .collect()
-            })
-            .unwrap_or_default()
-    };
+    let outputs = AddressExtractor::output_addresses(raw_hex, &network).unwrap_or_default();
+
+    let charm_data = analyzed.charm_row_data();
 
     // Save one charm entry per charm-bearing output with block_height=NULL (mempool)
     // stats_holders is NOT updated here — it only tracks confirmed balances.
     // Unconfirmed balance is computed at query time from charms WHERE block_height IS NULL.
     for asset in &analyzed.asset_infos {
-        let address = vout_addresses
-            .get(asset.vout_index as usize)
-            .and_then(|a| a.clone());
+        let address = AddressExtractor::charm_output_address(
+            &outputs,
+            asset.vout_index as usize,
+            txid,
+            &network,
+        );
         let is_beamed_out = analyzed.beamed_out_indices.contains(&(asset.vout_index as usize));
         let charm_model = charms::ActiveModel {
             txid: Set(txid.to_string()),
             vout: Set(asset.vout_index),
             block_height: Set(None),
-            data: Set(analyzed.charm_json.clone()),
+            data: Set(charm_data.clone()),
             date_created: Set(now),
-            asset_type: Set(asset.asset_type.clone()),
+            asset_type: Set(asset.asset_type.to_string()),
             blockchain: Set(blockchain.clone()),
             network: Set(network.clone()),
             address: Set(address),
@@ -140,18 +153,39 @@ pub async fn process_tx_with_hex(
             mempool_detected_at: Set(Some(now_tz)),
             tags: Set(analyzed.tags.clone()),
             verified: Set(true),
+            block_hash: Set(None),
+            tx_ordinal: Set(None),
+            is_placeholder: Set(charms_core::is_empty_spell_charm(&analyzed.charm_json)),
+            spending_txid: Set(None),
+            indexer_version: Set(Some(stamp.indexer_version.to_string())),
+            parser_revision: Set(Some(stamp.parser_revision)),
+            reindex_run_id: Set(None),
+            // Classified when the block write promotes the row
+            operation: Set(None),
+            // Set by the mempool consolidator once a block confirms it
+            block_time: Set(None),
         };
-        match charm_model.insert(db).await {
-            Ok(_) => {
-                logging::log_info(&format!(
-                    "[{}] 💾 Mempool charm saved: {} vout={} ({})",
-                    network, txid, asset.vout_index, asset.asset_type
-                ));
-            }
-            Err(e) if is_duplicate_key(&e) => {}
-            Err(e) => {
-                return Err(format!("Failed to save mempool charm: {}", e));
-            }
+        let inserted = charms::Entity::insert(charm_model)
+            .on_conflict(
+                OnConflict::columns([
+                    charms::Column::Txid,
+                    charms::Column::Vout,
+                    charms::Column::AppId,
+                    charms::Column::Network,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await
+            .map_err(|e| format!("Failed to save mempool charm: {}", e))?;
+        if inserted > 0 {
+            logging::log_info(&format!(
+                "[{}] 💾 Mempool charm saved: {} vout={} ({})",
+                network, txid, asset.vout_index, asset.asset_type
+            ));
+        } else {
+            warn_on_conflicting_charm(db, txid, asset, &charm_data, &network).await;
         }
     }
 
@@ -164,17 +198,26 @@ pub async fn process_tx_with_hex(
         raw: Set(raw_json),
         charm: Set(analyzed.charm_json.clone()),
         updated_at: Set(now),
-        status: Set("pending".to_string()),
+        status: Set(TransactionStatus::Pending.to_string()),
         confirmations: Set(0i32),
         blockchain: Set(blockchain.clone()),
         network: Set(network.clone()),
         mempool_detected_at: Set(Some(now_tz)),
         tags: Set(analyzed.tags.clone()),
         tx_type: Set(Some(analyzed.tx_type.clone())),
+        spell_outputs: Set(charms_core::spell_outputs(&analyzed.charm_json)),
     };
-    match tx_model.insert(db).await {
+    match transactions::Entity::insert(tx_model)
+        .on_conflict(
+            OnConflict::column(transactions::Column::Txid)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+    {
+        Ok(0) => warn_on_conflicting_tx(db, txid, &analyzed.charm_json, &network).await,
         Ok(_) => {}
-        Err(e) if is_duplicate_key(&e) => {}
         Err(e) => {
             logging::log_warning(&format!(
                 "[{}] ⚠️ Failed to save mempool transaction {}: {}",
@@ -208,3 +251,52 @@ pub async fn process_tx_with_hex(
 
     Ok(Some(MempoolDetectionResult { has_dex_order }))
 }
+
+/// A re-seen mempool charm is the normal case and stays silent; only a row
+/// whose stored spell data differs from what was just decoded is reported,
+/// since that means two different payloads claimed the same key.
+async fn warn_on_conflicting_charm(
+    db: &DatabaseConnection,
+    txid: &str,
+    asset: &AssetInfo,
+    charm_json: &serde_json::Value,
+    network: &str,
+) {
+    let existing = charms::Entity::find_by_id((
+        txid.to_string(),
+        asset.vout_index,
+        asset.app_id.clone(),
+        network.to_string(),
+    ))
+    .one(db)
+    .await;
+    if let Ok(Some(existing)) = existing {
+        if &existing.data != charm_json {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Conflicting mempool charm {} vout={} app={}: stored data differs, keeping existing row",
+                network, txid, asset.vout_index, asset.app_id
+            ));
+        }
+    }
+}
+
+/// Same as `warn_on_conflicting_charm` for the `transactions` row, whose key
+/// is the bare txid — a hit from another network is reported as well.
+async fn warn_on_conflicting_tx(
+    db: &DatabaseConnection,
+    txid: &str,
+    charm_json: &serde_json::Value,
+    network: &str,
+) {
+    let existing = transactions::Entity::find_by_id(txid.to_string())
+        .one(db)
+        .await;
+    if let Ok(Some(existing)) = existing {
+        if existing.network != network || &existing.charm != charm_json {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Conflicting mempool transaction {}: stored row ({}) differs, keeping existing row",
+                network, txid, existing.network
+            ));
+        }
+    }
+}
@@ -7,6 +7,8 @@ use std::collections::HashSet;
 
 use bitcoincore_rpc::bitcoin::{self, consensus::deserialize};
 
+use crate::application::indexer::block::utxo_indexer::looks_like_spell;
+use crate::domain::services::AddressExtractor;
 use crate::infrastructure::persistence::repositories::utxo_repository::UtxoInsert;
 use crate::infrastructure::persistence::repositories::{
     MempoolSpendsRepository, MonitoredAddressesRepository, UtxoRepository,
@@ -16,6 +18,9 @@ use crate::utils::logging;
 /// Track UTXO changes from a raw mempool transaction for monitored addresses.
 /// - Inputs spending monitored UTXOs → record in mempool_spends
 /// - Outputs to monitored addresses → insert in address_utxos with block_height=0 (unconfirmed)
+///
+/// Returns whether UTXOs were stored; the caller annotates them with
+/// `annotate_mempool_utxos` once the tx's charms are saved.
 pub async fn track_mempool_utxos(
     txid: &str,
     raw_hex: &str,
@@ -23,26 +28,22 @@ pub async fn track_mempool_utxos(
     monitored_set: &HashSet<String>,
     utxo_repository: &UtxoRepository,
     mempool_spends_repository: &MempoolSpendsRepository,
-) {
+) -> bool {
     if monitored_set.is_empty() {
-        return;
+        return false;
     }
 
     let tx_bytes = match hex::decode(raw_hex) {
         Ok(b) => b,
-        Err(_) => return,
+        Err(_) => return false,
     };
 
     let tx: bitcoin::Transaction = match deserialize(&tx_bytes) {
         Ok(t) => t,
-        Err(_) => return,
+        Err(_) => return false,
     };
 
-    let btc_network = match network {
-        "mainnet" => bitcoin::Network::Bitcoin,
-        "testnet4" => bitcoin::Network::Testnet,
-        _ => bitcoin::Network::Testnet,
-    };
+    let btc_network = AddressExtractor::network_for(network);
 
     // 1. Record mempool spends for inputs that consume monitored UTXOs
     let spends: Vec<(String, String, i32)> = tx
@@ -72,6 +73,7 @@ pub async fn track_mempool_utxos(
     }
 
     // 2. Insert new UTXOs for outputs going to monitored addresses
+    let possible_charm = looks_like_spell(&tx);
     let mut new_utxos: Vec<UtxoInsert> = Vec::new();
     for (vout, output) in tx.output.iter().enumerate() {
         if output.script_pubkey.is_provably_unspendable() {
@@ -89,25 +91,42 @@ pub async fn track_mempool_utxos(
                     block_height: 0, // 0 = unconfirmed/mempool
                     network: network.to_string(),
                     source: "node".to_string(),
+                    possible_charm,
                 });
             }
         }
     }
 
-    if !new_utxos.is_empty() {
-        if let Err(e) = utxo_repository.insert_batch(&new_utxos).await {
-            logging::log_debug(&format!(
-                "[{}] Mempool UTXO tracker: failed to insert UTXOs for {}: {}",
-                network, txid, e
-            ));
-        } else {
-            logging::log_info(&format!(
-                "[{}] 💰 Mempool: {} new UTXOs for monitored addresses from tx {}",
-                network,
-                new_utxos.len(),
-                txid
-            ));
-        }
+    if new_utxos.is_empty() {
+        return false;
+    }
+    if let Err(e) = utxo_repository.insert_batch(&new_utxos).await {
+        logging::log_debug(&format!(
+            "[{}] Mempool UTXO tracker: failed to insert UTXOs for {}: {}",
+            network, txid, e
+        ));
+        return false;
+    }
+    logging::log_info(&format!(
+        "[{}] 💰 Mempool: {} new UTXOs for monitored addresses from tx {}",
+        network,
+        new_utxos.len(),
+        txid
+    ));
+    true
+}
+
+/// Flag the UTXOs `track_mempool_utxos` stored for `txid` with the charms
+/// the mempool processor saved for it (none for a plain payment).
+pub async fn annotate_mempool_utxos(txid: &str, network: &str, utxo_repository: &UtxoRepository) {
+    if let Err(e) = utxo_repository
+        .annotate_charms(&[txid.to_string()], network)
+        .await
+    {
+        logging::log_debug(&format!(
+            "[{}] Mempool UTXO tracker: failed to annotate UTXOs of {}: {}",
+            network, txid, e
+        ));
     }
 }
 
@@ -0,0 +1,274 @@
+//! Bulk asset metadata refresh.
+//!
+//! Asset rows keep the name, symbol, image and collection derived when they
+//! were created. After the extraction improves, an operator queues a job
+//! through `POST /admin/assets/refresh-metadata`; this worker claims it and
+//! re-derives every targeted asset from what is already stored, without
+//! touching the node:
+//!
+//! - NFTs through `AssetMetadata::from_nft_data` on `assets.data`, with the
+//!   deployer grouping for the collection, as at creation;
+//! - tokens, after all NFTs, from their parent NFT's refreshed fields.
+//!
+//! A field the extraction finds nothing for keeps its stored value. Each
+//! asset whose fields change is updated and gets a
+//! `metadata_refresh_results` row listing the old and new values.
+//!
+//! Only one job is pending or running at a time (a unique index enforces
+//! it). A job whose worker stops heartbeating is re-claimed and re-run
+//! from the start; re-deriving is idempotent.
+
+use std::time::Duration;
+
+use serde_json::{json, Map, Value};
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::models::AssetMetadata;
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::{
+    AssetFields, MetadataRefreshRepository, RefreshSource, RefreshTarget,
+};
+use crate::utils::logging;
+
+#[derive(Debug, Clone)]
+pub struct MetadataRefreshConfig {
+    /// Pause between polls for a job.
+    pub poll_interval: Duration,
+    /// Assets loaded per page.
+    pub batch_size: u64,
+    /// A running job not heartbeating for this long is re-claimed.
+    pub stale_after: Duration,
+}
+
+impl Default for MetadataRefreshConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(10),
+            batch_size: 500,
+            stale_after: Duration::from_secs(600),
+        }
+    }
+}
+
+/// The fields `target` should hold under the current extraction.
+pub fn derive(target: &RefreshTarget) -> AssetFields {
+    let current = &target.current;
+    match &target.source {
+        RefreshSource::Nft { data, deployer } => {
+            let metadata = AssetMetadata::from_nft_data(data);
+            let collection = metadata.collection_or_deployer(deployer.as_deref());
+            AssetFields {
+                name: metadata.name.or_else(|| current.name.clone()),
+                symbol: metadata.symbol.or_else(|| current.symbol.clone()),
+                description: metadata.description.or_else(|| current.description.clone()),
+                image_url: metadata.image_url.or_else(|| current.image_url.clone()),
+                decimals: metadata.decimals as i16,
+                collection: collection.or_else(|| current.collection.clone()),
+            }
+        }
+        // Tokens inherit text and precision; the image stays on the NFT.
+        RefreshSource::Token { parent } => AssetFields {
+            name: parent.name.clone().or_else(|| current.name.clone()),
+            symbol: parent.symbol.clone().or_else(|| current.symbol.clone()),
+            description: parent
+                .description
+                .clone()
+                .or_else(|| current.description.clone()),
+            image_url: current.image_url.clone(),
+            decimals: parent.decimals,
+            collection: current.collection.clone(),
+        },
+    }
+}
+
+/// `{"field": {"old": .., "new": ..}}` for every field that differs, or
+/// `None` when nothing does.
+pub fn changes(old: &AssetFields, new: &AssetFields) -> Option<Value> {
+    let mut out = Map::new();
+    let mut diff = |field: &str, old: Value, new: Value| {
+        if old != new {
+            out.insert(field.to_string(), json!({"old": old, "new": new}));
+        }
+    };
+    diff("name", json!(old.name), json!(new.name));
+    diff("symbol", json!(old.symbol), json!(new.symbol));
+    diff("description", json!(old.description), json!(new.description));
+    diff("image_url", json!(old.image_url), json!(new.image_url));
+    diff("decimals", json!(old.decimals), json!(new.decimals));
+    diff("collection", json!(old.collection), json!(new.collection));
+    (!out.is_empty()).then_some(Value::Object(out))
+}
+
+pub struct MetadataRefreshWorker {
+    repo: MetadataRefreshRepository,
+    cfg: MetadataRefreshConfig,
+}
+
+impl MetadataRefreshWorker {
+    pub fn new(repo: MetadataRefreshRepository, cfg: MetadataRefreshConfig) -> Self {
+        Self { repo, cfg }
+    }
+
+    /// Poll for jobs until cancelled.
+    pub async fn run(self, cancel: CancellationToken) {
+        logging::log_info("[metadata-refresh] 🔁 MetadataRefreshWorker started");
+        loop {
+            if self.run_once().await.is_none() {
+                tokio::select! {
+                    _ = tokio::time::sleep(self.cfg.poll_interval) => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+            if cancel.is_cancelled() {
+                logging::log_info(
+                    "[metadata-refresh] 🛑 MetadataRefreshWorker stopping (cancellation requested)",
+                );
+                return;
+            }
+        }
+    }
+
+    /// Claim and run one job. Returns its id, `None` when none was queued.
+    pub async fn run_once(&self) -> Option<i64> {
+        let job_id = match self
+            .repo
+            .claim_next(self.cfg.stale_after.as_secs_f64())
+            .await
+        {
+            Ok(job) => job?,
+            Err(e) => {
+                logging::log_warning(&format!(
+                    "[metadata-refresh] ⚠️ Failed to claim a job: {}",
+                    e
+                ));
+                return None;
+            }
+        };
+        logging::log_info(&format!("[metadata-refresh] 🔁 Running job {}", job_id));
+
+        let outcome = match self.refresh(job_id).await {
+            Ok(scanned) => {
+                logging::log_info(&format!(
+                    "[metadata-refresh] ✅ Job {} done ({} assets scanned)",
+                    job_id, scanned
+                ));
+                self.repo.finish(job_id, scanned).await
+            }
+            Err(e) => {
+                logging::log_warning(&format!(
+                    "[metadata-refresh] ⚠️ Job {} failed: {}",
+                    job_id, e
+                ));
+                self.repo.fail(job_id, &e.to_string()).await
+            }
+        };
+        if let Err(e) = outcome {
+            logging::log_warning(&format!(
+                "[metadata-refresh] ⚠️ Failed to close job {}: {}",
+                job_id, e
+            ));
+        }
+        Some(job_id)
+    }
+
+    /// Re-derive all NFTs, then all tokens, in scope of the job. Returns the
+    /// number of assets scanned.
+    async fn refresh(&self, job_id: i64) -> Result<i32, DbError> {
+        let mut scanned = 0;
+        for tokens in [false, true] {
+            let mut after = 0;
+            loop {
+                let page = if tokens {
+                    self.repo.token_targets(job_id, after, self.cfg.batch_size).await?
+                } else {
+                    self.repo.nft_targets(job_id, after, self.cfg.batch_size).await?
+                };
+                let Some(last) = page.last() else { break };
+                after = last.asset_id;
+                for target in &page {
+                    let new = derive(target);
+                    if let Some(changes) = changes(&target.current, &new) {
+                        self.repo.apply(job_id, target, &new, &changes).await?;
+                    }
+                }
+                scanned += page.len() as i32;
+                self.repo.heartbeat(job_id, scanned).await?;
+            }
+        }
+        Ok(scanned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(name: &str, symbol: &str) -> AssetFields {
+        AssetFields {
+            name: Some(name.to_string()),
+            symbol: Some(symbol.to_string()),
+            decimals: 8,
+            ..AssetFields::default()
+        }
+    }
+
+    fn target(current: AssetFields, source: RefreshSource) -> RefreshTarget {
+        RefreshTarget {
+            asset_id: 1,
+            app_id: "n/aa/01".to_string(),
+            network: "mainnet".to_string(),
+            current,
+            source,
+        }
+    }
+
+    #[test]
+    fn nft_fields_come_from_its_data_and_keep_what_it_lacks() {
+        let mut current = fields("Old", "OLD");
+        current.description = Some("kept".to_string());
+        let nft = target(
+            current,
+            RefreshSource::Nft {
+                data: json!({"data": {"symbol": "NEW", "decimals": 6}}),
+                deployer: Some("bc1qdeployer".to_string()),
+            },
+        );
+
+        let new = derive(&nft);
+        assert_eq!(new.name.as_deref(), Some("Old"));
+        assert_eq!(new.symbol.as_deref(), Some("NEW"));
+        assert_eq!(new.description.as_deref(), Some("kept"));
+        assert_eq!(new.decimals, 6);
+        assert_eq!(new.collection.as_deref(), Some("deployer:bc1qdeployer"));
+        assert_eq!(
+            changes(&nft.current, &new),
+            Some(json!({
+                "symbol": {"old": "OLD", "new": "NEW"},
+                "decimals": {"old": 8, "new": 6},
+                "collection": {"old": null, "new": "deployer:bc1qdeployer"},
+            }))
+        );
+    }
+
+    #[test]
+    fn tokens_follow_their_parent_but_keep_their_image() {
+        let mut current = fields("Old", "OLD");
+        current.image_url = Some("https://x/t.png".to_string());
+        let parent = AssetFields {
+            image_url: Some("https://x/n.png".to_string()),
+            ..fields("Coin", "NEW")
+        };
+        let token = target(current, RefreshSource::Token { parent });
+
+        let new = derive(&token);
+        assert_eq!(new.name.as_deref(), Some("Coin"));
+        assert_eq!(new.symbol.as_deref(), Some("NEW"));
+        assert_eq!(new.image_url.as_deref(), Some("https://x/t.png"));
+    }
+
+    #[test]
+    fn unchanged_fields_report_nothing() {
+        let current = fields("Same", "SAME");
+        assert_eq!(changes(&current, &current.clone()), None);
+    }
+}
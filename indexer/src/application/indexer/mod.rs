@@ -2,12 +2,22 @@
 //!
 //! Real-time blockchain indexing for new blocks and mempool.
 
+pub mod archive;
+pub mod backfills;
 pub mod block;
+pub mod control;
+pub mod gc;
+pub mod leader;
+pub mod locked_supply;
 pub mod mempool;
+pub mod metadata_refresh;
 pub mod network_manager;
+pub mod offchain_metadata;
 pub mod processor_trait;
 pub mod seeder;
 pub mod supervisor;
+pub mod tip_watch;
+pub mod webhooks;
 
 pub use block::BitcoinProcessor;
 pub use network_manager::NetworkManager;
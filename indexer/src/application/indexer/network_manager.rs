@@ -5,10 +5,11 @@ use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
 use crate::application::indexer::block::BitcoinProcessor;
+use crate::application::indexer::leader::{LeaderElection, LeaderGate};
 use crate::application::indexer::mempool::MempoolProcessor;
 use crate::application::indexer::processor_trait::BlockchainProcessor;
 use crate::application::indexer::supervisor;
-use crate::config::{AppConfig, NetworkId, NetworkType};
+use crate::config::{AppConfig, BitcoinConfig, NetworkId, NetworkType};
 use crate::domain::errors::BlockProcessorError;
 use crate::domain::services::CharmService;
 use crate::infrastructure::bitcoin::{BitcoinClient, ProviderFactory, SimpleBitcoinClient};
@@ -26,6 +27,10 @@ pub struct NetworkManager {
     background_tasks: Vec<JoinHandle<()>>,
     /// Fired by `stop_all` to ask every worker to wind down cleanly.
     shutdown: CancellationToken,
+    /// Leader elections, one per network. Stopped last so the advisory lock
+    /// is only released once this replica's writers have exited.
+    election_tasks: Vec<JoinHandle<()>>,
+    election_shutdown: CancellationToken,
 }
 
 impl NetworkManager {
@@ -37,6 +42,8 @@ impl NetworkManager {
             tasks: HashMap::new(),
             background_tasks: Vec::new(),
             shutdown: CancellationToken::new(),
+            election_tasks: Vec::new(),
+            election_shutdown: CancellationToken::new(),
         }
     }
 
@@ -53,6 +60,13 @@ impl NetworkManager {
             self.initialize_bitcoin_processor("mainnet", repos).await?;
         }
         // TODO: Initialize Cardano processors when implemented
+        self.spawn_gc_if_enabled(repos);
+        self.spawn_archiver_if_enabled(repos);
+        self.spawn_locked_supply(repos);
+        self.spawn_webhooks_if_enabled(repos);
+        self.spawn_offchain_metadata_if_enabled(repos);
+        self.spawn_metadata_refresh(repos);
+        self.spawn_backfills(repos);
         Ok(())
     }
 
@@ -62,8 +76,9 @@ impl NetworkManager {
         network: &str,
         repos: &Repositories,
     ) -> Result<(), BlockProcessorError> {
+        // Owned, as the spawn helpers below borrow `self` mutably.
         let bitcoin_config = match self.config.get_bitcoin_config(network) {
-            Some(config) => config,
+            Some(config) => config.clone(),
             None => {
                 logging::log_error(&format!(
                     "Bitcoin configuration for network '{}' not found",
@@ -80,7 +95,7 @@ impl NetworkManager {
         let network_id = NetworkId::new(NetworkType::Bitcoin, network);
 
         // Create SimpleBitcoinClient using the new provider system
-        let simple_client = match SimpleBitcoinClient::new(bitcoin_config) {
+        let simple_client = match SimpleBitcoinClient::new(&bitcoin_config) {
             Ok(client) => client,
             Err(e) => {
                 logging::log_error(&format!(
@@ -92,7 +107,7 @@ impl NetworkManager {
         };
 
         // Log which provider is being used
-        let provider_name = ProviderFactory::get_provider_name(bitcoin_config);
+        let provider_name = ProviderFactory::get_provider_name(&bitcoin_config);
         logging::log_info(&format!(
             "[{}] 🔧 Using {} provider",
             network_id.name, provider_name
@@ -109,15 +124,51 @@ impl NetworkManager {
             repos.dex_orders.clone(),
         );
 
+        // Replicas sharing this database elect one writer per network; the
+        // block and mempool processors below both follow the election.
+        let election = LeaderElection::new(
+            &repos.mempool_spends.get_connection(),
+            repos.indexer_replicas.clone(),
+            network_id.clone(),
+            self.config.indexer.instance_id.clone(),
+        );
+        let leader_gate = election.gate();
+
         let processor = BitcoinProcessor::new(
             bitcoin_client,
             charm_service,
             repos,
             self.config.clone(),
             bitcoin_config.genesis_block_height,
+        )
+        .with_leader_gate(leader_gate.clone());
+        self.spawn_tip_watch_if_configured(
+            network_id.clone(),
+            &bitcoin_config,
+            leader_gate.clone(),
+            repos,
         );
+        let processor = match ProviderFactory::create_fallback_provider(&bitcoin_config) {
+            Some(Ok(provider)) => {
+                logging::log_info(&format!(
+                    "[{}] 🔧 Skipped blocks are retried via {}",
+                    network_id.name,
+                    provider.provider_name()
+                ));
+                processor.with_fallback_client(BitcoinClient::from_simple_client(
+                    SimpleBitcoinClient::from_provider(provider, network_id.clone()),
+                ))
+            }
+            Some(Err(e)) => {
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ Fallback provider unavailable, skipped blocks are retried on the live provider: {}",
+                    network_id.name, e
+                ));
+                processor
+            }
+            None => processor,
+        };
 
-        let network_id = NetworkId::new(NetworkType::Bitcoin, network);
         let network_key = network_id.to_string();
         self.processors.insert(
             network_key.clone(),
@@ -128,17 +179,20 @@ impl NetworkManager {
         // poll cycle restarts the worker instead of silently killing it
         // (root cause of the bloque 946,620 incident). The shutdown token
         // lets `stop_all` wind it down cleanly.
-        match BitcoinClient::new(bitcoin_config) {
+        match BitcoinClient::new(&bitcoin_config) {
             Ok(mempool_client) => {
                 let db_conn = repos.mempool_spends.get_connection();
-                let mempool_proc = Arc::new(MempoolProcessor::new(
-                    mempool_client,
-                    db_conn,
-                    repos.mempool_spends.clone(),
-                    repos.utxo.clone(),
-                    repos.monitored_addresses.clone(),
-                    network_id.clone(),
-                ));
+                let mempool_proc = Arc::new(
+                    MempoolProcessor::new(
+                        mempool_client,
+                        db_conn,
+                        repos.mempool_spends.clone(),
+                        repos.utxo.clone(),
+                        repos.monitored_addresses.clone(),
+                        network_id.clone(),
+                    )
+                    .with_leader_gate(leader_gate),
+                );
                 let supervisor_name = format!("mempool/{}", network_id.name);
                 let cancel = self.shutdown.clone();
                 let handle = tokio::spawn(async move {
@@ -164,6 +218,8 @@ impl NetworkManager {
             }
         }
 
+        self.spawn_election(election);
+
         // Spawn the BTC AddressSeeder under the same supervise() so a panic
         // restarts it instead of silently leaving charm-holder addresses
         // un-seeded. Disabled cleanly via env when Maestro is not configured.
@@ -172,6 +228,78 @@ impl NetworkManager {
         Ok(())
     }
 
+    /// Spawn a network's leader election under `supervise()`. A panic drops
+    /// the lock session, so the restarted election campaigns from scratch.
+    fn spawn_election(&mut self, election: LeaderElection) {
+        let supervisor_name = format!("leader/{}", election.network_id().name);
+        let cancel = self.election_shutdown.clone();
+        let handle = tokio::spawn(async move {
+            supervisor::supervise(&supervisor_name, move || {
+                let election = election.clone();
+                let cancel = cancel.clone();
+                async move { election.run(cancel).await }
+            })
+            .await;
+        });
+        self.election_tasks.push(handle);
+    }
+
+    /// Spawn the chain tip watch under `supervise()` when a fallback
+    /// provider is configured to compare the live provider against.
+    fn spawn_tip_watch_if_configured(
+        &mut self,
+        network_id: NetworkId,
+        bitcoin_config: &BitcoinConfig,
+        leader_gate: LeaderGate,
+        repos: &Repositories,
+    ) {
+        use crate::application::indexer::tip_watch::{TipWatch, TipWatchConfig};
+        use std::time::Duration;
+
+        let Some(fallback) = ProviderFactory::create_fallback_provider(bitcoin_config) else {
+            return;
+        };
+        let providers = fallback.and_then(|secondary| {
+            Ok((ProviderFactory::create_provider(bitcoin_config)?, secondary))
+        });
+        let (primary, secondary) = match providers {
+            Ok(pair) => pair,
+            Err(e) => {
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ Tip watch disabled, provider unavailable: {}",
+                    network_id.name, e
+                ));
+                return;
+            }
+        };
+
+        let indexer = &self.config.indexer;
+        let cfg = TipWatchConfig {
+            interval: Duration::from_secs(indexer.tip_watch_interval_secs.max(1)),
+            max_lag: indexer.tip_watch_max_lag_blocks,
+            stale_after: Duration::from_secs(indexer.tip_watch_stale_after_secs),
+        };
+        let summary = repos.summary.clone();
+        let cancel = self.shutdown.clone();
+        let supervisor_name = format!("tip-watch/{}", network_id.name);
+        let handle = tokio::spawn(async move {
+            supervisor::supervise(&supervisor_name, move || {
+                let watch = TipWatch::new(
+                    network_id.clone(),
+                    primary.clone(),
+                    secondary.clone(),
+                    summary.clone(),
+                    cfg.clone(),
+                )
+                .with_leader_gate(leader_gate.clone());
+                let cancel = cancel.clone();
+                async move { watch.run(cancel).await }
+            })
+            .await;
+        });
+        self.background_tasks.push(handle);
+    }
+
     fn spawn_btc_seeder_if_enabled(&mut self, network_id: NetworkId, repos: &Repositories) {
         if !self.config.indexer.btc_auto_seeder_enabled {
             logging::log_info(&format!(
@@ -229,6 +357,217 @@ impl NetworkManager {
         ));
     }
 
+    /// Spawn the garbage collector under `supervise()`. One task for all
+    /// networks: its sweeps are cross-network by design.
+    fn spawn_gc_if_enabled(&mut self, repos: &Repositories) {
+        let indexer = &self.config.indexer;
+        if !indexer.gc_enabled {
+            logging::log_info("[gc] 🗑️ GarbageCollector disabled (ENABLE_GC=false)");
+            return;
+        }
+        use crate::application::indexer::gc::{GarbageCollector, GcConfig, GcSweep};
+        use std::time::Duration;
+
+        let toggles = [
+            (GcSweep::MempoolCharms, indexer.gc_sweep_mempool_charms),
+            (GcSweep::DexOrders, indexer.gc_sweep_dex_orders),
+            (GcSweep::MonitoredAddresses, indexer.gc_sweep_monitored_addresses),
+            (GcSweep::MempoolSpends, indexer.gc_sweep_mempool_spends),
+            (GcSweep::ExpiredMonitors, indexer.gc_sweep_expired_monitors),
+            (GcSweep::Changefeed, indexer.gc_sweep_changefeed),
+            (GcSweep::UtxoSpends, indexer.gc_sweep_utxo_spends),
+        ];
+        let cfg = GcConfig {
+            interval: Duration::from_secs(indexer.gc_interval_secs.max(1)),
+            batch_size: indexer.gc_batch_size,
+            monitored_idle_days: indexer.gc_monitored_idle_days,
+            changefeed_retention_days: indexer.gc_changefeed_retention_days,
+            utxo_spends_retention_blocks: indexer.gc_utxo_spends_retention_blocks,
+            sweeps: toggles
+                .into_iter()
+                .filter_map(|(sweep, enabled)| enabled.then_some(sweep))
+                .collect(),
+        };
+        let conn = repos.mempool_spends.get_connection();
+        let summary = repos.summary.clone();
+        let cancel = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            supervisor::supervise("gc", move || {
+                let gc = GarbageCollector::new(conn.clone(), summary.clone(), cfg.clone());
+                let cancel = cancel.clone();
+                async move { gc.run(cancel).await }
+            })
+            .await;
+        });
+        self.background_tasks.push(handle);
+        logging::log_info("[gc] 🗑️ GarbageCollector spawned under supervisor");
+    }
+
+    /// Spawn the spent-charm archiver under `supervise()` when
+    /// `PRUNE_SPENT_CHARMS_AFTER_BLOCKS` is set. One task for all networks,
+    /// paced like the GC.
+    fn spawn_archiver_if_enabled(&mut self, repos: &Repositories) {
+        let indexer = &self.config.indexer;
+        let Some(after_blocks) = indexer.prune_spent_charms_after_blocks else {
+            return;
+        };
+        use crate::application::indexer::archive::{
+            ArchiveConfig, CharmArchiver, MIN_PRUNE_DEPTH,
+        };
+        use std::time::Duration;
+
+        if after_blocks < MIN_PRUNE_DEPTH {
+            logging::log_warning(&format!(
+                "[archive] ⚠️ PRUNE_SPENT_CHARMS_AFTER_BLOCKS={} is below the reorg depth, using {}",
+                after_blocks, MIN_PRUNE_DEPTH
+            ));
+        }
+        let cfg = ArchiveConfig {
+            interval: Duration::from_secs(indexer.gc_interval_secs.max(1)),
+            batch_size: indexer.gc_batch_size,
+            after_blocks,
+            archive: indexer.archive_pruned_charms,
+        };
+        let conn = repos.mempool_spends.get_connection();
+        let cancel = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            supervisor::supervise("archive", move || {
+                let archiver = CharmArchiver::new(conn.clone(), cfg.clone());
+                let cancel = cancel.clone();
+                async move { archiver.run(cancel).await }
+            })
+            .await;
+        });
+        self.background_tasks.push(handle);
+        logging::log_info("[archive] 🗄️ CharmArchiver spawned under supervisor");
+    }
+
+    /// Spawn the periodic locked-supply pass under `supervise()`. One task
+    /// for all networks, paced like the GC.
+    fn spawn_locked_supply(&mut self, repos: &Repositories) {
+        use crate::application::indexer::locked_supply::LockedSupply;
+        use std::time::Duration;
+
+        let indexer = &self.config.indexer;
+        let interval = Duration::from_secs(indexer.gc_interval_secs.max(1));
+        let addresses = indexer.locked_supply_addresses.clone();
+        let conn = repos.mempool_spends.get_connection();
+        let cancel = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            supervisor::supervise("locked-supply", move || {
+                let tracker = LockedSupply::new(conn.clone(), &addresses);
+                let cancel = cancel.clone();
+                async move { tracker.run(interval, cancel).await }
+            })
+            .await;
+        });
+        self.background_tasks.push(handle);
+        logging::log_info("[locked-supply] 🔒 LockedSupply spawned under supervisor");
+    }
+
+    /// Spawn the webhook dispatcher under `supervise()`. One task for all
+    /// networks; replicas share the queue safely (see `webhooks.rs`).
+    fn spawn_webhooks_if_enabled(&mut self, repos: &Repositories) {
+        let indexer = &self.config.indexer;
+        if !indexer.webhooks_enabled {
+            logging::log_info("[webhooks] 📮 WebhookDispatcher disabled (ENABLE_WEBHOOKS=false)");
+            return;
+        }
+        use crate::application::indexer::webhooks::{WebhookConfig, WebhookDispatcher};
+        use std::time::Duration;
+
+        let cfg = WebhookConfig {
+            timeout: Duration::from_secs(indexer.webhook_timeout_secs.max(1)),
+            max_attempts: indexer.webhook_max_attempts.max(1),
+            disable_after: indexer.webhook_disable_after.max(1),
+            ..WebhookConfig::default()
+        };
+        let repo = repos.webhooks.clone();
+        let cancel = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            supervisor::supervise("webhooks", move || {
+                let dispatcher = WebhookDispatcher::new(repo.clone(), cfg.clone());
+                let cancel = cancel.clone();
+                async move { dispatcher.run(cancel).await }
+            })
+            .await;
+        });
+        self.background_tasks.push(handle);
+        logging::log_info("[webhooks] 📮 WebhookDispatcher spawned under supervisor");
+    }
+
+    /// Spawn the off-chain metadata fetcher under `supervise()`. One task for
+    /// all networks, fed by the queue block processing fills.
+    fn spawn_offchain_metadata_if_enabled(&mut self, repos: &Repositories) {
+        if !self.config.indexer.fetch_offchain_metadata {
+            return;
+        }
+        use crate::application::indexer::offchain_metadata::{
+            OffchainMetadataConfig, OffchainMetadataFetcher,
+        };
+
+        let repo = repos.offchain_metadata.clone();
+        let cancel = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            supervisor::supervise("offchain-metadata", move || {
+                let fetcher =
+                    OffchainMetadataFetcher::new(repo.clone(), OffchainMetadataConfig::default());
+                let cancel = cancel.clone();
+                async move { fetcher.run(cancel).await }
+            })
+            .await;
+        });
+        self.background_tasks.push(handle);
+        logging::log_info("[offchain] 🌐 OffchainMetadataFetcher spawned under supervisor");
+    }
+
+    /// Spawn the metadata refresh worker under `supervise()`. Always on: it
+    /// only polls for jobs queued through the admin API.
+    fn spawn_metadata_refresh(&mut self, repos: &Repositories) {
+        use crate::application::indexer::metadata_refresh::{
+            MetadataRefreshConfig, MetadataRefreshWorker,
+        };
+
+        let repo = repos.metadata_refresh.clone();
+        let cancel = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            supervisor::supervise("metadata-refresh", move || {
+                let worker =
+                    MetadataRefreshWorker::new(repo.clone(), MetadataRefreshConfig::default());
+                let cancel = cancel.clone();
+                async move { worker.run(cancel).await }
+            })
+            .await;
+        });
+        self.background_tasks.push(handle);
+        logging::log_info("[metadata-refresh] 🔁 MetadataRefreshWorker spawned under supervisor");
+    }
+
+    /// Spawn the backfill worker under `supervise()`. Always on: it only
+    /// runs backfills requested through the admin API, at the configured rate.
+    fn spawn_backfills(&mut self, repos: &Repositories) {
+        use crate::application::indexer::backfills::BackfillWorker;
+        use crate::application::maintenance::BackfillConfig;
+
+        let conn = repos.mempool_spends.get_connection();
+        let cfg = BackfillConfig {
+            batch_size: self.config.indexer.backfill_batch_size,
+            rows_per_sec: self.config.indexer.backfill_rows_per_sec,
+            ..BackfillConfig::default()
+        };
+        let cancel = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            supervisor::supervise("backfills", move || {
+                let worker = BackfillWorker::new(conn.clone(), cfg.clone());
+                let cancel = cancel.clone();
+                async move { worker.run(cancel).await }
+            })
+            .await;
+        });
+        self.background_tasks.push(handle);
+        logging::log_info("[backfill] 🧩 BackfillWorker spawned under supervisor");
+    }
+
     /// Start all processors
     pub async fn start_all(&mut self) -> Result<(), BlockProcessorError> {
         // Collect keys first to avoid borrowing issues
@@ -300,6 +639,15 @@ impl NetworkManager {
             }
         }
 
+        // Writers are down: step down and release the leader locks.
+        self.election_shutdown.cancel();
+        let elections = std::mem::take(&mut self.election_tasks);
+        for handle in elections {
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, handle).await.is_err() {
+                logging::log_warning("leader election did not stop within timeout");
+            }
+        }
+
         logging::log_info("All processors stopped");
     }
 }
@@ -0,0 +1,337 @@
+//! Off-chain NFT metadata fetcher.
+//!
+//! Some NFTs carry only a link to a JSON document with their attributes.
+//! With `FETCH_OFFCHAIN_METADATA=true`, `BlockProcessor` queues one
+//! `offchain_metadata_fetches` row per such NFT once its block is persisted
+//! (see `OffchainMetadataRepository`). This task drains that queue: block
+//! processing never waits on a remote server.
+//!
+//! Each document is fetched under strict limits: `timeout` for the whole
+//! request, at most `max_bytes` of body, and a JSON content type. The parsed
+//! object lands in `assets.offchain_metadata`. Transport errors, timeouts and
+//! 5xx/429 responses are retried with exponential backoff up to
+//! `max_attempts`; anything a retry cannot fix (oversize, wrong content
+//! type, invalid JSON, other statuses) gives the fetch up at once.
+//!
+//! Fetches are claimed with `SKIP LOCKED` and a lease, so several replicas
+//! can run this worker without fetching a document twice.
+
+use std::fmt;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use crate::infrastructure::persistence::repositories::{OffchainMetadataRepository, PendingFetch};
+use crate::utils::logging;
+
+/// Fetches run at once.
+const CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct OffchainMetadataConfig {
+    /// Pause between queue polls when nothing was due.
+    pub poll_interval: Duration,
+    /// Budget for one fetch, connect to last body byte.
+    pub timeout: Duration,
+    /// Largest document accepted, bytes.
+    pub max_bytes: usize,
+    /// Fetches claimed per poll.
+    pub batch_size: u64,
+    /// Attempts per fetch before it is given up.
+    pub max_attempts: i32,
+    /// Delay before the first retry, doubled on each further one.
+    pub retry_base: Duration,
+    /// Upper bound of the retry delay.
+    pub retry_max: Duration,
+    /// Gateway `ipfs://` links are rewritten to.
+    pub ipfs_gateway: String,
+}
+
+impl Default for OffchainMetadataConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(5),
+            max_bytes: 256 * 1024,
+            batch_size: 20,
+            max_attempts: 8,
+            retry_base: Duration::from_secs(60),
+            retry_max: Duration::from_secs(6 * 3600),
+            ipfs_gateway: "https://ipfs.io/ipfs/".to_string(),
+        }
+    }
+}
+
+impl OffchainMetadataConfig {
+    /// Delay before retrying after `attempt` (1-based) failed.
+    pub fn backoff(&self, attempt: i32) -> Duration {
+        let shift = attempt.saturating_sub(1).clamp(0, 20) as u32;
+        self.retry_base
+            .saturating_mul(1u32 << shift)
+            .min(self.retry_max)
+    }
+
+    /// The HTTP(S) URL to fetch for `url`, with `ipfs://` rewritten to the
+    /// gateway. `None` for any other scheme.
+    pub fn resolve_url(&self, url: &str) -> Option<String> {
+        let url = url.trim();
+        if let Some(path) = url.strip_prefix("ipfs://") {
+            let path = path.strip_prefix("ipfs/").unwrap_or(path);
+            return Some(format!(
+                "{}/{}",
+                self.ipfs_gateway.trim_end_matches('/'),
+                path
+            ));
+        }
+        (url.starts_with("https://") || url.starts_with("http://")).then(|| url.to_string())
+    }
+}
+
+/// Why a document could not be stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchError {
+    UnsupportedUrl,
+    Timeout,
+    Transport(String),
+    Status(u16),
+    ContentType(String),
+    TooLarge { limit: usize },
+    InvalidJson(String),
+}
+
+impl FetchError {
+    /// Whether a later attempt may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Timeout | Self::Transport(_) => true,
+            Self::Status(code) => *code == 429 || *code >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedUrl => write!(f, "unsupported URL scheme"),
+            Self::Timeout => write!(f, "timed out"),
+            Self::Transport(e) => write!(f, "transport error: {e}"),
+            Self::Status(code) => write!(f, "HTTP {code}"),
+            Self::ContentType(ct) => write!(f, "unexpected content type {ct:?}"),
+            Self::TooLarge { limit } => write!(f, "document larger than {limit} bytes"),
+            Self::InvalidJson(e) => write!(f, "invalid JSON document: {e}"),
+        }
+    }
+}
+
+/// JSON media types, plus `text/plain`, which IPFS gateways use for raw
+/// files.
+fn is_json_content_type(content_type: &str) -> bool {
+    let media = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    media == "application/json"
+        || media == "text/json"
+        || media == "text/plain"
+        || (media.starts_with("application/") && media.ends_with("+json"))
+}
+
+pub struct OffchainMetadataFetcher {
+    repo: OffchainMetadataRepository,
+    client: reqwest::Client,
+    cfg: OffchainMetadataConfig,
+}
+
+impl OffchainMetadataFetcher {
+    pub fn new(repo: OffchainMetadataRepository, cfg: OffchainMetadataConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(cfg.timeout)
+            .redirect(reqwest::redirect::Policy::limited(3))
+            .user_agent(concat!("charms-indexer/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+        Self { repo, client, cfg }
+    }
+
+    /// Fetch due documents until cancelled. A full batch is followed by the
+    /// next one right away; otherwise the worker sleeps `poll_interval`.
+    pub async fn run(self, cancel: CancellationToken) {
+        logging::log_info(&format!(
+            "[offchain] 🌐 OffchainMetadataFetcher started (limit {} bytes, timeout {:?})",
+            self.cfg.max_bytes, self.cfg.timeout
+        ));
+        loop {
+            let fetched = self.run_once().await;
+            if (fetched as u64) < self.cfg.batch_size {
+                tokio::select! {
+                    _ = tokio::time::sleep(self.cfg.poll_interval) => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+            if cancel.is_cancelled() {
+                logging::log_info(
+                    "[offchain] 🛑 OffchainMetadataFetcher stopping (cancellation requested)",
+                );
+                return;
+            }
+        }
+    }
+
+    /// Claim one batch of due fetches and attempt each. Returns how many
+    /// were attempted.
+    pub async fn run_once(&self) -> usize {
+        // Outlive a full batch of timeouts, so a slow batch is never re-claimed.
+        let lease = self.cfg.timeout * (self.cfg.batch_size as u32 / CONCURRENCY as u32 + 2);
+        let due = match self
+            .repo
+            .claim_due(self.cfg.batch_size, lease.as_secs_f64())
+            .await
+        {
+            Ok(due) => due,
+            Err(e) => {
+                logging::log_warning(&format!("[offchain] ⚠️ Failed to claim fetches: {}", e));
+                return 0;
+            }
+        };
+        let count = due.len();
+        futures::stream::iter(due)
+            .for_each_concurrent(CONCURRENCY, |pending| self.process(pending))
+            .await;
+        count
+    }
+
+    /// Download and parse the document at `url` within the configured limits.
+    pub async fn fetch(&self, url: &str) -> Result<Value, FetchError> {
+        let url = self.cfg.resolve_url(url).ok_or(FetchError::UnsupportedUrl)?;
+        match tokio::time::timeout(self.cfg.timeout, self.download(&url)).await {
+            Ok(result) => result,
+            Err(_) => Err(FetchError::Timeout),
+        }
+    }
+
+    async fn download(&self, url: &str) -> Result<Value, FetchError> {
+        let transport = |e: reqwest::Error| {
+            if e.is_timeout() {
+                FetchError::Timeout
+            } else {
+                FetchError::Transport(e.to_string())
+            }
+        };
+        let mut resp = self.client.get(url).send().await.map_err(transport)?;
+        if !resp.status().is_success() {
+            return Err(FetchError::Status(resp.status().as_u16()));
+        }
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        if !is_json_content_type(&content_type) {
+            return Err(FetchError::ContentType(content_type));
+        }
+        let limit = self.cfg.max_bytes;
+        if resp.content_length().is_some_and(|len| len > limit as u64) {
+            return Err(FetchError::TooLarge { limit });
+        }
+
+        // Content-Length may be absent or wrong: count what actually arrives.
+        let mut body = Vec::new();
+        while let Some(chunk) = resp.chunk().await.map_err(transport)? {
+            if body.len() + chunk.len() > limit {
+                return Err(FetchError::TooLarge { limit });
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(doc) if doc.is_object() => Ok(doc),
+            Ok(_) => Err(FetchError::InvalidJson("not a JSON object".to_string())),
+            Err(e) => Err(FetchError::InvalidJson(e.to_string())),
+        }
+    }
+
+    async fn process(&self, pending: PendingFetch) {
+        let attempt = pending.attempts + 1;
+        let outcome = match self.fetch(&pending.url).await {
+            Ok(doc) => self.repo.mark_fetched(pending.asset_id, &doc).await,
+            Err(error) => {
+                let retry_in = (error.is_retryable() && attempt < self.cfg.max_attempts)
+                    .then(|| self.cfg.backoff(attempt).as_secs_f64());
+                if retry_in.is_none() {
+                    logging::log_warning(&format!(
+                        "[offchain] ⚠️ Giving up on metadata for asset {} ({}): {}",
+                        pending.asset_id, pending.url, error
+                    ));
+                }
+                self.repo
+                    .mark_failed(pending.asset_id, &error.to_string(), retry_in)
+                    .await
+            }
+        };
+        if let Err(e) = outcome {
+            logging::log_warning(&format!(
+                "[offchain] ⚠️ Failed to update fetch for asset {}: {}",
+                pending.asset_id, e
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let cfg = OffchainMetadataConfig::default();
+        assert_eq!(cfg.backoff(1), Duration::from_secs(60));
+        assert_eq!(cfg.backoff(3), Duration::from_secs(240));
+        assert_eq!(cfg.backoff(100), Duration::from_secs(6 * 3600));
+    }
+
+    #[test]
+    fn ipfs_links_go_through_the_gateway() {
+        let cfg = OffchainMetadataConfig::default();
+        assert_eq!(
+            cfg.resolve_url("ipfs://bafy/meta.json").as_deref(),
+            Some("https://ipfs.io/ipfs/bafy/meta.json")
+        );
+        assert_eq!(
+            cfg.resolve_url("ipfs://ipfs/bafy").as_deref(),
+            Some("https://ipfs.io/ipfs/bafy")
+        );
+        assert_eq!(
+            cfg.resolve_url(" https://x.example/a.json ").as_deref(),
+            Some("https://x.example/a.json")
+        );
+        assert_eq!(cfg.resolve_url("data:application/json,{}"), None);
+        assert_eq!(cfg.resolve_url("file:///etc/passwd"), None);
+    }
+
+    #[test]
+    fn only_json_content_types_pass() {
+        assert!(is_json_content_type("application/json"));
+        assert!(is_json_content_type("Application/JSON; charset=utf-8"));
+        assert!(is_json_content_type("application/ld+json"));
+        assert!(is_json_content_type("text/plain"));
+        assert!(!is_json_content_type("text/html"));
+        assert!(!is_json_content_type("image/png"));
+        assert!(!is_json_content_type(""));
+    }
+
+    #[test]
+    fn only_transient_failures_are_retried() {
+        assert!(FetchError::Timeout.is_retryable());
+        assert!(FetchError::Status(503).is_retryable());
+        assert!(FetchError::Status(429).is_retryable());
+        assert!(!FetchError::Status(404).is_retryable());
+        assert!(!FetchError::TooLarge { limit: 1 }.is_retryable());
+        assert!(!FetchError::ContentType("text/html".into()).is_retryable());
+    }
+}
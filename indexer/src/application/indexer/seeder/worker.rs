@@ -212,6 +212,7 @@ async fn seed_one_locked(
             block_height: u.block_height.unwrap_or(0),
             network: network.to_string(),
             source: "maestro".to_string(),
+            possible_charm: false,
         })
         .collect();
     let utxo_count = utxo_inserts.len();
@@ -221,6 +222,18 @@ async fn seed_one_locked(
             .insert_batch(&utxo_inserts)
             .await
             .map_err(|e| SeedError::Db(e.to_string()))?;
+        // The address's charms may predate its monitoring: flag their UTXOs.
+        let txids: Vec<String> = utxos
+            .iter()
+            .map(|u| u.txid.clone())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        repos
+            .utxo
+            .annotate_charms(&txids, network)
+            .await
+            .map_err(|e| SeedError::Db(e.to_string()))?;
     }
 
     let tx_inserts: Vec<AddressTxInsert> = txs
@@ -0,0 +1,247 @@
+//! Chain tip divergence between the live provider and the fallback.
+//!
+//! A primary node that stops following the chain keeps answering
+//! `getblockcount` with its old tip, so the block loop idles "waiting for new
+//! blocks" while the real chain moves on. When a fallback provider is
+//! configured, this task compares both tips every `TIP_WATCH_INTERVAL_SECS`.
+//! Once the primary has trailed by more than `TIP_WATCH_MAX_LAG_BLOCKS` for
+//! `TIP_WATCH_STALE_AFTER_SECS`, it logs an error and sets
+//! `summary.provider_stale`, shown on `/status`; the flag clears as soon as
+//! the primary is back within the lag.
+//!
+//! Only the leader checks: standby replicas do not index, so their
+//! providers' tips say nothing about the data on `/status`. Failing over to
+//! the fallback is left to the operator; tip queries keep going to the
+//! primary.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::application::indexer::leader::LeaderGate;
+use crate::config::NetworkId;
+use crate::infrastructure::bitcoin::BitcoinProvider;
+use crate::infrastructure::persistence::repositories::SummaryRepository;
+use crate::utils::logging;
+
+#[derive(Debug, Clone)]
+pub struct TipWatchConfig {
+    /// Pause between comparisons.
+    pub interval: Duration,
+    /// Blocks the primary may trail before it counts as lagging.
+    pub max_lag: u64,
+    /// How long it must keep lagging before it is flagged stale.
+    pub stale_after: Duration,
+}
+
+impl Default for TipWatchConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            max_lag: 3,
+            stale_after: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Lag bookkeeping for one network, kept apart from the providers so the
+/// flag lifecycle depends only on the tips seen and when.
+#[derive(Debug, Default)]
+struct Divergence {
+    /// First comparison of the current run of lagging ones.
+    lagging_since: Option<Instant>,
+    stale: bool,
+}
+
+impl Divergence {
+    /// Feed one pair of tips seen at `now`; returns the flag when it changed.
+    fn observe(
+        &mut self,
+        primary: u64,
+        secondary: u64,
+        cfg: &TipWatchConfig,
+        now: Instant,
+    ) -> Option<bool> {
+        let stale = if secondary.saturating_sub(primary) > cfg.max_lag {
+            let since = *self.lagging_since.get_or_insert(now);
+            now.duration_since(since) >= cfg.stale_after
+        } else {
+            self.lagging_since = None;
+            false
+        };
+        if stale == self.stale {
+            return None;
+        }
+        self.stale = stale;
+        Some(stale)
+    }
+}
+
+pub struct TipWatch {
+    network_id: NetworkId,
+    primary: Arc<dyn BitcoinProvider>,
+    secondary: Arc<dyn BitcoinProvider>,
+    summary: SummaryRepository,
+    leader_gate: LeaderGate,
+    cfg: TipWatchConfig,
+    divergence: Divergence,
+}
+
+impl TipWatch {
+    pub fn new(
+        network_id: NetworkId,
+        primary: Arc<dyn BitcoinProvider>,
+        secondary: Arc<dyn BitcoinProvider>,
+        summary: SummaryRepository,
+        cfg: TipWatchConfig,
+    ) -> Self {
+        Self {
+            network_id,
+            primary,
+            secondary,
+            summary,
+            leader_gate: LeaderGate::always(),
+            cfg,
+            divergence: Divergence::default(),
+        }
+    }
+
+    /// Check only while this replica leads `network_id`.
+    pub fn with_leader_gate(mut self, gate: LeaderGate) -> Self {
+        self.leader_gate = gate;
+        self
+    }
+
+    /// Whether the primary is currently flagged stale.
+    pub fn is_stale(&self) -> bool {
+        self.divergence.stale
+    }
+
+    pub async fn run(mut self, cancel: CancellationToken) {
+        logging::log_info(&format!(
+            "[{}] 🧭 Tip watch started ({} vs {}, every {}s, stale after {} blocks for {}s)",
+            self.network_id.name,
+            self.primary.provider_name(),
+            self.secondary.provider_name(),
+            self.cfg.interval.as_secs(),
+            self.cfg.max_lag,
+            self.cfg.stale_after.as_secs()
+        ));
+        // The flag may be left over from a previous process.
+        self.record(false).await;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.cfg.interval) => {}
+                _ = cancel.cancelled() => {
+                    logging::log_info(&format!(
+                        "[{}] 🛑 Tip watch stopping (cancellation requested)",
+                        self.network_id.name
+                    ));
+                    return;
+                }
+            }
+            if self.leader_gate.is_leader() {
+                self.check_at(Instant::now()).await;
+            }
+        }
+    }
+
+    /// Compare both tips once, as of `now`, and record the flag when it
+    /// changes. A failed tip query skips the comparison: it says nothing
+    /// about lag. Returns the flag when it changed.
+    pub async fn check_at(&mut self, now: Instant) -> Option<bool> {
+        let network = &self.network_id.name;
+        let (primary, secondary) =
+            match tokio::join!(self.primary.get_block_count(), self.secondary.get_block_count()) {
+                (Ok(p), Ok(s)) => (p, s),
+                (Err(e), _) | (_, Err(e)) => {
+                    logging::log_warning(&format!(
+                        "[{}] ⚠️ Tip watch could not read a chain tip: {}",
+                        network, e
+                    ));
+                    return None;
+                }
+            };
+
+        let changed = self.divergence.observe(primary, secondary, &self.cfg, now)?;
+        if changed {
+            tracing::error!(
+                network = %network,
+                primary = %self.primary.provider_name(),
+                primary_tip = primary,
+                secondary = %self.secondary.provider_name(),
+                secondary_tip = secondary,
+                lag = secondary - primary,
+                stale_after_secs = self.cfg.stale_after.as_secs(),
+                "primary provider tip is stale"
+            );
+        } else {
+            tracing::info!(
+                network = %network,
+                primary_tip = primary,
+                secondary_tip = secondary,
+                "primary provider tip caught up"
+            );
+        }
+        self.record(changed).await;
+        Some(changed)
+    }
+
+    async fn record(&self, stale: bool) {
+        if let Err(e) = self.summary.set_provider_stale(&self.network_id, stale).await {
+            logging::log_warning(&format!(
+                "[{}] ⚠️ Failed to record provider_stale: {}",
+                self.network_id.name, e
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> TipWatchConfig {
+        TipWatchConfig {
+            interval: Duration::from_secs(60),
+            max_lag: 3,
+            stale_after: Duration::from_secs(600),
+        }
+    }
+
+    #[test]
+    fn lag_must_last_before_the_flag_is_set() {
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        let mut d = Divergence::default();
+
+        assert_eq!(d.observe(100, 103, &cfg(), at(0)), None, "within the lag");
+        assert_eq!(d.observe(100, 104, &cfg(), at(60)), None, "lagging, not for long");
+        assert_eq!(d.observe(100, 105, &cfg(), at(600)), None);
+        assert_eq!(d.observe(100, 106, &cfg(), at(660)), Some(true));
+        assert_eq!(d.observe(100, 107, &cfg(), at(720)), None, "reported once");
+        assert_eq!(d.observe(107, 107, &cfg(), at(780)), Some(false));
+    }
+
+    #[test]
+    fn a_caught_up_comparison_restarts_the_clock() {
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        let mut d = Divergence::default();
+
+        d.observe(100, 110, &cfg(), at(0));
+        d.observe(110, 110, &cfg(), at(300));
+        assert_eq!(d.observe(110, 120, &cfg(), at(600)), None);
+        assert_eq!(d.observe(110, 120, &cfg(), at(1199)), None);
+        assert_eq!(d.observe(110, 121, &cfg(), at(1200)), Some(true));
+    }
+
+    #[test]
+    fn a_primary_ahead_is_not_lagging() {
+        let mut d = Divergence::default();
+        let t0 = Instant::now();
+        d.observe(120, 100, &cfg(), t0);
+        assert_eq!(d.observe(120, 100, &cfg(), t0 + Duration::from_secs(3600)), None);
+    }
+}
@@ -0,0 +1,260 @@
+//! Webhook delivery worker.
+//!
+//! After each block, `BlockProcessor` queues one `webhook_deliveries` row per
+//! event and matching subscription (see `WebhooksRepository`). This task
+//! drains that queue across all networks: it POSTs the JSON payload to the
+//! subscription URL with `X-Charms-Signature: sha256=<hex>`, the HMAC-SHA256
+//! of the body under the subscription secret.
+//!
+//! Any 2xx response delivers the event. Other responses and transport
+//! errors are retried with exponential backoff up to `max_attempts`, and
+//! every attempt is logged in `webhook_delivery_attempts`. A subscription
+//! failing `disable_after` attempts in a row is deactivated.
+//!
+//! Deliveries are claimed with `SKIP LOCKED` and a lease, so several
+//! replicas can run this worker without sending an event twice.
+
+use std::time::{Duration, Instant};
+
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use futures::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+use crate::infrastructure::persistence::repositories::{PendingDelivery, WebhooksRepository};
+use crate::utils::logging;
+
+/// Event types a subscription can ask for.
+pub const EVENT_TYPES: [&str; 5] = [
+    "charm_created",
+    "charm_spent",
+    "asset_supply_changed",
+    "dex_order_created",
+    "dex_order_filled",
+];
+
+pub const SIGNATURE_HEADER: &str = "X-Charms-Signature";
+pub const EVENT_HEADER: &str = "X-Charms-Event";
+pub const DELIVERY_HEADER: &str = "X-Charms-Delivery";
+
+/// Deliveries sent at once.
+const CONCURRENCY: usize = 8;
+/// Response body characters kept in the attempt log.
+const ERROR_BODY_LIMIT: usize = 200;
+
+/// `sha256=<hex>` HMAC of `body` keyed with `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(secret.as_bytes());
+    engine.input(body);
+    let mac = hmac::Hmac::<sha256::Hash>::from_engine(engine);
+    format!("sha256={}", hex::encode(mac.to_byte_array()))
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Pause between queue polls when nothing was due.
+    pub poll_interval: Duration,
+    /// Per-request timeout.
+    pub timeout: Duration,
+    /// Deliveries claimed per poll.
+    pub batch_size: u64,
+    /// Attempts per delivery before it is given up.
+    pub max_attempts: i32,
+    /// Delay before the first retry, doubled on each further one.
+    pub retry_base: Duration,
+    /// Upper bound of the retry delay.
+    pub retry_max: Duration,
+    /// Consecutive failed attempts that deactivate a subscription.
+    pub disable_after: i32,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(10),
+            batch_size: 50,
+            max_attempts: 12,
+            retry_base: Duration::from_secs(10),
+            retry_max: Duration::from_secs(3600),
+            disable_after: 10,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Delay before retrying after `attempt` (1-based) failed.
+    pub fn backoff(&self, attempt: i32) -> Duration {
+        let shift = attempt.saturating_sub(1).clamp(0, 20) as u32;
+        self.retry_base
+            .saturating_mul(1u32 << shift)
+            .min(self.retry_max)
+    }
+}
+
+pub struct WebhookDispatcher {
+    repo: WebhooksRepository,
+    client: reqwest::Client,
+    cfg: WebhookConfig,
+}
+
+impl WebhookDispatcher {
+    pub fn new(repo: WebhooksRepository, cfg: WebhookConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(cfg.timeout)
+            .user_agent(concat!("charms-indexer/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+        Self { repo, client, cfg }
+    }
+
+    /// Deliver due events until cancelled. A full batch is followed by the
+    /// next one right away; otherwise the worker sleeps `poll_interval`.
+    pub async fn run(self, cancel: CancellationToken) {
+        logging::log_info(&format!(
+            "[webhooks] 📮 WebhookDispatcher started (retries up to {}, disable after {} failures)",
+            self.cfg.max_attempts, self.cfg.disable_after
+        ));
+        loop {
+            let sent = self.run_once().await;
+            if (sent as u64) < self.cfg.batch_size {
+                tokio::select! {
+                    _ = tokio::time::sleep(self.cfg.poll_interval) => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+            if cancel.is_cancelled() {
+                logging::log_info(
+                    "[webhooks] 🛑 WebhookDispatcher stopping (cancellation requested)",
+                );
+                return;
+            }
+        }
+    }
+
+    /// Claim one batch of due deliveries and attempt each. Returns how many
+    /// were attempted.
+    pub async fn run_once(&self) -> usize {
+        // Outlive a full batch of timeouts, so a slow batch is never re-claimed.
+        let lease = self.cfg.timeout * (self.cfg.batch_size as u32 / CONCURRENCY as u32 + 2);
+        let due = match self
+            .repo
+            .claim_due(self.cfg.batch_size, lease.as_secs_f64())
+            .await
+        {
+            Ok(due) => due,
+            Err(e) => {
+                logging::log_warning(&format!("[webhooks] ⚠️ Failed to claim deliveries: {}", e));
+                return 0;
+            }
+        };
+        let count = due.len();
+        futures::stream::iter(due)
+            .for_each_concurrent(CONCURRENCY, |delivery| self.deliver(delivery))
+            .await;
+        count
+    }
+
+    async fn deliver(&self, delivery: PendingDelivery) {
+        let attempt = delivery.attempts + 1;
+        let started = Instant::now();
+        let result = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                sign(&delivery.secret, delivery.payload.as_bytes()),
+            )
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, &delivery.event_id)
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+
+        let (status_code, error) = match result {
+            Ok(resp) if resp.status().is_success() => (Some(resp.status().as_u16() as i32), None),
+            Ok(resp) => {
+                let status = resp.status();
+                let body = resp.text().await.unwrap_or_default();
+                let body: String = body.chars().take(ERROR_BODY_LIMIT).collect();
+                (
+                    Some(status.as_u16() as i32),
+                    Some(format!("HTTP {}: {}", status, body).trim_end().to_string()),
+                )
+            }
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+        if let Err(e) = self
+            .repo
+            .record_attempt(
+                delivery.id,
+                attempt,
+                status_code,
+                error.as_deref(),
+                duration_ms,
+            )
+            .await
+        {
+            logging::log_warning(&format!(
+                "[webhooks] ⚠️ Failed to log attempt for delivery {}: {}",
+                delivery.id, e
+            ));
+        }
+
+        let outcome = match error {
+            None => self.repo.mark_delivered(delivery.id).await,
+            Some(error) => {
+                let retry_in = (attempt < self.cfg.max_attempts)
+                    .then(|| self.cfg.backoff(attempt).as_secs_f64());
+                match self
+                    .repo
+                    .mark_failed(delivery.id, &error, retry_in, self.cfg.disable_after)
+                    .await
+                {
+                    Ok(disabled) => {
+                        if disabled {
+                            logging::log_warning(&format!(
+                                "[webhooks] 🔕 Subscription {} disabled after {} consecutive failures ({})",
+                                delivery.subscription_id, self.cfg.disable_after, delivery.url
+                            ));
+                        }
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+        };
+        if let Err(e) = outcome {
+            logging::log_warning(&format!(
+                "[webhooks] ⚠️ Failed to update delivery {}: {}",
+                delivery.id, e
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_matches_rfc4231_vector() {
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let cfg = WebhookConfig::default();
+        assert_eq!(cfg.backoff(1), Duration::from_secs(10));
+        assert_eq!(cfg.backoff(2), Duration::from_secs(20));
+        assert_eq!(cfg.backoff(4), Duration::from_secs(80));
+        assert_eq!(cfg.backoff(9), Duration::from_secs(2560));
+        assert_eq!(cfg.backoff(10), Duration::from_secs(3600));
+        assert_eq!(cfg.backoff(1000), Duration::from_secs(3600));
+    }
+}
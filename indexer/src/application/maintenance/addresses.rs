@@ -0,0 +1,103 @@
+//! Backfill charm addresses the live path could not derive (charms stored
+//! before the extractor read the charm's own output, or whose tx hex was
+//! unavailable at the time).
+//!
+//! Each charm with a NULL address is re-derived from the hex stored on its
+//! `transactions` row, using the script of the charm's vout. Charms whose
+//! output has no address form (P2PK, bare multisig, nonstandard) stay NULL
+//! and are counted per script type like the live path. Holders of every
+//! network with a filled charm are rebuilt afterwards.
+
+use std::collections::BTreeSet;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::domain::services::AddressExtractor;
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::StatsHoldersRepository;
+use crate::utils::logging;
+
+#[derive(Debug, Clone, Default)]
+pub struct AddressBackfillSummary {
+    /// Charm rows given an address.
+    pub updated: u64,
+    /// Charm rows still without one (no address form, or no stored hex).
+    pub unresolved: u64,
+}
+
+/// Charms with a NULL address and the stored hex of their tx. `$1` is an
+/// optional network filter.
+const MISSING_ADDRESS_SQL: &str = r#"
+    SELECT c.txid, c.vout, c.network, t.raw->>'hex' AS hex
+      FROM charms c
+      LEFT JOIN transactions t ON t.txid = c.txid AND t.network = c.network
+     WHERE c.address IS NULL
+       AND ($1::text IS NULL OR c.network = $1)
+  ORDER BY c.network, c.txid, c.vout"#;
+
+const FILL_ADDRESS_SQL: &str = r#"
+    UPDATE charms
+       SET address = $4
+     WHERE txid = $1 AND vout = $2 AND network = $3 AND address IS NULL"#;
+
+pub async fn backfill_addresses(
+    conn: &DatabaseConnection,
+    network: Option<&str>,
+) -> Result<AddressBackfillSummary, DbError> {
+    let mut summary = AddressBackfillSummary::default();
+    let mut networks: BTreeSet<String> = BTreeSet::new();
+
+    let rows = conn
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            MISSING_ADDRESS_SQL,
+            [network.into()],
+        ))
+        .await?;
+    for row in &rows {
+        let txid: String = row.try_get("", "txid")?;
+        let vout: i32 = row.try_get("", "vout")?;
+        let row_network: String = row.try_get("", "network")?;
+        let hex: Option<String> = row.try_get("", "hex")?;
+
+        let address = hex.and_then(|hex| {
+            let outputs = AddressExtractor::output_addresses(&hex, &row_network).ok()?;
+            AddressExtractor::charm_output_address(&outputs, vout as usize, &txid, &row_network)
+        });
+        let Some(address) = address else {
+            summary.unresolved += 1;
+            continue;
+        };
+
+        // One statement covers every app_id row sharing this output.
+        let res = conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                FILL_ADDRESS_SQL,
+                [
+                    txid.into(),
+                    vout.into(),
+                    row_network.clone().into(),
+                    address.into(),
+                ],
+            ))
+            .await?;
+        summary.updated += res.rows_affected();
+        if res.rows_affected() > 0 {
+            networks.insert(row_network);
+        }
+    }
+
+    let holders = StatsHoldersRepository::new(conn.clone());
+    for net in &networks {
+        holders.rebuild_from_charms(net, None).await?;
+    }
+
+    logging::log_info(&format!(
+        "🏠 backfill-addresses: {} charm(s) updated, {} unresolved ({} candidate(s))",
+        summary.updated,
+        summary.unresolved,
+        rows.len()
+    ));
+    Ok(summary)
+}
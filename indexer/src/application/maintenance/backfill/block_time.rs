@@ -0,0 +1,115 @@
+//! `block_time`: date charms indexed before their block's header time was
+//! stored.
+//!
+//! The `m20260802_000001_block_time` migration filled what it could in one
+//! pass; rows written since by paths that did not carry the time, and
+//! heights whose `address_transactions` rows arrived later, stayed NULL.
+//! Keys are block heights across all networks. Per range it fills
+//! `block_status.block_time` from `address_transactions`, then
+//! `charms.block_time` and `charms_archive.block_time` from `block_status`.
+//! Only NULL columns are written, so re-running is harmless.
+
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DatabaseTransaction, DbBackend, Statement};
+
+use super::{Backfill, Step};
+use crate::infrastructure::persistence::error::DbError;
+
+const HEIGHTS_SQL: &str =
+    "SELECT MIN(block_height)::bigint AS lo, MAX(block_height)::bigint AS hi FROM block_status";
+
+/// Heights in `($1, $2]`.
+const BLOCK_STATUS_SQL: &str = r#"
+    UPDATE block_status b
+       SET block_time = t.block_time
+      FROM (SELECT DISTINCT ON (network, block_height)
+                   network, block_height,
+                   to_timestamp(block_time) AT TIME ZONE 'UTC' AS block_time
+              FROM address_transactions
+             WHERE block_height > $1 AND block_height <= $2 AND block_time IS NOT NULL
+          ORDER BY network, block_height) t
+     WHERE b.block_time IS NULL
+       AND b.network = t.network
+       AND b.block_height = t.block_height"#;
+
+const CHARMS_SQL: &str = r#"
+    UPDATE charms c
+       SET block_time = b.block_time
+      FROM block_status b
+     WHERE c.block_height > $1 AND c.block_height <= $2
+       AND c.block_time IS NULL
+       AND b.block_time IS NOT NULL
+       AND b.network = c.network
+       AND b.block_height = c.block_height"#;
+
+const CHARMS_ARCHIVE_SQL: &str = r#"
+    UPDATE charms_archive c
+       SET block_time = b.block_time
+      FROM block_status b
+     WHERE c.block_height > $1 AND c.block_height <= $2
+       AND c.block_time IS NULL
+       AND b.block_time IS NOT NULL
+       AND b.network = c.network
+       AND b.block_height = c.block_height"#;
+
+pub struct BlockTimeBackfill;
+
+#[async_trait]
+impl Backfill for BlockTimeBackfill {
+    fn name(&self) -> &'static str {
+        "block_time"
+    }
+
+    fn description(&self) -> &'static str {
+        "fill missing block times on block_status, charms and charms_archive"
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn step(
+        &self,
+        txn: &DatabaseTransaction,
+        after: Option<i64>,
+        batch_size: u64,
+    ) -> Result<Step, DbError> {
+        let Some(row) = txn
+            .query_one(Statement::from_string(
+                DbBackend::Postgres,
+                HEIGHTS_SQL.to_string(),
+            ))
+            .await?
+        else {
+            return Ok(Step::done());
+        };
+        let (Some(lo), Some(hi)) = (
+            row.try_get::<Option<i64>>("", "lo")?,
+            row.try_get::<Option<i64>>("", "hi")?,
+        ) else {
+            return Ok(Step::done());
+        };
+
+        let from = after.unwrap_or(lo - 1);
+        if from >= hi {
+            return Ok(Step::done());
+        }
+        let to = from.saturating_add(batch_size as i64).min(hi);
+
+        let mut rows = 0;
+        for sql in [BLOCK_STATUS_SQL, CHARMS_SQL, CHARMS_ARCHIVE_SQL] {
+            rows += txn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    sql,
+                    [from.into(), to.into()],
+                ))
+                .await?
+                .rows_affected();
+        }
+        Ok(Step {
+            last_key: Some(to),
+            rows,
+        })
+    }
+}
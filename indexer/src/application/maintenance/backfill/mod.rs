@@ -0,0 +1,326 @@
+//! Chunked, resumable backfills for one-off data repairs.
+//!
+//! A repair that would be one long `UPDATE` over a big table (and hold its
+//! locks against the live indexer for as long) is written as a `Backfill`
+//! instead: a name, a step that covers the next `batch_size` primary keys
+//! after a cursor, and whether a finished run may be repeated. The runner
+//! drives the steps:
+//!
+//! - each batch runs in its own transaction together with the move of the
+//!   cursor in `backfill_progress`, so a batch is either fully applied and
+//!   recorded or not at all;
+//! - an interrupted run (cancelled, crashed, failed batch) resumes after the
+//!   last committed batch, never before it;
+//! - between batches it sleeps long enough to stay under `rows_per_sec`
+//!   written rows, so the live indexer keeps the database;
+//! - every batch refreshes the row's `heartbeat_at` and counters, which is
+//!   what `GET /admin/backfills` shows. A run whose heartbeat goes stale is
+//!   taken over by the next claim.
+//!
+//! Runs start from `charms-indexer backfill <name>` or, through the admin
+//! API, as a `pending` row the live indexer's `BackfillWorker` picks up.
+
+pub mod block_time;
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use tokio_util::sync::CancellationToken;
+
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::{
+    BackfillProgress, BackfillProgressRepository,
+};
+use crate::utils::logging;
+
+pub use block_time::BlockTimeBackfill;
+
+/// What one step covered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// Highest key the batch covered; `None` when no keys were left.
+    pub last_key: Option<i64>,
+    /// Rows written, which the rate limit counts.
+    pub rows: u64,
+}
+
+impl Step {
+    /// Nothing left after the cursor.
+    pub fn done() -> Self {
+        Self {
+            last_key: None,
+            rows: 0,
+        }
+    }
+}
+
+#[async_trait]
+pub trait Backfill: Send + Sync {
+    /// Key of the backfill in `backfill_progress` and on the command line.
+    fn name(&self) -> &'static str;
+
+    /// One line for `backfill --list`.
+    fn description(&self) -> &'static str;
+
+    /// Whether applying a batch twice leaves the rows as applying it once
+    /// did. Only idempotent backfills can be restarted after finishing.
+    fn idempotent(&self) -> bool;
+
+    /// Process the keys after `after` (from the first key when `None`), at
+    /// most `batch_size` of them, inside `txn`.
+    async fn step(
+        &self,
+        txn: &DatabaseTransaction,
+        after: Option<i64>,
+        batch_size: u64,
+    ) -> Result<Step, DbError>;
+}
+
+/// Every backfill the indexer knows, by name.
+pub fn registry() -> Vec<Box<dyn Backfill>> {
+    vec![Box::new(BlockTimeBackfill)]
+}
+
+pub fn find(name: &str) -> Option<Box<dyn Backfill>> {
+    registry().into_iter().find(|b| b.name() == name)
+}
+
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    /// Keys per batch.
+    pub batch_size: u64,
+    /// Written rows per second the runner stays under; 0 means no limit.
+    pub rows_per_sec: u64,
+    /// A run not heartbeating for this long may be taken over.
+    pub stale_after: Duration,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 1000,
+            rows_per_sec: 5000,
+            stale_after: Duration::from_secs(600),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkedBackfillSummary {
+    pub name: String,
+    /// Every key is covered; otherwise the run was interrupted.
+    pub finished: bool,
+    pub last_key: Option<i64>,
+    /// Totals over every run since the backfill (re)started.
+    pub rows_updated: i64,
+    pub batches: i32,
+}
+
+impl ChunkedBackfillSummary {
+    fn from_progress(progress: &BackfillProgress, finished: bool) -> Self {
+        Self {
+            name: progress.name.clone(),
+            finished,
+            last_key: progress.last_key,
+            rows_updated: progress.rows_updated,
+            batches: progress.batches,
+        }
+    }
+}
+
+pub struct BackfillRunner {
+    conn: DatabaseConnection,
+    repo: BackfillProgressRepository,
+    cfg: BackfillConfig,
+}
+
+impl BackfillRunner {
+    pub fn new(conn: DatabaseConnection, cfg: BackfillConfig) -> Self {
+        let repo = BackfillProgressRepository::new(conn.clone());
+        Self { conn, repo, cfg }
+    }
+
+    /// Give every registered backfill its row, so operators can list and
+    /// request them.
+    pub async fn register_all(&self) -> Result<(), DbError> {
+        for backfill in registry() {
+            self.repo
+                .register(backfill.name(), backfill.idempotent())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Run `backfill` from its cursor (from the start with `restart`)
+    /// until done or `cancel` fires. A finished backfill is reported as
+    /// such without running.
+    pub async fn run(
+        &self,
+        backfill: &dyn Backfill,
+        restart: bool,
+        cancel: &CancellationToken,
+    ) -> Result<ChunkedBackfillSummary, DbError> {
+        let name = backfill.name();
+        if restart && !backfill.idempotent() {
+            return Err(DbError::Other(format!(
+                "backfill {} is not idempotent and cannot be restarted",
+                name
+            )));
+        }
+        self.repo.register(name, backfill.idempotent()).await?;
+        let stale = self.cfg.stale_after.as_secs_f64();
+        if let Some(progress) = self.repo.claim(name, stale, restart).await? {
+            return self.run_claimed(backfill, progress, cancel, false).await;
+        }
+        match self.repo.get(name).await? {
+            Some(progress) if progress.status == "done" => {
+                Ok(ChunkedBackfillSummary::from_progress(&progress, true))
+            }
+            _ => Err(DbError::Other(format!(
+                "backfill {} is running elsewhere",
+                name
+            ))),
+        }
+    }
+
+    /// Claim the oldest requested backfill and run it. `None` when nothing
+    /// was requested. A run cut short by `cancel` goes back to `pending`,
+    /// so the next indexer start carries on with it.
+    pub async fn run_pending(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Option<Result<ChunkedBackfillSummary, DbError>> {
+        let stale = self.cfg.stale_after.as_secs_f64();
+        let progress = match self.repo.claim_pending(stale).await {
+            Ok(progress) => progress?,
+            Err(e) => return Some(Err(e)),
+        };
+        let Some(backfill) = find(&progress.name) else {
+            let error = format!("unknown backfill {}", progress.name);
+            return Some(match self.repo.fail(&progress.name, &error).await {
+                Ok(()) => Err(DbError::Other(error)),
+                Err(e) => Err(e),
+            });
+        };
+        Some(
+            self.run_claimed(backfill.as_ref(), progress, cancel, true)
+                .await,
+        )
+    }
+
+    async fn run_claimed(
+        &self,
+        backfill: &dyn Backfill,
+        progress: BackfillProgress,
+        cancel: &CancellationToken,
+        requeue: bool,
+    ) -> Result<ChunkedBackfillSummary, DbError> {
+        let name = backfill.name();
+        logging::log_info(&format!(
+            "[backfill] 🧩 {} starting after key {}",
+            name,
+            progress
+                .last_key
+                .map_or_else(|| "(start)".to_string(), |k| k.to_string())
+        ));
+        let mut summary = ChunkedBackfillSummary::from_progress(&progress, false);
+        loop {
+            if cancel.is_cancelled() {
+                if requeue {
+                    self.repo.requeue(name).await?;
+                } else {
+                    self.repo.pause(name).await?;
+                }
+                logging::log_info(&format!(
+                    "[backfill] ⏸️ {} paused after key {:?} ({} row(s) so far)",
+                    name, summary.last_key, summary.rows_updated
+                ));
+                return Ok(summary);
+            }
+
+            let started = Instant::now();
+            let step = match self.batch(backfill, summary.last_key).await {
+                Ok(step) => step,
+                Err(e) => {
+                    if let Err(close) = self.repo.fail(name, &e.to_string()).await {
+                        logging::log_warning(&format!(
+                            "[backfill] ⚠️ Failed to record the failure of {}: {}",
+                            name, close
+                        ));
+                    }
+                    return Err(e);
+                }
+            };
+            let Some(last_key) = step.last_key else {
+                self.repo.finish(name).await?;
+                summary.finished = true;
+                logging::log_info(&format!(
+                    "[backfill] ✅ {} done: {} row(s) in {} batch(es)",
+                    name, summary.rows_updated, summary.batches
+                ));
+                return Ok(summary);
+            };
+            summary.last_key = Some(last_key);
+            summary.rows_updated += step.rows as i64;
+            summary.batches += 1;
+
+            if let Some(wait) = pace(step.rows, self.cfg.rows_per_sec, started.elapsed()) {
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+        }
+    }
+
+    /// One step and its cursor move, committed together.
+    async fn batch(&self, backfill: &dyn Backfill, after: Option<i64>) -> Result<Step, DbError> {
+        let txn = self.conn.begin().await?;
+        let step = backfill.step(&txn, after, self.cfg.batch_size).await?;
+        if let Some(last_key) = step.last_key {
+            self.repo
+                .advance(&txn, backfill.name(), last_key, step.rows)
+                .await?;
+        }
+        txn.commit().await?;
+        Ok(step)
+    }
+}
+
+/// Remaining time `rows` written in `took` must be spread over at
+/// `rows_per_sec` (0 = unlimited).
+pub fn pace(rows: u64, rows_per_sec: u64, took: Duration) -> Option<Duration> {
+    if rows_per_sec == 0 || rows == 0 {
+        return None;
+    }
+    let budget = Duration::from_secs_f64(rows as f64 / rows_per_sec as f64);
+    budget.checked_sub(took).filter(|wait| !wait.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pace_spreads_rows_over_the_rate() {
+        assert_eq!(
+            pace(1000, 500, Duration::from_millis(500)),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(pace(1000, 500, Duration::from_secs(3)), None);
+        assert_eq!(pace(1000, 0, Duration::ZERO), None);
+        assert_eq!(pace(0, 500, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn registered_names_are_unique() {
+        let names: Vec<_> = registry().iter().map(|b| b.name()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), names.len());
+        assert!(find("block_time").is_some());
+        assert!(find("nope").is_none());
+    }
+}
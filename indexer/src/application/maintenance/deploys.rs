@@ -0,0 +1,80 @@
+//! Backfill where each asset was deployed (assets indexed before
+//! `deploy_txid` existed, or whose origin was taken from a later write).
+//!
+//! The origin is the earliest stored charm of the app_id: lowest block, then
+//! tx position, then vout. The deployer is the first address that deploy
+//! transaction sent the app to. A recorded origin is only replaced by a
+//! strictly earlier block, the same rule the live path follows, so the job
+//! can be re-run at any time.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+use crate::utils::logging;
+
+#[derive(Debug, Clone, Default)]
+pub struct DeployBackfillSummary {
+    /// Asset rows given a (new, earlier) deploy origin.
+    pub updated: u64,
+    /// Asset rows still without one (no mined charm stored for the app_id).
+    pub unresolved: u64,
+}
+
+/// `$1` is an optional network filter.
+const FILL_DEPLOYS_SQL: &str = r#"
+    WITH first_charm AS (
+        SELECT DISTINCT ON (network, app_id) network, app_id, txid, block_height
+          FROM charms
+         WHERE block_height IS NOT NULL
+           AND ($1::text IS NULL OR network = $1)
+      ORDER BY network, app_id, block_height, tx_ordinal NULLS LAST, vout
+    )
+    UPDATE assets a
+       SET deploy_txid = f.txid,
+           deploy_block_height = f.block_height,
+           deployer_address = (SELECT c.address FROM charms c
+                                WHERE c.app_id = f.app_id AND c.network = f.network
+                                  AND c.txid = f.txid AND c.address IS NOT NULL
+                             ORDER BY c.vout LIMIT 1),
+           updated_at = NOW()
+      FROM first_charm f
+     WHERE a.app_id = f.app_id AND a.network = f.network
+       AND (a.deploy_block_height IS NULL OR a.deploy_block_height > f.block_height)"#;
+
+const UNRESOLVED_SQL: &str = r#"
+    SELECT COUNT(*) AS n FROM assets
+     WHERE deploy_txid IS NULL
+       AND ($1::text IS NULL OR network = $1)"#;
+
+pub async fn backfill_deploys(
+    conn: &DatabaseConnection,
+    network: Option<&str>,
+) -> Result<DeployBackfillSummary, DbError> {
+    let res = conn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            FILL_DEPLOYS_SQL,
+            [network.into()],
+        ))
+        .await?;
+    let unresolved: i64 = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            UNRESOLVED_SQL,
+            [network.into()],
+        ))
+        .await?
+        .map(|row| row.try_get::<i64>("", "n"))
+        .transpose()?
+        .unwrap_or(0);
+
+    let summary = DeployBackfillSummary {
+        updated: res.rows_affected(),
+        unresolved: unresolved as u64,
+    };
+    logging::log_info(&format!(
+        "🚀 backfill-deploys: {} asset(s) updated, {} without a stored charm",
+        summary.updated, summary.unresolved
+    ));
+    Ok(summary)
+}
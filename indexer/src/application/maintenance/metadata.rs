@@ -0,0 +1,139 @@
+//! Backfill asset metadata that the live path missed (assets created before
+//! metadata parsing existed, or whose first charm lacked it).
+//!
+//! NFT rows are filled from the earliest stored charm of the same app_id,
+//! using the same parsing as block detection; NFTs without a declared
+//! collection are grouped by that charm's address. Token rows then inherit name,
+//! symbol and description from their parent `n/` row. Only NULL fields are
+//! written; existing values are never overwritten.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::application::indexer::block::detection::{
+    nft_metadata_from_charm_json, parse_metadata_fields,
+};
+use crate::domain::models::asset_metadata::deployer_collection;
+use crate::infrastructure::persistence::error::DbError;
+use crate::utils::logging;
+
+#[derive(Debug, Clone, Default)]
+pub struct BackfillSummary {
+    /// NFT rows with at least one field filled in.
+    pub nfts_updated: u64,
+    /// Token rows that inherited a field from their parent NFT.
+    pub tokens_updated: u64,
+}
+
+/// NFT assets with a metadata gap, paired with their earliest charm's data
+/// and address. `$1` is an optional network filter.
+const NFT_GAPS_SQL: &str = r#"
+    SELECT a.app_id, a.network, c.data, c.address
+      FROM assets a
+      JOIN LATERAL (
+            SELECT data, address FROM charms
+             WHERE app_id = a.app_id AND network = a.network
+          ORDER BY block_height ASC NULLS LAST, tx_ordinal ASC NULLS LAST, vout ASC
+             LIMIT 1
+      ) c ON true
+     WHERE a.asset_type = 'nft'
+       AND ($1::text IS NULL OR a.network = $1)
+       AND (a.name IS NULL OR a.symbol IS NULL OR a.description IS NULL OR a.image_url IS NULL
+         OR a.collection IS NULL)"#;
+
+const FILL_NFT_SQL: &str = r#"
+    UPDATE assets
+       SET name = COALESCE(name, $3),
+           symbol = COALESCE(symbol, $4),
+           description = COALESCE(description, $5),
+           image_url = COALESCE(image_url, $6),
+           collection = COALESCE(collection, $7),
+           updated_at = NOW()
+     WHERE app_id = $1 AND network = $2
+       AND ((name IS NULL AND $3::text IS NOT NULL)
+         OR (symbol IS NULL AND $4::text IS NOT NULL)
+         OR (description IS NULL AND $5::text IS NOT NULL)
+         OR (image_url IS NULL AND $6::text IS NOT NULL)
+         OR (collection IS NULL AND $7::text IS NOT NULL))"#;
+
+const INHERIT_TOKENS_SQL: &str = r#"
+    UPDATE assets t
+       SET name = COALESCE(t.name, n.name),
+           symbol = COALESCE(t.symbol, n.symbol),
+           description = COALESCE(t.description, n.description),
+           updated_at = NOW()
+      FROM assets n
+     WHERE t.asset_type = 'token'
+       AND n.asset_type = 'nft'
+       AND n.network = t.network
+       AND n.app_id = 'n/' || substr(t.app_id, 3)
+       AND ($1::text IS NULL OR t.network = $1)
+       AND ((t.name IS NULL AND n.name IS NOT NULL)
+         OR (t.symbol IS NULL AND n.symbol IS NOT NULL)
+         OR (t.description IS NULL AND n.description IS NOT NULL))"#;
+
+pub async fn backfill_metadata(
+    conn: &DatabaseConnection,
+    network: Option<&str>,
+) -> Result<BackfillSummary, DbError> {
+    let mut summary = BackfillSummary::default();
+
+    let rows = conn
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            NFT_GAPS_SQL,
+            [network.into()],
+        ))
+        .await?;
+    for row in &rows {
+        let app_id: String = row.try_get("", "app_id")?;
+        let row_network: String = row.try_get("", "network")?;
+        let data: serde_json::Value = row.try_get("", "data")?;
+        let address: Option<String> = row.try_get("", "address")?;
+
+        let parsed = parse_metadata_fields(&nft_metadata_from_charm_json(&data));
+        let collection = parsed
+            .collection
+            .or_else(|| address.as_deref().map(deployer_collection));
+        if parsed.name.is_none()
+            && parsed.symbol.is_none()
+            && parsed.description.is_none()
+            && parsed.image_url.is_none()
+            && collection.is_none()
+        {
+            continue;
+        }
+        let res = conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                FILL_NFT_SQL,
+                [
+                    app_id.into(),
+                    row_network.into(),
+                    parsed.name.into(),
+                    parsed.symbol.into(),
+                    parsed.description.into(),
+                    parsed.image_url.into(),
+                    collection.into(),
+                ],
+            ))
+            .await?;
+        summary.nfts_updated += res.rows_affected();
+    }
+
+    let res = conn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            INHERIT_TOKENS_SQL,
+            [network.into()],
+        ))
+        .await?;
+    summary.tokens_updated = res.rows_affected();
+
+    logging::log_info(&format!(
+        "🖼️ backfill-metadata: {} NFT(s) and {} token(s) updated ({} candidate NFT(s))",
+        summary.nfts_updated,
+        summary.tokens_updated,
+        rows.len()
+    ));
+    Ok(summary)
+}
@@ -0,0 +1,29 @@
+//! One-shot maintenance jobs run from the CLI instead of the live loop:
+//! - `reindex`: re-run the block pipeline over a height range
+//! - `metadata`: fill missing asset name/symbol/image from stored charms
+//! - `addresses`: re-derive missing charm addresses from stored tx hex
+//! - `backfill`: chunked, resumable repairs with a stored cursor
+//! - `deploys`: record the first-seen transaction of each asset
+//! - `spell_outputs`: derive each spell's per-output app map from its JSON
+//! - `snapshot`: export/import one network's indexed tables for bootstrap
+//!
+//! Holder recomputation lives on `StatsHoldersRepository::rebuild_from_charms`
+//! and the verifier in `application::verify`.
+
+pub mod addresses;
+pub mod backfill;
+pub mod deploys;
+pub mod metadata;
+pub mod reindex;
+pub mod reindex_report;
+pub mod snapshot;
+pub mod spell_outputs;
+
+pub use addresses::{backfill_addresses, AddressBackfillSummary};
+pub use backfill::{Backfill, BackfillConfig, BackfillRunner, ChunkedBackfillSummary};
+pub use deploys::{backfill_deploys, DeployBackfillSummary};
+pub use metadata::{backfill_metadata, BackfillSummary};
+pub use reindex::{reindex, reindex_with_client, ReindexOptions, ReindexSummary};
+pub use reindex_report::{reindex_report, ParserError, ReindexReport, SupplyChange};
+pub use snapshot::{export_snapshot, import_snapshot, ExportOptions, SnapshotManifest};
+pub use spell_outputs::{backfill_spell_outputs, SpellOutputsBackfillSummary};
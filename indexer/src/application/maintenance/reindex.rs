@@ -0,0 +1,457 @@
+//! Re-run `BlockProcessor::process_block` over a height range.
+//!
+//! Every step of the block pipeline is idempotent (charms and transactions
+//! upsert, supply keeps the highest declared value and any raise is recorded
+//! in `supply_changes` as `reindex`, a tx whose supply change the live
+//! indexer already recorded is not applied again, mint events are keyed on
+//! (txid, app_id) so the range's issuance history is re-emitted without
+//! duplicates), except holder deltas:
+//! the `last_updated_block` gate skips blocks at or below a holder's last
+//! update. Holders of the network are therefore rebuilt from `charms` once
+//! the range is done.
+//!
+//! Charms a run writes are stamped with its run id (see `WriteStamp`). With
+//! `parser_revision_lt`, only heights holding a charm written by an older
+//! `NativeCharmParser::PARSER_REVISION` (or never stamped) are reprocessed.
+//!
+//! Charms pruned into `charms_archive` are moved back into `charms` before
+//! a real run, so the range is rewritten in place with its spent flags
+//! intact; the archiver prunes them again later. Counts and the dry-run
+//! report read both tables.
+//!
+//! A height that fails does not stop the run: the error and the attempt are
+//! recorded in `block_status` and the run moves on. A height that failed
+//! `max_attempts` times is sidelined, and later runs leave it out until
+//! `retry_failed` resets the range's counters. A successful run of the block,
+//! here or live, clears its record. Only an unreachable node, which would fail
+//! every height alike, ends the run.
+//!
+//! A real run holds the network's `reindex_locks` row from start to end, and
+//! the live block loop stands still while it is held (see `PauseGate`), so
+//! the two never process the same blocks at once. The run refuses to start
+//! while a live indexer heartbeats on the network unless `force` is set; a
+//! crashed run's lock lapses after `LOCK_STALE_SECS`.
+//!
+//! A dry run makes no RPC calls and no writes; it replays the range's stored
+//! hex into an impact report (see `reindex_report`).
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use crate::application::indexer::block::BlockProcessor;
+use crate::application::indexer::leader::REPLICA_STALE_SECS;
+use crate::config::{AppConfig, NetworkId, NetworkType};
+use crate::domain::errors::BlockProcessorError;
+use crate::domain::models::WriteStamp;
+use crate::domain::services::CharmService;
+use crate::infrastructure::bitcoin::{BitcoinClient, SimpleBitcoinClient};
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::SupplyChangeReason;
+use crate::infrastructure::persistence::Repositories;
+use crate::utils::logging;
+
+use super::reindex_report::{reindex_report, ReindexReport};
+
+/// A reindex lock not refreshed for this long no longer holds the network.
+pub const LOCK_STALE_SECS: i64 = 300;
+
+/// Interval between two refreshes of the run's lock.
+const LOCK_HEARTBEAT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct ReindexOptions {
+    pub network: String,
+    pub from: u64,
+    /// Inclusive upper bound.
+    pub to: u64,
+    /// Only report what would change; no RPC calls, no writes.
+    pub dry_run: bool,
+    /// Also write the dry-run report to this JSON file.
+    pub report_path: Option<PathBuf>,
+    /// Only reprocess heights with charms written by a parser revision
+    /// below this one.
+    pub parser_revision_lt: Option<i32>,
+    /// Heights attempted between two progress lines.
+    pub batch_size: u64,
+    /// Failed attempts after which a height is sidelined.
+    pub max_attempts: u32,
+    /// Reset the range's failure counters first, so sidelined heights are
+    /// attempted again.
+    pub retry_failed: bool,
+    /// Run even though a live indexer is running on the network; its block
+    /// processing pauses until the run ends.
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReindexSummary {
+    /// Blocks selected for reprocessing.
+    pub blocks: u64,
+    /// Charms stored in the range before the run, archived ones included.
+    pub charms_in_range: u64,
+    /// Holder rows written by the final rebuild; 0 on a dry run.
+    pub holders_rebuilt: u64,
+    /// Impact report; set on a dry run only.
+    pub report: Option<ReindexReport>,
+    /// Stamped on the charms this run wrote; `None` on a dry run.
+    pub run_id: Option<String>,
+    /// Heights that failed in this run.
+    pub failed: Vec<u64>,
+    /// Heights at the attempt limit: left out of this run, or sidelined by
+    /// it.
+    pub sidelined: Vec<u64>,
+}
+
+pub async fn reindex(
+    config: &AppConfig,
+    repos: &Repositories,
+    opts: &ReindexOptions,
+) -> Result<ReindexSummary, BlockProcessorError> {
+    if opts.dry_run {
+        let (mut summary, _) = start(repos, opts).await?;
+        let report = reindex_report(repos, &opts.network, opts.from, opts.to).await?;
+        if let Some(path) = &opts.report_path {
+            report.write_json(path).map_err(|e| {
+                BlockProcessorError::ProcessingError(format!(
+                    "writing report to {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        }
+        summary.report = Some(report);
+        return Ok(summary);
+    }
+
+    let network = opts.network.as_str();
+    let bitcoin_config = config.get_bitcoin_config(network).ok_or_else(|| {
+        BlockProcessorError::ConfigError(format!(
+            "Bitcoin configuration for network '{}' not found",
+            network
+        ))
+    })?;
+    let simple_client =
+        SimpleBitcoinClient::new(bitcoin_config).map_err(BlockProcessorError::BitcoinClientError)?;
+    reindex_with_client(
+        BitcoinClient::from_simple_client(simple_client),
+        repos,
+        opts,
+    )
+    .await
+}
+
+/// Range check, stored charm count, the heights to reprocess and the
+/// opening log line. A real run also restores the range's archived charms.
+async fn start(
+    repos: &Repositories,
+    opts: &ReindexOptions,
+) -> Result<(ReindexSummary, Vec<u64>), BlockProcessorError> {
+    if opts.from > opts.to {
+        return Err(BlockProcessorError::ConfigError(format!(
+            "--from {} is above --to {}",
+            opts.from, opts.to
+        )));
+    }
+    let network = opts.network.as_str();
+    let charms_in_range = count_charms(repos, network, opts.from, opts.to).await?;
+    if !opts.dry_run {
+        let restored = repos
+            .charm
+            .restore_archived(network, opts.from, opts.to)
+            .await?;
+        if restored > 0 {
+            logging::log_info(&format!(
+                "[{}] 🗄️ reindex restored {} archived charm(s)",
+                network, restored
+            ));
+        }
+    }
+    let mut heights: Vec<u64> = match opts.parser_revision_lt {
+        Some(revision) => {
+            repos
+                .charm
+                .heights_below_parser_revision(network, opts.from, opts.to, revision)
+                .await?
+        }
+        None => (opts.from..=opts.to).collect(),
+    };
+    let mut sidelined = Vec::new();
+    if !opts.dry_run {
+        let network_id = NetworkId::new(NetworkType::Bitcoin, network);
+        let (from, to) = (opts.from as i32, opts.to as i32);
+        if opts.retry_failed {
+            let reset = repos
+                .block_status
+                .reset_failures(&network_id, from, to)
+                .await?;
+            logging::log_info(&format!(
+                "[{}] 🔁 reindex reset the failure count of {} block(s)",
+                network, reset
+            ));
+        }
+        sidelined = repos
+            .block_status
+            .get_sidelined(&network_id, from, to, opts.max_attempts as i32)
+            .await?
+            .into_iter()
+            .map(|h| h as u64)
+            .collect();
+        heights.retain(|h| !sidelined.contains(h));
+    }
+    let summary = ReindexSummary {
+        blocks: heights.len() as u64,
+        charms_in_range,
+        sidelined,
+        ..Default::default()
+    };
+
+    logging::log_info(&format!(
+        "[{}] 🔁 reindex {}..={} ({} block(s){}, {} stored charm(s)){}",
+        network,
+        opts.from,
+        opts.to,
+        summary.blocks,
+        match opts.parser_revision_lt {
+            Some(revision) => format!(" with charms below parser revision {}", revision),
+            None => String::new(),
+        },
+        charms_in_range,
+        match (opts.dry_run, summary.sidelined.len()) {
+            (true, _) => " — dry run".to_string(),
+            (false, 0) => String::new(),
+            (false, n) => format!(", {} sidelined block(s) left out", n),
+        }
+    ));
+    Ok((summary, heights))
+}
+
+/// The real run against `client`, for callers that bring their own provider.
+pub async fn reindex_with_client(
+    client: BitcoinClient,
+    repos: &Repositories,
+    opts: &ReindexOptions,
+) -> Result<ReindexSummary, BlockProcessorError> {
+    let run_id = run_id(opts);
+    lock_network(repos, opts, &run_id).await?;
+    let result = match start(repos, opts).await {
+        Ok((summary, heights)) => {
+            reindex_blocks(client, repos, opts, &run_id, summary, heights).await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = repos.reindex_locks.release(&opts.network, &run_id).await {
+        logging::log_warning(&format!(
+            "[{}] ⚠️ reindex {} could not release its lock (it lapses after {}s): {}",
+            opts.network, run_id, LOCK_STALE_SECS, e
+        ));
+    }
+    result
+}
+
+/// Refuse to run beside a live indexer unless `force` is set, then take the
+/// network's reindex lock for `run_id`.
+async fn lock_network(
+    repos: &Repositories,
+    opts: &ReindexOptions,
+    run_id: &str,
+) -> Result<(), BlockProcessorError> {
+    let network = opts.network.as_str();
+    let live = repos
+        .indexer_replicas
+        .live(network, REPLICA_STALE_SECS)
+        .await?;
+    if let Some(replica) = live.first() {
+        if !opts.force {
+            return Err(BlockProcessorError::ConfigError(format!(
+                "live indexer {} ({}) is running on {}; stop it first, or pass --force \
+                 to pause its block processing for the run",
+                replica.instance_id, replica.role, network
+            )));
+        }
+        logging::log_warning(&format!(
+            "[{}] ⚠️ reindex --force: live indexer {} pauses block processing until {} ends",
+            network, replica.instance_id, run_id
+        ));
+    }
+    repos
+        .reindex_locks
+        .acquire(network, run_id, opts.from, opts.to, LOCK_STALE_SECS)
+        .await?
+        .map_err(|holder| {
+            BlockProcessorError::ConfigError(format!(
+                "reindex {} is already running on {} (heights {}..={}, started {})",
+                holder.run_id, network, holder.from_height, holder.to_height, holder.started_at
+            ))
+        })
+}
+
+/// Run id stamped on the charms a run writes: network, range and start time.
+fn run_id(opts: &ReindexOptions) -> String {
+    format!(
+        "{}:{}-{}@{}",
+        opts.network,
+        opts.from,
+        opts.to,
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+    )
+}
+
+async fn reindex_blocks(
+    client: BitcoinClient,
+    repos: &Repositories,
+    opts: &ReindexOptions,
+    run_id: &str,
+    mut summary: ReindexSummary,
+    heights: Vec<u64>,
+) -> Result<ReindexSummary, BlockProcessorError> {
+    let network = opts.network.as_str();
+    let charm_service = CharmService::new(
+        repos
+            .charm
+            .clone()
+            .with_stamp(WriteStamp::reindex(run_id.to_string())),
+        repos
+            .asset
+            .clone()
+            .with_supply_reason(SupplyChangeReason::Reindex),
+        repos.stats_holders.clone(),
+        repos.dex_orders.clone(),
+    );
+    let processor = BlockProcessor::new(
+        client,
+        charm_service,
+        repos,
+    );
+    let network_id = NetworkId::new(NetworkType::Bitcoin, network);
+
+    let batch_size = opts.batch_size.max(1);
+    let mut attempted = 0u64;
+    let mut next = 0;
+    let mut heartbeat_at = Instant::now();
+    while let Some(&height) = heights.get(next) {
+        if heartbeat_at.elapsed() >= LOCK_HEARTBEAT {
+            heartbeat(repos, network, run_id).await;
+            heartbeat_at = Instant::now();
+        }
+        match processor.process_block(height, &network_id).await {
+            Ok(()) => next += 1,
+            // The stored chain diverged below this height and was rolled
+            // back; resume at the first selected height after the common
+            // ancestor.
+            Err(BlockProcessorError::ReorgRolledBackTo(h)) => {
+                logging::log_warning(&format!(
+                    "[{}] ⚠️ reindex hit a reorg, resuming from {}",
+                    network,
+                    h + 1
+                ));
+                next = heights.partition_point(|&selected| selected <= h);
+                continue;
+            }
+            // The node is unreachable after the handler's retries; every
+            // height would fail alike.
+            Err(BlockProcessorError::BitcoinClientError(e)) if e.is_retryable() => {
+                return Err(BlockProcessorError::BitcoinClientError(e));
+            }
+            Err(e) => {
+                record_failure(repos, opts, &network_id, height, &e, &mut summary).await?;
+                next += 1;
+            }
+        }
+        attempted += 1;
+        if attempted.is_multiple_of(batch_size) {
+            logging::log_info(&format!(
+                "[{}] 🔁 reindex progress: {}/{} block(s), {} failed, {} sidelined",
+                network,
+                next,
+                heights.len(),
+                summary.failed.len(),
+                summary.sidelined.len()
+            ));
+        }
+    }
+
+    heartbeat(repos, network, run_id).await;
+    summary.holders_rebuilt = repos
+        .stats_holders
+        .rebuild_from_charms(network, None)
+        .await?;
+    logging::log_info(&format!(
+        "[{}] ✅ reindex {} done, {} holder row(s) rebuilt, {} block(s) failed, {} sidelined",
+        network,
+        run_id,
+        summary.holders_rebuilt,
+        summary.failed.len(),
+        summary.sidelined.len()
+    ));
+    summary.run_id = Some(run_id.to_string());
+    Ok(summary)
+}
+
+/// Refresh the run's lock. A failure only shortens the time the lock holds
+/// if the run then stalls, so it is logged and the run goes on.
+async fn heartbeat(repos: &Repositories, network: &str, run_id: &str) {
+    if let Err(e) = repos.reindex_locks.heartbeat(network, run_id).await {
+        logging::log_warning(&format!(
+            "[{}] ⚠️ reindex {} could not refresh its lock: {}",
+            network, run_id, e
+        ));
+    }
+}
+
+/// Record `error` at `height` and sideline the height once it reaches
+/// `max_attempts`.
+async fn record_failure(
+    repos: &Repositories,
+    opts: &ReindexOptions,
+    network_id: &NetworkId,
+    height: u64,
+    error: &BlockProcessorError,
+    summary: &mut ReindexSummary,
+) -> Result<(), BlockProcessorError> {
+    let attempts = repos
+        .block_status
+        .record_failure(height as i32, &error.to_string(), network_id)
+        .await?;
+    summary.failed.push(height);
+    if attempts >= opts.max_attempts as i32 {
+        summary.sidelined.push(height);
+        logging::log_warning(&format!(
+            "[{}] ⚠️ reindex: block {} failed {} time(s), sidelined until --retry-failed: {}",
+            network_id.name, height, attempts, error
+        ));
+    } else {
+        logging::log_warning(&format!(
+            "[{}] ⚠️ reindex: block {} failed (attempt {}/{}): {}",
+            network_id.name, height, attempts, opts.max_attempts, error
+        ));
+    }
+    Ok(())
+}
+
+async fn count_charms(
+    repos: &Repositories,
+    network: &str,
+    from: u64,
+    to: u64,
+) -> Result<u64, BlockProcessorError> {
+    let conn = repos.mempool_spends.get_connection();
+    let row = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT (SELECT COUNT(*) FROM charms \
+                      WHERE network = $1 AND block_height BETWEEN $2 AND $3) \
+                  + (SELECT COUNT(*) FROM charms_archive \
+                      WHERE network = $1 AND block_height BETWEEN $2 AND $3) AS n",
+            [network.into(), (from as i64).into(), (to as i64).into()],
+        ))
+        .await
+        .map_err(DbError::from)?;
+    Ok(match row {
+        Some(r) => r
+            .try_get::<i64>("", "n")
+            .map_err(DbError::from)? as u64,
+        None => 0,
+    })
+}
@@ -0,0 +1,509 @@
+//! Impact report for `reindex --dry-run`.
+//!
+//! Replays detection over the hex stored in `transactions` for the range (no
+//! RPC) and feeds the batches into an in-memory projection instead of the
+//! repositories. Charm upserts, spends, asset supply and the final holder
+//! rebuild are projected against the current tables with the rules their
+//! writers apply. Detection gets no DEX or pending-spell write target, and
+//! nothing in this module issues a write.
+//!
+//! Stored hex only covers charm transactions, so spends by transactions
+//! without a spell are not seen, nor are spells the live path never stored.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+use rust_decimal::Decimal;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::application::indexer::block::detection::{self, ExtractedTx, TxSource};
+use crate::application::indexer::block::inflight::InflightBudget;
+use crate::application::indexer::block::{AssetBatchItem, CharmBatchItem};
+use crate::config::{NetworkId, NetworkType};
+use crate::domain::models::AssetType;
+use crate::domain::services::{CharmService, NativeCharmParser};
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::stats_holders_repository::EXPECTED_HOLDERS_CTE;
+use crate::infrastructure::persistence::Repositories;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReindexReport {
+    pub network: String,
+    pub from: u64,
+    pub to: u64,
+    pub blocks_scanned: u64,
+    /// Stored transactions whose spell parsed and verified.
+    pub spells_parsed: u64,
+    pub charms_inserted: u64,
+    /// Existing charms whose tags or operation the upsert would refresh (the
+    /// only columns it updates).
+    pub charms_updated: u64,
+    /// Unspent charms the range's inputs would mark spent.
+    pub charms_spent: u64,
+    /// Assets created or whose `total_supply` would move, by app_id.
+    pub supply_changes: Vec<SupplyChange>,
+    /// `stats_holders` rows the final rebuild would insert, change or delete.
+    pub holders_added: u64,
+    pub holders_changed: u64,
+    pub holders_removed: u64,
+    pub parser_errors: Vec<ParserError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SupplyChange {
+    pub app_id: String,
+    /// `None` when the asset row does not exist yet.
+    pub old: Option<Decimal>,
+    pub new: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParserError {
+    pub txid: String,
+    pub height: u64,
+    pub error: String,
+}
+
+impl ReindexReport {
+    /// Plain-text rendering for the CLI.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "[{}] reindex dry run {}..={}",
+            self.network, self.from, self.to
+        );
+        let _ = writeln!(out, "  blocks scanned      {}", self.blocks_scanned);
+        let _ = writeln!(out, "  spells parsed       {}", self.spells_parsed);
+        let _ = writeln!(
+            out,
+            "  charms              +{} inserted, {} updated, {} spent",
+            self.charms_inserted, self.charms_updated, self.charms_spent
+        );
+        let _ = writeln!(out, "  supply changes      {}", self.supply_changes.len());
+        for c in &self.supply_changes {
+            let old = c.old.map_or_else(|| "(new)".to_string(), |d| d.to_string());
+            let _ = writeln!(out, "         - {} {} -> {}", c.app_id, old, c.new);
+        }
+        let _ = writeln!(
+            out,
+            "  holders             +{} added, {} changed, -{} removed",
+            self.holders_added, self.holders_changed, self.holders_removed
+        );
+        let _ = writeln!(out, "  parser errors       {}", self.parser_errors.len());
+        for e in &self.parser_errors {
+            let _ = writeln!(out, "         - {} @{}: {}", e.txid, e.height, e.error);
+        }
+        out
+    }
+
+    /// Write the report as pretty-printed JSON.
+    pub fn write_json(&self, path: &Path) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+/// Stored charm-bearing txs of the range, in block order. `$1` network,
+/// `$2..$3` heights.
+const STORED_TXS_SQL: &str = r#"
+    SELECT txid, block_height, ordinal, raw->>'hex' AS hex
+      FROM transactions
+     WHERE network = $1 AND block_height BETWEEN $2 AND $3
+  ORDER BY block_height, ordinal"#;
+
+/// Current rows for a batch of txids (`$2` is a JSON array), archived ones
+/// included: a real run restores them and rewrites them in place.
+const EXISTING_CHARMS_SQL: &str = r#"
+    SELECT txid, vout, app_id, tags, operation
+      FROM charms
+     WHERE network = $1
+       AND txid IN (SELECT jsonb_array_elements_text($2::jsonb))
+     UNION ALL
+    SELECT txid, vout, app_id, tags, operation
+      FROM charms_archive
+     WHERE network = $1
+       AND txid IN (SELECT jsonb_array_elements_text($2::jsonb))"#;
+
+/// Unspent rows at a batch of outpoints (`$2` is a JSON array of
+/// `[txid, vout]`).
+const UNSPENT_AT_SQL: &str = r#"
+    SELECT c.txid, c.vout, c.app_id
+      FROM charms c
+      JOIN jsonb_array_elements($2::jsonb) o
+        ON c.txid = o->>0 AND c.vout = (o->>1)::int
+     WHERE c.network = $1 AND NOT c.spent"#;
+
+const ASSET_SUPPLY_SQL: &str = r#"
+    SELECT COALESCE(total_supply, 0) AS supply
+      FROM assets
+     WHERE app_id = $1 AND network = $2"#;
+
+/// Diff of the holder rebuild over `charms` with the projected inserts
+/// (`$2`) and spends (`$3`) applied, against the stored rows.
+const HOLDERS_DIFF_SQL: &str = r#"
+    WITH overlay AS (
+        SELECT * FROM jsonb_to_recordset($2::jsonb)
+            AS o(app_id text, address text, amount bigint, block_height int, spent boolean)
+    ),
+    spent_now AS (
+        SELECT * FROM jsonb_to_recordset($3::jsonb) AS s(txid text, vout int, app_id text)
+    ),
+    projected AS (
+        SELECT c.app_id, c.address, c.amount, c.block_height, c.network,
+               c.spent OR EXISTS (
+                   SELECT 1 FROM spent_now s
+                    WHERE s.txid = c.txid AND s.vout = c.vout AND s.app_id = c.app_id
+               ) AS spent
+          FROM charms c
+         WHERE c.network = $1
+        UNION ALL
+        SELECT app_id, address, amount, block_height, $1::text, spent FROM overlay
+    ),
+    {EXPECTED}
+    SELECT COUNT(*) FILTER (WHERE h.app_id IS NULL) AS added,
+           COUNT(*) FILTER (WHERE e.app_id IS NULL) AS removed,
+           COUNT(*) FILTER (WHERE e.app_id IS NOT NULL AND h.app_id IS NOT NULL
+                              AND (h.total_amount <> e.total OR h.charm_count <> e.charm_count)) AS changed
+      FROM (SELECT app_id, address, total, charm_count FROM expected WHERE total > 0) e
+      FULL JOIN (SELECT app_id, address, total_amount, charm_count
+                   FROM stats_holders WHERE network = $1) h
+        ON h.app_id = e.app_id AND h.address = e.address"#;
+
+type CharmKey = (String, i32, String);
+
+/// What the writers would leave behind, relative to the stored tables.
+#[derive(Default)]
+struct Projection {
+    /// New charm rows, with whether the range also spends them.
+    inserted: HashMap<CharmKey, (CharmBatchItem, bool)>,
+    /// Existing rows whose tags or operation would be refreshed, with the
+    /// new values.
+    refreshed: HashMap<CharmKey, (Option<String>, Option<String>)>,
+    /// Existing unspent rows the range would spend.
+    spent: HashSet<CharmKey>,
+    /// app_id → (stored supply, projected supply); `None` means no row.
+    supply: BTreeMap<String, (Option<Decimal>, Option<Decimal>)>,
+}
+
+/// Build the dry-run report for `network` over `from..=to`.
+pub async fn reindex_report(
+    repos: &Repositories,
+    network: &str,
+    from: u64,
+    to: u64,
+) -> Result<ReindexReport, DbError> {
+    let conn = repos.block_status.get_connection();
+    let network_id = NetworkId::new(NetworkType::Bitcoin, network);
+    let charm_service = CharmService::new(
+        repos.charm.clone(),
+        repos.asset.clone(),
+        repos.stats_holders.clone(),
+        repos.dex_orders.clone(),
+    );
+    let mut report = ReindexReport {
+        network: network.to_string(),
+        from,
+        to,
+        blocks_scanned: to - from + 1,
+        ..Default::default()
+    };
+    let mut projection = Projection::default();
+
+    let rows = conn
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            STORED_TXS_SQL,
+            [network.into(), (from as i64).into(), (to as i64).into()],
+        ))
+        .await?;
+    let mut by_height: BTreeMap<u64, Vec<(String, usize, Option<String>)>> = BTreeMap::new();
+    for row in &rows {
+        let height: i32 = row.try_get("", "block_height")?;
+        let ordinal: i64 = row.try_get("", "ordinal")?;
+        by_height.entry(height as u64).or_default().push((
+            row.try_get("", "txid")?,
+            ordinal as usize,
+            row.try_get("", "hex")?,
+        ));
+    }
+
+    let budget = InflightBudget::from_env();
+    for (height, stored) in by_height {
+        let mut txs = Vec::with_capacity(stored.len());
+        let mut hexes = Vec::with_capacity(stored.len());
+        for (txid, pos, hex) in stored {
+            match hex.and_then(|h| ExtractedTx::from_stored(txid.clone(), h, pos)) {
+                Some(tx) => {
+                    hexes.push((tx.txid.clone(), tx.tx_hex.clone()));
+                    txs.push(tx);
+                }
+                None => report.parser_errors.push(ParserError {
+                    txid,
+                    height,
+                    error: "stored hex missing or not a transaction".to_string(),
+                }),
+            }
+        }
+        let inputs: Vec<(String, u32)> = txs
+            .iter()
+            .flat_map(|tx| tx.input_utxos.iter().cloned())
+            .collect();
+        let block_hash = repos
+            .block_status
+            .get_block_hash(height as i32, &network_id)
+            .await?
+            .unwrap_or_default();
+        let block_time = repos
+            .block_status
+            .get_block_time(height as i32, &network_id)
+            .await?;
+
+        let (transactions, charms, assets, _) = detection::detect_charms_in_txs(
+            txs.into_iter().map(TxSource::Stored),
+            &budget,
+            &block_hash,
+            block_time,
+            height,
+            to,
+            network,
+            "Bitcoin",
+            &charm_service,
+            Some(&repos.dex_orders),
+            None,
+            None,
+        )
+        .await;
+
+        report.spells_parsed += transactions.len() as u64;
+        let parsed: HashSet<&str> = transactions.iter().map(|t| t.txid.as_str()).collect();
+        for (txid, hex) in &hexes {
+            if !parsed.contains(txid.as_str()) {
+                let error = match NativeCharmParser::extract_and_verify_charm(hex, false) {
+                    Err(e) => e.to_string(),
+                    Ok(_) => "spell parsed but was not detected as a charm".to_string(),
+                };
+                report.parser_errors.push(ParserError {
+                    txid: txid.clone(),
+                    height,
+                    error,
+                });
+            }
+        }
+
+        projection.apply_charms(&conn, network, charms).await?;
+        projection.apply_spends(&conn, network, inputs).await?;
+        projection.apply_assets(&conn, network, &assets).await?;
+    }
+
+    report.charms_inserted = projection.inserted.len() as u64;
+    report.charms_updated = projection.refreshed.len() as u64;
+    report.charms_spent = (projection.spent.len()
+        + projection.inserted.values().filter(|(_, spent)| *spent).count())
+        as u64;
+    report.supply_changes = projection
+        .supply
+        .iter()
+        .filter_map(|(app_id, (old, new))| match (old, new) {
+            (old, Some(new)) if old.as_ref() != Some(new) => Some(SupplyChange {
+                app_id: app_id.clone(),
+                old: *old,
+                new: *new,
+            }),
+            _ => None,
+        })
+        .collect();
+    (
+        report.holders_added,
+        report.holders_changed,
+        report.holders_removed,
+    ) = projection.holders_diff(&conn, network).await?;
+
+    Ok(report)
+}
+
+impl Projection {
+    /// Same outcome as `CharmRepository::save_batch`: new keys are
+    /// inserted, existing ones only get non-NULL tags and operation
+    /// refreshed.
+    async fn apply_charms(
+        &mut self,
+        conn: &DatabaseConnection,
+        network: &str,
+        charms: Vec<CharmBatchItem>,
+    ) -> Result<(), DbError> {
+        if charms.is_empty() {
+            return Ok(());
+        }
+        let txids: Vec<&str> = charms.iter().map(|c| c.txid.as_str()).collect();
+        let mut stored: HashMap<CharmKey, (Option<String>, Option<String>)> = HashMap::new();
+        for row in conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                EXISTING_CHARMS_SQL,
+                [network.into(), json!(txids).into()],
+            ))
+            .await?
+        {
+            stored.insert(
+                (
+                    row.try_get("", "txid")?,
+                    row.try_get("", "vout")?,
+                    row.try_get("", "app_id")?,
+                ),
+                (row.try_get("", "tags")?, row.try_get("", "operation")?),
+            );
+        }
+
+        for charm in charms {
+            let key = (charm.txid.clone(), charm.vout, charm.app_id.clone());
+            if let Some((existing, _)) = self.inserted.get_mut(&key) {
+                existing.operation = charm.operation;
+                if charm.tags.is_some() {
+                    existing.tags = charm.tags;
+                }
+            } else if let Some(row) = stored.get(&key) {
+                let (tags, operation) = self.refreshed.get(&key).unwrap_or(row).clone();
+                let new = (
+                    charm.tags.or(tags),
+                    Some(charm.operation.to_string()).or(operation),
+                );
+                if new != *row {
+                    self.refreshed.insert(key, new);
+                } else {
+                    self.refreshed.remove(&key);
+                }
+            } else {
+                self.inserted.insert(key, (charm, false));
+            }
+        }
+        Ok(())
+    }
+
+    /// Same outcome as `mark_charms_as_spent_batch`: every unspent row at a
+    /// consumed outpoint, including rows inserted earlier in the range.
+    async fn apply_spends(
+        &mut self,
+        conn: &DatabaseConnection,
+        network: &str,
+        inputs: Vec<(String, u32)>,
+    ) -> Result<(), DbError> {
+        if inputs.is_empty() {
+            return Ok(());
+        }
+        let outpoints: HashSet<(String, i32)> = inputs
+            .into_iter()
+            .map(|(txid, vout)| (txid, vout as i32))
+            .collect();
+        for ((txid, vout, _), (_, spent)) in self.inserted.iter_mut() {
+            if outpoints.contains(&(txid.clone(), *vout)) {
+                *spent = true;
+            }
+        }
+
+        let param: Vec<serde_json::Value> = outpoints
+            .iter()
+            .map(|(txid, vout)| json!([txid, vout]))
+            .collect();
+        for row in conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                UNSPENT_AT_SQL,
+                [network.into(), serde_json::Value::Array(param).into()],
+            ))
+            .await?
+        {
+            self.spent.insert((
+                row.try_get("", "txid")?,
+                row.try_get("", "vout")?,
+                row.try_get("", "app_id")?,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Same outcome as the asset batch writer: an NFT row is created once
+    /// with supply 0, a token's supply is the highest declared one.
+    async fn apply_assets(
+        &mut self,
+        conn: &DatabaseConnection,
+        network: &str,
+        assets: &[AssetBatchItem],
+    ) -> Result<(), DbError> {
+        for asset in assets {
+            if !self.supply.contains_key(&asset.app_id) {
+                let stored = conn
+                    .query_one(Statement::from_sql_and_values(
+                        DbBackend::Postgres,
+                        ASSET_SUPPLY_SQL,
+                        [asset.app_id.clone().into(), network.into()],
+                    ))
+                    .await?
+                    .map(|row| row.try_get::<Decimal>("", "supply"))
+                    .transpose()?;
+                self.supply.insert(asset.app_id.clone(), (stored, stored));
+            }
+            let (_, projected) = self.supply.get_mut(&asset.app_id).expect("inserted above");
+            let declared = if asset.asset_type == AssetType::Nft {
+                Decimal::ZERO
+            } else {
+                Decimal::from(asset.supply)
+            };
+            *projected = Some(match *projected {
+                None => declared,
+                Some(current) if asset.asset_type == AssetType::Nft => current,
+                Some(current) => current.max(declared),
+            });
+        }
+        Ok(())
+    }
+
+    async fn holders_diff(
+        &self,
+        conn: &DatabaseConnection,
+        network: &str,
+    ) -> Result<(u64, u64, u64), DbError> {
+        let overlay: Vec<serde_json::Value> = self
+            .inserted
+            .values()
+            .map(|(c, spent)| {
+                json!({
+                    "app_id": c.app_id,
+                    "address": c.address,
+                    "amount": c.amount,
+                    "block_height": c.block_height,
+                    "spent": spent,
+                })
+            })
+            .collect();
+        let spent_now: Vec<serde_json::Value> = self
+            .spent
+            .iter()
+            .map(|(txid, vout, app_id)| json!({"txid": txid, "vout": vout, "app_id": app_id}))
+            .collect();
+        let row = conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                // The rebuild's own aggregation, read from the projection.
+                HOLDERS_DIFF_SQL.replace(
+                    "{EXPECTED}",
+                    &EXPECTED_HOLDERS_CTE.replace("FROM charms", "FROM projected"),
+                ),
+                [
+                    network.into(),
+                    serde_json::Value::Array(overlay).into(),
+                    serde_json::Value::Array(spent_now).into(),
+                ],
+            ))
+            .await?;
+        let Some(row) = row else {
+            return Ok((0, 0, 0));
+        };
+        let count = |col: &str| row.try_get::<i64>("", col).map(|n| n as u64);
+        Ok((count("added")?, count("changed")?, count("removed")?))
+    }
+}
@@ -0,0 +1,375 @@
+//! Snapshot export and import of one network's indexed state, so a new
+//! deployment can bootstrap from a recent tip instead of syncing from the
+//! first charms block.
+//!
+//! A snapshot is a directory `<network>-<tip>/` with one gzip-compressed CSV
+//! per table (written and read with `COPY ... (FORMAT csv, HEADER true)`) and
+//! a `manifest.json` carrying the format version, the schema version (last
+//! applied migration), the tip height and per-table row counts. Serial `id`
+//! columns are left out, so imported rows take fresh ids in the target.
+//!
+//! Export runs in one REPEATABLE READ transaction so every file reflects the
+//! same tip. Import runs in one transaction too, and finishes by marking the
+//! tip processed in `block_status` and `summary`: the live indexer then
+//! resumes at `tip + 1`.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+
+use crate::infrastructure::persistence::error::DbError;
+use crate::utils::gzip::{GzipDecoder, GzipEncoder};
+use crate::utils::logging;
+
+/// Bumped whenever the file layout or manifest changes incompatibly.
+pub const FORMAT_VERSION: u32 = 1;
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Exported tables, in import order. Each is filtered by its `network` column.
+pub const SNAPSHOT_TABLES: [&str; 8] = [
+    "block_status",
+    "transactions",
+    "spells",
+    "charms",
+    "charms_archive",
+    "assets",
+    "stats_holders",
+    "summary",
+];
+
+const BLOCKCHAIN: &str = "Bitcoin";
+const GZIP_LEVEL: u8 = 6;
+const COPY_CHUNK: usize = 256 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub network: String,
+    /// Parent directory; the snapshot is written to `<dir>/<network>-<tip>`.
+    pub dir: PathBuf,
+    /// Keep `transactions.raw`; when false it is exported as `{}`.
+    pub include_raw: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub format_version: u32,
+    /// Last applied migration in the source database, if it tracks them.
+    pub schema_version: Option<String>,
+    pub network: String,
+    pub tip_height: i32,
+    pub created_at: DateTime<Utc>,
+    pub include_raw: bool,
+    pub tables: Vec<SnapshotTable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotTable {
+    pub name: String,
+    pub file: String,
+    pub columns: Vec<String>,
+    pub rows: u64,
+}
+
+fn sql_err(e: sqlx::Error) -> DbError {
+    DbError::QueryError(e.to_string())
+}
+
+fn io_err(path: &Path, e: impl std::fmt::Display) -> DbError {
+    DbError::Other(format!("{}: {}", path.display(), e))
+}
+
+fn quote(s: &str) -> String {
+    s.replace('\'', "''")
+}
+
+fn column_list(columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|c| format!("\"{}\"", c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Latest `seaql_migrations` version, or None when the table is absent.
+async fn schema_version(conn: &mut PgConnection) -> Result<Option<String>, DbError> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('seaql_migrations') IS NOT NULL")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(sql_err)?;
+    if !exists {
+        return Ok(None);
+    }
+    sqlx::query_scalar("SELECT MAX(version) FROM seaql_migrations")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(sql_err)
+}
+
+/// Non-serial columns of `table`, in table order.
+async fn table_columns(conn: &mut PgConnection, table: &str) -> Result<Vec<String>, DbError> {
+    let rows = sqlx::query(
+        "SELECT column_name::text AS name FROM information_schema.columns
+          WHERE table_schema = current_schema() AND table_name = $1
+            AND COALESCE(column_default, '') NOT LIKE 'nextval(%'
+          ORDER BY ordinal_position",
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(sql_err)?;
+    rows.iter()
+        .map(|r| r.try_get::<String, _>("name").map_err(sql_err))
+        .collect()
+}
+
+/// Highest processed block, falling back to `summary.last_processed_block`.
+async fn tip_height(conn: &mut PgConnection, network: &str) -> Result<Option<i32>, DbError> {
+    let tip: Option<i32> = sqlx::query_scalar(
+        "SELECT COALESCE(
+            (SELECT MAX(block_height) FROM block_status
+              WHERE network = $1 AND blockchain = $2 AND processed),
+            (SELECT NULLIF(last_processed_block, 0) FROM summary WHERE network = $1))",
+    )
+    .bind(network)
+    .bind(BLOCKCHAIN)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(sql_err)?;
+    Ok(tip)
+}
+
+pub async fn export_snapshot(
+    conn: &DatabaseConnection,
+    opts: &ExportOptions,
+) -> Result<(PathBuf, SnapshotManifest), DbError> {
+    let mut tx = conn
+        .get_postgres_connection_pool()
+        .begin()
+        .await
+        .map_err(sql_err)?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await
+        .map_err(sql_err)?;
+
+    let tip_height = tip_height(&mut tx, &opts.network)
+        .await?
+        .ok_or_else(|| DbError::Other(format!("nothing indexed for {}", opts.network)))?;
+    let out_dir = opts.dir.join(format!("{}-{}", opts.network, tip_height));
+    if out_dir.join(MANIFEST_FILE).exists() {
+        return Err(DbError::Other(format!(
+            "{} already holds a snapshot",
+            out_dir.display()
+        )));
+    }
+    std::fs::create_dir_all(&out_dir).map_err(|e| io_err(&out_dir, e))?;
+
+    let mut manifest = SnapshotManifest {
+        format_version: FORMAT_VERSION,
+        schema_version: schema_version(&mut tx).await?,
+        network: opts.network.clone(),
+        tip_height,
+        created_at: Utc::now(),
+        include_raw: opts.include_raw,
+        tables: Vec::new(),
+    };
+
+    for table in SNAPSHOT_TABLES {
+        let columns = table_columns(&mut tx, table).await?;
+        let select = columns
+            .iter()
+            .map(|c| {
+                if table == "transactions" && c == "raw" && !opts.include_raw {
+                    "'{}'::jsonb AS raw".to_string()
+                } else {
+                    format!("\"{}\"", c)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let filter = format!("network = '{}'", quote(&opts.network));
+        let rows: i64 =
+            sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} WHERE {}", table, filter))
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(sql_err)?;
+
+        let file = format!("{}.csv.gz", table);
+        let path = out_dir.join(&file);
+        let handle = File::create(&path).map_err(|e| io_err(&path, e))?;
+        let mut encoder =
+            GzipEncoder::new(BufWriter::new(handle), GZIP_LEVEL).map_err(|e| io_err(&path, e))?;
+        let mut stream = tx
+            .copy_out_raw(&format!(
+                "COPY (SELECT {} FROM {} WHERE {}) TO STDOUT WITH (FORMAT csv, HEADER true)",
+                select, table, filter
+            ))
+            .await
+            .map_err(sql_err)?;
+        while let Some(chunk) = stream.try_next().await.map_err(sql_err)? {
+            encoder.write_all(&chunk).map_err(|e| io_err(&path, e))?;
+        }
+        drop(stream);
+        encoder.finish().map_err(|e| io_err(&path, e))?;
+
+        logging::log_info(&format!(
+            "[snapshot] {}: {} row(s) -> {}",
+            table, rows, file
+        ));
+        manifest.tables.push(SnapshotTable {
+            name: table.to_string(),
+            file,
+            columns,
+            rows: rows as u64,
+        });
+    }
+    tx.commit().await.map_err(sql_err)?;
+
+    let path = out_dir.join(MANIFEST_FILE);
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|e| io_err(&path, e))?;
+    std::fs::write(&path, json).map_err(|e| io_err(&path, e))?;
+    Ok((out_dir, manifest))
+}
+
+pub fn read_manifest(dir: &Path) -> Result<SnapshotManifest, DbError> {
+    let path = dir.join(MANIFEST_FILE);
+    let bytes = std::fs::read(&path).map_err(|e| io_err(&path, e))?;
+    let manifest: SnapshotManifest =
+        serde_json::from_slice(&bytes).map_err(|e| io_err(&path, e))?;
+    if manifest.format_version != FORMAT_VERSION {
+        return Err(DbError::Other(format!(
+            "snapshot format v{} is not supported (expected v{})",
+            manifest.format_version, FORMAT_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+/// Load the snapshot in `dir`. Refuses when any snapshot table already has
+/// rows for the snapshot's network, or when the schema versions differ;
+/// `force` skips both checks and replaces that network's rows.
+pub async fn import_snapshot(
+    conn: &DatabaseConnection,
+    dir: &Path,
+    force: bool,
+) -> Result<SnapshotManifest, DbError> {
+    let manifest = read_manifest(dir)?;
+    let network = quote(&manifest.network);
+    let mut tx = conn
+        .get_postgres_connection_pool()
+        .begin()
+        .await
+        .map_err(sql_err)?;
+
+    let target_version = schema_version(&mut tx).await?;
+    if let (Some(ours), Some(theirs)) = (&target_version, &manifest.schema_version) {
+        if ours != theirs && !force {
+            return Err(DbError::Other(format!(
+                "schema version mismatch: database is at {}, snapshot at {} (use --force to import anyway)",
+                ours, theirs
+            )));
+        }
+    }
+
+    let mut occupied = Vec::new();
+    for table in &manifest.tables {
+        let exists: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {} WHERE network = '{}')",
+            table.name, network
+        ))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(sql_err)?;
+        if exists {
+            occupied.push(table.name.as_str());
+        }
+    }
+    if !occupied.is_empty() {
+        if !force {
+            return Err(DbError::Other(format!(
+                "{} already hold {} rows (use --force to replace them)",
+                occupied.join(", "),
+                manifest.network
+            )));
+        }
+        for table in manifest.tables.iter().rev() {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE network = '{}'",
+                table.name, network
+            ))
+            .execute(&mut *tx)
+            .await
+            .map_err(sql_err)?;
+        }
+    }
+
+    for table in &manifest.tables {
+        let path = dir.join(&table.file);
+        let handle = File::open(&path).map_err(|e| io_err(&path, e))?;
+        let mut decoder = GzipDecoder::new(BufReader::new(handle)).map_err(|e| io_err(&path, e))?;
+        let mut copy = tx
+            .copy_in_raw(&format!(
+                "COPY {} ({}) FROM STDIN WITH (FORMAT csv, HEADER true)",
+                table.name,
+                column_list(&table.columns)
+            ))
+            .await
+            .map_err(sql_err)?;
+        let mut buf = vec![0u8; COPY_CHUNK];
+        loop {
+            let n = match decoder.read(&mut buf) {
+                Ok(n) => n,
+                Err(e) => {
+                    let _ = copy.abort(e.to_string()).await;
+                    return Err(io_err(&path, e));
+                }
+            };
+            if n == 0 {
+                break;
+            }
+            copy.send(&buf[..n]).await.map_err(sql_err)?;
+        }
+        let rows = copy.finish().await.map_err(sql_err)?;
+        if rows != table.rows {
+            return Err(DbError::Other(format!(
+                "{}: loaded {} row(s), manifest lists {}",
+                table.name, rows, table.rows
+            )));
+        }
+        logging::log_info(&format!(
+            "[snapshot] {}: {} row(s) loaded",
+            table.name, rows
+        ));
+    }
+
+    sqlx::query(
+        "INSERT INTO block_status (block_height, network, blockchain, downloaded, processed, downloaded_at, processed_at)
+         VALUES ($1, $2, $3, TRUE, TRUE, NOW(), NOW())
+         ON CONFLICT (block_height, network, blockchain)
+         DO UPDATE SET downloaded = TRUE, processed = TRUE, updated_at = NOW()",
+    )
+    .bind(manifest.tip_height)
+    .bind(&manifest.network)
+    .bind(BLOCKCHAIN)
+    .execute(&mut *tx)
+    .await
+    .map_err(sql_err)?;
+    sqlx::query(
+        "UPDATE summary SET last_processed_block = GREATEST(last_processed_block, $1), updated_at = NOW()
+          WHERE network = $2",
+    )
+    .bind(manifest.tip_height)
+    .bind(&manifest.network)
+    .execute(&mut *tx)
+    .await
+    .map_err(sql_err)?;
+
+    tx.commit().await.map_err(sql_err)?;
+    Ok(manifest)
+}
@@ -0,0 +1,83 @@
+//! Backfill `transactions.spell_outputs` for spells stored before the column
+//! existed.
+//!
+//! The map is derived from the spell JSON already stored in `charm` (see
+//! `charms_core::spell_outputs`), so no node is needed. Rows are read in
+//! txid order, a page at a time, since each carries the full spell. Rows
+//! whose JSON holds no parsed spell stay NULL and are counted; re-running
+//! the job only revisits those.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+use crate::utils::logging;
+
+const PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Default)]
+pub struct SpellOutputsBackfillSummary {
+    /// Transaction rows given their output map.
+    pub updated: u64,
+    /// Rows left NULL (no parsed spell in the stored JSON).
+    pub unresolved: u64,
+}
+
+/// Rows without a map, after txid `$2`. `$1` is an optional network filter.
+const MISSING_OUTPUTS_SQL: &str = r#"
+    SELECT txid, charm
+      FROM transactions
+     WHERE spell_outputs IS NULL
+       AND ($1::text IS NULL OR network = $1)
+       AND txid > $2
+  ORDER BY txid
+     LIMIT $3"#;
+
+const FILL_OUTPUTS_SQL: &str = r#"
+    UPDATE transactions
+       SET spell_outputs = $2
+     WHERE txid = $1 AND spell_outputs IS NULL"#;
+
+pub async fn backfill_spell_outputs(
+    conn: &DatabaseConnection,
+    network: Option<&str>,
+) -> Result<SpellOutputsBackfillSummary, DbError> {
+    let mut summary = SpellOutputsBackfillSummary::default();
+    let mut after = String::new();
+
+    loop {
+        let rows = conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                MISSING_OUTPUTS_SQL,
+                [network.into(), after.clone().into(), PAGE_SIZE.into()],
+            ))
+            .await?;
+        for row in &rows {
+            let txid: String = row.try_get("", "txid")?;
+            let charm: serde_json::Value = row.try_get("", "charm")?;
+            match charms_core::spell_outputs(&charm) {
+                Some(outputs) => {
+                    let res = conn
+                        .execute(Statement::from_sql_and_values(
+                            DbBackend::Postgres,
+                            FILL_OUTPUTS_SQL,
+                            [txid.clone().into(), outputs.into()],
+                        ))
+                        .await?;
+                    summary.updated += res.rows_affected();
+                }
+                None => summary.unresolved += 1,
+            }
+            after = txid;
+        }
+        if (rows.len() as i64) < PAGE_SIZE {
+            break;
+        }
+    }
+
+    logging::log_info(&format!(
+        "🧾 backfill-spell-outputs: {} transaction(s) updated, {} without a parsed spell",
+        summary.updated, summary.unresolved
+    ));
+    Ok(summary)
+}
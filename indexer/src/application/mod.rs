@@ -1 +1,4 @@
 pub mod indexer;
+pub mod maintenance;
+pub mod preflight;
+pub mod verify;
@@ -0,0 +1,648 @@
+//! Pre-flight checks: is this deployment able to index at all?
+//!
+//! A node on the wrong chain, a database missing migrations or a genesis
+//! height the node has pruned away otherwise surface as errors deep into
+//! processing. The suite checks them up front:
+//! - `database`: the database answers
+//! - `migrations`: every bundled migration is recorded in `seaql_migrations`
+//! - `scratch_write`: a write/read round-trip on a temporary table
+//! - per endpoint of each enabled Bitcoin network:
+//!   - `rpc_reachable` / `quicknode_credentials`: `getblockchaininfo` answers
+//!     (a QuickNode endpoint carries its token in the URL, so a rejected
+//!     call is a credential problem)
+//!   - `chain`: the node's chain is the configured network's
+//!   - `genesis_available`: `genesis_block_height` is at or below the tip
+//!     and not pruned
+//!
+//! `charms-indexer check` (`--check`, `STARTUP_CHECK_ONLY=true`) runs it and
+//! exits 1 on a failure. `run` runs it first and logs failures as warnings,
+//! or refuses to start with `STARTUP_CHECK_FATAL=true`.
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bitcoincore_rpc::{Auth, Client, RpcApi};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement, TransactionTrait};
+use serde_json::{json, Value};
+
+use crate::config::{redact_url, AppConfig, BitcoinConfig, ProviderType};
+use crate::infrastructure::persistence::migrations;
+
+/// Time each node gets to answer.
+const NODE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Pending migrations named in the detail; the rest are counted.
+const PENDING_SHOWN: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail,
+    /// Not run because a check it depends on failed.
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Pass => "PASS",
+            Status::Fail => "FAIL",
+            Status::Skip => "SKIP",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub check: &'static str,
+    /// `None` for database checks, else `<network> <endpoint>`.
+    pub scope: Option<String>,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(check: &'static str, scope: Option<&str>, status: Status, detail: String) -> Self {
+        Self {
+            check,
+            scope: scope.map(str::to_string),
+            status,
+            detail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    pub results: Vec<CheckResult>,
+}
+
+impl PreflightReport {
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.results.iter().filter(|r| r.status == Status::Fail)
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Plain-text pass/fail table.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for r in &self.results {
+            let _ = writeln!(
+                out,
+                "  {:<4} {:<22} {:<36} {}",
+                r.status.label(),
+                r.check,
+                r.scope.as_deref().unwrap_or("database"),
+                r.detail
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{} check(s) run, {} failed",
+            self.results.len(),
+            self.failures().count()
+        );
+        out
+    }
+}
+
+/// A `getblockchaininfo` answer, reduced to what the checks read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainInfo {
+    pub chain: String,
+    pub blocks: u64,
+    /// Lowest height a pruned node still has; `None` when not pruned.
+    pub prune_height: Option<u64>,
+}
+
+impl ChainInfo {
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        let chain = value["chain"]
+            .as_str()
+            .ok_or("getblockchaininfo has no chain")?
+            .to_string();
+        let blocks = value["blocks"]
+            .as_u64()
+            .ok_or("getblockchaininfo has no blocks")?;
+        let prune_height = match value["pruned"].as_bool() {
+            Some(true) => Some(value["pruneheight"].as_u64().unwrap_or(0)),
+            _ => None,
+        };
+        Ok(Self {
+            chain,
+            blocks,
+            prune_height,
+        })
+    }
+}
+
+/// Where the network checks get `getblockchaininfo` from.
+#[async_trait]
+pub trait ChainInfoSource: Send + Sync {
+    async fn blockchain_info(&self) -> Result<ChainInfo, String>;
+}
+
+/// Bitcoin Core over JSON-RPC.
+pub struct RpcNode {
+    url: String,
+    auth: Auth,
+}
+
+impl RpcNode {
+    pub fn new(host: &str, port: &str, username: &str, password: &str) -> Self {
+        Self {
+            url: format!("http://{}:{}", host, port),
+            auth: Auth::UserPass(username.to_string(), password.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl ChainInfoSource for RpcNode {
+    async fn blockchain_info(&self) -> Result<ChainInfo, String> {
+        let (url, auth) = (self.url.clone(), self.auth.clone());
+        let value = tokio::task::spawn_blocking(move || {
+            let client = Client::new(&url, auth).map_err(|e| e.to_string())?;
+            client
+                .call::<Value>("getblockchaininfo", &[])
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        ChainInfo::from_json(&value)
+    }
+}
+
+/// A QuickNode endpoint; the URL carries the token.
+pub struct QuickNodeEndpoint {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl QuickNodeEndpoint {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl ChainInfoSource for QuickNodeEndpoint {
+    async fn blockchain_info(&self) -> Result<ChainInfo, String> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "getblockchaininfo",
+                "params": [],
+            }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(format!("credentials rejected (HTTP {})", status.as_u16()));
+        }
+        if !status.is_success() {
+            return Err(format!("HTTP {}", status.as_u16()));
+        }
+        let body: Value = response.json().await.map_err(|e| e.to_string())?;
+        if let Some(error) = body.get("error").filter(|e| !e.is_null()) {
+            return Err(error.to_string());
+        }
+        ChainInfo::from_json(&body["result"])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointKind {
+    Rpc,
+    QuickNode,
+}
+
+/// One node a network reads from.
+pub struct Endpoint {
+    pub kind: EndpointKind,
+    /// Host only; never the credentials.
+    pub label: String,
+    pub source: Box<dyn ChainInfoSource>,
+}
+
+impl Endpoint {
+    fn rpc(host: &str, port: &str, username: &str, password: &str) -> Self {
+        Self {
+            kind: EndpointKind::Rpc,
+            label: format!("rpc {}:{}", host, port),
+            source: Box::new(RpcNode::new(host, port, username, password)),
+        }
+    }
+
+    fn quicknode(url: &str) -> Self {
+        Self {
+            kind: EndpointKind::QuickNode,
+            label: format!("quicknode {}", redact_url(url)),
+            source: Box::new(QuickNodeEndpoint::new(url)),
+        }
+    }
+}
+
+/// Every endpoint `config` reads blocks from: its provider list, or its
+/// single provider, plus the QuickNode fallback when that is separate.
+pub fn endpoints(config: &BitcoinConfig) -> Vec<Endpoint> {
+    let mut endpoints = Vec::new();
+    if config.providers.is_empty() {
+        match config.provider_type {
+            ProviderType::BitcoinNode => endpoints.push(Endpoint::rpc(
+                &config.host,
+                &config.port,
+                &config.username,
+                &config.password,
+            )),
+            ProviderType::QuickNode => {
+                if let Some(url) = &config.quicknode_endpoint {
+                    endpoints.push(Endpoint::quicknode(url));
+                }
+            }
+        }
+    }
+    for spec in &config.providers {
+        match ProviderType::parse(&spec.provider_type) {
+            ProviderType::QuickNode => {
+                if let Some(url) = &spec.url {
+                    endpoints.push(Endpoint::quicknode(url));
+                }
+            }
+            ProviderType::BitcoinNode => endpoints.push(Endpoint::rpc(
+                spec.host.as_deref().unwrap_or(&config.host),
+                spec.port.as_deref().unwrap_or(&config.port),
+                spec.username.as_deref().unwrap_or(&config.username),
+                spec.password.as_deref().unwrap_or(&config.password),
+            )),
+        }
+    }
+    let fallback_separate =
+        !config.providers.is_empty() || config.provider_type != ProviderType::QuickNode;
+    if let Some(url) = config
+        .quicknode_endpoint
+        .as_ref()
+        .filter(|_| fallback_separate)
+    {
+        endpoints.push(Endpoint::quicknode(url));
+    }
+    endpoints
+}
+
+/// The `chain` a node on `network` reports.
+pub fn expected_chain(network: &str) -> &str {
+    match network {
+        "mainnet" => "main",
+        "testnet" | "testnet3" => "test",
+        other => other,
+    }
+}
+
+/// The database checks. `conn` is the connection, or why it failed.
+pub async fn database_checks(conn: Result<&DatabaseConnection, String>) -> Vec<CheckResult> {
+    let conn = match conn {
+        Ok(conn) => conn,
+        Err(e) => {
+            return vec![
+                CheckResult::new("database", None, Status::Fail, e),
+                skipped("migrations", None, "no database"),
+                skipped("scratch_write", None, "no database"),
+            ];
+        }
+    };
+    if let Err(e) = conn
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT 1".to_string(),
+        ))
+        .await
+    {
+        return vec![
+            CheckResult::new("database", None, Status::Fail, e.to_string()),
+            skipped("migrations", None, "no database"),
+            skipped("scratch_write", None, "no database"),
+        ];
+    }
+
+    let mut results = vec![CheckResult::new(
+        "database",
+        None,
+        Status::Pass,
+        "connected".to_string(),
+    )];
+    results.push(match migrations::pending(conn).await {
+        Ok(pending) if pending.is_empty() => CheckResult::new(
+            "migrations",
+            None,
+            Status::Pass,
+            format!("{} applied", migrations::MIGRATIONS.len()),
+        ),
+        Ok(pending) => {
+            let mut detail = format!(
+                "{} pending: {}",
+                pending.len(),
+                pending[..pending.len().min(PENDING_SHOWN)].join(", ")
+            );
+            if pending.len() > PENDING_SHOWN {
+                detail.push_str(", …");
+            }
+            CheckResult::new("migrations", None, Status::Fail, detail)
+        }
+        Err(e) => CheckResult::new("migrations", None, Status::Fail, e.to_string()),
+    });
+    results.push(match scratch_round_trip(conn).await {
+        Ok(()) => CheckResult::new(
+            "scratch_write",
+            None,
+            Status::Pass,
+            "write/read ok".to_string(),
+        ),
+        Err(e) => CheckResult::new("scratch_write", None, Status::Fail, e),
+    });
+    results
+}
+
+/// Write a row to a temporary table and read it back, in a transaction
+/// that is rolled back.
+async fn scratch_round_trip(conn: &DatabaseConnection) -> Result<(), String> {
+    let token = chrono::Utc::now().to_rfc3339();
+    let tx = conn.begin().await.map_err(|e| e.to_string())?;
+    let read = async {
+        tx.execute(Statement::from_string(
+            DbBackend::Postgres,
+            "CREATE TEMPORARY TABLE preflight_scratch (value TEXT NOT NULL) ON COMMIT DROP"
+                .to_string(),
+        ))
+        .await?;
+        tx.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "INSERT INTO preflight_scratch (value) VALUES ($1)",
+            [token.clone().into()],
+        ))
+        .await?;
+        tx.query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT value FROM preflight_scratch".to_string(),
+        ))
+        .await?
+        .map(|row| row.try_get::<String>("", "value"))
+        .transpose()
+    }
+    .await;
+    tx.rollback().await.map_err(|e| e.to_string())?;
+    match read.map_err(|e| e.to_string())? {
+        Some(value) if value == token => Ok(()),
+        other => Err(format!("read back {:?}", other)),
+    }
+}
+
+/// The checks of one endpoint of `network`.
+pub async fn endpoint_checks(network: &str, genesis: u64, endpoint: &Endpoint) -> Vec<CheckResult> {
+    let scope = format!("{} {}", network, endpoint.label);
+    let scope = Some(scope.as_str());
+    let reachable = match endpoint.kind {
+        EndpointKind::Rpc => "rpc_reachable",
+        EndpointKind::QuickNode => "quicknode_credentials",
+    };
+    let info = match tokio::time::timeout(NODE_TIMEOUT, endpoint.source.blockchain_info()).await {
+        Ok(Ok(info)) => info,
+        Ok(Err(e)) => {
+            return vec![
+                CheckResult::new(reachable, scope, Status::Fail, e),
+                skipped("chain", scope, "node unavailable"),
+                skipped("genesis_available", scope, "node unavailable"),
+            ];
+        }
+        Err(_) => {
+            return vec![
+                CheckResult::new(
+                    reachable,
+                    scope,
+                    Status::Fail,
+                    format!("no answer within {}s", NODE_TIMEOUT.as_secs()),
+                ),
+                skipped("chain", scope, "node unavailable"),
+                skipped("genesis_available", scope, "node unavailable"),
+            ];
+        }
+    };
+
+    let expected = expected_chain(network);
+    let chain = if info.chain == expected {
+        CheckResult::new("chain", scope, Status::Pass, info.chain.clone())
+    } else {
+        CheckResult::new(
+            "chain",
+            scope,
+            Status::Fail,
+            format!("node is on {}, expected {}", info.chain, expected),
+        )
+    };
+    let genesis_available = match info.prune_height {
+        _ if genesis > info.blocks => CheckResult::new(
+            "genesis_available",
+            scope,
+            Status::Fail,
+            format!("genesis {} is above the node tip {}", genesis, info.blocks),
+        ),
+        Some(pruned) if genesis < pruned => CheckResult::new(
+            "genesis_available",
+            scope,
+            Status::Fail,
+            format!("genesis {} is pruned (node keeps {}+)", genesis, pruned),
+        ),
+        _ => CheckResult::new(
+            "genesis_available",
+            scope,
+            Status::Pass,
+            format!("genesis {}, tip {}", genesis, info.blocks),
+        ),
+    };
+    vec![
+        CheckResult::new(reachable, scope, Status::Pass, "answered".to_string()),
+        chain,
+        genesis_available,
+    ]
+}
+
+fn skipped(check: &'static str, scope: Option<&str>, why: &str) -> CheckResult {
+    CheckResult::new(check, scope, Status::Skip, why.to_string())
+}
+
+/// The whole suite: database, then every endpoint of each enabled Bitcoin
+/// network, in network order.
+pub async fn run(config: &AppConfig, conn: Result<&DatabaseConnection, String>) -> PreflightReport {
+    let mut results = database_checks(conn).await;
+    let mut networks: Vec<&BitcoinConfig> = config.bitcoin_configs.values().collect();
+    networks.sort_by(|a, b| a.network.cmp(&b.network));
+    for network in networks {
+        for endpoint in endpoints(network) {
+            results.extend(
+                endpoint_checks(&network.network, network.genesis_block_height, &endpoint).await,
+            );
+        }
+    }
+    PreflightReport { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    struct FixedNode(Result<ChainInfo, String>);
+
+    #[async_trait]
+    impl ChainInfoSource for FixedNode {
+        async fn blockchain_info(&self) -> Result<ChainInfo, String> {
+            self.0.clone()
+        }
+    }
+
+    fn node(kind: EndpointKind, answer: Result<ChainInfo, String>) -> Endpoint {
+        Endpoint {
+            kind,
+            label: "test".to_string(),
+            source: Box::new(FixedNode(answer)),
+        }
+    }
+
+    fn info(chain: &str, blocks: u64, prune_height: Option<u64>) -> ChainInfo {
+        ChainInfo {
+            chain: chain.to_string(),
+            blocks,
+            prune_height,
+        }
+    }
+
+    async fn statuses(
+        network: &str,
+        genesis: u64,
+        endpoint: Endpoint,
+    ) -> Vec<(&'static str, Status)> {
+        endpoint_checks(network, genesis, &endpoint)
+            .await
+            .into_iter()
+            .map(|r| (r.check, r.status))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn a_synced_node_on_the_right_chain_passes() {
+        let endpoint = node(EndpointKind::Rpc, Ok(info("main", 900_000, Some(800_000))));
+        assert_eq!(
+            statuses("mainnet", 840_000, endpoint).await,
+            [
+                ("rpc_reachable", Status::Pass),
+                ("chain", Status::Pass),
+                ("genesis_available", Status::Pass),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_node_on_another_chain_fails_the_chain_check() {
+        let endpoint = node(EndpointKind::Rpc, Ok(info("testnet4", 90_000, None)));
+        let results = endpoint_checks("mainnet", 0, &endpoint).await;
+        assert_eq!(results[1].status, Status::Fail);
+        assert_eq!(results[1].detail, "node is on testnet4, expected main");
+    }
+
+    #[tokio::test]
+    async fn a_pruned_or_short_node_fails_the_genesis_check() {
+        let pruned = node(EndpointKind::Rpc, Ok(info("main", 900_000, Some(850_000))));
+        let results = endpoint_checks("mainnet", 840_000, &pruned).await;
+        assert_eq!(results[2].status, Status::Fail);
+        assert_eq!(
+            results[2].detail,
+            "genesis 840000 is pruned (node keeps 850000+)"
+        );
+
+        let short = node(EndpointKind::Rpc, Ok(info("main", 830_000, None)));
+        assert_eq!(
+            statuses("mainnet", 840_000, short).await[2],
+            ("genesis_available", Status::Fail)
+        );
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_node_skips_the_rest() {
+        let endpoint = node(EndpointKind::Rpc, Err("connection refused".to_string()));
+        assert_eq!(
+            statuses("mainnet", 0, endpoint).await,
+            [
+                ("rpc_reachable", Status::Fail),
+                ("chain", Status::Skip),
+                ("genesis_available", Status::Skip),
+            ]
+        );
+    }
+
+    /// Answers every request with `status_line` and an empty JSON body.
+    async fn http_server(status_line: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/qn-token/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "{}\r\ncontent-type: application/json\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{{}}",
+                    status_line
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn rejected_quicknode_credentials_fail() {
+        let url = http_server("HTTP/1.1 401 Unauthorized").await;
+        let endpoint = Endpoint::quicknode(&url);
+        assert!(!endpoint.label.contains("qn-token"));
+        let results = endpoint_checks("mainnet", 0, &endpoint).await;
+        assert_eq!(results[0].check, "quicknode_credentials");
+        assert_eq!(results[0].status, Status::Fail);
+        assert_eq!(results[0].detail, "credentials rejected (HTTP 401)");
+    }
+
+    #[test]
+    fn chain_info_reads_the_prune_height_only_when_pruned() {
+        let pruned = json!({"chain": "main", "blocks": 10, "pruned": true, "pruneheight": 4});
+        assert_eq!(ChainInfo::from_json(&pruned), Ok(info("main", 10, Some(4))));
+        let full = json!({"chain": "main", "blocks": 10, "pruned": false});
+        assert_eq!(ChainInfo::from_json(&full), Ok(info("main", 10, None)));
+        assert!(ChainInfo::from_json(&json!({"blocks": 10})).is_err());
+    }
+
+    #[test]
+    fn the_report_fails_on_any_failed_check() {
+        let mut report = PreflightReport {
+            results: vec![
+                CheckResult::new("database", None, Status::Pass, "connected".to_string()),
+                skipped("chain", Some("mainnet rpc"), "node unavailable"),
+            ],
+        };
+        assert!(report.passed());
+        report.results.push(CheckResult::new(
+            "migrations",
+            None,
+            Status::Fail,
+            "1 pending: m1".to_string(),
+        ));
+        assert!(!report.passed());
+        assert!(report.render().ends_with("3 check(s) run, 1 failed\n"));
+    }
+}
@@ -0,0 +1,254 @@
+//! SQL for the cross-table reconciliation checks and their repairs.
+//!
+//! Every check query selects a single text column `key` — one row per
+//! discrepancy, already formatted for the report. `run_check` wraps it to
+//! get the total count and a bounded sample in one round trip.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::stats_holders_repository::EXPECTED_HOLDERS_CTE;
+use crate::infrastructure::persistence::repositories::{StatsHoldersRepository, SupplyChangeReason};
+
+/// One class of cross-table inconsistency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// Unspent confirmed token amounts exceed the asset's recorded supply
+    /// (or no asset row exists to hold it). Each example names the last
+    /// `supply_changes` write to that supply.
+    SupplyVsCharms,
+    /// `stats_holders` disagrees with the balances derived from charms.
+    HoldersVsCharms,
+    /// Charm rows, live or archived, whose txid has no `transactions` row
+    /// on that network.
+    CharmsWithoutTransaction,
+    /// Spell rows with no charm, live or archived, produced by the same tx.
+    SpellsWithoutCharms,
+}
+
+impl Check {
+    pub const ALL: [Check; 4] = [
+        Check::SupplyVsCharms,
+        Check::HoldersVsCharms,
+        Check::CharmsWithoutTransaction,
+        Check::SpellsWithoutCharms,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::SupplyVsCharms => "supply_vs_charms",
+            Check::HoldersVsCharms => "holders_vs_charms",
+            Check::CharmsWithoutTransaction => "charms_without_transaction",
+            Check::SpellsWithoutCharms => "spells_without_charms",
+        }
+    }
+
+    /// Whether `--fix` knows a safe repair for this check. Orphans are only
+    /// reported: deleting them could drop data a later block would explain.
+    pub fn is_fixable(self) -> bool {
+        matches!(self, Check::SupplyVsCharms | Check::HoldersVsCharms)
+    }
+
+    fn sql(self) -> &'static str {
+        match self {
+            Check::SupplyVsCharms => SUPPLY_SQL,
+            Check::HoldersVsCharms => HOLDERS_SQL,
+            Check::CharmsWithoutTransaction => ORPHAN_CHARMS_SQL,
+            Check::SpellsWithoutCharms => ORPHAN_SPELLS_SQL,
+        }
+    }
+}
+
+/// Circulating token supply per app_id. Token supply lives on the `t/`
+/// asset row, or on the parent `n/` row when no token row was created.
+const CIRCULATING_CTE: &str = r#"
+    circ AS (
+        SELECT c.app_id, SUM(c.amount) AS circulating
+          FROM charms c
+         WHERE c.network = $1
+           AND NOT c.spent
+           AND c.block_height IS NOT NULL
+           AND c.app_id LIKE 't/%'
+      GROUP BY c.app_id
+    ),
+    supply AS (
+        SELECT circ.app_id, circ.circulating,
+               COALESCE(tok.id, nft.id) AS asset_id,
+               COALESCE(tok.app_id, nft.app_id) AS asset_app_id,
+               COALESCE(tok.total_supply, nft.total_supply) AS total_supply
+          FROM circ
+     LEFT JOIN assets tok ON tok.network = $1 AND tok.app_id = circ.app_id
+     LEFT JOIN assets nft ON nft.network = $1 AND nft.app_id = 'n/' || substr(circ.app_id, 3)
+    )"#;
+
+const SUPPLY_SQL: &str = r#"
+    WITH {CIRC}
+    SELECT s.app_id || ' circulating=' || s.circulating
+               || ' supply=' || COALESCE(s.total_supply::text, 'missing')
+               || COALESCE(' last=' || last.reason || ' ' || COALESCE(last.txid, '-')
+                               || '@' || COALESCE(last.block_height::text, '-')
+                               || ' (' || COALESCE(last.old_supply::text, 'new')
+                               || '->' || last.new_supply || ')',
+                           ' last=none') AS key
+      FROM supply s
+      LEFT JOIN LATERAL (
+           SELECT reason, txid, block_height, old_supply, new_supply
+             FROM supply_changes sc
+            WHERE sc.network = $1 AND sc.app_id = s.asset_app_id
+         ORDER BY sc.id DESC
+            LIMIT 1) last ON true
+     WHERE s.total_supply IS NULL OR s.circulating > s.total_supply"#;
+
+const HOLDERS_SQL: &str = r#"
+    WITH {EXPECTED},
+    actual AS (
+        SELECT app_id, address, total_amount AS total
+          FROM stats_holders
+         WHERE network = $1
+    )
+    SELECT COALESCE(e.app_id, a.app_id) || ' ' || COALESCE(e.address, a.address)
+               || ' expected=' || COALESCE(e.total, 0)
+               || ' actual=' || COALESCE(a.total, 0) AS key
+      FROM expected e
+ FULL JOIN actual a ON a.app_id = e.app_id AND a.address = e.address
+     WHERE COALESCE(e.total, 0) <> COALESCE(a.total, 0)"#;
+
+/// Live and archived rows alike: archiving keeps the `transactions` row.
+const ORPHAN_CHARMS_SQL: &str = r#"
+    SELECT c.txid || ':' || c.vout || ' ' || c.app_id AS key
+      FROM (SELECT txid, vout, app_id, network FROM charms
+             UNION ALL
+            SELECT txid, vout, app_id, network FROM charms_archive) c
+     WHERE c.network = $1
+       AND NOT EXISTS (
+           SELECT 1 FROM transactions t
+            WHERE t.txid = c.txid AND t.network = c.network
+       )"#;
+
+/// A spell whose charms were all archived still has charms.
+const ORPHAN_SPELLS_SQL: &str = r#"
+    SELECT s.txid AS key
+      FROM spells s
+     WHERE s.network = $1
+       AND NOT EXISTS (
+           SELECT 1 FROM charms c
+            WHERE c.txid = s.txid AND c.network = s.network
+       )
+       AND NOT EXISTS (
+           SELECT 1 FROM charms_archive a
+            WHERE a.txid = s.txid AND a.network = s.network
+       )"#;
+
+fn expand(sql: &str) -> String {
+    sql.replace("{CIRC}", CIRCULATING_CTE)
+        .replace("{EXPECTED}", EXPECTED_HOLDERS_CTE)
+}
+
+/// Run `check` for `network`; returns the discrepancy count and up to
+/// `sample` example keys.
+pub async fn run_check(
+    conn: &DatabaseConnection,
+    check: Check,
+    network: &str,
+    sample: u64,
+) -> Result<(u64, Vec<String>), DbError> {
+    let sql = format!(
+        "SELECT key, COUNT(*) OVER () AS total FROM ({}) q ORDER BY key LIMIT {}",
+        expand(check.sql()),
+        sample.max(1)
+    );
+    let rows = conn
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            [network.into()],
+        ))
+        .await?;
+
+    let total = match rows.first() {
+        Some(row) => row.try_get::<i64>("", "total")? as u64,
+        None => 0,
+    };
+    let examples = rows
+        .iter()
+        .take(sample as usize)
+        .map(|r| r.try_get::<String>("", "key"))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((total, examples))
+}
+
+/// Spells are only present on deployments created by the sea-orm migrator.
+pub async fn spells_table_exists(conn: &DatabaseConnection) -> Result<bool, DbError> {
+    let row = conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT to_regclass('spells') IS NOT NULL AS present".to_string(),
+        ))
+        .await?;
+    Ok(match row {
+        Some(r) => r.try_get::<bool>("", "present")?,
+        None => false,
+    })
+}
+
+/// Apply the safe repair for `check`. Returns the number of rows written.
+pub async fn repair(
+    conn: &DatabaseConnection,
+    check: Check,
+    network: &str,
+) -> Result<u64, DbError> {
+    match check {
+        Check::SupplyVsCharms => {
+            // Supply is an upper bound (highest declared supply), so only
+            // raise it to what is provably circulating; never lower it. The
+            // trail rows are written by the same statement.
+            let sql = format!(
+                "WITH {}, raised AS ( \
+                     UPDATE assets a SET total_supply = s.circulating, updated_at = NOW() \
+                       FROM supply s \
+                      WHERE a.id = s.asset_id \
+                        AND (a.total_supply IS NULL OR s.circulating > a.total_supply) \
+                  RETURNING a.app_id, s.total_supply AS old_supply, a.total_supply AS new_supply) \
+                 INSERT INTO supply_changes (app_id, network, delta, reason, old_supply, new_supply) \
+                 SELECT app_id, $1, new_supply - COALESCE(old_supply, 0), '{}', old_supply, new_supply \
+                   FROM raised",
+                CIRCULATING_CTE,
+                SupplyChangeReason::Manual.as_str()
+            );
+            let res = conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    sql,
+                    [network.into()],
+                ))
+                .await?;
+            Ok(res.rows_affected())
+        }
+        Check::HoldersVsCharms => {
+            StatsHoldersRepository::new(conn.clone())
+                .rebuild_from_charms(network, None)
+                .await
+        }
+        Check::CharmsWithoutTransaction | Check::SpellsWithoutCharms => Ok(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_inlines_every_cte_placeholder() {
+        for check in Check::ALL {
+            let sql = expand(check.sql());
+            assert!(!sql.contains('{'), "{} left a placeholder", check.name());
+            assert!(sql.contains("$1"), "{} is not network-scoped", check.name());
+        }
+    }
+
+    #[test]
+    fn only_supply_and_holders_are_fixable() {
+        let fixable: Vec<_> = Check::ALL.into_iter().filter(|c| c.is_fixable()).collect();
+        assert_eq!(fixable, vec![Check::SupplyVsCharms, Check::HoldersVsCharms]);
+    }
+}
@@ -0,0 +1,312 @@
+//! Cross-table consistency verifier.
+//!
+//! Reconciles the derived tables against `charms` per network and reports
+//! how many rows disagree, with a few example keys for each check:
+//! - `assets.total_supply` vs unspent token amounts (t/ ↔ n/ consolidated),
+//!   with the last `supply_changes` write to each supply that disagrees
+//! - `stats_holders` vs balances grouped from charms
+//! - charms without a `transactions` row
+//! - spells without any charm
+//!
+//! Supply and holders only count unspent rows, so charms pruned into
+//! `charms_archive` never affect them; the orphan checks read both tables.
+//!
+//! Runs via `charms-indexer verify` or `VERIFY_MODE=true`. With `fix`, the
+//! supply and holder checks are repaired and then re-checked (supply repairs
+//! are recorded in `supply_changes` as `manual`); orphans are only reported.
+//!
+//! The report also lists each network's charms by parser revision and
+//! indexer version. That distribution is informational and never fails a run.
+
+pub mod checks;
+
+use std::fmt::Write as _;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::domain::services::NativeCharmParser;
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::{CharmRepository, RevisionCount};
+use crate::utils::logging;
+
+pub use checks::Check;
+
+/// Options for a verification run.
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    /// Networks to check; empty means every network present in `charms`.
+    pub networks: Vec<String>,
+    /// Discrepancies tolerated per check before the run counts as failed.
+    pub tolerance: u64,
+    /// Apply safe repairs for fixable checks.
+    pub fix: bool,
+    /// Example keys kept per check.
+    pub sample: u64,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        Self {
+            networks: Vec::new(),
+            tolerance: 0,
+            fix: false,
+            sample: 5,
+        }
+    }
+}
+
+/// Outcome of one check on one network.
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub check: Check,
+    pub network: String,
+    /// Discrepancies left after any repair.
+    pub discrepancies: u64,
+    pub examples: Vec<String>,
+    /// Rows written by `fix`; 0 when nothing was repaired.
+    pub repaired: u64,
+    /// Set when the check could not run (e.g. the spells table is absent).
+    pub skipped: Option<&'static str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    pub results: Vec<CheckResult>,
+    pub tolerance: u64,
+    /// Charms per parser revision and indexer version, by network.
+    pub revisions: Vec<(String, Vec<RevisionCount>)>,
+}
+
+impl VerifyReport {
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.results
+            .iter()
+            .filter(move |r| r.discrepancies > self.tolerance)
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Plain-text report, one block per network.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut current_network: Option<&str> = None;
+        for r in &self.results {
+            if current_network != Some(r.network.as_str()) {
+                let _ = writeln!(out, "[{}]", r.network);
+                current_network = Some(r.network.as_str());
+            }
+            let status = match r.skipped {
+                Some(_) => "SKIP",
+                None if r.discrepancies > self.tolerance => "FAIL",
+                None => "OK",
+            };
+            let _ = write!(
+                out,
+                "  {:<4} {:<28} discrepancies={}",
+                status,
+                r.check.name(),
+                r.discrepancies
+            );
+            if r.repaired > 0 {
+                let _ = write!(out, " repaired={}", r.repaired);
+            }
+            if let Some(reason) = r.skipped {
+                let _ = write!(out, " ({})", reason);
+            }
+            out.push('\n');
+            for example in &r.examples {
+                let _ = writeln!(out, "         - {}", example);
+            }
+        }
+        for (network, counts) in &self.revisions {
+            let _ = writeln!(
+                out,
+                "[{}] charms by parser revision (current {}):",
+                network,
+                NativeCharmParser::PARSER_REVISION
+            );
+            for c in counts {
+                let _ = writeln!(
+                    out,
+                    "  {:<10} {:<12} {}",
+                    c.parser_revision
+                        .map_or("unstamped".to_string(), |r| format!("r{}", r)),
+                    c.indexer_version.as_deref().unwrap_or("-"),
+                    c.charms
+                );
+            }
+        }
+        let failed = self.failures().count();
+        let _ = writeln!(
+            out,
+            "{} check(s) run, {} over tolerance ({})",
+            self.results.len(),
+            failed,
+            self.tolerance
+        );
+        out
+    }
+}
+
+/// Run every check for the requested networks.
+pub async fn run(conn: &DatabaseConnection, opts: &VerifyOptions) -> Result<VerifyReport, DbError> {
+    let networks = if opts.networks.is_empty() {
+        indexed_networks(conn).await?
+    } else {
+        opts.networks.clone()
+    };
+    let has_spells = checks::spells_table_exists(conn).await?;
+    let charms = CharmRepository::new(conn.clone());
+
+    let mut results = Vec::new();
+    let mut revisions = Vec::new();
+    for network in &networks {
+        revisions.push((
+            network.clone(),
+            charms.count_by_parser_revision(Some(network)).await?,
+        ));
+        for check in Check::ALL {
+            if check == Check::SpellsWithoutCharms && !has_spells {
+                results.push(CheckResult {
+                    check,
+                    network: network.clone(),
+                    discrepancies: 0,
+                    examples: Vec::new(),
+                    repaired: 0,
+                    skipped: Some("no spells table"),
+                });
+                continue;
+            }
+
+            let (mut discrepancies, mut examples) =
+                checks::run_check(conn, check, network, opts.sample).await?;
+            let mut repaired = 0;
+            if opts.fix && discrepancies > 0 && check.is_fixable() {
+                repaired = checks::repair(conn, check, network).await?;
+                logging::log_info(&format!(
+                    "[{}] 🔧 verify: {} repaired {} row(s)",
+                    network,
+                    check.name(),
+                    repaired
+                ));
+                (discrepancies, examples) =
+                    checks::run_check(conn, check, network, opts.sample).await?;
+            }
+
+            results.push(CheckResult {
+                check,
+                network: network.clone(),
+                discrepancies,
+                examples,
+                repaired,
+                skipped: None,
+            });
+        }
+    }
+
+    Ok(VerifyReport {
+        results,
+        tolerance: opts.tolerance,
+        revisions,
+    })
+}
+
+async fn indexed_networks(conn: &DatabaseConnection) -> Result<Vec<String>, DbError> {
+    let rows = conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT network FROM charms UNION SELECT network FROM charms_archive \
+             ORDER BY network"
+                .to_string(),
+        ))
+        .await?;
+    rows.iter()
+        .map(|r| r.try_get::<String>("", "network").map_err(DbError::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(check: Check, discrepancies: u64) -> CheckResult {
+        CheckResult {
+            check,
+            network: "mainnet".to_string(),
+            discrepancies,
+            examples: Vec::new(),
+            repaired: 0,
+            skipped: None,
+        }
+    }
+
+    #[test]
+    fn tolerance_is_per_check() {
+        let report = VerifyReport {
+            results: vec![
+                result(Check::SupplyVsCharms, 2),
+                result(Check::HoldersVsCharms, 1),
+            ],
+            tolerance: 2,
+            revisions: Vec::new(),
+        };
+        assert!(report.passed());
+
+        let report = VerifyReport {
+            tolerance: 1,
+            ..report
+        };
+        assert!(!report.passed());
+        assert_eq!(
+            report.failures().map(|r| r.check).collect::<Vec<_>>(),
+            vec![Check::SupplyVsCharms]
+        );
+    }
+
+    #[test]
+    fn render_lists_status_and_examples() {
+        let mut failing = result(Check::CharmsWithoutTransaction, 1);
+        failing.examples.push("aa:0 t/x/y".to_string());
+        let report = VerifyReport {
+            results: vec![result(Check::SupplyVsCharms, 0), failing],
+            tolerance: 0,
+            revisions: Vec::new(),
+        };
+        let text = report.render();
+        assert!(text.starts_with("[mainnet]\n"));
+        assert!(text.contains("OK   supply_vs_charms"));
+        assert!(text.contains("FAIL charms_without_transaction"));
+        assert!(text.contains("- aa:0 t/x/y"));
+        assert!(text.ends_with("2 check(s) run, 1 over tolerance (0)\n"));
+    }
+
+    #[test]
+    fn render_lists_parser_revisions_without_failing() {
+        let report = VerifyReport {
+            results: Vec::new(),
+            tolerance: 0,
+            revisions: vec![(
+                "mainnet".to_string(),
+                vec![
+                    RevisionCount {
+                        parser_revision: None,
+                        indexer_version: None,
+                        charms: 3,
+                    },
+                    RevisionCount {
+                        parser_revision: Some(1),
+                        indexer_version: Some("0.2.0".to_string()),
+                        charms: 40,
+                    },
+                ],
+            )],
+        };
+        let text = report.render();
+        assert!(report.passed());
+        assert!(text.contains("[mainnet] charms by parser revision (current"));
+        assert!(text.contains("  unstamped  -            3\n"));
+        assert!(text.contains("  r1         0.2.0        40\n"));
+    }
+}
@@ -1,8 +1,9 @@
 //! `cargo run --bin migrate` — apply pending SQL migrations in order.
 //!
-//! Bundles every file in `../database/migrations/` into the binary at compile
-//! time, so deployments do not need the source tree present at runtime. Each
-//! file is applied inside a transaction and recorded in `seaql_migrations`.
+//! Every file in `../database/migrations/` is bundled into the binary at
+//! compile time (see `persistence::migrations`), so deployments do not need
+//! the source tree present at runtime. Each file is applied inside a
+//! transaction and recorded in `seaql_migrations`.
 //!
 //! Versions are derived from filename prefix: `m{YYYYMMDD}_{NNNNNN}_*.sql`.
 //! The full filename without `.sql` extension is used as the version key,
@@ -11,56 +12,9 @@
 use sea_orm::{ConnectionTrait, Database, DbBackend, Statement, TransactionTrait};
 
 use charms_indexer::config::AppConfig;
+use charms_indexer::infrastructure::persistence::migrations::MIGRATIONS;
 use charms_indexer::utils::logging;
 
-/// (filename_without_ext, sql_contents) — populated by `include_str!`.
-const MIGRATIONS: &[(&str, &str)] = &[
-    (
-        "m20260221_000001_mempool_indexing",
-        include_str!("../../../database/migrations/m20260221_000001_mempool_indexing.sql"),
-    ),
-    (
-        "m20260228_000001_address_utxos_and_backfill",
-        include_str!(
-            "../../../database/migrations/m20260228_000001_address_utxos_and_backfill.sql"
-        ),
-    ),
-    (
-        "m20260228_000002_address_transactions",
-        include_str!("../../../database/migrations/m20260228_000002_address_transactions.sql"),
-    ),
-    (
-        "m20260301_000001_transactions_mempool_columns",
-        include_str!(
-            "../../../database/migrations/m20260301_000001_transactions_mempool_columns.sql"
-        ),
-    ),
-    (
-        "m20260323_000001_tx_type_column",
-        include_str!("../../../database/migrations/m20260323_000001_tx_type_column.sql"),
-    ),
-    (
-        "m20260611_000001_stats_holders_network_column",
-        include_str!(
-            "../../../database/migrations/m20260611_000001_stats_holders_network_column.sql"
-        ),
-    ),
-    (
-        "m20260613_000001_mainnet_readiness",
-        include_str!("../../../database/migrations/m20260613_000001_mainnet_readiness.sql"),
-    ),
-    (
-        "m20260615_000001_charms_pk_with_app_id",
-        include_str!("../../../database/migrations/m20260615_000001_charms_pk_with_app_id.sql"),
-    ),
-    (
-        "m20260622_000001_assets_unique_app_id_network",
-        include_str!(
-            "../../../database/migrations/m20260622_000001_assets_unique_app_id_network.sql"
-        ),
-    ),
-];
-
 #[tokio::main]
 async fn main() {
     logging::init_logger();
@@ -0,0 +1,448 @@
+//! Command-line interface of the `charms-indexer` binary.
+//!
+//! With no subcommand the binary runs the live indexer, as it always has,
+//! unless `--check` or a mode variable picks a one-shot job instead
+//! (`STARTUP_CHECK_ONLY`, `VERIFY_MODE`, `REINDEX_MODE`, `RECOMPUTE_HOLDERS`,
+//! `BACKFILL_METADATA`, `BACKFILL_ADDRESSES`, `BACKFILL_DEPLOYS`,
+//! `BACKFILL_SPELL_OUTPUTS`, `EXPORT_SNAPSHOT`, `IMPORT_SNAPSHOT`). Every flag
+//! also reads an environment variable, so container deployments can run a
+//! job without changing the entrypoint.
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+pub const BIN_NAME: &str = "charms-indexer";
+
+/// Mode variables and the subcommand each one selects, in precedence order.
+const ENV_MODES: [(&str, &str); 10] = [
+    ("STARTUP_CHECK_ONLY", "check"),
+    ("VERIFY_MODE", "verify"),
+    ("REINDEX_MODE", "reindex"),
+    ("RECOMPUTE_HOLDERS", "recompute-holders"),
+    ("BACKFILL_METADATA", "backfill-metadata"),
+    ("BACKFILL_ADDRESSES", "backfill-addresses"),
+    ("BACKFILL_DEPLOYS", "backfill-deploys"),
+    ("BACKFILL_SPELL_OUTPUTS", "backfill-spell-outputs"),
+    ("EXPORT_SNAPSHOT", "export-snapshot"),
+    ("IMPORT_SNAPSHOT", "import-snapshot"),
+];
+
+#[derive(Debug, Parser)]
+#[command(name = BIN_NAME, version, about = "Bitcoin indexer for the Charms protocol")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    /// Run the pre-flight checks and exit (same as `check`)
+    #[arg(long)]
+    pub check: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Live indexing of every enabled network (default)
+    Run,
+    /// Check node and database compatibility; exits 1 when a check fails
+    Check,
+    /// Re-run block processing over a height range, then rebuild holders
+    Reindex {
+        /// First height to reprocess
+        #[arg(long, env = "REINDEX_FROM")]
+        from: u64,
+        /// Last height to reprocess, inclusive (defaults to --from)
+        #[arg(long, env = "REINDEX_TO")]
+        to: Option<u64>,
+        #[arg(
+            long,
+            env = "REINDEX_NETWORK",
+            default_value = "mainnet",
+            value_parser = network_arg
+        )]
+        network: String,
+        /// Replay stored hex and report what would change, without touching anything
+        #[arg(long, env = "REINDEX_DRY_RUN")]
+        dry_run: bool,
+        /// Also write the dry-run report to this JSON file
+        #[arg(long, env = "REINDEX_REPORT", requires = "dry_run")]
+        report: Option<PathBuf>,
+        /// Only reprocess heights with charms written by an older parser revision
+        #[arg(long, env = "REINDEX_PARSER_REVISION_LT")]
+        parser_revision_lt: Option<i32>,
+        /// Blocks attempted between two progress lines
+        #[arg(long, env = "REINDEX_BATCH_SIZE", default_value_t = 1000)]
+        batch_size: u64,
+        /// Failed attempts after which a block is sidelined (skipped by later runs)
+        #[arg(long, env = "REINDEX_MAX_ATTEMPTS", default_value_t = 3)]
+        max_attempts: u32,
+        /// Reset the failure counts in the range first, so sidelined blocks run again
+        #[arg(long, env = "REINDEX_RETRY_FAILED")]
+        retry_failed: bool,
+        /// Run even while a live indexer is running on the network, pausing its
+        /// block processing until the reindex ends
+        #[arg(long, env = "REINDEX_FORCE")]
+        force: bool,
+    },
+    /// Reconcile derived tables against charms; exits 1 when over tolerance
+    Verify {
+        /// Network to check; repeat for several (default: all indexed)
+        #[arg(
+            long = "network",
+            env = "VERIFY_NETWORKS",
+            value_delimiter = ',',
+            value_parser = network_arg
+        )]
+        networks: Vec<String>,
+        /// Discrepancies tolerated per check
+        #[arg(long, env = "VERIFY_TOLERANCE", default_value_t = 0)]
+        tolerance: u64,
+        /// Example keys printed per check
+        #[arg(long, env = "VERIFY_SAMPLE", default_value_t = 5)]
+        sample: u64,
+        /// Repair supply and holder discrepancies, then re-check
+        #[arg(long, env = "VERIFY_FIX")]
+        fix: bool,
+    },
+    /// Rebuild stats_holders from unspent charms
+    RecomputeHolders {
+        /// Only this asset (`t/` or `n/` app_id); default is every asset
+        #[arg(long, env = "RECOMPUTE_HOLDERS_APP_ID")]
+        app_id: Option<String>,
+        #[arg(
+            long,
+            env = "RECOMPUTE_HOLDERS_NETWORK",
+            default_value = "mainnet",
+            value_parser = network_arg
+        )]
+        network: String,
+    },
+    /// Fill missing asset name/symbol/description/image from stored charms
+    BackfillMetadata {
+        /// Only this network (default: all)
+        #[arg(long, env = "BACKFILL_METADATA_NETWORK", value_parser = network_arg)]
+        network: Option<String>,
+    },
+    /// Re-derive missing charm addresses from stored transaction hex
+    BackfillAddresses {
+        /// Only this network (default: all)
+        #[arg(long, env = "BACKFILL_ADDRESSES_NETWORK", value_parser = network_arg)]
+        network: Option<String>,
+    },
+    /// Record each asset's first-seen (deploy) transaction from stored charms
+    BackfillDeploys {
+        /// Only this network (default: all)
+        #[arg(long, env = "BACKFILL_DEPLOYS_NETWORK", value_parser = network_arg)]
+        network: Option<String>,
+    },
+    /// Derive each stored spell's per-output app map from its JSON
+    BackfillSpellOutputs {
+        /// Only this network (default: all)
+        #[arg(long, env = "BACKFILL_SPELL_OUTPUTS_NETWORK", value_parser = network_arg)]
+        network: Option<String>,
+    },
+    /// Run a registered backfill in batches, resuming after its last
+    /// committed batch; Ctrl+C stops it between batches
+    Backfill {
+        /// Backfill to run (see --list)
+        #[arg(required_unless_present = "list")]
+        name: Option<String>,
+        /// List the registered backfills and exit
+        #[arg(long, conflicts_with = "name")]
+        list: bool,
+        /// Start over from the first key (idempotent backfills only)
+        #[arg(long)]
+        restart: bool,
+        /// Primary keys per batch
+        #[arg(long, env = "BACKFILL_BATCH_SIZE", default_value_t = 1000)]
+        batch_size: u64,
+        /// Rows written per second at most; 0 lifts the limit
+        #[arg(long, env = "BACKFILL_ROWS_PER_SEC", default_value_t = 5000)]
+        rows_per_sec: u64,
+    },
+    /// Dump one network's indexed tables to a compressed snapshot directory
+    ExportSnapshot {
+        #[arg(
+            long,
+            env = "SNAPSHOT_NETWORK",
+            default_value = "mainnet",
+            value_parser = network_arg
+        )]
+        network: String,
+        /// Parent directory; the snapshot is written to `<dir>/<network>-<tip>`
+        #[arg(long, env = "SNAPSHOT_DIR")]
+        dir: PathBuf,
+        /// Export transactions with an empty `raw` column
+        #[arg(long, env = "SNAPSHOT_WITHOUT_RAW")]
+        without_raw: bool,
+    },
+    /// Load a snapshot into an empty database so indexing resumes at its tip
+    ImportSnapshot {
+        /// Snapshot directory (the one holding manifest.json)
+        #[arg(long, env = "SNAPSHOT_DIR")]
+        dir: PathBuf,
+        /// Replace the network's existing rows and ignore a schema mismatch
+        #[arg(long, env = "SNAPSHOT_FORCE")]
+        force: bool,
+    },
+}
+
+impl Cli {
+    /// The command to run: the explicit subcommand, else the one selected by
+    /// a mode variable (its flags then come from the environment), else `run`.
+    pub fn into_command(self) -> Result<Command, clap::Error> {
+        if let Some(command) = self.command {
+            return Ok(command);
+        }
+        if self.check {
+            return Ok(Command::Check);
+        }
+        match env_mode(|var| std::env::var(var).ok()) {
+            Some(subcommand) => Cli::try_parse_from([BIN_NAME, subcommand])
+                .map(|cli| cli.command.unwrap_or(Command::Run)),
+            None => Ok(Command::Run),
+        }
+    }
+}
+
+/// First subcommand whose mode variable is set to `true`.
+pub fn env_mode(lookup: impl Fn(&str) -> Option<String>) -> Option<&'static str> {
+    ENV_MODES
+        .iter()
+        .find(|(var, _)| {
+            lookup(var)
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or(false)
+        })
+        .map(|(_, subcommand)| *subcommand)
+}
+
+/// `--network` values are stored and filtered in canonical form, so
+/// `Testnet` selects the `testnet4` rows.
+fn network_arg(raw: &str) -> Result<String, String> {
+    Ok(charms_core::normalize_network(raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse(args: &[&str]) -> Option<Command> {
+        Cli::try_parse_from(std::iter::once(BIN_NAME).chain(args.iter().copied()))
+            .unwrap()
+            .command
+    }
+
+    #[test]
+    fn cli_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn no_arguments_means_no_subcommand() {
+        assert_eq!(parse(&[]), None);
+    }
+
+    #[test]
+    fn the_check_flag_selects_the_check_command() {
+        let cli = Cli::try_parse_from([BIN_NAME, "--check"]).unwrap();
+        assert_eq!(cli.into_command().unwrap(), Command::Check);
+        assert_eq!(parse(&["check"]), Some(Command::Check));
+        assert!(Cli::try_parse_from([BIN_NAME, "--check", "verify"]).is_err());
+    }
+
+    #[test]
+    fn reindex_flags_parse() {
+        assert_eq!(
+            parse(&["reindex", "--from", "10", "--to", "20", "--network", "testnet4", "--dry-run"]),
+            Some(Command::Reindex {
+                from: 10,
+                to: Some(20),
+                network: "testnet4".to_string(),
+                dry_run: true,
+                report: None,
+                parser_revision_lt: None,
+                batch_size: 1000,
+                max_attempts: 3,
+                retry_failed: false,
+                force: false,
+            })
+        );
+        assert_eq!(
+            parse(&["reindex", "--from", "10", "--dry-run", "--report", "/tmp/r.json"]),
+            Some(Command::Reindex {
+                from: 10,
+                to: None,
+                network: "mainnet".to_string(),
+                dry_run: true,
+                report: Some(PathBuf::from("/tmp/r.json")),
+                parser_revision_lt: None,
+                batch_size: 1000,
+                max_attempts: 3,
+                retry_failed: false,
+                force: false,
+            })
+        );
+        assert_eq!(
+            parse(&["reindex", "--from", "10", "--to", "20", "--parser-revision-lt", "2"]),
+            Some(Command::Reindex {
+                from: 10,
+                to: Some(20),
+                network: "mainnet".to_string(),
+                dry_run: false,
+                report: None,
+                parser_revision_lt: Some(2),
+                batch_size: 1000,
+                max_attempts: 3,
+                retry_failed: false,
+                force: false,
+            })
+        );
+        assert_eq!(
+            parse(&["reindex", "--from", "10", "--max-attempts", "5", "--retry-failed", "--force"]),
+            Some(Command::Reindex {
+                from: 10,
+                to: None,
+                network: "mainnet".to_string(),
+                dry_run: false,
+                report: None,
+                parser_revision_lt: None,
+                batch_size: 1000,
+                max_attempts: 5,
+                retry_failed: true,
+                force: true,
+            })
+        );
+    }
+
+    #[test]
+    fn verify_accepts_repeated_networks() {
+        assert_eq!(
+            parse(&["verify", "--network", "mainnet", "--network", "testnet4", "--fix"]),
+            Some(Command::Verify {
+                networks: vec!["mainnet".to_string(), "testnet4".to_string()],
+                tolerance: 0,
+                sample: 5,
+                fix: true,
+            })
+        );
+    }
+
+    #[test]
+    fn snapshot_flags_parse() {
+        assert_eq!(
+            parse(&["export-snapshot", "--dir", "/snapshots", "--network", "testnet4", "--without-raw"]),
+            Some(Command::ExportSnapshot {
+                network: "testnet4".to_string(),
+                dir: PathBuf::from("/snapshots"),
+                without_raw: true,
+            })
+        );
+        assert_eq!(
+            parse(&["import-snapshot", "--dir", "/snapshots/testnet4-100"]),
+            Some(Command::ImportSnapshot {
+                dir: PathBuf::from("/snapshots/testnet4-100"),
+                force: false,
+            })
+        );
+    }
+
+    #[test]
+    fn backfill_addresses_network_is_optional() {
+        assert_eq!(
+            parse(&["backfill-addresses"]),
+            Some(Command::BackfillAddresses { network: None })
+        );
+        assert_eq!(
+            parse(&["backfill-addresses", "--network", "testnet4"]),
+            Some(Command::BackfillAddresses {
+                network: Some("testnet4".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn backfill_deploys_network_is_optional() {
+        assert_eq!(
+            parse(&["backfill-deploys", "--network", "mainnet"]),
+            Some(Command::BackfillDeploys {
+                network: Some("mainnet".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn network_arguments_are_canonicalised() {
+        assert_eq!(
+            parse(&["backfill-deploys", "--network", "Bitcoin-testnet4"]),
+            Some(Command::BackfillDeploys {
+                network: Some("testnet4".to_string())
+            })
+        );
+        assert_eq!(
+            parse(&["verify", "--network", "Mainnet,Testnet"]),
+            Some(Command::Verify {
+                networks: vec!["mainnet".to_string(), "testnet4".to_string()],
+                tolerance: 0,
+                sample: 5,
+                fix: false,
+            })
+        );
+    }
+
+    #[test]
+    fn backfill_spell_outputs_network_is_optional() {
+        assert_eq!(
+            parse(&["backfill-spell-outputs"]),
+            Some(Command::BackfillSpellOutputs { network: None })
+        );
+    }
+
+    #[test]
+    fn backfill_takes_a_name_or_list() {
+        assert_eq!(
+            parse(&["backfill", "block_time", "--restart", "--rows-per-sec", "0"]),
+            Some(Command::Backfill {
+                name: Some("block_time".to_string()),
+                list: false,
+                restart: true,
+                batch_size: 1000,
+                rows_per_sec: 0,
+            })
+        );
+        assert_eq!(
+            parse(&["backfill", "--list"]),
+            Some(Command::Backfill {
+                name: None,
+                list: true,
+                restart: false,
+                batch_size: 1000,
+                rows_per_sec: 5000,
+            })
+        );
+        assert!(Cli::try_parse_from([BIN_NAME, "backfill"]).is_err());
+    }
+
+    #[test]
+    fn env_mode_picks_first_true_variable() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |var: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == var)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(env_mode(env(&[])), None);
+        assert_eq!(env_mode(env(&[("VERIFY_MODE", "false")])), None);
+        assert_eq!(env_mode(env(&[("VERIFY_MODE", "yes")])), None);
+        assert_eq!(
+            env_mode(env(&[("RECOMPUTE_HOLDERS", "true"), ("BACKFILL_METADATA", "true")])),
+            Some("recompute-holders")
+        );
+        assert_eq!(
+            env_mode(env(&[("REINDEX_MODE", "true"), ("VERIFY_MODE", "true")])),
+            Some("verify")
+        );
+    }
+}
@@ -2,6 +2,9 @@ use dotenv::dotenv;
 use std::collections::HashMap;
 use std::env;
 
+mod snapshot;
+pub use snapshot::{redact_url, Sources};
+
 /// Network type enum
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum NetworkType {
@@ -21,12 +24,14 @@ pub struct NetworkId {
 }
 
 impl NetworkId {
-    /// Create a new network identifier
+    /// Create a new network identifier. Bitcoin names are stored in their
+    /// canonical form (`charms_core::network`), whatever the spelling.
     pub fn new(network_type: NetworkType, name: &str) -> Self {
-        Self {
-            network_type,
-            name: name.to_string(),
-        }
+        let name = match network_type {
+            NetworkType::Bitcoin => charms_core::normalize_network(name),
+            NetworkType::Cardano => name.to_string(),
+        };
+        Self { network_type, name }
     }
 
     /// Get the blockchain type as a string
@@ -63,6 +68,77 @@ impl ProviderType {
     }
 }
 
+/// One entry of `BITCOIN_<NET>_PROVIDERS`, e.g.
+/// `[{"type":"quicknode","url":"https://…","weight":2},{"type":"bitcoin_node","weight":1,"primary":true}]`.
+/// `bitcoin_node` entries fall back to the network's `BITCOIN_<NET>_RPC_*`
+/// values for any connection field they omit.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct ProviderSpec {
+    #[serde(rename = "type")]
+    pub provider_type: String,
+    /// QuickNode endpoint URL
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
+    pub port: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Relative share of tx/block reads; 0 keeps the endpoint for failover only
+    #[serde(default = "ProviderSpec::default_weight")]
+    pub weight: u32,
+    /// Serves `getblockcount`/`getblockhash` so the tip stays consistent
+    #[serde(default)]
+    pub primary: bool,
+}
+
+impl ProviderSpec {
+    fn default_weight() -> u32 {
+        1
+    }
+
+    /// Parse and validate a JSON provider list. When no entry is flagged
+    /// `primary`, the first one becomes primary.
+    pub fn parse_list(json: &str) -> Result<Vec<ProviderSpec>, String> {
+        let mut specs: Vec<ProviderSpec> =
+            serde_json::from_str(json).map_err(|e| format!("invalid provider list: {}", e))?;
+        if specs.is_empty() {
+            return Err("provider list is empty".to_string());
+        }
+        for (i, spec) in specs.iter().enumerate() {
+            match spec.provider_type.to_lowercase().as_str() {
+                "quicknode" if spec.url.as_deref().unwrap_or("").is_empty() => {
+                    return Err(format!("provider #{} (quicknode) needs a url", i));
+                }
+                "quicknode" | "bitcoin_node" => {}
+                other => return Err(format!("provider #{} has unknown type '{}'", i, other)),
+            }
+        }
+        if specs.iter().all(|s| s.weight == 0) {
+            return Err("at least one provider needs a non-zero weight".to_string());
+        }
+        match specs.iter().filter(|s| s.primary).count() {
+            0 => specs[0].primary = true,
+            1 => {}
+            n => return Err(format!("{} providers flagged primary, expected one", n)),
+        }
+        Ok(specs)
+    }
+
+    /// Read `BITCOIN_<NET>_PROVIDERS`; unset or blank means single-provider mode.
+    fn from_env(network: &str) -> Vec<ProviderSpec> {
+        let key = format!("BITCOIN_{}_PROVIDERS", network.to_uppercase());
+        match env::var(&key) {
+            Ok(json) if !json.trim().is_empty() => ProviderSpec::parse_list(&json)
+                .unwrap_or_else(|e| panic!("{} is invalid: {}", key, e)),
+            _ => Vec::new(),
+        }
+    }
+}
+
 /// Configuration for the Bitcoin client
 #[derive(Debug, Clone)]
 pub struct BitcoinConfig {
@@ -82,6 +158,8 @@ pub struct BitcoinConfig {
     pub quicknode_endpoint: Option<String>,
     /// Provider type to use for this network
     pub provider_type: ProviderType,
+    /// Weighted endpoint list; when non-empty it replaces `provider_type`
+    pub providers: Vec<ProviderSpec>,
 }
 
 /// Configuration for the Cardano client
@@ -135,6 +213,69 @@ pub struct IndexerConfig {
     pub btc_auto_seeder_idle_interval_ms: u64,
     /// Maestro API key (PRIVATE). Empty disables the seeder.
     pub private_maestro_api_key: String,
+    /// Garbage collector: periodically delete orphaned rows (see `gc.rs`).
+    pub gc_enabled: bool,
+    /// Seconds between GC passes.
+    pub gc_interval_secs: u64,
+    /// Rows deleted per statement, to keep row locks short.
+    pub gc_batch_size: u64,
+    /// Monitored addresses idle for this many days are dropped.
+    pub gc_monitored_idle_days: u64,
+    /// Changefeed entries older than this many days are dropped.
+    pub gc_changefeed_retention_days: u64,
+    /// UTXO spend records more than this many blocks below the tip are dropped.
+    pub gc_utxo_spends_retention_blocks: u64,
+    /// Per-sweep toggles.
+    pub gc_sweep_mempool_charms: bool,
+    pub gc_sweep_dex_orders: bool,
+    pub gc_sweep_monitored_addresses: bool,
+    pub gc_sweep_mempool_spends: bool,
+    pub gc_sweep_expired_monitors: bool,
+    pub gc_sweep_changefeed: bool,
+    pub gc_sweep_utxo_spends: bool,
+    /// Prune charms spent more than this many blocks ago (see `archive.rs`);
+    /// `None` keeps every row.
+    pub prune_spent_charms_after_blocks: Option<u64>,
+    /// Move pruned charms to `charms_archive` instead of deleting them.
+    pub archive_pruned_charms: bool,
+    /// Addresses whose token holdings count as locked supply, besides open
+    /// DEX order addresses (see `locked_supply.rs`); `*` suffix wildcards.
+    pub locked_supply_addresses: Vec<String>,
+    /// Name this replica uses in leader election and `indexer_replicas`.
+    pub instance_id: String,
+    /// Webhook delivery worker (see `webhooks.rs`).
+    pub webhooks_enabled: bool,
+    /// Attempts per delivery before it is given up.
+    pub webhook_max_attempts: i32,
+    /// Consecutive failed attempts that deactivate a subscription.
+    pub webhook_disable_after: i32,
+    /// Per-request timeout, seconds.
+    pub webhook_timeout_secs: u64,
+    /// Queue and fetch off-chain NFT metadata documents (see
+    /// `offchain_metadata.rs`).
+    pub fetch_offchain_metadata: bool,
+    /// Catch-up blocks aggregated into one progress line (see `progress.rs`).
+    pub progress_every_blocks: u64,
+    /// Longest time between two catch-up progress lines, seconds.
+    pub progress_interval_secs: u64,
+    /// Seconds between chain tip comparisons of the primary and fallback
+    /// providers (see `tip_watch.rs`).
+    pub tip_watch_interval_secs: u64,
+    /// Blocks the primary may trail the fallback before it counts as lagging.
+    pub tip_watch_max_lag_blocks: u64,
+    /// Seconds of continuous lag before the primary is flagged stale.
+    pub tip_watch_stale_after_secs: u64,
+    /// Processed blocks re-run on startup before resuming, to pick up
+    /// charms a stale provider answer hid (see `rescan.rs`); 0 disables.
+    pub rescan_depth: u64,
+    /// Refuse to start when a pre-flight check fails instead of logging it
+    /// (see `preflight.rs`).
+    pub startup_check_fatal: bool,
+    /// Primary keys a backfill covers per batch (see
+    /// `maintenance::backfill`).
+    pub backfill_batch_size: u64,
+    /// Rows a backfill may write per second; 0 lifts the limit.
+    pub backfill_rows_per_sec: u64,
 }
 
 /// Application configuration
@@ -193,6 +334,7 @@ impl AppConfig {
                         .expect("BITCOIN_TESTNET4_GENESIS_BLOCK_HEIGHT must be a valid u64"),
                     quicknode_endpoint: None, // Testnet4 uses local node only
                     provider_type,
+                    providers: ProviderSpec::from_env("testnet4"),
                 },
             );
         }
@@ -228,6 +370,7 @@ impl AppConfig {
                         .expect("BITCOIN_MAINNET_GENESIS_BLOCK_HEIGHT must be a valid u64"),
                     quicknode_endpoint: env::var("BITCOIN_MAINNET_QUICKNODE_ENDPOINT").ok(),
                     provider_type,
+                    providers: ProviderSpec::from_env("mainnet"),
                 },
             );
         }
@@ -328,6 +471,131 @@ impl AppConfig {
                 .parse::<u64>()
                 .unwrap_or(30000),
             private_maestro_api_key: env::var("PRIVATE_MAESTRO_API_KEY").unwrap_or_default(),
+            gc_enabled: env::var("ENABLE_GC")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            gc_interval_secs: env::var("GC_INTERVAL_SECS")
+                .unwrap_or_else(|_| "21600".to_string())
+                .parse::<u64>()
+                .unwrap_or(21600),
+            gc_batch_size: env::var("GC_BATCH_SIZE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse::<u64>()
+                .unwrap_or(1000),
+            gc_monitored_idle_days: env::var("GC_MONITORED_IDLE_DAYS")
+                .unwrap_or_else(|_| "180".to_string())
+                .parse::<u64>()
+                .unwrap_or(180),
+            gc_changefeed_retention_days: env::var("GC_CHANGEFEED_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse::<u64>()
+                .unwrap_or(30),
+            gc_utxo_spends_retention_blocks: env::var("GC_UTXO_SPENDS_RETENTION_BLOCKS")
+                .unwrap_or_else(|_| "4320".to_string())
+                .parse::<u64>()
+                .unwrap_or(4320),
+            gc_sweep_mempool_charms: env::var("GC_SWEEP_MEMPOOL_CHARMS")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            gc_sweep_dex_orders: env::var("GC_SWEEP_DEX_ORDERS")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            gc_sweep_monitored_addresses: env::var("GC_SWEEP_MONITORED_ADDRESSES")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            gc_sweep_mempool_spends: env::var("GC_SWEEP_MEMPOOL_SPENDS")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            gc_sweep_expired_monitors: env::var("GC_SWEEP_EXPIRED_MONITORS")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            gc_sweep_changefeed: env::var("GC_SWEEP_CHANGEFEED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            gc_sweep_utxo_spends: env::var("GC_SWEEP_UTXO_SPENDS")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            prune_spent_charms_after_blocks: env::var("PRUNE_SPENT_CHARMS_AFTER_BLOCKS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok()),
+            archive_pruned_charms: env::var("ARCHIVE")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            locked_supply_addresses: env::var("LOCKED_SUPPLY_ADDRESSES")
+                .map(|v| v.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            instance_id: env::var("INDEXER_INSTANCE_ID").unwrap_or_else(|_| {
+                format!(
+                    "{}-{}",
+                    env::var("HOSTNAME").unwrap_or_else(|_| "indexer".to_string()),
+                    std::process::id()
+                )
+            }),
+            webhooks_enabled: env::var("ENABLE_WEBHOOKS")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse::<bool>()
+                .unwrap_or(true),
+            webhook_max_attempts: env::var("WEBHOOK_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "12".to_string())
+                .parse::<i32>()
+                .unwrap_or(12),
+            webhook_disable_after: env::var("WEBHOOK_DISABLE_AFTER_FAILURES")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse::<i32>()
+                .unwrap_or(10),
+            webhook_timeout_secs: env::var("WEBHOOK_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse::<u64>()
+                .unwrap_or(10),
+            fetch_offchain_metadata: env::var("FETCH_OFFCHAIN_METADATA")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .unwrap_or(false),
+            progress_every_blocks: env::var("INDEXER_PROGRESS_EVERY_BLOCKS")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse::<u64>()
+                .unwrap_or(100),
+            progress_interval_secs: env::var("INDEXER_PROGRESS_INTERVAL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse::<u64>()
+                .unwrap_or(30),
+            tip_watch_interval_secs: env::var("TIP_WATCH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse::<u64>()
+                .unwrap_or(60),
+            tip_watch_max_lag_blocks: env::var("TIP_WATCH_MAX_LAG_BLOCKS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse::<u64>()
+                .unwrap_or(3),
+            tip_watch_stale_after_secs: env::var("TIP_WATCH_STALE_AFTER_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse::<u64>()
+                .unwrap_or(600),
+            rescan_depth: env::var("RESCAN_DEPTH")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse::<u64>()
+                .unwrap_or(0),
+            startup_check_fatal: env::var("STARTUP_CHECK_FATAL")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .unwrap_or(false),
+            backfill_batch_size: env::var("BACKFILL_BATCH_SIZE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse::<u64>()
+                .unwrap_or(1000),
+            backfill_rows_per_sec: env::var("BACKFILL_ROWS_PER_SEC")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse::<u64>()
+                .unwrap_or(5000),
         };
 
         Self {
@@ -349,3 +617,51 @@ impl AppConfig {
         self.cardano_configs.get(network)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_weighted_provider_list() {
+        let specs = ProviderSpec::parse_list(
+            r#"[
+                {"type": "quicknode", "url": "https://a.example", "weight": 2},
+                {"type": "quicknode", "url": "https://b.example"},
+                {"type": "bitcoin_node", "host": "10.0.0.5", "port": "8332", "primary": true}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(specs.len(), 3);
+        assert_eq!(specs[0].weight, 2);
+        assert_eq!(specs[1].weight, 1);
+        assert!(!specs[0].primary);
+        assert!(specs[2].primary);
+        assert_eq!(specs[2].host.as_deref(), Some("10.0.0.5"));
+        assert_eq!(specs[2].username, None);
+    }
+
+    #[test]
+    fn first_provider_is_primary_by_default() {
+        let specs = ProviderSpec::parse_list(
+            r#"[{"type": "bitcoin_node"}, {"type": "quicknode", "url": "https://a.example"}]"#,
+        )
+        .unwrap();
+        assert!(specs[0].primary);
+        assert!(!specs[1].primary);
+    }
+
+    #[test]
+    fn rejects_invalid_provider_lists() {
+        assert!(ProviderSpec::parse_list("not json").is_err());
+        assert!(ProviderSpec::parse_list("[]").is_err());
+        assert!(ProviderSpec::parse_list(r#"[{"type": "quicknode"}]"#).is_err());
+        assert!(ProviderSpec::parse_list(r#"[{"type": "electrum", "url": "x"}]"#).is_err());
+        assert!(ProviderSpec::parse_list(r#"[{"type": "bitcoin_node", "weight": 0}]"#).is_err());
+        assert!(ProviderSpec::parse_list(
+            r#"[{"type": "bitcoin_node", "primary": true}, {"type": "bitcoin_node", "primary": true}]"#
+        )
+        .is_err());
+    }
+}
@@ -17,8 +17,7 @@
 /// ```
 use serde::{Deserialize, Serialize};
 
-/// Default number of decimals for tokens (Bitcoin standard)
-pub const DEFAULT_DECIMALS: u8 = 8;
+pub use charms_core::{DEFAULT_DECIMALS, MAX_DECIMALS};
 
 /// Asset metadata extracted from NFT charm data
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +37,9 @@ pub struct AssetMetadata {
 
     /// Image URL (optional)
     pub image_url: Option<String>,
+
+    /// Collection identifier declared by the NFT (optional)
+    pub collection: Option<String>,
 }
 
 impl Default for AssetMetadata {
@@ -48,10 +50,36 @@ impl Default for AssetMetadata {
             symbol: None,
             description: None,
             image_url: None,
+            collection: None,
         }
     }
 }
 
+/// Prefix for collections derived from the deploying address when the NFT
+/// does not declare one.
+pub const DEPLOYER_COLLECTION_PREFIX: &str = "deployer:";
+
+/// Parse a `collection` metadata value: either a plain string or an object
+/// carrying an `id` (or, failing that, a `name`). Blank values are ignored.
+pub fn parse_collection(value: &serde_json::Value) -> Option<String> {
+    let raw = match value {
+        serde_json::Value::String(s) => s.as_str(),
+        serde_json::Value::Object(obj) => obj
+            .get("id")
+            .or_else(|| obj.get("name"))
+            .and_then(|v| v.as_str())?,
+        _ => return None,
+    };
+    let trimmed = raw.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+/// Collection id used for NFTs without a declared collection: everything
+/// minted to the same address is grouped together.
+pub fn deployer_collection(address: &str) -> String {
+    format!("{}{}", DEPLOYER_COLLECTION_PREFIX, address)
+}
+
 /// Normalize image value - handles both URLs and base64 data
 /// People sometimes put URLs in the 'image' field instead of 'image_url'
 /// This function detects the type and returns the value as-is (both are valid for display)
@@ -96,13 +124,13 @@ impl AssetMetadata {
             metadata: &mut AssetMetadata,
         ) {
             if let Some(decimals) = obj.get("decimals").and_then(|v| v.as_u64()) {
-                if decimals > 18 {
+                if decimals > MAX_DECIMALS as u64 {
                     crate::utils::logging::log_warning(&format!(
-                        "asset metadata decimals={} capped to 18",
-                        decimals
+                        "asset metadata decimals={} capped to {}",
+                        decimals, MAX_DECIMALS
                     ));
                 }
-                metadata.decimals = decimals.min(18) as u8;
+                metadata.decimals = decimals.min(MAX_DECIMALS as u64) as u8;
             }
             if metadata.name.is_none() {
                 if let Some(name) = obj.get("name").and_then(|v| v.as_str()) {
@@ -126,6 +154,9 @@ impl AssetMetadata {
                     metadata.image_url = Some(normalize_image_value(image_url));
                 }
             }
+            if metadata.collection.is_none() {
+                metadata.collection = obj.get("collection").and_then(parse_collection);
+            }
         }
 
         // First try top-level (for batch-saved assets)
@@ -147,6 +178,12 @@ impl AssetMetadata {
         metadata
     }
 
+    /// Declared collection, or the deployer grouping when there is none.
+    pub fn collection_or_deployer(&self, deployer: Option<&str>) -> Option<String> {
+        self.collection
+            .clone()
+            .or_else(|| deployer.map(deployer_collection))
+    }
 }
 
 #[cfg(test)]
@@ -176,4 +213,29 @@ mod tests {
         assert_eq!(metadata.symbol, Some("TEST".to_string()));
     }
 
+    #[test]
+    fn test_collection_declared_in_metadata() {
+        let plain = AssetMetadata::from_nft_data(&json!({"data": {"collection": " Punks "}}));
+        assert_eq!(plain.collection.as_deref(), Some("Punks"));
+
+        let object = AssetMetadata::from_nft_data(&json!({
+            "collection": {"id": "punks-v2", "name": "Punks"}
+        }));
+        assert_eq!(object.collection.as_deref(), Some("punks-v2"));
+        assert_eq!(
+            object.collection_or_deployer(Some("bc1qdeployer")).as_deref(),
+            Some("punks-v2")
+        );
+    }
+
+    #[test]
+    fn test_collection_falls_back_to_deployer() {
+        let metadata = AssetMetadata::from_nft_data(&json!({"data": {"collection": ""}}));
+        assert_eq!(metadata.collection, None);
+        assert_eq!(
+            metadata.collection_or_deployer(Some("bc1qdeployer")).as_deref(),
+            Some("deployer:bc1qdeployer")
+        );
+        assert_eq!(metadata.collection_or_deployer(None), None);
+    }
 }
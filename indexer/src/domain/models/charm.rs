@@ -19,9 +19,12 @@ pub struct Charm {
     /// JSON data associated with the charm
     pub data: Value,
 
-    /// Date when the charm was created
+    /// When the indexer wrote the charm (ingestion time, not chain time)
     pub date_created: NaiveDateTime,
 
+    /// Header time of the confirming block (None while in mempool)
+    pub block_time: Option<NaiveDateTime>,
+
     /// Type of asset
     pub asset_type: String,
 
@@ -64,6 +67,7 @@ impl Charm {
         block_height: Option<u64>,
         data: Value,
         date_created: NaiveDateTime,
+        block_time: Option<NaiveDateTime>,
         asset_type: String,
         blockchain: String,
         network: String,
@@ -78,6 +82,7 @@ impl Charm {
             block_height,
             data,
             date_created,
+            block_time,
             asset_type,
             blockchain,
             network,
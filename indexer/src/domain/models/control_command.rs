@@ -0,0 +1,57 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Operator command for a network's block processor, stored in
+/// `control_commands.command`. The newest command per network wins, so
+/// repeating one is a no-op.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ControlCommand {
+    /// Stop processing blocks until resumed; the process keeps running.
+    Pause,
+    Resume,
+}
+
+impl ControlCommand {
+    pub const ALL: [ControlCommand; 2] = [ControlCommand::Pause, ControlCommand::Resume];
+
+    /// Value stored in `control_commands.command`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ControlCommand::Pause => "pause",
+            ControlCommand::Resume => "resume",
+        }
+    }
+}
+
+impl fmt::Display for ControlCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ControlCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|command| command.as_str() == s)
+            .ok_or_else(|| format!("unknown control command: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_round_trips_through_its_column_value() {
+        for command in ControlCommand::ALL {
+            assert_eq!(command.as_str().parse::<ControlCommand>(), Ok(command));
+        }
+        assert!("stop".parse::<ControlCommand>().is_err());
+    }
+}
@@ -1,11 +1,16 @@
 pub mod asset;
 pub mod asset_metadata;
 pub mod charm;
+pub mod control_command;
 pub mod spell;
 pub mod transaction;
+pub mod write_stamp;
 
 pub use asset::Asset;
+pub use charms_core::{AppId, AppKind, AssetType};
 pub use asset_metadata::{AssetMetadata, DEFAULT_DECIMALS};
 pub use charm::Charm;
+pub use control_command::ControlCommand;
 pub use spell::Spell;
-pub use transaction::Transaction;
+pub use transaction::{Transaction, TransactionStatus, CONFIRMATION_DEPTH};
+pub use write_stamp::{WriteStamp, INDEXER_VERSION};
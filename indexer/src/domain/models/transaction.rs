@@ -1,7 +1,72 @@
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// Confirmations after which a transaction (and its block) counts as final.
+pub const CONFIRMATION_DEPTH: i32 = 6;
+
+/// Lifecycle of a row in `transactions.status`.
+///
+/// `Pending` rows come from the mempool and have no block. Mined rows start
+/// as `Confirming` and are promoted to `Confirmed` once their block is
+/// `CONFIRMATION_DEPTH` deep, either at insert time or by the maintenance
+/// pass in `BitcoinProcessor::confirm_pending_blocks`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Pending,
+    Confirming,
+    Confirmed,
+}
+
+impl TransactionStatus {
+    pub const ALL: [TransactionStatus; 3] = [
+        TransactionStatus::Pending,
+        TransactionStatus::Confirming,
+        TransactionStatus::Confirmed,
+    ];
+
+    /// Status of a transaction with `confirmations` confirmations (0 = mempool).
+    pub fn for_confirmations(confirmations: i32) -> Self {
+        if confirmations <= 0 {
+            TransactionStatus::Pending
+        } else if confirmations < CONFIRMATION_DEPTH {
+            TransactionStatus::Confirming
+        } else {
+            TransactionStatus::Confirmed
+        }
+    }
+
+    /// Value stored in `transactions.status`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TransactionStatus::Pending => "pending",
+            TransactionStatus::Confirming => "confirming",
+            TransactionStatus::Confirmed => "confirmed",
+        }
+    }
+}
+
+impl fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TransactionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown transaction status: {}", s))
+    }
+}
+
 /// Represents a blockchain transaction with charm data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -26,8 +91,8 @@ pub struct Transaction {
     /// Number of confirmations
     pub confirmations: i32,
 
-    /// Status of the transaction (pending, confirmed, etc.)
-    pub status: String,
+    /// Lifecycle status of the transaction
+    pub status: TransactionStatus,
 
     /// Blockchain type (e.g., "Bitcoin", "Cardano")
     pub blockchain: String,
@@ -47,7 +112,7 @@ impl Transaction {
         charm: Value,
         updated_at: NaiveDateTime,
         confirmations: i32,
-        status: String,
+        status: TransactionStatus,
         blockchain: String,
         network: String,
     ) -> Self {
@@ -64,5 +129,35 @@ impl Transaction {
             network,
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_follows_confirmation_depth() {
+        assert_eq!(TransactionStatus::for_confirmations(0), TransactionStatus::Pending);
+        assert_eq!(TransactionStatus::for_confirmations(1), TransactionStatus::Confirming);
+        assert_eq!(
+            TransactionStatus::for_confirmations(CONFIRMATION_DEPTH - 1),
+            TransactionStatus::Confirming
+        );
+        assert_eq!(
+            TransactionStatus::for_confirmations(CONFIRMATION_DEPTH),
+            TransactionStatus::Confirmed
+        );
+    }
 
+    #[test]
+    fn status_round_trips_through_its_column_value() {
+        for status in TransactionStatus::ALL {
+            assert_eq!(status.as_str().parse::<TransactionStatus>(), Ok(status));
+            assert_eq!(
+                serde_json::to_value(status).unwrap(),
+                serde_json::Value::String(status.as_str().to_string())
+            );
+        }
+        assert!("mined".parse::<TransactionStatus>().is_err());
+    }
 }
@@ -0,0 +1,41 @@
+//! Which build wrote an indexed row. Every charm write carries a stamp so
+//! that, once a parser bug is fixed, the rows the buggy build produced can be
+//! counted (`verify`) and reindexed (`REINDEX_PARSER_REVISION_LT`).
+
+use crate::domain::services::NativeCharmParser;
+
+/// Crate version of the running indexer.
+pub const INDEXER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteStamp {
+    pub indexer_version: &'static str,
+    pub parser_revision: i32,
+    /// Set on rows written by a `reindex` run.
+    pub reindex_run_id: Option<String>,
+}
+
+impl WriteStamp {
+    /// Rows written by the live indexer (block and mempool paths).
+    pub fn live() -> Self {
+        Self {
+            indexer_version: INDEXER_VERSION,
+            parser_revision: NativeCharmParser::PARSER_REVISION,
+            reindex_run_id: None,
+        }
+    }
+
+    /// Rows written by the reindex run `run_id`.
+    pub fn reindex(run_id: impl Into<String>) -> Self {
+        Self {
+            reindex_run_id: Some(run_id.into()),
+            ..Self::live()
+        }
+    }
+}
+
+impl Default for WriteStamp {
+    fn default() -> Self {
+        Self::live()
+    }
+}
@@ -1,38 +1,138 @@
 use anyhow::Result;
 use bitcoincore_rpc::bitcoin::consensus::deserialize;
-use bitcoincore_rpc::bitcoin::{Address, Network, Transaction};
+use bitcoincore_rpc::bitcoin::{Address, Network, Script, Transaction};
+
+use crate::utils::{logging, metrics};
 
 /// Extracts Bitcoin addresses from transaction hex data
 pub struct AddressExtractor;
 
+/// One transaction output: its address, if the script has one, and the
+/// script type used to label extraction failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputAddress {
+    pub address: Option<String>,
+    pub script_type: &'static str,
+}
+
 impl AddressExtractor {
-    /// Extract all output addresses from a transaction hex string
-    fn extract_all_addresses(tx_hex: &str, network: &str) -> Result<Vec<String>> {
-        let tx_bytes = hex::decode(tx_hex)?;
-        let tx: Transaction = deserialize(&tx_bytes)?;
-        
-        let btc_network = match network {
+    /// Map an indexer network name to its address network. Unknown names fall
+    /// back to testnet, like the rest of the indexer.
+    pub fn network_for(network: &str) -> Network {
+        match network {
             "mainnet" => Network::Bitcoin,
-            "testnet4" => Network::Testnet,
-            "testnet" => Network::Testnet,
+            "testnet4" | "testnet" => Network::Testnet,
             "regtest" => Network::Regtest,
             _ => Network::Testnet,
+        }
+    }
+
+    /// Canonical stored form of an address: bech32 (`bc1`, `tb1`, `bcrt1`)
+    /// lowercased, base58 untouched since its case is significant. Every
+    /// address column is written in this form so lookups can match exactly.
+    pub fn canonical(address: &str) -> String {
+        let lower = address.trim().to_ascii_lowercase();
+        if ["bc1", "tb1", "bcrt1"]
+            .iter()
+            .any(|hrp| lower.starts_with(hrp))
+        {
+            lower
+        } else {
+            address.trim().to_string()
+        }
+    }
+
+    /// Classify an output script. Everything `Address::from_script` accepts
+    /// (P2PKH, P2SH and all witness programs) has an address; P2PK, bare
+    /// multisig, OP_RETURN and nonstandard scripts do not.
+    pub fn script_type(script: &Script) -> &'static str {
+        if script.is_p2pkh() {
+            "p2pkh"
+        } else if script.is_p2sh() {
+            "p2sh"
+        } else if script.is_v0_p2wpkh() {
+            "p2wpkh"
+        } else if script.is_v0_p2wsh() {
+            "p2wsh"
+        } else if script.is_v1_p2tr() {
+            "p2tr"
+        } else if script.is_witness_program() {
+            "witness_unknown"
+        } else if script.is_op_return() {
+            "op_return"
+        } else if script.is_p2pk() {
+            "p2pk"
+        } else if script.as_bytes().last() == Some(&0xae) {
+            // OP_CHECKMULTISIG
+            "multisig"
+        } else {
+            "nonstandard"
+        }
+    }
+
+    /// Every output of a transaction, index-aligned with `tx.output`.
+    pub fn output_addresses(tx_hex: &str, network: &str) -> Result<Vec<OutputAddress>> {
+        let tx_bytes = hex::decode(tx_hex)?;
+        let tx: Transaction = deserialize(&tx_bytes)?;
+        let btc_network = Self::network_for(network);
+
+        Ok(tx
+            .output
+            .iter()
+            .map(|output| OutputAddress {
+                address: Address::from_script(&output.script_pubkey, btc_network)
+                    .ok()
+                    .map(|a| a.to_string()),
+                script_type: Self::script_type(&output.script_pubkey),
+            })
+            .collect())
+    }
+
+    /// Address of the charm output `vout`. A miss is logged and counted by
+    /// script type (`missing_output` when the tx has no such output).
+    pub fn charm_output_address(
+        outputs: &[OutputAddress],
+        vout: usize,
+        txid: &str,
+        network: &str,
+    ) -> Option<String> {
+        let (address, script_type) = match outputs.get(vout) {
+            Some(out) => (out.address.clone(), out.script_type),
+            None => (None, "missing_output"),
         };
-        
-        let mut addresses = Vec::new();
-        
-        for output in &tx.output {
-            if let Ok(address) = Address::from_script(&output.script_pubkey, btc_network) {
-                addresses.push(address.to_string());
-            }
+        if address.is_none() {
+            logging::log_debug(&format!(
+                "[{}] No address for charm output {}:{} ({})",
+                network, txid, vout, script_type
+            ));
+            metrics::charm_address_missing(network, script_type);
         }
-        
-        Ok(addresses)
+        address
     }
-    
-    /// Extract the address that likely holds the charm asset.
-    /// Prioritizes P2PKH and P2SH over bech32 segwit/taproot.
-    pub fn extract_charm_holder_address(tx_hex: &str, network: &str) -> Result<Option<String>> {
+
+    /// Extract all output addresses from a transaction hex string
+    fn extract_all_addresses(tx_hex: &str, network: &str) -> Result<Vec<String>> {
+        Ok(Self::output_addresses(tx_hex, network)?
+            .into_iter()
+            .filter_map(|o| o.address)
+            .collect())
+    }
+
+    /// Extract the address that likely holds the charm asset: the address of
+    /// `charm_vout` when it has one, else the first legacy (P2PKH, then P2SH)
+    /// address, else the first bech32 one.
+    pub fn extract_charm_holder_address(
+        tx_hex: &str,
+        network: &str,
+        charm_vout: Option<usize>,
+    ) -> Result<Option<String>> {
+        if let Some(vout) = charm_vout {
+            let outputs = Self::output_addresses(tx_hex, network)?;
+            if let Some(address) = outputs.get(vout).and_then(|o| o.address.clone()) {
+                return Ok(Some(address));
+            }
+        }
+
         let addresses = Self::extract_all_addresses(tx_hex, network)?;
 
         let preferred = addresses
@@ -46,7 +146,7 @@ impl AddressExtractor {
             .or_else(|| {
                 addresses
                     .iter()
-                    .find(|a| a.starts_with("bc1") || a.starts_with("tb1"))
+                    .find(|a| a.starts_with("bc1") || a.starts_with("tb1") || a.starts_with("bcrt1"))
             });
 
         Ok(preferred.cloned().or_else(|| addresses.first().cloned()))
@@ -63,13 +163,13 @@ mod tests {
 
     #[test]
     fn invalid_hex_returns_error() {
-        let result = AddressExtractor::extract_charm_holder_address("zzzz", "mainnet");
+        let result = AddressExtractor::extract_charm_holder_address("zzzz", "mainnet", None);
         assert!(result.is_err());
     }
 
     #[test]
     fn truncated_tx_bytes_return_error() {
-        let result = AddressExtractor::extract_charm_holder_address("deadbeef", "mainnet");
+        let result = AddressExtractor::extract_charm_holder_address("deadbeef", "mainnet", None);
         assert!(result.is_err());
     }
 
@@ -98,10 +198,183 @@ mod tests {
 
     #[test]
     fn holder_picks_bech32_when_no_legacy_outputs() {
-        let res = AddressExtractor::extract_charm_holder_address(V10_CHARM_TX_HEX, "mainnet")
+        let res = AddressExtractor::extract_charm_holder_address(V10_CHARM_TX_HEX, "mainnet", None)
             .expect("parse")
             .expect("at least one address");
         assert!(res.starts_with("bc1"), "got: {res}");
     }
-}
 
+    /// Fixture scripts built from the BIP173/BIP86 test vectors.
+    const P2PKH: &str = "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac";
+    const P2SH: &str = "a914751e76e8199196d454941c45d1b3a323f1433bd687";
+    const P2WPKH: &str = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+    const P2WSH: &str = "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262";
+    const P2TR: &str = "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c";
+    const P2PK: &str = "210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ac";
+    /// 1-of-1 bare multisig over the same key.
+    const MULTISIG: &str = "51210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f8179851ae";
+    const OP_RETURN: &str = "6a0568656c6c6f";
+    const NONSTANDARD: &str = "51";
+
+    /// Serialize a one-input tx with one output per script.
+    fn tx_hex(scripts: &[&str]) -> String {
+        use bitcoincore_rpc::bitcoin::absolute::LockTime;
+        use bitcoincore_rpc::bitcoin::consensus::encode::serialize_hex;
+        use bitcoincore_rpc::bitcoin::{OutPoint, ScriptBuf, Sequence, TxIn, TxOut, Witness};
+
+        serialize_hex(&Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: scripts
+                .iter()
+                .map(|s| TxOut {
+                    value: 1_000,
+                    script_pubkey: ScriptBuf::from(hex::decode(s).unwrap()),
+                })
+                .collect(),
+        })
+    }
+
+    fn single_output(script: &str, network: &str) -> OutputAddress {
+        AddressExtractor::output_addresses(&tx_hex(&[script]), network)
+            .expect("parse")
+            .remove(0)
+    }
+
+    #[test]
+    fn standard_scripts_map_to_addresses_per_network() {
+        let cases = [
+            (
+                P2PKH,
+                "p2pkh",
+                [
+                    "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH",
+                    "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r",
+                    "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r",
+                ],
+            ),
+            (
+                P2SH,
+                "p2sh",
+                [
+                    "3CNHUhP3uyB9EUtRLsmvFUmvGdjGdkTxJw",
+                    "2N3vVYSK5XRgVSGWy21PnsRmBUywSQNdCsf",
+                    "2N3vVYSK5XRgVSGWy21PnsRmBUywSQNdCsf",
+                ],
+            ),
+            (
+                P2WPKH,
+                "p2wpkh",
+                [
+                    "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+                    "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+                    "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+                ],
+            ),
+            (
+                P2WSH,
+                "p2wsh",
+                [
+                    "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+                    "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                    "bcrt1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qzf4jry",
+                ],
+            ),
+            (
+                P2TR,
+                "p2tr",
+                [
+                    "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                    "tb1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqp3mvzv",
+                    "bcrt1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqvg32hk",
+                ],
+            ),
+        ];
+        for (script, script_type, expected) in cases {
+            for (network, address) in ["mainnet", "testnet4", "regtest"].iter().zip(expected) {
+                assert_eq!(
+                    single_output(script, network),
+                    OutputAddress {
+                        address: Some(address.to_string()),
+                        script_type,
+                    },
+                    "{script_type} on {network}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn scripts_without_address_are_classified_not_errors() {
+        for (script, script_type) in [
+            (P2PK, "p2pk"),
+            (MULTISIG, "multisig"),
+            (OP_RETURN, "op_return"),
+            (NONSTANDARD, "nonstandard"),
+        ] {
+            assert_eq!(
+                single_output(script, "mainnet"),
+                OutputAddress {
+                    address: None,
+                    script_type,
+                },
+                "{script_type}"
+            );
+        }
+    }
+
+    #[test]
+    fn output_addresses_stay_index_aligned() {
+        let outputs =
+            AddressExtractor::output_addresses(&tx_hex(&[OP_RETURN, MULTISIG, P2TR]), "mainnet")
+                .expect("parse");
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[0].address, None);
+        assert_eq!(outputs[1].address, None);
+        assert!(outputs[2].address.as_deref().unwrap().starts_with("bc1p"));
+        assert_eq!(
+            AddressExtractor::charm_output_address(&outputs, 5, "tx", "mainnet"),
+            None
+        );
+    }
+
+    #[test]
+    fn canonical_lowercases_only_bech32() {
+        assert_eq!(
+            AddressExtractor::canonical("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4"),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            AddressExtractor::canonical(" TB1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KXPJZSX"),
+            "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"
+        );
+        assert_eq!(
+            AddressExtractor::canonical("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+    }
+
+    #[test]
+    fn holder_uses_charm_vout_over_legacy_preference() {
+        // Spell-first layout: OP_RETURN at 0, a legacy change output at 1 and
+        // the charm at 2 on a taproot output.
+        let hex = tx_hex(&[OP_RETURN, P2PKH, P2TR]);
+        assert_eq!(
+            AddressExtractor::extract_charm_holder_address(&hex, "mainnet", Some(2)).unwrap(),
+            Some("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr".to_string())
+        );
+        // Without a usable vout the legacy heuristic still applies.
+        for vout in [None, Some(0)] {
+            assert_eq!(
+                AddressExtractor::extract_charm_holder_address(&hex, "mainnet", vout).unwrap(),
+                Some("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH".to_string())
+            );
+        }
+    }
+}
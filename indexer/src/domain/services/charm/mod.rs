@@ -9,6 +9,8 @@ mod spent_tracking;
 
 use std::fmt;
 
+use charms_core::AppKind;
+
 use crate::domain::errors::CharmError;
 use crate::infrastructure::persistence::repositories::{
     AssetRepository, CharmRepository, DexOrdersRepository, StatsHoldersRepository,
@@ -76,25 +78,29 @@ impl CharmService {
     // ==================== Persistence Methods ====================
 
     /// Saves multiple charms in a single database operation.
-    /// Returns the list of (txid, vout) that were actually inserted (not duplicates).
+    /// Returns the (txid, vout, app_id) keys that were actually inserted (not duplicates).
     /// Tuple shape matches `block/batch.rs::CharmBatchItem`.
     #[allow(clippy::type_complexity)]
     pub async fn save_batch(
         &self,
         charms: Vec<(
-            String,            // txid
-            i32,               // vout
-            u64,               // block_height
-            serde_json::Value, // data
-            String,            // asset_type
-            String,            // blockchain
-            String,            // network
-            Option<String>,    // address
-            String,            // app_id
-            i64,               // amount
-            Option<String>,    // tags
+            String,                        // txid
+            i32,                           // vout
+            u64,                           // block_height
+            serde_json::Value,             // data
+            String,                        // asset_type
+            String,                        // blockchain
+            String,                        // network
+            Option<String>,                // address
+            String,                        // app_id
+            i64,                           // amount
+            Option<String>,                // tags
+            Option<String>,                // block_hash
+            Option<i32>,                   // tx_ordinal
+            Option<String>,                // operation
+            Option<chrono::NaiveDateTime>, // block_time
         )>,
-    ) -> Result<Vec<(String, i32)>, CharmError> {
+    ) -> Result<Vec<(String, i32, String)>, CharmError> {
         let persistence = CharmPersistence::new(&self.charm_repository, &self.asset_repository);
         persistence.save_charm_batch(charms).await
     }
@@ -121,6 +127,7 @@ impl CharmService {
             Option<String>, // cardano_policy_id
             Option<String>, // cardano_asset_name
             Option<String>, // cardano_fingerprint
+            Option<String>, // collection
         )>,
     ) -> Result<(), CharmError> {
         let persistence = CharmPersistence::new(&self.charm_repository, &self.asset_repository);
@@ -142,32 +149,37 @@ impl CharmService {
             })
     }
 
-    /// Mark multiple charms as spent in a batch using (txid, vout) pairs.
-    /// `network` scopes the update so mainnet/testnet rows do not bleed.
+    /// Mark multiple charms as spent in a batch using (txid, vout,
+    /// spending_txid) triples. `network` scopes the update so mainnet/testnet rows do not bleed.
     /// `block_height` tags the returned negative deltas so the block
     /// processor can merge them with the additive deltas before calling
-    /// `update_holders_batch` once per (app_id, address) per block.
+    /// `apply_block_deltas` once per (app_id, address) per block.
     ///
-    /// This function NO LONGER touches `stats_holders` directly — the
-    /// per-step idempotency gate `last_updated_block < block` would reject
-    /// the second call when the same address both gained and lost balance
-    /// within one block (anomaly A1).
+    /// This function NO LONGER touches `stats_holders` directly — the block
+    /// claim taken by `apply_block_deltas` would skip a second call when the
+    /// same address both gained and lost balance within one block (anomaly A1).
     pub async fn mark_charms_as_spent_batch(
         &self,
-        txid_vouts: Vec<(String, i32)>,
+        spends: Vec<(String, i32, String)>,
         network: &str,
         block_height: i32,
     ) -> Result<Vec<(String, String, i64, i32)>, CharmError> {
         // 1. Get charm info before marking as spent (for stats_holders update)
+        let txid_vouts = spends
+            .iter()
+            .map(|(txid, vout, _)| (txid.clone(), *vout))
+            .collect();
         let charm_info = self
             .charm_repository
-            .get_charms_for_spent_update(txid_vouts.clone(), network)
+            .get_charms_for_spent_update(txid_vouts, network)
             .await
             .map_err(|e| CharmError::ProcessingError(format!("Failed to get charm info: {}", e)))?;
 
         // 2. Mark charms as spent
         let tracker = SpentTracker::new(&self.charm_repository);
-        tracker.mark_charms_as_spent_batch(txid_vouts, network).await?;
+        tracker
+            .mark_charms_as_spent_batch(spends, network, block_height)
+            .await?;
 
         // No longer decrement asset.total_supply on spent. After anomaly A2
         // the field represents the highest declared spell supply (an upper
@@ -182,13 +194,13 @@ impl CharmService {
         let holder_updates: Vec<(String, String, i64, i32)> = charm_info
             .into_iter()
             .filter_map(|(app_id, address, amount)| {
-                if app_id.starts_with("t/") {
-                    let nft_app_id = crate::domain::services::app_id::token_to_nft(&app_id);
-                    Some((nft_app_id, address, -amount, block_height))
-                } else if app_id.starts_with("n/") {
-                    Some((app_id, address, -1_i64, block_height))
-                } else {
-                    None
+                match AppKind::of(&app_id) {
+                    AppKind::Token => {
+                        let nft_app_id = charms_core::token_to_nft(&app_id);
+                        Some((nft_app_id, address, -amount, block_height))
+                    }
+                    AppKind::Nft => Some((app_id, address, -1_i64, block_height)),
+                    _ => None,
                 }
             })
             .collect();
@@ -22,26 +22,30 @@ impl<'a> CharmPersistence<'a> {
     }
 
     /// Saves multiple charms in a single database operation.
-    /// Returns the list of (txid, vout) that were actually inserted (not duplicates).
+    /// Returns the (txid, vout, app_id) keys that were actually inserted (not duplicates).
     /// Tuple shape matches the SQL row layout — see `block/batch.rs::CharmBatchItem`
     /// for the named-field analogue used by callers in the application layer.
     #[allow(clippy::type_complexity)]
     pub async fn save_charm_batch(
         &self,
         charms: Vec<(
-            String,            // txid
-            i32,               // vout
-            u64,               // block_height
-            serde_json::Value, // data
-            String,            // asset_type
-            String,            // blockchain
-            String,            // network
-            Option<String>,    // address
-            String,            // app_id
-            i64,               // amount
-            Option<String>,    // tags
+            String,                        // txid
+            i32,                           // vout
+            u64,                           // block_height
+            serde_json::Value,             // data
+            String,                        // asset_type
+            String,                        // blockchain
+            String,                        // network
+            Option<String>,                // address
+            String,                        // app_id
+            i64,                           // amount
+            Option<String>,                // tags
+            Option<String>,                // block_hash
+            Option<i32>,                   // tx_ordinal
+            Option<String>,                // operation
+            Option<chrono::NaiveDateTime>, // block_time
         )>,
-    ) -> Result<Vec<(String, i32)>, CharmError> {
+    ) -> Result<Vec<(String, i32, String)>, CharmError> {
         self.charm_repository
             .save_batch(charms)
             .await
@@ -70,6 +74,7 @@ impl<'a> CharmPersistence<'a> {
             Option<String>, // cardano_policy_id
             Option<String>, // cardano_asset_name
             Option<String>, // cardano_fingerprint
+            Option<String>, // collection
         )>,
     ) -> Result<(), CharmError> {
         if batch.is_empty() {
@@ -108,6 +113,7 @@ impl<'a> CharmPersistence<'a> {
                     cardano_policy_id,
                     cardano_asset_name,
                     cardano_fingerprint,
+                    collection,
                 )| {
                     // Build data JSON with supply and metadata
                     let mut data = serde_json::json!({"supply": supply});
@@ -135,6 +141,9 @@ impl<'a> CharmPersistence<'a> {
                     if let Some(fp) = cardano_fingerprint {
                         data["cardano_fingerprint"] = serde_json::json!(fp);
                     }
+                    if let Some(c) = collection {
+                        data["collection"] = serde_json::json!(c);
+                    }
 
                     (
                         app_id.clone(),              // app_id
@@ -12,14 +12,16 @@ impl<'a> SpentTracker<'a> {
         Self { charm_repository }
     }
 
-    /// Mark multiple charms as spent in a batch, scoped by `network`.
+    /// Mark multiple charms as spent in a batch, scoped by `network`, by
+    /// the block at `block_height`. Each item: (txid, vout, spending_txid)
     pub async fn mark_charms_as_spent_batch(
         &self,
-        txid_vouts: Vec<(String, i32)>,
+        spends: Vec<(String, i32, String)>,
         network: &str,
+        block_height: i32,
     ) -> Result<(), CharmError> {
         self.charm_repository
-            .mark_charms_as_spent_batch(txid_vouts, network)
+            .mark_charms_as_spent_batch(spends, network, block_height)
             .await
             .map_err(|e| {
                 CharmError::ProcessingError(format!(
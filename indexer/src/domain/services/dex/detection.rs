@@ -142,6 +142,8 @@ fn parse_order_data(data: &Value, _dex_app_id: &str) -> Option<DexOrder> {
         .unwrap_or("")
         .to_string();
 
+    let (expires_at_height, expires_at_time) = parse_expiry(data.get("expiry"));
+
     Some(DexOrder {
         maker: maker.to_string(),
         side,
@@ -151,9 +153,23 @@ fn parse_order_data(data: &Value, _dex_app_id: &str) -> Option<DexOrder> {
         quantity,
         asset_app_id,
         scrolls_address: None, // Will be set from output address if available
+        expires_at_height,
+        expires_at_time,
     })
 }
 
+/// Parse the optional expiry `{"height": N}` / `{"time": T}` (both may be
+/// set; T in unix seconds) into (height, time)
+fn parse_expiry(value: Option<&Value>) -> (Option<u64>, Option<u64>) {
+    let Some(expiry) = value else {
+        return (None, None);
+    };
+    (
+        expiry.get("height").and_then(|v| v.as_u64()),
+        expiry.get("time").and_then(|v| v.as_u64()),
+    )
+}
+
 /// Parse exec_type from JSON
 fn parse_exec_type(value: Option<&Value>) -> ExecType {
     match value {
@@ -266,4 +282,20 @@ mod tests {
             panic!("Expected Partial exec type");
         }
     }
+    #[test]
+    fn test_parse_order_expiry() {
+        let order = json!({
+            "maker": "bc1pmaker",
+            "side": "ask",
+            "price": [1, 2],
+            "expiry": {"height": 900_000, "time": 1_760_000_000u64}
+        });
+        let parsed = parse_order_data(&order, "").unwrap();
+        assert_eq!(parsed.expires_at_height, Some(900_000));
+        assert_eq!(parsed.expires_at_time, Some(1_760_000_000));
+
+        let only_height = json!({"height": 12});
+        assert_eq!(parse_expiry(Some(&only_height)), (Some(12), None));
+        assert_eq!(parse_expiry(None), (None, None));
+    }
 }
@@ -1,5 +1,6 @@
 //! DEX types for Charms Cast order detection and parsing
 
+use charms_core::{AppId, AppKind};
 use serde::{Deserialize, Serialize};
 
 /// Known DEX contract verification keys
@@ -106,6 +107,20 @@ pub struct DexOrder {
     pub asset_app_id: String,
     /// Scrolls address where order is held (if applicable)
     pub scrolls_address: Option<String>,
+    /// Last block height at which the order may still be filled
+    #[serde(default)]
+    pub expires_at_height: Option<u64>,
+    /// Last unix time (seconds) at which the order may still be filled
+    #[serde(default)]
+    pub expires_at_time: Option<u64>,
+}
+
+impl DexOrder {
+    /// `expires_at_time` as a UTC timestamp, the shape `dex_orders` stores
+    pub fn expires_at_datetime(&self) -> Option<chrono::NaiveDateTime> {
+        let secs = i64::try_from(self.expires_at_time?).ok()?;
+        chrono::DateTime::from_timestamp(secs, 0).map(|t| t.naive_utc())
+    }
 }
 
 /// Result of DEX detection on a transaction
@@ -120,15 +135,15 @@ pub struct DexDetectionResult {
 /// Check if an app_id is a known DEX contract
 pub fn is_dex_app_id(app_id: &str) -> bool {
     // DEX app_id format: b/0000...0000/<vk>
-    if !app_id.starts_with("b/") {
+    let Ok(id) = app_id.parse::<AppId>() else {
+        return false;
+    };
+    if id.classify() != AppKind::Dex {
         return false;
     }
 
     // Check for identity = 0 (64 zeros)
-    let zero_identity = "0".repeat(64);
-    if !app_id.contains(&format!("/{}/", zero_identity))
-        && !app_id.starts_with(&format!("b/{}/", zero_identity))
-    {
+    if id.identity().len() != 64 || id.identity().bytes().any(|b| b != b'0') {
         return false;
     }
 
@@ -1,5 +1,4 @@
 pub mod address_extractor;
-pub mod app_id;
 pub mod charm; // Modular charm service
 pub mod dex; // DEX detection for Charms Cast
 pub mod native_charm_parser;
@@ -8,4 +7,4 @@ pub mod tx_analyzer;
 // Re-export services for direct imports
 pub use address_extractor::AddressExtractor;
 pub use charm::CharmService; // Now from the charm module
-pub use native_charm_parser::{AssetInfo, NativeCharmParser};
+pub use native_charm_parser::{AssetInfo, NativeCharmParser, SpellFailure};
@@ -1,14 +1,30 @@
 use anyhow::Result;
 use bitcoin::consensus::encode::deserialize_hex;
 use charms_client::NormalizedSpell;
+use charms_core::{AppKind, AssetType};
+use bitcoin::script::Instruction;
 use charms_client::bitcoin_tx::{
-    BitcoinTx, parse_spell_and_proof_from_op_return, parse_spell_and_proof_from_witness,
+    BitcoinTx, SPELL_MARKER, parse_spell_and_proof_from_op_return,
+    parse_spell_and_proof_from_witness,
 };
 use charms_client::tx::{EnchantedTx, Tx};
 // Import from charms_client - the library handles version detection internally
 // For versions V0-V10, the library uses its internal VKs automatically
 // For CURRENT_VERSION (V11), we pass the correct VK from charms-lib
-use charms_client::{V7, V10};
+use charms_client::{CURRENT_VERSION, V7, V10};
+
+/// Why a transaction yielded no charm, as counted by parser statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpellFailure {
+    /// No spell envelope: a plain transaction (or hex that does not decode).
+    NoEnvelope,
+    /// An envelope whose payload does not decode into a spell and proof.
+    CborError,
+    /// A spell declaring a protocol version above `CURRENT_VERSION`.
+    VersionUnsupported(u32),
+    /// A spell that decodes but whose proof does not verify.
+    VerificationFailed,
+}
 
 /// Native charm parser using the charms-client crate
 /// Provides direct parsing and verification of charm transactions
@@ -28,6 +44,12 @@ impl NativeCharmParser {
         0xde, 0x0c, 0xd5, 0xcb, 0x3d, 0xe0, 0xae, 0x52,
     ];
 
+    /// Revision of what this parser extracts from a transaction, stamped on
+    /// every charm written. Bump it with any change to parsing or asset
+    /// extraction semantics, so older rows can be targeted for reindexing.
+    /// 2: charms carry their mint / transfer / burn `operation`.
+    pub const PARSER_REVISION: i32 = 2;
+
     /// Extract and verify a charm from a transaction hex string
     ///
     /// This function:
@@ -69,6 +91,13 @@ impl NativeCharmParser {
                 .ok_or_else(|| anyhow::anyhow!("no inputs"))
                 .and_then(|(last_in, _)| parse_spell_and_proof_from_witness(last_in))
         })?;
+        // A newer spell may still decode; its data cannot be trusted to mean
+        // what this parser thinks, so leave it to `unsupported_spell_version`.
+        anyhow::ensure!(
+            spell.version <= CURRENT_VERSION,
+            "unsupported spell version {}",
+            spell.version
+        );
 
         // Replicate spell_with_committed_ins_and_coins (pub(crate) in charms-client)
         // using the public EnchantedTx trait methods.
@@ -98,6 +127,57 @@ impl NativeCharmParser {
         }
     }
 
+    /// Protocol version of a spell this parser cannot read yet.
+    ///
+    /// Returns `Some(version)` when the tx carries a spell OP_RETURN
+    /// (`OP_RETURN "spell" <payload>`) whose CBOR payload declares a version
+    /// above `CURRENT_VERSION`. Both extract paths reject such txs, which
+    /// would otherwise look like plain non-charm txs. Witness envelopes
+    /// predate V9 and are never new, so only OP_RETURN outputs are checked.
+    /// A payload without a readable `version` field returns `None`.
+    pub fn unsupported_spell_version(tx_hex: &str) -> Option<u32> {
+        let tx: bitcoin::Transaction = deserialize_hex(tx_hex).ok()?;
+        let version = tx
+            .output
+            .iter()
+            .filter(|out| out.script_pubkey.is_op_return())
+            .find_map(|out| {
+                let mut instructions = out.script_pubkey.instructions().skip(1);
+                match (instructions.next(), instructions.next()) {
+                    (
+                        Some(Ok(Instruction::PushBytes(marker))),
+                        Some(Ok(Instruction::PushBytes(payload))),
+                    ) if marker.as_bytes() == SPELL_MARKER => {
+                        declared_spell_version(payload.as_bytes())
+                    }
+                    _ => None,
+                }
+            })?;
+        (version > CURRENT_VERSION).then_some(version)
+    }
+
+    /// Why `extract_and_verify_charm` found no charm in `tx_hex`.
+    ///
+    /// Plain transactions are told apart by a scan for the spell marker in
+    /// the OP_RETURN outputs and the last input's witness; only txs carrying
+    /// an envelope are decoded again, so classifying every non-charm tx of a
+    /// block stays cheap.
+    pub fn classify_failure(tx_hex: &str) -> SpellFailure {
+        let Ok(tx) = deserialize_hex::<bitcoin::Transaction>(tx_hex) else {
+            return SpellFailure::NoEnvelope;
+        };
+        if !has_spell_envelope(&tx) {
+            return SpellFailure::NoEnvelope;
+        }
+        if let Some(version) = Self::unsupported_spell_version(tx_hex) {
+            return SpellFailure::VersionUnsupported(version);
+        }
+        match Self::extract_spell_no_verify(tx_hex) {
+            Ok(_) => SpellFailure::VerificationFailed,
+            Err(_) => SpellFailure::CborError,
+        }
+    }
+
     /// Extract asset-related data from a normalized spell
     /// Returns information that can be used to populate the assets table
     pub fn extract_asset_info(spell: &NormalizedSpell) -> Vec<AssetInfo> {
@@ -127,19 +207,12 @@ impl NativeCharmParser {
                                 .and_then(|v| v.as_str())
                             {
                                 // DEX order: use the token being traded as the asset
-                                let atype = if token_id.starts_with("t/") {
-                                    "token".to_string()
-                                } else if token_id.starts_with("n/") {
-                                    "nft".to_string()
-                                } else {
-                                    "other".to_string()
-                                };
-                                (token_id.to_string(), atype)
+                                (token_id.to_string(), AppKind::of(token_id).asset_type())
                             } else {
-                                (app.to_string(), determine_asset_type_from_app(app))
+                                (app.to_string(), AppKind::from_tag(app.tag).asset_type())
                             }
                         } else {
-                            (app.to_string(), determine_asset_type_from_app(app))
+                            (app.to_string(), AppKind::from_tag(app.tag).asset_type())
                         };
 
                     // A3 fix: a DEX-ask output exposes the token both via the
@@ -174,7 +247,7 @@ pub struct AssetInfo {
     pub app_id: String,
     pub vout_index: i32,
     pub amount: u64,
-    pub asset_type: String,
+    pub asset_type: AssetType,
 }
 
 /// Extract amount from charm data.
@@ -185,6 +258,45 @@ pub struct AssetInfo {
 ///
 /// For complex structs, serialize to JSON and extract the `amount` field.
 /// Never fall back to raw bytes — that produces garbage numbers.
+/// Whether `tx` carries the spell marker: as the first push of an OP_RETURN
+/// output (V9+) or anywhere in the last input's witness (taproot envelope).
+fn has_spell_envelope(tx: &bitcoin::Transaction) -> bool {
+    let in_op_return = tx
+        .output
+        .iter()
+        .filter(|out| out.script_pubkey.is_op_return())
+        .any(|out| {
+            matches!(
+                out.script_pubkey.instructions().nth(1),
+                Some(Ok(Instruction::PushBytes(marker))) if marker.as_bytes() == SPELL_MARKER
+            )
+        });
+    in_op_return
+        || tx.input.last().is_some_and(|input| {
+            input
+                .witness
+                .iter()
+                .any(|item| item.windows(SPELL_MARKER.len()).any(|w| w == SPELL_MARKER))
+        })
+}
+
+/// `version` of the spell in a `(NormalizedSpell, Proof)` CBOR payload, read
+/// without knowing the rest of the spell layout.
+fn declared_spell_version(payload: &[u8]) -> Option<u32> {
+    let ciborium::Value::Array(items) = ciborium::from_reader(payload).ok()? else {
+        return None;
+    };
+    let ciborium::Value::Map(fields) = items.into_iter().next()? else {
+        return None;
+    };
+    fields.into_iter().find_map(|(key, value)| match (key, value) {
+        (ciborium::Value::Text(k), ciborium::Value::Integer(v)) if k == "version" => {
+            u32::try_from(i128::from(v)).ok()
+        }
+        _ => None,
+    })
+}
+
 fn extract_amount_from_charm_data(charm_data: &charms_data::Data) -> u64 {
     // Case 1: plain integer (simple token transfer)
     if let Ok(amount) = charm_data.value::<u64>() {
@@ -227,17 +339,6 @@ fn extract_amount_from_charm_data(charm_data: &charms_data::Data) -> u64 {
     0
 }
 
-/// Determine the asset type based on app information
-fn determine_asset_type_from_app(app: &charms_data::App) -> String {
-    // Logic to determine asset type based on app tag
-    // This follows the charm protocol rules for asset types
-    match app.tag {
-        charms_data::TOKEN => "token".to_string(),
-        charms_data::NFT => "nft".to_string(),
-        _ => "unknown".to_string(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -294,34 +395,14 @@ mod tests {
         assert_eq!(extract_amount_from_charm_data(&Data::empty()), 0);
     }
 
+    /// The shared rules key on the same tag characters charms-data uses.
     #[test]
-    fn determine_asset_type_token() {
-        let app = charms_data::App {
-            tag: charms_data::TOKEN,
-            identity: charms_data::B32([0u8; 32]),
-            vk: charms_data::B32([0u8; 32]),
-        };
-        assert_eq!(determine_asset_type_from_app(&app), "token");
-    }
-
-    #[test]
-    fn determine_asset_type_nft() {
-        let app = charms_data::App {
-            tag: charms_data::NFT,
-            identity: charms_data::B32([0u8; 32]),
-            vk: charms_data::B32([0u8; 32]),
-        };
-        assert_eq!(determine_asset_type_from_app(&app), "nft");
-    }
-
-    #[test]
-    fn determine_asset_type_other() {
-        let app = charms_data::App {
-            tag: 'x',
-            identity: charms_data::B32([0u8; 32]),
-            vk: charms_data::B32([0u8; 32]),
-        };
-        assert_eq!(determine_asset_type_from_app(&app), "unknown");
+    fn shared_app_kind_matches_charms_data_tags() {
+        assert_eq!(AppKind::from_tag(charms_data::TOKEN), AppKind::Token);
+        assert_eq!(AppKind::from_tag(charms_data::NFT), AppKind::Nft);
+        assert_eq!(AppKind::from_tag(charms_data::TOKEN).asset_type(), AssetType::Token);
+        assert_eq!(AppKind::from_tag(charms_data::NFT).asset_type(), AssetType::Nft);
+        assert_eq!(AppKind::from_tag('x').asset_type(), AssetType::Other);
     }
 
     /// Regression test: real production tx 7269cf1b (V10, OP_RETURN, mock=false)
@@ -356,6 +437,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn unsupported_spell_version_detects_future_spell() {
+        // The V10 DEX bid of the parser corpus with its version byte set to 23.
+        let tx_hex = include_str!("../../../tests/fixtures/parser/future_version_v23.hex").trim();
+        assert_eq!(NativeCharmParser::unsupported_spell_version(tx_hex), Some(23));
+        assert!(NativeCharmParser::extract_and_verify_charm(tx_hex, false).is_err());
+        let err = NativeCharmParser::extract_spell_no_verify(tx_hex).unwrap_err();
+        assert_eq!(err.to_string(), "unsupported spell version 23");
+    }
+
+    #[test]
+    fn unsupported_spell_version_ignores_supported_and_plain_txs() {
+        let v10 = include_str!("../../../tests/fixtures/parser/dex_bid_order_7269cf1b.hex").trim();
+        let plain = include_str!("../../../tests/fixtures/parser/no_spell.hex").trim();
+        assert_eq!(NativeCharmParser::unsupported_spell_version(v10), None);
+        assert_eq!(NativeCharmParser::unsupported_spell_version(plain), None);
+        assert_eq!(NativeCharmParser::unsupported_spell_version("zz"), None);
+    }
+
+    #[test]
+    fn classify_failure_sorts_non_charm_txs() {
+        let classify = NativeCharmParser::classify_failure;
+        let plain = include_str!("../../../tests/fixtures/parser/no_spell.hex").trim();
+        let corrupt =
+            include_str!("../../../tests/fixtures/parser/corrupt_spell_payload.hex").trim();
+        let future = include_str!("../../../tests/fixtures/parser/future_version_v23.hex").trim();
+        assert_eq!(classify(plain), SpellFailure::NoEnvelope);
+        assert_eq!(classify("zz"), SpellFailure::NoEnvelope);
+        assert_eq!(classify(corrupt), SpellFailure::CborError);
+        assert_eq!(classify(future), SpellFailure::VersionUnsupported(23));
+
+        // The corpus DEX bid with one byte of its proof changed: the spell
+        // still decodes, the proof no longer verifies.
+        let forged = include_str!("../../../tests/fixtures/parser/dex_bid_order_7269cf1b.hex")
+            .trim()
+            .replacen("99010418a4", "99010418a5", 1);
+        assert!(NativeCharmParser::extract_and_verify_charm(&forged, false).is_err());
+        assert_eq!(classify(&forged), SpellFailure::VerificationFailed);
+    }
+
     #[test]
     fn test_spell_vk_constant() {
         // Wiring sanity: SPELL_VK is 32 bytes and is not all zeros.
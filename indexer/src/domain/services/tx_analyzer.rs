@@ -4,11 +4,16 @@
 //! raw transaction hex and extract charm/spell/DEX data.
 //! No persistence happens here — callers decide how to save.
 
+use std::sync::OnceLock;
+
+use bitcoin::consensus::encode::deserialize_hex;
+use charms_core::{AppKind, AssetType};
 use serde_json::{json, Value};
 
 use super::address_extractor::AddressExtractor;
 use super::dex;
 use super::native_charm_parser::{AssetInfo, NativeCharmParser};
+use crate::utils::logging;
 
 /// Result of analyzing a single transaction.
 /// Contains everything needed for persistence — callers just save it.
@@ -16,8 +21,10 @@ use super::native_charm_parser::{AssetInfo, NativeCharmParser};
 pub struct AnalyzedTx {
     pub txid: String,
     pub charm_json: Value,
+    /// Primary app of the tx (first asset) for tags and logs only; an output
+    /// may carry several tokens, so persistence walks `asset_infos`.
     pub app_id: String,
-    pub asset_type: String,
+    pub asset_type: AssetType,
     pub amount: i64,
     pub address: Option<String>,
     pub tags: Option<String>,
@@ -31,6 +38,109 @@ pub struct AnalyzedTx {
     pub beamed_out_indices: std::collections::HashSet<usize>,
 }
 
+impl AnalyzedTx {
+    /// Turn a `FulfillAsk` into a `FulfillBid` once the consumed order is
+    /// known to be a bid. A 3-output fulfill without token change has the
+    /// same spell either way, so only the parent order tells them apart.
+    /// Operation, tags and `tx_type` are updated together so every path
+    /// persists the same classification.
+    pub fn reclassify_as_fulfill_bid(&mut self) {
+        if let Some(ref mut result) = self.dex_result {
+            result.operation = dex::DexOperation::FulfillBid;
+        }
+        if let Some(ref mut tags) = self.tags {
+            *tags = tags.replace("fulfill-ask", "fulfill-bid");
+        }
+        if self.tx_type == "dex_fulfill_ask" {
+            self.tx_type = "dex_fulfill_bid".to_string();
+        }
+    }
+
+    /// `charm_json` as stored on each charm row: trimmed past
+    /// `INDEXER_MAX_CHARM_DATA_BYTES` (see `charms_core::charm_data`). The
+    /// transaction row keeps the full JSON.
+    pub fn charm_row_data(&self) -> Value {
+        charms_core::trim_charm_data(&self.charm_json, max_charm_data_bytes())
+            .unwrap_or_else(|| self.charm_json.clone())
+    }
+}
+
+/// `INDEXER_MAX_CHARM_DATA_BYTES` when set to a positive number, otherwise
+/// 64 KiB. Read once.
+fn max_charm_data_bytes() -> usize {
+    static MAX: OnceLock<usize> = OnceLock::new();
+    *MAX.get_or_init(|| {
+        std::env::var("INDEXER_MAX_CHARM_DATA_BYTES")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|bytes| *bytes > 0)
+            .unwrap_or(charms_core::DEFAULT_MAX_CHARM_DATA_BYTES)
+    })
+}
+
+/// Where a spell's charm-bearing outputs land in the transaction.
+///
+/// `spell.tx.outs[i]` describes tx output `i`: outputs without charms
+/// (change, the spell OP_RETURN, trailing funding outputs) have an empty
+/// entry or none at all, so each charm keeps the vout of its spell entry
+/// and interleaved plain outputs are skipped without shifting the rest.
+/// A spell declaring charms past the last tx output is malformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharmOutputMap {
+    /// Outputs the transaction has.
+    pub tx_outputs: usize,
+    /// `(vout, app_ids)` of every charm-bearing output the tx has, by vout.
+    pub outputs: Vec<(u32, Vec<String>)>,
+    /// Declared charm-bearing outputs past the end of the tx.
+    pub missing: Vec<u32>,
+}
+
+impl CharmOutputMap {
+    pub fn build(asset_infos: &[AssetInfo], tx_outputs: usize) -> Self {
+        let mut map = CharmOutputMap {
+            tx_outputs,
+            outputs: Vec::new(),
+            missing: Vec::new(),
+        };
+        for asset in asset_infos {
+            let vout = asset.vout_index as u32;
+            if vout as usize >= tx_outputs {
+                if map.missing.last() != Some(&vout) {
+                    map.missing.push(vout);
+                }
+                continue;
+            }
+            match map.outputs.last_mut() {
+                Some((last, apps)) if *last == vout => apps.push(asset.app_id.clone()),
+                _ => map.outputs.push((vout, vec![asset.app_id.clone()])),
+            }
+        }
+        map
+    }
+
+    pub fn is_malformed(&self) -> bool {
+        !self.missing.is_empty()
+    }
+
+    /// Whether `asset` sits on an output the tx has.
+    pub fn contains(&self, asset: &AssetInfo) -> bool {
+        (asset.vout_index as usize) < self.tx_outputs
+    }
+
+    /// `charm_output_map` as stored on the charm JSON.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "tx_outputs": self.tx_outputs,
+            "outputs": self
+                .outputs
+                .iter()
+                .map(|(vout, apps)| json!({ "vout": vout, "apps": apps }))
+                .collect::<Vec<_>>(),
+            "missing": self.missing,
+        })
+    }
+}
+
 /// Pure analysis: parse raw tx hex → Option<AnalyzedTx>.
 /// Returns None if the tx does not contain a charm spell.
 /// This is intentionally a free function, not a method on a struct,
@@ -64,37 +174,47 @@ pub fn analyze_tx(
         VerifyMode::Permissive => NativeCharmParser::extract_spell_no_verify(raw_hex).ok()?,
     };
 
-    // 2. Build charm JSON (same structure used by all paths)
+    // 2. Extract asset info from spell outputs and map it onto the tx
+    // outputs. Charms declared past the last output are dropped rather than
+    // written against a vout that doesn't exist; the spell is kept, flagged.
+    let mut asset_infos = NativeCharmParser::extract_asset_info(&spell);
+    let tx_outputs = deserialize_hex::<bitcoin::Transaction>(raw_hex)
+        .map(|tx| tx.output.len())
+        .ok()?;
+    let output_map = CharmOutputMap::build(&asset_infos, tx_outputs);
+    if output_map.is_malformed() {
+        logging::log_warning(&format!(
+            "[{}] ⚠️ Malformed spell in tx {}: declares charms at outputs {:?} of a {}-output tx",
+            network, txid, output_map.missing, tx_outputs
+        ));
+        asset_infos.retain(|a| output_map.contains(a));
+    }
+
+    // 3. Build charm JSON (same structure used by all paths)
     let spell_json = serde_json::to_value(&spell).unwrap_or_default();
-    let charm_json = json!({
+    let mut charm_json = json!({
         "type": "spell",
         "detected": true,
         "has_native_data": true,
         "native_data": spell_json,
-        "version": "native_parser"
+        "version": "native_parser",
+        "charm_output_map": output_map.to_json()
     });
-
-    // 3. Extract asset info from spell outputs
-    let asset_infos = NativeCharmParser::extract_asset_info(&spell);
+    if output_map.is_malformed() {
+        charm_json["valid"] = json!(false);
+    }
 
     // 4. Derive primary app_id / asset_type / amount from first asset
     let (app_id, asset_type, amount) = if let Some(first) = asset_infos.first() {
-        let atype = if first.app_id.starts_with("t/") {
-            "token"
-        } else if first.app_id.starts_with("n/") {
-            "nft"
-        } else if first.app_id.starts_with("B/") {
-            "dapp"
-        } else {
-            "other"
-        };
-        (first.app_id.clone(), atype.to_string(), first.amount as i64)
+        let atype = AppKind::of(&first.app_id).asset_type();
+        (first.app_id.clone(), atype, first.amount as i64)
     } else {
-        ("other".to_string(), "spell".to_string(), 0i64)
+        ("other".to_string(), AssetType::Spell, 0i64)
     };
 
-    // 5. Extract holder address
-    let address = AddressExtractor::extract_charm_holder_address(raw_hex, network)
+    // 5. Extract holder address from the first charm output
+    let charm_vout = asset_infos.first().map(|a| a.vout_index as usize);
+    let address = AddressExtractor::extract_charm_holder_address(raw_hex, network, charm_vout)
         .ok()
         .flatten();
 
@@ -121,7 +241,7 @@ pub fn analyze_tx(
     let has_contract_app = spell
         .app_public_inputs
         .keys()
-        .any(|app| app.to_string().starts_with("c/"));
+        .any(|app| AppKind::from_tag(app.tag) == AppKind::Contract);
     let is_beaming = has_beamed_outs || has_contract_app;
     if has_beamed_outs {
         tag_list.push("beaming".to_string());
@@ -245,4 +365,124 @@ mod tests {
         assert!(!analyzed.asset_infos.is_empty());
         assert!(analyzed.app_id.starts_with("t/") || analyzed.app_id.starts_with("n/"));
     }
+
+    fn asset(app_id: &str, vout_index: i32) -> AssetInfo {
+        AssetInfo {
+            app_id: app_id.to_string(),
+            vout_index,
+            amount: 1,
+            asset_type: AppKind::of(app_id).asset_type(),
+        }
+    }
+
+    #[test]
+    fn output_map_keeps_vouts_across_interleaved_change() {
+        // Change at 1 and 3, the spell OP_RETURN at 5.
+        let assets = [
+            asset("t/aa/01", 0),
+            asset("t/aa/01", 2),
+            asset("n/bb/02", 2),
+            asset("t/aa/01", 4),
+        ];
+        let map = CharmOutputMap::build(&assets, 6);
+        assert_eq!(
+            map.outputs,
+            vec![
+                (0, vec!["t/aa/01".to_string()]),
+                (2, vec!["t/aa/01".to_string(), "n/bb/02".to_string()]),
+                (4, vec!["t/aa/01".to_string()]),
+            ]
+        );
+        assert!(!map.is_malformed());
+    }
+
+    #[test]
+    fn output_map_flags_outputs_past_the_tx() {
+        let assets = [asset("t/aa/01", 0), asset("t/aa/01", 3), asset("n/bb/02", 3)];
+        let map = CharmOutputMap::build(&assets, 2);
+        assert_eq!(map.outputs, vec![(0, vec!["t/aa/01".to_string()])]);
+        assert_eq!(map.missing, vec![3]);
+        assert!(map.is_malformed());
+        assert!(map.contains(&assets[0]) && !map.contains(&assets[1]));
+    }
+
+    #[test]
+    fn fixture_charm_keeps_its_vout_ahead_of_change_and_spell_outputs() {
+        // Charm at 0, change at 1, spell OP_RETURN at 2, change at 3.
+        let hex = include_str!("../../../tests/fixtures/parser/dex_bid_order_7269cf1b.hex").trim();
+        let analyzed = analyze_tx("7269cf1b", hex, "mainnet", VerifyMode::Strict).unwrap();
+        let vouts: Vec<i32> = analyzed.asset_infos.iter().map(|a| a.vout_index).collect();
+        assert_eq!(vouts, [0]);
+        assert_eq!(
+            analyzed.charm_json["charm_output_map"],
+            json!({
+                "tx_outputs": 4,
+                "outputs": [{ "vout": 0, "apps": [analyzed.app_id] }],
+                "missing": [],
+            })
+        );
+        assert!(analyzed.charm_json.get("valid").is_none());
+    }
+
+    #[test]
+    fn oversized_spell_is_trimmed_on_charm_rows_only() {
+        let image = format!("data:image/png;base64,{}", "A".repeat(200_000));
+        let charm_json = json!({
+            "type": "spell",
+            "detected": true,
+            "native_data": {"version": 10, "tx": {"outs": [{"0": {"name": "Pic", "image": image}}]}},
+        });
+        let analyzed = AnalyzedTx {
+            txid: "aa".to_string(),
+            charm_json: charm_json.clone(),
+            app_id: "n/aa/bb".to_string(),
+            asset_type: AssetType::Nft,
+            amount: 0,
+            address: None,
+            tags: None,
+            dex_result: None,
+            asset_infos: Vec::new(),
+            is_beaming: false,
+            version: 10,
+            tx_type: "spell".to_string(),
+            beamed_out_indices: Default::default(),
+        };
+
+        let row = analyzed.charm_row_data();
+        assert!(charms_core::is_data_truncated(&row));
+        assert_eq!(row["native_data"]["tx"]["outs"][0]["0"]["name"], "Pic");
+        assert!(row["native_data"]["tx"]["outs"][0]["0"]["image"].is_null());
+        assert_eq!(analyzed.charm_json, charm_json);
+    }
+
+    #[test]
+    fn reclassify_as_fulfill_bid_updates_operation_tags_and_tx_type() {
+        let mut analyzed = AnalyzedTx {
+            txid: "aa".to_string(),
+            charm_json: json!({}),
+            app_id: "t/aa/bb".to_string(),
+            asset_type: AssetType::Token,
+            amount: 0,
+            address: None,
+            tags: Some("charms-cast,fulfill-ask".to_string()),
+            dex_result: Some(dex::DexDetectionResult {
+                operation: dex::DexOperation::FulfillAsk,
+                dex_app_id: "b/00/cc".to_string(),
+                order: None,
+                tags: vec!["charms-cast".to_string()],
+            }),
+            asset_infos: Vec::new(),
+            is_beaming: false,
+            version: 10,
+            tx_type: "dex_fulfill_ask".to_string(),
+            beamed_out_indices: Default::default(),
+        };
+        analyzed.reclassify_as_fulfill_bid();
+        assert_eq!(
+            analyzed.dex_result.map(|d| d.operation),
+            Some(dex::DexOperation::FulfillBid)
+        );
+        assert_eq!(analyzed.tags.as_deref(), Some("charms-cast,fulfill-bid"));
+        assert_eq!(analyzed.tx_type, "dex_fulfill_bid");
+    }
 }
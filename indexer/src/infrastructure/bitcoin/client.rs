@@ -3,6 +3,7 @@ use bitcoincore_rpc::bitcoin::BlockHash;
 use bitcoincore_rpc::bitcoin::Txid;
 use bitcoincore_rpc::{Auth, Client, RpcApi};
 use std::env;
+use std::future::Future;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -39,12 +40,50 @@ async fn fetch_esplora_mempool(base_url: &str) -> Result<Vec<String>, String> {
     res.json::<Vec<String>>().await.map_err(|e| e.to_string())
 }
 
+/// Upper bounds for a single node call. A node that hangs (disk thrash,
+/// network partition) would otherwise freeze the block loop with no error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcTimeouts {
+    /// Block count / hash / full block fetches
+    pub block: Duration,
+    /// Single raw transaction fetches
+    pub tx: Duration,
+}
+
+impl Default for RpcTimeouts {
+    fn default() -> Self {
+        Self {
+            block: Duration::from_secs(30),
+            tx: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RpcTimeouts {
+    /// `BITCOIN_RPC_TIMEOUT_SECS` overrides both limits when set; otherwise
+    /// blocks get 30s and transactions 10s.
+    pub fn from_env() -> Self {
+        match env::var("BITCOIN_RPC_TIMEOUT_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+        {
+            Some(secs) => Self {
+                block: Duration::from_secs(secs),
+                tx: Duration::from_secs(secs),
+            },
+            None => Self::default(),
+        }
+    }
+}
+
 /// Provides access to Bitcoin Core RPC API
 #[derive(Debug, Clone)]
 pub struct BitcoinClient {
     client: Option<Arc<Client>>,                // Legacy single client
     simple_client: Option<SimpleBitcoinClient>, // New simple client
     network_id: NetworkId,
+    timeouts: RpcTimeouts,
 }
 
 impl BitcoinClient {
@@ -73,6 +112,7 @@ impl BitcoinClient {
                     client: Some(Arc::new(client)),
                     simple_client: None,
                     network_id,
+                    timeouts: RpcTimeouts::from_env(),
                 })
             }
             Err(e) => {
@@ -96,23 +136,63 @@ impl BitcoinClient {
             client: None,
             simple_client: Some(simple_client),
             network_id,
+            timeouts: RpcTimeouts::from_env(),
         }
     }
 
+    /// Overrides the per-call timeouts read from the environment
+    pub fn with_timeouts(mut self, timeouts: RpcTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
     /// Returns the network identifier for this client
     pub fn network_id(&self) -> &NetworkId {
         &self.network_id
     }
 
+    /// Bounds `fut` by `limit`, mapping an elapsed deadline to
+    /// `BitcoinClientError::Timeout`.
+    async fn timed<T>(
+        &self,
+        limit: Duration,
+        operation: &str,
+        fut: impl Future<Output = Result<T, BitcoinClientError>>,
+    ) -> Result<T, BitcoinClientError> {
+        match tokio::time::timeout(limit, fut).await {
+            Ok(result) => result,
+            Err(_) => Err(BitcoinClientError::Timeout(format!(
+                "[{}] {} did not answer within {:?}",
+                self.network_id.name, operation, limit
+            ))),
+        }
+    }
+
+    /// Runs a blocking legacy-client call on the blocking pool so the
+    /// timeout can fire even while the RPC is stuck on the socket.
+    async fn blocking_call<T, F>(client: &Arc<Client>, call: F) -> Result<T, BitcoinClientError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Client) -> Result<T, bitcoincore_rpc::Error> + Send + 'static,
+    {
+        let client = client.clone();
+        tokio::task::spawn_blocking(move || call(&client).map_err(BitcoinClientError::RpcError))
+            .await
+            .map_err(|e| BitcoinClientError::Other(format!("spawn_blocking join error: {}", e)))?
+    }
+
     /// Returns the current blockchain height
     pub async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
         if let Some(simple_client) = &self.simple_client {
-            simple_client.get_block_count().await
+            self.timed(self.timeouts.block, "getblockcount", simple_client.get_block_count())
+                .await
         } else if let Some(client) = &self.client {
-            match client.get_block_count() {
-                Ok(count) => Ok(count),
-                Err(e) => Err(e.into()),
-            }
+            self.timed(
+                self.timeouts.block,
+                "getblockcount",
+                Self::blocking_call(client, |c| c.get_block_count()),
+            )
+            .await
         } else {
             Err(BitcoinClientError::ConnectionError(
                 "No client available".to_string(),
@@ -123,15 +203,20 @@ impl BitcoinClient {
     /// Returns the block hash at specified height
     pub async fn get_block_hash(&self, height: u64) -> Result<BlockHash, BitcoinClientError> {
         if let Some(simple_client) = &self.simple_client {
-            let bitcoin_hash = simple_client.get_block_hash(height).await?;
+            let bitcoin_hash = self
+                .timed(self.timeouts.block, "getblockhash", simple_client.get_block_hash(height))
+                .await?;
             // Convert from bitcoin::BlockHash to bitcoincore_rpc::bitcoin::BlockHash
             bitcoincore_rpc::bitcoin::BlockHash::from_str(&bitcoin_hash.to_string()).map_err(|e| {
                 BitcoinClientError::Other(format!("Failed to convert block hash: {}", e))
             })
         } else if let Some(client) = &self.client {
-            client
-                .get_block_hash(height)
-                .map_err(BitcoinClientError::RpcError)
+            self.timed(
+                self.timeouts.block,
+                "getblockhash",
+                Self::blocking_call(client, move |c| c.get_block_hash(height)),
+            )
+            .await
         } else {
             Err(BitcoinClientError::ConnectionError(
                 "No client available".to_string(),
@@ -143,16 +228,27 @@ impl BitcoinClient {
     pub async fn get_best_block_hash(&self) -> Result<BlockHash, BitcoinClientError> {
         if let Some(simple_client) = &self.simple_client {
             // Get current block count and then get hash for that height
-            let block_count = simple_client.get_block_count().await?;
-            let bitcoin_hash = simple_client.get_block_hash(block_count).await?;
+            let block_count = self
+                .timed(self.timeouts.block, "getblockcount", simple_client.get_block_count())
+                .await?;
+            let bitcoin_hash = self
+                .timed(
+                    self.timeouts.block,
+                    "getblockhash",
+                    simple_client.get_block_hash(block_count),
+                )
+                .await?;
             // Convert from bitcoin::BlockHash to bitcoincore_rpc::bitcoin::BlockHash
             bitcoincore_rpc::bitcoin::BlockHash::from_str(&bitcoin_hash.to_string()).map_err(|e| {
                 BitcoinClientError::Other(format!("Failed to convert best block hash: {}", e))
             })
         } else if let Some(client) = &self.client {
-            client
-                .get_best_block_hash()
-                .map_err(BitcoinClientError::RpcError)
+            self.timed(
+                self.timeouts.block,
+                "getbestblockhash",
+                Self::blocking_call(client, |c| c.get_best_block_hash()),
+            )
+            .await
         } else {
             Err(BitcoinClientError::ConnectionError(
                 "No client available".to_string(),
@@ -163,9 +259,16 @@ impl BitcoinClient {
     /// Returns the full block data for specified hash
     pub async fn get_block(&self, hash: &BlockHash) -> Result<Block, BitcoinClientError> {
         if let Some(simple_client) = &self.simple_client {
-            simple_client.get_block(hash).await
+            self.timed(self.timeouts.block, "getblock", simple_client.get_block(hash))
+                .await
         } else if let Some(client) = &self.client {
-            client.get_block(hash).map_err(|e| e.into())
+            let hash = *hash;
+            self.timed(
+                self.timeouts.block,
+                "getblock",
+                Self::blocking_call(client, move |c| c.get_block(&hash)),
+            )
+            .await
         } else {
             Err(BitcoinClientError::ConnectionError(
                 "No client available".to_string(),
@@ -235,15 +338,29 @@ impl BitcoinClient {
         };
 
         if let Some(simple_client) = &self.simple_client {
-            simple_client
-                .get_raw_transaction_hex(txid, block_hash)
-                .await
+            self.timed(
+                self.timeouts.tx,
+                "getrawtransaction",
+                simple_client.get_raw_transaction_hex(txid, block_hash),
+            )
+            .await
         } else if let Some(client) = &self.client {
-            match client.get_raw_transaction(&txid_parsed, block_hash) {
+            let block_hash = block_hash.copied();
+            let fetched = self
+                .timed(
+                    self.timeouts.tx,
+                    "getrawtransaction",
+                    Self::blocking_call(client, move |c| {
+                        c.get_raw_transaction(&txid_parsed, block_hash.as_ref())
+                    }),
+                )
+                .await;
+            match fetched {
                 Ok(tx) => {
                     let tx_bytes = bitcoincore_rpc::bitcoin::consensus::serialize(&tx);
                     Ok(hex::encode(tx_bytes))
                 }
+                Err(e @ BitcoinClientError::Timeout(_)) => Err(e),
                 Err(e) => {
                     // Local node may not have the tx in its mempool when we
                     // discovered it via the Esplora supplement. Try the
@@ -253,7 +370,7 @@ impl BitcoinClient {
                             return Ok(hex);
                         }
                     }
-                    Err(BitcoinClientError::RpcError(e))
+                    Err(e)
                 }
             }
         } else {
@@ -276,3 +393,101 @@ async fn fetch_esplora_tx_hex(base_url: &str, txid: &str) -> Result<String, Stri
     }
     res.text().await.map(|t| t.trim().to_string()).map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::bitcoin::BitcoinProvider;
+    use async_trait::async_trait;
+
+    /// Provider whose every call hangs forever, like a node stuck on disk I/O.
+    #[derive(Debug)]
+    struct HangingProvider;
+
+    #[async_trait]
+    impl BitcoinProvider for HangingProvider {
+        fn provider_name(&self) -> String {
+            "hanging".to_string()
+        }
+
+        async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+            std::future::pending().await
+        }
+
+        async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+            std::future::pending().await
+        }
+
+        async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+            std::future::pending().await
+        }
+
+        async fn get_raw_transaction_hex(
+            &self,
+            _txid: &str,
+            _block_hash: Option<&BlockHash>,
+        ) -> Result<String, BitcoinClientError> {
+            std::future::pending().await
+        }
+
+        async fn apply_rate_limiting(&self) {}
+    }
+
+    fn hanging_client(timeouts: RpcTimeouts) -> BitcoinClient {
+        let simple = SimpleBitcoinClient::from_provider(
+            Arc::new(HangingProvider),
+            NetworkId::new(NetworkType::Bitcoin, "testnet4"),
+        );
+        BitcoinClient::from_simple_client(simple).with_timeouts(timeouts)
+    }
+
+    const SHORT: RpcTimeouts = RpcTimeouts {
+        block: Duration::from_millis(50),
+        tx: Duration::from_millis(20),
+    };
+
+    #[tokio::test]
+    async fn block_fetch_times_out() {
+        let client = hanging_client(SHORT);
+        let hash = BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        )
+        .unwrap();
+
+        let err = client.get_block(&hash).await.unwrap_err();
+        assert!(matches!(err, BitcoinClientError::Timeout(_)), "got {err}");
+        assert!(matches!(
+            client.get_block_count().await,
+            Err(BitcoinClientError::Timeout(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn tx_fetch_uses_tx_timeout() {
+        let client = hanging_client(RpcTimeouts {
+            block: Duration::from_secs(60),
+            tx: Duration::from_millis(20),
+        });
+        let started = std::time::Instant::now();
+        let err = client
+            .get_raw_transaction_hex(
+                "97dc8dd9d239a86efc0d7bf6154eb960001973d10d417b1f2bbb806771b2c26d",
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BitcoinClientError::Timeout(_)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn timeout_is_retryable_not_skippable() {
+        let err = BitcoinClientError::Timeout("getblock: block not found yet".to_string());
+        assert!(err.is_retryable());
+        assert!(!err.is_block_unavailable());
+
+        let pruned = BitcoinClientError::Other("Block not available (pruned data)".to_string());
+        assert!(pruned.is_block_unavailable());
+        assert!(!pruned.is_retryable());
+    }
+}
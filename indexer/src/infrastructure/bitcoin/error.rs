@@ -14,6 +14,8 @@ pub enum BitcoinClientError {
     NetworkError(String),
     /// Parse error
     ParseError(String),
+    /// The node did not answer within the configured RPC timeout
+    Timeout(String),
     /// Other error
     Other(String),
 }
@@ -26,6 +28,7 @@ impl Clone for BitcoinClientError {
             BitcoinClientError::ConfigError(msg) => BitcoinClientError::ConfigError(msg.clone()),
             BitcoinClientError::NetworkError(msg) => BitcoinClientError::NetworkError(msg.clone()),
             BitcoinClientError::ParseError(msg) => BitcoinClientError::ParseError(msg.clone()),
+            BitcoinClientError::Timeout(msg) => BitcoinClientError::Timeout(msg.clone()),
             BitcoinClientError::Other(msg) => BitcoinClientError::Other(msg.clone()),
         }
     }
@@ -39,6 +42,7 @@ impl fmt::Display for BitcoinClientError {
             BitcoinClientError::ConfigError(msg) => write!(f, "Configuration error: {}", msg),
             BitcoinClientError::NetworkError(msg) => write!(f, "Network error: {}", msg),
             BitcoinClientError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            BitcoinClientError::Timeout(msg) => write!(f, "Timeout: {}", msg),
             BitcoinClientError::Other(msg) => write!(f, "Error: {}", msg),
         }
     }
@@ -46,6 +50,36 @@ impl fmt::Display for BitcoinClientError {
 
 impl Error for BitcoinClientError {}
 
+impl BitcoinClientError {
+    /// Transient failures (hung node, dropped connection) that are worth
+    /// retrying against the same block instead of giving up on it.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            BitcoinClientError::Timeout(_)
+                | BitcoinClientError::ConnectionError(_)
+                | BitcoinClientError::NetworkError(_)
+                | BitcoinClientError::RpcError(bitcoincore_rpc::Error::JsonRpc(
+                    bitcoincore_rpc::jsonrpc::Error::Transport(_)
+                ))
+        )
+    }
+
+    /// The node answered but does not have the block (pruned, out of range).
+    /// The block loop skips these heights. A timeout never counts here even
+    /// if its message happens to mention a missing block.
+    pub fn is_block_unavailable(&self) -> bool {
+        if matches!(self, BitcoinClientError::Timeout(_)) {
+            return false;
+        }
+        let error_msg = self.to_string().to_lowercase();
+        error_msg.contains("pruned")
+            || error_msg.contains("block not available")
+            || error_msg.contains("block height out of range")
+            || error_msg.contains("block not found")
+    }
+}
+
 impl From<bitcoincore_rpc::Error> for BitcoinClientError {
     fn from(error: bitcoincore_rpc::Error) -> Self {
         BitcoinClientError::RpcError(error)
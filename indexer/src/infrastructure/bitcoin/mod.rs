@@ -4,8 +4,8 @@ mod provider_factory;
 mod providers;
 mod simple_client;
 
-pub use client::BitcoinClient;
+pub use client::{BitcoinClient, RpcTimeouts};
 pub use error::BitcoinClientError;
-pub use providers::{BitcoinProvider, QuickNodeProvider, BitcoinNodeProvider};
+pub use providers::{BitcoinProvider, QuickNodeProvider, BitcoinNodeProvider, LoadBalancedProvider};
 pub use provider_factory::ProviderFactory;
 pub use simple_client::SimpleBitcoinClient;
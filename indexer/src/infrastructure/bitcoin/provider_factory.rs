@@ -1,9 +1,11 @@
 //! Provider factory for creating Bitcoin providers based on configuration
 
 use std::sync::Arc;
-use crate::config::{BitcoinConfig, ProviderType};
+use crate::config::{BitcoinConfig, ProviderSpec, ProviderType};
 use crate::infrastructure::bitcoin::error::BitcoinClientError;
-use crate::infrastructure::bitcoin::providers::{BitcoinProvider, QuickNodeProvider, BitcoinNodeProvider};
+use crate::infrastructure::bitcoin::providers::{
+    BitcoinProvider, QuickNodeProvider, BitcoinNodeProvider, LoadBalancedProvider,
+};
 
 /// Factory for creating Bitcoin providers
 pub struct ProviderFactory;
@@ -11,6 +13,10 @@ pub struct ProviderFactory;
 impl ProviderFactory {
     /// Create a provider based on the configuration
     pub fn create_provider(config: &BitcoinConfig) -> Result<Arc<dyn BitcoinProvider>, BitcoinClientError> {
+        if !config.providers.is_empty() {
+            return Self::create_load_balanced(config);
+        }
+
         match config.provider_type {
             ProviderType::QuickNode => {
                 let endpoint = config.quicknode_endpoint
@@ -19,7 +25,7 @@ impl ProviderFactory {
                         "QuickNode endpoint not configured".to_string()
                     ))?;
                 
-                let provider = QuickNodeProvider::new(endpoint.clone());
+                let provider = QuickNodeProvider::new(endpoint.clone())?;
                 Ok(Arc::new(provider))
             },
             ProviderType::BitcoinNode => {
@@ -35,8 +41,67 @@ impl ProviderFactory {
         }
     }
 
+    /// QuickNode provider for re-fetching blocks the live provider did not
+    /// have, when an endpoint is configured and is not the live provider.
+    pub fn create_fallback_provider(
+        config: &BitcoinConfig,
+    ) -> Option<Result<Arc<dyn BitcoinProvider>, BitcoinClientError>> {
+        let endpoint = config.quicknode_endpoint.as_ref()?;
+        if config.providers.is_empty() && config.provider_type == ProviderType::QuickNode {
+            return None;
+        }
+        Some(
+            QuickNodeProvider::new(endpoint.clone())
+                .map(|p| Arc::new(p) as Arc<dyn BitcoinProvider>),
+        )
+    }
+
+    /// Build one provider per `BITCOIN_<NET>_PROVIDERS` entry and wrap them
+    /// in a `LoadBalancedProvider`
+    fn create_load_balanced(config: &BitcoinConfig) -> Result<Arc<dyn BitcoinProvider>, BitcoinClientError> {
+        let mut providers = Vec::with_capacity(config.providers.len());
+        let mut primary = 0;
+        for (i, spec) in config.providers.iter().enumerate() {
+            if spec.primary {
+                primary = i;
+            }
+            providers.push((Self::create_from_spec(spec, config)?, spec.weight));
+        }
+        Ok(Arc::new(LoadBalancedProvider::new(providers, primary)?))
+    }
+
+    /// Build a single provider from a spec, falling back to the network's
+    /// RPC settings for any omitted `bitcoin_node` field
+    fn create_from_spec(
+        spec: &ProviderSpec,
+        config: &BitcoinConfig,
+    ) -> Result<Arc<dyn BitcoinProvider>, BitcoinClientError> {
+        match ProviderType::parse(&spec.provider_type) {
+            ProviderType::QuickNode => {
+                let endpoint = spec.url.clone().ok_or_else(|| BitcoinClientError::ConfigError(
+                    "QuickNode provider spec without url".to_string()
+                ))?;
+                Ok(Arc::new(QuickNodeProvider::new(endpoint)?))
+            }
+            ProviderType::BitcoinNode => {
+                let provider = BitcoinNodeProvider::new(
+                    spec.host.clone().unwrap_or_else(|| config.host.clone()),
+                    spec.port.clone().unwrap_or_else(|| config.port.clone()),
+                    spec.username.clone().unwrap_or_else(|| config.username.clone()),
+                    spec.password.clone().unwrap_or_else(|| config.password.clone()),
+                    config.network.clone(),
+                )?;
+                Ok(Arc::new(provider))
+            }
+        }
+    }
+
     /// Get provider name for logging
     pub fn get_provider_name(config: &BitcoinConfig) -> String {
+        if !config.providers.is_empty() {
+            return format!("LoadBalanced ({} endpoints)", config.providers.len());
+        }
+
         match config.provider_type {
             ProviderType::QuickNode => "QuickNode".to_string(),
             ProviderType::BitcoinNode => format!("Bitcoin Node ({})", config.network),
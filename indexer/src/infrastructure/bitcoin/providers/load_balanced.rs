@@ -0,0 +1,395 @@
+//! Weighted load balancing across several Bitcoin providers
+//!
+//! Block and transaction reads are spread over the endpoints by weight.
+//! Tip queries (`getblockcount` / `getblockhash`) always go to the primary
+//! so the block loop never sees heights from two different nodes. An
+//! endpoint that keeps failing is taken out of rotation for a cooldown,
+//! and a failed call falls over to the next healthy endpoint.
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash};
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::BitcoinProvider;
+use crate::infrastructure::bitcoin::error::BitcoinClientError;
+use crate::utils::logging;
+
+/// Per-endpoint circuit breaker. After `FAILURE_THRESHOLD` consecutive
+/// transport failures the endpoint is skipped for `COOLDOWN_SECS`.
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    /// Unix timestamp (secs) when the circuit opened (0 = healthy)
+    open_since: AtomicU64,
+    failures: AtomicU32,
+}
+
+impl EndpointHealth {
+    const FAILURE_THRESHOLD: u32 = 3;
+    const COOLDOWN_SECS: u64 = 60;
+
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    fn is_open(&self) -> bool {
+        let opened = self.open_since.load(Ordering::Relaxed);
+        if opened == 0 {
+            return false;
+        }
+        if Self::now().saturating_sub(opened) >= Self::COOLDOWN_SECS {
+            // Cooldown expired — let the endpoint back in on probation
+            self.open_since.store(0, Ordering::Relaxed);
+            self.failures.store(0, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    fn record_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns true when this failure opened the circuit
+    fn record_failure(&self) -> bool {
+        let n = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if n >= Self::FAILURE_THRESHOLD && self.open_since.load(Ordering::Relaxed) == 0 {
+            self.open_since.store(Self::now(), Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    provider: Arc<dyn BitcoinProvider>,
+    weight: u32,
+    health: EndpointHealth,
+}
+
+/// Provider that fans reads out over several weighted endpoints
+#[derive(Debug)]
+pub struct LoadBalancedProvider {
+    endpoints: Vec<Endpoint>,
+    primary: usize,
+    cursor: AtomicU64,
+}
+
+impl LoadBalancedProvider {
+    /// `providers` is a list of (provider, weight); `primary` indexes into it.
+    pub fn new(
+        providers: Vec<(Arc<dyn BitcoinProvider>, u32)>,
+        primary: usize,
+    ) -> Result<Self, BitcoinClientError> {
+        if providers.is_empty() {
+            return Err(BitcoinClientError::ConfigError(
+                "Load-balanced provider needs at least one endpoint".to_string(),
+            ));
+        }
+        if primary >= providers.len() {
+            return Err(BitcoinClientError::ConfigError(format!(
+                "Primary provider index {} out of range",
+                primary
+            )));
+        }
+        if providers.iter().all(|(_, w)| *w == 0) {
+            return Err(BitcoinClientError::ConfigError(
+                "Load-balanced provider needs a non-zero weight".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            endpoints: providers
+                .into_iter()
+                .map(|(provider, weight)| Endpoint {
+                    provider,
+                    weight,
+                    health: EndpointHealth::default(),
+                })
+                .collect(),
+            primary,
+            cursor: AtomicU64::new(0),
+        })
+    }
+
+    /// Endpoints to try for a tip query: primary first, then the rest.
+    fn primary_order(&self) -> Vec<usize> {
+        let mut order = vec![self.primary];
+        order.extend((0..self.endpoints.len()).filter(|i| *i != self.primary));
+        self.healthy_or_all(order)
+    }
+
+    /// Endpoints to try for a read: one picked by weighted round-robin over
+    /// the healthy set, then the remaining endpoints as failover.
+    fn weighted_order(&self) -> Vec<usize> {
+        let healthy: Vec<usize> = (0..self.endpoints.len())
+            .filter(|i| self.endpoints[*i].weight > 0 && !self.endpoints[*i].health.is_open())
+            .collect();
+        let total: u64 = healthy.iter().map(|i| self.endpoints[*i].weight as u64).sum();
+
+        let mut picked = None;
+        if total > 0 {
+            let mut slot = self.cursor.fetch_add(1, Ordering::Relaxed) % total;
+            for i in &healthy {
+                let weight = self.endpoints[*i].weight as u64;
+                if slot < weight {
+                    picked = Some(*i);
+                    break;
+                }
+                slot -= weight;
+            }
+        }
+        let mut order: Vec<usize> = picked.into_iter().collect();
+        order.extend((0..self.endpoints.len()).filter(|i| Some(*i) != picked));
+        self.healthy_or_all(order)
+    }
+
+    /// Drop endpoints with an open circuit, unless that would leave none —
+    /// better to hammer a sick endpoint than to fail every call outright.
+    fn healthy_or_all(&self, order: Vec<usize>) -> Vec<usize> {
+        let healthy: Vec<usize> = order
+            .iter()
+            .copied()
+            .filter(|i| !self.endpoints[*i].health.is_open())
+            .collect();
+        if healthy.is_empty() {
+            order
+        } else {
+            healthy
+        }
+    }
+
+    /// Run `call` against each endpoint in `order` until one answers.
+    /// Transport-level failures count against the endpoint and fall over to
+    /// the next; any other error (pruned block, bad txid) is returned as-is.
+    async fn dispatch<'a, T>(
+        &'a self,
+        order: Vec<usize>,
+        call: impl Fn(&'a dyn BitcoinProvider) -> BoxFuture<'a, Result<T, BitcoinClientError>>,
+    ) -> Result<T, BitcoinClientError> {
+        let mut last_err = None;
+        for idx in order {
+            let endpoint = &self.endpoints[idx];
+            match call(endpoint.provider.as_ref()).await {
+                Ok(value) => {
+                    endpoint.health.record_success();
+                    return Ok(value);
+                }
+                Err(e) if e.is_retryable() => {
+                    if endpoint.health.record_failure() {
+                        logging::log_warning(&format!(
+                            "{} removed from rotation for {}s: {}",
+                            endpoint.provider.provider_name(),
+                            EndpointHealth::COOLDOWN_SECS,
+                            e
+                        ));
+                    }
+                    last_err = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            BitcoinClientError::ConnectionError("No provider available".to_string())
+        }))
+    }
+}
+
+#[async_trait]
+impl BitcoinProvider for LoadBalancedProvider {
+    fn provider_name(&self) -> String {
+        let names: Vec<String> = self
+            .endpoints
+            .iter()
+            .map(|e| format!("{}×{}", e.provider.provider_name(), e.weight))
+            .collect();
+        format!("LoadBalanced[{}]", names.join(", "))
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        self.dispatch(self.primary_order(), |p| p.get_block_count())
+            .await
+    }
+
+    async fn get_block_hash(&self, height: u64) -> Result<BlockHash, BitcoinClientError> {
+        self.dispatch(self.primary_order(), |p| p.get_block_hash(height))
+            .await
+    }
+
+    async fn get_block(&self, block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        self.dispatch(self.weighted_order(), |p| p.get_block(block_hash))
+            .await
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        txid: &str,
+        block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        self.dispatch(self.weighted_order(), |p| {
+            Box::pin(async move {
+                p.apply_rate_limiting().await;
+                p.get_raw_transaction_hex(txid, block_hash).await
+            })
+        })
+        .await
+    }
+
+    async fn apply_rate_limiting(&self) {
+        // Applied per endpoint inside `get_raw_transaction_hex`
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const ZERO_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+    /// Counts calls; answers with its own tip height or a transport error.
+    #[derive(Debug)]
+    struct CountingProvider {
+        name: &'static str,
+        tip: u64,
+        down: bool,
+        calls: AtomicU64,
+    }
+
+    impl CountingProvider {
+        fn new(name: &'static str, tip: u64, down: bool) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                tip,
+                down,
+                calls: AtomicU64::new(0),
+            })
+        }
+
+        fn calls(&self) -> u64 {
+            self.calls.load(Ordering::Relaxed)
+        }
+
+        fn answer<T>(&self, value: T) -> Result<T, BitcoinClientError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            if self.down {
+                Err(BitcoinClientError::ConnectionError(format!("{} down", self.name)))
+            } else {
+                Ok(value)
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BitcoinProvider for CountingProvider {
+        fn provider_name(&self) -> String {
+            self.name.to_string()
+        }
+
+        async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+            self.answer(self.tip)
+        }
+
+        async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+            self.answer(BlockHash::from_str(ZERO_HASH).unwrap())
+        }
+
+        async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Err(BitcoinClientError::Other("block not found".to_string()))
+        }
+
+        async fn get_raw_transaction_hex(
+            &self,
+            _txid: &str,
+            _block_hash: Option<&BlockHash>,
+        ) -> Result<String, BitcoinClientError> {
+            self.answer(self.name.to_string())
+        }
+
+        async fn apply_rate_limiting(&self) {}
+    }
+
+    #[tokio::test]
+    async fn distributes_reads_by_weight() {
+        let a = CountingProvider::new("a", 100, false);
+        let b = CountingProvider::new("b", 100, false);
+        let c = CountingProvider::new("c", 100, false);
+        let lb = LoadBalancedProvider::new(
+            vec![(a.clone(), 2), (b.clone(), 1), (c.clone(), 1)],
+            0,
+        )
+        .unwrap();
+
+        for _ in 0..1000 {
+            lb.get_raw_transaction_hex("txid", None).await.unwrap();
+        }
+
+        assert_eq!(a.calls(), 500);
+        assert_eq!(b.calls(), 250);
+        assert_eq!(c.calls(), 250);
+    }
+
+    #[tokio::test]
+    async fn tip_queries_always_hit_primary() {
+        let a = CountingProvider::new("a", 100, false);
+        let b = CountingProvider::new("b", 99, false);
+        let lb = LoadBalancedProvider::new(vec![(a.clone(), 5), (b.clone(), 5)], 1).unwrap();
+
+        for _ in 0..10 {
+            assert_eq!(lb.get_block_count().await.unwrap(), 99);
+        }
+        assert_eq!(a.calls(), 0);
+        assert_eq!(b.calls(), 10);
+    }
+
+    #[tokio::test]
+    async fn unhealthy_endpoint_leaves_rotation() {
+        let up = CountingProvider::new("up", 100, false);
+        let down = CountingProvider::new("down", 100, true);
+        let lb = LoadBalancedProvider::new(vec![(up.clone(), 1), (down.clone(), 1)], 0).unwrap();
+
+        for _ in 0..100 {
+            assert_eq!(lb.get_raw_transaction_hex("txid", None).await.unwrap(), "up");
+        }
+
+        // Every call routed to `down` failed over to `up`; after the
+        // threshold `down` is no longer tried at all.
+        assert_eq!(down.calls(), EndpointHealth::FAILURE_THRESHOLD as u64);
+        assert_eq!(up.calls(), 100);
+    }
+
+    #[tokio::test]
+    async fn primary_failure_falls_over() {
+        let down = CountingProvider::new("down", 100, true);
+        let up = CountingProvider::new("up", 101, false);
+        let lb = LoadBalancedProvider::new(vec![(down, 1), (up, 1)], 0).unwrap();
+
+        assert_eq!(lb.get_block_count().await.unwrap(), 101);
+    }
+
+    #[tokio::test]
+    async fn non_transport_errors_do_not_fail_over() {
+        let a = CountingProvider::new("a", 100, false);
+        let b = CountingProvider::new("b", 100, false);
+        let lb = LoadBalancedProvider::new(vec![(a.clone(), 1), (b.clone(), 1)], 0).unwrap();
+        let hash = BlockHash::from_str(ZERO_HASH).unwrap();
+
+        let err = lb.get_block(&hash).await.unwrap_err();
+        assert!(err.is_block_unavailable());
+        assert_eq!(a.calls() + b.calls(), 1);
+    }
+
+    #[test]
+    fn rejects_bad_construction() {
+        let a: Arc<dyn BitcoinProvider> = CountingProvider::new("a", 1, false);
+        assert!(LoadBalancedProvider::new(vec![], 0).is_err());
+        assert!(LoadBalancedProvider::new(vec![(a.clone(), 1)], 1).is_err());
+        assert!(LoadBalancedProvider::new(vec![(a, 0)], 0).is_err());
+    }
+}
@@ -5,9 +5,11 @@
 
 pub mod quicknode;
 pub mod bitcoin_node;
+pub mod load_balanced;
 
 pub use quicknode::QuickNodeProvider;
 pub use bitcoin_node::BitcoinNodeProvider;
+pub use load_balanced::LoadBalancedProvider;
 
 use crate::infrastructure::bitcoin::error::BitcoinClientError;
 use bitcoincore_rpc::bitcoin::{Block, BlockHash};
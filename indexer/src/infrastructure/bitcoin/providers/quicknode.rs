@@ -2,30 +2,89 @@
 
 use async_trait::async_trait;
 use bitcoincore_rpc::bitcoin::{Block, BlockHash};
-use reqwest::Client;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, StatusCode};
 use serde_json::{json, Value};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::infrastructure::bitcoin::error::BitcoinClientError;
+use crate::utils::{logging, metrics};
 use super::BitcoinProvider;
 
+/// How many times a single call is re-sent after a 429 before giving up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// Cap on how long we honour a Retry-After header, so a misbehaving
+/// upstream cannot park the block loop for minutes
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Request counters for the provider, mirrored to Prometheus
+#[derive(Debug, Default)]
+pub struct QuickNodeCounters {
+    requests: AtomicU64,
+    rate_limited: AtomicU64,
+    retries: AtomicU64,
+}
+
+/// Point-in-time copy of `QuickNodeCounters`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuickNodeStats {
+    pub requests: u64,
+    pub rate_limited: u64,
+    pub retries: u64,
+}
+
+impl QuickNodeCounters {
+    pub fn snapshot(&self) -> QuickNodeStats {
+        QuickNodeStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+}
+
 /// QuickNode provider for Bitcoin RPC calls
 #[derive(Debug)]
 pub struct QuickNodeProvider {
     endpoint: String,
     client: Client,
+    counters: Arc<QuickNodeCounters>,
 }
 
 impl QuickNodeProvider {
-    /// Create a new QuickNode provider
-    pub fn new(endpoint: String) -> Self {
-        Self {
+    /// Create a new QuickNode provider. The HTTP client is built once here
+    /// and reused for every call so TLS sessions stay warm in the pool.
+    pub fn new(endpoint: String) -> Result<Self, BitcoinClientError> {
+        let client = Client::builder()
+            .pool_max_idle_per_host(32)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(60))
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .http2_keep_alive_while_idle(true)
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| {
+                BitcoinClientError::ConfigError(format!("Failed to build QuickNode client: {}", e))
+            })?;
+
+        Ok(Self {
             endpoint,
-            client: Client::new(),
-        }
+            client,
+            counters: Arc::new(QuickNodeCounters::default()),
+        })
+    }
+
+    /// Request / 429 / retry counters since the provider was created
+    pub fn stats(&self) -> QuickNodeStats {
+        self.counters.snapshot()
     }
 
-    /// Make a JSON-RPC call to QuickNode
+    /// Make a JSON-RPC call to QuickNode, re-sending on HTTP 429 after the
+    /// delay the server asks for (or an exponential fallback).
     async fn rpc_call(&self, method: &str, params: Value) -> Result<Value, BitcoinClientError> {
         let request_body = json!({
             "jsonrpc": "2.0",
@@ -34,13 +93,44 @@ impl QuickNodeProvider {
             "params": params
         });
 
-        let response = self
-            .client
-            .post(&self.endpoint)
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| BitcoinClientError::NetworkError(e.to_string()))?;
+        let mut attempt = 0;
+        let response = loop {
+            self.counters.requests.fetch_add(1, Ordering::Relaxed);
+            metrics::quicknode_request();
+
+            let response = self
+                .client
+                .post(&self.endpoint)
+                .json(&request_body)
+                .send()
+                .await
+                .map_err(|e| BitcoinClientError::NetworkError(e.to_string()))?;
+
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                break response;
+            }
+
+            self.counters.rate_limited.fetch_add(1, Ordering::Relaxed);
+            metrics::quicknode_rate_limited();
+            if attempt >= MAX_RATE_LIMIT_RETRIES {
+                return Err(BitcoinClientError::NetworkError(format!(
+                    "QuickNode rate limited {} after {} retries",
+                    method, attempt
+                )));
+            }
+
+            let delay = retry_after(response.headers())
+                .unwrap_or_else(|| Duration::from_millis(500 * 2_u64.pow(attempt)))
+                .min(MAX_RETRY_AFTER);
+            attempt += 1;
+            self.counters.retries.fetch_add(1, Ordering::Relaxed);
+            metrics::quicknode_retry();
+            logging::log_warning(&format!(
+                "QuickNode 429 on {}; retry {}/{} in {:?}",
+                method, attempt, MAX_RATE_LIMIT_RETRIES, delay
+            ));
+            tokio::time::sleep(delay).await;
+        };
 
         let response_text = response
             .text()
@@ -50,7 +140,7 @@ impl QuickNodeProvider {
         let response_json: Value = serde_json::from_str(&response_text)
             .map_err(|e| BitcoinClientError::ParseError(e.to_string()))?;
 
-        if let Some(error) = response_json.get("error") {
+        if let Some(error) = response_json.get("error").filter(|e| !e.is_null()) {
             return Err(BitcoinClientError::NetworkError(error.to_string()));
         }
 
@@ -61,6 +151,17 @@ impl QuickNodeProvider {
     }
 }
 
+/// Parse a Retry-After header: either delta-seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
 #[async_trait]
 impl BitcoinProvider for QuickNodeProvider {
     fn provider_name(&self) -> String {
@@ -120,3 +221,92 @@ impl BitcoinProvider for QuickNodeProvider {
         // No rate limiting for maximum performance
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Reads one HTTP request (headers + Content-Length body) off the socket.
+    async fn read_request(socket: &mut tokio::net::TcpStream) {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            if n == 0 {
+                return;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buf);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let content_length = text[..end]
+                    .lines()
+                    .find_map(|l| {
+                        let (k, v) = l.split_once(':')?;
+                        k.eq_ignore_ascii_case("content-length")
+                            .then(|| v.trim().parse::<usize>().ok())
+                            .flatten()
+                    })
+                    .unwrap_or(0);
+                if buf.len() >= end + 4 + content_length {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Serves the given raw responses in order, one per connection.
+    async fn mock_server(responses: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                read_request(&mut socket).await;
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.ok();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn retries_once_after_429() {
+        let endpoint = mock_server(vec![
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 36\r\nConnection: close\r\n\r\n{\"result\":42,\"error\":null,\"id\":1}   ",
+        ])
+        .await;
+        let provider = QuickNodeProvider::new(endpoint).unwrap();
+
+        assert_eq!(provider.get_block_count().await.unwrap(), 42);
+        assert_eq!(
+            provider.stats(),
+            QuickNodeStats {
+                requests: 2,
+                rate_limited: 1,
+                retries: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+    }
+
+    #[test]
+    fn past_retry_after_date_means_no_wait() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn missing_retry_after_falls_back() {
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+}
@@ -29,6 +29,14 @@ impl SimpleBitcoinClient {
         })
     }
 
+    /// Create a client around an already-built provider
+    pub fn from_provider(provider: Arc<dyn BitcoinProvider>, network_id: NetworkId) -> Self {
+        Self {
+            provider,
+            network_id,
+        }
+    }
+
     /// Get the current block count
     pub async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
         self.provider.get_block_count().await
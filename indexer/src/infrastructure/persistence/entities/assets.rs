@@ -33,6 +33,13 @@ pub struct Model {
     pub cardano_policy_id: Option<String>,
     pub cardano_asset_name: Option<String>,
     pub cardano_fingerprint: Option<String>,
+    /// Declared collection id, or `deployer:{address}` for ungrouped NFTs
+    pub collection: Option<String>,
+    /// First transaction seen for this app_id; only an earlier block replaces it
+    pub deploy_txid: Option<String>,
+    pub deploy_block_height: Option<i32>,
+    /// Address holding the deploy transaction's first output of this app
+    pub deployer_address: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -22,6 +22,24 @@ pub struct Model {
     pub previous_block_hash: Option<String>,
     pub tx_count: Option<i32>,
     pub charm_count: Option<i32>,
+    /// Node did not have the block (pruned/missing); the retry pass
+    /// re-attempts it. Never set together with `processed`.
+    pub skipped_pruned: bool,
+    /// Wall time of `process_block`, and of its fetch, detection and
+    /// persistence phases. Null for blocks not timed.
+    pub processing_ms: Option<i32>,
+    pub rpc_ms: Option<i32>,
+    pub parse_ms: Option<i32>,
+    pub db_ms: Option<i32>,
+    /// Header time of the block, recorded on download; the reindexer's
+    /// source for `charms.block_time`.
+    #[sea_orm(nullable)]
+    pub block_time: Option<DateTime>,
+    /// Reindex runs that failed on the block since it was last processed,
+    /// and the latest error. Runs skip a block at their attempt limit.
+    pub failed_attempts: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
     #[sea_orm(column_type = "TimestampWithTimeZone", nullable)]
     pub downloaded_at: Option<DateTimeWithTimeZone>,
     #[sea_orm(column_type = "TimestampWithTimeZone", nullable)]
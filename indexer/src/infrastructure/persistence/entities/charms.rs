@@ -5,12 +5,13 @@ use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-// Composite PK (txid, vout, app_id) — a single UTXO may carry multiple
-// distinct charm tokens (different app_indices in the NormalizedCharms
-// map). The PK was (txid, vout) until anomaly A5; that lost multi-token
-// outputs on `ON CONFLICT DO NOTHING`. `mark_charms_as_spent_batch`
-// still matches on (txid, vout) so spending a UTXO flips every token in
-// it.
+// Composite PK (txid, vout, app_id, network) — a single UTXO may carry
+// multiple distinct charm tokens (different app_indices in the
+// NormalizedCharms map). The PK was (txid, vout) until anomaly A5; that
+// lost multi-token outputs on `ON CONFLICT DO NOTHING`. `network` is part
+// of the key so the same txid on mainnet and testnet4 never collides.
+// `mark_charms_as_spent_batch` still matches on (txid, vout) so spending a
+// UTXO flips every token in it.
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "charms")]
 pub struct Model {
@@ -20,6 +21,8 @@ pub struct Model {
     pub vout: i32,
     #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
     pub app_id: String,
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub network: String,
     #[sea_orm(nullable)]
     pub block_height: Option<i32>,
     pub data: Value,
@@ -28,8 +31,6 @@ pub struct Model {
     pub asset_type: String,
     #[sea_orm(column_type = "Text")]
     pub blockchain: String,
-    #[sea_orm(column_type = "Text")]
-    pub network: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub address: Option<String>,
     pub spent: bool,
@@ -40,6 +41,36 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub tags: Option<String>,
     pub verified: bool,
+    /// Hash of the confirming block; NULL while in mempool
+    #[sea_orm(column_type = "Text", nullable)]
+    pub block_hash: Option<String>,
+    /// Position of the tx inside its block; NULL while in mempool
+    #[sea_orm(nullable)]
+    pub tx_ordinal: Option<i32>,
+    /// Empty-spell placeholder (`charms_core::is_empty_spell_charm`); kept
+    /// for txid lookups but excluded from listings and counts
+    pub is_placeholder: bool,
+    /// Transaction that spent this output, recorded by the block spent
+    /// tracker; NULL while unspent
+    #[sea_orm(column_type = "Text", nullable)]
+    pub spending_txid: Option<String>,
+    /// Crate version of the indexer that last wrote this row
+    #[sea_orm(column_type = "Text", nullable)]
+    pub indexer_version: Option<String>,
+    /// `NativeCharmParser::PARSER_REVISION` of that write
+    #[sea_orm(nullable)]
+    pub parser_revision: Option<i32>,
+    /// Reindex run that last wrote this row; NULL for live writes
+    #[sea_orm(column_type = "Text", nullable)]
+    pub reindex_run_id: Option<String>,
+    /// `charms_core::CharmOperation` of the block write; NULL while in
+    /// mempool and for rows indexed before the classification existed
+    #[sea_orm(column_type = "Text", nullable)]
+    pub operation: Option<String>,
+    /// Header time of the confirming block; NULL while in mempool and for
+    /// rows not yet backfilled. `date_created` stays the ingestion time.
+    #[sea_orm(nullable)]
+    pub block_time: Option<NaiveDateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
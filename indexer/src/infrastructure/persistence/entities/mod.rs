@@ -6,6 +6,7 @@ pub mod address_utxos;
 pub mod assets;
 pub mod block_status;
 pub mod charms;
+pub mod control_commands;
 pub mod dex_orders;
 pub mod mempool_spends; // Tracks UTXOs spent by unconfirmed txs
 pub mod monitored_addresses;
@@ -18,6 +18,12 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub seed_block_hash: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Last balance/history request through the API
+    #[sea_orm(nullable)]
+    pub last_queried_at: Option<DateTime<Utc>>,
+    /// Tracking stops past this (unless the address holds charms); NULL = no TTL
+    #[sea_orm(nullable)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
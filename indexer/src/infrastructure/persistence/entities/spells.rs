@@ -10,8 +10,10 @@ use serde_json::Value;
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "spells")]
 pub struct Model {
-    #[sea_orm(primary_key, column_type = "Text")]
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
     pub txid: String,
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub network: String,
     pub block_height: i32,
     pub data: Value,
     pub date_created: NaiveDateTime,
@@ -19,8 +21,10 @@ pub struct Model {
     pub asset_type: String,
     #[sea_orm(column_type = "Text")]
     pub blockchain: String,
-    #[sea_orm(column_type = "Text")]
-    pub network: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub indexer_version: Option<String>,
+    #[sea_orm(nullable)]
+    pub parser_revision: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
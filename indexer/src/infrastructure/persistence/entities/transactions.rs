@@ -31,6 +31,9 @@ pub struct Model {
     pub tags: Option<String>,
     #[sea_orm(column_type = "Text", nullable)]
     pub tx_type: Option<String>,
+    /// Per-output app map of the spell (`charms_core::spell_outputs`).
+    #[sea_orm(nullable)]
+    pub spell_outputs: Option<Value>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
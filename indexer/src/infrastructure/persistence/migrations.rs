@@ -0,0 +1,320 @@
+//! The SQL migrations under `database/migrations/`, bundled at compile time.
+//!
+//! `bin/migrate` applies them; the startup pre-flight compares them with
+//! `seaql_migrations` to catch a database that was not migrated. Versions
+//! are the filenames without `.sql` (`m{YYYYMMDD}_{NNNNNN}_*`).
+
+use std::collections::HashSet;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+
+/// (filename_without_ext, sql_contents) — populated by `include_str!`.
+pub const MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "m20260221_000001_mempool_indexing",
+        include_str!("../../../../database/migrations/m20260221_000001_mempool_indexing.sql"),
+    ),
+    (
+        "m20260228_000001_address_utxos_and_backfill",
+        include_str!(
+            "../../../../database/migrations/m20260228_000001_address_utxos_and_backfill.sql"
+        ),
+    ),
+    (
+        "m20260228_000002_address_transactions",
+        include_str!("../../../../database/migrations/m20260228_000002_address_transactions.sql"),
+    ),
+    (
+        "m20260301_000001_transactions_mempool_columns",
+        include_str!(
+            "../../../../database/migrations/m20260301_000001_transactions_mempool_columns.sql"
+        ),
+    ),
+    (
+        "m20260323_000001_tx_type_column",
+        include_str!("../../../../database/migrations/m20260323_000001_tx_type_column.sql"),
+    ),
+    (
+        "m20260611_000001_stats_holders_network_column",
+        include_str!(
+            "../../../../database/migrations/m20260611_000001_stats_holders_network_column.sql"
+        ),
+    ),
+    (
+        "m20260613_000001_mainnet_readiness",
+        include_str!("../../../../database/migrations/m20260613_000001_mainnet_readiness.sql"),
+    ),
+    (
+        "m20260615_000001_charms_pk_with_app_id",
+        include_str!("../../../../database/migrations/m20260615_000001_charms_pk_with_app_id.sql"),
+    ),
+    (
+        "m20260622_000001_assets_unique_app_id_network",
+        include_str!(
+            "../../../../database/migrations/m20260622_000001_assets_unique_app_id_network.sql"
+        ),
+    ),
+    (
+        "m20260701_000001_charms_block_hash_tx_ordinal",
+        include_str!(
+            "../../../../database/migrations/m20260701_000001_charms_block_hash_tx_ordinal.sql"
+        ),
+    ),
+    (
+        "m20260702_000001_stats_holders_dedupe_unique",
+        include_str!(
+            "../../../../database/migrations/m20260702_000001_stats_holders_dedupe_unique.sql"
+        ),
+    ),
+    (
+        "m20260703_000001_per_network_uniqueness",
+        include_str!("../../../../database/migrations/m20260703_000001_per_network_uniqueness.sql"),
+    ),
+    (
+        "m20260704_000001_charms_is_placeholder",
+        include_str!("../../../../database/migrations/m20260704_000001_charms_is_placeholder.sql"),
+    ),
+    (
+        "m20260705_000001_transactions_status_lifecycle",
+        include_str!(
+            "../../../../database/migrations/m20260705_000001_transactions_status_lifecycle.sql"
+        ),
+    ),
+    (
+        "m20260706_000001_control_commands",
+        include_str!("../../../../database/migrations/m20260706_000001_control_commands.sql"),
+    ),
+    (
+        "m20260707_000001_summary_gc_stats",
+        include_str!("../../../../database/migrations/m20260707_000001_summary_gc_stats.sql"),
+    ),
+    (
+        "m20260708_000001_monitored_addresses_lifecycle",
+        include_str!(
+            "../../../../database/migrations/m20260708_000001_monitored_addresses_lifecycle.sql"
+        ),
+    ),
+    (
+        "m20260709_000001_charms_spending_txid",
+        include_str!("../../../../database/migrations/m20260709_000001_charms_spending_txid.sql"),
+    ),
+    (
+        "m20260710_000001_likes_charm_id_index",
+        include_str!("../../../../database/migrations/m20260710_000001_likes_charm_id_index.sql"),
+    ),
+    (
+        "m20260711_000001_assets_collection",
+        include_str!("../../../../database/migrations/m20260711_000001_assets_collection.sql"),
+    ),
+    (
+        "m20260712_000001_mint_events",
+        include_str!("../../../../database/migrations/m20260712_000001_mint_events.sql"),
+    ),
+    (
+        "m20260713_000001_indexer_replicas",
+        include_str!("../../../../database/migrations/m20260713_000001_indexer_replicas.sql"),
+    ),
+    (
+        "m20260714_000001_webhooks",
+        include_str!("../../../../database/migrations/m20260714_000001_webhooks.sql"),
+    ),
+    (
+        "m20260715_000001_charms_confirmation_delay",
+        include_str!(
+            "../../../../database/migrations/m20260715_000001_charms_confirmation_delay.sql"
+        ),
+    ),
+    (
+        "m20260716_000001_pending_spells",
+        include_str!("../../../../database/migrations/m20260716_000001_pending_spells.sql"),
+    ),
+    (
+        "m20260717_000001_dex_order_expiry",
+        include_str!("../../../../database/migrations/m20260717_000001_dex_order_expiry.sql"),
+    ),
+    (
+        "m20260718_000001_block_status_skipped_pruned",
+        include_str!(
+            "../../../../database/migrations/m20260718_000001_block_status_skipped_pruned.sql"
+        ),
+    ),
+    (
+        "m20260719_000001_supply_changes",
+        include_str!("../../../../database/migrations/m20260719_000001_supply_changes.sql"),
+    ),
+    (
+        "m20260720_000001_likes_created_at_index",
+        include_str!("../../../../database/migrations/m20260720_000001_likes_created_at_index.sql"),
+    ),
+    (
+        "m20260721_000001_offchain_metadata",
+        include_str!("../../../../database/migrations/m20260721_000001_offchain_metadata.sql"),
+    ),
+    (
+        "m20260722_000001_dapp_asset_type",
+        include_str!("../../../../database/migrations/m20260722_000001_dapp_asset_type.sql"),
+    ),
+    (
+        "m20260723_000001_assets_deploy_origin",
+        include_str!("../../../../database/migrations/m20260723_000001_assets_deploy_origin.sql"),
+    ),
+    (
+        "m20260724_000001_charms_write_stamp",
+        include_str!("../../../../database/migrations/m20260724_000001_charms_write_stamp.sql"),
+    ),
+    (
+        "m20260725_000001_charms_type_network_index",
+        include_str!(
+            "../../../../database/migrations/m20260725_000001_charms_type_network_index.sql"
+        ),
+    ),
+    (
+        "m20260726_000001_metadata_refresh_jobs",
+        include_str!("../../../../database/migrations/m20260726_000001_metadata_refresh_jobs.sql"),
+    ),
+    (
+        "m20260727_000001_charms_operation",
+        include_str!("../../../../database/migrations/m20260727_000001_charms_operation.sql"),
+    ),
+    (
+        "m20260728_000001_block_status_timings",
+        include_str!("../../../../database/migrations/m20260728_000001_block_status_timings.sql"),
+    ),
+    (
+        "m20260729_000001_moderation",
+        include_str!("../../../../database/migrations/m20260729_000001_moderation.sql"),
+    ),
+    (
+        "m20260730_000001_provider_stale",
+        include_str!("../../../../database/migrations/m20260730_000001_provider_stale.sql"),
+    ),
+    (
+        "m20260731_000001_charms_archive",
+        include_str!("../../../../database/migrations/m20260731_000001_charms_archive.sql"),
+    ),
+    (
+        "m20260801_000001_canonical_addresses",
+        include_str!("../../../../database/migrations/m20260801_000001_canonical_addresses.sql"),
+    ),
+    (
+        "m20260802_000001_block_time",
+        include_str!("../../../../database/migrations/m20260802_000001_block_time.sql"),
+    ),
+    (
+        "m20260803_000001_locked_supply",
+        include_str!("../../../../database/migrations/m20260803_000001_locked_supply.sql"),
+    ),
+    (
+        "m20260804_000001_block_failures",
+        include_str!("../../../../database/migrations/m20260804_000001_block_failures.sql"),
+    ),
+    (
+        "m20260805_000001_possible_charm",
+        include_str!("../../../../database/migrations/m20260805_000001_possible_charm.sql"),
+    ),
+    (
+        "m20260806_000001_config_snapshots",
+        include_str!("../../../../database/migrations/m20260806_000001_config_snapshots.sql"),
+    ),
+    (
+        "m20260807_000001_charms_archive_spending_txid",
+        include_str!(
+            "../../../../database/migrations/m20260807_000001_charms_archive_spending_txid.sql"
+        ),
+    ),
+    (
+        "m20260808_000001_charms_archive_address",
+        include_str!("../../../../database/migrations/m20260808_000001_charms_archive_address.sql"),
+    ),
+    (
+        "m20260809_000001_changefeed",
+        include_str!("../../../../database/migrations/m20260809_000001_changefeed.sql"),
+    ),
+    (
+        "m20260810_000001_parser_stats",
+        include_str!("../../../../database/migrations/m20260810_000001_parser_stats.sql"),
+    ),
+    (
+        "m20260811_000001_address_utxos_charm_flags",
+        include_str!(
+            "../../../../database/migrations/m20260811_000001_address_utxos_charm_flags.sql"
+        ),
+    ),
+    (
+        "m20260812_000001_transactions_spell_outputs",
+        include_str!(
+            "../../../../database/migrations/m20260812_000001_transactions_spell_outputs.sql"
+        ),
+    ),
+    (
+        "m20260813_000001_canonical_network_names",
+        include_str!(
+            "../../../../database/migrations/m20260813_000001_canonical_network_names.sql"
+        ),
+    ),
+    (
+        "m20260814_000001_reindex_locks",
+        include_str!("../../../../database/migrations/m20260814_000001_reindex_locks.sql"),
+    ),
+    (
+        "m20260815_000001_block_detail_indexes",
+        include_str!("../../../../database/migrations/m20260815_000001_block_detail_indexes.sql"),
+    ),
+    (
+        "m20260816_000001_likes_unique_user",
+        include_str!("../../../../database/migrations/m20260816_000001_likes_unique_user.sql"),
+    ),
+    (
+        "m20260817_000001_admin_audit_log",
+        include_str!("../../../../database/migrations/m20260817_000001_admin_audit_log.sql"),
+    ),
+    (
+        "m20260818_000001_backfill_progress",
+        include_str!("../../../../database/migrations/m20260818_000001_backfill_progress.sql"),
+    ),
+    (
+        "m20260819_000001_wallet_dex_orders",
+        include_str!("../../../../database/migrations/m20260819_000001_wallet_dex_orders.sql"),
+    ),
+    (
+        "m20260820_000001_wallet_changes",
+        include_str!("../../../../database/migrations/m20260820_000001_wallet_changes.sql"),
+    ),
+];
+
+/// Versions recorded in `seaql_migrations`; empty when the table is absent.
+pub async fn applied_versions(conn: &DatabaseConnection) -> Result<HashSet<String>, DbError> {
+    let exists = conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT to_regclass('seaql_migrations') IS NOT NULL AS present".to_string(),
+        ))
+        .await?
+        .map(|row| row.try_get::<bool>("", "present"))
+        .transpose()?
+        .unwrap_or(false);
+    if !exists {
+        return Ok(HashSet::new());
+    }
+    let rows = conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT version FROM seaql_migrations".to_string(),
+        ))
+        .await?;
+    rows.into_iter()
+        .map(|r| r.try_get::<String>("", "version").map_err(Into::into))
+        .collect()
+}
+
+/// Bundled migrations not recorded as applied, in order.
+pub async fn pending(conn: &DatabaseConnection) -> Result<Vec<&'static str>, DbError> {
+    let applied = applied_versions(conn).await?;
+    Ok(MIGRATIONS
+        .iter()
+        .map(|(version, _)| *version)
+        .filter(|version| !applied.contains(*version))
+        .collect())
+}
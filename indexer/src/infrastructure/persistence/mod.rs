@@ -1,6 +1,7 @@
 pub mod connection;
 pub mod entities;
 pub mod error;
+pub mod migrations;
 pub mod repositories;
 
 pub use connection::DbPool;
@@ -4,6 +4,7 @@
 use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
 use std::fmt;
 
+use crate::domain::services::address_extractor::AddressExtractor;
 use crate::infrastructure::persistence::error::DbError;
 
 /// A single address transaction to insert
@@ -59,7 +60,7 @@ impl AddressTransactionsRepository {
                     format!(
                         "('{}', '{}', '{}', '{}', {}, {}, {}, {}, {})",
                         t.txid.replace('\'', "''"),
-                        t.address.replace('\'', "''"),
+                        AddressExtractor::canonical(&t.address).replace('\'', "''"),
                         t.network.replace('\'', "''"),
                         t.direction.replace('\'', "''"),
                         t.amount,
@@ -1,18 +1,14 @@
 //! Helper functions for asset repository
 
-use crate::domain::models::Asset;
+use crate::domain::models::{AppId, Asset};
 use crate::infrastructure::persistence::entities::assets;
 
-/// Extract identity hash from app_id
-/// Format: "{tag}/{identity}/{vk}" -> returns "{identity}"
-/// Example: "t/abc123.../def456..." -> "abc123..."
-pub fn extract_hash_from_app_id(app_id: &str) -> String {
-    // Split by '/' and get the identity part (second element)
-    let parts: Vec<&str> = app_id.split('/').collect();
-    if parts.len() >= 2 {
-        parts[1].to_string()
-    } else {
-        app_id.to_string()
+/// `LIKE` pattern matching the NFTs that share `app_id`'s identity:
+/// "{tag}/{identity}/{vk}" -> "n/{identity}/%".
+pub fn parent_nft_pattern(app_id: &str) -> String {
+    match app_id.parse::<AppId>() {
+        Ok(id) => format!("n/{}/%", id.identity()),
+        Err(_) => format!("n/{}/%", app_id),
     }
 }
 
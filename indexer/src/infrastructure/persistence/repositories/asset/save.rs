@@ -2,14 +2,24 @@
 
 use chrono::{DateTime, FixedOffset, Utc};
 use rust_decimal::Decimal;
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, NotSet, QueryFilter, Set};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, NotSet,
+    QueryFilter, QueryOrder, Set, Statement, TransactionTrait,
+};
 use serde_json::Value;
 
 use super::helpers;
 use crate::domain::models::asset_metadata::{AssetMetadata, DEFAULT_DECIMALS};
-use crate::domain::models::Asset;
+use crate::domain::models::{Asset, AssetType};
 use crate::infrastructure::persistence::entities::{assets, charms, prelude::*};
 use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::changefeed_repository::{
+    self as changefeed, Change, ChangeEntity, ChangeOp,
+};
+use crate::infrastructure::persistence::repositories::supply_changes_repository::{
+    self as supply_changes, SupplyChangeReason, SupplySource,
+};
+use crate::utils::throttled_log;
 
 /// Extract Cardano fields from data JSON
 fn extract_cardano_fields(data: &serde_json::Value) -> (Option<String>, Option<String>, Option<String>) {
@@ -19,6 +29,69 @@ fn extract_cardano_fields(data: &serde_json::Value) -> (Option<String>, Option<S
     (policy_id, asset_name, fingerprint)
 }
 
+/// Address holding the NFT's mint output, used to group NFTs that do not
+/// declare a collection. Charms are saved before assets, so the row exists.
+pub(crate) async fn deployer_address(
+    db: &DatabaseConnection,
+    app_id: &str,
+    txid: &str,
+    network: &str,
+) -> Result<Option<String>, DbError> {
+    let charm = Charms::find()
+        .filter(charms::Column::AppId.eq(app_id))
+        .filter(charms::Column::Txid.eq(txid))
+        .filter(charms::Column::Network.eq(network))
+        .filter(charms::Column::Address.is_not_null())
+        .order_by_asc(charms::Column::Vout)
+        .one(db)
+        .await
+        .map_err(DbError::SeaOrmError)?;
+    Ok(charm.and_then(|c| c.address))
+}
+
+/// Point an existing asset's deploy origin at `txid` when it is from a
+/// strictly earlier block than the one recorded: first seen wins, even when
+/// a reindex replays blocks out of order. Rows without an origin are left to
+/// `backfill-deploys`.
+pub(crate) async fn claim_earlier_deploy(
+    db: &DatabaseConnection,
+    app_id: &str,
+    network: &str,
+    txid: &str,
+    block_height: i32,
+) -> Result<(), DbError> {
+    let txn = db.begin().await?;
+    let res = txn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE assets a \
+                SET deploy_txid = $3, deploy_block_height = $4, \
+                    deployer_address = (SELECT c.address FROM charms c \
+                                         WHERE c.app_id = a.app_id AND c.network = a.network \
+                                           AND c.txid = $3 AND c.address IS NOT NULL \
+                                      ORDER BY c.vout LIMIT 1) \
+              WHERE a.app_id = $1 AND a.network = $2 AND a.deploy_block_height > $4",
+            [app_id.into(), network.into(), txid.into(), block_height.into()],
+        ))
+        .await?;
+    if res.rows_affected() > 0 {
+        changefeed::record(&txn, &[asset_update(app_id, network, Some(block_height))]).await?;
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Changefeed entry for a write to an existing asset row.
+fn asset_update(app_id: &str, network: &str, block_height: Option<i32>) -> Change {
+    Change::new(
+        ChangeEntity::Asset,
+        app_id,
+        ChangeOp::Update,
+        block_height,
+        network,
+    )
+}
+
 /// Save or update asset with correct supply logic
 /// Extract and store decimals from NFT metadata
 ///
@@ -35,13 +108,15 @@ pub async fn save_or_update_asset(
     asset: &Asset,
     amount: i64,
 ) -> Result<(), DbError> {
-    let asset_type = asset.asset_type.as_str();
-
-    // Extract hash for NFT-Token matching
-    let hash = helpers::extract_hash_from_app_id(&asset.app_id);
+    let asset_type = AssetType::of(&asset.asset_type);
+    let source = SupplySource::at(
+        SupplyChangeReason::Mint,
+        &asset.txid,
+        asset.block_height as i32,
+    );
 
     match asset_type {
-        "nft" => {
+        AssetType::Nft => {
             // NFT creation: check if already exists (per network — the same
             // app_id can legitimately exist on mainnet and testnet4).
             let existing_nft = Assets::find()
@@ -54,6 +129,9 @@ pub async fn save_or_update_asset(
             if existing_nft.is_none() {
                 // Extract metadata from NFT data
                 let metadata = AssetMetadata::from_nft_data(&asset.data);
+                let deployer =
+                    deployer_address(db, &asset.app_id, &asset.txid, &asset.network).await?;
+                let collection = metadata.collection_or_deployer(deployer.as_deref());
 
                 // Create new NFT with supply = 0 and extracted decimals
                 // Note: is_reference_nft starts as false, will be set to true when a token is found
@@ -75,6 +153,10 @@ pub async fn save_or_update_asset(
                     symbol: Set(metadata.symbol),
                     description: Set(metadata.description),
                     image_url: Set(metadata.image_url),
+                    collection: Set(collection),
+                    deploy_txid: Set(Some(asset.txid.clone())),
+                    deploy_block_height: Set(Some(asset.block_height as i32)),
+                    deployer_address: Set(deployer),
                     total_supply: Set(Some(Decimal::ZERO)), // NFT supply starts at 0
                     decimals: Set(metadata.decimals as i16), //
                     is_reference_nft: Set(false),
@@ -85,20 +167,26 @@ pub async fn save_or_update_asset(
                     updated_at: Set(Utc::now().into()),
                 };
 
-                Assets::insert(active_model)
-                    .exec(db)
-                    .await
-                    .map_err(DbError::SeaOrmError)?;
+                supply_changes::insert_asset(db, active_model, &source).await?;
+            } else {
+                // Already indexed: only an earlier block can move its origin
+                claim_earlier_deploy(
+                    db,
+                    &asset.app_id,
+                    &asset.network,
+                    &asset.txid,
+                    asset.block_height as i32,
+                )
+                .await?;
             }
-            // If NFT already exists, do nothing (idempotent)
         }
-        "token" => {
+        AssetType::Token => {
             // Token creation: find parent NFT with same identity hash
             // app_id format: {tag}/{identity}/{vk}
             // NFT and token share the same identity, so we search by n/{identity}/%
-            let parent_nft_pattern = format!("n/{}/%", hash);
+            let parent_nft_pattern = helpers::parent_nft_pattern(&asset.app_id);
             let parent_nft = Assets::find()
-                .filter(assets::Column::AssetType.eq("nft"))
+                .filter(assets::Column::AssetType.eq(AssetType::Nft.as_str()))
                 .filter(assets::Column::AppId.like(&parent_nft_pattern))
                 .filter(assets::Column::Network.eq(&asset.network))
                 .one(db)
@@ -125,10 +213,10 @@ pub async fn save_or_update_asset(
             if should_mark_nft_as_reference {
                 if let Some(ref nft) = parent_nft {
                     if let Err(e) = mark_nft_as_reference(db, &nft.app_id, &asset.network).await {
-                        crate::utils::logging::log_warning(&format!(
-                            "Failed to mark NFT as reference: {}",
-                            e
-                        ));
+                        let e = e.to_string();
+                        throttled_log::global().warn("nft-reference", &e, || {
+                            format!("Failed to mark NFT as reference: {}", e)
+                        });
                     }
                 }
             }
@@ -145,6 +233,15 @@ pub async fn save_or_update_asset(
 
             match existing_token {
                 Some(existing) => {
+                    claim_earlier_deploy(
+                        db,
+                        &asset.app_id,
+                        &asset.network,
+                        &asset.txid,
+                        asset.block_height as i32,
+                    )
+                    .await?;
+
                     // Idempotency guard: check if this charm (txid+vout) already exists.
                     // Charms are saved in STEP 3 before assets in STEP 4, so if the charm
                     // already exists, this block was partially processed and we must NOT
@@ -167,20 +264,12 @@ pub async fn save_or_update_asset(
                     let amount_decimal = Decimal::from(amount);
                     let new_supply = old_supply + amount_decimal;
 
-                    let update_model = assets::ActiveModel {
-                        id: Set(existing.id),
-                        total_supply: Set(Some(new_supply)),
-                        updated_at: Set(Utc::now().into()),
-                        ..Default::default()
-                    };
-
-                    Assets::update(update_model)
-                        .exec(db)
-                        .await
-                        .map_err(DbError::SeaOrmError)?;
+                    supply_changes::update_supply(db, &existing, new_supply, &source).await?;
                 }
                 None => {
                     // Create new token asset with metadata inherited from parent NFT
+                    let deployer =
+                        deployer_address(db, &asset.app_id, &asset.txid, &asset.network).await?;
                     let active_model = assets::ActiveModel {
                         id: NotSet,
                         app_id: Set(asset.app_id.clone()),
@@ -199,6 +288,10 @@ pub async fn save_or_update_asset(
                         symbol: Set(symbol),           // Inherit from parent NFT
                         description: Set(description), // Inherit from parent NFT
                         image_url: Set(asset.data.get("image_url").and_then(|v| v.as_str()).map(|s| s.to_string())),
+                        collection: Set(None),
+                        deploy_txid: Set(Some(asset.txid.clone())),
+                        deploy_block_height: Set(Some(asset.block_height as i32)),
+                        deployer_address: Set(deployer),
                         total_supply: Set(Some(Decimal::from(amount))),
                         decimals: Set(decimals),
                         is_reference_nft: Set(false),
@@ -209,15 +302,12 @@ pub async fn save_or_update_asset(
                         updated_at: Set(Utc::now().into()),
                     };
 
-                    Assets::insert(active_model)
-                        .exec(db)
-                        .await
-                        .map_err(DbError::SeaOrmError)?;
+                    supply_changes::insert_asset(db, active_model, &source).await?;
                 }
             }
         }
-        _ => {
-            // Other asset types: use simple accumulation with default decimals
+        AssetType::Dapp | AssetType::Other | AssetType::Spell => {
+            // Dapps and other apps: use simple accumulation with default decimals
             let existing_asset = Assets::find()
                 .filter(assets::Column::AppId.eq(&asset.app_id))
                 .filter(assets::Column::Network.eq(&asset.network))
@@ -227,6 +317,15 @@ pub async fn save_or_update_asset(
 
             match existing_asset {
                 Some(existing) => {
+                    claim_earlier_deploy(
+                        db,
+                        &asset.app_id,
+                        &asset.network,
+                        &asset.txid,
+                        asset.block_height as i32,
+                    )
+                    .await?;
+
                     // Idempotency guard: skip supply increment if charm already exists
                     let charm_exists = charms::Entity::find()
                         .filter(charms::Column::Txid.eq(&asset.txid))
@@ -244,19 +343,11 @@ pub async fn save_or_update_asset(
                     let amount_decimal = Decimal::from(amount);
                     let new_supply = old_supply + amount_decimal;
 
-                    let update_model = assets::ActiveModel {
-                        id: Set(existing.id),
-                        total_supply: Set(Some(new_supply)),
-                        updated_at: Set(Utc::now().into()),
-                        ..Default::default()
-                    };
-
-                    Assets::update(update_model)
-                        .exec(db)
-                        .await
-                        .map_err(DbError::SeaOrmError)?;
+                    supply_changes::update_supply(db, &existing, new_supply, &source).await?;
                 }
                 None => {
+                    let deployer =
+                        deployer_address(db, &asset.app_id, &asset.txid, &asset.network).await?;
                     let active_model = assets::ActiveModel {
                         id: NotSet,
                         app_id: Set(asset.app_id.clone()),
@@ -275,6 +366,10 @@ pub async fn save_or_update_asset(
                         symbol: Set(None),
                         description: Set(None),
                         image_url: Set(None),
+                        collection: Set(None),
+                        deploy_txid: Set(Some(asset.txid.clone())),
+                        deploy_block_height: Set(Some(asset.block_height as i32)),
+                        deployer_address: Set(deployer),
                         total_supply: Set(Some(Decimal::from(amount))),
                         decimals: Set(DEFAULT_DECIMALS as i16),
                         is_reference_nft: Set(false),
@@ -285,10 +380,7 @@ pub async fn save_or_update_asset(
                         updated_at: Set(Utc::now().into()),
                     };
 
-                    Assets::insert(active_model)
-                        .exec(db)
-                        .await
-                        .map_err(DbError::SeaOrmError)?;
+                    supply_changes::insert_asset(db, active_model, &source).await?;
                 }
             }
         }
@@ -319,10 +411,13 @@ async fn mark_nft_as_reference(
             ..Default::default()
         };
 
+        let txn = db.begin().await?;
         Assets::update(update_model)
-            .exec(db)
+            .exec(&txn)
             .await
             .map_err(DbError::SeaOrmError)?;
+        changefeed::record(&txn, &[asset_update(nft_app_id, network, None)]).await?;
+        txn.commit().await?;
     }
 
     Ok(())
@@ -335,7 +430,8 @@ pub async fn save_asset(db: &DatabaseConnection, asset: &Asset) -> Result<(), Db
 }
 
 /// Save multiple assets in a batch operation.
-/// For tokens, inherits metadata from parent NFT if it exists.
+/// For tokens, inherits metadata from parent NFT if it exists. Supply
+/// changes are recorded under `reason`.
 #[allow(clippy::type_complexity)]
 pub async fn save_batch(
     db: &DatabaseConnection,
@@ -350,6 +446,7 @@ pub async fn save_batch(
         String, // blockchain
         String, // network
     )>,
+    reason: SupplyChangeReason,
 ) -> Result<(), DbError> {
     if assets.is_empty() {
         return Ok(());
@@ -370,19 +467,33 @@ pub async fn save_batch(
     // Separate NFTs and tokens - NFTs must be inserted first so tokens can find their parent
     let (nfts, tokens): (Vec<_>, Vec<_>) = assets
         .into_iter()
-        .partition(|(_, _, _, _, _, _, asset_type, _, _)| asset_type == "nft");
+        .partition(|(_, _, _, _, _, _, asset_type, _, _)| {
+            AssetType::of(asset_type) == AssetType::Nft
+        });
+    // Dapps have no parent NFT to inherit from
+    let (dapps, tokens): (Vec<_>, Vec<_>) = tokens
+        .into_iter()
+        .partition(|(_, _, _, _, _, _, asset_type, _, _)| {
+            AssetType::of(asset_type) == AssetType::Dapp
+        });
 
     // Process NFTs first
     for (app_id, txid, vout_index, charm_id, block_height, data, asset_type, blockchain, network) in
         nfts
     {
         let metadata = AssetMetadata::from_nft_data(&data);
+        let deployer = deployer_address(db, &app_id, &txid, &network).await?;
+        let collection = metadata.collection_or_deployer(deployer.as_deref());
 
         let (c_pid, c_aname, c_fp) = extract_cardano_fields(&data);
+        let source = SupplySource::at(reason, &txid, block_height as i32);
         let active_model = assets::ActiveModel {
             id: NotSet,
-            app_id: Set(app_id),
-            txid: Set(txid),
+            app_id: Set(app_id.clone()),
+            deploy_txid: Set(Some(txid.clone())),
+            deploy_block_height: Set(Some(block_height as i32)),
+            deployer_address: Set(deployer),
+            txid: Set(txid.clone()),
             vout_index: Set(vout_index),
             charm_id: Set(charm_id),
             block_height: Set(block_height as i32),
@@ -390,11 +501,12 @@ pub async fn save_batch(
             data: Set(data),
             asset_type: Set(asset_type),
             blockchain: Set(blockchain),
-            network: Set(network),
+            network: Set(network.clone()),
             name: Set(metadata.name),
             symbol: Set(metadata.symbol),
             description: Set(metadata.description),
             image_url: Set(metadata.image_url),
+            collection: Set(collection),
             total_supply: Set(Some(Decimal::ZERO)),
             decimals: Set(metadata.decimals as i16),
             is_reference_nft: Set(false),
@@ -406,11 +518,80 @@ pub async fn save_batch(
         };
 
         // Insert NFT immediately so tokens can find it
-        if let Err(e) = Assets::insert(active_model).exec(db).await {
-            crate::utils::logging::log_warning(&format!(
-                "NFT insert error (may be duplicate): {}",
-                e
-            ));
+        if let Err(e) = supply_changes::insert_asset(db, active_model, &source).await {
+            let e = e.to_string();
+            throttled_log::global().warn("nft-insert", &e, || {
+                format!("NFT insert error (may be duplicate): {}", e)
+            });
+            // Already indexed: only an earlier block can move its origin
+            claim_earlier_deploy(db, &app_id, &network, &txid, block_height as i32).await?;
+        }
+    }
+
+    // Dapps: one row per app, supply simply accumulates
+    for (app_id, txid, vout_index, charm_id, block_height, data, asset_type, blockchain, network) in
+        dapps
+    {
+        let amount = data.get("supply").and_then(|v| v.as_i64()).unwrap_or(0);
+        let existing_dapp = Assets::find()
+            .filter(assets::Column::AppId.eq(&app_id))
+            .filter(assets::Column::Network.eq(&network))
+            .one(db)
+            .await
+            .map_err(DbError::SeaOrmError)?;
+
+        let source = SupplySource::at(reason, &txid, block_height as i32);
+
+        if existing_dapp.is_some() {
+            claim_earlier_deploy(db, &app_id, &network, &txid, block_height as i32).await?;
+        }
+
+        match existing_dapp {
+            Some(existing) if amount > 0 => {
+                let old_supply = existing.total_supply.unwrap_or(Decimal::ZERO);
+                let new_supply = old_supply + Decimal::from(amount);
+                supply_changes::update_supply(db, &existing, new_supply, &source).await?;
+            }
+            Some(_) => {}
+            None => {
+                let deployer = deployer_address(db, &app_id, &txid, &network).await?;
+                let active_model = assets::ActiveModel {
+                    id: NotSet,
+                    app_id: Set(app_id),
+                    deploy_txid: Set(Some(txid.clone())),
+                    deploy_block_height: Set(Some(block_height as i32)),
+                    deployer_address: Set(deployer),
+                    txid: Set(txid),
+                    vout_index: Set(vout_index),
+                    charm_id: Set(charm_id),
+                    block_height: Set(block_height as i32),
+                    date_created: Set(now.into()),
+                    data: Set(data),
+                    asset_type: Set(asset_type),
+                    blockchain: Set(blockchain),
+                    network: Set(network),
+                    name: Set(None),
+                    symbol: Set(None),
+                    description: Set(None),
+                    image_url: Set(None),
+                    collection: Set(None),
+                    total_supply: Set(Some(Decimal::from(amount))),
+                    decimals: Set(DEFAULT_DECIMALS as i16),
+                    is_reference_nft: Set(false),
+                    cardano_policy_id: Set(None),
+                    cardano_asset_name: Set(None),
+                    cardano_fingerprint: Set(None),
+                    created_at: Set(now.into()),
+                    updated_at: Set(now.into()),
+                };
+
+                if let Err(e) = supply_changes::insert_asset(db, active_model, &source).await {
+                    let e = e.to_string();
+                    throttled_log::global().warn("dapp-insert", &e, || {
+                        format!("Dapp insert error (may be duplicate): {}", e)
+                    });
+                }
+            }
         }
     }
 
@@ -438,10 +619,11 @@ pub async fn save_batch(
             .await
             .map_err(DbError::SeaOrmError)?;
 
-        // Extract identity hash for finding parent NFT
-        let hash = helpers::extract_hash_from_app_id(&app_id);
+        let source = SupplySource::at(reason, &txid, block_height as i32);
 
         if let Some(existing) = existing_token {
+            claim_earlier_deploy(db, &app_id, &network, &txid, block_height as i32).await?;
+
             // Asset.total_supply semantics: highest declared supply observed
             // in any spell for this token, NOT the running sum of every charm
             // output the indexer processes. Transfer txs carry the same
@@ -453,23 +635,14 @@ pub async fn save_batch(
             let new_supply = std::cmp::max(old_supply, mint_amount);
 
             if new_supply != old_supply {
-                let update_model = assets::ActiveModel {
-                    id: Set(existing.id),
-                    total_supply: Set(Some(new_supply)),
-                    updated_at: Set(now.into()),
-                    ..Default::default()
-                };
-                Assets::update(update_model)
-                    .exec(db)
-                    .await
-                    .map_err(DbError::SeaOrmError)?;
+                supply_changes::update_supply(db, &existing, new_supply, &source).await?;
             }
         } else {
             // Token doesn't exist - create new with inherited metadata from parent NFT
-            let parent_nft_pattern = format!("n/{}/%", hash);
+            let parent_nft_pattern = helpers::parent_nft_pattern(&app_id);
 
             let (name, symbol, description, decimals) = if let Ok(Some(parent_nft)) = Assets::find()
-                .filter(assets::Column::AssetType.eq("nft"))
+                .filter(assets::Column::AssetType.eq(AssetType::Nft.as_str()))
                 .filter(assets::Column::AppId.like(&parent_nft_pattern))
                 .filter(assets::Column::Network.eq(&network))
                 .one(db)
@@ -478,10 +651,10 @@ pub async fn save_batch(
                 // Mark parent NFT as reference if not already marked
                 if !parent_nft.is_reference_nft {
                     if let Err(e) = mark_nft_as_reference(db, &parent_nft.app_id, &network).await {
-                        crate::utils::logging::log_warning(&format!(
-                            "Failed to mark NFT as reference: {}",
-                            e
-                        ));
+                        let e = e.to_string();
+                        throttled_log::global().warn("nft-reference", &e, || {
+                            format!("Failed to mark NFT as reference: {}", e)
+                        });
                     }
                 }
                 (
@@ -496,9 +669,13 @@ pub async fn save_batch(
 
             let (c_pid, c_aname, c_fp) = extract_cardano_fields(&data);
             let img_url = data.get("image_url").and_then(|v| v.as_str()).map(|s| s.to_string());
+            let deployer = deployer_address(db, &app_id, &txid, &network).await?;
             let active_model = assets::ActiveModel {
                 id: NotSet,
                 app_id: Set(app_id),
+                deploy_txid: Set(Some(txid.clone())),
+                deploy_block_height: Set(Some(block_height as i32)),
+                deployer_address: Set(deployer),
                 txid: Set(txid),
                 vout_index: Set(vout_index),
                 charm_id: Set(charm_id),
@@ -512,6 +689,7 @@ pub async fn save_batch(
                 symbol: Set(symbol),
                 description: Set(description),
                 image_url: Set(img_url),
+                collection: Set(None),
                 total_supply: Set(Some(mint_amount)),
                 decimals: Set(decimals),
                 is_reference_nft: Set(false),
@@ -522,11 +700,11 @@ pub async fn save_batch(
                 updated_at: Set(now.into()),
             };
 
-            if let Err(e) = Assets::insert(active_model).exec(db).await {
-                crate::utils::logging::log_warning(&format!(
-                    "Token insert error (may be duplicate): {}",
-                    e
-                ));
+            if let Err(e) = supply_changes::insert_asset(db, active_model, &source).await {
+                let e = e.to_string();
+                throttled_log::global().warn("token-insert", &e, || {
+                    format!("Token insert error (may be duplicate): {}", e)
+                });
             }
         }
     }
@@ -562,10 +740,13 @@ pub async fn update_nft_metadata(
         }
         active.updated_at = Set(Utc::now().into());
 
+        let txn = db.begin().await?;
         Assets::update(active)
-            .exec(db)
+            .exec(&txn)
             .await
             .map_err(DbError::SeaOrmError)?;
+        changefeed::record(&txn, &[asset_update(app_id, network, None)]).await?;
+        txn.commit().await?;
     }
 
     Ok(())
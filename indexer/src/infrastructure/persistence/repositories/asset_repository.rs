@@ -5,20 +5,38 @@ use rust_decimal::Decimal;
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, NotSet, QueryFilter, Set};
 use serde_json::Value;
 
-use crate::domain::models::Asset;
+use crate::domain::models::{Asset, AssetType};
 use crate::infrastructure::persistence::entities::{assets, prelude::*};
 use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::asset::save;
+use crate::infrastructure::persistence::repositories::supply_changes_repository::{
+    self as supply_changes, SupplyChangeReason, SupplySource,
+};
 
 /// Repository for asset-related database operations
 #[derive(Debug, Clone)]
 pub struct AssetRepository {
     db: DatabaseConnection,
+    /// Reason recorded in `supply_changes` for supply this repository
+    /// raises; `Mint` unless a maintenance job says otherwise.
+    supply_reason: SupplyChangeReason,
 }
 
 impl AssetRepository {
     /// Create a new AssetRepository
     pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+        Self {
+            db,
+            supply_reason: SupplyChangeReason::Mint,
+        }
+    }
+
+    /// Same repository, recording supply it raises under `reason`.
+    pub fn with_supply_reason(self, reason: SupplyChangeReason) -> Self {
+        Self {
+            supply_reason: reason,
+            ..self
+        }
     }
 
     /// Save or update asset with supply accumulation
@@ -30,27 +48,34 @@ impl AssetRepository {
             .await
             .map_err(DbError::SeaOrmError)?;
 
+        let source = SupplySource::at(
+            self.supply_reason,
+            &asset.txid,
+            asset.block_height as i32,
+        );
         match existing_asset {
             Some(existing) => {
+                save::claim_earlier_deploy(
+                    &self.db,
+                    &existing.app_id,
+                    &existing.network,
+                    &asset.txid,
+                    asset.block_height as i32,
+                )
+                .await?;
+
                 // Asset exists, update supply
                 let old_supply = existing.total_supply.unwrap_or(Decimal::ZERO);
                 let amount_decimal = Decimal::from(amount);
                 let new_supply = old_supply + amount_decimal;
 
-                let update_model = assets::ActiveModel {
-                    id: Set(existing.id),
-                    total_supply: Set(Some(new_supply)),
-                    updated_at: Set(Utc::now().into()),
-                    ..Default::default()
-                };
-
-                Assets::update(update_model)
-                    .exec(&self.db)
-                    .await
-                    .map_err(DbError::SeaOrmError)?;
+                supply_changes::update_supply(&self.db, &existing, new_supply, &source).await?;
             }
             None => {
                 // Asset doesn't exist, create new one
+                let deployer =
+                    save::deployer_address(&self.db, &asset.app_id, &asset.txid, &asset.network)
+                        .await?;
                 let active_model = assets::ActiveModel {
                     id: NotSet,
                     app_id: Set(asset.app_id.clone()),
@@ -75,14 +100,15 @@ impl AssetRepository {
                     cardano_policy_id: Set(None),
                     cardano_asset_name: Set(None),
                     cardano_fingerprint: Set(None),
+                    collection: Set(None),
+                    deploy_txid: Set(Some(asset.txid.clone())),
+                    deploy_block_height: Set(Some(asset.block_height as i32)),
+                    deployer_address: Set(deployer),
                     created_at: Set(Utc::now().into()),
                     updated_at: Set(Utc::now().into()),
                 };
 
-                Assets::insert(active_model)
-                    .exec(&self.db)
-                    .await
-                    .map_err(DbError::SeaOrmError)?;
+                supply_changes::insert_asset(&self.db, active_model, &source).await?;
             }
         }
 
@@ -106,8 +132,12 @@ impl AssetRepository {
             String, // network
         )>,
     ) -> Result<(), DbError> {
-        crate::infrastructure::persistence::repositories::asset::save::save_batch(&self.db, assets)
-            .await
+        crate::infrastructure::persistence::repositories::asset::save::save_batch(
+            &self.db,
+            assets,
+            self.supply_reason,
+        )
+        .await
     }
 
     /// Update supply when charms are marked as spent
@@ -115,14 +145,15 @@ impl AssetRepository {
         &self,
         app_id: &str,
         amount: i64,
-        asset_type: &str,
+        asset_type: AssetType,
     ) -> Result<(), DbError> {
-        let hash = self.extract_hash_from_app_id(app_id);
-
-        let target_app_id = if asset_type == "token" {
-            let parent_nft_pattern = format!("n/{}/%", hash);
+        let target_app_id = if asset_type == AssetType::Token {
+            let parent_nft_pattern =
+                crate::infrastructure::persistence::repositories::asset::helpers::parent_nft_pattern(
+                    app_id,
+                );
             let parent_nft = Assets::find()
-                .filter(assets::Column::AssetType.eq("nft"))
+                .filter(assets::Column::AssetType.eq(AssetType::Nft.as_str()))
                 .filter(assets::Column::AppId.like(&parent_nft_pattern))
                 .one(&self.db)
                 .await
@@ -148,38 +179,15 @@ impl AssetRepository {
             let amount_decimal = Decimal::from(amount);
             let new_supply = (old_supply - amount_decimal).max(Decimal::ZERO);
 
-            let update_model = assets::ActiveModel {
-                id: Set(asset_model.id),
-                total_supply: Set(Some(new_supply)),
-                updated_at: Set(Utc::now().into()),
-                ..Default::default()
-            };
-
-            Assets::update(update_model)
-                .exec(&self.db)
-                .await
-                .map_err(DbError::SeaOrmError)?;
+            supply_changes::update_supply(
+                &self.db,
+                &asset_model,
+                new_supply,
+                &SupplySource::new(SupplyChangeReason::Spend),
+            )
+            .await?;
         }
 
         Ok(())
     }
-
-    /// Extract hash from app_id (removes t/ or n/ prefix and returns only the 64-char hash)
-    fn extract_hash_from_app_id(&self, app_id: &str) -> String {
-        let without_prefix = if let Some(stripped) = app_id.strip_prefix("t/") {
-            stripped
-        } else if let Some(stripped) = app_id.strip_prefix("n/") {
-            stripped
-        } else {
-            app_id
-        };
-
-        // Hash is 64 characters
-        if without_prefix.len() >= 64 {
-            without_prefix[..64].to_string()
-        } else {
-            without_prefix.to_string()
-        }
-    }
-
 }
@@ -0,0 +1,227 @@
+//! Repository for `backfill_progress`: one row per registered backfill with
+//! its status and cursor. The runner claims the row, moves the cursor inside
+//! each batch's transaction and closes it; operators queue a run by setting
+//! it `pending` (the API's `POST /admin/backfills/{name}` or `request`).
+
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, QueryResult, Statement,
+};
+
+use crate::infrastructure::persistence::error::DbError;
+
+/// A backfill's row as a run sees it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillProgress {
+    pub name: String,
+    pub status: String,
+    /// Highest key whose batch has committed; `None` before the first.
+    pub last_key: Option<i64>,
+    pub rows_updated: i64,
+    pub batches: i32,
+}
+
+const COLUMNS: &str = "name, status, last_key, rows_updated, batches";
+
+/// Claim update shared by `claim` and `claim_pending`: start over when a
+/// restart was asked for (and the backfill is idempotent), else keep the
+/// cursor. `$1` is the stale-after window, `$2` a restart asked for by the
+/// caller.
+const CLAIM_SET: &str = "
+    SET status = 'running',
+        last_key = CASE WHEN ($2 OR b.restart) AND b.idempotent THEN NULL ELSE b.last_key END,
+        rows_updated = CASE WHEN ($2 OR b.restart) AND b.idempotent THEN 0 ELSE b.rows_updated END,
+        batches = CASE WHEN ($2 OR b.restart) AND b.idempotent THEN 0 ELSE b.batches END,
+        started_at = CASE WHEN ($2 OR b.restart) AND b.idempotent OR b.started_at IS NULL
+                          THEN NOW() ELSE b.started_at END,
+        restart = FALSE,
+        heartbeat_at = NOW(),
+        finished_at = NULL,
+        error = NULL";
+
+/// Rows a claim may take: anything not running or finished, a run whose
+/// heartbeat went stale, and a finished idempotent one when restarting.
+const CLAIMABLE: &str = "
+    (b.status IN ('idle', 'pending', 'paused', 'failed')
+     OR (b.status = 'running' AND b.heartbeat_at < NOW() - make_interval(secs => $1))
+     OR (b.status = 'done' AND ($2 OR b.restart) AND b.idempotent))";
+
+#[derive(Clone, Debug)]
+pub struct BackfillProgressRepository {
+    conn: DatabaseConnection,
+}
+
+impl BackfillProgressRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Make `name` visible to operators (`idle`) if it has no row yet, and
+    /// record whether it may be restarted once done.
+    pub async fn register(&self, name: &str, idempotent: bool) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO backfill_progress (name, idempotent) VALUES ($1, $2) \
+                 ON CONFLICT (name) DO UPDATE SET idempotent = EXCLUDED.idempotent",
+                [name.into(), idempotent.into()],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Queue `name` for the live indexer. A finished backfill only runs
+    /// again with `restart`, and only if idempotent; a running one is left
+    /// alone. Returns whether the request was recorded.
+    pub async fn request(
+        &self,
+        name: &str,
+        requested_by: &str,
+        restart: bool,
+    ) -> Result<bool, DbError> {
+        let res = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE backfill_progress \
+                    SET status = 'pending', restart = $3, requested_by = $2, \
+                        requested_at = NOW(), error = NULL \
+                  WHERE name = $1 \
+                    AND (status IN ('idle', 'paused', 'failed') \
+                         OR (status = 'done' AND $3 AND idempotent))",
+                [name.into(), requested_by.into(), restart.into()],
+            ))
+            .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    /// Claim `name` for a run started here (the CLI); `register` it first.
+    /// `None` when another run holds it or it is done and not restarted;
+    /// `get` tells which.
+    pub async fn claim(
+        &self,
+        name: &str,
+        stale_secs: f64,
+        restart: bool,
+    ) -> Result<Option<BackfillProgress>, DbError> {
+        let sql = format!(
+            "UPDATE backfill_progress b {CLAIM_SET} \
+              WHERE b.name = $3 AND {CLAIMABLE} \
+          RETURNING {COLUMNS}"
+        );
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [stale_secs.into(), restart.into(), name.into()],
+            ))
+            .await?;
+        row.as_ref().map(progress).transpose()
+    }
+
+    /// Claim the oldest queued backfill, or a run whose heartbeat went
+    /// stale, for the live worker.
+    pub async fn claim_pending(
+        &self,
+        stale_secs: f64,
+    ) -> Result<Option<BackfillProgress>, DbError> {
+        let sql = format!(
+            "UPDATE backfill_progress b {CLAIM_SET} \
+              WHERE b.name = (SELECT q.name FROM backfill_progress q \
+                               WHERE q.status = 'pending' \
+                                  OR (q.status = 'running' \
+                                      AND q.heartbeat_at < NOW() - make_interval(secs => $1)) \
+                               ORDER BY q.requested_at NULLS LAST, q.name \
+                               LIMIT 1 \
+                                 FOR UPDATE SKIP LOCKED) \
+          RETURNING {COLUMNS}"
+        );
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [stale_secs.into(), false.into()],
+            ))
+            .await?;
+        row.as_ref().map(progress).transpose()
+    }
+
+    /// Move the cursor past a batch. Runs in the batch's transaction, so
+    /// the rows and the cursor commit together; doubles as the heartbeat.
+    pub async fn advance(
+        &self,
+        txn: &DatabaseTransaction,
+        name: &str,
+        last_key: i64,
+        rows: u64,
+    ) -> Result<(), DbError> {
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE backfill_progress \
+                SET last_key = $2, rows_updated = rows_updated + $3, batches = batches + 1, \
+                    heartbeat_at = NOW() \
+              WHERE name = $1",
+            [name.into(), last_key.into(), (rows as i64).into()],
+        ))
+        .await?;
+        Ok(())
+    }
+
+    /// Every key is covered.
+    pub async fn finish(&self, name: &str) -> Result<(), DbError> {
+        self.close(name, "done", None).await
+    }
+
+    /// Stopped between batches; the next claim resumes from the cursor.
+    pub async fn pause(&self, name: &str) -> Result<(), DbError> {
+        self.close(name, "paused", None).await
+    }
+
+    /// Stopped by a shutdown of the live indexer, which claims it again on
+    /// its next start.
+    pub async fn requeue(&self, name: &str) -> Result<(), DbError> {
+        self.close(name, "pending", None).await
+    }
+
+    /// A batch failed and was rolled back; the cursor stays before it.
+    pub async fn fail(&self, name: &str, error: &str) -> Result<(), DbError> {
+        self.close(name, "failed", Some(error)).await
+    }
+
+    async fn close(&self, name: &str, status: &str, error: Option<&str>) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE backfill_progress \
+                    SET status = $2, error = $3, heartbeat_at = NOW(), \
+                        finished_at = CASE WHEN $2 IN ('done', 'failed') THEN NOW() END \
+                  WHERE name = $1",
+                [name.into(), status.into(), error.map(str::to_string).into()],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, name: &str) -> Result<Option<BackfillProgress>, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!("SELECT {COLUMNS} FROM backfill_progress WHERE name = $1"),
+                [name.into()],
+            ))
+            .await?;
+        row.as_ref().map(progress).transpose()
+    }
+}
+
+fn progress(r: &QueryResult) -> Result<BackfillProgress, DbError> {
+    Ok(BackfillProgress {
+        name: r.try_get("", "name")?,
+        status: r.try_get("", "status")?,
+        last_key: r.try_get("", "last_key")?,
+        rows_updated: r.try_get("", "rows_updated")?,
+        batches: r.try_get("", "batches")?,
+    })
+}
@@ -1,16 +1,45 @@
 //! Repository for block_status operations
 //! Unified block tracking for indexer control
 
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, Statement,
 };
 use std::fmt;
+use std::time::Duration;
 
 use crate::config::NetworkId;
 use crate::infrastructure::persistence::entities::block_status;
 use crate::infrastructure::persistence::error::DbError;
 
+/// Where `process_block` spent its time, in milliseconds. The phases are
+/// consecutive, so they add up to `processing_ms` give or take rounding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTimings {
+    pub processing_ms: i32,
+    /// Fetching the block and checking it for a reorg.
+    pub rpc_ms: i32,
+    /// Detecting its charms.
+    pub parse_ms: i32,
+    /// Persisting everything else.
+    pub db_ms: i32,
+}
+
+impl BlockTimings {
+    /// From the elapsed times at the end of the fetch and detection phases
+    /// and at the end of the block.
+    pub fn from_marks(fetched: Duration, parsed: Duration, total: Duration) -> Self {
+        let ms = |d: Duration| d.as_millis().min(i32::MAX as u128) as i32;
+        Self {
+            processing_ms: ms(total),
+            rpc_ms: ms(fetched),
+            parse_ms: ms(parsed.saturating_sub(fetched)),
+            db_ms: ms(total.saturating_sub(parsed)),
+        }
+    }
+}
+
 /// Repository for block_status operations
 #[derive(Clone)]
 pub struct BlockStatusRepository {
@@ -51,13 +80,15 @@ impl BlockStatusRepository {
         Ok(result.map(|b| b.block_height))
     }
 
-    /// Mark a block as downloaded
+    /// Mark a block as downloaded. `block_time` is the header time; `None`
+    /// keeps whatever is stored.
     pub async fn mark_downloaded(
         &self,
         block_height: i32,
         block_hash: Option<&str>,
         previous_block_hash: Option<&str>,
         tx_count: i32,
+        block_time: Option<NaiveDateTime>,
         network_id: &NetworkId,
     ) -> Result<(), DbError> {
         let now = Utc::now();
@@ -72,9 +103,13 @@ impl BlockStatusRepository {
         if let Some(model) = existing {
             let mut update_model: block_status::ActiveModel = model.into();
             update_model.downloaded = Set(true);
+            update_model.skipped_pruned = Set(false);
             update_model.block_hash = Set(block_hash.map(|s| s.to_string()));
             update_model.previous_block_hash = Set(previous_block_hash.map(|s| s.to_string()));
             update_model.tx_count = Set(Some(tx_count));
+            if block_time.is_some() {
+                update_model.block_time = Set(block_time);
+            }
             update_model.downloaded_at = Set(Some(now.into()));
             update_model.updated_at = Set(now.into());
             update_model.update(&self.conn).await?;
@@ -90,6 +125,14 @@ impl BlockStatusRepository {
                 previous_block_hash: Set(previous_block_hash.map(|s| s.to_string())),
                 tx_count: Set(Some(tx_count)),
                 charm_count: Set(None),
+                skipped_pruned: Set(false),
+                processing_ms: Set(None),
+                rpc_ms: Set(None),
+                parse_ms: Set(None),
+                db_ms: Set(None),
+                block_time: Set(block_time),
+                failed_attempts: Set(0),
+                last_error: Set(None),
                 downloaded_at: Set(Some(now.into())),
                 processed_at: Set(None),
                 created_at: Set(now.into()),
@@ -101,7 +144,10 @@ impl BlockStatusRepository {
         Ok(())
     }
 
-    /// Get the stored hash for a height/network (returns None if not downloaded yet).
+    /// Get the stored hash for a height/network (returns None if not downloaded
+    /// yet, skipped as pruned, or only holding a failure record: the
+    /// placeholder hash of such a row must not read as a chain mismatch to the
+    /// reorg guard).
     pub async fn get_block_hash(
         &self,
         block_height: i32,
@@ -111,11 +157,28 @@ impl BlockStatusRepository {
             .filter(block_status::Column::BlockHeight.eq(block_height))
             .filter(block_status::Column::Network.eq(network_id.name.clone()))
             .filter(block_status::Column::Blockchain.eq(network_id.blockchain_type()))
+            .filter(block_status::Column::SkippedPruned.eq(false))
+            .filter(block_status::Column::BlockHash.ne("unknown"))
             .one(&self.conn)
             .await?;
         Ok(row.and_then(|r| r.block_hash))
     }
 
+    /// Stored header time of the block at `block_height`, if recorded.
+    pub async fn get_block_time(
+        &self,
+        block_height: i32,
+        network_id: &NetworkId,
+    ) -> Result<Option<NaiveDateTime>, DbError> {
+        let row = block_status::Entity::find()
+            .filter(block_status::Column::BlockHeight.eq(block_height))
+            .filter(block_status::Column::Network.eq(network_id.name.clone()))
+            .filter(block_status::Column::Blockchain.eq(network_id.blockchain_type()))
+            .one(&self.conn)
+            .await?;
+        Ok(row.and_then(|r| r.block_time))
+    }
+
     /// Delete all block_status rows above `height` for `network_id`.
     /// Used by the reorg recovery path.
     pub async fn delete_above(
@@ -132,7 +195,8 @@ impl BlockStatusRepository {
         Ok(result.rows_affected)
     }
 
-    /// Mark a block as processed
+    /// Mark a block as processed. Clears any failure recorded by the
+    /// reindex job.
     pub async fn mark_processed(
         &self,
         block_height: i32,
@@ -153,6 +217,8 @@ impl BlockStatusRepository {
             update_model.processed = Set(true);
             update_model.charm_count = Set(Some(charm_count));
             update_model.processed_at = Set(Some(now.into()));
+            update_model.failed_attempts = Set(0);
+            update_model.last_error = Set(None);
             update_model.updated_at = Set(now.into());
             update_model.update(&self.conn).await?;
         } else {
@@ -165,6 +231,33 @@ impl BlockStatusRepository {
         Ok(())
     }
 
+    /// Store a processed block's timings: one UPDATE on its existing row.
+    pub async fn record_timings(
+        &self,
+        block_height: i32,
+        timings: &BlockTimings,
+        network_id: &NetworkId,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE block_status \
+                    SET processing_ms = $4, rpc_ms = $5, parse_ms = $6, db_ms = $7 \
+                  WHERE block_height = $1 AND network = $2 AND blockchain = $3",
+                [
+                    block_height.into(),
+                    network_id.name.clone().into(),
+                    network_id.blockchain_type().into(),
+                    timings.processing_ms.into(),
+                    timings.rpc_ms.into(),
+                    timings.parse_ms.into(),
+                    timings.db_ms.into(),
+                ],
+            ))
+            .await?;
+        Ok(())
+    }
+
     /// Mark a single block as confirmed.
     pub async fn mark_confirmed(
         &self,
@@ -211,11 +304,142 @@ impl BlockStatusRepository {
             .filter(block_status::Column::Network.eq(network_id.name.clone()))
             .filter(block_status::Column::Blockchain.eq(network_id.blockchain_type()))
             .filter(block_status::Column::Confirmed.eq(false))
+            .filter(block_status::Column::SkippedPruned.eq(false))
+            .order_by_asc(block_status::Column::BlockHeight)
+            .all(&self.conn)
+            .await?;
+
+        Ok(results.into_iter().map(|b| b.block_height).collect())
+    }
+
+    /// Record that the node did not have the block at `block_height`. The row
+    /// stays unprocessed so the retry pass can fill it in once a provider
+    /// serves the block; `mark_downloaded` clears the flag.
+    pub async fn mark_skipped_pruned(
+        &self,
+        block_height: i32,
+        network_id: &NetworkId,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO block_status (block_height, network, blockchain, skipped_pruned) \
+                 VALUES ($1, $2, $3, TRUE) \
+                 ON CONFLICT (block_height, network, blockchain) DO UPDATE SET \
+                     skipped_pruned = TRUE, downloaded = FALSE, processed = FALSE, \
+                     updated_at = NOW()",
+                [
+                    block_height.into(),
+                    network_id.name.clone().into(),
+                    network_id.blockchain_type().into(),
+                ],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Oldest `limit` heights skipped as pruned/missing.
+    pub async fn get_skipped_pruned(
+        &self,
+        network_id: &NetworkId,
+        limit: u64,
+    ) -> Result<Vec<i32>, DbError> {
+        let results = block_status::Entity::find()
+            .filter(block_status::Column::Network.eq(network_id.name.clone()))
+            .filter(block_status::Column::Blockchain.eq(network_id.blockchain_type()))
+            .filter(block_status::Column::SkippedPruned.eq(true))
             .order_by_asc(block_status::Column::BlockHeight)
+            .limit(limit)
             .all(&self.conn)
             .await?;
 
         Ok(results.into_iter().map(|b| b.block_height).collect())
     }
 
+    /// Number of heights currently skipped as pruned/missing.
+    pub async fn count_skipped_pruned(&self, network_id: &NetworkId) -> Result<u64, DbError> {
+        let count = block_status::Entity::find()
+            .filter(block_status::Column::Network.eq(network_id.name.clone()))
+            .filter(block_status::Column::Blockchain.eq(network_id.blockchain_type()))
+            .filter(block_status::Column::SkippedPruned.eq(true))
+            .count(&self.conn)
+            .await?;
+        Ok(count)
+    }
+
+    /// Record a failed attempt at `block_height` with its `error`. Returns
+    /// the block's failed attempts so far. A height without a row gets one
+    /// holding only the failure.
+    pub async fn record_failure(
+        &self,
+        block_height: i32,
+        error: &str,
+        network_id: &NetworkId,
+    ) -> Result<i32, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO block_status (block_height, network, blockchain, failed_attempts, last_error) \
+                 VALUES ($1, $2, $3, 1, $4) \
+                 ON CONFLICT (block_height, network, blockchain) DO UPDATE SET \
+                     failed_attempts = block_status.failed_attempts + 1, \
+                     last_error = EXCLUDED.last_error, updated_at = NOW() \
+                 RETURNING failed_attempts",
+                [
+                    block_height.into(),
+                    network_id.name.clone().into(),
+                    network_id.blockchain_type().into(),
+                    error.into(),
+                ],
+            ))
+            .await?
+            .ok_or_else(|| DbError::Other("record_failure returned no row".to_string()))?;
+        Ok(row.try_get("", "failed_attempts")?)
+    }
+
+    /// Heights in `from..=to` that failed at least `max_attempts` times.
+    pub async fn get_sidelined(
+        &self,
+        network_id: &NetworkId,
+        from: i32,
+        to: i32,
+        max_attempts: i32,
+    ) -> Result<Vec<i32>, DbError> {
+        let results = block_status::Entity::find()
+            .filter(block_status::Column::Network.eq(network_id.name.clone()))
+            .filter(block_status::Column::Blockchain.eq(network_id.blockchain_type()))
+            .filter(block_status::Column::BlockHeight.between(from, to))
+            .filter(block_status::Column::FailedAttempts.gte(max_attempts))
+            .order_by_asc(block_status::Column::BlockHeight)
+            .all(&self.conn)
+            .await?;
+
+        Ok(results.into_iter().map(|b| b.block_height).collect())
+    }
+
+    /// Forget the failures recorded in `from..=to`. Returns the rows reset.
+    pub async fn reset_failures(
+        &self,
+        network_id: &NetworkId,
+        from: i32,
+        to: i32,
+    ) -> Result<u64, DbError> {
+        let res = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE block_status SET failed_attempts = 0, last_error = NULL, updated_at = NOW() \
+                 WHERE network = $1 AND blockchain = $2 AND block_height BETWEEN $3 AND $4 \
+                   AND failed_attempts > 0",
+                [
+                    network_id.name.clone().into(),
+                    network_id.blockchain_type().into(),
+                    from.into(),
+                    to.into(),
+                ],
+            ))
+            .await?;
+        Ok(res.rows_affected())
+    }
 }
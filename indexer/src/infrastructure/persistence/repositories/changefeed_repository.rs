@@ -0,0 +1,184 @@
+//! Append-only `changefeed` for downstream ETL.
+//! The charm, asset, DEX order and holder repositories call `record` with
+//! the keys a write inserted or updated, inside the write's transaction, so
+//! the feed neither misses nor invents a change. One multi-row INSERT per
+//! call: a batched write costs one extra statement whatever its size.
+//!
+//! Unconfirmed charms and DEX orders enter the feed when a block confirms
+//! them: the mempool consolidator's promotion appends through
+//! `updates_from` in the same statement.
+//!
+//! Not fed: deletes (a holder row removed at zero balance shows as the
+//! update that zeroed it) and the bulk rewrites of maintenance jobs
+//! (metadata refresh, backfills, `verify --fix`), after which consumers
+//! should re-sync the affected tables.
+
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+
+/// Table an entry is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeEntity {
+    /// Key `txid:vout:app_id`.
+    Charm,
+    /// Key `app_id`.
+    Asset,
+    /// Key `order_id`.
+    DexOrder,
+    /// Key `app_id:address`.
+    StatsHolder,
+}
+
+impl ChangeEntity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeEntity::Charm => "charm",
+            ChangeEntity::Asset => "asset",
+            ChangeEntity::DexOrder => "dex_order",
+            ChangeEntity::StatsHolder => "stats_holder",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeOp {
+    Insert,
+    Update,
+}
+
+impl ChangeOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeOp::Insert => "insert",
+            ChangeOp::Update => "update",
+        }
+    }
+
+    /// `Insert` for rows an upsert's `RETURNING (xmax = 0)` reports as new.
+    pub fn upserted(inserted: bool) -> Self {
+        if inserted {
+            ChangeOp::Insert
+        } else {
+            ChangeOp::Update
+        }
+    }
+}
+
+/// One feed entry, before it has an id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub entity: ChangeEntity,
+    pub key: String,
+    pub operation: ChangeOp,
+    /// Block of the write, when a block caused it.
+    pub block_height: Option<i32>,
+    pub network: String,
+}
+
+impl Change {
+    pub fn new(
+        entity: ChangeEntity,
+        key: impl Into<String>,
+        operation: ChangeOp,
+        block_height: Option<i32>,
+        network: &str,
+    ) -> Self {
+        Self {
+            entity,
+            key: key.into(),
+            operation,
+            block_height,
+            network: network.to_string(),
+        }
+    }
+
+    pub fn charm(
+        txid: &str,
+        vout: i32,
+        app_id: &str,
+        operation: ChangeOp,
+        block_height: Option<i32>,
+        network: &str,
+    ) -> Self {
+        Self::new(
+            ChangeEntity::Charm,
+            format!("{}:{}:{}", txid, vout, app_id),
+            operation,
+            block_height,
+            network,
+        )
+    }
+
+    pub fn holder(
+        app_id: &str,
+        address: &str,
+        operation: ChangeOp,
+        block_height: i32,
+        network: &str,
+    ) -> Self {
+        Self::new(
+            ChangeEntity::StatsHolder,
+            format!("{}:{}", app_id, address),
+            operation,
+            Some(block_height),
+            network,
+        )
+    }
+}
+
+/// `INSERT ... SELECT` appending an update of `entity` for every row of the
+/// CTE `cte`, keyed by the SQL expression `key_sql` over its columns. For
+/// writes that are one UPDATE: `WITH cte AS (UPDATE ... RETURNING ...)`
+/// followed by this records them in the same statement.
+pub fn updates_from(
+    entity: ChangeEntity,
+    cte: &str,
+    key_sql: &str,
+    block_height: i32,
+    network: &str,
+) -> String {
+    format!(
+        "INSERT INTO changefeed (entity_type, entity_key, operation, block_height, network) \
+         SELECT '{}', {}, '{}', {}, '{}' FROM {}",
+        entity.as_str(),
+        key_sql,
+        ChangeOp::Update.as_str(),
+        block_height,
+        network.replace('\'', "''"),
+        cte
+    )
+}
+
+/// Append `changes` on `conn` (the write's transaction) in one statement.
+/// Ids follow slice order.
+pub async fn record<C: ConnectionTrait>(conn: &C, changes: &[Change]) -> Result<(), DbError> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let values = changes
+        .iter()
+        .map(|c| {
+            format!(
+                "('{}', '{}', '{}', {}, '{}')",
+                c.entity.as_str(),
+                c.key.replace('\'', "''"),
+                c.operation.as_str(),
+                c.block_height
+                    .map_or_else(|| "NULL".to_string(), |h| h.to_string()),
+                c.network.replace('\'', "''"),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    conn.execute(Statement::from_string(
+        DbBackend::Postgres,
+        format!(
+            "INSERT INTO changefeed (entity_type, entity_key, operation, block_height, network) \
+             VALUES {}",
+            values
+        ),
+    ))
+    .await?;
+    Ok(())
+}
@@ -1,21 +1,46 @@
 use sea_orm::{
     ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, QueryFilter,
-    Statement,
+    Statement, TransactionTrait,
 };
 
+use crate::domain::models::WriteStamp;
+use crate::domain::services::address_extractor::AddressExtractor;
 use crate::infrastructure::persistence::entities::charms;
 use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::changefeed_repository::{
+    self as changefeed, Change, ChangeOp,
+};
+
+/// Charms written by one (parser revision, indexer version) pair; both are
+/// `None` for rows written before stamping existed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevisionCount {
+    pub parser_revision: Option<i32>,
+    pub indexer_version: Option<String>,
+    pub charms: u64,
+}
 
 /// Repository for charm operations
 #[derive(Clone, Debug)]
 pub struct CharmRepository {
     conn: DatabaseConnection,
+    /// Stamped on every charm this repository writes; live unless a
+    /// reindex run says otherwise.
+    stamp: WriteStamp,
 }
 
 impl CharmRepository {
     /// Create a new CharmRepository
     pub fn new(conn: DatabaseConnection) -> Self {
-        Self { conn }
+        Self {
+            conn,
+            stamp: WriteStamp::live(),
+        }
+    }
+
+    /// Same repository, stamping the charms it writes with `stamp`.
+    pub fn with_stamp(self, stamp: WriteStamp) -> Self {
+        Self { stamp, ..self }
     }
 
     /// Optimize the session for high-throughput writer by toggling synchronous_commit
@@ -62,6 +87,119 @@ impl CharmRepository {
         Ok(heights)
     }
 
+    /// Charm counts per (parser revision, indexer version), oldest revision
+    /// first, unstamped rows leading. `network` narrows to one network.
+    pub async fn count_by_parser_revision(
+        &self,
+        network: Option<&str>,
+    ) -> Result<Vec<RevisionCount>, DbError> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT parser_revision, indexer_version, COUNT(*) AS n FROM charms \
+                  WHERE ($1::text IS NULL OR network = $1) \
+                  GROUP BY parser_revision, indexer_version \
+                  ORDER BY parser_revision NULLS FIRST, indexer_version NULLS FIRST",
+                [network.into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(RevisionCount {
+                    parser_revision: row.try_get("", "parser_revision")?,
+                    indexer_version: row.try_get("", "indexer_version")?,
+                    charms: row.try_get::<i64>("", "n")? as u64,
+                })
+            })
+            .collect::<Result<Vec<_>, sea_orm::DbErr>>()
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Heights in `from..=to` holding a charm, live or archived, written by
+    /// a parser revision below `revision` (or before stamping existed),
+    /// ascending.
+    pub async fn heights_below_parser_revision(
+        &self,
+        network: &str,
+        from: u64,
+        to: u64,
+        revision: i32,
+    ) -> Result<Vec<u64>, DbError> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT block_height FROM charms \
+                  WHERE network = $1 AND block_height BETWEEN $2 AND $3 \
+                    AND (parser_revision IS NULL OR parser_revision < $4) \
+                  UNION \
+                 SELECT block_height FROM charms_archive \
+                  WHERE network = $1 AND block_height BETWEEN $2 AND $3 \
+                    AND (parser_revision IS NULL OR parser_revision < $4) \
+                  ORDER BY block_height",
+                [
+                    network.into(),
+                    (from as i64).into(),
+                    (to as i64).into(),
+                    revision.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        rows.iter()
+            .map(|row| row.try_get::<i32>("", "block_height").map(|h| h as u64))
+            .collect::<Result<Vec<_>, sea_orm::DbErr>>()
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Charms of `network` stored at heights `from..=to`, placeholders
+    /// excluded.
+    pub async fn count_in_range(&self, network: &str, from: u64, to: u64) -> Result<u64, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT COUNT(*) AS n FROM charms \
+                  WHERE network = $1 AND block_height BETWEEN $2 AND $3 AND NOT is_placeholder",
+                [network.into(), (from as i64).into(), (to as i64).into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        Ok(row
+            .and_then(|r| r.try_get::<i64>("", "n").ok())
+            .unwrap_or(0) as u64)
+    }
+
+    /// Move the archived charms of `network` between heights `from` and
+    /// `to` (inclusive) back into `charms`, so a reindex of that range
+    /// rewrites them in place instead of re-inserting them as unspent.
+    /// Returns the rows restored.
+    pub async fn restore_archived(
+        &self,
+        network: &str,
+        from: u64,
+        to: u64,
+    ) -> Result<u64, DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "WITH restored AS ( \
+                     DELETE FROM charms_archive \
+                      WHERE network = $1 AND block_height BETWEEN $2 AND $3 \
+                  RETURNING *) \
+                 INSERT INTO charms SELECT * FROM restored \
+                 ON CONFLICT (txid, vout, app_id, network) DO NOTHING",
+                [network.into(), (from as i64).into(), (to as i64).into()],
+            ))
+            .await
+            .map(|res| res.rows_affected())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
     /// Save multiple charms in a batch.
     /// Tuple shape mirrors the SQL row layout — see `block/batch.rs::CharmBatchItem`
     /// for the named-field representation used by the application layer.
@@ -69,45 +207,85 @@ impl CharmRepository {
     pub async fn save_batch(
         &self,
         charms: Vec<(
-            String,            // txid
-            i32,               // vout
-            u64,               // block_height
-            serde_json::Value, // data
-            String,            // asset_type
-            String,            // blockchain
-            String,            // network
-            Option<String>,    // address
-            String,            // app_id
-            i64,               // amount
-            Option<String>,    // tags
+            String,                        // txid
+            i32,                           // vout
+            u64,                           // block_height
+            serde_json::Value,             // data
+            String,                        // asset_type
+            String,                        // blockchain
+            String,                        // network
+            Option<String>,                // address
+            String,                        // app_id
+            i64,                           // amount
+            Option<String>,                // tags
+            Option<String>,                // block_hash
+            Option<i32>,                   // tx_ordinal
+            Option<String>,                // operation
+            Option<chrono::NaiveDateTime>, // block_time
         )>,
-    ) -> Result<Vec<(String, i32)>, DbError> {
+    ) -> Result<Vec<(String, i32, String)>, DbError> {
         if charms.is_empty() {
             return Ok(vec![]);
         }
 
         let now = chrono::Utc::now().naive_utc();
         let now_str = now.format("%Y-%m-%d %H:%M:%S%.6f").to_string();
+        let stamp_sql = format!(
+            "'{}', {}, {}",
+            self.stamp.indexer_version.replace('\'', "''"),
+            self.stamp.parser_revision,
+            match &self.stamp.reindex_run_id {
+                Some(id) => format!("'{}'", id.replace('\'', "''")),
+                None => "NULL".to_string(),
+            }
+        );
 
-        // Build raw SQL with ON CONFLICT DO NOTHING so that duplicates are
-        // silently skipped while the rest of the batch is still inserted.
-        // Returns the (txid, vout) pairs that were actually inserted so callers
-        // can update stats_holders only for truly new charms (not mempool-promoted ones).
+        // Build raw SQL that skips duplicates while the rest of the batch is
+        // still inserted. The only columns refreshed on conflict are `tags`,
+        // `operation` and `block_time`, so a mempool-promoted charm ends up
+        // with the block path's classification and block time.
+        // `date_created` is always this write's wall-clock time.
+        // A row that is rewritten (new tags, operation or block time, or any
+        // write by a reindex run)
+        // takes this write's stamp.
+        // Returns the (txid, vout, app_id) keys that were actually inserted
+        // (`xmax = 0`) so callers can update stats_holders only for truly new
+        // charms (not mempool-promoted ones). The app_id is part of the key
+        // because one output can carry several tokens. Every returned row,
+        // inserted or rewritten, goes to the changefeed in the same
+        // transaction.
         let mut values_parts: Vec<String> = Vec::with_capacity(charms.len());
 
-        for (txid, vout, block_height, data, asset_type, blockchain, network, address, app_id, amount, tags) in &charms {
+        for (txid, vout, block_height, data, asset_type, blockchain, network, address, app_id, amount, tags, block_hash, tx_ordinal, operation, block_time) in &charms {
             let addr_sql = match address {
-                Some(a) => format!("'{}'", a.replace('\'', "''")),
+                Some(a) => format!("'{}'", AddressExtractor::canonical(a).replace('\'', "''")),
                 None => "NULL".to_string(),
             };
             let tags_sql = match tags {
                 Some(t) => format!("'{}'", t.replace('\'', "''")),
                 None => "NULL".to_string(),
             };
+            let block_hash_sql = match block_hash {
+                Some(h) => format!("'{}'", h.replace('\'', "''")),
+                None => "NULL".to_string(),
+            };
+            let tx_ordinal_sql = match tx_ordinal {
+                Some(o) => o.to_string(),
+                None => "NULL".to_string(),
+            };
+            let operation_sql = match operation {
+                Some(o) => format!("'{}'", o.replace('\'', "''")),
+                None => "NULL".to_string(),
+            };
+            let block_time_sql = match block_time {
+                Some(t) => format!("'{}'", t.format("%Y-%m-%d %H:%M:%S")),
+                None => "NULL".to_string(),
+            };
             let data_json = serde_json::to_string(data).unwrap_or_else(|_| "{}".to_string());
+            let is_placeholder = charms_core::is_empty_spell_charm(data);
 
             values_parts.push(format!(
-                "('{}', {}, {}, '{}'::jsonb, '{}', '{}', '{}', '{}', {}, false, '{}', {}, NULL, {}, true)",
+                "('{}', {}, {}, '{}'::jsonb, '{}', '{}', '{}', '{}', {}, false, '{}', {}, NULL, {}, true, {}, {}, {}, {}, {}, {})",
                 txid.replace('\'', "''"),
                 vout,
                 block_height,
@@ -120,68 +298,133 @@ impl CharmRepository {
                 app_id.replace('\'', "''"),
                 amount,
                 tags_sql,
+                block_hash_sql,
+                tx_ordinal_sql,
+                is_placeholder,
+                stamp_sql,
+                operation_sql,
+                block_time_sql,
             ));
         }
 
         // PK is (txid, vout, app_id) — multi-token UTXOs persist as N rows.
         let sql = format!(
-            "INSERT INTO charms (txid, vout, block_height, data, date_created, asset_type, blockchain, network, address, spent, app_id, amount, mempool_detected_at, tags, verified) \
+            "INSERT INTO charms (txid, vout, block_height, data, date_created, asset_type, blockchain, network, address, spent, app_id, amount, mempool_detected_at, tags, verified, block_hash, tx_ordinal, is_placeholder, indexer_version, parser_revision, reindex_run_id, operation, block_time) \
              VALUES {} \
-             ON CONFLICT (txid, vout, app_id) DO NOTHING \
-             RETURNING txid, vout",
+             ON CONFLICT (txid, vout, app_id, network) DO UPDATE SET tags = COALESCE(EXCLUDED.tags, charms.tags), \
+                 operation = COALESCE(EXCLUDED.operation, charms.operation), \
+                 block_time = COALESCE(EXCLUDED.block_time, charms.block_time), \
+                 indexer_version = EXCLUDED.indexer_version, parser_revision = EXCLUDED.parser_revision, \
+                 reindex_run_id = EXCLUDED.reindex_run_id \
+             WHERE (EXCLUDED.tags IS NOT NULL AND charms.tags IS DISTINCT FROM EXCLUDED.tags) \
+                OR (EXCLUDED.operation IS NOT NULL AND charms.operation IS DISTINCT FROM EXCLUDED.operation) \
+                OR (EXCLUDED.block_time IS NOT NULL AND charms.block_time IS DISTINCT FROM EXCLUDED.block_time) \
+                OR EXCLUDED.reindex_run_id IS NOT NULL \
+             RETURNING txid, vout, app_id, block_height, network, (xmax = 0) AS inserted",
             values_parts.join(", ")
         );
 
-        let rows = self.conn
+        let txn = self.conn.begin().await?;
+        let rows = txn
             .query_all(Statement::from_string(DbBackend::Postgres, sql))
             .await
             .map_err(|e| DbError::QueryError(e.to_string()))?;
 
-        let inserted: Vec<(String, i32)> = rows
-            .iter()
-            .filter_map(|row| {
-                let txid: String = row.try_get("", "txid").ok()?;
-                let vout: i32 = row.try_get("", "vout").ok()?;
-                Some((txid, vout))
-            })
-            .collect();
+        let mut inserted: Vec<(String, i32, String)> = Vec::new();
+        let mut changes = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let (Ok(txid), Ok(vout), Ok(app_id), Ok(network)) = (
+                row.try_get::<String>("", "txid"),
+                row.try_get::<i32>("", "vout"),
+                row.try_get::<String>("", "app_id"),
+                row.try_get::<String>("", "network"),
+            ) else {
+                continue;
+            };
+            let is_new = row.try_get::<bool>("", "inserted").unwrap_or(false);
+            changes.push(Change::charm(
+                &txid,
+                vout,
+                &app_id,
+                ChangeOp::upserted(is_new),
+                row.try_get("", "block_height").ok().flatten(),
+                &network,
+            ));
+            if is_new {
+                inserted.push((txid, vout, app_id));
+            }
+        }
+        changefeed::record(&txn, &changes).await?;
+        txn.commit().await?;
 
         Ok(inserted)
     }
 
-    /// Mark multiple charms as spent in a batch using (txid, vout) pairs.
+    /// Mark multiple charms as spent in a batch using (txid, vout,
+    /// spending_txid) triples, recording the spender for wallet history
+    /// and `block_height` as `spent_height` for the archiver. The rows
+    /// flipped go to the changefeed as updates at `block_height`.
     /// Scoped by `network` so collisions across mainnet/testnet do not bleed
     /// into each other.
     pub async fn mark_charms_as_spent_batch(
         &self,
-        txid_vouts: Vec<(String, i32)>,
+        spends: Vec<(String, i32, String)>,
         network: &str,
+        block_height: i32,
     ) -> Result<(), DbError> {
-        if txid_vouts.is_empty() {
+        if spends.is_empty() {
             return Ok(());
         }
 
-        let values = txid_vouts
+        let values = spends
             .iter()
-            .map(|(txid, vout)| format!("('{}', {})", txid.replace('\'', "''"), vout))
+            .map(|(txid, vout, spending_txid)| {
+                format!(
+                    "('{}', {}, '{}')",
+                    txid.replace('\'', "''"),
+                    vout,
+                    spending_txid.replace('\'', "''")
+                )
+            })
             .collect::<Vec<_>>()
             .join(", ");
 
         let stmt = Statement::from_string(
             DbBackend::Postgres,
             format!(
-                "UPDATE charms SET spent = true \
-                 WHERE (txid, vout) IN (VALUES {}) AND spent = false AND network = '{}'",
+                "UPDATE charms c SET spent = true, spending_txid = v.spending_txid, \
+                 spent_height = {} \
+                 FROM (VALUES {}) AS v(txid, vout, spending_txid) \
+                 WHERE c.txid = v.txid AND c.vout = v.vout \
+                 AND c.spent = false AND c.network = '{}' \
+                 RETURNING c.txid, c.vout, c.app_id",
+                block_height,
                 values,
                 network.replace('\'', "''"),
             ),
         );
 
-        self.conn
-            .execute(stmt)
+        let txn = self.conn.begin().await?;
+        let rows = txn
+            .query_all(stmt)
             .await
-            .map(|_| ())
-            .map_err(|e| DbError::QueryError(e.to_string()))
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        let changes: Vec<Change> = rows
+            .iter()
+            .filter_map(|row| {
+                Some(Change::charm(
+                    &row.try_get::<String>("", "txid").ok()?,
+                    row.try_get("", "vout").ok()?,
+                    &row.try_get::<String>("", "app_id").ok()?,
+                    ChangeOp::Update,
+                    Some(block_height),
+                    network,
+                ))
+            })
+            .collect();
+        changefeed::record(&txn, &changes).await?;
+        txn.commit().await?;
+        Ok(())
     }
 
     /// Get charm info for stats_holders updates before marking as spent.
@@ -0,0 +1,74 @@
+//! Repository for the `control_commands` table (operator pause/resume).
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use std::fmt;
+
+use crate::domain::models::ControlCommand;
+use crate::infrastructure::persistence::error::DbError;
+
+#[derive(Clone)]
+pub struct ControlCommandsRepository {
+    conn: DatabaseConnection,
+}
+
+impl fmt::Debug for ControlCommandsRepository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ControlCommandsRepository")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ControlCommandsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Record `command` for `network` on behalf of `requested_by`, unless it
+    /// is already the network's current command (no commands = running).
+    /// Returns whether a row was written.
+    pub async fn issue(
+        &self,
+        network: &str,
+        command: ControlCommand,
+        requested_by: &str,
+    ) -> Result<bool, DbError> {
+        let res = self
+            .conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO control_commands (network, command, requested_by) \
+                 SELECT $1, $2, $3 \
+                  WHERE $2 <> COALESCE( \
+                        (SELECT command FROM control_commands \
+                          WHERE network = $1 ORDER BY id DESC LIMIT 1), \
+                        'resume')",
+                [network.into(), command.as_str().into(), requested_by.into()],
+            ))
+            .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    /// Current command for `network` (`None` if none was ever issued).
+    /// Stamps `acknowledged_at` on every command not yet picked up, so
+    /// operators can see that the indexer has seen it.
+    pub async fn poll(&self, network: &str) -> Result<Option<ControlCommand>, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "WITH ack AS ( \
+                     UPDATE control_commands SET acknowledged_at = NOW() \
+                      WHERE network = $1 AND acknowledged_at IS NULL \
+                 ) \
+                 SELECT command FROM control_commands \
+                  WHERE network = $1 ORDER BY id DESC LIMIT 1",
+                [network.into()],
+            ))
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let command: String = row.try_get("", "command")?;
+        command.parse().map(Some).map_err(DbError::QueryError)
+    }
+}
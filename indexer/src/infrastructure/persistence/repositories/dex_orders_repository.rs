@@ -1,12 +1,18 @@
 //! Repository for DEX orders operations
 
+use sea_orm::sea_query::OnConflict;
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, EntityTrait, Set,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, Set, Statement,
+    TransactionTrait,
 };
 
+use crate::domain::services::address_extractor::AddressExtractor;
 use crate::domain::services::dex::{DexOperation, DexOrder, ExecType, OrderSide};
 use crate::infrastructure::persistence::entities::dex_orders;
-use crate::infrastructure::persistence::error::{is_duplicate_key, DbError};
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::changefeed_repository::{
+    self as changefeed, Change, ChangeEntity, ChangeOp,
+};
 
 /// Repository for DEX orders operations
 #[derive(Clone, Debug)]
@@ -62,13 +68,20 @@ impl DexOrdersRepository {
             None
         };
 
+        let change = Change::new(
+            ChangeEntity::DexOrder,
+            &order_id,
+            ChangeOp::Insert,
+            block_height.map(|h| h as i32),
+            network,
+        );
         let model = dex_orders::ActiveModel {
             order_id: Set(order_id),
             txid: Set(txid.to_string()),
             vout: Set(vout),
             block_height: Set(block_height.map(|h| h as i32)),
             platform: Set(platform.to_string()),
-            maker: Set(order.maker.clone()),
+            maker: Set(AddressExtractor::canonical(&order.maker)),
             side: Set(side_str.to_string()),
             exec_type: Set(exec_type_str.to_string()),
             price_num: Set(order.price.0 as i64),
@@ -81,22 +94,62 @@ impl DexOrdersRepository {
             scrolls_address: Set(order.scrolls_address.clone()),
             status: Set(status.to_string()),
             parent_order_id: Set(parent_order_id),
+            expires_at_height: Set(order.expires_at_height.map(|h| h as i32)),
+            expires_at_time: Set(order.expires_at_datetime()),
             created_at: Set(now),
             updated_at: Set(now),
             blockchain: Set(blockchain.to_string()),
             network: Set(network.to_string()),
         };
 
-        match model.insert(&self.conn).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                if is_duplicate_key(&e) {
-                    Ok(()) // Order already exists
-                } else {
-                    Err(e.into())
-                }
-            }
+        // Re-detecting an order (mempool then block) is a no-op.
+        self.insert_ignoring_existing(model, change).await
+    }
+
+    /// Insert unless a row with the same `order_id` exists already; only an
+    /// actual insert records `change`.
+    async fn insert_ignoring_existing(
+        &self,
+        model: dex_orders::ActiveModel,
+        change: Change,
+    ) -> Result<(), DbError> {
+        let txn = self.conn.begin().await?;
+        let inserted = dex_orders::Entity::insert(model)
+            .on_conflict(
+                OnConflict::column(dex_orders::Column::OrderId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+        if inserted > 0 {
+            changefeed::record(&txn, &[change]).await?;
         }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Apply `m` and record it as an update of `order`.
+    async fn update_recorded(
+        &self,
+        order: &dex_orders::Model,
+        m: dex_orders::ActiveModel,
+    ) -> Result<(), DbError> {
+        let txn = self.conn.begin().await?;
+        m.update(&txn).await?;
+        changefeed::record(
+            &txn,
+            &[Change::new(
+                ChangeEntity::DexOrder,
+                &order.order_id,
+                ChangeOp::Update,
+                order.block_height,
+                &order.network,
+            )],
+        )
+        .await?;
+        txn.commit().await?;
+        Ok(())
     }
 
     /// Get order by ID
@@ -113,10 +166,10 @@ impl DexOrdersRepository {
             .one(&self.conn)
             .await?
         {
-            let mut active_model: dex_orders::ActiveModel = order.into();
+            let mut active_model: dex_orders::ActiveModel = order.clone().into();
             active_model.status = Set(status.to_string());
             active_model.updated_at = Set(chrono::Utc::now().naive_utc());
-            active_model.update(&self.conn).await?;
+            self.update_recorded(&order, active_model).await?;
         }
         Ok(())
     }
@@ -132,16 +185,58 @@ impl DexOrdersRepository {
             .one(&self.conn)
             .await?
         {
-            let mut m: dex_orders::ActiveModel = order.into();
+            let mut m: dex_orders::ActiveModel = order.clone().into();
             m.status = Set("filled".to_string());
             m.filled_amount = Set(amount);
             m.filled_quantity = Set(quantity);
             m.updated_at = Set(chrono::Utc::now().naive_utc());
-            m.update(&self.conn).await?;
+            self.update_recorded(&order, m).await?;
         }
         Ok(())
     }
 
+    /// Move open/partial orders of `network` whose expiry a block at
+    /// `height` mined at unix `block_time` has passed to `expired`. An order
+    /// stays fillable through its expiry height/time inclusive. Returns the
+    /// number of orders expired, each recorded as an update at `height`.
+    pub async fn expire_orders(
+        &self,
+        network: &str,
+        height: u64,
+        block_time: u32,
+    ) -> Result<u64, DbError> {
+        let block_time = chrono::DateTime::from_timestamp(block_time as i64, 0)
+            .map(|t| t.naive_utc())
+            .unwrap_or_default();
+        let txn = self.conn.begin().await?;
+        let rows = txn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE dex_orders SET status = 'expired', updated_at = NOW() \
+                  WHERE network = $1 AND status IN ('open', 'partial') \
+                    AND (expires_at_height < $2 OR expires_at_time < $3) \
+                  RETURNING order_id",
+                [network.into(), (height as i64).into(), block_time.into()],
+            ))
+            .await?;
+        let changes: Vec<Change> = rows
+            .iter()
+            .filter_map(|row| row.try_get::<String>("", "order_id").ok())
+            .map(|id| {
+                Change::new(
+                    ChangeEntity::DexOrder,
+                    id,
+                    ChangeOp::Update,
+                    Some(height as i32),
+                    network,
+                )
+            })
+            .collect();
+        changefeed::record(&txn, &changes).await?;
+        txn.commit().await?;
+        Ok(rows.len() as u64)
+    }
+
     /// Save a FULFILL/CANCEL activity row by copying data from the parent order.
     /// The new row gets its own order_id (based on the fulfill/cancel txid) and
     /// links back to the parent via parent_order_id.
@@ -156,6 +251,13 @@ impl DexOrdersRepository {
     ) -> Result<(), DbError> {
         let order_id = format!("{}:0", txid);
         let now = chrono::Utc::now().naive_utc();
+        let change = Change::new(
+            ChangeEntity::DexOrder,
+            &order_id,
+            ChangeOp::Insert,
+            block_height.map(|h| h as i32),
+            network,
+        );
 
         let model = dex_orders::ActiveModel {
             order_id: Set(order_id),
@@ -163,7 +265,7 @@ impl DexOrdersRepository {
             vout: Set(0i32),
             block_height: Set(block_height.map(|h| h as i32)),
             platform: Set(parent.platform.clone()),
-            maker: Set(parent.maker.clone()),
+            maker: Set(AddressExtractor::canonical(&parent.maker)),
             side: Set(parent.side.clone()),
             exec_type: Set(parent.exec_type.clone()),
             price_num: Set(parent.price_num),
@@ -176,22 +278,16 @@ impl DexOrdersRepository {
             scrolls_address: Set(parent.scrolls_address.clone()),
             status: Set(status.to_string()),
             parent_order_id: Set(Some(parent.order_id.clone())),
+            expires_at_height: Set(None),
+            expires_at_time: Set(None),
             created_at: Set(now),
             updated_at: Set(now),
             blockchain: Set(blockchain.to_string()),
             network: Set(network.to_string()),
         };
 
-        match model.insert(&self.conn).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                if is_duplicate_key(&e) {
-                    Ok(()) // Activity row already exists (idempotent)
-                } else {
-                    Err(e.into())
-                }
-            }
-        }
+        // Idempotent: re-processing keeps the first activity row.
+        self.insert_ignoring_existing(model, change).await
     }
 
 }
@@ -0,0 +1,113 @@
+//! Repository for indexer_replicas table
+//! One heartbeat row per (replica, network) with the replica's current role,
+//! plus the replica's `config_snapshots` row.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexerReplica {
+    pub instance_id: String,
+    pub role: String,
+    pub role_since: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexerReplicasRepository {
+    conn: DatabaseConnection,
+}
+
+impl IndexerReplicasRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Refresh the replica's row. `role_since` only moves when the role changes.
+    pub async fn heartbeat(
+        &self,
+        instance_id: &str,
+        network: &str,
+        role: &str,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO indexer_replicas (instance_id, network, role) VALUES ($1, $2, $3) \
+                 ON CONFLICT (instance_id, network) DO UPDATE \
+                    SET role_since = CASE WHEN indexer_replicas.role = EXCLUDED.role \
+                                          THEN indexer_replicas.role_since ELSE NOW() END, \
+                        role = EXCLUDED.role, \
+                        last_seen = NOW()",
+                [instance_id.into(), network.into(), role.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Drop the replica's row on clean shutdown.
+    pub async fn remove(&self, instance_id: &str, network: &str) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM indexer_replicas WHERE instance_id = $1 AND network = $2",
+                [instance_id.into(), network.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Replace the replica's redacted configuration snapshot.
+    pub async fn record_config_snapshot(
+        &self,
+        instance_id: &str,
+        snapshot: &serde_json::Value,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO config_snapshots (instance_id, snapshot) VALUES ($1, $2) \
+                 ON CONFLICT (instance_id) DO UPDATE \
+                    SET snapshot = EXCLUDED.snapshot, recorded_at = NOW()",
+                [instance_id.into(), snapshot.clone().into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Replicas of `network` seen within `max_age_secs`, leader first.
+    pub async fn live(
+        &self,
+        network: &str,
+        max_age_secs: i64,
+    ) -> Result<Vec<IndexerReplica>, DbError> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT instance_id, role, role_since, last_seen FROM indexer_replicas \
+                  WHERE network = $1 AND last_seen > NOW() - make_interval(secs => $2) \
+                  ORDER BY role = 'leader' DESC, instance_id",
+                [network.into(), (max_age_secs as f64).into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        rows.iter()
+            .map(|r| {
+                Ok(IndexerReplica {
+                    instance_id: r.try_get("", "instance_id")?,
+                    role: r.try_get("", "role")?,
+                    role_since: r.try_get("", "role_since")?,
+                    last_seen: r.try_get("", "last_seen")?,
+                })
+            })
+            .collect::<Result<_, sea_orm::DbErr>>()
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+}
@@ -0,0 +1,272 @@
+//! Repository for metadata_refresh_jobs and metadata_refresh_results. The
+//! API queues a job; the refresh worker claims it, walks the targeted
+//! assets in id order and records what each re-derivation changed.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, QueryResult, Statement};
+use serde_json::Value;
+
+use crate::infrastructure::persistence::error::DbError;
+
+/// Metadata columns a refresh may rewrite.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AssetFields {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub decimals: i16,
+    pub collection: Option<String>,
+}
+
+/// What an asset's metadata is derived from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefreshSource {
+    /// The NFT's stored data and its deployer, as at creation.
+    Nft {
+        data: Value,
+        deployer: Option<String>,
+    },
+    /// The parent NFT's current fields, which tokens inherit.
+    Token { parent: AssetFields },
+}
+
+/// One asset in scope of a refresh job.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefreshTarget {
+    pub asset_id: i32,
+    pub app_id: String,
+    pub network: String,
+    pub current: AssetFields,
+    pub source: RefreshSource,
+}
+
+/// Assets in scope of job `$1`: every asset when the job names no app ids,
+/// else the listed ones (tokens also through their parent NFT's app id),
+/// optionally limited to one network. Paged by asset id.
+const NFT_TARGETS_SQL: &str = "
+SELECT a.id, a.app_id, a.network, a.name, a.symbol, a.description, a.image_url,
+       a.decimals, a.collection, a.data, a.deployer_address
+  FROM assets a
+  JOIN metadata_refresh_jobs j ON j.id = $1
+ WHERE a.asset_type = 'nft' AND a.id > $2
+   AND (j.network IS NULL OR a.network = j.network)
+   AND (j.app_ids IS NULL OR a.app_id = ANY (j.app_ids))
+ ORDER BY a.id
+ LIMIT $3";
+
+const TOKEN_TARGETS_SQL: &str = "
+SELECT t.id, t.app_id, t.network, t.name, t.symbol, t.description, t.image_url,
+       t.decimals, t.collection,
+       p.name AS parent_name, p.symbol AS parent_symbol,
+       p.description AS parent_description, p.image_url AS parent_image_url,
+       p.decimals AS parent_decimals, p.collection AS parent_collection
+  FROM assets t
+  JOIN metadata_refresh_jobs j ON j.id = $1
+  JOIN LATERAL (SELECT * FROM assets n
+                 WHERE n.asset_type = 'nft' AND n.network = t.network
+                   AND n.app_id LIKE 'n/' || split_part(t.app_id, '/', 2) || '/%'
+                 ORDER BY n.id
+                 LIMIT 1) p ON TRUE
+ WHERE t.asset_type = 'token' AND t.id > $2
+   AND (j.network IS NULL OR t.network = j.network)
+   AND (j.app_ids IS NULL OR t.app_id = ANY (j.app_ids) OR p.app_id = ANY (j.app_ids))
+ ORDER BY t.id
+ LIMIT $3";
+
+#[derive(Clone, Debug)]
+pub struct MetadataRefreshRepository {
+    conn: DatabaseConnection,
+}
+
+impl MetadataRefreshRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Claim the pending job, or a running one whose worker stopped
+    /// heartbeating `stale_secs` ago. Returns its id.
+    pub async fn claim_next(&self, stale_secs: f64) -> Result<Option<i64>, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE metadata_refresh_jobs j \
+                    SET status = 'running', started_at = COALESCE(j.started_at, NOW()), \
+                        heartbeat_at = NOW() \
+                  WHERE j.id = (SELECT q.id FROM metadata_refresh_jobs q \
+                                 WHERE q.status = 'pending' \
+                                    OR (q.status = 'running' \
+                                        AND q.heartbeat_at < NOW() - make_interval(secs => $1)) \
+                                 ORDER BY q.id \
+                                 LIMIT 1 \
+                                   FOR UPDATE SKIP LOCKED) \
+              RETURNING j.id",
+                [stale_secs.into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        row.map(|r| r.try_get("", "id"))
+            .transpose()
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Next page of NFTs in scope of `job_id`, after asset id `after`.
+    pub async fn nft_targets(
+        &self,
+        job_id: i64,
+        after: i32,
+        limit: u64,
+    ) -> Result<Vec<RefreshTarget>, DbError> {
+        self.targets(NFT_TARGETS_SQL, job_id, after, limit, |r| {
+            Ok(RefreshSource::Nft {
+                data: r.try_get("", "data")?,
+                deployer: r.try_get("", "deployer_address")?,
+            })
+        })
+        .await
+    }
+
+    /// Next page of tokens in scope of `job_id`, after asset id `after`.
+    /// Tokens without a parent NFT have nothing to inherit and are skipped.
+    pub async fn token_targets(
+        &self,
+        job_id: i64,
+        after: i32,
+        limit: u64,
+    ) -> Result<Vec<RefreshTarget>, DbError> {
+        self.targets(TOKEN_TARGETS_SQL, job_id, after, limit, |r| {
+            Ok(RefreshSource::Token {
+                parent: fields(r, "parent_")?,
+            })
+        })
+        .await
+    }
+
+    async fn targets(
+        &self,
+        sql: &str,
+        job_id: i64,
+        after: i32,
+        limit: u64,
+        source: impl Fn(&QueryResult) -> Result<RefreshSource, sea_orm::DbErr>,
+    ) -> Result<Vec<RefreshTarget>, DbError> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [job_id.into(), after.into(), (limit as i64).into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        rows.iter()
+            .map(|r| {
+                Ok(RefreshTarget {
+                    asset_id: r.try_get("", "id")?,
+                    app_id: r.try_get("", "app_id")?,
+                    network: r.try_get("", "network")?,
+                    current: fields(r, "")?,
+                    source: source(r)?,
+                })
+            })
+            .collect::<Result<Vec<_>, sea_orm::DbErr>>()
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Write the re-derived fields and record `changes` for the job.
+    pub async fn apply(
+        &self,
+        job_id: i64,
+        target: &RefreshTarget,
+        new: &AssetFields,
+        changes: &Value,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "WITH a AS ( \
+                     UPDATE assets \
+                        SET name = $5, symbol = $6, description = $7, image_url = $8, \
+                            decimals = $9, collection = $10, updated_at = NOW() \
+                      WHERE id = $2 RETURNING id) \
+                 INSERT INTO metadata_refresh_results (job_id, asset_id, app_id, network, changes) \
+                 SELECT $1, a.id, $3, $4, $11 FROM a \
+                 ON CONFLICT (job_id, asset_id) DO UPDATE SET changes = EXCLUDED.changes",
+                [
+                    job_id.into(),
+                    target.asset_id.into(),
+                    target.app_id.clone().into(),
+                    target.network.clone().into(),
+                    new.name.clone().into(),
+                    new.symbol.clone().into(),
+                    new.description.clone().into(),
+                    new.image_url.clone().into(),
+                    new.decimals.into(),
+                    new.collection.clone().into(),
+                    changes.clone().into(),
+                ],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Record progress; keeps the claim alive.
+    pub async fn heartbeat(&self, job_id: i64, scanned: i32) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE metadata_refresh_jobs \
+                    SET heartbeat_at = NOW(), assets_scanned = $2 \
+                  WHERE id = $1",
+                [job_id.into(), scanned.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Close the job; the changed count comes from its result rows, so a
+    /// job resumed after a crash still counts what it changed before.
+    pub async fn finish(&self, job_id: i64, scanned: i32) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE metadata_refresh_jobs \
+                    SET status = 'done', finished_at = NOW(), assets_scanned = $2, \
+                        assets_changed = (SELECT COUNT(*) FROM metadata_refresh_results \
+                                           WHERE job_id = $1) \
+                  WHERE id = $1",
+                [job_id.into(), scanned.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    pub async fn fail(&self, job_id: i64, error: &str) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE metadata_refresh_jobs \
+                    SET status = 'failed', finished_at = NOW(), error = $2 \
+                  WHERE id = $1",
+                [job_id.into(), error.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+}
+
+/// The metadata columns of `r`, named with `prefix`.
+fn fields(r: &QueryResult, prefix: &str) -> Result<AssetFields, sea_orm::DbErr> {
+    Ok(AssetFields {
+        name: r.try_get("", &format!("{prefix}name"))?,
+        symbol: r.try_get("", &format!("{prefix}symbol"))?,
+        description: r.try_get("", &format!("{prefix}description"))?,
+        image_url: r.try_get("", &format!("{prefix}image_url"))?,
+        decimals: r.try_get("", &format!("{prefix}decimals"))?,
+        collection: r.try_get("", &format!("{prefix}collection"))?,
+    })
+}
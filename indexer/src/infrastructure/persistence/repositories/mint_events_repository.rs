@@ -0,0 +1,63 @@
+//! Repository for mint_events table
+//! One row per (network, txid, app_id) whose net supply increased in that transaction.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+
+#[derive(Clone, Debug)]
+pub struct MintEventsRepository {
+    conn: DatabaseConnection,
+}
+
+impl MintEventsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Record mint events in a single batch INSERT.
+    /// Each item: (app_id, txid, block_height, minted_amount, minter_address)
+    ///
+    /// Reprocessing a block (reindex, restart mid-block) re-emits the same
+    /// events; the first row for a (network, txid, app_id) is kept.
+    pub async fn record_batch(
+        &self,
+        events: &[(String, String, u64, i64, Option<String>)],
+        network: &str,
+    ) -> Result<(), DbError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let values: Vec<String> = events
+            .iter()
+            .map(|(app_id, txid, block_height, minted_amount, minter)| {
+                format!(
+                    "('{}', '{}', {}, {}, {}, '{}')",
+                    app_id.replace('\'', "''"),
+                    txid.replace('\'', "''"),
+                    block_height,
+                    minted_amount,
+                    minter
+                        .as_ref()
+                        .map(|a| format!("'{}'", a.replace('\'', "''")))
+                        .unwrap_or_else(|| "NULL".to_string()),
+                    network.replace('\'', "''"),
+                )
+            })
+            .collect();
+
+        let sql = format!(
+            "INSERT INTO mint_events (app_id, txid, block_height, minted_amount, minter_address, network) \
+             VALUES {} \
+             ON CONFLICT (network, txid, app_id) DO NOTHING",
+            values.join(", ")
+        );
+
+        self.conn
+            .execute(Statement::from_string(DbBackend::Postgres, sql))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+}
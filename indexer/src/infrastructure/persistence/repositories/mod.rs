@@ -1,29 +1,57 @@
 pub mod address_transactions_repository;
 pub mod asset;
 pub mod asset_repository;
+pub mod backfill_progress_repository;
 pub mod block_status_repository;
+pub mod changefeed_repository;
 pub mod charm_repository;
+pub mod control_commands_repository;
 pub mod dex_orders_repository;
+pub mod indexer_replicas_repository;
 pub mod mempool_spends_repository;
+pub mod metadata_refresh_repository;
+pub mod mint_events_repository;
 pub mod monitored_addresses_repository;
+pub mod offchain_metadata_repository;
+pub mod parser_stats_repository;
+pub mod pending_spells_repository;
+pub mod reindex_locks_repository;
 pub mod reorg_events_repository;
 pub mod stats_holders_repository;
 pub mod summary_repository;
+pub mod supply_changes_repository;
 pub mod transaction_repository;
 pub mod utxo_repository;
+pub mod webhooks_repository;
 
 pub use address_transactions_repository::AddressTransactionsRepository;
 pub use asset_repository::AssetRepository;
-pub use block_status_repository::BlockStatusRepository;
-pub use charm_repository::CharmRepository;
+pub use backfill_progress_repository::{BackfillProgress, BackfillProgressRepository};
+pub use block_status_repository::{BlockStatusRepository, BlockTimings};
+pub use changefeed_repository::{Change, ChangeEntity, ChangeOp};
+pub use charm_repository::{CharmRepository, RevisionCount};
+pub use control_commands_repository::ControlCommandsRepository;
 pub use dex_orders_repository::DexOrdersRepository;
+pub use indexer_replicas_repository::{IndexerReplica, IndexerReplicasRepository};
 pub use mempool_spends_repository::MempoolSpendsRepository;
+pub use metadata_refresh_repository::{
+    AssetFields, MetadataRefreshRepository, RefreshSource, RefreshTarget,
+};
+pub use mint_events_repository::MintEventsRepository;
 pub use monitored_addresses_repository::MonitoredAddressesRepository;
+pub use offchain_metadata_repository::{OffchainMetadataRepository, PendingFetch};
+pub use parser_stats_repository::ParserStatsRepository;
+pub use pending_spells_repository::{PendingSpell, PendingSpellsRepository};
+pub use reindex_locks_repository::{ReindexLock, ReindexLocksRepository};
 pub use reorg_events_repository::ReorgEventsRepository;
 pub use stats_holders_repository::StatsHoldersRepository;
 pub use summary_repository::SummaryRepository;
+pub use supply_changes_repository::{SupplyChangeReason, SupplySource};
 pub use transaction_repository::TransactionRepository;
 pub use utxo_repository::UtxoRepository;
+pub use webhooks_repository::{PendingDelivery, WebhooksRepository};
+
+use sea_orm::DatabaseConnection;
 
 use crate::infrastructure::persistence::connection::DbPool;
 
@@ -32,34 +60,57 @@ use crate::infrastructure::persistence::connection::DbPool;
 pub struct Repositories {
     pub address_transactions: AddressTransactionsRepository,
     pub asset: AssetRepository,
+    pub backfill_progress: BackfillProgressRepository,
     pub block_status: BlockStatusRepository,
     pub charm: CharmRepository,
+    pub control_commands: ControlCommandsRepository,
     pub dex_orders: DexOrdersRepository,
+    pub indexer_replicas: IndexerReplicasRepository,
     pub stats_holders: StatsHoldersRepository,
     pub summary: SummaryRepository,
     pub transaction: TransactionRepository,
     pub utxo: UtxoRepository,
     pub monitored_addresses: MonitoredAddressesRepository,
     pub mempool_spends: MempoolSpendsRepository,
+    pub metadata_refresh: MetadataRefreshRepository,
+    pub mint_events: MintEventsRepository,
+    pub offchain_metadata: OffchainMetadataRepository,
+    pub parser_stats: ParserStatsRepository,
+    pub pending_spells: PendingSpellsRepository,
+    pub reindex_locks: ReindexLocksRepository,
     pub reorg_events: ReorgEventsRepository,
+    pub webhooks: WebhooksRepository,
 }
 
 impl Repositories {
     pub fn from_pool(db_pool: &DbPool) -> Self {
-        let conn = db_pool.get_connection().clone();
+        Self::from_connection(db_pool.get_connection().clone())
+    }
+
+    pub fn from_connection(conn: DatabaseConnection) -> Self {
         Self {
             address_transactions: AddressTransactionsRepository::new(conn.clone()),
             asset: AssetRepository::new(conn.clone()),
+            backfill_progress: BackfillProgressRepository::new(conn.clone()),
             block_status: BlockStatusRepository::new(conn.clone()),
             charm: CharmRepository::new(conn.clone()),
+            control_commands: ControlCommandsRepository::new(conn.clone()),
             dex_orders: DexOrdersRepository::new(conn.clone()),
+            indexer_replicas: IndexerReplicasRepository::new(conn.clone()),
             stats_holders: StatsHoldersRepository::new(conn.clone()),
             summary: SummaryRepository::new(conn.clone()),
             transaction: TransactionRepository::new(conn.clone()),
             utxo: UtxoRepository::new(conn.clone()),
             monitored_addresses: MonitoredAddressesRepository::new(conn.clone()),
             mempool_spends: MempoolSpendsRepository::new(conn.clone()),
-            reorg_events: ReorgEventsRepository::new(conn),
+            metadata_refresh: MetadataRefreshRepository::new(conn.clone()),
+            mint_events: MintEventsRepository::new(conn.clone()),
+            offchain_metadata: OffchainMetadataRepository::new(conn.clone()),
+            parser_stats: ParserStatsRepository::new(conn.clone()),
+            pending_spells: PendingSpellsRepository::new(conn.clone()),
+            reindex_locks: ReindexLocksRepository::new(conn.clone()),
+            reorg_events: ReorgEventsRepository::new(conn.clone()),
+            webhooks: WebhooksRepository::new(conn),
         }
     }
 }
@@ -3,8 +3,16 @@ use std::fmt;
 
 use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
 
+use crate::domain::services::address_extractor::AddressExtractor;
 use crate::infrastructure::persistence::error::DbError;
 
+/// Rows the indexer keeps tracking: no TTL, TTL not reached yet, or the
+/// address still holds unspent charms (holders never expire).
+const NOT_EXPIRED: &str = "(expires_at IS NULL OR expires_at > NOW() \
+     OR EXISTS (SELECT 1 FROM charms c \
+                 WHERE c.address = monitored_addresses.address \
+                   AND c.network = monitored_addresses.network AND NOT c.spent))";
+
 /// Repository for monitored_addresses table operations.
 /// Tracks which addresses the indexer should maintain UTXO data for.
 #[derive(Clone)]
@@ -24,11 +32,12 @@ impl MonitoredAddressesRepository {
         Self { conn }
     }
 
-    /// Load all monitored addresses for a network into a HashSet (for fast lookup).
+    /// Load all unexpired monitored addresses for a network into a HashSet (for fast lookup).
     pub async fn load_set(&self, network: &str) -> Result<HashSet<String>, DbError> {
         let sql = format!(
-            "SELECT address FROM monitored_addresses WHERE network = '{}'",
-            network.replace('\'', "''")
+            "SELECT address FROM monitored_addresses WHERE network = '{}' AND {}",
+            network.replace('\'', "''"),
+            NOT_EXPIRED
         );
 
         let rows = self
@@ -65,7 +74,7 @@ impl MonitoredAddressesRepository {
                 .map(|addr| {
                     format!(
                         "('{}', '{}', '{}', NOW())",
-                        addr.replace('\'', "''"),
+                        AddressExtractor::canonical(addr).replace('\'', "''"),
                         network.replace('\'', "''"),
                         source.replace('\'', "''"),
                     )
@@ -91,6 +100,7 @@ impl MonitoredAddressesRepository {
     }
 
     /// Fetch a batch of addresses pending a Maestro seed (seeded_at IS NULL).
+    /// Expired rows are skipped: they wait for an API call to renew them.
     /// Used by the BTC auto-seeder worker. Oldest registrations first so
     /// addresses don't starve when the queue is permanently busy.
     pub async fn fetch_unseeded(
@@ -100,9 +110,10 @@ impl MonitoredAddressesRepository {
     ) -> Result<Vec<String>, DbError> {
         let sql = format!(
             "SELECT address FROM monitored_addresses \
-             WHERE network = '{}' AND seeded_at IS NULL \
+             WHERE network = '{}' AND seeded_at IS NULL AND {} \
              ORDER BY created_at ASC LIMIT {}",
             network.replace('\'', "''"),
+            NOT_EXPIRED,
             limit,
         );
         let rows = self
@@ -119,6 +130,7 @@ impl MonitoredAddressesRepository {
     /// Mark an address as seeded by persisting the Maestro tip cursor.
     /// The block_hash + height pair is what `api::is_seeded` later validates
     /// against `block_status` to detect reorgs between seed and handoff.
+    /// A first seed also starts `synced_from`.
     pub async fn mark_seeded(
         &self,
         address: &str,
@@ -128,8 +140,9 @@ impl MonitoredAddressesRepository {
     ) -> Result<(), DbError> {
         let sql = format!(
             "UPDATE monitored_addresses \
-             SET seeded_at = NOW(), seed_height = {}, seed_block_hash = '{}' \
-             WHERE address = '{}' AND network = '{}'",
+             SET seeded_at = NOW(), seed_height = {0}, seed_block_hash = '{1}', \
+                 synced_from = COALESCE(synced_from, NULLIF({0}, 0)) \
+             WHERE address = '{2}' AND network = '{3}'",
             seed_height,
             seed_block_hash.replace('\'', "''"),
             address.replace('\'', "''"),
@@ -188,12 +201,14 @@ impl MonitoredAddressesRepository {
         h.finish() as i64
     }
 
-    /// Load only seeded addresses (seeded_at IS NOT NULL) for a network into a HashSet.
+    /// Load only seeded, unexpired addresses (seeded_at IS NOT NULL) for a network into a HashSet.
     /// These are addresses whose BTC UTXOs have been populated and should be tracked in real time.
     pub async fn load_seeded_set(&self, network: &str) -> Result<HashSet<String>, DbError> {
         let sql = format!(
-            "SELECT address FROM monitored_addresses WHERE network = '{}' AND seeded_at IS NOT NULL",
-            network.replace('\'', "''")
+            "SELECT address FROM monitored_addresses \
+             WHERE network = '{}' AND seeded_at IS NOT NULL AND {}",
+            network.replace('\'', "''"),
+            NOT_EXPIRED
         );
 
         let rows = self
@@ -0,0 +1,147 @@
+//! Repository for offchain_metadata_fetches and assets.offchain_metadata.
+//! The block processor queues NFTs whose metadata points at an off-chain
+//! JSON document; the fetch worker claims, stores and retries them.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde_json::Value;
+
+use crate::infrastructure::persistence::error::DbError;
+
+/// A queued fetch claimed by the worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingFetch {
+    pub asset_id: i32,
+    pub url: String,
+    /// Attempts made before this one.
+    pub attempts: i32,
+}
+
+/// NFTs of one block that point at an off-chain document: an explicit
+/// `metadata_url` (in the spell's NFT metadata or the asset data), else an
+/// image URL ending in `.json`. Assets already holding a document are skipped
+/// and queued ones are left alone, so re-queueing a block is a no-op.
+const ENQUEUE_BLOCK_SQL: &str = "
+INSERT INTO offchain_metadata_fetches (asset_id, url)
+SELECT id, url
+  FROM (SELECT a.id,
+               COALESCE(
+                   NULLIF(btrim(c.data #>> '{native_data,tx,outs,0,0,metadata_url}'), ''),
+                   NULLIF(btrim(a.data ->> 'metadata_url'), ''),
+                   CASE WHEN lower(split_part(a.image_url, '?', 1)) LIKE '%.json'
+                        THEN btrim(a.image_url) END) AS url
+          FROM assets a
+          LEFT JOIN LATERAL (SELECT data FROM charms
+                              WHERE txid = a.txid AND app_id = a.app_id AND network = a.network
+                              LIMIT 1) c ON TRUE
+         WHERE a.network = $1 AND a.block_height = $2
+           AND a.asset_type = 'nft' AND a.offchain_metadata IS NULL) candidates
+ WHERE url IS NOT NULL
+ON CONFLICT (asset_id) DO NOTHING";
+
+#[derive(Clone, Debug)]
+pub struct OffchainMetadataRepository {
+    conn: DatabaseConnection,
+}
+
+impl OffchainMetadataRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Queue a fetch for every NFT created at `height` whose metadata links
+    /// an off-chain document. Returns the number of fetches queued.
+    pub async fn enqueue_block_assets(&self, network: &str, height: i32) -> Result<u64, DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                ENQUEUE_BLOCK_SQL,
+                [network.into(), height.into()],
+            ))
+            .await
+            .map(|r| r.rows_affected())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Claim up to `limit` due fetches. Claimed rows are pushed
+    /// `lease_secs` into the future, so a worker that dies mid-batch leaves
+    /// them to be retried, and concurrent workers skip each other's rows.
+    pub async fn claim_due(
+        &self,
+        limit: u64,
+        lease_secs: f64,
+    ) -> Result<Vec<PendingFetch>, DbError> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE offchain_metadata_fetches f \
+                    SET next_attempt_at = NOW() + make_interval(secs => $2) \
+                  WHERE f.asset_id IN (SELECT q.asset_id FROM offchain_metadata_fetches q \
+                                        WHERE q.status = 'pending' AND q.next_attempt_at <= NOW() \
+                                        ORDER BY q.next_attempt_at, q.asset_id \
+                                        LIMIT $1 \
+                                          FOR UPDATE SKIP LOCKED) \
+              RETURNING f.asset_id, f.url, f.attempts",
+                [(limit as i64).into(), lease_secs.into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        let mut claimed = rows
+            .iter()
+            .map(|r| {
+                Ok(PendingFetch {
+                    asset_id: r.try_get("", "asset_id")?,
+                    url: r.try_get("", "url")?,
+                    attempts: r.try_get("", "attempts")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sea_orm::DbErr>>()
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        claimed.sort_by_key(|f| f.asset_id);
+        Ok(claimed)
+    }
+
+    /// Store the fetched document on the asset and close the fetch.
+    pub async fn mark_fetched(&self, asset_id: i32, document: &Value) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "WITH f AS ( \
+                     UPDATE offchain_metadata_fetches \
+                        SET status = 'fetched', attempts = attempts + 1, \
+                            fetched_at = NOW(), last_error = NULL \
+                      WHERE asset_id = $1 RETURNING asset_id) \
+                 UPDATE assets a SET offchain_metadata = $2, updated_at = NOW() \
+                   FROM f WHERE a.id = f.asset_id",
+                [asset_id.into(), document.clone().into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Record a failed attempt: retry in `retry_in_secs`, or give the fetch
+    /// up when `None`.
+    pub async fn mark_failed(
+        &self,
+        asset_id: i32,
+        error: &str,
+        retry_in_secs: Option<f64>,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE offchain_metadata_fetches \
+                    SET attempts = attempts + 1, last_error = $2, \
+                        status = CASE WHEN $3::float8 IS NULL THEN 'failed' ELSE 'pending' END, \
+                        next_attempt_at = CASE WHEN $3::float8 IS NULL THEN next_attempt_at \
+                                               ELSE NOW() + make_interval(secs => $3::float8) END \
+                  WHERE asset_id = $1",
+                [asset_id.into(), error.into(), retry_in_secs.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+}
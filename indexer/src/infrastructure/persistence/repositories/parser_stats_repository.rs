@@ -0,0 +1,55 @@
+//! Repository for `parser_stats`: hourly parser counters per network and
+//! source, fed from the in-memory `utils::parser_stats` accumulator.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+use crate::utils::parser_stats::{ParseSource, ParserCounts};
+
+#[derive(Clone, Debug)]
+pub struct ParserStatsRepository {
+    conn: DatabaseConnection,
+}
+
+impl ParserStatsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Add `counts` to the bucket of the current hour.
+    pub async fn add(
+        &self,
+        network: &str,
+        source: ParseSource,
+        counts: &ParserCounts,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO parser_stats (network, source, bucket, txs_scanned, envelopes_found, \
+                     parsed, no_envelope, cbor_error, version_unsupported, verification_failed) \
+                 VALUES ($1, $2, date_trunc('hour', NOW()), $3, $4, $5, $6, $7, $8, $9) \
+                 ON CONFLICT (network, source, bucket) DO UPDATE SET \
+                     txs_scanned = parser_stats.txs_scanned + EXCLUDED.txs_scanned, \
+                     envelopes_found = parser_stats.envelopes_found + EXCLUDED.envelopes_found, \
+                     parsed = parser_stats.parsed + EXCLUDED.parsed, \
+                     no_envelope = parser_stats.no_envelope + EXCLUDED.no_envelope, \
+                     cbor_error = parser_stats.cbor_error + EXCLUDED.cbor_error, \
+                     version_unsupported = parser_stats.version_unsupported + EXCLUDED.version_unsupported, \
+                     verification_failed = parser_stats.verification_failed + EXCLUDED.verification_failed",
+                [
+                    network.into(),
+                    source.as_str().into(),
+                    (counts.txs_scanned as i64).into(),
+                    (counts.envelopes_found as i64).into(),
+                    (counts.parsed as i64).into(),
+                    (counts.no_envelope as i64).into(),
+                    (counts.cbor_error as i64).into(),
+                    (counts.version_unsupported as i64).into(),
+                    (counts.verification_failed as i64).into(),
+                ],
+            ))
+            .await?;
+        Ok(())
+    }
+}
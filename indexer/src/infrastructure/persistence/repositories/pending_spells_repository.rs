@@ -0,0 +1,100 @@
+//! Repository for `pending_spells`: txs whose spell declares a protocol
+//! version the parser does not support yet, kept for reprocessing after an
+//! upgrade.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+
+/// A captured tx, as read back for reprocessing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingSpell {
+    pub txid: String,
+    pub raw_hex: String,
+    pub detected_version: u32,
+    /// `None` while the tx has only been seen in mempool.
+    pub block_height: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PendingSpellsRepository {
+    conn: DatabaseConnection,
+}
+
+impl PendingSpellsRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Capture a tx. Seeing it again keeps the first `first_seen_at`; a block
+    /// height, once known, is never reset to NULL by a later mempool sighting.
+    pub async fn record(
+        &self,
+        txid: &str,
+        network: &str,
+        raw_hex: &str,
+        detected_version: u32,
+        block_height: Option<u64>,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO pending_spells (txid, network, raw_hex, detected_version, block_height) \
+                 VALUES ($1, $2, $3, $4, $5) \
+                 ON CONFLICT (txid, network) DO UPDATE SET \
+                     detected_version = EXCLUDED.detected_version, \
+                     block_height = COALESCE(EXCLUDED.block_height, pending_spells.block_height)",
+                [
+                    txid.into(),
+                    network.into(),
+                    raw_hex.into(),
+                    (detected_version as i32).into(),
+                    block_height.map(|h| h as i32).into(),
+                ],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Captured txs of `network` whose version is now at most `max_supported`,
+    /// confirmed ones by ascending height first.
+    pub async fn ready(
+        &self,
+        network: &str,
+        max_supported: u32,
+    ) -> Result<Vec<PendingSpell>, DbError> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT txid, raw_hex, detected_version, block_height FROM pending_spells \
+                  WHERE network = $1 AND detected_version <= $2 \
+                  ORDER BY block_height NULLS LAST, txid",
+                [network.into(), (max_supported as i64).into()],
+            ))
+            .await?;
+        rows.iter()
+            .map(|r| {
+                Ok(PendingSpell {
+                    txid: r.try_get("", "txid")?,
+                    raw_hex: r.try_get("", "raw_hex")?,
+                    detected_version: r.try_get::<i32>("", "detected_version")? as u32,
+                    block_height: r
+                        .try_get::<Option<i32>>("", "block_height")?
+                        .map(|h| h as u64),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn remove(&self, txid: &str, network: &str) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM pending_spells WHERE txid = $1 AND network = $2",
+                [txid.into(), network.into()],
+            ))
+            .await?;
+        Ok(())
+    }
+}
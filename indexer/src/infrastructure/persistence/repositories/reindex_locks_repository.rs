@@ -0,0 +1,131 @@
+//! Repository for reindex_locks table
+//! The row a `reindex` run holds on its network, and its heartbeat.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReindexLock {
+    pub run_id: String,
+    pub from_height: i64,
+    pub to_height: i64,
+    pub started_at: DateTime<Utc>,
+    pub heartbeat_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReindexLocksRepository {
+    conn: DatabaseConnection,
+}
+
+impl ReindexLocksRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Take `network` for `run_id`. A lock whose heartbeat is older than
+    /// `stale_secs` is taken over; a fresh one is left alone and returned as
+    /// the error.
+    pub async fn acquire(
+        &self,
+        network: &str,
+        run_id: &str,
+        from_height: u64,
+        to_height: u64,
+        stale_secs: i64,
+    ) -> Result<Result<(), ReindexLock>, DbError> {
+        loop {
+            let taken = self
+                .conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    "INSERT INTO reindex_locks (network, run_id, from_height, to_height) \
+                     VALUES ($1, $2, $3, $4) \
+                     ON CONFLICT (network) DO UPDATE \
+                        SET run_id = EXCLUDED.run_id, \
+                            from_height = EXCLUDED.from_height, \
+                            to_height = EXCLUDED.to_height, \
+                            started_at = NOW(), \
+                            heartbeat_at = NOW() \
+                      WHERE reindex_locks.heartbeat_at <= NOW() - make_interval(secs => $5)",
+                    [
+                        network.into(),
+                        run_id.into(),
+                        (from_height as i64).into(),
+                        (to_height as i64).into(),
+                        (stale_secs as f64).into(),
+                    ],
+                ))
+                .await
+                .map_err(|e| DbError::QueryError(e.to_string()))?
+                .rows_affected();
+            if taken > 0 {
+                return Ok(Ok(()));
+            }
+            // Otherwise held, unless released between the two statements.
+            if let Some(holder) = self.active(network, stale_secs).await? {
+                return Ok(Err(holder));
+            }
+        }
+    }
+
+    /// Refresh the heartbeat of `run_id`'s lock.
+    pub async fn heartbeat(&self, network: &str, run_id: &str) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE reindex_locks SET heartbeat_at = NOW() \
+                  WHERE network = $1 AND run_id = $2",
+                [network.into(), run_id.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Drop `run_id`'s lock. A lock since taken over by another run stays.
+    pub async fn release(&self, network: &str, run_id: &str) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM reindex_locks WHERE network = $1 AND run_id = $2",
+                [network.into(), run_id.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// The lock on `network` with a heartbeat within `stale_secs`, if any.
+    pub async fn active(
+        &self,
+        network: &str,
+        stale_secs: i64,
+    ) -> Result<Option<ReindexLock>, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT run_id, from_height, to_height, started_at, heartbeat_at \
+                   FROM reindex_locks \
+                  WHERE network = $1 AND heartbeat_at > NOW() - make_interval(secs => $2)",
+                [network.into(), (stale_secs as f64).into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        row.map(|r| {
+            Ok(ReindexLock {
+                run_id: r.try_get("", "run_id")?,
+                from_height: r.try_get("", "from_height")?,
+                to_height: r.try_get("", "to_height")?,
+                started_at: r.try_get("", "started_at")?,
+                heartbeat_at: r.try_get("", "heartbeat_at")?,
+            })
+        })
+        .transpose()
+        .map_err(|e: sea_orm::DbErr| DbError::QueryError(e.to_string()))
+    }
+}
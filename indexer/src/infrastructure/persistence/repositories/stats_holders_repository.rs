@@ -1,8 +1,34 @@
 // Repository for stats_holders table operations in indexer
 
-use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, QueryResult, Statement,
+    TransactionTrait,
+};
 
 use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::changefeed_repository::{
+    self as changefeed, Change, ChangeOp,
+};
+
+/// Holder balances derived from `charms`, mirroring the live path: tokens
+/// are keyed by their NFT app_id and add their amount, NFTs add 1, spent
+/// and unconfirmed outputs do not count. `$1` is the network.
+pub(crate) const EXPECTED_HOLDERS_CTE: &str = r#"
+    expected AS (
+        SELECT CASE WHEN app_id LIKE 't/%' THEN 'n/' || substr(app_id, 3) ELSE app_id END AS app_id,
+               address,
+               SUM(CASE WHEN app_id LIKE 't/%' THEN amount ELSE 1 END) AS total,
+               COUNT(*) AS charm_count,
+               MIN(block_height) AS first_seen_block,
+               MAX(block_height) AS last_updated_block
+          FROM charms
+         WHERE network = $1
+           AND NOT spent
+           AND block_height IS NOT NULL
+           AND address IS NOT NULL AND address <> ''
+           AND (app_id LIKE 'n/%' OR (app_id LIKE 't/%' AND amount > 0))
+      GROUP BY 1, 2
+    )"#;
 
 /// Repository for holder statistics operations.
 ///
@@ -59,6 +85,7 @@ impl StatsHoldersRepository {
                     last_updated_block = {block},
                     updated_at = CURRENT_TIMESTAMP
                 WHERE stats_holders.last_updated_block < {block}
+                RETURNING app_id, address, last_updated_block, (xmax = 0) AS inserted
                 "#,
                 app_id = app_id.replace('\'', "''"),
                 address = address.replace('\'', "''"),
@@ -68,11 +95,13 @@ impl StatsHoldersRepository {
             ),
         );
 
-        self.conn
-            .execute(stmt)
+        let txn = self.conn.begin().await?;
+        let rows = txn
+            .query_all(stmt)
             .await
-            .map(|_| ())
             .map_err(|e| DbError::QueryError(e.to_string()))?;
+        changefeed::record(&txn, &holder_changes(&rows, network)).await?;
+        txn.commit().await?;
 
         if amount_delta < 0 {
             self.cleanup_zero_holders(app_id, address, network).await?;
@@ -105,9 +134,15 @@ impl StatsHoldersRepository {
             .map_err(|e| DbError::QueryError(e.to_string()))
     }
 
-    /// Group updates by (app_id, address) and apply them per network.
-    /// The grouping reduces the number of UPSERTs when the same holder
-    /// receives several charms in the same block.
+    /// Group updates by (app_id, address) and apply them per network in a
+    /// single multi-row UPSERT. Grouping is required, not just an
+    /// optimisation: Postgres rejects an `ON CONFLICT DO UPDATE` that touches
+    /// the same row twice in one statement. The inserted `total_amount` is
+    /// the delta, so `EXCLUDED.total_amount` is added to the stored balance.
+    ///
+    /// Every call is applied, whatever its block height, so concurrent
+    /// writers on one holder always sum. Replaying a block is therefore not
+    /// idempotent here; the block path goes through `apply_block_deltas`.
     pub async fn update_holders_batch(
         &self,
         updates: Vec<(String, String, i64, i32)>,
@@ -117,28 +152,188 @@ impl StatsHoldersRepository {
             return Ok(());
         }
 
-        use std::collections::HashMap;
-        let mut grouped: HashMap<(String, String), (i64, i32)> = HashMap::new();
+        let txn = self.conn.begin().await?;
+        upsert_deltas(&txn, updates, network).await?;
+        txn.commit().await?;
 
-        for (app_id, address, amount, block_height) in updates {
-            let key = (app_id.clone(), address.clone());
-            let entry = grouped.entry(key).or_insert((0, block_height));
-            let old_value = entry.0;
-            entry.0 = entry.0.checked_add(amount).unwrap_or_else(|| {
-                crate::utils::logging::log_warning(&format!(
+        Ok(())
+    }
+
+    /// Apply the holder deltas of block `block_hash` exactly once per
+    /// network. The block is claimed in `stats_holders_applied_blocks` in
+    /// the same transaction as the UPSERT, so re-processing a block after a
+    /// crash finds the claim and skips it. Returns whether the deltas were
+    /// applied.
+    pub async fn apply_block_deltas(
+        &self,
+        updates: Vec<(String, String, i64, i32)>,
+        network: &str,
+        block_hash: &str,
+        block_height: i32,
+    ) -> Result<bool, DbError> {
+        let txn = self.conn.begin().await?;
+        let claimed = txn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO stats_holders_applied_blocks (network, block_hash, block_height) \
+                 VALUES ($1, $2, $3) ON CONFLICT (network, block_hash) DO NOTHING",
+                vec![network.into(), block_hash.into(), block_height.into()],
+            ))
+            .await?
+            .rows_affected();
+        if claimed == 0 {
+            return Ok(false);
+        }
+
+        upsert_deltas(&txn, updates, network).await?;
+        txn.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Replace the holder rows of `network` (optionally a single holder
+    /// app_id, `t/` or `n/`) with balances recomputed from `charms`. Runs in
+    /// one transaction so readers never see a half-empty table. Returns the
+    /// number of rows written.
+    pub async fn rebuild_from_charms(
+        &self,
+        network: &str,
+        app_id: Option<&str>,
+    ) -> Result<u64, DbError> {
+        let app_id = app_id.map(charms_core::token_to_nft);
+        let values = || -> Vec<sea_orm::Value> { vec![network.into(), app_id.clone().into()] };
+
+        let txn = self.conn.begin().await?;
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "DELETE FROM stats_holders WHERE network = $1 AND ($2::text IS NULL OR app_id = $2)",
+            values(),
+        ))
+        .await?;
+        let sql = format!(
+            "WITH {} INSERT INTO stats_holders \
+                 (app_id, address, network, total_amount, charm_count, first_seen_block, last_updated_block, created_at, updated_at) \
+             SELECT app_id, address, $1, total, charm_count, first_seen_block, last_updated_block, \
+                    CURRENT_TIMESTAMP, CURRENT_TIMESTAMP \
+               FROM expected \
+              WHERE total > 0 AND ($2::text IS NULL OR app_id = $2)",
+            EXPECTED_HOLDERS_CTE
+        );
+        let res = txn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                &sql,
+                values(),
+            ))
+            .await?;
+        txn.commit().await?;
+        Ok(res.rows_affected())
+    }
+}
+
+/// Add `updates` to the holder balances of `network` inside `txn` and drop
+/// the rows a negative delta emptied.
+async fn upsert_deltas(
+    txn: &DatabaseTransaction,
+    updates: Vec<(String, String, i64, i32)>,
+    network: &str,
+) -> Result<(), DbError> {
+    if updates.is_empty() {
+        return Ok(());
+    }
+
+    use std::collections::HashMap;
+    let mut grouped: HashMap<(String, String), (i64, i32)> = HashMap::new();
+
+    for (app_id, address, amount, block_height) in updates {
+        let key = (app_id.clone(), address.clone());
+        let entry = grouped.entry(key).or_insert((0, block_height));
+        let old_value = entry.0;
+        entry.0 = entry.0.checked_add(amount).unwrap_or_else(|| {
+            crate::utils::throttled_log::global().warn("holder-overflow", &app_id, || {
+                format!(
                     "[STATS_HOLDERS] Overflow adding {} to {} for {}/{}",
                     amount, old_value, app_id, address
-                ));
-                old_value
+                )
             });
-            entry.1 = entry.1.max(block_height);
-        }
+            old_value
+        });
+        entry.1 = entry.1.max(block_height);
+    }
 
-        for ((app_id, address), (total_delta, block_height)) in grouped {
-            self.update_holder_stats(&app_id, &address, network, total_delta, block_height)
-                .await?;
+    let network_sql = network.replace('\'', "''");
+    let mut values = Vec::with_capacity(grouped.len());
+    let mut decremented = Vec::new();
+    for ((app_id, address), (total_delta, block_height)) in &grouped {
+        let capped = if *total_delta > 0 {
+            (*total_delta).min(i64::MAX / 2)
+        } else {
+            (*total_delta).max(i64::MIN / 2)
+        };
+        let key_sql = format!(
+            "'{}', '{}'",
+            app_id.replace('\'', "''"),
+            address.replace('\'', "''")
+        );
+        if capped < 0 {
+            decremented.push(format!("({}, '{}')", key_sql, network_sql));
         }
+        values.push(format!(
+            "({}, '{}', {}, 1, {}, {}, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+            key_sql, network_sql, capped, block_height, block_height
+        ));
+    }
 
-        Ok(())
+    let sql = format!(
+        r#"
+        INSERT INTO stats_holders
+            (app_id, address, network, total_amount, charm_count, first_seen_block, last_updated_block, created_at, updated_at)
+        VALUES
+            {}
+        ON CONFLICT (app_id, address, network)
+        DO UPDATE SET
+            total_amount = stats_holders.total_amount + EXCLUDED.total_amount,
+            charm_count = CASE
+                WHEN EXCLUDED.total_amount > 0 THEN stats_holders.charm_count + 1
+                ELSE stats_holders.charm_count - 1
+            END,
+            last_updated_block = GREATEST(stats_holders.last_updated_block, EXCLUDED.last_updated_block),
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING app_id, address, last_updated_block, (xmax = 0) AS inserted
+        "#,
+        values.join(",\n            ")
+    );
+
+    let rows = txn
+        .query_all(Statement::from_string(DbBackend::Postgres, sql))
+        .await
+        .map_err(|e| DbError::QueryError(e.to_string()))?;
+    changefeed::record(txn, &holder_changes(&rows, network)).await?;
+
+    if !decremented.is_empty() {
+        let sql = format!(
+            "DELETE FROM stats_holders WHERE (app_id, address, network) IN ({}) AND total_amount <= 0",
+            decremented.join(", ")
+        );
+        txn.execute(Statement::from_string(DbBackend::Postgres, sql))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
     }
+    Ok(())
+}
+
+/// Changefeed entries for the rows an upsert `RETURNING app_id, address,
+/// last_updated_block, (xmax = 0) AS inserted` touched.
+fn holder_changes(rows: &[QueryResult], network: &str) -> Vec<Change> {
+    rows.iter()
+        .filter_map(|row| {
+            Some(Change::holder(
+                &row.try_get::<String>("", "app_id").ok()?,
+                &row.try_get::<String>("", "address").ok()?,
+                ChangeOp::upserted(row.try_get("", "inserted").unwrap_or(false)),
+                row.try_get("", "last_updated_block").ok()?,
+                network,
+            ))
+        })
+        .collect()
 }
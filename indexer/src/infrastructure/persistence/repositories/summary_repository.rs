@@ -127,6 +127,10 @@ impl SummaryRepository {
                 charms_cast_count: Set(0),
                 bro_count: Set(0),
                 dex_orders_count: Set(0),
+                indexer_paused: Set(false),
+                provider_stale: Set(false),
+                last_gc_at: Set(None),
+                last_gc_stats: Set(None),
             };
 
             new_summary.insert(&self.conn).await?;
@@ -135,4 +139,87 @@ impl SummaryRepository {
         Ok(())
     }
 
+    /// Record whether block processing is paused and refresh `last_updated`,
+    /// which doubles as the indexer heartbeat on `/status`.
+    pub async fn set_indexer_paused(
+        &self,
+        network_id: &NetworkId,
+        paused: bool,
+    ) -> Result<(), DbError> {
+        use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE summary SET indexer_paused = $2, last_updated = NOW(), updated_at = NOW() \
+                  WHERE network = $1",
+                [network_id.name.clone().into(), paused.into()],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Record whether the primary provider's tip is stale. Leaves
+    /// `last_updated` alone: a stuck node must not look like a live loop.
+    pub async fn set_provider_stale(
+        &self,
+        network_id: &NetworkId,
+        stale: bool,
+    ) -> Result<(), DbError> {
+        use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE summary SET provider_stale = $2, updated_at = NOW() WHERE network = $1",
+                [network_id.name.clone().into(), stale.into()],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Stamp the outcome of a garbage-collector pass on every network's
+    /// summary row. The GC is global, so all rows carry the same stats.
+    pub async fn record_gc(&self, stats: serde_json::Value) -> Result<(), DbError> {
+        use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE summary SET last_gc_at = NOW(), last_gc_stats = $1",
+                [stats.into()],
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Count `promoted` more transactions as confirmed and refresh the
+    /// confirmation rate. Used by the confirmation maintenance pass, which
+    /// promotes rows that were still confirming when their block was
+    /// summarised. Capped at `total_transactions`.
+    pub async fn add_confirmed_transactions(
+        &self,
+        network_id: &NetworkId,
+        promoted: u64,
+    ) -> Result<(), DbError> {
+        use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+        if promoted == 0 {
+            return Ok(());
+        }
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE summary \
+                    SET confirmed_transactions = LEAST(confirmed_transactions + $2, total_transactions), \
+                        confirmation_rate = CASE WHEN total_transactions > 0 \
+                            THEN (LEAST(confirmed_transactions + $2, total_transactions) * 100 / total_transactions)::int \
+                            ELSE 0 END, \
+                        updated_at = NOW() \
+                  WHERE network = $1",
+                [network_id.name.clone().into(), (promoted as i64).into()],
+            ))
+            .await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,236 @@
+//! Audit trail for `assets.total_supply`.
+//! Every write to the column goes through `update_supply` or `insert_asset`,
+//! which record a `supply_changes` row in the same transaction. Writes that
+//! leave the value where it was record nothing. Both also record the asset
+//! row in the changefeed, moved or not.
+//!
+//! Supply is owned by the block pipeline's asset saves: the live indexer's
+//! (`mint`) or, over its range, the `reindex` job's. Spends and
+//! `verify --fix` adjust it without a transaction. A write caused by a
+//! transaction is applied once per (network, app_id, txid): `update_supply`
+//! skips it when the trail already holds a change from that tx, whichever
+//! path wrote it, so a retried batch or a reindex running alongside the live
+//! indexer cannot add the same mint twice.
+
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveValue, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, Set, Statement,
+    TransactionTrait,
+};
+
+use crate::infrastructure::persistence::entities::{assets, prelude::*};
+use crate::infrastructure::persistence::error::DbError;
+use crate::infrastructure::persistence::repositories::changefeed_repository::{
+    self as changefeed, Change, ChangeEntity, ChangeOp,
+};
+use crate::utils::throttled_log;
+
+/// Why a supply value moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SupplyChangeReason {
+    /// Block pipeline saw a spell declaring a (higher) supply.
+    Mint,
+    /// A charm of the asset was spent.
+    Spend,
+    /// The block pipeline, run by the `reindex` job.
+    Reindex,
+    /// An operator repair (`verify --fix`).
+    Manual,
+}
+
+impl SupplyChangeReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SupplyChangeReason::Mint => "mint",
+            SupplyChangeReason::Spend => "spend",
+            SupplyChangeReason::Reindex => "reindex",
+            SupplyChangeReason::Manual => "manual",
+        }
+    }
+}
+
+/// The write behind a supply change: its reason and, when a transaction
+/// caused it, that transaction and its block.
+#[derive(Clone, Debug)]
+pub struct SupplySource {
+    pub reason: SupplyChangeReason,
+    pub txid: Option<String>,
+    pub block_height: Option<i32>,
+}
+
+impl SupplySource {
+    pub fn new(reason: SupplyChangeReason) -> Self {
+        Self {
+            reason,
+            txid: None,
+            block_height: None,
+        }
+    }
+
+    pub fn at(reason: SupplyChangeReason, txid: &str, block_height: i32) -> Self {
+        Self {
+            reason,
+            txid: Some(txid.to_string()),
+            block_height: Some(block_height),
+        }
+    }
+}
+
+/// Insert one trail row on `conn` (a transaction, for callers that write
+/// the supply themselves). No-op when the value did not move.
+pub async fn record<C: ConnectionTrait>(
+    conn: &C,
+    app_id: &str,
+    network: &str,
+    old_supply: Option<Decimal>,
+    new_supply: Decimal,
+    source: &SupplySource,
+) -> Result<(), DbError> {
+    let delta = new_supply - old_supply.unwrap_or(Decimal::ZERO);
+    if delta.is_zero() && old_supply.is_some() {
+        return Ok(());
+    }
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "INSERT INTO supply_changes \
+             (app_id, network, delta, reason, txid, block_height, old_supply, new_supply) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        [
+            app_id.into(),
+            network.into(),
+            delta.into(),
+            source.reason.as_str().into(),
+            source.txid.clone().into(),
+            source.block_height.into(),
+            old_supply.into(),
+            new_supply.into(),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Whether the trail holds a change of (app_id, network) from `txid`.
+async fn applied<C: ConnectionTrait>(
+    conn: &C,
+    app_id: &str,
+    network: &str,
+    txid: &str,
+) -> Result<bool, DbError> {
+    let row = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT 1 FROM supply_changes WHERE network = $1 AND app_id = $2 AND txid = $3 LIMIT 1",
+            [network.into(), app_id.into(), txid.into()],
+        ))
+        .await?;
+    Ok(row.is_some())
+}
+
+/// Set `asset`'s supply to `new_supply` and record the change. `false`
+/// when `source`'s tx already changed this asset's supply: nothing is
+/// written, the duplicate is logged.
+pub async fn update_supply(
+    db: &DatabaseConnection,
+    asset: &assets::Model,
+    new_supply: Decimal,
+    source: &SupplySource,
+) -> Result<bool, DbError> {
+    let txn = db.begin().await?;
+    if let Some(txid) = &source.txid {
+        // The row lock queues concurrent writers of the asset behind this
+        // check, so two paths applying the same tx cannot both pass it.
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT 1 FROM assets WHERE id = $1 FOR UPDATE",
+            [asset.id.into()],
+        ))
+        .await?;
+        if applied(&txn, &asset.app_id, &asset.network, txid).await? {
+            txn.rollback().await?;
+            throttled_log::global().warn("supply-duplicate", &asset.app_id, || {
+                format!(
+                    "Supply of {} ({}) already changed by {}; not applying {} → {} again",
+                    asset.app_id,
+                    asset.network,
+                    txid,
+                    asset.total_supply.unwrap_or(Decimal::ZERO),
+                    new_supply
+                )
+            });
+            return Ok(false);
+        }
+    }
+    Assets::update(assets::ActiveModel {
+        id: Set(asset.id),
+        total_supply: Set(Some(new_supply)),
+        updated_at: Set(chrono::Utc::now().into()),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await?;
+    record(
+        &txn,
+        &asset.app_id,
+        &asset.network,
+        asset.total_supply,
+        new_supply,
+        source,
+    )
+    .await?;
+    changefeed::record(
+        &txn,
+        &[Change::new(
+            ChangeEntity::Asset,
+            &asset.app_id,
+            ChangeOp::Update,
+            source.block_height,
+            &asset.network,
+        )],
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(true)
+}
+
+/// Insert a new asset row and record its opening supply. A zero opening
+/// supply (NFT rows) is recorded too, as the starting point of the trail.
+pub async fn insert_asset(
+    db: &DatabaseConnection,
+    model: assets::ActiveModel,
+    source: &SupplySource,
+) -> Result<(), DbError> {
+    let (ActiveValue::Set(app_id), ActiveValue::Set(network), ActiveValue::Set(supply)) = (
+        model.app_id.clone(),
+        model.network.clone(),
+        model.total_supply.clone(),
+    ) else {
+        return Err(DbError::QueryError(
+            "asset insert without app_id, network and total_supply".to_string(),
+        ));
+    };
+    let txn = db.begin().await?;
+    Assets::insert(model).exec(&txn).await?;
+    record(
+        &txn,
+        &app_id,
+        &network,
+        None,
+        supply.unwrap_or(Decimal::ZERO),
+        source,
+    )
+    .await?;
+    changefeed::record(
+        &txn,
+        &[Change::new(
+            ChangeEntity::Asset,
+            &app_id,
+            ChangeOp::Insert,
+            source.block_height,
+            &network,
+        )],
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(())
+}
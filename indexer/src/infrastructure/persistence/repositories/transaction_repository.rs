@@ -1,7 +1,7 @@
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
 use std::fmt;
 
-use crate::domain::models::Transaction;
+use crate::domain::models::{Transaction, TransactionStatus, CONFIRMATION_DEPTH};
 use crate::infrastructure::persistence::entities::transactions;
 use crate::infrastructure::persistence::error::DbError;
 
@@ -41,8 +41,10 @@ impl TransactionRepository {
     }
 
     /// Save multiple transactions in a batch.
-    /// Uses ON CONFLICT DO UPDATE to promote pending/mempool transactions to
-    /// confirmed status when the block processor re-encounters them.
+    /// Uses ON CONFLICT DO UPDATE to promote pending/mempool transactions
+    /// when the block processor re-encounters them; the stored status is
+    /// re-derived from the resulting confirmation count. `spell_outputs` is
+    /// derived from each `charm` JSON.
     /// Tuple shape matches `block/batch.rs::TransactionBatchItem`.
     #[allow(clippy::type_complexity)]
     pub async fn save_batch(
@@ -54,7 +56,7 @@ impl TransactionRepository {
             serde_json::Value,
             serde_json::Value,
             i32,
-            bool,
+            TransactionStatus,
             String,
             String,
             Option<String>,
@@ -80,17 +82,12 @@ impl TransactionRepository {
                     raw,
                     charm,
                     confirmations,
-                    is_confirmed,
+                    status,
                     blockchain,
                     network,
                     tags,
                     tx_type,
                 )| {
-                    let status = if *is_confirmed {
-                        "confirmed"
-                    } else {
-                        "pending"
-                    };
                     let raw_str = serde_json::to_string(raw).unwrap_or_else(|_| "{}".to_string());
                     let charm_str =
                         serde_json::to_string(charm).unwrap_or_else(|_| "{}".to_string());
@@ -102,9 +99,15 @@ impl TransactionRepository {
                         Some(t) => format!("'{}'", t.replace('\'', "''")),
                         None => "NULL".to_string(),
                     };
+                    let spell_outputs_sql = match charms_core::spell_outputs(charm) {
+                        Some(outputs) => {
+                            format!("'{}'::jsonb", outputs.to_string().replace('\'', "''"))
+                        }
+                        None => "NULL".to_string(),
+                    };
 
                     format!(
-                        "('{}', {}, {}, '{}'::jsonb, '{}'::jsonb, '{}', '{}', {}, '{}', '{}', {}, {})",
+                        "('{}', {}, {}, '{}'::jsonb, '{}'::jsonb, '{}', '{}', {}, '{}', '{}', {}, {}, {})",
                         txid.replace('\'', "''"),
                         block_height,
                         ordinal,
@@ -117,24 +120,32 @@ impl TransactionRepository {
                         network.replace('\'', "''"),
                         tags_sql,
                         tx_type_sql,
+                        spell_outputs_sql,
                     )
                 },
             )
             .collect();
 
         let sql = format!(
-            "INSERT INTO transactions (txid, block_height, ordinal, raw, charm, updated_at, status, confirmations, blockchain, network, tags, tx_type) \
+            "INSERT INTO transactions (txid, block_height, ordinal, raw, charm, updated_at, status, confirmations, blockchain, network, tags, tx_type, spell_outputs) \
              VALUES {} \
              ON CONFLICT (txid) DO UPDATE SET \
                block_height = COALESCE(EXCLUDED.block_height, transactions.block_height), \
-               status = CASE WHEN EXCLUDED.block_height IS NOT NULL THEN 'confirmed' ELSE transactions.status END, \
+               status = CASE \
+                 WHEN EXCLUDED.block_height IS NULL THEN transactions.status \
+                 WHEN GREATEST(EXCLUDED.confirmations, transactions.confirmations) >= {depth} THEN '{confirmed}' \
+                 ELSE '{confirming}' END, \
                confirmations = GREATEST(EXCLUDED.confirmations, transactions.confirmations), \
                updated_at = EXCLUDED.updated_at, \
                charm = CASE WHEN EXCLUDED.charm != '{{}}'::jsonb THEN EXCLUDED.charm ELSE transactions.charm END, \
                raw = CASE WHEN EXCLUDED.raw != '{{}}'::jsonb THEN EXCLUDED.raw ELSE transactions.raw END, \
                tags = COALESCE(EXCLUDED.tags, transactions.tags), \
-               tx_type = COALESCE(EXCLUDED.tx_type, transactions.tx_type)",
-            values.join(", ")
+               tx_type = COALESCE(EXCLUDED.tx_type, transactions.tx_type), \
+               spell_outputs = COALESCE(EXCLUDED.spell_outputs, transactions.spell_outputs)",
+            values.join(", "),
+            depth = CONFIRMATION_DEPTH,
+            confirmed = TransactionStatus::Confirmed,
+            confirming = TransactionStatus::Confirming,
         );
 
         self.conn
@@ -144,6 +155,57 @@ impl TransactionRepository {
             .map_err(|e| DbError::QueryError(e.to_string()))
     }
 
+    /// Promote `confirming` transactions on `network` whose block is now at
+    /// least `CONFIRMATION_DEPTH` deep below `tip`, recording their
+    /// confirmation count at that point. Works through the backlog in
+    /// batches of `batch_size` rows, one UPDATE ... FROM per batch, and
+    /// returns the total number of rows promoted.
+    pub async fn confirm_matured(
+        &self,
+        network: &str,
+        tip: u64,
+        batch_size: u64,
+    ) -> Result<u64, DbError> {
+        use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+        let matured_height = tip as i64 - CONFIRMATION_DEPTH as i64 + 1;
+        if matured_height < 0 {
+            return Ok(0);
+        }
+
+        let sql = format!(
+            "UPDATE transactions t \
+                SET confirmations = $2 - t.block_height + 1, status = '{confirmed}', updated_at = NOW() \
+               FROM (SELECT txid FROM transactions \
+                      WHERE network = $1 AND status = '{confirming}' AND block_height <= $3 \
+                      LIMIT $4) m \
+              WHERE t.txid = m.txid AND t.network = $1",
+            confirmed = TransactionStatus::Confirmed,
+            confirming = TransactionStatus::Confirming,
+        );
+
+        let mut promoted = 0;
+        loop {
+            let res = self
+                .conn
+                .execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    &sql,
+                    [
+                        network.into(),
+                        (tip as i64).into(),
+                        matured_height.into(),
+                        (batch_size.max(1) as i64).into(),
+                    ],
+                ))
+                .await?;
+            promoted += res.rows_affected();
+            if res.rows_affected() < batch_size.max(1) {
+                return Ok(promoted);
+            }
+        }
+    }
+
     /// Convert a database entity to a domain model
     fn to_domain_model(&self, entity: transactions::Model) -> Transaction {
         Transaction::new(
@@ -154,7 +216,9 @@ impl TransactionRepository {
             entity.charm,
             entity.updated_at,
             entity.confirmations,
-            entity.status,
+            entity.status.parse().unwrap_or_else(|_| {
+                TransactionStatus::for_confirmations(entity.confirmations)
+            }),
             entity.blockchain,
             entity.network,
         )
@@ -1,6 +1,7 @@
 use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
 use std::fmt;
 
+use crate::domain::services::address_extractor::AddressExtractor;
 use crate::infrastructure::persistence::error::DbError;
 
 /// Repository for address_utxos table operations
@@ -19,7 +20,8 @@ impl fmt::Debug for UtxoRepository {
 /// A single UTXO to be inserted.
 /// `source` is the provenance label persisted in `address_utxos.source`
 /// (one of `node`, `maestro`, `backfill`). The indexer always writes
-/// `node` and overrides snapshots from external providers.
+/// `node` and overrides snapshots from external providers. `possible_charm`
+/// flags an output of a spell-like tx (see `utxo_indexer::looks_like_spell`).
 #[derive(Debug, Clone)]
 pub struct UtxoInsert {
     pub txid: String,
@@ -30,6 +32,7 @@ pub struct UtxoInsert {
     pub block_height: i32,
     pub network: String,
     pub source: String,
+    pub possible_charm: bool,
 }
 
 impl UtxoRepository {
@@ -51,15 +54,16 @@ impl UtxoRepository {
                 .iter()
                 .map(|u| {
                     format!(
-                        "('{}', {}, '{}', '{}', {}, '{}', {}, '{}')",
+                        "('{}', {}, '{}', '{}', {}, '{}', {}, '{}', {})",
                         u.txid.replace('\'', "''"),
                         u.vout,
                         u.network.replace('\'', "''"),
-                        u.address.replace('\'', "''"),
+                        AddressExtractor::canonical(&u.address).replace('\'', "''"),
                         u.value,
                         u.script_pubkey.replace('\'', "''"),
                         u.block_height,
                         u.source.replace('\'', "''"),
+                        u.possible_charm,
                     )
                 })
                 .collect();
@@ -67,14 +71,15 @@ impl UtxoRepository {
             // Indexer is authoritative: overwrite external snapshots
             // (source != 'node') with the on-chain value.
             let sql = format!(
-                "INSERT INTO address_utxos (txid, vout, network, address, value, script_pubkey, block_height, source) \
+                "INSERT INTO address_utxos (txid, vout, network, address, value, script_pubkey, block_height, source, possible_charm) \
                  VALUES {} \
                  ON CONFLICT (txid, vout, network) DO UPDATE SET \
                    address = EXCLUDED.address, \
                    value = EXCLUDED.value, \
                    script_pubkey = EXCLUDED.script_pubkey, \
                    block_height = EXCLUDED.block_height, \
-                   source = EXCLUDED.source \
+                   source = EXCLUDED.source, \
+                   possible_charm = EXCLUDED.possible_charm \
                  WHERE address_utxos.source IS DISTINCT FROM 'node'",
                 values.join(", ")
             );
@@ -91,11 +96,14 @@ impl UtxoRepository {
         Ok(total_inserted)
     }
 
-    /// Delete spent UTXOs (from block inputs)
-    /// Each item is (txid, vout) of the previous output being spent
+    /// Delete spent UTXOs (from block inputs) and record each one in
+    /// `address_utxo_spends` with its spender, so wallet change feeds can
+    /// report the removal. Each item is (txid, vout, spending_txid);
+    /// outpoints not tracked here are ignored.
     pub async fn delete_spent_batch(
         &self,
-        spent: &[(String, i32)],
+        spent: &[(String, i32, String)],
+        spent_height: i32,
         network: &str,
     ) -> Result<usize, DbError> {
         if spent.is_empty() {
@@ -104,17 +112,38 @@ impl UtxoRepository {
 
         let mut total_deleted = 0usize;
         for chunk in spent.chunks(500) {
-            let conditions: Vec<String> = chunk
+            let values: Vec<String> = chunk
                 .iter()
-                .map(|(txid, vout)| {
-                    format!("(txid = '{}' AND vout = {})", txid.replace('\'', "''"), vout)
+                .map(|(txid, vout, spending_txid)| {
+                    format!(
+                        "('{}', {}, '{}')",
+                        txid.replace('\'', "''"),
+                        vout,
+                        spending_txid.replace('\'', "''")
+                    )
                 })
                 .collect();
 
+            // The delete and the spend record commit as one statement.
             let sql = format!(
-                "DELETE FROM address_utxos WHERE network = '{}' AND ({})",
-                network.replace('\'', "''"),
-                conditions.join(" OR ")
+                "WITH spent (txid, vout, spending_txid) AS (VALUES {values}), \
+                 gone AS ( \
+                   DELETE FROM address_utxos u USING spent s \
+                    WHERE u.network = '{network}' AND u.txid = s.txid AND u.vout = s.vout \
+                RETURNING u.txid, u.vout, u.network, u.address, u.value, u.block_height, \
+                          s.spending_txid) \
+                 INSERT INTO address_utxo_spends \
+                   (txid, vout, network, address, value, block_height, spending_txid, spent_height) \
+                 SELECT txid, vout, network, address, value, block_height, spending_txid, {spent_height} \
+                   FROM gone \
+                 ON CONFLICT (txid, vout, network) DO UPDATE SET \
+                   address = EXCLUDED.address, \
+                   value = EXCLUDED.value, \
+                   block_height = EXCLUDED.block_height, \
+                   spending_txid = EXCLUDED.spending_txid, \
+                   spent_height = EXCLUDED.spent_height",
+                values = values.join(", "),
+                network = network.replace('\'', "''"),
             );
 
             let result = self
@@ -129,4 +158,47 @@ impl UtxoRepository {
         Ok(total_deleted)
     }
 
+    /// Set `has_charms` and `charm_app_ids` of the UTXOs created by `txids`
+    /// from the charms stored for their outpoints. Run once those txs'
+    /// charms are saved; until then a new row keeps `has_charms` NULL.
+    pub async fn annotate_charms(&self, txids: &[String], network: &str) -> Result<usize, DbError> {
+        if txids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut total_annotated = 0usize;
+        for chunk in txids.chunks(500) {
+            let ids: Vec<String> = chunk
+                .iter()
+                .map(|txid| format!("'{}'", txid.replace('\'', "''")))
+                .collect();
+
+            let sql = format!(
+                "WITH found AS ( \
+                   SELECT u.txid, u.vout, \
+                          (SELECT jsonb_agg(DISTINCT c.app_id ORDER BY c.app_id) \
+                             FROM charms c \
+                            WHERE c.txid = u.txid AND c.vout = u.vout AND c.network = u.network) AS app_ids \
+                     FROM address_utxos u \
+                    WHERE u.network = '{network}' AND u.txid IN ({ids})) \
+                 UPDATE address_utxos u \
+                    SET has_charms = f.app_ids IS NOT NULL, \
+                        charm_app_ids = COALESCE(f.app_ids, '[]'::jsonb) \
+                   FROM found f \
+                  WHERE u.network = '{network}' AND u.txid = f.txid AND u.vout = f.vout",
+                network = network.replace('\'', "''"),
+                ids = ids.join(", ")
+            );
+
+            let result = self
+                .conn
+                .execute(Statement::from_string(DbBackend::Postgres, sql))
+                .await
+                .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+            total_annotated += result.rows_affected() as usize;
+        }
+
+        Ok(total_annotated)
+    }
 }
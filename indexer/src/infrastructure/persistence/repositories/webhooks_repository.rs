@@ -0,0 +1,278 @@
+//! Repository for webhook_subscriptions / webhook_deliveries / webhook_delivery_attempts
+//! Subscriptions are managed by the API; the indexer queues and delivers events.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+use crate::infrastructure::persistence::error::DbError;
+
+/// A queued delivery claimed for sending, with its endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingDelivery {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub event_id: String,
+    pub event_type: String,
+    /// The JSON body, exactly as it is signed and sent.
+    pub payload: String,
+    /// Attempts made before this one.
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct WebhooksRepository {
+    conn: DatabaseConnection,
+}
+
+impl WebhooksRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    async fn has_active_subscriptions(&self) -> Result<bool, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_string(
+                DbBackend::Postgres,
+                "SELECT EXISTS (SELECT 1 FROM webhook_subscriptions WHERE active) AS any"
+                    .to_string(),
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        Ok(row
+            .and_then(|r| r.try_get::<bool>("", "any").ok())
+            .unwrap_or(false))
+    }
+
+    /// Queue the events of a persisted block for every active subscription
+    /// matching their type and app_id. `spending_txids` are the block's
+    /// non-coinbase txids, which find the charms it spent. Queueing the same
+    /// block again is a no-op. Returns the number of deliveries queued.
+    pub async fn enqueue_block_events(
+        &self,
+        network: &str,
+        height: i32,
+        spending_txids: &[String],
+    ) -> Result<u64, DbError> {
+        if !self.has_active_subscriptions().await? {
+            return Ok(0);
+        }
+        let spenders = if spending_txids.is_empty() {
+            "ARRAY[]::text[]".to_string()
+        } else {
+            format!(
+                "ARRAY[{}]",
+                spending_txids
+                    .iter()
+                    .map(|t| format!("'{}'", t.replace('\'', "''")))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        let sql = format!(
+            "WITH created AS ( \
+                 SELECT txid, vout, app_id, asset_type, address, amount FROM charms \
+                  WHERE network = $1 AND block_height = $2), \
+             spent AS ( \
+                 SELECT txid, vout, app_id, asset_type, address, amount, spending_txid FROM charms \
+                  WHERE network = $1 AND spent AND spending_txid = ANY({spenders})), \
+             supply AS ( \
+                 SELECT app_id, SUM(delta) AS delta FROM ( \
+                     SELECT app_id, amount AS delta FROM created \
+                     UNION ALL SELECT app_id, -amount FROM spent) d \
+                  GROUP BY app_id HAVING SUM(delta) <> 0), \
+             events (event_id, event_type, app_id, data) AS ( \
+                 SELECT 'charm_created:' || txid || ':' || vout || ':' || app_id, 'charm_created', app_id, \
+                        jsonb_build_object('txid', txid, 'vout', vout, 'app_id', app_id, \
+                                           'asset_type', asset_type, 'address', address, 'amount', amount) \
+                   FROM created \
+                 UNION ALL \
+                 SELECT 'charm_spent:' || txid || ':' || vout || ':' || app_id, 'charm_spent', app_id, \
+                        jsonb_build_object('txid', txid, 'vout', vout, 'app_id', app_id, \
+                                           'asset_type', asset_type, 'address', address, 'amount', amount, \
+                                           'spending_txid', spending_txid) \
+                   FROM spent \
+                 UNION ALL \
+                 SELECT 'asset_supply_changed:' || s.app_id || ':' || $2, 'asset_supply_changed', s.app_id, \
+                        jsonb_build_object('app_id', s.app_id, 'delta', s.delta, \
+                                           'total_supply', (SELECT a.total_supply FROM assets a \
+                                                             WHERE a.app_id = s.app_id AND a.network = $1 \
+                                                             LIMIT 1)) \
+                   FROM supply s \
+                 UNION ALL \
+                 SELECT CASE WHEN o.status = 'open' THEN 'dex_order_created:' ELSE 'dex_order_filled:' END \
+                          || o.order_id, \
+                        CASE WHEN o.status = 'open' THEN 'dex_order_created' ELSE 'dex_order_filled' END, \
+                        o.asset_app_id, \
+                        jsonb_build_object('order_id', o.order_id, 'txid', o.txid, 'status', o.status, \
+                                           'side', o.side, 'maker', o.maker, 'platform', o.platform, \
+                                           'asset_app_id', o.asset_app_id, 'price_num', o.price_num, \
+                                           'price_den', o.price_den, 'amount', o.amount, \
+                                           'quantity', o.quantity, 'parent_order_id', o.parent_order_id) \
+                   FROM dex_orders o \
+                  WHERE o.network = $1 AND o.block_height = $2 \
+                    AND o.status IN ('open', 'partial', 'filled')) \
+             INSERT INTO webhook_deliveries \
+                    (subscription_id, event_id, event_type, network, block_height, payload) \
+             SELECT s.id, e.event_id, e.event_type, $1, $2, \
+                    jsonb_build_object('id', e.event_id, 'type', e.event_type, 'network', $1, \
+                                       'block_height', $2, 'app_id', e.app_id, 'data', e.data) \
+               FROM events e \
+               JOIN webhook_subscriptions s \
+                 ON s.active AND e.event_type = ANY(s.event_types) \
+                AND (s.app_id IS NULL OR s.app_id = e.app_id) \
+             ON CONFLICT (subscription_id, event_id) DO NOTHING"
+        );
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [network.into(), height.into()],
+            ))
+            .await
+            .map(|r| r.rows_affected())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Claim up to `limit` due deliveries of active subscriptions. Claimed
+    /// rows are pushed `lease_secs` into the future, so another replica
+    /// polling meanwhile skips them; a crashed sender's rows come back due.
+    pub async fn claim_due(
+        &self,
+        limit: u64,
+        lease_secs: f64,
+    ) -> Result<Vec<PendingDelivery>, DbError> {
+        let rows = self
+            .conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE webhook_deliveries d \
+                    SET next_attempt_at = NOW() + make_interval(secs => $2) \
+                   FROM webhook_subscriptions s \
+                  WHERE s.id = d.subscription_id \
+                    AND d.id IN (SELECT q.id FROM webhook_deliveries q \
+                                   JOIN webhook_subscriptions qs ON qs.id = q.subscription_id \
+                                  WHERE q.status = 'pending' AND q.next_attempt_at <= NOW() \
+                                    AND qs.active \
+                                  ORDER BY q.next_attempt_at, q.id \
+                                  LIMIT $1 \
+                                    FOR UPDATE OF q SKIP LOCKED) \
+              RETURNING d.id, d.subscription_id, d.event_id, d.event_type, \
+                        d.payload::text AS payload, d.attempts, s.url, s.secret",
+                [(limit as i64).into(), lease_secs.into()],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        let mut claimed = rows
+            .iter()
+            .map(|r| {
+                Ok(PendingDelivery {
+                    id: r.try_get("", "id")?,
+                    subscription_id: r.try_get("", "subscription_id")?,
+                    event_id: r.try_get("", "event_id")?,
+                    event_type: r.try_get("", "event_type")?,
+                    payload: r.try_get("", "payload")?,
+                    attempts: r.try_get("", "attempts")?,
+                    url: r.try_get("", "url")?,
+                    secret: r.try_get("", "secret")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sea_orm::DbErr>>()
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        claimed.sort_by_key(|d| d.id);
+        Ok(claimed)
+    }
+
+    /// Log one HTTP attempt. `status_code` is `None` when no response came.
+    pub async fn record_attempt(
+        &self,
+        delivery_id: i64,
+        attempt: i32,
+        status_code: Option<i32>,
+        error: Option<&str>,
+        duration_ms: i32,
+    ) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO webhook_delivery_attempts \
+                        (delivery_id, attempt, status_code, error, duration_ms) \
+                 VALUES ($1, $2, $3, $4, $5)",
+                [
+                    delivery_id.into(),
+                    attempt.into(),
+                    status_code.into(),
+                    error.map(str::to_string).into(),
+                    duration_ms.into(),
+                ],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Mark a delivery sent and reset its subscription's failure streak.
+    pub async fn mark_delivered(&self, delivery_id: i64) -> Result<(), DbError> {
+        self.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "WITH d AS ( \
+                     UPDATE webhook_deliveries \
+                        SET status = 'delivered', attempts = attempts + 1, \
+                            delivered_at = NOW(), last_error = NULL \
+                      WHERE id = $1 RETURNING subscription_id) \
+                 UPDATE webhook_subscriptions s SET consecutive_failures = 0 \
+                   FROM d WHERE s.id = d.subscription_id",
+                [delivery_id.into()],
+            ))
+            .await
+            .map(|_| ())
+            .map_err(|e| DbError::QueryError(e.to_string()))
+    }
+
+    /// Record a failed attempt: retry in `retry_in_secs`, or give the
+    /// delivery up when `None`. The subscription's failure streak grows and
+    /// it is deactivated once the streak reaches `disable_after`. Returns
+    /// true when this call deactivated it.
+    pub async fn mark_failed(
+        &self,
+        delivery_id: i64,
+        error: &str,
+        retry_in_secs: Option<f64>,
+        disable_after: i32,
+    ) -> Result<bool, DbError> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "WITH d AS ( \
+                     UPDATE webhook_deliveries \
+                        SET attempts = attempts + 1, last_error = $2, \
+                            status = CASE WHEN $3::float8 IS NULL THEN 'failed' ELSE 'pending' END, \
+                            next_attempt_at = CASE WHEN $3::float8 IS NULL THEN next_attempt_at \
+                                                   ELSE NOW() + make_interval(secs => $3::float8) END \
+                      WHERE id = $1 RETURNING subscription_id) \
+                 UPDATE webhook_subscriptions s \
+                    SET consecutive_failures = s.consecutive_failures + 1, \
+                        active = s.active AND s.consecutive_failures + 1 < $4, \
+                        disabled_at = CASE WHEN s.active AND s.consecutive_failures + 1 >= $4 \
+                                           THEN NOW() ELSE s.disabled_at END \
+                   FROM d \
+                  WHERE s.id = d.subscription_id \
+              RETURNING s.consecutive_failures = $4 AS disabled",
+                [
+                    delivery_id.into(),
+                    error.into(),
+                    retry_in_secs.into(),
+                    disable_after.into(),
+                ],
+            ))
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+        Ok(row
+            .and_then(|r| r.try_get::<bool>("", "disabled").ok())
+            .unwrap_or(false))
+    }
+}
@@ -1,6 +1,7 @@
 // Charms Indexer Library
 
 pub mod application;
+pub mod cli;
 pub mod config;
 pub mod domain;
 pub mod infrastructure;
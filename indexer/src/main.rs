@@ -5,15 +5,45 @@
 //! ## Usage
 //!
 //! ```bash
-//! cargo run --release
+//! cargo run --release                      # live indexing (same as `run`)
+//! cargo run --release -- check              # pre-flight checks only (same as `--check`)
+//! cargo run --release -- reindex --from <h> [--to <h>] [--network <name>] [--dry-run] [--retry-failed] [--force]
+//! cargo run --release -- verify [--network <name>]... [--tolerance N] [--sample N] [--fix]
+//! cargo run --release -- recompute-holders [--app-id <id>] [--network <name>]
+//! cargo run --release -- backfill-metadata [--network <name>]
+//! cargo run --release -- backfill-deploys [--network <name>]
+//! cargo run --release -- backfill-spell-outputs [--network <name>]
+//! cargo run --release -- backfill <name> [--restart] [--batch-size N] [--rows-per-sec N]
+//! cargo run --release -- backfill --list
+//! cargo run --release -- export-snapshot --dir <path> [--network <name>] [--without-raw]
+//! cargo run --release -- import-snapshot --dir <path> [--force]
 //! ```
+//!
+//! See `charms_indexer::cli` for the environment equivalents. Jobs exit 0 on
+//! success and 2 on error; `verify` exits 1 when a check is over tolerance,
+//! `reindex` when a block failed, `check` when a pre-flight check failed,
+//! `backfill` when interrupted before it finished.
 
 use charms_indexer::application::indexer::NetworkManager;
-use charms_indexer::config::AppConfig;
+use charms_indexer::application::maintenance::{
+    self, BackfillConfig, BackfillRunner, ExportOptions, ReindexOptions,
+};
+use charms_indexer::application::preflight;
+use charms_indexer::application::verify::{self, VerifyOptions};
+use charms_indexer::cli::{Cli, Command};
+use charms_indexer::config::{AppConfig, Sources};
+use charms_indexer::infrastructure::persistence::error::DbError;
+use charms_indexer::infrastructure::persistence::repositories::StatsHoldersRepository;
 use charms_indexer::infrastructure::persistence::{DbPool, Repositories};
 use charms_indexer::utils::{logging, metrics};
+use clap::Parser;
+use sea_orm::DatabaseConnection;
 
 fn main() {
+    // Load `.env` before parsing so flag env fallbacks can see it.
+    dotenv::dotenv().ok();
+    let command = Cli::parse().into_command().unwrap_or_else(|e| e.exit());
+
     // Build tokio runtime with 8MB worker thread stack (default 2MB)
     // Required for deep recursion in charms-data parsing
     let runtime = tokio::runtime::Builder::new_multi_thread()
@@ -23,28 +53,157 @@ fn main() {
         .build()
         .expect("Failed to build tokio runtime");
 
-    runtime.block_on(async_main());
+    let code = runtime.block_on(async_main(command));
+    if code != 0 {
+        std::process::exit(code);
+    }
 }
 
-async fn async_main() {
+async fn async_main(command: Command) -> i32 {
     logging::init_logger();
-    metrics::init();
 
-    let config = AppConfig::from_env();
+    match command {
+        Command::Run => {
+            metrics::init();
 
-    // Connect to database
-    let db_pool = match DbPool::new(&config).await {
-        Ok(pool) => pool,
-        Err(e) => {
-            logging::log_error(&format!("Failed to connect to database: {}", e));
-            return;
+            let config = AppConfig::from_env();
+            let db_pool = match DbPool::new(&config).await {
+                Ok(db_pool) => db_pool,
+                Err(e) => {
+                    logging::log_error(&format!("Failed to connect to database: {}", e));
+                    return 0;
+                }
+            };
+            if !startup_checks(&config, db_pool.get_connection()).await {
+                return 1;
+            }
+            let repositories = Repositories::from_pool(&db_pool);
+
+            // Unified indexer - uses block_status to determine what needs processing
+            run_production_indexer(config, repositories).await;
+            0
         }
-    };
+        Command::Check => run_check().await,
+        Command::Reindex {
+            from,
+            to,
+            network,
+            dry_run,
+            report,
+            parser_revision_lt,
+            batch_size,
+            max_attempts,
+            retry_failed,
+            force,
+        } => {
+            let opts = ReindexOptions {
+                network,
+                from,
+                to: to.unwrap_or(from),
+                dry_run,
+                report_path: report,
+                parser_revision_lt,
+                batch_size,
+                max_attempts,
+                retry_failed,
+                force,
+            };
+            run_reindex(opts).await
+        }
+        Command::Verify {
+            networks,
+            tolerance,
+            sample,
+            fix,
+        } => {
+            let opts = VerifyOptions {
+                networks,
+                tolerance,
+                fix,
+                sample,
+            };
+            run_verify(opts).await
+        }
+        Command::RecomputeHolders { app_id, network } => {
+            run_recompute_holders(&network, app_id.as_deref()).await
+        }
+        Command::BackfillMetadata { network } => run_backfill_metadata(network.as_deref()).await,
+        Command::BackfillAddresses { network } => run_backfill_addresses(network.as_deref()).await,
+        Command::BackfillDeploys { network } => run_backfill_deploys(network.as_deref()).await,
+        Command::BackfillSpellOutputs { network } => {
+            run_backfill_spell_outputs(network.as_deref()).await
+        }
+        Command::Backfill {
+            name,
+            list: _,
+            restart,
+            batch_size,
+            rows_per_sec,
+        } => match name {
+            // clap requires a name unless --list is given
+            Some(name) => {
+                let cfg = BackfillConfig {
+                    batch_size,
+                    rows_per_sec,
+                    ..BackfillConfig::default()
+                };
+                run_backfill(&name, restart, cfg).await
+            }
+            None => {
+                for backfill in maintenance::backfill::registry() {
+                    println!("{:<20} {}", backfill.name(), backfill.description());
+                }
+                0
+            }
+        },
+        Command::ExportSnapshot {
+            network,
+            dir,
+            without_raw,
+        } => {
+            run_export_snapshot(ExportOptions {
+                network,
+                dir,
+                include_raw: !without_raw,
+            })
+            .await
+        }
+        Command::ImportSnapshot { dir, force } => run_import_snapshot(&dir, force).await,
+    }
+}
 
+/// Shared setup for commands that need the full config: load `AppConfig`,
+/// open the pool and build the repositories.
+async fn bootstrap() -> Result<(AppConfig, Repositories), DbError> {
+    let config = AppConfig::from_env();
+    let db_pool = DbPool::new(&config).await?;
     let repositories = Repositories::from_pool(&db_pool);
+    Ok((config, repositories))
+}
 
-    // Unified indexer - uses block_status to determine what needs processing
-    run_production_indexer(config, repositories).await;
+/// Pre-flight before live indexing. Failures are logged as warnings, or
+/// stop startup (`false`) with `STARTUP_CHECK_FATAL=true`.
+async fn startup_checks(config: &AppConfig, conn: &DatabaseConnection) -> bool {
+    let report = preflight::run(config, Ok(conn)).await;
+    let fatal = config.indexer.startup_check_fatal;
+    for failure in report.failures() {
+        let message = format!(
+            "Pre-flight check {} failed ({}): {}",
+            failure.check,
+            failure.scope.as_deref().unwrap_or("database"),
+            failure.detail
+        );
+        if fatal {
+            logging::log_error(&message);
+        } else {
+            logging::log_warning(&message);
+        }
+    }
+    if fatal && !report.passed() {
+        logging::log_error("Refusing to start (STARTUP_CHECK_FATAL=true)");
+        return false;
+    }
+    true
 }
 
 /// Production indexer - runs indefinitely processing new blocks
@@ -52,6 +211,15 @@ async fn run_production_indexer(
     config: AppConfig,
     repositories: charms_indexer::infrastructure::persistence::Repositories,
 ) {
+    let snapshot = config.snapshot(&Sources::from_env());
+    if let Err(e) = repositories
+        .indexer_replicas
+        .record_config_snapshot(&config.indexer.instance_id, &snapshot)
+        .await
+    {
+        logging::log_warning(&format!("Failed to record config snapshot: {}", e));
+    }
+
     let mut network_manager = NetworkManager::new(config.clone());
 
     match network_manager.initialize(&repositories).await
@@ -78,3 +246,312 @@ async fn run_production_indexer(
         }
     }
 }
+
+async fn run_reindex(opts: ReindexOptions) -> i32 {
+    let (config, repositories) = match bootstrap().await {
+        Ok(setup) => setup,
+        Err(e) => {
+            logging::log_error(&format!("Failed to connect to database: {}", e));
+            return 2;
+        }
+    };
+
+    match maintenance::reindex(&config, &repositories, &opts).await {
+        Ok(summary) => {
+            println!(
+                "reindex {} {}..={}: {} block(s), {} stored charm(s), {} holder row(s) rebuilt{}",
+                opts.network,
+                opts.from,
+                opts.to,
+                summary.blocks,
+                summary.charms_in_range,
+                summary.holders_rebuilt,
+                match &summary.run_id {
+                    Some(run_id) => format!(" (run {})", run_id),
+                    None => " (dry run)".to_string(),
+                }
+            );
+            if let Some(report) = &summary.report {
+                print!("{}", report.render());
+            }
+            if !summary.failed.is_empty() || !summary.sidelined.is_empty() {
+                println!(
+                    "failed: {:?}; sidelined: {:?} (rerun with --retry-failed to attempt them again)",
+                    summary.failed, summary.sidelined
+                );
+            }
+            if summary.failed.is_empty() {
+                0
+            } else {
+                1
+            }
+        }
+        Err(e) => {
+            logging::log_error(&format!("reindex failed: {}", e));
+            2
+        }
+    }
+}
+
+async fn run_check() -> i32 {
+    let config = AppConfig::from_env();
+    let conn = sea_orm::Database::connect(&config.database.url)
+        .await
+        .map_err(|e| e.to_string());
+    let report = preflight::run(&config, conn.as_ref().map_err(String::clone)).await;
+    print!("{}", report.render());
+    if report.passed() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Database-only jobs skip `AppConfig` (which insists on RPC settings for
+/// every enabled network) and connect with `DATABASE_URL` alone.
+async fn connect_database() -> Option<DatabaseConnection> {
+    let url = match std::env::var("DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            logging::log_error("DATABASE_URL environment variable is required");
+            return None;
+        }
+    };
+    match sea_orm::Database::connect(&url).await {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            logging::log_error(&format!("Failed to connect to database: {}", e));
+            None
+        }
+    }
+}
+
+async fn run_verify(opts: VerifyOptions) -> i32 {
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    match verify::run(&conn, &opts).await {
+        Ok(report) => {
+            print!("{}", report.render());
+            if report.passed() {
+                0
+            } else {
+                1
+            }
+        }
+        Err(e) => {
+            logging::log_error(&format!("verify failed: {}", e));
+            2
+        }
+    }
+}
+
+async fn run_recompute_holders(network: &str, app_id: Option<&str>) -> i32 {
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    match StatsHoldersRepository::new(conn)
+        .rebuild_from_charms(network, app_id)
+        .await
+    {
+        Ok(rows) => {
+            println!(
+                "recompute-holders {} {}: {} holder row(s) written",
+                network,
+                app_id.unwrap_or("(all assets)"),
+                rows
+            );
+            0
+        }
+        Err(e) => {
+            logging::log_error(&format!("recompute-holders failed: {}", e));
+            2
+        }
+    }
+}
+
+async fn run_backfill_metadata(network: Option<&str>) -> i32 {
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    match maintenance::backfill_metadata(&conn, network).await {
+        Ok(summary) => {
+            println!(
+                "backfill-metadata {}: {} NFT(s), {} token(s) updated",
+                network.unwrap_or("(all networks)"),
+                summary.nfts_updated,
+                summary.tokens_updated
+            );
+            0
+        }
+        Err(e) => {
+            logging::log_error(&format!("backfill-metadata failed: {}", e));
+            2
+        }
+    }
+}
+
+async fn run_backfill_addresses(network: Option<&str>) -> i32 {
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    match maintenance::backfill_addresses(&conn, network).await {
+        Ok(summary) => {
+            println!(
+                "backfill-addresses {}: {} charm(s) updated, {} unresolved",
+                network.unwrap_or("(all networks)"),
+                summary.updated,
+                summary.unresolved
+            );
+            0
+        }
+        Err(e) => {
+            logging::log_error(&format!("backfill-addresses failed: {}", e));
+            2
+        }
+    }
+}
+
+async fn run_backfill_deploys(network: Option<&str>) -> i32 {
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    match maintenance::backfill_deploys(&conn, network).await {
+        Ok(summary) => {
+            println!(
+                "backfill-deploys {}: {} asset(s) updated, {} unresolved",
+                network.unwrap_or("(all networks)"),
+                summary.updated,
+                summary.unresolved
+            );
+            0
+        }
+        Err(e) => {
+            logging::log_error(&format!("backfill-deploys failed: {}", e));
+            2
+        }
+    }
+}
+
+async fn run_backfill_spell_outputs(network: Option<&str>) -> i32 {
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    match maintenance::backfill_spell_outputs(&conn, network).await {
+        Ok(summary) => {
+            println!(
+                "backfill-spell-outputs {}: {} transaction(s) updated, {} unresolved",
+                network.unwrap_or("(all networks)"),
+                summary.updated,
+                summary.unresolved
+            );
+            0
+        }
+        Err(e) => {
+            logging::log_error(&format!("backfill-spell-outputs failed: {}", e));
+            2
+        }
+    }
+}
+
+/// Exits 0 when the backfill finished, 1 when Ctrl+C stopped it (rerun to
+/// resume) and 2 on error.
+async fn run_backfill(name: &str, restart: bool, cfg: BackfillConfig) -> i32 {
+    let Some(backfill) = maintenance::backfill::find(name) else {
+        logging::log_error(&format!(
+            "unknown backfill {} (see `backfill --list`)",
+            name
+        ));
+        return 2;
+    };
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let on_ctrl_c = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            on_ctrl_c.cancel();
+        }
+    });
+
+    match BackfillRunner::new(conn, cfg)
+        .run(backfill.as_ref(), restart, &cancel)
+        .await
+    {
+        Ok(summary) => {
+            println!(
+                "backfill {}: {} after key {:?}, {} row(s) in {} batch(es)",
+                summary.name,
+                if summary.finished { "done" } else { "paused" },
+                summary.last_key,
+                summary.rows_updated,
+                summary.batches
+            );
+            if summary.finished {
+                0
+            } else {
+                1
+            }
+        }
+        Err(e) => {
+            logging::log_error(&format!("backfill {} failed: {}", name, e));
+            2
+        }
+    }
+}
+
+async fn run_export_snapshot(opts: ExportOptions) -> i32 {
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    match maintenance::export_snapshot(&conn, &opts).await {
+        Ok((dir, manifest)) => {
+            let rows: u64 = manifest.tables.iter().map(|t| t.rows).sum();
+            println!(
+                "export-snapshot {} at {}: {} row(s) in {} table(s) -> {}",
+                manifest.network,
+                manifest.tip_height,
+                rows,
+                manifest.tables.len(),
+                dir.display()
+            );
+            0
+        }
+        Err(e) => {
+            logging::log_error(&format!("export-snapshot failed: {}", e));
+            2
+        }
+    }
+}
+
+async fn run_import_snapshot(dir: &std::path::Path, force: bool) -> i32 {
+    let Some(conn) = connect_database().await else {
+        return 2;
+    };
+
+    match maintenance::import_snapshot(&conn, dir, force).await {
+        Ok(manifest) => {
+            let rows: u64 = manifest.tables.iter().map(|t| t.rows).sum();
+            println!(
+                "import-snapshot {}: {} row(s) loaded, indexing resumes at {}",
+                manifest.network,
+                rows,
+                manifest.tip_height + 1
+            );
+            0
+        }
+        Err(e) => {
+            logging::log_error(&format!("import-snapshot failed: {}", e));
+            2
+        }
+    }
+}
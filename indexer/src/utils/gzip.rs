@@ -0,0 +1,259 @@
+//! Streaming gzip (RFC 1952) over miniz_oxide's raw deflate, used for
+//! snapshot files. Writes a single member with no optional header fields;
+//! reads anything `gzip` itself produces for a single member.
+
+use std::io::{self, Read, Write};
+
+use crc::{Crc, Digest, CRC_32_ISO_HDLC};
+use miniz_oxide::deflate::core::CompressorOxide;
+use miniz_oxide::inflate::stream::InflateState;
+use miniz_oxide::{DataFormat, MZError, MZFlush, MZStatus};
+
+const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+const METHOD_DEFLATE: u8 = 8;
+const FHCRC: u8 = 0x02;
+const FEXTRA: u8 = 0x04;
+const FNAME: u8 = 0x08;
+const FCOMMENT: u8 = 0x10;
+const CHUNK: usize = 64 * 1024;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("gzip: {}", msg))
+}
+
+pub struct GzipEncoder<W: Write> {
+    inner: W,
+    compressor: Box<CompressorOxide>,
+    crc: Digest<'static, u32>,
+    size: u32,
+    out: Vec<u8>,
+}
+
+impl<W: Write> GzipEncoder<W> {
+    /// `level` is the usual 0-9 deflate level.
+    pub fn new(mut inner: W, level: u8) -> io::Result<Self> {
+        // No mtime, unknown OS.
+        inner.write_all(&[MAGIC[0], MAGIC[1], METHOD_DEFLATE, 0, 0, 0, 0, 0, 0, 255])?;
+        let mut compressor = Box::<CompressorOxide>::default();
+        compressor.set_format_and_level(DataFormat::Raw, level);
+        Ok(Self {
+            inner,
+            compressor,
+            crc: CRC32.digest(),
+            size: 0,
+            out: vec![0; CHUNK],
+        })
+    }
+
+    fn deflate(&mut self, mut input: &[u8], flush: MZFlush) -> io::Result<()> {
+        loop {
+            let res = miniz_oxide::deflate::stream::deflate(
+                &mut self.compressor,
+                input,
+                &mut self.out,
+                flush,
+            );
+            input = &input[res.bytes_consumed..];
+            self.inner.write_all(&self.out[..res.bytes_written])?;
+            match res.status {
+                Ok(MZStatus::StreamEnd) => return Ok(()),
+                Ok(_) if input.is_empty() && flush == MZFlush::None => return Ok(()),
+                Ok(_) => {}
+                Err(MZError::Buf) if input.is_empty() && flush == MZFlush::None => return Ok(()),
+                Err(e) => return Err(invalid(&format!("deflate failed: {:?}", e))),
+            }
+        }
+    }
+
+    /// Flush the deflate stream and the trailer, returning the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.deflate(&[], MZFlush::Finish)?;
+        let crc = self.crc.finalize();
+        self.inner.write_all(&crc.to_le_bytes())?;
+        self.inner.write_all(&self.size.to_le_bytes())?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for GzipEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.crc.update(buf);
+        // ISIZE is the length modulo 2^32.
+        self.size = self.size.wrapping_add(buf.len() as u32);
+        self.deflate(buf, MZFlush::None)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub struct GzipDecoder<R: Read> {
+    inner: R,
+    state: Box<InflateState>,
+    crc: Digest<'static, u32>,
+    size: u32,
+    input: Vec<u8>,
+    pos: usize,
+    len: usize,
+    eof: bool,
+    done: bool,
+}
+
+impl<R: Read> GzipDecoder<R> {
+    /// Reads and checks the member header.
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0u8; 10];
+        inner.read_exact(&mut header)?;
+        if header[..2] != MAGIC || header[2] != METHOD_DEFLATE {
+            return Err(invalid("not a gzip file"));
+        }
+        let flags = header[3];
+        if flags & FEXTRA != 0 {
+            let mut len = [0u8; 2];
+            inner.read_exact(&mut len)?;
+            io::copy(
+                &mut (&mut inner).take(u16::from_le_bytes(len) as u64),
+                &mut io::sink(),
+            )?;
+        }
+        for flag in [FNAME, FCOMMENT] {
+            if flags & flag != 0 {
+                let mut byte = [0u8; 1];
+                loop {
+                    inner.read_exact(&mut byte)?;
+                    if byte[0] == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+        if flags & FHCRC != 0 {
+            inner.read_exact(&mut [0u8; 2])?;
+        }
+        Ok(Self {
+            inner,
+            state: InflateState::new_boxed(DataFormat::Raw),
+            crc: CRC32.digest(),
+            size: 0,
+            input: vec![0; CHUNK],
+            pos: 0,
+            len: 0,
+            eof: false,
+            done: false,
+        })
+    }
+
+    fn check_trailer(&mut self) -> io::Result<()> {
+        let mut trailer = [0u8; 8];
+        let buffered = (self.len - self.pos).min(8);
+        trailer[..buffered].copy_from_slice(&self.input[self.pos..self.pos + buffered]);
+        self.inner.read_exact(&mut trailer[buffered..])?;
+        let crc = std::mem::replace(&mut self.crc, CRC32.digest()).finalize();
+        if trailer[..4] != crc.to_le_bytes() {
+            return Err(invalid("CRC mismatch"));
+        }
+        if trailer[4..] != self.size.to_le_bytes() {
+            return Err(invalid("length mismatch"));
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for GzipDecoder<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() || self.done {
+            return Ok(0);
+        }
+        loop {
+            if self.pos == self.len && !self.eof {
+                self.len = self.inner.read(&mut self.input)?;
+                self.pos = 0;
+                self.eof = self.len == 0;
+            }
+            let res = miniz_oxide::inflate::stream::inflate(
+                &mut self.state,
+                &self.input[self.pos..self.len],
+                out,
+                MZFlush::None,
+            );
+            self.pos += res.bytes_consumed;
+            let written = res.bytes_written;
+            self.crc.update(&out[..written]);
+            self.size = self.size.wrapping_add(written as u32);
+            match res.status {
+                Ok(MZStatus::StreamEnd) => {
+                    self.done = true;
+                    self.check_trailer()?;
+                    return Ok(written);
+                }
+                Ok(_) | Err(MZError::Buf) if written > 0 => return Ok(written),
+                Ok(_) | Err(MZError::Buf) if self.eof && res.bytes_consumed == 0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "gzip: truncated stream",
+                    ))
+                }
+                Ok(_) | Err(MZError::Buf) => {}
+                Err(e) => return Err(invalid(&format!("inflate failed: {:?}", e))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzipEncoder::new(Vec::new(), 6).unwrap();
+        // Uneven writes exercise the streaming path.
+        for chunk in data.chunks(7_001) {
+            encoder.write_all(chunk).unwrap();
+        }
+        encoder.finish().unwrap()
+    }
+
+    fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        GzipDecoder::new(data)?.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn round_trips_multi_chunk_input() {
+        let data: Vec<u8> = (0..300_000u32)
+            .flat_map(|i| format!("txid{},{}\n", i % 977, i).into_bytes())
+            .collect();
+        let gz = compress(&data);
+        assert!(gz.len() < data.len() / 2);
+        assert_eq!(decompress(&gz).unwrap(), data);
+        assert_eq!(decompress(&compress(b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn reads_output_of_the_gzip_tool() {
+        // `printf 'hello snapshot\n' | gzip -n`
+        let gz = [
+            31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 203, 72, 205, 201, 201, 87, 40, 206, 75, 44, 40, 206,
+            200, 47, 225, 2, 0, 206, 211, 59, 229, 15, 0, 0, 0,
+        ];
+        assert_eq!(decompress(&gz).unwrap(), b"hello snapshot\n");
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupt_input() {
+        let gz = compress(b"charms,assets,spells\n".repeat(100).as_slice());
+        assert!(decompress(&gz[..gz.len() - 3]).is_err());
+
+        let mut corrupt = gz.clone();
+        let crc_at = corrupt.len() - 8;
+        corrupt[crc_at] ^= 0xff;
+        assert!(decompress(&corrupt).is_err());
+
+        assert!(decompress(b"plain,csv\n").is_err());
+    }
+}
@@ -100,3 +100,48 @@ pub fn reorg_detected(network: &str, depth: u64) {
     metrics::counter!("indexer_reorgs_total", "network" => network.to_string()).increment(1);
     metrics::histogram!("indexer_reorg_depth", "network" => network.to_string()).record(depth as f64);
 }
+
+/// Record one block's input amount lookups: txids served from the per-block
+/// cache and txids fetched from the database.
+pub fn input_amount_cache(network: &str, hits: u64, misses: u64) {
+    metrics::counter!("indexer_input_amount_cache_hits_total", "network" => network.to_string())
+        .increment(hits);
+    metrics::counter!("indexer_input_amount_cache_misses_total", "network" => network.to_string())
+        .increment(misses);
+}
+
+/// Record an HTTP request sent to the QuickNode provider (retries included).
+pub fn quicknode_request() {
+    metrics::counter!("indexer_quicknode_requests_total").increment(1);
+}
+
+/// Record a QuickNode response with HTTP 429.
+pub fn quicknode_rate_limited() {
+    metrics::counter!("indexer_quicknode_rate_limited_total").increment(1);
+}
+
+/// Record a QuickNode call re-sent after a 429.
+pub fn quicknode_retry() {
+    metrics::counter!("indexer_quicknode_retries_total").increment(1);
+}
+
+/// Record a charm output whose address could not be derived. `script_type`
+/// is the output's script kind (e.g. `"p2pk"`, `"multisig"`, `"nonstandard"`).
+pub fn charm_address_missing(network: &str, script_type: &str) {
+    metrics::counter!(
+        "indexer_charm_address_missing_total",
+        "network" => network.to_string(),
+        "script_type" => script_type.to_string()
+    )
+    .increment(1);
+}
+
+/// Record a verified spell whose outputs carry no charm; it is indexed as a
+/// transaction only.
+pub fn spell_without_charms(network: &str) {
+    metrics::counter!(
+        "indexer_spell_txs_without_charms_total",
+        "network" => network.to_string()
+    )
+    .increment(1);
+}
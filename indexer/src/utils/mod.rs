@@ -1,2 +1,5 @@
+pub mod gzip;
 pub mod logging;
 pub mod metrics;
+pub mod parser_stats;
+pub mod throttled_log;
@@ -0,0 +1,183 @@
+//! In-memory parser counters, persisted hourly into `parser_stats`.
+//!
+//! Block detection and the mempool processor `record` every transaction
+//! they run through the parser; the block processor `take`s a network's
+//! counts after each block and adds them to the current hour's bucket
+//! (mempool counts gathered since the previous block ride along). Counts
+//! whose write fails are `merge`d back and go out with the next block.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::domain::services::SpellFailure;
+
+/// Which path parsed the transaction. A confirmed tx seen in mempool first
+/// is counted under both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseSource {
+    Block,
+    Mempool,
+}
+
+impl ParseSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ParseSource::Block => "block",
+            ParseSource::Mempool => "mempool",
+        }
+    }
+}
+
+/// Counters of one (network, source); the columns of `parser_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParserCounts {
+    pub txs_scanned: u64,
+    pub envelopes_found: u64,
+    pub parsed: u64,
+    pub no_envelope: u64,
+    pub cbor_error: u64,
+    pub version_unsupported: u64,
+    pub verification_failed: u64,
+}
+
+impl ParserCounts {
+    /// Count one tx: `Ok` for a parsed spell, else why it yielded no charm.
+    pub fn add(&mut self, outcome: Result<(), SpellFailure>) {
+        self.txs_scanned += 1;
+        match outcome {
+            Ok(()) => self.parsed += 1,
+            Err(SpellFailure::NoEnvelope) => self.no_envelope += 1,
+            Err(SpellFailure::CborError) => self.cbor_error += 1,
+            Err(SpellFailure::VersionUnsupported(_)) => self.version_unsupported += 1,
+            Err(SpellFailure::VerificationFailed) => self.verification_failed += 1,
+        }
+        if outcome != Err(SpellFailure::NoEnvelope) {
+            self.envelopes_found += 1;
+        }
+    }
+
+    pub fn merge(&mut self, other: &ParserCounts) {
+        self.txs_scanned += other.txs_scanned;
+        self.envelopes_found += other.envelopes_found;
+        self.parsed += other.parsed;
+        self.no_envelope += other.no_envelope;
+        self.cbor_error += other.cbor_error;
+        self.version_unsupported += other.version_unsupported;
+        self.verification_failed += other.verification_failed;
+    }
+}
+
+#[derive(Default)]
+pub struct ParserStats {
+    counts: Mutex<HashMap<(String, ParseSource), ParserCounts>>,
+}
+
+/// The process-wide accumulator.
+pub fn global() -> &'static ParserStats {
+    static STATS: OnceLock<ParserStats> = OnceLock::new();
+    STATS.get_or_init(ParserStats::default)
+}
+
+impl ParserStats {
+    pub fn record(&self, network: &str, source: ParseSource, outcome: Result<(), SpellFailure>) {
+        self.lock()
+            .entry((network.to_string(), source))
+            .or_default()
+            .add(outcome);
+    }
+
+    /// Add `counts` back, for a write that failed.
+    pub fn merge(&self, network: &str, source: ParseSource, counts: &ParserCounts) {
+        self.lock()
+            .entry((network.to_string(), source))
+            .or_default()
+            .merge(counts);
+    }
+
+    /// Remove and return the counts of `network`, per source.
+    pub fn take(&self, network: &str) -> Vec<(ParseSource, ParserCounts)> {
+        let mut taken = Vec::new();
+        self.lock().retain(|(n, source), counts| {
+            let keep = n != network;
+            if !keep {
+                taken.push((*source, *counts));
+            }
+            keep
+        });
+        taken
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, ParseSource), ParserCounts>> {
+        self.counts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcomes_land_in_their_counters() {
+        let mut counts = ParserCounts::default();
+        counts.add(Ok(()));
+        counts.add(Err(SpellFailure::NoEnvelope));
+        counts.add(Err(SpellFailure::NoEnvelope));
+        counts.add(Err(SpellFailure::CborError));
+        counts.add(Err(SpellFailure::VersionUnsupported(23)));
+        counts.add(Err(SpellFailure::VerificationFailed));
+        assert_eq!(
+            counts,
+            ParserCounts {
+                txs_scanned: 6,
+                envelopes_found: 4,
+                parsed: 1,
+                no_envelope: 2,
+                cbor_error: 1,
+                version_unsupported: 1,
+                verification_failed: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn take_drains_one_network_and_merge_restores() {
+        let stats = ParserStats::default();
+        stats.record("mainnet", ParseSource::Block, Ok(()));
+        stats.record(
+            "mainnet",
+            ParseSource::Mempool,
+            Err(SpellFailure::CborError),
+        );
+        stats.record(
+            "testnet4",
+            ParseSource::Block,
+            Err(SpellFailure::NoEnvelope),
+        );
+
+        let mut taken = stats.take("mainnet");
+        taken.sort_by_key(|(source, _)| source.as_str());
+        assert_eq!(taken.len(), 2);
+        assert_eq!(taken[0].0, ParseSource::Block);
+        assert_eq!(taken[0].1.parsed, 1);
+        assert_eq!(taken[1].1.cbor_error, 1);
+        assert!(stats.take("mainnet").is_empty());
+
+        stats.merge("mainnet", ParseSource::Block, &taken[0].1);
+        stats.record("mainnet", ParseSource::Block, Ok(()));
+        let again = stats.take("mainnet");
+        assert_eq!(
+            again,
+            [(
+                ParseSource::Block,
+                ParserCounts {
+                    txs_scanned: 2,
+                    envelopes_found: 2,
+                    parsed: 2,
+                    ..ParserCounts::default()
+                }
+            )]
+        );
+
+        assert_eq!(stats.take("testnet4")[0].1.no_envelope, 1);
+    }
+}
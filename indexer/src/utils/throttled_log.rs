@@ -0,0 +1,243 @@
+//! Deduplicated warnings for per-row failures.
+//!
+//! When something systemic breaks (a column missing after a partial
+//! migration, a constraint every row violates) the same warning fires for
+//! every row of every block. `ThrottledLogger` logs the first occurrence of
+//! a (category, key prefix) pair, then counts repeats for `window` and
+//! reports them as one "suppressed N similar" line: on the next occurrence
+//! after the window, or from `flush`, which the block processor calls after
+//! each block.
+//!
+//! The key is usually the error text: rows failing for the same reason
+//! share a slot whatever row they are about.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Characters of the key that tell two failures apart.
+const KEY_PREFIX_LEN: usize = 80;
+
+/// Default suppression window of the process-wide logger.
+const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// What to do with one occurrence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Log it; `suppressed` repeats of the previous window went unlogged.
+    Log { suppressed: u64 },
+    /// A repeat within the window: counted, not logged.
+    Suppress,
+}
+
+/// Repeats held back for one (category, key prefix), see `flush_at`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppressed {
+    pub category: &'static str,
+    pub key: String,
+    pub count: u64,
+}
+
+struct Slot {
+    since: Instant,
+    suppressed: u64,
+}
+
+pub struct ThrottledLogger {
+    window: Duration,
+    slots: Mutex<HashMap<(&'static str, String), Slot>>,
+}
+
+/// The process-wide logger, with a one-minute window.
+pub fn global() -> &'static ThrottledLogger {
+    static LOGGER: OnceLock<ThrottledLogger> = OnceLock::new();
+    LOGGER.get_or_init(|| ThrottledLogger::new(DEFAULT_WINDOW))
+}
+
+fn key_prefix(key: &str) -> String {
+    key.chars().take(KEY_PREFIX_LEN).collect()
+}
+
+impl ThrottledLogger {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Log `message` as a warning unless `(category, key)` was logged within
+    /// the window. `message` is only built when it is logged.
+    pub fn warn(&self, category: &'static str, key: &str, message: impl FnOnce() -> String) {
+        match self.record_at(Instant::now(), category, key) {
+            Decision::Log { suppressed } => {
+                if suppressed > 0 {
+                    tracing::warn!(
+                        category,
+                        suppressed,
+                        "suppressed {} similar {} warnings",
+                        suppressed,
+                        category
+                    );
+                }
+                tracing::warn!(category, "{}", message());
+            }
+            Decision::Suppress => {}
+        }
+    }
+
+    /// Report the repeats of every window that has ended.
+    pub fn flush(&self) {
+        for s in self.flush_at(Instant::now()) {
+            tracing::warn!(
+                category = s.category,
+                suppressed = s.count,
+                "suppressed {} similar {} warnings ({})",
+                s.count,
+                s.category,
+                s.key
+            );
+        }
+    }
+
+    /// Decide for an occurrence at `now`.
+    pub fn record_at(&self, now: Instant, category: &'static str, key: &str) -> Decision {
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        match slots.get_mut(&(category, key_prefix(key))) {
+            Some(slot) if now.duration_since(slot.since) < self.window => {
+                slot.suppressed += 1;
+                Decision::Suppress
+            }
+            Some(slot) => {
+                let suppressed = slot.suppressed;
+                *slot = Slot {
+                    since: now,
+                    suppressed: 0,
+                };
+                Decision::Log { suppressed }
+            }
+            None => {
+                slots.insert(
+                    (category, key_prefix(key)),
+                    Slot {
+                        since: now,
+                        suppressed: 0,
+                    },
+                );
+                Decision::Log { suppressed: 0 }
+            }
+        }
+    }
+
+    /// Drop the slots whose window ended before `now`, returning the ones
+    /// that held back repeats.
+    pub fn flush_at(&self, now: Instant) -> Vec<Suppressed> {
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = Vec::new();
+        slots.retain(|(category, key), slot| {
+            if now.duration_since(slot.since) < self.window {
+                return true;
+            }
+            if slot.suppressed > 0 {
+                out.push(Suppressed {
+                    category,
+                    key: key.clone(),
+                    count: slot.suppressed,
+                });
+            }
+            false
+        });
+        out.sort_by(|a, b| (a.category, &a.key).cmp(&(b.category, &b.key)));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn repeats_are_counted_and_reported_with_the_next_occurrence() {
+        let logger = ThrottledLogger::new(WINDOW);
+        let t0 = Instant::now();
+        let err = "column \"locked_supply\" does not exist";
+
+        assert_eq!(
+            logger.record_at(t0, "asset-insert", err),
+            Decision::Log { suppressed: 0 }
+        );
+        for i in 1..=5 {
+            assert_eq!(
+                logger.record_at(t0 + Duration::from_secs(i), "asset-insert", err),
+                Decision::Suppress
+            );
+        }
+        // Another category, or another error, has its own slot.
+        assert_eq!(
+            logger.record_at(t0, "holder-overflow", err),
+            Decision::Log { suppressed: 0 }
+        );
+        assert_eq!(
+            logger.record_at(t0, "asset-insert", "duplicate key"),
+            Decision::Log { suppressed: 0 }
+        );
+
+        assert_eq!(
+            logger.record_at(t0 + WINDOW, "asset-insert", err),
+            Decision::Log { suppressed: 5 }
+        );
+        assert_eq!(
+            logger.record_at(t0 + WINDOW, "asset-insert", err),
+            Decision::Suppress
+        );
+    }
+
+    #[test]
+    fn keys_are_compared_by_prefix() {
+        let logger = ThrottledLogger::new(WINDOW);
+        let t0 = Instant::now();
+        let base = "x".repeat(KEY_PREFIX_LEN);
+        logger.record_at(t0, "asset-insert", &format!("{base} row 1"));
+        assert_eq!(
+            logger.record_at(t0, "asset-insert", &format!("{base} row 2")),
+            Decision::Suppress
+        );
+    }
+
+    #[test]
+    fn flush_summarizes_ended_windows_only() {
+        let logger = ThrottledLogger::new(WINDOW);
+        let t0 = Instant::now();
+        logger.record_at(t0, "asset-insert", "boom");
+        logger.record_at(t0, "asset-insert", "boom");
+        logger.record_at(t0, "asset-insert", "boom");
+        logger.record_at(t0, "nft-reference", "quiet");
+        logger.record_at(t0 + Duration::from_secs(30), "holder-overflow", "late");
+        logger.record_at(t0 + Duration::from_secs(30), "holder-overflow", "late");
+
+        assert!(logger.flush_at(t0 + Duration::from_secs(59)).is_empty());
+        assert_eq!(
+            logger.flush_at(t0 + WINDOW),
+            [Suppressed {
+                category: "asset-insert",
+                key: "boom".to_string(),
+                count: 2,
+            }]
+        );
+        // Flushed slots start over; the later window is still open.
+        assert_eq!(
+            logger.record_at(t0 + WINDOW, "asset-insert", "boom"),
+            Decision::Log { suppressed: 0 }
+        );
+        assert_eq!(
+            logger.flush_at(t0 + Duration::from_secs(90)),
+            [Suppressed {
+                category: "holder-overflow",
+                key: "late".to_string(),
+                count: 1,
+            }]
+        );
+    }
+}
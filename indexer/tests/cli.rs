@@ -0,0 +1,76 @@
+//! `--help` / `--version` output of the `charms-indexer` binary. These run
+//! the real executable but never reach the database.
+
+use std::process::Command;
+
+fn indexer(args: &[&str]) -> (bool, String) {
+    let out = Command::new(env!("CARGO_BIN_EXE_charms-indexer"))
+        .args(args)
+        .env_remove("VERIFY_MODE")
+        .env_remove("REINDEX_MODE")
+        .env_remove("RECOMPUTE_HOLDERS")
+        .env_remove("BACKFILL_METADATA")
+        .env_remove("BACKFILL_DEPLOYS")
+        .env_remove("EXPORT_SNAPSHOT")
+        .env_remove("IMPORT_SNAPSHOT")
+        .output()
+        .expect("failed to spawn charms-indexer");
+    (
+        out.status.success(),
+        String::from_utf8_lossy(&out.stdout).into_owned(),
+    )
+}
+
+#[test]
+fn version_prints_package_version() {
+    let (ok, stdout) = indexer(&["--version"]);
+    assert!(ok);
+    assert_eq!(
+        stdout.trim(),
+        format!("charms-indexer {}", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[test]
+fn help_lists_every_subcommand() {
+    let (ok, stdout) = indexer(&["--help"]);
+    assert!(ok);
+    for subcommand in [
+        "run",
+        "reindex",
+        "verify",
+        "recompute-holders",
+        "backfill-metadata",
+        "backfill-deploys",
+        "export-snapshot",
+        "import-snapshot",
+    ] {
+        assert!(
+            stdout
+                .lines()
+                .any(|l| l.trim_start().starts_with(subcommand)),
+            "`{}` missing from --help:\n{}",
+            subcommand,
+            stdout
+        );
+    }
+}
+
+#[test]
+fn subcommand_help_shows_flags_and_env_names() {
+    let (ok, stdout) = indexer(&["reindex", "--help"]);
+    assert!(ok);
+    for flag in ["--from", "--to", "--network", "--dry-run", "REINDEX_FROM"] {
+        assert!(stdout.contains(flag), "`{}` missing:\n{}", flag, stdout);
+    }
+
+    let (ok, stdout) = indexer(&["verify", "--help"]);
+    assert!(ok);
+    assert!(stdout.contains("--fix"));
+}
+
+#[test]
+fn unknown_subcommand_is_rejected() {
+    let (ok, _) = indexer(&["frobnicate"]);
+    assert!(!ok);
+}
@@ -37,13 +37,21 @@ impl TestDb {
 const SCHEMA: &str = include_str!("../fixtures/schema.sql");
 
 async fn apply_schema(conn: &DatabaseConnection) {
-    for raw in SCHEMA.split(';') {
+    // Comments go first, so a `;` in one can't split a statement.
+    let schema: Vec<&str> = SCHEMA
+        .lines()
+        .map(|line| line.split("--").next().unwrap_or(""))
+        .collect();
+    for raw in schema.join("\n").split(';') {
         let stmt = raw.trim();
         if stmt.is_empty() {
             continue;
         }
-        conn.execute(Statement::from_string(DbBackend::Postgres, stmt.to_string()))
-            .await
-            .unwrap_or_else(|e| panic!("apply schema (stmt: {stmt:.60}…): {e}"));
+        conn.execute(Statement::from_string(
+            DbBackend::Postgres,
+            stmt.to_string(),
+        ))
+        .await
+        .unwrap_or_else(|e| panic!("apply schema (stmt: {stmt:.60}…): {e}"));
     }
 }
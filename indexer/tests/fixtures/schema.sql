@@ -19,9 +19,41 @@ CREATE TABLE charms (
     mempool_detected_at TIMESTAMPTZ,
     tags                TEXT,
     verified            BOOLEAN     NOT NULL DEFAULT TRUE,
+    block_hash          TEXT,
+    tx_ordinal          INTEGER,
+    is_placeholder      BOOLEAN     NOT NULL DEFAULT FALSE,
+    spending_txid       TEXT,
+    confirmation_delay_secs INTEGER,
+    indexer_version     TEXT,
+    parser_revision     INTEGER,
+    reindex_run_id      TEXT,
+    operation           TEXT        CHECK (operation IN ('mint', 'transfer', 'burn')),
+    moderation_status   TEXT        NOT NULL DEFAULT 'visible',
+    spent_height        INTEGER,
+    block_time          TIMESTAMP,
     -- Composite PK including app_id supports multi-token UTXOs (a single
-    -- output can carry N distinct charm tokens, one row per token).
-    PRIMARY KEY (txid, vout, app_id)
+    -- output can carry N distinct charm tokens, one row per token), and
+    -- network keeps mainnet and testnet4 rows for the same txid apart.
+    PRIMARY KEY (txid, vout, app_id, network)
+);
+
+-- Spent charms moved out by the archiver, with the same columns.
+CREATE TABLE charms_archive (
+    LIKE charms INCLUDING DEFAULTS INCLUDING CONSTRAINTS,
+    PRIMARY KEY (txid, vout, app_id, network)
+);
+
+CREATE TABLE spells (
+    txid          TEXT      NOT NULL,
+    block_height  INTEGER   NOT NULL,
+    data          JSONB     NOT NULL DEFAULT '{}'::jsonb,
+    date_created  TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    asset_type    TEXT      NOT NULL DEFAULT 'spell',
+    blockchain    TEXT      NOT NULL DEFAULT 'Bitcoin',
+    network       TEXT      NOT NULL DEFAULT 'testnet4',
+    indexer_version TEXT,
+    parser_revision INTEGER,
+    PRIMARY KEY (txid, network)
 );
 
 CREATE TABLE transactions (
@@ -37,7 +69,8 @@ CREATE TABLE transactions (
     network             TEXT        NOT NULL,
     mempool_detected_at TIMESTAMPTZ,
     tags                TEXT,
-    tx_type             TEXT
+    tx_type             TEXT,
+    spell_outputs       JSONB
 );
 
 CREATE TABLE assets (
@@ -63,7 +96,14 @@ CREATE TABLE assets (
     is_reference_nft         BOOLEAN     NOT NULL DEFAULT FALSE,
     cardano_policy_id        TEXT,
     cardano_asset_name       TEXT,
-    cardano_fingerprint      TEXT
+    cardano_fingerprint      TEXT,
+    collection               TEXT,
+    offchain_metadata        JSONB,
+    deploy_txid              TEXT,
+    deploy_block_height      INTEGER,
+    deployer_address         TEXT,
+    moderation_status        TEXT        NOT NULL DEFAULT 'visible',
+    locked_supply            NUMERIC(30, 0) NOT NULL DEFAULT 0
 );
 
 CREATE TABLE summary (
@@ -87,7 +127,11 @@ CREATE TABLE summary (
     updated_at                    TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
     charms_cast_count             BIGINT      NOT NULL DEFAULT 0,
     bro_count                     BIGINT      NOT NULL DEFAULT 0,
-    dex_orders_count              BIGINT      NOT NULL DEFAULT 0
+    dex_orders_count              BIGINT      NOT NULL DEFAULT 0,
+    indexer_paused                BOOLEAN     NOT NULL DEFAULT FALSE,
+    provider_stale                BOOLEAN     NOT NULL DEFAULT FALSE,
+    last_gc_at                    TIMESTAMPTZ,
+    last_gc_stats                 JSONB
 );
 
 CREATE TABLE stats_holders (
@@ -104,6 +148,21 @@ CREATE TABLE stats_holders (
     UNIQUE (app_id, address, network)
 );
 
+CREATE TABLE stats_holders_applied_blocks (
+    network      TEXT      NOT NULL,
+    block_hash   TEXT      NOT NULL,
+    block_height INTEGER   NOT NULL,
+    applied_at   TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (network, block_hash)
+);
+
+CREATE TABLE likes (
+    id          SERIAL      PRIMARY KEY,
+    charm_id    TEXT        NOT NULL,
+    user_id     INTEGER     NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
 CREATE TABLE address_utxos (
     txid          TEXT    NOT NULL,
     vout          INTEGER NOT NULL,
@@ -113,6 +172,9 @@ CREATE TABLE address_utxos (
     script_pubkey TEXT    NOT NULL DEFAULT '',
     block_height  INTEGER,
     source        TEXT    CHECK (source IS NULL OR source IN ('maestro', 'node', 'backfill')),
+    possible_charm BOOLEAN NOT NULL DEFAULT FALSE,
+    has_charms    BOOLEAN,
+    charm_app_ids JSONB   NOT NULL DEFAULT '[]'::jsonb,
     PRIMARY KEY (txid, vout, network)
 );
 
@@ -140,8 +202,16 @@ CREATE TABLE block_status (
     previous_block_hash   TEXT,
     tx_count              INTEGER,
     charm_count           INTEGER,
+    skipped_pruned        BOOLEAN     NOT NULL DEFAULT FALSE,
+    processing_ms         INTEGER,
+    rpc_ms                INTEGER,
+    parse_ms              INTEGER,
+    db_ms                 INTEGER,
     downloaded_at         TIMESTAMPTZ,
     processed_at          TIMESTAMPTZ,
+    block_time            TIMESTAMP,
+    failed_attempts       INTEGER     NOT NULL DEFAULT 0,
+    last_error            TEXT,
     created_at            TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at            TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
     PRIMARY KEY (block_height, network, blockchain)
@@ -166,6 +236,8 @@ CREATE TABLE dex_orders (
     scrolls_address   TEXT,
     status            TEXT      NOT NULL,
     parent_order_id   TEXT,
+    expires_at_height INTEGER,
+    expires_at_time   TIMESTAMP,
     created_at        TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at        TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
     blockchain        TEXT      NOT NULL,
@@ -189,9 +261,21 @@ CREATE TABLE monitored_addresses (
     seed_height      INTEGER,
     seed_block_hash  TEXT,
     created_at       TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_queried_at  TIMESTAMPTZ,
+    expires_at       TIMESTAMPTZ,
+    synced_from      INTEGER,
     PRIMARY KEY (address, network)
 );
 
+CREATE TABLE control_commands (
+    id               BIGSERIAL   PRIMARY KEY,
+    network          TEXT        NOT NULL,
+    command          TEXT        NOT NULL CHECK (command IN ('pause', 'resume')),
+    requested_by     TEXT        NOT NULL,
+    requested_at     TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    acknowledged_at  TIMESTAMPTZ
+);
+
 CREATE TABLE reorg_events (
     id            SERIAL      PRIMARY KEY,
     network       TEXT        NOT NULL,
@@ -213,3 +297,206 @@ CREATE TABLE sync_discrepancies (
     value_b      BIGINT,
     detected_at  TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
+
+CREATE TABLE mint_events (
+    id              BIGSERIAL   PRIMARY KEY,
+    app_id          TEXT        NOT NULL,
+    txid            TEXT        NOT NULL,
+    block_height    INTEGER     NOT NULL,
+    minted_amount   BIGINT      NOT NULL,
+    minter_address  TEXT,
+    network         TEXT        NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (network, txid, app_id)
+);
+
+CREATE TABLE indexer_replicas (
+    instance_id  TEXT        NOT NULL,
+    network      TEXT        NOT NULL,
+    role         TEXT        NOT NULL CHECK (role IN ('leader', 'standby')),
+    started_at   TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    role_since   TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_seen    TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (instance_id, network)
+);
+
+CREATE TABLE webhook_subscriptions (
+    id                   BIGSERIAL   PRIMARY KEY,
+    url                  TEXT        NOT NULL,
+    secret               TEXT        NOT NULL,
+    app_id               TEXT,
+    event_types          TEXT[]      NOT NULL,
+    active               BOOLEAN     NOT NULL DEFAULT TRUE,
+    consecutive_failures INTEGER     NOT NULL DEFAULT 0,
+    disabled_at          TIMESTAMPTZ,
+    created_by           TEXT,
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE webhook_deliveries (
+    id               BIGSERIAL   PRIMARY KEY,
+    subscription_id  BIGINT      NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+    event_id         TEXT        NOT NULL,
+    event_type       TEXT        NOT NULL,
+    network          TEXT        NOT NULL,
+    block_height     INTEGER     NOT NULL,
+    payload          JSONB       NOT NULL,
+    status           TEXT        NOT NULL DEFAULT 'pending'
+                                 CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts         INTEGER     NOT NULL DEFAULT 0,
+    next_attempt_at  TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_error       TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at     TIMESTAMPTZ,
+    UNIQUE (subscription_id, event_id)
+);
+
+CREATE TABLE webhook_delivery_attempts (
+    id            BIGSERIAL   PRIMARY KEY,
+    delivery_id   BIGINT      NOT NULL REFERENCES webhook_deliveries (id) ON DELETE CASCADE,
+    attempt       INTEGER     NOT NULL,
+    status_code   INTEGER,
+    error         TEXT,
+    duration_ms   INTEGER     NOT NULL,
+    attempted_at  TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE pending_spells (
+    txid              TEXT        NOT NULL,
+    network           TEXT        NOT NULL,
+    raw_hex           TEXT        NOT NULL,
+    detected_version  INTEGER     NOT NULL,
+    block_height      INTEGER,
+    first_seen_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (txid, network)
+);
+
+CREATE TABLE supply_changes (
+    id            BIGSERIAL      PRIMARY KEY,
+    app_id        TEXT           NOT NULL,
+    network       TEXT           NOT NULL,
+    delta         NUMERIC(30, 0) NOT NULL,
+    reason        TEXT           NOT NULL
+                  CHECK (reason IN ('mint', 'spend', 'reindex', 'manual')),
+    txid          TEXT,
+    block_height  INTEGER,
+    old_supply    NUMERIC(30, 0),
+    new_supply    NUMERIC(30, 0) NOT NULL,
+    created_at    TIMESTAMPTZ    NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE offchain_metadata_fetches (
+    asset_id         INTEGER     PRIMARY KEY REFERENCES assets (id) ON DELETE CASCADE,
+    url              TEXT        NOT NULL,
+    status           TEXT        NOT NULL DEFAULT 'pending'
+                                 CHECK (status IN ('pending', 'fetched', 'failed')),
+    attempts         INTEGER     NOT NULL DEFAULT 0,
+    next_attempt_at  TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_error       TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    fetched_at       TIMESTAMPTZ
+);
+
+CREATE TABLE metadata_refresh_jobs (
+    id              BIGSERIAL   PRIMARY KEY,
+    network         TEXT,
+    app_ids         TEXT[],
+    status          TEXT        NOT NULL DEFAULT 'pending'
+                                CHECK (status IN ('pending', 'running', 'done', 'failed')),
+    requested_by    TEXT        NOT NULL,
+    requested_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at      TIMESTAMPTZ,
+    heartbeat_at    TIMESTAMPTZ,
+    finished_at     TIMESTAMPTZ,
+    assets_scanned  INTEGER     NOT NULL DEFAULT 0,
+    assets_changed  INTEGER     NOT NULL DEFAULT 0,
+    error           TEXT
+);
+
+CREATE UNIQUE INDEX uq_metadata_refresh_jobs_active
+    ON metadata_refresh_jobs ((TRUE))
+    WHERE status IN ('pending', 'running');
+
+CREATE TABLE metadata_refresh_results (
+    job_id    BIGINT  NOT NULL REFERENCES metadata_refresh_jobs (id) ON DELETE CASCADE,
+    asset_id  INTEGER NOT NULL,
+    app_id    TEXT    NOT NULL,
+    network   TEXT    NOT NULL,
+    changes   JSONB   NOT NULL,
+    PRIMARY KEY (job_id, asset_id)
+);
+
+CREATE TABLE config_snapshots (
+    instance_id  TEXT        PRIMARY KEY,
+    snapshot     JSONB       NOT NULL,
+    recorded_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE changefeed (
+    id            BIGSERIAL   PRIMARY KEY,
+    entity_type   TEXT        NOT NULL
+                  CHECK (entity_type IN ('charm', 'asset', 'dex_order', 'stats_holder')),
+    entity_key    TEXT        NOT NULL,
+    operation     TEXT        NOT NULL CHECK (operation IN ('insert', 'update')),
+    block_height  INTEGER,
+    network       TEXT        NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE parser_stats (
+    network              TEXT        NOT NULL,
+    source               TEXT        NOT NULL CHECK (source IN ('block', 'mempool')),
+    bucket               TIMESTAMPTZ NOT NULL,
+    txs_scanned          BIGINT      NOT NULL DEFAULT 0,
+    envelopes_found      BIGINT      NOT NULL DEFAULT 0,
+    parsed               BIGINT      NOT NULL DEFAULT 0,
+    no_envelope          BIGINT      NOT NULL DEFAULT 0,
+    cbor_error           BIGINT      NOT NULL DEFAULT 0,
+    version_unsupported  BIGINT      NOT NULL DEFAULT 0,
+    verification_failed  BIGINT      NOT NULL DEFAULT 0,
+    PRIMARY KEY (network, source, bucket)
+);
+
+CREATE TABLE reindex_locks (
+    network       TEXT        PRIMARY KEY,
+    run_id        TEXT        NOT NULL,
+    from_height   BIGINT      NOT NULL,
+    to_height     BIGINT      NOT NULL,
+    started_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    heartbeat_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE backfill_progress (
+    name            TEXT        PRIMARY KEY,
+    status          TEXT        NOT NULL DEFAULT 'idle'
+                                CHECK (status IN ('idle', 'pending', 'running', 'paused', 'done', 'failed')),
+    last_key        BIGINT,
+    rows_updated    BIGINT      NOT NULL DEFAULT 0,
+    batches         INTEGER     NOT NULL DEFAULT 0,
+    idempotent      BOOLEAN     NOT NULL DEFAULT TRUE,
+    restart         BOOLEAN     NOT NULL DEFAULT FALSE,
+    requested_by    TEXT,
+    requested_at    TIMESTAMPTZ,
+    started_at      TIMESTAMPTZ,
+    heartbeat_at    TIMESTAMPTZ,
+    finished_at     TIMESTAMPTZ,
+    error           TEXT
+);
+
+CREATE TABLE address_utxo_spends (
+    txid           TEXT    NOT NULL,
+    vout           INTEGER NOT NULL,
+    network        TEXT    NOT NULL,
+    address        TEXT    NOT NULL,
+    value          BIGINT  NOT NULL,
+    block_height   INTEGER,
+    spending_txid  TEXT    NOT NULL,
+    spent_height   INTEGER NOT NULL,
+    PRIMARY KEY (txid, vout, network)
+);
+
+CREATE TABLE wallet_sync_horizon (
+    network      TEXT        PRIMARY KEY,
+    from_height  INTEGER     NOT NULL,
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
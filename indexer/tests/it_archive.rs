@@ -0,0 +1,173 @@
+//! The spent-charm archiver moves only old spent rows, never deploy or
+//! supply-audit rows, and archived rows stay visible to verify and reindex.
+
+mod common;
+
+use charms_indexer::application::indexer::archive::{ArchiveConfig, CharmArchiver};
+use charms_indexer::application::verify::checks::{run_check, Check};
+use charms_indexer::infrastructure::persistence::repositories::CharmRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+async fn txids(conn: &DatabaseConnection, table: &str) -> Vec<String> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        format!("SELECT txid FROM {table} ORDER BY txid"),
+    ))
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|r| r.try_get("", "txid").unwrap())
+    .collect()
+}
+
+fn archiver(db: &TestDb, archive: bool) -> CharmArchiver {
+    CharmArchiver::new(
+        db.conn.clone(),
+        ArchiveConfig {
+            // Batch size 1 forces several statements per pass.
+            batch_size: 1,
+            after_blocks: 100,
+            archive,
+            ..ArchiveConfig::default()
+        },
+    )
+}
+
+/// Mainnet tip 1000. Only `old` and `old2` (minted at 40, the rest at 50)
+/// are prunable: the others are unspent, spent too recently, deploy or
+/// supply audit rows, spent at an unknown height, or below a low tip.
+async fn seed(conn: &DatabaseConnection) {
+    conn.execute_unprepared(
+        "INSERT INTO block_status (block_height, network, blockchain, processed) VALUES \
+            (1000, 'mainnet', 'Bitcoin', TRUE), (1001, 'mainnet', 'Bitcoin', FALSE), \
+            (150, 'testnet4', 'Bitcoin', TRUE); \
+         INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id, \
+                             spent, spent_height) VALUES \
+            ('old',     0, 40, 'token', 'Bitcoin', 'mainnet',  't/a', TRUE,  900), \
+            ('old2',    0, 40, 'token', 'Bitcoin', 'mainnet',  't/a', TRUE,  120), \
+            ('recent',  0, 50, 'token', 'Bitcoin', 'mainnet',  't/a', TRUE,  901), \
+            ('live',    0, 50, 'token', 'Bitcoin', 'mainnet',  't/a', FALSE, NULL), \
+            ('deploy',  0, 50, 'nft',   'Bitcoin', 'mainnet',  'n/a', TRUE,  60), \
+            ('minted',  0, 50, 'token', 'Bitcoin', 'mainnet',  't/a', TRUE,  60), \
+            ('unknown', 0, 50, 'token', 'Bitcoin', 'mainnet',  't/a', TRUE,  NULL), \
+            ('testnet', 0, 50, 'token', 'Bitcoin', 'testnet4', 't/a', TRUE,  60); \
+         INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, \
+                             blockchain, network, deploy_txid) VALUES \
+            ('n/a', 'deploy', 0, 'a', 50, 'nft', 'Bitcoin', 'mainnet', 'deploy'); \
+         INSERT INTO supply_changes (app_id, network, delta, reason, txid, block_height, \
+                                     new_supply) VALUES \
+            ('t/a', 'mainnet', 5, 'mint', 'minted', 50, 5)",
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn old_spent_charms_move_to_the_archive() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+
+    assert_eq!(archiver(&db, true).run_once().await.unwrap(), 2);
+
+    assert_eq!(txids(&db.conn, "charms_archive").await, ["old", "old2"]);
+    assert_eq!(
+        txids(&db.conn, "charms").await,
+        ["deploy", "live", "minted", "recent", "testnet", "unknown"]
+    );
+    let row = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT spent, spent_height, app_id FROM charms_archive WHERE txid = 'old'".to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(row.try_get::<bool>("", "spent").unwrap());
+    assert_eq!(
+        row.try_get::<Option<i32>>("", "spent_height").unwrap(),
+        Some(900)
+    );
+
+    // Nothing left to move.
+    assert_eq!(archiver(&db, true).run_once().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn without_archive_old_spent_charms_are_deleted() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+
+    assert_eq!(archiver(&db, false).run_once().await.unwrap(), 2);
+
+    assert!(txids(&db.conn, "charms_archive").await.is_empty());
+    assert_eq!(
+        txids(&db.conn, "charms").await,
+        ["deploy", "live", "minted", "recent", "testnet", "unknown"]
+    );
+    // Wallet change cursors before the last dropped spend can no longer be
+    // answered.
+    let horizon: i32 = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT from_height FROM wallet_sync_horizon WHERE network = 'mainnet'".to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "from_height")
+        .unwrap();
+    assert_eq!(horizon, 900);
+}
+
+#[tokio::test]
+async fn verify_and_reindex_see_archived_charms() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+    db.conn
+        .execute_unprepared(
+            "INSERT INTO spells (txid, block_height, network) VALUES ('old', 40, 'mainnet'); \
+             INSERT INTO transactions (txid, block_height, ordinal, blockchain, network) VALUES \
+                ('old', 40, 1, 'Bitcoin', 'mainnet')",
+        )
+        .await
+        .unwrap();
+    archiver(&db, true).run_once().await.unwrap();
+
+    let (orphan_spells, _) = run_check(&db.conn, Check::SpellsWithoutCharms, "mainnet", 10)
+        .await
+        .unwrap();
+    assert_eq!(
+        orphan_spells, 0,
+        "the spell's charm is archived, not missing"
+    );
+    let (_, orphan_charms) = run_check(&db.conn, Check::CharmsWithoutTransaction, "mainnet", 10)
+        .await
+        .unwrap();
+    assert!(orphan_charms.iter().any(|k| k.starts_with("old2:0")));
+    assert!(!orphan_charms.iter().any(|k| k.starts_with("old:0")));
+
+    let repo = CharmRepository::new(db.conn.clone());
+    assert_eq!(
+        repo.heights_below_parser_revision("mainnet", 30, 45, 1)
+            .await
+            .unwrap(),
+        [40]
+    );
+    assert_eq!(repo.restore_archived("mainnet", 40, 60).await.unwrap(), 2);
+    assert!(txids(&db.conn, "charms_archive").await.is_empty());
+    let spent: bool = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT spent FROM charms WHERE txid = 'old'".to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "spent")
+        .unwrap();
+    assert!(spent, "restored rows keep their spent flag");
+}
@@ -0,0 +1,183 @@
+//! Integration tests for `AssetRepository` against an ephemeral Postgres.
+
+mod common;
+
+use charms_indexer::infrastructure::persistence::repositories::AssetRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+use serde_json::json;
+
+async fn collections(conn: &sea_orm::DatabaseConnection) -> Vec<(String, Option<String>)> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        "SELECT app_id, collection FROM assets ORDER BY app_id".to_string(),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|r| {
+        (
+            r.try_get("", "app_id").unwrap(),
+            r.try_get("", "collection").unwrap(),
+        )
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn save_batch_groups_nfts_by_declared_collection_or_deployer() {
+    let db = TestDb::new().await;
+    // The live path saves charms before assets; the mint output's address
+    // is the fallback grouping.
+    db.conn
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount) VALUES \
+             ('tx1', 0, 100, 'nft', 'Bitcoin', 'mainnet', 'addrA', 'n/aa/01', 1), \
+             ('tx2', 0, 100, 'nft', 'Bitcoin', 'mainnet', 'addrA', 'n/bb/01', 1), \
+             ('tx3', 0, 100, 'nft', 'Bitcoin', 'mainnet', 'addrB', 'n/cc/01', 1)"
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let nft = |app_id: &str, txid: &str, data: serde_json::Value| {
+        (
+            app_id.to_string(),
+            txid.to_string(),
+            0,
+            format!("charm-{app_id}"),
+            100u64,
+            data,
+            "nft".to_string(),
+            "Bitcoin".to_string(),
+            "mainnet".to_string(),
+        )
+    };
+    AssetRepository::new(db.conn.clone())
+        .save_batch(vec![
+            nft("n/aa/01", "tx1", json!({"supply": 1, "collection": "punks"})),
+            nft("n/bb/01", "tx2", json!({"supply": 1})),
+            nft("n/cc/01", "tx3", json!({"supply": 1})),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        collections(&db.conn).await,
+        vec![
+            ("n/aa/01".to_string(), Some("punks".to_string())),
+            ("n/bb/01".to_string(), Some("deployer:addrA".to_string())),
+            ("n/cc/01".to_string(), Some("deployer:addrB".to_string())),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn save_batch_keeps_one_dapp_row_per_app() {
+    let db = TestDb::new().await;
+    let dapp = |txid: &str, supply: i64| {
+        (
+            "d/aa/01".to_string(),
+            txid.to_string(),
+            0,
+            "charm-d/aa/01".to_string(),
+            100u64,
+            json!({ "supply": supply }),
+            "dapp".to_string(),
+            "Bitcoin".to_string(),
+            "mainnet".to_string(),
+        )
+    };
+    let repo = AssetRepository::new(db.conn.clone());
+    repo.save_batch(vec![dapp("tx1", 0)]).await.unwrap();
+    repo.save_batch(vec![dapp("tx2", 0), dapp("tx3", 3)])
+        .await
+        .unwrap();
+
+    let row = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT count(*) AS n, max(asset_type) AS asset_type, \
+                    max(txid) AS txid, max(total_supply)::BIGINT AS supply \
+               FROM assets"
+                .to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.try_get::<i64>("", "n").unwrap(), 1);
+    assert_eq!(row.try_get::<String>("", "asset_type").unwrap(), "dapp");
+    assert_eq!(row.try_get::<String>("", "txid").unwrap(), "tx1");
+    assert_eq!(row.try_get::<i64>("", "supply").unwrap(), 3);
+}
+
+async fn deploy_origin(conn: &sea_orm::DatabaseConnection) -> (String, i32, Option<String>, String) {
+    let row = conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT deploy_txid, deploy_block_height, deployer_address, txid FROM assets"
+                .to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    (
+        row.try_get("", "deploy_txid").unwrap(),
+        row.try_get("", "deploy_block_height").unwrap(),
+        row.try_get("", "deployer_address").unwrap(),
+        row.try_get("", "txid").unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn deploy_origin_is_first_seen_even_when_blocks_replay_out_of_order() {
+    let db = TestDb::new().await;
+    db.conn
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount) VALUES \
+             ('deploy', 0, 100, 'token', 'Bitcoin', 'mainnet', 'addrDeployer', 't/aa/01', 10), \
+             ('later', 0, 120, 'token', 'Bitcoin', 'mainnet', 'addrB', 't/aa/01', 10), \
+             ('latest', 0, 130, 'token', 'Bitcoin', 'mainnet', 'addrC', 't/aa/01', 10)"
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+    let token = |txid: &str, block_height: u64| {
+        (
+            "t/aa/01".to_string(),
+            txid.to_string(),
+            0,
+            "charm-t/aa/01".to_string(),
+            block_height,
+            json!({ "supply": 10 }),
+            "token".to_string(),
+            "Bitcoin".to_string(),
+            "mainnet".to_string(),
+        )
+    };
+    let repo = AssetRepository::new(db.conn.clone());
+
+    // A reindex reaches block 120 before the deploy block.
+    repo.save_batch(vec![token("later", 120)]).await.unwrap();
+    repo.save_batch(vec![token("latest", 130)]).await.unwrap();
+    assert_eq!(
+        deploy_origin(&db.conn).await,
+        ("later".to_string(), 120, Some("addrB".to_string()), "later".to_string())
+    );
+
+    // The true deploy replaces it; later blocks never do.
+    repo.save_batch(vec![token("deploy", 100)]).await.unwrap();
+    repo.save_batch(vec![token("later", 120)]).await.unwrap();
+    assert_eq!(
+        deploy_origin(&db.conn).await,
+        (
+            "deploy".to_string(),
+            100,
+            Some("addrDeployer".to_string()),
+            "later".to_string()
+        )
+    );
+}
@@ -0,0 +1,322 @@
+//! Integration tests for the backfill runner: batches commit with their
+//! cursor, an interrupted run resumes after the last committed batch without
+//! touching finished ranges, and the `block_time` backfill fills only what
+//! is missing.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use charms_indexer::application::maintenance::backfill::{
+    Backfill, BackfillConfig, BackfillRunner, BlockTimeBackfill, Step,
+};
+use charms_indexer::infrastructure::persistence::error::DbError;
+use charms_indexer::infrastructure::persistence::repositories::BackfillProgressRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, Statement};
+use tokio_util::sync::CancellationToken;
+
+async fn exec(conn: &DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+async fn scalar<T: sea_orm::TryGetable>(conn: &DatabaseConnection, sql: &str) -> T {
+    conn.query_one(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "v")
+        .unwrap()
+}
+
+/// Covers keys 1..=`LAST_KEY`, inserting one `hits` row per key, so a key
+/// processed twice breaks the primary key. Records each range it was given,
+/// cancels `stop` after `stop_after` steps and fails on step `fail_on`.
+struct Recording {
+    ranges: Arc<Mutex<Vec<(i64, i64)>>>,
+    steps: Mutex<usize>,
+    stop: CancellationToken,
+    stop_after: Option<usize>,
+    fail_on: Option<usize>,
+}
+
+const LAST_KEY: i64 = 10;
+
+impl Recording {
+    fn new(ranges: &Arc<Mutex<Vec<(i64, i64)>>>) -> Self {
+        Self {
+            ranges: ranges.clone(),
+            steps: Mutex::new(0),
+            stop: CancellationToken::new(),
+            stop_after: None,
+            fail_on: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Backfill for Recording {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    fn description(&self) -> &'static str {
+        "test backfill"
+    }
+
+    fn idempotent(&self) -> bool {
+        false
+    }
+
+    async fn step(
+        &self,
+        txn: &DatabaseTransaction,
+        after: Option<i64>,
+        batch_size: u64,
+    ) -> Result<Step, DbError> {
+        let from = after.unwrap_or(0);
+        if from >= LAST_KEY {
+            return Ok(Step::done());
+        }
+        let to = (from + batch_size as i64).min(LAST_KEY);
+        txn.execute(Statement::from_string(
+            DbBackend::Postgres,
+            format!(
+                "INSERT INTO hits (key) SELECT generate_series({}, {})",
+                from + 1,
+                to
+            ),
+        ))
+        .await?;
+
+        let step = {
+            let mut steps = self.steps.lock().unwrap();
+            *steps += 1;
+            *steps
+        };
+        if self.fail_on == Some(step) {
+            return Err(DbError::Other("boom".to_string()));
+        }
+        self.ranges.lock().unwrap().push((from + 1, to));
+        if self.stop_after == Some(step) {
+            self.stop.cancel();
+        }
+        Ok(Step {
+            last_key: Some(to),
+            rows: (to - from) as u64,
+        })
+    }
+}
+
+fn runner(db: &TestDb, batch_size: u64) -> BackfillRunner {
+    BackfillRunner::new(
+        db.conn.clone(),
+        BackfillConfig {
+            batch_size,
+            rows_per_sec: 0,
+            ..BackfillConfig::default()
+        },
+    )
+}
+
+#[tokio::test]
+async fn an_interrupted_run_resumes_after_its_last_batch() {
+    let db = TestDb::new().await;
+    exec(&db.conn, "CREATE TABLE hits (key BIGINT PRIMARY KEY)").await;
+    let ranges = Arc::new(Mutex::new(Vec::new()));
+    let runner = runner(&db, 3);
+
+    let first = Recording {
+        stop_after: Some(2),
+        ..Recording::new(&ranges)
+    };
+    let summary = runner.run(&first, false, &first.stop).await.unwrap();
+    assert!(!summary.finished);
+    assert_eq!(summary.last_key, Some(6));
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT status || '/' || last_key || '/' || rows_updated || '/' || batches AS v \
+               FROM backfill_progress WHERE name = 'recording'"
+        )
+        .await,
+        "paused/6/6/2"
+    );
+
+    let second = Recording::new(&ranges);
+    let summary = runner.run(&second, false, &second.stop).await.unwrap();
+    assert!(summary.finished);
+    assert_eq!(summary.rows_updated, LAST_KEY);
+    assert_eq!(
+        *ranges.lock().unwrap(),
+        vec![(1, 3), (4, 6), (7, 9), (10, 10)]
+    );
+    assert_eq!(
+        scalar::<i64>(&db.conn, "SELECT COUNT(*) AS v FROM hits").await,
+        LAST_KEY
+    );
+
+    // Done: a third run reports it without stepping again, and a restart is
+    // refused for a backfill that is not idempotent.
+    let third = Recording::new(&ranges);
+    assert!(
+        runner
+            .run(&third, false, &third.stop)
+            .await
+            .unwrap()
+            .finished
+    );
+    assert_eq!(ranges.lock().unwrap().len(), 4);
+    assert!(runner.run(&third, true, &third.stop).await.is_err());
+}
+
+#[tokio::test]
+async fn a_failed_batch_rolls_back_and_is_retried() {
+    let db = TestDb::new().await;
+    exec(&db.conn, "CREATE TABLE hits (key BIGINT PRIMARY KEY)").await;
+    let ranges = Arc::new(Mutex::new(Vec::new()));
+    let runner = runner(&db, 4);
+
+    let failing = Recording {
+        fail_on: Some(2),
+        ..Recording::new(&ranges)
+    };
+    assert!(runner.run(&failing, false, &failing.stop).await.is_err());
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT status || '/' || last_key || '/' || error AS v \
+               FROM backfill_progress WHERE name = 'recording'"
+        )
+        .await,
+        "failed/4/Error: boom"
+    );
+    // The failed batch's insert went with its transaction.
+    assert_eq!(
+        scalar::<i64>(&db.conn, "SELECT COUNT(*) AS v FROM hits").await,
+        4
+    );
+
+    let retry = Recording::new(&ranges);
+    assert!(
+        runner
+            .run(&retry, false, &retry.stop)
+            .await
+            .unwrap()
+            .finished
+    );
+    assert_eq!(*ranges.lock().unwrap(), vec![(1, 4), (5, 8), (9, 10)]);
+    assert_eq!(
+        scalar::<i64>(&db.conn, "SELECT COUNT(*) AS v FROM hits").await,
+        LAST_KEY
+    );
+}
+
+#[tokio::test]
+async fn requested_backfills_run_in_the_worker_and_resume_on_the_next_start() {
+    let db = TestDb::new().await;
+    let runner = runner(&db, 1);
+    let repo = BackfillProgressRepository::new(db.conn.clone());
+    runner.register_all().await.unwrap();
+    exec(&db.conn, "INSERT INTO block_status (block_height, network, blockchain) VALUES \
+                    (100, 'mainnet', 'bitcoin'), (101, 'mainnet', 'bitcoin'), (102, 'mainnet', 'bitcoin')")
+        .await;
+
+    let cancel = CancellationToken::new();
+    assert!(
+        runner.run_pending(&cancel).await.is_none(),
+        "nothing requested"
+    );
+    assert!(repo.request("block_time", "ops", false).await.unwrap());
+    assert!(
+        !repo.request("block_time", "ops", false).await.unwrap(),
+        "already pending"
+    );
+
+    // A shutdown before the first batch puts it back in the queue.
+    cancel.cancel();
+    let summary = runner.run_pending(&cancel).await.unwrap().unwrap();
+    assert!(!summary.finished);
+    assert_eq!(
+        repo.get("block_time").await.unwrap().unwrap().status,
+        "pending"
+    );
+
+    let summary = runner
+        .run_pending(&CancellationToken::new())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(summary.finished);
+    assert_eq!((summary.last_key, summary.batches), (Some(102), 3));
+
+    assert!(
+        !repo.request("block_time", "ops", false).await.unwrap(),
+        "done"
+    );
+    assert!(repo.request("block_time", "ops", true).await.unwrap());
+    let progress = repo.claim_pending(600.0).await.unwrap().unwrap();
+    assert_eq!(
+        (progress.last_key, progress.batches),
+        (None, 0),
+        "restarted"
+    );
+}
+
+#[tokio::test]
+async fn block_time_fills_only_missing_times() {
+    let db = TestDb::new().await;
+    exec(
+        &db.conn,
+        "INSERT INTO block_status (block_height, network, blockchain, block_time) VALUES \
+                    (100, 'mainnet', 'bitcoin', '2024-04-20 00:00:00'), \
+                    (101, 'mainnet', 'bitcoin', '2024-04-20 00:10:00'), \
+                    (102, 'mainnet', 'bitcoin', NULL), \
+                    (102, 'testnet4', 'bitcoin', '2025-01-01 00:00:00')",
+    )
+    .await;
+    // 102 on mainnet gets its time from a wallet transaction.
+    exec(&db.conn, "INSERT INTO address_transactions (txid, address, network, direction, amount, block_height, block_time) \
+                    VALUES ('w1', 'bc1qw', 'mainnet', 'in', 1, 102, 1713572400)")
+        .await;
+    exec(&db.conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id, block_time) VALUES \
+                    ('a', 0, 100, 'token', 'Bitcoin', 'mainnet', 't/aa/01', NULL), \
+                    ('b', 0, 102, 'token', 'Bitcoin', 'mainnet', 't/aa/01', NULL), \
+                    ('c', 0, 102, 'token', 'Bitcoin', 'testnet4', 't/aa/01', NULL), \
+                    ('d', 0, 101, 'token', 'Bitcoin', 'mainnet', 't/aa/01', '2000-01-01 00:00:00')")
+        .await;
+    exec(&db.conn, "INSERT INTO charms_archive (txid, vout, block_height, asset_type, blockchain, network, app_id) VALUES \
+                    ('e', 0, 101, 'token', 'Bitcoin', 'mainnet', 't/aa/01')")
+        .await;
+
+    let cancel = CancellationToken::new();
+    let summary = runner(&db, 1)
+        .run(&BlockTimeBackfill, false, &cancel)
+        .await
+        .unwrap();
+    assert!(summary.finished);
+    // block_status 102/mainnet, charms a, b, c, archive e.
+    assert_eq!(summary.rows_updated, 5);
+
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT string_agg(txid || '=' || to_char(block_time, 'YYYY-MM-DD HH24:MI'), ' ' ORDER BY txid) AS v \
+               FROM (SELECT txid, block_time FROM charms UNION ALL \
+                     SELECT txid, block_time FROM charms_archive) c"
+        )
+        .await,
+        "a=2024-04-20 00:00 b=2024-04-20 00:20 c=2025-01-01 00:00 d=2000-01-01 00:00 e=2024-04-20 00:10"
+    );
+
+    // Idempotent: starting over writes nothing new.
+    let again = runner(&db, 10)
+        .run(&BlockTimeBackfill, true, &cancel)
+        .await
+        .unwrap();
+    assert!(again.finished);
+    assert_eq!(again.rows_updated, 0);
+}
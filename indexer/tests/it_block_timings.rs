@@ -0,0 +1,149 @@
+//! Integration test for the per-block timings `process_block` stores on
+//! `block_status`.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    Block, BlockHash, CompactTarget, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Witness,
+};
+use charms_indexer::application::indexer::block::processor::BlockProcessor;
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::domain::services::CharmService;
+use charms_indexer::infrastructure::bitcoin::{
+    BitcoinClient, BitcoinClientError, BitcoinProvider, SimpleBitcoinClient,
+};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+/// Time the fake node takes to serve a block.
+const FETCH_DELAY: Duration = Duration::from_millis(50);
+
+/// A block holding only a coinbase.
+fn coinbase_block() -> Block {
+    let coinbase = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: 312_500_000,
+            script_pubkey: ScriptBuf::new(),
+        }],
+    };
+    Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata: vec![coinbase],
+    }
+}
+
+/// Serves `block` at every height, `FETCH_DELAY` after being asked.
+#[derive(Debug)]
+struct SlowProvider {
+    block: Block,
+}
+
+#[async_trait]
+impl BitcoinProvider for SlowProvider {
+    fn provider_name(&self) -> String {
+        "slow".to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(110)
+    }
+
+    async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+        Ok(self.block.block_hash())
+    }
+
+    async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        tokio::time::sleep(FETCH_DELAY).await;
+        Ok(self.block.clone())
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("not served".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+#[tokio::test]
+async fn processed_block_records_its_timings() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let network_id = NetworkId::new(NetworkType::Bitcoin, "mainnet");
+    let client = BitcoinClient::from_simple_client(SimpleBitcoinClient::from_provider(
+        Arc::new(SlowProvider {
+            block: coinbase_block(),
+        }),
+        network_id.clone(),
+    ));
+    let bp = BlockProcessor::new(
+        client,
+        CharmService::new(
+            repos.charm.clone(),
+            repos.asset.clone(),
+            repos.stats_holders.clone(),
+            repos.dex_orders.clone(),
+        ),
+        &repos,
+    );
+
+    bp.process_block(100, &network_id).await.unwrap();
+
+    let row = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT tx_count, charm_count, processing_ms, rpc_ms, parse_ms, db_ms \
+               FROM block_status WHERE block_height = 100 AND network = 'mainnet'"
+                .to_string(),
+        ))
+        .await
+        .unwrap()
+        .expect("block_status row");
+    let get = |col: &str| row.try_get::<Option<i32>>("", col).unwrap();
+    assert_eq!(get("tx_count"), Some(1));
+    assert_eq!(get("charm_count"), Some(0));
+
+    let (total, rpc, parse, db_ms) = (
+        get("processing_ms").expect("processing_ms"),
+        get("rpc_ms").expect("rpc_ms"),
+        get("parse_ms").expect("parse_ms"),
+        get("db_ms").expect("db_ms"),
+    );
+    assert!(rpc >= FETCH_DELAY.as_millis() as i32, "rpc_ms {rpc}");
+    assert!(parse >= 0 && db_ms >= 0);
+    // Each phase is rounded down on its own.
+    let sum = rpc + parse + db_ms;
+    assert!(
+        (total - 2..=total).contains(&sum),
+        "phases {rpc} + {parse} + {db_ms} vs total {total}"
+    );
+}
@@ -0,0 +1,100 @@
+//! Integration tests for migration `m20260813_000001_canonical_network_names`:
+//! legacy network spellings become the canonical names, so exact-match
+//! filters find the rows, and a legacy row with a canonical twin is dropped.
+
+mod common;
+
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+const MIGRATION: &str =
+    include_str!("../../database/migrations/m20260813_000001_canonical_network_names.sql");
+
+async fn networks(conn: &DatabaseConnection, table: &str) -> Vec<String> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        format!("SELECT network FROM {} ORDER BY network", table),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|r| r.try_get("", "network").unwrap())
+    .collect()
+}
+
+#[tokio::test]
+async fn legacy_rows_become_queryable_under_the_canonical_name() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    db.conn
+        .execute_unprepared(
+            "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id) VALUES \
+                ('aa', 0, 100, 'token', 'Bitcoin', 'Testnet', 't/x/y'), \
+                ('bb', 0, 100, 'token', 'Bitcoin', 'Bitcoin-testnet4', 't/x/y'), \
+                ('cc', 0, 100, 'token', 'Bitcoin', 'testnet4', 't/x/y'), \
+                ('cc', 0, 100, 'token', 'Bitcoin', 'TESTNET4', 't/x/y'), \
+                ('dd', 0, 100, 'token', 'Bitcoin', 'Mainnet', 't/x/y'); \
+             INSERT INTO transactions (txid, block_height, ordinal, blockchain, network) VALUES \
+                ('aa', 100, 0, 'Bitcoin', 'Testnet'); \
+             INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network) VALUES \
+                ('t/x/y', 'aa', 0, 'x', 100, 'token', 'Bitcoin', 'Bitcoin-testnet4'); \
+             INSERT INTO block_status (block_height, network, blockchain) VALUES \
+                (100, 'Bitcoin-testnet4', 'Bitcoin'), \
+                (100, 'testnet4', 'Bitcoin'), \
+                (101, 'testnet', 'Bitcoin')",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        repos
+            .charm
+            .count_in_range("testnet4", 100, 100)
+            .await
+            .unwrap(),
+        1
+    );
+
+    db.conn
+        .execute_unprepared("CREATE TABLE seaql_migrations (version TEXT PRIMARY KEY)")
+        .await
+        .unwrap();
+    db.conn
+        .execute_unprepared(MIGRATION)
+        .await
+        .expect("migrate");
+
+    assert_eq!(
+        repos
+            .charm
+            .count_in_range("testnet4", 100, 100)
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        repos
+            .charm
+            .count_in_range("mainnet", 100, 100)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(networks(&db.conn, "transactions").await, ["testnet4"]);
+    assert_eq!(networks(&db.conn, "assets").await, ["testnet4"]);
+    assert_eq!(
+        networks(&db.conn, "block_status").await,
+        ["testnet4", "testnet4"]
+    );
+
+    // Re-running finds nothing left to rewrite.
+    db.conn.execute_unprepared(MIGRATION).await.expect("re-run");
+    assert_eq!(
+        repos
+            .charm
+            .count_in_range("testnet4", 100, 100)
+            .await
+            .unwrap(),
+        3
+    );
+}
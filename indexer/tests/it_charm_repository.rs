@@ -3,6 +3,7 @@
 mod common;
 
 use charms_indexer::infrastructure::persistence::repositories::CharmRepository;
+use chrono::NaiveDateTime;
 use common::TestDb;
 use serde_json::json;
 
@@ -18,6 +19,10 @@ type Row = (
     String,
     i64,
     Option<String>,
+    Option<String>,
+    Option<i32>,
+    Option<String>,
+    Option<NaiveDateTime>,
 );
 
 fn charm_row(
@@ -40,6 +45,10 @@ fn charm_row(
         app_id.to_string(),
         amount,
         tags.map(String::from),
+        None,
+        None,
+        None,
+        None,
     )
 }
 
@@ -55,8 +64,8 @@ async fn save_batch_inserts_new_charms_and_reports_them() {
 
     let inserted = repo.save_batch(batch).await.expect("save");
     assert_eq!(inserted.len(), 2);
-    assert!(inserted.contains(&("aa".to_string(), 0)));
-    assert!(inserted.contains(&("aa".to_string(), 1)));
+    assert!(inserted.contains(&("aa".to_string(), 0, "t/x/y".to_string())));
+    assert!(inserted.contains(&("aa".to_string(), 1, "t/x/y".to_string())));
 }
 
 #[tokio::test]
@@ -85,7 +94,11 @@ async fn mark_charms_as_spent_batch_flips_spent_flag() {
     .await
     .expect("save");
 
-    repo.mark_charms_as_spent_batch(vec![("cc".to_string(), 0)], "mainnet")
+    repo.mark_charms_as_spent_batch(
+        vec![("cc".to_string(), 0, "spender".to_string())],
+        "mainnet",
+        101,
+    )
         .await
         .expect("mark spent");
 
@@ -98,6 +111,83 @@ async fn mark_charms_as_spent_batch_flips_spent_flag() {
         .expect("query");
     assert_eq!(still_unspent.len(), 1);
     assert_eq!(still_unspent[0].1, 1, "vout=1 should remain unspent");
+
+    use sea_orm::{ConnectionTrait, DbBackend, Statement};
+    let spenders: Vec<(i32, Option<String>)> = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT vout, spending_txid FROM charms WHERE txid = 'cc' ORDER BY vout".to_string(),
+        ))
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r.try_get("", "vout").unwrap(),
+                r.try_get("", "spending_txid").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(spenders, vec![(0, Some("spender".to_string())), (1, None)]);
+}
+
+/// One output carrying two different tokens persists as two rows; spending
+/// the output reports and spends both balances.
+#[tokio::test]
+async fn multi_asset_output_keeps_every_balance() {
+    let db = TestDb::new().await;
+    let repo = CharmRepository::new(db.conn.clone());
+
+    let inserted = repo
+        .save_batch(vec![
+            charm_row("ma", 0, "mainnet", "t/aa/01", 100, None),
+            charm_row("ma", 0, "mainnet", "t/bb/02", 7, None),
+        ])
+        .await
+        .expect("save");
+    assert_eq!(inserted.len(), 2);
+    assert!(inserted.contains(&("ma".to_string(), 0, "t/aa/01".to_string())));
+    assert!(inserted.contains(&("ma".to_string(), 0, "t/bb/02".to_string())));
+
+    let mut unspent: Vec<(String, i64)> = repo
+        .get_unspent_charms_by_txid_vout(vec![("ma".to_string(), 0)])
+        .await
+        .expect("query")
+        .into_iter()
+        .map(|(_, _, app_id, _, amount)| (app_id, amount))
+        .collect();
+    unspent.sort();
+    assert_eq!(
+        unspent,
+        vec![("t/aa/01".to_string(), 100), ("t/bb/02".to_string(), 7)]
+    );
+
+    let mut before_spend = repo
+        .get_charms_for_spent_update(vec![("ma".to_string(), 0)], "mainnet")
+        .await
+        .expect("spent info");
+    before_spend.sort();
+    assert_eq!(
+        before_spend,
+        vec![
+            ("t/aa/01".to_string(), "bc1qxxx".to_string(), 100),
+            ("t/bb/02".to_string(), "bc1qxxx".to_string(), 7),
+        ]
+    );
+
+    repo.mark_charms_as_spent_batch(
+        vec![("ma".to_string(), 0, "spender".to_string())],
+        "mainnet",
+        101,
+    )
+    .await
+    .expect("mark spent");
+    assert!(repo
+        .get_unspent_charms_by_txid_vout(vec![("ma".to_string(), 0)])
+        .await
+        .expect("query")
+        .is_empty());
 }
 
 #[tokio::test]
@@ -151,7 +241,14 @@ async fn mark_spent_batch_isolates_networks() {
     .await
     .expect("save");
 
-    repo.mark_charms_as_spent_batch(vec![("dd".to_string(), 0), ("dd".to_string(), 1)], "mainnet")
+    repo.mark_charms_as_spent_batch(
+        vec![
+            ("dd".to_string(), 0, "spender".to_string()),
+            ("dd".to_string(), 1, "spender".to_string()),
+        ],
+        "mainnet",
+        101,
+    )
         .await
         .expect("mark");
 
@@ -208,3 +305,328 @@ async fn get_distinct_block_heights_filters_by_network_and_ignores_null() {
         .expect("query");
     assert_eq!(testnet_heights, vec![300]);
 }
+
+/// The same txid can exist on mainnet and testnet4; uniqueness is scoped by
+/// network so neither insert shadows the other.
+#[tokio::test]
+async fn save_batch_keeps_same_outpoint_on_both_networks() {
+    let db = TestDb::new().await;
+    let repo = CharmRepository::new(db.conn.clone());
+
+    let inserted = repo
+        .save_batch(vec![
+            charm_row("ff", 0, "mainnet", "t/x/y", 10, None),
+            charm_row("ff", 0, "testnet4", "t/x/y", 20, None),
+        ])
+        .await
+        .expect("save");
+    assert_eq!(inserted.len(), 2);
+
+    // Replaying either row is still a no-op.
+    let again = repo
+        .save_batch(vec![charm_row("ff", 0, "testnet4", "t/x/y", 20, None)])
+        .await
+        .expect("replay");
+    assert!(again.is_empty());
+
+    assert_eq!(repo.get_distinct_block_heights("mainnet").await.unwrap(), vec![100]);
+    assert_eq!(repo.get_distinct_block_heights("testnet4").await.unwrap(), vec![100]);
+}
+
+#[tokio::test]
+async fn spells_are_unique_per_network() {
+    use charms_indexer::infrastructure::persistence::entities::spells;
+    use sea_orm::sea_query::OnConflict;
+    use sea_orm::{EntityTrait, PaginatorTrait, Set};
+
+    let db = TestDb::new().await;
+    let spell = |network: &str| spells::ActiveModel {
+        txid: Set("gg".to_string()),
+        network: Set(network.to_string()),
+        block_height: Set(100),
+        data: Set(json!({})),
+        date_created: Set(chrono::Utc::now().naive_utc()),
+        asset_type: Set("spell".to_string()),
+        blockchain: Set("Bitcoin".to_string()),
+        indexer_version: Set(None),
+        parser_revision: Set(None),
+    };
+    let on_conflict = OnConflict::columns([spells::Column::Txid, spells::Column::Network])
+        .do_nothing()
+        .to_owned();
+
+    for network in ["mainnet", "testnet4", "mainnet"] {
+        spells::Entity::insert(spell(network))
+            .on_conflict(on_conflict.clone())
+            .exec_without_returning(&db.conn)
+            .await
+            .expect("insert spell");
+    }
+
+    assert_eq!(spells::Entity::find().count(&db.conn).await.unwrap(), 2);
+}
+
+/// Empty-spell placeholders are stored with `is_placeholder` set so listings
+/// can drop them in SQL, but they stay retrievable by txid.
+#[tokio::test]
+async fn save_batch_flags_empty_spell_placeholders() {
+    use charms_indexer::infrastructure::persistence::entities::charms;
+    use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+
+    let db = TestDb::new().await;
+    let repo = CharmRepository::new(db.conn.clone());
+
+    let mut placeholder = charm_row("hh", 0, "mainnet", "t/x/y", 0, None);
+    placeholder.3 = json!({"type": "spell", "detected": true, "data": {}});
+    repo.save_batch(vec![placeholder, charm_row("hh", 1, "mainnet", "t/x/y", 5, None)])
+        .await
+        .expect("save");
+
+    let listed = charms::Entity::find()
+        .filter(charms::Column::Network.eq("mainnet"))
+        .filter(charms::Column::IsPlaceholder.eq(false))
+        .all(&db.conn)
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].vout, 1);
+
+    let by_txid = charms::Entity::find()
+        .filter(charms::Column::Txid.eq("hh"))
+        .filter(charms::Column::Vout.eq(0))
+        .one(&db.conn)
+        .await
+        .unwrap()
+        .expect("placeholder still stored");
+    assert!(by_txid.is_placeholder);
+    assert_eq!(charms::Entity::find().count(&db.conn).await.unwrap(), 2);
+}
+
+/// The migration backfills rows written before the column existed with the
+/// same predicate as `charms_core::is_empty_spell_charm`, and the per-network
+/// count can be answered from its partial index.
+#[tokio::test]
+async fn placeholder_migration_backfills_and_indexes_listings() {
+    use sea_orm::{ConnectionTrait, DbBackend, Statement, TransactionTrait};
+
+    const MIGRATION: &str = include_str!(
+        "../../database/migrations/m20260704_000001_charms_is_placeholder.sql"
+    );
+
+    let db = TestDb::new().await;
+    let datas = [
+        json!({"type": "spell", "detected": true, "data": {}}),
+        json!({"type": "spell", "detected": true, "data": {"x": 1}}),
+        json!({"type": "spell", "detected": false, "data": {}}),
+        json!({"type": "charm", "detected": true, "data": {}}),
+    ];
+    for (vout, data) in datas.iter().enumerate() {
+        db.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO charms (txid, vout, block_height, data, asset_type, blockchain, network, app_id) \
+                 VALUES ('ii', $1, 100, $2, 'token', 'Bitcoin', 'mainnet', 't/x/y')",
+                [(vout as i32).into(), data.clone().into()],
+            ))
+            .await
+            .expect("insert");
+    }
+
+    db.conn
+        .execute_unprepared("CREATE TABLE seaql_migrations (version TEXT PRIMARY KEY)")
+        .await
+        .unwrap();
+    db.conn.execute_unprepared(MIGRATION).await.expect("migrate");
+
+    let rows = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT vout, is_placeholder FROM charms ORDER BY vout".to_string(),
+        ))
+        .await
+        .unwrap();
+    let flags: Vec<bool> = rows
+        .iter()
+        .map(|r| r.try_get("", "is_placeholder").unwrap())
+        .collect();
+    let expected: Vec<bool> = datas.iter().map(charms_core::is_empty_spell_charm).collect();
+    assert_eq!(flags, expected);
+    assert_eq!(flags, vec![true, false, false, false]);
+
+    // Pin one connection so the planner setting applies to the EXPLAIN.
+    let txn = db.conn.begin().await.unwrap();
+    txn.execute_unprepared("SET LOCAL enable_seqscan = off").await.unwrap();
+    let plan = txn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "EXPLAIN SELECT COUNT(*) FROM charms WHERE network = 'mainnet' AND is_placeholder = false"
+                .to_string(),
+        ))
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.try_get::<String>("", "QUERY PLAN").unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+    txn.rollback().await.unwrap();
+    assert!(
+        plan.contains("idx_charms_listed_net_height_ordinal"),
+        "count should use the partial index:\n{}",
+        plan
+    );
+}
+
+/// Live writes carry the build's version and parser revision; a reindex
+/// re-save restamps the existing row with its run id.
+#[tokio::test]
+async fn save_batch_stamps_version_and_reindex_run() {
+    use charms_indexer::domain::models::{WriteStamp, INDEXER_VERSION};
+    use charms_indexer::domain::services::NativeCharmParser;
+    use charms_indexer::infrastructure::persistence::entities::charms;
+    use sea_orm::EntityTrait;
+
+    let db = TestDb::new().await;
+    let row = charm_row("jj", 0, "mainnet", "t/x/y", 7, None);
+    CharmRepository::new(db.conn.clone())
+        .save_batch(vec![row.clone()])
+        .await
+        .expect("live save");
+
+    let stored = charms::Entity::find().one(&db.conn).await.unwrap().unwrap();
+    assert_eq!(stored.indexer_version.as_deref(), Some(INDEXER_VERSION));
+    assert_eq!(stored.parser_revision, Some(NativeCharmParser::PARSER_REVISION));
+    assert_eq!(stored.reindex_run_id, None);
+
+    let restamped = CharmRepository::new(db.conn.clone())
+        .with_stamp(WriteStamp::reindex("run-1"))
+        .save_batch(vec![row])
+        .await
+        .expect("reindex save");
+    assert!(restamped.is_empty(), "a restamp is not a new charm");
+
+    let stored = charms::Entity::find().one(&db.conn).await.unwrap().unwrap();
+    assert_eq!(stored.reindex_run_id.as_deref(), Some("run-1"));
+    assert_eq!(stored.parser_revision, Some(NativeCharmParser::PARSER_REVISION));
+}
+
+/// A mempool row has no operation yet; the block write classifies it
+/// without reporting it as a new charm.
+#[tokio::test]
+async fn block_write_classifies_a_mempool_row() {
+    use charms_indexer::infrastructure::persistence::entities::charms;
+    use sea_orm::EntityTrait;
+
+    let db = TestDb::new().await;
+    let repo = CharmRepository::new(db.conn.clone());
+    let mempool = charm_row("kk", 0, "mainnet", "t/x/y", 7, None);
+    repo.save_batch(vec![mempool.clone()]).await.expect("mempool save");
+
+    let mut block = mempool;
+    block.13 = Some("transfer".to_string());
+    let inserted = repo.save_batch(vec![block]).await.expect("block save");
+    assert!(inserted.is_empty(), "a promoted row is not a new charm");
+
+    let stored = charms::Entity::find().one(&db.conn).await.unwrap().unwrap();
+    assert_eq!(stored.operation.as_deref(), Some("transfer"));
+}
+
+/// Catching up on old blocks dates each charm by its block; `date_created`
+/// stays the ingestion time. A mempool row gets its block time when the block
+/// write confirms it.
+#[tokio::test]
+async fn catch_up_dates_charms_by_block_time() {
+    use charms_indexer::infrastructure::persistence::entities::charms;
+    use sea_orm::{EntityTrait, QueryOrder};
+
+    let db = TestDb::new().await;
+    let repo = CharmRepository::new(db.conn.clone());
+    let at = |secs| {
+        chrono::DateTime::from_timestamp(secs, 0)
+            .unwrap()
+            .naive_utc()
+    };
+    let mut older = charm_row("m1", 0, "mainnet", "t/x/y", 1, None);
+    older.14 = Some(at(1_700_000_000));
+    let mut newer = charm_row("m2", 0, "mainnet", "t/x/y", 1, None);
+    newer.14 = Some(at(1_700_000_600));
+    // Newer block first, as a parallel catch-up may write them.
+    repo.save_batch(vec![newer]).await.unwrap();
+    repo.save_batch(vec![older]).await.unwrap();
+
+    let rows = charms::Entity::find()
+        .order_by_asc(charms::Column::BlockTime)
+        .all(&db.conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        rows.iter().map(|r| r.txid.as_str()).collect::<Vec<_>>(),
+        ["m1", "m2"]
+    );
+    assert_eq!(rows[0].block_time, Some(at(1_700_000_000)));
+    assert!(rows.iter().all(|r| r.date_created > at(1_700_000_600)));
+
+    let mempool = charm_row("m3", 0, "mainnet", "t/x/y", 1, None);
+    repo.save_batch(vec![mempool.clone()]).await.unwrap();
+    let mut block = mempool;
+    block.14 = Some(at(1_700_001_200));
+    repo.save_batch(vec![block]).await.unwrap();
+    let promoted = charms::Entity::find_by_id((
+        "m3".to_string(),
+        0,
+        "t/x/y".to_string(),
+        "mainnet".to_string(),
+    ))
+    .one(&db.conn)
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(promoted.block_time, Some(at(1_700_001_200)));
+}
+
+/// Targeted reindexing picks heights with unstamped or older-revision charms,
+/// within the range and network.
+#[tokio::test]
+async fn heights_below_parser_revision_select_stale_blocks() {
+    use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+    let db = TestDb::new().await;
+    db.conn
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            "INSERT INTO charms (txid, vout, block_height, data, asset_type, blockchain, network, app_id, parser_revision) VALUES \
+             ('k1', 0, 100, '{}', 'token', 'Bitcoin', 'mainnet', 't/x/y', NULL), \
+             ('k2', 0, 101, '{}', 'token', 'Bitcoin', 'mainnet', 't/x/y', 1), \
+             ('k3', 0, 102, '{}', 'token', 'Bitcoin', 'mainnet', 't/x/y', 2), \
+             ('k4', 0, 102, '{}', 'token', 'Bitcoin', 'mainnet', 't/x/y', 1), \
+             ('k5', 0, 103, '{}', 'token', 'Bitcoin', 'testnet4', 't/x/y', 1), \
+             ('k6', 0, 104, '{}', 'token', 'Bitcoin', 'mainnet', 't/x/y', 2), \
+             ('k7', 0, 110, '{}', 'token', 'Bitcoin', 'mainnet', 't/x/y', NULL)"
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let repo = CharmRepository::new(db.conn.clone());
+    assert_eq!(
+        repo.heights_below_parser_revision("mainnet", 100, 105, 2)
+            .await
+            .unwrap(),
+        vec![100, 101, 102]
+    );
+    assert_eq!(
+        repo.heights_below_parser_revision("mainnet", 100, 105, 1)
+            .await
+            .unwrap(),
+        vec![100]
+    );
+    assert_eq!(
+        repo.count_by_parser_revision(Some("testnet4"))
+            .await
+            .unwrap()
+            .iter()
+            .map(|c| (c.parser_revision, c.charms))
+            .collect::<Vec<_>>(),
+        vec![(Some(1), 1)]
+    );
+}
@@ -0,0 +1,138 @@
+//! Integration tests for operator pause/resume: `ControlCommandsRepository`
+//! and the `PauseGate` consulted by the block loop.
+
+mod common;
+
+use charms_indexer::application::indexer::control::PauseGate;
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::domain::models::ControlCommand;
+use charms_indexer::infrastructure::persistence::repositories::{
+    ControlCommandsRepository, ReindexLocksRepository, SummaryRepository,
+};
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, FromQueryResult, Statement};
+
+#[derive(FromQueryResult, Debug)]
+struct CommandRow {
+    command: String,
+    requested_by: String,
+    acknowledged: bool,
+}
+
+async fn commands(conn: &sea_orm::DatabaseConnection, network: &str) -> Vec<CommandRow> {
+    CommandRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT command, requested_by, acknowledged_at IS NOT NULL AS acknowledged \
+           FROM control_commands WHERE network = $1 ORDER BY id",
+        [network.into()],
+    ))
+    .all(conn)
+    .await
+    .unwrap()
+}
+
+async fn summary_paused(conn: &sea_orm::DatabaseConnection, network: &str) -> bool {
+    conn.query_one(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT indexer_paused FROM summary WHERE network = $1",
+        [network.into()],
+    ))
+    .await
+    .unwrap()
+    .expect("summary row")
+    .try_get("", "indexer_paused")
+    .unwrap()
+}
+
+#[tokio::test]
+async fn issue_is_idempotent_and_poll_acknowledges() {
+    let db = TestDb::new().await;
+    let repo = ControlCommandsRepository::new(db.conn.clone());
+
+    assert_eq!(repo.poll("mainnet").await.unwrap(), None);
+    // Resuming a network that was never paused records nothing.
+    assert!(!repo.issue("mainnet", ControlCommand::Resume, "ops").await.unwrap());
+
+    assert!(repo.issue("mainnet", ControlCommand::Pause, "alice").await.unwrap());
+    assert!(!repo.issue("mainnet", ControlCommand::Pause, "bob").await.unwrap());
+    let rows = commands(&db.conn, "mainnet").await;
+    assert_eq!(rows.len(), 1);
+    assert_eq!(
+        (rows[0].command.as_str(), rows[0].requested_by.as_str(), rows[0].acknowledged),
+        ("pause", "alice", false)
+    );
+
+    assert_eq!(repo.poll("mainnet").await.unwrap(), Some(ControlCommand::Pause));
+    assert!(commands(&db.conn, "mainnet").await[0].acknowledged);
+
+    // Scoped by network.
+    assert_eq!(repo.poll("testnet4").await.unwrap(), None);
+
+    assert!(repo.issue("mainnet", ControlCommand::Resume, "alice").await.unwrap());
+    assert_eq!(repo.poll("mainnet").await.unwrap(), Some(ControlCommand::Resume));
+    assert!(commands(&db.conn, "mainnet").await.iter().all(|r| r.acknowledged));
+}
+
+/// Drive a catch-up loop the way `BitcoinProcessor::start_processing` does
+/// and pause it part-way: no block may be processed until resume.
+#[tokio::test]
+async fn pause_mid_catch_up_holds_blocks_until_resume() {
+    let db = TestDb::new().await;
+    let control = ControlCommandsRepository::new(db.conn.clone());
+    let summary = SummaryRepository::new(db.conn.clone());
+    let reindex_locks = ReindexLocksRepository::new(db.conn.clone());
+    db.conn
+        .execute_unprepared("INSERT INTO summary (network) VALUES ('mainnet'), ('testnet4')")
+        .await
+        .unwrap();
+
+    let mut gate = PauseGate::new(
+        NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+        control.clone(),
+        reindex_locks.clone(),
+        summary.clone(),
+    );
+    let mut other = PauseGate::new(
+        NetworkId::new(NetworkType::Bitcoin, "testnet4"),
+        control.clone(),
+        reindex_locks,
+        summary,
+    );
+    let tip = 110;
+    let mut height = 100;
+    let mut other_height = 100;
+
+    let step = |paused: bool, h: &mut u64| {
+        if !paused && *h < tip {
+            *h += 1;
+        }
+    };
+
+    for _ in 0..3 {
+        step(gate.is_paused().await, &mut height);
+    }
+    assert_eq!(height, 103);
+
+    control
+        .issue("mainnet", ControlCommand::Pause, "maintenance")
+        .await
+        .unwrap();
+    for _ in 0..5 {
+        step(gate.is_paused().await, &mut height);
+        step(other.is_paused().await, &mut other_height);
+    }
+    assert_eq!(height, 103, "no blocks while paused");
+    assert_eq!(other_height, 105, "other networks keep indexing");
+    assert!(summary_paused(&db.conn, "mainnet").await);
+    assert!(!summary_paused(&db.conn, "testnet4").await);
+
+    control
+        .issue("mainnet", ControlCommand::Resume, "maintenance")
+        .await
+        .unwrap();
+    while height < tip {
+        step(gate.is_paused().await, &mut height);
+    }
+    assert_eq!(height, tip);
+    assert!(!summary_paused(&db.conn, "mainnet").await);
+}
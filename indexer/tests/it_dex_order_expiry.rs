@@ -0,0 +1,87 @@
+//! Integration tests for DEX order expiry: the per-block sweep moves open and
+//! partial orders past their expiry height/time to `expired`.
+
+mod common;
+
+use charms_indexer::domain::services::dex::{DexOperation, DexOrder, ExecType, OrderSide};
+use charms_indexer::infrastructure::persistence::repositories::DexOrdersRepository;
+use common::TestDb;
+
+const H: u64 = 900_000;
+const T: u32 = 1_760_000_000;
+
+fn order(expires_at_height: Option<u64>, expires_at_time: Option<u64>) -> DexOrder {
+    DexOrder {
+        maker: "bc1pmaker".to_string(),
+        side: OrderSide::Ask,
+        exec_type: ExecType::Partial { from: None },
+        price: (1, 2),
+        amount: 1_000,
+        quantity: 2_000,
+        asset_app_id: "t/aa/bb".to_string(),
+        scrolls_address: None,
+        expires_at_height,
+        expires_at_time,
+    }
+}
+
+async fn save(repo: &DexOrdersRepository, txid: &str, order: &DexOrder, network: &str) {
+    repo.save_order(
+        txid,
+        0,
+        Some(H - 10),
+        order,
+        &DexOperation::CreateAskOrder,
+        "charms-cast",
+        "Bitcoin",
+        network,
+    )
+    .await
+    .unwrap();
+}
+
+async fn status(repo: &DexOrdersRepository, txid: &str) -> String {
+    repo.get_by_id(&format!("{txid}:0"))
+        .await
+        .unwrap()
+        .unwrap()
+        .status
+}
+
+#[tokio::test]
+async fn order_expires_once_a_block_passes_its_height() {
+    let db = TestDb::new().await;
+    let repo = DexOrdersRepository::new(db.conn.clone());
+    save(&repo, "byheight", &order(Some(H), None), "mainnet").await;
+    save(&repo, "forever", &order(None, None), "mainnet").await;
+    save(&repo, "other", &order(Some(H), None), "testnet4").await;
+    let saved = repo.get_by_id("byheight:0").await.unwrap().unwrap();
+    assert_eq!(saved.expires_at_height, Some(H as i32));
+
+    // Still fillable in block H itself.
+    assert_eq!(repo.expire_orders("mainnet", H, T).await.unwrap(), 0);
+    assert_eq!(status(&repo, "byheight").await, "open");
+
+    assert_eq!(repo.expire_orders("mainnet", H + 1, T).await.unwrap(), 1);
+    assert_eq!(status(&repo, "byheight").await, "expired");
+    assert_eq!(status(&repo, "forever").await, "open");
+    assert_eq!(status(&repo, "other").await, "open");
+}
+
+#[tokio::test]
+async fn order_expires_once_block_time_passes_its_time() {
+    let db = TestDb::new().await;
+    let repo = DexOrdersRepository::new(db.conn.clone());
+    save(&repo, "bytime", &order(None, Some(T as u64)), "mainnet").await;
+    save(&repo, "filled", &order(Some(H), None), "mainnet").await;
+    repo.update_status("filled:0", "filled").await.unwrap();
+
+    assert_eq!(repo.expire_orders("mainnet", H, T).await.unwrap(), 0);
+    assert_eq!(
+        repo.expire_orders("mainnet", H + 1, T + 1).await.unwrap(),
+        1
+    );
+    assert_eq!(status(&repo, "bytime").await, "expired");
+    // Only open/partial orders expire.
+    assert_eq!(status(&repo, "filled").await, "filled");
+}
@@ -0,0 +1,181 @@
+//! Integration tests for tag consistency between the mempool and block
+//! paths: a charm must end up with the same tags whichever path saw it first.
+
+mod common;
+
+use std::collections::HashSet;
+
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::consensus::deserialize;
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash, CompactTarget, Transaction};
+use charms_indexer::application::indexer::block::{
+    detection, mempool_consolidator, BatchProcessor,
+};
+use charms_indexer::application::indexer::mempool::processor;
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::domain::services::CharmService;
+use charms_indexer::infrastructure::persistence::repositories::{
+    AssetRepository, CharmRepository, DexOrdersRepository, MempoolSpendsRepository,
+    MintEventsRepository, StatsHoldersRepository, TransactionRepository,
+};
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+/// A real mainnet V10 Charms Cast bid order (see `fixtures/parser/README.md`).
+const DEX_TX_HEX: &str = include_str!("fixtures/parser/dex_bid_order_7269cf1b.hex");
+const HEIGHT: u64 = 900;
+
+fn dex_tx() -> Transaction {
+    deserialize(&hex::decode(DEX_TX_HEX.trim()).unwrap()).unwrap()
+}
+
+fn block_with(tx: Transaction) -> Block {
+    Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata: vec![tx],
+    }
+}
+
+fn network_id() -> NetworkId {
+    NetworkId::new(NetworkType::Bitcoin, "mainnet")
+}
+
+/// Run the block pipeline's detection, consolidation and saves for `block`.
+async fn index_block(conn: &DatabaseConnection, block: &Block) {
+    let charm_service = CharmService::new(
+        CharmRepository::new(conn.clone()),
+        AssetRepository::new(conn.clone()),
+        StatsHoldersRepository::new(conn.clone()),
+        DexOrdersRepository::new(conn.clone()),
+    );
+    let dex_repo = DexOrdersRepository::new(conn.clone());
+    let (transactions, charms, _, _) = detection::detect_charms(
+        block,
+        HEIGHT,
+        HEIGHT + 5,
+        "mainnet",
+        "Bitcoin",
+        &charm_service,
+        Some(&dex_repo),
+        None,
+    )
+    .await;
+    assert!(!charms.is_empty(), "fixture should be detected as a charm");
+
+    let verified: HashSet<String> = transactions.iter().map(|t| t.txid.clone()).collect();
+    mempool_consolidator::consolidate(
+        block,
+        HEIGHT,
+        &network_id(),
+        &MempoolSpendsRepository::new(conn.clone()),
+        &verified,
+    )
+    .await;
+
+    let batch = BatchProcessor::new(
+        charm_service,
+        TransactionRepository::new(conn.clone()),
+        MintEventsRepository::new(conn.clone()),
+    );
+    batch
+        .save_transaction_batch(transactions, HEIGHT, &network_id())
+        .await
+        .unwrap();
+    batch
+        .save_charm_batch(charms, HEIGHT, &network_id())
+        .await
+        .unwrap();
+}
+
+/// Charm tags by output, then the transaction's tags and tx_type.
+async fn persisted_tags(conn: &DatabaseConnection) -> Vec<String> {
+    let rows = conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "(SELECT vout || ':' || app_id || '=' || COALESCE(tags, '') AS t, block_height \
+                FROM charms ORDER BY vout, app_id) \
+             UNION ALL \
+             SELECT 'tx=' || COALESCE(tags, '') || '/' || COALESCE(tx_type, ''), block_height::INTEGER \
+               FROM transactions"
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+    rows.iter()
+        .map(|r| {
+            let height: Option<i32> = r.try_get("", "block_height").unwrap();
+            assert_eq!(height, Some(HEIGHT as i32), "row left unconfirmed");
+            r.try_get::<String>("", "t").unwrap()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn mempool_first_and_block_first_persist_identical_tags() {
+    let via_mempool = TestDb::new().await;
+    let direct = TestDb::new().await;
+    let tx = dex_tx();
+    let txid = tx.txid().to_string();
+    let block = block_with(tx);
+
+    let detected = processor::process_tx_with_hex(
+        &txid,
+        DEX_TX_HEX.trim(),
+        &network_id(),
+        &via_mempool.conn,
+        &MempoolSpendsRepository::new(via_mempool.conn.clone()),
+    )
+    .await
+    .unwrap();
+    assert!(detected.is_some_and(|d| d.has_dex_order));
+    index_block(&via_mempool.conn, &block).await;
+    index_block(&direct.conn, &block).await;
+
+    let tags = persisted_tags(&direct.conn).await;
+    assert!(tags.iter().all(|t| t.contains("charms-cast")), "{tags:?}");
+    assert_eq!(persisted_tags(&via_mempool.conn).await, tags);
+}
+
+#[tokio::test]
+async fn block_tags_replace_stale_mempool_tags() {
+    let stale = TestDb::new().await;
+    let direct = TestDb::new().await;
+    let tx = dex_tx();
+    let txid = tx.txid().to_string();
+    let block = block_with(tx);
+
+    // A mempool sighting classified differently from the confirmed spell.
+    processor::process_tx_with_hex(
+        &txid,
+        DEX_TX_HEX.trim(),
+        &network_id(),
+        &stale.conn,
+        &MempoolSpendsRepository::new(stale.conn.clone()),
+    )
+    .await
+    .unwrap();
+    stale
+        .conn
+        .execute_unprepared(
+            "UPDATE charms SET tags = 'charms-cast,fulfill-ask'; \
+             UPDATE transactions SET tags = 'charms-cast,fulfill-ask'",
+        )
+        .await
+        .unwrap();
+    index_block(&stale.conn, &block).await;
+    index_block(&direct.conn, &block).await;
+
+    assert_eq!(
+        persisted_tags(&stale.conn).await,
+        persisted_tags(&direct.conn).await
+    );
+}
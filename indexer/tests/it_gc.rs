@@ -0,0 +1,249 @@
+//! Integration tests for the garbage collector: each sweep removes its junk
+//! rows and nothing else.
+
+mod common;
+
+use charms_indexer::application::indexer::gc::{GarbageCollector, GcConfig, GcSweep};
+use charms_indexer::infrastructure::persistence::repositories::SummaryRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+async fn keys(conn: &DatabaseConnection, sql: &str) -> Vec<String> {
+    conn.query_all(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.try_get_by_index::<String>(0).unwrap())
+        .collect()
+}
+
+fn collector(db: &TestDb, sweeps: Vec<GcSweep>) -> GarbageCollector {
+    GarbageCollector::new(
+        db.conn.clone(),
+        SummaryRepository::new(db.conn.clone()),
+        GcConfig {
+            // Batch size 1 forces several DELETEs per sweep.
+            batch_size: 1,
+            sweeps,
+            ..GcConfig::default()
+        },
+    )
+}
+
+async fn seed(conn: &DatabaseConnection) {
+    conn.execute_unprepared(
+        "INSERT INTO transactions (txid, ordinal, block_height, status, confirmations, blockchain, network) VALUES \
+            ('tx_mined',     0, 100,  'confirmed',  10, 'Bitcoin', 'mainnet'), \
+            ('tx_pending',   0, NULL, 'pending',    0,  'Bitcoin', 'mainnet'), \
+            ('tx_confirming',0, 199,  'confirming', 2,  'Bitcoin', 'mainnet')",
+    )
+    .await
+    .unwrap();
+
+    // Charms: key = txid/app_id/network.
+    conn.execute_unprepared(
+        "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id) VALUES \
+            ('tx_mined',   0, NULL, 'token', 'Bitcoin', 'mainnet',  't/a'), \
+            ('tx_sibling', 0, NULL, 'token', 'Bitcoin', 'mainnet',  't/a'), \
+            ('tx_sibling', 0, 150,  'token', 'Bitcoin', 'mainnet',  't/b'), \
+            ('tx_pending', 0, NULL, 'token', 'Bitcoin', 'mainnet',  't/a'), \
+            ('tx_mined',   0, NULL, 'token', 'Bitcoin', 'testnet4', 't/a')",
+    )
+    .await
+    .unwrap();
+
+    let order = |id: &str, height: &str, age: &str, network: &str| {
+        format!(
+            "('{id}', 'tx_{id}', 0, {height}, 'scrolls', 'maker', 'ask', 'all_or_none', 1, 1, 1, 1, \
+              'n/x', 'open', NOW() - INTERVAL '{age}', 'Bitcoin', '{network}')"
+        )
+    };
+    conn.execute_unprepared(&format!(
+        "INSERT INTO dex_orders (order_id, txid, vout, block_height, platform, maker, side, exec_type, \
+            price_num, price_den, amount, quantity, asset_app_id, status, created_at, blockchain, network) \
+         VALUES {}, {}, {}, {}",
+        order("o_stale_mismatch", "NULL", "2 days", "bitcoin"),
+        order("o_stale", "NULL", "2 days", "mainnet"),
+        order("o_fresh", "NULL", "1 hour", "mainnet"),
+        order("o_mined", "120", "30 days", "mainnet"),
+    ))
+    .await
+    .unwrap();
+
+    // Monitored addresses: only `idle` matches every condition.
+    conn.execute_unprepared(
+        "INSERT INTO monitored_addresses (address, network, source, seeded_at, created_at) VALUES \
+            ('idle',     'mainnet', 'block', NOW() - INTERVAL '300 days', NOW() - INTERVAL '300 days'), \
+            ('has_utxo', 'mainnet', 'block', NOW() - INTERVAL '300 days', NOW() - INTERVAL '300 days'), \
+            ('recent_tx','mainnet', 'block', NOW() - INTERVAL '300 days', NOW() - INTERVAL '300 days'), \
+            ('holder',   'mainnet', 'block', NOW() - INTERVAL '300 days', NOW() - INTERVAL '300 days'), \
+            ('unseeded', 'mainnet', 'block', NULL,                        NOW() - INTERVAL '300 days'), \
+            ('young',    'mainnet', 'block', NOW(),                       NOW())",
+    )
+    .await
+    .unwrap();
+    conn.execute_unprepared(
+        "INSERT INTO address_utxos (txid, vout, network, address, value, block_height) VALUES \
+            ('u1', 0, 'mainnet', 'has_utxo', 1000, 10)",
+    )
+    .await
+    .unwrap();
+    conn.execute_unprepared(
+        "INSERT INTO address_transactions (txid, address, network, direction, amount, block_height, block_time) VALUES \
+            ('old',  'idle',      'mainnet', 'in', 1, 10,  EXTRACT(EPOCH FROM NOW() - INTERVAL '400 days')::BIGINT), \
+            ('new',  'recent_tx', 'mainnet', 'in', 1, 190, EXTRACT(EPOCH FROM NOW() - INTERVAL '1 day')::BIGINT)",
+    )
+    .await
+    .unwrap();
+    conn.execute_unprepared(
+        "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id, address) VALUES \
+            ('tx_held', 0, 90, 'nft', 'Bitcoin', 'mainnet', 'n/h', 'holder')",
+    )
+    .await
+    .unwrap();
+
+    // Mempool spends, keyed by spent_txid.
+    conn.execute_unprepared(
+        "INSERT INTO mempool_spends (spent_txid, spent_vout, network, spending_txid) VALUES \
+            ('s_confirmed',  0, 'mainnet',  'tx_mined'), \
+            ('s_confirming', 0, 'mainnet',  'tx_confirming'), \
+            ('s_pending',    0, 'mainnet',  'tx_pending'), \
+            ('s_other_net',  0, 'testnet4', 'tx_mined')",
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn run_once_removes_only_orphaned_rows() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+    db.conn
+        .execute_unprepared("INSERT INTO summary (network) VALUES ('mainnet'), ('testnet4')")
+        .await
+        .unwrap();
+
+    let removed = collector(&db, GcSweep::ALL.to_vec()).run_once().await;
+    assert_eq!(
+        removed,
+        vec![
+            (GcSweep::MempoolCharms, 2),
+            (GcSweep::DexOrders, 2),
+            (GcSweep::MonitoredAddresses, 1),
+            (GcSweep::MempoolSpends, 1),
+            (GcSweep::ExpiredMonitors, 0),
+            (GcSweep::Changefeed, 0),
+            (GcSweep::UtxoSpends, 0),
+        ]
+    );
+
+    assert_eq!(
+        keys(
+            &db.conn,
+            "SELECT txid || '/' || app_id || '/' || network FROM charms ORDER BY 1"
+        )
+        .await,
+        vec![
+            "tx_held/n/h/mainnet",
+            "tx_mined/t/a/testnet4",
+            "tx_pending/t/a/mainnet",
+            "tx_sibling/t/b/mainnet",
+        ]
+    );
+    assert_eq!(
+        keys(&db.conn, "SELECT order_id FROM dex_orders ORDER BY 1").await,
+        vec!["o_fresh", "o_mined"]
+    );
+    assert_eq!(
+        keys(&db.conn, "SELECT address FROM monitored_addresses ORDER BY 1").await,
+        vec!["has_utxo", "holder", "recent_tx", "unseeded", "young"]
+    );
+    assert_eq!(
+        keys(&db.conn, "SELECT spent_txid FROM mempool_spends ORDER BY 1").await,
+        vec!["s_confirming", "s_other_net", "s_pending"]
+    );
+
+    // Stats land on every summary row for /status.
+    let stats = keys(
+        &db.conn,
+        "SELECT last_gc_stats::text FROM summary WHERE last_gc_at IS NOT NULL ORDER BY network",
+    )
+    .await;
+    assert_eq!(stats.len(), 2);
+    let stats: serde_json::Value = serde_json::from_str(&stats[0]).unwrap();
+    assert_eq!(
+        stats,
+        serde_json::json!({
+            "mempool_charms": 2,
+            "dex_orders": 2,
+            "monitored_addresses": 1,
+            "mempool_spends": 1,
+            "expired_monitors": 0,
+            "changefeed": 0,
+            "utxo_spends": 0,
+        })
+    );
+
+    // A second pass finds nothing.
+    assert!(collector(&db, GcSweep::ALL.to_vec())
+        .run_once()
+        .await
+        .iter()
+        .all(|(_, n)| *n == 0));
+}
+
+#[tokio::test]
+async fn disabled_sweeps_leave_their_rows() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+
+    let removed = collector(&db, vec![GcSweep::MempoolSpends]).run_once().await;
+    assert_eq!(removed, vec![(GcSweep::MempoolSpends, 1)]);
+    assert_eq!(
+        keys(&db.conn, "SELECT COUNT(*)::text FROM charms WHERE block_height IS NULL").await,
+        vec!["4"]
+    );
+    assert_eq!(
+        keys(&db.conn, "SELECT COUNT(*)::text FROM dex_orders").await,
+        vec!["4"]
+    );
+    assert_eq!(
+        keys(&db.conn, "SELECT COUNT(*)::text FROM monitored_addresses").await,
+        vec!["6"]
+    );
+}
+
+#[tokio::test]
+async fn old_utxo_spends_go_and_raise_the_sync_horizon() {
+    let db = TestDb::new().await;
+    db.conn
+        .execute_unprepared(
+            "INSERT INTO summary (network, last_processed_block) VALUES \
+                ('mainnet', 10000), ('testnet4', 500); \
+             INSERT INTO wallet_sync_horizon (network, from_height) VALUES ('mainnet', 100); \
+             INSERT INTO address_utxo_spends \
+                (txid, vout, network, address, value, block_height, spending_txid, spent_height) VALUES \
+                ('a', 0, 'mainnet',  'w', 1, 10,   's1', 5000), \
+                ('b', 0, 'mainnet',  'w', 1, 10,   's2', 5679), \
+                ('c', 0, 'mainnet',  'w', 1, 10,   's3', 5680), \
+                ('d', 0, 'testnet4', 'w', 1, 10,   's4', 400)",
+        )
+        .await
+        .unwrap();
+
+    // Retention 4320: mainnet keeps spends at 5680 and above.
+    let removed = collector(&db, vec![GcSweep::UtxoSpends]).run_once().await;
+    assert_eq!(removed, vec![(GcSweep::UtxoSpends, 2)]);
+    assert_eq!(
+        keys(&db.conn, "SELECT txid FROM address_utxo_spends ORDER BY 1").await,
+        vec!["c", "d"]
+    );
+    assert_eq!(
+        keys(
+            &db.conn,
+            "SELECT network || '=' || from_height FROM wallet_sync_horizon ORDER BY 1"
+        )
+        .await,
+        vec!["mainnet=5679"]
+    );
+}
@@ -0,0 +1,194 @@
+//! Integration tests for advisory-lock leader election between replicas
+//! sharing one database: two in-process elections per test, sampled while
+//! they campaign, fail over and shut down.
+
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use charms_indexer::application::indexer::leader::{LeaderElection, LeaderGate, Role};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::persistence::repositories::IndexerReplicasRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+const POLL: Duration = Duration::from_millis(100);
+
+struct Replica {
+    election: LeaderElection,
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+}
+
+fn replica(conn: &DatabaseConnection, network: &str, instance: &str) -> Replica {
+    let election = LeaderElection::new(
+        conn,
+        IndexerReplicasRepository::new(conn.clone()),
+        NetworkId::new(NetworkType::Bitcoin, network),
+        instance.to_string(),
+    )
+    .with_poll_interval(POLL);
+    let cancel = CancellationToken::new();
+    let task = tokio::spawn({
+        let election = election.clone();
+        let cancel = cancel.clone();
+        async move { election.run(cancel).await }
+    });
+    Replica {
+        election,
+        cancel,
+        task,
+    }
+}
+
+/// Records whether both gates were ever open at once, every few ms.
+fn watch_overlap(a: LeaderGate, b: LeaderGate) -> (Arc<AtomicBool>, CancellationToken) {
+    let overlap = Arc::new(AtomicBool::new(false));
+    let stop = CancellationToken::new();
+    tokio::spawn({
+        let overlap = overlap.clone();
+        let stop = stop.clone();
+        async move {
+            while !stop.is_cancelled() {
+                if a.is_leader() && b.is_leader() {
+                    overlap.store(true, Ordering::SeqCst);
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+    });
+    (overlap, stop)
+}
+
+async fn wait_for(what: &str, check: impl Fn() -> bool) {
+    for _ in 0..200 {
+        if check() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    panic!("timed out waiting for {what}");
+}
+
+async fn replica_roles(conn: &DatabaseConnection, network: &str) -> Vec<(String, String)> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        format!(
+            "SELECT instance_id, role FROM indexer_replicas \
+             WHERE network = '{network}' ORDER BY instance_id"
+        ),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|r| {
+        (
+            r.try_get("", "instance_id").unwrap(),
+            r.try_get("", "role").unwrap(),
+        )
+    })
+    .collect()
+}
+
+/// Kill the backend holding this database's advisory lock, as a crashed or
+/// partitioned leader would lose it.
+async fn drop_lock_holder(conn: &DatabaseConnection) {
+    let killed = conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT pg_terminate_backend(pid) FROM pg_locks \
+              WHERE locktype = 'advisory' AND granted \
+                AND database = (SELECT oid FROM pg_database WHERE datname = current_database())"
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(killed.len(), 1);
+}
+
+#[tokio::test]
+async fn one_leader_and_failover_when_its_connection_drops() {
+    let db = TestDb::new().await;
+    let a = replica(&db.conn, "mainnet", "replica-a");
+    wait_for("a to lead", || a.election.role() == Role::Leader).await;
+    let b = replica(&db.conn, "mainnet", "replica-b");
+    let (overlap, stop) = watch_overlap(a.election.gate(), b.election.gate());
+
+    // B keeps standing by while A holds the lock.
+    tokio::time::sleep(POLL * 8).await;
+    assert_eq!(b.election.role(), Role::Standby);
+    assert_eq!(
+        replica_roles(&db.conn, "mainnet").await,
+        vec![
+            ("replica-a".to_string(), "leader".to_string()),
+            ("replica-b".to_string(), "standby".to_string()),
+        ]
+    );
+
+    drop_lock_holder(&db.conn).await;
+    wait_for("b to take over", || b.election.role() == Role::Leader).await;
+    // A reconnects but cannot win the lock back.
+    tokio::time::sleep(POLL * 8).await;
+    assert_eq!(a.election.role(), Role::Standby);
+    assert_eq!(b.election.role(), Role::Leader);
+
+    stop.cancel();
+    assert!(!overlap.load(Ordering::SeqCst), "both replicas led at once");
+    a.cancel.cancel();
+    b.cancel.cancel();
+    a.task.await.unwrap();
+    b.task.await.unwrap();
+}
+
+#[tokio::test]
+async fn clean_shutdown_hands_over_and_clears_heartbeat() {
+    let db = TestDb::new().await;
+    let a = replica(&db.conn, "testnet4", "replica-a");
+    let b = replica(&db.conn, "testnet4", "replica-b");
+    let (overlap, stop) = watch_overlap(a.election.gate(), b.election.gate());
+    wait_for("a leader", || {
+        a.election.role() == Role::Leader || b.election.role() == Role::Leader
+    })
+    .await;
+    let (leader, standby) = if a.election.role() == Role::Leader {
+        (a, b)
+    } else {
+        (b, a)
+    };
+
+    leader.cancel.cancel();
+    leader.task.await.unwrap();
+    assert_eq!(leader.election.role(), Role::Standby);
+    wait_for("the standby to take over", || {
+        standby.election.role() == Role::Leader
+    })
+    .await;
+    let roles = replica_roles(&db.conn, "testnet4").await;
+    assert_eq!(roles.len(), 1);
+    assert_eq!(roles[0].1, "leader");
+
+    stop.cancel();
+    assert!(!overlap.load(Ordering::SeqCst), "both replicas led at once");
+    standby.cancel.cancel();
+    standby.task.await.unwrap();
+    assert!(replica_roles(&db.conn, "testnet4").await.is_empty());
+}
+
+#[tokio::test]
+async fn networks_elect_independently() {
+    let db = TestDb::new().await;
+    let mainnet = replica(&db.conn, "mainnet", "replica-a");
+    let testnet = replica(&db.conn, "testnet4", "replica-b");
+    wait_for("both networks to have a leader", || {
+        mainnet.election.role() == Role::Leader && testnet.election.role() == Role::Leader
+    })
+    .await;
+    for r in [mainnet, testnet] {
+        r.cancel.cancel();
+        r.task.await.unwrap();
+    }
+}
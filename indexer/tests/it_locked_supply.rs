@@ -0,0 +1,87 @@
+//! Locked supply counts unspent confirmed amounts at open-order scrolls
+//! addresses and configured lock addresses, per network.
+
+mod common;
+
+use charms_indexer::application::indexer::locked_supply::LockedSupply;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+async fn locked(conn: &DatabaseConnection, app_id: &str) -> String {
+    conn.query_one(Statement::from_string(
+        DbBackend::Postgres,
+        format!("SELECT locked_supply::TEXT AS locked FROM assets WHERE app_id = '{app_id}'"),
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "locked")
+    .unwrap()
+}
+
+/// `t/aa` (supply 150) has 40 resting in an open order at block 50, 10 at a
+/// burn address and 100 with a holder. The spent, mempool and testnet4 rows
+/// at the scrolls address do not count. `t/bb` has 8 in the order, minted
+/// at block 60.
+async fn seed(conn: &DatabaseConnection) {
+    conn.execute_unprepared(
+        "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, \
+                             blockchain, network, total_supply) VALUES \
+            ('t/aa', 'deploy-a', 0, 't/aa', 40, 'token', 'Bitcoin', 'mainnet', 150), \
+            ('t/bb', 'deploy-b', 0, 't/bb', 40, 'token', 'Bitcoin', 'mainnet', 8); \
+         INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id, \
+                             address, amount, spent) VALUES \
+            ('hold',    0, 40,   'token', 'Bitcoin', 'mainnet',  't/aa', 'bc1qholder',   100, FALSE), \
+            ('order',   0, 50,   'token', 'Bitcoin', 'mainnet',  't/aa', 'bc1qscrolls', 40,  FALSE), \
+            ('burn',    0, 40,   'token', 'Bitcoin', 'mainnet',  't/aa', 'bc1qburn0001', 10, FALSE), \
+            ('old',     0, 40,   'token', 'Bitcoin', 'mainnet',  't/aa', 'bc1qscrolls', 5,   TRUE), \
+            ('pending', 0, NULL, 'token', 'Bitcoin', 'mainnet',  't/aa', 'bc1qscrolls', 7,   FALSE), \
+            ('test',    0, 50,   'token', 'Bitcoin', 'testnet4', 't/aa', 'bc1qscrolls', 3,   FALSE), \
+            ('order-b', 0, 60,   'token', 'Bitcoin', 'mainnet',  't/bb', 'bc1qscrolls', 8,   FALSE); \
+         INSERT INTO dex_orders (order_id, txid, vout, block_height, platform, maker, side, \
+                                 exec_type, price_num, price_den, amount, quantity, \
+                                 asset_app_id, scrolls_address, status, blockchain, network) \
+         VALUES ('o1', 'order', 0, 50, 'charms-cast', 'bc1qmaker', 'ask', 'all_or_none', \
+                 1, 1, 40, 40, 't/aa', 'bc1qscrolls', 'open', 'Bitcoin', 'mainnet')",
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn a_token_resting_in_an_open_order_is_locked() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+    let tracker = LockedSupply::new(db.conn.clone(), &["BC1QBURN*".to_string()]);
+
+    assert_eq!(tracker.refresh_all().await.unwrap(), 2);
+    assert_eq!(locked(&db.conn, "t/aa").await, "50");
+    assert_eq!(locked(&db.conn, "t/bb").await, "8");
+    // Nothing changed since.
+    assert_eq!(tracker.refresh_all().await.unwrap(), 0);
+
+    // Once the order is filled its address no longer locks anything. A
+    // block refresh only recomputes the tokens of that block.
+    db.conn
+        .execute_unprepared("UPDATE dex_orders SET status = 'filled' WHERE order_id = 'o1'")
+        .await
+        .unwrap();
+    assert_eq!(tracker.refresh_block("mainnet", 50).await.unwrap(), 1);
+    assert_eq!(locked(&db.conn, "t/aa").await, "10");
+    assert_eq!(locked(&db.conn, "t/bb").await, "8");
+
+    tracker.refresh_all().await.unwrap();
+    assert_eq!(locked(&db.conn, "t/bb").await, "0");
+}
+
+#[tokio::test]
+async fn without_patterns_only_order_addresses_lock() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+
+    LockedSupply::new(db.conn.clone(), &[])
+        .refresh_all()
+        .await
+        .unwrap();
+    assert_eq!(locked(&db.conn, "t/aa").await, "40");
+}
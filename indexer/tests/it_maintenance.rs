@@ -0,0 +1,347 @@
+//! Integration tests for the one-shot maintenance jobs behind the CLI
+//! (`recompute-holders`, `backfill-metadata`, `backfill-addresses`,
+//! `backfill-deploys`, `backfill-spell-outputs`).
+
+mod common;
+
+use charms_indexer::application::maintenance;
+use charms_indexer::infrastructure::persistence::repositories::StatsHoldersRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+/// (app_id, name, symbol, image_url)
+type AssetMeta = (String, Option<String>, Option<String>, Option<String>);
+
+/// (app_id, deploy_txid, deploy_block_height, deployer_address)
+type AssetDeploy = (String, Option<String>, Option<i32>, Option<String>);
+
+async fn exec(conn: &sea_orm::DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+async fn holders(conn: &sea_orm::DatabaseConnection, app_id: &str) -> Vec<(String, i64)> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        format!(
+            "SELECT address, total_amount FROM stats_holders \
+             WHERE app_id = '{app_id}' AND network = 'mainnet' ORDER BY address"
+        ),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|r| {
+        (
+            r.try_get("", "address").unwrap(),
+            r.try_get("", "total_amount").unwrap(),
+        )
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn recompute_holders_scoped_to_one_asset_leaves_others_alone() {
+    let db = TestDb::new().await;
+    exec(&db.conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount, spent) VALUES \
+                    ('tx1', 0, 100, 'token', 'Bitcoin', 'mainnet', 'addrA', 't/aa/bb', 60, false), \
+                    ('tx1', 1, 100, 'token', 'Bitcoin', 'mainnet', 'addrB', 't/aa/bb', 40, true), \
+                    ('tx1', 2, 100, 'nft', 'Bitcoin', 'mainnet', 'addrC', 'n/cc/dd', 0, false)")
+        .await;
+    // Stale rows for both assets.
+    exec(&db.conn, "INSERT INTO stats_holders (app_id, address, network, total_amount, charm_count) VALUES \
+                    ('n/aa/bb', 'addrA', 'mainnet', 5, 1), ('n/aa/bb', 'addrB', 'mainnet', 40, 1), \
+                    ('n/cc/dd', 'addrZ', 'mainnet', 1, 1)")
+        .await;
+
+    let repo = StatsHoldersRepository::new(db.conn.clone());
+    // The token app_id is mapped onto its NFT holder key.
+    let written = repo.rebuild_from_charms("mainnet", Some("t/aa/bb")).await.unwrap();
+
+    assert_eq!(written, 1);
+    assert_eq!(holders(&db.conn, "n/aa/bb").await, vec![("addrA".to_string(), 60)]);
+    assert_eq!(holders(&db.conn, "n/cc/dd").await, vec![("addrZ".to_string(), 1)]);
+
+    repo.rebuild_from_charms("mainnet", None).await.unwrap();
+    assert_eq!(holders(&db.conn, "n/cc/dd").await, vec![("addrC".to_string(), 1)]);
+}
+
+#[tokio::test]
+async fn backfill_metadata_fills_only_missing_fields() {
+    let db = TestDb::new().await;
+    let meta = r#"{"native_data": {"tx": {"outs": [{"0": {"name": "Bro", "ticker": "BRO", "description": "d", "image": "https://img/bro.png"}}]}}}"#;
+    exec(&db.conn, &format!(
+        "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount, data) VALUES \
+         ('tx1', 0, 100, 'nft', 'Bitcoin', 'mainnet', 'addrA', 'n/aa/bb', 0, '{meta}'::jsonb)"
+    ))
+    .await;
+    // NFT already has a curated name; token has nothing.
+    exec(&db.conn, "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, name) VALUES \
+                    ('n/aa/bb', 'tx1', 0, 'aa', 100, 'nft', 'Bitcoin', 'mainnet', 'Curated'), \
+                    ('t/aa/bb', 'tx2', 0, 'aa', 101, 'token', 'Bitcoin', 'mainnet', NULL)")
+        .await;
+
+    let summary = maintenance::backfill_metadata(&db.conn, Some("mainnet")).await.unwrap();
+    assert_eq!((summary.nfts_updated, summary.tokens_updated), (1, 1));
+
+    let rows = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT app_id, name, symbol, image_url FROM assets ORDER BY app_id".to_string(),
+        ))
+        .await
+        .unwrap();
+    let got: Vec<AssetMeta> = rows
+        .iter()
+        .map(|r| {
+            (
+                r.try_get("", "app_id").unwrap(),
+                r.try_get("", "name").unwrap(),
+                r.try_get("", "symbol").unwrap(),
+                r.try_get("", "image_url").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        got,
+        vec![
+            (
+                "n/aa/bb".to_string(),
+                Some("Curated".to_string()),
+                Some("BRO".to_string()),
+                Some("https://img/bro.png".to_string())
+            ),
+            (
+                "t/aa/bb".to_string(),
+                Some("Curated".to_string()),
+                Some("BRO".to_string()),
+                None
+            ),
+        ]
+    );
+
+    // A second run has nothing left to fill.
+    let again = maintenance::backfill_metadata(&db.conn, None).await.unwrap();
+    assert_eq!((again.nfts_updated, again.tokens_updated), (0, 0));
+}
+
+#[tokio::test]
+async fn backfill_metadata_groups_historical_nfts_into_collections() {
+    let db = TestDb::new().await;
+    let declared = r#"{"native_data": {"tx": {"outs": [{"0": {"name": "Punk", "collection": {"id": "punks"}}}]}}}"#;
+    exec(&db.conn, &format!(
+        "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount, data) VALUES \
+         ('tx1', 0, 100, 'nft', 'Bitcoin', 'mainnet', 'addrA', 'n/aa/01', 0, '{declared}'::jsonb), \
+         ('tx2', 0, 101, 'nft', 'Bitcoin', 'mainnet', 'addrB', 'n/bb/01', 0, '{{}}'::jsonb), \
+         ('tx3', 0, 102, 'nft', 'Bitcoin', 'mainnet', 'addrC', 'n/bb/01', 0, '{{}}'::jsonb)"
+    ))
+    .await;
+    // Rows written before the column existed; names are already curated.
+    exec(&db.conn, "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, name, symbol, description, image_url) VALUES \
+                    ('n/aa/01', 'tx1', 0, 'aa', 100, 'nft', 'Bitcoin', 'mainnet', 'n', 's', 'd', 'i'), \
+                    ('n/bb/01', 'tx2', 0, 'bb', 101, 'nft', 'Bitcoin', 'mainnet', 'n', 's', 'd', 'i')")
+        .await;
+
+    let summary = maintenance::backfill_metadata(&db.conn, Some("mainnet")).await.unwrap();
+    assert_eq!(summary.nfts_updated, 2);
+
+    let rows = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT app_id, collection FROM assets ORDER BY app_id".to_string(),
+        ))
+        .await
+        .unwrap();
+    let got: Vec<(String, Option<String>)> = rows
+        .iter()
+        .map(|r| (r.try_get("", "app_id").unwrap(), r.try_get("", "collection").unwrap()))
+        .collect();
+    // Declared id wins; otherwise the earliest charm's address (the mint).
+    assert_eq!(
+        got,
+        vec![
+            ("n/aa/01".to_string(), Some("punks".to_string())),
+            ("n/bb/01".to_string(), Some("deployer:addrB".to_string())),
+        ]
+    );
+}
+
+/// Hex of a tx with one output per script: OP_RETURN, P2PKH, P2TR and a
+/// 1-of-1 bare multisig.
+fn spell_first_tx_hex() -> String {
+    use bitcoincore_rpc::bitcoin::absolute::LockTime;
+    use bitcoincore_rpc::bitcoin::consensus::encode::serialize_hex;
+    use bitcoincore_rpc::bitcoin::{
+        OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    };
+
+    let scripts = [
+        "6a0568656c6c6f",
+        "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac",
+        "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+        "51210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f8179851ae",
+    ];
+    serialize_hex(&Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: scripts
+            .iter()
+            .map(|s| TxOut {
+                value: 1_000,
+                script_pubkey: ScriptBuf::from(hex::decode(s).unwrap()),
+            })
+            .collect(),
+    })
+}
+
+#[tokio::test]
+async fn backfill_addresses_rederives_from_the_charm_vout() {
+    let db = TestDb::new().await;
+    let tx_hex = spell_first_tx_hex();
+    exec(&db.conn, &format!(
+        "INSERT INTO transactions (txid, block_height, ordinal, raw, blockchain, network) VALUES \
+         ('tx1', 100, 1, '{{\"hex\": \"{tx_hex}\"}}'::jsonb, 'Bitcoin', 'mainnet')"
+    ))
+    .await;
+    // The token sits on the taproot output, not on the legacy change output;
+    // the multisig output and the charm without a stored tx stay NULL.
+    exec(&db.conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount, spent) VALUES \
+                    ('tx1', 2, 100, 'token', 'Bitcoin', 'mainnet', NULL, 't/aa/bb', 50, false), \
+                    ('tx1', 3, 100, 'token', 'Bitcoin', 'mainnet', NULL, 't/aa/bb', 5, false), \
+                    ('tx9', 0, 100, 'token', 'Bitcoin', 'mainnet', NULL, 't/aa/bb', 7, false)")
+        .await;
+
+    let summary = maintenance::backfill_addresses(&db.conn, Some("mainnet")).await.unwrap();
+    assert_eq!((summary.updated, summary.unresolved), (1, 2));
+
+    let taproot = "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr";
+    let rows = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT address FROM charms ORDER BY txid, vout".to_string(),
+        ))
+        .await
+        .unwrap();
+    let got: Vec<Option<String>> = rows.iter().map(|r| r.try_get("", "address").unwrap()).collect();
+    assert_eq!(got, vec![Some(taproot.to_string()), None, None]);
+    assert_eq!(holders(&db.conn, "n/aa/bb").await, vec![(taproot.to_string(), 50)]);
+
+    // Other networks are out of scope and nothing is left to fill.
+    let again = maintenance::backfill_addresses(&db.conn, Some("testnet4")).await.unwrap();
+    assert_eq!((again.updated, again.unresolved), (0, 0));
+}
+
+#[tokio::test]
+async fn backfill_deploys_takes_the_earliest_charm_and_keeps_earlier_origins() {
+    let db = TestDb::new().await;
+    exec(&db.conn, "INSERT INTO charms (txid, vout, block_height, tx_ordinal, asset_type, blockchain, network, address, app_id, amount) VALUES \
+                    ('mint', 1, 100, 3, 'nft', 'Bitcoin', 'mainnet', 'addrDeployer', 'n/aa/01', 0), \
+                    ('same-block-later', 0, 100, 7, 'nft', 'Bitcoin', 'mainnet', 'addrX', 'n/aa/01', 0), \
+                    ('transfer', 0, 150, 0, 'nft', 'Bitcoin', 'mainnet', 'addrB', 'n/aa/01', 0), \
+                    ('t-mint', 0, 200, 0, 'token', 'Bitcoin', 'mainnet', 'addrT', 't/bb/01', 5)")
+        .await;
+    // n/aa/01 was created by a later write; t/bb/01 already has an origin
+    // earlier than any stored charm (pruned history); n/cc/01 has no charms.
+    exec(&db.conn, "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, deploy_txid, deploy_block_height, deployer_address) VALUES \
+                    ('n/aa/01', 'transfer', 0, 'c', 150, 'nft', 'Bitcoin', 'mainnet', NULL, NULL, NULL), \
+                    ('t/bb/01', 't-mint', 0, 'c', 200, 'token', 'Bitcoin', 'mainnet', 'pruned', 90, 'addrOld'), \
+                    ('n/cc/01', 'tx9', 0, 'c', 300, 'nft', 'Bitcoin', 'mainnet', NULL, NULL, NULL)")
+        .await;
+
+    let summary = maintenance::backfill_deploys(&db.conn, Some("mainnet")).await.unwrap();
+    assert_eq!((summary.updated, summary.unresolved), (1, 1));
+
+    let rows = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT app_id, deploy_txid, deploy_block_height, deployer_address \
+               FROM assets ORDER BY app_id"
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+    let got: Vec<AssetDeploy> = rows
+        .iter()
+        .map(|r| {
+            (
+                r.try_get("", "app_id").unwrap(),
+                r.try_get("", "deploy_txid").unwrap(),
+                r.try_get("", "deploy_block_height").unwrap(),
+                r.try_get("", "deployer_address").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        got,
+        vec![
+            (
+                "n/aa/01".to_string(),
+                Some("mint".to_string()),
+                Some(100),
+                Some("addrDeployer".to_string())
+            ),
+            ("n/cc/01".to_string(), None, None, None),
+            (
+                "t/bb/01".to_string(),
+                Some("pruned".to_string()),
+                Some(90),
+                Some("addrOld".to_string())
+            ),
+        ]
+    );
+
+    let again = maintenance::backfill_deploys(&db.conn, None).await.unwrap();
+    assert_eq!(again.updated, 0);
+}
+
+#[tokio::test]
+async fn backfill_spell_outputs_derives_the_map_from_the_stored_spell() {
+    let db = TestDb::new().await;
+    // Two outputs, two apps: output 0 holds the NFT and 100 tokens, output 1
+    // the other 900. `plain` was stored without a parsed spell.
+    exec(&db.conn, r#"INSERT INTO transactions (txid, block_height, ordinal, charm, blockchain, network) VALUES
+                    ('spell', 100, 1, '{"native_data": {"app_public_inputs": {"n/aa/bb": null, "t/aa/bb": null},
+                                                        "tx": {"outs": [{"0": {"ticker": "AA"}, "1": 100}, {"1": 900}]}}}'::jsonb,
+                     'Bitcoin', 'mainnet'),
+                    ('plain', 100, 2, '{}'::jsonb, 'Bitcoin', 'mainnet'),
+                    ('other', 100, 3, '{"native_data": {"app_public_inputs": {}, "tx": {"outs": []}}}'::jsonb,
+                     'Bitcoin', 'testnet4')"#)
+        .await;
+
+    let summary = maintenance::backfill_spell_outputs(&db.conn, Some("mainnet")).await.unwrap();
+    assert_eq!((summary.updated, summary.unresolved), (1, 1));
+
+    let outputs: Option<serde_json::Value> = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT spell_outputs FROM transactions WHERE txid = 'spell'".to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "spell_outputs")
+        .unwrap();
+    assert_eq!(
+        outputs,
+        Some(serde_json::json!([
+            {"vout": 0, "charms": {"n/aa/bb": {"ticker": "AA"}, "t/aa/bb": 100}},
+            {"vout": 1, "charms": {"t/aa/bb": 900}},
+        ]))
+    );
+
+    // Filled rows are not revisited; the other network's row is filled now.
+    let again = maintenance::backfill_spell_outputs(&db.conn, None).await.unwrap();
+    assert_eq!((again.updated, again.unresolved), (1, 1));
+}
@@ -0,0 +1,128 @@
+//! Integration tests for the mempool consolidator: promoting mempool charms
+//! of a mined block and stamping how long they waited unconfirmed.
+
+mod common;
+
+use std::collections::HashSet;
+
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash, CompactTarget, OutPoint, Transaction, TxIn};
+use charms_indexer::application::indexer::block::mempool_consolidator;
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::persistence::repositories::MempoolSpendsRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+const BLOCK_TIME: u32 = 1_760_000_000;
+
+/// A non-coinbase tx; `tag` makes each txid distinct.
+fn tx(tag: u32) -> Transaction {
+    Transaction {
+        version: 2,
+        lock_time: LockTime::from_consensus(tag),
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Hash::all_zeros(), tag),
+            ..Default::default()
+        }],
+        output: vec![],
+    }
+}
+
+fn block(txdata: Vec<Transaction>) -> Block {
+    Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: BLOCK_TIME,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+async fn exec(conn: &DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+/// Insert an unconfirmed charm first seen `secs_before` the block time.
+async fn mempool_charm(conn: &DatabaseConnection, txid: &str, secs_before: i64) {
+    exec(
+        conn,
+        &format!(
+            "INSERT INTO charms (txid, vout, asset_type, blockchain, network, app_id, mempool_detected_at) \
+             VALUES ('{txid}', 0, 'token', 'Bitcoin', 'mainnet', 't/aa/bb', to_timestamp({}))",
+            BLOCK_TIME as i64 - secs_before
+        ),
+    )
+    .await;
+}
+
+async fn promoted(conn: &DatabaseConnection, txid: &str) -> (Option<i32>, Option<i32>) {
+    let row = conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            format!(
+                "SELECT block_height, confirmation_delay_secs FROM charms WHERE txid = '{txid}'"
+            ),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    (
+        row.try_get("", "block_height").unwrap(),
+        row.try_get("", "confirmation_delay_secs").unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn promotion_stamps_time_spent_in_mempool() {
+    let db = TestDb::new().await;
+    let (waited, early, unverified) = (tx(1), tx(2), tx(3));
+    let [waited_id, early_id, unverified_id] =
+        [&waited, &early, &unverified].map(|t| t.txid().to_string());
+    mempool_charm(&db.conn, &waited_id, 754).await;
+    // Seen "after" its block: the miner's clock ran behind ours.
+    mempool_charm(&db.conn, &early_id, -30).await;
+    mempool_charm(&db.conn, &unverified_id, 100).await;
+    // Charm found straight in the block, never in mempool.
+    exec(
+        &db.conn,
+        "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id) \
+         VALUES ('direct', 0, 500, 'token', 'Bitcoin', 'mainnet', 't/aa/bb')",
+    )
+    .await;
+
+    let verified: HashSet<String> = [waited_id.clone(), early_id.clone()].into();
+    mempool_consolidator::consolidate(
+        &block(vec![waited, early, unverified]),
+        500,
+        &NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+        &MempoolSpendsRepository::new(db.conn.clone()),
+        &verified,
+    )
+    .await;
+
+    assert_eq!(promoted(&db.conn, &waited_id).await, (Some(500), Some(754)));
+    assert_eq!(promoted(&db.conn, &early_id).await, (Some(500), Some(0)));
+    assert_eq!(promoted(&db.conn, "direct").await, (Some(500), None));
+    // The unverified tx's mempool charm is purged, not promoted.
+    let left = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            format!("SELECT COUNT(*) AS n FROM charms WHERE txid = '{unverified_id}'"),
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get::<i64>("", "n")
+        .unwrap();
+    assert_eq!(left, 0);
+}
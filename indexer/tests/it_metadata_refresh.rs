@@ -0,0 +1,145 @@
+//! Integration tests for the bulk metadata refresh: a queued job re-derives
+//! assets from their stored data, updates what changed and reports it.
+
+mod common;
+
+use charms_indexer::application::indexer::metadata_refresh::{
+    MetadataRefreshConfig, MetadataRefreshWorker,
+};
+use charms_indexer::infrastructure::persistence::repositories::MetadataRefreshRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde_json::{json, Value};
+
+async fn exec(conn: &DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+async fn scalar<T: sea_orm::TryGetable>(conn: &DatabaseConnection, sql: &str) -> T {
+    conn.query_one(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "v")
+        .unwrap()
+}
+
+/// An NFT indexed with symbol `OLD` whose stored data now yields `NEW`, its
+/// token, and an unrelated NFT already up to date.
+async fn seed(conn: &DatabaseConnection) {
+    exec(conn, "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, \
+                                    data, name, symbol, decimals, collection) VALUES \
+                ('n/aa/01', 'tx1', 0, 'c', 100, 'nft', 'Bitcoin', 'mainnet', \
+                 '{\"data\": {\"name\": \"Coin\", \"symbol\": \"NEW\"}}', 'Coin', 'OLD', 8, 'deployer:bc1q'), \
+                ('t/aa/01', 'tx2', 0, 'c', 101, 'token', 'Bitcoin', 'mainnet', \
+                 '{}', 'Coin', 'OLD', 8, NULL), \
+                ('n/bb/01', 'tx3', 0, 'c', 102, 'nft', 'Bitcoin', 'mainnet', \
+                 '{\"data\": {\"name\": \"B\", \"symbol\": \"B\"}}', 'B', 'B', 8, 'deployer:bc1q')")
+        .await;
+    exec(conn, "UPDATE assets SET deployer_address = 'bc1q' WHERE asset_type = 'nft'")
+        .await;
+}
+
+#[tokio::test]
+async fn refresh_updates_changed_assets_and_reports_them() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+    exec(&db.conn, "INSERT INTO metadata_refresh_jobs (app_ids, requested_by) VALUES (NULL, 'ops')")
+        .await;
+
+    let worker = MetadataRefreshWorker::new(
+        MetadataRefreshRepository::new(db.conn.clone()),
+        MetadataRefreshConfig {
+            batch_size: 1,
+            ..MetadataRefreshConfig::default()
+        },
+    );
+    assert_eq!(worker.run_once().await, Some(1));
+    assert_eq!(worker.run_once().await, None);
+
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT string_agg(app_id || '=' || symbol, ' ' ORDER BY app_id) AS v FROM assets"
+        )
+        .await,
+        "n/aa/01=NEW n/bb/01=B t/aa/01=NEW"
+    );
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT status || '/' || assets_scanned || '/' || assets_changed AS v \
+               FROM metadata_refresh_jobs"
+        )
+        .await,
+        "done/3/2"
+    );
+    assert_eq!(
+        scalar::<Value>(
+            &db.conn,
+            "SELECT changes AS v FROM metadata_refresh_results WHERE app_id = 'n/aa/01'"
+        )
+        .await,
+        json!({"symbol": {"old": "OLD", "new": "NEW"}})
+    );
+    assert_eq!(
+        scalar::<i64>(
+            &db.conn,
+            "SELECT COUNT(*) AS v FROM metadata_refresh_results WHERE app_id = 't/aa/01'"
+        )
+        .await,
+        1
+    );
+}
+
+#[tokio::test]
+async fn listed_app_ids_scope_the_job_and_reach_their_tokens() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+    exec(&db.conn, "UPDATE assets SET symbol = 'STALE' WHERE app_id = 'n/bb/01'")
+        .await;
+    exec(&db.conn, "INSERT INTO metadata_refresh_jobs (app_ids, requested_by) \
+                    VALUES (ARRAY['n/aa/01'], 'ops')")
+        .await;
+
+    let worker = MetadataRefreshWorker::new(
+        MetadataRefreshRepository::new(db.conn.clone()),
+        MetadataRefreshConfig::default(),
+    );
+    assert_eq!(worker.run_once().await, Some(1));
+
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT string_agg(app_id, ' ' ORDER BY app_id) AS v FROM metadata_refresh_results"
+        )
+        .await,
+        "n/aa/01 t/aa/01"
+    );
+    assert_eq!(
+        scalar::<String>(&db.conn, "SELECT symbol AS v FROM assets WHERE app_id = 'n/bb/01'")
+            .await,
+        "STALE"
+    );
+}
+
+#[tokio::test]
+async fn only_one_job_is_active_at_a_time() {
+    let db = TestDb::new().await;
+    exec(&db.conn, "INSERT INTO metadata_refresh_jobs (requested_by) VALUES ('ops')")
+        .await;
+    let second = db
+        .conn
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            "INSERT INTO metadata_refresh_jobs (requested_by) VALUES ('ops')".to_string(),
+        ))
+        .await;
+    assert!(second.is_err());
+
+    exec(&db.conn, "UPDATE metadata_refresh_jobs SET status = 'done'").await;
+    exec(&db.conn, "INSERT INTO metadata_refresh_jobs (requested_by) VALUES ('ops')")
+        .await;
+}
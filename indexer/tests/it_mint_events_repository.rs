@@ -0,0 +1,84 @@
+//! Integration tests for `MintEventsRepository` — reprocessing a block must
+//! not duplicate issuance history.
+
+mod common;
+
+use charms_indexer::infrastructure::persistence::repositories::MintEventsRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, FromQueryResult, Statement};
+
+#[derive(FromQueryResult, Debug, PartialEq, Eq)]
+struct MintRow {
+    app_id: String,
+    txid: String,
+    minted_amount: i64,
+    minter_address: Option<String>,
+}
+
+#[tokio::test]
+async fn record_batch_dedups_on_network_txid_and_app_id() {
+    let db = TestDb::new().await;
+    let repo = MintEventsRepository::new(db.conn.clone());
+    let mint = (
+        "t/aa/bb".to_string(),
+        "tx1".to_string(),
+        100u64,
+        1000i64,
+        Some("addrA".to_string()),
+    );
+
+    repo.record_batch(std::slice::from_ref(&mint), "mainnet")
+        .await
+        .unwrap();
+    // Same block processed again (reindex).
+    repo.record_batch(&[mint], "mainnet").await.unwrap();
+
+    let rows = MintRow::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        "SELECT app_id, txid, minted_amount, minter_address FROM mint_events".to_string(),
+    ))
+    .all(&db.conn)
+    .await
+    .unwrap();
+    assert_eq!(
+        rows,
+        vec![MintRow {
+            app_id: "t/aa/bb".to_string(),
+            txid: "tx1".to_string(),
+            minted_amount: 1000,
+            minter_address: Some("addrA".to_string()),
+        }]
+    );
+}
+
+/// The same (txid, app_id) on another network is a separate mint.
+#[tokio::test]
+async fn record_batch_keeps_networks_apart() {
+    let db = TestDb::new().await;
+    let repo = MintEventsRepository::new(db.conn.clone());
+    let mint = (
+        "t/aa/bb".to_string(),
+        "tx1".to_string(),
+        100u64,
+        1000i64,
+        None,
+    );
+
+    repo.record_batch(std::slice::from_ref(&mint), "mainnet")
+        .await
+        .unwrap();
+    repo.record_batch(&[mint], "testnet4").await.unwrap();
+
+    let networks: Vec<String> = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT network FROM mint_events ORDER BY network".to_string(),
+        ))
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.try_get("", "network").unwrap())
+        .collect();
+    assert_eq!(networks, ["mainnet", "testnet4"]);
+}
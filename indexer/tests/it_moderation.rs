@@ -0,0 +1,95 @@
+//! An operator's `moderation_status` survives the indexer rewriting a row.
+
+mod common;
+
+use charms_indexer::domain::models::WriteStamp;
+use charms_indexer::infrastructure::persistence::repositories::{
+    AssetRepository, CharmRepository,
+};
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+use serde_json::json;
+
+async fn status(conn: &sea_orm::DatabaseConnection, table: &str) -> String {
+    conn.query_one(Statement::from_string(
+        DbBackend::Postgres,
+        format!("SELECT moderation_status FROM {table}"),
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "moderation_status")
+    .unwrap()
+}
+
+async fn moderate(conn: &sea_orm::DatabaseConnection, table: &str, to: &str) {
+    conn.execute(Statement::from_string(
+        DbBackend::Postgres,
+        format!("UPDATE {table} SET moderation_status = '{to}'"),
+    ))
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn reindex_and_block_writes_keep_a_hidden_charm_hidden() {
+    let db = TestDb::new().await;
+    let row = (
+        "aa".to_string(),
+        0,
+        100u64,
+        json!({"amount": 7}),
+        "token".to_string(),
+        "Bitcoin".to_string(),
+        "mainnet".to_string(),
+        Some("bc1qxxx".to_string()),
+        "t/x/y".to_string(),
+        7i64,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    CharmRepository::new(db.conn.clone())
+        .save_batch(vec![row.clone()])
+        .await
+        .unwrap();
+    assert_eq!(status(&db.conn, "charms").await, "visible");
+
+    moderate(&db.conn, "charms", "hidden").await;
+    let mut reclassified = row;
+    reclassified.13 = Some("mint".to_string());
+    CharmRepository::new(db.conn.clone())
+        .with_stamp(WriteStamp::reindex("run-1"))
+        .save_batch(vec![reclassified])
+        .await
+        .unwrap();
+
+    assert_eq!(status(&db.conn, "charms").await, "hidden");
+}
+
+#[tokio::test]
+async fn asset_consolidation_keeps_a_flagged_asset_flagged() {
+    let db = TestDb::new().await;
+    let token = |txid: &str, supply: i64| {
+        (
+            "t/aa/01".to_string(),
+            txid.to_string(),
+            0,
+            "charm-t/aa/01".to_string(),
+            100u64,
+            json!({ "amount": supply }),
+            "token".to_string(),
+            "Bitcoin".to_string(),
+            "mainnet".to_string(),
+        )
+    };
+    let repo = AssetRepository::new(db.conn.clone());
+    repo.save_batch(vec![token("tx1", 10)]).await.unwrap();
+    moderate(&db.conn, "assets", "flagged").await;
+
+    repo.save_batch(vec![token("tx2", 5)]).await.unwrap();
+
+    assert_eq!(status(&db.conn, "assets").await, "flagged");
+}
@@ -0,0 +1,95 @@
+//! Integration tests for the monitored-address lifecycle: TTL expiry,
+//! demotion by the GC, and renewal by a fresh API call.
+
+mod common;
+
+use charms_indexer::application::indexer::gc::{GarbageCollector, GcConfig, GcSweep};
+use charms_indexer::infrastructure::persistence::repositories::{
+    MonitoredAddressesRepository, SummaryRepository,
+};
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+fn sorted(set: std::collections::HashSet<String>) -> Vec<String> {
+    let mut v: Vec<String> = set.into_iter().collect();
+    v.sort();
+    v
+}
+
+async fn utxo_count(db: &TestDb, address: &str) -> i64 {
+    db.conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS n FROM address_utxos WHERE address = $1",
+            [address.into()],
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "n")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn expired_address_is_untracked_demoted_and_renewed() {
+    let db = TestDb::new().await;
+    let repo = MonitoredAddressesRepository::new(db.conn.clone());
+
+    db.conn
+        .execute_unprepared(
+            "INSERT INTO monitored_addresses \
+                (address, network, source, seeded_at, seed_height, seed_block_hash, last_queried_at, expires_at) VALUES \
+                ('expired', 'mainnet', 'api',     NOW() - INTERVAL '100 days', 10, 'h', NOW() - INTERVAL '100 days', NOW() - INTERVAL '10 days'), \
+                ('holder',  'mainnet', 'api',     NOW() - INTERVAL '100 days', 10, 'h', NOW() - INTERVAL '100 days', NOW() - INTERVAL '10 days'), \
+                ('active',  'mainnet', 'api',     NOW(),                       10, 'h', NOW(),                      NOW() + INTERVAL '90 days'), \
+                ('charmed', 'mainnet', 'indexer', NOW(),                       10, 'h', NULL,                       NULL)",
+        )
+        .await
+        .unwrap();
+    db.conn
+        .execute_unprepared(
+            "INSERT INTO address_utxos (txid, vout, network, address, value, block_height) VALUES \
+                ('u1', 0, 'mainnet', 'expired', 1000, 5), \
+                ('u2', 0, 'mainnet', 'holder',  2000, 5); \
+             INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id, address) VALUES \
+                ('c1', 0, 5, 'nft', 'Bitcoin', 'mainnet', 'n/x', 'holder')",
+        )
+        .await
+        .unwrap();
+
+    // Expired rows drop out of the tracked sets right away, unless the
+    // address still holds charms.
+    let tracked = vec!["active", "charmed", "holder"];
+    assert_eq!(sorted(repo.load_set("mainnet").await.unwrap()), tracked);
+    assert_eq!(sorted(repo.load_seeded_set("mainnet").await.unwrap()), tracked);
+
+    // The GC demotes it: UTXOs and seed are dropped, the row stays.
+    let gc = GarbageCollector::new(
+        db.conn.clone(),
+        SummaryRepository::new(db.conn.clone()),
+        GcConfig {
+            sweeps: vec![GcSweep::ExpiredMonitors],
+            ..GcConfig::default()
+        },
+    );
+    assert_eq!(gc.run_once().await, vec![(GcSweep::ExpiredMonitors, 1)]);
+    assert_eq!(utxo_count(&db, "expired").await, 0);
+    assert_eq!(utxo_count(&db, "holder").await, 1);
+    // Demoted and expired: the auto-seeder leaves it alone.
+    assert!(repo.fetch_unseeded("mainnet", 10).await.unwrap().is_empty());
+
+    // A fresh balance call renews the TTL (as the API does), which puts it
+    // back in the seeding queue; once seeded it is tracked again.
+    db.conn
+        .execute_unprepared(
+            "UPDATE monitored_addresses \
+                SET last_queried_at = NOW(), expires_at = NOW() + INTERVAL '90 days' \
+              WHERE address = 'expired'",
+        )
+        .await
+        .unwrap();
+    assert_eq!(repo.fetch_unseeded("mainnet", 10).await.unwrap(), vec!["expired"]);
+    repo.mark_seeded("expired", "mainnet", 20, "h2").await.unwrap();
+    assert!(repo.load_seeded_set("mainnet").await.unwrap().contains("expired"));
+    assert_eq!(gc.run_once().await, vec![(GcSweep::ExpiredMonitors, 0)]);
+}
@@ -0,0 +1,226 @@
+//! Integration tests for off-chain NFT metadata: which NFTs of a block are
+//! queued, and the fetcher against a local mock server (success, oversize
+//! document, timeout with retries).
+
+mod common;
+
+use std::time::Duration;
+
+use charms_indexer::application::indexer::offchain_metadata::{
+    FetchError, OffchainMetadataConfig, OffchainMetadataFetcher,
+};
+use charms_indexer::infrastructure::persistence::repositories::OffchainMetadataRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+async fn exec(conn: &DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+async fn scalar<T: sea_orm::TryGetable>(conn: &DatabaseConnection, sql: &str) -> T {
+    conn.query_one(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "v")
+        .unwrap()
+}
+
+/// One NFT at block 100 pointing at `url` through its image, queued.
+async fn queue_one(conn: &DatabaseConnection, url: &str) {
+    exec(
+        conn,
+        &format!(
+            "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, image_url) \
+             VALUES ('n/aa/01', 'tx1', 0, 'charm-n/aa/01', 100, 'nft', 'Bitcoin', 'mainnet', '{url}')"
+        ),
+    )
+    .await;
+    assert_eq!(
+        OffchainMetadataRepository::new(conn.clone())
+            .enqueue_block_assets("mainnet", 100)
+            .await
+            .unwrap(),
+        1
+    );
+}
+
+async fn fetch_state(conn: &DatabaseConnection) -> String {
+    scalar(
+        conn,
+        "SELECT status || '/' || attempts || '/' || COALESCE(last_error, '-') AS v \
+           FROM offchain_metadata_fetches",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn block_nfts_linking_a_document_are_queued_once() {
+    let db = TestDb::new().await;
+    exec(&db.conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, app_id, data) VALUES \
+                    ('tx1', 0, 100, 'nft', 'Bitcoin', 'mainnet', 'n/aa/01', \
+                     '{\"native_data\": {\"tx\": {\"outs\": [{\"0\": {\"name\": \"A\", \"metadata_url\": \"ipfs://bafy/a.json\"}}]}}}')")
+        .await;
+    exec(&db.conn, "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, image_url, offchain_metadata) VALUES \
+                    ('n/aa/01', 'tx1', 0, 'c', 100, 'nft', 'Bitcoin', 'mainnet', 'https://x/a.png', NULL), \
+                    ('n/bb/01', 'tx2', 0, 'c', 100, 'nft', 'Bitcoin', 'mainnet', 'https://x/b.JSON?v=1', NULL), \
+                    ('n/cc/01', 'tx3', 0, 'c', 100, 'nft', 'Bitcoin', 'mainnet', 'https://x/c.png', NULL), \
+                    ('n/dd/01', 'tx4', 0, 'c', 100, 'nft', 'Bitcoin', 'mainnet', 'https://x/d.json', '{}'), \
+                    ('n/ee/01', 'tx5', 0, 'c', 101, 'nft', 'Bitcoin', 'mainnet', 'https://x/e.json', NULL), \
+                    ('t/ff/01', 'tx6', 0, 'c', 100, 'token', 'Bitcoin', 'mainnet', 'https://x/f.json', NULL), \
+                    ('n/gg/01', 'tx7', 0, 'c', 100, 'nft', 'Bitcoin', 'testnet4', 'https://x/g.json', NULL)")
+        .await;
+
+    let repo = OffchainMetadataRepository::new(db.conn.clone());
+    assert_eq!(repo.enqueue_block_assets("mainnet", 100).await.unwrap(), 2);
+    assert_eq!(repo.enqueue_block_assets("mainnet", 100).await.unwrap(), 0);
+
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT string_agg(a.app_id || '=' || f.url, ' ' ORDER BY a.app_id) AS v \
+               FROM offchain_metadata_fetches f JOIN assets a ON a.id = f.asset_id"
+        )
+        .await,
+        "n/aa/01=ipfs://bafy/a.json n/bb/01=https://x/b.JSON?v=1"
+    );
+}
+
+/// Answers every request with `head` (status line and headers) and `body`
+/// after `delay`, then closes the connection.
+async fn mock_server(head: &'static str, body: Vec<u8>, delay: Duration) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/meta.json", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let body = body.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                tokio::time::sleep(delay).await;
+                let _ = socket.write_all(head.as_bytes()).await;
+                let _ = socket.write_all(&body).await;
+                socket.shutdown().await.ok();
+            });
+        }
+    });
+    url
+}
+
+/// Short limits and immediate retries, so each `run_once` re-attempts.
+fn test_config() -> OffchainMetadataConfig {
+    OffchainMetadataConfig {
+        poll_interval: Duration::from_millis(10),
+        timeout: Duration::from_millis(300),
+        max_bytes: 1024,
+        max_attempts: 2,
+        retry_base: Duration::ZERO,
+        ..OffchainMetadataConfig::default()
+    }
+}
+
+fn fetcher(conn: &DatabaseConnection) -> OffchainMetadataFetcher {
+    OffchainMetadataFetcher::new(OffchainMetadataRepository::new(conn.clone()), test_config())
+}
+
+#[tokio::test]
+async fn fetched_document_is_stored_on_the_asset() {
+    let db = TestDb::new().await;
+    let doc = json!({"attributes": [{"trait_type": "eyes", "value": "laser"}]});
+    let url = mock_server(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json; charset=utf-8\r\nConnection: close\r\n\r\n",
+        doc.to_string().into_bytes(),
+        Duration::ZERO,
+    )
+    .await;
+    queue_one(&db.conn, &url).await;
+    let fetcher = fetcher(&db.conn);
+
+    assert_eq!(fetcher.run_once().await, 1);
+    assert_eq!(fetcher.run_once().await, 0);
+
+    assert_eq!(fetch_state(&db.conn).await, "fetched/1/-");
+    assert_eq!(
+        scalar::<Value>(&db.conn, "SELECT offchain_metadata AS v FROM assets").await,
+        doc
+    );
+}
+
+#[tokio::test]
+async fn oversize_document_is_given_up_without_retry() {
+    let db = TestDb::new().await;
+    // No Content-Length: the limit must hold while streaming the body.
+    let big = format!("{{\"pad\": \"{}\"}}", "x".repeat(4096));
+    let url = mock_server(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n",
+        big.into_bytes(),
+        Duration::ZERO,
+    )
+    .await;
+    queue_one(&db.conn, &url).await;
+    let fetcher = fetcher(&db.conn);
+
+    assert_eq!(
+        fetcher.fetch(&url).await,
+        Err(FetchError::TooLarge { limit: 1024 })
+    );
+    assert_eq!(fetcher.run_once().await, 1);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(fetcher.run_once().await, 0);
+
+    assert_eq!(
+        fetch_state(&db.conn).await,
+        "failed/1/document larger than 1024 bytes"
+    );
+    assert!(
+        scalar::<Option<Value>>(&db.conn, "SELECT offchain_metadata AS v FROM assets")
+            .await
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn timeouts_are_retried_then_given_up() {
+    let db = TestDb::new().await;
+    let url = mock_server(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n",
+        b"{}".to_vec(),
+        Duration::from_secs(5),
+    )
+    .await;
+    queue_one(&db.conn, &url).await;
+    let fetcher = fetcher(&db.conn);
+
+    assert_eq!(fetcher.run_once().await, 1);
+    assert_eq!(fetch_state(&db.conn).await, "pending/1/timed out");
+
+    // The retry is due once the zero backoff has elapsed; attempt 2 of 2
+    // exhausts the fetch.
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(fetcher.run_once().await, 1);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(fetcher.run_once().await, 0);
+    assert_eq!(fetch_state(&db.conn).await, "failed/2/timed out");
+}
+
+#[tokio::test]
+async fn non_json_content_type_is_rejected() {
+    let url = mock_server(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nConnection: close\r\n\r\n",
+        b"{}".to_vec(),
+        Duration::ZERO,
+    )
+    .await;
+    let db = TestDb::new().await;
+    let fetcher = fetcher(&db.conn);
+    assert_eq!(
+        fetcher.fetch(&url).await,
+        Err(FetchError::ContentType("text/html".to_string()))
+    );
+}
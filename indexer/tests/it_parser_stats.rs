@@ -0,0 +1,91 @@
+//! Integration tests for the parser counters: outcomes recorded into the
+//! accumulator land in the hourly `parser_stats` buckets, added up across
+//! flushes and kept apart per source.
+
+mod common;
+
+use charms_indexer::domain::services::SpellFailure;
+use charms_indexer::infrastructure::persistence::repositories::ParserStatsRepository;
+use charms_indexer::utils::parser_stats::{ParseSource, ParserStats};
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+/// Counters of `source` summed over all buckets, in column order.
+async fn persisted(conn: &DatabaseConnection, source: &str) -> [i64; 7] {
+    let row = conn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COALESCE(SUM(txs_scanned), 0)::BIGINT AS a, \
+                    COALESCE(SUM(envelopes_found), 0)::BIGINT AS b, \
+                    COALESCE(SUM(parsed), 0)::BIGINT AS c, \
+                    COALESCE(SUM(no_envelope), 0)::BIGINT AS d, \
+                    COALESCE(SUM(cbor_error), 0)::BIGINT AS e, \
+                    COALESCE(SUM(version_unsupported), 0)::BIGINT AS f, \
+                    COALESCE(SUM(verification_failed), 0)::BIGINT AS g \
+               FROM parser_stats WHERE network = 'mainnet' AND source = $1",
+            [source.into()],
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    ["a", "b", "c", "d", "e", "f", "g"].map(|c| row.try_get::<i64>("", c).unwrap())
+}
+
+async fn flush(stats: &ParserStats, repo: &ParserStatsRepository) {
+    for (source, counts) in stats.take("mainnet") {
+        repo.add("mainnet", source, &counts).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn flushed_counts_add_up_per_source() {
+    let db = TestDb::new().await;
+    let repo = ParserStatsRepository::new(db.conn.clone());
+    let stats = ParserStats::default();
+
+    for outcome in [
+        Ok(()),
+        Ok(()),
+        Err(SpellFailure::NoEnvelope),
+        Err(SpellFailure::NoEnvelope),
+        Err(SpellFailure::NoEnvelope),
+        Err(SpellFailure::CborError),
+        Err(SpellFailure::VersionUnsupported(23)),
+    ] {
+        stats.record("mainnet", ParseSource::Block, outcome);
+    }
+    stats.record(
+        "mainnet",
+        ParseSource::Mempool,
+        Err(SpellFailure::CborError),
+    );
+    stats.record("testnet4", ParseSource::Block, Ok(()));
+    flush(&stats, &repo).await;
+
+    // A second block in the same hour adds to the bucket.
+    stats.record(
+        "mainnet",
+        ParseSource::Block,
+        Err(SpellFailure::VerificationFailed),
+    );
+    stats.record("mainnet", ParseSource::Block, Ok(()));
+    flush(&stats, &repo).await;
+
+    assert_eq!(persisted(&db.conn, "block").await, [9, 6, 3, 3, 1, 1, 1]);
+    assert_eq!(persisted(&db.conn, "mempool").await, [1, 1, 0, 0, 1, 0, 0]);
+
+    // Other networks stay in memory until their own block.
+    let testnet: i64 = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS n FROM parser_stats WHERE network = 'testnet4'",
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "n")
+        .unwrap();
+    assert_eq!(testnet, 0);
+    assert_eq!(stats.take("testnet4")[0].1.parsed, 1);
+}
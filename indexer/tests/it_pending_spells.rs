@@ -0,0 +1,165 @@
+//! Integration tests for spells of an unsupported protocol version: capture
+//! into `pending_spells` by block detection, and the drain once supported.
+
+mod common;
+
+use std::sync::Mutex;
+
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::consensus::deserialize;
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash, CompactTarget, Transaction};
+use charms_client::CURRENT_VERSION;
+use charms_indexer::application::indexer::block::detection;
+use charms_indexer::application::indexer::block::pending_spells::{self, DrainSummary};
+use charms_indexer::domain::errors::BlockProcessorError;
+use charms_indexer::domain::services::CharmService;
+use charms_indexer::infrastructure::persistence::repositories::{
+    AssetRepository, CharmRepository, DexOrdersRepository, PendingSpell, PendingSpellsRepository,
+    StatsHoldersRepository,
+};
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+/// The V10 DEX bid of the parser corpus with its version byte set to 23.
+const FUTURE_TX_HEX: &str = include_str!("fixtures/parser/future_version_v23.hex");
+
+fn block_with(tx: Transaction) -> Block {
+    Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata: vec![tx],
+    }
+}
+
+async fn rows(repo: &PendingSpellsRepository) -> Vec<PendingSpell> {
+    // Every captured row of the network, whatever its version.
+    repo.ready("mainnet", u32::MAX).await.unwrap()
+}
+
+#[tokio::test]
+async fn block_detection_captures_unsupported_spell() {
+    let db = TestDb::new().await;
+    let pending = PendingSpellsRepository::new(db.conn.clone());
+    let charm_service = CharmService::new(
+        CharmRepository::new(db.conn.clone()),
+        AssetRepository::new(db.conn.clone()),
+        StatsHoldersRepository::new(db.conn.clone()),
+        DexOrdersRepository::new(db.conn.clone()),
+    );
+    let tx_hex = FUTURE_TX_HEX.trim();
+    let tx: Transaction = deserialize(&hex::decode(tx_hex).unwrap()).unwrap();
+    let txid = tx.txid().to_string();
+
+    // First seen in mempool, then mined at 900.
+    pending
+        .record(&txid, "mainnet", tx_hex, 23, None)
+        .await
+        .unwrap();
+    let (transactions, charms, assets, mints) = detection::detect_charms(
+        &block_with(tx),
+        900,
+        905,
+        "mainnet",
+        "Bitcoin",
+        &charm_service,
+        None,
+        Some(&pending),
+    )
+    .await;
+
+    assert!(transactions.is_empty() && charms.is_empty() && assets.is_empty() && mints.is_empty());
+    assert_eq!(
+        rows(&pending).await,
+        vec![PendingSpell {
+            txid,
+            raw_hex: tx_hex.to_string(),
+            detected_version: 23,
+            block_height: Some(900),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn later_mempool_sighting_keeps_block_height() {
+    let db = TestDb::new().await;
+    let pending = PendingSpellsRepository::new(db.conn.clone());
+    pending
+        .record("aa", "mainnet", "00", 23, Some(900))
+        .await
+        .unwrap();
+    pending
+        .record("aa", "mainnet", "00", 23, None)
+        .await
+        .unwrap();
+    assert_eq!(rows(&pending).await[0].block_height, Some(900));
+}
+
+#[tokio::test]
+async fn drain_reprocesses_supported_blocks_and_keeps_the_rest() {
+    let db = TestDb::new().await;
+    let pending = PendingSpellsRepository::new(db.conn.clone());
+    let stats_holders = StatsHoldersRepository::new(db.conn.clone());
+    let supported = CURRENT_VERSION;
+    for (txid, version, height) in [
+        ("a1", supported, Some(100)),
+        ("a2", supported, Some(100)),
+        ("m1", supported, None),
+        ("f1", supported, Some(102)),
+        ("n1", supported + 1, Some(101)),
+    ] {
+        pending
+            .record(txid, "mainnet", "00", version, height)
+            .await
+            .unwrap();
+    }
+    pending
+        .record("t1", "testnet4", "00", supported, Some(100))
+        .await
+        .unwrap();
+
+    let reprocessed = Mutex::new(Vec::new());
+    let summary = pending_spells::drain(&pending, &stats_holders, "mainnet", |height| {
+        reprocessed.lock().unwrap().push(height);
+        async move {
+            if height == 102 {
+                Err(BlockProcessorError::ProcessingError("rpc down".into()))
+            } else {
+                Ok(())
+            }
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        summary,
+        DrainSummary {
+            blocks: 1,
+            spells: 2,
+            unconfirmed_dropped: 1,
+        }
+    );
+    assert_eq!(*reprocessed.lock().unwrap(), vec![100, 102]);
+    let left: Vec<String> = rows(&pending).await.into_iter().map(|p| p.txid).collect();
+    assert_eq!(left, vec!["n1", "f1"]);
+    let other = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS n FROM pending_spells WHERE network = 'testnet4'".to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get::<i64>("", "n")
+        .unwrap();
+    assert_eq!(other, 1);
+}
@@ -0,0 +1,115 @@
+//! A monitored address receiving an output of a spell-shaped tx the parser
+//! could not read gets the UTXO flagged `possible_charm`.
+
+mod common;
+
+use std::str::FromStr;
+
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    Address, Block, BlockHash, CompactTarget, Network, OutPoint, ScriptBuf, Sequence, Transaction,
+    TxIn, TxOut, Txid, Witness,
+};
+use charms_indexer::application::indexer::block::utxo_indexer;
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+const OWNER: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+/// A tx paying 546 sats to `OWNER` and spending an outpoint with `witness`.
+fn payment(witness: Vec<Vec<u8>>, seed: u8) -> Transaction {
+    let owner = Address::from_str(OWNER)
+        .unwrap()
+        .require_network(Network::Bitcoin)
+        .unwrap();
+    Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::from_byte_array([seed; 32]), 0),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::from_slice(&witness),
+        }],
+        output: vec![TxOut {
+            value: 546,
+            script_pubkey: owner.script_pubkey(),
+        }],
+    }
+}
+
+/// Taproot script-path witness whose envelope holds garbage after the
+/// spell marker: no parser version can read it.
+fn unparseable_spell_witness() -> Vec<Vec<u8>> {
+    let mut script = vec![0x20];
+    script.extend([7u8; 32]);
+    script.extend([0xac, 0x00, 0x63, 0x05]);
+    script.extend(b"spell");
+    script.extend([0x04, 0xde, 0xad, 0xbe, 0xef, 0x68]);
+    vec![vec![1; 64], script, vec![0xc0; 33]]
+}
+
+#[tokio::test]
+async fn spell_like_outputs_to_monitored_addresses_are_flagged() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    repos
+        .monitored_addresses
+        .register_batch(&[OWNER.to_string()], "mainnet", "indexer")
+        .await
+        .unwrap();
+
+    let spell_like = payment(unparseable_spell_witness(), 1);
+    let plain = payment(vec![vec![1; 64]], 2);
+    let block = Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata: vec![spell_like.clone(), plain.clone()],
+    };
+
+    utxo_indexer::update_monitored_utxos(
+        &block,
+        100,
+        &NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+        &repos.monitored_addresses,
+        &repos.utxo,
+    )
+    .await
+    .unwrap();
+
+    let rows = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT txid, possible_charm FROM address_utxos ORDER BY possible_charm DESC",
+        ))
+        .await
+        .unwrap();
+    let flags: Vec<(String, bool)> = rows
+        .iter()
+        .map(|r| {
+            (
+                r.try_get("", "txid").unwrap(),
+                r.try_get("", "possible_charm").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        flags,
+        [
+            (spell_like.txid().to_string(), true),
+            (plain.txid().to_string(), false),
+        ]
+    );
+}
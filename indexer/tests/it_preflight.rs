@@ -0,0 +1,76 @@
+//! Integration tests for the pre-flight database checks: a fresh fixture
+//! schema has every migration pending, recording them clears the check, and
+//! an unreachable database skips the checks that depend on it.
+
+mod common;
+
+use charms_indexer::application::preflight::{self, Status};
+use charms_indexer::infrastructure::persistence::migrations::MIGRATIONS;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+fn statuses(results: &[preflight::CheckResult]) -> Vec<(&'static str, Status)> {
+    results.iter().map(|r| (r.check, r.status)).collect()
+}
+
+#[tokio::test]
+async fn an_unmigrated_database_fails_only_the_migrations_check() {
+    let db = TestDb::new().await;
+
+    let results = preflight::database_checks(Ok(&db.conn)).await;
+    assert_eq!(
+        statuses(&results),
+        vec![
+            ("database", Status::Pass),
+            ("migrations", Status::Fail),
+            ("scratch_write", Status::Pass),
+        ]
+    );
+    assert!(results[1]
+        .detail
+        .starts_with(&format!("{} pending", MIGRATIONS.len())));
+}
+
+#[tokio::test]
+async fn recorded_migrations_pass() {
+    let db = TestDb::new().await;
+    db.conn
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            "CREATE TABLE seaql_migrations (version VARCHAR PRIMARY KEY, applied_at BIGINT NOT NULL)"
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+    for (version, _) in MIGRATIONS {
+        db.conn
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "INSERT INTO seaql_migrations (version, applied_at) VALUES ($1, 0)",
+                [(*version).into()],
+            ))
+            .await
+            .unwrap();
+    }
+
+    let results = preflight::database_checks(Ok(&db.conn)).await;
+    assert!(
+        results.iter().all(|r| r.status == Status::Pass),
+        "{:?}",
+        results
+    );
+}
+
+#[tokio::test]
+async fn an_unreachable_database_skips_the_dependent_checks() {
+    let results = preflight::database_checks(Err("connection refused".to_string())).await;
+    assert_eq!(
+        statuses(&results),
+        vec![
+            ("database", Status::Fail),
+            ("migrations", Status::Skip),
+            ("scratch_write", Status::Skip),
+        ]
+    );
+    assert_eq!(results[0].detail, "connection refused");
+}
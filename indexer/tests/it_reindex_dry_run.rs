@@ -0,0 +1,330 @@
+//! Integration tests for `reindex --dry-run`: the impact report is built
+//! from stored hex without writing, and matches what the real run changes.
+
+mod common;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::consensus::deserialize;
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash, CompactTarget, Transaction};
+use charms_indexer::application::maintenance::{self, ReindexOptions, ReindexReport, SupplyChange};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::bitcoin::{
+    BitcoinClient, BitcoinClientError, BitcoinProvider, SimpleBitcoinClient,
+};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use rust_decimal::Decimal;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+/// A real mainnet V10 Charms Cast bid order (see `fixtures/parser/README.md`).
+/// Output 0 carries 10000 of `TOKEN`; input 0 spends `cdb65a6e…:3`.
+const DEX_TX_HEX: &str = include_str!("fixtures/parser/dex_bid_order_7269cf1b.hex");
+const CORRUPT_TX_HEX: &str = include_str!("fixtures/parser/corrupt_spell_payload.hex");
+const TOKEN: &str = "t/3d7fe7e4cea6121947af73d70e5119bebd8aa5b7edfe74bfaf6e779a1847bd9b/c975d4e0c292fb95efbda5c13312d6ac1d8b5aeff7f0f1e5578645a2da70ff5f";
+const INPUT_TXID: &str = "cdb65a6e8aa9b6f247c76d349607fb413a3c8045174655bf11aa377f21ec4e01";
+const HEIGHT: u64 = 900;
+
+fn dex_block() -> Block {
+    let tx: Transaction = deserialize(&hex::decode(DEX_TX_HEX.trim()).unwrap()).unwrap();
+    Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata: vec![tx],
+    }
+}
+
+/// Serves `block` at every height.
+#[derive(Debug)]
+struct FixedBlockProvider {
+    block: Block,
+}
+
+#[async_trait]
+impl BitcoinProvider for FixedBlockProvider {
+    fn provider_name(&self) -> String {
+        "fixed".to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(HEIGHT + 10)
+    }
+
+    async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+        Ok(self.block.block_hash())
+    }
+
+    async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        Ok(self.block.clone())
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("not served".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+async fn exec(conn: &DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+/// The range as an older indexer left it: both txs stored with their hex,
+/// the charm at the order's input unspent, a token row with a lower supply
+/// and a holder row that no charm backs.
+async fn seed(conn: &DatabaseConnection, dex_charm_tags: Option<&str>) {
+    let dex_txid = dex_block().txdata[0].txid().to_string();
+    exec(
+        conn,
+        &format!(
+        "INSERT INTO transactions (txid, block_height, ordinal, raw, blockchain, network) VALUES \
+         ('{dex_txid}', {HEIGHT}, 0, '{{\"hex\": \"{}\"}}'::jsonb, 'Bitcoin', 'mainnet'), \
+         ('bad', {HEIGHT}, 1, '{{\"hex\": \"{}\"}}'::jsonb, 'Bitcoin', 'mainnet')",
+        DEX_TX_HEX.trim(),
+        CORRUPT_TX_HEX.trim()
+    ),
+    )
+    .await;
+    exec(conn, &format!(
+        "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount, spent) VALUES \
+         ('{INPUT_TXID}', 3, 800, 'token', 'Bitcoin', 'mainnet', 'addrIn', '{TOKEN}', 4000, false)"
+    ))
+    .await;
+    if let Some(tags) = dex_charm_tags {
+        exec(conn, &format!(
+            "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount, spent, tags) VALUES \
+             ('{dex_txid}', 0, {HEIGHT}, 'token', 'Bitcoin', 'mainnet', 'bc1qgdz2kpmwsfa5s7clsevf9yn4q84te3z6qme4xa', '{TOKEN}', 10000, false, '{tags}')"
+        ))
+        .await;
+    }
+    exec(conn, &format!(
+        "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, total_supply) VALUES \
+         ('{TOKEN}', '{INPUT_TXID}', 0, '3d7f', 800, 'token', 'Bitcoin', 'mainnet', 5000)"
+    ))
+    .await;
+    exec(
+        conn,
+        "INSERT INTO stats_holders (app_id, address, network, total_amount, charm_count) VALUES \
+                ('n/zz/zz', 'ghost', 'mainnet', 5, 1)",
+    )
+    .await;
+    // Holders in line with the seeded charms; the ghost row survives
+    // because the scoped rebuild leaves other assets alone.
+    Repositories::from_connection(conn.clone())
+        .stats_holders
+        .rebuild_from_charms("mainnet", Some(TOKEN))
+        .await
+        .unwrap();
+}
+
+/// (txid, vout, app_id) → (tags, operation, spent)
+type CharmRows = BTreeMap<(String, i32, String), (Option<String>, Option<String>, bool)>;
+
+#[derive(Debug, PartialEq)]
+struct Tables {
+    charms: CharmRows,
+    /// app_id → total_supply
+    assets: BTreeMap<String, Option<Decimal>>,
+    /// (app_id, address) → (total_amount, charm_count)
+    holders: BTreeMap<(String, String), (i64, i32)>,
+}
+
+async fn tables(conn: &DatabaseConnection) -> Tables {
+    let query =
+        |sql: &str| conn.query_all(Statement::from_string(DbBackend::Postgres, sql.to_string()));
+    let mut t = Tables {
+        charms: BTreeMap::new(),
+        assets: BTreeMap::new(),
+        holders: BTreeMap::new(),
+    };
+    for r in query("SELECT txid, vout, app_id, tags, operation, spent FROM charms")
+        .await
+        .unwrap()
+    {
+        t.charms.insert(
+            (
+                r.try_get("", "txid").unwrap(),
+                r.try_get("", "vout").unwrap(),
+                r.try_get("", "app_id").unwrap(),
+            ),
+            (
+                r.try_get("", "tags").unwrap(),
+                r.try_get("", "operation").unwrap(),
+                r.try_get("", "spent").unwrap(),
+            ),
+        );
+    }
+    for r in query("SELECT app_id, total_supply FROM assets")
+        .await
+        .unwrap()
+    {
+        t.assets.insert(
+            r.try_get("", "app_id").unwrap(),
+            r.try_get("", "total_supply").unwrap(),
+        );
+    }
+    for r in query("SELECT app_id, address, total_amount, charm_count FROM stats_holders")
+        .await
+        .unwrap()
+    {
+        t.holders.insert(
+            (
+                r.try_get("", "app_id").unwrap(),
+                r.try_get("", "address").unwrap(),
+            ),
+            (
+                r.try_get("", "total_amount").unwrap(),
+                r.try_get("", "charm_count").unwrap(),
+            ),
+        );
+    }
+    t
+}
+
+/// The report fields a real run can be checked against, derived from the
+/// tables before and after it.
+fn observed(report: &ReindexReport, before: &Tables, after: &Tables) -> ReindexReport {
+    let new_charms: BTreeSet<_> = after
+        .charms
+        .keys()
+        .filter(|k| !before.charms.contains_key(*k))
+        .collect();
+    let keys = |m: &BTreeMap<(String, String), (i64, i32)>| -> BTreeSet<(String, String)> {
+        m.keys().cloned().collect()
+    };
+    ReindexReport {
+        charms_inserted: new_charms.len() as u64,
+        charms_updated: after
+            .charms
+            .iter()
+            .filter(|(k, (tags, op, _))| {
+                before
+                    .charms
+                    .get(*k)
+                    .is_some_and(|(t, o, _)| t != tags || o != op)
+            })
+            .count() as u64,
+        charms_spent: after
+            .charms
+            .iter()
+            .filter(|(k, (_, _, spent))| {
+                *spent && !before.charms.get(*k).is_some_and(|(_, _, s)| *s)
+            })
+            .count() as u64,
+        supply_changes: after
+            .assets
+            .iter()
+            .filter(|(app_id, supply)| before.assets.get(*app_id) != Some(supply))
+            .map(|(app_id, supply)| SupplyChange {
+                app_id: app_id.clone(),
+                old: before.assets.get(app_id).copied().flatten(),
+                new: supply.unwrap_or_default(),
+            })
+            .collect(),
+        holders_added: keys(&after.holders)
+            .difference(&keys(&before.holders))
+            .count() as u64,
+        holders_removed: keys(&before.holders)
+            .difference(&keys(&after.holders))
+            .count() as u64,
+        holders_changed: after
+            .holders
+            .iter()
+            .filter(|(k, v)| before.holders.get(*k).is_some_and(|b| b != *v))
+            .count() as u64,
+        ..report.clone()
+    }
+}
+
+/// Dry run, check nothing moved, then the real run; returns the report and
+/// what the real run actually changed.
+async fn dry_run_then_real(dex_charm_tags: Option<&str>) -> (ReindexReport, ReindexReport) {
+    let db = TestDb::new().await;
+    seed(&db.conn, dex_charm_tags).await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let before = tables(&db.conn).await;
+
+    let report = maintenance::reindex_report(&repos, "mainnet", HEIGHT, HEIGHT)
+        .await
+        .unwrap();
+    assert_eq!(tables(&db.conn).await, before, "dry run must not write");
+
+    let client = BitcoinClient::from_simple_client(SimpleBitcoinClient::from_provider(
+        Arc::new(FixedBlockProvider { block: dex_block() }),
+        NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+    ));
+    let opts = ReindexOptions {
+        network: "mainnet".to_string(),
+        from: HEIGHT,
+        to: HEIGHT,
+        dry_run: false,
+        report_path: None,
+        parser_revision_lt: None,
+        batch_size: 1000,
+        max_attempts: 3,
+        retry_failed: false,
+        force: false,
+    };
+    maintenance::reindex_with_client(client, &repos, &opts)
+        .await
+        .unwrap();
+    let after = tables(&db.conn).await;
+
+    let real = observed(&report, &before, &after);
+    (report, real)
+}
+
+#[tokio::test]
+async fn dry_run_report_matches_the_real_run() {
+    let (report, real) = dry_run_then_real(None).await;
+
+    assert_eq!(report.blocks_scanned, 1);
+    assert_eq!(report.spells_parsed, 1);
+    assert_eq!(report.parser_errors.len(), 1);
+    assert_eq!(report.parser_errors[0].txid, "bad");
+    assert_eq!((report.charms_inserted, report.charms_spent), (1, 1));
+    // 10000 out, 4000 in: a 6000 mint raises the declared supply.
+    assert_eq!(
+        report.supply_changes,
+        vec![SupplyChange {
+            app_id: TOKEN.to_string(),
+            old: Some(Decimal::from(5000)),
+            new: Decimal::from(6000),
+        }]
+    );
+    // The spent input's holder becomes the order output's; the ghost goes.
+    assert_eq!(
+        (
+            report.holders_added,
+            report.holders_changed,
+            report.holders_removed
+        ),
+        (1, 0, 2)
+    );
+    assert_eq!(report, real);
+}
+
+#[tokio::test]
+async fn dry_run_counts_tag_refresh_of_existing_charms() {
+    let (report, real) = dry_run_then_real(Some("stale")).await;
+
+    assert_eq!((report.charms_inserted, report.charms_updated), (0, 1));
+    assert_eq!(report, real);
+}
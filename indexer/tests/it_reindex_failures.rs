@@ -0,0 +1,202 @@
+//! Integration tests for failing blocks in a reindex run: the failure is
+//! recorded and the run goes on, the block is sidelined at the attempt limit,
+//! and `retry_failed` lets it run again.
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    Block, BlockHash, CompactTarget, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Witness,
+};
+use charms_indexer::application::maintenance::{self, ReindexOptions, ReindexSummary};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::bitcoin::{
+    BitcoinClient, BitcoinClientError, BitcoinProvider, SimpleBitcoinClient,
+};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+const FAILING: u64 = 101;
+
+/// Coinbase-only blocks 99..=102, each pointing at the one before.
+fn chain() -> BTreeMap<u64, Block> {
+    let mut blocks = BTreeMap::new();
+    let mut prev = BlockHash::all_zeros();
+    for height in 99..=102u64 {
+        let coinbase = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 312_500_000,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        let block = Block {
+            header: Header {
+                version: Version::TWO,
+                prev_blockhash: prev,
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 1_760_000_000 + height as u32,
+                bits: CompactTarget::from_consensus(0x1d00ffff),
+                nonce: 0,
+            },
+            txdata: vec![coinbase],
+        };
+        prev = block.block_hash();
+        blocks.insert(height, block);
+    }
+    blocks
+}
+
+/// Serves `chain()`, except that `FAILING` errors until `healed` is set.
+#[derive(Debug)]
+struct FlakyProvider {
+    blocks: BTreeMap<u64, Block>,
+    healed: AtomicBool,
+}
+
+#[async_trait]
+impl BitcoinProvider for FlakyProvider {
+    fn provider_name(&self) -> String {
+        "flaky".to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(*self.blocks.keys().last().expect("non-empty chain"))
+    }
+
+    async fn get_block_hash(&self, height: u64) -> Result<BlockHash, BitcoinClientError> {
+        if height == FAILING && !self.healed.load(Ordering::Relaxed) {
+            return Err(BitcoinClientError::Other("boom".to_string()));
+        }
+        self.blocks
+            .get(&height)
+            .map(Block::block_hash)
+            .ok_or_else(|| BitcoinClientError::Other(format!("no block at {height}")))
+    }
+
+    async fn get_block(&self, block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        Ok(self
+            .blocks
+            .values()
+            .find(|block| block.block_hash() == *block_hash)
+            .cloned()
+            .expect("served hash"))
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("not served".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+/// `(failed_attempts, last_error, processed)` of the block at `height`.
+async fn status(conn: &DatabaseConnection, height: u64) -> (i32, Option<String>, bool) {
+    let row = conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            format!(
+                "SELECT failed_attempts, last_error, processed FROM block_status \
+                 WHERE network = 'mainnet' AND block_height = {height}"
+            ),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    (
+        row.try_get("", "failed_attempts").unwrap(),
+        row.try_get("", "last_error").unwrap(),
+        row.try_get("", "processed").unwrap(),
+    )
+}
+
+async fn run(
+    repos: &Repositories,
+    provider: &Arc<FlakyProvider>,
+    retry_failed: bool,
+) -> ReindexSummary {
+    let client = BitcoinClient::from_simple_client(SimpleBitcoinClient::from_provider(
+        provider.clone(),
+        NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+    ));
+    let opts = ReindexOptions {
+        network: "mainnet".to_string(),
+        from: 100,
+        to: 102,
+        dry_run: false,
+        report_path: None,
+        parser_revision_lt: None,
+        batch_size: 2,
+        max_attempts: 2,
+        retry_failed,
+        force: false,
+    };
+    maintenance::reindex_with_client(client, repos, &opts)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn failing_block_is_sidelined_after_the_limit() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let provider = Arc::new(FlakyProvider {
+        blocks: chain(),
+        healed: AtomicBool::new(false),
+    });
+
+    // The failure does not stop the run: the blocks around it complete.
+    let first = run(&repos, &provider, false).await;
+    assert_eq!(first.blocks, 3);
+    assert_eq!(first.failed, vec![FAILING]);
+    assert!(first.sidelined.is_empty());
+    assert!(status(&db.conn, 100).await.2);
+    assert!(status(&db.conn, 102).await.2);
+    let (attempts, error, processed) = status(&db.conn, FAILING).await;
+    assert_eq!(attempts, 1);
+    assert!(error.unwrap().contains("boom"));
+    assert!(!processed);
+
+    // The second failure reaches the limit.
+    let second = run(&repos, &provider, false).await;
+    assert_eq!(second.failed, vec![FAILING]);
+    assert_eq!(second.sidelined, vec![FAILING]);
+    assert_eq!(status(&db.conn, FAILING).await.0, 2);
+
+    // Later runs leave it out.
+    let third = run(&repos, &provider, false).await;
+    assert_eq!(third.blocks, 2);
+    assert!(third.failed.is_empty());
+    assert_eq!(third.sidelined, vec![FAILING]);
+    assert_eq!(status(&db.conn, FAILING).await.0, 2);
+
+    // Until the counters are reset; the block now goes through and its
+    // failure record is cleared.
+    provider.healed.store(true, Ordering::Relaxed);
+    let retried = run(&repos, &provider, true).await;
+    assert_eq!(retried.blocks, 3);
+    assert!(retried.failed.is_empty());
+    assert!(retried.sidelined.is_empty());
+    assert_eq!(status(&db.conn, FAILING).await, (0, None, true));
+}
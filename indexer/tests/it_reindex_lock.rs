@@ -0,0 +1,250 @@
+//! Integration tests for the reindex lock: the live block loop pauses while a
+//! reindex holds the network, and a reindex refuses to start beside a live
+//! indexer (unless forced) or another run.
+
+mod common;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash};
+use charms_indexer::application::indexer::control::PauseGate;
+use charms_indexer::application::maintenance::reindex::LOCK_STALE_SECS;
+use charms_indexer::application::maintenance::{self, ReindexOptions};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::bitcoin::{
+    BitcoinClient, BitcoinClientError, BitcoinProvider, SimpleBitcoinClient,
+};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+/// A node whose every block fetch fails: each height of a run is recorded as
+/// failed, without ending the run.
+#[derive(Debug)]
+struct FailingProvider;
+
+#[async_trait]
+impl BitcoinProvider for FailingProvider {
+    fn provider_name(&self) -> String {
+        "failing".to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(0)
+    }
+
+    async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+        Err(BitcoinClientError::Other("boom".to_string()))
+    }
+
+    async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        Err(BitcoinClientError::Other("boom".to_string()))
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("not served".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+fn opts(force: bool) -> ReindexOptions {
+    ReindexOptions {
+        network: "mainnet".to_string(),
+        from: 100,
+        to: 100,
+        dry_run: false,
+        report_path: None,
+        parser_revision_lt: None,
+        batch_size: 1000,
+        max_attempts: 3,
+        retry_failed: false,
+        force,
+    }
+}
+
+fn client() -> BitcoinClient {
+    BitcoinClient::from_simple_client(SimpleBitcoinClient::from_provider(
+        Arc::new(FailingProvider),
+        NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+    ))
+}
+
+async fn lock_rows(conn: &DatabaseConnection) -> i64 {
+    conn.query_one(Statement::from_string(
+        DbBackend::Postgres,
+        "SELECT COUNT(*) AS n FROM reindex_locks",
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "n")
+    .unwrap()
+}
+
+async fn summary_paused(conn: &DatabaseConnection, network: &str) -> bool {
+    conn.query_one(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT indexer_paused FROM summary WHERE network = $1",
+        [network.into()],
+    ))
+    .await
+    .unwrap()
+    .expect("summary row")
+    .try_get("", "indexer_paused")
+    .unwrap()
+}
+
+/// Drive the live loop the way `BitcoinProcessor::start_processing` does
+/// while a reindex takes and releases the network.
+#[tokio::test]
+async fn the_live_loop_pauses_while_a_reindex_holds_the_network() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    db.conn
+        .execute_unprepared("INSERT INTO summary (network) VALUES ('mainnet'), ('testnet4')")
+        .await
+        .unwrap();
+    let gate = |network: &str| {
+        PauseGate::new(
+            NetworkId::new(NetworkType::Bitcoin, network),
+            repos.control_commands.clone(),
+            repos.reindex_locks.clone(),
+            repos.summary.clone(),
+        )
+    };
+    let (mut live, mut other) = (gate("mainnet"), gate("testnet4"));
+    let tip = 110;
+    let (mut height, mut other_height) = (100, 100);
+    let step = |paused: bool, h: &mut u64| {
+        if !paused && *h < tip {
+            *h += 1;
+        }
+    };
+
+    step(live.is_paused().await, &mut height);
+    assert_eq!(height, 101);
+
+    repos
+        .reindex_locks
+        .acquire("mainnet", "mainnet:90-95@run", 90, 95, LOCK_STALE_SECS)
+        .await
+        .unwrap()
+        .expect("free network");
+    for _ in 0..5 {
+        step(live.is_paused().await, &mut height);
+        step(other.is_paused().await, &mut other_height);
+    }
+    assert_eq!(height, 101, "no live blocks during the reindex");
+    assert_eq!(other_height, 105, "other networks keep indexing");
+    assert!(summary_paused(&db.conn, "mainnet").await);
+    assert!(!summary_paused(&db.conn, "testnet4").await);
+
+    repos
+        .reindex_locks
+        .release("mainnet", "mainnet:90-95@run")
+        .await
+        .unwrap();
+    while height < tip {
+        step(live.is_paused().await, &mut height);
+    }
+    assert!(!summary_paused(&db.conn, "mainnet").await);
+}
+
+#[tokio::test]
+async fn a_crashed_reindex_stops_holding_the_network_once_stale() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    db.conn
+        .execute_unprepared("INSERT INTO summary (network) VALUES ('mainnet')")
+        .await
+        .unwrap();
+    let mut live = PauseGate::new(
+        NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+        repos.control_commands.clone(),
+        repos.reindex_locks.clone(),
+        repos.summary.clone(),
+    );
+
+    repos
+        .reindex_locks
+        .acquire("mainnet", "crashed", 90, 95, LOCK_STALE_SECS)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(live.is_paused().await);
+
+    db.conn
+        .execute_unprepared("UPDATE reindex_locks SET heartbeat_at = NOW() - INTERVAL '1 hour'")
+        .await
+        .unwrap();
+    assert!(!live.is_paused().await);
+    // And a new run takes it over.
+    repos
+        .reindex_locks
+        .acquire("mainnet", "next", 90, 95, LOCK_STALE_SECS)
+        .await
+        .unwrap()
+        .expect("stale lock taken over");
+}
+
+#[tokio::test]
+async fn reindex_refuses_to_run_beside_a_live_indexer_unless_forced() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    repos
+        .indexer_replicas
+        .heartbeat("replica-a", "mainnet", "leader")
+        .await
+        .unwrap();
+
+    let err = maintenance::reindex_with_client(client(), &repos, &opts(false))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("replica-a"), "{}", err);
+    assert!(err.to_string().contains("--force"), "{}", err);
+    assert_eq!(lock_rows(&db.conn).await, 0, "refused before locking");
+
+    // Forced, the run goes ahead and gives the network back when done.
+    let summary = maintenance::reindex_with_client(client(), &repos, &opts(true))
+        .await
+        .unwrap();
+    assert_eq!(summary.failed, vec![100]);
+    assert_eq!(lock_rows(&db.conn).await, 0);
+}
+
+#[tokio::test]
+async fn a_second_reindex_refuses_while_the_first_holds_the_network() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    repos
+        .reindex_locks
+        .acquire("mainnet", "first", 0, 1000, LOCK_STALE_SECS)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let err = maintenance::reindex_with_client(client(), &repos, &opts(true))
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("reindex first is already running"),
+        "{}",
+        err
+    );
+    // The first run's lock is left alone.
+    assert_eq!(
+        repos
+            .reindex_locks
+            .active("mainnet", LOCK_STALE_SECS)
+            .await
+            .unwrap()
+            .map(|lock| lock.run_id),
+        Some("first".to_string())
+    );
+}
@@ -0,0 +1,215 @@
+//! Integration test for reorg rollback: every table wiped above the common
+//! ancestor agrees on which blocks are gone, including charms whose stored
+//! `block_hash` does not match the orphaned branch.
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    Block, BlockHash, CompactTarget, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Witness,
+};
+use charms_indexer::application::indexer::block::reorg::{self, ReorgDecision};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::bitcoin::{
+    BitcoinClient, BitcoinClientError, BitcoinProvider, SimpleBitcoinClient,
+};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+/// The common ancestor of the two branches.
+const FORK: u64 = 100;
+
+/// Coinbase-only blocks 99..=103. Blocks above `FORK` carry `branch` as
+/// their nonce, so two branches share 99..=FORK and differ after it.
+fn chain(branch: u32) -> BTreeMap<u64, Block> {
+    let mut blocks = BTreeMap::new();
+    let mut prev = BlockHash::all_zeros();
+    for height in 99..=103u64 {
+        let coinbase = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 312_500_000,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        let block = Block {
+            header: Header {
+                version: Version::TWO,
+                prev_blockhash: prev,
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 1_760_000_000 + height as u32,
+                bits: CompactTarget::from_consensus(0x1d00ffff),
+                nonce: if height > FORK { branch } else { 0 },
+            },
+            txdata: vec![coinbase],
+        };
+        prev = block.block_hash();
+        blocks.insert(height, block);
+    }
+    blocks
+}
+
+/// Serves one branch of `chain`.
+#[derive(Debug)]
+struct ChainProvider {
+    blocks: BTreeMap<u64, Block>,
+}
+
+#[async_trait]
+impl BitcoinProvider for ChainProvider {
+    fn provider_name(&self) -> String {
+        "chain".to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(*self.blocks.keys().last().expect("non-empty chain"))
+    }
+
+    async fn get_block_hash(&self, height: u64) -> Result<BlockHash, BitcoinClientError> {
+        self.blocks
+            .get(&height)
+            .map(Block::block_hash)
+            .ok_or_else(|| BitcoinClientError::Other(format!("no block at {height}")))
+    }
+
+    async fn get_block(&self, block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        self.blocks
+            .values()
+            .find(|block| block.block_hash() == *block_hash)
+            .cloned()
+            .ok_or_else(|| BitcoinClientError::Other(format!("no block {block_hash}")))
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("not served".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+async fn exec(conn: &DatabaseConnection, sql: String) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql))
+        .await
+        .unwrap();
+}
+
+/// Heights still holding rows in `table`, ascending.
+async fn heights(conn: &DatabaseConnection, table: &str) -> Vec<i32> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        format!(
+            "SELECT DISTINCT block_height FROM {table} \
+             WHERE network = 'mainnet' ORDER BY block_height"
+        ),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| row.try_get("", "block_height").unwrap())
+    .collect()
+}
+
+/// Index 99..=102 of `indexed`, one charm, transaction, asset and UTXO per
+/// block. The charm at 102 records a hash from neither branch, as a row
+/// written before its block was re-fetched would.
+async fn seed(repos: &Repositories, network: &NetworkId, indexed: &BTreeMap<u64, Block>) {
+    let conn = repos.block_status.get_connection();
+    for height in 99..=102u64 {
+        let hash = indexed[&height].block_hash().to_string();
+        repos
+            .block_status
+            .mark_downloaded(height as i32, Some(&hash), None, 1, None, network)
+            .await
+            .unwrap();
+        let charm_hash = if height == 102 { "stale".to_string() } else { hash };
+        exec(
+            &conn,
+            format!(
+                "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, \
+                                     app_id, block_hash, tx_ordinal) \
+                 VALUES ('tx{height}', 0, {height}, 'token', 'bitcoin', 'mainnet', \
+                         't/app{height}', '{charm_hash}', 1)"
+            ),
+        )
+        .await;
+        exec(
+            &conn,
+            format!(
+                "INSERT INTO transactions (txid, block_height, ordinal, blockchain, network) \
+                 VALUES ('tx{height}', {height}, 1, 'bitcoin', 'mainnet')"
+            ),
+        )
+        .await;
+        exec(
+            &conn,
+            format!(
+                "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, \
+                                     asset_type, blockchain, network) \
+                 VALUES ('t/app{height}', 'tx{height}', 0, 'c{height}', {height}, \
+                         'token', 'bitcoin', 'mainnet')"
+            ),
+        )
+        .await;
+        exec(
+            &conn,
+            format!(
+                "INSERT INTO address_utxos (txid, vout, network, address, value, block_height) \
+                 VALUES ('tx{height}', 0, 'mainnet', 'bc1qaaa', 1000, {height})"
+            ),
+        )
+        .await;
+    }
+}
+
+#[tokio::test]
+async fn rollback_wipes_all_tables_above_the_fork_alike() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let network = NetworkId::new(NetworkType::Bitcoin, "mainnet");
+    seed(&repos, &network, &chain(0)).await;
+
+    let winning = chain(1);
+    let next = winning[&103].clone();
+    let client = BitcoinClient::from_simple_client(SimpleBitcoinClient::from_provider(
+        Arc::new(ChainProvider { blocks: winning }),
+        network.clone(),
+    ));
+
+    let decision = reorg::check_and_recover(
+        103,
+        &next,
+        &network,
+        &client,
+        &repos.block_status,
+        &repos.reorg_events,
+    )
+    .await
+    .unwrap();
+    assert!(matches!(decision, ReorgDecision::RolledBackTo(h) if h == FORK));
+
+    let kept = vec![99, FORK as i32];
+    for table in ["charms", "transactions", "assets", "address_utxos"] {
+        assert_eq!(heights(&db.conn, table).await, kept, "{table}");
+    }
+    assert_eq!(heights(&db.conn, "block_status").await, kept);
+}
@@ -0,0 +1,227 @@
+//! Integration tests for the startup rescan: a block whose first pass saw
+//! stale data gains its charm when re-run, a re-run that finds nothing new
+//! changes nothing, and blocks the node no longer has are skipped.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::consensus::deserialize;
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    Block, BlockHash, CompactTarget, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Witness,
+};
+use charms_indexer::application::indexer::block::processor::BlockProcessor;
+use charms_indexer::application::indexer::block::rescan::{self, RescanSummary};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::domain::services::CharmService;
+use charms_indexer::infrastructure::bitcoin::{
+    BitcoinClient, BitcoinClientError, BitcoinProvider, SimpleBitcoinClient,
+};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+/// A real mainnet V10 Charms Cast bid order (see `fixtures/parser/README.md`).
+const DEX_TX_HEX: &str = include_str!("fixtures/parser/dex_bid_order_7269cf1b.hex");
+const DEX_TOKEN: &str = "t/3d7fe7e4cea6121947af73d70e5119bebd8aa5b7edfe74bfaf6e779a1847bd9b/c975d4e0c292fb95efbda5c13312d6ac1d8b5aeff7f0f1e5578645a2da70ff5f";
+
+fn block(txdata: Vec<Transaction>) -> Block {
+    Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+/// What a stale provider answered: the block without its spell.
+fn coinbase_block() -> Block {
+    block(vec![Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: 312_500_000,
+            script_pubkey: ScriptBuf::new(),
+        }],
+    }])
+}
+
+fn dex_block() -> Block {
+    let tx: Transaction = deserialize(&hex::decode(DEX_TX_HEX.trim()).unwrap()).unwrap();
+    block(vec![tx])
+}
+
+/// Serves the current `block` at every height; `None` answers like a
+/// pruned node.
+#[derive(Debug)]
+struct SwappableProvider {
+    block: Mutex<Option<Block>>,
+}
+
+impl SwappableProvider {
+    fn serve(&self, block: Option<Block>) {
+        *self.block.lock().unwrap() = block;
+    }
+}
+
+#[async_trait]
+impl BitcoinProvider for SwappableProvider {
+    fn provider_name(&self) -> String {
+        "swappable".to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(905)
+    }
+
+    async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+        Ok(self
+            .block
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or_else(BlockHash::all_zeros, Block::block_hash))
+    }
+
+    async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        self.block.lock().unwrap().clone().ok_or_else(|| {
+            BitcoinClientError::Other("Block not available (pruned data)".to_string())
+        })
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("not served".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+fn mainnet() -> NetworkId {
+    NetworkId::new(NetworkType::Bitcoin, "mainnet")
+}
+
+fn processor(repos: &Repositories, provider: Arc<SwappableProvider>) -> BlockProcessor {
+    let client =
+        BitcoinClient::from_simple_client(SimpleBitcoinClient::from_provider(provider, mainnet()));
+    BlockProcessor::new(
+        client,
+        CharmService::new(
+            repos.charm.clone(),
+            repos.asset.clone(),
+            repos.stats_holders.clone(),
+            repos.dex_orders.clone(),
+        ),
+        repos,
+    )
+}
+
+async fn token_charms(conn: &DatabaseConnection) -> i64 {
+    conn.query_one(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT COUNT(*) AS n FROM charms WHERE app_id = $1 AND block_height = 900",
+        [DEX_TOKEN.into()],
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "n")
+    .unwrap()
+}
+
+#[tokio::test]
+async fn rescan_adds_the_charm_a_stale_first_pass_missed() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let network_id = mainnet();
+    let provider = Arc::new(SwappableProvider {
+        block: Mutex::new(Some(coinbase_block())),
+    });
+    let bp = processor(&repos, provider.clone());
+
+    // First pass: the provider answered without the spell; the block is done.
+    bp.process_block(900, &network_id).await.unwrap();
+    assert_eq!(token_charms(&db.conn).await, 0);
+
+    let run = || {
+        rescan::rescan(
+            &repos.charm,
+            &repos.stats_holders,
+            &network_id.name,
+            900,
+            900,
+            |height| {
+                let (bp, network_id) = (&bp, &network_id);
+                async move { bp.process_block(height, network_id).await }
+            },
+        )
+    };
+
+    provider.serve(Some(dex_block()));
+    let summary = run().await.unwrap();
+    assert_eq!(summary.blocks, 1);
+    assert!(summary.new_charms > 0);
+    assert_eq!(token_charms(&db.conn).await, 1);
+
+    // Nothing left to find: a second rescan stores nothing new.
+    assert_eq!(
+        run().await.unwrap(),
+        RescanSummary {
+            blocks: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(token_charms(&db.conn).await, 1);
+}
+
+#[tokio::test]
+async fn rescan_skips_blocks_the_node_no_longer_has() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let network_id = mainnet();
+    let provider = Arc::new(SwappableProvider {
+        block: Mutex::new(None),
+    });
+    let bp = processor(&repos, provider);
+
+    let summary = rescan::rescan(
+        &repos.charm,
+        &repos.stats_holders,
+        &network_id.name,
+        899,
+        900,
+        |height| {
+            let (bp, network_id) = (&bp, &network_id);
+            async move { bp.process_block(height, network_id).await }
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        summary,
+        RescanSummary {
+            pruned: 2,
+            ..Default::default()
+        }
+    );
+}
@@ -0,0 +1,246 @@
+//! Integration tests for blocks skipped as pruned/missing: the skip flag,
+//! and the retry pass filling them in once the provider serves them.
+
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    Block, BlockHash, CompactTarget, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Witness,
+};
+use charms_indexer::application::indexer::block::processor::BlockProcessor;
+use charms_indexer::application::indexer::block::skipped_blocks::{self, RetrySummary};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::domain::services::CharmService;
+use charms_indexer::infrastructure::bitcoin::{
+    BitcoinClient, BitcoinClientError, BitcoinProvider, SimpleBitcoinClient,
+};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+
+/// A block holding only a coinbase.
+fn coinbase_block() -> Block {
+    let coinbase = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: 312_500_000,
+            script_pubkey: ScriptBuf::new(),
+        }],
+    };
+    Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata: vec![coinbase],
+    }
+}
+
+/// Serves `block` at every height once `has_block` is set; until then
+/// answers like a pruned node.
+#[derive(Debug)]
+struct PrunedProvider {
+    block: Block,
+    has_block: AtomicBool,
+}
+
+#[async_trait]
+impl BitcoinProvider for PrunedProvider {
+    fn provider_name(&self) -> String {
+        "pruned".to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(110)
+    }
+
+    async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+        Ok(self.block.block_hash())
+    }
+
+    async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        if self.has_block.load(Ordering::Relaxed) {
+            Ok(self.block.clone())
+        } else {
+            Err(BitcoinClientError::Other(
+                "Block not available (pruned data)".to_string(),
+            ))
+        }
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("not served".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+fn mainnet() -> NetworkId {
+    NetworkId::new(NetworkType::Bitcoin, "mainnet")
+}
+
+#[tokio::test]
+async fn skipped_block_is_filled_in_once_served() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let network_id = mainnet();
+    let provider = Arc::new(PrunedProvider {
+        block: coinbase_block(),
+        has_block: AtomicBool::new(false),
+    });
+    let client = BitcoinClient::from_simple_client(SimpleBitcoinClient::from_provider(
+        provider.clone(),
+        network_id.clone(),
+    ));
+    let bp = BlockProcessor::new(
+        client,
+        CharmService::new(
+            repos.charm.clone(),
+            repos.asset.clone(),
+            repos.stats_holders.clone(),
+            repos.dex_orders.clone(),
+        ),
+        &repos,
+    );
+    repos
+        .block_status
+        .mark_skipped_pruned(100, &network_id)
+        .await
+        .unwrap();
+
+    let retry = || {
+        skipped_blocks::retry(
+            &repos.block_status,
+            &repos.stats_holders,
+            &network_id,
+            10,
+            |height| {
+                let (bp, network_id) = (&bp, &network_id);
+                async move { bp.process_block(height, network_id).await }
+            },
+        )
+    };
+
+    // First attempt: the node still answers "pruned".
+    assert_eq!(
+        retry().await.unwrap(),
+        RetrySummary {
+            still_missing: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        repos
+            .block_status
+            .count_skipped_pruned(&network_id)
+            .await
+            .unwrap(),
+        1
+    );
+
+    // Second attempt: the block is served and its row filled in.
+    provider.has_block.store(true, Ordering::Relaxed);
+    assert_eq!(
+        retry().await.unwrap(),
+        RetrySummary {
+            filled: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        repos
+            .block_status
+            .count_skipped_pruned(&network_id)
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        repos
+            .block_status
+            .get_block_hash(100, &network_id)
+            .await
+            .unwrap(),
+        Some(provider.block.block_hash().to_string())
+    );
+    assert_eq!(
+        repos
+            .block_status
+            .get_last_processed_block(&network_id)
+            .await
+            .unwrap(),
+        Some(100)
+    );
+}
+
+#[tokio::test]
+async fn skipped_row_is_neither_processed_nor_a_stored_hash() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let network_id = mainnet();
+    let hash = coinbase_block().block_hash().to_string();
+    repos
+        .block_status
+        .mark_downloaded(99, Some(&hash), None, 1, None, &network_id)
+        .await
+        .unwrap();
+    repos
+        .block_status
+        .mark_processed(99, 0, &network_id)
+        .await
+        .unwrap();
+    repos
+        .block_status
+        .mark_skipped_pruned(100, &network_id)
+        .await
+        .unwrap();
+
+    // The live loop resumes after the last processed block, and the reorg
+    // guard at 101 finds no stored hash for the skipped height.
+    assert_eq!(
+        repos
+            .block_status
+            .get_last_processed_block(&network_id)
+            .await
+            .unwrap(),
+        Some(99)
+    );
+    assert_eq!(
+        repos
+            .block_status
+            .get_block_hash(100, &network_id)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        repos
+            .block_status
+            .get_skipped_pruned(&network_id, 10)
+            .await
+            .unwrap(),
+        vec![100]
+    );
+}
@@ -0,0 +1,280 @@
+//! Integration tests for `export-snapshot` / `import-snapshot`: a seeded
+//! database is exported, loaded into a fresh one, and the two are compared.
+
+mod common;
+
+use std::path::PathBuf;
+
+use charms_indexer::application::maintenance::snapshot::{
+    read_manifest, MANIFEST_FILE, SNAPSHOT_TABLES,
+};
+use charms_indexer::application::maintenance::{export_snapshot, import_snapshot, ExportOptions};
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+async fn exec(conn: &DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+/// Rows of `sql` with every column cast to text, for comparison across DBs.
+async fn rows(conn: &DatabaseConnection, sql: &str) -> Vec<String> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        format!("SELECT row_to_json(q)::text AS r FROM ({sql}) q"),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|r| r.try_get("", "r").unwrap())
+    .collect()
+}
+
+async fn count(conn: &DatabaseConnection, table: &str, network: &str) -> i64 {
+    conn.query_one(Statement::from_string(
+        DbBackend::Postgres,
+        format!("SELECT COUNT(*) AS n FROM {table} WHERE network = '{network}'"),
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "n")
+    .unwrap()
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "charms-snapshot-{}-{}-{}",
+        name,
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+async fn seed(conn: &DatabaseConnection) {
+    exec(conn, "INSERT INTO block_status (block_height, network, blockchain, downloaded, processed, block_hash) VALUES \
+                (100, 'mainnet', 'Bitcoin', true, true, 'h100'), (101, 'mainnet', 'Bitcoin', true, true, 'h101'), \
+                (102, 'mainnet', 'Bitcoin', true, false, 'h102'), (50, 'testnet4', 'Bitcoin', true, true, 't50')")
+        .await;
+    exec(conn, "INSERT INTO transactions (txid, block_height, ordinal, raw, charm, status, confirmations, blockchain, network) VALUES \
+                ('tx1', 100, 1, '{\"hex\": \"0200\"}', '{\"version\": 2}', 'confirmed', 2, 'Bitcoin', 'mainnet'), \
+                ('tx2', 101, 1, '{\"hex\": \"0201\"}', '{\"note\": \"a,b \\\"quoted\\\"\\nline\"}', 'confirmed', 1, 'Bitcoin', 'mainnet'), \
+                ('ttx', 50, 1, '{}', '{}', 'confirmed', 1, 'Bitcoin', 'testnet4')")
+        .await;
+    exec(
+        conn,
+        "INSERT INTO spells (txid, block_height, data, network) VALUES \
+                ('tx1', 100, '{\"ins\": []}', 'mainnet'), ('ttx', 50, '{}', 'testnet4')",
+    )
+    .await;
+    exec(conn, "INSERT INTO charms (txid, vout, block_height, data, asset_type, blockchain, network, address, app_id, amount, spent, tags) VALUES \
+                ('tx1', 0, 100, '{\"name\": \"Bro\"}', 'nft', 'Bitcoin', 'mainnet', 'addrA', 'n/aa/bb', 0, false, NULL), \
+                ('tx1', 1, 100, '{}', 'token', 'Bitcoin', 'mainnet', 'addrA', 't/aa/bb', 60, true, 'x,y'), \
+                ('tx2', 0, 101, '{}', 'token', 'Bitcoin', 'mainnet', 'addrB', 't/aa/bb', 60, false, ''), \
+                ('ttx', 0, 50, '{}', 'token', 'Bitcoin', 'testnet4', 'addrT', 't/cc/dd', 1, false, NULL)")
+        .await;
+    exec(conn, "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, name, description, total_supply, collection) VALUES \
+                ('n/aa/bb', 'tx1', 0, 'n/aa/bb', 100, 'nft', 'Bitcoin', 'mainnet', 'Bro', 'multi\nline, with comma', NULL, 'bros'), \
+                ('t/aa/bb', 'tx1', 1, 't/aa/bb', 100, 'token', 'Bitcoin', 'mainnet', 'Bro', NULL, 123456789012345678901234, NULL), \
+                ('t/cc/dd', 'ttx', 0, 't/cc/dd', 50, 'token', 'Bitcoin', 'testnet4', NULL, NULL, 1, NULL)")
+        .await;
+    exec(
+        conn,
+        "INSERT INTO stats_holders (app_id, address, network, total_amount, charm_count) VALUES \
+                ('n/aa/bb', 'addrB', 'mainnet', 60, 1), ('n/cc/dd', 'addrT', 'testnet4', 1, 1)",
+    )
+    .await;
+    exec(
+        conn,
+        "INSERT INTO summary (network, last_processed_block, total_charms) VALUES \
+                ('mainnet', 101, 3), ('testnet4', 50, 1)",
+    )
+    .await;
+}
+
+/// Representative columns per table, excluding serial ids.
+const SAMPLES: [&str; 7] = [
+    "SELECT block_height, blockchain, downloaded, processed, block_hash FROM block_status WHERE network = 'mainnet' ORDER BY block_height",
+    "SELECT txid, block_height, ordinal, charm, status, confirmations FROM transactions WHERE network = 'mainnet' ORDER BY txid",
+    "SELECT txid, block_height, data, asset_type FROM spells WHERE network = 'mainnet' ORDER BY txid",
+    "SELECT txid, vout, app_id, data, address, amount, spent, tags, date_created FROM charms WHERE network = 'mainnet' ORDER BY txid, vout",
+    "SELECT app_id, name, description, total_supply, decimals, collection, created_at FROM assets WHERE network = 'mainnet' ORDER BY app_id",
+    "SELECT app_id, address, total_amount, charm_count FROM stats_holders WHERE network = 'mainnet' ORDER BY app_id",
+    "SELECT total_charms, created_at FROM summary WHERE network = 'mainnet'",
+];
+
+#[tokio::test]
+async fn export_then_import_reproduces_the_network() {
+    let source = TestDb::new().await;
+    seed(&source.conn).await;
+    let parent = scratch_dir("roundtrip");
+
+    let (dir, manifest) = export_snapshot(
+        &source.conn,
+        &ExportOptions {
+            network: "mainnet".to_string(),
+            dir: parent.clone(),
+            include_raw: true,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(dir, parent.join("mainnet-101"));
+    assert_eq!(manifest.tip_height, 101);
+    assert_eq!(read_manifest(&dir).unwrap(), manifest);
+    let names: Vec<&str> = manifest.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, SNAPSHOT_TABLES);
+    let assets = manifest.tables.iter().find(|t| t.name == "assets").unwrap();
+    assert!(!assets.columns.contains(&"id".to_string()));
+    for table in &manifest.tables {
+        assert!(dir.join(&table.file).exists());
+    }
+
+    let target = TestDb::new().await;
+    import_snapshot(&target.conn, &dir, false).await.unwrap();
+
+    for table in SNAPSHOT_TABLES {
+        let expected = count(&source.conn, table, "mainnet").await;
+        let listed = manifest
+            .tables
+            .iter()
+            .find(|t| t.name == table)
+            .unwrap()
+            .rows;
+        assert_eq!(listed, expected as u64, "{table} manifest count");
+        assert_eq!(
+            count(&target.conn, table, "mainnet").await,
+            expected,
+            "{table}"
+        );
+        assert_eq!(count(&target.conn, table, "testnet4").await, 0, "{table}");
+    }
+    for sql in SAMPLES {
+        assert_eq!(
+            rows(&target.conn, sql).await,
+            rows(&source.conn, sql).await,
+            "{sql}"
+        );
+    }
+    assert_eq!(
+        rows(&target.conn, "SELECT raw FROM transactions ORDER BY txid").await,
+        rows(
+            &source.conn,
+            "SELECT raw FROM transactions WHERE network = 'mainnet' ORDER BY txid"
+        )
+        .await
+    );
+
+    // Live indexing resumes after the tip.
+    assert_eq!(
+        rows(
+            &target.conn,
+            "SELECT MAX(block_height) AS h FROM block_status WHERE network = 'mainnet' AND processed"
+        )
+        .await,
+        vec![r#"{"h":101}"#.to_string()]
+    );
+    assert_eq!(
+        rows(&target.conn, "SELECT last_processed_block FROM summary").await,
+        vec![r#"{"last_processed_block":101}"#.to_string()]
+    );
+
+    std::fs::remove_dir_all(&parent).unwrap();
+}
+
+#[tokio::test]
+async fn import_refuses_populated_network_unless_forced() {
+    let source = TestDb::new().await;
+    seed(&source.conn).await;
+    let parent = scratch_dir("force");
+    let (dir, _) = export_snapshot(
+        &source.conn,
+        &ExportOptions {
+            network: "mainnet".to_string(),
+            dir: parent.clone(),
+            include_raw: false,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Exporting the same tip again would overwrite the manifest.
+    assert!(export_snapshot(
+        &source.conn,
+        &ExportOptions {
+            network: "mainnet".to_string(),
+            dir: parent.clone(),
+            include_raw: false,
+        },
+    )
+    .await
+    .is_err());
+
+    let err = import_snapshot(&source.conn, &dir, false)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("--force"), "{err}");
+    assert_eq!(count(&source.conn, "charms", "mainnet").await, 3);
+
+    exec(
+        &source.conn,
+        "INSERT INTO charms (txid, vout, asset_type, blockchain, network, app_id) VALUES \
+                        ('stale', 0, 'token', 'Bitcoin', 'mainnet', 't/ee/ff')",
+    )
+    .await;
+    import_snapshot(&source.conn, &dir, true).await.unwrap();
+
+    assert_eq!(count(&source.conn, "charms", "mainnet").await, 3);
+    assert_eq!(count(&source.conn, "charms", "testnet4").await, 1);
+    // Exported without raw transactions.
+    assert_eq!(
+        rows(
+            &source.conn,
+            "SELECT raw FROM transactions WHERE network = 'mainnet' ORDER BY txid"
+        )
+        .await,
+        vec![r#"{"raw":{}}"#.to_string(), r#"{"raw":{}}"#.to_string()]
+    );
+
+    std::fs::remove_dir_all(&parent).unwrap();
+}
+
+#[tokio::test]
+async fn import_rejects_damaged_files() {
+    let source = TestDb::new().await;
+    seed(&source.conn).await;
+    let parent = scratch_dir("damaged");
+    let (dir, _) = export_snapshot(
+        &source.conn,
+        &ExportOptions {
+            network: "mainnet".to_string(),
+            dir: parent.clone(),
+            include_raw: true,
+        },
+    )
+    .await
+    .unwrap();
+
+    let path = dir.join("charms.csv.gz");
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+
+    let target = TestDb::new().await;
+    assert!(import_snapshot(&target.conn, &dir, false).await.is_err());
+    // Nothing committed.
+    for table in SNAPSHOT_TABLES {
+        assert_eq!(count(&target.conn, table, "mainnet").await, 0, "{table}");
+    }
+
+    std::fs::remove_file(dir.join(MANIFEST_FILE)).unwrap();
+    assert!(import_snapshot(&target.conn, &dir, false).await.is_err());
+
+    std::fs::remove_dir_all(&parent).unwrap();
+}
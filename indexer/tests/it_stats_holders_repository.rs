@@ -1,7 +1,7 @@
 //! Integration tests for `StatsHoldersRepository`.
 //!
 //! Covers the original audit findings:
-//!   N1 — non-idempotent re-processing (still open; documented as known bug)
+//!   N1 — re-processing a block (claimed once per block by `apply_block_deltas`)
 //!   N2 — cross-network leak (FIXED in T3.3, regression test below)
 
 mod common;
@@ -156,10 +156,9 @@ async fn later_block_does_apply_after_idempotent_skip() {
 
 /// Regression for anomaly A1: within a single block, an address can both
 /// gain and lose balance (the classic "spend with change" pattern). The
-/// repo's idempotency gate (`last_updated_block < block`) requires the
-/// caller to MERGE the additive and subtractive deltas to a single net
-/// value before invoking `update_holders_batch`. This test enforces that
-/// contract by replaying the merged delta directly.
+/// block processor MERGES the additive and subtractive deltas to a single
+/// net value per (app_id, address). This test replays the merged delta
+/// directly.
 #[tokio::test]
 async fn within_block_merged_delta_lands_correctly() {
     let db = TestDb::new().await;
@@ -194,7 +193,8 @@ async fn within_block_merged_delta_lands_correctly() {
 
 /// Companion to the merged-delta test: when the merged delta zeroes out
 /// the balance (sender spends entire holdings, no change), the row is
-/// removed by `cleanup_zero_holders` instead of lingering as a ghost row.
+/// removed by the post-UPSERT zero-balance cleanup instead of lingering
+/// as a ghost row.
 #[tokio::test]
 async fn within_block_full_spend_drops_to_zero() {
     let db = TestDb::new().await;
@@ -240,3 +240,135 @@ async fn cross_network_balances_are_isolated() {
         Some((50, 1))
     );
 }
+
+/// Concurrent batches on the same (app_id, address, network) must land in a
+/// single row with no lost updates. Every batch adds +10; pairs of batches
+/// share a block height and commit in no particular order, so the final
+/// balance must be the sum of all of them.
+#[tokio::test]
+async fn concurrent_batches_on_same_key_do_not_duplicate_or_lose_updates() {
+    let db = TestDb::new().await;
+    let repo = StatsHoldersRepository::new(db.conn.clone());
+
+    let tasks: Vec<_> = (0..8)
+        .map(|i| {
+            let repo = repo.clone();
+            let block = 100 + i / 2;
+            tokio::spawn(async move {
+                repo.update_holders_batch(
+                    vec![
+                        ("t/x/y".to_string(), "bc1qaaa".to_string(), 10, block),
+                        ("t/x/y".to_string(), format!("bc1q{i}"), 1, block),
+                    ],
+                    "mainnet",
+                )
+                .await
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    let count = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS n FROM stats_holders \
+             WHERE app_id = 't/x/y' AND address = 'bc1qaaa' AND network = 'mainnet'"
+                .to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(count.try_get::<i64>("", "n").unwrap(), 1);
+
+    assert_eq!(
+        row(&db.conn, "t/x/y", "bc1qaaa", "mainnet").await,
+        Some((8 * 10, 8))
+    );
+
+    // Per-batch keys never contend, so every one of them is present.
+    for i in 0..8 {
+        assert_eq!(
+            row(&db.conn, "t/x/y", &format!("bc1q{i}"), "mainnet").await,
+            Some((1, 1))
+        );
+    }
+}
+
+/// A block's deltas are applied once: replaying the same block hash is a
+/// no-op, while another block at the same height (after a reorg) applies.
+#[tokio::test]
+async fn block_deltas_apply_once_per_block_hash() {
+    let db = TestDb::new().await;
+    let repo = StatsHoldersRepository::new(db.conn.clone());
+    let deltas = || vec![("n/x/y".to_string(), "addrA".to_string(), 100, 100)];
+
+    assert!(repo
+        .apply_block_deltas(deltas(), "mainnet", "hash-a", 100)
+        .await
+        .unwrap());
+    assert!(!repo
+        .apply_block_deltas(deltas(), "mainnet", "hash-a", 100)
+        .await
+        .unwrap());
+    assert_eq!(
+        row(&db.conn, "n/x/y", "addrA", "mainnet").await,
+        Some((100, 1))
+    );
+
+    assert!(repo
+        .apply_block_deltas(deltas(), "mainnet", "hash-b", 100)
+        .await
+        .unwrap());
+    assert!(repo
+        .apply_block_deltas(deltas(), "testnet4", "hash-a", 100)
+        .await
+        .unwrap());
+    assert_eq!(
+        row(&db.conn, "n/x/y", "addrA", "mainnet").await,
+        Some((200, 2))
+    );
+    assert_eq!(
+        row(&db.conn, "n/x/y", "addrA", "testnet4").await,
+        Some((100, 1))
+    );
+}
+
+/// Two batches touching the same key in ascending block order sum exactly,
+/// and the mixed-sign rows of one batch go through the single UPSERT.
+#[tokio::test]
+async fn batch_upsert_sums_across_blocks_and_drops_emptied_rows() {
+    let db = TestDb::new().await;
+    let repo = StatsHoldersRepository::new(db.conn.clone());
+
+    let (a, b) = tokio::join!(
+        repo.update_holders_batch(
+            vec![("t/x/y".to_string(), "bc1qaaa".to_string(), 100, 100)],
+            "mainnet",
+        ),
+        repo.update_holders_batch(
+            vec![("t/x/y".to_string(), "bc1qbbb".to_string(), 40, 100)],
+            "mainnet",
+        ),
+    );
+    a.unwrap();
+    b.unwrap();
+
+    repo.update_holders_batch(
+        vec![
+            ("t/x/y".to_string(), "bc1qaaa".to_string(), 25, 101),
+            ("t/x/y".to_string(), "bc1qbbb".to_string(), -40, 101),
+        ],
+        "mainnet",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        row(&db.conn, "t/x/y", "bc1qaaa", "mainnet").await,
+        Some((125, 2))
+    );
+    assert_eq!(row(&db.conn, "t/x/y", "bc1qbbb", "mainnet").await, None);
+}
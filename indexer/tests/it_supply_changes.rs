@@ -0,0 +1,402 @@
+//! Integration tests for the `supply_changes` trail: every path that writes
+//! `assets.total_supply` records exactly one row per change, and none when
+//! the value stays put; a transaction's change is applied once, whichever
+//! path replays it.
+
+mod common;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::consensus::deserialize;
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash, CompactTarget, Transaction};
+use charms_indexer::application::indexer::block::processor::BlockProcessor;
+use charms_indexer::application::verify::{self, VerifyOptions};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::domain::models::{Asset, AssetType};
+use charms_indexer::domain::services::CharmService;
+use charms_indexer::infrastructure::bitcoin::{
+    BitcoinClient, BitcoinClientError, BitcoinProvider, SimpleBitcoinClient,
+};
+use charms_indexer::infrastructure::persistence::repositories::{
+    AssetRepository, SupplyChangeReason,
+};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde_json::json;
+
+/// A real mainnet V10 Charms Cast bid order (see `fixtures/parser/README.md`).
+/// With none of its inputs indexed, it mints its 10 000 token output.
+const DEX_TX_HEX: &str = include_str!("fixtures/parser/dex_bid_order_7269cf1b.hex");
+const DEX_TOKEN: &str = "t/3d7fe7e4cea6121947af73d70e5119bebd8aa5b7edfe74bfaf6e779a1847bd9b/c975d4e0c292fb95efbda5c13312d6ac1d8b5aeff7f0f1e5578645a2da70ff5f";
+
+/// (app_id, reason, txid, block_height, old_supply, new_supply, delta)
+type TrailRow = (
+    String,
+    String,
+    Option<String>,
+    Option<i32>,
+    Option<i64>,
+    i64,
+    i64,
+);
+
+/// Trail rows written since the previous call.
+struct Trail {
+    seen: i64,
+}
+
+impl Trail {
+    async fn new_rows(&mut self, conn: &DatabaseConnection) -> Vec<TrailRow> {
+        let rows = conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT id, app_id, reason, txid, block_height, old_supply::bigint AS old_supply, \
+                        new_supply::bigint AS new_supply, delta::bigint AS delta \
+                   FROM supply_changes WHERE id > $1 ORDER BY id",
+                [self.seen.into()],
+            ))
+            .await
+            .unwrap();
+        if let Some(last) = rows.last() {
+            self.seen = last.try_get("", "id").unwrap();
+        }
+        rows.iter()
+            .map(|r| {
+                (
+                    r.try_get("", "app_id").unwrap(),
+                    r.try_get("", "reason").unwrap(),
+                    r.try_get("", "txid").unwrap(),
+                    r.try_get("", "block_height").unwrap(),
+                    r.try_get("", "old_supply").unwrap(),
+                    r.try_get("", "new_supply").unwrap(),
+                    r.try_get("", "delta").unwrap(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn row(
+    app_id: &str,
+    reason: &str,
+    at: Option<(&str, i32)>,
+    old: Option<i64>,
+    new: i64,
+) -> TrailRow {
+    (
+        app_id.to_string(),
+        reason.to_string(),
+        at.map(|(txid, _)| txid.to_string()),
+        at.map(|(_, h)| h),
+        old,
+        new,
+        new - old.unwrap_or(0),
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn batch_item(
+    app_id: &str,
+    txid: &str,
+    height: u64,
+    supply: i64,
+) -> (
+    String,
+    String,
+    i32,
+    String,
+    u64,
+    serde_json::Value,
+    String,
+    String,
+    String,
+) {
+    let asset_type = match &app_id[..2] {
+        "n/" => "nft",
+        "B/" => "dapp",
+        _ => "token",
+    };
+    (
+        app_id.to_string(),
+        txid.to_string(),
+        0,
+        format!("charm-{app_id}"),
+        height,
+        json!({ "supply": supply }),
+        asset_type.to_string(),
+        "Bitcoin".to_string(),
+        "mainnet".to_string(),
+    )
+}
+
+#[tokio::test]
+async fn block_pipeline_batch_save_records_creation_and_raises_only() {
+    let db = TestDb::new().await;
+    let repo = AssetRepository::new(db.conn.clone());
+    let mut trail = Trail { seen: 0 };
+
+    repo.save_batch(vec![
+        batch_item("n/aa/01", "tx1", 100, 1),
+        batch_item("t/aa/01", "tx1", 100, 500),
+    ])
+    .await
+    .unwrap();
+    assert_eq!(
+        trail.new_rows(&db.conn).await,
+        vec![
+            row("n/aa/01", "mint", Some(("tx1", 100)), None, 0),
+            row("t/aa/01", "mint", Some(("tx1", 100)), None, 500),
+        ]
+    );
+
+    // A transfer re-declaring the same supply writes nothing; a secondary
+    // mint declaring more writes one row.
+    repo.save_batch(vec![batch_item("t/aa/01", "tx2", 101, 500)])
+        .await
+        .unwrap();
+    assert_eq!(trail.new_rows(&db.conn).await, vec![]);
+    repo.save_batch(vec![batch_item("t/aa/01", "tx3", 102, 800)])
+        .await
+        .unwrap();
+    assert_eq!(
+        trail.new_rows(&db.conn).await,
+        vec![row("t/aa/01", "mint", Some(("tx3", 102)), Some(500), 800)]
+    );
+
+    // The reindex job's repository records its raises as `reindex`.
+    repo.clone()
+        .with_supply_reason(SupplyChangeReason::Reindex)
+        .save_batch(vec![batch_item("t/aa/01", "tx4", 103, 900)])
+        .await
+        .unwrap();
+    assert_eq!(
+        trail.new_rows(&db.conn).await,
+        vec![row(
+            "t/aa/01",
+            "reindex",
+            Some(("tx4", 103)),
+            Some(800),
+            900
+        )]
+    );
+}
+
+#[tokio::test]
+async fn accumulating_save_and_spend_record_one_row_each() {
+    let db = TestDb::new().await;
+    let repo = AssetRepository::new(db.conn.clone());
+    let mut trail = Trail { seen: 0 };
+    let asset = |txid: &str, height: u64| {
+        Asset::new(
+            "t/bb/01".to_string(),
+            txid.to_string(),
+            0,
+            "charm-bb".to_string(),
+            height,
+            chrono::Utc::now().naive_utc(),
+            json!({}),
+            "token".to_string(),
+            "Bitcoin".to_string(),
+            "mainnet".to_string(),
+        )
+    };
+
+    repo.save_or_update_asset(&asset("tx1", 100), 40)
+        .await
+        .unwrap();
+    repo.save_or_update_asset(&asset("tx2", 101), 60)
+        .await
+        .unwrap();
+    repo.update_supply_on_spent("t/bb/01", 30, AssetType::Token)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        trail.new_rows(&db.conn).await,
+        vec![
+            row("t/bb/01", "mint", Some(("tx1", 100)), None, 40),
+            row("t/bb/01", "mint", Some(("tx2", 101)), Some(40), 100),
+            row("t/bb/01", "spend", None, Some(100), 70),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn verify_fix_records_manual_raises() {
+    let db = TestDb::new().await;
+    for sql in [
+        "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, total_supply) VALUES \
+         ('t/cc/01', 'tx1', 0, 'cc', 100, 'token', 'Bitcoin', 'mainnet', 10), \
+         ('t/dd/01', 'tx1', 1, 'dd', 100, 'token', 'Bitcoin', 'mainnet', 50)",
+        "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount) VALUES \
+         ('tx1', 0, 100, 'token', 'Bitcoin', 'mainnet', 'addrA', 't/cc/01', 25), \
+         ('tx1', 1, 100, 'token', 'Bitcoin', 'mainnet', 'addrB', 't/dd/01', 50)",
+    ] {
+        db.conn
+            .execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+            .await
+            .unwrap();
+    }
+    let mut trail = Trail { seen: 0 };
+
+    verify::run(
+        &db.conn,
+        &VerifyOptions {
+            networks: vec!["mainnet".to_string()],
+            fix: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    // Only the supply that was raised is recorded.
+    assert_eq!(
+        trail.new_rows(&db.conn).await,
+        vec![row("t/cc/01", "manual", None, Some(10), 25)]
+    );
+}
+
+/// Serves `block` at every height.
+#[derive(Debug)]
+struct OneBlock {
+    block: Block,
+}
+
+#[async_trait]
+impl BitcoinProvider for OneBlock {
+    fn provider_name(&self) -> String {
+        "one-block".to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(905)
+    }
+
+    async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+        Ok(self.block.block_hash())
+    }
+
+    async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        Ok(self.block.clone())
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("not served".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+fn dex_block() -> Block {
+    let tx: Transaction = deserialize(&hex::decode(DEX_TX_HEX.trim()).unwrap()).unwrap();
+    Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata: vec![tx],
+    }
+}
+
+/// The block processor of the live indexer, or of the reindex job with
+/// `reason` `Reindex`.
+fn processor(repos: &Repositories, reason: SupplyChangeReason) -> BlockProcessor {
+    let network_id = NetworkId::new(NetworkType::Bitcoin, "mainnet");
+    let client = BitcoinClient::from_simple_client(SimpleBitcoinClient::from_provider(
+        Arc::new(OneBlock { block: dex_block() }),
+        network_id,
+    ));
+    BlockProcessor::new(
+        client,
+        CharmService::new(
+            repos.charm.clone(),
+            repos.asset.clone().with_supply_reason(reason),
+            repos.stats_holders.clone(),
+            repos.dex_orders.clone(),
+        ),
+        repos,
+    )
+}
+
+async fn supply(conn: &DatabaseConnection, app_id: &str) -> Option<i64> {
+    conn.query_one(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT total_supply::bigint AS supply FROM assets WHERE app_id = $1",
+        [app_id.into()],
+    ))
+    .await
+    .unwrap()
+    .map(|r| r.try_get("", "supply").unwrap())
+}
+
+#[tokio::test]
+async fn block_pipeline_applies_a_mint_exactly_once() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    let network_id = NetworkId::new(NetworkType::Bitcoin, "mainnet");
+    let mut trail = Trail { seen: 0 };
+
+    let live = processor(&repos, SupplyChangeReason::Mint);
+    live.process_block(900, &network_id).await.unwrap();
+    assert_eq!(supply(&db.conn, DEX_TOKEN).await, Some(10_000));
+    let rows = trail.new_rows(&db.conn).await;
+    assert_eq!(rows.len(), 1);
+    assert_eq!((rows[0].1.as_str(), rows[0].5), ("mint", 10_000));
+
+    // Rerunning the block live, then through the reindex job, moves nothing.
+    live.process_block(900, &network_id).await.unwrap();
+    processor(&repos, SupplyChangeReason::Reindex)
+        .process_block(900, &network_id)
+        .await
+        .unwrap();
+    assert_eq!(supply(&db.conn, DEX_TOKEN).await, Some(10_000));
+    assert_eq!(trail.new_rows(&db.conn).await, vec![]);
+}
+
+#[tokio::test]
+async fn accumulated_supply_is_not_reapplied_for_the_same_tx() {
+    let db = TestDb::new().await;
+    let repo = AssetRepository::new(db.conn.clone());
+    let reindex = repo.clone().with_supply_reason(SupplyChangeReason::Reindex);
+    let mut trail = Trail { seen: 0 };
+
+    repo.save_batch(vec![batch_item("B/ee/01", "tx1", 100, 5)])
+        .await
+        .unwrap();
+    repo.save_batch(vec![batch_item("B/ee/01", "tx2", 101, 3)])
+        .await
+        .unwrap();
+    // A retried batch and a reindex over the same blocks.
+    repo.save_batch(vec![batch_item("B/ee/01", "tx2", 101, 3)])
+        .await
+        .unwrap();
+    for (txid, height, amount) in [("tx1", 100, 5), ("tx2", 101, 3)] {
+        reindex
+            .save_batch(vec![batch_item("B/ee/01", txid, height, amount)])
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(supply(&db.conn, "B/ee/01").await, Some(8));
+    assert_eq!(
+        trail.new_rows(&db.conn).await,
+        vec![
+            row("B/ee/01", "mint", Some(("tx1", 100)), None, 5),
+            row("B/ee/01", "mint", Some(("tx2", 101)), Some(5), 8),
+        ]
+    );
+}
@@ -0,0 +1,124 @@
+//! The tip watch flags a primary provider that trails the fallback and
+//! clears `summary.provider_stale` once it catches up.
+
+mod common;
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::{Block, BlockHash};
+use charms_indexer::application::indexer::tip_watch::{TipWatch, TipWatchConfig};
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::bitcoin::{BitcoinClientError, BitcoinProvider};
+use charms_indexer::infrastructure::persistence::repositories::SummaryRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+/// Answers `getblockcount` with a tip the test moves.
+#[derive(Debug)]
+struct TipProvider {
+    name: &'static str,
+    tip: AtomicU64,
+}
+
+impl TipProvider {
+    fn at(name: &'static str, tip: u64) -> Arc<Self> {
+        Arc::new(Self {
+            name,
+            tip: AtomicU64::new(tip),
+        })
+    }
+
+    fn set(&self, tip: u64) {
+        self.tip.store(tip, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl BitcoinProvider for TipProvider {
+    fn provider_name(&self) -> String {
+        self.name.to_string()
+    }
+
+    async fn get_block_count(&self) -> Result<u64, BitcoinClientError> {
+        Ok(self.tip.load(Ordering::Relaxed))
+    }
+
+    async fn get_block_hash(&self, _height: u64) -> Result<BlockHash, BitcoinClientError> {
+        Ok(BlockHash::from_str(&"00".repeat(32)).unwrap())
+    }
+
+    async fn get_block(&self, _block_hash: &BlockHash) -> Result<Block, BitcoinClientError> {
+        Err(BitcoinClientError::Other("no blocks".to_string()))
+    }
+
+    async fn get_raw_transaction_hex(
+        &self,
+        _txid: &str,
+        _block_hash: Option<&BlockHash>,
+    ) -> Result<String, BitcoinClientError> {
+        Err(BitcoinClientError::Other("no transactions".to_string()))
+    }
+
+    async fn apply_rate_limiting(&self) {}
+}
+
+async fn provider_stale(conn: &sea_orm::DatabaseConnection, network: &str) -> bool {
+    conn.query_one(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT provider_stale FROM summary WHERE network = $1",
+        [network.into()],
+    ))
+    .await
+    .unwrap()
+    .expect("summary row")
+    .try_get("", "provider_stale")
+    .unwrap()
+}
+
+#[tokio::test]
+async fn stuck_primary_is_flagged_until_it_catches_up() {
+    let db = TestDb::new().await;
+    db.conn
+        .execute_unprepared("INSERT INTO summary (network) VALUES ('mainnet'), ('testnet4')")
+        .await
+        .unwrap();
+
+    let primary = TipProvider::at("node", 900_000);
+    let secondary = TipProvider::at("quicknode", 900_000);
+    let mut watch = TipWatch::new(
+        NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+        primary.clone(),
+        secondary.clone(),
+        SummaryRepository::new(db.conn.clone()),
+        TipWatchConfig {
+            interval: Duration::from_secs(60),
+            max_lag: 2,
+            stale_after: Duration::from_secs(300),
+        },
+    );
+    let t0 = Instant::now();
+    let at = |secs| t0 + Duration::from_secs(secs);
+
+    assert_eq!(watch.check_at(at(0)).await, None);
+
+    // The primary stops while the chain moves on.
+    secondary.set(900_005);
+    assert_eq!(watch.check_at(at(60)).await, None, "lag alone is not enough");
+    assert_eq!(watch.check_at(at(300)).await, None);
+    assert!(!provider_stale(&db.conn, "mainnet").await);
+
+    secondary.set(900_007);
+    assert_eq!(watch.check_at(at(360)).await, Some(true));
+    assert!(watch.is_stale());
+    assert!(provider_stale(&db.conn, "mainnet").await);
+    assert!(!provider_stale(&db.conn, "testnet4").await);
+
+    // Within the allowed lag again.
+    primary.set(900_006);
+    assert_eq!(watch.check_at(at(420)).await, Some(false));
+    assert!(!provider_stale(&db.conn, "mainnet").await);
+}
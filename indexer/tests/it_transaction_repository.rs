@@ -0,0 +1,195 @@
+//! Integration tests for `TransactionRepository` — the confirmation
+//! lifecycle (pending → confirming → confirmed) and the stored spell
+//! output map.
+
+mod common;
+
+use charms_indexer::application::indexer::block::batch::TransactionBatchItem;
+use charms_indexer::domain::models::TransactionStatus;
+use charms_indexer::infrastructure::persistence::repositories::TransactionRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, FromQueryResult, Statement};
+use serde_json::json;
+
+#[derive(FromQueryResult, Debug, PartialEq, Eq)]
+struct StatusRow {
+    txid: String,
+    status: String,
+    confirmations: i32,
+}
+
+fn mined(txid: &str, network: &str, block_height: u64, tip: u64) -> TransactionBatchItem {
+    let confirmations = (tip - block_height + 1) as i32;
+    TransactionBatchItem {
+        txid: txid.to_string(),
+        block_height,
+        position: 0,
+        raw_json: json!({ "txid": txid }),
+        charm_data: json!({}),
+        confirmations,
+        status: TransactionStatus::for_confirmations(confirmations),
+        blockchain: "Bitcoin".to_string(),
+        network: network.to_string(),
+        tags: None,
+        tx_type: None,
+    }
+}
+
+async fn statuses(conn: &sea_orm::DatabaseConnection) -> Vec<StatusRow> {
+    StatusRow::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        "SELECT txid, status, confirmations FROM transactions ORDER BY txid".to_string(),
+    ))
+    .all(conn)
+    .await
+    .unwrap()
+}
+
+fn row(txid: &str, status: TransactionStatus, confirmations: i32) -> StatusRow {
+    StatusRow {
+        txid: txid.to_string(),
+        status: status.to_string(),
+        confirmations,
+    }
+}
+
+#[tokio::test]
+async fn confirm_matured_promotes_rows_saved_near_the_tip() {
+    let db = TestDb::new().await;
+    let repo = TransactionRepository::new(db.conn.clone());
+
+    let tip = 1_000;
+    repo.save_batch(
+        ["a1", "a2", "a3"]
+            .into_iter()
+            .map(|txid| mined(txid, "mainnet", tip - 1, tip).into_tuple())
+            .collect(),
+    )
+    .await
+    .expect("save");
+    assert!(statuses(&db.conn)
+        .await
+        .iter()
+        .all(|r| r.status == "confirming" && r.confirmations == 2));
+
+    // Tip advances by 10; a batch size of 2 forces more than one UPDATE.
+    let promoted = repo
+        .confirm_matured("mainnet", tip + 10, 2)
+        .await
+        .expect("confirm");
+    assert_eq!(promoted, 3);
+    assert_eq!(
+        statuses(&db.conn).await,
+        vec![
+            row("a1", TransactionStatus::Confirmed, 12),
+            row("a2", TransactionStatus::Confirmed, 12),
+            row("a3", TransactionStatus::Confirmed, 12),
+        ]
+    );
+
+    // Nothing left to do on the next cycle.
+    assert_eq!(repo.confirm_matured("mainnet", tip + 11, 2).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn confirm_matured_leaves_shallow_pending_and_other_network_rows() {
+    let db = TestDb::new().await;
+    let repo = TransactionRepository::new(db.conn.clone());
+
+    let tip = 500;
+    repo.save_batch(vec![
+        mined("b1", "mainnet", 490, tip).into_tuple(),
+        mined("b2", "mainnet", 498, tip).into_tuple(),
+        mined("b3", "testnet4", 498, tip).into_tuple(),
+    ])
+    .await
+    .expect("save");
+    db.conn
+        .execute_unprepared(
+            "INSERT INTO transactions (txid, ordinal, status, confirmations, blockchain, network) \
+             VALUES ('b4', 0, 'pending', 0, 'Bitcoin', 'mainnet')",
+        )
+        .await
+        .unwrap();
+
+    // At tip + 1, b2 has only 4 confirmations; b1 was confirmed on insert.
+    assert_eq!(repo.confirm_matured("mainnet", tip + 1, 100).await.unwrap(), 0);
+
+    assert_eq!(repo.confirm_matured("mainnet", tip + 5, 100).await.unwrap(), 1);
+    assert_eq!(
+        statuses(&db.conn).await,
+        vec![
+            row("b1", TransactionStatus::Confirmed, 11),
+            row("b2", TransactionStatus::Confirmed, 8),
+            row("b3", TransactionStatus::Confirming, 3),
+            row("b4", TransactionStatus::Pending, 0),
+        ]
+    );
+}
+
+/// The block path re-derives the status when it re-saves a known txid.
+#[tokio::test]
+async fn save_batch_upgrades_status_on_conflict() {
+    let db = TestDb::new().await;
+    let repo = TransactionRepository::new(db.conn.clone());
+
+    repo.save_batch(vec![mined("c1", "mainnet", 700, 700).into_tuple()])
+        .await
+        .unwrap();
+    repo.save_batch(vec![mined("c1", "mainnet", 700, 706).into_tuple()])
+        .await
+        .unwrap();
+    assert_eq!(
+        statuses(&db.conn).await,
+        vec![row("c1", TransactionStatus::Confirmed, 7)]
+    );
+}
+
+async fn spell_outputs(
+    conn: &sea_orm::DatabaseConnection,
+    txid: &str,
+) -> Option<serde_json::Value> {
+    conn.query_one(Statement::from_string(
+        DbBackend::Postgres,
+        format!("SELECT spell_outputs FROM transactions WHERE txid = '{txid}'"),
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "spell_outputs")
+    .unwrap()
+}
+
+#[tokio::test]
+async fn save_batch_stores_the_spell_output_map() {
+    let db = TestDb::new().await;
+    let repo = TransactionRepository::new(db.conn.clone());
+
+    let mut spell = mined("s1", "mainnet", 100, 100);
+    spell.charm_data = json!({
+        "type": "spell",
+        "native_data": {
+            "app_public_inputs": {"n/aa/bb": null, "t/aa/bb": null},
+            "tx": {"outs": [{"0": {"ticker": "AA"}, "1": 100}, {"1": 900}]},
+        },
+    });
+    repo.save_batch(vec![
+        spell.into_tuple(),
+        mined("p1", "mainnet", 100, 100).into_tuple(),
+    ])
+    .await
+    .expect("save");
+
+    let expected = json!([
+        {"vout": 0, "charms": {"n/aa/bb": {"ticker": "AA"}, "t/aa/bb": 100}},
+        {"vout": 1, "charms": {"t/aa/bb": 900}},
+    ]);
+    assert_eq!(spell_outputs(&db.conn, "s1").await, Some(expected.clone()));
+    assert_eq!(spell_outputs(&db.conn, "p1").await, None);
+
+    // A later write without the spell JSON keeps the map.
+    repo.save_batch(vec![mined("s1", "mainnet", 100, 110).into_tuple()])
+        .await
+        .expect("re-save");
+    assert_eq!(spell_outputs(&db.conn, "s1").await, Some(expected));
+}
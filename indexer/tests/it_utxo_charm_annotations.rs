@@ -0,0 +1,191 @@
+//! Monitored UTXOs carry `has_charms` / `charm_app_ids`: set by the block
+//! pipeline for the charms it saved, and backfilled by the migration for
+//! rows written before the columns existed.
+
+mod common;
+
+use std::str::FromStr;
+
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    Address, Block, BlockHash, CompactTarget, Network, OutPoint, ScriptBuf, Sequence, Transaction,
+    TxIn, TxOut, Txid, Witness,
+};
+use charms_indexer::application::indexer::block::utxo_indexer;
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde_json::json;
+
+const OWNER: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+const MIGRATION: &str =
+    include_str!("../../database/migrations/m20260811_000001_address_utxos_charm_flags.sql");
+
+async fn insert_charm(conn: &DatabaseConnection, txid: &str, vout: i32, app_id: &str) {
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "INSERT INTO charms (txid, vout, block_height, data, asset_type, blockchain, network, app_id, address) \
+         VALUES ($1, $2, 100, '{}'::jsonb, 'token', 'Bitcoin', 'mainnet', $3, 'bc1qsomeoneelse')",
+        [txid.into(), vout.into(), app_id.into()],
+    ))
+    .await
+    .expect("insert charm");
+}
+
+/// (txid, has_charms, charm_app_ids) of every row, by txid.
+async fn flags(conn: &DatabaseConnection) -> Vec<(String, Option<bool>, serde_json::Value)> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        "SELECT txid, has_charms, charm_app_ids FROM address_utxos ORDER BY txid",
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|r| {
+        (
+            r.try_get("", "txid").unwrap(),
+            r.try_get("", "has_charms").unwrap(),
+            r.try_get("", "charm_app_ids").unwrap(),
+        )
+    })
+    .collect()
+}
+
+/// A tx paying 546 sats to `OWNER`, distinct per `seed`.
+fn payment(seed: u8) -> Transaction {
+    let owner = Address::from_str(OWNER)
+        .unwrap()
+        .require_network(Network::Bitcoin)
+        .unwrap();
+    Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::from_byte_array([seed; 32]), 0),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::from_slice(&[vec![1; 64]]),
+        }],
+        output: vec![TxOut {
+            value: 546,
+            script_pubkey: owner.script_pubkey(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn block_utxos_are_flagged_with_the_charms_saved_for_them() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    repos
+        .monitored_addresses
+        .register_batch(&[OWNER.to_string()], "mainnet", "indexer")
+        .await
+        .unwrap();
+
+    let charmed = payment(1);
+    let plain = payment(2);
+    // Detection saves the block's charms before the UTXO index runs.
+    let charmed_txid = charmed.txid().to_string();
+    insert_charm(&db.conn, &charmed_txid, 0, "t/bb/cc").await;
+    insert_charm(&db.conn, &charmed_txid, 0, "n/aa/cc").await;
+
+    let block = Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata: vec![charmed, plain.clone()],
+    };
+    utxo_indexer::update_monitored_utxos(
+        &block,
+        100,
+        &NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+        &repos.monitored_addresses,
+        &repos.utxo,
+    )
+    .await
+    .unwrap();
+
+    let mut expected = vec![
+        (charmed_txid, Some(true), json!(["n/aa/cc", "t/bb/cc"])),
+        (plain.txid().to_string(), Some(false), json!([])),
+    ];
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(flags(&db.conn).await, expected);
+}
+
+#[tokio::test]
+async fn migration_backfills_utxos_created_before_monitoring() {
+    let db = TestDb::new().await;
+    // Rows seeded before the annotation existed, one holding a charm stored
+    // long before its address was monitored.
+    insert_charm(&db.conn, "aa", 1, "t/x/y").await;
+    db.conn
+        .execute_unprepared(
+            "INSERT INTO address_utxos (txid, vout, network, address, value, source) VALUES \
+                ('aa', 1, 'mainnet', 'bc1qowner', 546, 'maestro'), \
+                ('aa', 0, 'testnet4', 'tb1qowner', 546, 'maestro'), \
+                ('bb', 0, 'mainnet', 'bc1qowner', 9000, 'maestro')",
+        )
+        .await
+        .unwrap();
+    assert!(flags(&db.conn)
+        .await
+        .iter()
+        .all(|(_, has, _)| has.is_none()));
+
+    db.conn
+        .execute_unprepared("CREATE TABLE seaql_migrations (version TEXT PRIMARY KEY)")
+        .await
+        .unwrap();
+    db.conn
+        .execute_unprepared(MIGRATION)
+        .await
+        .expect("migrate");
+
+    let rows = db
+        .conn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT txid, vout, network, has_charms, charm_app_ids FROM address_utxos \
+             ORDER BY txid, vout",
+        ))
+        .await
+        .unwrap();
+    let got: Vec<(String, i32, String, Option<bool>, serde_json::Value)> = rows
+        .iter()
+        .map(|r| {
+            (
+                r.try_get("", "txid").unwrap(),
+                r.try_get("", "vout").unwrap(),
+                r.try_get("", "network").unwrap(),
+                r.try_get("", "has_charms").unwrap(),
+                r.try_get("", "charm_app_ids").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        got,
+        [
+            ("aa".into(), 0, "testnet4".into(), Some(false), json!([])),
+            (
+                "aa".into(),
+                1,
+                "mainnet".into(),
+                Some(true),
+                json!(["t/x/y"])
+            ),
+            ("bb".into(), 0, "mainnet".into(), Some(false), json!([])),
+        ]
+    );
+}
@@ -0,0 +1,119 @@
+//! A block spending a monitored UTXO moves it to `address_utxo_spends`
+//! with its spender and height, which the API's `/wallet/changes` reports
+//! as a removal.
+
+mod common;
+
+use std::str::FromStr;
+
+use bitcoincore_rpc::bitcoin::absolute::LockTime;
+use bitcoincore_rpc::bitcoin::block::{Header, Version};
+use bitcoincore_rpc::bitcoin::hash_types::TxMerkleNode;
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    Address, Block, BlockHash, CompactTarget, Network, OutPoint, ScriptBuf, Sequence, Transaction,
+    TxIn, TxOut, Txid, Witness,
+};
+use charms_indexer::application::indexer::block::utxo_indexer;
+use charms_indexer::config::{NetworkId, NetworkType};
+use charms_indexer::infrastructure::persistence::Repositories;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+
+const OWNER: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+/// A tx spending `prev` and paying 546 sats to `to`.
+fn tx(prev: OutPoint, to: &str) -> Transaction {
+    let to = Address::from_str(to)
+        .unwrap()
+        .require_network(Network::Bitcoin)
+        .unwrap();
+    Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: prev,
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::from_slice(&[vec![1; 64]]),
+        }],
+        output: vec![TxOut {
+            value: 546,
+            script_pubkey: to.script_pubkey(),
+        }],
+    }
+}
+
+async fn index(repos: &Repositories, txdata: Vec<Transaction>, height: u64) {
+    let block = Block {
+        header: Header {
+            version: Version::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 1_760_000_000,
+            bits: CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        },
+        txdata,
+    };
+    utxo_indexer::update_monitored_utxos(
+        &block,
+        height,
+        &NetworkId::new(NetworkType::Bitcoin, "mainnet"),
+        &repos.monitored_addresses,
+        &repos.utxo,
+    )
+    .await
+    .unwrap();
+}
+
+async fn rows(conn: &DatabaseConnection, sql: &str) -> Vec<String> {
+    conn.query_all(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.try_get_by_index::<String>(0).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn spent_utxos_leave_a_record_of_their_spender() {
+    let db = TestDb::new().await;
+    let repos = Repositories::from_connection(db.conn.clone());
+    repos
+        .monitored_addresses
+        .register_batch(&[OWNER.to_string()], "mainnet", "indexer")
+        .await
+        .unwrap();
+
+    let receive = tx(OutPoint::new(Txid::from_byte_array([1; 32]), 0), OWNER);
+    index(&repos, vec![receive.clone()], 100).await;
+    assert_eq!(
+        rows(&db.conn, "SELECT txid || ':' || vout FROM address_utxos").await,
+        vec![format!("{}:0", receive.txid())]
+    );
+
+    // The payment leaves for an unmonitored address one block later.
+    let spend = tx(
+        OutPoint::new(receive.txid(), 0),
+        "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+    );
+    index(&repos, vec![spend.clone()], 101).await;
+
+    assert!(rows(&db.conn, "SELECT txid FROM address_utxos")
+        .await
+        .is_empty());
+    assert_eq!(
+        rows(
+            &db.conn,
+            "SELECT concat_ws(' ', txid, vout, address, value, block_height, spending_txid, spent_height) \
+               FROM address_utxo_spends"
+        )
+        .await,
+        vec![format!(
+            "{} 0 {OWNER} 546 100 {} 101",
+            receive.txid(),
+            spend.txid()
+        )]
+    );
+}
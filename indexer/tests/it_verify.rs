@@ -0,0 +1,155 @@
+//! Integration tests for the cross-table verifier: seed one inconsistency
+//! of each class and assert the report catches it (and `fix` repairs the
+//! repairable ones).
+
+mod common;
+
+use charms_indexer::application::verify::{self, Check, VerifyOptions};
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+async fn exec(conn: &sea_orm::DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+/// A consistent baseline plus exactly one discrepancy per check, all on
+/// mainnet; testnet4 is left empty to show results stay network-scoped.
+async fn seed(conn: &sea_orm::DatabaseConnection) {
+    // Consistent: token t/aa/bb, 60 + 40 unspent at two addresses, supply 100.
+    exec(conn, "INSERT INTO transactions (txid, block_height, ordinal, blockchain, network) VALUES \
+                ('tx1', 100, 1, 'Bitcoin', 'mainnet')")
+        .await;
+    exec(conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount) VALUES \
+                ('tx1', 0, 100, 'token', 'Bitcoin', 'mainnet', 'addrA', 't/aa/bb', 60), \
+                ('tx1', 1, 100, 'token', 'Bitcoin', 'mainnet', 'addrB', 't/aa/bb', 40)")
+        .await;
+    exec(conn, "INSERT INTO stats_holders (app_id, address, network, total_amount, charm_count) VALUES \
+                ('n/aa/bb', 'addrA', 'mainnet', 60, 1), ('n/aa/bb', 'addrB', 'mainnet', 40, 1)")
+        .await;
+
+    // Supply: token t/cc/dd has no token row; its parent NFT declares 10
+    // but 25 is circulating.
+    exec(conn, "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, total_supply) VALUES \
+                ('t/aa/bb', 'tx1', 0, 'aa', 100, 'token', 'Bitcoin', 'mainnet', 100), \
+                ('n/cc/dd', 'tx1', 2, 'cc', 100, 'nft', 'Bitcoin', 'mainnet', 10)")
+        .await;
+    exec(conn, "INSERT INTO supply_changes (app_id, network, delta, reason, txid, block_height, old_supply, new_supply) VALUES \
+                ('n/cc/dd', 'mainnet', 10, 'mint', 'tx1', 100, NULL, 10)")
+        .await;
+    exec(conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount) VALUES \
+                ('tx1', 2, 100, 'token', 'Bitcoin', 'mainnet', 'addrC', 't/cc/dd', 25)")
+        .await;
+    exec(conn, "INSERT INTO stats_holders (app_id, address, network, total_amount, charm_count) VALUES \
+                ('n/cc/dd', 'addrC', 'mainnet', 25, 1)")
+        .await;
+
+    // Holders: addrA is recorded with a stale balance.
+    exec(conn, "UPDATE stats_holders SET total_amount = 75 WHERE app_id = 'n/aa/bb' AND address = 'addrA'")
+        .await;
+
+    // Orphan charm: an NFT whose transaction row never landed.
+    exec(conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount) VALUES \
+                ('tx_orphan', 0, 101, 'nft', 'Bitcoin', 'mainnet', 'addrD', 'n/ee/ff', 0)")
+        .await;
+    exec(conn, "INSERT INTO stats_holders (app_id, address, network, total_amount, charm_count) VALUES \
+                ('n/ee/ff', 'addrD', 'mainnet', 1, 1)")
+        .await;
+
+    // Orphan spell: no charm for its txid on mainnet.
+    exec(conn, "INSERT INTO spells (txid, block_height, network) VALUES \
+                ('tx1', 100, 'mainnet'), ('tx_lonely', 102, 'mainnet')")
+        .await;
+}
+
+fn discrepancies(report: &verify::VerifyReport, check: Check) -> (u64, Vec<String>) {
+    let r = report
+        .results
+        .iter()
+        .find(|r| r.check == check && r.network == "mainnet")
+        .unwrap_or_else(|| panic!("no result for {}", check.name()));
+    (r.discrepancies, r.examples.clone())
+}
+
+#[tokio::test]
+async fn report_catches_each_class_of_inconsistency() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+
+    let opts = VerifyOptions {
+        networks: vec!["mainnet".to_string()],
+        ..Default::default()
+    };
+    let report = verify::run(&db.conn, &opts).await.expect("verify");
+
+    let (n, ex) = discrepancies(&report, Check::SupplyVsCharms);
+    assert_eq!(n, 1);
+    // The example names the write that last set the supply.
+    assert_eq!(
+        ex,
+        vec!["t/cc/dd circulating=25 supply=10 last=mint tx1@100 (new->10)".to_string()]
+    );
+
+    let (n, ex) = discrepancies(&report, Check::HoldersVsCharms);
+    assert_eq!(n, 1);
+    assert_eq!(ex, vec!["n/aa/bb addrA expected=60 actual=75".to_string()]);
+
+    let (n, ex) = discrepancies(&report, Check::CharmsWithoutTransaction);
+    assert_eq!(n, 1);
+    assert_eq!(ex, vec!["tx_orphan:0 n/ee/ff".to_string()]);
+
+    let (n, ex) = discrepancies(&report, Check::SpellsWithoutCharms);
+    assert_eq!(n, 1);
+    assert_eq!(ex, vec!["tx_lonely".to_string()]);
+
+    assert!(!report.passed());
+    assert_eq!(report.failures().count(), 4);
+
+    // Tolerating one discrepancy per check lets the same data pass.
+    let lenient = verify::run(&db.conn, &VerifyOptions { tolerance: 1, ..opts })
+        .await
+        .expect("verify");
+    assert!(lenient.passed());
+}
+
+#[tokio::test]
+async fn fix_repairs_supply_and_holders_but_not_orphans() {
+    let db = TestDb::new().await;
+    seed(&db.conn).await;
+
+    let opts = VerifyOptions {
+        networks: vec!["mainnet".to_string()],
+        fix: true,
+        ..Default::default()
+    };
+    let report = verify::run(&db.conn, &opts).await.expect("verify --fix");
+
+    assert_eq!(discrepancies(&report, Check::SupplyVsCharms).0, 0);
+    assert_eq!(discrepancies(&report, Check::HoldersVsCharms).0, 0);
+    assert_eq!(discrepancies(&report, Check::CharmsWithoutTransaction).0, 1);
+    assert_eq!(discrepancies(&report, Check::SpellsWithoutCharms).0, 1);
+
+    let row = db
+        .conn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT total_supply::bigint AS s FROM assets WHERE app_id = 'n/cc/dd'".to_string(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.try_get::<i64>("", "s").unwrap(), 25);
+
+    // A clean network reports nothing.
+    let clean = verify::run(
+        &db.conn,
+        &VerifyOptions {
+            networks: vec!["testnet4".to_string()],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("verify testnet4");
+    assert!(clean.passed());
+}
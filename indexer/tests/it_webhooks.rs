@@ -0,0 +1,438 @@
+//! Integration tests for webhook subscriptions: events queued for a persisted
+//! block, and their delivery to a local mock receiver with signature checks,
+//! retries and deactivation of a failing endpoint.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use charms_indexer::application::indexer::webhooks::{
+    sign, WebhookConfig, WebhookDispatcher, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER,
+};
+use charms_indexer::infrastructure::persistence::repositories::WebhooksRepository;
+use common::TestDb;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+async fn exec(conn: &DatabaseConnection, sql: &str) {
+    conn.execute(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("seed failed ({sql:.60}…): {e}"));
+}
+
+async fn scalar<T: sea_orm::TryGetable>(conn: &DatabaseConnection, sql: &str) -> T {
+    conn.query_one(Statement::from_string(DbBackend::Postgres, sql.to_string()))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "v")
+        .unwrap()
+}
+
+async fn subscribe(
+    conn: &DatabaseConnection,
+    url: &str,
+    app_id: Option<&str>,
+    events: &[&str],
+) -> i64 {
+    let app_id = app_id.map_or("NULL".to_string(), |a| format!("'{a}'"));
+    let events = events
+        .iter()
+        .map(|e| format!("'{e}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    scalar(
+        conn,
+        &format!(
+            "INSERT INTO webhook_subscriptions (url, secret, app_id, event_types) \
+             VALUES ('{url}', 's3cret', {app_id}, ARRAY[{events}]::text[]) RETURNING id AS v"
+        ),
+    )
+    .await
+}
+
+/// Block 100 mints 60 `t/aa/bb` to addrB out of a 40 `t/aa/bb` charm and an
+/// NFT of addrA (spent by tx2), and carries a new DEX order and a fill.
+async fn seed_block(conn: &DatabaseConnection) {
+    exec(conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount, spent, spending_txid) VALUES \
+                ('tx0', 0, 90, 'token', 'Bitcoin', 'mainnet', 'addrA', 't/aa/bb', 40, true, 'tx2'), \
+                ('tx0', 1, 90, 'nft', 'Bitcoin', 'mainnet', 'addrA', 'n/aa/bb', 0, true, 'tx2'), \
+                ('tx2', 0, 100, 'token', 'Bitcoin', 'mainnet', 'addrB', 't/aa/bb', 100, false, NULL), \
+                ('tx2', 1, 100, 'nft', 'Bitcoin', 'mainnet', 'addrB', 'n/aa/bb', 0, false, NULL), \
+                ('tx9', 0, 100, 'token', 'Bitcoin', 'testnet4', 'addrT', 't/aa/bb', 5, false, NULL)")
+        .await;
+    exec(conn, "INSERT INTO assets (app_id, txid, vout_index, charm_id, block_height, asset_type, blockchain, network, total_supply) VALUES \
+                ('t/aa/bb', 'tx0', 0, 't/aa/bb', 90, 'token', 'Bitcoin', 'mainnet', 100)")
+        .await;
+    exec(conn, "INSERT INTO dex_orders (order_id, txid, vout, block_height, platform, maker, side, exec_type, price_num, price_den, amount, quantity, asset_app_id, status, parent_order_id, blockchain, network) VALUES \
+                ('tx3:0', 'tx3', 0, 100, 'charms-cast', 'makerA', 'ask', 'all_or_none', 1, 2, 50, 100, 't/aa/bb', 'open', NULL, 'Bitcoin', 'mainnet'), \
+                ('tx4:0', 'tx4', 0, 100, 'charms-cast', 'makerB', 'bid', 'all_or_none', 1, 2, 10, 20, 't/cc/dd', 'filled', 'tx1:0', 'Bitcoin', 'mainnet'), \
+                ('tx5:0', 'tx5', 0, 100, 'charms-cast', 'makerC', 'ask', 'all_or_none', 1, 2, 10, 20, 't/aa/bb', 'cancelled', 'tx1:0', 'Bitcoin', 'mainnet')")
+        .await;
+}
+
+async fn queued(conn: &DatabaseConnection, subscription: i64) -> Vec<Value> {
+    conn.query_all(Statement::from_string(
+        DbBackend::Postgres,
+        format!(
+            "SELECT payload FROM webhook_deliveries WHERE subscription_id = {subscription} \
+             ORDER BY event_id"
+        ),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|r| r.try_get("", "payload").unwrap())
+    .collect()
+}
+
+#[tokio::test]
+async fn block_events_are_queued_per_matching_subscription() {
+    let db = TestDb::new().await;
+    seed_block(&db.conn).await;
+    let all = subscribe(
+        &db.conn,
+        "http://all",
+        None,
+        &[
+            "charm_created",
+            "charm_spent",
+            "asset_supply_changed",
+            "dex_order_created",
+            "dex_order_filled",
+        ],
+    )
+    .await;
+    let token = subscribe(
+        &db.conn,
+        "http://token",
+        Some("t/aa/bb"),
+        &["charm_created"],
+    )
+    .await;
+    let idle = subscribe(&db.conn, "http://idle", None, &["charm_created"]).await;
+    exec(
+        &db.conn,
+        &format!("UPDATE webhook_subscriptions SET active = false WHERE id = {idle}"),
+    )
+    .await;
+
+    let repo = WebhooksRepository::new(db.conn.clone());
+    let spenders = vec!["tx2".to_string(), "tx3".to_string()];
+    assert_eq!(
+        repo.enqueue_block_events("mainnet", 100, &spenders)
+            .await
+            .unwrap(),
+        8
+    );
+    // Reprocessing the block queues nothing new.
+    assert_eq!(
+        repo.enqueue_block_events("mainnet", 100, &spenders)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let ids: Vec<String> = queued(&db.conn, all)
+        .await
+        .iter()
+        .map(|p| p["id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            "asset_supply_changed:t/aa/bb:100",
+            "charm_created:tx2:0:t/aa/bb",
+            "charm_created:tx2:1:n/aa/bb",
+            "charm_spent:tx0:0:t/aa/bb",
+            "charm_spent:tx0:1:n/aa/bb",
+            "dex_order_created:tx3:0",
+            "dex_order_filled:tx4:0",
+        ]
+    );
+    let all_payloads = queued(&db.conn, all).await;
+    assert_eq!(
+        all_payloads[0],
+        json!({
+            "id": "asset_supply_changed:t/aa/bb:100",
+            "type": "asset_supply_changed",
+            "network": "mainnet",
+            "block_height": 100,
+            "app_id": "t/aa/bb",
+            "data": {"app_id": "t/aa/bb", "delta": 60, "total_supply": 100},
+        })
+    );
+    assert_eq!(
+        all_payloads[3]["data"],
+        json!({
+            "txid": "tx0", "vout": 0, "app_id": "t/aa/bb", "asset_type": "token",
+            "address": "addrA", "amount": 40, "spending_txid": "tx2",
+        })
+    );
+    assert_eq!(all_payloads[6]["app_id"], "t/cc/dd");
+    assert_eq!(all_payloads[6]["data"]["parent_order_id"], "tx1:0");
+
+    let token_ids: Vec<Value> = queued(&db.conn, token)
+        .await
+        .iter()
+        .map(|p| p["id"].clone())
+        .collect();
+    assert_eq!(token_ids, [json!("charm_created:tx2:0:t/aa/bb")]);
+    assert!(queued(&db.conn, idle).await.is_empty());
+}
+
+/// A request as the mock receiver saw it.
+#[derive(Debug, Clone)]
+struct Received {
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+/// Reads one HTTP request (headers + Content-Length body) off the socket.
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Received {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    loop {
+        let n = socket.read(&mut chunk).await.unwrap();
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf).to_string();
+        if let Some(end) = text.find("\r\n\r\n") {
+            let headers: HashMap<String, String> = text[..end]
+                .lines()
+                .skip(1)
+                .filter_map(|l| {
+                    let (k, v) = l.split_once(':')?;
+                    Some((k.trim().to_ascii_lowercase(), v.trim().to_string()))
+                })
+                .collect();
+            let length = headers
+                .get("content-length")
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(0);
+            if buf.len() >= end + 4 + length || n == 0 {
+                return Received {
+                    headers,
+                    body: text[end + 4..].to_string(),
+                };
+            }
+        }
+        assert!(n > 0, "connection closed mid-request");
+    }
+}
+
+/// Answers each request with the next status of `statuses`, repeating the
+/// last one, and records what it received.
+async fn mock_receiver(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Received>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let received = Arc::new(Mutex::new(Vec::new()));
+    tokio::spawn({
+        let received = received.clone();
+        async move {
+            let mut n = 0;
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut socket).await;
+                received.lock().unwrap().push(request);
+                let status = statuses[n.min(statuses.len() - 1)];
+                n += 1;
+                let response = format!(
+                    "HTTP/1.1 {status} Mock\r\nContent-Length: 4\r\nConnection: close\r\n\r\nnope"
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.ok();
+            }
+        }
+    });
+    (url, received)
+}
+
+/// Immediate retries, so each `run_once` re-attempts a failed delivery.
+fn fast_config(max_attempts: i32, disable_after: i32) -> WebhookConfig {
+    WebhookConfig {
+        poll_interval: Duration::from_millis(10),
+        timeout: Duration::from_secs(5),
+        retry_base: Duration::ZERO,
+        max_attempts,
+        disable_after,
+        ..WebhookConfig::default()
+    }
+}
+
+async fn queue_one(conn: &DatabaseConnection, url: &str) -> i64 {
+    let id = subscribe(conn, url, Some("t/aa/bb"), &["charm_created"]).await;
+    seed_block(conn).await;
+    WebhooksRepository::new(conn.clone())
+        .enqueue_block_events("mainnet", 100, &[])
+        .await
+        .unwrap();
+    id
+}
+
+#[tokio::test]
+async fn delivers_signed_payload() {
+    let db = TestDb::new().await;
+    let (url, received) = mock_receiver(vec![204]).await;
+    let subscription = queue_one(&db.conn, &url).await;
+    let dispatcher =
+        WebhookDispatcher::new(WebhooksRepository::new(db.conn.clone()), fast_config(5, 5));
+
+    assert_eq!(dispatcher.run_once().await, 1);
+    assert_eq!(dispatcher.run_once().await, 0);
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 1);
+    let request = &received[0];
+    assert_eq!(
+        request.headers[&SIGNATURE_HEADER.to_ascii_lowercase()],
+        sign("s3cret", request.body.as_bytes())
+    );
+    assert_eq!(
+        request.headers[&EVENT_HEADER.to_ascii_lowercase()],
+        "charm_created"
+    );
+    assert_eq!(
+        request.headers[&DELIVERY_HEADER.to_ascii_lowercase()],
+        "charm_created:tx2:0:t/aa/bb"
+    );
+    assert_eq!(request.headers["content-type"], "application/json");
+    let body: Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(body, queued(&db.conn, subscription).await[0]);
+    assert_eq!(body["data"]["amount"], 100);
+
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT status || '/' || attempts AS v FROM webhook_deliveries"
+        )
+        .await,
+        "delivered/1"
+    );
+    assert_eq!(
+        scalar::<i32>(
+            &db.conn,
+            "SELECT status_code AS v FROM webhook_delivery_attempts"
+        )
+        .await,
+        204
+    );
+}
+
+#[tokio::test]
+async fn retries_until_success_and_resets_failure_streak() {
+    let db = TestDb::new().await;
+    let (url, received) = mock_receiver(vec![500, 503, 200]).await;
+    queue_one(&db.conn, &url).await;
+    let dispatcher =
+        WebhookDispatcher::new(WebhooksRepository::new(db.conn.clone()), fast_config(5, 5));
+
+    for _ in 0..3 {
+        assert_eq!(dispatcher.run_once().await, 1);
+        // The retry is due once the zero backoff has elapsed.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(dispatcher.run_once().await, 0);
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 3);
+    // Every retry resends the same signed body.
+    assert!(received.iter().all(|r| r.body == received[0].body));
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT status || '/' || attempts AS v FROM webhook_deliveries"
+        )
+        .await,
+        "delivered/3"
+    );
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT string_agg(attempt || ':' || status_code || ':' || COALESCE(error, '-'), ' ' \
+                              ORDER BY attempt) AS v FROM webhook_delivery_attempts"
+        )
+        .await,
+        "1:500:HTTP 500 Internal Server Error: nope 2:503:HTTP 503 Service Unavailable: nope 3:200:-"
+    );
+    assert_eq!(
+        scalar::<i32>(
+            &db.conn,
+            "SELECT consecutive_failures AS v FROM webhook_subscriptions"
+        )
+        .await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn failing_endpoint_is_disabled_and_exhausted_deliveries_give_up() {
+    let db = TestDb::new().await;
+    let (url, received) = mock_receiver(vec![500]).await;
+    let subscription = queue_one(&db.conn, &url).await;
+    let dispatcher =
+        WebhookDispatcher::new(WebhooksRepository::new(db.conn.clone()), fast_config(2, 3));
+
+    // Attempt 2 exhausts the delivery: it is given up, not retried.
+    for _ in 0..2 {
+        assert_eq!(dispatcher.run_once().await, 1);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(dispatcher.run_once().await, 0);
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            "SELECT status || '/' || attempts AS v FROM webhook_deliveries"
+        )
+        .await,
+        "failed/2"
+    );
+
+    // A second event pushes the failure streak to 3: the endpoint is
+    // disabled and its remaining deliveries stay queued but unsent.
+    exec(&db.conn, "INSERT INTO charms (txid, vout, block_height, asset_type, blockchain, network, address, app_id, amount) VALUES \
+                    ('tx6', 0, 101, 'token', 'Bitcoin', 'mainnet', 'addrC', 't/aa/bb', 1), \
+                    ('tx7', 0, 101, 'token', 'Bitcoin', 'mainnet', 'addrC', 't/aa/bb', 1)")
+        .await;
+    let repo = WebhooksRepository::new(db.conn.clone());
+    assert_eq!(
+        repo.enqueue_block_events("mainnet", 101, &[])
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(dispatcher.run_once().await, 2);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(dispatcher.run_once().await, 0);
+
+    assert_eq!(received.lock().unwrap().len(), 4);
+    assert_eq!(
+        scalar::<String>(
+            &db.conn,
+            &format!(
+                "SELECT active || '/' || consecutive_failures || '/' || (disabled_at IS NOT NULL) AS v \
+                   FROM webhook_subscriptions WHERE id = {subscription}"
+            )
+        )
+        .await,
+        "false/4/true"
+    );
+    assert_eq!(
+        scalar::<i64>(
+            &db.conn,
+            "SELECT COUNT(*) AS v FROM webhook_delivery_attempts"
+        )
+        .await,
+        4
+    );
+    // A disabled subscription queues no new events.
+    assert_eq!(
+        repo.enqueue_block_events("mainnet", 100, &[])
+            .await
+            .unwrap(),
+        0
+    );
+}
@@ -0,0 +1,87 @@
+//! Golden outputs of `NativeCharmParser` over a corpus of raw transactions.
+//!
+//! Each `tests/fixtures/parser/<case>.hex` is parsed with proof verification
+//! (block path) and without it (mempool path); the spell and the
+//! `extract_asset_info` rows of each are compared against
+//! `<case>.golden.json`. After an intended parser change, regenerate with
+//! `UPDATE_GOLDEN=1 cargo test --test parser_corpus` and review the diff.
+//! Never touches the database.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use charms_indexer::domain::services::NativeCharmParser;
+use serde_json::{json, Value};
+
+fn corpus_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/parser")
+}
+
+/// Spell and asset rows of one parse, or its error.
+fn outcome(parsed: anyhow::Result<charms_client::NormalizedSpell>) -> Value {
+    match parsed {
+        Ok(spell) => {
+            let assets: Vec<Value> = NativeCharmParser::extract_asset_info(&spell)
+                .into_iter()
+                .map(|a| {
+                    json!({
+                        "app_id": a.app_id,
+                        "vout_index": a.vout_index,
+                        "amount": a.amount,
+                        "asset_type": a.asset_type.as_str(),
+                    })
+                })
+                .collect();
+            json!({ "spell": spell, "assets": assets })
+        }
+        Err(e) => json!({ "error": e.to_string() }),
+    }
+}
+
+fn parse(tx_hex: &str) -> Value {
+    json!({
+        "verified": outcome(NativeCharmParser::extract_and_verify_charm(tx_hex, false)),
+        "unverified": outcome(NativeCharmParser::extract_spell_no_verify(tx_hex)),
+    })
+}
+
+#[test]
+fn parser_output_matches_golden_corpus() {
+    let update = std::env::var("UPDATE_GOLDEN").is_ok_and(|v| v == "1");
+    let mut cases: Vec<PathBuf> = fs::read_dir(corpus_dir())
+        .expect("fixtures/parser missing")
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "hex"))
+        .collect();
+    cases.sort();
+    assert!(!cases.is_empty(), "no .hex fixtures in {:?}", corpus_dir());
+
+    let mut failures = Vec::new();
+    for hex_path in &cases {
+        let name = hex_path.file_stem().unwrap().to_string_lossy();
+        let tx_hex = fs::read_to_string(hex_path).unwrap();
+        let actual = serde_json::to_string_pretty(&parse(tx_hex.trim())).unwrap() + "\n";
+        let golden_path = hex_path.with_extension("golden.json");
+
+        if update {
+            fs::write(&golden_path, &actual).unwrap();
+            continue;
+        }
+        match fs::read_to_string(&golden_path) {
+            Ok(golden) if golden == actual => {}
+            Ok(golden) => failures.push(format!(
+                "{name}: output differs from golden (left: golden, right: actual)\n{}",
+                pretty_assertions::StrComparison::new(&golden, &actual)
+            )),
+            Err(_) => failures.push(format!("{name}: no golden at {:?}", golden_path)),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} corpus cases changed; rerun with UPDATE_GOLDEN=1 if intended:\n\n{}",
+        failures.len(),
+        cases.len(),
+        failures.join("\n\n")
+    );
+}
@@ -22,6 +22,7 @@ async fn db_starts_schema_applies_round_trip() {
         "summary",
         "transactions",
         "address_transactions",
+        "spells",
     ];
     for t in tables {
         let sql = format!("SELECT 1 FROM {t} LIMIT 0");
@@ -19,11 +19,19 @@ const SECTIONS = [
           { name: 'page', type: 'u64', required: false, desc: 'Page number (default: 1)' },
           { name: 'limit', type: 'u64', required: false, desc: 'Items per page (default: 20)' },
           { name: 'network', type: 'string', required: false, desc: 'mainnet | testnet4' },
+          { name: 'operation', type: 'string', required: false, desc: 'mint | transfer | burn' },
+          { name: 'include_hidden', type: 'bool', required: false, desc: 'Include moderated (hidden) rows; needs the admin token' },
         ],
         response: `{
   "data": { "charms": [...] },
-  "pagination": { "total": 1234, "page": 1, "limit": 20, "total_pages": 62 }
+  "pagination": {
+    "total": 1234, "page": 1, "limit": 20, "total_pages": 62,
+    "has_next": true, "has_prev": false,
+    "next_url": "https://<api host>/v1/charms?limit=20&page=2",
+    "prev_url": null
+  }
 }`,
+        note: 'Every paginated listing carries has_next/has_prev and absolute next_url/prev_url links that keep the request\'s filters; follow them instead of rebuilding the query.',
       },
       {
         method: 'GET',
@@ -61,13 +69,14 @@ const SECTIONS = [
         desc: 'Charms filtered by asset type',
         params: [
           { name: 'asset_type', type: 'string', required: true, desc: 'token, nft, or dapp' },
+          { name: 'network', type: 'string', required: false, desc: 'mainnet | testnet4 (default: mainnet)' },
         ],
       },
       {
         method: 'GET',
         path: '/v1/charms/count-by-type',
         desc: 'Count charms grouped by asset type',
-        response: '{ "total": 5000, "nft": 200, "token": 4700, "dapp": 100 }',
+        response: '{ "total": 5000, "nft": 200, "token": 4700, "dapp": 100, "by_operation": { "burn": 3, "mint": 1200, "transfer": 3700 } }',
       },
       {
         method: 'POST',
@@ -94,6 +103,7 @@ const SECTIONS = [
           { name: 'limit', type: 'u64', required: false, desc: 'Items per page' },
           { name: 'asset_type', type: 'string', required: false, desc: 'token, nft, dapp' },
           { name: 'network', type: 'string', required: false, desc: 'mainnet | testnet4' },
+          { name: 'include_hidden', type: 'bool', required: false, desc: 'Include moderated (hidden) rows; needs the admin token' },
         ],
       },
       {
@@ -105,7 +115,9 @@ const SECTIONS = [
   "asset_type": "token",
   "name": "BRO",
   "symbol": "BRO",
-  "total_supply": 21000000
+  "total_supply": 2100000000000000,
+  "total_supply_formatted": "21000000",
+  "decimals": 8
 }`,
       },
       {
@@ -128,12 +140,21 @@ const SECTIONS = [
       {
         method: 'GET',
         path: '/v1/assets/{app_id}/holders',
-        desc: 'Top holders for an asset',
+        desc: 'Top holders for an asset. Amounts are formatted with the decimals resolved from the token row, then its reference NFT, then 8 — the same value wallet balances and asset listings use.',
+        params: [
+          { name: 'page', type: 'u64', required: false, desc: 'Page number' },
+          { name: 'limit', type: 'u64', required: false, desc: 'Holders per page' },
+          { name: 'network', type: 'string', required: false, desc: 'mainnet | testnet4, for the decimals lookup' },
+        ],
         response: `{
+  "app_id": "t/abc.../vk",
+  "total_holders": 100,
+  "total_supply": 2100000000000000,
+  "total_supply_formatted": "21000000",
+  "decimals": 8,
   "holders": [
-    { "address": "bc1q...", "balance": 50000 }
-  ],
-  "total": 100
+    { "address": "bc1q...", "total_amount": 5000000000000, "total_amount_formatted": "50000", "percentage": 0.238 }
+  ]
 }`,
       },
     ],
@@ -278,11 +299,16 @@ const SECTIONS = [
       "appId": "t/abc123.../vk",
       "assetType": "token",
       "symbol": "BRO",
+      "decimals": 8,
       "confirmed": 1000,
+      "confirmed_formatted": "0.00001",
       "unconfirmed": 50,
+      "unconfirmed_formatted": "0.0000005",
       "mempoolSpent": 200,
       "available": 1050,
+      "available_formatted": "0.0000105",
       "total": 1250,
+      "total_formatted": "0.0000125",
       "utxos": [
         {
           "txid": "def456...",
@@ -395,7 +421,11 @@ const SECTIONS = [
   "page": 1,
   "page_size": 50,
   "total": 127,
-  "total_pages": 3
+  "total_pages": 3,
+  "has_next": true,
+  "has_prev": false,
+  "next_url": "...&page=2",
+  "prev_url": null
 }`,
         note: 'Amounts in satoshis. "direction": "in" = received, "out" = sent. block_height/block_time may be null for unconfirmed mempool transactions. Seeded lazily from Maestro/QuickNode on first request; Indexer keeps it current afterward.',
       },
@@ -424,6 +454,85 @@ const SECTIONS = [
 }`,
         note: 'Max 10 txids per request. All fetched concurrently via Maestro. Used by DEX to build prev_txs array for Scrolls spell signing. Replaces direct QuickNode/mempool.space calls from the frontend.',
       },
+      {
+        method: 'GET',
+        path: '/v1/wallet/orders/{address}',
+        desc: 'Resting DEX orders of a wallet, their fills and what they lock',
+        params: [
+          { name: 'network', type: 'string', required: false, desc: 'mainnet | testnet4 (default: mainnet)' },
+          { name: 'status', type: 'string', required: false, desc: 'open | partial (default: both)' },
+        ],
+        response: `{
+  "address": "bc1q...",
+  "network": "mainnet",
+  "orders": [
+    {
+      "order_id": "abc123...:0",
+      "side": "ask",
+      "status": "partial",
+      "confirmed": true,
+      "quantity": 1000,
+      "filled_quantity": 400,
+      "asset_app_id": "t/abc123.../vk",
+      "parent_order_id": "def456...:0",
+      "fills": [
+        { "txid": "fed987...", "status": "filled", "block_height": null, "confirmed": false }
+      ]
+    }
+  ],
+  "pending_fills": 1,
+  "locked_in_orders": [
+    {
+      "asset_app_id": "t/abc123.../vk",
+      "orders": 1,
+      "unconfirmed_orders": 0,
+      "quantity": 600,
+      "quantity_formatted": "0.000006",
+      "amount": 0
+    }
+  ]
+}`,
+        note: 'Orders are matched on their maker address and carry every field of GET /dex/orders/{order_id}. "confirmed": false = order still in the mempool. fills: transactions that spent the order, mempool ones last; pending_fills counts the unconfirmed ones. locked_in_orders: per asset, unfilled token units of asks (held at the scrolls address, not in the wallet balance) and unfilled sats of bids. Orders past their expiry stay listed until cancelled.',
+      },
+      {
+        method: 'GET',
+        path: '/v1/wallet/changes/{address}',
+        desc: 'What changed for a wallet since a block height, for polling clients',
+        params: [
+          { name: 'since_height', type: 'number', required: true, desc: 'tip_height of the previous call (or of the full state the wallet loaded)' },
+          { name: 'since_mempool_seq', type: 'number', required: false, desc: 'mempool.seq of the previous call' },
+          { name: 'network', type: 'string', required: false, desc: 'mainnet | testnet4 (default: mainnet)' },
+        ],
+        response: `{
+  "address": "bc1q...",
+  "network": "mainnet",
+  "since_height": 100,
+  "tip_height": 101,
+  "resync_required": false,
+  "charms": {
+    "received": [],
+    "spent": [
+      { "txid": "abc123...", "vout": 0, "app_id": "t/abc123.../vk", "amount": 25, "spending_txid": "def456...", "spent_height": 101 }
+    ]
+  },
+  "btc": {
+    "added": [
+      { "txid": "def456...", "vout": 1, "value": 400, "block_height": 101 }
+    ],
+    "removed": [
+      { "txid": "abc123...", "vout": 1, "value": 1000, "spending_txid": "def456...", "spent_height": 101 }
+    ]
+  },
+  "mempool": {
+    "seq": 182736450912345,
+    "changed": true,
+    "charms": [],
+    "utxos": [{ "txid": "fed987...", "vout": 1, "value": 300 }],
+    "spends": [{ "txid": "def456...", "vout": 1, "spending_txid": "fed987..." }]
+  }
+}`,
+        note: 'Confirmed changes cover blocks after since_height up to tip_height; pass tip_height and mempool.seq back on the next call. An output created and spent in between is left out. mempool lists are empty with "changed": false while the pending set matches since_mempool_seq; otherwise they hold the whole set, replacing the previous one. "resync_required": true (with resync_reason) when the changes since since_height are no longer kept, a reorg rolled it back, or the address was not tracked since then: reload balances and UTXOs, then poll from tip_height.',
+      },
     ],
   },
   {
@@ -494,6 +603,24 @@ const SECTIONS = [
         ],
         response: '// Same shape as /v1/dex/orders/open',
       },
+      {
+        method: 'GET',
+        path: '/v1/dex/markets/{asset_app_id}',
+        desc: 'Market summary: best bid/ask, spread, open interest',
+        params: [
+          { name: 'network', type: 'string', required: false, desc: 'mainnet | testnet4 (default: mainnet)' },
+        ],
+        response: `{
+  "asset_app_id": "t/abc.../vk", "network": "mainnet", "decimals": 8,
+  "best_bid": 95.0, "best_ask": 100.0, "mid_price": 97.5,
+  "spread": 5.0, "spread_pct": 5.13, "crossed": false,
+  "bids": { "orders": 2, "quantity": 150000000, "quantity_formatted": "1.5", "value_sats": 140 },
+  "asks": { "orders": 1, "quantity": 50000000, "quantity_formatted": "0.5", "value_sats": 50 },
+  "trades_24h": 0, "volume_24h_sats": 0,
+  "last_activity_at": "2026-03-05 12:00:00"
+}`,
+        note: 'Prices are sats per whole token, adjusted for the asset decimals. Live orders are open or partially filled and not expired. Prices are null when that side of the book is empty; spread is negative when the book is crossed.',
+      },
     ],
   },
   {
@@ -514,7 +641,10 @@ const SECTIONS = [
         ],
         response: `{
   "data": [...],
-  "pagination": { "total": 5678, "page": 1, "limit": 20, "total_pages": 284 }
+  "pagination": {
+    "total": 5678, "page": 1, "limit": 20, "total_pages": 284,
+    "has_next": true, "has_prev": false, "next_url": "...&page=2", "prev_url": null
+  }
 }`,
       },
       {
@@ -580,6 +710,44 @@ const SECTIONS = [
         desc: 'Database diagnostics',
         response: '// Detailed database health and table stats',
       },
+      {
+        method: 'POST',
+        path: '/v1/admin/moderate',
+        desc: 'Hide or flag a charm or asset',
+        body: '{ "target_type": "asset", "id": "n/abc.../vk", "network": "mainnet", "status": "hidden", "reason": "phishing link" }',
+        response: '{ "id": 1, "target_type": "asset", "status": "hidden", "previous_status": "visible", "moderated_by": "ops", ... }',
+        note: 'Requires Authorization: Bearer $ADMIN_API_TOKEN. target_type is charm (id = txid, all its outputs) or asset (id = app_id); status is visible | hidden | flagged. Hidden rows leave public listings and detail endpoints. Every change is recorded in moderation_log.',
+      },
+      {
+        method: 'GET',
+        path: '/v1/admin/audit',
+        desc: 'Audit log of admin actions',
+        params: [
+          { name: 'from', type: 'string', required: false, desc: 'RFC 3339; calls at or after it' },
+          { name: 'to', type: 'string', required: false, desc: 'RFC 3339; calls before it' },
+          { name: 'action', type: 'string', required: false, desc: 'Method and route, e.g. POST /admin/moderate' },
+          { name: 'limit', type: 'u64', required: false, desc: 'Rows (default: 100, max: 1000)' },
+        ],
+        response: '{ "limit": 100, "entries": [{ "id": 7, "action": "POST /admin/reset", "parameters": { "path": {}, "query": {}, "body": null }, "key_fingerprint": "sha256:…", "source_ip": "203.0.113.7", "requested_by": "ops", "success": true, "status_code": 200, "result": { "rows_deleted": { ... } }, ... }] }',
+        note: 'Requires Authorization: Bearer $ADMIN_API_TOKEN. Every admin mutation leaves one row, opened before it runs and completed with its outcome; secrets in parameters are redacted.',
+      },
+      {
+        method: 'GET',
+        path: '/v1/admin/backfills',
+        desc: 'Registered indexer backfills and their progress',
+        response: '{ "backfills": [{ "name": "block_time", "status": "running", "last_key": 841000, "rows_updated": 52310, "batches": 41, "idempotent": true, "heartbeat_at": "...", ... }] }',
+        note: 'Requires Authorization: Bearer $ADMIN_API_TOKEN. last_key is the highest primary key whose batch has committed; heartbeat_at moves with every batch.',
+      },
+      {
+        method: 'POST',
+        path: '/v1/admin/backfills/{name}',
+        desc: 'Queue a backfill for the live indexer',
+        params: [
+          { name: 'restart', type: 'bool', required: false, desc: 'Start a finished, idempotent backfill over' },
+        ],
+        response: '{ "name": "block_time", "status": "pending" }',
+        note: 'Requires Authorization: Bearer $ADMIN_API_TOKEN. 202 when queued; 404 for an unknown name; 409 while it is pending or running, or when it is done and not restarted. The indexer resumes after the last committed batch and stays under BACKFILL_ROWS_PER_SEC.',
+      },
     ],
   },
 ];